@@ -0,0 +1,235 @@
+//! Hot-path benchmarks (`cargo bench --features testing --bench hot_paths`).
+//!
+//! Covers the paths a schema or storage redesign (e.g. moving tags from the
+//! `subjects` JSON column to `book_tags`) is most likely to slow down: book
+//! listing with filters, tag counting, unified-search normalization and
+//! scoring, UNIMARC parsing (SUDOC, BNF SRU) and replay of peer operations.
+//!
+//! Database benchmarks run against an in-memory SQLite filled by
+//! [`TestLibrary`], so numbers compare across runs on the same machine only.
+//! CI runs them through `.github/workflows/perf.yml`, against a baseline
+//! saved from `main`.
+
+use std::time::{Duration, Instant};
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use rust_lib_app::api::integrations::{calculate_relevance, normalize_string};
+use rust_lib_app::domain::{BookFilter, BookRepository};
+use rust_lib_app::infrastructure::repositories::SeaOrmBookRepository;
+use rust_lib_app::models::{book, operation_log};
+use rust_lib_app::modules::integrations::{bnf, sudoc};
+use rust_lib_app::services::book_service::{self, BookFilter as ServiceBookFilter};
+use rust_lib_app::sync::processor;
+use rust_lib_app::testing::TestLibrary;
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, Set, Statement};
+use tokio::runtime::Runtime;
+
+const LIBRARY_SIZES: [usize; 2] = [100, 1_000];
+const SYNC_BATCH: usize = 100;
+
+const SUDOC_XML: &str = include_str!("../tests/fixtures/sudoc_9782367321257.xml");
+const BNF_SRU_XML: &str = include_str!("../tests/fixtures/bnf_sru_9782367321257.xml");
+
+const SUBJECTS: [&str; 6] = [
+    "Roman",
+    "Science-fiction",
+    "Histoire",
+    "Poésie",
+    "Jeunesse",
+    "Bande dessinée",
+];
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime")
+}
+
+/// A library of `books` fixture books, each tagged with two of [`SUBJECTS`]
+/// and spread across reading statuses.
+async fn seeded_library(books: usize) -> DatabaseConnection {
+    let library = TestLibrary::builder()
+        .with_books(books)
+        .build_in_memory()
+        .await
+        .expect("fixture library");
+    for (n, id) in library.book_ids.iter().enumerate() {
+        let subjects = serde_json::json!([
+            SUBJECTS[n % SUBJECTS.len()],
+            SUBJECTS[(n / SUBJECTS.len()) % SUBJECTS.len()],
+        ]);
+        let status = ["to_read", "reading", "read"][n % 3];
+        library
+            .db
+            .execute(Statement::from_sql_and_values(
+                library.db.get_database_backend(),
+                "UPDATE books SET subjects = ?, reading_status = ? WHERE id = ?",
+                [
+                    subjects.to_string().into(),
+                    status.into(),
+                    id.clone().into(),
+                ],
+            ))
+            .await
+            .expect("tag fixture book");
+    }
+    library.db
+}
+
+fn bench_book_listing(c: &mut Criterion) {
+    let rt = runtime();
+    let mut group = c.benchmark_group("book_listing");
+    for size in LIBRARY_SIZES {
+        let db = rt.block_on(seeded_library(size));
+        group.bench_with_input(BenchmarkId::new("unfiltered", size), &db, |b, db| {
+            b.to_async(&rt).iter(|| async {
+                book_service::list_books(db, ServiceBookFilter::default()).await
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("status_and_tag", size), &db, |b, db| {
+            b.to_async(&rt).iter(|| async {
+                let filter = ServiceBookFilter {
+                    status: Some("read".to_owned()),
+                    tag: Some("Roman".to_owned()),
+                    ..Default::default()
+                };
+                book_service::list_books(db, filter).await
+            });
+        });
+        let repo = SeaOrmBookRepository::new(db.clone());
+        group.bench_with_input(
+            BenchmarkId::new("paginated_search", size),
+            &repo,
+            |b, repo| {
+                b.to_async(&rt).iter(|| async {
+                    let filter = BookFilter {
+                        query: Some("Fixture".to_owned()),
+                        tag: Some("Histoire".to_owned()),
+                        page: Some(2),
+                        limit: Some(20),
+                        ..Default::default()
+                    };
+                    repo.find_all(filter).await
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_tag_counting(c: &mut Criterion) {
+    let rt = runtime();
+    let mut group = c.benchmark_group("tag_counting");
+    for size in LIBRARY_SIZES {
+        let db = rt.block_on(seeded_library(size));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &db, |b, db| {
+            b.to_async(&rt)
+                .iter(|| async { book_service::list_tags(db).await });
+        });
+    }
+    group.finish();
+}
+
+fn bench_search_normalization(c: &mut Criterion) {
+    let book = book::Book {
+        title: "L'Écume des jours : édition illustrée".to_owned(),
+        author: Some("Boris Vian".to_owned()),
+        publisher: Some("Gallimard".to_owned()),
+        language: Some("fr".to_owned()),
+        ..Default::default()
+    };
+    let langs = vec!["fr".to_owned(), "en".to_owned()];
+
+    let mut group = c.benchmark_group("unified_search");
+    group.bench_function("normalize_string", |b| {
+        b.iter(|| {
+            normalize_string(black_box(
+                "Les Misérables — Tôme Première, Édition Complète",
+            ))
+        })
+    });
+    group.bench_function("calculate_relevance", |b| {
+        b.iter(|| {
+            calculate_relevance(
+                black_box(&book),
+                black_box("vian"),
+                black_box("ecume des jours"),
+                black_box(""),
+                &langs,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn bench_marc_parsing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("marc_parsing");
+    group.bench_function("sudoc_unimarc", |b| {
+        b.iter(|| sudoc::parse_sudoc_xml(black_box(SUDOC_XML), "000000000"))
+    });
+    group.bench_function("bnf_sru", |b| {
+        b.iter(|| bnf::parse_bnf_sru_record(black_box(BNF_SRU_XML), "9782367321257"))
+    });
+    group.finish();
+}
+
+/// Queue `count` peer-sourced book creations, as a sync pull would.
+async fn queue_peer_book_ops(db: &DatabaseConnection, round: u64, count: usize) {
+    let ops = (0..count).map(|n| operation_log::ActiveModel {
+        entity_type: Set("book".to_owned()),
+        entity_id: Set(format!("bench-{round}-{n}")),
+        operation: Set("insert".to_owned()),
+        payload: Set(Some(
+            serde_json::json!({
+                "title": format!("Synced Book {round}-{n}"),
+                "reading_status": "to_read",
+            })
+            .to_string(),
+        )),
+        status: Set("pending".to_owned()),
+        source: Set("device:bench".to_owned()),
+        created_at: Set(chrono::Utc::now().to_rfc3339()),
+        ..Default::default()
+    });
+    operation_log::Entity::insert_many(ops)
+        .exec_without_returning(db)
+        .await
+        .expect("queue operations");
+}
+
+fn bench_sync_application(c: &mut Criterion) {
+    let rt = runtime();
+    let db = rt.block_on(seeded_library(LIBRARY_SIZES[0]));
+    let mut round = 0u64;
+
+    let mut group = c.benchmark_group("sync_application");
+    group.sample_size(20);
+    group.bench_function(BenchmarkId::new("book_inserts", SYNC_BATCH), |b| {
+        b.iter_custom(|iters| {
+            rt.block_on(async {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    round += 1;
+                    queue_peer_book_ops(&db, round, SYNC_BATCH).await;
+                    let start = Instant::now();
+                    let applied = processor::process_pending(&db).await.expect("apply");
+                    elapsed += start.elapsed();
+                    assert_eq!(applied, SYNC_BATCH);
+                }
+                elapsed
+            })
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_book_listing,
+    bench_tag_counting,
+    bench_search_normalization,
+    bench_marc_parsing,
+    bench_sync_application
+);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# Fail when a criterion benchmark regressed against the saved baseline.
+#
+# Reads the `change/estimates.json` criterion writes when a run is compared
+# with `--baseline`, and flags every benchmark whose mean slowed down by more
+# than the threshold (percent, default 15).
+#
+# Usage: scripts/check_bench_regressions.sh [threshold_percent]
+
+set -euo pipefail
+
+THRESHOLD="${1:-15}"
+CRITERION_DIR="${CARGO_TARGET_DIR:-target}/criterion"
+
+if [ ! -d "$CRITERION_DIR" ]; then
+    echo "No criterion results in $CRITERION_DIR" >&2
+    exit 1
+fi
+
+failed=0
+while IFS= read -r estimates; do
+    bench="${estimates#"$CRITERION_DIR"/}"
+    bench="${bench%/change/estimates.json}"
+    change=$(jq -r '.mean.point_estimate * 100' "$estimates")
+    printf '%-60s %+7.2f%%\n' "$bench" "$change"
+    if awk -v c="$change" -v t="$THRESHOLD" 'BEGIN { exit !(c > t) }'; then
+        echo "  ❌ regressed by more than ${THRESHOLD}%"
+        failed=1
+    fi
+done < <(find "$CRITERION_DIR" -path '*/change/estimates.json' | sort)
+
+exit "$failed"
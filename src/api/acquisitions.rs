@@ -0,0 +1,183 @@
+//! Purchase orders and acquisition budget (see `services::acquisitions`).
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::acquisitions::{self, AcquisitionError, BudgetPeriod, NewOrder};
+
+fn acquisition_error(e: AcquisitionError) -> Response {
+    let status = match e {
+        AcquisitionError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        AcquisitionError::NotFound => StatusCode::NOT_FOUND,
+        AcquisitionError::NotOpen(_) => StatusCode::CONFLICT,
+        AcquisitionError::SupplierNotFound
+        | AcquisitionError::BookNotFound(_)
+        | AcquisitionError::Invalid(_) => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Query parameters of `GET /api/orders`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct OrdersQuery {
+    /// `ordered`, `received` or `cancelled`
+    pub status: Option<String>,
+}
+
+/// GET /api/orders - Purchase orders, latest first
+#[utoipa::path(
+    get,
+    path = "/api/orders",
+    tag = "acquisitions",
+    params(OrdersQuery),
+    responses(
+        (status = 200, description = "Orders with their lines and totals", body = [OrderDetail])
+    )
+)]
+pub async fn list_orders(
+    State(state): State<AppState>,
+    Query(query): Query<OrdersQuery>,
+) -> Response {
+    match acquisitions::list_orders(state.db(), query.status.as_deref()).await {
+        Ok(orders) => Json(json!({ "orders": orders })).into_response(),
+        Err(e) => acquisition_error(e.into()),
+    }
+}
+
+/// POST /api/orders - Place an order
+#[utoipa::path(
+    post,
+    path = "/api/orders",
+    tag = "acquisitions",
+    request_body = NewOrder,
+    responses(
+        (status = 201, description = "Order placed", body = OrderDetail),
+        (status = 400, description = "No lines, bad quantity or cost, unknown supplier or book")
+    )
+)]
+pub async fn create_order(State(state): State<AppState>, Json(new): Json<NewOrder>) -> Response {
+    match acquisitions::create_order(state.db(), new).await {
+        Ok(order) => (StatusCode::CREATED, Json(order)).into_response(),
+        Err(e) => acquisition_error(e),
+    }
+}
+
+/// GET /api/orders/:id - One order
+#[utoipa::path(
+    get,
+    path = "/api/orders/{id}",
+    tag = "acquisitions",
+    params(("id" = String, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "The order with its lines", body = OrderDetail),
+        (status = 404, description = "Order not found")
+    )
+)]
+pub async fn get_order(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match acquisitions::get_order(state.db(), &id).await {
+        Ok(Some(order)) => Json(order).into_response(),
+        Ok(None) => acquisition_error(AcquisitionError::NotFound),
+        Err(e) => acquisition_error(e.into()),
+    }
+}
+
+/// Request body for receiving an order
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct ReceiveOrderRequest {
+    /// `YYYY-MM-DD`, defaults to today
+    pub received_at: Option<String>,
+}
+
+/// POST /api/orders/:id/receive - Receive an order into the catalogue
+#[utoipa::path(
+    post,
+    path = "/api/orders/{id}/receive",
+    tag = "acquisitions",
+    params(("id" = String, Path, description = "Order id")),
+    request_body = ReceiveOrderRequest,
+    responses(
+        (status = 200, description = "Order received; its books and copies are in the catalogue", body = OrderDetail),
+        (status = 404, description = "Order not found"),
+        (status = 409, description = "Order already received or cancelled")
+    )
+)]
+pub async fn receive_order(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<ReceiveOrderRequest>>,
+) -> Response {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    match acquisitions::receive_order(state.db(), &id, req.received_at).await {
+        Ok(order) => Json(order).into_response(),
+        Err(e) => acquisition_error(e),
+    }
+}
+
+/// POST /api/orders/:id/cancel - Cancel an open order
+#[utoipa::path(
+    post,
+    path = "/api/orders/{id}/cancel",
+    tag = "acquisitions",
+    params(("id" = String, Path, description = "Order id")),
+    responses(
+        (status = 200, description = "Order cancelled", body = OrderDetail),
+        (status = 404, description = "Order not found"),
+        (status = 409, description = "Order already received or cancelled")
+    )
+)]
+pub async fn cancel_order(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match acquisitions::cancel_order(state.db(), &id).await {
+        Ok(order) => Json(order).into_response(),
+        Err(e) => acquisition_error(e),
+    }
+}
+
+/// Query parameters of `GET /api/orders/budget`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct BudgetQuery {
+    /// First day, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Last day, `YYYY-MM-DD`
+    pub to: Option<String>,
+    /// `month` (default), `quarter` or `year`
+    #[param(value_type = Option<String>)]
+    pub period: Option<BudgetPeriod>,
+    /// Budget for the range; the report then shows what remains of it
+    pub budget: Option<f64>,
+}
+
+/// GET /api/orders/budget - Spending by period and by supplier
+#[utoipa::path(
+    get,
+    path = "/api/orders/budget",
+    tag = "acquisitions",
+    params(BudgetQuery),
+    responses(
+        (status = 200, description = "Committed and spent amounts by period and supplier", body = BudgetReport),
+        (status = 400, description = "Malformed date")
+    )
+)]
+pub async fn get_budget_report(
+    State(state): State<AppState>,
+    Query(query): Query<BudgetQuery>,
+) -> Response {
+    match acquisitions::budget_report(
+        state.db(),
+        query.from,
+        query.to,
+        query.period.unwrap_or_default(),
+        query.budget,
+    )
+    .await
+    {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => acquisition_error(e),
+    }
+}
@@ -1,6 +1,25 @@
-use axum::{Json, response::IntoResponse};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use sea_orm::DatabaseConnection;
+use serde_json::json;
 use std::process;
 
+use crate::config::Config;
+use crate::infrastructure::control_token;
+use crate::services::{consistency, db_maintenance};
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/shutdown",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Server is shutting down")
+    )
+)]
 pub async fn shutdown() -> impl IntoResponse {
     // Spawn a thread to exit the process after a short delay
     // to allow the response to be sent
@@ -12,3 +31,175 @@ pub async fn shutdown() -> impl IntoResponse {
 
     Json(serde_json::json!({ "message": "Server shutting down..." }))
 }
+
+/// Desktop handshake: proves to the Flutter app, which read the token from
+/// the handshake file, that the backend on this port is the one that wrote it.
+#[utoipa::path(
+    get,
+    path = "/api/control/handshake",
+    tag = "setup",
+    params(("X-Control-Token" = String, Header, description = "Token from the handshake file")),
+    responses(
+        (status = 200, description = "Identity of the backend: service, version, profile and pid"),
+        (status = 401, description = "Missing or wrong control token")
+    )
+)]
+pub async fn control_handshake(headers: HeaderMap) -> impl IntoResponse {
+    let authorized = headers
+        .get(control_token::CONTROL_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(control_token::verify);
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "Missing or wrong control token"})),
+        )
+            .into_response();
+    }
+    Json(json!({
+        "service": "bibliogenius",
+        "version": env!("CARGO_PKG_VERSION"),
+        "profile": Config::runtime().profile,
+        "pid": process::id(),
+    }))
+    .into_response()
+}
+
+/// Effective configuration (defaults < config.toml < environment), with the
+/// layer each value came from.
+#[utoipa::path(
+    get,
+    path = "/api/config/runtime",
+    tag = "setup",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Effective configuration, per-key source and config file path"),
+        (status = 403, description = "Caller is not an admin")
+    )
+)]
+pub async fn runtime_config(claims: crate::auth::Claims) -> impl IntoResponse {
+    if claims.role != "admin" {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Only an admin can read the runtime configuration"})),
+        )
+            .into_response();
+    }
+    runtime_config_body(Config::runtime()).into_response()
+}
+
+/// Last database maintenance run and the schedule.
+#[utoipa::path(
+    get,
+    path = "/api/admin/maintenance",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Schedule and last run", body = MaintenanceStatus)
+    )
+)]
+pub async fn maintenance_status() -> impl IntoResponse {
+    Json(db_maintenance::status())
+}
+
+/// Run database maintenance now: integrity check, orphan sweep, ANALYZE and
+/// VACUUM.
+#[utoipa::path(
+    post,
+    path = "/api/admin/maintenance",
+    tag = "setup",
+    responses(
+        (status = 200, description = "What the run did", body = MaintenanceReport),
+        (status = 409, description = "A run is already under way"),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn run_maintenance(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    match db_maintenance::run(&db).await {
+        Ok(Some(report)) => (StatusCode::OK, Json(json!(report))).into_response(),
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(json!({"error": "Maintenance is already running"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+/// Check the invariants the schema does not enforce; nothing is changed.
+#[utoipa::path(
+    get,
+    path = "/api/admin/consistency",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Broken invariants found", body = ConsistencyReport),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn check_consistency(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    consistency_response(consistency::check(&db, false).await)
+}
+
+/// Check the invariants and repair the rows breaking them.
+#[utoipa::path(
+    post,
+    path = "/api/admin/consistency",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Repair report", body = ConsistencyReport),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn repair_consistency(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    consistency_response(consistency::check(&db, true).await)
+}
+
+fn consistency_response(
+    result: Result<consistency::ConsistencyReport, sea_orm::DbErr>,
+) -> axum::response::Response {
+    match result {
+        Ok(report) => (StatusCode::OK, Json(json!(report))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+fn runtime_config_body(config: &Config) -> Json<serde_json::Value> {
+    Json(json!({
+        "config": config,
+        "sources": config.sources,
+        "config_file": config.config_file.as_ref().map(|p| p.display().to_string()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn runtime_config_is_admin_only() {
+        let claims = crate::infrastructure::auth::Claims {
+            sub: "reader".to_string(),
+            role: "user".to_string(),
+            exp: 0,
+        };
+        let response = runtime_config(claims).await.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn runtime_config_reports_values_and_sources() {
+        let Json(body) = runtime_config_body(Config::runtime());
+        assert!(body["config"]["port"].is_u64());
+        assert!(body["config"]["database_url"].is_string());
+        assert!(
+            ["default", "file", "env"].contains(&body["sources"]["port"].as_str().unwrap_or(""))
+        );
+    }
+}
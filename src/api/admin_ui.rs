@@ -0,0 +1,29 @@
+//! Embedded admin web UI (feature `admin-ui`).
+//!
+//! A single self-contained page, compiled into the binary, so a headless
+//! install is manageable from a browser with nothing else to deploy. It is
+//! served at `/admin` from loopback only; a remote machine reaches it through
+//! an SSH tunnel (`ssh -L 8000:127.0.0.1:8000 server`), which keeps the
+//! request loopback. The page signs in with `POST /api/auth/login` and calls
+//! the owner API with the JWT; `LoopbackNoBrowser` admits it because its
+//! `Origin` is this server on a loopback address.
+
+use axum::{
+    http::header,
+    response::{Html, IntoResponse},
+};
+
+use crate::auth::LoopbackOnly;
+
+const ADMIN_HTML: &str = include_str!("admin_ui.html");
+
+/// The admin page. It holds no data: everything is fetched once signed in.
+pub async fn admin_page(_: LoopbackOnly) -> impl IntoResponse {
+    (
+        [
+            (header::CACHE_CONTROL, "no-store"),
+            (header::X_FRAME_OPTIONS, "DENY"),
+        ],
+        Html(ADMIN_HTML),
+    )
+}
@@ -0,0 +1,132 @@
+//! Announcements to and from connected peers (see `services::announcements`).
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::announcements::{self, AnnouncementError, Draft};
+
+fn announcement_error(e: AnnouncementError) -> Response {
+    let status = match e {
+        AnnouncementError::Invalid(_) => StatusCode::BAD_REQUEST,
+        AnnouncementError::PeerNotFound | AnnouncementError::NotFound => StatusCode::NOT_FOUND,
+        AnnouncementError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Query filters for listing announcements
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct AnnouncementsQuery {
+    /// `sent` or `received`; both when absent
+    pub scope: Option<String>,
+    /// Leave out expired announcements
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// GET /api/announcements - Announcements published and received
+#[utoipa::path(
+    get,
+    path = "/api/announcements",
+    tag = "announcements",
+    params(AnnouncementsQuery),
+    responses(
+        (status = 200, description = "Newest first, with the sending peer's name", body = [AnnouncementView]),
+        (status = 400, description = "Unknown scope")
+    )
+)]
+pub async fn list_announcements(
+    State(state): State<AppState>,
+    Query(query): Query<AnnouncementsQuery>,
+) -> Response {
+    match announcements::list(state.db(), query.scope.as_deref(), query.current).await {
+        Ok(found) => Json(json!({ "announcements": found })).into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
+
+/// POST /api/announcements - Publish an announcement to connected peers
+#[utoipa::path(
+    post,
+    path = "/api/announcements",
+    tag = "announcements",
+    request_body = Draft,
+    responses(
+        (status = 201, description = "The announcement, and how it was handed to each accepted peer", body = [Delivery]),
+        (status = 400, description = "Unknown kind, empty or too long title or body, or invalid date")
+    )
+)]
+pub async fn publish_announcement(
+    State(state): State<AppState>,
+    Json(draft): Json<Draft>,
+) -> Response {
+    match announcements::publish(&state, draft).await {
+        Ok((announcement, deliveries)) => (
+            StatusCode::CREATED,
+            Json(json!({ "announcement": announcement, "deliveries": deliveries })),
+        )
+            .into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
+
+/// DELETE /api/announcements/:id - Remove an announcement from this library
+#[utoipa::path(
+    delete,
+    path = "/api/announcements/{id}",
+    tag = "announcements",
+    params(("id" = String, Path, description = "Announcement id")),
+    responses(
+        (status = 200, description = "Removed here; peers keep their copy"),
+        (status = 404, description = "Announcement not found")
+    )
+)]
+pub async fn delete_announcement(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match announcements::delete(state.db(), &id).await {
+        Ok(()) => Json(json!({ "deleted": id })).into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
+
+/// Request body for trusting a peer's announcements
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct AnnouncementTrustRequest {
+    pub trusted: bool,
+}
+
+/// PUT /api/peers/:id/announcements - Accept or ignore a peer's announcements
+#[utoipa::path(
+    put,
+    path = "/api/peers/{id}/announcements",
+    tag = "announcements",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = AnnouncementTrustRequest,
+    responses(
+        (status = 200, description = "Whether the peer's next announcements reach the inbox"),
+        (status = 404, description = "Peer not found")
+    )
+)]
+pub async fn set_announcement_trust(
+    State(state): State<AppState>,
+    Path(peer_id): Path<i32>,
+    Json(req): Json<AnnouncementTrustRequest>,
+) -> Response {
+    match announcements::set_trust(state.db(), peer_id, req.trusted).await {
+        Ok(peer) => Json(json!({
+            "peer_id": peer.id,
+            "trust_announcements": peer.trust_announcements,
+        }))
+        .into_response(),
+        Err(e) => announcement_error(e),
+    }
+}
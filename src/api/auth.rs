@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use totp_rs::{Algorithm, Secret, TOTP};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct LoginRequest {
     username: String,
     password: String,
@@ -18,6 +18,16 @@ pub struct LoginResponse {
 }
 
 // --- Login with MFA check ---
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "JWT, or an MFA challenge"),
+        (status = 401, description = "Invalid credentials")
+    )
+)]
 pub async fn login(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<LoginRequest>,
@@ -62,13 +72,23 @@ pub async fn login(
 }
 
 // --- MFA Login ---
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct MfaLoginRequest {
     username: String,
     password: String,
     code: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/login-mfa",
+    tag = "auth",
+    request_body = MfaLoginRequest,
+    responses(
+        (status = 200, description = "JWT"),
+        (status = 401, description = "Invalid credentials or code")
+    )
+)]
 pub async fn login_mfa(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<MfaLoginRequest>,
@@ -128,6 +148,15 @@ pub struct MfaSetupResponse {
     qr: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/setup",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "TOTP secret and provisioning QR code")
+    )
+)]
 pub async fn setup_2fa(
     State(_db): State<DatabaseConnection>,
     claims: crate::auth::Claims,
@@ -159,12 +188,23 @@ pub async fn setup_2fa(
         .into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct MfaVerifyRequest {
     secret: String,
     code: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/2fa/verify",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = MfaVerifyRequest,
+    responses(
+        (status = 200, description = "2FA enabled"),
+        (status = 400, description = "Invalid code")
+    )
+)]
 pub async fn verify_2fa(
     State(db): State<DatabaseConnection>,
     claims: crate::auth::Claims,
@@ -207,12 +247,21 @@ pub async fn verify_2fa(
 }
 
 // Temporary helper to create admin user if not exists
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct CreateUserRequest {
     username: String,
     password: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "Admin user created")
+    )
+)]
 pub async fn create_admin(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<CreateUserRequest>,
@@ -251,6 +300,17 @@ pub struct MeResponse {
     pub mfa_enabled: bool,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The authenticated user"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "User not found")
+    )
+)]
 pub async fn get_me(
     State(db): State<DatabaseConnection>,
     claims: crate::auth::Claims,
@@ -333,6 +393,16 @@ pub struct PairingCodeResponse {
 }
 
 /// Start Manual Pairing: Generate 6-digit code
+#[utoipa::path(
+    post,
+    path = "/api/auth/pairing/code",
+    tag = "auth",
+    request_body(content = Value, description = "`{\"uuid\", \"secret\", \"ip\"}` to share"),
+    responses(
+        (status = 200, description = "Six-digit code valid for five minutes"),
+        (status = 400, description = "Missing uuid")
+    )
+)]
 pub async fn pairing_generate_code(
     State(_db): State<DatabaseConnection>,
     // TODO: Verify admin/owner permission via claims?
@@ -404,6 +474,17 @@ pub async fn pairing_generate_code(
 /// Verify Pairing Code (Target calls Source via HTTP)
 /// Input: { "code": "123456" }
 /// Output: { "uuid": "...", "secret": "..." }
+#[utoipa::path(
+    post,
+    path = "/api/auth/pairing/verify",
+    tag = "auth",
+    request_body(content = Value, description = "`{\"code\": \"123456\"}`"),
+    responses(
+        (status = 200, description = "The shared uuid, secret and ip"),
+        (status = 400, description = "Code expired"),
+        (status = 404, description = "Invalid code")
+    )
+)]
 pub async fn pairing_verify_code(
     State(_db): State<DatabaseConnection>,
     Json(payload): Json<serde_json::Value>,
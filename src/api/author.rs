@@ -1,20 +1,31 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::domain::DomainError;
 use crate::infrastructure::AppState;
+use crate::services::author_match::{self, AuthorMatch, TitleMatch};
+use crate::services::author_normalize::{self, AcceptFixes};
+use crate::services::author_works::{self, AuthorIdentifiers};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct CreateAuthorRequest {
     name: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/authors",
+    tag = "authors",
+    responses(
+        (status = 200, description = "All authors")
+    )
+)]
 pub async fn list_authors(State(state): State<AppState>) -> impl IntoResponse {
     match state.author_repo.find_all().await {
         Ok(authors) => (StatusCode::OK, Json(authors)).into_response(),
@@ -26,6 +37,15 @@ pub async fn list_authors(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/authors",
+    tag = "authors",
+    request_body = CreateAuthorRequest,
+    responses(
+        (status = 201, description = "Author created")
+    )
+)]
 pub async fn create_author(
     State(state): State<AppState>,
     Json(payload): Json<CreateAuthorRequest>,
@@ -44,6 +64,131 @@ pub async fn create_author(
     }
 }
 
+/// Query parameters of `GET /api/authors/match`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct MatchQuery {
+    /// Author name as typed
+    pub name: Option<String>,
+    /// Book title as typed
+    pub title: Option<String>,
+    /// Lowest score returned, 0.75 by default
+    pub min_score: Option<f64>,
+    /// Matches per list, 5 by default, 50 at most
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MatchResponse {
+    /// Close authors, best first; empty without `name`
+    pub authors: Vec<AuthorMatch>,
+    /// Close titles, best first; empty without `title`
+    pub books: Vec<TitleMatch>,
+}
+
+/// Catalogued authors and titles close to what was typed, to offer the
+/// existing spelling before creating a duplicate ("J.R.R. Tolkein").
+#[utoipa::path(
+    get,
+    path = "/api/authors/match",
+    tag = "authors",
+    params(MatchQuery),
+    responses(
+        (status = 200, description = "Close authors and titles", body = MatchResponse),
+        (status = 400, description = "Neither name nor title given")
+    )
+)]
+pub async fn match_authors(
+    State(state): State<AppState>,
+    Query(query): Query<MatchQuery>,
+) -> impl IntoResponse {
+    fn given(v: &Option<String>) -> Option<&str> {
+        v.as_deref().map(str::trim).filter(|v| !v.is_empty())
+    }
+    let (name, title) = (given(&query.name), given(&query.title));
+    if name.is_none() && title.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "Give a name or a title to match" })),
+        )
+            .into_response();
+    }
+    let min_score = query.min_score.unwrap_or(author_match::DEFAULT_MIN_SCORE);
+    let limit = query.limit.unwrap_or(author_match::DEFAULT_LIMIT);
+    let db = state.db();
+    let authors = match name {
+        Some(name) => author_match::match_authors(db, name, min_score, limit).await,
+        None => Ok(Vec::new()),
+    };
+    let books = match title {
+        Some(title) => author_match::match_titles(db, title, min_score, limit).await,
+        None => Ok(Vec::new()),
+    };
+    match (authors, books) {
+        (Ok(authors), Ok(books)) => Json(MatchResponse { authors, books }).into_response(),
+        (Err(e), _) | (_, Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Author-name inconsistencies and the fix proposed for each: "Last, First"
+/// order, accents, stray spaces, the same writer under several authors.
+#[utoipa::path(
+    get,
+    path = "/api/authors/normalize",
+    tag = "authors",
+    responses(
+        (status = 200, description = "Proposed fixes, largest first", body = Vec<AuthorFix>)
+    )
+)]
+pub async fn normalize_proposals(State(state): State<AppState>) -> impl IntoResponse {
+    match author_normalize::proposals(state.db()).await {
+        Ok(fixes) => (StatusCode::OK, Json(json!(fixes))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Apply the accepted fixes: rename the kept author, move the books of the
+/// others to it and remove them.
+#[utoipa::path(
+    post,
+    path = "/api/authors/normalize",
+    tag = "authors",
+    request_body = AcceptFixes,
+    responses(
+        (status = 200, description = "What was renamed and merged", body = FixSummary)
+    )
+)]
+pub async fn apply_normalization(
+    State(state): State<AppState>,
+    Json(payload): Json<AcceptFixes>,
+) -> impl IntoResponse {
+    match author_normalize::apply(state.db(), &payload.accept).await {
+        Ok(summary) => (StatusCode::OK, Json(json!(summary))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/authors/{id}",
+    tag = "authors",
+    params(("id" = String, Path, description = "Author id")),
+    responses(
+        (status = 200, description = "The author"),
+        (status = 404, description = "Author not found")
+    )
+)]
 pub async fn get_author(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -63,6 +208,16 @@ pub async fn get_author(
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/authors/{id}",
+    tag = "authors",
+    params(("id" = String, Path, description = "Author id")),
+    responses(
+        (status = 200, description = "Author deleted"),
+        (status = 404, description = "Author not found")
+    )
+)]
 pub async fn delete_author(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -84,3 +239,89 @@ pub async fn delete_author(
             .into_response(),
     }
 }
+
+/// Query parameters of `GET /api/authors/:id/works`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct AuthorWorksQuery {
+    /// Include the peers' books by this author (default false)
+    pub peers: Option<bool>,
+    /// Include Open Library's list of the author's works (default false)
+    pub external: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/authors/{id}/works",
+    tag = "authors",
+    params(("id" = String, Path, description = "Author id"), AuthorWorksQuery),
+    responses(
+        (status = 200, description = "The author's books, identifiers and authority links", body = AuthorWorks),
+        (status = 404, description = "Author not found")
+    )
+)]
+pub async fn get_author_works(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AuthorWorksQuery>,
+) -> impl IntoResponse {
+    match author_works::author_works(
+        state.db(),
+        &id,
+        query.peers.unwrap_or(false),
+        query.external.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(Some(works)) => (StatusCode::OK, Json(works)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Author not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/authors/{id}/identifiers",
+    tag = "authors",
+    params(("id" = String, Path, description = "Author id")),
+    request_body = AuthorIdentifiers,
+    responses(
+        (status = 200, description = "Identifiers saved in canonical form, with their links"),
+        (status = 400, description = "Malformed identifier"),
+        (status = 404, description = "Author not found")
+    )
+)]
+pub async fn update_author_identifiers(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<AuthorIdentifiers>,
+) -> impl IntoResponse {
+    let identifiers = match payload.canonical() {
+        Ok(ids) => ids,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    match author_works::set_identifiers(state.db(), &id, identifiers.clone()).await {
+        Ok(Some(_)) => (
+            StatusCode::OK,
+            Json(json!({ "links": identifiers.links(), "identifiers": identifiers })),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Author not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -849,6 +849,23 @@ pub async fn restore_backup(
     Ok(summary)
 }
 
+/// Snapshot the live DB as a rollback sibling before a destructive operation
+/// (the full app reset), so it is listed and restorable like a restore
+/// rollback for the next 24h. The DB file comes from `database_url`, the URL
+/// `db` was opened from; `None` when that names no file (in-memory DB).
+pub async fn snapshot_rollback(
+    db: &DatabaseConnection,
+    database_url: Option<&str>,
+) -> Result<Option<PathBuf>, BackupError> {
+    let Some(db_path) = database_url.and_then(crate::infrastructure::mcp_token::database_file_path)
+    else {
+        return Ok(None);
+    };
+    let target = sibling_with_suffix(&db_path, ROLLBACK_SUFFIX, &Utc::now());
+    snapshot_db(db, &target).await?;
+    Ok(Some(target))
+}
+
 /// Scan the directory of `db_path` for rollback siblings and return them as a
 /// list ordered most-recent first.
 pub fn list_available_rollbacks(db_path: &Path) -> Vec<RollbackInfo> {
@@ -1365,6 +1382,9 @@ async fn load_merge_payload(
         gamification_progress: Some(gamification_progress),
         gamification_achievements: Some(gamification_achievements),
         gamification_streaks: Some(gamification_streaks),
+        // Install-specific, outside the merge whitelist.
+        installation_profile: None,
+        loan_settings: None,
     })
 }
 
@@ -1376,7 +1396,12 @@ async fn apply_upsert(
     let db = sea_orm::Database::connect(&url)
         .await
         .map_err(|e| BackupError::Db(e.to_string()))?;
-    let _ = crate::api::export::run_import_upsert(&db, payload).await;
+    let _ = crate::api::export::run_import_upsert(
+        &db,
+        payload,
+        crate::api::export::SettingsMerge::Overwrite,
+    )
+    .await;
     db.close()
         .await
         .map_err(|e| BackupError::Db(e.to_string()))?;
@@ -1414,6 +1439,11 @@ fn book_model_to_import_book(m: crate::models::book::Model) -> crate::api::expor
         private: m.private,
         page_count: m.page_count,
         loan_duration_days: m.loan_duration_days,
+        edition: m.edition,
+        physical_format: m.physical_format,
+        dimensions: m.dimensions,
+        archived: m.archived,
+        digital_source_url: m.digital_source_url,
         author: None,
     }
 }
@@ -1441,6 +1471,10 @@ fn contact_model_to_import_contact(
         is_active: m.is_active,
         created_at: Some(m.created_at),
         updated_at: Some(m.updated_at),
+        member_number: m.member_number,
+        member_since: m.member_since,
+        membership_expires_at: m.membership_expires_at,
+        membership_fee_paid: m.membership_fee_paid,
     }
 }
 
@@ -1450,6 +1484,7 @@ fn tag_model_to_import_tag(m: crate::models::tag::Model) -> crate::api::export::
         name: m.name,
         parent_id: m.parent_id,
         path: m.path,
+        private: m.private,
         created_at: Some(m.created_at),
         updated_at: Some(m.updated_at),
     }
@@ -1,11 +1,21 @@
 use crate::models::book;
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+};
 use serde::Deserialize;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct BatchEditRequest {
     pub ids: Vec<String>,
+    /// `{"type": "Delete"}`
+    #[schema(value_type = Object)]
     pub action: BatchAction,
 }
 
@@ -16,18 +26,27 @@ pub enum BatchAction {
     // AddTag(String), // TODO: Implement tagging
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct BatchSortRequest {
     pub sort_by: SortCriteria,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub enum SortCriteria {
     Author,
     Title,
     Year,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/books/batch/edit",
+    tag = "books",
+    request_body = BatchEditRequest,
+    responses(
+        (status = 200, description = "Number of books changed")
+    )
+)]
 pub async fn batch_edit(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<BatchEditRequest>,
@@ -60,6 +79,197 @@ pub async fn batch_edit(
     }
 }
 
+/// Request body of the bulk delete, archive and unarchive endpoints
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct BatchConfirmRequest {
+    pub ids: Vec<String>,
+    /// How many books the caller means to change. It must equal the number
+    /// of `ids` that exist, or nothing is changed: a stale selection or a
+    /// mistyped list is refused rather than half-applied.
+    pub confirm_count: usize,
+}
+
+/// The existing books among `req.ids`, once `confirm_count` matches them;
+/// otherwise a 409 carrying the number actually matched.
+async fn confirmed_ids(
+    db: &DatabaseConnection,
+    req: BatchConfirmRequest,
+) -> Result<Vec<String>, Response> {
+    let mut ids = req.ids;
+    ids.sort();
+    ids.dedup();
+
+    let mut matched: Vec<String> = Vec::with_capacity(ids.len());
+    // Chunked to stay under SQLite's bound-variable limit.
+    for chunk in ids.chunks(500) {
+        let found: Vec<String> = book::Entity::find()
+            .select_only()
+            .column(book::Column::Id)
+            .filter(book::Column::Id.is_in(chunk.to_vec()))
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+                    .into_response()
+            })?;
+        matched.extend(found);
+    }
+
+    if matched.len() != req.confirm_count {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "confirm_count does not match the books found; nothing was changed",
+                "matched": matched.len(),
+                "confirm_count": req.confirm_count,
+            })),
+        )
+            .into_response());
+    }
+    Ok(matched)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/books/batch/delete",
+    tag = "books",
+    request_body = BatchConfirmRequest,
+    responses(
+        (status = 200, description = "Books deleted with their copies, loans and notes"),
+        (status = 409, description = "confirm_count does not match the books found; nothing deleted")
+    )
+)]
+pub async fn batch_delete(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<BatchConfirmRequest>,
+) -> Response {
+    let ids = match confirmed_ids(&db, payload).await {
+        Ok(ids) => ids,
+        Err(response) => return response,
+    };
+
+    match crate::services::book_service::delete_books(&db, &ids).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "deleted": ids.len() })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("{e:?}") })),
+        )
+            .into_response(),
+    }
+}
+
+/// Set `archived` on the confirmed books, logging each one that changed.
+async fn set_archived(
+    db: &DatabaseConnection,
+    payload: BatchConfirmRequest,
+    archived: bool,
+) -> Response {
+    let ids = match confirmed_ids(db, payload).await {
+        Ok(ids) => ids,
+        Err(response) => return response,
+    };
+
+    let mut changed = Vec::new();
+    for chunk in ids.chunks(500) {
+        let found: Vec<String> = match book::Entity::find()
+            .select_only()
+            .column(book::Column::Id)
+            .filter(book::Column::Id.is_in(chunk.to_vec()))
+            .filter(book::Column::Archived.ne(archived))
+            .into_tuple()
+            .all(db)
+            .await
+        {
+            Ok(found) => found,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+        };
+        changed.extend(found);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for chunk in changed.chunks(500) {
+        if let Err(e) = book::Entity::update_many()
+            .col_expr(book::Column::Archived, Expr::value(archived))
+            .col_expr(book::Column::UpdatedAt, Expr::value(now.clone()))
+            .filter(book::Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await
+        {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    }
+    for id in &changed {
+        let _ = crate::sync::log_operation(db, "book", id, "UPDATE", None).await;
+    }
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "matched": ids.len(), "changed": changed.len() })),
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/books/batch/archive",
+    tag = "books",
+    request_body = BatchConfirmRequest,
+    responses(
+        (status = 200, description = "Books archived: kept, but out of default lists and peer catalogues"),
+        (status = 409, description = "confirm_count does not match the books found; nothing archived")
+    )
+)]
+pub async fn batch_archive(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<BatchConfirmRequest>,
+) -> Response {
+    set_archived(&db, payload, true).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/books/batch/unarchive",
+    tag = "books",
+    request_body = BatchConfirmRequest,
+    responses(
+        (status = 200, description = "Books back in the default lists"),
+        (status = 409, description = "confirm_count does not match the books found; nothing unarchived")
+    )
+)]
+pub async fn batch_unarchive(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<BatchConfirmRequest>,
+) -> Response {
+    set_archived(&db, payload, false).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/books/batch/sort",
+    tag = "books",
+    request_body = BatchSortRequest,
+    responses(
+        (status = 200, description = "Shelf positions rewritten")
+    )
+)]
 pub async fn batch_sort(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<BatchSortRequest>,
@@ -101,6 +311,14 @@ pub async fn batch_sort(
         .into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/books/duplicates",
+    tag = "books",
+    responses(
+        (status = 200, description = "Groups of likely duplicate books")
+    )
+)]
 pub async fn find_duplicates(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     let books = book::Entity::find().all(&db).await.unwrap_or_default();
 
@@ -0,0 +1,222 @@
+//! Content-addressed covers and files (see `services::content_blobs`): the
+//! index, manifests and chunks peers fetch, and the blobs fetched from peers,
+//! served locally so their catalogues browse offline.
+
+use axum::{
+    Json,
+    extract::{Path, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+use tower::util::ServiceExt;
+
+use crate::infrastructure::AppState;
+use crate::models::peer_blob;
+use crate::services::content_blobs::{self, BlobKind};
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn not_found() -> Response {
+    error(StatusCode::NOT_FOUND, "Blob not found")
+}
+
+/// GET /api/blobs - Covers and files peers may fetch
+#[utoipa::path(
+    get,
+    path = "/api/blobs",
+    tag = "blobs",
+    responses(
+        (status = 200, description = "Cover thumbnails of the shared books, and their digital files when the owner shares them", body = [BlobEntry])
+    )
+)]
+pub async fn list_blobs(State(state): State<AppState>) -> Response {
+    match content_blobs::refresh_index(state.db(), &content_blobs::store_dir()).await {
+        Ok(blobs) => Json(json!({ "blobs": blobs })).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/blobs/:sha256 - How a shared blob is cut into chunks
+#[utoipa::path(
+    get,
+    path = "/api/blobs/{sha256}",
+    tag = "blobs",
+    params(("sha256" = String, Path, description = "SHA-256 of the blob")),
+    responses(
+        (status = 200, description = "Size, chunk size and chunk hashes", body = Manifest),
+        (status = 404, description = "Not a shared blob")
+    )
+)]
+pub async fn get_manifest(State(state): State<AppState>, Path(sha256): Path<String>) -> Response {
+    match content_blobs::shared_source(state.db(), &sha256).await {
+        Ok(Some(source)) => Json(content_blobs::source_manifest(&source)).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// GET /api/blobs/:sha256/chunks/:index - One chunk of a shared blob
+#[utoipa::path(
+    get,
+    path = "/api/blobs/{sha256}/chunks/{index}",
+    tag = "blobs",
+    params(
+        ("sha256" = String, Path, description = "SHA-256 of the blob"),
+        ("index" = usize, Path, description = "Chunk index, from 0")
+    ),
+    responses(
+        (status = 200, description = "Chunk bytes", content_type = "application/octet-stream"),
+        (status = 404, description = "Not a shared blob, or past its end")
+    )
+)]
+pub async fn get_chunk(
+    State(state): State<AppState>,
+    Path((sha256, index)): Path<(String, usize)>,
+) -> Response {
+    let source = match content_blobs::shared_source(state.db(), &sha256).await {
+        Ok(Some(source)) => source,
+        Ok(None) => return not_found(),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    match content_blobs::read_chunk(&source, index).await {
+        Ok(Some(bytes)) => (
+            [
+                (header::CONTENT_TYPE, "application/octet-stream"),
+                // Content-addressed: a chunk never changes.
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            tracing::warn!("blob {sha256}: {e}");
+            error(
+                StatusCode::CONFLICT,
+                "Blob changed, fetch the manifest again",
+            )
+        }
+    }
+}
+
+/// A blob a peer lists for one of its books
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PeerBookBlob {
+    pub kind: BlobKind,
+    pub sha256: String,
+    pub size: u64,
+    pub format: Option<String>,
+    /// Whether it is in the local store, readable offline
+    pub cached: bool,
+}
+
+/// GET /api/peers/:id/books/:book_id/blobs - A peer book's cover and files
+#[utoipa::path(
+    get,
+    path = "/api/peers/{id}/books/{book_id}/blobs",
+    tag = "blobs",
+    params(
+        ("id" = i32, Path, description = "Local peer id"),
+        ("book_id" = String, Path, description = "The peer's book id")
+    ),
+    responses(
+        (status = 200, description = "What the peer lists for the book, and what was fetched", body = [PeerBookBlob])
+    )
+)]
+pub async fn list_peer_book_blobs(
+    State(state): State<AppState>,
+    Path((peer_id, book_id)): Path<(i32, String)>,
+) -> Response {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    let listed = match peer_blob::Entity::find()
+        .filter(peer_blob::Column::PeerId.eq(peer_id))
+        .filter(peer_blob::Column::RemoteBookId.eq(book_id))
+        .all(state.db())
+        .await
+    {
+        Ok(listed) => listed,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let dir = content_blobs::store_dir();
+    let blobs: Vec<PeerBookBlob> = listed
+        .into_iter()
+        .filter_map(|b| {
+            Some(PeerBookBlob {
+                kind: BlobKind::parse(&b.kind)?,
+                cached: content_blobs::store_path(&dir, &b.sha256).exists(),
+                sha256: b.sha256,
+                size: b.size as u64,
+                format: b.format,
+            })
+        })
+        .collect();
+    Json(json!({ "blobs": blobs })).into_response()
+}
+
+/// GET /api/peers/:id/blobs/:sha256 - A blob fetched from a peer
+#[utoipa::path(
+    get,
+    path = "/api/peers/{id}/blobs/{sha256}",
+    tag = "blobs",
+    params(
+        ("id" = i32, Path, description = "Local peer id"),
+        ("sha256" = String, Path, description = "SHA-256 of the blob")
+    ),
+    responses(
+        (status = 200, description = "Blob bytes; `Range` requests are honoured"),
+        (status = 404, description = "Not listed by the peer, or not fetched yet")
+    )
+)]
+pub async fn get_peer_blob(
+    State(state): State<AppState>,
+    Path((peer_id, sha256)): Path<(i32, String)>,
+    request: Request,
+) -> Response {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    if !content_blobs::valid_sha256(&sha256) {
+        return not_found();
+    }
+    let listed = match peer_blob::Entity::find()
+        .filter(peer_blob::Column::PeerId.eq(peer_id))
+        .filter(peer_blob::Column::Sha256.eq(sha256.clone()))
+        .one(state.db())
+        .await
+    {
+        Ok(Some(listed)) => listed,
+        Ok(None) => return not_found(),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let path = content_blobs::store_path(&content_blobs::store_dir(), &sha256);
+    if !path.exists() {
+        return not_found();
+    }
+    let mut response = match tower_http::services::ServeFile::new(path)
+        .oneshot(request)
+        .await
+    {
+        Ok(resp) => resp.map(axum::body::Body::new),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let mime = match (BlobKind::parse(&listed.kind), listed.format.as_deref()) {
+        (Some(BlobKind::File), Some(format)) => {
+            crate::modules::book_files::domain::ATTACHMENT_FORMATS
+                .iter()
+                .find(|(f, _)| *f == format)
+                .map(|(_, mime)| *mime)
+                .unwrap_or("application/octet-stream")
+        }
+        _ => "image/jpeg",
+    };
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(mime));
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{sha256}\"")) {
+        headers.insert(header::ETAG, etag);
+    }
+    response
+}
@@ -7,6 +7,7 @@ use axum::{
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde_json::json;
 
+use crate::api::validation::ValidatedJson;
 use crate::models::Book;
 use crate::models::book::Entity as BookEntity;
 
@@ -23,6 +24,9 @@ pub struct BookFilter {
     /// When true, only return books the user owns (excludes borrowed/wishlist).
     /// Used by peers to avoid exposing non-owned books.
     pub owned_only: Option<bool>,
+    /// `true` lists the archived books instead of the default, unarchived,
+    /// list. Peers never see archived books either way.
+    pub archived: Option<bool>,
     /// Delta sync cursor (ADR-028). When set, the endpoint returns the
     /// operations applied since this `operation_log.id` instead of the
     /// full catalog. Absent means full catalog (ETag-equipped) as before.
@@ -38,6 +42,7 @@ pub const DELTA_MAX_LIMIT: i64 = 2000;
 #[utoipa::path(
     get,
     path = "/api/books",
+    tag = "books",
     params(
         ("status" = Option<String>, Query, description = "Reading status"),
         ("author" = Option<String>, Query, description = "Filter by author name"),
@@ -46,7 +51,8 @@ pub const DELTA_MAX_LIMIT: i64 = 2000;
         ("q" = Option<String>, Query, description = "Unified search (Title, ISBN, Subjects)"),
         ("sort" = Option<String>, Query, description = "Sort by: author_asc, title_asc"),
         ("page" = Option<u64>, Query, description = "Page number (0-indexed)"),
-        ("limit" = Option<u64>, Query, description = "Items per page")
+        ("limit" = Option<u64>, Query, description = "Items per page"),
+        ("archived" = Option<bool>, Query, description = "List archived books instead")
     ),
     responses(
         (status = 200, description = "List all books")
@@ -89,6 +95,7 @@ pub async fn list_books(
         // HTTP route, which peers also call.
         owned: None,
         collection: None,
+        archived: filter.archived,
     };
 
     // Fetch via repository
@@ -103,26 +110,27 @@ pub async fn list_books(
         result.total
     );
 
+    // The repository fills in `available_copies`, which redaction keeps: the
+    // iPhone-side carousel filter needs it to drop fully-lent books the peer
+    // can't actually fulfill.
     let mut book_dtos = result.books;
     let mut total = result.total;
 
-    // Populate copy-derived availability BEFORE redact so peers see accurate
-    // `available_copies` (not redacted — needed so the iPhone-side carousel
-    // filter can drop fully-lent books the peer can't actually fulfill).
-    if let Err(e) =
-        crate::services::book_service::populate_available_copies(state.db(), &mut book_dtos).await
-    {
-        tracing::error!("Failed to populate available_copies: {}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
-
     // Peer-facing privacy (E1): filter private books and redact personal
     // annotations when no owner JWT is present. Post-query in-memory
     // filtering is acceptable for the project's catalog sizes (<1k books);
     // `total` is adjusted so paginated clients see the filtered count.
     if !is_owner {
+        let hidden = Book::tag_restricted_ids(state.db()).await.map_err(|e| {
+            tracing::error!("Failed to load private tags: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
         let before = book_dtos.len() as u64;
-        book_dtos.retain(|b| !b.private.unwrap_or(false));
+        book_dtos.retain(|b| {
+            !b.private.unwrap_or(false)
+                && !b.archived.unwrap_or(false)
+                && b.id.as_ref().is_none_or(|id| !hidden.contains(id))
+        });
         let removed = before - book_dtos.len() as u64;
         total = total.saturating_sub(removed);
         for b in &mut book_dtos {
@@ -243,8 +251,8 @@ pub enum BookDeltaOutcome {
 }
 
 /// Build a book delta window honouring the same privacy pipeline as the
-/// full-catalog endpoint: drops `private=true` and non-owned books for
-/// non-owner callers and applies `redact_for_peer` on survivors.
+/// full-catalog endpoint: drops private, privately tagged and non-owned books
+/// for non-owner callers and applies `redact_for_peer` on survivors.
 ///
 /// `since = None` means "from the oldest retained operation_log row" (first
 /// sync). `limit` caps the raw-row scan and is clamped to
@@ -281,6 +289,12 @@ pub async fn build_book_delta_response(
         Upsert(usize),
     }
 
+    let hidden = if is_owner {
+        Default::default()
+    } else {
+        Book::tag_restricted_ids(state.db()).await?
+    };
+
     let mut pending: Vec<Pending> = Vec::with_capacity(window.operations.len());
     let mut upserts: Vec<Book> = Vec::new();
 
@@ -297,9 +311,16 @@ pub async fn build_book_delta_response(
                     continue;
                 };
                 if !is_owner {
-                    if book.private.unwrap_or(false) {
+                    if book.archived.unwrap_or(false) {
+                        // Archiving withdraws a book the peer may already
+                        // hold, and its existence is no secret: a tombstone.
+                        pending.push(Pending::Delete(op.entity_id.clone()));
+                        continue;
+                    }
+                    if book.private.unwrap_or(false) || hidden.contains(&op.entity_id) {
                         // Per ADR-028 D6: omit operations whose current state
-                        // is private rather than leak the id via a tombstone.
+                        // is private (or privately tagged) rather than leak
+                        // the id via a tombstone.
                         continue;
                     }
                     if !book.owned.unwrap_or(true) {
@@ -423,15 +444,18 @@ async fn list_books_delta(
 #[utoipa::path(
     post,
     path = "/api/books",
+    tag = "books",
+    security(("bearer_auth" = [])),
+    request_body = Book,
     responses(
-        (status = 201, description = "Book created successfully"),
+        (status = 201, description = "Book created; `similar_authors` lists the new author names close to catalogued ones"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn create_book(
     State(state): State<crate::infrastructure::AppState>,
     _claims: crate::auth::Claims,
-    Json(book): Json<Book>,
+    ValidatedJson(book): ValidatedJson<Book>,
 ) -> impl IntoResponse {
     let db = state.db();
     let now = chrono::Utc::now();
@@ -455,6 +479,10 @@ pub async fn create_book(
             let book_id = created_book.id.clone().expect("Created book must have ID");
             let owned = created_book.owned.unwrap_or(true);
 
+            // Names created although close to a catalogued author, offered
+            // back so the app can suggest the existing spelling.
+            let mut similar_authors = Vec::new();
+
             // Handle authors - find or create, then link to book
             if !author_names.is_empty() {
                 use crate::models::author::{ActiveModel as AuthorActive, Entity as AuthorEntity};
@@ -469,6 +497,20 @@ pub async fn create_book(
                     {
                         Ok(Some(existing)) => existing,
                         _ => {
+                            if let Ok(matches) = crate::services::author_match::match_authors(
+                                db,
+                                &author_name,
+                                crate::services::author_match::DEFAULT_MIN_SCORE,
+                                crate::services::author_match::DEFAULT_LIMIT,
+                            )
+                            .await
+                                && !matches.is_empty()
+                            {
+                                similar_authors.push(json!({
+                                    "name": author_name,
+                                    "matches": matches,
+                                }));
+                            }
                             let new_author = AuthorActive {
                                 name: Set(author_name),
                                 created_at: Set(now.to_rfc3339()),
@@ -531,7 +573,8 @@ pub async fn create_book(
                 StatusCode::CREATED,
                 Json(json!({
                     "message": "Book created successfully",
-                    "book": created_book
+                    "book": created_book,
+                    "similar_authors": similar_authors,
                 })),
             )
                 .into_response()
@@ -547,8 +590,10 @@ pub async fn create_book(
 #[utoipa::path(
     delete,
     path = "/api/books/{id}",
+    tag = "books",
+    security(("bearer_auth" = [])),
     params(
-        ("id" = i32, Path, description = "Book ID")
+        ("id" = String, Path, description = "Book ID")
     ),
     responses(
         (status = 200, description = "Book deleted successfully"),
@@ -592,9 +637,12 @@ pub async fn delete_book(
 #[utoipa::path(
     put,
     path = "/api/books/{id}",
+    tag = "books",
+    security(("bearer_auth" = [])),
     params(
-        ("id" = i32, Path, description = "Book ID")
+        ("id" = String, Path, description = "Book ID")
     ),
+    request_body = Book,
     responses(
         (status = 200, description = "Book updated successfully"),
         (status = 404, description = "Book not found"),
@@ -605,7 +653,7 @@ pub async fn update_book(
     State(state): State<crate::infrastructure::AppState>,
     _claims: crate::auth::Claims,
     axum::extract::Path(id): axum::extract::Path<String>,
-    Json(book_data): Json<Book>,
+    ValidatedJson(book_data): ValidatedJson<Book>,
 ) -> impl IntoResponse {
     use crate::domain::DomainError;
 
@@ -654,6 +702,15 @@ pub async fn update_book(
         Ok(updated_book) => {
             let _ = crate::sync::log_operation(db, "book", &id, "UPDATE", None).await;
 
+            if let (Some(previous), Some(current)) = (
+                current_book.reading_status.as_deref(),
+                updated_book.reading_status.as_deref(),
+            ) {
+                let _ =
+                    crate::services::reading_queue::on_status_change(db, &id, previous, current)
+                        .await;
+            }
+
             // Update authors in book_authors join table
             {
                 use crate::models::author::{ActiveModel as AuthorActive, Entity as AuthorEntity};
@@ -783,7 +840,7 @@ pub async fn update_book(
     }
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
 pub struct TagDto {
     pub name: String,
     pub count: usize,
@@ -792,8 +849,9 @@ pub struct TagDto {
 #[utoipa::path(
     get,
     path = "/api/books/tags",
+    tag = "books",
     responses(
-        (status = 200, description = "List all tags with counts")
+        (status = 200, description = "List all tags with counts", body = [TagDto])
     )
 )]
 pub async fn list_tags(
@@ -834,8 +892,9 @@ pub async fn list_tags(
 #[utoipa::path(
     get,
     path = "/api/books/{id}",
+    tag = "books",
     params(
-        ("id" = i32, Path, description = "Book ID")
+        ("id" = String, Path, description = "Book ID")
     ),
     responses(
         (status = 200, description = "Book found"),
@@ -854,7 +913,14 @@ pub async fn get_book(
             if !is_owner {
                 // Hide private books behind a 404 rather than a 403 so an
                 // anonymous caller can't confirm their existence.
-                if book_dto.private.unwrap_or(false) {
+                let tag_restricted = Book::tag_restricted_ids(state.db())
+                    .await
+                    .map(|hidden| hidden.contains(&id))
+                    .unwrap_or(true);
+                if book_dto.private.unwrap_or(false)
+                    || book_dto.archived.unwrap_or(false)
+                    || tag_restricted
+                {
                     return (
                         StatusCode::NOT_FOUND,
                         Json(json!({"error": "Book not found"})),
@@ -863,6 +929,12 @@ pub async fn get_book(
                 }
                 book_dto.redact_for_peer();
             }
+            // Visitors only see related books they could open themselves.
+            match crate::services::book_relations::related_books(state.db(), &id, !is_owner).await {
+                Ok(related) if !related.is_empty() => book_dto.related = Some(related),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to load related books of {}: {}", id, e),
+            }
             (StatusCode::OK, Json(book_dto)).into_response()
         }
         Ok(None) => (
@@ -878,16 +950,43 @@ pub async fn get_book(
     }
 }
 
+/// Query parameters of `GET /api/books/:id/cover`
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct CoverQuery {
+    /// `thumbnail` (100x150), `medium` (300x450) or `large` (600x900)
+    pub size: Option<String>,
+}
+
 /// Serves a book's cover image as a resized JPEG thumbnail.
 ///
 /// Output is always 300x450 JPEG (quality 85, ~50 KB cap). Resizing happens
 /// on every request so Flutter's HTTP cache (and Cache-Control below) does
 /// the heavy lifting across clients; encoding is CPU-bound but quick on a
-/// 300x450 target.
+/// 300x450 target. With `size`, one of the stored sizes of the cover is
+/// served instead, cached on disk (see `services::book_covers`).
+#[utoipa::path(
+    get,
+    path = "/api/books/{id}/cover",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID"), CoverQuery),
+    responses(
+        (status = 200, description = "JPEG cover, 300x450 unless `size` asks otherwise", content_type = "image/jpeg"),
+        (status = 400, description = "Unknown size"),
+        (status = 404, description = "Book or cover not found")
+    )
+)]
 pub async fn get_book_cover(
     State(state): State<crate::infrastructure::AppState>,
     axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<CoverQuery>,
 ) -> Result<Response, StatusCode> {
+    use crate::utils::cover_image::CoverSize;
+
+    let size = match query.size.as_deref() {
+        Some(s) => Some(CoverSize::parse(s).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+
     let book = crate::models::book::Entity::find_by_id(id.clone())
         .one(state.db())
         .await
@@ -908,30 +1007,41 @@ pub async fn get_book_cover(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    // Re-base onto the current covers dir when registered (FFI mode): iOS may
+    // Re-based onto the current covers dir when registered (FFI mode): iOS may
     // have reassigned the data-container UUID across an update, leaving the
     // persisted absolute path pointing at a dead container even though the file
-    // survives under the new one. In server-binary mode the covers dir is not
-    // registered, so the stored path is read as-is (paths are stable there).
-    let read_path = match crate::api::frb::covers_dir() {
-        Some(dir) => crate::utils::cover_url::rebase_local_cover_path(dir, cover_path, &id),
-        None => std::path::PathBuf::from(cover_path),
-    };
-
-    let raw = tokio::fs::read(&read_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
+    // survives under the new one. In server-binary mode a full path is read
+    // as-is and a bare `<id>.jpg` from the covers root.
+    let read_path = crate::utils::cover_url::local_cover_read_path(cover_path, &id)
+        .ok_or(StatusCode::NOT_FOUND)?;
 
-    // Decode + resize is CPU-bound; keep the async runtime free.
-    let jpeg = tokio::task::spawn_blocking(move || {
-        crate::utils::cover_image::resize_to_jpeg_thumbnail(&raw)
-    })
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .map_err(|e| {
-        tracing::warn!("cover {id}: resize failed: {e}");
-        StatusCode::UNPROCESSABLE_ENTITY
-    })?;
+    let jpeg = match size {
+        Some(size) => crate::services::book_covers::read_cover(read_path, &id, size)
+            .await
+            .map_err(|e| match e {
+                crate::services::book_covers::BookCoverError::Io(_) => StatusCode::NOT_FOUND,
+                e => {
+                    tracing::warn!("cover {id}: {} failed: {e}", size.as_str());
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+            })?,
+        None => {
+            let raw = tokio::fs::read(&read_path)
+                .await
+                .map_err(|_| StatusCode::NOT_FOUND)?;
+
+            // Decode + resize is CPU-bound; keep the async runtime free.
+            tokio::task::spawn_blocking(move || {
+                crate::utils::cover_image::resize_to_jpeg_thumbnail(&raw)
+            })
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map_err(|e| {
+                tracing::warn!("cover {id}: resize failed: {e}");
+                StatusCode::UNPROCESSABLE_ENTITY
+            })?
+        }
+    };
 
     Ok(Response::builder()
         .status(StatusCode::OK)
@@ -948,7 +1058,173 @@ pub async fn get_book_cover(
         .unwrap())
 }
 
-#[derive(serde::Deserialize)]
+/// Upload a cover photo for a book.
+///
+/// The image, turned and cropped as asked, becomes the book's cover,
+/// replacing the URL a provider derived, and is stored at every size
+/// `GET /api/books/:id/cover` serves. `format` is ignored: covers are JPEG.
+#[utoipa::path(
+    post,
+    path = "/api/books/{id}/cover",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID"), crate::services::images::ImageOps),
+    request_body(content = String, description = "Multipart upload of a JPEG or PNG cover photo", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Cover stored; the updated book", body = Book),
+        (status = 400, description = "No image in request, or a bad rotation or crop"),
+        (status = 404, description = "Book not found"),
+        (status = 413, description = "Image over `max_image_upload_mb` (10 MB by default)"),
+        (status = 415, description = "Not a JPEG, PNG or WebP image"),
+        (status = 507, description = "Image storage quota reached")
+    )
+)]
+pub async fn upload_book_cover(
+    State(state): State<crate::infrastructure::AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::Query(ops): axum::extract::Query<crate::services::images::ImageOps>,
+    mut multipart: axum::extract::Multipart,
+) -> Response {
+    use crate::services::book_covers::{self, BookCoverError};
+
+    let image = match crate::api::images::read_upload(&mut multipart).await {
+        Ok(image) => image,
+        Err(resp) => return resp,
+    };
+
+    let dir = crate::utils::cover_url::covers_root();
+    let limits = crate::services::images::ImageLimits::runtime();
+    match book_covers::store_cover(state.db(), &dir, &id, image, ops, limits).await {
+        Ok(model) => {
+            crate::services::catalog_notification::schedule_catalog_changed_notification(
+                state.clone(),
+            );
+            Json(Book::from(model)).into_response()
+        }
+        Err(e) => {
+            let status = match &e {
+                BookCoverError::BookNotFound => StatusCode::NOT_FOUND,
+                BookCoverError::Image(e) => crate::api::images::image_status(e),
+                BookCoverError::Db(_) | BookCoverError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
+
+/// Free digitized editions of a book (Gallica, Internet Archive, Wikisource).
+///
+/// Queries the providers live on each call: a provider that is down only
+/// leaves its editions out.
+#[utoipa::path(
+    get,
+    path = "/api/books/{id}/digital-editions",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID")),
+    responses(
+        (status = 200, description = "Free digitized editions, the one a provider linked to the book first", body = [DigitalEdition]),
+        (status = 404, description = "Book not found")
+    )
+)]
+pub async fn get_digital_editions(
+    State(state): State<crate::infrastructure::AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> impl IntoResponse {
+    match crate::services::digital_editions::digital_editions(state.db(), &id).await {
+        Ok(Some(editions)) => (StatusCode::OK, Json(editions)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Book not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+fn book_relation_error(e: crate::services::book_relations::BookRelationError) -> Response {
+    use crate::services::book_relations::BookRelationError;
+    let status = match e {
+        BookRelationError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        BookRelationError::BookNotFound(_) | BookRelationError::NotFound => StatusCode::NOT_FOUND,
+        BookRelationError::Duplicate => StatusCode::CONFLICT,
+        BookRelationError::Invalid(_) => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Books related to a book: box set, bound-with, adaptation, translation.
+#[utoipa::path(
+    get,
+    path = "/api/books/{id}/relations",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID")),
+    responses(
+        (status = 200, description = "Related books, each relation read from this book's side", body = [RelatedBook])
+    )
+)]
+pub async fn get_book_relations(
+    State(state): State<crate::infrastructure::AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Response {
+    match crate::services::book_relations::related_books(state.db(), &id, false).await {
+        Ok(related) => (StatusCode::OK, Json(related)).into_response(),
+        Err(e) => book_relation_error(e.into()),
+    }
+}
+
+/// Relate a book to another one.
+#[utoipa::path(
+    post,
+    path = "/api/books/{id}/relations",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID")),
+    request_body = NewBookRelation,
+    responses(
+        (status = 201, description = "Relation added; the book's related books", body = [RelatedBook]),
+        (status = 400, description = "Unknown relation type, or the book itself"),
+        (status = 404, description = "Book or related book not found"),
+        (status = 409, description = "The books are already related this way")
+    )
+)]
+pub async fn add_book_relation(
+    State(state): State<crate::infrastructure::AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Json(new): Json<crate::services::book_relations::NewBookRelation>,
+) -> Response {
+    match crate::services::book_relations::add_relation(state.db(), &id, new).await {
+        Ok(related) => (StatusCode::CREATED, Json(related)).into_response(),
+        Err(e) => book_relation_error(e),
+    }
+}
+
+/// Remove a relation, from either of its books.
+#[utoipa::path(
+    delete,
+    path = "/api/books/{id}/relations/{relation_id}",
+    tag = "books",
+    params(
+        ("id" = String, Path, description = "Book ID"),
+        ("relation_id" = String, Path, description = "Relation ID")
+    ),
+    responses(
+        (status = 200, description = "Relation removed; the book's related books", body = [RelatedBook]),
+        (status = 404, description = "No such relation on this book")
+    )
+)]
+pub async fn remove_book_relation(
+    State(state): State<crate::infrastructure::AppState>,
+    axum::extract::Path((id, relation_id)): axum::extract::Path<(String, String)>,
+) -> Response {
+    match crate::services::book_relations::remove_relation(state.db(), &id, &relation_id).await {
+        Ok(related) => (StatusCode::OK, Json(related)).into_response(),
+        Err(e) => book_relation_error(e),
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct ReorderRequest {
     pub book_ids: Vec<i32>,
 }
@@ -956,6 +1232,8 @@ pub struct ReorderRequest {
 #[utoipa::path(
     patch,
     path = "/api/books/reorder",
+    tag = "books",
+    security(("bearer_auth" = [])),
     request_body = ReorderRequest,
     responses(
         (status = 200, description = "Books reordered successfully"),
@@ -2,7 +2,9 @@ use axum::{Json, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Deserialize)]
+use crate::services::nl_query;
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ChatRequest {
     pub message: String,
 }
@@ -14,8 +16,19 @@ pub struct ChatResponse {
     pub data: Option<Value>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/chat",
+    tag = "books",
+    request_body = ChatRequest,
+    responses(
+        (status = 200, description = "Assistant reply")
+    )
+)]
 pub async fn chat_handler(Json(payload): Json<ChatRequest>) -> impl IntoResponse {
     let message = payload.message.to_lowercase();
+    // "unread sci-fi from the 80s" needs no "search" to be a search.
+    let parsed = nl_query::parse(&payload.message);
 
     let (text, intent, data) =
         if message.contains("search") || message.contains("find") || message.contains("lookup") {
@@ -28,8 +41,11 @@ pub async fn chat_handler(Json(payload): Json<ChatRequest>) -> impl IntoResponse
                 .trim()
                 .to_string();
 
+            let filters = nl_query::parse(&query);
             if query.is_empty() {
                 ("What would you like to search for?".to_string(), None, None)
+            } else if filters.has_filters() {
+                library_search(filters)
             } else {
                 (
                     format!("I can help you find books about '{}'.", query),
@@ -37,6 +53,8 @@ pub async fn chat_handler(Json(payload): Json<ChatRequest>) -> impl IntoResponse
                     Some(serde_json::json!({ "query": query })),
                 )
             }
+        } else if parsed.has_filters() {
+            library_search(parsed)
         } else if message.contains("hello") || message.contains("hi") {
             (
                 "Hello! I'm BiblioGenius. I can help you find books or manage your library."
@@ -55,3 +73,13 @@ pub async fn chat_handler(Json(payload): Json<ChatRequest>) -> impl IntoResponse
 
     Json(ChatResponse { text, intent, data })
 }
+
+/// A reply carrying the filters of `services::nl_query`, for the app to list
+/// the matching books (`GET /api/search/nl` takes the same words).
+fn library_search(filters: nl_query::ParsedQuery) -> (String, Option<String>, Option<Value>) {
+    (
+        "Here are the books of your library matching that.".to_string(),
+        Some("LIBRARY_SEARCH".to_string()),
+        serde_json::to_value(filters).ok(),
+    )
+}
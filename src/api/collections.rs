@@ -10,12 +10,21 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, QueryFilter, Set};
 use serde::Deserialize;
 use serde_json::json;
 
+use crate::api::uploads;
 use crate::domain::CreateCollectionInput;
 use crate::infrastructure::AppState;
 use crate::services::collection_service::{self, CollectionServiceError};
 use crate::utils::library_helpers::resolve_library_id;
 
 /// List all collections with book counts
+#[utoipa::path(
+    get,
+    path = "/api/collections",
+    tag = "collections",
+    responses(
+        (status = 200, description = "All collections with book counts")
+    )
+)]
 pub async fn list_collections(State(state): State<AppState>) -> impl IntoResponse {
     match state.collection_repo.find_all().await {
         Ok(collections) => (StatusCode::OK, Json(collections)).into_response(),
@@ -27,7 +36,7 @@ pub async fn list_collections(State(state): State<AppState>) -> impl IntoRespons
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct CreateCollectionRequest {
     pub name: String,
     pub description: Option<String>,
@@ -35,6 +44,15 @@ pub struct CreateCollectionRequest {
 }
 
 /// Create a new collection
+#[utoipa::path(
+    post,
+    path = "/api/collections",
+    tag = "collections",
+    request_body = CreateCollectionRequest,
+    responses(
+        (status = 201, description = "Collection created")
+    )
+)]
 pub async fn create_collection(
     State(state): State<AppState>,
     Json(payload): Json<CreateCollectionRequest>,
@@ -66,6 +84,16 @@ pub async fn create_collection(
 }
 
 /// Get a single collection by ID
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}",
+    tag = "collections",
+    params(("id" = String, Path, description = "Collection id")),
+    responses(
+        (status = 200, description = "The collection"),
+        (status = 404, description = "Collection not found")
+    )
+)]
 pub async fn get_collection(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -85,7 +113,7 @@ pub async fn get_collection(
     }
 }
 
-#[derive(Deserialize, Default)]
+#[derive(Deserialize, Default, utoipa::IntoParams)]
 pub struct DeleteCollectionQuery {
     /// When true, also delete books that are not loaned/borrowed, not in
     /// another collection, and not on any shelf.
@@ -94,6 +122,19 @@ pub struct DeleteCollectionQuery {
 }
 
 /// Delete a collection by ID. Optionally delete its books via `?delete_books=true`.
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}",
+    tag = "collections",
+    params(
+        ("id" = String, Path, description = "Collection id"),
+        DeleteCollectionQuery
+    ),
+    responses(
+        (status = 200, description = "Collection deleted, with the books removed alongside"),
+        (status = 204, description = "Collection deleted")
+    )
+)]
 pub async fn delete_collection(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -119,6 +160,16 @@ pub async fn delete_collection(
 }
 
 /// Preview what happens if a collection is deleted along with its books.
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}/deletion-preview",
+    tag = "collections",
+    params(("id" = String, Path, description = "Collection id")),
+    responses(
+        (status = 200, description = "Books that only belong to this collection"),
+        (status = 404, description = "Collection not found")
+    )
+)]
 pub async fn deletion_preview(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -139,6 +190,15 @@ pub async fn deletion_preview(
 }
 
 /// Get all books in a collection
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}/books",
+    tag = "collections",
+    params(("id" = String, Path, description = "Collection id")),
+    responses(
+        (status = 200, description = "Books of the collection")
+    )
+)]
 pub async fn get_collection_books(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -154,6 +214,18 @@ pub async fn get_collection_books(
 }
 
 /// Add a book to a collection
+#[utoipa::path(
+    post,
+    path = "/api/collections/{collection_id}/books/{book_id}",
+    tag = "collections",
+    params(
+        ("collection_id" = String, Path, description = "Collection id"),
+        ("book_id" = String, Path, description = "Book id")
+    ),
+    responses(
+        (status = 201, description = "Book added")
+    )
+)]
 pub async fn add_book_to_collection(
     State(state): State<AppState>,
     Path((collection_id, book_id)): Path<(String, String)>,
@@ -183,6 +255,18 @@ pub async fn add_book_to_collection(
 }
 
 /// Remove a book from a collection
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{collection_id}/books/{book_id}",
+    tag = "collections",
+    params(
+        ("collection_id" = String, Path, description = "Collection id"),
+        ("book_id" = String, Path, description = "Book id")
+    ),
+    responses(
+        (status = 204, description = "Book removed")
+    )
+)]
 pub async fn remove_book_from_collection(
     State(state): State<AppState>,
     Path((collection_id, book_id)): Path<(String, String)>,
@@ -212,6 +296,15 @@ pub async fn remove_book_from_collection(
 }
 
 /// Get all collections a book belongs to
+#[utoipa::path(
+    get,
+    path = "/api/books/{id}/collections",
+    tag = "collections",
+    params(("id" = String, Path, description = "Book id")),
+    responses(
+        (status = 200, description = "Collections the book belongs to")
+    )
+)]
 pub async fn get_book_collections(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
@@ -226,12 +319,22 @@ pub async fn get_book_collections(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UpdateBookCollectionsRequest {
     pub collection_ids: Vec<String>,
 }
 
 /// Update which collections a book belongs to
+#[utoipa::path(
+    put,
+    path = "/api/books/{id}/collections",
+    tag = "collections",
+    params(("id" = String, Path, description = "Book id")),
+    request_body = UpdateBookCollectionsRequest,
+    responses(
+        (status = 200, description = "Memberships replaced")
+    )
+)]
 pub async fn update_book_collections(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
@@ -256,12 +359,23 @@ pub async fn update_book_collections(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct MarkSeriesRequest {
     pub is_series: bool,
 }
 
 /// Mark a collection as a series (`source = 'series'`) or revert it to `manual`.
+#[utoipa::path(
+    put,
+    path = "/api/collections/{id}/series",
+    tag = "collections",
+    params(("id" = String, Path, description = "Collection id")),
+    request_body = MarkSeriesRequest,
+    responses(
+        (status = 200, description = "Series flag updated"),
+        (status = 404, description = "Collection not found")
+    )
+)]
 pub async fn mark_collection_as_series(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -297,12 +411,25 @@ pub async fn mark_collection_as_series(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct SetVolumeRequest {
     pub volume_number: Option<i32>,
 }
 
 /// Set (or clear) a book's reading-order position within a collection.
+#[utoipa::path(
+    put,
+    path = "/api/collections/{collection_id}/books/{book_id}/volume",
+    tag = "collections",
+    params(
+        ("collection_id" = String, Path, description = "Collection id"),
+        ("book_id" = String, Path, description = "Book id")
+    ),
+    request_body = SetVolumeRequest,
+    responses(
+        (status = 200, description = "Volume number set")
+    )
+)]
 pub async fn set_book_volume_number(
     State(state): State<AppState>,
     Path((collection_id, book_id)): Path<(String, String)>,
@@ -335,25 +462,136 @@ pub async fn set_book_volume_number(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct ImportQuery {
     pub owned: Option<bool>,
+    /// `merge` (default), `skip`, `create_anyway` or `update`: what to do
+    /// with a row matching a catalogued book by ISBN, or by close title and
+    /// author. Matched books are added to the collection all the same.
+    #[param(value_type = Option<String>)]
+    pub dedup: Option<crate::import::DedupPolicy>,
+}
+
+/// Add the books of imported rows to the collection, with a copy when
+/// `owned` and the book has none yet (so re-uploading a list does not add
+/// copies). Returns how many were linked and the linking errors.
+async fn link_imported_rows(
+    state: &AppState,
+    collection_id: &str,
+    summary: &crate::import::ImportSummary,
+    owned: bool,
+) -> (usize, Vec<String>) {
+    use crate::models::copy;
+    use sea_orm::EntityTrait;
+
+    let db = state.db();
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for row in &summary.rows {
+        // Failed rows carry no book
+        let Some(book_id) = row.book_id.as_deref() else {
+            continue;
+        };
+        if let Err(e) = state.collection_repo.add_book(collection_id, book_id).await {
+            errors.push(format!("Failed to link {}: {}", row.title, e));
+            continue;
+        }
+        let _ = crate::sync::log_operation_with_str_id(
+            db,
+            "collection_book",
+            collection_id,
+            "INSERT",
+            Some(serde_json::json!({ "book_id": book_id })),
+        )
+        .await;
+        count += 1;
+
+        let has_copy = copy::Entity::find()
+            .filter(copy::Column::BookId.eq(book_id))
+            .one(db)
+            .await
+            .ok()
+            .flatten()
+            .is_some();
+        if owned
+            && !has_copy
+            && let Ok(lib_id) = resolve_library_id(db).await
+        {
+            let now = chrono::Utc::now();
+            let copy_model = copy::ActiveModel {
+                book_id: Set(book_id.to_string()),
+                library_id: Set(lib_id),
+                status: Set("available".to_string()),
+                is_temporary: Set(false),
+                created_at: Set(now.to_rfc3339()),
+                updated_at: Set(now.to_rfc3339()),
+                ..Default::default()
+            };
+            if let Ok(saved_copy) = copy_model.insert(db).await {
+                let _ = crate::sync::log_operation(
+                    db,
+                    "copy",
+                    &saved_copy.id,
+                    "INSERT",
+                    Some(serde_json::json!({ "book_id": book_id })),
+                )
+                .await;
+            }
+        }
+    }
+    (count, errors)
+}
+
+/// `(row key, book id)` of the rows that became or matched a book.
+fn imported_row_keys(
+    keys: Vec<String>,
+    summary: &crate::import::ImportSummary,
+) -> Vec<(String, String)> {
+    keys.into_iter()
+        .zip(&summary.rows)
+        .filter_map(|(key, row)| row.book_id.clone().map(|id| (key, id)))
+        .collect()
+}
+
+fn collection_not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({"error": "Collection not found"})),
+    )
+        .into_response()
 }
 
 /// Import books from file into a collection
 /// Note: This handler uses direct DB access for complex book/copy creation logic
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/books",
+    tag = "collections",
+    params(
+        ("id" = String, Path, description = "Collection id"),
+        ImportQuery
+    ),
+    request_body(content = String, description = "Multipart upload of a list of ISBNs", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Import summary, with each row's action and the dedup rule that matched it. The file becomes the collection's import source"),
+        (status = 400, description = "Missing or unreadable file"),
+        (status = 413, description = "File over `max_import_upload_mb` (20 MB by default)"),
+        (status = 415, description = "Not a text export: an image, archive, PDF or other binary file"),
+        (status = 404, description = "Collection not found")
+    )
+)]
 pub async fn import_collection(
     State(state): State<AppState>,
     Path(id): Path<String>,
     axum::extract::Query(query): axum::extract::Query<ImportQuery>,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
-    use crate::import;
-    use crate::models::{book, copy};
-    use sea_orm::EntityTrait;
+    use crate::import::{self, DedupPolicy};
+    use crate::services::collection_source;
 
     let db = state.db();
     let import_as_owned = query.owned.unwrap_or(false);
+    let policy = query.dedup.unwrap_or(DedupPolicy::Merge);
 
     // Verify collection exists
     if state
@@ -364,129 +602,233 @@ pub async fn import_collection(
         .flatten()
         .is_none()
     {
-        return (
+        return collection_not_found();
+    }
+
+    let data = match uploads::read_file(&mut multipart, uploads::UploadKind::Import).await {
+        Ok(data) => data,
+        Err(e) => return e.into_response(),
+    };
+    let books = match import::parse_import_file(&data) {
+        Ok(books) => books,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+
+    // 1. Create, merge or match each row (see `import::import_books`)
+    let keys: Vec<String> = books.iter().map(import::row_key).collect();
+    let summary = import::import_books(db, books, policy, Some(import_as_owned)).await;
+    // 2. Link to Collection, with copies when owned
+    let (count, mut errors) = link_imported_rows(&state, &id, &summary, import_as_owned).await;
+    errors.splice(0..0, summary.errors.iter().cloned());
+
+    // 3. Remember the file for later re-imports
+    if let Some(file) = collection_source::fingerprint(&data)
+        && let Err(e) = collection_source::remember(
+            db,
+            &id,
+            &file,
+            import_as_owned,
+            imported_row_keys(keys, &summary),
+            Vec::new(),
+        )
+        .await
+    {
+        tracing::warn!("collection {id}: could not record the import source: {e}");
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "imported": count,
+            "created": summary.created,
+            "skipped": summary.skipped,
+            "merged": summary.merged,
+            "updated": summary.updated,
+            "rows": summary.rows,
+            "errors": if errors.is_empty() { None } else { Some(errors) }
+        })),
+    )
+        .into_response()
+}
+
+/// The file a collection was last imported from
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}/source",
+    tag = "collections",
+    params(("id" = String, Path, description = "Collection id")),
+    responses(
+        (status = 200, description = "Format, column layout and row count of the last import", body = CollectionImportSource),
+        (status = 404, description = "The collection was never imported from a file")
+    )
+)]
+pub async fn get_collection_source(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match crate::services::collection_source::get(state.db(), &id).await {
+        Ok(Some(source)) => Json(source).into_response(),
+        Ok(None) => (
             StatusCode::NOT_FOUND,
-            Json(json!({"error": "Collection not found"})),
+            Json(json!({"error": "This collection was not imported from a file"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SyncQuery {
+    /// Take the books of rows no longer in the file out of the collection;
+    /// otherwise they are only reported
+    #[serde(default)]
+    pub prune: bool,
+}
+
+/// Re-import an updated version of a collection's source file
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/sync",
+    tag = "collections",
+    params(
+        ("id" = String, Path, description = "Collection id"),
+        SyncQuery
+    ),
+    request_body(content = String, description = "Multipart upload of the updated file", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "New rows added, matched rows updated, rows gone from the file reported (`missing`) or taken out (`removed`)"),
+        (status = 400, description = "Missing or unreadable file"),
+        (status = 413, description = "File over `max_import_upload_mb` (20 MB by default)"),
+        (status = 415, description = "Not a text export: an image, archive, PDF or other binary file"),
+        (status = 404, description = "Collection not found, or never imported from a file"),
+        (status = 409, description = "The file's format or columns differ from the source's")
+    )
+)]
+pub async fn sync_collection(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<SyncQuery>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    use crate::import::{self, DedupPolicy};
+    use crate::services::collection_source;
+    use std::collections::HashSet;
+
+    let db = state.db();
+    let internal = |e: sea_orm::DbErr| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response()
+    };
+    let source = match collection_source::get(db, &id).await {
+        Ok(Some(source)) => source,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Import a file into this collection first"})),
+            )
+                .into_response();
+        }
+        Err(e) => return internal(e),
+    };
+
+    let data = match uploads::read_file(&mut multipart, uploads::UploadKind::Import).await {
+        Ok(data) => data,
+        Err(e) => return e.into_response(),
+    };
+    let Some(file) = collection_source::fingerprint(&data) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Unknown file format"})),
+        )
+            .into_response();
+    };
+    if !collection_source::same_layout(&source, &file) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "This file's format or columns differ from the collection's source; import it instead",
+                "source_format": source.format,
+                "file_format": file.format,
+            })),
         )
             .into_response();
     }
+    let books = match import::parse_import_file(&data) {
+        Ok(books) => books,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
 
-    while let Some(field) = multipart.next_field().await.unwrap_or(None) {
-        if field.name() == Some("file") {
-            let data = field.bytes().await.unwrap_or_default();
-            match import::parse_import_file(&data) {
-                Ok(books) => {
-                    let mut count = 0;
-                    let mut errors = Vec::new();
-                    for req in books {
-                        let now = chrono::Utc::now();
-                        // 1. Find existing book by ISBN, or create
-                        let existing = if let Some(ref isbn) = req.isbn {
-                            book::Entity::find()
-                                .filter(book::Column::Isbn.eq(isbn))
-                                .one(db)
-                                .await
-                                .ok()
-                                .flatten()
-                        } else {
-                            None
-                        };
-                        let book_result = if let Some(existing_book) = existing {
-                            // Book already exists - reuse it
-                            if import_as_owned && !existing_book.owned {
-                                let mut active: book::ActiveModel = existing_book.clone().into();
-                                active.owned = Set(true);
-                                active.updated_at = Set(now.to_rfc3339());
-                                let _ = active.update(db).await;
-                            }
-                            Ok(existing_book)
-                        } else {
-                            let new_book = book::ActiveModel {
-                                title: Set(req.title.clone()),
-                                isbn: Set(req.isbn),
-                                summary: Set(None),
-                                publisher: Set(req.publisher),
-                                publication_year: Set(req.publication_year),
-                                created_at: Set(now.to_rfc3339()),
-                                updated_at: Set(now.to_rfc3339()),
-                                owned: Set(import_as_owned),
-                                ..Default::default()
-                            };
-                            new_book.insert(db).await
-                        };
-                        match book_result {
-                            Ok(created_book) => {
-                                let _ = crate::sync::log_operation(
-                                    db,
-                                    "book",
-                                    &created_book.id,
-                                    "INSERT",
-                                    None,
-                                )
-                                .await;
-
-                                // 2. Link to Collection via repository
-                                if let Err(e) =
-                                    state.collection_repo.add_book(&id, &created_book.id).await
-                                {
-                                    errors.push(format!("Failed to link {}: {}", req.title, e));
-                                    continue;
-                                }
-                                let _ = crate::sync::log_operation_with_str_id(
-                                    db,
-                                    "collection_book",
-                                    &id,
-                                    "INSERT",
-                                    Some(serde_json::json!({ "book_id": created_book.id })),
-                                )
-                                .await;
-                                count += 1;
-
-                                // 3. Create Copy if owned
-                                if import_as_owned && let Ok(lib_id) = resolve_library_id(db).await
-                                {
-                                    let copy_model = copy::ActiveModel {
-                                        book_id: Set(created_book.id.clone()),
-                                        library_id: Set(lib_id),
-                                        status: Set("available".to_string()),
-                                        is_temporary: Set(false),
-                                        created_at: Set(now.to_rfc3339()),
-                                        updated_at: Set(now.to_rfc3339()),
-                                        ..Default::default()
-                                    };
-                                    if let Ok(saved_copy) = copy_model.insert(db).await {
-                                        let _ = crate::sync::log_operation(
-                                            db,
-                                            "copy",
-                                            &saved_copy.id,
-                                            "INSERT",
-                                            Some(serde_json::json!({ "book_id": created_book.id })),
-                                        )
-                                        .await;
-                                    }
-                                }
-                            }
-                            Err(e) => errors.push(format!("{}: {}", req.title, e)),
-                        }
-                    }
-                    return (
-                        StatusCode::OK,
-                        Json(json!({
-                            "imported": count,
-                            "errors": if errors.is_empty() { None } else { Some(errors) }
-                        })),
-                    )
-                        .into_response();
-                }
-                Err(e) => {
-                    return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response();
-                }
+    // New rows are created, rows still there update their book
+    let keys: Vec<String> = books.iter().map(import::row_key).collect();
+    let present: HashSet<String> = keys.iter().cloned().collect();
+    let summary = import::import_books(db, books, DedupPolicy::Update, Some(source.owned)).await;
+    let (_, mut errors) = link_imported_rows(&state, &id, &summary, source.owned).await;
+    errors.splice(0..0, summary.errors.iter().cloned());
+
+    // Rows gone from the file
+    let missing = match collection_source::missing_rows(db, &id, &present).await {
+        Ok(missing) => missing,
+        Err(e) => return internal(e),
+    };
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+    for row in missing {
+        if !query.prune {
+            kept.push(row);
+            continue;
+        }
+        match state.collection_repo.remove_book(&id, &row.book_id).await {
+            Ok(()) => {
+                let _ = crate::sync::log_operation_with_str_id(
+                    db,
+                    "collection_book",
+                    &id,
+                    "DELETE",
+                    Some(serde_json::json!({ "book_id": row.book_id })),
+                )
+                .await;
+                removed.push(row.book_id);
+            }
+            Err(e) => {
+                errors.push(format!("Failed to remove {}: {}", row.book_id, e));
+                kept.push(row);
             }
         }
     }
+    let missing: Vec<String> = kept.iter().map(|r| r.book_id.clone()).collect();
+
+    if let Err(e) = collection_source::remember(
+        db,
+        &id,
+        &file,
+        source.owned,
+        imported_row_keys(keys, &summary),
+        kept,
+    )
+    .await
+    {
+        return internal(e);
+    }
+
     (
-        StatusCode::BAD_REQUEST,
-        Json(json!({"error": "No file uploaded"})),
+        StatusCode::OK,
+        Json(json!({
+            "unchanged_file": file.file_sha256 == source.file_sha256,
+            "created": summary.created,
+            "updated": summary.updated,
+            "unchanged": summary.skipped,
+            "missing": missing,
+            "removed": removed,
+            "rows": summary.rows,
+            "errors": if errors.is_empty() { None } else { Some(errors) }
+        })),
     )
         .into_response()
 }
@@ -1,18 +1,25 @@
+use crate::api::validation::{
+    Validate, ValidatedJson, ValidationErrors, check_email, require_non_blank,
+};
+use crate::domain::{Contact as DomainContact, ContactInput, ContactQuery, DomainError};
+use crate::infrastructure::AppState;
 use crate::models::{
-    contact::{self as contact_model, Entity as Contact},
+    contact::{self as contact_model},
     peer, peer_book,
 };
+use crate::services::contact_routes;
+use crate::services::membership::{self, MemberStatus, Membership};
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     response::IntoResponse,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ContactDto {
     pub id: Option<String>,
     pub r#type: String,
@@ -33,10 +40,14 @@ pub struct ContactDto {
     pub is_active: bool,
     /// True when this contact's peer owns the requested book (book_isbn query param).
     pub has_book: bool,
+    /// Read-only here; set through `PUT /api/contacts/{id}/membership`.
+    #[serde(default)]
+    pub membership: Option<Membership>,
 }
 
 impl From<contact_model::Model> for ContactDto {
     fn from(model: contact_model::Model) -> Self {
+        let membership = membership::is_member(&model).then(|| Membership::from(&model));
         Self {
             id: Some(model.id),
             r#type: model.r#type,
@@ -54,13 +65,83 @@ impl From<contact_model::Model> for ContactDto {
             notes: model.notes,
             user_id: model.user_id,
             library_owner_id: Some(model.library_owner_id),
+            membership,
             is_active: model.is_active,
             has_book: false,
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl From<DomainContact> for ContactDto {
+    fn from(contact: DomainContact) -> Self {
+        let membership = membership::membership_of(&contact);
+        Self {
+            id: Some(contact.id),
+            r#type: contact.contact_type,
+            name: contact.name,
+            first_name: contact.first_name,
+            email: contact.email,
+            phone: contact.phone,
+            address: contact.address,
+            street_address: contact.street_address,
+            postal_code: contact.postal_code,
+            city: contact.city,
+            country: contact.country,
+            latitude: contact.latitude,
+            longitude: contact.longitude,
+            notes: contact.notes,
+            user_id: contact.user_id,
+            library_owner_id: Some(contact.library_owner_id),
+            membership,
+            is_active: contact.is_active,
+            has_book: false,
+        }
+    }
+}
+
+impl From<ContactDto> for ContactInput {
+    fn from(dto: ContactDto) -> Self {
+        Self {
+            contact_type: dto.r#type,
+            name: dto.name,
+            first_name: dto.first_name,
+            email: dto.email,
+            phone: dto.phone,
+            address: dto.address,
+            street_address: dto.street_address,
+            postal_code: dto.postal_code,
+            city: dto.city,
+            country: dto.country,
+            latitude: dto.latitude,
+            longitude: dto.longitude,
+            notes: dto.notes,
+            user_id: dto.user_id,
+            library_owner_id: dto.library_owner_id,
+            is_active: dto.is_active,
+        }
+    }
+}
+
+impl Validate for ContactDto {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        require_non_blank(&mut errors, "name", &self.name);
+        check_email(&mut errors, "email", self.email.as_deref());
+        if let Some(lat) = self.latitude
+            && !(-90.0..=90.0).contains(&lat)
+        {
+            errors.add("latitude", "must be between -90 and 90");
+        }
+        if let Some(lon) = self.longitude
+            && !(-180.0..=180.0).contains(&lon)
+        {
+            errors.add("longitude", "must be between -180 and 180");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
 pub struct ContactsQuery {
     pub library_id: Option<i32>,
     pub r#type: Option<String>,
@@ -70,22 +151,26 @@ pub struct ContactsQuery {
 }
 
 // List contacts with optional filters
+#[utoipa::path(
+    get,
+    path = "/api/contacts",
+    tag = "contacts",
+    params(ContactsQuery),
+    responses(
+        (status = 200, description = "Contacts matching the filters")
+    )
+)]
 pub async fn list_contacts(
-    State(db): State<DatabaseConnection>,
+    State(state): State<AppState>,
     Query(params): Query<ContactsQuery>,
 ) -> impl IntoResponse {
-    // Start with only active contacts
-    let mut query = Contact::find().filter(contact_model::Column::IsActive.eq(true));
-
-    if let Some(library_id) = params.library_id {
-        query = query.filter(contact_model::Column::LibraryOwnerId.eq(library_id));
-    }
-
-    if let Some(contact_type) = &params.r#type {
-        query = query.filter(contact_model::Column::Type.eq(contact_type.clone()));
-    }
+    let db = state.db();
+    let query = ContactQuery {
+        library_id: params.library_id,
+        contact_type: params.r#type.clone(),
+    };
 
-    let contacts = match query.all(&db).await {
+    let contacts = match state.contact_repo.find_active(&query).await {
         Ok(c) => c,
         Err(e) => {
             return (
@@ -102,7 +187,7 @@ pub async fn list_contacts(
         Some(isbn) => {
             let matching_books = peer_book::Entity::find()
                 .filter(peer_book::Column::Isbn.eq(isbn.as_str()))
-                .all(&db)
+                .all(db)
                 .await
                 .unwrap_or_default();
             let peer_ids: Vec<i32> = matching_books.iter().map(|b| b.peer_id).collect();
@@ -111,7 +196,7 @@ pub async fn list_contacts(
             } else {
                 peer::Entity::find()
                     .filter(peer::Column::Id.is_in(peer_ids))
-                    .all(&db)
+                    .all(db)
                     .await
                     .unwrap_or_default()
                     .into_iter()
@@ -141,14 +226,29 @@ pub async fn list_contacts(
 }
 
 // Get single contact
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact id")),
+    responses(
+        (status = 200, description = "The contact, with `fee_balance`, the fines they still owe"),
+        (status = 404, description = "Contact not found")
+    )
+)]
 pub async fn get_contact(
-    State(db): State<DatabaseConnection>,
+    State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    match Contact::find_by_id(id).one(&db).await {
+    match state.contact_repo.find_by_id(&id).await {
         Ok(Some(contact)) => {
+            // Unpaid fines are part of the contact's record (see `services::fines`).
+            let fee_balance = crate::services::fines::balance(state.db(), &contact.id)
+                .await
+                .ok();
             let contact_dto = ContactDto::from(contact);
-            Json(serde_json::json!({"contact": contact_dto})).into_response()
+            Json(serde_json::json!({"contact": contact_dto, "fee_balance": fee_balance}))
+                .into_response()
         }
         Ok(None) => (
             StatusCode::NOT_FOUND,
@@ -164,70 +264,25 @@ pub async fn get_contact(
 }
 
 // Create contact
+#[utoipa::path(
+    post,
+    path = "/api/contacts",
+    tag = "contacts",
+    request_body = ContactDto,
+    responses(
+        (status = 201, description = "Contact created"),
+        (status = 400, description = "Invalid contact"),
+        (status = 422, description = "Validation failed")
+    )
+)]
 pub async fn create_contact(
-    State(db): State<DatabaseConnection>,
-    Json(contact_dto): Json<ContactDto>,
+    State(state): State<AppState>,
+    ValidatedJson(contact_dto): ValidatedJson<ContactDto>,
 ) -> impl IntoResponse {
-    let now = chrono::Utc::now().to_rfc3339();
-
-    // Resolve library_owner_id. The replicated `contacts` table no longer
-    // carries a foreign key into `libraries` (ADR-044), so reject a dangling
-    // owner id at the app layer, as the database constraint once did.
-    let library_owner_id = match contact_dto.library_owner_id {
-        Some(id) => match crate::utils::library_helpers::library_exists(&db, id).await {
-            Ok(true) => id,
-            Ok(false) => {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(serde_json::json!({"error": format!("library {id} does not exist")})),
-                )
-                    .into_response();
-            }
-            Err(e) => {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": format!("Database error: {}", e)})),
-                )
-                    .into_response();
-            }
-        },
-        None => match crate::utils::library_helpers::resolve_library_id(&db).await {
-            Ok(id) => id,
-            Err(e) => {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": format!("No library found: {}", e)})),
-                )
-                    .into_response();
-            }
-        },
-    };
-
-    let new_contact = contact_model::ActiveModel {
-        r#type: Set(contact_dto.r#type),
-        name: Set(contact_dto.name),
-        first_name: Set(contact_dto.first_name),
-        email: Set(contact_dto.email),
-        phone: Set(contact_dto.phone),
-        address: Set(contact_dto.address),
-        street_address: Set(contact_dto.street_address),
-        postal_code: Set(contact_dto.postal_code),
-        city: Set(contact_dto.city),
-        country: Set(contact_dto.country),
-        latitude: Set(contact_dto.latitude),
-        longitude: Set(contact_dto.longitude),
-        notes: Set(contact_dto.notes),
-        user_id: Set(contact_dto.user_id),
-        library_owner_id: Set(library_owner_id),
-        is_active: Set(contact_dto.is_active),
-        created_at: Set(now.clone()),
-        updated_at: Set(now),
-        ..Default::default()
-    };
-
-    match new_contact.insert(&db).await {
-        Ok(model) => {
-            let contact_dto = ContactDto::from(model);
+    // The repository resolves the owning library, and rejects a dangling one.
+    match state.contact_repo.create(contact_dto.into()).await {
+        Ok(contact) => {
+            let contact_dto = ContactDto::from(contact);
             (
                 StatusCode::CREATED,
                 Json(serde_json::json!({
@@ -237,6 +292,11 @@ pub async fn create_contact(
             )
                 .into_response()
         }
+        Err(DomainError::Validation(msg)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": msg})),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({"error": format!("Failed to create contact: {}", e)})),
@@ -246,94 +306,413 @@ pub async fn create_contact(
 }
 
 // Update contact
+#[utoipa::path(
+    put,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact id")),
+    request_body = ContactDto,
+    responses(
+        (status = 200, description = "Contact updated"),
+        (status = 404, description = "Contact not found"),
+        (status = 422, description = "Validation failed")
+    )
+)]
 pub async fn update_contact(
-    State(db): State<DatabaseConnection>,
+    State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(contact_dto): Json<ContactDto>,
+    ValidatedJson(contact_dto): ValidatedJson<ContactDto>,
 ) -> impl IntoResponse {
-    let contact = Contact::find_by_id(id).one(&db).await.unwrap_or(None);
-
-    if let Some(contact) = contact {
-        let mut active_model: contact_model::ActiveModel = contact.into();
-        let now = chrono::Utc::now().to_rfc3339();
-
-        active_model.r#type = Set(contact_dto.r#type);
-        active_model.name = Set(contact_dto.name);
-        active_model.first_name = Set(contact_dto.first_name);
-        active_model.email = Set(contact_dto.email);
-        active_model.phone = Set(contact_dto.phone);
-        active_model.address = Set(contact_dto.address);
-        active_model.street_address = Set(contact_dto.street_address);
-        active_model.postal_code = Set(contact_dto.postal_code);
-        active_model.city = Set(contact_dto.city);
-        active_model.country = Set(contact_dto.country);
-        active_model.latitude = Set(contact_dto.latitude);
-        active_model.longitude = Set(contact_dto.longitude);
-        active_model.notes = Set(contact_dto.notes);
-        active_model.user_id = Set(contact_dto.user_id);
-        if let Some(lid) = contact_dto.library_owner_id {
-            active_model.library_owner_id = Set(lid);
+    match state.contact_repo.update(&id, contact_dto.into()).await {
+        Ok(contact) => {
+            let contact_dto = ContactDto::from(contact);
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "contact": contact_dto,
+                    "message": "Contact updated successfully"
+                })),
+            )
+                .into_response()
         }
-        active_model.is_active = Set(contact_dto.is_active);
-        active_model.updated_at = Set(now);
+        Err(DomainError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Contact not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to update contact: {}", e)})),
+        )
+            .into_response(),
+    }
+}
 
-        match active_model.update(&db).await {
-            Ok(model) => {
-                let contact_dto = ContactDto::from(model);
-                (
-                    StatusCode::OK,
-                    Json(serde_json::json!({
-                        "contact": contact_dto,
-                        "message": "Contact updated successfully"
-                    })),
-                )
-                    .into_response()
-            }
-            Err(e) => (
+// Promote a quick-loan contact to a full contact
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/promote",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact id")),
+    request_body = ContactDto,
+    responses(
+        (status = 200, description = "Contact promoted; its loans are kept"),
+        (status = 400, description = "Not a quick contact, or still typed as one"),
+        (status = 404, description = "Contact not found"),
+        (status = 422, description = "Validation failed")
+    )
+)]
+pub async fn promote_contact(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidatedJson(contact_dto): ValidatedJson<ContactDto>,
+) -> impl IntoResponse {
+    let contact = match state.contact_repo.find_by_id(&id).await {
+        Ok(Some(contact)) => contact,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({"error": "Contact not found"})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Failed to update contact: {}", e)})),
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
             )
-                .into_response(),
+                .into_response();
         }
-    } else {
-        (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": "Contact not found"})),
+    };
+
+    if contact.contact_type != contact_model::QUICK_CONTACT_TYPE {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Contact is already a full contact"})),
         )
-            .into_response()
+            .into_response();
+    }
+    if contact_dto.r#type == contact_model::QUICK_CONTACT_TYPE {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Pick a contact type other than Quick"})),
+        )
+            .into_response();
+    }
+
+    // Same row, new type and details: the loans recorded so far follow along.
+    match state.contact_repo.update(&id, contact_dto.into()).await {
+        Ok(contact) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "contact": ContactDto::from(contact),
+                "message": "Contact promoted successfully"
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to update contact: {}", e)})),
+        )
+            .into_response(),
     }
 }
 
 // Delete contact (soft delete)
+#[utoipa::path(
+    delete,
+    path = "/api/contacts/{id}",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact id")),
+    responses(
+        (status = 200, description = "Contact deleted"),
+        (status = 404, description = "Contact not found")
+    )
+)]
 pub async fn delete_contact(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.contact_repo.deactivate(&id).await {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({"message": "Contact deleted successfully"})),
+        )
+            .into_response(),
+        Err(DomainError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Contact not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Failed to delete contact: {}", e)})),
+        )
+            .into_response(),
+    }
+}
+
+impl Validate for Membership {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let day = |field: &str, value: &Option<String>, errors: &mut ValidationErrors| {
+            let parsed = value
+                .as_deref()
+                .map(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d"));
+            if let Some(Err(_)) = parsed {
+                errors.add(field, "must be a YYYY-MM-DD date");
+            }
+            parsed.and_then(Result::ok)
+        };
+        let since = day("member_since", &self.member_since, &mut errors);
+        let expires = day("expires_at", &self.expires_at, &mut errors);
+        if let (Some(since), Some(expires)) = (since, expires)
+            && expires < since
+        {
+            errors.add("expires_at", "must not be before member_since");
+        }
+        if let Some(number) = &self.member_number {
+            require_non_blank(&mut errors, "member_number", number);
+        }
+        if self.fee_paid.is_some_and(|fee| fee < 0.0) {
+            errors.add("fee_paid", "must not be negative");
+        }
+        errors.into_result()
+    }
+}
+
+// Set or end a contact's membership
+#[utoipa::path(
+    put,
+    path = "/api/contacts/{id}/membership",
+    tag = "contacts",
+    params(("id" = String, Path, description = "Contact id")),
+    request_body = Membership,
+    responses(
+        (status = 200, description = "Membership saved; all fields null ends it"),
+        (status = 404, description = "Contact not found"),
+        (status = 422, description = "Validation failed")
+    )
+)]
+pub async fn update_membership(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
+    ValidatedJson(membership): ValidatedJson<Membership>,
 ) -> impl IntoResponse {
-    let contact = Contact::find_by_id(id).one(&db).await.unwrap_or(None);
+    match membership::set_membership(&db, &id, membership).await {
+        Ok(Some(model)) => Json(serde_json::json!({
+            "contact": ContactDto::from(model),
+            "message": "Membership updated successfully"
+        }))
+        .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "Contact not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+            .into_response(),
+    }
+}
 
-    if let Some(contact) = contact {
-        let mut active_model: contact_model::ActiveModel = contact.into();
-        active_model.is_active = Set(false);
-        active_model.updated_at = Set(chrono::Utc::now().to_rfc3339());
+/// Query parameters of `GET /api/contacts/members`
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct MembersQuery {
+    /// `all` (default), `current` or `lapsed`
+    #[param(value_type = Option<String>)]
+    pub status: Option<MemberStatus>,
+    /// `json` (default) or `csv`, an attachment for printing or a spreadsheet
+    pub format: Option<String>,
+}
 
-        match active_model.update(&db).await {
-            Ok(_) => (
-                StatusCode::OK,
-                Json(serde_json::json!({"message": "Contact deleted successfully"})),
+// List the members of the library
+#[utoipa::path(
+    get,
+    path = "/api/contacts/members",
+    tag = "contacts",
+    params(MembersQuery),
+    responses(
+        (status = 200, description = "Members by member number, as JSON or as a CSV attachment"),
+        (status = 400, description = "Unknown format")
+    )
+)]
+pub async fn list_members(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<MembersQuery>,
+) -> impl IntoResponse {
+    let members = match membership::list_members(&db, params.status.unwrap_or_default()).await {
+        Ok(m) => m,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": format!("Database error: {}", e)})),
             )
-                .into_response(),
+                .into_response();
+        }
+    };
+
+    let today = crate::utils::time_zone::library_zone(&db)
+        .await
+        .unwrap_or(crate::utils::time_zone::LibraryZone::Host)
+        .today();
+    match params.format.as_deref().unwrap_or("json") {
+        "json" => {
+            let members: Vec<serde_json::Value> = members
+                .into_iter()
+                .map(|m| {
+                    let lapsed = membership::lapsed_on(&m, today);
+                    serde_json::json!({ "contact": ContactDto::from(m), "lapsed": lapsed })
+                })
+                .collect();
+            let total = members.len();
+            Json(serde_json::json!({ "members": members, "total": total })).into_response()
+        }
+        "csv" => match membership::members_csv(&members, today) {
+            Ok(csv) => {
+                let filename = format!("members_{}.csv", today.format("%Y-%m-%d"));
+                (
+                    [
+                        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                        (
+                            header::CONTENT_DISPOSITION,
+                            format!("attachment; filename=\"{filename}\""),
+                        ),
+                    ],
+                    csv,
+                )
+                    .into_response()
+            }
             Err(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Failed to delete contact: {}", e)})),
+                Json(serde_json::json!({"error": e.to_string()})),
             )
                 .into_response(),
-        }
-    } else {
-        (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({"error": "Contact not found"})),
+        },
+        other => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": format!("unknown format: {other}")})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct GeocodeQuery {
+    /// Re-geocode contacts that already have coordinates too
+    #[serde(default)]
+    pub force: bool,
+}
+
+// Fill in contact coordinates from their address
+#[utoipa::path(
+    post,
+    path = "/api/contacts/geocode",
+    tag = "contacts",
+    params(GeocodeQuery),
+    responses(
+        (status = 200, description = "How many contacts were geocoded, not found, failed or skipped", body = GeocodeReport),
+        (status = 403, description = "Geocoding is not enabled")
+    )
+)]
+pub async fn geocode_contacts(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<GeocodeQuery>,
+) -> impl IntoResponse {
+    use crate::services::geocoding;
+
+    if !geocoding::is_enabled(&db).await {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": format!("Geocoding is off: add `{}` to the enabled modules", geocoding::MODULE_FLAG)
+            })),
         )
-            .into_response()
+            .into_response();
+    }
+    match geocoding::geocode_all(&db, params.force).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": format!("Database error: {}", e)})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct NearbyQuery {
+    /// Point searched around; the library's location when not given
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    /// Metres, 2000 by default
+    pub radius_m: Option<u32>,
+    /// 50 by default
+    pub limit: Option<usize>,
+}
+
+fn route_error_response(e: contact_routes::RouteError) -> axum::response::Response {
+    let status = match e {
+        contact_routes::RouteError::NoOrigin => StatusCode::BAD_REQUEST,
+        contact_routes::RouteError::UnknownContacts(_) => StatusCode::NOT_FOUND,
+        contact_routes::RouteError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({"error": e.to_string()}))).into_response()
+}
+
+// Contacts living around a point
+#[utoipa::path(
+    get,
+    path = "/api/contacts/nearby",
+    tag = "contacts",
+    params(NearbyQuery),
+    responses(
+        (status = 200, description = "Contacts with coordinates within the radius, nearest first", body = [LocatedContact]),
+        (status = 400, description = "No point given and the library has no location")
+    )
+)]
+pub async fn nearby_contacts(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<NearbyQuery>,
+) -> impl IntoResponse {
+    let point = params.lat.zip(params.lon);
+    match contact_routes::nearby(
+        &db,
+        point,
+        params.radius_m.unwrap_or(2000),
+        params.limit.unwrap_or(50),
+    )
+    .await
+    {
+        Ok(contacts) => {
+            let total = contacts.len();
+            Json(serde_json::json!({ "contacts": contacts, "total": total })).into_response()
+        }
+        Err(e) => route_error_response(e),
+    }
+}
+
+// Visiting order for a round of deliveries and pickups
+#[utoipa::path(
+    post,
+    path = "/api/contacts/route",
+    tag = "contacts",
+    request_body = RouteRequest,
+    responses(
+        (status = 200, description = "Stops in visiting order (nearest neighbour first), with leg and total distances", body = Route),
+        (status = 400, description = "No start point and nothing to start from"),
+        (status = 404, description = "Unknown contact ids")
+    )
+)]
+pub async fn plan_contact_route(
+    State(db): State<DatabaseConnection>,
+    Json(request): Json<contact_routes::RouteRequest>,
+) -> impl IntoResponse {
+    match contact_routes::plan_route(&db, &request).await {
+        Ok(route) => Json(route).into_response(),
+        Err(e) => route_error_response(e),
     }
 }
 
@@ -341,7 +720,7 @@ pub async fn delete_contact(
 #[allow(clippy::needless_update)]
 mod tests {
     use super::*;
-    use sea_orm::{ConnectionTrait, Database, Set, Statement};
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, Set, Statement};
 
     async fn setup() -> DatabaseConnection {
         let db = Database::connect("sqlite::memory:").await.unwrap();
@@ -517,12 +896,15 @@ mod tests {
             .exec(&db)
             .await;
 
-        let still_active = Contact::find()
-            .filter(contact_model::Column::IsActive.eq(true))
-            .filter(contact_model::Column::Name.eq("Carol"))
-            .all(&db)
+        use crate::domain::ContactRepository;
+
+        let still_active: Vec<_> = crate::infrastructure::SeaOrmContactRepository::new(db.clone())
+            .find_active(&ContactQuery::default())
             .await
-            .unwrap();
+            .unwrap()
+            .into_iter()
+            .filter(|c| c.name == "Carol")
+            .collect();
 
         assert!(
             still_active.is_empty(),
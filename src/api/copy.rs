@@ -9,10 +9,23 @@ use axum::{
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::domain::{CreateCopyInput, DomainError, UpdateCopyInput};
+use crate::api::validation::{Validate, ValidatedJson, ValidationErrors, check_range};
+use crate::domain::{CreateCopyInput, DomainError, LendingTerms, UpdateCopyInput};
 use crate::infrastructure::AppState;
+use crate::services::copy_calendar;
+
+/// Longest `max_duration_days` a copy's lending terms may set (one year).
+pub const MAX_LENDING_DURATION_DAYS: i32 = 365;
 
 // List all copies with book details
+#[utoipa::path(
+    get,
+    path = "/api/copies",
+    tag = "copies",
+    responses(
+        (status = 200, description = "All copies with book details and a total")
+    )
+)]
 pub async fn list_copies(State(state): State<AppState>) -> impl IntoResponse {
     match state.copy_repo.find_all().await {
         Ok(result) => Json(json!({
@@ -29,7 +42,7 @@ pub async fn list_copies(State(state): State<AppState>) -> impl IntoResponse {
 }
 
 /// Request DTO for creating a copy
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct CreateCopyRequest {
     pub book_id: String,
     pub library_id: Option<i32>,
@@ -48,6 +61,16 @@ pub struct CreateCopyRequest {
 }
 
 // Create a new copy
+#[utoipa::path(
+    post,
+    path = "/api/copies",
+    tag = "copies",
+    request_body = CreateCopyRequest,
+    responses(
+        (status = 201, description = "Copy created"),
+        (status = 400, description = "Invalid copy")
+    )
+)]
 pub async fn create_copy(
     State(state): State<AppState>,
     Json(payload): Json<CreateCopyRequest>,
@@ -122,6 +145,16 @@ pub async fn create_copy(
 }
 
 // Get a single copy by ID
+#[utoipa::path(
+    get,
+    path = "/api/copies/{id}",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "The copy"),
+        (status = 404, description = "Copy not found")
+    )
+)]
 pub async fn get_copy(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     match state.copy_repo.find_by_id(&id).await {
         Ok(Some(copy)) => (StatusCode::OK, Json(json!({"copy": copy}))).into_response(),
@@ -138,7 +171,113 @@ pub async fn get_copy(State(state): State<AppState>, Path(id): Path<String>) ->
     }
 }
 
+/// GET /api/copies/:id/history - Where a copy has been
+#[utoipa::path(
+    get,
+    path = "/api/copies/{id}/history",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "Acquisition, loans, transfers and status changes, oldest first", body = [CopyEvent]),
+        (status = 404, description = "Copy not found")
+    )
+)]
+pub async fn get_copy_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match crate::services::copy_history::copy_history(state.db(), &id).await {
+        Ok(Some(events)) => (StatusCode::OK, Json(json!({"history": events}))).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Copy not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": format!("Database error: {}", e)})),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/copies/:id/availability - When a copy is likely to be free
+///
+/// Open to peers and patrons: dates only, and a copy of a book they could
+/// not open is not found.
+#[utoipa::path(
+    get,
+    path = "/api/copies/{id}/availability",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "Current loan, hold and waiting requests, and the day the copy is likely free", body = CopyCalendar),
+        (status = 404, description = "Copy not found")
+    )
+)]
+pub async fn get_copy_availability(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    claims: Option<crate::auth::Claims>,
+) -> impl IntoResponse {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Copy not found"})),
+        )
+            .into_response()
+    };
+    let settings = match state.loan_settings_repo.get_settings().await {
+        Ok(s) => s,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response();
+        }
+    };
+    let calendar = match copy_calendar::calendar(state.db(), &id, &settings).await {
+        Ok(Some(calendar)) => calendar,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Database error: {}", e)})),
+            )
+                .into_response();
+        }
+    };
+    if claims.is_none() {
+        // Same visibility as `GET /api/books/:id` for visitors.
+        let hidden = match state.book_repo.find_by_id(&calendar.book_id).await {
+            Ok(Some(book)) => {
+                book.private.unwrap_or(false)
+                    || book.archived.unwrap_or(false)
+                    || crate::models::Book::tag_restricted_ids(state.db())
+                        .await
+                        .map(|hidden| hidden.contains(&calendar.book_id))
+                        .unwrap_or(true)
+            }
+            _ => true,
+        };
+        if hidden {
+            return not_found();
+        }
+    }
+    (StatusCode::OK, Json(calendar)).into_response()
+}
+
 // Get copies of a specific book
+#[utoipa::path(
+    get,
+    path = "/api/books/{id}/copies",
+    tag = "copies",
+    params(("id" = String, Path, description = "Book id")),
+    responses(
+        (status = 200, description = "Copies of the book and a total")
+    )
+)]
 pub async fn get_book_copies(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
@@ -160,6 +299,14 @@ pub async fn get_book_copies(
 /// Get borrowed copies (status='borrowed', borrowed from a peer or a contact)
 /// with book details
 /// Returns "loans" key for Flutter compatibility
+#[utoipa::path(
+    get,
+    path = "/api/copies/borrowed",
+    tag = "copies",
+    responses(
+        (status = 200, description = "Copies borrowed from peers or contacts, as `loans`")
+    )
+)]
 pub async fn get_borrowed_copies(State(state): State<AppState>) -> impl IntoResponse {
     match state.copy_repo.find_borrowed().await {
         Ok(result) => {
@@ -204,6 +351,15 @@ pub async fn get_borrowed_copies(State(state): State<AppState>) -> impl IntoResp
 }
 
 // Delete a copy
+#[utoipa::path(
+    delete,
+    path = "/api/copies/{id}",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "Copy deleted (idempotent)")
+    )
+)]
 pub async fn delete_copy(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -234,31 +390,74 @@ pub async fn delete_copy(
 }
 
 /// DTO for partial copy updates
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateCopyRequest {
     pub status: Option<String>,
     pub notes: Option<Option<String>>,
     pub acquisition_date: Option<Option<String>>,
     pub price: Option<Option<f64>>,
+    /// Lending terms. An empty object (`{}`) clears them.
+    pub lending_terms: Option<LendingTerms>,
+}
+
+impl Validate for UpdateCopyRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(terms) = &self.lending_terms {
+            check_range(
+                &mut errors,
+                "lending_terms.max_duration_days",
+                terms.max_duration_days,
+                1,
+                MAX_LENDING_DURATION_DAYS,
+            );
+            if let Some(value) = terms.replacement_value
+                && (!value.is_finite() || value < 0.0)
+            {
+                errors.add(
+                    "lending_terms.replacement_value",
+                    "must be a non-negative number",
+                );
+            }
+        }
+        errors.into_result()
+    }
 }
 
 /// Update a copy (mainly for status changes)
+#[utoipa::path(
+    put,
+    path = "/api/copies/{id}",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    request_body = UpdateCopyRequest,
+    responses(
+        (status = 200, description = "Copy updated"),
+        (status = 404, description = "Copy not found"),
+        (status = 422, description = "Invalid lending terms")
+    )
+)]
 pub async fn update_copy(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateCopyRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateCopyRequest>,
 ) -> impl IntoResponse {
+    // Carried in the log entry: peers apply it, and the copy history shows it.
+    let logged = payload.status.as_ref().map(|s| json!({ "status": s }));
     let input = UpdateCopyInput {
         status: payload.status,
         notes: payload.notes,
         acquisition_date: payload.acquisition_date,
         price: payload.price,
+        lending_terms: payload
+            .lending_terms
+            .map(|t| Some(t).filter(|t| *t != LendingTerms::default())),
         ..Default::default()
     };
 
     match state.copy_repo.update(&id, input).await {
         Ok(copy) => {
-            let _ = crate::sync::log_operation(state.db(), "copy", &id, "UPDATE", None).await;
+            let _ = crate::sync::log_operation(state.db(), "copy", &id, "UPDATE", logged).await;
             (StatusCode::OK, Json(json!({"copy": copy}))).into_response()
         }
         Err(DomainError::NotFound) => (
@@ -1,73 +1,66 @@
-use crate::{import, models::book};
+use crate::api::uploads::{UploadKind, read_file};
+use crate::import;
 use axum::{
     Json,
-    extract::{Multipart, State},
+    extract::{Multipart, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
 
+/// Query parameters of `POST /api/import/file`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ImportFileQuery {
+    /// `skip` (default), `merge` or `create_anyway`: what to do with a row
+    /// matching a catalogued book by ISBN, or by close title and author
+    #[param(value_type = Option<String>)]
+    pub dedup: Option<import::DedupPolicy>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import/file",
+    tag = "data",
+    params(ImportFileQuery),
+    request_body(content = String, description = "Multipart upload of a CSV, Goodreads, Babelio or MARC file", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Import summary, with each row's action and the dedup rule that matched it"),
+        (status = 400, description = "Missing or unreadable file"),
+        (status = 413, description = "File over `max_import_upload_mb` (20 MB by default)"),
+        (status = 415, description = "Not a text export: an image, archive, PDF or other binary file")
+    )
+)]
 pub async fn import_file(
     State(db): State<DatabaseConnection>,
+    Query(query): Query<ImportFileQuery>,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
-    while let Some(field) = multipart.next_field().await.unwrap_or(None) {
-        if field.name() == Some("file") {
-            let data = field.bytes().await.unwrap_or_default();
-            match import::parse_import_file(&data) {
-                Ok(books) => {
-                    let mut count = 0;
-                    let mut errors = Vec::new();
-                    for req in books {
-                        let now = chrono::Utc::now();
-                        // Check for existing book by ISBN
-                        let existing = if let Some(ref isbn) = req.isbn {
-                            book::Entity::find()
-                                .filter(book::Column::Isbn.eq(isbn))
-                                .one(&db)
-                                .await
-                                .ok()
-                                .flatten()
-                        } else {
-                            None
-                        };
-                        if existing.is_some() {
-                            count += 1; // Already exists, skip
-                            continue;
-                        }
-                        let new_book = book::ActiveModel {
-                            title: Set(req.title.clone()),
-                            isbn: Set(req.isbn),
-                            summary: Set(None),
-                            publisher: Set(req.publisher),
-                            publication_year: Set(req.publication_year),
-                            created_at: Set(now.to_rfc3339()),
-                            updated_at: Set(now.to_rfc3339()),
-                            ..Default::default()
-                        };
-                        match new_book.insert(&db).await {
-                            Ok(_) => count += 1,
-                            Err(e) => errors.push(format!("{}: {}", req.title, e)),
-                        }
-                    }
-                    return (
-                        StatusCode::OK,
-                        Json(serde_json::json!({
-                            "imported": count,
-                            "errors": if errors.is_empty() { None } else { Some(errors) }
-                        })),
-                    )
-                        .into_response();
-                }
-                Err(e) => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({ "error": e })),
-                    )
-                        .into_response();
-                }
-            }
+    let data = match read_file(&mut multipart, UploadKind::Import).await {
+        Ok(data) => data,
+        Err(e) => return e.into_response(),
+    };
+    match import::parse_import_file(&data) {
+        Ok(books) => {
+            let policy = query.dedup.unwrap_or_default();
+            let summary = import::import_books(&db, books, policy, None).await;
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "imported": summary.imported,
+                    "created": summary.created,
+                    "skipped": summary.skipped,
+                    "merged": summary.merged,
+                    "rows": summary.rows,
+                    "errors": if summary.errors.is_empty() { None } else { Some(summary.errors) }
+                })),
+            )
+                .into_response()
         }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e })),
+        )
+            .into_response(),
     }
-    (StatusCode::BAD_REQUEST, "No file uploaded").into_response()
 }
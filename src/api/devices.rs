@@ -0,0 +1,281 @@
+//! Same-owner device sync: pairing, operation exchange and revocation.
+//! The protocol lives in `services::device_sync`.
+//!
+//! The `/devices/pair` and `/devices/sync/*` endpoints are called by the other
+//! install over the network: pairing is guarded by the pairing code, the rest by
+//! the secret shared at pairing. Everything else is owner-only.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::models::linked_device;
+use crate::services::device_sync::{
+    self, DEVICE_SECRET_HEADER, DEVICE_UUID_HEADER, DeviceSyncError, PairRequest, SyncOperation,
+};
+
+/// Request body for pairing with another install
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PairWithRequest {
+    /// Base URL of the install showing the code
+    pub url: String,
+    pub code: String,
+}
+
+/// Query parameters for pulling operations
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct OperationsQuery {
+    /// Last `operation_log.id` already imported
+    #[serde(default)]
+    pub since: i32,
+    pub limit: Option<u64>,
+}
+
+fn error_response(e: DeviceSyncError) -> Response {
+    let status = match e {
+        DeviceSyncError::InvalidCode | DeviceSyncError::Unauthorized => StatusCode::UNAUTHORIZED,
+        DeviceSyncError::SelfPairing => StatusCode::BAD_REQUEST,
+        DeviceSyncError::NotFound => StatusCode::NOT_FOUND,
+        DeviceSyncError::NotIdentified => StatusCode::SERVICE_UNAVAILABLE,
+        DeviceSyncError::Unreachable(_) | DeviceSyncError::Rejected(_) => StatusCode::BAD_GATEWAY,
+        DeviceSyncError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({"error": e.to_string()}))).into_response()
+}
+
+/// The paired device presenting the device-sync headers.
+async fn calling_device(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<linked_device::Model, Response> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+    device_sync::authenticate(
+        state.db(),
+        header(DEVICE_UUID_HEADER),
+        header(DEVICE_SECRET_HEADER),
+    )
+    .await
+    .map_err(error_response)
+}
+
+/// GET /api/devices - Installs paired for device sync
+#[utoipa::path(
+    get,
+    path = "/api/devices",
+    tag = "devices",
+    responses(
+        (status = 200, description = "Paired devices, oldest pairing first")
+    )
+)]
+pub async fn list_devices(State(state): State<AppState>) -> Response {
+    match device_sync::list_devices(state.db()).await {
+        Ok(devices) => (StatusCode::OK, Json(json!({"devices": devices}))).into_response(),
+        Err(e) => error_response(e.into()),
+    }
+}
+
+/// POST /api/devices/pairing - Show a pairing code to type on the other install
+#[utoipa::path(
+    post,
+    path = "/api/devices/pairing",
+    tag = "devices",
+    responses(
+        (status = 200, description = "Single-use code, valid ten minutes", body = PairingCode)
+    )
+)]
+pub async fn start_pairing() -> Response {
+    (StatusCode::OK, Json(device_sync::start_pairing())).into_response()
+}
+
+/// POST /api/devices/pair-with - Pair with the install showing a code
+#[utoipa::path(
+    post,
+    path = "/api/devices/pair-with",
+    tag = "devices",
+    request_body = PairWithRequest,
+    responses(
+        (status = 201, description = "Paired"),
+        (status = 401, description = "Code refused"),
+        (status = 502, description = "Install unreachable")
+    )
+)]
+pub async fn pair_with(
+    State(state): State<AppState>,
+    Json(req): Json<PairWithRequest>,
+) -> Response {
+    match device_sync::pair_with(&state, &req.url, &req.code).await {
+        Ok(device) => {
+            // Bring the new install up to date right away.
+            let sync_state = state.clone();
+            let paired = device.clone();
+            tokio::spawn(async move {
+                if let Err(e) = device_sync::sync_with_device(&sync_state, &paired).await {
+                    tracing::warn!("First sync with {} failed: {e}", paired.name);
+                }
+            });
+            (StatusCode::CREATED, Json(json!({"device": device}))).into_response()
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/devices/pair - Redeem our pairing code (called by the other install)
+#[utoipa::path(
+    post,
+    path = "/api/devices/pair",
+    tag = "devices",
+    request_body = PairRequest,
+    responses(
+        (status = 200, description = "Paired", body = PairResponse),
+        (status = 401, description = "Unknown, expired or used code")
+    )
+)]
+pub async fn accept_pairing(
+    State(state): State<AppState>,
+    Json(req): Json<PairRequest>,
+) -> Response {
+    match device_sync::accept_pairing(&state, req).await {
+        Ok(answer) => (StatusCode::OK, Json(answer)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/devices/:id/sync - Sync with a paired install now
+#[utoipa::path(
+    post,
+    path = "/api/devices/{id}/sync",
+    tag = "devices",
+    params(("id" = i32, Path, description = "Linked device id")),
+    responses(
+        (status = 200, description = "Operations pulled and pushed", body = SyncReport),
+        (status = 404, description = "Device not found"),
+        (status = 502, description = "Device unreachable")
+    )
+)]
+pub async fn sync_device(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    match device_sync::sync_device(&state, id).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// DELETE /api/devices/:id - Revoke a pairing on both installs
+#[utoipa::path(
+    delete,
+    path = "/api/devices/{id}",
+    tag = "devices",
+    params(("id" = i32, Path, description = "Linked device id")),
+    responses(
+        (status = 204, description = "Pairing revoked"),
+        (status = 404, description = "Device not found")
+    )
+)]
+pub async fn revoke_device(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    match device_sync::revoke(&state, id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/devices/sync/operations - Our operations since a cursor (paired install)
+#[utoipa::path(
+    get,
+    path = "/api/devices/sync/operations",
+    tag = "devices",
+    params(OperationsQuery),
+    responses(
+        (status = 200, description = "One page of operations", body = OperationBatch),
+        (status = 401, description = "Device not paired")
+    )
+)]
+pub async fn pull_operations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<OperationsQuery>,
+) -> Response {
+    if let Err(response) = calling_device(&state, &headers).await {
+        return response;
+    }
+    let limit = query
+        .limit
+        .unwrap_or(device_sync::SYNC_BATCH)
+        .clamp(1, device_sync::SYNC_BATCH);
+    match device_sync::export_operations(state.db(), query.since, limit).await {
+        Ok(batch) => (StatusCode::OK, Json(batch)).into_response(),
+        Err(e) => error_response(e.into()),
+    }
+}
+
+/// POST /api/devices/sync/operations - Receive a paired install's operations
+#[utoipa::path(
+    post,
+    path = "/api/devices/sync/operations",
+    tag = "devices",
+    request_body = Vec<SyncOperation>,
+    responses(
+        (status = 200, description = "Operations queued for the processor"),
+        (status = 401, description = "Device not paired")
+    )
+)]
+pub async fn push_operations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(operations): Json<Vec<SyncOperation>>,
+) -> Response {
+    let device = match calling_device(&state, &headers).await {
+        Ok(device) => device,
+        Err(response) => return response,
+    };
+    match device_sync::import_operations(state.db(), &device, operations).await {
+        Ok(imported) => (StatusCode::OK, Json(json!({"imported": imported}))).into_response(),
+        Err(e) => error_response(e.into()),
+    }
+}
+
+/// POST /api/devices/sync/revoke - A paired install revoked the pairing
+#[utoipa::path(
+    post,
+    path = "/api/devices/sync/revoke",
+    tag = "devices",
+    responses(
+        (status = 204, description = "Pairing forgotten"),
+        (status = 401, description = "Device not paired")
+    )
+)]
+pub async fn receive_revocation(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let device = match calling_device(&state, &headers).await {
+        Ok(device) => device,
+        Err(response) => return response,
+    };
+    match device_sync::receive_revocation(state.db(), &device).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e.into()),
+    }
+}
+
+/// GET /api/sync/status - Size of the operation log, retention and compaction
+#[utoipa::path(
+    get,
+    path = "/api/sync/status",
+    tag = "devices",
+    responses(
+        (status = 200, description = "Operation log size and upkeep", body = LogStatus)
+    )
+)]
+pub async fn sync_status(State(state): State<AppState>) -> Response {
+    match crate::services::oplog_compaction::log_status(state.db()).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(e) => error_response(e.into()),
+    }
+}
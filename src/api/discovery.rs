@@ -7,12 +7,20 @@ use serde::Deserialize;
 use serde_json::json;
 
 use crate::services::{
-    MAX_DISCOVERED_PEERS, get_local_peer_count, get_local_peers, is_mdns_active, restart_mdns,
-    stop_mdns,
+    MAX_DISCOVERED_PEERS, announced_record, get_local_peer_count, get_local_peers, is_mdns_active,
+    restart_mdns, stop_mdns,
 };
 
 /// GET /api/discovery/local
 /// Returns list of discovered BiblioGenius libraries on the local network
+#[utoipa::path(
+    get,
+    path = "/api/discovery/local",
+    tag = "discovery",
+    responses(
+        (status = 200, description = "Libraries announced on the LAN over mDNS")
+    )
+)]
 pub async fn list_local_peers() -> impl IntoResponse {
     let peers = get_local_peers();
     (
@@ -26,26 +34,44 @@ pub async fn list_local_peers() -> impl IntoResponse {
 }
 
 /// GET /api/discovery/status
-/// Returns the current status of mDNS service
+/// Returns the current status of mDNS service and the record announced
+#[utoipa::path(
+    get,
+    path = "/api/discovery/status",
+    tag = "discovery",
+    responses(
+        (status = 200, description = "Whether mDNS is running, and the record announced (name, port, addresses; null while stopped)")
+    )
+)]
 pub async fn mdns_status() -> impl IntoResponse {
     (
         StatusCode::OK,
         Json(json!({
             "active": is_mdns_active(),
             "service_type": "_bibliogenius._tcp.local.",
+            "announced": announced_record(),
             "peer_count": get_local_peer_count(),
             "max_peers": MAX_DISCOVERED_PEERS
         })),
     )
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ToggleRequest {
     pub enabled: bool,
 }
 
 /// POST /api/discovery/toggle
 /// Enable or disable mDNS discovery at runtime
+#[utoipa::path(
+    post,
+    path = "/api/discovery/toggle",
+    tag = "discovery",
+    request_body = ToggleRequest,
+    responses(
+        (status = 200, description = "mDNS started or stopped")
+    )
+)]
 pub async fn toggle_mdns(Json(payload): Json<ToggleRequest>) -> impl IntoResponse {
     if payload.enabled {
         // Start/restart mDNS using stored config from init_mdns
@@ -18,10 +18,23 @@ use crate::crypto::envelope::{ClearMessage, EncryptedEnvelope};
 use crate::infrastructure::AppState;
 use crate::models::peer;
 use crate::services::crypto_service::PeerInfo;
+use crate::utils::sanitize;
 
 /// Receive and process an encrypted peer message.
 ///
 /// Pipeline: open envelope → identify sender → dispatch by message_type → optional encrypted response.
+#[utoipa::path(
+    post,
+    path = "/api/e2ee/message",
+    tag = "peer-transport",
+    request_body(content = Value, description = "Sealed `EncryptedEnvelope`"),
+    responses(
+        (status = 200, description = "Message handled; the body may carry a sealed reply"),
+        (status = 400, description = "Envelope cannot be opened"),
+        (status = 403, description = "Sender unknown or blocked"),
+        (status = 409, description = "Replayed message")
+    )
+)]
 pub async fn receive_encrypted_message(
     State(state): State<AppState>,
     Json(envelope): Json<EncryptedEnvelope>,
@@ -150,6 +163,25 @@ pub async fn dispatch_clear_message(
     our_library_uuid: Option<&str>,
 ) -> axum::response::Response {
     let db = state.db();
+    // Handlers store payload fields as they come: clean them once here.
+    let mut clear_message = clear_message.clone();
+    sanitize::json_strings(&mut clear_message.payload);
+    let clear_message = &clear_message;
+    if crate::api::peer::is_peer_blocked(sender_peer) {
+        tracing::info!(
+            "E2EE: dropping '{}' from blocked peer {}",
+            clear_message.message_type,
+            sender_peer.id
+        );
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "Peer is blocked" })),
+        )
+            .into_response();
+    }
+    // The peer is reachable again: whatever we queued for it is due now.
+    crate::services::peer_outbox::retry_peer_now(db, sender_peer.id).await;
+
     match clear_message.message_type.as_str() {
         "loan_request" => {
             let response_payload =
@@ -190,10 +222,62 @@ pub async fn dispatch_clear_message(
             )
         }
 
-        "status_update" => handle_status_update(db, clear_message, sender_peer).await,
+        "status_update" => handle_status_update(state, clear_message, sender_peer).await,
 
         "peer_disconnect" => handle_peer_disconnect(db, sender_peer, our_library_uuid).await,
 
+        // Acknowledged even when ignored, so the sender does not retry it.
+        "announcement" => {
+            match crate::services::announcements::receive(db, sender_peer, &clear_message.payload)
+                .await
+            {
+                Ok(received) => {
+                    (StatusCode::OK, Json(json!({ "status": received.as_str() }))).into_response()
+                }
+                Err(crate::services::announcements::AnnouncementError::Invalid(e)) => {
+                    (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response()
+                }
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": e.to_string() })),
+                )
+                    .into_response(),
+            }
+        }
+
+        // Book clubs: state pushed by the host, actions sent to it. Both
+        // acknowledged even when ignored, so the sender does not retry them.
+        "book_club_state" | "book_club_action" => {
+            let received = if clear_message.message_type == "book_club_state" {
+                crate::modules::book_club::service::receive_state(
+                    state,
+                    sender_peer,
+                    &clear_message.payload,
+                )
+                .await
+            } else {
+                crate::modules::book_club::service::receive_action(
+                    state,
+                    sender_peer,
+                    &clear_message.payload,
+                )
+                .await
+            };
+            match received {
+                Ok(received) => {
+                    (StatusCode::OK, Json(json!({ "status": received.as_str() }))).into_response()
+                }
+                Err(crate::modules::book_club::domain::ClubError::Invalid(e)) => {
+                    (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response()
+                }
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": e.to_string() })),
+                )
+                    .into_response(),
+            }
+        }
+
         // ── Library sync via relay (ADR-012) ─────────────────────────
         "library_manifest_request" => {
             let response_payload = handle_library_manifest_request(db, our_library_uuid).await;
@@ -236,7 +320,8 @@ pub async fn dispatch_clear_message(
         }
 
         "request_status_query" => {
-            let response_payload = handle_request_status_query(db, clear_message).await;
+            let response_payload =
+                handle_request_status_query(db, clear_message, sender_peer).await;
             seal_response(
                 crypto_service,
                 &known_peers[peer_index],
@@ -321,6 +406,11 @@ async fn save_loan_request(
         .get("book_title")
         .and_then(|v| v.as_str())
         .unwrap_or("");
+    let (book_isbn, book_title) = (
+        sanitize::line(book_isbn, sanitize::MAX_CODE_CHARS),
+        sanitize::line(book_title, sanitize::MAX_TITLE_CHARS),
+    );
+    let (book_isbn, book_title) = (book_isbn.as_str(), book_title.as_str());
 
     if book_isbn.is_empty() && book_title.is_empty() {
         return Err("Missing book_isbn or book_title".to_string());
@@ -332,8 +422,15 @@ async fn save_loan_request(
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
+    // Wait in line for a copy rather than be turned down when none is free.
+    let hold = msg
+        .payload
+        .get("hold")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     // Check copy availability before creating the request
-    let has_available_copy = {
+    let (book_known, has_available_copy) = {
         use crate::models::{book, copy};
 
         let book_found = book::Entity::find()
@@ -343,15 +440,16 @@ async fn save_loan_request(
             .unwrap_or(None);
 
         if let Some(b) = book_found {
-            copy::Entity::find()
+            let available = copy::Entity::find()
                 .filter(copy::Column::BookId.eq(b.id))
                 .filter(copy::Column::Status.eq("available"))
                 .one(db)
                 .await
                 .unwrap_or(None)
-                .is_some()
+                .is_some();
+            (true, available)
         } else {
-            false
+            (false, false)
         }
     };
 
@@ -363,6 +461,7 @@ async fn save_loan_request(
         .filter(
             Condition::any()
                 .add(p2p_request::Column::Status.eq("pending"))
+                .add(p2p_request::Column::Status.eq("waitlisted"))
                 .add(p2p_request::Column::Status.eq("accepted")),
         )
         .one(db)
@@ -370,8 +469,12 @@ async fn save_loan_request(
         .unwrap_or(None)
         .is_some();
 
-    let initial_status = if has_available_copy && !already_has_active_request {
+    let initial_status = if already_has_active_request {
+        "rejected"
+    } else if has_available_copy {
         "pending"
+    } else if book_known && hold {
+        "waitlisted"
     } else {
         "rejected"
     };
@@ -388,6 +491,7 @@ async fn save_loan_request(
         created_at: Set(now.clone()),
         updated_at: Set(now),
         requester_request_id: Set(requester_request_id),
+        ..Default::default()
     };
 
     new_request
@@ -407,6 +511,12 @@ async fn save_loan_request(
             book_title,
             reason
         );
+    } else if initial_status == "waitlisted" {
+        tracing::info!(
+            "E2EE: Loan request waitlisted until a copy is back: {} for '{}'",
+            request_id,
+            book_title
+        );
     } else {
         tracing::info!(
             "E2EE: Loan request created: {} for '{}'",
@@ -427,6 +537,21 @@ async fn handle_loan_request_payload(
     sender_peer: &peer::Model,
     msg: &ClearMessage,
 ) -> serde_json::Value {
+    // A request retried from the sender's outbox may already be here:
+    // answer with the one we have instead of rejecting it as a duplicate.
+    if let Some(existing) = find_replayed_loan_request(db, sender_peer, msg).await {
+        tracing::info!(
+            "E2EE: Loan request {} replayed by {}, answering with its status",
+            existing.id,
+            sender_peer.name
+        );
+        return json!({
+            "request_id": existing.id,
+            "status": existing.status,
+            "message": "Loan request already received",
+        });
+    }
+
     match save_loan_request(db, sender_peer, msg).await {
         Ok((request_id, status)) => {
             // Check auto-approve: if enabled and peer is accepted, accept inline
@@ -480,6 +605,7 @@ async fn handle_loan_request_payload(
                             "isbn": result.book_isbn,
                             "title": result.book_title,
                             "cover_url": result.book_cover_url,
+                            "terms": result.terms,
                             "message": "Loan request auto-approved",
                         });
                     }
@@ -495,7 +621,7 @@ async fn handle_loan_request_payload(
             }
 
             // Emit borrow_request notification (only when NOT auto-approved)
-            if status == "pending" {
+            if status == "pending" || status == "waitlisted" {
                 let book_title = msg
                     .payload
                     .get("book_title")
@@ -520,6 +646,27 @@ async fn handle_loan_request_payload(
     }
 }
 
+/// The request `sender_peer` already sent under the same `requester_request_id`.
+async fn find_replayed_loan_request(
+    db: &DatabaseConnection,
+    sender_peer: &peer::Model,
+    msg: &ClearMessage,
+) -> Option<crate::models::p2p_request::Model> {
+    use crate::models::p2p_request;
+
+    let requester_request_id = msg
+        .payload
+        .get("requester_request_id")
+        .and_then(|v| v.as_str())?;
+    p2p_request::Entity::find()
+        .filter(p2p_request::Column::FromPeerId.eq(sender_peer.id))
+        .filter(p2p_request::Column::RequesterRequestId.eq(requester_request_id))
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+}
+
 /// Relay variant: saves loan request and returns JSON payload for deposit.
 pub async fn handle_loan_request_for_relay(
     db: &DatabaseConnection,
@@ -557,6 +704,7 @@ async fn handle_loan_confirmation(
         .unwrap_or("Unknown");
 
     let lender_request_id = msg.payload.get("request_id").and_then(|v| v.as_str());
+    let terms = super::peer::lending_terms_from_payload(&msg.payload);
 
     let requester_request_id = msg
         .payload
@@ -578,11 +726,15 @@ async fn handle_loan_confirmation(
         requester_request_id
     );
 
-    // Guard: verify a matching pending outgoing request exists.
+    // Guard: verify a matching open outgoing request exists (pending, or
+    // waiting for a copy on the lender's side).
     // This prevents stale relay messages from creating orphan borrowed copies.
     let has_matching_request = if let Some(rr_id) = requester_request_id {
         p2p_outgoing_request::Entity::find_by_id(rr_id)
-            .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+            .filter(
+                p2p_outgoing_request::Column::Status
+                    .is_in(crate::api::peer::AWAITING_LOAN_STATUSES),
+            )
             .one(db)
             .await
             .ok()
@@ -594,7 +746,10 @@ async fn handle_loan_confirmation(
         if !isbn_filter.is_empty() {
             p2p_outgoing_request::Entity::find()
                 .filter(p2p_outgoing_request::Column::BookIsbn.eq(isbn_filter))
-                .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+                .filter(
+                    p2p_outgoing_request::Column::Status
+                        .is_in(crate::api::peer::AWAITING_LOAN_STATUSES),
+                )
                 .one(db)
                 .await
                 .ok()
@@ -636,6 +791,7 @@ async fn handle_loan_confirmation(
         // id at the lender, so a second synced device can notify them on return.
         lender_library_uuid: sender_peer.library_uuid.as_deref(),
         lender_request_id,
+        lending_terms: terms.as_ref(),
     };
 
     let result = match super::peer::create_borrowed_copy(db, &params).await {
@@ -745,6 +901,7 @@ async fn handle_loan_offer(
     }
 
     let lender_request_id = msg.payload.get("request_id").and_then(|v| v.as_str());
+    let terms = super::peer::lending_terms_from_payload(&msg.payload);
 
     tracing::info!(
         "E2EE: Loan offer for '{}' from {} (request_id={:?})",
@@ -765,6 +922,7 @@ async fn handle_loan_offer(
         // ADR-049: same stable-identity capture as the confirmation path.
         lender_library_uuid: sender_peer.library_uuid.as_deref(),
         lender_request_id,
+        lending_terms: terms.as_ref(),
     };
 
     let result = match super::peer::create_borrowed_copy(db, &params).await {
@@ -782,8 +940,11 @@ async fn handle_loan_offer(
             let outgoing = p2p_outgoing_request::ActiveModel {
                 id: Set(outgoing_id),
                 to_peer_id: Set(sender_peer.id),
-                book_isbn: Set(isbn.unwrap_or_default().to_string()),
-                book_title: Set(title.to_string()),
+                book_isbn: Set(sanitize::line(
+                    isbn.unwrap_or_default(),
+                    sanitize::MAX_CODE_CHARS,
+                )),
+                book_title: Set(sanitize::line(title, sanitize::MAX_TITLE_CHARS)),
                 status: Set("accepted".to_string()),
                 lender_request_id: Set(Some(lender_req_id.to_string())),
                 book_id: Set(Some(result.book_id.clone())),
@@ -902,7 +1063,7 @@ pub async fn handle_search_request(
         .unwrap_or("");
 
     let books = book::Entity::find()
-        .filter(book::Column::Private.eq(false))
+        .filter(crate::models::Book::shared_condition(db).await)
         .filter(
             Condition::any()
                 .add(book::Column::Title.contains(query))
@@ -1011,11 +1172,12 @@ async fn resolve_reclaimed_book_by_lender(
 /// - Lender → Borrower (accepted/rejected): updates `p2p_outgoing_request`
 /// - Borrower → Lender (returned): updates `p2p_request` + loan + copy
 async fn handle_status_update(
-    db: &DatabaseConnection,
+    state: &AppState,
     msg: &ClearMessage,
     sender_peer: &peer::Model,
 ) -> axum::response::Response {
-    use crate::models::{contact, copy, loan, p2p_outgoing_request, p2p_request, peer};
+    use crate::models::{p2p_outgoing_request, p2p_request};
+    let db = state.db();
 
     let loan_id = msg
         .payload
@@ -1065,8 +1227,15 @@ async fn handle_status_update(
         let book_isbn = req.book_isbn.clone();
         let book_title = req.book_title.clone();
         let book_id = req.book_id.clone();
+        let previous_status = req.status.clone();
+        // The lender's acknowledgment of our return closes the request.
+        let stored_status = if status == "return_confirmed" {
+            "returned"
+        } else {
+            status
+        };
         let mut active: p2p_outgoing_request::ActiveModel = req.into();
-        active.status = Set(status.to_string());
+        active.status = Set(stored_status.to_string());
         active.updated_at = Set(chrono::Utc::now().to_rfc3339());
         match active.update(db).await {
             Ok(_) => {
@@ -1116,6 +1285,28 @@ async fn handle_status_update(
             .await;
         }
 
+        if status == "return_confirmed" {
+            crate::api::peer::confirm_returned_copy(
+                db,
+                sender_peer,
+                book_id.as_deref(),
+                &book_isbn,
+                &book_title,
+                loan_id,
+            )
+            .await;
+        }
+
+        crate::api::peer::notify_hold_status(
+            db,
+            &previous_status,
+            status,
+            &book_title,
+            &sender_peer.name,
+            loan_id,
+        )
+        .await;
+
         return (StatusCode::OK, Json(json!({ "message": "Status updated" }))).into_response();
     }
 
@@ -1140,85 +1331,24 @@ async fn handle_status_update(
                     .into_response();
             }
 
-            // Process return logic (same as update_request_status for "returned")
-            if status == "returned" && req.status == "accepted" {
-                // Find peer → contact → book → loan → mark returned + copy available
-                if let Ok(Some(the_peer)) = peer::Entity::find_by_id(req.from_peer_id).one(db).await
-                {
-                    let the_contact = contact::Entity::find()
-                        .filter(contact::Column::Name.eq(&the_peer.name))
-                        .filter(contact::Column::Type.eq("Library"))
-                        .one(db)
-                        .await
-                        .unwrap_or(None);
-
-                    if let Some(the_contact) = the_contact {
-                        let book = crate::models::book::Entity::find()
-                            .filter(crate::models::book::Column::Isbn.eq(&req.book_isbn))
-                            .one(db)
-                            .await
-                            .unwrap_or(None);
-
-                        if let Some(book) = book {
-                            let copies = copy::Entity::find()
-                                .filter(copy::Column::BookId.eq(book.id))
-                                .all(db)
-                                .await
-                                .unwrap_or_default();
-
-                            let copy_ids: Vec<String> =
-                                copies.iter().map(|c| c.id.clone()).collect();
-
-                            let active_loan = loan::Entity::find()
-                                .filter(loan::Column::ContactId.eq(the_contact.id))
-                                .filter(loan::Column::Status.eq("active"))
-                                .filter(loan::Column::CopyId.is_in(copy_ids))
-                                .one(db)
-                                .await
-                                .unwrap_or(None);
-
-                            if let Some(l) = active_loan {
-                                let copy_id = l.copy_id.clone();
-                                let mut active_loan: loan::ActiveModel = l.into();
-                                active_loan.status = Set("returned".to_string());
-                                active_loan.return_date =
-                                    Set(Some(chrono::Utc::now().to_rfc3339()));
-                                active_loan.updated_at = Set(chrono::Utc::now().to_rfc3339());
-                                let _ = active_loan.update(db).await;
-
-                                if let Some(the_copy) = copy::Entity::find_by_id(copy_id)
-                                    .one(db)
-                                    .await
-                                    .ok()
-                                    .flatten()
-                                {
-                                    let mut active_copy: copy::ActiveModel = the_copy.into();
-                                    active_copy.status = Set("available".to_string());
-                                    let _ = active_copy.update(db).await;
-                                }
-
-                                tracing::info!(
-                                    "E2EE: Processed return for request {} — loan + copy updated",
-                                    loan_id
-                                );
-
-                                // Emit book_returned notification
-                                crate::services::notification_service::emit(
-                                    db,
-                                    crate::domain::CreateNotification {
-                                        event_type:
-                                            crate::domain::NotificationEventType::BookReturned,
-                                        title: book.title.clone(),
-                                        body: Some(the_peer.name.clone()),
-                                        ref_type: Some("loan".to_string()),
-                                        ref_id: Some(loan_id.to_string()),
-                                    },
-                                )
-                                .await;
-                            }
-                        }
-                    }
+            // Process return logic (same as update_request_status for "returned"),
+            // then acknowledge it so the borrower can drop its archived copy. A
+            // replayed return of a loan already closed is acknowledged again.
+            if status == "returned" && (req.status == "accepted" || req.status == "returned") {
+                if req.status == "accepted" {
+                    crate::api::peer::record_peer_return(db, &req).await;
                 }
+                let (state, peer, req) = (state.clone(), sender_peer.clone(), req.clone());
+                tokio::spawn(async move {
+                    crate::api::peer::send_request_status_update(
+                        &state,
+                        &peer,
+                        &req,
+                        "return_confirmed",
+                        json!({}),
+                    )
+                    .await;
+                });
             }
 
             // Update the incoming request status
@@ -1228,6 +1358,9 @@ async fn handle_status_update(
             match active.update(db).await {
                 Ok(_) => {
                     tracing::info!("E2EE: Updated incoming request {} to '{}'", loan_id, status);
+                    if status == "returned" {
+                        crate::services::p2p_holds::spawn_serve(state);
+                    }
                     (StatusCode::OK, Json(json!({ "message": "Status updated" }))).into_response()
                 }
                 Err(e) => (
@@ -1527,7 +1660,7 @@ pub async fn handle_library_page_request(
         .min(50) as usize;
 
     let mut query = book::Entity::find()
-        .filter(book::Column::Private.eq(false))
+        .filter(crate::models::Book::shared_condition(db).await)
         .order_by_asc(book::Column::Id);
     if let Some(c) = cursor {
         query = query.filter(book::Column::Id.gt(c));
@@ -1592,7 +1725,7 @@ pub async fn handle_library_browse_request(
 
     let query = book::Entity::find()
         .filter(book::Column::Owned.eq(true))
-        .filter(book::Column::Private.eq(false))
+        .filter(crate::models::Book::shared_condition(db).await)
         .order_by_asc(book::Column::ShelfPosition);
 
     let paginator = query.paginate(db, limit);
@@ -1634,7 +1767,7 @@ pub async fn handle_library_search_via_relay(
         .min(50) as usize;
 
     let books = book::Entity::find()
-        .filter(book::Column::Private.eq(false))
+        .filter(crate::models::Book::shared_condition(db).await)
         .filter(
             Condition::any()
                 .add(book::Column::Title.contains(query))
@@ -1724,15 +1857,30 @@ fn seal_response(
 pub async fn handle_request_status_query(
     db: &DatabaseConnection,
     msg: &ClearMessage,
+    sender_peer: &peer::Model,
 ) -> serde_json::Value {
-    use crate::models::p2p_request;
-
     let requester_request_id = msg
         .payload
         .get("requester_request_id")
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
+    request_status_report(db, requester_request_id, sender_peer.id).await
+}
+
+/// Current status of the request `requester_peer_id` made under its own id
+/// `requester_request_id`, with the loan details once accepted. Shared by the
+/// encrypted query and the plaintext `/api/peers/requests/status-query`.
+///
+/// Scoped on the requester: a peer asking about someone else's request id is
+/// told `not_found`, exactly as if it did not exist.
+pub async fn request_status_report(
+    db: &DatabaseConnection,
+    requester_request_id: &str,
+    requester_peer_id: i32,
+) -> serde_json::Value {
+    use crate::models::p2p_request;
+
     if requester_request_id.is_empty() {
         return json!({ "error": "Missing requester_request_id" });
     }
@@ -1740,6 +1888,7 @@ pub async fn handle_request_status_query(
     // Find the request by the borrower's outgoing ID
     let request = p2p_request::Entity::find()
         .filter(p2p_request::Column::RequesterRequestId.eq(requester_request_id))
+        .filter(p2p_request::Column::FromPeerId.eq(requester_peer_id))
         .one(db)
         .await
         .ok()
@@ -1752,6 +1901,10 @@ pub async fn handle_request_status_query(
                 "status": req.status,
             });
 
+            if req.status == "ready" {
+                response["hold_expires_at"] = json!(req.hold_expires_at);
+            }
+
             // If accepted, include loan details so borrower can create the borrowed copy
             if req.status == "accepted" {
                 // Get lender name and due date from the loan
@@ -2416,8 +2569,12 @@ mod reclaim_tests {
             .await
             .expect("find")
             .expect("intruder");
-        let response =
-            handle_status_update(&db, &status_update("loan-1", "returned"), &intruder_peer).await;
+        let response = handle_status_update(
+            &AppState::new(db.clone()),
+            &status_update("loan-1", "returned"),
+            &intruder_peer,
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
         assert_eq!(
@@ -2447,8 +2604,12 @@ mod reclaim_tests {
             .await
             .expect("find")
             .expect("lender");
-        let response =
-            handle_status_update(&db, &status_update("loan-1", "returned"), &lender_peer).await;
+        let response = handle_status_update(
+            &AppState::new(db.clone()),
+            &status_update("loan-1", "returned"),
+            &lender_peer,
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(count_copies(&db, &book_id).await, 0, "borrowed copy purged");
@@ -2477,6 +2638,7 @@ mod reclaim_tests {
             created_at: Set(now.clone()),
             updated_at: Set(now),
             requester_request_id: Set(None),
+            ..Default::default()
         }
         .insert(&db)
         .await
@@ -2487,8 +2649,12 @@ mod reclaim_tests {
             .await
             .expect("find")
             .expect("intruder");
-        let response =
-            handle_status_update(&db, &status_update("req-1", "returned"), &intruder_peer).await;
+        let response = handle_status_update(
+            &AppState::new(db.clone()),
+            &status_update("req-1", "returned"),
+            &intruder_peer,
+        )
+        .await;
 
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
         let req = crate::models::p2p_request::Entity::find_by_id("req-1")
@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
@@ -9,11 +9,14 @@ use sea_orm::{
     sea_query::OnConflict,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
+use crate::domain::{LoanSettings, LoanSettingsRepository};
+use crate::infrastructure::repositories::loan_settings_repository::SeaOrmLoanSettingsRepository;
 use crate::models::{
     author, book, book_authors, book_tags, collection, collection_book, contact, copy,
     gamification_achievements, gamification_config, gamification_progress, gamification_streaks,
-    library_config, loan, peer, sale, tag,
+    installation_profile, library_config, loan, peer, sale, tag,
 };
 
 // --- Export ---
@@ -39,52 +42,68 @@ pub struct BackupData {
     pub gamification_progress: Vec<gamification_progress::Model>,
     pub gamification_achievements: Vec<gamification_achievements::Model>,
     pub gamification_streaks: Vec<gamification_streaks::Model>,
+    /// Profile, modules and theme. `api_keys` is left out: keys are secrets
+    /// that stay on the install.
+    pub installation_profile: Option<installation_profile::Model>,
+    pub loan_settings: Option<LoanSettings>,
 }
 
-pub async fn export_data(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+/// Snapshot of the whole library in the JSON backup format served by
+/// `GET /api/export` (also written by the `bibliogenius export` command).
+/// Tables that fail to load come out empty.
+pub async fn collect_backup_data(db: &DatabaseConnection) -> BackupData {
     let config = library_config::Entity::find_by_id(1)
-        .one(&db)
+        .one(db)
         .await
         .unwrap_or(None);
-    let books = book::Entity::find().all(&db).await.unwrap_or_default();
-    let authors = author::Entity::find().all(&db).await.unwrap_or_default();
+    let books = book::Entity::find().all(db).await.unwrap_or_default();
+    let authors = author::Entity::find().all(db).await.unwrap_or_default();
     let book_authors = book_authors::Entity::find()
-        .all(&db)
-        .await
-        .unwrap_or_default();
-    let copies = copy::Entity::find().all(&db).await.unwrap_or_default();
-    let contacts = contact::Entity::find().all(&db).await.unwrap_or_default();
-    let loans = loan::Entity::find().all(&db).await.unwrap_or_default();
-    let sales = sale::Entity::find().all(&db).await.unwrap_or_default();
-    let tags = tag::Entity::find().all(&db).await.unwrap_or_default();
-    let book_tags = book_tags::Entity::find().all(&db).await.unwrap_or_default();
-    let collections = collection::Entity::find()
-        .all(&db)
+        .all(db)
         .await
         .unwrap_or_default();
+    let copies = copy::Entity::find().all(db).await.unwrap_or_default();
+    let contacts = contact::Entity::find().all(db).await.unwrap_or_default();
+    let loans = loan::Entity::find().all(db).await.unwrap_or_default();
+    let sales = sale::Entity::find().all(db).await.unwrap_or_default();
+    let tags = tag::Entity::find().all(db).await.unwrap_or_default();
+    let book_tags = book_tags::Entity::find().all(db).await.unwrap_or_default();
+    let collections = collection::Entity::find().all(db).await.unwrap_or_default();
     let collection_books = collection_book::Entity::find()
-        .all(&db)
+        .all(db)
         .await
         .unwrap_or_default();
-    let peers = peer::Entity::find().all(&db).await.unwrap_or_default();
+    let peers = peer::Entity::find().all(db).await.unwrap_or_default();
+    let profile = installation_profile::Entity::find()
+        .one(db)
+        .await
+        .unwrap_or(None)
+        .map(|p| installation_profile::Model {
+            api_keys: None,
+            ..p
+        });
+    let loan_settings = SeaOrmLoanSettingsRepository::new(db.clone())
+        .get_settings()
+        .await
+        .ok();
     let gam_config = gamification_config::Entity::find()
-        .one(&db)
+        .one(db)
         .await
         .unwrap_or(None);
     let gam_progress = gamification_progress::Entity::find()
-        .all(&db)
+        .all(db)
         .await
         .unwrap_or_default();
     let gam_achievements = gamification_achievements::Entity::find()
-        .all(&db)
+        .all(db)
         .await
         .unwrap_or_default();
     let gam_streaks = gamification_streaks::Entity::find()
-        .all(&db)
+        .all(db)
         .await
         .unwrap_or_default();
 
-    let backup = BackupData {
+    BackupData {
         version: "2.0".to_string(),
         exported_at: chrono::Utc::now().to_rfc3339(),
         library_config: config,
@@ -104,7 +123,82 @@ pub async fn export_data(State(db): State<DatabaseConnection>) -> impl IntoRespo
         gamification_progress: gam_progress,
         gamification_achievements: gam_achievements,
         gamification_streaks: gam_streaks,
-    };
+        installation_profile: profile,
+        loan_settings,
+    }
+}
+
+impl BackupData {
+    /// Drop what the owner keeps to themselves: private books, books with a
+    /// private tag (see `Book::tag_restricted_ids`), the private tags, and
+    /// every row hanging off a dropped book (copies and their loans and sales,
+    /// author and tag links, collection entries).
+    pub async fn exclude_private(&mut self, db: &DatabaseConnection) -> Result<(), DbErr> {
+        let mut hidden = crate::models::Book::tag_restricted_ids(db).await?;
+        hidden.extend(
+            self.books
+                .iter()
+                .filter(|b| b.private)
+                .map(|b| b.id.clone()),
+        );
+        let private_tags: HashSet<String> = tag::restricting_tags(db)
+            .await?
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+
+        self.books.retain(|b| !hidden.contains(&b.id));
+        self.book_authors.retain(|ba| !hidden.contains(&ba.book_id));
+        self.book_tags
+            .retain(|bt| !hidden.contains(&bt.book_id) && !private_tags.contains(&bt.tag_id));
+        self.collection_books
+            .retain(|cb| !hidden.contains(&cb.book_id));
+        self.tags.retain(|t| !private_tags.contains(&t.id));
+
+        let dropped_copies: HashSet<String> = self
+            .copies
+            .iter()
+            .filter(|c| hidden.contains(&c.book_id))
+            .map(|c| c.id.clone())
+            .collect();
+        self.copies.retain(|c| !dropped_copies.contains(&c.id));
+        self.loans.retain(|l| !dropped_copies.contains(&l.copy_id));
+        self.sales.retain(|s| !dropped_copies.contains(&s.copy_id));
+        Ok(())
+    }
+}
+
+/// Query parameters of `GET /api/export`
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ExportOptions {
+    /// Also export private books and books with a private tag
+    #[serde(default)]
+    pub include_private: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/export",
+    tag = "data",
+    params(ExportOptions),
+    responses(
+        (status = 200, description = "JSON backup of the library, as an attachment; private books are left out unless `include_private=true`")
+    )
+)]
+pub async fn export_data(
+    State(db): State<DatabaseConnection>,
+    Query(options): Query<ExportOptions>,
+) -> impl IntoResponse {
+    let mut backup = collect_backup_data(&db).await;
+    if !options.include_private
+        && let Err(e) = backup.exclude_private(&db).await
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response();
+    }
 
     let filename = format!(
         "bibliogenius_backup_{}.json",
@@ -120,7 +214,7 @@ pub async fn export_data(State(db): State<DatabaseConnection>) -> impl IntoRespo
             .unwrap(),
     );
 
-    (StatusCode::OK, headers, Json(backup))
+    (StatusCode::OK, headers, Json(backup)).into_response()
 }
 
 // --- Import ---
@@ -160,6 +254,16 @@ pub struct ImportBook {
     pub private: bool,
     pub page_count: Option<i32>,
     pub loan_duration_days: Option<i32>,
+    #[serde(default)]
+    pub edition: Option<String>,
+    #[serde(default)]
+    pub physical_format: Option<String>,
+    #[serde(default)]
+    pub dimensions: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub digital_source_url: Option<String>,
     // Ignored fields from simplified format
     #[serde(default)]
     pub author: Option<String>,
@@ -190,6 +294,10 @@ pub struct ImportContact {
     pub is_active: bool,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    pub member_number: Option<String>,
+    pub member_since: Option<String>,
+    pub membership_expires_at: Option<String>,
+    pub membership_fee_paid: Option<f64>,
 }
 
 /// Flexible tag type that accepts both formats.
@@ -200,6 +308,8 @@ pub struct ImportTag {
     pub parent_id: Option<String>,
     #[serde(default)]
     pub path: String,
+    #[serde(default)]
+    pub private: bool,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -226,9 +336,33 @@ pub struct ImportBackupData {
     pub gamification_progress: Option<Vec<gamification_progress::Model>>,
     pub gamification_achievements: Option<Vec<gamification_achievements::Model>>,
     pub gamification_streaks: Option<Vec<gamification_streaks::Model>>,
+    pub installation_profile: Option<installation_profile::Model>,
+    pub loan_settings: Option<LoanSettings>,
 }
 
-#[derive(Serialize)]
+/// What an import does with the settings carried by a backup (library
+/// config, installation profile, gamification config, loan settings).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsMerge {
+    /// The backup's settings replace the local ones.
+    #[default]
+    Overwrite,
+    /// Only settings missing on this install are taken from the backup.
+    KeepLocal,
+    /// The backup's settings are ignored.
+    Skip,
+}
+
+/// Query parameters of `POST /api/import` and `POST /api/import-upsert`
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ImportOptions {
+    /// `overwrite` (default), `keep_local` or `skip`
+    #[serde(default)]
+    pub settings: SettingsMerge,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
 pub struct ImportResult {
     pub success: bool,
     pub books_imported: usize,
@@ -241,17 +375,21 @@ pub struct ImportResult {
     pub peers_imported: usize,
     pub sales_imported: usize,
     pub gamification_imported: usize,
+    pub settings_imported: usize,
     pub message: String,
 }
 
 /// Wipe all catalog tables (books, authors, tags, copies, contacts, loans,
-/// sales, collections, peers, gamification, library_config) in a FK-safe order.
+/// sales, collections, peers, gamification progress, achievements and streaks)
+/// in a FK-safe order.
 ///
+/// Settings (`library_config`, `gamification_config`, `installation_profile`,
+/// `loan_settings`) are kept: `import_settings` merges them afterwards.
 /// Out of scope: install-/identity-/transient-state tables that are NOT in the
 /// export, e.g. `users`, `libraries`, `crypto_keys`, `operation_log`,
-/// `installation_profile`, `linked_devices`, `relay_*`, `notifications`,
-/// `loan_settings`, `hub_directory_config`, `library_view_stats`. These belong
-/// to the install, not the catalog content the user backed up.
+/// `linked_devices`, `relay_*`, `notifications`, `hub_directory_config`,
+/// `library_view_stats`. These belong to the install, not the catalog content
+/// the user backed up.
 async fn wipe_catalog<C: ConnectionTrait>(conn: &C) -> Result<(), DbErr> {
     collection_book::Entity::delete_many().exec(conn).await?;
     collection::Entity::delete_many().exec(conn).await?;
@@ -278,10 +416,6 @@ async fn wipe_catalog<C: ConnectionTrait>(conn: &C) -> Result<(), DbErr> {
     gamification_progress::Entity::delete_many()
         .exec(conn)
         .await?;
-    gamification_config::Entity::delete_many()
-        .exec(conn)
-        .await?;
-    library_config::Entity::delete_many().exec(conn).await?;
     Ok(())
 }
 
@@ -300,13 +434,25 @@ fn import_error(message: String) -> (StatusCode, Json<ImportResult>) {
             peers_imported: 0,
             sales_imported: 0,
             gamification_imported: 0,
+            settings_imported: 0,
             message,
         }),
     )
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    tag = "data",
+    params(ImportOptions),
+    request_body(content = Value, description = "A backup produced by `GET /api/export`; replaces the catalogue"),
+    responses(
+        (status = 200, description = "Import summary", body = ImportResult)
+    )
+)]
 pub async fn import_data(
     State(db): State<DatabaseConnection>,
+    Query(options): Query<ImportOptions>,
     Json(backup): Json<ImportBackupData>,
 ) -> (StatusCode, Json<ImportResult>) {
     use sea_orm::{ActiveModelTrait, IntoActiveModel};
@@ -377,6 +523,11 @@ pub async fn import_data(
                 private: Set(b.private),
                 page_count: Set(b.page_count),
                 loan_duration_days: Set(b.loan_duration_days),
+                edition: Set(b.edition),
+                physical_format: Set(b.physical_format),
+                dimensions: Set(b.dimensions),
+                archived: Set(b.archived),
+                digital_source_url: Set(b.digital_source_url),
             };
             if active.insert(&txn).await.is_ok() {
                 books_count += 1;
@@ -402,6 +553,7 @@ pub async fn import_data(
                 name: Set(t.name),
                 parent_id: Set(t.parent_id),
                 path: Set(t.path),
+                private: Set(t.private),
                 created_at: Set(t.created_at.unwrap_or_else(|| now.clone())),
                 updated_at: Set(t.updated_at.unwrap_or_else(|| now.clone())),
             };
@@ -444,6 +596,10 @@ pub async fn import_data(
                 is_active: Set(c.is_active),
                 created_at: Set(c.created_at.unwrap_or_else(|| now.clone())),
                 updated_at: Set(c.updated_at.unwrap_or_else(|| now.clone())),
+                member_number: Set(c.member_number),
+                member_since: Set(c.member_since),
+                membership_expires_at: Set(c.membership_expires_at),
+                membership_fee_paid: Set(c.membership_fee_paid),
             };
             if active.insert(&txn).await.is_ok() {
                 contacts_count += 1;
@@ -534,6 +690,9 @@ pub async fn import_data(
                 catalog_hash: Set(None),
                 last_catalog_sync: Set(None),
                 last_delta_cursor: Set(None),
+                loan_duration_days: Set(p.loan_duration_days),
+                onion_url: Set(p.onion_url),
+                trust_announcements: Set(p.trust_announcements),
                 created_at: Set(p.created_at),
                 updated_at: Set(now.clone()),
             };
@@ -543,14 +702,7 @@ pub async fn import_data(
         }
     }
 
-    // 13. Import gamification
-    if let Some(gc) = backup.gamification_config {
-        let active = gc.into_active_model();
-        if active.insert(&txn).await.is_ok() {
-            gamification_count += 1;
-        }
-    }
-
+    // 13. Import gamification (the config is a setting, see step 14)
     if let Some(gps) = backup.gamification_progress {
         for gp in gps {
             let active = gp.into_active_model();
@@ -578,16 +730,21 @@ pub async fn import_data(
         }
     }
 
-    // 14. Import library config (singleton, wiped above)
-    if let Some(lc) = backup.library_config {
-        let active = lc.into_active_model();
-        let _ = active.insert(&txn).await;
-    }
-
     if let Err(e) = txn.commit().await {
         return import_error(format!("Failed to commit restore transaction: {}", e));
     }
 
+    // 14. Settings (kept by the wipe, merged as asked)
+    let settings_count = import_settings(
+        &db,
+        backup.library_config,
+        backup.gamification_config,
+        backup.installation_profile,
+        backup.loan_settings,
+        options.settings,
+    )
+    .await;
+
     let total = books_count
         + copies_count
         + contacts_count
@@ -597,7 +754,8 @@ pub async fn import_data(
         + collections_count
         + peers_count
         + sales_count
-        + gamification_count;
+        + gamification_count
+        + settings_count;
 
     let result = ImportResult {
         success: true,
@@ -611,6 +769,7 @@ pub async fn import_data(
         peers_imported: peers_count,
         sales_imported: sales_count,
         gamification_imported: gamification_count,
+        settings_imported: settings_count,
         message: format!("Successfully imported {} items", total),
     };
 
@@ -619,24 +778,40 @@ pub async fn import_data(
 
 // --- Upsert Import (for auto-backup sync) ---
 
+#[utoipa::path(
+    post,
+    path = "/api/import-upsert",
+    tag = "data",
+    params(ImportOptions),
+    request_body(content = Value, description = "A backup produced by `GET /api/export`; merged by id"),
+    responses(
+        (status = 200, description = "Import summary", body = ImportResult)
+    )
+)]
 pub async fn import_data_upsert(
     State(db): State<DatabaseConnection>,
+    Query(options): Query<ImportOptions>,
     Json(backup): Json<ImportBackupData>,
 ) -> impl IntoResponse {
-    let result = run_import_upsert(&db, backup).await;
+    let result = run_import_upsert(&db, backup, options.settings).await;
     (StatusCode::OK, Json(result))
 }
 
 /// Core upsert logic, callable directly without going through axum.
 ///
 /// Returns `ImportResult`. Used by the HTTP handler `import_data_upsert`
-/// and by the local-backup Merge restore path (ADR-037 §5).
+/// and by the local-backup Merge restore path (ADR-037 §5). `settings`
+/// decides what happens to the backup's settings, see `SettingsMerge`.
 ///
 /// Skips peers (local network config), `crypto_keys`, `operation_log`,
 /// `notification`, `relay_config`, `linked_device`, and any other
 /// install-/identity-specific table; these are not present in
 /// `ImportBackupData` to begin with.
-pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData) -> ImportResult {
+pub async fn run_import_upsert(
+    db: &DatabaseConnection,
+    backup: ImportBackupData,
+    settings: SettingsMerge,
+) -> ImportResult {
     use sea_orm::IntoActiveModel;
 
     let now = chrono::Utc::now().to_rfc3339();
@@ -698,6 +873,11 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
                 private: Set(b.private),
                 page_count: Set(b.page_count),
                 loan_duration_days: Set(b.loan_duration_days),
+                edition: Set(b.edition),
+                physical_format: Set(b.physical_format),
+                dimensions: Set(b.dimensions),
+                archived: Set(b.archived),
+                digital_source_url: Set(b.digital_source_url),
             };
             let res = book::Entity::insert(active)
                 .on_conflict(
@@ -761,6 +941,7 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
                 name: Set(t.name),
                 parent_id: Set(t.parent_id),
                 path: Set(t.path),
+                private: Set(t.private),
                 created_at: Set(t.created_at.unwrap_or_else(|| now.clone())),
                 updated_at: Set(t.updated_at.unwrap_or_else(|| now.clone())),
             };
@@ -771,6 +952,7 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
                             tag::Column::Name,
                             tag::Column::ParentId,
                             tag::Column::Path,
+                            tag::Column::Private,
                         ])
                         .to_owned(),
                 )
@@ -820,6 +1002,10 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
                 is_active: Set(c.is_active),
                 created_at: Set(c.created_at.unwrap_or_else(|| now.clone())),
                 updated_at: Set(c.updated_at.unwrap_or_else(|| now.clone())),
+                member_number: Set(c.member_number),
+                member_since: Set(c.member_since),
+                membership_expires_at: Set(c.membership_expires_at),
+                membership_fee_paid: Set(c.membership_fee_paid),
             };
             let res = contact::Entity::insert(active)
                 .on_conflict(
@@ -842,6 +1028,10 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
                             contact::Column::LibraryOwnerId,
                             contact::Column::IsActive,
                             contact::Column::UpdatedAt,
+                            contact::Column::MemberNumber,
+                            contact::Column::MemberSince,
+                            contact::Column::MembershipExpiresAt,
+                            contact::Column::MembershipFeePaid,
                         ])
                         .to_owned(),
                 )
@@ -974,32 +1164,7 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
 
     // 12. Skip peers (local network config, not relevant for backup)
 
-    // 13. Upsert gamification
-    if let Some(gc) = backup.gamification_config {
-        let res = gamification_config::Entity::insert(gc.into_active_model())
-            .on_conflict(
-                OnConflict::column(gamification_config::Column::Id)
-                    .update_columns([
-                        gamification_config::Column::UserId,
-                        gamification_config::Column::Preset,
-                        gamification_config::Column::StreaksEnabled,
-                        gamification_config::Column::AchievementsEnabled,
-                        gamification_config::Column::AchievementsStyle,
-                        gamification_config::Column::ReadingGoalsEnabled,
-                        gamification_config::Column::ReadingGoalYearly,
-                        gamification_config::Column::TracksEnabled,
-                        gamification_config::Column::NotificationsEnabled,
-                        gamification_config::Column::UpdatedAt,
-                    ])
-                    .to_owned(),
-            )
-            .exec(db)
-            .await;
-        if res.is_ok() {
-            gamification_count += 1;
-        }
-    }
-
+    // 13. Upsert gamification (the config is a setting, see step 14)
     if let Some(gps) = backup.gamification_progress {
         for gp in gps {
             let res = gamification_progress::Entity::insert(gp.into_active_model())
@@ -1063,26 +1228,16 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
         }
     }
 
-    // 14. Upsert library config
-    if let Some(lc) = backup.library_config {
-        let _ = library_config::Entity::insert(lc.into_active_model())
-            .on_conflict(
-                OnConflict::column(library_config::Column::Id)
-                    .update_columns([
-                        library_config::Column::Name,
-                        library_config::Column::Description,
-                        library_config::Column::Tags,
-                        library_config::Column::Latitude,
-                        library_config::Column::Longitude,
-                        library_config::Column::ShareLocation,
-                        library_config::Column::ShowBorrowedBooks,
-                        library_config::Column::UpdatedAt,
-                    ])
-                    .to_owned(),
-            )
-            .exec(db)
-            .await;
-    }
+    // 14. Settings
+    let settings_count = import_settings(
+        db,
+        backup.library_config,
+        backup.gamification_config,
+        backup.installation_profile,
+        backup.loan_settings,
+        settings,
+    )
+    .await;
 
     let total = books_count
         + copies_count
@@ -1092,7 +1247,8 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
         + authors_count
         + collections_count
         + sales_count
-        + gamification_count;
+        + gamification_count
+        + settings_count;
 
     ImportResult {
         success: true,
@@ -1106,10 +1262,132 @@ pub async fn run_import_upsert(db: &DatabaseConnection, backup: ImportBackupData
         peers_imported: 0,
         sales_imported: sales_count,
         gamification_imported: gamification_count,
+        settings_imported: settings_count,
         message: format!("Successfully upserted {} items", total),
     }
 }
 
+/// Merge the settings carried by a backup into this install, as `mode` asks.
+/// Returns how many settings were written. The installation profile never
+/// touches the local `api_keys`.
+async fn import_settings(
+    db: &DatabaseConnection,
+    library_config: Option<library_config::Model>,
+    gamification_config: Option<gamification_config::Model>,
+    installation_profile: Option<installation_profile::Model>,
+    loan_settings: Option<LoanSettings>,
+    mode: SettingsMerge,
+) -> usize {
+    use sea_orm::IntoActiveModel;
+
+    if mode == SettingsMerge::Skip {
+        return 0;
+    }
+    let mut count = 0;
+
+    if let Some(lc) = library_config {
+        let res = library_config::Entity::insert(lc.into_active_model())
+            .on_conflict(settings_conflict(
+                mode,
+                library_config::Column::Id,
+                vec![
+                    library_config::Column::Name,
+                    library_config::Column::Description,
+                    library_config::Column::Tags,
+                    library_config::Column::Latitude,
+                    library_config::Column::Longitude,
+                    library_config::Column::ShareLocation,
+                    library_config::Column::ShowBorrowedBooks,
+                    library_config::Column::Currency,
+                    library_config::Column::Timezone,
+                    library_config::Column::UpdatedAt,
+                ],
+            ))
+            .exec(db)
+            .await;
+        if res.is_ok() {
+            count += 1;
+        }
+    }
+
+    if let Some(gc) = gamification_config {
+        let res = gamification_config::Entity::insert(gc.into_active_model())
+            .on_conflict(settings_conflict(
+                mode,
+                gamification_config::Column::Id,
+                vec![
+                    gamification_config::Column::UserId,
+                    gamification_config::Column::Preset,
+                    gamification_config::Column::StreaksEnabled,
+                    gamification_config::Column::AchievementsEnabled,
+                    gamification_config::Column::AchievementsStyle,
+                    gamification_config::Column::ReadingGoalsEnabled,
+                    gamification_config::Column::ReadingGoalYearly,
+                    gamification_config::Column::TracksEnabled,
+                    gamification_config::Column::NotificationsEnabled,
+                    gamification_config::Column::UpdatedAt,
+                ],
+            ))
+            .exec(db)
+            .await;
+        if res.is_ok() {
+            count += 1;
+        }
+    }
+
+    if let Some(profile) = installation_profile {
+        let profile = installation_profile::Model {
+            api_keys: None,
+            ..profile
+        };
+        let res = installation_profile::Entity::insert(profile.into_active_model())
+            .on_conflict(settings_conflict(
+                mode,
+                installation_profile::Column::Id,
+                vec![
+                    installation_profile::Column::ProfileType,
+                    installation_profile::Column::EnabledModules,
+                    installation_profile::Column::Theme,
+                    installation_profile::Column::AvatarConfig,
+                    installation_profile::Column::UpdatedAt,
+                ],
+            ))
+            .exec(db)
+            .await;
+        if res.is_ok() {
+            count += 1;
+        }
+    }
+
+    // The loan settings row always exists, so only an overwrite changes it.
+    if let Some(ls) = loan_settings
+        && mode == SettingsMerge::Overwrite
+        && SeaOrmLoanSettingsRepository::new(db.clone())
+            .update_settings(ls)
+            .await
+            .is_ok()
+    {
+        count += 1;
+    }
+
+    count
+}
+
+/// Conflict clause for a settings row: overwrite `columns`, or, when keeping
+/// local settings, only insert rows missing here.
+fn settings_conflict<C: sea_orm::sea_query::IntoIden>(
+    mode: SettingsMerge,
+    id: C,
+    columns: Vec<C>,
+) -> OnConflict {
+    let mut on_conflict = OnConflict::column(id);
+    match mode {
+        SettingsMerge::Overwrite => on_conflict.update_columns(columns),
+        _ => on_conflict.do_nothing(),
+    };
+    on_conflict
+}
+
 // --- Helpers ---
 
 fn default_reading_status() -> String {
@@ -1183,6 +1461,8 @@ mod tests {
             gamification_progress: None,
             gamification_achievements: None,
             gamification_streaks: None,
+            installation_profile: None,
+            loan_settings: None,
         }
     }
 
@@ -1214,6 +1494,11 @@ mod tests {
             private: false,
             page_count: None,
             loan_duration_days: None,
+            edition: None,
+            physical_format: None,
+            dimensions: None,
+            archived: false,
+            digital_source_url: None,
             author: None,
         }
     }
@@ -1256,7 +1541,12 @@ mod tests {
             ..empty_backup()
         };
 
-        let (status, Json(result)) = import_data(State(db.clone()), Json(backup)).await;
+        let (status, Json(result)) = import_data(
+            State(db.clone()),
+            Query(ImportOptions::default()),
+            Json(backup),
+        )
+        .await;
         assert_eq!(status, StatusCode::OK);
         assert!(result.success);
         assert_eq!(result.books_imported, 1);
@@ -1301,13 +1591,21 @@ mod tests {
                 last_catalog_sync: None,
                 avatar_config: None,
                 last_delta_cursor: None,
+                loan_duration_days: None,
+                onion_url: None,
+                trust_announcements: false,
                 created_at: now.clone(),
                 updated_at: now.clone(),
             }]),
             ..empty_backup()
         };
 
-        let (status, Json(result)) = import_data(State(db.clone()), Json(backup)).await;
+        let (status, Json(result)) = import_data(
+            State(db.clone()),
+            Query(ImportOptions::default()),
+            Json(backup),
+        )
+        .await;
         assert_eq!(status, StatusCode::OK);
         assert_eq!(result.peers_imported, 1);
 
@@ -1340,7 +1638,12 @@ mod tests {
         .await
         .unwrap();
 
-        let (status, Json(result)) = import_data(State(db.clone()), Json(empty_backup())).await;
+        let (status, Json(result)) = import_data(
+            State(db.clone()),
+            Query(ImportOptions::default()),
+            Json(empty_backup()),
+        )
+        .await;
         assert_eq!(status, StatusCode::OK);
         assert!(result.success);
         assert_eq!(result.books_imported, 0);
@@ -1348,4 +1651,137 @@ mod tests {
         let books = book::Entity::find().all(&db).await.unwrap();
         assert!(books.is_empty());
     }
+
+    /// Settings survive a restore, and the caller picks whose win.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn import_data_merges_settings_as_asked() {
+        let db = setup().await;
+        let mut exported = collect_backup_data(&db).await;
+        let local_name = exported.library_config.as_ref().unwrap().name.clone();
+        let profile = exported.installation_profile.take().unwrap();
+        let backup = || ImportBackupData {
+            library_config: exported
+                .library_config
+                .clone()
+                .map(|lc| library_config::Model {
+                    name: "Restored Library".to_string(),
+                    ..lc
+                }),
+            installation_profile: Some(installation_profile::Model {
+                theme: Some("dark".to_string()),
+                ..profile.clone()
+            }),
+            loan_settings: Some(LoanSettings {
+                default_loan_duration_days: 30,
+                ..Default::default()
+            }),
+            ..empty_backup()
+        };
+
+        for mode in [SettingsMerge::Skip, SettingsMerge::KeepLocal] {
+            let (_, Json(result)) = import_data(
+                State(db.clone()),
+                Query(ImportOptions { settings: mode }),
+                Json(backup()),
+            )
+            .await;
+            assert_eq!(result.settings_imported, 0, "{mode:?}");
+            let config = library_config::Entity::find_by_id(1)
+                .one(&db)
+                .await
+                .unwrap();
+            assert_eq!(config.unwrap().name, local_name, "{mode:?}");
+        }
+
+        let (_, Json(result)) = import_data(
+            State(db.clone()),
+            Query(ImportOptions::default()),
+            Json(backup()),
+        )
+        .await;
+        assert_eq!(result.settings_imported, 3);
+        let after = collect_backup_data(&db).await;
+        assert_eq!(after.library_config.unwrap().name, "Restored Library");
+        assert_eq!(
+            after.installation_profile.unwrap().theme.as_deref(),
+            Some("dark")
+        );
+        assert_eq!(after.loan_settings.unwrap().default_loan_duration_days, 30);
+    }
+
+    #[tokio::test]
+    async fn export_leaves_out_privately_tagged_books_unless_asked() {
+        let db = setup().await;
+        let now = chrono::Utc::now().to_rfc3339();
+        let insert_book = |title: &'static str, subjects: Option<&'static str>| {
+            let db = db.clone();
+            let now = now.clone();
+            async move {
+                book::ActiveModel {
+                    title: Set(title.to_string()),
+                    subjects: Set(subjects.map(str::to_string)),
+                    owned: Set(true),
+                    created_at: Set(now.clone()),
+                    updated_at: Set(now),
+                    ..Default::default()
+                }
+                .insert(&db)
+                .await
+                .unwrap()
+            }
+        };
+        let shared = insert_book("Dune", None).await;
+        let gift = insert_book("Birthday present", Some(r#"["gifts"]"#)).await;
+        let nested = insert_book("Surprise", None).await;
+
+        let gifts = tag::ActiveModel {
+            name: Set("gifts".to_string()),
+            private: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let birthdays = tag::ActiveModel {
+            name: Set("birthdays".to_string()),
+            parent_id: Set(Some(gifts.id.clone())),
+            path: Set("gifts".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        book_tags::ActiveModel {
+            book_id: Set(nested.id.clone()),
+            tag_id: Set(birthdays.id.clone()),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        copy::ActiveModel {
+            book_id: Set(gift.id.clone()),
+            library_id: Set(1),
+            status: Set("available".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let mut backup = collect_backup_data(&db).await;
+        assert_eq!(backup.books.len(), 3, "the full snapshot has everything");
+        backup.exclude_private(&db).await.unwrap();
+        let titles: Vec<&str> = backup.books.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, vec![shared.title.as_str()]);
+        assert!(backup.copies.is_empty());
+        assert!(backup.book_tags.is_empty());
+        assert!(backup.tags.is_empty(), "nested tags are private too");
+    }
 }
@@ -0,0 +1,123 @@
+//! Fee ledger endpoints: a contact's fines and payments, recording a payment,
+//! and the report over every ledger (see `services::fines`).
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::fines;
+
+fn db_error(e: sea_orm::DbErr) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": e.to_string() })),
+    )
+        .into_response()
+}
+
+fn contact_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "Contact not found" })),
+    )
+        .into_response()
+}
+
+/// GET /api/contacts/:id/fees - A contact's fee ledger
+#[utoipa::path(
+    get,
+    path = "/api/contacts/{id}/fees",
+    tag = "loans",
+    params(("id" = String, Path, description = "Contact id")),
+    responses(
+        (status = 200, description = "Fines and payments, oldest first, with the balance owed", body = ContactFees),
+        (status = 404, description = "Contact not found")
+    )
+)]
+pub async fn get_contact_fees(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match fines::contact_fees(state.db(), &id).await {
+        Ok(Some(fees)) => Json(fees).into_response(),
+        Ok(None) => contact_not_found(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Request body for recording a payment
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct FeePaymentRequest {
+    pub amount: f64,
+    /// e.g. "cash", a receipt number
+    pub note: Option<String>,
+}
+
+/// POST /api/contacts/:id/fees/payments - Record a payment
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/fees/payments",
+    tag = "loans",
+    params(("id" = String, Path, description = "Contact id")),
+    request_body = FeePaymentRequest,
+    responses(
+        (status = 201, description = "Payment recorded; the new balance is returned"),
+        (status = 400, description = "Amount not positive, or finer than the currency's minor unit"),
+        (status = 404, description = "Contact not found")
+    )
+)]
+pub async fn record_fee_payment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<FeePaymentRequest>,
+) -> Response {
+    let db = state.db();
+    let currency = match crate::utils::money::library_currency(db).await {
+        Ok(c) => c,
+        Err(e) => return db_error(e),
+    };
+    let invalid = if req.amount > 0.0 {
+        crate::utils::money::check_amount(req.amount, currency)
+    } else {
+        Some("must be positive".to_string())
+    };
+    if let Some(reason) = invalid {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("amount {reason}") })),
+        )
+            .into_response();
+    }
+    let entry = match fines::record_payment(db, &id, req.amount, req.note).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return contact_not_found(),
+        Err(e) => return db_error(e),
+    };
+    match fines::balance(db, &id).await {
+        Ok(balance) => (
+            StatusCode::CREATED,
+            Json(json!({ "payment": entry, "balance": balance })),
+        )
+            .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// GET /api/fees/report - Fines and payments over every contact
+#[utoipa::path(
+    get,
+    path = "/api/fees/report",
+    tag = "loans",
+    responses(
+        (status = 200, description = "Totals fined, paid and outstanding, and who still owes", body = FeesReport)
+    )
+)]
+pub async fn get_fees_report(State(state): State<AppState>) -> Response {
+    match fines::report(state.db()).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => db_error(e),
+    }
+}
@@ -2,7 +2,9 @@
 // This module exposes core functionality to Flutter without HTTP layer
 //
 // ARCHITECTURE: This module provides direct database access for all native platforms.
-// Web uses WASM (future). All native platforms use FFI for local-first operation.
+// All native platforms use FFI for local-first operation. There is no web (wasm32)
+// build: tokio, the SQLite pool, axum, mDNS and native TLS all need a host OS, and
+// a browser would need its own storage backend behind the domain repository traits.
 
 use flutter_rust_bridge::frb;
 use sea_orm::{ActiveModelTrait, DatabaseConnection};
@@ -494,7 +494,8 @@ pub async fn account_sync_now_ffi() -> Result<String, String> {
         // Custom cover bytes ride their own lanes (ADR-046): cr-sqlite syncs the
         // cover_url row but not the file. The covers directory is registered in
         // `init_backend`; without it (server binary) covers are not transported.
-        let stats = if let Some(covers_dir) = covers_dir() {
+        let stats = if let Some(covers_dir) = crate::infrastructure::paths::registered_covers_dir()
+        {
             let cover_source =
                 crate::services::cover_sync::DbCoverSource::new(db.clone(), covers_dir.clone());
             let cover_sink =
@@ -49,6 +49,11 @@ impl From<FrbBook> for crate::models::Book {
             private: Some(frb_book.private),
             page_count: frb_book.page_count,
             loan_duration_days: None,
+            edition: frb_book.edition,
+            physical_format: frb_book.physical_format,
+            dimensions: frb_book.dimensions,
+            archived: None,
+            digital_source_url: None,
             added_at: frb_book.added_at,
             // FrbBook (FFI DTO) doesn't carry updated_at; the cover
             // versioning pipeline only needs it on the catalog-push side
@@ -59,6 +64,7 @@ impl From<FrbBook> for crate::models::Book {
             // never dictated by the client. Whatever Dart sent is discarded.
             is_borrowed: None,
             is_lent: None,
+            related: None,
         }
     }
 }
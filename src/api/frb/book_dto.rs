@@ -31,6 +31,11 @@ pub struct FrbBook {
     pub digital_formats: Option<Vec<String>>,
     pub private: bool, // Hidden from network peers
     pub page_count: Option<i32>,
+    /// Edition statement, binding (one of `models::book::PHYSICAL_FORMATS`)
+    /// and free-text dimensions of this edition.
+    pub edition: Option<String>,
+    pub physical_format: Option<String>,
+    pub dimensions: Option<String>,
     /// ISO 8601 timestamp of when the book was added to its owner's library
     /// (maps to `books.created_at`). Used by the "new" badge and by the
     /// "recently added" carousel.
@@ -78,6 +83,9 @@ impl From<crate::models::Book> for FrbBook {
             digital_formats: book.digital_formats,
             private: book.private.unwrap_or(false),
             page_count: book.page_count,
+            edition: book.edition,
+            physical_format: book.physical_format,
+            dimensions: book.dimensions,
             added_at: book.added_at,
             hub_cover_upload_failed_at: book.hub_cover_upload_failed_at,
             is_borrowed: book.is_borrowed,
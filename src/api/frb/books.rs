@@ -1,4 +1,4 @@
-// Book CRUD: create, list, count.
+// Book CRUD: create, list, count; the "up next" reading queue.
 // Included by api/frb.rs (include!, not a module): items must stay in
 // crate::api::frb so the generated bindings keep their names, and file order
 // mirrors the include! order because the generated Dart facade follows
@@ -66,6 +66,8 @@ pub async fn get_all_books(
         title,
         tag,
         author: None,
+        archived: None,
+        ..Default::default()
     };
 
     match crate::services::book_service::list_books(db, filter).await {
@@ -83,3 +85,100 @@ pub async fn count_books() -> Result<i64, String> {
         Err(e) => Err(format!("{:?}", e)),
     }
 }
+
+// ============ Reading queue ============
+
+/// A book in the "up next" reading queue.
+pub struct FrbQueuedBook {
+    pub book_id: String,
+    /// 0 for the next book to read
+    pub position: i32,
+    pub title: String,
+    pub author: Option<String>,
+    pub cover_url: Option<String>,
+    pub reading_status: String,
+    pub added_at: String,
+}
+
+impl From<crate::services::reading_queue::QueuedBook> for FrbQueuedBook {
+    fn from(q: crate::services::reading_queue::QueuedBook) -> Self {
+        Self {
+            book_id: q.book_id,
+            position: q.position,
+            title: q.title,
+            author: q.author,
+            cover_url: q.cover_url,
+            reading_status: q.reading_status,
+            added_at: q.added_at,
+        }
+    }
+}
+
+/// The reading queue, next book first.
+pub async fn get_reading_queue() -> Result<Vec<FrbQueuedBook>, String> {
+    let db = db().ok_or("Database not initialized")?;
+    let queue = crate::services::reading_queue::list(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(queue.into_iter().map(FrbQueuedBook::from).collect())
+}
+
+/// Queue a book at `position` (the end when `None`), or move it there.
+pub async fn queue_book(
+    book_id: String,
+    position: Option<u32>,
+) -> Result<Vec<FrbQueuedBook>, String> {
+    let db = db().ok_or("Database not initialized")?;
+    let queue = crate::services::reading_queue::add(db, &book_id, position.map(|p| p as usize))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(queue.into_iter().map(FrbQueuedBook::from).collect())
+}
+
+/// Take a book off the reading queue.
+pub async fn unqueue_book(book_id: String) -> Result<(), String> {
+    let db = db().ok_or("Database not initialized")?;
+    crate::services::reading_queue::remove(db, &book_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Put the reading queue in the order of `book_ids`.
+pub async fn reorder_reading_queue(book_ids: Vec<String>) -> Result<Vec<FrbQueuedBook>, String> {
+    let db = db().ok_or("Database not initialized")?;
+    let queue = crate::services::reading_queue::reorder(db, &book_ids)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(queue.into_iter().map(FrbQueuedBook::from).collect())
+}
+
+/// Books being read and the next queued ones, for the dashboard.
+pub struct FrbReadingSummary {
+    pub reading: Vec<FrbBook>,
+    pub up_next: Vec<FrbQueuedBook>,
+    pub queued: u32,
+}
+
+/// What is being read and what comes next.
+pub async fn get_reading_summary() -> Result<FrbReadingSummary, String> {
+    let db = db().ok_or("Database not initialized")?;
+    let summary = crate::services::reading_queue::summary(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut reading = Vec::with_capacity(summary.reading.len());
+    for current in summary.reading {
+        let book = crate::services::book_service::get_book(db, &current.book_id)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        reading.push(FrbBook::from(book));
+    }
+    Ok(FrbReadingSummary {
+        reading,
+        up_next: summary
+            .up_next
+            .into_iter()
+            .map(FrbQueuedBook::from)
+            .collect(),
+        queued: summary.queued as u32,
+    })
+}
@@ -81,13 +81,14 @@ pub async fn get_all_contacts(
     contact_type: Option<String>,
 ) -> Result<Vec<FrbContact>, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
 
     let filter = crate::services::contact_service::ContactFilter {
         library_id,
         contact_type,
     };
 
-    match crate::services::contact_service::list_contacts(db, filter).await {
+    match crate::services::contact_service::list_contacts(&repo, filter).await {
         Ok(contacts) => Ok(contacts.into_iter().map(FrbContact::from).collect()),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -96,8 +97,9 @@ pub async fn get_all_contacts(
 /// Count total contacts
 pub async fn count_contacts() -> Result<i64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
 
-    match crate::services::contact_service::count_contacts(db).await {
+    match crate::services::contact_service::count_contacts(&repo).await {
         Ok(count) => Ok(count),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -106,6 +108,7 @@ pub async fn count_contacts() -> Result<i64, String> {
 /// Create a new contact
 pub async fn create_contact(contact: FrbContact) -> Result<FrbContact, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
 
     // Convert FrbContact to ContactDto for the service layer
     let dto = crate::services::contact_service::ContactDto {
@@ -129,7 +132,7 @@ pub async fn create_contact(contact: FrbContact) -> Result<FrbContact, String> {
         is_active: contact.is_active,
     };
 
-    match crate::services::contact_service::create_contact(db, dto).await {
+    match crate::services::contact_service::create_contact(&repo, dto).await {
         Ok(created) => Ok(FrbContact::from(created)),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -138,6 +141,7 @@ pub async fn create_contact(contact: FrbContact) -> Result<FrbContact, String> {
 /// Update an existing contact
 pub async fn update_contact(contact: FrbContact) -> Result<FrbContact, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
 
     // Convert FrbContact to ContactDto for the service layer
     let dto = crate::services::contact_service::ContactDto {
@@ -161,7 +165,7 @@ pub async fn update_contact(contact: FrbContact) -> Result<FrbContact, String> {
         is_active: contact.is_active,
     };
 
-    match crate::services::contact_service::update_contact(db, dto).await {
+    match crate::services::contact_service::update_contact(&repo, dto).await {
         Ok(updated) => Ok(FrbContact::from(updated)),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -11,11 +11,6 @@ static DB: OnceLock<DatabaseConnection> = OnceLock::new();
 /// display backend logs without relying on Xcode Console (stderr is invisible
 /// to the iOS FFI host process).
 static LOG_PATH: OnceLock<std::path::PathBuf> = OnceLock::new();
-/// The application's covers directory (`<data dir>/covers`). Set once in
-/// `init_backend` from the FFI-provided db path; read by `get_book_cover` to
-/// re-base persisted cover paths after an iOS data-container UUID change.
-/// `None` in server-binary mode where no FFI init runs.
-static COVERS_DIR: OnceLock<std::path::PathBuf> = OnceLock::new();
 /// Global AppState - set once in `initBackend`, read by FFI handlers that need
 /// services not available as individual statics (e.g. catalog notifications).
 static GLOBAL_APP_STATE: OnceLock<crate::infrastructure::AppState> = OnceLock::new();
@@ -75,12 +70,6 @@ fn global_app_state() -> Option<&'static crate::infrastructure::AppState> {
     GLOBAL_APP_STATE.get()
 }
 
-/// The covers directory registered in `init_backend`, or `None` in
-/// server-binary mode. Used by `get_book_cover` to re-base stored cover paths.
-pub(crate) fn covers_dir() -> Option<&'static std::path::PathBuf> {
-    COVERS_DIR.get()
-}
-
 /// Load the Google Books API key from the installation profile.
 async fn load_google_books_api_key() -> Option<String> {
     use crate::models::installation_profile::Entity as ProfileEntity;
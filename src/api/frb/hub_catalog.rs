@@ -68,12 +68,14 @@ async fn hub_directory_sync_catalog_inner() -> Result<i32, String> {
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Not registered in directory".to_string())?;
 
-    // Collect ALL owned books with their authors (no ISBN filter).
+    // Collect ALL shared owned books with their authors (no ISBN filter):
+    // private books and books with a private tag stay off the directory.
     let books_with_authors: Vec<(
         crate::models::book::Model,
         Vec<crate::models::author::Model>,
     )> = BookEntity::find()
         .filter(BookColumn::Owned.eq(true))
+        .filter(crate::models::Book::shared_condition(db).await)
         .find_with_related(crate::models::author::Entity)
         .all(db)
         .await
@@ -127,9 +129,15 @@ async fn hub_directory_sync_catalog_inner() -> Result<i32, String> {
         let cover_url_raw = book.cover_url.unwrap_or_default();
         let cover_url = if crate::utils::cover_url::is_servable_remotely(&cover_url_raw) {
             Some(cover_url_raw)
-        } else if !cover_url_raw.is_empty() {
+        } else if let Some(path) =
+            crate::utils::cover_url::local_cover_read_path(&cover_url_raw, &book_id_val)
+        {
             // Local file path: schedule for thumbnail upload
-            local_covers.push((book_id_val.clone(), cover_url_raw, book_updated_at));
+            local_covers.push((
+                book_id_val.clone(),
+                path.to_string_lossy().into_owned(),
+                book_updated_at,
+            ));
             None // Will be updated after upload
         } else {
             None
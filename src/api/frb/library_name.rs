@@ -22,10 +22,17 @@ pub async fn update_library_name_ffi(name: String) -> Result<(), String> {
         .map_err(|e| e.to_string())?;
 
     if let Some(c) = config {
+        let before = c.clone();
         let mut active = c.into_active_model();
         active.name = Set(name.clone());
         active.updated_at = Set(chrono::Utc::now().to_rfc3339());
-        active.update(db).await.map_err(|e| e.to_string())?;
+        let saved = active.update(db).await.map_err(|e| e.to_string())?;
+        // Versioned, and mDNS and peers follow the new name
+        if let Err(e) =
+            crate::services::config_history::record(db, Some(&before), &saved, "app").await
+        {
+            tracing::warn!("library name: cannot record config version: {e}");
+        }
     }
 
     // Also update libraries.name (id=1) for consistency
@@ -22,21 +22,19 @@ pub async fn init_backend(db_path: String) -> Result<String, String> {
     // Both are writable and retrievable via `get_rust_log_tail` FFI.
     // Truncated at each init so the file does not grow indefinitely across
     // launches - within a single session tracing keeps appending.
-    let log_path = std::path::Path::new(&db_path)
-        .parent()
-        .unwrap_or_else(|| std::path::Path::new("."))
-        .join("bibliogenius-rust.log");
+    //
+    // The DB's directory becomes the data directory: covers (re-based after an
+    // iOS data-container UUID change, mirroring the Flutter
+    // `LocalCoverResolver`) and ebook / audiobook media live under it.
+    crate::infrastructure::paths::register_data_dir(
+        std::path::Path::new(&db_path)
+            .parent()
+            .unwrap_or_else(|| std::path::Path::new("."))
+            .to_path_buf(),
+    );
+    let log_path = crate::infrastructure::paths::data_dir().join("bibliogenius-rust.log");
     let _ = LOG_PATH.set(log_path.clone());
 
-    // Register the covers directory (sibling of the DB file) so the
-    // peer-facing cover endpoint can re-base persisted absolute paths after an
-    // iOS data-container UUID change. Mirrors the Flutter `LocalCoverResolver`.
-    let covers_dir = std::path::Path::new(&db_path)
-        .parent()
-        .unwrap_or_else(|| std::path::Path::new("."))
-        .join("covers");
-    let _ = COVERS_DIR.set(covers_dir);
-
     static TRACING_INIT: std::sync::Once = std::sync::Once::new();
     TRACING_INIT.call_once(|| {
         if cfg!(debug_assertions) {
@@ -44,7 +42,7 @@ pub async fn init_backend(db_path: String) -> Result<String, String> {
             // events are always visible in debug builds - they live outside
             // the `rust_lib_app` namespace so they would otherwise be dropped.
             let filter = tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "rust_lib_app=info,ssrf=warn".into());
+                .unwrap_or_else(|_| "rust_lib_app=info,ssrf=warn,access=info".into());
 
             if let Ok(file) = std::fs::OpenOptions::new()
                 .create(true)
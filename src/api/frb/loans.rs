@@ -63,7 +63,8 @@ pub async fn get_all_loans(
         offset: None,
     };
 
-    match crate::services::loan_service::list_loans(db, filter).await {
+    let repos = crate::services::loan_service::SeaOrmLoanRepos::new(db);
+    match crate::services::loan_service::list_loans(repos.repos(), filter).await {
         Ok(loans) => Ok(loans.into_iter().map(FrbLoan::from).collect()),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -72,8 +73,9 @@ pub async fn get_all_loans(
 /// Count active loans
 pub async fn count_active_loans() -> Result<i64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmLoanRepository::new(db.clone());
 
-    match crate::services::loan_service::count_active_loans(db).await {
+    match crate::services::loan_service::count_active_loans(&repo).await {
         Ok(count) => Ok(count),
         Err(e) => Err(format!("{:?}", e)),
     }
@@ -111,7 +113,8 @@ pub async fn create_loan(
         notes,
     };
 
-    match crate::services::loan_service::create_loan(db, dto).await {
+    let repos = crate::services::loan_service::SeaOrmLoanRepos::new(db);
+    match crate::services::loan_service::create_loan(repos.repos(), dto).await {
         Ok(loan) => Ok(loan.id),
         Err(crate::services::loan_service::ServiceError::NotFound) => {
             Err("Copy not found".to_string())
@@ -124,8 +127,9 @@ pub async fn create_loan(
 /// Count returned loans (for cleanup confirmation dialog)
 pub async fn count_returned_loans() -> Result<i64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmLoanRepository::new(db.clone());
 
-    crate::services::loan_service::count_returned_loans(db)
+    crate::services::loan_service::count_returned_loans(&repo)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -133,8 +137,9 @@ pub async fn count_returned_loans() -> Result<i64, String> {
 /// Delete all returned loans, returns the number of deleted rows
 pub async fn delete_returned_loans() -> Result<u64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let repo = crate::infrastructure::SeaOrmLoanRepository::new(db.clone());
 
-    crate::services::loan_service::delete_returned_loans(db)
+    crate::services::loan_service::delete_returned_loans(&repo)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -142,8 +147,9 @@ pub async fn delete_returned_loans() -> Result<u64, String> {
 /// Count closed incoming P2P requests (not pending)
 pub async fn count_closed_incoming_requests() -> Result<i64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let requests = crate::infrastructure::SeaOrmP2pRequestRepository::new(db.clone());
 
-    crate::services::loan_service::count_closed_incoming_requests(db)
+    crate::services::loan_service::count_closed_incoming_requests(&requests)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -151,8 +157,9 @@ pub async fn count_closed_incoming_requests() -> Result<i64, String> {
 /// Delete all closed incoming P2P requests (not pending)
 pub async fn delete_closed_incoming_requests() -> Result<u64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let requests = crate::infrastructure::SeaOrmP2pRequestRepository::new(db.clone());
 
-    crate::services::loan_service::delete_closed_incoming_requests(db)
+    crate::services::loan_service::delete_closed_incoming_requests(&requests)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -160,8 +167,9 @@ pub async fn delete_closed_incoming_requests() -> Result<u64, String> {
 /// Count closed outgoing P2P requests (not pending)
 pub async fn count_closed_outgoing_requests() -> Result<i64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let requests = crate::infrastructure::SeaOrmP2pRequestRepository::new(db.clone());
 
-    crate::services::loan_service::count_closed_outgoing_requests(db)
+    crate::services::loan_service::count_closed_outgoing_requests(&requests)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -169,8 +177,9 @@ pub async fn count_closed_outgoing_requests() -> Result<i64, String> {
 /// Delete all closed outgoing P2P requests (not pending)
 pub async fn delete_closed_outgoing_requests() -> Result<u64, String> {
     let db = db().ok_or("Database not initialized")?;
+    let requests = crate::infrastructure::SeaOrmP2pRequestRepository::new(db.clone());
 
-    crate::services::loan_service::delete_closed_outgoing_requests(db)
+    crate::services::loan_service::delete_closed_outgoing_requests(&requests)
         .await
         .map_err(|e| format!("{:?}", e))
 }
@@ -179,12 +188,17 @@ pub async fn delete_closed_outgoing_requests() -> Result<u64, String> {
 pub async fn return_loan(id: String) -> Result<String, String> {
     let db = db().ok_or("Database not initialized")?;
 
-    match crate::services::loan_service::return_loan(db, &id).await {
+    let repos = crate::services::loan_service::SeaOrmLoanRepos::new(db);
+    match crate::services::loan_service::return_loan(repos.repos(), &id).await {
         Ok(_) => {
             // Dismiss any pending due-date reminders for this loan
             use crate::domain::NotificationRepository;
             let notif_repo = crate::infrastructure::SeaOrmNotificationRepository::new(db.clone());
             let _ = notif_repo.dismiss_by_ref("loan", &id).await;
+            // Peers waitlisted for the book get the copy on hold.
+            if let Some(state) = global_app_state() {
+                crate::services::p2p_holds::spawn_serve(state);
+            }
             Ok("Loan returned successfully".to_string())
         }
         Err(crate::services::loan_service::ServiceError::NotFound) => {
@@ -229,11 +243,15 @@ pub async fn update_loan_settings(
     let repo = crate::infrastructure::SeaOrmLoanSettingsRepository::new(db.clone());
     use crate::domain::LoanSettingsRepository;
 
+    // The grace period, per-type durations, hold expiry, request cleanup and
+    // fee policy are managed over HTTP; keep them.
+    let current = repo.get_settings().await.map_err(|e| e.to_string())?;
     let updated = repo
         .update_settings(crate::domain::LoanSettings {
             default_loan_duration_days,
             per_book_duration_enabled,
             reminder_days_before_due,
+            ..current
         })
         .await
         .map_err(|e| e.to_string())?;
@@ -257,8 +275,8 @@ pub async fn check_loan_reminders(language: String) -> Result<i32, String> {
     use crate::domain::notification_repository::{CreateNotification, NotificationEventType};
     use crate::domain::{LoanSettingsRepository, NotificationRepository};
     use crate::infrastructure::{SeaOrmLoanSettingsRepository, SeaOrmNotificationRepository};
-    use crate::services::loan_service::{LoanFilter, list_loans};
-    use chrono::{Local, NaiveDate};
+    use crate::services::loan_service::{LoanFilter, SeaOrmLoanRepos, list_loans};
+    use chrono::NaiveDate;
 
     let db = db().ok_or("Database not initialized")?;
 
@@ -270,7 +288,7 @@ pub async fn check_loan_reminders(language: String) -> Result<i32, String> {
     let reminder_days = settings.reminder_days_before_due;
 
     let loans = list_loans(
-        db,
+        SeaOrmLoanRepos::new(db).repos(),
         LoanFilter {
             status: Some("active".to_string()),
             ..Default::default()
@@ -280,7 +298,10 @@ pub async fn check_loan_reminders(language: String) -> Result<i32, String> {
     .map_err(|e| format!("{:?}", e))?;
 
     let notif_repo = SeaOrmNotificationRepository::new(db.clone());
-    let today = Local::now().date_naive();
+    let today = crate::utils::time_zone::library_zone(db)
+        .await
+        .map_err(|e| e.to_string())?
+        .today();
     let lang = language.as_str();
     let mut created = 0i32;
 
@@ -82,10 +82,14 @@ pub async fn init_mdns_ffi(
         ed25519_public_key.is_some()
     );
 
+    let library_uuid = global_app_state()
+        .and_then(|state| state.identity_service.library_uuid())
+        .map(str::to_string);
     match crate::services::mdns::init_mdns(
         &library_name,
         port,
         library_id,
+        library_uuid,
         ed25519_public_key,
         x25519_public_key,
     ) {
@@ -7,10 +7,26 @@
 // ============ Reset API ============
 
 /// Reset the entire application - deletes all data from all tables
-/// This is irreversible and should be used with caution
+/// The DB is snapshotted as a rollback first; a failed snapshot aborts, and so
+/// does a database with no file to snapshot (`DATABASE_URL`, set by
+/// `init_backend`, missing or in memory).
+///
+/// Unlike `POST /api/reset` there is no admin role and no dry-run token: FFI
+/// functions are only reachable in-process, from the embedding app acting as
+/// the device owner, which asks the user to confirm before calling this.
 pub async fn reset_app() -> Result<String, String> {
     let db = db().ok_or("Database not initialized")?;
 
+    // Keep the library as a rollback (restorable for 24h) before touching it.
+    let database_url = std::env::var("DATABASE_URL").ok();
+    match crate::api::backup::snapshot_rollback(db, database_url.as_deref()).await {
+        Ok(Some(path)) => tracing::info!("Pre-reset backup written to {}", path.display()),
+        Ok(None) => {
+            return Err("Pre-reset backup failed: DATABASE_URL names no database file".to_string());
+        }
+        Err(e) => return Err(format!("Pre-reset backup failed: {e}")),
+    }
+
     // Unregister from hub directory BEFORE deleting local data (needs write_token).
     // Fire-and-forget: failure should not block local reset.
     {
@@ -136,10 +152,14 @@ pub async fn start_server(port: u16) -> Result<u16, String> {
 
                 let shared_id_svc = IDENTITY_SERVICE
                     .get_or_init(|| crate::services::IdentityService::new(db.clone()));
-                let state = crate::infrastructure::AppState::with_identity_service(
+                let mut state = crate::infrastructure::AppState::with_identity_service(
                     db,
                     std::sync::Arc::new(shared_id_svc.clone()),
                 );
+                // `init_backend` published the URL it opened the database from.
+                if let Ok(database_url) = std::env::var("DATABASE_URL") {
+                    state = state.with_database_url(database_url);
+                }
                 state.set_server_port(actual_port);
                 // Store globally so FFI handlers (create_book, delete_book) can
                 // trigger catalog-change notifications without going through HTTP.
@@ -170,6 +190,66 @@ pub async fn start_server(port: u16) -> Result<u16, String> {
                 // Spawn delta sync retention pruner (ADR-028 D5)
                 crate::services::oplog_pruner::spawn(state.db().clone());
 
+                // Spawn hold shelf expiry (unclaimed P2P holds)
+                crate::services::hold_expiry::spawn(state.clone());
+
+                // Spawn overdue fine accrual
+                crate::services::fines::spawn(state.db().clone());
+
+                // Spawn outgoing request status polling (lost lender callbacks)
+                crate::services::outgoing_request_sync::spawn(state.clone());
+
+                // Spawn P2P request expiry and cleanup
+                crate::services::request_cleanup::spawn(state.clone());
+
+                // Spawn P2P outbox retries (messages for unreachable peers)
+                crate::services::peer_outbox::spawn(state.clone());
+
+                // Spawn device sync with this user's other paired installs
+                crate::services::device_sync::spawn(state.clone());
+
+                // Spawn hub directory follows refresh
+                crate::services::hub_sync::spawn(state.clone());
+
+                // Spawn subscribed peer catalogue syncs
+                crate::services::peer_subscriptions::spawn(state.clone());
+
+                // Spawn saved search runs (new match notifications)
+                crate::services::saved_searches::spawn(state.db().clone());
+
+                // Spawn scheduled database maintenance (VACUUM, orphan sweep)
+                crate::services::db_maintenance::spawn(state.db().clone());
+
+                // Nudge peers about library configuration changes
+                crate::services::config_history::spawn(state.clone());
+
+                // Keep the mDNS record on the current name, port and addresses
+                crate::services::mdns::spawn_announcer(state.db().clone());
+                if let Err(e) = crate::services::mdns::reannounce_mdns(None, Some(actual_port)) {
+                    tracing::warn!("Could not re-announce mDNS on port {}: {}", actual_port, e);
+                }
+
+                // Load certificates pinned for self-signed HTTPS peers
+                if let Err(e) = crate::services::cert_pins::load(state.db()).await {
+                    tracing::warn!("Could not load pinned peer certificates: {}", e);
+                }
+
+                // Load metadata provider quotas and spawn their usage writer
+                if let Err(e) = crate::services::api_quota::load(state.db()).await {
+                    tracing::warn!("Could not load metadata provider quotas: {}", e);
+                }
+                crate::services::api_quota::spawn(state.db().clone());
+
+                // Load the network mode (full, LAN-only, offline)
+                if let Err(e) = crate::services::network_mode::load(state.db()).await {
+                    tracing::warn!("Could not load the network mode: {}", e);
+                }
+
+                // Load the SOCKS5 proxy and onion address settings
+                if let Err(e) = crate::services::tor::load(state.db()).await {
+                    tracing::warn!("Could not load the Tor settings: {}", e);
+                }
+
                 let api = crate::api::api_router_with_state(state);
                 // Allow CORS for all origins/methods/headers for P2P ease
                 let cors = CorsLayer::new()
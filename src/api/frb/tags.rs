@@ -18,11 +18,10 @@ pub async fn get_all_tags() -> Result<Vec<FrbTag>, String> {
     let db = db().ok_or("Database not initialized")?;
 
     // 1. Fetch hierarchical tags from DB
-    use crate::models::tag;
-    use sea_orm::{EntityTrait, QueryOrder};
-    let db_tags = tag::Entity::find()
-        .order_by_asc(tag::Column::Name)
-        .all(db)
+    use crate::domain::TagRepository;
+    use sea_orm::EntityTrait;
+    let db_tags = crate::infrastructure::SeaOrmTagRepository::new(db.clone())
+        .find_all()
         .await
         .map_err(|e| format!("{:?}", e))?;
 
@@ -86,27 +85,18 @@ pub async fn get_all_tags() -> Result<Vec<FrbTag>, String> {
 /// Create a new tag
 pub async fn create_tag(name: String, parent_id: Option<String>) -> Result<FrbTag, String> {
     let db = db().ok_or("Database not initialized")?;
-    use crate::models::tag;
-    use sea_orm::{ActiveModelTrait, Set};
-
-    let new_tag = tag::ActiveModel {
-        name: Set(name),
-        parent_id: Set(parent_id),
-        created_at: Set(chrono::Utc::now().to_rfc3339()),
-        updated_at: Set(chrono::Utc::now().to_rfc3339()),
-        ..Default::default()
-    };
+    use crate::domain::TagRepository;
 
-    match new_tag.insert(db).await {
-        Ok(t) => {
-            let _ = crate::sync::log_operation(db, "tag", &t.id, "INSERT", None).await;
-            Ok(FrbTag {
-                id: t.id,
-                name: t.name,
-                parent_id: t.parent_id,
-                count: 0,
-            })
-        }
+    match crate::infrastructure::SeaOrmTagRepository::new(db.clone())
+        .create(name, parent_id)
+        .await
+    {
+        Ok(t) => Ok(FrbTag {
+            id: t.id,
+            name: t.name,
+            parent_id: t.parent_id,
+            count: 0,
+        }),
         Err(e) => Err(format!("{:?}", e)),
     }
 }
@@ -118,31 +108,19 @@ pub async fn update_tag(
     parent_id: Option<String>,
 ) -> Result<FrbTag, String> {
     let db = db().ok_or("Database not initialized")?;
-    use crate::models::tag;
-    use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+    use crate::domain::TagRepository;
+    let repo = crate::infrastructure::SeaOrmTagRepository::new(db.clone());
 
-    let tag_model = tag::Entity::find_by_id(id)
-        .one(db)
-        .await
-        .map_err(|e| format!("{:?}", e))?;
-    let Some(tag_model) = tag_model else {
+    let Some(existing) = repo.find_by_id(&id).await.map_err(|e| format!("{:?}", e))? else {
         return Err("Tag not found".to_string());
     };
 
-    let old_name = tag_model.name.clone();
-
-    let mut active: tag::ActiveModel = tag_model.into();
-    active.name = Set(name.clone());
-    active.parent_id = Set(parent_id);
-    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
-
-    match active.update(db).await {
+    match repo.update(&id, name.clone(), parent_id).await {
         Ok(t) => {
             // Also rename the subject in all books that reference the old name
-            if old_name != name {
-                rename_subject_in_books(db, &old_name, &name).await;
+            if existing.name != name {
+                rename_subject_in_books(db, &existing.name, &name).await;
             }
-            let _ = crate::sync::log_operation(db, "tag", &t.id, "UPDATE", None).await;
             Ok(FrbTag {
                 id: t.id,
                 name: t.name,
@@ -200,20 +178,16 @@ async fn rename_subject_in_books(db: &sea_orm::DatabaseConnection, old_name: &st
     }
 }
 
-/// Delete a tag
+/// Delete a tag. Deleting a tag that is already gone succeeds.
 pub async fn delete_tag(id: String) -> Result<(), String> {
     let db = db().ok_or("Database not initialized")?;
-    use sea_orm::TransactionTrait;
+    use crate::domain::{DomainError, TagRepository};
 
-    // Cascade the tag's book links and re-parent its children in one
-    // transaction: the database no longer cascades these since the replicated
-    // tables lost their foreign keys (ADR-044).
-    let txn = db.begin().await.map_err(|e| format!("{e:?}"))?;
-    crate::infrastructure::referential_integrity::delete_tag_cascade(&txn, &id)
+    match crate::infrastructure::SeaOrmTagRepository::new(db.clone())
+        .delete(&id)
         .await
-        .map_err(|e| format!("{e:?}"))?;
-    txn.commit().await.map_err(|e| format!("{e:?}"))?;
-
-    let _ = crate::sync::log_operation(db, "tag", &id, "DELETE", None).await;
-    Ok(())
+    {
+        Ok(()) | Err(DomainError::NotFound) => Ok(()),
+        Err(e) => Err(format!("{e:?}")),
+    }
 }
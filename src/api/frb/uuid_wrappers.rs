@@ -75,7 +75,8 @@ pub async fn delete_tag_by_uuid(uuid: String) -> Result<(), String> {
 /// Fetch a contact by its uuid (single fetch, no id round-trip).
 pub async fn get_contact_by_uuid(uuid: String) -> Result<FrbContact, String> {
     let db = db().ok_or("Database not initialized")?;
-    match crate::services::contact_service::get_contact_by_uuid(db, &uuid).await {
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
+    match crate::services::contact_service::get_contact_by_uuid(&repo, &uuid).await {
         Ok(contact) => Ok(FrbContact::from(contact)),
         Err(crate::services::contact_service::ServiceError::NotFound) => {
             Err("Contact not found".to_string())
@@ -87,7 +88,8 @@ pub async fn get_contact_by_uuid(uuid: String) -> Result<FrbContact, String> {
 /// Delete a contact identified by its uuid.
 pub async fn delete_contact_by_uuid(uuid: String) -> Result<(), String> {
     let db = db().ok_or("Database not initialized")?;
-    match crate::services::contact_service::delete_contact(db, &uuid).await {
+    let repo = crate::infrastructure::SeaOrmContactRepository::new(db.clone());
+    match crate::services::contact_service::delete_contact(&repo, &uuid).await {
         Ok(_) => Ok(()),
         Err(e) => Err(format!("{e:?}")),
     }
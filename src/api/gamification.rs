@@ -14,6 +14,14 @@ use crate::services::gamification_service;
 pub use gamification_service::{PublicGamificationStats, PublicTrackStats};
 
 /// GET /api/user/status
+#[utoipa::path(
+    get,
+    path = "/api/user/status",
+    tag = "gamification",
+    responses(
+        (status = 200, description = "Level, streaks and achievements")
+    )
+)]
 pub async fn get_user_status(State(state): State<AppState>) -> impl IntoResponse {
     match gamification_service::get_user_status(state.gamification_repo.as_ref()).await {
         Ok(status) => (StatusCode::OK, Json(json!(status))).into_response(),
@@ -26,6 +34,15 @@ pub async fn get_user_status(State(state): State<AppState>) -> impl IntoResponse
 }
 
 /// GET /api/gamification/public-stats
+#[utoipa::path(
+    get,
+    path = "/api/gamification/public-stats",
+    tag = "gamification",
+    responses(
+        (status = 200, description = "Shareable leaderboard stats"),
+        (status = 403, description = "Sharing is disabled")
+    )
+)]
 pub async fn get_public_stats(State(state): State<AppState>) -> impl IntoResponse {
     match gamification_service::get_public_stats(state.gamification_repo.as_ref()).await {
         Ok(Some(stats)) => (StatusCode::OK, Json(json!(stats))).into_response(),
@@ -43,6 +60,15 @@ pub async fn get_public_stats(State(state): State<AppState>) -> impl IntoRespons
 }
 
 /// GET /api/gamification/leaderboard
+#[utoipa::path(
+    get,
+    path = "/api/gamification/leaderboard",
+    tag = "gamification",
+    responses(
+        (status = 200, description = "Leaderboard across connected peers"),
+        (status = 403, description = "Sharing is disabled")
+    )
+)]
 pub async fn get_leaderboard(State(state): State<AppState>) -> impl IntoResponse {
     match gamification_service::build_leaderboard(state.gamification_repo.as_ref()).await {
         Ok(leaderboard) => (StatusCode::OK, Json(json!(leaderboard))).into_response(),
@@ -62,6 +88,15 @@ pub async fn get_leaderboard(State(state): State<AppState>) -> impl IntoResponse
 /// Syncs gamification stats from all connected peers, then returns the leaderboard.
 /// NOTE: The peer sync logic in `peer::sync_peer_gamification_stats` is intentionally
 /// kept unchanged (TNR-safe) — it still uses direct SeaORM via DatabaseConnection.
+#[utoipa::path(
+    post,
+    path = "/api/gamification/refresh-leaderboard",
+    tag = "gamification",
+    responses(
+        (status = 200, description = "Peer stats refreshed"),
+        (status = 403, description = "Sharing is disabled")
+    )
+)]
 pub async fn refresh_leaderboard(State(state): State<AppState>) -> impl IntoResponse {
     use crate::models::{contact, peer};
     use sea_orm::{
@@ -101,13 +136,11 @@ pub async fn refresh_leaderboard(State(state): State<AppState>) -> impl IntoResp
         .await
         .unwrap_or_default();
 
-    let client = crate::api::peer::get_safe_client();
-
     // Sync all peers in parallel to avoid sequential timeouts
     let sync_futures: Vec<_> = peers
         .iter()
         .map(|p| {
-            let client = client.clone();
+            let client = crate::api::peer::get_safe_client_for(&p.url);
             let peer_url = p.url.clone();
             let peer_name = p.name.clone();
             let peer_id = p.id;
@@ -168,7 +201,7 @@ pub async fn refresh_leaderboard(State(state): State<AppState>) -> impl IntoResp
                 db,
                 peer_id,
                 &peer_url,
-                &client,
+                &crate::api::peer::get_safe_client_for(&peer_url),
                 Some(config.share_gamification_stats),
             )
             .await;
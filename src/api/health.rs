@@ -4,6 +4,7 @@ use serde_json::{Value, json};
 #[utoipa::path(
     get,
     path = "/api/health",
+    tag = "system",
     responses(
         (status = 200, description = "Service is healthy")
     )
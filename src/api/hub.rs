@@ -0,0 +1,53 @@
+//! Hub directory sync: the cached follows and the last refresh (see
+//! `services::hub_sync`), and a trigger to refresh now.
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::hub_sync;
+
+/// GET /api/hub/sync - The last hub refresh and the follows it cached
+#[utoipa::path(
+    get,
+    path = "/api/hub/sync",
+    tag = "hub",
+    responses(
+        (status = 200, description = "Last refresh (`hub`) with the cached `following` and `followers`")
+    )
+)]
+pub async fn hub_sync_status() -> Response {
+    let (following, followers) = hub_sync::cached_follows();
+    Json(json!({
+        "hub": hub_sync::status(),
+        "following": following,
+        "followers": followers,
+    }))
+    .into_response()
+}
+
+/// POST /api/hub/sync - Refresh from the hub now
+#[utoipa::path(
+    post,
+    path = "/api/hub/sync",
+    tag = "hub",
+    responses(
+        (status = 200, description = "Refreshed, or nothing to sync", body = HubSyncStatus),
+        (status = 502, description = "The hub could not be reached or failed, after retries")
+    )
+)]
+pub async fn sync_hub(State(state): State<AppState>) -> Response {
+    match hub_sync::refresh(&state).await {
+        Ok(status) => Json(json!({ "hub": status })).into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": e.to_string(), "hub": hub_sync::status() })),
+        )
+            .into_response(),
+    }
+}
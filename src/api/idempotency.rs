@@ -0,0 +1,330 @@
+//! `Idempotency-Key` support for endpoints a caller may retry.
+//!
+//! A peer whose delivery timed out (the outbox, a flaky network) cannot tell
+//! whether the first attempt was handled, so it sends the same request again.
+//! With an `Idempotency-Key` header, the first answer is kept for a day in
+//! `idempotency_keys` and a retry with the same key, endpoint and body gets
+//! that answer back (with `Idempotent-Replayed: true`) instead of creating a
+//! second request, loan or book. The same key with another body is refused
+//! (422), as is a retry arriving while the first request is still handled
+//! (409). A 5xx answer is not kept, so the retry is handled anew. Requests
+//! without the header are untouched.
+//!
+//! Applied per route with [`idempotency_layer`]; the store is reached through
+//! the `DatabaseConnection` extension the router adds.
+
+use axum::{
+    Json,
+    body::{Body, to_bytes},
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{Duration, Utc};
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::*;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::models::idempotency_key;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// How long an answer is replayed.
+const KEY_TTL_HOURS: i64 = 24;
+
+/// A first request still unanswered after this long is taken to have died
+/// with its handler, and the key is handed to the retry.
+const STALE_IN_FLIGHT_MINUTES: i64 = 10;
+
+/// Bodies hashed in memory; a keyed request above this is refused.
+const MAX_REQUEST_BYTES: usize = 64 * 1024 * 1024;
+
+/// Larger answers are replayed as their status alone.
+const MAX_STORED_RESPONSE_BYTES: usize = 256 * 1024;
+
+/// Accept a key only if it is short and plain ASCII (UUIDs, outbox ids).
+fn sanitize(candidate: &str) -> Option<&str> {
+    let ok = !candidate.is_empty()
+        && candidate.len() <= 128
+        && candidate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    ok.then_some(candidate)
+}
+
+fn refuse(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+/// What the store says about a key.
+enum Claim {
+    /// First time: handle the request, then record its answer
+    New,
+    Replay(idempotency_key::Model),
+    InFlight,
+    Mismatch,
+}
+
+async fn claim(
+    db: &DatabaseConnection,
+    key: &str,
+    scope: &str,
+    request_hash: &str,
+) -> Result<Claim, DbErr> {
+    let now = Utc::now();
+    idempotency_key::Entity::delete_many()
+        .filter(
+            idempotency_key::Column::CreatedAt
+                .lt((now - Duration::hours(KEY_TTL_HOURS)).to_rfc3339()),
+        )
+        .exec(db)
+        .await?;
+
+    let inserted = idempotency_key::Entity::insert(idempotency_key::ActiveModel {
+        key: Set(key.to_string()),
+        scope: Set(scope.to_string()),
+        request_hash: Set(request_hash.to_string()),
+        status: Set(None),
+        content_type: Set(None),
+        body: Set(None),
+        created_at: Set(now.to_rfc3339()),
+    })
+    .on_conflict(
+        OnConflict::columns([idempotency_key::Column::Key, idempotency_key::Column::Scope])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    if inserted > 0 {
+        return Ok(Claim::New);
+    }
+
+    let Some(existing) = idempotency_key::Entity::find_by_id((key.to_string(), scope.to_string()))
+        .one(db)
+        .await?
+    else {
+        // Pruned between the insert and the read: a first time after all.
+        return Ok(Claim::New);
+    };
+    if existing.request_hash != request_hash {
+        return Ok(Claim::Mismatch);
+    }
+    if existing.status.is_some() {
+        return Ok(Claim::Replay(existing));
+    }
+    let stale = chrono::DateTime::parse_from_rfc3339(&existing.created_at)
+        .is_ok_and(|t| t < now - Duration::minutes(STALE_IN_FLIGHT_MINUTES));
+    if stale {
+        let mut active: idempotency_key::ActiveModel = existing.into();
+        active.created_at = Set(now.to_rfc3339());
+        active.update(db).await?;
+        return Ok(Claim::New);
+    }
+    Ok(Claim::InFlight)
+}
+
+async fn release(db: &DatabaseConnection, key: &str, scope: &str) {
+    let _ = idempotency_key::Entity::delete_by_id((key.to_string(), scope.to_string()))
+        .exec(db)
+        .await;
+}
+
+async fn record(
+    db: &DatabaseConnection,
+    key: &str,
+    scope: &str,
+    status: StatusCode,
+    content_type: Option<String>,
+    body: Option<Vec<u8>>,
+) -> Result<(), DbErr> {
+    idempotency_key::Entity::update_many()
+        .col_expr(
+            idempotency_key::Column::Status,
+            Expr::value(status.as_u16() as i32),
+        )
+        .col_expr(
+            idempotency_key::Column::ContentType,
+            Expr::value(content_type),
+        )
+        .col_expr(idempotency_key::Column::Body, Expr::value(body))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .filter(idempotency_key::Column::Scope.eq(scope))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+fn replay(stored: idempotency_key::Model) -> Response {
+    let status = stored
+        .status
+        .and_then(|s| StatusCode::from_u16(s as u16).ok())
+        .unwrap_or(StatusCode::OK);
+    let mut response = Response::new(Body::from(stored.body.unwrap_or_default()));
+    *response.status_mut() = status;
+    if let Some(value) = stored
+        .content_type
+        .as_deref()
+        .and_then(|c| HeaderValue::from_str(c).ok())
+    {
+        response.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    response
+        .headers_mut()
+        .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Middleware replaying the answer to a request already handled under the
+/// same `Idempotency-Key`.
+pub async fn idempotency_layer(req: Request, next: Next) -> Response {
+    let Some(key) = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .map(str::to_string)
+    else {
+        return next.run(req).await;
+    };
+    if sanitize(&key).is_none() {
+        return refuse(StatusCode::BAD_REQUEST, "Malformed Idempotency-Key");
+    }
+    let Some(db) = req.extensions().get::<DatabaseConnection>().cloned() else {
+        return next.run(req).await;
+    };
+
+    let scope = format!("{} {}", req.method(), req.uri().path());
+    let (parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return refuse(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Request body too large for an Idempotency-Key",
+            );
+        }
+    };
+    let request_hash = hex::encode(Sha256::digest(&bytes));
+
+    match claim(&db, &key, &scope, &request_hash).await {
+        Ok(Claim::New) => {}
+        Ok(Claim::Replay(stored)) => return replay(stored),
+        Ok(Claim::InFlight) => {
+            return refuse(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is still being handled",
+            );
+        }
+        Ok(Claim::Mismatch) => {
+            return refuse(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key already used for a different request",
+            );
+        }
+        Err(e) => {
+            // Without the store the request is handled as if it had no key.
+            tracing::warn!("idempotency: {e}");
+            return next
+                .run(Request::from_parts(parts, Body::from(bytes)))
+                .await;
+        }
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    if response.status().is_server_error() {
+        release(&db, &key, &scope).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let (body, stored) = match to_bytes(body, MAX_STORED_RESPONSE_BYTES).await {
+        Ok(bytes) => (Body::from(bytes.clone()), Some(bytes.to_vec())),
+        // Too large to keep: the handler already ran, so the key stays
+        // claimed and a retry gets the status alone.
+        Err(_) => (Body::empty(), None),
+    };
+    if let Err(e) = record(&db, &key, &scope, parts.status, content_type, stored).await {
+        tracing::warn!("idempotency: {e}");
+    }
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::post};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    fn request(key: Option<&str>, body: &'static str) -> Request {
+        let mut builder = axum::http::Request::post("/items");
+        if let Some(key) = key {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        builder.body(Body::from(body)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_retried_request_is_answered_once() {
+        let db = setup().await;
+        let handled = Arc::new(AtomicUsize::new(0));
+        let counter = handled.clone();
+        let app = Router::new()
+            .route(
+                "/items",
+                post(move || {
+                    let counter = counter.clone();
+                    async move {
+                        let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                        (StatusCode::CREATED, Json(json!({ "item": n })))
+                    }
+                })
+                .layer(axum::middleware::from_fn(idempotency_layer)),
+            )
+            .layer(axum::Extension(db));
+
+        let first = app
+            .clone()
+            .oneshot(request(Some("k-1"), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let retry = app
+            .clone()
+            .oneshot(request(Some("k-1"), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers()[REPLAYED_HEADER], "true");
+        let body = to_bytes(retry.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"item":1}"#);
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+
+        let reused = app
+            .clone()
+            .oneshot(request(Some("k-1"), "[]"))
+            .await
+            .unwrap();
+        assert_eq!(reused.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        app.clone().oneshot(request(None, "{}")).await.unwrap();
+        app.oneshot(request(Some("k-2"), "{}")).await.unwrap();
+        assert_eq!(handled.load(Ordering::SeqCst), 3);
+    }
+}
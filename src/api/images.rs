@@ -0,0 +1,70 @@
+//! Image processing (see `services::images`).
+
+use axum::{
+    Json,
+    extract::{Multipart, Query},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::api::uploads::{self, UploadKind};
+use crate::services::images::{self, ImageError, ImageLimits, ImageOps};
+
+/// The status an image that could not be processed answers with.
+pub(crate) fn image_status(e: &ImageError) -> StatusCode {
+    match e {
+        ImageError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        ImageError::Unreadable(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ImageError::Invalid(_) => StatusCode::BAD_REQUEST,
+        ImageError::QuotaExceeded { .. } => StatusCode::INSUFFICIENT_STORAGE,
+        ImageError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn image_error(e: ImageError) -> Response {
+    (image_status(&e), Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// The uploaded image, as bytes (see `uploads::read_file`).
+pub(crate) async fn read_upload(multipart: &mut Multipart) -> Result<Vec<u8>, Response> {
+    uploads::read_file(multipart, UploadKind::Image)
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(IntoResponse::into_response)
+}
+
+/// POST /api/images/process - Rotate, crop, resize or convert an image
+///
+/// Nothing is stored: the processed image is the response body.
+#[utoipa::path(
+    post,
+    path = "/api/images/process",
+    tag = "books",
+    params(ImageOps),
+    request_body(content = String, description = "Multipart upload of an image", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The processed image, without EXIF metadata"),
+        (status = 400, description = "No image, or a bad rotation, crop or box"),
+        (status = 413, description = "Image over `max_image_upload_mb` (10 MB by default)"),
+        (status = 415, description = "Not a JPEG, PNG or WebP image")
+    )
+)]
+pub async fn process_image(Query(ops): Query<ImageOps>, mut multipart: Multipart) -> Response {
+    let input = match read_upload(&mut multipart).await {
+        Ok(input) => input,
+        Err(resp) => return resp,
+    };
+    let limits = ImageLimits::runtime();
+    let processed =
+        tokio::task::spawn_blocking(move || images::process(&input, &ops, &limits)).await;
+    match processed {
+        Ok(Ok(out)) => (
+            [(header::CONTENT_TYPE, out.format.content_type())],
+            out.bytes,
+        )
+            .into_response(),
+        Ok(Err(e)) => image_error(e),
+        Err(e) => image_error(ImageError::Io(std::io::Error::other(e))),
+    }
+}
@@ -13,23 +13,68 @@ use strsim::jaro_winkler;
 use crate::infrastructure::auth::LoopbackNoBrowser;
 use crate::infrastructure::mcp_token;
 use crate::models::book;
+use crate::modules::integrations::bnf::BnfSource;
 use crate::modules::integrations::sudoc;
+use crate::services::api_quota;
 use crate::utils::lang::{base_lang, lang_matches_any};
 use futures::stream::{self, StreamExt};
 
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
-    pub isbn: String,
+    pub isbn: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub q: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/integrations/sudoc/search",
+    tag = "integrations",
+    params(
+        ("isbn" = Option<String>, Query, description = "ISBN to look up; takes precedence over the other parameters"),
+        ("title" = Option<String>, Query, description = "Words of the title"),
+        ("author" = Option<String>, Query, description = "Words of an author's name"),
+        ("q" = Option<String>, Query, description = "Words searched in every index, when neither title nor author is given")
+    ),
+    responses(
+        (status = 200, description = "SUDOC record (`book`) for an ISBN, or up to 20 records (`books`)"),
+        (status = 400, description = "Lookup failed, or nothing to search")
+    )
+)]
 pub async fn search_sudoc(
     State(_db): State<DatabaseConnection>,
     Query(params): Query<SearchQuery>,
 ) -> impl IntoResponse {
-    match sudoc::fetch_by_isbn(&params.isbn).await {
-        Ok(book) => (
+    if let Some(isbn) = params.isbn.as_deref().filter(|i| !i.trim().is_empty()) {
+        return match sudoc::fetch_by_isbn(isbn).await {
+            Ok(book) => (
+                StatusCode::OK,
+                Json(json!({ "success": true, "book": book })),
+            )
+                .into_response(),
+            Err(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "success": false, "error": e })),
+            )
+                .into_response(),
+        };
+    }
+
+    let q = params.q.as_deref().unwrap_or("");
+    let title = params.title.as_deref().filter(|t| !t.trim().is_empty());
+    let author = params.author.as_deref().filter(|a| !a.trim().is_empty());
+    if q.trim().is_empty() && title.is_none() && author.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "success": false, "error": "isbn, title, author or q is required" })),
+        )
+            .into_response();
+    }
+    match sudoc::search_sudoc_sru(q, title, author).await {
+        Ok(books) => (
             StatusCode::OK,
-            Json(json!({ "success": true, "book": book })),
+            Json(json!({ "success": true, "books": books })),
         )
             .into_response(),
         Err(e) => (
@@ -62,7 +107,9 @@ struct OpenLibraryDoc {
     key: String,                      // Work ID (e.g. "/works/OL12345W")
 }
 
-fn normalize_string(s: &str) -> String {
+/// Lowercase and fold accents, as every relevance comparison does. Public for
+/// the `hot_paths` benchmark.
+pub fn normalize_string(s: &str) -> String {
     s.to_lowercase()
         .chars()
         .map(|c| match c {
@@ -86,7 +133,7 @@ fn normalize_string(s: &str) -> String {
 
 /// Fetch ISBN and Publisher from OpenLibrary edition API when search results don't include them
 async fn fetch_edition_extras(edition_key: &str) -> Option<(String, Option<String>)> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(3))
         .build()
         .ok()?;
@@ -122,7 +169,7 @@ async fn fetch_edition_extras(edition_key: &str) -> Option<(String, Option<Strin
 
 /// Fetch ISBN and Publisher from OpenLibrary Work API (get first edition)
 async fn fetch_work_edition_extras(work_key: &str) -> Option<(String, Option<String>)> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(4))
         .build()
         .ok()?;
@@ -168,7 +215,7 @@ async fn fetch_work_edition_extras(work_key: &str) -> Option<(String, Option<Str
 /// Fetch description from an OpenLibrary Work.
 /// Handles both plain string and `{type, value}` object formats.
 pub async fn fetch_work_description(work_key: &str) -> Option<String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(3))
         .build()
         .ok()?;
@@ -208,10 +255,10 @@ pub async fn search_external(
 
     if enable_openlibrary {
         // Add timeout to prevent hanging when OpenLibrary is down
-        let client = reqwest::Client::builder()
+        let client = crate::services::network_mode::client_builder()
             .timeout(std::time::Duration::from_secs(12))
             .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+            .unwrap_or_else(|_| crate::services::network_mode::client());
 
         // Build Open Library Query
         // Always use `q=` (general search) for OpenLibrary because field-specific
@@ -297,7 +344,12 @@ pub async fn search_external(
                             digital_formats: None,
                             private: false,
                             page_count: None,
+                            edition: None,
+                            physical_format: None,
+                            dimensions: None,
                             loan_duration_days: None,
+                            archived: false,
+                            digital_source_url: None,
                         };
                         books.push(book);
                     }
@@ -310,15 +362,25 @@ pub async fn search_external(
 }
 
 /// Public endpoint for Open Library search (proxy to avoid CORS issues)
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct OpenLibraryQuery {
     title: Option<String>,
     author: Option<String>,
     subject: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/integrations/openlibrary/search",
+    tag = "integrations",
+    params(OpenLibraryQuery),
+    responses(
+        (status = 200, description = "Open Library results"),
+        (status = 502, description = "Open Library unreachable")
+    )
+)]
 pub async fn search_openlibrary(Query(params): Query<OpenLibraryQuery>) -> impl IntoResponse {
-    let client = reqwest::Client::new();
+    let client = crate::services::network_mode::client();
 
     // Build query parameters for Open Library
     let mut query_params = vec![("limit", "20".to_string())];
@@ -366,7 +428,7 @@ pub async fn search_openlibrary(Query(params): Query<OpenLibraryQuery>) -> impl
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct UnifiedSearchQuery {
     pub q: Option<String>,
     pub title: Option<String>,
@@ -374,10 +436,19 @@ pub struct UnifiedSearchQuery {
     pub publisher: Option<String>,
     pub subject: Option<String>,
     pub lang: Option<String>, // User's preferred language (e.g., "fr", "en")
-    pub source: Option<String>, // Filter to specific source: "inventaire", "bnf", "openlibrary", "google_books" or comma-separated
+    pub source: Option<String>, // Filter to specific source: "inventaire", "bnf", "openlibrary", "google_books", "sudoc" or comma-separated
     pub autocomplete: Option<bool>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/integrations/search_unified",
+    tag = "integrations",
+    params(UnifiedSearchQuery),
+    responses(
+        (status = 200, description = "Merged, deduplicated results from every enabled source")
+    )
+)]
 pub async fn search_unified(
     State(db): State<DatabaseConnection>,
     Query(params): Query<UnifiedSearchQuery>,
@@ -391,9 +462,11 @@ pub async fn search_unified(
     // Load profile config to check enabled providers and API keys
     let (
         mut enable_inventaire,
-        enable_bnf,
+        mut enable_bnf,
         mut enable_openlibrary,
         mut enable_google_books,
+        sudoc_available,
+        mut enable_sudoc,
         google_books_api_key,
     ) = match ProfileEntity::find_by_id(1).one(&db).await {
         Ok(Some(profile_model)) => {
@@ -409,24 +482,31 @@ pub async fn search_unified(
                 .and_then(|s| serde_json::from_str(s).ok())
                 .unwrap_or_default();
             let gb_key = api_keys.get("google_books").cloned();
-            (inv, bnf, ol, gb, gb_key)
+            // SUDOC (French academic union catalogue) comes with professional
+            // cataloguing, and is searched by default for librarians only.
+            let sudoc_available =
+                crate::modules::registry::FeatureModule::ProCataloguing.is_enabled_in(&modules);
+            let sudoc = sudoc_available
+                && profile_model.profile_type == "librarian"
+                && !modules.contains(&"disable_fallback:sudoc".to_string());
+            (inv, bnf, ol, gb, sudoc_available, sudoc, gb_key)
         }
-        _ => (true, true, true, false, None),
+        _ => (true, true, true, false, true, false, None),
     };
 
     let is_autocomplete = params.autocomplete.unwrap_or(false);
     let mut search_timeout = std::time::Duration::from_secs(12);
 
-    // Separate BNF flags: SPARQL (slow) vs SRU (fast, better metadata)
-    let mut enable_bnf_sparql = enable_bnf;
-    let mut enable_bnf_sru = enable_bnf;
+    // BNF tries SRU (fast, better metadata) and falls back to SPARQL (slow)
+    let mut bnf_sparql_fallback = true;
 
     if is_autocomplete {
-        // Always disable slow BNF SPARQL in autocomplete
-        enable_bnf_sparql = false;
+        // Always disable the slow BNF SPARQL fallback and SUDOC in autocomplete
+        bnf_sparql_fallback = false;
+        enable_sudoc = false;
         search_timeout = std::time::Duration::from_secs(4);
 
-        // Enable BNF SRU only for multi-word queries (≥3 raw words)
+        // Enable BNF only for multi-word queries (≥3 raw words)
         // where niche French titles are likely missing from Inventaire/OpenLibrary.
         // Count raw words (not significant words) because French stop words like
         // "pour", "la" still indicate a specific multi-word title query
@@ -437,7 +517,7 @@ pub async fn search_unified(
             .or(params.q.as_deref())
             .unwrap_or("");
         let raw_word_count = raw_q.split_whitespace().filter(|w| w.len() > 1).count();
-        enable_bnf_sru = enable_bnf && raw_word_count >= 3;
+        enable_bnf = enable_bnf && raw_word_count >= 3;
     }
 
     // Apply source filter if provided (overrides profile settings)
@@ -445,11 +525,9 @@ pub async fn search_unified(
         let sources: Vec<&str> = filter.split(',').map(|s| s.trim()).collect();
         // When user explicitly selects sources, use ONLY those (truly override profile)
         enable_inventaire = sources.iter().any(|s| s.eq_ignore_ascii_case("inventaire"));
-        let bnf_selected = sources
+        enable_bnf = sources
             .iter()
             .any(|s| s.eq_ignore_ascii_case("bnf") || s.eq_ignore_ascii_case("data.bnf.fr"));
-        enable_bnf_sparql = bnf_selected;
-        enable_bnf_sru = bnf_selected;
         enable_openlibrary = sources.iter().any(|s| {
             s.eq_ignore_ascii_case("openlibrary") || s.eq_ignore_ascii_case("open library")
         });
@@ -458,6 +536,7 @@ pub async fn search_unified(
                 || s.eq_ignore_ascii_case("google")
                 || s.eq_ignore_ascii_case("googlebooks")
         });
+        enable_sudoc = sudoc_available && sources.iter().any(|s| s.eq_ignore_ascii_case("sudoc"));
     }
 
     // 1. Build Query String for Inventaire (General Search)
@@ -490,13 +569,15 @@ pub async fn search_unified(
         .map(|l| l.split('-').next().unwrap_or(l).trim().to_string())
         .filter(|l| !l.is_empty());
 
-    // 3. Execute Searches in Parallel (Inventaire, BNF, OpenLibrary, BNF SRU)
+    // 3. Execute Searches in Parallel (Inventaire, OpenLibrary, BNF, Google Books, SUDOC)
     // We clone necessary data for each async task to avoid borrow checker issues with async blocks
     let inv_query_str = final_inv_query.clone();
     let bnf_query_str = final_inv_query.clone();
-    let bnf_sru_query_str = final_inv_query.clone();
-    let bnf_sru_title = params.title.clone();
-    let bnf_sru_author = params.author.clone();
+    let bnf_title = params.title.clone();
+    let bnf_author = params.author.clone();
+    let sudoc_query_str = final_inv_query.clone();
+    let sudoc_title = params.title.clone();
+    let sudoc_author = params.author.clone();
     let db_clone = db.clone();
 
     // Construct SearchQuery for search_external
@@ -511,6 +592,7 @@ pub async fn search_unified(
         subjects: params.subject.clone(),
         sources: None,
         autocomplete: params.autocomplete,
+        collapse_editions: None,
     };
     // Clone search_query for the tasks
     let ol_query = search_query.clone();
@@ -534,7 +616,7 @@ pub async fn search_unified(
 
     // Execute ALL sources in parallel with individual error isolation
     // This ensures one slow/failing source doesn't block or crash others
-    let (inv_res, ol_res, bnf_res, bnf_sru_res, gb_res) = tokio::join!(
+    let (inv_res, ol_res, bnf_res, gb_res, sudoc_res) = tokio::join!(
         // Task 1: Inventaire (wrapped in timeout to prevent blocking)
         async move {
             if enable_inventaire && !inv_query_str.trim().is_empty() {
@@ -574,46 +656,29 @@ pub async fn search_unified(
                 Vec::new()
             }
         },
-        // Task 3: BNF SPARQL (wrapped in timeout for extra safety)
-        async move {
-            if enable_bnf_sparql && !bnf_query_str.trim().is_empty() {
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(8),
-                    crate::modules::integrations::bnf::search_bnf(&bnf_query_str),
-                )
-                .await
-                {
-                    Ok(result) => result,
-                    Err(_) => Ok(Vec::new()),
-                }
-            } else {
-                Ok(Vec::new())
-            }
-        },
-        // Task 4: BNF SRU (catalogue.bnf.fr - better coverage for recent French books)
+        // Task 3: BNF (catalogue.bnf.fr SRU, data.bnf.fr SPARQL as fallback)
         async move {
-            if enable_bnf_sru
-                && (!bnf_sru_query_str.trim().is_empty()
-                    || bnf_sru_title.is_some()
-                    || bnf_sru_author.is_some())
+            if enable_bnf
+                && (!bnf_query_str.trim().is_empty() || bnf_title.is_some() || bnf_author.is_some())
             {
                 // In autocomplete, use tighter timeout and pass query as title hint
                 // so SRU searches bib.title (indexed) instead of bib.anywhere (full-text)
-                let (sru_timeout, sru_title) = if is_autocomplete {
-                    let title_hint = bnf_sru_title
+                let (bnf_timeout, bnf_title_hint) = if is_autocomplete {
+                    let title_hint = bnf_title
                         .as_deref()
-                        .or(Some(bnf_sru_query_str.as_str()))
+                        .or(Some(bnf_query_str.as_str()))
                         .filter(|s| !s.trim().is_empty());
                     (std::time::Duration::from_secs(4), title_hint)
                 } else {
-                    (std::time::Duration::from_secs(8), bnf_sru_title.as_deref())
+                    (search_timeout, bnf_title.as_deref())
                 };
                 match tokio::time::timeout(
-                    sru_timeout,
-                    crate::modules::integrations::bnf::search_bnf_sru(
-                        &bnf_sru_query_str,
-                        sru_title,
-                        bnf_sru_author.as_deref(),
+                    bnf_timeout,
+                    crate::modules::integrations::bnf::search(
+                        &bnf_query_str,
+                        bnf_title_hint,
+                        bnf_author.as_deref(),
+                        bnf_sparql_fallback,
                     ),
                 )
                 .await
@@ -625,7 +690,7 @@ pub async fn search_unified(
                 Ok(Vec::new())
             }
         },
-        // Task 5: Google Books (wrapped in timeout)
+        // Task 4: Google Books (wrapped in timeout)
         async move {
             if run_gb {
                 tokio::time::timeout(
@@ -637,6 +702,30 @@ pub async fn search_unified(
             } else {
                 crate::modules::integrations::google_books::GoogleBooksSearchResult::default()
             }
+        },
+        // Task 5: SUDOC SRU (academic libraries, theses and scholarly works)
+        async move {
+            if enable_sudoc
+                && (!sudoc_query_str.trim().is_empty()
+                    || sudoc_title.is_some()
+                    || sudoc_author.is_some())
+            {
+                match tokio::time::timeout(
+                    std::time::Duration::from_secs(8),
+                    crate::modules::integrations::sudoc::search_sudoc_sru(
+                        &sudoc_query_str,
+                        sudoc_title.as_deref(),
+                        sudoc_author.as_deref(),
+                    ),
+                )
+                .await
+                {
+                    Ok(result) => result,
+                    Err(_) => Ok(Vec::new()),
+                }
+            } else {
+                Ok(Vec::new())
+            }
         }
     );
 
@@ -685,13 +774,19 @@ pub async fn search_unified(
                 available_copies: None,
                 private: None,
                 page_count: None,
+                edition: None,
+                physical_format: None,
+                dimensions: None,
                 loan_duration_days: None,
+                archived: None,
+                digital_source_url: None,
                 added_at: None,
                 updated_at: None,
                 hub_cover_upload_failed_at: None,
                 // A search result is not in the library: it has no copies.
                 is_borrowed: None,
                 is_lent: None,
+                related: None,
             };
             results.push(book);
         }
@@ -699,69 +794,14 @@ pub async fn search_unified(
     }
 
     // Process BNF Results
+    // A book another source already returned is replaced only when BNF has
+    // strictly more data for it (ISBN or cover).
     match bnf_res {
         Ok(ref bnf_results) => {
             for bnf_book in bnf_results {
-                let book = book::Book {
-                    id: None,
-                    title: bnf_book.title.clone(),
-                    isbn: bnf_book.isbn.clone(),
-                    publisher: bnf_book.publisher.clone(),
-                    publication_year: bnf_book.publication_year,
-                    summary: bnf_book.description.clone(),
-                    dewey_decimal: None,
-                    lcc: None,
-                    subjects: None,
-                    marc_record: None,
-                    cataloguing_notes: None,
-                    source_data: Some(
-                        serde_json::json!({
-                            "source": "bnf",
-                            "bnf_uri": bnf_book.bnf_uri,
-                            "languages": ["fr"]
-                        })
-                        .to_string(),
-                    ),
-                    shelf_position: None,
-                    reading_status: Some("to_read".to_string()),
-                    source: Some("BNF".to_string()),
-                    author: bnf_book.author.clone(),
-                    authors: bnf_book.author.clone().map(|a| vec![a]),
-                    cover_url: bnf_book.cover_url.clone(),
-                    large_cover_url: None,
-                    finished_reading_at: None,
-                    started_reading_at: None,
-                    user_rating: None,
-                    owned: Some(true),
-                    price: None,
-                    language: Some("fr".to_string()), // BNF is French National Library
-                    digital_formats: None,
-                    available_copies: None,
-                    private: None,
-                    page_count: None,
-                    loan_duration_days: None,
-                    added_at: None,
-                    updated_at: None,
-                    hub_cover_upload_failed_at: None,
-                    // A search result is not in the library: it has no copies.
-                    is_borrowed: None,
-                    is_lent: None,
-                };
-                results.push(book);
-            }
-        }
-        Err(_e) => (),
-    }
-
-    // Process BNF SRU Results (catalogue.bnf.fr)
-    // SRU has richer metadata (ISBN, cover, publisher) than SPARQL, so when both
-    // return the same book we prefer SRU and replace the SPARQL entry.
-    match bnf_sru_res {
-        Ok(ref bnf_sru_results) => {
-            for bnf_book in bnf_sru_results {
                 // Dedup: check ISBN match OR normalized title+author match
-                let sru_title_norm = normalize_string(&bnf_book.title);
-                let sru_author_norm = normalize_string(bnf_book.author.as_deref().unwrap_or(""));
+                let bnf_title_norm = normalize_string(&bnf_book.title);
+                let bnf_author_norm = normalize_string(bnf_book.author.as_deref().unwrap_or(""));
 
                 // Check if a duplicate already exists
                 let dup_idx = results.iter().position(|b| {
@@ -771,32 +811,37 @@ pub async fn search_unified(
                     {
                         return true;
                     }
-                    // Title+author match (normalized) — catches SPARQL entries without ISBN
+                    // Title+author match (normalized) — catches entries without ISBN
                     let existing_title = normalize_string(&b.title);
                     let existing_author = normalize_string(b.author.as_deref().unwrap_or(""));
-                    !sru_title_norm.is_empty()
-                        && existing_title.contains(&sru_title_norm)
-                        && (sru_author_norm.is_empty()
-                            || existing_author.contains(&sru_author_norm)
-                            || sru_author_norm.contains(&existing_author))
+                    !bnf_title_norm.is_empty()
+                        && existing_title.contains(&bnf_title_norm)
+                        && (bnf_author_norm.is_empty()
+                            || existing_author.contains(&bnf_author_norm)
+                            || bnf_author_norm.contains(&existing_author))
                 });
 
                 if let Some(idx) = dup_idx {
-                    // SRU has better metadata — replace the existing entry only if
-                    // the SRU result has strictly more data (ISBN or cover)
+                    // Replace the existing entry only if the BNF result has
+                    // strictly more data (ISBN or cover)
                     let existing = &results[idx];
-                    let sru_has_more = (bnf_book.isbn.is_some() && existing.isbn.is_none())
+                    let bnf_has_more = (bnf_book.isbn.is_some() && existing.isbn.is_none())
                         || (bnf_book.cover_url.is_some() && existing.cover_url.is_none());
-                    if !sru_has_more {
+                    if !bnf_has_more {
                         continue; // Existing entry is at least as good
                     }
                     results.remove(idx);
-                    // Fall through to insert the SRU version below
+                    // Fall through to insert the BNF version below
                 } else if let Some(ref isbn) = bnf_book.isbn
                     && results.iter().any(|b| b.isbn.as_ref() == Some(isbn))
                 {
                     continue;
                 }
+                // Which BNF endpoint answered: "bnf-sru" or "bnf" (SPARQL).
+                let source_tag = match bnf_book.source {
+                    BnfSource::Sru => "bnf-sru",
+                    BnfSource::Sparql => "bnf",
+                };
                 let book = book::Book {
                     id: None,
                     title: bnf_book.title.clone(),
@@ -811,8 +856,9 @@ pub async fn search_unified(
                     cataloguing_notes: None,
                     source_data: Some(
                         serde_json::json!({
-                            "source": "bnf-sru",
+                            "source": source_tag,
                             "bnf_uri": bnf_book.bnf_uri,
+                            "bnf_work": bnf_book.work_id,
                             "languages": ["fr"]
                         })
                         .to_string(),
@@ -833,14 +879,20 @@ pub async fn search_unified(
                     digital_formats: None,
                     available_copies: None,
                     private: None,
-                    page_count: None,
+                    page_count: bnf_book.page_count.map(|p| p as i32),
+                    edition: bnf_book.edition.clone(),
+                    physical_format: None,
+                    dimensions: bnf_book.dimensions.clone(),
                     loan_duration_days: None,
+                    archived: None,
+                    digital_source_url: bnf_book.digital_source_url.clone(),
                     added_at: None,
                     updated_at: None,
                     hub_cover_upload_failed_at: None,
                     // A search result is not in the library: it has no copies.
                     is_borrowed: None,
                     is_lent: None,
+                    related: None,
                 };
                 results.push(book);
             }
@@ -848,6 +900,79 @@ pub async fn search_unified(
         Err(_e) => (),
     }
 
+    // Process SUDOC Results. Skipped when BNF or Inventaire already returned
+    // the same book: SUDOC brings classification (Dewey, RAMEAU) rather than
+    // covers.
+    if let Ok(ref sudoc_results) = sudoc_res {
+        for sudoc_book in sudoc_results {
+            let title_norm = normalize_string(&sudoc_book.title);
+            let author_norm = normalize_string(sudoc_book.author.as_deref().unwrap_or(""));
+            let is_dup = results.iter().any(|b| {
+                if let (Some(existing_isbn), Some(new_isbn)) = (&b.isbn, &sudoc_book.isbn) {
+                    return existing_isbn == new_isbn;
+                }
+                let existing_author = normalize_string(b.author.as_deref().unwrap_or(""));
+                normalize_string(&b.title) == title_norm
+                    && (author_norm.is_empty() || existing_author.contains(&author_norm))
+            });
+            if is_dup {
+                continue;
+            }
+            let book = book::Book {
+                id: None,
+                title: sudoc_book.title.clone(),
+                isbn: sudoc_book.isbn.clone(),
+                publisher: sudoc_book.publisher.clone(),
+                publication_year: sudoc_book.publication_year,
+                summary: sudoc_book.summary.clone(),
+                dewey_decimal: sudoc_book.dewey.clone(),
+                lcc: None,
+                subjects: Some(sudoc_book.subjects.clone()).filter(|s| !s.is_empty()),
+                marc_record: None,
+                cataloguing_notes: None,
+                source_data: Some(
+                    serde_json::json!({
+                        "source": "sudoc",
+                        "ppn": sudoc_book.ppn,
+                        "sudoc_uri": format!("https://www.sudoc.fr/{}", sudoc_book.ppn),
+                    })
+                    .to_string(),
+                ),
+                shelf_position: None,
+                reading_status: Some("to_read".to_string()),
+                source: Some("SUDOC".to_string()),
+                author: sudoc_book.author.clone(),
+                authors: sudoc_book.author.clone().map(|a| vec![a]),
+                cover_url: None,
+                large_cover_url: None,
+                finished_reading_at: None,
+                started_reading_at: None,
+                user_rating: None,
+                owned: Some(true),
+                price: None,
+                language: None,
+                digital_formats: None,
+                available_copies: None,
+                private: None,
+                page_count: None,
+                edition: None,
+                physical_format: None,
+                dimensions: None,
+                loan_duration_days: None,
+                archived: None,
+                digital_source_url: None,
+                added_at: None,
+                updated_at: None,
+                hub_cover_upload_failed_at: None,
+                // A search result is not in the library: it has no copies.
+                is_borrowed: None,
+                is_lent: None,
+                related: None,
+            };
+            results.push(book);
+        }
+    }
+
     // Process OpenLibrary Results - PARALLELIZED
     // Check time budget: skip expensive HTTP enrichment if search phase was slow.
     // Flutter client has a 15s receiveTimeout — leave at least 4s for enrichment.
@@ -1313,7 +1438,9 @@ fn best_phrase_similarity(title: &str, query: &str) -> f64 {
     best
 }
 
-fn calculate_relevance(
+/// Relevance score of a unified-search result for the query. Public for the
+/// `hot_paths` benchmark.
+pub fn calculate_relevance(
     book: &book::Book,
     q_author: &str,
     q_title: &str,
@@ -1647,6 +1774,15 @@ fn resolve_backend_binary_path() -> String {
 ///
 /// The helper no longer opens the database, so no `DATABASE_URL` is emitted: the
 /// only environment it needs is the token used to authenticate to the running app.
+#[utoipa::path(
+    get,
+    path = "/api/integrations/mcp-config",
+    tag = "integrations",
+    responses(
+        (status = 200, description = "MCP client configuration for this install"),
+        (status = 503, description = "MCP token unavailable")
+    )
+)]
 pub async fn mcp_config(_guard: LoopbackNoBrowser) -> impl IntoResponse {
     let binary_path = resolve_backend_binary_path();
 
@@ -1690,6 +1826,65 @@ pub async fn mcp_config(_guard: LoopbackNoBrowser) -> impl IntoResponse {
     }))).into_response()
 }
 
+/// GET /api/integrations/usage - Today's calls and budget of each external
+/// metadata provider, with the last days
+#[utoipa::path(
+    get,
+    path = "/api/integrations/usage",
+    tag = "integrations",
+    responses(
+        (status = 200, description = "One entry per provider (`providers`)", body = [ProviderUsage]),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn get_usage(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    match api_quota::usage(&db).await {
+        Ok(providers) => (StatusCode::OK, Json(json!({ "providers": providers }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct QuotasUpdate {
+    /// Daily call limit per provider; `null` lifts it. Providers left out
+    /// keep theirs.
+    pub quotas: std::collections::HashMap<api_quota::Provider, Option<u64>>,
+}
+
+/// PUT /api/integrations/quotas - Set the daily call quotas of external
+/// metadata providers
+#[utoipa::path(
+    put,
+    path = "/api/integrations/quotas",
+    tag = "integrations",
+    request_body = QuotasUpdate,
+    responses(
+        (status = 200, description = "Quotas saved; usage as reported by `/api/integrations/usage`", body = [ProviderUsage]),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn set_quotas(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<QuotasUpdate>,
+) -> impl IntoResponse {
+    let result = match api_quota::set_limits(&db, &body.quotas).await {
+        Ok(()) => api_quota::usage(&db).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(providers) => (StatusCode::OK, Json(json!({ "providers": providers }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,331 @@
+//! Kiosk mode: token management (owner) and the lending-desk endpoints a
+//! kiosk token opens (see `services::kiosk`).
+//!
+//! The `/kiosk/search`, `/kiosk/checkout` and `/kiosk/checkin` endpoints are
+//! reachable from the LAN and require `Authorization: Bearer <kiosk token>`.
+//! Token management is owner-only.
+
+use axum::{
+    Json, async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use sea_orm::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::infrastructure::AppState;
+use crate::models::{book, copy};
+use crate::services::kiosk::{self, KioskError};
+
+/// Most books returned by one kiosk search.
+const SEARCH_LIMIT: u64 = 25;
+
+/// Extractor admitting a request that carries a valid kiosk token.
+pub struct KioskSession;
+
+#[async_trait]
+impl FromRequestParts<AppState> for KioskSession {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "Missing kiosk token" })),
+            ))?;
+
+        match kiosk::authenticate(state.db(), token).await {
+            Ok(Some(_)) => Ok(KioskSession),
+            Ok(None) => Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "Invalid kiosk token" })),
+            )),
+            Err(e) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )),
+        }
+    }
+}
+
+fn kiosk_error(e: KioskError) -> (StatusCode, String) {
+    let status = match e {
+        KioskError::UnknownCode => StatusCode::NOT_FOUND,
+        KioskError::NoCopy(_) | KioskError::Ambiguous => StatusCode::CONFLICT,
+        KioskError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+fn db_error(e: DbErr) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": e.to_string() })),
+    )
+        .into_response()
+}
+
+/// Request body for creating a kiosk token
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateKioskTokenRequest {
+    /// Label of the device, e.g. "Front desk tablet"
+    pub name: String,
+}
+
+/// GET /api/kiosk/tokens - Kiosk tokens (without the tokens themselves)
+#[utoipa::path(
+    get,
+    path = "/api/kiosk/tokens",
+    tag = "kiosk",
+    responses(
+        (status = 200, description = "Kiosk tokens, oldest first")
+    )
+)]
+pub async fn list_tokens(State(state): State<AppState>) -> Response {
+    match kiosk::list_tokens(state.db()).await {
+        Ok(tokens) => (StatusCode::OK, Json(json!({ "tokens": tokens }))).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// POST /api/kiosk/tokens - Create a kiosk token, shown this once
+#[utoipa::path(
+    post,
+    path = "/api/kiosk/tokens",
+    tag = "kiosk",
+    request_body = CreateKioskTokenRequest,
+    responses(
+        (status = 201, description = "Token created; `token` is not shown again"),
+        (status = 400, description = "Blank name")
+    )
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    Json(req): Json<CreateKioskTokenRequest>,
+) -> Response {
+    if req.name.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "name must not be blank" })),
+        )
+            .into_response();
+    }
+    match kiosk::create_token(state.db(), &req.name).await {
+        Ok((created, token)) => (
+            StatusCode::CREATED,
+            Json(json!({ "kiosk": created, "token": token })),
+        )
+            .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// DELETE /api/kiosk/tokens/:id - Revoke a kiosk token
+#[utoipa::path(
+    delete,
+    path = "/api/kiosk/tokens/{id}",
+    tag = "kiosk",
+    params(("id" = String, Path, description = "Kiosk token id")),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "No such token")
+    )
+)]
+pub async fn revoke_token(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match kiosk::revoke_token(state.db(), &id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Kiosk token not found" })),
+        )
+            .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Query parameters of the kiosk search
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct KioskSearchQuery {
+    /// Words of the title, or an ISBN
+    pub q: String,
+}
+
+/// GET /api/kiosk/search - Search the shelves (kiosk token)
+#[utoipa::path(
+    get,
+    path = "/api/kiosk/search",
+    tag = "kiosk",
+    params(KioskSearchQuery),
+    responses(
+        (status = 200, description = "Owned, shared books with their available copies"),
+        (status = 401, description = "Missing or invalid kiosk token")
+    )
+)]
+pub async fn search(
+    _kiosk: KioskSession,
+    State(state): State<AppState>,
+    Query(query): Query<KioskSearchQuery>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db();
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Ok(Json(json!({ "books": [] })));
+    }
+
+    let books = book::Entity::find()
+        .filter(book::Column::Owned.eq(true))
+        .filter(crate::models::Book::shared_condition(db).await)
+        .filter(
+            Condition::any()
+                .add(book::Column::Title.contains(q))
+                .add(book::Column::Isbn.eq(q)),
+        )
+        .order_by_asc(book::Column::Title)
+        .limit(SEARCH_LIMIT)
+        .all(db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut results = Vec::with_capacity(books.len());
+    for b in books {
+        let available = copy::Entity::find()
+            .filter(copy::Column::BookId.eq(b.id.clone()))
+            .filter(copy::Column::Status.eq("available"))
+            .count(db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        results.push(json!({
+            "id": b.id,
+            "title": b.title,
+            "isbn": b.isbn,
+            "cover_url": b.cover_url,
+            "available_copies": available,
+        }));
+    }
+
+    Ok(Json(json!({ "books": results })))
+}
+
+/// Request body of a kiosk check-out
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct KioskCheckoutRequest {
+    /// Scanned copy label or ISBN
+    pub code: String,
+    /// Name of the borrower, recorded as a quick contact
+    pub borrower_name: String,
+}
+
+/// POST /api/kiosk/checkout - Lend a scanned copy (kiosk token)
+#[utoipa::path(
+    post,
+    path = "/api/kiosk/checkout",
+    tag = "kiosk",
+    request_body = KioskCheckoutRequest,
+    responses(
+        (status = 200, description = "Copy lent; due date from the loan policy"),
+        (status = 400, description = "Blank name or copy not available"),
+        (status = 401, description = "Missing or invalid kiosk token"),
+        (status = 404, description = "Unknown code"),
+        (status = 409, description = "No available copy of this book")
+    )
+)]
+pub async fn checkout(
+    _kiosk: KioskSession,
+    State(state): State<AppState>,
+    Json(req): Json<KioskCheckoutRequest>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db();
+    let name = req.borrower_name.trim();
+    if name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "borrower_name must not be blank".to_string(),
+        ));
+    }
+
+    let found = kiosk::copy_for_code(db, &req.code, "available")
+        .await
+        .map_err(kiosk_error)?;
+    let copy = crate::api::loan::available_copy(db, &found.id).await?;
+    let contact = crate::api::loan::quick_contact(db, name, copy.library_id).await?;
+    let title = book::Entity::find_by_id(copy.book_id.clone())
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|b| b.title);
+
+    let loan = crate::models::loan::LoanDto {
+        id: None,
+        copy_id: copy.id.clone(),
+        contact_id: contact.id,
+        library_id: copy.library_id,
+        loan_date: crate::utils::time_zone::library_zone(db)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .today()
+            .format("%Y-%m-%d")
+            .to_string(),
+        due_date: String::new(),
+        return_date: None,
+        status: None,
+        notes: None,
+    };
+    let saved = crate::api::loan::lend_copy(&state, copy, loan).await?;
+
+    Ok(Json(json!({
+        "loan_id": saved.id,
+        "book_title": title,
+        "borrower": contact.name,
+        "due_date": saved.due_date,
+    })))
+}
+
+/// Request body of a kiosk check-in
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct KioskCheckinRequest {
+    /// Scanned copy label or ISBN
+    pub code: String,
+}
+
+/// POST /api/kiosk/checkin - Take a scanned copy back (kiosk token)
+#[utoipa::path(
+    post,
+    path = "/api/kiosk/checkin",
+    tag = "kiosk",
+    request_body = KioskCheckinRequest,
+    responses(
+        (status = 200, description = "Loan returned, copy available again"),
+        (status = 401, description = "Missing or invalid kiosk token"),
+        (status = 404, description = "Unknown code, or copy not on loan"),
+        (status = 409, description = "No copy of this book is out, or several are")
+    )
+)]
+pub async fn checkin(
+    _kiosk: KioskSession,
+    State(state): State<AppState>,
+    Json(req): Json<KioskCheckinRequest>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let copy = kiosk::copy_for_code(state.db(), &req.code, "loaned")
+        .await
+        .map_err(kiosk_error)?;
+    let loan = kiosk::active_loan(state.db(), &copy.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            "This copy is not out on loan".to_string(),
+        ))?;
+
+    // The owner's return path: copy status, notification, P2P follow-up.
+    crate::api::loan::return_loan(State(state), Path(loan.id)).await
+}
@@ -1,10 +1,24 @@
-use axum::{Json, extract::State, http::StatusCode};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde_json::{Value, json};
 
 use crate::models::LibraryConfig;
 use crate::models::library_config::{ActiveModel, Entity as LibraryConfigEntity};
+use crate::services::config_history::{self, ConfigVersion, RollbackError};
 
+#[utoipa::path(
+    get,
+    path = "/api/library/config",
+    tag = "library",
+    responses(
+        (status = 200, description = "Library name, description, tags and location settings")
+    )
+)]
 pub async fn get_config(State(db): State<DatabaseConnection>) -> Result<Json<Value>, StatusCode> {
     // Get the first (and only) library config
     let config = LibraryConfigEntity::find()
@@ -45,15 +59,64 @@ pub async fn get_config(State(db): State<DatabaseConnection>) -> Result<Json<Val
             "name": "Unknown Library",
             "description": null,
             "tags": [],
+            "currency": crate::utils::money::DEFAULT_CURRENCY,
             "enabled_modules": enabled_modules
         }))),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/library/currencies",
+    tag = "library",
+    responses(
+        (status = 200, description = "Currencies the library can keep its prices, sales and fees in", body = [Currency])
+    )
+)]
+pub async fn list_currencies() -> Json<&'static [crate::utils::money::Currency]> {
+    Json(crate::utils::money::CURRENCIES)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/library/config",
+    tag = "library",
+    request_body = LibraryConfig,
+    responses(
+        (status = 200, description = "Saved configuration"),
+        (status = 400, description = "Unknown currency code or time zone")
+    )
+)]
 pub async fn update_config(
     State(db): State<DatabaseConnection>,
     Json(config): Json<LibraryConfig>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Json<Value>, Response> {
+    let currency = match config.currency.as_deref() {
+        Some(code) => match crate::utils::money::find(code) {
+            Some(c) => Some(c.code.to_string()),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("Unknown currency: {code}") })),
+                )
+                    .into_response());
+            }
+        },
+        None => None,
+    };
+    let timezone = match config.timezone.as_deref() {
+        Some(name) => match crate::utils::time_zone::parse_zone(name) {
+            Some(tz) => Some(tz.name().to_string()),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("Unknown time zone: {name}") })),
+                )
+                    .into_response());
+            }
+        },
+        None => None,
+    };
     let now = chrono::Utc::now();
     let tags_json = serde_json::to_string(&config.tags).unwrap_or_else(|_| "[]".to_string());
 
@@ -61,9 +124,10 @@ pub async fn update_config(
     let existing = LibraryConfigEntity::find()
         .one(&db)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let before = existing.clone();
 
-    if let Some(existing_config) = existing {
+    let saved = if let Some(existing_config) = existing {
         // Update existing
         let mut active: ActiveModel = existing_config.into();
         active.name = Set(config.name);
@@ -71,12 +135,18 @@ pub async fn update_config(
         active.tags = Set(tags_json);
         active.share_location = Set(Some(config.share_location));
         active.show_borrowed_books = Set(Some(config.show_borrowed_books));
+        if currency.is_some() {
+            active.currency = Set(currency);
+        }
+        if timezone.is_some() {
+            active.timezone = Set(timezone);
+        }
         active.updated_at = Set(now.to_rfc3339());
 
         active
             .update(&db)
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
     } else {
         // Create new
         let new_config = ActiveModel {
@@ -85,6 +155,8 @@ pub async fn update_config(
             tags: Set(tags_json),
             share_location: Set(Some(config.share_location)),
             show_borrowed_books: Set(Some(config.show_borrowed_books)),
+            currency: Set(currency),
+            timezone: Set(timezone),
             created_at: Set(now.to_rfc3339()),
             updated_at: Set(now.to_rfc3339()),
             ..Default::default()
@@ -93,10 +165,71 @@ pub async fn update_config(
         new_config
             .insert(&db)
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    };
+
+    // Settings replicate to paired devices (`services::device_sync`).
+    let _ =
+        crate::sync::log_operation(&db, "library_config", &saved.id.to_string(), "UPDATE", None)
+            .await;
+    if let Err(e) = config_history::record(&db, before.as_ref(), &saved, "api").await {
+        tracing::warn!("library config: cannot record version: {e}");
     }
 
     Ok(Json(json!({
         "message": "Library configuration updated successfully"
     })))
 }
+
+#[derive(Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct ConfigHistoryQuery {
+    /// 50 by default
+    pub limit: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/library/config/history",
+    tag = "library",
+    params(ConfigHistoryQuery),
+    responses(
+        (status = 200, description = "Saved versions of the configuration, newest first, with who saved them and what changed", body = [ConfigVersion])
+    )
+)]
+pub async fn get_config_history(
+    State(db): State<DatabaseConnection>,
+    Query(params): Query<ConfigHistoryQuery>,
+) -> Result<Json<Vec<ConfigVersion>>, StatusCode> {
+    let limit = params
+        .limit
+        .unwrap_or(config_history::DEFAULT_HISTORY_LIMIT);
+    config_history::history(&db, limit)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/library/config/rollback/{version}",
+    tag = "library",
+    params(("version" = i64, Path, description = "Version to bring back")),
+    responses(
+        (status = 200, description = "Configuration as restored, saved as a new version", body = LibraryConfig),
+        (status = 404, description = "Unknown version, or no configuration yet")
+    )
+)]
+pub async fn rollback_config(
+    State(db): State<DatabaseConnection>,
+    Path(version): Path<i64>,
+) -> Result<Json<LibraryConfig>, Response> {
+    match config_history::rollback(&db, version, "api").await {
+        Ok(saved) => Ok(Json(LibraryConfig::from(saved))),
+        Err(e @ (RollbackError::NotFound(_) | RollbackError::NoConfig)) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response()),
+        Err(RollbackError::Db(_)) => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    }
+}
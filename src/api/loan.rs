@@ -3,10 +3,10 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
 };
-use chrono::Local;
 use sea_orm::*;
 use serde::Deserialize;
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 
 use crate::infrastructure::AppState;
 use crate::models::book::Entity as Book;
@@ -14,13 +14,22 @@ use crate::models::contact::Entity as Contact;
 use crate::models::copy::{self, Entity as Copy};
 use crate::models::loan::{self, Entity as Loan};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct ListLoansQuery {
     pub library_id: Option<i32>,
     pub status: Option<String>,
     pub contact_id: Option<i32>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/loans",
+    tag = "loans",
+    params(ListLoansQuery),
+    responses(
+        (status = 200, description = "Loans with copy, book and contact details; `due_at` is the end of the due day in the library's time zone")
+    )
+)]
 pub async fn list_loans(
     State(db): State<DatabaseConnection>,
     Query(query): Query<ListLoansQuery>,
@@ -72,9 +81,28 @@ pub async fn list_loans(
         }
     }
 
+    // Overdue is reported only once the policy's grace period has run out.
+    let settings = {
+        use crate::domain::LoanSettingsRepository;
+        crate::infrastructure::SeaOrmLoanSettingsRepository::new(db.clone())
+            .get_settings()
+            .await
+            .unwrap_or_default()
+    };
+    let zone = crate::utils::time_zone::library_zone(&db)
+        .await
+        .unwrap_or(crate::utils::time_zone::LibraryZone::Host);
+    let today = zone.today();
+
     let result: Vec<Value> = loans_with_contacts
         .into_iter()
         .map(|(loan, contact)| {
+            let overdue = crate::services::loan_service::is_loan_overdue(
+                &settings,
+                &loan.status,
+                &loan.due_date,
+                today,
+            );
             let book = copy_book_map.get(&loan.copy_id);
             let contact_name = contact
                 .as_ref()
@@ -96,8 +124,10 @@ pub async fn list_loans(
                 "library_id": loan.library_id,
                 "loan_date": loan.loan_date,
                 "due_date": loan.due_date,
+                "due_at": zone.due_at(&loan.due_date),
                 "return_date": loan.return_date,
                 "status": loan.status,
+                "overdue": overdue,
                 "notes": loan.notes,
                 "contact_name": contact_name,
                 "book_title": book_title,
@@ -113,15 +143,37 @@ pub async fn list_loans(
     Ok(Json(json!({ "loans": result })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/loans",
+    tag = "loans",
+    request_body = LoanDto,
+    responses(
+        (status = 200, description = "Loan created and copy marked as loaned"),
+        (status = 400, description = "Copy not available"),
+        (status = 403, description = "Borrower's membership has lapsed"),
+        (status = 404, description = "Copy or contact not found")
+    )
+)]
 pub async fn create_loan(
-    State(db): State<DatabaseConnection>,
+    State(state): State<AppState>,
     Json(payload): Json<loan::LoanDto>,
 ) -> Result<Json<Value>, (StatusCode, String)> {
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let copy = available_copy(state.db(), &payload.copy_id).await?;
+    let saved_loan = lend_copy(&state, copy, payload).await?;
 
-    // 1. Check if copy exists and is available
-    let copy = Copy::find_by_id(payload.copy_id.clone())
-        .one(&db)
+    Ok(Json(
+        json!({ "loan": saved_loan, "message": "Loan created successfully" }),
+    ))
+}
+
+/// The copy `copy_id`, if it can be lent right now.
+pub(crate) async fn available_copy(
+    db: &DatabaseConnection,
+    copy_id: &str,
+) -> Result<copy::Model, (StatusCode, String)> {
+    let copy = Copy::find_by_id(copy_id.to_owned())
+        .one(db)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "Copy not found".to_string()))?;
@@ -132,46 +184,191 @@ pub async fn create_loan(
             format!("Copy is currently {}", copy.status),
         ));
     }
+    Ok(copy)
+}
 
-    // 2. Create Loan
-    let new_loan = loan::ActiveModel {
-        copy_id: Set(payload.copy_id),
-        contact_id: Set(payload.contact_id),
-        library_id: Set(payload.library_id),
-        loan_date: Set(payload.loan_date),
-        due_date: Set(payload.due_date),
-        return_date: Set(None),
-        status: Set("active".to_owned()),
-        notes: Set(payload.notes),
-        created_at: Set(now.clone()),
-        updated_at: Set(now),
-        ..Default::default()
+/// Record the loan of an available copy and mark the copy as loaned.
+pub(crate) async fn lend_copy(
+    state: &AppState,
+    copy: copy::Model,
+    payload: loan::LoanDto,
+) -> Result<crate::domain::Loan, (StatusCode, String)> {
+    use crate::services::loan_service::{self, LoanRepos};
+
+    let db = state.db();
+    let repos = LoanRepos::from_state(state);
+    let now = crate::utils::time_zone::library_zone(db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .now_local_string();
+
+    if let Some(expired) = crate::services::membership::lapsed_membership(db, &payload.contact_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Membership expired on {expired}; renew it before lending"),
+        ));
+    }
+
+    // No due date from the client: apply the loan policy for this borrower.
+    let due_date = if payload.due_date.trim().is_empty() {
+        loan_service::policy_due_date(repos, &copy.book_id, &payload.contact_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:?}", e)))?
+    } else {
+        payload.due_date
     };
 
-    let saved_loan = new_loan
-        .insert(&db)
+    let saved_loan = repos
+        .loans
+        .create(crate::domain::NewLoan {
+            copy_id: payload.copy_id,
+            contact_id: payload.contact_id,
+            library_id: payload.library_id,
+            loan_date: payload.loan_date,
+            due_date,
+            notes: payload.notes,
+            // Snapshot the copy's terms: later edits to the copy leave this loan alone.
+            lending_terms: copy.lending_terms.clone(),
+            created_at: now,
+        })
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    // 3. Update Copy status to 'loaned'
     let mut copy_active: copy::ActiveModel = copy.into();
     copy_active.status = Set("loaned".to_owned());
     copy_active
-        .update(&db)
+        .update(db)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(
-        json!({ "loan": saved_loan, "message": "Loan created successfully" }),
-    ))
+    Ok(saved_loan)
+}
+
+/// Request body for lending a copy to someone who is not a contact yet
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct QuickLoanRequest {
+    pub copy_id: String,
+    /// Free-text name of the borrower
+    pub borrower_name: String,
+    /// Defaults to today
+    pub loan_date: Option<String>,
+    /// Defaults to the loan policy
+    pub due_date: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/loans/quick",
+    tag = "loans",
+    request_body = QuickLoanRequest,
+    responses(
+        (status = 200, description = "Loan created, borrower recorded as a quick contact"),
+        (status = 400, description = "Blank name or copy not available"),
+        (status = 404, description = "Copy not found")
+    )
+)]
+pub async fn create_quick_loan(
+    State(state): State<AppState>,
+    Json(payload): Json<QuickLoanRequest>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let db = state.db();
+    let name = payload.borrower_name.trim();
+    if name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "borrower_name must not be blank".to_string(),
+        ));
+    }
+    let copy = available_copy(db, &payload.copy_id).await?;
+    let contact = quick_contact(db, name, copy.library_id).await?;
+
+    let loan = loan::LoanDto {
+        id: None,
+        copy_id: payload.copy_id,
+        contact_id: contact.id.clone(),
+        library_id: copy.library_id,
+        loan_date: match payload.loan_date {
+            Some(date) => date,
+            None => crate::utils::time_zone::library_zone(db)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .today()
+                .format("%Y-%m-%d")
+                .to_string(),
+        },
+        due_date: payload.due_date.unwrap_or_default(),
+        return_date: None,
+        status: None,
+        notes: payload.notes,
+    };
+    let saved_loan = lend_copy(&state, copy, loan).await?;
+
+    Ok(Json(json!({
+        "loan": saved_loan,
+        "contact": contact,
+        "message": "Loan created successfully"
+    })))
 }
 
+/// The quick contact named `name`, created on first use so that lending
+/// twice to the same person keeps a single ledger entry.
+pub(crate) async fn quick_contact(
+    db: &DatabaseConnection,
+    name: &str,
+    library_id: i32,
+) -> Result<crate::models::contact::Model, (StatusCode, String)> {
+    use crate::models::contact::{self, QUICK_CONTACT_TYPE};
+
+    let existing = Contact::find()
+        .filter(contact::Column::Type.eq(QUICK_CONTACT_TYPE))
+        .filter(contact::Column::IsActive.eq(true))
+        .filter(contact::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    contact::ActiveModel {
+        r#type: Set(QUICK_CONTACT_TYPE.to_string()),
+        name: Set(name.to_string()),
+        library_owner_id: Set(library_id),
+        is_active: Set(true),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/loans/{id}/return",
+    tag = "loans",
+    params(("id" = String, Path, description = "Loan id")),
+    responses(
+        (status = 200, description = "Loan returned, copy available again"),
+        (status = 400, description = "Loan already returned"),
+        (status = 404, description = "Loan not found")
+    )
+)]
 pub async fn return_loan(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<Value>, (StatusCode, String)> {
     let db = state.db().clone();
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = crate::utils::time_zone::library_zone(&db)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .now_local_string();
 
     // 1. Find Loan
     let loan = Loan::find_by_id(id.clone())
@@ -198,6 +395,10 @@ pub async fn return_loan(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    if let Err(e) = crate::services::fines::settle_returned_loan(&db, &updated_loan).await {
+        tracing::warn!("fines: could not settle loan {id}: {e}");
+    }
+
     // 3. Update Copy status to 'available'
     // First fetch the copy to get its full state
     let copy = Copy::find_by_id(loan.copy_id.clone())
@@ -215,6 +416,8 @@ pub async fn return_loan(
         .update(&db)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // Peers waitlisted for the book get the copy on hold.
+    crate::services::p2p_holds::spawn_serve(&state);
 
     // 4. Emit book_returned notification
     if let Ok(Some(book)) = Book::find_by_id(copy.book_id.clone()).one(&db).await {
@@ -328,7 +531,7 @@ pub async fn return_loan(
 
                                 // SSRF-safe client (bounded timeout, no redirects), matching
                                 // the twin plaintext fallback in `update_request_status`.
-                                match crate::api::peer::get_safe_client()
+                                match crate::api::peer::get_safe_client_for(&url)
                                     .put(&url)
                                     .json(&serde_json::json!({
                                         "status": "returned",
@@ -365,79 +568,261 @@ pub async fn return_loan(
     })))
 }
 
+/// Request body for renewing a loan
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct RenewLoanRequest {
+    /// New due date (YYYY-MM-DD). Omitted: the policy's loan period restarts
+    /// today, without ever bringing the due date forward.
+    #[serde(default)]
+    pub due_date: Option<String>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/loans/{id}/renew",
+    tag = "loans",
+    params(("id" = String, Path, description = "Loan ID")),
+    request_body = RenewLoanRequest,
+    responses(
+        (status = 200, description = "Loan renewed"),
+        (status = 400, description = "Loan already returned"),
+        (status = 404, description = "Loan not found")
+    )
+)]
+pub async fn renew_loan(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RenewLoanRequest>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    use crate::services::loan_service::{self, LoanRepos, ServiceError};
+
+    let renewed = loan_service::renew_loan(LoanRepos::from_state(&state), &id, payload.due_date)
+        .await
+        .map_err(|e| match e {
+            ServiceError::NotFound => (StatusCode::NOT_FOUND, "Loan not found".to_string()),
+            ServiceError::InvalidState(msg) => (StatusCode::BAD_REQUEST, msg),
+            ServiceError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        })?;
+
+    Ok(Json(json!({ "loan": renewed })))
+}
+
 // ── Loan Settings (Clean Architecture) ──────────────────────────────
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UpdateLoanSettingsPayload {
     pub default_loan_duration_days: i32,
     pub per_book_duration_enabled: bool,
     #[serde(default = "default_reminder_days")]
     pub reminder_days_before_due: i32,
+    /// Omitted = keep the current value (clients predating the loan policy).
+    pub grace_period_days: Option<i32>,
+    /// Omitted = keep the current map; `{}` clears it.
+    pub contact_type_durations: Option<BTreeMap<String, i32>>,
+    /// Omitted = keep the current value.
+    pub hold_expiry_days: Option<i32>,
+    /// Omitted = keep the current value.
+    pub request_expiry_days: Option<i32>,
+    /// Omitted = keep the current value.
+    pub resolved_request_retention_days: Option<i32>,
+    /// Omitted = keep the current value; 0 disables fines.
+    pub fine_per_day: Option<f64>,
+    /// Omitted = keep the current value; 0 means no cap.
+    pub max_fine: Option<f64>,
 }
 
 fn default_reminder_days() -> i32 {
     2
 }
 
-pub async fn get_loan_settings(
-    State(state): State<AppState>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let settings = state.loan_settings_repo.get_settings().await.map_err(|e| {
-        (
+fn settings_error(e: crate::domain::DomainError) -> (StatusCode, Json<Value>) {
+    match e {
+        crate::domain::DomainError::NotFound => {
+            (StatusCode::NOT_FOUND, Json(json!({ "error": "Not found" })))
+        }
+        e => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+        ),
+    }
+}
 
-    Ok(Json(json!({
+fn settings_json(settings: &crate::domain::LoanSettings) -> Value {
+    json!({
         "default_loan_duration_days": settings.default_loan_duration_days,
         "per_book_duration_enabled": settings.per_book_duration_enabled,
         "reminder_days_before_due": settings.reminder_days_before_due,
-    })))
+        "grace_period_days": settings.grace_period_days,
+        "contact_type_durations": settings.contact_type_durations,
+        "hold_expiry_days": settings.hold_expiry_days,
+        "request_expiry_days": settings.request_expiry_days,
+        "resolved_request_retention_days": settings.resolved_request_retention_days,
+        "fine_per_day": settings.fine_per_day,
+        "max_fine": settings.max_fine,
+    })
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/loan-settings",
+    tag = "loans",
+    responses(
+        (status = 200, description = "Current loan settings")
+    )
+)]
+pub async fn get_loan_settings(
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let settings = state
+        .loan_settings_repo
+        .get_settings()
+        .await
+        .map_err(settings_error)?;
+
+    Ok(Json(settings_json(&settings)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/loan-settings",
+    tag = "loans",
+    request_body = UpdateLoanSettingsPayload,
+    responses(
+        (status = 200, description = "Saved loan settings"),
+        (status = 400, description = "Value out of range")
+    )
+)]
 pub async fn update_loan_settings(
     State(state): State<AppState>,
     Json(payload): Json<UpdateLoanSettingsPayload>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     use crate::domain::LoanSettings;
 
+    let current = state
+        .loan_settings_repo
+        .get_settings()
+        .await
+        .map_err(settings_error)?;
     let updated = state
         .loan_settings_repo
         .update_settings(LoanSettings {
             default_loan_duration_days: payload.default_loan_duration_days,
             per_book_duration_enabled: payload.per_book_duration_enabled,
             reminder_days_before_due: payload.reminder_days_before_due,
+            grace_period_days: payload
+                .grace_period_days
+                .unwrap_or(current.grace_period_days),
+            contact_type_durations: payload
+                .contact_type_durations
+                .unwrap_or(current.contact_type_durations),
+            hold_expiry_days: payload.hold_expiry_days.unwrap_or(current.hold_expiry_days),
+            request_expiry_days: payload
+                .request_expiry_days
+                .unwrap_or(current.request_expiry_days),
+            resolved_request_retention_days: payload
+                .resolved_request_retention_days
+                .unwrap_or(current.resolved_request_retention_days),
+            fine_per_day: payload.fine_per_day.unwrap_or(current.fine_per_day),
+            max_fine: payload.max_fine.unwrap_or(current.max_fine),
         })
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .map_err(settings_error)?;
 
-    Ok(Json(json!({
-        "default_loan_duration_days": updated.default_loan_duration_days,
-        "per_book_duration_enabled": updated.per_book_duration_enabled,
-        "reminder_days_before_due": updated.reminder_days_before_due,
-    })))
+    Ok(Json(settings_json(&updated)))
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct EffectiveDurationQuery {
+    pub contact_type: Option<String>,
+    pub peer_id: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/loan-settings/effective/{book_id}",
+    tag = "loans",
+    params(
+        ("book_id" = String, Path, description = "Book id"),
+        EffectiveDurationQuery
+    ),
+    responses(
+        (status = 200, description = "Loan duration that applies, and where it comes from")
+    )
+)]
 pub async fn get_effective_loan_duration(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
+    Query(query): Query<EffectiveDurationQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let borrower = crate::domain::LoanBorrower {
+        contact_type: query.contact_type,
+        peer_id: query.peer_id,
+    };
     let days = state
         .loan_settings_repo
-        .get_effective_duration(&book_id)
+        .get_effective_duration_for(&book_id, &borrower)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .map_err(settings_error)?;
 
     Ok(Json(json!({ "duration_days": days })))
 }
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct PeerLoanDurationPayload {
+    /// `null` clears the override.
+    pub loan_duration_days: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/loan-settings/peers/{peer_id}",
+    tag = "loans",
+    params(("peer_id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Loan duration override for the peer, if any")
+    )
+)]
+pub async fn get_peer_loan_duration(
+    State(state): State<AppState>,
+    Path(peer_id): Path<i32>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let days = state
+        .loan_settings_repo
+        .get_peer_loan_duration(peer_id)
+        .await
+        .map_err(settings_error)?;
+
+    Ok(Json(
+        json!({ "peer_id": peer_id, "loan_duration_days": days }),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/loan-settings/peers/{peer_id}",
+    tag = "loans",
+    params(("peer_id" = i32, Path, description = "Local peer id")),
+    request_body = PeerLoanDurationPayload,
+    responses(
+        (status = 200, description = "Override saved (or cleared with null)"),
+        (status = 400, description = "Value out of range")
+    )
+)]
+pub async fn set_peer_loan_duration(
+    State(state): State<AppState>,
+    Path(peer_id): Path<i32>,
+    Json(payload): Json<PeerLoanDurationPayload>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let repo = &state.loan_settings_repo;
+    repo.set_peer_loan_duration(peer_id, payload.loan_duration_days)
+        .await
+        .map_err(settings_error)?;
+    let days = repo
+        .get_peer_loan_duration(peer_id)
+        .await
+        .map_err(settings_error)?;
+
+    Ok(Json(
+        json!({ "peer_id": peer_id, "loan_duration_days": days }),
+    ))
+}
@@ -6,11 +6,24 @@ use axum::{
 };
 use sea_orm::DatabaseConnection;
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
 pub struct LookupParams {
     pub lang: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/lookup/{isbn}",
+    tag = "integrations",
+    params(
+        ("isbn" = String, Path, description = "ISBN-10 or ISBN-13"),
+        LookupParams
+    ),
+    responses(
+        (status = 200, description = "Metadata merged from the lookup providers"),
+        (status = 404, description = "No provider knows this ISBN")
+    )
+)]
 pub async fn lookup_book(
     State(db): State<DatabaseConnection>,
     Path(isbn): Path<String>,
@@ -36,3 +49,57 @@ pub async fn lookup_book(
             .into_response(),
     }
 }
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+pub struct AvailabilityParams {
+    /// Ask every peer now instead of relying on their last catalogue sync
+    pub live: Option<bool>,
+    /// Include libraries and bookshops nearby (default true)
+    pub nearby: Option<bool>,
+    /// Search radius for nearby places, in metres (default 5000, max 25000)
+    pub radius: Option<u32>,
+    /// Search around this point instead of the library's location
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lookup/{isbn}/availability",
+    tag = "integrations",
+    params(
+        ("isbn" = String, Path, description = "ISBN-10 or ISBN-13"),
+        AvailabilityParams
+    ),
+    responses(
+        (status = 200, description = "Copies in this library, peers holding the book, nearby places and the fastest way to get it", body = Availability),
+        (status = 400, description = "Not a valid ISBN")
+    )
+)]
+pub async fn lookup_availability(
+    State(db): State<DatabaseConnection>,
+    Path(isbn): Path<String>,
+    axum::extract::Query(params): axum::extract::Query<AvailabilityParams>,
+) -> impl IntoResponse {
+    use crate::services::availability::{self, AvailabilityError, AvailabilityOptions};
+
+    let options = AvailabilityOptions {
+        live_peers: params.live.unwrap_or(false),
+        nearby: params.nearby.unwrap_or(true),
+        radius_m: params.radius.unwrap_or(availability::DEFAULT_RADIUS_M),
+        location: params.lat.zip(params.lon),
+    };
+    match availability::resolve(&db, &isbn, options).await {
+        Ok(found) => (StatusCode::OK, Json(found)).into_response(),
+        Err(AvailabilityError::InvalidIsbn) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Not a valid ISBN" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -59,7 +59,7 @@ fn health_body_is_bibliogenius(body: &Value) -> bool {
 /// remapped to its own (empty) container.
 #[cfg(feature = "mcp")]
 async fn discover_running_app() -> Option<String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(Duration::from_millis(400))
         .build()
         .ok()?;
@@ -178,6 +178,16 @@ fn tools_list_result() -> Value {
 /// Guarded by [`McpAuth`]: loopback source, no browser `Origin`, and a valid token.
 /// The private library must never be queryable by LAN peers that reach this shared
 /// (0.0.0.0-bound) router, nor by a web page running in the user's own browser.
+#[utoipa::path(
+    post,
+    path = "/api/mcp/rpc",
+    tag = "integrations",
+    request_body(content = Value, description = "JSON-RPC 2.0 request"),
+    responses(
+        (status = 200, description = "JSON-RPC response"),
+        (status = 204, description = "Notification, no response")
+    )
+)]
 pub async fn rpc_endpoint(
     _guard: McpAuth,
     State(db): State<DatabaseConnection>,
@@ -218,7 +228,7 @@ pub async fn start_server() {
         Some(base) => tracing::info!("MCP: proxying requests to running app at {}", base),
         None => tracing::info!("MCP: app not detected yet, will retry on the first tool call"),
     }
-    let http = reqwest::Client::builder()
+    let http = crate::services::network_mode::client_builder()
         .timeout(Duration::from_secs(15))
         .build()
         .ok();
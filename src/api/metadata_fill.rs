@@ -22,6 +22,14 @@ fn err(e: String) -> (StatusCode, Json<serde_json::Value>) {
     )
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/metadata-fill/stats",
+    tag = "metadata",
+    responses(
+        (status = 200, description = "Books missing metadata, per field")
+    )
+)]
 pub async fn get_stats(State(state): State<AppState>) -> impl IntoResponse {
     match svc::stats(&state).await {
         Ok(s) => (
@@ -39,7 +47,7 @@ pub async fn get_stats(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
-#[derive(serde::Deserialize, Default)]
+#[derive(serde::Deserialize, Default, utoipa::ToSchema)]
 pub struct StartBody {
     /// Comma-joined reading languages for summary coherence (ADR-040).
     pub languages: Option<String>,
@@ -47,6 +55,15 @@ pub struct StartBody {
     pub lot_limit: Option<u64>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/metadata-fill/start",
+    tag = "metadata",
+    request_body(content = Option<StartBody>, description = "Preferred languages and lot size"),
+    responses(
+        (status = 200, description = "Run started, or the one already running")
+    )
+)]
 pub async fn start(
     State(state): State<AppState>,
     body: Option<Json<StartBody>>,
@@ -73,6 +90,14 @@ fn run_json(run: &FillRun) -> serde_json::Value {
     })
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/metadata-fill/progress",
+    tag = "metadata",
+    responses(
+        (status = 200, description = "Progress of the current run")
+    )
+)]
 pub async fn get_progress(State(state): State<AppState>) -> impl IntoResponse {
     match svc::progress(&state).await {
         Ok(Some(run)) => (StatusCode::OK, Json(run_json(&run))).into_response(),
@@ -81,6 +106,14 @@ pub async fn get_progress(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/metadata-fill/cancel",
+    tag = "metadata",
+    responses(
+        (status = 200, description = "Cancellation requested")
+    )
+)]
 pub async fn cancel(State(state): State<AppState>) -> impl IntoResponse {
     match svc::cancel(&state).await {
         Ok(()) => (StatusCode::OK, Json(json!({ "ok": true }))).into_response(),
@@ -88,7 +121,7 @@ pub async fn cancel(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
 pub struct RecentQuery {
     pub limit: Option<u64>,
 }
@@ -112,6 +145,15 @@ fn recent_json(books: &[RecentFilledBook]) -> serde_json::Value {
     )
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/metadata-fill/recent",
+    tag = "metadata",
+    params(RecentQuery),
+    responses(
+        (status = 200, description = "Most recent field changes")
+    )
+)]
 pub async fn get_recent(
     State(state): State<AppState>,
     axum::extract::Query(q): axum::extract::Query<RecentQuery>,
@@ -122,6 +164,14 @@ pub async fn get_recent(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/metadata-fill/no-isbn",
+    tag = "metadata",
+    responses(
+        (status = 200, description = "Books the fill cannot look up (no ISBN)")
+    )
+)]
 pub async fn get_no_isbn(State(state): State<AppState>) -> impl IntoResponse {
     match svc::books_without_isbn(&state).await {
         Ok(books) => (
@@ -138,11 +188,20 @@ pub async fn get_no_isbn(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
 pub struct IncompleteQuery {
     pub limit: Option<u64>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/metadata-fill/incomplete",
+    tag = "metadata",
+    params(IncompleteQuery),
+    responses(
+        (status = 200, description = "Books still missing fields after a run")
+    )
+)]
 pub async fn get_incomplete(
     State(state): State<AppState>,
     axum::extract::Query(q): axum::extract::Query<IncompleteQuery>,
@@ -176,6 +235,15 @@ fn outcome_str(o: UndoOutcome) -> &'static str {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/metadata-fill/undo/field/{journal_id}",
+    tag = "metadata",
+    params(("journal_id" = i64, Path, description = "Journal entry id")),
+    responses(
+        (status = 200, description = "Field restored")
+    )
+)]
 pub async fn undo_field(
     State(state): State<AppState>,
     Path(journal_id): Path<i64>,
@@ -186,13 +254,22 @@ pub async fn undo_field(
     }
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct UndoBookBody {
     pub batch_id: String,
     /// Book uuid (the entity PK is now a uuid String).
     pub book_id: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/metadata-fill/undo/book",
+    tag = "metadata",
+    request_body = UndoBookBody,
+    responses(
+        (status = 200, description = "Fields of the book restored for that run")
+    )
+)]
 pub async fn undo_book(
     State(state): State<AppState>,
     Json(body): Json<UndoBookBody>,
@@ -203,6 +280,15 @@ pub async fn undo_book(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/metadata-fill/undo/run/{batch_id}",
+    tag = "metadata",
+    params(("batch_id" = String, Path, description = "Run id")),
+    responses(
+        (status = 200, description = "Every change of the run restored")
+    )
+)]
 pub async fn undo_run(
     State(state): State<AppState>,
     Path(batch_id): Path<String>,
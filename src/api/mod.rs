@@ -1,37 +1,63 @@
+pub mod acquisitions;
 pub mod admin;
+#[cfg(feature = "admin-ui")]
+pub mod admin_ui;
+pub mod announcements;
 pub mod auth;
 pub mod author;
 pub mod backup;
 pub mod batch;
+pub mod blobs;
 pub mod books;
 pub mod chat;
 pub mod collections;
 pub mod contact;
 pub mod copy;
 pub mod data;
+pub mod devices;
 pub mod discovery;
 pub mod e2ee;
 pub mod export;
+pub mod fees;
 pub mod frb; // FFI API for flutter_rust_bridge
 pub mod gamification;
 pub mod health;
+pub mod hub;
+pub mod idempotency;
+pub mod images;
 pub mod integrations;
 pub mod invite_page;
+pub mod kiosk;
 pub mod library;
 pub mod loan;
 pub mod lookup;
 pub mod metadata_fill;
+pub mod network;
 pub mod peer;
+pub mod periodicals;
+pub mod portal;
 pub mod profile;
 pub mod public_stats;
+pub mod reading_queue;
 pub mod relay;
+pub mod reports;
+pub mod request_id;
 pub mod sales; // Sales endpoints for bookseller profile
+pub mod saved_searches;
 pub mod scan;
 pub mod search;
 pub mod setup;
+pub mod stats;
+pub mod subject_authority;
+pub mod suggestions;
 pub mod tag;
+pub mod transfers;
+pub mod uploads;
 pub mod user;
+pub mod validation;
 pub mod view_counter;
+pub mod weeding;
+pub mod works;
 
 // The `mcp` module is always compiled: the loopback `/api/mcp/rpc` endpoint must
 // be served by every build (notably the FFI framework, which is built without the
@@ -57,12 +83,18 @@ pub fn api_router_with_state(state: AppState) -> Router {
         .with_state(state)
         // Layer order matters: outermost layer runs first.
         // 1. Extension layers add data to request extensions
-        // 2. Middleware reads from extensions and counts views
+        // 2. Middleware reads from extensions: counts views, then turns away
+        //    routes of modules disabled in the installation profile
+        .layer(axum::middleware::from_fn(
+            crate::modules::registry::module_gate_middleware,
+        ))
         .layer(axum::middleware::from_fn(
             view_counter::view_counter_middleware,
         ))
         .layer(axum::Extension(tracker))
         .layer(axum::Extension(db_for_views))
+        // 3. Request ID + access log wraps everything, so guard rejections get one too
+        .layer(axum::middleware::from_fn(request_id::request_id_middleware))
 }
 
 /// Create API router from DatabaseConnection (convenience wrapper)
@@ -107,6 +139,11 @@ fn public_routes() -> Router<AppState> {
         .route("/books", get(books::list_books))
         .route("/books/:id", get(books::get_book))
         .route("/books/:id/cover", get(books::get_book_cover))
+        .route("/copies/:id/availability", get(copy::get_copy_availability))
+        // Content-addressed covers and shared files, fetched by peers in chunks
+        .route("/blobs", get(blobs::list_blobs))
+        .route("/blobs/:sha256", get(blobs::get_manifest))
+        .route("/blobs/:sha256/chunks/:index", get(blobs::get_chunk))
         // Handshake / identity exchange
         .route("/config", get(setup::get_config))
         // Public leaderboard stats
@@ -125,26 +162,74 @@ fn public_routes() -> Router<AppState> {
             post(peer::receive_disconnect_notification),
         )
         .route("/peers/verify-disconnect", post(peer::verify_disconnect))
+        .route("/peers/handshake", post(peer::handshake)) // Protocol version and features
         .route("/peers/search", post(peer::search_local))
-        .route("/peers/request", post(peer::receive_request))
-        .route("/peers/loans/offer", post(peer::receive_loan_offer))
+        // Receivers a retrying sender may hit twice carry `Idempotency-Key`
+        // support (see `idempotency`)
+        .route(
+            "/peers/request",
+            post(peer::receive_request)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
+        .route(
+            "/peers/loans/offer",
+            post(peer::receive_loan_offer)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
         .route(
             "/peers/loans/confirm",
-            post(peer::receive_loan_confirmation),
+            post(peer::receive_loan_confirmation)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
+        .route(
+            "/peers/loans/returned",
+            post(peer::receive_loan_returned)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
         )
         // Loan-status notifications from the other party (ownership-checked in the
         // handler per ADR-050); the borrower/lender must be able to reach them.
         .route(
             "/peers/requests/status/:id",
-            put(peer::update_outgoing_status),
+            put(peer::update_outgoing_status)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
+        // Status poll from a keyless borrower whose callback never arrived.
+        .route(
+            "/peers/requests/status-query",
+            post(peer::query_request_status),
         )
         .route(
             "/peers/requests/cancel/:id",
             axum::routing::delete(peer::cancel_request),
         )
+        // Legacy plaintext return from a borrower; any other status change is
+        // refused in the handler unless the caller passes the owner guard.
         .route("/peers/requests/:id", put(peer::update_request_status))
         // E2EE encrypted peer transport (single sealed entry point)
         .route("/e2ee/message", post(e2ee::receive_encrypted_message))
+        // Device sync between one owner's installs: the pairing code or the
+        // pairing secret is checked in the handler
+        .route("/devices/pair", post(devices::accept_pairing))
+        .route(
+            "/devices/sync/operations",
+            get(devices::pull_operations).post(devices::push_operations),
+        )
+        .route("/devices/sync/revoke", post(devices::receive_revocation))
+        // Lending-desk kiosk: the kiosk token is checked in the handler
+        .route("/kiosk/search", get(kiosk::search))
+        .route("/kiosk/checkout", post(kiosk::checkout))
+        .route("/kiosk/checkin", post(kiosk::checkin))
+        // Contact portal: the portal token is checked in the handler
+        .route("/portal/loans", get(portal::my_loans))
+        .route("/portal/loans/:id/renewal", post(portal::request_renewal))
+        .route("/portal/catalog", get(portal::catalog))
+        .route(
+            "/portal/suggestions",
+            get(portal::my_suggestions).post(portal::suggest_book),
+        )
+        // OPDS feed and downloads for e-readers: the reader token is checked
+        // in the handler (see `modules::book_files::tokens`)
+        .merge(crate::modules::book_files::public_routes())
         // Relay mailbox (any instance can serve as a relay for peers)
         .route("/relay/mailbox", post(relay::create_mailbox))
         .route(
@@ -164,6 +249,16 @@ fn owner_routes() -> Router<AppState> {
     Router::new()
         // Admin
         .route("/admin/shutdown", post(admin::shutdown))
+        .route(
+            "/admin/maintenance",
+            get(admin::maintenance_status).post(admin::run_maintenance),
+        )
+        .route(
+            "/admin/consistency",
+            get(admin::check_consistency).post(admin::repair_consistency),
+        )
+        .route("/config/runtime", get(admin::runtime_config))
+        .route("/control/handshake", get(admin::control_handshake))
         // Auth
         .route("/auth/login", post(auth::login))
         .route("/auth/login-mfa", post(auth::login_mfa))
@@ -174,11 +269,76 @@ fn owner_routes() -> Router<AppState> {
         // Pairing (legacy)
         .route("/auth/pairing/code", post(auth::pairing_generate_code))
         .route("/auth/pairing/verify", post(auth::pairing_verify_code))
+        // Device sync (this owner's other installs)
+        .route("/devices", get(devices::list_devices))
+        .route("/devices/pairing", post(devices::start_pairing))
+        .route("/devices/pair-with", post(devices::pair_with))
+        .route("/devices/:id/sync", post(devices::sync_device))
+        .route("/sync/status", get(devices::sync_status))
+        .route(
+            "/devices/:id",
+            axum::routing::delete(devices::revoke_device),
+        )
+        // Kiosk tokens (shared lending-desk tablets)
+        .route(
+            "/kiosk/tokens",
+            get(kiosk::list_tokens).post(kiosk::create_token),
+        )
+        .route(
+            "/kiosk/tokens/:id",
+            axum::routing::delete(kiosk::revoke_token),
+        )
+        // Contact portal tokens
+        .route(
+            "/contacts/:id/portal-token",
+            post(portal::issue_token).delete(portal::revoke_token),
+        )
         // Library config
         .route("/library/config", get(library::get_config))
         .route("/library/config", post(library::update_config))
+        .route("/library/config/history", get(library::get_config_history))
+        .route(
+            "/library/config/rollback/:version",
+            post(library::rollback_config),
+        )
+        .route("/library/currencies", get(library::list_currencies))
         // Books (writes; the read side lives in `public_routes`)
         .route("/books/search", get(search::search_books))
+        .route("/search/nl", get(search::nl_search))
+        .route("/search/suggest", get(search::suggest))
+        .route(
+            "/reading-queue",
+            get(reading_queue::list_queue)
+                .post(reading_queue::queue_book)
+                .put(reading_queue::reorder_queue),
+        )
+        .route(
+            "/reading-queue/:book_id",
+            axum::routing::delete(reading_queue::unqueue_book),
+        )
+        .route("/dashboard", get(reading_queue::get_dashboard))
+        .route(
+            "/saved-searches",
+            get(saved_searches::list_saved_searches).post(saved_searches::create_saved_search),
+        )
+        .route(
+            "/saved-searches/:id",
+            axum::routing::delete(saved_searches::delete_saved_search),
+        )
+        .route(
+            "/saved-searches/:id/matches",
+            get(saved_searches::list_saved_search_matches),
+        )
+        .route(
+            "/saved-searches/:id/run",
+            post(saved_searches::run_saved_search),
+        )
+        .route(
+            "/search/voice",
+            post(search::voice_search).layer(axum::extract::DefaultBodyLimit::max(
+                crate::services::speech::MAX_CLIP_BYTES + 64 * 1024,
+            )),
+        )
         .route("/books/tags", get(books::list_tags))
         .route("/chat", post(chat::chat_handler))
         .route("/books", post(books::create_book))
@@ -187,10 +347,32 @@ fn owner_routes() -> Router<AppState> {
             put(books::update_book).delete(books::delete_book),
         )
         .route("/books/reorder", axum::routing::patch(books::reorder_books))
+        // Uploads are cut off at their configured size (see `uploads`)
+        .route(
+            "/books/:id/cover",
+            post(books::upload_book_cover).layer(uploads::body_limit(uploads::UploadKind::Image)),
+        )
         .route(
             "/books/:id/collections",
             get(collections::get_book_collections).put(collections::update_book_collections),
         )
+        .route(
+            "/books/:id/digital-editions",
+            get(books::get_digital_editions),
+        )
+        .route(
+            "/books/:id/relations",
+            get(books::get_book_relations).post(books::add_book_relation),
+        )
+        .route(
+            "/books/:id/relations/:relation_id",
+            axum::routing::delete(books::remove_book_relation),
+        )
+        .route("/books/:id/work", put(works::set_book_work))
+        // Works
+        .route("/works", get(works::list_works))
+        .route("/works/cluster", post(works::cluster_works))
+        .route("/works/:id", get(works::get_work))
         // Collections
         .route(
             "/collections",
@@ -210,7 +392,18 @@ fn owner_routes() -> Router<AppState> {
         )
         .route(
             "/collections/:id/books",
-            get(collections::get_collection_books).post(collections::import_collection),
+            get(collections::get_collection_books)
+                .post(collections::import_collection)
+                .layer(uploads::body_limit(uploads::UploadKind::Import)),
+        )
+        .route(
+            "/collections/:id/source",
+            get(collections::get_collection_source),
+        )
+        .route(
+            "/collections/:id/sync",
+            post(collections::sync_collection)
+                .layer(uploads::body_limit(uploads::UploadKind::Import)),
         )
         .route(
             "/collections/:collection_id/books/:book_id",
@@ -224,14 +417,25 @@ fn owner_routes() -> Router<AppState> {
         // Authors
         .route("/authors", get(author::list_authors))
         .route("/authors", post(author::create_author))
+        .route("/authors/match", get(author::match_authors))
+        .route(
+            "/authors/normalize",
+            get(author::normalize_proposals).post(author::apply_normalization),
+        )
         .route("/authors/:id", get(author::get_author))
         .route("/authors/:id", axum::routing::delete(author::delete_author))
+        .route("/authors/:id/works", get(author::get_author_works))
+        .route(
+            "/authors/:id/identifiers",
+            put(author::update_author_identifiers),
+        )
         // Tags
         .route("/tags", get(tag::list_tags))
         .route("/tags", post(tag::create_tag))
         .route("/tags/tree", get(tag::list_tags_tree))
         .route("/tags/:id", get(tag::get_tag))
         .route("/tags/:id", axum::routing::delete(tag::delete_tag))
+        .route("/tags/:id/private", put(tag::set_tag_privacy))
         // Peer management and orchestration (local UI; several call peers outbound)
         .route("/peers", get(peer::list_peers))
         .route("/peers/:id", axum::routing::delete(peer::delete_peer)) // Delete peer
@@ -242,6 +446,19 @@ fn owner_routes() -> Router<AppState> {
             axum::routing::patch(peer::update_peer_display_name),
         )
         .route("/peers/connect", post(peer::connect))
+        .route("/peers/import", post(peer::import_peers)) // Bulk create/update from CSV or a hub bundle
+        .route(
+            "/peers/certificates",
+            get(peer::list_peer_certificates).delete(peer::unpin_peer_certificate),
+        ) // Certificates pinned on first use
+        .route(
+            "/peers/certificates/probe",
+            get(peer::probe_peer_certificate),
+        )
+        .route(
+            "/peers/certificates/rotate",
+            post(peer::rotate_peer_certificate),
+        )
         .route(
             "/peers/auto_approve_all",
             post(peer::auto_approve_all_peers),
@@ -250,6 +467,30 @@ fn owner_routes() -> Router<AppState> {
         .route("/peers/sync_by_url", post(peer::sync_peer_by_url)) // Sync by URL (solves Hub ID mismatch)
         .route("/peers/:id/cache_books", post(peer::cache_books_by_id)) // Save pre-fetched books to cache
         .route("/peers/:id/books", get(peer::list_peer_books))
+        .route("/peers/subscriptions", get(peer::list_subscriptions)) // Background catalogue syncs
+        .route("/peers/:id/capabilities", get(peer::get_peer_capabilities))
+        .route("/peers/:id/handshake", post(peer::negotiate_with_peer))
+        .route(
+            "/peers/:id/subscription",
+            put(peer::subscribe_peer).delete(peer::unsubscribe_peer),
+        )
+        .route(
+            "/peers/:id/subscription/sync",
+            post(peer::sync_subscription),
+        )
+        // Announcements to connected peers, and whose reach the inbox
+        .route(
+            "/announcements",
+            get(announcements::list_announcements).post(announcements::publish_announcement),
+        )
+        .route(
+            "/announcements/:id",
+            axum::routing::delete(announcements::delete_announcement),
+        )
+        .route(
+            "/peers/:id/announcements",
+            put(announcements::set_announcement_trust),
+        )
         .route("/peers/books_by_url", post(peer::list_peer_books_by_url)) // Get books by URL
         .route(
             "/peers/cached_books_by_url",
@@ -260,12 +501,23 @@ fn owner_routes() -> Router<AppState> {
             post(peer::cleanup_stale_peer_books),
         ) // TTL cleanup for privacy
         .route("/peers/cover-proxy", get(peer::cover_proxy))
+        .route(
+            "/peers/:id/books/:book_id/blobs",
+            get(blobs::list_peer_book_blobs),
+        ) // Covers and files fetched from the peer
+        .route("/peers/:id/blobs/:sha256", get(blobs::get_peer_blob))
         .route("/peers/proxy_search", post(peer::proxy_search)) // Local fan-out; calls peers' /peers/search
         .route("/peers/return_book", post(peer::return_borrowed_book)) // Borrower-initiated return
         .route("/peers/request_by_url", post(peer::request_book_by_url)) // Send request by URL
         .route("/peers/:id/offer-loan", post(peer::offer_loan)) // Lender-initiated loan to peer
         .route("/peers/:id/request", post(peer::request_book)) // Send request
         .route("/peers/requests", get(peer::list_requests)) // List incoming requests
+        .route("/peers/outbox", get(peer::list_outbox)) // Undelivered P2P messages
+        .route("/peers/outbox/retry", post(peer::retry_outbox))
+        .route(
+            "/peers/outbox/:id",
+            axum::routing::delete(peer::dismiss_outbox_entry),
+        )
         .route(
             "/peers/requests/outgoing",
             get(peer::list_outgoing_requests).post(peer::create_outgoing_request),
@@ -273,7 +525,7 @@ fn owner_routes() -> Router<AppState> {
         .route(
             "/peers/requests/outgoing/clear",
             axum::routing::delete(peer::clear_outgoing_requests),
-        ) // Clear non-pending outgoing requests
+        ) // Clear closed outgoing requests
         .route(
             "/peers/requests/outgoing/:id",
             axum::routing::delete(peer::delete_outgoing_request),
@@ -285,28 +537,56 @@ fn owner_routes() -> Router<AppState> {
         .route(
             "/peers/requests/clear",
             axum::routing::delete(peer::clear_incoming_requests),
-        ) // Clear non-pending incoming requests
+        ) // Clear closed incoming requests
+        .route("/peers/requests/expire", post(peer::expire_requests))
+        .route(
+            "/peers/requests/clear-resolved",
+            axum::routing::delete(peer::clear_resolved_requests),
+        ) // Bulk request cleanup, both directions
         .route(
             "/peers/requests/:id",
             axum::routing::delete(peer::delete_request),
         ) // Delete request (local; the peer-facing PUT lives in public_routes)
         // Dead plaintext receivers from a shelved device-to-device sync feature:
         // no caller constructs these URLs (in either the Rust core or the Flutter
-        // client). `pull_operations` would dump the whole operation_log and
+        // client). `pull_operations` would page out the whole operation_log and
         // `push_operations` would ingest oplog rows unauthenticated, so they are
         // guarded rather than left reachable on the LAN. `receive_loan_request`
         // is superseded by the peer-facing `POST /peers/request` (`receive_request`).
-        .route("/peers/push", post(peer::push_operations))
+        .route(
+            "/peers/push",
+            post(peer::push_operations)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
         .route("/peers/pull", get(peer::pull_operations))
-        .route("/peers/requests/incoming", post(peer::receive_loan_request))
+        .route(
+            "/peers/requests/incoming",
+            post(peer::receive_loan_request)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
         // Local Discovery (mDNS) - local control only
         .route("/discovery/local", get(discovery::list_local_peers))
         .route("/discovery/status", get(discovery::mdns_status))
         .route("/discovery/toggle", post(discovery::toggle_mdns))
-        // Scanning
-        .route("/scan/image", post(scan::scan_image))
+        // Scanning and image processing
+        .route(
+            "/scan/image",
+            post(scan::scan_image).layer(uploads::body_limit(uploads::UploadKind::Image)),
+        )
+        .route("/scan/corrections", post(scan::record_scan_correction))
+        .route(
+            "/scan/corrections/export",
+            get(scan::export_scan_corrections),
+        )
+        .route(
+            "/images/process",
+            post(images::process_image).layer(uploads::body_limit(uploads::UploadKind::Image)),
+        )
         // Batch Operations
         .route("/books/batch/edit", post(batch::batch_edit))
+        .route("/books/batch/delete", post(batch::batch_delete))
+        .route("/books/batch/archive", post(batch::batch_archive))
+        .route("/books/batch/unarchive", post(batch::batch_unarchive))
         .route("/books/batch/sort", post(batch::batch_sort))
         .route("/books/duplicates", get(batch::find_duplicates))
         // Copies
@@ -320,6 +600,24 @@ fn owner_routes() -> Router<AppState> {
                 .put(copy::update_copy)
                 .delete(copy::delete_copy),
         )
+        .route("/copies/:id/history", get(copy::get_copy_history))
+        // Weeding
+        .route(
+            "/copies/:id/weeding",
+            post(weeding::flag_copy).delete(weeding::unflag_copy),
+        )
+        .route("/weeding", get(weeding::get_review_list))
+        .route("/weeding/withdraw", post(weeding::withdraw_copies))
+        .route("/weeding/report", get(weeding::get_weeding_report))
+        // Transfers between libraries
+        .route(
+            "/copies/:id/transfers",
+            get(transfers::list_copy_transfers).post(transfers::create_transfer),
+        )
+        .route("/transfers", get(transfers::list_transfers))
+        .route("/transfers/:id/ship", post(transfers::ship_transfer))
+        .route("/transfers/:id/receive", post(transfers::receive_transfer))
+        .route("/transfers/:id/cancel", post(transfers::cancel_transfer))
         // Contacts
         .route(
             "/contacts",
@@ -331,10 +629,66 @@ fn owner_routes() -> Router<AppState> {
                 .put(contact::update_contact)
                 .delete(contact::delete_contact),
         )
+        .route("/contacts/:id/promote", post(contact::promote_contact))
+        .route("/contacts/members", get(contact::list_members))
+        .route("/contacts/geocode", post(contact::geocode_contacts))
+        .route("/contacts/nearby", get(contact::nearby_contacts))
+        .route("/contacts/route", post(contact::plan_contact_route))
+        .route("/contacts/:id/membership", put(contact::update_membership))
+        .route("/contacts/:id/fees", get(fees::get_contact_fees))
+        .route(
+            "/contacts/:id/fees/payments",
+            post(fees::record_fee_payment),
+        )
+        .route("/fees/report", get(fees::get_fees_report))
+        // Acquisitions
+        .route(
+            "/orders",
+            get(acquisitions::list_orders).post(acquisitions::create_order),
+        )
+        .route("/orders/budget", get(acquisitions::get_budget_report))
+        .route("/orders/:id", get(acquisitions::get_order))
+        .route("/orders/:id/receive", post(acquisitions::receive_order))
+        .route("/orders/:id/cancel", post(acquisitions::cancel_order))
+        // Periodicals
+        .route(
+            "/periodicals",
+            get(periodicals::list_periodicals).post(periodicals::create_periodical),
+        )
+        .route(
+            "/periodicals/:id",
+            get(periodicals::get_periodical)
+                .put(periodicals::update_periodical)
+                .delete(periodicals::delete_periodical),
+        )
+        .route("/periodicals/:id/issues", post(periodicals::check_in_issue))
+        .route(
+            "/periodicals/:id/issues/:issue_id",
+            axum::routing::delete(periodicals::delete_issue),
+        )
+        .route("/suggestions", get(suggestions::list_suggestions))
+        .route(
+            "/suggestions/:id/accept",
+            post(suggestions::accept_suggestion),
+        )
+        .route(
+            "/suggestions/:id/reject",
+            post(suggestions::reject_suggestion),
+        )
         .route("/profile", put(profile::update_profile))
+        .route(
+            "/profile/modules",
+            get(profile::list_modules).put(profile::update_modules),
+        )
+        .route(
+            "/profile/appearance",
+            get(profile::get_appearance).put(profile::update_appearance),
+        )
         // Loans
         .route("/loans", get(loan::list_loans).post(loan::create_loan))
+        .route("/loans/quick", post(loan::create_quick_loan))
         .route("/loans/:id/return", put(loan::return_loan))
+        .route("/loans/:id/renew", put(loan::renew_loan))
         .route(
             "/loan-settings",
             get(loan::get_loan_settings).put(loan::update_loan_settings),
@@ -343,12 +697,20 @@ fn owner_routes() -> Router<AppState> {
             "/loan-settings/effective/:book_id",
             get(loan::get_effective_loan_duration),
         )
+        .route(
+            "/loan-settings/peers/:peer_id",
+            get(loan::get_peer_loan_duration).put(loan::set_peer_loan_duration),
+        )
         // Sales (Bookseller profile)
         .route("/sales", get(sales::list_sales).post(sales::create_sale))
         .route("/sales/:id", axum::routing::delete(sales::cancel_sale))
         .route("/statistics/sales", get(sales::get_sales_statistics))
         // Lookup
         .route("/lookup/:isbn", get(lookup::lookup_book))
+        .route(
+            "/lookup/:isbn/availability",
+            get(lookup::lookup_availability),
+        )
         // Bulk metadata gap-fill (ADR-041)
         .route("/metadata-fill/stats", get(metadata_fill::get_stats))
         .route("/metadata-fill/start", post(metadata_fill::start))
@@ -369,10 +731,45 @@ fn owner_routes() -> Router<AppState> {
             "/metadata-fill/undo/run/:batch_id",
             post(metadata_fill::undo_run),
         )
+        // Subject authority control (pro cataloguing)
+        .route(
+            "/subject-authorities",
+            get(subject_authority::list_vocabularies),
+        )
+        .route(
+            "/subject-authorities/suggest",
+            get(subject_authority::suggest_headings),
+        )
+        .route(
+            "/subject-authorities/validate",
+            post(subject_authority::validate_subjects),
+        )
+        .route(
+            "/subject-authorities/report",
+            get(subject_authority::get_unauthorized_report),
+        )
+        .route(
+            "/subject-authorities/:vocabulary",
+            axum::routing::delete(subject_authority::delete_vocabulary),
+        )
+        .route(
+            "/subject-authorities/:vocabulary/import",
+            post(subject_authority::import_vocabulary)
+                .layer(axum::extract::DefaultBodyLimit::disable()),
+        )
         // Data Import/Export
-        .route("/import/file", axum::routing::post(data::import_file))
+        .route(
+            "/import/file",
+            axum::routing::post(data::import_file)
+                .layer(uploads::body_limit(uploads::UploadKind::Import))
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
         // Setup and Config (GET /config is peer-facing and lives in public_routes)
         .route("/setup", axum::routing::post(setup::setup))
+        .route("/setup/state", get(setup::get_setup_state))
+        .route("/setup/steps", post(setup::save_setup_step))
+        .route("/setup/complete", post(setup::complete_setup))
+        .route("/setup/demo-data", post(setup::seed_demo_data))
         .route("/reset", axum::routing::post(setup::reset_app))
         .route("/identity/init", post(setup::init_identity))
         // Integrations (Professional)
@@ -389,6 +786,8 @@ fn owner_routes() -> Router<AppState> {
             get(integrations::search_unified),
         )
         .route("/integrations/mcp-config", get(integrations::mcp_config))
+        .route("/integrations/usage", get(integrations::get_usage))
+        .route("/integrations/quotas", put(integrations::set_quotas))
         // Internal loopback-only endpoint: lets the standalone `--mcp` helper proxy
         // JSON-RPC to this running app (which already holds an initialized database).
         .route("/mcp/rpc", post(mcp::rpc_endpoint))
@@ -402,6 +801,10 @@ fn owner_routes() -> Router<AppState> {
             "/gamification/refresh-leaderboard",
             post(gamification::refresh_leaderboard),
         )
+        // Book Clubs: shared reading lists and schedules (self-contained module)
+        .merge(crate::modules::book_club::routes())
+        // Book Files: ebook/audiobook attachments + OPDS reader tokens (self-contained module)
+        .merge(crate::modules::book_files::routes())
         // Book Notes (self-contained module)
         .merge(crate::modules::book_notes::routes())
         // Memory Game (self-contained module)
@@ -418,6 +821,16 @@ fn owner_routes() -> Router<AppState> {
             "/peers/relay/config",
             get(peer::get_relay_config_endpoint).delete(peer::delete_relay_config_endpoint),
         )
+        // SOCKS5 proxy (Tor) for peer requests and the onion address to publish
+        .route(
+            "/peers/tor/config",
+            get(peer::get_tor_config).put(peer::update_tor_config),
+        )
+        // Network mode: full, LAN-only or offline, for every outbound request
+        .route(
+            "/network/mode",
+            get(network::get_network_mode).put(network::set_network_mode),
+        )
         // Peer relay library sync (ADR-012) - local orchestrators
         .route(
             "/peers/relay/library_request",
@@ -430,10 +843,40 @@ fn owner_routes() -> Router<AppState> {
         // Relay control (local trigger/status; the mailbox itself is peer-facing)
         .route("/relay/poll_now", post(relay::poll_now))
         .route("/relay/status", get(relay::relay_status))
+        // Hub directory sync (cached; the background refresh is `services::hub_sync`)
+        .route("/hub/sync", get(hub::hub_sync_status).post(hub::sync_hub))
+        // Catalogue quality report
+        .route("/reports/quality", get(reports::get_quality_report))
         // View stats
         .route("/stats/views", get(view_counter::get_view_stats_handler))
+        .route("/stats/year/:year", get(stats::get_year_in_books))
+        .route("/stats/card.png", get(stats::get_stats_card))
+        .route("/stats/p2p", get(stats::get_p2p_stats))
         // Export/Import
         .route("/export", get(export::export_data))
-        .route("/import", post(export::import_data))
-        .route("/import-upsert", post(export::import_data_upsert))
+        .route(
+            "/import",
+            post(export::import_data)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
+        .route(
+            "/import-upsert",
+            post(export::import_data_upsert)
+                .layer(axum::middleware::from_fn(idempotency::idempotency_layer)),
+        )
+        // Fixture builder for Flutter integration tests (`testing` builds only)
+        .merge(fixture_routes())
+}
+
+/// `POST /debug/fixtures` in builds with the `testing` feature, nothing
+/// otherwise.
+fn fixture_routes() -> Router<AppState> {
+    #[cfg(feature = "testing")]
+    {
+        crate::testing::debug_routes()
+    }
+    #[cfg(not(feature = "testing"))]
+    {
+        Router::new()
+    }
 }
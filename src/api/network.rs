@@ -0,0 +1,51 @@
+//! Network mode setting (see `services::network_mode`).
+
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::AppState;
+use crate::services::network_mode::{self, NetworkMode};
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NetworkModeSetting {
+    pub mode: NetworkMode,
+}
+
+/// GET /api/network/mode - How far outbound requests may go
+#[utoipa::path(
+    get,
+    path = "/api/network/mode",
+    tag = "network",
+    responses(
+        (status = 200, description = "`full`, `lan_only` or `offline`", body = NetworkModeSetting)
+    )
+)]
+pub async fn get_network_mode() -> Json<NetworkModeSetting> {
+    Json(NetworkModeSetting {
+        mode: network_mode::current(),
+    })
+}
+
+/// PUT /api/network/mode - Switch between full, LAN-only and offline
+#[utoipa::path(
+    put,
+    path = "/api/network/mode",
+    tag = "network",
+    request_body = NetworkModeSetting,
+    responses(
+        (status = 200, description = "Mode stored; it applies to the next outbound request", body = NetworkModeSetting)
+    )
+)]
+pub async fn set_network_mode(
+    State(state): State<AppState>,
+    Json(setting): Json<NetworkModeSetting>,
+) -> Response {
+    match network_mode::set(state.db(), setting.mode).await {
+        Ok(()) => Json(setting).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
@@ -1,9 +1,10 @@
 //! Peer administration: listing, approval, status, URL, display name, deletion.
 
 use super::*;
+use crate::api::validation::{Validate, ValidatedJson, ValidationErrors, check_peer_url};
 use crate::models::peer;
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
 };
@@ -13,6 +14,14 @@ use serde::Deserialize;
 use serde_json::json;
 
 /// Bulk-approve all pending peers (called when connection_validation is toggled OFF)
+#[utoipa::path(
+    post,
+    path = "/api/peers/auto_approve_all",
+    tag = "peers",
+    responses(
+        (status = 200, description = "Number of peers switched to auto-approve")
+    )
+)]
 pub async fn auto_approve_all_peers(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     let peers = peer::Entity::find()
         .filter(peer::Column::ConnectionStatus.eq("pending"))
@@ -37,11 +46,21 @@ pub async fn auto_approve_all_peers(State(db): State<DatabaseConnection>) -> imp
         .into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/peers",
+    tag = "peers",
+    responses(
+        (status = 200, description = "Known peers with their connection status, and the last hub sync under `meta.hub`")
+    )
+)]
 pub async fn list_peers(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     // Legacy hub peer sync removed: peers are managed locally via invite
     // links, QR codes, and mDNS discovery. The old GET /api/peers hub
     // endpoint was causing SQLite lock contention and timeouts on every
-    // list_peers call, making peers appear to vanish from the UI.
+    // list_peers call, making peers appear to vanish from the UI. Hub
+    // follows are refreshed in the background (`services::hub_sync`); only
+    // the outcome of the last refresh is reported here, under `meta.hub`.
 
     let peers = peer::Entity::find().all(&db).await.unwrap_or(vec![]);
 
@@ -49,10 +68,10 @@ pub async fn list_peers(State(db): State<DatabaseConnection>) -> impl IntoRespon
     let peers_with_status: Vec<serde_json::Value> = peers
         .into_iter()
         .map(|p| {
-            let status = if p.connection_status == "pending" {
-                "pending"
-            } else {
-                "connected"
+            let status = match p.connection_status.as_str() {
+                "pending" => "pending",
+                PEER_BLOCKED => "blocked",
+                _ => "connected",
             };
             json!({
                 "id": p.id,
@@ -81,18 +100,35 @@ pub async fn list_peers(State(db): State<DatabaseConnection>) -> impl IntoRespon
     (
         StatusCode::OK,
         Json(json!({
-            "data": peers_with_status
+            "data": peers_with_status,
+            "meta": { "hub": crate::services::hub_sync::status() },
         })),
     )
         .into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct UpdatePeerStatusRequest {
-    status: String, // "active" (accept) or "rejected"
+    status: String, // "active" (accept), "rejected", "blocked" or "unblocked"
+    /// With "rejected": keep the peer as blocked instead of deleting it, so it
+    /// cannot immediately send a new connection request.
+    #[serde(default)]
+    block: bool,
 }
 
-/// Update a peer's status (accept or reject a connection request)
+/// Update a peer's status (accept, reject, block or unblock a connection)
+#[utoipa::path(
+    put,
+    path = "/api/peers/{id}/status",
+    tag = "peers",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = UpdatePeerStatusRequest,
+    responses(
+        (status = 200, description = "Status updated"),
+        (status = 404, description = "Peer not found"),
+        (status = 409, description = "Unblocking a peer that is not blocked")
+    )
+)]
 pub async fn update_peer_status(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
@@ -117,6 +153,60 @@ pub async fn update_peer_status(
         }
     };
 
+    // Blocking keeps the row, so the next request from this library is
+    // recognised and refused (see `is_peer_blocked`).
+    if payload.status == PEER_BLOCKED || (payload.status == "rejected" && payload.block) {
+        let mut active_model: peer::ActiveModel = peer.into();
+        active_model.connection_status = Set(PEER_BLOCKED.to_string());
+        active_model.auto_approve = Set(false);
+        active_model.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        return match active_model.update(&db).await {
+            Ok(updated) => {
+                tracing::info!("Peer {} blocked", peer_id);
+                (
+                    StatusCode::OK,
+                    Json(json!({ "message": "Peer blocked", "peer": updated })),
+                )
+                    .into_response()
+            }
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("Failed to update peer: {}", e) })),
+            )
+                .into_response(),
+        };
+    }
+
+    // Unblocking puts the peer back among the pending connection requests,
+    // where the owner can accept or reject it.
+    if payload.status == "unblocked" {
+        if !is_peer_blocked(&peer) {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "error": "Peer is not blocked" })),
+            )
+                .into_response();
+        }
+        let mut active_model: peer::ActiveModel = peer.into();
+        active_model.connection_status = Set("pending".to_string());
+        active_model.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        return match active_model.update(&db).await {
+            Ok(updated) => {
+                tracing::info!("Peer {} unblocked", peer_id);
+                (
+                    StatusCode::OK,
+                    Json(json!({ "message": "Peer unblocked", "peer": updated })),
+                )
+                    .into_response()
+            }
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("Failed to update peer: {}", e) })),
+            )
+                .into_response(),
+        };
+    }
+
     // If rejecting, delete the peer entirely
     if payload.status == "rejected" {
         match peer::Entity::delete_by_id(peer_id).exec(&db).await {
@@ -203,7 +293,7 @@ pub async fn update_peer_status(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdatePeerUrlRequest {
     pub url: String,
     /// Optional library_uuid to backfill when discovered via mDNS.
@@ -211,12 +301,37 @@ pub struct UpdatePeerUrlRequest {
     pub library_uuid: Option<String>,
 }
 
+impl Validate for UpdatePeerUrlRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.url.trim().is_empty() {
+            errors.add("url", "must not be empty");
+        } else if !self.url.starts_with("relay://") {
+            check_peer_url(&mut errors, "url", &self.url);
+        }
+        errors.into_result()
+    }
+}
+
 /// Update a peer's URL (for mDNS IP changes)
 /// Security: Only pending peers can have their URL updated
+#[utoipa::path(
+    put,
+    path = "/api/peers/{id}/url",
+    tag = "peers",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = UpdatePeerUrlRequest,
+    responses(
+        (status = 200, description = "URL updated"),
+        (status = 403, description = "Library UUID does not match the peer"),
+        (status = 404, description = "Peer not found"),
+        (status = 409, description = "Another peer already uses this URL")
+    )
+)]
 pub async fn update_peer_url(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
-    Json(payload): Json<UpdatePeerUrlRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdatePeerUrlRequest>,
 ) -> impl IntoResponse {
     // Find the peer
     let peer = match peer::Entity::find_by_id(peer_id).one(&db).await {
@@ -349,6 +464,16 @@ async fn purge_hub_catalog_cache(db: &DatabaseConnection, library_uuid: &str) {
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/peers/{id}",
+    tag = "peers",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Peer deleted and notified"),
+        (status = 404, description = "Peer not found")
+    )
+)]
 pub async fn delete_peer(
     State(state): State<crate::infrastructure::AppState>,
     Path(peer_id): Path<i32>,
@@ -417,12 +542,23 @@ pub async fn delete_peer(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdatePeerDisplayNameRequest {
     pub display_name: String,
 }
 
 /// Update a peer's user-defined display name.
+#[utoipa::path(
+    patch,
+    path = "/api/peers/{id}/display-name",
+    tag = "peers",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = UpdatePeerDisplayNameRequest,
+    responses(
+        (status = 200, description = "Display name updated"),
+        (status = 404, description = "Peer not found")
+    )
+)]
 pub async fn update_peer_display_name(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
@@ -469,6 +605,60 @@ pub async fn update_peer_display_name(
     }
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ImportPeersQuery {
+    /// Validate and report without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Create or update many peers at once from a CSV list (header row: `name`,
+/// `url`, `public_key`, ...) or a JSON array / hub bundle `{"peers": [...]}`.
+/// See `services::peer_import`.
+#[utoipa::path(
+    post,
+    path = "/api/peers/import",
+    tag = "peers",
+    params(ImportPeersQuery),
+    request_body(content = String, description = "CSV with a header row, or JSON: an array of `PeerEntry` or `{\"peers\": [...]}`", content_type = "text/plain"),
+    responses(
+        (status = 200, description = "What became of each row; invalid rows are skipped", body = ImportReport),
+        (status = 400, description = "Neither CSV nor a JSON list of peers")
+    )
+)]
+pub async fn import_peers(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<ImportPeersQuery>,
+    body: String,
+) -> impl IntoResponse {
+    use crate::services::peer_import;
+
+    let entries = match peer_import::parse(&body) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response();
+        }
+    };
+    match peer_import::import_peers(&db, entries, query.dry_run).await {
+        Ok(report) => {
+            if !report.dry_run {
+                tracing::info!(
+                    "Peer import: {} created, {} updated, {} invalid",
+                    report.created,
+                    report.updated,
+                    report.invalid
+                );
+            }
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("Database error: {}", e) })),
+        )
+            .into_response(),
+    }
+}
+
 #[cfg(test)]
 mod hub_catalog_cache_tests {
     use super::*;
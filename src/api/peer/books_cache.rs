@@ -12,7 +12,7 @@ use serde::Deserialize;
 use serde_json::json;
 
 /// Query params for the cover-proxy endpoint.
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::IntoParams)]
 pub struct CoverProxyQuery {
     pub peer_url: String,
     pub book_id: i32,
@@ -22,17 +22,49 @@ pub struct CoverProxyQuery {
 ///
 /// Proxies a cover image fetch through the local Rust backend so that
 /// Flutter does not make direct HTTP calls to the peer (which fail on
-/// iOS/macOS due to firewall, ATS, or NAT issues).
+/// iOS/macOS due to firewall, ATS, or NAT issues). A cover already fetched
+/// into the blob store during sync is served from there, peer offline or not.
+#[utoipa::path(
+    get,
+    path = "/api/peers/cover-proxy",
+    tag = "peer-catalogue",
+    params(CoverProxyQuery),
+    responses(
+        (status = 200, description = "Cover image bytes"),
+        (status = 400, description = "Invalid peer URL"),
+        (status = 404, description = "Peer not found or has no cover"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
 pub async fn cover_proxy(
     State(db): State<DatabaseConnection>,
     axum::extract::Query(params): axum::extract::Query<CoverProxyQuery>,
 ) -> Result<axum::response::Response, StatusCode> {
     let peer_url = validate_url(&params.peer_url).map_err(|_| StatusCode::BAD_REQUEST)?;
-    ensure_registered_peer(&db, &peer_url).await?;
+    let registered = ensure_registered_peer(&db, &peer_url).await?;
+
+    if let Ok(Some((_, path))) = crate::services::content_blobs::cached_peer_blob(
+        &db,
+        &crate::services::content_blobs::store_dir(),
+        registered.id,
+        &params.book_id.to_string(),
+        crate::services::content_blobs::BlobKind::Cover,
+    )
+    .await
+        && let Ok(bytes) = tokio::fs::read(&path).await
+    {
+        return axum::response::Response::builder()
+            .status(StatusCode::OK)
+            .header(axum::http::header::CONTENT_TYPE, "image/jpeg")
+            .header(axum::http::header::CACHE_CONTROL, "public, max-age=3600")
+            .body(axum::body::Body::from(bytes))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     let peer_url = peer_url.trim_end_matches('/');
     let url = format!("{}/api/books/{}/cover", peer_url, params.book_id);
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(peer_url);
     let resp = client
         .get(&url)
         .send()
@@ -74,6 +106,16 @@ pub async fn cover_proxy(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/peers/{id}/books",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Cached books of the peer"),
+        (status = 403, description = "Peer not accepted")
+    )
+)]
 pub async fn list_peer_books(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
@@ -101,6 +143,18 @@ pub async fn list_peer_books(
 }
 
 /// List peer books by URL (solves ID mismatch)
+#[utoipa::path(
+    post,
+    path = "/api/peers/books_by_url",
+    tag = "peer-catalogue",
+    request_body(content = Value, description = "`{\"url\": \"http://...\"}`"),
+    responses(
+        (status = 200, description = "Cached books of the peer at that URL"),
+        (status = 400, description = "Missing URL"),
+        (status = 403, description = "Peer not accepted"),
+        (status = 404, description = "Peer not found")
+    )
+)]
 pub async fn list_peer_books_by_url(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<serde_json::Value>,
@@ -159,6 +213,16 @@ pub async fn list_peer_books_by_url(
 
 /// Get cached peer books with staleness metadata (no network call to peer)
 /// Returns books from local cache along with last_synced timestamp for UI staleness indicator
+#[utoipa::path(
+    post,
+    path = "/api/peers/cached_books_by_url",
+    tag = "peer-catalogue",
+    request_body(content = Value, description = "`{\"url\": \"http://...\"}`"),
+    responses(
+        (status = 200, description = "Cached books with cache metadata"),
+        (status = 400, description = "Missing URL")
+    )
+)]
 pub async fn get_cached_books_by_url(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<serde_json::Value>,
@@ -241,6 +305,14 @@ pub async fn get_cached_books_by_url(
 
 /// Cleanup peer_books entries older than 30 days (TTL for privacy)
 /// Call this on app startup to auto-purge stale caches
+#[utoipa::path(
+    post,
+    path = "/api/peers/cleanup_stale_cache",
+    tag = "peer-catalogue",
+    responses(
+        (status = 200, description = "Number of stale cached books removed")
+    )
+)]
 pub async fn cleanup_stale_peer_books(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     use crate::models::peer_book;
     use sea_orm::QueryFilter;
@@ -295,6 +367,18 @@ pub async fn cleanup_stale_peer_books(State(db): State<DatabaseConnection>) -> i
 /// Called by Flutter after loading books via relay or live WiFi fetch,
 /// so the Rust backend does not need to re-fetch from the remote peer.
 /// Input: { "books": [{ "id": 5, "title": "...", ... }, ...] }
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/cache_books",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body(content = Value, description = "`{\"books\": [...]}` as fetched from the peer"),
+    responses(
+        (status = 200, description = "Books cached"),
+        (status = 400, description = "Missing `books` array"),
+        (status = 404, description = "Peer not found")
+    )
+)]
 pub async fn cache_books_by_id(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
@@ -0,0 +1,127 @@
+//! Certificates pinned for self-signed HTTPS peers (see `services::cert_pins`).
+
+use axum::{
+    extract::{Json, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::cert_pins::{self, PinError};
+
+fn pin_error(e: PinError) -> Response {
+    let status = match e {
+        PinError::NotHttps => StatusCode::BAD_REQUEST,
+        PinError::NotPinned => StatusCode::NOT_FOUND,
+        PinError::Unreachable(_) => StatusCode::BAD_GATEWAY,
+        PinError::Mismatch { .. } => StatusCode::CONFLICT,
+        PinError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/peers/certificates - Certificates pinned on first connect
+#[utoipa::path(
+    get,
+    path = "/api/peers/certificates",
+    tag = "peers",
+    responses(
+        (status = 200, description = "Pinned certificates by origin, with their fingerprints", body = [PeerCertificate])
+    )
+)]
+pub async fn list_peer_certificates(State(state): State<AppState>) -> Response {
+    match cert_pins::list(state.db()).await {
+        Ok(pins) => Json(json!({ "certificates": pins })).into_response(),
+        Err(e) => pin_error(e.into()),
+    }
+}
+
+/// Query naming a peer by URL
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct PeerUrlQuery {
+    /// The peer's `https://` URL
+    pub url: String,
+}
+
+/// GET /api/peers/certificates/probe - The certificate a peer presents now
+#[utoipa::path(
+    get,
+    path = "/api/peers/certificates/probe",
+    tag = "peers",
+    params(PeerUrlQuery),
+    responses(
+        (status = 200, description = "Presented and pinned fingerprints", body = Probe),
+        (status = 400, description = "Not an HTTPS URL"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
+pub async fn probe_peer_certificate(
+    State(state): State<AppState>,
+    Query(query): Query<PeerUrlQuery>,
+) -> Response {
+    if let Err(e) = super::validate_url(&query.url) {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response();
+    }
+    match cert_pins::probe(state.db(), &query.url).await {
+        Ok(probe) => Json(probe).into_response(),
+        Err(e) => pin_error(e),
+    }
+}
+
+/// Request body for re-pinning a peer's certificate
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct RotateCertificateRequest {
+    /// The peer's `https://` URL
+    pub url: String,
+    /// Fingerprint of the new certificate, as the peer's owner reads it
+    pub sha256: String,
+}
+
+/// POST /api/peers/certificates/rotate - Pin a peer's new certificate
+#[utoipa::path(
+    post,
+    path = "/api/peers/certificates/rotate",
+    tag = "peers",
+    request_body = RotateCertificateRequest,
+    responses(
+        (status = 200, description = "The new pin; the replaced fingerprint is in `previous_sha256`", body = PeerCertificate),
+        (status = 400, description = "Not an HTTPS URL"),
+        (status = 409, description = "The peer presents another certificate than `sha256`"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
+pub async fn rotate_peer_certificate(
+    State(state): State<AppState>,
+    Json(req): Json<RotateCertificateRequest>,
+) -> Response {
+    if let Err(e) = super::validate_url(&req.url) {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response();
+    }
+    match cert_pins::rotate(state.db(), &req.url, &req.sha256).await {
+        Ok(pin) => Json(pin).into_response(),
+        Err(e) => pin_error(e),
+    }
+}
+
+/// DELETE /api/peers/certificates - Forget a peer's pinned certificate
+#[utoipa::path(
+    delete,
+    path = "/api/peers/certificates",
+    tag = "peers",
+    params(PeerUrlQuery),
+    responses(
+        (status = 200, description = "Unpinned; the next connect pins again"),
+        (status = 404, description = "No certificate pinned for this peer")
+    )
+)]
+pub async fn unpin_peer_certificate(
+    State(state): State<AppState>,
+    Query(query): Query<PeerUrlQuery>,
+) -> Response {
+    match cert_pins::unpin(state.db(), &query.url).await {
+        Ok(()) => Json(json!({ "unpinned": query.url })).into_response(),
+        Err(e) => pin_error(e),
+    }
+}
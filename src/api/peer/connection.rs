@@ -1,6 +1,7 @@
 //! Peer connection and disconnection lifecycle.
 
 use super::*;
+use crate::api::validation::{Validate, ValidatedJson, ValidationErrors, check_peer_url};
 use crate::models::{peer, peer_book};
 use axum::{
     extract::{Json, State},
@@ -16,7 +17,7 @@ use serde::Deserialize;
 use serde_json::json;
 use tracing::info;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ConnectRequest {
     name: String,
     url: String,
@@ -41,20 +42,34 @@ pub struct ConnectRequest {
     relay_write_token: Option<String>,
 }
 
+impl Validate for ConnectRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        check_peer_url(&mut errors, "url", &self.url);
+        errors.into_result()
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/peers/connect",
+    tag = "peers",
+    request_body = ConnectRequest,
+    responses(
+        (status = 201, description = "Peer registered and connection request sent"),
+        (status = 400, description = "Invalid payload")
+    )
+)]
 pub async fn connect(
     State(db): State<DatabaseConnection>,
-    Json(payload): Json<ConnectRequest>,
+    ValidatedJson(payload): ValidatedJson<ConnectRequest>,
 ) -> impl IntoResponse {
-    // Relay-only peers have an empty URL — skip URL validation and remote
-    // config fetch in that case. All data comes from the request payload.
+    // Relay-only peers have an empty URL — skip the remote config fetch in that
+    // case. All data comes from the request payload. LAN URLs were already
+    // checked against the SSRF allow-list by `ValidatedJson`.
     let is_relay_only = payload.url.is_empty();
 
-    // 1. Validate URL (only for LAN peers with a real HTTP URL)
-    if !is_relay_only && let Err(e) = validate_url(&payload.url) {
-        return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response();
-    }
-
-    // 2. Fetch remote config to get location and verify connectivity
+    // Fetch remote config to get location and verify connectivity
     struct RemoteConfigData {
         latitude: Option<f64>,
         longitude: Option<f64>,
@@ -86,7 +101,10 @@ pub async fn connect(
             false,
         )
     } else {
-        let client = get_safe_client();
+        // First contact with a self-signed HTTPS peer: trust the certificate
+        // it presents now, and only that one from then on.
+        crate::services::cert_pins::pin_on_first_use(&db, &payload.url).await;
+        let client = get_safe_client_for(&payload.url);
         let config_url = format!("{}/api/config", payload.url.trim_end_matches('/'));
         match client.get(&config_url).send().await {
             Ok(res) => {
@@ -365,7 +383,7 @@ pub async fn connect(
     (StatusCode::CREATED, Json(json!({ "id": peer_id }))).into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct IncomingConnectionRequest {
     name: String,
     url: String,
@@ -389,13 +407,42 @@ pub struct IncomingConnectionRequest {
     relay_write_token: Option<String>,
 }
 
+impl IncomingConnectionRequest {
+    /// Clean the fields shown to the user and stored as they come. Keys and
+    /// the write token are checked where they are used.
+    fn sanitized(self) -> Self {
+        use crate::utils::sanitize::{
+            MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_URL_CHARS, line, opt_line,
+        };
+        Self {
+            name: line(&self.name, MAX_NAME_CHARS),
+            url: line(&self.url, MAX_URL_CHARS),
+            library_uuid: opt_line(self.library_uuid, MAX_CODE_CHARS),
+            relay_url: opt_line(self.relay_url, MAX_URL_CHARS),
+            mailbox_id: opt_line(self.mailbox_id, MAX_CODE_CHARS),
+            ..self
+        }
+    }
+}
+
 /// Receive an incoming connection request from a remote peer.
 /// Always creates/updates the peer in local SQLite and returns our E2EE keys.
 /// Also forwards to the Hub (fire-and-forget) for the central directory.
+#[utoipa::path(
+    post,
+    path = "/api/peers/incoming",
+    tag = "peer-transport",
+    request_body = IncomingConnectionRequest,
+    responses(
+        (status = 200, description = "Connection request recorded"),
+        (status = 403, description = "Sender is blocked")
+    )
+)]
 pub async fn receive_connection_request(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<IncomingConnectionRequest>,
 ) -> impl IntoResponse {
+    let payload = payload.sanitized();
     tracing::info!(
         "Peer: Received connection_request from '{}' (url='{}', e2ee={}, relay={}, library_uuid={:?})",
         payload.name,
@@ -445,7 +492,25 @@ pub async fn receive_connection_request(
         payload.ed25519_public_key.is_some() && payload.x25519_public_key.is_some();
 
     match existing {
+        Ok(Some(existing_peer)) if is_peer_blocked(&existing_peer) => {
+            tracing::info!(
+                "Peer: refusing connection_request from blocked peer {}",
+                existing_peer.id
+            );
+            (
+                StatusCode::FORBIDDEN,
+                Json(json!({ "error": "Connection refused" })),
+            )
+                .into_response()
+        }
         Ok(Some(existing_peer)) => {
+            // Found by its library UUID at another URL: follow it there.
+            if !payload.url.is_empty() && existing_peer.url != payload.url {
+                let (db, known, url) = (db.clone(), existing_peer.clone(), payload.url.clone());
+                tokio::spawn(async move {
+                    crate::services::peer_identity_sync::relink(&db, &known, &url).await;
+                });
+            }
             // Peer already exists - update keys, relay info, and library_uuid if provided
             let old_uuid = existing_peer.library_uuid.clone();
             let peer_id = existing_peer.id;
@@ -751,7 +816,7 @@ pub(crate) async fn notify_peer_of_disconnect(
         &timestamp,
     );
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer_model.url);
     let url = format!("{}/api/peers/notify-disconnect", peer_model.url);
     match client
         .post(&url)
@@ -788,6 +853,18 @@ pub(crate) async fn notify_peer_of_disconnect(
 /// 2. Validates timestamp within +/-5 minutes (replay window)
 /// 3. Verifies HMAC using X25519 static shared secret
 /// 4. Re-handshake: asks the sender to confirm the disconnect
+#[utoipa::path(
+    post,
+    path = "/api/peers/notify-disconnect",
+    tag = "peer-transport",
+    request_body = DisconnectNotification,
+    responses(
+        (status = 200, description = "Peer removed"),
+        (status = 400, description = "Missing or stale signature fields"),
+        (status = 401, description = "Signature does not verify"),
+        (status = 409, description = "Replayed notification")
+    )
+)]
 pub async fn receive_disconnect_notification(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<DisconnectNotification>,
@@ -1015,7 +1092,7 @@ pub async fn receive_disconnect_notification(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct DisconnectNotification {
     pub peer_url: String,
     /// Stable library UUID - used as primary lookup for peer identification.
@@ -1027,7 +1104,7 @@ pub struct DisconnectNotification {
 }
 
 /// Request body for the re-handshake confirmation endpoint.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct VerifyDisconnectRequest {
     /// The library_uuid of the peer asking for confirmation.
     pub library_uuid: String,
@@ -1038,6 +1115,15 @@ pub struct VerifyDisconnectRequest {
 /// Returns `confirmed: true` if we no longer have this peer in our database
 /// (meaning we did initiate a disconnect). Returns `confirmed: false` if the
 /// peer still exists (the disconnect was likely spoofed).
+#[utoipa::path(
+    post,
+    path = "/api/peers/verify-disconnect",
+    tag = "peer-transport",
+    request_body = VerifyDisconnectRequest,
+    responses(
+        (status = 200, description = "Whether this library still knows the caller")
+    )
+)]
 pub async fn verify_disconnect(
     State(state): State<crate::infrastructure::AppState>,
     Json(req): Json<VerifyDisconnectRequest>,
@@ -1079,7 +1165,7 @@ pub(crate) async fn verify_disconnect_with_peer(
         return None;
     }
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(peer_url);
     let url = format!("{}/api/peers/verify-disconnect", peer_url);
 
     match client
@@ -163,15 +163,61 @@ pub async fn ensure_registered_peer_or_mdns(
     }
 }
 
-/// Create a safe HTTP client with restricted redirects and timeouts
+/// Create a safe HTTP client with restricted redirects and timeouts.
+/// Inside a request, the client forwards its `X-Request-Id` (and trace
+/// context) to the peer.
 pub(crate) fn get_safe_client() -> reqwest::Client {
-    reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .redirect(reqwest::redirect::Policy::none()) // Disable redirects to prevent bypass
+    safe_client_builder(None).build().unwrap_or_default()
+}
+
+/// Like `get_safe_client`, for requests to the peer at `url`: an onion peer
+/// is reached through the SOCKS5 proxy (see `services::tor`), and when the
+/// peer's self-signed certificate was pinned (see `services::cert_pins`),
+/// only that certificate (and, right after a rotation, the one it replaced)
+/// is trusted, whatever host name it was issued for.
+pub(crate) fn get_safe_client_for(url: &str) -> reqwest::Client {
+    let pinned: Vec<reqwest::Certificate> = crate::services::cert_pins::pinned_ders(url)
+        .iter()
+        .filter_map(|der| reqwest::Certificate::from_der(der).ok())
+        .collect();
+    if pinned.is_empty() {
+        return safe_client_builder(Some(url)).build().unwrap_or_default();
+    }
+    pinned
+        .into_iter()
+        .fold(
+            safe_client_builder(Some(url)).tls_built_in_root_certs(false),
+            reqwest::ClientBuilder::add_root_certificate,
+        )
+        // Self-signed peer certificates rarely name the LAN address they are
+        // reached at; the pin is what identifies the peer.
+        .danger_accept_invalid_hostnames(true)
         .build()
         .unwrap_or_default()
 }
 
+/// Shared by both clients: requests also go through the SOCKS5 proxy of
+/// `services::tor` when `url` (or, unknown, every peer) must use it.
+fn safe_client_builder(url: Option<&str>) -> reqwest::ClientBuilder {
+    let mut headers = reqwest::header::HeaderMap::new();
+    crate::infrastructure::telemetry::inject_context(&mut headers);
+    if let Some(value) = crate::api::request_id::current()
+        .and_then(|id| reqwest::header::HeaderValue::from_str(&id).ok())
+    {
+        headers.insert(crate::api::request_id::REQUEST_ID_HEADER, value);
+    }
+    let builder = crate::services::network_mode::client_builder()
+        .redirect(reqwest::redirect::Policy::none()) // Disable redirects to prevent bypass
+        .default_headers(headers);
+    match crate::services::tor::proxy_for(url) {
+        // Circuits take seconds to build: 5s would fail most first requests.
+        Some(proxy) => builder
+            .proxy(proxy)
+            .timeout(std::time::Duration::from_secs(30)),
+        None => builder.timeout(std::time::Duration::from_secs(5)),
+    }
+}
+
 /// Translate localhost URLs to Docker service names for inter-container communication
 /// Examples:
 /// - http://localhost:8001 -> http://bibliogenius-a:8000
@@ -206,6 +252,16 @@ pub(crate) async fn is_auto_approve_loans_enabled(db: &DatabaseConnection) -> bo
     false
 }
 
+/// `connection_status` of a peer the owner blocked. Its connection requests,
+/// borrow requests and encrypted messages are refused until it is unblocked,
+/// so a rejected library cannot immediately ask again.
+pub const PEER_BLOCKED: &str = "blocked";
+
+/// Whether the owner blocked this peer (see [`PEER_BLOCKED`]).
+pub fn is_peer_blocked(peer: &peer::Model) -> bool {
+    peer.connection_status == PEER_BLOCKED
+}
+
 /// Check if a specific peer is approved for access.
 /// Returns true if connection_validation is disabled OR if the peer has connection_status == "accepted".
 pub(crate) async fn is_peer_approved(db: &DatabaseConnection, peer: &peer::Model) -> bool {
@@ -63,6 +63,7 @@ mod loan_offer_lender_resolution_tests {
             due_date: "2026-09-01".to_string(),
             request_id: Some("lender-req-1".to_string()),
             library_uuid: library_uuid.map(|s| s.to_string()),
+            terms: None,
         }
     }
 
@@ -150,7 +151,11 @@ mod loan_offer_lender_resolution_tests {
             .build()
             .unwrap();
 
-        let silent = rt.block_on(body_of(return_outcome(false, Some("no_outgoing_request"))));
+        let silent = rt.block_on(body_of(return_outcome(
+            false,
+            false,
+            Some("no_outgoing_request"),
+        )));
         assert_eq!(silent["lender_notified"], serde_json::json!(false));
         assert_eq!(silent["reason"], serde_json::json!("no_outgoing_request"));
         assert!(
@@ -159,8 +164,9 @@ mod loan_offer_lender_resolution_tests {
             silent["message"]
         );
 
-        let notified = rt.block_on(body_of(return_outcome(true, None)));
+        let notified = rt.block_on(body_of(return_outcome(true, false, None)));
         assert_eq!(notified["lender_notified"], serde_json::json!(true));
+        assert_eq!(notified["return_confirmed"], serde_json::json!(false));
         assert_eq!(
             notified["reason"],
             serde_json::Value::Null,
@@ -279,6 +285,7 @@ mod loan_offer_lender_resolution_tests {
             due_date: "2026-09-01".to_string(),
             request_id: Some("lender-req-1".to_string()),
             requester_request_id: None,
+            terms: None,
         };
         let response = receive_loan_confirmation(State(db.clone()), Json(payload))
             .await
@@ -351,6 +358,7 @@ mod loan_offer_lender_resolution_tests {
             due_date: "2026-09-01".to_string(),
             request_id: Some("lender-req-1".to_string()),
             requester_request_id: Some("borrower-req-1".to_string()),
+            terms: None,
         };
         let response = receive_loan_confirmation(State(db.clone()), Json(payload))
             .await
@@ -363,6 +371,42 @@ mod loan_offer_lender_resolution_tests {
             "the confirmation names its lender through the outgoing request"
         );
     }
+
+    /// The lender's terms travel with the confirmation and land on the
+    /// borrowed copy, so both sides read the same agreement.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_loan_confirmation_carries_the_lending_terms_to_the_borrowed_copy() {
+        let db = setup_db().await;
+        let lender = insert_known_lender(&db, Some(LENDER_UUID)).await;
+        insert_pending_request(&db, "borrower-req-1", lender).await;
+
+        let terms = crate::domain::LendingTerms {
+            max_duration_days: Some(14),
+            renewable: false,
+            replacement_value: Some(30.0),
+            conditions: None,
+        };
+        let payload: LoanConfirmation = serde_json::from_value(json!({
+            "isbn": "978-1",
+            "title": "Le Livre",
+            "lender_name": "christophe",
+            "due_date": "2026-09-01",
+            "request_id": "lender-req-1",
+            "requester_request_id": "borrower-req-1",
+            "terms": terms,
+        }))
+        .expect("confirmation payload");
+        let response = receive_loan_confirmation(State(db.clone()), Json(payload))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let stored = borrowed_copy(&db).await.lending_terms;
+        assert_eq!(
+            crate::domain::LendingTerms::from_column(stored.as_deref()),
+            Some(terms)
+        );
+    }
 }
 
 /// A book row is a bibliographic record carrying many copies, and each copy can be
@@ -413,6 +457,7 @@ mod multi_lender_borrow_tests {
             due_date: "2026-09-01".to_string(),
             request_id: Some(request_id.to_string()),
             library_uuid: Some(library_uuid.to_string()),
+            terms: None,
         }
     }
 
@@ -755,6 +800,7 @@ mod multi_lender_borrow_tests {
                 due_date: "2026-09-01".to_string(),
                 request_id: Some("alice-req".to_string()),
                 library_uuid: Some(ALICE_UUID.to_string()),
+                terms: None,
             };
             receive(&db, offer).await;
 
@@ -803,6 +849,7 @@ mod multi_lender_borrow_tests {
             due_date: "2026-09-01".to_string(),
             request_id: Some("alice-req".to_string()),
             library_uuid: Some(ALICE_UUID.to_string()),
+            terms: None,
         };
         receive(&db, offer).await;
 
@@ -1105,6 +1152,7 @@ mod multi_lender_borrow_tests {
             created_at: Set(now.clone()),
             updated_at: Set(now),
             requester_request_id: Set(None),
+            ..Default::default()
         }
         .insert(db)
         .await
@@ -1238,6 +1286,84 @@ mod multi_lender_borrow_tests {
         assert_eq!(outgoing_status(&db, "req-1").await, "returned");
     }
 
+    /// The lender's acknowledgment of a return deletes the copy archived as
+    /// `returning` and closes the outgoing request.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_return_acknowledgment_deletes_the_archived_copy() {
+        let db = setup_db().await;
+        let alice = insert_peer(&db, "alice", ALICE_UUID).await;
+        let book = insert_book_with_isbn(&db, "Le Livre", Some("978-x")).await;
+        let copy_id = insert_peer_copy(&db, &book, Some(alice)).await;
+        let mut archived: copy::ActiveModel = copy::Entity::find_by_id(copy_id.clone())
+            .one(&db)
+            .await
+            .expect("find")
+            .expect("copy")
+            .into();
+        archived.status = Set("returning".to_string());
+        archived.update(&db).await.expect("archive copy");
+        insert_legacy_request(&db, "req-1", alice, "978-x").await;
+
+        let response = update_outgoing_status(
+            State(db.clone()),
+            axum::extract::Path("req-1".to_string()),
+            Json(serde_json::json!({ "status": "return_confirmed", "library_uuid": ALICE_UUID })),
+        )
+        .await
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            copy::Entity::find_by_id(copy_id)
+                .one(&db)
+                .await
+                .expect("find")
+                .is_none(),
+            "the archived copy goes once the lender confirms"
+        );
+        assert_eq!(outgoing_status(&db, "req-1").await, "returned");
+    }
+
+    /// The lender's plaintext return endpoint only answers the requester it names,
+    /// and acknowledges a replayed return again.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn the_return_endpoint_acknowledges_only_the_requester() {
+        let db = setup_db().await;
+        let alice = insert_peer(&db, "alice", ALICE_UUID).await;
+        let _bob = insert_peer(&db, "bob", BOB_UUID).await;
+        insert_incoming_request(&db, "inc-1", alice).await;
+        let state = crate::infrastructure::AppState::new(db.clone());
+
+        let returned = |uuid: &'static str| {
+            receive_loan_returned(
+                State(state.clone()),
+                Json(LoanReturnedPayload {
+                    loan_id: "inc-1".to_string(),
+                    library_uuid: Some(uuid.to_string()),
+                }),
+            )
+        };
+
+        let intruder = returned(BOB_UUID).await.into_response();
+        assert_eq!(intruder.status(), StatusCode::FORBIDDEN);
+
+        for _ in 0..2 {
+            let response = returned(ALICE_UUID).await.into_response();
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(body["return_confirmed"], serde_json::json!(true));
+        }
+        let req = crate::models::p2p_request::Entity::find_by_id("inc-1")
+            .one(&db)
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(req.status, "returned");
+    }
+
     /// A cancel with no identity is anonymous: it must not delete the request.
     #[tokio::test(flavor = "multi_thread")]
     async fn a_plaintext_cancel_without_identity_is_refused() {
@@ -1323,6 +1449,8 @@ mod multi_lender_borrow_tests {
                 book_isbn: "978-x".to_string(),
                 book_title: "Le Livre".to_string(),
                 requester_request_id: None,
+                hold: false,
+                library_uuid: None,
             }),
         )
         .await
@@ -1377,3 +1505,352 @@ mod multi_lender_borrow_tests {
         assert!(!created.auto_approve);
     }
 }
+
+/// Hold shelf: `ready` reserves a copy without lending it, `accepted` lends
+/// that same copy at handover, and `rejected` puts it back on the shelf.
+#[cfg(test)]
+mod hold_shelf_tests {
+    use super::*;
+    use crate::db;
+    use crate::infrastructure::AppState;
+    use crate::models::{book, copy, loan, p2p_request};
+    use sea_orm::{EntityTrait, PaginatorTrait, Set};
+
+    const BOB_LIBRARY: &str = "bob-library";
+
+    async fn setup() -> (AppState, String) {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let now = chrono::Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Le Livre".to_string()),
+            isbn: Set(Some("978-x".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert book");
+        let copy = copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(crate::utils::library_helpers::resolve_library_id(&db)
+                .await
+                .expect("library")),
+            status: Set("available".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert copy");
+        // Unroutable URL: the status updates sent to the requester just fail.
+        let peer = peer::ActiveModel {
+            name: Set("bob".to_string()),
+            url: Set("http://127.0.0.1:9".to_string()),
+            library_uuid: Set(Some(BOB_LIBRARY.to_string())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert peer");
+        p2p_request::ActiveModel {
+            id: Set("req-1".to_string()),
+            from_peer_id: Set(peer.id),
+            book_isbn: Set("978-x".to_string()),
+            book_title: Set("Le Livre".to_string()),
+            status: Set("pending".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert request");
+        (AppState::new(db), copy.id)
+    }
+
+    async fn set_status(state: &AppState, status: &str) -> StatusCode {
+        update_request_status(
+            State(state.clone()),
+            axum::extract::Path("req-1".to_string()),
+            Ok(crate::infrastructure::auth::LoopbackNoBrowser),
+            Json(RequestAction {
+                status: status.to_string(),
+                library_uuid: None,
+            }),
+        )
+        .await
+        .into_response()
+        .status()
+    }
+
+    /// `returned` from a caller outside the owner guard, naming `library_uuid`.
+    async fn return_remotely(state: &AppState, library_uuid: Option<&str>) -> StatusCode {
+        update_request_status(
+            State(state.clone()),
+            axum::extract::Path("req-1".to_string()),
+            Err((StatusCode::FORBIDDEN, Json(serde_json::json!({})))),
+            Json(RequestAction {
+                status: "returned".to_string(),
+                library_uuid: library_uuid.map(str::to_string),
+            }),
+        )
+        .await
+        .into_response()
+        .status()
+    }
+
+    async fn copy_status(state: &AppState, copy_id: &str) -> String {
+        copy::Entity::find_by_id(copy_id.to_string())
+            .one(state.db())
+            .await
+            .expect("find")
+            .expect("copy")
+            .status
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_held_request_reserves_the_copy_and_lends_it_at_handover() {
+        let (state, copy_id) = setup().await;
+
+        assert_eq!(set_status(&state, "ready").await, StatusCode::OK);
+        let req = p2p_request::Entity::find_by_id("req-1")
+            .one(state.db())
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(req.status, "ready");
+        assert_eq!(req.held_copy_id.as_deref(), Some(copy_id.as_str()));
+        assert!(req.hold_expires_at.is_some());
+        assert_eq!(copy_status(&state, &copy_id).await, "reserved");
+        assert_eq!(loan::Entity::find().count(state.db()).await.unwrap(), 0);
+
+        assert_eq!(set_status(&state, "accepted").await, StatusCode::OK);
+        let loan = loan::Entity::find()
+            .one(state.db())
+            .await
+            .expect("find")
+            .expect("loan created at handover");
+        assert_eq!(loan.copy_id, copy_id);
+        assert_eq!(copy_status(&state, &copy_id).await, "loaned");
+        let req = p2p_request::Entity::find_by_id("req-1")
+            .one(state.db())
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(req.held_copy_id, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn withdrawing_a_hold_puts_the_copy_back() {
+        let (state, copy_id) = setup().await;
+
+        assert_eq!(set_status(&state, "ready").await, StatusCode::OK);
+        assert_eq!(set_status(&state, "rejected").await, StatusCode::OK);
+
+        assert_eq!(copy_status(&state, &copy_id).await, "available");
+        assert_eq!(loan::Entity::find().count(state.db()).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_remote_return_is_only_taken_from_the_requester() {
+        let (state, copy_id) = setup().await;
+        assert_eq!(set_status(&state, "accepted").await, StatusCode::OK);
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set("carol".to_string()),
+            url: Set("http://127.0.0.1:10".to_string()),
+            library_uuid: Set(Some("carol-library".to_string())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(state.db())
+        .await
+        .expect("insert peer");
+
+        assert_eq!(return_remotely(&state, None).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            return_remotely(&state, Some("carol-library")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(copy_status(&state, &copy_id).await, "loaned");
+
+        assert_eq!(
+            return_remotely(&state, Some(BOB_LIBRARY)).await,
+            StatusCode::OK
+        );
+        let req = p2p_request::Entity::find_by_id("req-1")
+            .one(state.db())
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(req.status, "returned");
+    }
+}
+
+/// Outgoing request sync: the lender's status-query endpoint, and a polled
+/// answer applied to the borrower's request with its notification.
+#[cfg(test)]
+mod outgoing_sync_tests {
+    use super::*;
+    use crate::db;
+    use crate::models::{copy, notification, p2p_outgoing_request, p2p_request};
+    use sea_orm::{EntityTrait, Set};
+
+    const ALICE_UUID: &str = "6f1d1a4e-0000-4000-8000-0000000000a1";
+    const BOB_UUID: &str = "6f1d1a4e-0000-4000-8000-0000000000b0";
+
+    async fn insert_peer(db: &DatabaseConnection, name: &str, library_uuid: &str) -> peer::Model {
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set(name.to_string()),
+            url: Set(format!("http://{name}.local:8000")),
+            library_uuid: Set(Some(library_uuid.to_string())),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert peer")
+    }
+
+    async fn insert_outgoing(
+        db: &DatabaseConnection,
+        id: &str,
+        to_peer_id: i32,
+    ) -> p2p_outgoing_request::Model {
+        let now = chrono::Utc::now().to_rfc3339();
+        p2p_outgoing_request::ActiveModel {
+            id: Set(id.to_string()),
+            to_peer_id: Set(to_peer_id),
+            book_isbn: Set("978-sync".to_string()),
+            book_title: Set("Le Livre".to_string()),
+            status: Set("pending".to_string()),
+            lender_request_id: Set(None),
+            book_id: Set(None),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await
+        .expect("insert outgoing request")
+    }
+
+    async fn query(db: &DatabaseConnection, library_uuid: &str) -> (StatusCode, serde_json::Value) {
+        let response = query_request_status(
+            State(db.clone()),
+            Json(RequestStatusQuery {
+                requester_request_id: "out-1".to_string(),
+                library_uuid: Some(library_uuid.to_string()),
+            }),
+        )
+        .await
+        .into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    /// A keyless borrower learns the status of its own request, and only its own.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn the_status_query_answers_only_the_requester() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let alice = insert_peer(&db, "alice", ALICE_UUID).await;
+        let _bob = insert_peer(&db, "bob", BOB_UUID).await;
+        let now = chrono::Utc::now().to_rfc3339();
+        p2p_request::ActiveModel {
+            id: Set("inc-1".to_string()),
+            from_peer_id: Set(alice.id),
+            book_isbn: Set("978-sync".to_string()),
+            book_title: Set("Le Livre".to_string()),
+            status: Set("rejected".to_string()),
+            requester_request_id: Set(Some("out-1".to_string())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert incoming request");
+
+        let (status, body) = query(&db, ALICE_UUID).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "rejected");
+
+        let (status, body) = query(&db, BOB_UUID).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "not_found");
+
+        let (status, _) = query(&db, "6f1d1a4e-0000-4000-8000-00000000dead").await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    /// A rejection the callback never delivered is applied once, with its notification.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_polled_rejection_updates_the_request_and_notifies() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let lender = insert_peer(&db, "alice", ALICE_UUID).await;
+        let outgoing = insert_outgoing(&db, "out-1", lender.id).await;
+
+        let report = json!({ "requester_request_id": "out-1", "status": "rejected" });
+        assert!(apply_polled_status(&db, &outgoing, &lender, &report).await);
+
+        let stored = p2p_outgoing_request::Entity::find_by_id("out-1")
+            .one(&db)
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(stored.status, "rejected");
+        assert!(!apply_polled_status(&db, &stored, &lender, &report).await);
+
+        let notifications = notification::Entity::find()
+            .filter(notification::Column::EventType.eq("borrow_rejected"))
+            .all(&db)
+            .await
+            .expect("notifications");
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].ref_id.as_deref(), Some("out-1"));
+    }
+
+    /// A polled acceptance stands in for the lost confirmation: the borrowed
+    /// copy is created and the lender's request id recorded.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_polled_acceptance_creates_the_borrowed_copy() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let lender = insert_peer(&db, "alice", ALICE_UUID).await;
+        let outgoing = insert_outgoing(&db, "out-1", lender.id).await;
+
+        let report = json!({
+            "requester_request_id": "out-1",
+            "status": "accepted",
+            "request_id": "inc-1",
+            "isbn": "978-sync",
+            "title": "Le Livre",
+            "lender_name": "Alice",
+            "due_date": "2026-12-01",
+        });
+        assert!(apply_polled_status(&db, &outgoing, &lender, &report).await);
+
+        let stored = p2p_outgoing_request::Entity::find_by_id("out-1")
+            .one(&db)
+            .await
+            .expect("find")
+            .expect("request");
+        assert_eq!(stored.status, "accepted");
+        assert_eq!(stored.lender_request_id.as_deref(), Some("inc-1"));
+        let borrowed = copy::Entity::find()
+            .filter(copy::Column::Status.eq("borrowed"))
+            .all(&db)
+            .await
+            .expect("copies");
+        assert_eq!(borrowed.len(), 1);
+        assert_eq!(borrowed[0].lender_peer_id, Some(lender.id));
+    }
+}
@@ -1,6 +1,7 @@
 //! Lender-initiated loan offers and P2P loan confirmations.
 
 use super::*;
+use crate::domain::LendingTerms;
 use crate::models::peer;
 use axum::{
     extract::{Json, Path, State},
@@ -12,7 +13,7 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Qu
 use serde::Deserialize;
 use serde_json::json;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct OfferLoanRequest {
     pub book_id: Option<String>,
     pub book_isbn: Option<String>,
@@ -23,6 +24,18 @@ pub struct OfferLoanRequest {
 /// Lender initiates a loan to a connected peer. Creates the loan locally and
 /// notifies the peer via E2EE (with relay fallback) so a borrowed copy appears
 /// on the borrower's device.
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/offer-loan",
+    tag = "peer-requests",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = OfferLoanRequest,
+    responses(
+        (status = 200, description = "Loan created and offered to the peer"),
+        (status = 404, description = "Peer or book not found"),
+        (status = 409, description = "No available copy")
+    )
+)]
 pub async fn offer_loan(
     State(state): State<crate::infrastructure::AppState>,
     Path(peer_id): Path<i32>,
@@ -146,8 +159,12 @@ pub async fn offer_loan(
         }
     };
 
-    // 5. Calculate loan duration and create loan
-    let duration_days = resolve_loan_duration_days(db, &book.id).await;
+    // 5. Calculate loan duration (capped by the copy's lending terms) and create loan
+    let terms = LendingTerms::from_column(available_copy.lending_terms.as_deref());
+    let duration_days = resolve_loan_duration_days(db, &book.id, &peer).await;
+    let duration_days = terms
+        .as_ref()
+        .map_or(duration_days, |t| t.cap_duration(duration_days));
     let due = Utc::now() + chrono::Duration::days(duration_days);
     let due_date_str = due.format("%Y-%m-%d").to_string();
 
@@ -168,6 +185,7 @@ pub async fn offer_loan(
         loan_date: Set(Utc::now().to_rfc3339()),
         due_date: Set(due.to_rfc3339()),
         status: Set("active".to_string()),
+        lending_terms: Set(terms.as_ref().map(|t| t.to_column())),
         created_at: Set(Utc::now().to_rfc3339()),
         updated_at: Set(Utc::now().to_rfc3339()),
         ..Default::default()
@@ -206,6 +224,7 @@ pub async fn offer_loan(
             requester_request_id: Set(None),
             created_at: Set(Utc::now().to_rfc3339()),
             updated_at: Set(Utc::now().to_rfc3339()),
+            ..Default::default()
         };
         if let Err(e) = p2p_request::Entity::insert(req).exec(db).await {
             tracing::warn!("Failed to create p2p_request for offer_loan: {e}");
@@ -230,6 +249,7 @@ pub async fn offer_loan(
         ),
         "lender_name": lender_name,
         "due_date": due_date_str,
+        "terms": terms,
         "request_id": request_id,
         // Our stable identity. The plaintext endpoint has no authenticated sender,
         // so this is what lets the borrower resolve us to their local `peers` row
@@ -251,7 +271,7 @@ pub async fn offer_loan(
         Ok(None) => {
             // E2EE not available -- fall back to plaintext
             if let Ok(validated) = validate_url(&peer.url) {
-                let client = get_safe_client();
+                let client = get_safe_client_for(&validated);
                 match client
                     .post(format!("{validated}/api/peers/loans/offer"))
                     .json(&offer_payload)
@@ -281,13 +301,14 @@ pub async fn offer_loan(
             "loan_id": loan_insert.id,
             "contact_id": peer_contact.id,
             "due_date": due_date_str,
+            "terms": terms,
             "notification_sent": notification_sent,
         })),
     )
         .into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct LoanConfirmation {
     pub isbn: Option<String>,
     pub title: String,
@@ -298,10 +319,21 @@ pub struct LoanConfirmation {
     pub request_id: Option<String>,
     /// Borrower's outgoing request ID (for precise confirmation matching)
     pub requester_request_id: Option<String>,
+    /// Lending terms of the lent copy. Absent from lenders that predate them.
+    pub terms: Option<LendingTerms>,
 }
 
 /// Receive loan confirmation from lender
 /// Creates the book (if not exists) and a borrowed copy in the borrower's library
+#[utoipa::path(
+    post,
+    path = "/api/peers/loans/confirm",
+    tag = "peer-transport",
+    request_body = LoanConfirmation,
+    responses(
+        (status = 200, description = "Accepted request recorded as a borrowed copy")
+    )
+)]
 pub async fn receive_loan_confirmation(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<LoanConfirmation>,
@@ -315,12 +347,13 @@ pub async fn receive_loan_confirmation(
         payload.requester_request_id
     );
 
-    // Guard: verify a matching pending outgoing request exists.
+    // Guard: verify a matching open outgoing request exists (pending, or
+    // waiting for a copy on the lender's side).
     // This prevents stale relay messages from creating orphan borrowed copies.
     let has_matching_request = if let Some(ref rr_id) = payload.requester_request_id {
         // Precise match by borrower's outgoing request ID
         p2p_outgoing_request::Entity::find_by_id(rr_id)
-            .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+            .filter(p2p_outgoing_request::Column::Status.is_in(AWAITING_LOAN_STATUSES))
             .one(&db)
             .await
             .ok()
@@ -332,7 +365,7 @@ pub async fn receive_loan_confirmation(
         if !isbn_filter.is_empty() {
             p2p_outgoing_request::Entity::find()
                 .filter(p2p_outgoing_request::Column::BookIsbn.eq(&isbn_filter))
-                .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+                .filter(p2p_outgoing_request::Column::Status.is_in(AWAITING_LOAN_STATUSES))
                 .one(&db)
                 .await
                 .ok()
@@ -407,6 +440,7 @@ pub async fn receive_loan_confirmation(
         // resolves it from the peer row named by `lender_peer_id` (ADR-049).
         lender_library_uuid: None,
         lender_request_id: payload.request_id.as_deref(),
+        lending_terms: payload.terms.as_ref(),
     };
 
     let result = match create_borrowed_copy(&db, &params).await {
@@ -461,7 +495,7 @@ pub async fn receive_loan_confirmation(
         .into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct LoanOffer {
     pub isbn: Option<String>,
     pub title: String,
@@ -475,6 +509,8 @@ pub struct LoanOffer {
     /// row. Absent from offers sent by builds that predate this field, so the
     /// decoder tolerates its absence rather than rejecting the whole offer.
     pub library_uuid: Option<String>,
+    /// Lending terms of the lent copy. Absent from lenders that predate them.
+    pub terms: Option<LendingTerms>,
 }
 
 /// POST /api/peers/loans/offer -- Plaintext endpoint for receiving a loan offer.
@@ -482,6 +518,15 @@ pub struct LoanOffer {
 /// Called when a lender initiates a loan to us (no prior borrow request).
 /// Unlike `receive_loan_confirmation`, this does NOT require a matching
 /// `p2p_outgoing_request` since the borrower never requested the loan.
+#[utoipa::path(
+    post,
+    path = "/api/peers/loans/offer",
+    tag = "peer-transport",
+    request_body = LoanOffer,
+    responses(
+        (status = 200, description = "Offered loan recorded as a borrowed copy")
+    )
+)]
 pub async fn receive_loan_offer(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<LoanOffer>,
@@ -510,6 +555,7 @@ pub async fn receive_loan_offer(
         // device notify the lender on return (ADR-049).
         lender_library_uuid: payload.library_uuid.as_deref(),
         lender_request_id: payload.request_id.as_deref(),
+        lending_terms: payload.terms.as_ref(),
     };
 
     let result = match create_borrowed_copy(&db, &params).await {
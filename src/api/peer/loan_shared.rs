@@ -7,6 +7,10 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Qu
 use serde_json::json;
 use tracing::info;
 
+/// Outgoing statuses a loan confirmation can close: a request awaiting an
+/// answer, one on the lender's waitlist and one on its hold shelf.
+pub(crate) const AWAITING_LOAN_STATUSES: [&str; 3] = ["pending", "waitlisted", "ready"];
+
 /// Result of a successful loan acceptance on the lender side.
 pub(crate) struct LoanAcceptResult {
     pub lender_name: String,
@@ -14,16 +18,28 @@ pub(crate) struct LoanAcceptResult {
     pub book_isbn: Option<String>,
     pub book_title: String,
     pub book_cover_url: Option<String>,
+    /// Lending terms snapshotted on the loan, forwarded to the borrower.
+    pub terms: Option<crate::domain::LendingTerms>,
 }
 
-/// Resolve the effective loan duration (in days) for a given book.
+/// Resolve the effective loan duration (in days) for lending a book to a peer.
 ///
-/// Reads from `loan_settings` (global default + per-book override).
+/// Reads the loan policy from `loan_settings` (per-book override, the peer's
+/// own override, the `Library` contact-type duration, then the global default).
 /// Falls back to 21 days if the settings table is unreachable.
-pub(crate) async fn resolve_loan_duration_days(db: &DatabaseConnection, book_id: &str) -> i64 {
+pub(crate) async fn resolve_loan_duration_days(
+    db: &DatabaseConnection,
+    book_id: &str,
+    peer: &peer::Model,
+) -> i64 {
     let repo = crate::infrastructure::SeaOrmLoanSettingsRepository::new(db.clone());
     use crate::domain::LoanSettingsRepository;
-    match repo.get_effective_duration(book_id).await {
+    // P2P borrowers are recorded as `Library` contacts.
+    let borrower = crate::domain::LoanBorrower {
+        contact_type: Some("Library".to_string()),
+        peer_id: Some(peer.id),
+    };
+    match repo.get_effective_duration_for(book_id, &borrower).await {
         Ok(days) => days as i64,
         Err(e) => {
             tracing::warn!("Failed to read loan settings, using 21-day default: {e}");
@@ -111,7 +127,11 @@ pub(crate) async fn perform_loan_acceptance(
     let lib_id = crate::utils::library_helpers::resolve_library_id(db)
         .await
         .map_err(|e| format!("No library: {e}"))?;
-    let duration_days = resolve_loan_duration_days(db, &book.id).await;
+    let terms = crate::domain::LendingTerms::from_column(copy.lending_terms.as_deref());
+    let duration_days = resolve_loan_duration_days(db, &book.id, peer).await;
+    let duration_days = terms
+        .as_ref()
+        .map_or(duration_days, |t| t.cap_duration(duration_days));
     let due = Utc::now() + chrono::Duration::days(duration_days);
     let loan = loan::ActiveModel {
         copy_id: Set(copy.id.clone()),
@@ -120,6 +140,7 @@ pub(crate) async fn perform_loan_acceptance(
         loan_date: Set(Utc::now().to_rfc3339()),
         due_date: Set(due.to_rfc3339()),
         status: Set("active".to_string()),
+        lending_terms: Set(terms.as_ref().map(|t| t.to_column())),
         created_at: Set(Utc::now().to_rfc3339()),
         updated_at: Set(Utc::now().to_rfc3339()),
         ..Default::default()
@@ -164,6 +185,7 @@ pub(crate) async fn perform_loan_acceptance(
             Some(book.updated_at.as_str()),
             hub_prefix.as_deref(),
         ),
+        terms,
     })
 }
 
@@ -189,6 +211,17 @@ pub(crate) struct BorrowedCopyParams<'a> {
     /// copied onto the copy so the return notification survives on a device that
     /// never held the outgoing request (ADR-049).
     pub lender_request_id: Option<&'a str>,
+    /// Lending terms sent by the lender with the confirmation or offer, stored
+    /// on the borrowed copy so the borrower sees the agreed terms.
+    pub lending_terms: Option<&'a crate::domain::LendingTerms>,
+}
+
+/// Read the `terms` object of a loan confirmation or offer payload. Missing
+/// (older lender), null or malformed terms all read as "no terms".
+pub(crate) fn lending_terms_from_payload(
+    payload: &serde_json::Value,
+) -> Option<crate::domain::LendingTerms> {
+    serde_json::from_value(payload.get("terms")?.clone()).ok()
 }
 
 /// Resolve the local `peers` row that a plaintext payload claims to come from.
@@ -211,6 +244,57 @@ pub(crate) async fn resolve_peer_by_library_uuid(
         .flatten()
 }
 
+/// Refuse a plaintext return of `req` unless it comes from the requester.
+///
+/// A requester that completed the key exchange returns over the encrypted
+/// channel only; any other must name itself by the `library_uuid` of the peer
+/// that made the request. Returns the `403` to answer with, `None` when the
+/// caller is the requester.
+pub(crate) async fn refuse_unless_requester(
+    db: &DatabaseConnection,
+    req: &crate::models::p2p_request::Model,
+    library_uuid: Option<&str>,
+) -> Option<axum::response::Response> {
+    use axum::response::IntoResponse;
+
+    let requester = peer::Entity::find_by_id(req.from_peer_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten();
+    if requester.is_some_and(|p| p.key_exchange_done) {
+        tracing::warn!(
+            "Plaintext return for request {} names a key-exchanged borrower; refusing",
+            req.id
+        );
+        return Some(
+            (
+                StatusCode::FORBIDDEN,
+                axum::Json(json!({ "error": "This loan is served over the encrypted channel" })),
+            )
+                .into_response(),
+        );
+    }
+    if resolve_peer_by_library_uuid(db, library_uuid)
+        .await
+        .map(|p| p.id)
+        != Some(req.from_peer_id)
+    {
+        tracing::warn!(
+            "Plaintext return for request {} carries no matching sender identity; refusing",
+            req.id
+        );
+        return Some(
+            (
+                StatusCode::FORBIDDEN,
+                axum::Json(json!({ "error": "Sender does not own this loan" })),
+            )
+                .into_response(),
+        );
+    }
+    None
+}
+
 /// Find the temporary copy already borrowed from `lender_peer_id` on this book row.
 ///
 /// A book row is a bibliographic record carrying many copies, so it can legitimately
@@ -265,6 +349,26 @@ pub(crate) async fn create_borrowed_copy(
     params: &BorrowedCopyParams<'_>,
 ) -> Result<BorrowedCopyResult, (StatusCode, serde_json::Value)> {
     use crate::models::{book, copy};
+    use crate::utils::sanitize::{
+        MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_TITLE_CHARS, MAX_URL_CHARS, line,
+    };
+
+    // Every text field was written by the lender.
+    let title = line(params.title, MAX_TITLE_CHARS);
+    let isbn = params.isbn.map(|s| line(s, MAX_CODE_CHARS));
+    let author = params.author.map(|s| line(s, MAX_NAME_CHARS));
+    let cover_url = params.cover_url.map(|s| line(s, MAX_URL_CHARS));
+    let lender_name = line(params.lender_name, MAX_NAME_CHARS);
+    let due_date = line(params.due_date, MAX_CODE_CHARS);
+    let params = &BorrowedCopyParams {
+        title: &title,
+        isbn: isbn.as_deref(),
+        author: author.as_deref(),
+        cover_url: cover_url.as_deref(),
+        lender_name: &lender_name,
+        due_date: &due_date,
+        ..*params
+    };
 
     // An empty ISBN is not an ISBN. Peers send `"isbn": ""` as readily as they omit the
     // field, and `Isbn.eq("")` matches every row that stores the empty string, so the
@@ -430,6 +534,7 @@ pub(crate) async fn create_borrowed_copy(
         lender_request_id: Set(lender_request_id),
         borrow_due_date: Set(Some(params.due_date.to_string())),
         borrow_source: Set(Some(crate::domain::BorrowSource::Peer.as_str().to_string())),
+        lending_terms: Set(params.lending_terms.map(|t| t.to_column())),
         acquisition_date: Set(Some(now.clone())),
         created_at: Set(now.clone()),
         updated_at: Set(now),
@@ -452,6 +557,315 @@ pub(crate) async fn create_borrowed_copy(
     }
 }
 
+/// Lender side: close the active loan behind an accepted request the borrower
+/// reports as returned, put the copy back on the shelf and emit
+/// `book_returned`. Requests are not linked to loans, so the loan is inferred:
+/// the active loan of a copy of the requested book held by the requester's
+/// `Library` contact. Returns whether such a loan was found.
+pub(crate) async fn record_peer_return(
+    db: &DatabaseConnection,
+    req: &crate::models::p2p_request::Model,
+) -> bool {
+    use crate::models::{book, contact, copy, loan};
+
+    // Graceful: if the peer is gone, the caller still updates the request status.
+    let Some(the_peer) = peer::Entity::find_by_id(req.from_peer_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+    else {
+        tracing::warn!(
+            "Peer {} not found for return of request {} - updating request status only",
+            req.from_peer_id,
+            req.id
+        );
+        return false;
+    };
+
+    let Some(the_contact) = contact::Entity::find()
+        .filter(contact::Column::Name.eq(&the_peer.name))
+        .filter(contact::Column::Type.eq("Library"))
+        .one(db)
+        .await
+        .unwrap_or(None)
+    else {
+        return false;
+    };
+    let Some(book) = book::Entity::find()
+        .filter(book::Column::Isbn.eq(&req.book_isbn))
+        .one(db)
+        .await
+        .unwrap_or(None)
+    else {
+        return false;
+    };
+
+    let copy_ids: Vec<String> = copy::Entity::find()
+        .filter(copy::Column::BookId.eq(book.id.as_str()))
+        .all(db)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    let Some(l) = loan::Entity::find()
+        .filter(loan::Column::ContactId.eq(the_contact.id))
+        .filter(loan::Column::Status.eq("active"))
+        .filter(loan::Column::CopyId.is_in(copy_ids))
+        .one(db)
+        .await
+        .unwrap_or(None)
+    else {
+        return false;
+    };
+
+    let copy_id = l.copy_id.clone();
+    let mut active_loan: loan::ActiveModel = l.into();
+    active_loan.status = Set("returned".to_string());
+    active_loan.return_date = Set(Some(Utc::now().to_rfc3339()));
+    active_loan.updated_at = Set(Utc::now().to_rfc3339());
+    let _ = active_loan.update(db).await;
+
+    if let Some(the_copy) = copy::Entity::find_by_id(copy_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+    {
+        let mut active_copy: copy::ActiveModel = the_copy.into();
+        active_copy.status = Set("available".to_string());
+        let _ = active_copy.update(db).await;
+    }
+
+    tracing::info!(
+        "Processed return for request {} — loan + copy updated",
+        req.id
+    );
+
+    crate::services::notification_service::emit(
+        db,
+        crate::domain::CreateNotification {
+            event_type: crate::domain::NotificationEventType::BookReturned,
+            title: book.title.clone(),
+            body: Some(the_peer.name.clone()),
+            ref_type: Some("loan".to_string()),
+            ref_id: Some(req.id.clone()),
+        },
+    )
+    .await;
+    true
+}
+
+/// Borrower side: the lender acknowledged a return. Delete the copy archived
+/// as `returning` when the return was sent, and emit `return_confirmed`.
+/// Only that lender's archived copy on the loan's book is touched.
+pub(crate) async fn confirm_returned_copy(
+    db: &DatabaseConnection,
+    lender: &peer::Model,
+    book_id: Option<&str>,
+    book_isbn: &str,
+    request_title: &str,
+    loan_id: &str,
+) {
+    use crate::models::copy;
+
+    let book = crate::services::loan_service::resolve_returned_book(db, book_id, book_isbn).await;
+    if let Some(book) = book.as_ref() {
+        match copy::Entity::delete_many()
+            .filter(copy::Column::BookId.eq(book.id.as_str()))
+            .filter(copy::Column::Status.eq("returning"))
+            .filter(copy::Column::LenderPeerId.eq(lender.id))
+            .exec(db)
+            .await
+        {
+            Ok(res) => tracing::info!(
+                "Return confirmed by {}: removed {} archived copies of {}",
+                lender.name,
+                res.rows_affected,
+                book.id
+            ),
+            Err(e) => tracing::warn!("Failed to remove archived copies of {}: {e}", book.id),
+        }
+    }
+
+    crate::services::notification_service::emit(
+        db,
+        crate::domain::CreateNotification {
+            event_type: crate::domain::NotificationEventType::ReturnConfirmed,
+            title: book
+                .map(|b| b.title)
+                .unwrap_or_else(|| request_title.to_string()),
+            body: Some(lender.name.clone()),
+            ref_type: Some("loan".to_string()),
+            ref_id: Some(loan_id.to_string()),
+        },
+    )
+    .await;
+}
+
+/// Tell the requester that their incoming request moved to `status`.
+///
+/// The update is keyed by the requester's own request id so they can match it
+/// to their outgoing request (our local id for older peers). `extra` fields are
+/// merged into the payload. E2EE first; the plaintext status endpoint is only
+/// used when the peer has no E2EE channel, asserting our `library_uuid` so the
+/// requester's ownership check accepts it. An update the requester cannot
+/// receive right now is queued in the outbox (`services::peer_outbox`).
+///
+/// Boxed because the E2EE message handlers spawn it: `try_send_e2ee` reaches
+/// back into the dispatcher, and the compiler cannot prove that cycle `Send`
+/// through an opaque `async fn` type.
+pub(crate) fn send_request_status_update<'a>(
+    state: &'a crate::infrastructure::AppState,
+    peer: &'a peer::Model,
+    req: &'a crate::models::p2p_request::Model,
+    status: &'a str,
+    extra: serde_json::Value,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'a>> {
+    Box::pin(async move {
+        let borrower_loan_id = req
+            .requester_request_id
+            .clone()
+            .unwrap_or_else(|| req.id.clone());
+
+        let mut status_payload = json!({
+            "loan_id": borrower_loan_id,
+            "status": status,
+        });
+        if let (Some(payload), serde_json::Value::Object(extra)) =
+            (status_payload.as_object_mut(), extra)
+        {
+            payload.extend(extra);
+        }
+
+        match super::try_send_e2ee(state, peer, "status_update", status_payload.clone()).await {
+            Ok(Some(_)) => {
+                tracing::info!("E2EE: Status update sent to {} (encrypted)", peer.name);
+            }
+            Err(e) => {
+                // E2EE transport error — message MAY have been delivered.
+                // Do NOT fall back to plaintext to avoid duplicate status updates:
+                // queue it, re-applying the same status is a no-op.
+                tracing::warn!("E2EE: Status update error (no plaintext fallback): {e}");
+                crate::services::peer_outbox::enqueue_or_log(
+                    state.db(),
+                    peer.id,
+                    "status_update",
+                    &status_payload,
+                    None,
+                    &e,
+                )
+                .await;
+            }
+            Ok(None) => {
+                // E2EE not available for this peer — fall back to plaintext.
+                let peer_url = peer.url.clone();
+                let e2ee_payload = status_payload.clone();
+                if let Some(payload) = status_payload.as_object_mut() {
+                    payload.remove("loan_id");
+                    payload.insert(
+                        "library_uuid".to_string(),
+                        json!(state.identity_service.library_uuid()),
+                    );
+                }
+
+                let queue_db = state.db().clone();
+                let peer_id = peer.id;
+                tokio::spawn(async move {
+                    let client = super::get_safe_client_for(&peer_url);
+                    let notify_path = format!("/api/peers/requests/status/{}", borrower_loan_id);
+                    let notify_url = format!("{}{}", peer_url, notify_path);
+
+                    tracing::info!(
+                        "Notifying borrower {} of status change: {} -> {}",
+                        peer_url,
+                        borrower_loan_id,
+                        status_payload["status"]
+                    );
+
+                    match client.put(&notify_url).json(&status_payload).send().await {
+                        Ok(res) => {
+                            tracing::info!("Borrower notified: {}", res.status());
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to notify borrower: {}", e);
+                            let fallback = crate::services::peer_outbox::PlaintextFallback {
+                                method: "PUT",
+                                path: notify_path,
+                                body: status_payload,
+                            };
+                            crate::services::peer_outbox::enqueue_or_log(
+                                &queue_db,
+                                peer_id,
+                                "status_update",
+                                &e2ee_payload,
+                                Some(fallback),
+                                &e.to_string(),
+                            )
+                            .await;
+                        }
+                    }
+                });
+            }
+        }
+    })
+}
+
+/// Put the copy held for a request back on the shelf. Only a copy still
+/// `reserved` is touched: one the owner since marked lost or lent stays as is.
+pub(crate) async fn release_held_copy(db: &DatabaseConnection, copy_id: Option<&str>) {
+    use crate::models::copy;
+
+    let Some(copy_id) = copy_id else {
+        return;
+    };
+    let held = copy::Entity::find_by_id(copy_id.to_owned())
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .filter(|c| c.status == "reserved");
+    if let Some(held) = held {
+        let mut active: copy::ActiveModel = held.into();
+        active.status = Set("available".to_string());
+        active.updated_at = Set(Utc::now().to_rfc3339());
+        if let Err(e) = active.update(db).await {
+            tracing::warn!("Failed to release held copy {copy_id}: {e}");
+        }
+    }
+}
+
+/// Borrower side: emit the notification matching a hold status sent by the
+/// lender (`ready`, or `expired` for a request that was on the hold shelf).
+/// Other statuses, and a plain pending request expiring, are left to the
+/// caller.
+pub(crate) async fn notify_hold_status(
+    db: &DatabaseConnection,
+    previous_status: &str,
+    status: &str,
+    book_title: &str,
+    lender_name: &str,
+    loan_id: &str,
+) {
+    let event_type = match (previous_status, status) {
+        (_, "ready") => crate::domain::NotificationEventType::BorrowReady,
+        ("ready", "expired") => crate::domain::NotificationEventType::BorrowHoldExpired,
+        _ => return,
+    };
+    crate::services::notification_service::emit(
+        db,
+        crate::domain::CreateNotification {
+            event_type,
+            title: book_title.to_string(),
+            body: Some(lender_name.to_string()),
+            ref_type: Some("loan".to_string()),
+            ref_id: Some(loan_id.to_string()),
+        },
+    )
+    .await;
+}
+
 /// ADR-049: `create_borrowed_copy` records the lender's stable identity and the
 /// loan id on the copy at borrow time, so a return from any of the borrower's
 /// synced devices can notify the lender.
@@ -508,6 +922,7 @@ mod create_borrowed_copy_lender_identity_tests {
             lender_peer_id: Some(peer_id),
             lender_library_uuid: None, // not supplied: resolve from the peer row
             lender_request_id: Some("req-42"),
+            lending_terms: None,
         };
         let result = create_borrowed_copy(&db, &params).await.expect("create");
         let copy = fetch_copy(&db, &result.copy_id).await;
@@ -533,6 +948,7 @@ mod create_borrowed_copy_lender_identity_tests {
             lender_peer_id: None, // peer unknown on this device
             lender_library_uuid: Some("lib-carol"),
             lender_request_id: Some("req-7"),
+            lending_terms: None,
         };
         let result = create_borrowed_copy(&db, &params).await.expect("create");
         let copy = fetch_copy(&db, &result.copy_id).await;
@@ -560,6 +976,7 @@ mod create_borrowed_copy_lender_identity_tests {
             lender_peer_id: Some(peer_id),
             lender_library_uuid: None,
             lender_request_id: None, // first pass: no loan id yet
+            lending_terms: None,
         };
         let first = create_borrowed_copy(&db, &base).await.expect("create");
         assert!(!first.already_existed);
@@ -590,3 +1007,77 @@ mod create_borrowed_copy_lender_identity_tests {
         );
     }
 }
+
+/// Lending terms set on the lender's copy cap the loan duration and are
+/// snapshotted on the loan created by `perform_loan_acceptance`.
+#[cfg(test)]
+mod perform_loan_acceptance_terms_tests {
+    use super::*;
+    use crate::db;
+    use crate::domain::LendingTerms;
+    use crate::models::{book, copy, loan};
+
+    #[tokio::test]
+    async fn copy_terms_cap_the_duration_and_travel_with_the_loan() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let now = Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some("978-1".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert book");
+        let terms = LendingTerms {
+            max_duration_days: Some(7),
+            renewable: true,
+            replacement_value: Some(12.5),
+            conditions: None,
+        };
+        copy::ActiveModel {
+            book_id: Set(book.id.clone()),
+            library_id: Set(crate::utils::library_helpers::resolve_library_id(&db)
+                .await
+                .expect("library")),
+            status: Set("available".to_string()),
+            is_temporary: Set(false),
+            lending_terms: Set(Some(terms.to_column())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert copy");
+        let peer = peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://bob.local:8080".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert peer");
+
+        let result = perform_loan_acceptance(&db, "req-1", "978-1", "Dune", &peer)
+            .await
+            .expect("accept");
+
+        assert_eq!(result.terms.as_ref(), Some(&terms));
+        let expected_due = (Utc::now() + chrono::Duration::days(7))
+            .format("%Y-%m-%d")
+            .to_string();
+        assert_eq!(result.due_date, expected_due, "21-day default capped to 7");
+        let loan = loan::Entity::find()
+            .one(&db)
+            .await
+            .expect("query loans")
+            .expect("loan created");
+        assert_eq!(
+            LendingTerms::from_column(loan.lending_terms.as_deref()),
+            Some(terms)
+        );
+    }
+}
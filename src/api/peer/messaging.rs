@@ -570,7 +570,7 @@ pub(crate) async fn mark_peer_invite_stale(db: &DatabaseConnection, peer_id: i32
 
 /// Refresh relay credentials via direct HTTP to the peer's LAN URL.
 async fn refresh_via_lan(peer_model: &peer::Model) -> Option<(String, String, String)> {
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer_model.url);
     let config_url = format!("{}/api/config", peer_model.url.trim_end_matches('/'));
     tracing::debug!(
         "Relay: LAN refresh attempt for peer '{}' at {}",
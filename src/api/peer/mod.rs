@@ -5,31 +5,41 @@
 
 mod admin;
 mod books_cache;
+mod certificates;
 mod connection;
 mod helpers;
 mod loan_offer;
 mod loan_shared;
 mod messaging;
+mod outbox;
+mod protocol;
 mod relay_config;
 mod requests_incoming;
 mod requests_outgoing;
 mod returns;
 mod search;
+mod subscriptions;
 mod sync;
+mod tor_config;
 
 #[cfg(test)]
 mod loan_flow_tests;
 
 pub use admin::*;
 pub use books_cache::*;
+pub use certificates::*;
 pub use connection::*;
 pub use helpers::*;
 pub use loan_offer::*;
 pub(crate) use loan_shared::*;
 pub use messaging::*;
+pub use outbox::*;
+pub use protocol::*;
 pub use relay_config::*;
 pub use requests_incoming::*;
 pub use requests_outgoing::*;
 pub use returns::*;
 pub use search::*;
+pub use subscriptions::*;
 pub use sync::*;
+pub use tor_config::*;
@@ -0,0 +1,89 @@
+//! Outbox endpoints: P2P messages waiting for an unreachable peer
+//! (see `services::peer_outbox`).
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::services::peer_outbox;
+
+/// GET /api/peers/outbox — Messages not yet delivered to a peer.
+#[utoipa::path(
+    get,
+    path = "/api/peers/outbox",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Pending and failed deliveries, oldest first", body = Vec<OutboxEntry>)
+    )
+)]
+pub async fn list_outbox(
+    State(state): State<crate::infrastructure::AppState>,
+) -> impl IntoResponse {
+    match peer_outbox::list(state.db()).await {
+        Ok(entries) => (StatusCode::OK, Json(json!(entries))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/peers/outbox/retry — Retry every pending delivery now.
+#[utoipa::path(
+    post,
+    path = "/api/peers/outbox/retry",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Sweep done; `delivered` messages left the outbox")
+    )
+)]
+pub async fn retry_outbox(
+    State(state): State<crate::infrastructure::AppState>,
+) -> impl IntoResponse {
+    let result = match peer_outbox::retry_all_now(state.db()).await {
+        Ok(_) => peer_outbox::deliver_due(&state).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(delivered) => (StatusCode::OK, Json(json!({ "delivered": delivered }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /api/peers/outbox/{id} — Drop a queued or failed delivery.
+#[utoipa::path(
+    delete,
+    path = "/api/peers/outbox/{id}",
+    tag = "peer-requests",
+    params(("id" = String, Path, description = "Outbox entry id")),
+    responses(
+        (status = 204, description = "Entry dropped"),
+        (status = 404, description = "No such entry")
+    )
+)]
+pub async fn dismiss_outbox_entry(
+    State(state): State<crate::infrastructure::AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match peer_outbox::dismiss(state.db(), &id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Outbox entry not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,98 @@
+//! Protocol version negotiation (see `services::peer_protocol`).
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sea_orm::EntityTrait;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::models::peer;
+use crate::services::peer_protocol::{self, Handshake};
+
+/// POST /api/peers/handshake - Exchange protocol version and features
+///
+/// Answers with this library's handshake. A caller claiming a library we
+/// hold other capabilities for is asked again at its next sync.
+#[utoipa::path(
+    post,
+    path = "/api/peers/handshake",
+    tag = "peer-transport",
+    request_body = Handshake,
+    responses(
+        (status = 200, description = "This library's protocol version, features and UUID", body = Handshake)
+    )
+)]
+pub async fn handshake(State(state): State<AppState>, Json(theirs): Json<Handshake>) -> Response {
+    if let Err(e) = peer_protocol::received(state.db(), &theirs.sanitized()).await {
+        tracing::warn!("handshake: {e}");
+    }
+    Json(Handshake::ours(&state)).into_response()
+}
+
+async fn find_peer(state: &AppState, id: i32) -> Result<peer::Model, Response> {
+    match peer::Entity::find_by_id(id).one(state.db()).await {
+        Ok(Some(found)) => Ok(found),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Peer not found" })),
+        )
+            .into_response()),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response()),
+    }
+}
+
+/// GET /api/peers/:id/capabilities - What a peer supports
+#[utoipa::path(
+    get,
+    path = "/api/peers/{id}/capabilities",
+    tag = "peer-transport",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Protocol version and features, as last negotiated", body = PeerCapabilities),
+        (status = 404, description = "Peer not found")
+    )
+)]
+pub async fn get_peer_capabilities(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    let found = match find_peer(&state, id).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    match peer_protocol::capabilities(state.db(), found.id).await {
+        Ok(capabilities) => Json(capabilities).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// POST /api/peers/:id/handshake - Negotiate with a peer now
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/handshake",
+    tag = "peer-transport",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "What the peer answered", body = PeerCapabilities),
+        (status = 404, description = "Peer not found"),
+        (status = 502, description = "Peer unreachable or answered as another library")
+    )
+)]
+pub async fn negotiate_with_peer(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    let found = match find_peer(&state, id).await {
+        Ok(found) => found,
+        Err(response) => return response,
+    };
+    match peer_protocol::negotiate(&state, &found).await {
+        Ok(capabilities) => Json(capabilities).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(json!({ "error": e }))).into_response(),
+    }
+}
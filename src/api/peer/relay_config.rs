@@ -11,7 +11,7 @@ use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde::Deserialize;
 use serde_json::json;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct SetupRelayRequest {
     pub relay_url: String,
 }
@@ -19,6 +19,17 @@ pub struct SetupRelayRequest {
 /// POST /api/peers/relay/setup — Register a mailbox on a relay hub.
 ///
 /// Calls the relay hub to create a new mailbox, then stores the config locally.
+#[utoipa::path(
+    post,
+    path = "/api/peers/relay/setup",
+    tag = "relay",
+    request_body = SetupRelayRequest,
+    responses(
+        (status = 200, description = "Mailbox created on the relay and saved"),
+        (status = 400, description = "Invalid relay URL"),
+        (status = 502, description = "Relay unreachable")
+    )
+)]
 pub async fn setup_relay(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<SetupRelayRequest>,
@@ -216,6 +227,15 @@ async fn apply_relay_setup(
 }
 
 /// GET /api/peers/relay/config — Get current relay config (if any).
+#[utoipa::path(
+    get,
+    path = "/api/peers/relay/config",
+    tag = "relay",
+    responses(
+        (status = 200, description = "Current relay configuration"),
+        (status = 404, description = "No relay configured")
+    )
+)]
 pub async fn get_relay_config_endpoint(
     State(state): State<crate::infrastructure::AppState>,
 ) -> impl IntoResponse {
@@ -244,6 +264,14 @@ pub async fn get_relay_config_endpoint(
 ///
 /// Before deleting the local config, attempts to delete the mailbox on the hub
 /// so it does not linger as an orphan accepting stale deposits.
+#[utoipa::path(
+    delete,
+    path = "/api/peers/relay/config",
+    tag = "relay",
+    responses(
+        (status = 200, description = "Relay configuration removed")
+    )
+)]
 pub async fn delete_relay_config_endpoint(
     State(state): State<crate::infrastructure::AppState>,
 ) -> impl IntoResponse {
@@ -307,7 +335,7 @@ pub async fn delete_relay_config_endpoint(
 ///
 /// POST /api/peers/relay/library_request
 /// Body: { "peer_id": int, "request_type": "manifest"|"page"|"search", ... }
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct RelayLibraryRequest {
     pub peer_id: i32,
     pub request_type: String,
@@ -319,6 +347,18 @@ pub struct RelayLibraryRequest {
     pub query: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/relay/library_request",
+    tag = "relay",
+    request_body = RelayLibraryRequest,
+    responses(
+        (status = 200, description = "Answer received directly"),
+        (status = 202, description = "Request queued on the relay; await the correlation id"),
+        (status = 404, description = "Peer not found"),
+        (status = 503, description = "No relay configured")
+    )
+)]
 pub async fn relay_library_request(
     State(state): State<crate::infrastructure::AppState>,
     Json(req): Json<RelayLibraryRequest>,
@@ -458,7 +498,7 @@ pub async fn relay_library_request(
 ///
 /// POST /api/peers/relay/await_response
 /// Body: { "correlation_id": "uuid", "timeout_ms": 5000 }
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct AwaitRelayResponse {
     pub correlation_id: String,
     #[serde(default = "default_timeout_ms")]
@@ -469,6 +509,17 @@ fn default_timeout_ms() -> u64 {
     5000
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/relay/await_response",
+    tag = "relay",
+    request_body = AwaitRelayResponse,
+    responses(
+        (status = 200, description = "Relayed answer"),
+        (status = 408, description = "No answer before the timeout"),
+        (status = 410, description = "Unknown or expired correlation id")
+    )
+)]
 pub async fn await_relay_response(
     State(state): State<crate::infrastructure::AppState>,
     Json(req): Json<AwaitRelayResponse>,
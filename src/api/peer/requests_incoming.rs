@@ -1,6 +1,7 @@
 //! Lender-side loan requests: receiving, listing, accepting or rejecting.
 
 use super::*;
+use crate::infrastructure::auth::LoopbackNoBrowser;
 use crate::models::peer;
 use axum::{
     extract::{Json, Path, State},
@@ -14,22 +15,102 @@ use serde::Deserialize;
 use serde_json::json;
 use tracing::{error, info};
 
-/// Delete all non-pending incoming requests (cleanup).
-pub async fn clear_incoming_requests(State(db): State<DatabaseConnection>) -> impl IntoResponse {
-    use sea_orm::ConnectionTrait;
-    let result = db
-        .execute(sea_orm::Statement::from_string(
-            db.get_database_backend(),
-            "DELETE FROM p2p_requests WHERE status != 'pending'".to_owned(),
-        ))
-        .await;
-
-    match result {
-        Ok(r) => (
-            StatusCode::OK,
-            Json(json!({ "deleted": r.rows_affected() })),
+/// Delete all closed incoming requests (cleanup). Pending requests and those
+/// on the hold shelf are kept: see `loan_service::delete_closed_incoming_requests`.
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/clear",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Closed incoming requests deleted")
+    )
+)]
+pub async fn clear_incoming_requests(
+    State(state): State<crate::infrastructure::AppState>,
+) -> impl IntoResponse {
+    match crate::services::loan_service::delete_closed_incoming_requests(
+        state.p2p_request_repo.as_ref(),
+    )
+    .await
+    {
+        Ok(deleted) => (StatusCode::OK, Json(json!({ "deleted": deleted }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("{e:?}") })),
         )
             .into_response(),
+    }
+}
+
+#[derive(Deserialize, Default, utoipa::ToSchema)]
+pub struct ExpireRequestsPayload {
+    /// Expire pending requests older than this many days. Omitted = the
+    /// configured `request_expiry_days`.
+    pub older_than_days: Option<i32>,
+}
+
+/// POST /peers/requests/expire: expire stale pending requests now, incoming
+/// and outgoing, instead of waiting for the periodic sweep.
+#[utoipa::path(
+    post,
+    path = "/api/peers/requests/expire",
+    tag = "peer-requests",
+    request_body(content = Option<ExpireRequestsPayload>, description = "Defaults to the configured `request_expiry_days`"),
+    responses(
+        (status = 200, description = "Number of requests expired per side"),
+        (status = 400, description = "Expiry is disabled and no age was given")
+    )
+)]
+pub async fn expire_requests(
+    State(state): State<crate::infrastructure::AppState>,
+    payload: Option<Json<ExpireRequestsPayload>>,
+) -> impl IntoResponse {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let days = match payload.older_than_days {
+        Some(days) => days,
+        None => match state.loan_settings_repo.get_settings().await {
+            Ok(settings) => settings.request_expiry_days,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+        },
+    };
+    if days <= 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "Request expiry is disabled; pass older_than_days" })),
+        )
+            .into_response();
+    }
+
+    match crate::services::request_cleanup::expire_stale_requests(&state, days).await {
+        Ok(counts) => (StatusCode::OK, Json(json!({ "expired": counts }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// DELETE /peers/requests/clear-resolved: delete every resolved request,
+/// incoming and outgoing, whatever its age. Open requests and running loans
+/// are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/clear-resolved",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Number of resolved requests deleted per side")
+    )
+)]
+pub async fn clear_resolved_requests(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    match crate::services::request_cleanup::purge_resolved_requests(&db, None).await {
+        Ok(counts) => (StatusCode::OK, Json(json!({ "deleted": counts }))).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "error": e.to_string() })),
@@ -38,32 +119,80 @@ pub async fn clear_incoming_requests(State(db): State<DatabaseConnection>) -> im
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct IncomingRequest {
     pub(crate) from_peer_url: String,
     pub(crate) from_peer_name: String,
     pub(crate) book_isbn: String,
     pub(crate) book_title: String,
     pub(crate) requester_request_id: Option<String>,
+    /// Wait in line for a copy when none is on the shelf, instead of being
+    /// turned down.
+    #[serde(default)]
+    pub(crate) hold: bool,
+    /// The sender's library UUID, which identifies it whatever URL it posts
+    /// from. Absent from older senders.
+    #[serde(default)]
+    pub(crate) library_uuid: Option<String>,
 }
 
+impl IncomingRequest {
+    /// Anyone can post a request: clean its text before it is stored.
+    fn sanitized(self) -> Self {
+        use crate::utils::sanitize::{
+            MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_TITLE_CHARS, MAX_URL_CHARS, line, opt_line,
+        };
+        Self {
+            from_peer_url: line(&self.from_peer_url, MAX_URL_CHARS),
+            from_peer_name: line(&self.from_peer_name, MAX_NAME_CHARS),
+            book_isbn: line(&self.book_isbn, MAX_CODE_CHARS),
+            book_title: line(&self.book_title, MAX_TITLE_CHARS),
+            requester_request_id: opt_line(self.requester_request_id, MAX_CODE_CHARS),
+            hold: self.hold,
+            library_uuid: opt_line(self.library_uuid, MAX_CODE_CHARS),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/peers/request",
+    tag = "peer-transport",
+    request_body = IncomingRequest,
+    responses(
+        (status = 201, description = "Request recorded"),
+        (status = 202, description = "No available copy: waitlisted, held for the requester when one comes back"),
+        (status = 200, description = "Duplicate of an open request, or a replay of one already recorded"),
+        (status = 403, description = "Sender unknown, not accepted or blocked"),
+        (status = 409, description = "No available copy")
+    )
+)]
 pub async fn receive_request(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<IncomingRequest>,
 ) -> impl IntoResponse {
+    let payload = payload.sanitized();
     let db = state.db().clone();
 
-    // 1. Find or Create Peer
-    let peer = match peer::Entity::find()
-        .filter(peer::Column::Url.eq(&payload.from_peer_url))
-        .one(&db)
-        .await
-    {
+    // 1. Find or Create Peer: by library UUID, which survives address
+    // changes, then by URL. A created peer gets no UUID from an
+    // unauthenticated payload, which would keep the real library from
+    // pairing under it.
+    let found = match resolve_peer_by_library_uuid(&db, payload.library_uuid.as_deref()).await {
+        Some(p) => Ok(Some(p)),
+        None => {
+            peer::Entity::find()
+                .filter(peer::Column::Url.eq(&payload.from_peer_url))
+                .one(&db)
+                .await
+        }
+    };
+    let peer = match found {
         Ok(Some(p)) => p,
         Ok(None) => {
             let new_peer = peer::ActiveModel {
                 name: Set(payload.from_peer_name),
-                url: Set(payload.from_peer_url),
+                url: Set(payload.from_peer_url.clone()),
                 // An unauthenticated POST must never mint a trusted, auto-approving peer.
                 // A first-contact library is created pending: it is auto-approved for
                 // nothing until the owner explicitly accepts it. The column default is
@@ -94,8 +223,45 @@ pub async fn receive_request(
         }
     };
 
+    if is_peer_blocked(&peer) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "Requests from this library are refused" })),
+        )
+            .into_response();
+    }
+
+    // A known library posting from a new address: follow it there.
+    if peer.url != payload.from_peer_url {
+        let (db, known, url) = (db.clone(), peer.clone(), payload.from_peer_url.clone());
+        tokio::spawn(async move {
+            crate::services::peer_identity_sync::relink(&db, &known, &url).await;
+        });
+    }
+
+    // A request retried from the sender's outbox may already be here: answer
+    // with the one we have instead of rejecting it as a duplicate.
+    if let Some(rr_id) = payload.requester_request_id.as_deref()
+        && let Ok(Some(existing)) = crate::models::p2p_request::Entity::find()
+            .filter(crate::models::p2p_request::Column::FromPeerId.eq(peer.id))
+            .filter(crate::models::p2p_request::Column::RequesterRequestId.eq(rr_id))
+            .one(&db)
+            .await
+    {
+        return (
+            StatusCode::OK,
+            Json(json!({
+                "success": true,
+                "request_id": existing.id,
+                "status": existing.status,
+                "message": "Loan request already received",
+            })),
+        )
+            .into_response();
+    }
+
     // 2. Check copy availability and guard against duplicate active loans.
-    let has_available_copy = {
+    let (book_known, has_available_copy) = {
         use crate::models::book;
         use crate::models::copy;
 
@@ -106,15 +272,16 @@ pub async fn receive_request(
             .unwrap_or(None);
 
         if let Some(b) = book_found {
-            copy::Entity::find()
+            let available = copy::Entity::find()
                 .filter(copy::Column::BookId.eq(b.id))
                 .filter(copy::Column::Status.eq("available"))
                 .one(&db)
                 .await
                 .unwrap_or(None)
-                .is_some()
+                .is_some();
+            (true, available)
         } else {
-            false
+            (false, false)
         }
     };
 
@@ -128,6 +295,7 @@ pub async fn receive_request(
             .filter(
                 Condition::any()
                     .add(p2p_request::Column::Status.eq("pending"))
+                    .add(p2p_request::Column::Status.eq("waitlisted"))
                     .add(p2p_request::Column::Status.eq("accepted")),
             )
             .one(&db)
@@ -140,8 +308,13 @@ pub async fn receive_request(
     let auto_approve =
         is_auto_approve_loans_enabled(&db).await && peer.connection_status == "accepted";
 
-    // Determine initial status: auto-reject if no copy available or duplicate request
-    let initial_status = if !has_available_copy || already_has_active_request {
+    // Determine initial status: auto-reject if no copy available or duplicate request,
+    // unless the requester asked to wait for a copy of a book we do have.
+    let waitlisted =
+        !has_available_copy && !already_has_active_request && book_known && payload.hold;
+    let initial_status = if waitlisted {
+        "waitlisted"
+    } else if !has_available_copy || already_has_active_request {
         "rejected"
     } else {
         "pending"
@@ -158,6 +331,7 @@ pub async fn receive_request(
         created_at: Set(chrono::Utc::now().to_rfc3339()),
         updated_at: Set(chrono::Utc::now().to_rfc3339()),
         requester_request_id: Set(payload.requester_request_id.clone()),
+        ..Default::default()
     };
 
     match crate::models::p2p_request::Entity::insert(request)
@@ -165,6 +339,32 @@ pub async fn receive_request(
         .await
     {
         Ok(_) => {
+            // Waitlisted: `p2p_holds` puts the next copy back on the hold
+            // shelf for it and tells the requester.
+            if waitlisted {
+                tracing::info!(
+                    "Waitlisted loan request {} for '{}' until a copy is back",
+                    request_id,
+                    payload.book_title
+                );
+                crate::services::notification_service::emit(
+                    &db,
+                    crate::domain::CreateNotification {
+                        event_type: crate::domain::NotificationEventType::BorrowRequest,
+                        title: payload.book_title.clone(),
+                        body: Some(peer.name.clone()),
+                        ref_type: Some("peer".to_string()),
+                        ref_id: Some(request_id.clone()),
+                    },
+                )
+                .await;
+                return (
+                    StatusCode::ACCEPTED,
+                    Json(json!({ "success": true, "status": "waitlisted", "request_id": request_id })),
+                )
+                    .into_response();
+            }
+
             // Auto-rejected: no available copy or duplicate active request
             if !has_available_copy || already_has_active_request {
                 let reason = if already_has_active_request {
@@ -222,6 +422,7 @@ pub async fn receive_request(
                             "cover_url": result.book_cover_url,
                             "lender_name": result.lender_name,
                             "due_date": result.due_date,
+                            "terms": result.terms,
                             "request_id": request_id,
                             "requester_request_id": payload.requester_request_id,
                         });
@@ -239,6 +440,7 @@ pub async fn receive_request(
                                 "isbn": result.book_isbn,
                                 "title": result.book_title,
                                 "cover_url": result.book_cover_url,
+                                "terms": result.terms,
                             })),
                         )
                             .into_response();
@@ -281,6 +483,14 @@ pub async fn receive_request(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/peers/requests",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Incoming requests with the requesting peer")
+    )
+)]
 pub async fn list_requests(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     use crate::models::book;
     use crate::utils::cover_url::{self, ResolveScope};
@@ -350,18 +560,149 @@ pub async fn list_requests(State(db): State<DatabaseConnection>) -> impl IntoRes
     (StatusCode::OK, Json(dtos)).into_response()
 }
 
-#[derive(Deserialize)]
+/// Find the local book an incoming request names: by ISBN, else by title.
+async fn find_requested_book(
+    db: &DatabaseConnection,
+    req: &crate::models::p2p_request::Model,
+) -> Result<crate::models::book::Model, axum::response::Response> {
+    use crate::models::book;
+
+    tracing::info!(
+        "Looking for book with ISBN: '{}' for request {}",
+        req.book_isbn,
+        req.id
+    );
+    match book::Entity::find()
+        .filter(book::Column::Isbn.eq(&req.book_isbn))
+        .one(db)
+        .await
+    {
+        Ok(Some(b)) => {
+            tracing::info!("Found book: {} (id={})", b.title, b.id);
+            Ok(b)
+        }
+        Ok(None) => {
+            tracing::warn!(
+                "Book not found for ISBN: '{}'. Checking by title: '{}'",
+                req.book_isbn,
+                req.book_title
+            );
+            // Fallback: Try to find by title if ISBN lookup fails
+            match book::Entity::find()
+                .filter(book::Column::Title.eq(&req.book_title))
+                .one(db)
+                .await
+            {
+                Ok(Some(b)) => {
+                    tracing::info!("Found book by title: {} (id={})", b.title, b.id);
+                    Ok(b)
+                }
+                _ => Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("Book not found (ISBN: '{}', Title: '{}')", req.book_isbn, req.book_title) })),
+                )
+                    .into_response()),
+            }
+        }
+        Err(e) => {
+            tracing::error!("DB error looking up book: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("DB error: {}", e) })),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Find an available copy of `book_id`, or the 409 telling why there is none.
+async fn find_available_copy(
+    db: &DatabaseConnection,
+    book_id: &str,
+) -> Result<crate::models::copy::Model, axum::response::Response> {
+    use crate::models::copy;
+
+    match copy::Entity::find()
+        .filter(copy::Column::BookId.eq(book_id))
+        .filter(copy::Column::Status.eq("available"))
+        .one(db)
+        .await
+    {
+        Ok(Some(c)) => Ok(c),
+        _ => {
+            // Self-healing: Check if ANY copy exists
+            let any_copy = copy::Entity::find()
+                .filter(copy::Column::BookId.eq(book_id))
+                .one(db)
+                .await
+                .unwrap_or(None);
+
+            if any_copy.is_none() {
+                Err((
+                    StatusCode::CONFLICT,
+                    Json(json!({ "error": "No copy found" })),
+                )
+                    .into_response())
+            } else {
+                // Copies exist but none are available (truly borrowed)
+                Err((
+                    StatusCode::CONFLICT,
+                    Json(json!({ "error": "No available copies" })),
+                )
+                    .into_response())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct RequestAction {
     pub status: String,
+    /// The requester's library, required on a `returned` from a remote caller.
+    #[serde(default)]
+    pub library_uuid: Option<String>,
 }
 
+/// Move an incoming request to `payload.status` (accept, hold, reject, ...).
+///
+/// The route is peer-facing only for the legacy plaintext return, where a
+/// borrower that predates `POST /peers/loans/returned` reports `returned`.
+/// Like that endpoint, a remote `returned` must come from the requester,
+/// named by its `library_uuid`. Every other transition is the owner's decision and needs the same caller
+/// as the owner half of the router: loopback and no browser `Origin`.
+#[utoipa::path(
+    put,
+    path = "/api/peers/requests/{id}",
+    tag = "peer-requests",
+    params(("id" = String, Path, description = "Incoming request id")),
+    request_body = RequestAction,
+    responses(
+        (status = 200, description = "Status changed and requester notified"),
+        (status = 403, description = "Only `returned`, from the requester, is accepted from a remote caller"),
+        (status = 404, description = "Request not found"),
+        (status = 409, description = "No copy available to lend")
+    )
+)]
 pub async fn update_request_status(
     State(state): State<crate::infrastructure::AppState>,
     Path(id): Path<String>,
+    owner: Result<LoopbackNoBrowser, (StatusCode, Json<serde_json::Value>)>,
     Json(payload): Json<RequestAction>,
 ) -> impl IntoResponse {
-    use crate::models::{book, contact, copy, loan, p2p_request};
+    use crate::models::{contact, copy, loan, p2p_request};
     let db = state.db().clone();
+    let from_owner = owner.is_ok();
+
+    if payload.status != "returned"
+        && let Err(rejection) = owner
+    {
+        tracing::warn!(
+            "Refused remote status change to '{}' for request {}",
+            payload.status,
+            id
+        );
+        return rejection.into_response();
+    }
 
     let req = match p2p_request::Entity::find_by_id(&id).one(&db).await {
         Ok(Some(r)) => r,
@@ -374,11 +715,70 @@ pub async fn update_request_status(
         }
     };
 
+    if !from_owner
+        && let Some(refusal) =
+            refuse_unless_requester(&db, &req, payload.library_uuid.as_deref()).await
+    {
+        return refusal;
+    }
+
     let mut active: p2p_request::ActiveModel = req.clone().into();
     let new_status = payload.status.as_str();
 
+    // Extra fields for the status update sent to the requester.
+    let mut status_extra = json!({});
+
     // State transition logic
-    if new_status == "accepted" && req.status == "pending" {
+    if new_status == "ready" && (req.status == "pending" || req.status == "waitlisted") {
+        // Hold shelf: reserve a copy for the requester without lending it yet.
+        // The loan is created when the owner marks the request `accepted` at
+        // handover; an unclaimed hold is released by `services::hold_expiry`.
+        let book = match find_requested_book(&db, &req).await {
+            Ok(b) => b,
+            Err(resp) => return resp,
+        };
+        let copy = match find_available_copy(&db, &book.id).await {
+            Ok(c) => c,
+            Err(resp) => return resp,
+        };
+
+        let hold_days = state
+            .loan_settings_repo
+            .get_settings()
+            .await
+            .map(|s| s.hold_expiry_days)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to read loan settings, using 7-day hold: {e}");
+                7
+            });
+        let expires_at =
+            (chrono::Utc::now() + chrono::Duration::days(hold_days as i64)).to_rfc3339();
+
+        let copy_id = copy.id.clone();
+        let mut active_copy: copy::ActiveModel = copy.into();
+        active_copy.status = Set("reserved".to_string());
+        active_copy.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        if let Err(e) = active_copy.update(&db).await {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("Failed to reserve copy: {}", e) })),
+            )
+                .into_response();
+        }
+        info!(
+            "Copy {} held for request {} until {}",
+            copy_id, req.id, expires_at
+        );
+
+        active.held_copy_id = Set(Some(copy_id));
+        active.hold_expires_at = Set(Some(expires_at.clone()));
+        status_extra = json!({ "hold_expires_at": expires_at });
+    } else if new_status == "rejected" && req.status == "ready" {
+        // The owner withdrew the hold: the copy goes back on the shelf.
+        release_held_copy(&db, req.held_copy_id.as_deref()).await;
+        active.held_copy_id = Set(None);
+        active.hold_expires_at = Set(None);
+    } else if new_status == "accepted" && (req.status == "pending" || req.status == "ready") {
         // 1. Find Peer to link/create Contact
         let peer = match peer::Entity::find_by_id(req.from_peer_id).one(&db).await {
             Ok(Some(p)) => p,
@@ -401,86 +801,27 @@ pub async fn update_request_status(
             }
         };
 
-        // 2. Find Book and Available Copy
-        tracing::info!(
-            "Looking for book with ISBN: '{}' for request {}",
-            req.book_isbn,
-            req.id
-        );
-        let book = match book::Entity::find()
-            .filter(book::Column::Isbn.eq(&req.book_isbn))
-            .one(&db)
-            .await
-        {
-            Ok(Some(b)) => {
-                tracing::info!("Found book: {} (id={})", b.title, b.id);
-                b
-            }
-            Ok(None) => {
-                tracing::warn!(
-                    "Book not found for ISBN: '{}'. Checking by title: '{}'",
-                    req.book_isbn,
-                    req.book_title
-                );
-                // Fallback: Try to find by title if ISBN lookup fails
-                match book::Entity::find()
-                    .filter(book::Column::Title.eq(&req.book_title))
-                    .one(&db)
-                    .await
-                {
-                    Ok(Some(b)) => {
-                        tracing::info!("Found book by title: {} (id={})", b.title, b.id);
-                        b
-                    }
-                    _ => {
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(json!({ "error": format!("Book not found (ISBN: '{}', Title: '{}')", req.book_isbn, req.book_title) })),
-                        )
-                            .into_response()
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::error!("DB error looking up book: {}", e);
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": format!("DB error: {}", e) })),
-                )
-                    .into_response();
-            }
+        // 2. Find Book and the copy to lend: the one held for this request
+        // when it went through the hold shelf, else any available copy.
+        let book = match find_requested_book(&db, &req).await {
+            Ok(b) => b,
+            Err(resp) => return resp,
         };
-
-        let copy = match copy::Entity::find()
-            .filter(copy::Column::BookId.eq(book.id.clone()))
-            .filter(copy::Column::Status.eq("available"))
-            .one(&db)
-            .await
-        {
-            Ok(Some(c)) => c,
-            _ => {
-                // Self-healing: Check if ANY copy exists
-                let any_copy = copy::Entity::find()
-                    .filter(copy::Column::BookId.eq(book.id.clone()))
-                    .one(&db)
-                    .await
-                    .unwrap_or(None);
-
-                if any_copy.is_none() {
-                    return (
-                        StatusCode::CONFLICT,
-                        Json(json!({ "error": "No copy found" })),
-                    )
-                        .into_response();
-                } else {
-                    // Copies exist but none are available (truly borrowed)
-                    return (
-                        StatusCode::CONFLICT,
-                        Json(json!({ "error": "No available copies" })),
-                    )
-                        .into_response();
-                }
-            }
+        let held_copy = match req.held_copy_id.as_deref() {
+            Some(copy_id) if req.status == "ready" => copy::Entity::find_by_id(copy_id.to_owned())
+                .one(&db)
+                .await
+                .ok()
+                .flatten()
+                .filter(|c| c.status == "reserved"),
+            _ => None,
+        };
+        let copy = match held_copy {
+            Some(c) => c,
+            None => match find_available_copy(&db, &book.id).await {
+                Ok(c) => c,
+                Err(resp) => return resp,
+            },
         };
 
         // 3. Find or Create Contact for Peer
@@ -534,6 +875,14 @@ pub async fn update_request_status(
             }
         };
 
+        // The copy's lending terms cap the loan duration and are snapshotted on
+        // the loan, then sent to the borrower with the confirmation.
+        let terms = crate::domain::LendingTerms::from_column(copy.lending_terms.as_deref());
+        let duration_days = resolve_loan_duration_days(&db, &book.id, &peer).await;
+        let duration_days = terms
+            .as_ref()
+            .map_or(duration_days, |t| t.cap_duration(duration_days));
+
         // 4. Create Loan
         let loan = loan::ActiveModel {
             copy_id: Set(copy.id.clone()),
@@ -551,10 +900,9 @@ pub async fn update_request_status(
                 },
             ),
             loan_date: Set(chrono::Utc::now().to_rfc3339()),
-            due_date: Set((chrono::Utc::now()
-                + chrono::Duration::days(resolve_loan_duration_days(&db, &book.id).await))
-            .to_rfc3339()),
+            due_date: Set((chrono::Utc::now() + chrono::Duration::days(duration_days)).to_rfc3339()),
             status: Set("active".to_string()),
+            lending_terms: Set(terms.as_ref().map(|t| t.to_column())),
             created_at: Set(chrono::Utc::now().to_rfc3339()),
             updated_at: Set(chrono::Utc::now().to_rfc3339()),
             ..Default::default()
@@ -583,6 +931,8 @@ pub async fn update_request_status(
             )
                 .into_response();
         }
+        active.held_copy_id = Set(None);
+        active.hold_expires_at = Set(None);
 
         // 5. Notify borrower that loan was accepted
         let peer_url = peer.url.clone();
@@ -597,10 +947,9 @@ pub async fn update_request_status(
             Some(book.updated_at.as_str()),
             hub_prefix.as_deref(),
         );
-        let due_date = (chrono::Utc::now()
-            + chrono::Duration::days(resolve_loan_duration_days(&db, &book.id).await))
-        .format("%Y-%m-%d")
-        .to_string();
+        let due_date = (chrono::Utc::now() + chrono::Duration::days(duration_days))
+            .format("%Y-%m-%d")
+            .to_string();
 
         // Get library name for lender identification
         let lender_name = crate::utils::library_helpers::resolve_lender_display_name(&db).await;
@@ -612,6 +961,7 @@ pub async fn update_request_status(
             "cover_url": book_cover,
             "lender_name": lender_name,
             "due_date": due_date,
+            "terms": terms,
             "request_id": req.id,
             "requester_request_id": req.requester_request_id,
         });
@@ -623,14 +973,26 @@ pub async fn update_request_status(
             }
             Err(e) => {
                 // E2EE transport error — message MAY have been delivered.
-                // Do NOT fall back to plaintext to avoid duplicate borrowed copies.
+                // Do NOT fall back to plaintext to avoid duplicate borrowed copies:
+                // queue it, the borrower ignores a confirmation it already applied.
                 tracing::warn!("E2EE: Loan confirmation error (no plaintext fallback): {e}");
+                crate::services::peer_outbox::enqueue_or_log(
+                    &db,
+                    peer.id,
+                    "loan_confirmation",
+                    &confirm_payload,
+                    None,
+                    &e,
+                )
+                .await;
             }
             Ok(None) => {
                 // E2EE not available for this peer — fall back to plaintext
                 let peer_url_clone = peer_url.clone();
+                let queue_db = db.clone();
+                let peer_id = peer.id;
                 tokio::spawn(async move {
-                    let client = reqwest::Client::new();
+                    let client = crate::services::network_mode::client();
                     let confirm_result = client
                         .post(format!("{}/api/peers/loans/confirm", peer_url_clone))
                         .json(&confirm_payload)
@@ -652,105 +1014,27 @@ pub async fn update_request_status(
                                 peer_url_clone,
                                 e
                             );
+                            let fallback = crate::services::peer_outbox::PlaintextFallback {
+                                method: "POST",
+                                path: "/api/peers/loans/confirm".to_string(),
+                                body: confirm_payload.clone(),
+                            };
+                            crate::services::peer_outbox::enqueue_or_log(
+                                &queue_db,
+                                peer_id,
+                                "loan_confirmation",
+                                &confirm_payload,
+                                Some(fallback),
+                                &e.to_string(),
+                            )
+                            .await;
                         }
                     }
                 });
             }
         }
     } else if new_status == "returned" && req.status == "accepted" {
-        // Handle Return
-        // Find the loan associated with this peer (contact) and book
-        // This is tricky because we didn't link Loan to Request directly.
-        // We have to infer: Find active loan for this book's copy where contact matches peer.
-
-        // 1. Find Peer/Contact (graceful: if peer is gone, still update request status)
-        let peer_opt = peer::Entity::find_by_id(req.from_peer_id)
-            .one(&db)
-            .await
-            .ok()
-            .flatten();
-
-        if peer_opt.is_none() {
-            tracing::warn!(
-                "Peer {} not found for return of request {} - updating request status only",
-                req.from_peer_id,
-                req.id
-            );
-        }
-
-        let contact = match &peer_opt {
-            Some(peer) => contact::Entity::find()
-                .filter(contact::Column::Name.eq(&peer.name))
-                .filter(contact::Column::Type.eq("Library"))
-                .one(&db)
-                .await
-                .unwrap_or(None),
-            None => None,
-        };
-
-        if let Some(contact) = contact {
-            let book = book::Entity::find()
-                .filter(book::Column::Isbn.eq(&req.book_isbn))
-                .one(&db)
-                .await
-                .unwrap_or(None);
-
-            if let Some(book) = book {
-                // 3. Find Active Loan for any copy of this book for this contact
-                let copies = copy::Entity::find()
-                    .filter(copy::Column::BookId.eq(book.id.as_str()))
-                    .all(&db)
-                    .await
-                    .unwrap_or(vec![]);
-
-                let copy_ids: Vec<String> = copies.iter().map(|c| c.id.clone()).collect();
-
-                let active_loan = loan::Entity::find()
-                    .filter(loan::Column::ContactId.eq(contact.id))
-                    .filter(loan::Column::Status.eq("active"))
-                    .filter(loan::Column::CopyId.is_in(copy_ids))
-                    .one(&db)
-                    .await
-                    .unwrap_or(None);
-
-                if let Some(l) = active_loan {
-                    let mut active_loan: loan::ActiveModel = l.clone().into();
-                    active_loan.status = Set("returned".to_string());
-                    active_loan.return_date = Set(Some(chrono::Utc::now().to_rfc3339()));
-                    active_loan.updated_at = Set(chrono::Utc::now().to_rfc3339());
-                    let _ = active_loan.update(&db).await;
-
-                    // Update Copy
-                    if let Some(copy) = copy::Entity::find_by_id(l.copy_id)
-                        .one(&db)
-                        .await
-                        .ok()
-                        .flatten()
-                    {
-                        let mut active_copy: copy::ActiveModel = copy.into();
-                        active_copy.status = Set("available".to_string());
-                        let _ = active_copy.update(&db).await;
-                    }
-
-                    // Emit book_returned notification
-                    let peer_name = peer_opt
-                        .as_ref()
-                        .map(|p| p.name.clone())
-                        .unwrap_or_default();
-                    crate::services::notification_service::emit(
-                        &db,
-                        crate::domain::CreateNotification {
-                            event_type: crate::domain::NotificationEventType::BookReturned,
-                            title: book.title.clone(),
-                            body: Some(peer_name),
-                            ref_type: Some("loan".to_string()),
-                            ref_id: Some(req.id.clone()),
-                        },
-                    )
-                    .await;
-                }
-            }
-        }
+        record_peer_return(&db, &req).await;
     }
 
     // Update Request Status
@@ -765,73 +1049,17 @@ pub async fn update_request_status(
         .flatten();
 
     if let Some(peer) = peer_for_notify {
-        // Use the borrower's original request ID so they can match
-        // the status update to their outgoing request. Fall back to
-        // our local ID for backward compat with old peers.
-        let borrower_loan_id = req
-            .requester_request_id
-            .clone()
-            .unwrap_or_else(|| req.id.clone());
-
-        let status_payload = json!({
-            "loan_id": borrower_loan_id,
-            "status": new_status,
-        });
-
-        // Try E2EE first
-        match try_send_e2ee(&state, &peer, "status_update", status_payload).await {
-            Ok(Some(_)) => {
-                tracing::info!("E2EE: Status update sent to {} (encrypted)", peer.name);
-            }
-            Err(e) => {
-                // E2EE transport error — message MAY have been delivered.
-                // Do NOT fall back to plaintext to avoid duplicate status updates.
-                tracing::warn!("E2EE: Status update error (no plaintext fallback): {e}");
-            }
-            Ok(None) => {
-                // E2EE not available for this peer — fall back to plaintext.
-                // Assert our own identity so the borrower's ownership check accepts it:
-                // the borrower resolves this uuid and requires it to name the lender.
-                let peer_url = peer.url.clone();
-                let request_id = borrower_loan_id;
-                let status_to_send = new_status.to_string();
-                let our_uuid = state.identity_service.library_uuid().map(|s| s.to_string());
-
-                tokio::spawn(async move {
-                    let client = get_safe_client();
-                    let notify_url =
-                        format!("{}/api/peers/requests/status/{}", peer_url, request_id);
-
-                    tracing::info!(
-                        "Notifying borrower {} of status change: {} -> {}",
-                        peer_url,
-                        request_id,
-                        status_to_send
-                    );
-
-                    match client
-                        .put(&notify_url)
-                        .json(&serde_json::json!({
-                            "status": status_to_send,
-                            "library_uuid": our_uuid,
-                        }))
-                        .send()
-                        .await
-                    {
-                        Ok(res) => {
-                            tracing::info!("Borrower notified: {}", res.status());
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to notify borrower: {}", e);
-                        }
-                    }
-                });
-            }
-        }
+        send_request_status_update(&state, &peer, &req, new_status, status_extra).await;
     }
 
     match active.update(&db).await {
-        Ok(_) => StatusCode::OK.into_response(),
+        Ok(_) => {
+            // A copy back on the shelf goes to the next peer waiting for it.
+            if new_status == "returned" || (new_status == "rejected" && req.status == "ready") {
+                crate::services::p2p_holds::spawn_serve(&state);
+            }
+            StatusCode::OK.into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "error": e.to_string() })),
@@ -840,12 +1068,27 @@ pub async fn update_request_status(
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/{id}",
+    tag = "peer-requests",
+    params(("id" = String, Path, description = "Incoming request id")),
+    responses(
+        (status = 200, description = "Request deleted, held copy released"),
+        (status = 404, description = "Request not found")
+    )
+)]
 pub async fn delete_request(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     use crate::models::p2p_request;
 
+    // Deleting a request still on the hold shelf gives its copy back.
+    if let Ok(Some(req)) = p2p_request::Entity::find_by_id(&id).one(&db).await {
+        release_held_copy(&db, req.held_copy_id.as_deref()).await;
+    }
+
     match p2p_request::Entity::delete_by_id(id).exec(&db).await {
         Ok(res) => {
             if res.rows_affected == 0 {
@@ -866,7 +1109,66 @@ pub async fn delete_request(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RequestStatusQuery {
+    /// The borrower's own id for the request (`p2p_outgoing_requests.id`).
+    pub requester_request_id: String,
+    /// The borrower's library, which must be the one that made the request.
+    pub library_uuid: Option<String>,
+}
+
+/// Lender answers a keyless borrower polling the status of one of its requests.
+///
+/// Plaintext counterpart of the encrypted `request_status_query`, for borrowers
+/// whose callbacks cannot reach (or were never sent to) this library. The
+/// sender is identified by its `library_uuid` as in `receive_loan_returned`,
+/// and only ever learns about requests it made itself.
+#[utoipa::path(
+    post,
+    path = "/api/peers/requests/status-query",
+    tag = "peer-transport",
+    request_body = RequestStatusQuery,
+    responses(
+        (status = 200, description = "Status report for the requester's request"),
+        (status = 400, description = "Missing requester_request_id"),
+        (status = 403, description = "Unknown sender, or one that must use E2EE")
+    )
+)]
+pub async fn query_request_status(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<RequestStatusQuery>,
+) -> impl IntoResponse {
+    if payload.requester_request_id.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "Missing requester_request_id" })),
+        )
+            .into_response();
+    }
+
+    let Some(requester) = resolve_peer_by_library_uuid(&db, payload.library_uuid.as_deref()).await
+    else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "Unknown sender" })),
+        )
+            .into_response();
+    };
+    if requester.key_exchange_done {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "This peer is served over the encrypted channel" })),
+        )
+            .into_response();
+    }
+
+    let report =
+        crate::api::e2ee::request_status_report(&db, &payload.requester_request_id, requester.id)
+            .await;
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct IncomingLoanRequest {
     pub from_name: String,
     pub from_url: String,
@@ -875,10 +1177,35 @@ pub struct IncomingLoanRequest {
     pub book_title: String,
 }
 
+impl IncomingLoanRequest {
+    fn sanitized(self) -> Self {
+        use crate::utils::sanitize::{
+            MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_TITLE_CHARS, MAX_URL_CHARS, line, opt_line,
+        };
+        Self {
+            from_name: line(&self.from_name, MAX_NAME_CHARS),
+            from_url: line(&self.from_url, MAX_URL_CHARS),
+            library_uuid: opt_line(self.library_uuid, MAX_CODE_CHARS),
+            book_isbn: line(&self.book_isbn, MAX_CODE_CHARS),
+            book_title: line(&self.book_title, MAX_TITLE_CHARS),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/peers/requests/incoming",
+    tag = "peer-requests",
+    request_body = IncomingLoanRequest,
+    responses(
+        (status = 200, description = "Request recorded (legacy receiver)")
+    )
+)]
 pub async fn receive_loan_request(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<IncomingLoanRequest>,
 ) -> impl IntoResponse {
+    let payload = payload.sanitized();
     use crate::models::p2p_request;
     use chrono::Utc;
     use uuid::Uuid;
@@ -45,6 +45,7 @@ pub(crate) async fn process_borrower_acceptance(
         .get("due_date")
         .and_then(|v| v.as_str())
         .unwrap_or("Unknown");
+    let terms = lending_terms_from_payload(payload);
 
     if title.is_empty() {
         tracing::warn!("process_borrower_acceptance: empty title, skipping");
@@ -162,6 +163,7 @@ pub(crate) async fn process_borrower_acceptance(
         lender_peer_id: Set(lender_peer_id),
         borrow_due_date: Set(Some(due_date.to_string())),
         borrow_source: Set(Some(crate::domain::BorrowSource::Peer.as_str().to_string())),
+        lending_terms: Set(terms.map(|t| t.to_column())),
         acquisition_date: Set(Some(now.clone())),
         created_at: Set(now.clone()),
         updated_at: Set(now),
@@ -194,12 +196,30 @@ pub(crate) async fn process_borrower_acceptance(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct BookRequest {
     book_isbn: String,
     book_title: String,
+    /// Join the lender's waitlist when no copy is free instead of being
+    /// turned down
+    #[serde(default)]
+    hold: bool,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/request",
+    tag = "peer-requests",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = BookRequest,
+    responses(
+        (status = 200, description = "Request sent to the peer"),
+        (status = 404, description = "Peer not found"),
+        (status = 202, description = "Peer unreachable, request queued in the outbox"),
+        (status = 409, description = "An open request for this book already exists"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
 pub async fn request_book(
     State(state): State<crate::infrastructure::AppState>,
     Path(peer_id): Path<i32>,
@@ -231,6 +251,7 @@ pub async fn request_book(
             .filter(
                 Condition::any()
                     .add(p2p_outgoing_request::Column::Status.eq("pending"))
+                    .add(p2p_outgoing_request::Column::Status.eq("waitlisted"))
                     .add(p2p_outgoing_request::Column::Status.eq("accepted")),
             )
             .one(db)
@@ -307,9 +328,11 @@ pub async fn request_book(
     let e2ee_payload = json!({
         "from_peer_url": state.our_public_url(),
         "from_peer_name": my_config.name,
+        "library_uuid": state.identity_service.library_uuid(),
         "book_isbn": payload.book_isbn,
         "book_title": payload.book_title,
-        "requester_request_id": outgoing_id
+        "requester_request_id": outgoing_id,
+        "hold": payload.hold
     });
 
     match try_send_e2ee(&state, &peer, "loan_request", e2ee_payload.clone()).await {
@@ -322,6 +345,10 @@ pub async fn request_book(
                     .and_then(|s| s.as_str())
                     .unwrap_or("pending");
 
+                if status == "waitlisted" {
+                    return waitlisted_response(db, &outgoing_id).await;
+                }
+
                 if status == "rejected" {
                     let _ = crate::models::p2p_outgoing_request::Entity::update_many()
                         .col_expr(
@@ -382,27 +409,27 @@ pub async fn request_book(
         }
         Err(e) => {
             // E2EE transport error - both direct and relay failed.
-            // Do NOT fall back to plaintext to avoid duplicate requests.
+            // Do NOT fall back to plaintext to avoid duplicate requests:
+            // queue the E2EE message, the lender ignores a replay.
             tracing::warn!("E2EE send failed (no plaintext fallback): {}", e);
-            crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
-            return (
-                StatusCode::BAD_GATEWAY,
-                Json(json!({ "error": "Failed to deliver request to peer" })),
-            )
-                .into_response();
+            return queue_loan_request(&state, &peer, &outgoing_id, &e2ee_payload, None, &e).await;
         }
     }
 
     // Legacy plaintext path (only reached if E2EE returned Ok(None))
     if let Err(e) = validate_url(&peer.url) {
-        crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
+        crate::services::loan_service::mark_outgoing_request_failed(
+            state.p2p_request_repo.as_ref(),
+            &outgoing_id,
+        )
+        .await;
         return (
             StatusCode::BAD_GATEWAY,
             Json(json!({ "error": format!("Cannot reach peer: {}", e) })),
         )
             .into_response();
     }
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer.url);
     let url = format!("{}/api/peers/request", peer.url);
 
     let res = client.post(&url).json(&e2ee_payload).send().await;
@@ -413,6 +440,11 @@ pub async fn request_book(
             let body = response.text().await.unwrap_or_default();
 
             if resp_status.is_success() {
+                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&body)
+                    && parsed.get("status").and_then(|s| s.as_str()) == Some("waitlisted")
+                {
+                    return waitlisted_response(db, &outgoing_id).await;
+                }
                 // Parse response body to check for auto-acceptance
                 if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&body)
                     && parsed.get("status").and_then(|s| s.as_str()) == Some("accepted")
@@ -466,7 +498,11 @@ pub async fn request_book(
                     )
                         .into_response();
                 }
-                crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
+                crate::services::loan_service::mark_outgoing_request_failed(
+                    state.p2p_request_repo.as_ref(),
+                    &outgoing_id,
+                )
+                .await;
                 (
                     StatusCode::BAD_GATEWAY,
                     Json(json!({ "error": "Peer rejected request" })),
@@ -474,8 +510,92 @@ pub async fn request_book(
                     .into_response()
             }
         }
-        Err(_) => {
-            crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
+        Err(e) => {
+            let fallback = crate::services::peer_outbox::PlaintextFallback {
+                method: "POST",
+                path: "/api/peers/request".to_string(),
+                body: e2ee_payload.clone(),
+            };
+            queue_loan_request(
+                &state,
+                &peer,
+                &outgoing_id,
+                &e2ee_payload,
+                Some(fallback),
+                &e.to_string(),
+            )
+            .await
+        }
+    }
+}
+
+/// The lender has no copy free and put our request on its waitlist: it sends
+/// a `ready` status update once a copy is held for us.
+async fn waitlisted_response(
+    db: &DatabaseConnection,
+    outgoing_id: &str,
+) -> axum::response::Response {
+    use crate::models::p2p_outgoing_request;
+
+    let _ = p2p_outgoing_request::Entity::update_many()
+        .col_expr(
+            p2p_outgoing_request::Column::Status,
+            sea_orm::prelude::Expr::value("waitlisted"),
+        )
+        .col_expr(
+            p2p_outgoing_request::Column::UpdatedAt,
+            sea_orm::prelude::Expr::value(Utc::now().to_rfc3339()),
+        )
+        .filter(p2p_outgoing_request::Column::Id.eq(outgoing_id))
+        .exec(db)
+        .await;
+    tracing::info!("Outgoing request {} waitlisted by peer", outgoing_id);
+    (
+        StatusCode::OK,
+        Json(
+            json!({ "message": "No copy free, waitlisted by the lender", "status": "waitlisted" }),
+        ),
+    )
+        .into_response()
+}
+
+/// Put a loan request the lender could not receive in the outbox. The
+/// outgoing request stays `pending` and the caller gets `202 Accepted`; it is
+/// only marked failed when queueing fails or the outbox gives up.
+async fn queue_loan_request(
+    state: &crate::infrastructure::AppState,
+    peer: &peer::Model,
+    outgoing_id: &str,
+    payload: &serde_json::Value,
+    fallback: Option<crate::services::peer_outbox::PlaintextFallback>,
+    error: &str,
+) -> axum::response::Response {
+    match crate::services::peer_outbox::enqueue(
+        state.db(),
+        peer.id,
+        "loan_request",
+        payload,
+        fallback,
+        Some(outgoing_id),
+        error,
+    )
+    .await
+    {
+        Ok(_) => (
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "message": "Peer unreachable, request queued for delivery",
+                "status": "queued"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to queue outgoing request {}: {}", outgoing_id, e);
+            crate::services::loan_service::mark_outgoing_request_failed(
+                state.p2p_request_repo.as_ref(),
+                outgoing_id,
+            )
+            .await;
             (
                 StatusCode::BAD_GATEWAY,
                 Json(json!({ "error": "Failed to contact peer" })),
@@ -485,13 +605,30 @@ pub async fn request_book(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct BookRequestByUrl {
     peer_url: String,
     book_isbn: String,
     book_title: String,
+    /// Join the lender's waitlist when no copy is free instead of being
+    /// turned down
+    #[serde(default)]
+    hold: bool,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/request_by_url",
+    tag = "peer-requests",
+    request_body = BookRequestByUrl,
+    responses(
+        (status = 200, description = "Request sent to the peer"),
+        (status = 400, description = "Invalid peer URL"),
+        (status = 202, description = "Peer unreachable, request queued in the outbox"),
+        (status = 409, description = "An open request for this book already exists"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
 pub async fn request_book_by_url(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<BookRequestByUrl>,
@@ -549,6 +686,7 @@ pub async fn request_book_by_url(
         let request_payload = json!({
             "from_peer_url": state.our_public_url(),
             "from_peer_name": my_config.name,
+            "library_uuid": state.identity_service.library_uuid(),
             "book_isbn": payload.book_isbn,
             "book_title": payload.book_title,
             "requester_request_id": uuid::Uuid::new_v4().to_string()
@@ -587,6 +725,7 @@ pub async fn request_book_by_url(
             .filter(
                 Condition::any()
                     .add(p2p_outgoing_request::Column::Status.eq("pending"))
+                    .add(p2p_outgoing_request::Column::Status.eq("waitlisted"))
                     .add(p2p_outgoing_request::Column::Status.eq("accepted")),
             )
             .one(db)
@@ -661,9 +800,11 @@ pub async fn request_book_by_url(
     let e2ee_payload = json!({
         "from_peer_url": state.our_public_url(),
         "from_peer_name": my_config.name,
+        "library_uuid": state.identity_service.library_uuid(),
         "book_isbn": payload.book_isbn,
         "book_title": payload.book_title,
-        "requester_request_id": outgoing_id
+        "requester_request_id": outgoing_id,
+        "hold": payload.hold
     });
 
     // Try E2EE path first
@@ -676,6 +817,10 @@ pub async fn request_book_by_url(
                     .get("status")
                     .and_then(|s| s.as_str())
                     .unwrap_or("pending");
+                if status == "waitlisted" {
+                    return waitlisted_response(db, &outgoing_id).await;
+                }
+
                 if status == "rejected" {
                     let _ = crate::models::p2p_outgoing_request::Entity::update_many()
                         .col_expr(
@@ -746,14 +891,18 @@ pub async fn request_book_by_url(
     // SSRF validation: only needed here for direct HTTP to peer URL.
     // Relay-only peers (relay://) never reach this point because E2EE handles them.
     if let Err(e) = validate_url(&peer.url) {
-        crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
+        crate::services::loan_service::mark_outgoing_request_failed(
+            state.p2p_request_repo.as_ref(),
+            &outgoing_id,
+        )
+        .await;
         return (
             StatusCode::BAD_GATEWAY,
             Json(json!({ "error": format!("Cannot reach peer: {}", e) })),
         )
             .into_response();
     }
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer.url);
     let url = format!("{}/api/peers/request", peer.url);
 
     let res = client.post(&url).json(&e2ee_payload).send().await;
@@ -764,6 +913,11 @@ pub async fn request_book_by_url(
             let body = response.text().await.unwrap_or_default();
 
             if resp_status.is_success() {
+                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&body)
+                    && parsed.get("status").and_then(|s| s.as_str()) == Some("waitlisted")
+                {
+                    return waitlisted_response(db, &outgoing_id).await;
+                }
                 // Parse response body to check for auto-acceptance
                 if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&body)
                     && parsed.get("status").and_then(|s| s.as_str()) == Some("accepted")
@@ -818,7 +972,11 @@ pub async fn request_book_by_url(
                     )
                         .into_response();
                 }
-                crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
+                crate::services::loan_service::mark_outgoing_request_failed(
+                    state.p2p_request_repo.as_ref(),
+                    &outgoing_id,
+                )
+                .await;
                 (
                     StatusCode::BAD_GATEWAY,
                     Json(json!({ "error": "Peer rejected request" })),
@@ -826,17 +984,33 @@ pub async fn request_book_by_url(
                     .into_response()
             }
         }
-        Err(_) => {
-            crate::services::loan_service::mark_outgoing_request_failed(db, &outgoing_id).await;
-            (
-                StatusCode::BAD_GATEWAY,
-                Json(json!({ "error": "Failed to contact peer" })),
+        Err(e) => {
+            let fallback = crate::services::peer_outbox::PlaintextFallback {
+                method: "POST",
+                path: "/api/peers/request".to_string(),
+                body: e2ee_payload.clone(),
+            };
+            queue_loan_request(
+                &state,
+                &peer,
+                &outgoing_id,
+                &e2ee_payload,
+                Some(fallback),
+                &e.to_string(),
             )
-                .into_response()
+            .await
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/peers/requests/outgoing",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Requests this library sent")
+    )
+)]
 pub async fn list_outgoing_requests(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     use crate::models::book;
     use crate::utils::cover_url::{self, ResolveScope};
@@ -906,113 +1080,321 @@ pub async fn list_outgoing_requests(State(db): State<DatabaseConnection>) -> imp
     (StatusCode::OK, Json(dtos)).into_response()
 }
 
-/// Delete all non-pending outgoing requests (cleanup).
-pub async fn clear_outgoing_requests(State(db): State<DatabaseConnection>) -> impl IntoResponse {
-    use sea_orm::ConnectionTrait;
-    let result = db
-        .execute(sea_orm::Statement::from_string(
-            db.get_database_backend(),
-            "DELETE FROM p2p_outgoing_requests WHERE status != 'pending'".to_owned(),
-        ))
-        .await;
-
-    match result {
-        Ok(r) => (
-            StatusCode::OK,
-            Json(json!({ "deleted": r.rows_affected() })),
-        )
-            .into_response(),
+/// Delete all closed outgoing requests (cleanup). Pending requests and returns
+/// awaiting acknowledgment are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/outgoing/clear",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Closed outgoing requests deleted")
+    )
+)]
+pub async fn clear_outgoing_requests(
+    State(state): State<crate::infrastructure::AppState>,
+) -> impl IntoResponse {
+    match crate::services::loan_service::delete_closed_outgoing_requests(
+        state.p2p_request_repo.as_ref(),
+    )
+    .await
+    {
+        Ok(deleted) => (StatusCode::OK, Json(json!({ "deleted": deleted }))).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
+            Json(json!({ "error": format!("{e:?}") })),
         )
             .into_response(),
     }
 }
 
-/// Sync pending outgoing requests by querying each lender for current status.
-///
-/// For each pending outgoing request, sends a `request_status_query` via E2EE
-/// (with relay fallback) to the lender. If the lender reports the request has been
-/// accepted/rejected, updates the local outgoing request accordingly and creates
-/// the borrowed copy if accepted.
+/// Outgoing statuses the lender can still move: a request awaiting an answer,
+/// one waiting for a copy, one on the hold shelf, a running loan and a return
+/// awaiting acknowledgment.
+const OPEN_OUTGOING_STATUSES: [&str; 5] = [
+    "pending",
+    "waitlisted",
+    "ready",
+    "accepted",
+    "return_pending",
+];
+
+/// Sync open outgoing requests by querying each lender for current status.
+#[utoipa::path(
+    post,
+    path = "/api/peers/requests/outgoing/sync",
+    tag = "peer-requests",
+    responses(
+        (status = 200, description = "Number of requests polled and updated")
+    )
+)]
 pub async fn sync_outgoing_requests(
     State(state): State<crate::infrastructure::AppState>,
 ) -> impl IntoResponse {
+    let (synced, updated) = sync_open_outgoing_requests(&state).await;
+    (
+        StatusCode::OK,
+        Json(json!({ "synced": synced, "updated": updated })),
+    )
+        .into_response()
+}
+
+/// Poll the lender of every open outgoing request and apply what it reports.
+///
+/// Callbacks (`status_update`, `loan_confirmation`) are fire-and-forget and get
+/// lost when the borrower is offline or unreachable, which left requests
+/// `pending` forever. This is the pull side: a `request_status_query` via E2EE
+/// (with relay fallback), or the plaintext `/api/peers/requests/status-query`
+/// for a lender without a key exchange. Run by the manual sync endpoint and by
+/// `services::outgoing_request_sync`. Returns `(queried, updated)`.
+pub async fn sync_open_outgoing_requests(state: &crate::infrastructure::AppState) -> (u32, u32) {
     use crate::models::p2p_outgoing_request;
 
     let db = state.db();
-    let pending = p2p_outgoing_request::Entity::find()
-        .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+    let open = p2p_outgoing_request::Entity::find()
+        .filter(p2p_outgoing_request::Column::Status.is_in(OPEN_OUTGOING_STATUSES))
         .all(db)
         .await
         .unwrap_or_default();
 
-    if pending.is_empty() {
-        return (StatusCode::OK, Json(json!({ "synced": 0, "updated": 0 }))).into_response();
-    }
-
     let mut synced = 0u32;
     let mut updated = 0u32;
 
-    for outgoing in &pending {
+    for outgoing in &open {
         // Find the lender peer
         let lender = match peer::Entity::find_by_id(outgoing.to_peer_id).one(db).await {
             Ok(Some(p)) => p,
             _ => continue,
         };
 
-        let query_payload = json!({
-            "requester_request_id": outgoing.id,
-        });
-
-        // Try E2EE (with relay fallback)
-        let result = try_send_e2ee(&state, &lender, "request_status_query", query_payload).await;
         synced += 1;
+        let Some(report) = query_lender_status(state, &lender, &outgoing.id).await else {
+            continue;
+        };
+        if apply_polled_status(db, outgoing, &lender, &report).await {
+            updated += 1;
+        }
+    }
 
-        if let Ok(Some(Some(ref clear_msg))) = result {
-            let remote_status = clear_msg
-                .payload
-                .get("status")
-                .and_then(|v| v.as_str())
-                .unwrap_or("pending");
-
-            if remote_status != "pending" && remote_status != "not_found" {
-                tracing::info!(
-                    "Sync: outgoing request {} status changed to '{}'",
-                    outgoing.id,
-                    remote_status
-                );
-
-                if remote_status == "accepted" {
-                    let lender_request_id =
-                        clear_msg.payload.get("request_id").and_then(|v| v.as_str());
-                    process_borrower_acceptance(
-                        db,
-                        &outgoing.id,
-                        &clear_msg.payload,
-                        lender_request_id,
-                    )
-                    .await;
-                } else {
-                    // rejected or returned
-                    let mut active: p2p_outgoing_request::ActiveModel = outgoing.clone().into();
-                    active.status = Set(remote_status.to_string());
-                    active.updated_at = Set(Utc::now().to_rfc3339());
-                    let _ = active.update(db).await;
-                }
-                updated += 1;
+    (synced, updated)
+}
+
+/// Ask `lender` for the status of our request `outgoing_id`. `None` when the
+/// lender could not be reached or gave no usable answer.
+async fn query_lender_status(
+    state: &crate::infrastructure::AppState,
+    lender: &peer::Model,
+    outgoing_id: &str,
+) -> Option<serde_json::Value> {
+    let query_payload = json!({
+        "requester_request_id": outgoing_id,
+    });
+
+    match try_send_e2ee(state, lender, "request_status_query", query_payload.clone()).await {
+        Ok(Some(Some(clear_msg))) => Some(clear_msg.payload),
+        Ok(Some(None)) => None,
+        Err(e) => {
+            tracing::debug!("Sync: status query to {} failed: {e}", lender.name);
+            None
+        }
+        Ok(None) => {
+            let mut query_payload = query_payload;
+            query_payload["library_uuid"] = json!(state.identity_service.library_uuid());
+            let url = format!("{}/api/peers/requests/status-query", lender.url);
+            let res = get_safe_client_for(&lender.url)
+                .post(&url)
+                .json(&query_payload)
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await
+                .ok()?;
+            if !res.status().is_success() {
+                tracing::debug!("Sync: status query to {} returned {}", url, res.status());
+                return None;
             }
+            res.json().await.ok()
         }
     }
+}
 
-    (
-        StatusCode::OK,
-        Json(json!({ "synced": synced, "updated": updated })),
+/// Apply a lender's status report to our outgoing request, with the same side
+/// effects and notifications the matching callback would have had. Returns
+/// whether the request changed.
+pub(crate) async fn apply_polled_status(
+    db: &DatabaseConnection,
+    outgoing: &crate::models::p2p_outgoing_request::Model,
+    lender: &peer::Model,
+    report: &serde_json::Value,
+) -> bool {
+    use crate::models::p2p_outgoing_request;
+
+    let remote_status = report
+        .get("status")
+        .and_then(|v| v.as_str())
+        .unwrap_or("pending");
+    let local_status = outgoing.status.as_str();
+
+    let stored_status = match (local_status, remote_status) {
+        (local, remote) if local == remote => return false,
+        (_, "pending" | "not_found") => return false,
+        // The lender has not recorded our return yet.
+        ("return_pending", "accepted") => return false,
+        // A loan never goes back to the hold shelf, nor a hold to the waitlist.
+        ("accepted" | "return_pending", "ready") => return false,
+        ("ready" | "accepted" | "return_pending", "waitlisted") => return false,
+        (_, "accepted") => {
+            // The confirmation never reached us: create the borrowed copy now.
+            // The report falls back to our own record of the book when the
+            // lender no longer finds it by ISBN.
+            let mut payload = report.clone();
+            if payload.get("title").and_then(|v| v.as_str()).is_none() {
+                payload["title"] = json!(outgoing.book_title);
+                payload["isbn"] = json!(outgoing.book_isbn);
+                payload["lender_name"] = json!(lender.name);
+            }
+            let lender_request_id = report.get("request_id").and_then(|v| v.as_str());
+            process_borrower_acceptance(db, &outgoing.id, &payload, lender_request_id).await;
+            tracing::info!("Sync: outgoing request {} accepted", outgoing.id);
+            return true;
+        }
+        (_, status) => status,
+    };
+
+    tracing::info!(
+        "Sync: outgoing request {} status changed '{}' -> '{}'",
+        outgoing.id,
+        local_status,
+        stored_status
+    );
+    let mut active: p2p_outgoing_request::ActiveModel = outgoing.clone().into();
+    active.status = Set(stored_status.to_string());
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    if let Err(e) = active.update(db).await {
+        tracing::warn!(
+            "Sync: failed to update outgoing request {}: {e}",
+            outgoing.id
+        );
+        return false;
+    }
+
+    match (local_status, stored_status) {
+        // Our return was recorded: the lender's acknowledgment.
+        ("return_pending", "returned") => {
+            confirm_returned_copy(
+                db,
+                lender,
+                outgoing.book_id.as_deref(),
+                &outgoing.book_isbn,
+                &outgoing.book_title,
+                &outgoing.id,
+            )
+            .await;
+        }
+        (_, "returned") => {
+            clear_returned_loan(
+                db,
+                lender.id,
+                outgoing.book_id.as_deref(),
+                &outgoing.book_isbn,
+                &outgoing.book_title,
+                &outgoing.id,
+            )
+            .await;
+        }
+        (_, "rejected") => {
+            crate::services::notification_service::emit(
+                db,
+                crate::domain::CreateNotification {
+                    event_type: crate::domain::NotificationEventType::BorrowRejected,
+                    title: outgoing.book_title.clone(),
+                    body: Some(lender.name.clone()),
+                    ref_type: Some("loan".to_string()),
+                    ref_id: Some(outgoing.id.clone()),
+                },
+            )
+            .await;
+        }
+        (_, status) => {
+            notify_hold_status(
+                db,
+                local_status,
+                status,
+                &outgoing.book_title,
+                &lender.name,
+                &outgoing.id,
+            )
+            .await;
+        }
+    }
+    true
+}
+
+/// Borrower side of a loan the lender marked returned: drop the copies this
+/// lender lent us and tell the user.
+///
+/// Resolves the book the loan names, declining rather than guessing when only
+/// an ambiguous ISBN identifies it, then deletes the copies this lender lent.
+/// The row may also carry a contact loan, a copy the user owns, or another
+/// peer's live loan; those are never touched.
+///
+/// The book row itself stays, as it does in `retain_returned_book` and
+/// `release_reclaimed_book`. A book read without being owned is a first-class
+/// state carrying reading dates, a rating and notes the reader entered, and
+/// this runs on an inbound message with nobody in front of the screen.
+/// Removing a book from the library is left to an explicit user action.
+/// `owned` is untouched for the same reason: the row may be a book the user
+/// genuinely owns, reused by `create_borrowed_copy` on an ISBN match.
+async fn clear_returned_loan(
+    db: &DatabaseConnection,
+    lender_peer_id: i32,
+    book_id: Option<&str>,
+    book_isbn: &str,
+    request_title: &str,
+    loan_id: &str,
+) {
+    tracing::info!("🧹 Cleaning up borrowed copy for book ISBN: {}", book_isbn);
+
+    let resolved_book =
+        crate::services::loan_service::resolve_returned_book(db, book_id, book_isbn).await;
+
+    if let Some(book) = resolved_book.as_ref() {
+        crate::services::loan_service::purge_copies_lent_by(db, &book.id, lender_peer_id).await;
+    }
+
+    // Emit book_returned notification on borrower side. The request's own
+    // title stands in when no local book row could be resolved.
+    let book_title = resolved_book
+        .map(|b| b.title)
+        .unwrap_or_else(|| request_title.to_string());
+    let lender_name = peer::Entity::find_by_id(lender_peer_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|p| p.name)
+        .unwrap_or_default();
+    crate::services::notification_service::emit(
+        db,
+        crate::domain::CreateNotification {
+            event_type: crate::domain::NotificationEventType::BookReturned,
+            title: book_title,
+            body: Some(lender_name),
+            ref_type: Some("loan".to_string()),
+            ref_id: Some(loan_id.to_string()),
+        },
     )
-        .into_response()
+    .await;
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/outgoing/{id}",
+    tag = "peer-requests",
+    params(("id" = String, Path, description = "Outgoing request id")),
+    responses(
+        (status = 200, description = "Request deleted and the lender told to cancel"),
+        (status = 404, description = "Request not found")
+    )
+)]
 pub async fn delete_outgoing_request(
     State(state): State<crate::infrastructure::AppState>,
     Path(id): Path<String>,
@@ -1064,7 +1446,7 @@ pub async fn delete_outgoing_request(
     };
 
     // 3. Notify the peer about the cancellation (best effort)
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer.url);
     let cancel_url = format!("{}/api/peers/requests/cancel/{}", peer.url, id);
     // Assert our own identity so the lender's ownership check accepts the cancel:
     // it resolves this uuid and requires it to name the borrower that made the request.
@@ -1110,6 +1492,17 @@ pub async fn delete_outgoing_request(
 }
 
 /// Receive cancellation notification from a peer who cancelled their outgoing request
+#[utoipa::path(
+    delete,
+    path = "/api/peers/requests/cancel/{id}",
+    tag = "peer-transport",
+    params(("id" = String, Path, description = "Lender-side request id")),
+    request_body(content = Option<Value>, description = "`{\"library_uuid\": ...}` of the requester"),
+    responses(
+        (status = 200, description = "Request cancelled (or already gone)"),
+        (status = 403, description = "Caller is not the requester")
+    )
+)]
 pub async fn cancel_request(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
@@ -1180,6 +1573,9 @@ pub async fn cancel_request(
             .into_response();
     }
 
+    // A request cancelled while on the hold shelf gives its copy back.
+    release_held_copy(&db, request.held_copy_id.as_deref()).await;
+
     // Delete the incoming request that matches this ID
     match p2p_request::Entity::delete_by_id(&id).exec(&db).await {
         Ok(res) => {
@@ -1204,6 +1600,18 @@ pub async fn cancel_request(
 }
 
 /// Receive status update notification from lender (updates local outgoing request)
+#[utoipa::path(
+    put,
+    path = "/api/peers/requests/status/{id}",
+    tag = "peer-transport",
+    params(("id" = String, Path, description = "Borrower-side request id")),
+    request_body(content = Value, description = "`{\"status\": ..., \"library_uuid\": ...}` from the lender"),
+    responses(
+        (status = 200, description = "Status applied"),
+        (status = 400, description = "Missing status"),
+        (status = 403, description = "Caller is not the lender of this request")
+    )
+)]
 pub async fn update_outgoing_status(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
@@ -1299,10 +1707,16 @@ pub async fn update_outgoing_status(
     let lender_peer_id = request.to_peer_id;
     let loan_book_id = request.book_id.clone();
     let request_title = request.book_title.clone();
+    let previous_status = request.status.clone();
 
-    // Update the status
+    // Update the status. The lender's acknowledgment of our return closes it.
+    let stored_status = if new_status == "return_confirmed" {
+        "returned"
+    } else {
+        new_status
+    };
     let mut active: p2p_outgoing_request::ActiveModel = request.into();
-    active.status = Set(new_status.to_string());
+    active.status = Set(stored_status.to_string());
     active.updated_at = Set(chrono::Utc::now().to_rfc3339());
 
     match active.update(&db).await {
@@ -1311,55 +1725,24 @@ pub async fn update_outgoing_status(
 
             // If the loan is returned, clean up the borrowed copy
             if new_status == "returned" {
-                tracing::info!("🧹 Cleaning up borrowed copy for book ISBN: {}", book_isbn);
-
-                // Resolve the book this loan names, declining rather than guessing when
-                // only an ambiguous ISBN identifies it, then delete the copies this lender
-                // lent. The row may also carry a contact loan, a copy the user owns, or
-                // another peer's live loan; those are never touched.
-                //
-                // The book row itself stays, as it does in `retain_returned_book` and
-                // `release_reclaimed_book`. A book read without being owned is a
-                // first-class state carrying reading dates, a rating and notes the reader
-                // entered, and this runs on an inbound message with nobody in front of the
-                // screen. Removing a book from the library is left to an explicit user
-                // action. `owned` is untouched for the same reason: the row may be a book
-                // the user genuinely owns, reused by `create_borrowed_copy` on an ISBN
-                // match.
-                let resolved_book = crate::services::loan_service::resolve_returned_book(
+                clear_returned_loan(
                     &db,
+                    lender_peer_id,
                     loan_book_id.as_deref(),
                     &book_isbn,
+                    &request_title,
+                    &id,
                 )
                 .await;
+            }
 
-                if let Some(book) = resolved_book.as_ref() {
-                    crate::services::loan_service::purge_copies_lent_by(
-                        &db,
-                        &book.id,
-                        lender_peer_id,
-                    )
-                    .await;
-                }
-
-                // Emit book_returned notification on borrower side. The request's own
-                // title stands in when no local book row could be resolved.
-                let book_title = resolved_book
-                    .map(|b| b.title)
-                    .unwrap_or_else(|| request_title.clone());
-                let lender_name = peer::Entity::find_by_id(lender_peer_id)
-                    .one(&db)
-                    .await
-                    .ok()
-                    .flatten()
-                    .map(|p| p.name)
-                    .unwrap_or_default();
+            if new_status == "rejected" {
                 crate::services::notification_service::emit(
                     &db,
                     crate::domain::CreateNotification {
-                        event_type: crate::domain::NotificationEventType::BookReturned,
-                        title: book_title,
-                        body: Some(lender_name),
+                        event_type: crate::domain::NotificationEventType::BorrowRejected,
+                        title: request_title.clone(),
+                        body: lender.as_ref().map(|p| p.name.clone()),
                         ref_type: Some("loan".to_string()),
                         ref_id: Some(id.clone()),
                     },
@@ -1367,6 +1750,29 @@ pub async fn update_outgoing_status(
                 .await;
             }
 
+            if let Some(lender) = lender.as_ref() {
+                if new_status == "return_confirmed" {
+                    confirm_returned_copy(
+                        &db,
+                        lender,
+                        loan_book_id.as_deref(),
+                        &book_isbn,
+                        &request_title,
+                        &id,
+                    )
+                    .await;
+                }
+                notify_hold_status(
+                    &db,
+                    &previous_status,
+                    new_status,
+                    &request_title,
+                    &lender.name,
+                    &id,
+                )
+                .await;
+            }
+
             StatusCode::OK.into_response()
         }
         Err(e) => {
@@ -1380,7 +1786,7 @@ pub async fn update_outgoing_status(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct OutgoingLoanRequestDto {
     pub to_peer_url: String,
     pub book_isbn: String,
@@ -1388,6 +1794,15 @@ pub struct OutgoingLoanRequestDto {
     pub request_id: Option<String>, // ID from remote peer for sync
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/requests/outgoing",
+    tag = "peer-requests",
+    request_body = OutgoingLoanRequestDto,
+    responses(
+        (status = 200, description = "Outgoing request recorded")
+    )
+)]
 pub async fn create_outgoing_request(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<OutgoingLoanRequestDto>,
@@ -13,35 +13,40 @@ use sea_orm::{
 use serde::Deserialize;
 use serde_json::json;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ReturnBorrowedBookPayload {
     pub copy_id: String,
 }
 
 /// Outcome of a borrower-initiated return.
 ///
-/// The local copy is removed on every path, so HTTP 200 says nothing useful on
-/// its own. `lender_notified` is what the UI must branch on: a return the lender
+/// The local copy is removed or archived on every path, so HTTP 200 says nothing
+/// useful on its own. `lender_notified` is what the UI must branch on: a return the lender
 /// never hears about leaves the book out on loan on their side, indefinitely,
 /// with no signal anywhere. Reporting that as a success is a lie, and the user
 /// loses the only moment they could have acted on it.
 ///
+/// `return_confirmed` tells a return the lender already recorded from one still
+/// awaiting its acknowledgment (the copy is archived as `returning` until then).
+///
 /// `reason` names the failure so the UI (and a bug report) can tell the cases
 /// apart. It is None exactly when `lender_notified` is true.
 pub(crate) fn return_outcome(
     lender_notified: bool,
+    return_confirmed: bool,
     reason: Option<&str>,
 ) -> axum::response::Response {
-    let message = if lender_notified {
-        "Book returned successfully"
-    } else {
-        "Copy removed locally; the lender was not notified"
+    let message = match (lender_notified, return_confirmed) {
+        (true, true) => "Book returned successfully",
+        (true, false) => "Return sent; waiting for the lender to confirm",
+        (false, _) => "Copy removed locally; the lender was not notified",
     };
     (
         StatusCode::OK,
         Json(json!({
             "message": message,
             "lender_notified": lender_notified,
+            "return_confirmed": lender_notified && return_confirmed,
             "reason": reason,
         })),
     )
@@ -49,6 +54,16 @@ pub(crate) fn return_outcome(
 }
 
 /// Borrower initiates a return: notifies the lender and cleans up local data.
+#[utoipa::path(
+    post,
+    path = "/api/peers/return_book",
+    tag = "peer-requests",
+    request_body = ReturnBorrowedBookPayload,
+    responses(
+        (status = 200, description = "Return recorded; reports whether the lender was notified"),
+        (status = 404, description = "Copy or request not found")
+    )
+)]
 pub async fn return_borrowed_book(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<ReturnBorrowedBookPayload>,
@@ -107,13 +122,13 @@ pub async fn return_borrowed_book(
             // Fallback: delete the local copy + clean up orphaned book
             let _ = copy::Entity::delete_by_id(payload.copy_id).exec(&db).await;
             retain_returned_book(&db, the_copy.book_id).await;
-            return return_outcome(false, Some("no_outgoing_request"));
+            return return_outcome(false, false, Some("no_outgoing_request"));
         }
         Err(e) => {
             tracing::error!("DB error finding outgoing request: {}", e);
             let _ = copy::Entity::delete_by_id(payload.copy_id).exec(&db).await;
             retain_returned_book(&db, the_copy.book_id).await;
-            return return_outcome(false, Some("request_lookup_failed"));
+            return return_outcome(false, false, Some("request_lookup_failed"));
         }
     };
 
@@ -132,7 +147,7 @@ pub async fn return_borrowed_book(
             active.status = Set("returned".to_string());
             active.updated_at = Set(chrono::Utc::now().to_rfc3339());
             let _ = active.update(&db).await;
-            return return_outcome(false, Some("peer_unknown"));
+            return return_outcome(false, false, Some("peer_unknown"));
         }
         Err(e) => {
             return (
@@ -150,8 +165,12 @@ pub async fn return_borrowed_book(
     // was shown a success: the one moment they could have acted on it. The E2EE
     // attempt above already blocks, so awaiting the fallback adds no wait that was
     // not already there.
+    //
+    // The plaintext endpoint answers with the lender's acknowledgment. Over E2EE
+    // the acknowledgment arrives later as a `return_confirmed` status update.
     let lender_request_id = outgoing_req.lender_request_id.clone();
     let mut reason: Option<&str> = None;
+    let mut return_confirmed = false;
     let lender_notified = if let Some(ref lender_req_id) = lender_request_id {
         let return_payload = json!({
             "loan_id": lender_req_id,
@@ -174,32 +193,16 @@ pub async fn return_borrowed_book(
                 reason = Some("e2ee_send_failed");
                 false
             }
-            Ok(None) => {
-                // Plaintext fallback
-                let url = format!("{}/api/peers/requests/{}", peer.url, lender_req_id);
-                match get_safe_client()
-                    .put(&url)
-                    .json(&serde_json::json!({ "status": "returned" }))
-                    .timeout(std::time::Duration::from_secs(10))
-                    .send()
-                    .await
-                {
-                    Ok(res) if res.status().is_success() => {
-                        tracing::info!("Return notification sent to lender: {}", res.status());
-                        true
-                    }
-                    Ok(res) => {
-                        tracing::warn!("Lender refused the return notification: {}", res.status());
-                        reason = Some("lender_refused_notification");
-                        false
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to send return notification to lender: {}", e);
-                        reason = Some("lender_unreachable");
-                        false
-                    }
+            Ok(None) => match send_plaintext_return(&state, &peer, lender_req_id).await {
+                Ok(()) => {
+                    return_confirmed = true;
+                    true
                 }
-            }
+                Err(r) => {
+                    reason = Some(r);
+                    false
+                }
+            },
         }
     } else {
         tracing::warn!(
@@ -210,23 +213,181 @@ pub async fn return_borrowed_book(
         false
     };
 
-    // 4. Update outgoing request status to "returned"
+    // 4. A return the lender still has to acknowledge keeps the copy, archived
+    // as `returning` (out of the borrowed shelf), until `return_confirmed`
+    // arrives. Otherwise the request is closed and the copy deleted.
+    let awaiting_ack = lender_notified && !return_confirmed;
     let mut active: p2p_outgoing_request::ActiveModel = outgoing_req.into();
-    active.status = Set("returned".to_string());
+    active.status = Set(if awaiting_ack {
+        "return_pending".to_string()
+    } else {
+        "returned".to_string()
+    });
     active.updated_at = Set(chrono::Utc::now().to_rfc3339());
     if let Err(e) = active.update(&db).await {
         tracing::warn!("Failed to update outgoing request status: {}", e);
     }
 
-    // 5. Delete the borrowed copy
-    if let Err(e) = copy::Entity::delete_by_id(payload.copy_id).exec(&db).await {
+    // 5. Archive or delete the borrowed copy
+    if awaiting_ack {
+        let mut archived: copy::ActiveModel = the_copy.clone().into();
+        archived.status = Set("returning".to_string());
+        archived.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        if let Err(e) = archived.update(&db).await {
+            tracing::warn!("Failed to archive borrowed copy: {}", e);
+        }
+    } else if let Err(e) = copy::Entity::delete_by_id(payload.copy_id).exec(&db).await {
         tracing::warn!("Failed to delete borrowed copy: {}", e);
     }
 
     // 6. Clean up book if no longer needed
     retain_returned_book(&db, the_copy.book_id).await;
 
-    return_outcome(lender_notified, reason)
+    return_outcome(lender_notified, return_confirmed, reason)
+}
+
+/// Report a return to a lender without an E2EE channel.
+///
+/// `POST /api/peers/loans/returned` records the return and acknowledges it in
+/// the same exchange. A lender that predates it (404/405) gets the legacy
+/// `PUT /api/peers/requests/:id`, whose success is just as final. The error is
+/// the `reason` reported to the UI.
+async fn send_plaintext_return(
+    state: &crate::infrastructure::AppState,
+    peer: &crate::models::peer::Model,
+    lender_req_id: &str,
+) -> Result<(), &'static str> {
+    let client = get_safe_client_for(&peer.url);
+    let res = client
+        .post(format!("{}/api/peers/loans/returned", peer.url))
+        .json(&json!({
+            "loan_id": lender_req_id,
+            "library_uuid": state.identity_service.library_uuid(),
+        }))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await;
+    let res = match res {
+        Ok(res)
+            if res.status() == StatusCode::NOT_FOUND
+                || res.status() == StatusCode::METHOD_NOT_ALLOWED =>
+        {
+            tracing::info!("Lender has no return endpoint, using the legacy status update");
+            client
+                .put(format!("{}/api/peers/requests/{}", peer.url, lender_req_id))
+                .json(&json!({
+                    "status": "returned",
+                    "library_uuid": state.identity_service.library_uuid(),
+                }))
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await
+        }
+        other => other,
+    };
+    match res {
+        Ok(res) if res.status().is_success() => {
+            tracing::info!("Return notification sent to lender: {}", res.status());
+            Ok(())
+        }
+        Ok(res) => {
+            tracing::warn!("Lender refused the return notification: {}", res.status());
+            Err("lender_refused_notification")
+        }
+        Err(e) => {
+            tracing::warn!("Failed to send return notification to lender: {}", e);
+            Err("lender_unreachable")
+        }
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct LoanReturnedPayload {
+    /// The loan's id at the lender (`p2p_requests.id`).
+    pub loan_id: String,
+    /// The borrower's library, which must be the one that made the request.
+    pub library_uuid: Option<String>,
+}
+
+/// Lender receives a plaintext return notification from the borrower.
+///
+/// Records the return like `update_request_status` does for `returned` and
+/// answers with the acknowledgment the borrower waits for. A replay of a
+/// return already recorded is acknowledged again. Ownership is checked as in
+/// `update_outgoing_status`: only the keyless requester named by the request,
+/// identified by its `library_uuid`, may close it.
+#[utoipa::path(
+    post,
+    path = "/api/peers/loans/returned",
+    tag = "peer-transport",
+    request_body = LoanReturnedPayload,
+    responses(
+        (status = 200, description = "Return recorded and acknowledged"),
+        (status = 403, description = "Caller is not the borrower"),
+        (status = 404, description = "Loan not found"),
+        (status = 409, description = "Loan is not active")
+    )
+)]
+pub async fn receive_loan_returned(
+    State(state): State<crate::infrastructure::AppState>,
+    Json(payload): Json<LoanReturnedPayload>,
+) -> impl IntoResponse {
+    use crate::models::p2p_request;
+
+    let db = state.db().clone();
+
+    let req = match p2p_request::Entity::find_by_id(&payload.loan_id)
+        .one(&db)
+        .await
+    {
+        Ok(Some(req)) => req,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Request not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(refusal) = refuse_unless_requester(&db, &req, payload.library_uuid.as_deref()).await
+    {
+        return refusal;
+    }
+
+    match req.status.as_str() {
+        "returned" => {}
+        "accepted" => {
+            record_peer_return(&db, &req).await;
+            let mut active: p2p_request::ActiveModel = req.into();
+            active.status = Set("returned".to_string());
+            active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+            if let Err(e) = active.update(&db).await {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+            crate::services::p2p_holds::spawn_serve(&state);
+        }
+        _ => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "error": "This loan is not active" })),
+            )
+                .into_response();
+        }
+    }
+
+    (StatusCode::OK, Json(json!({ "return_confirmed": true }))).into_response()
 }
 
 /// Find the accepted outgoing request matching the borrowed copy being returned.
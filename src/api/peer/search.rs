@@ -12,11 +12,20 @@ use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilt
 use serde::Deserialize;
 use serde_json::json;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct SearchRequest {
     query: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/search",
+    tag = "peer-transport",
+    request_body = SearchRequest,
+    responses(
+        (status = 200, description = "Matching public books of this library")
+    )
+)]
 pub async fn search_local(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<SearchRequest>,
@@ -25,7 +34,7 @@ pub async fn search_local(
     use sea_orm::sea_query::Expr;
 
     let books = book::Entity::find()
-        .filter(book::Column::Private.eq(false))
+        .filter(crate::models::Book::shared_condition(&db).await)
         .filter(
             Condition::any()
                 .add(book::Column::Title.contains(&payload.query))
@@ -43,7 +52,7 @@ pub async fn search_local(
     (StatusCode::OK, Json(book_dtos)).into_response()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct ProxySearchRequest {
     peer_id: Option<i32>,
     peer_url: Option<String>,
@@ -57,13 +66,14 @@ pub struct ProxySearchRequest {
 /// Without pagination params, returns a flat `Vec<Book>` array (legacy).
 /// The peer's response carries `added_at` directly (the owner's
 /// `books.created_at`), so the "new" badge works without local enrichment.
+#[tracing::instrument(skip_all)]
 async fn plaintext_proxy_search(
     peer_url: &str,
     query: &str,
     page: Option<u64>,
     limit: Option<u64>,
 ) -> axum::response::Response {
-    let client = get_safe_client();
+    let client = get_safe_client_for(peer_url);
     let res = if query.is_empty() {
         let mut url = format!("{}/api/books?owned_only=true", peer_url);
         if let Some(p) = page {
@@ -131,6 +141,17 @@ async fn plaintext_proxy_search(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/proxy_search",
+    tag = "peer-catalogue",
+    request_body = ProxySearchRequest,
+    responses(
+        (status = 200, description = "Search results from the peer"),
+        (status = 400, description = "Neither peer_id nor peer_url given"),
+        (status = 404, description = "Peer not found")
+    )
+)]
 pub async fn proxy_search(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<ProxySearchRequest>,
@@ -266,6 +287,7 @@ pub async fn proxy_search(
         .into_response()
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn broadcast_search(
     db: &DatabaseConnection,
     params: &crate::api::search::SearchQuery,
@@ -275,16 +297,15 @@ pub async fn broadcast_search(
         return vec![];
     }
 
-    let client = get_safe_client();
     let query_str = params.title.clone().unwrap_or_default(); // Simple query for now
 
     let futures = peers.into_iter().map(|peer| {
-        let client = client.clone();
         let q = query_str.clone();
         async move {
             if validate_url(&peer.url).is_err() {
                 return vec![];
             }
+            let client = get_safe_client_for(&peer.url);
             let url = format!("{}/api/peers/search", peer.url);
             match client
                 .post(&url)
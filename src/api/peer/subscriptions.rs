@@ -0,0 +1,111 @@
+//! Subscriptions to peer catalogues (see `services::peer_subscriptions`).
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::peer_subscriptions::{self, SubscriptionError};
+
+fn subscription_error(e: SubscriptionError) -> Response {
+    let status = match e {
+        SubscriptionError::PeerNotFound | SubscriptionError::NotSubscribed => StatusCode::NOT_FOUND,
+        SubscriptionError::NotApproved => StatusCode::FORBIDDEN,
+        SubscriptionError::Busy => StatusCode::CONFLICT,
+        SubscriptionError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/peers/subscriptions - Subscribed peers and how their syncs went
+#[utoipa::path(
+    get,
+    path = "/api/peers/subscriptions",
+    tag = "peer-catalogue",
+    responses(
+        (status = 200, description = "Subscriptions with status, last success and last error", body = [SubscriptionView])
+    )
+)]
+pub async fn list_subscriptions(State(state): State<AppState>) -> Response {
+    match peer_subscriptions::list(state.db()).await {
+        Ok(found) => Json(json!({ "subscriptions": found })).into_response(),
+        Err(e) => subscription_error(e.into()),
+    }
+}
+
+/// Request body for subscribing to a peer
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct SubscribeRequest {
+    /// Minutes between scheduled syncs, 60 by default, from 5 to a week
+    pub interval_minutes: Option<i32>,
+}
+
+/// PUT /api/peers/:id/subscription - Keep a peer's catalogue fresh
+#[utoipa::path(
+    put,
+    path = "/api/peers/{id}/subscription",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    request_body = SubscribeRequest,
+    responses(
+        (status = 200, description = "The subscription; a new one syncs within a minute", body = PeerSubscription),
+        (status = 403, description = "Peer connection pending approval"),
+        (status = 404, description = "Peer not found")
+    )
+)]
+pub async fn subscribe_peer(
+    State(state): State<AppState>,
+    Path(peer_id): Path<i32>,
+    body: Option<Json<SubscribeRequest>>,
+) -> Response {
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    match peer_subscriptions::subscribe(state.db(), peer_id, req.interval_minutes).await {
+        Ok(subscription) => Json(subscription).into_response(),
+        Err(e) => subscription_error(e),
+    }
+}
+
+/// DELETE /api/peers/:id/subscription - Stop syncing a peer in the background
+#[utoipa::path(
+    delete,
+    path = "/api/peers/{id}/subscription",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Unsubscribed; cached books stay"),
+        (status = 404, description = "Not subscribed to this peer")
+    )
+)]
+pub async fn unsubscribe_peer(State(state): State<AppState>, Path(peer_id): Path<i32>) -> Response {
+    match peer_subscriptions::unsubscribe(state.db(), peer_id).await {
+        Ok(true) => Json(json!({ "unsubscribed": peer_id })).into_response(),
+        Ok(false) => subscription_error(SubscriptionError::NotSubscribed),
+        Err(e) => subscription_error(e.into()),
+    }
+}
+
+/// POST /api/peers/:id/subscription/sync - Sync a subscribed peer now
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/subscription/sync",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "The subscription after the sync; a failure shows in `status` and `last_error`", body = PeerSubscription),
+        (status = 404, description = "Not subscribed to this peer"),
+        (status = 409, description = "A sync of this peer is already running")
+    )
+)]
+pub async fn sync_subscription(
+    State(state): State<AppState>,
+    Path(peer_id): Path<i32>,
+) -> Response {
+    match peer_subscriptions::sync_now(&state, peer_id).await {
+        Ok(subscription) => Json(subscription).into_response(),
+        Err(e) => subscription_error(e),
+    }
+}
@@ -9,7 +9,7 @@ use axum::{
 };
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
-    Set,
+    QueryOrder, QuerySelect, Set,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -94,6 +94,7 @@ pub(crate) async fn upsert_peer_books_cache(
 
     // 2. Upsert each book
     for book in books {
+        let book = crate::utils::sanitize::book(book);
         let remote_id = book.id.unwrap_or_default();
         fresh_ids.insert(remote_id.clone());
 
@@ -207,6 +208,7 @@ pub(crate) async fn upsert_peer_books_cache(
 }
 
 /// Internal sync function for background sync after connect
+#[tracing::instrument(skip_all, fields(peer_id = peer_id))]
 pub(crate) async fn sync_peer_internal(
     db: &DatabaseConnection,
     peer_id: i32,
@@ -215,7 +217,7 @@ pub(crate) async fn sync_peer_internal(
     // Validate URL
     validate_url(peer_url).map_err(|e| format!("Invalid peer URL: {}", e))?;
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(peer_url);
 
     // First, check peer's config for privacy consent flags
     let config_url = format!("{}/api/config", peer_url);
@@ -226,6 +228,13 @@ pub(crate) async fn sync_peer_internal(
         _ => None,
     };
 
+    if let Some(config) = &peer_config
+        && let Err(e) =
+            crate::services::tor::record_peer_onion(db, peer_id, config.onion_url.as_deref()).await
+    {
+        tracing::warn!("Could not record the onion URL of peer {}: {}", peer_id, e);
+    }
+
     // Distinguish "peer explicitly disallows caching" from "peer unreachable"
     let peer_reachable = peer_config.is_some();
     let allows_caching = peer_config
@@ -279,6 +288,17 @@ pub(crate) async fn sync_peer_internal(
             .filter(peer_book::Column::PeerId.eq(peer_id))
             .exec(db)
             .await;
+        // Its covers and files go with the cache
+        if crate::services::content_blobs::record_peer_blobs(db, peer_id, Vec::new())
+            .await
+            .is_ok()
+        {
+            let _ = crate::services::content_blobs::prune_store(
+                db,
+                &crate::services::content_blobs::store_dir(),
+            )
+            .await;
+        }
         // Still sync gamification stats if available
         sync_peer_gamification_stats(db, peer_id, peer_url, &client, shares_gamification).await;
         // Still sync memory game scores
@@ -350,6 +370,10 @@ pub(crate) async fn sync_peer_internal(
     // a complete snapshot: prune books the peer no longer owns.
     let count = upsert_peer_books_cache(db, peer_id, None, data.books, true).await;
 
+    // Fetch covers and shared files by content hash, so the catalogue
+    // browses offline with its images
+    sync_peer_blobs(db, peer_id, peer_url, &client).await;
+
     // Sync gamification stats if both sides have the module enabled
     sync_peer_gamification_stats(db, peer_id, peer_url, &client, shares_gamification).await;
 
@@ -401,6 +425,49 @@ pub(crate) async fn sync_peer_internal(
     Ok(count)
 }
 
+/// Fetch the covers (and digital files, if the peer shares them) a peer
+/// lists, into the local blob store. Best effort: a peer without the blob
+/// endpoints is skipped, and a transfer cut short resumes on the next sync.
+pub(crate) async fn sync_peer_blobs(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    peer_url: &str,
+    client: &reqwest::Client,
+) {
+    use crate::services::content_blobs::{self, BlobEntry};
+
+    #[derive(Deserialize)]
+    struct BlobsResponse {
+        blobs: Vec<BlobEntry>,
+    }
+
+    let url = format!("{}/api/blobs", peer_url.trim_end_matches('/'));
+    let listed = match client.get(&url).send().await {
+        Ok(res) if res.status().is_success() => res.json::<BlobsResponse>().await.ok(),
+        _ => None,
+    };
+    let Some(listed) = listed else {
+        return;
+    };
+    if let Err(e) = content_blobs::record_peer_blobs(db, peer_id, listed.blobs).await {
+        tracing::warn!("peer {}: could not record blobs: {}", peer_id, e);
+        return;
+    }
+
+    let dir = content_blobs::store_dir();
+    match content_blobs::fetch_peer_blobs(db, client, peer_id, peer_url, &dir).await {
+        Ok(summary) => tracing::info!(
+            "peer {}: {} blobs fetched ({} bytes), {} pending",
+            peer_id,
+            summary.fetched,
+            summary.bytes,
+            summary.pending
+        ),
+        Err(e) => tracing::warn!("peer {}: blob fetch failed: {}", peer_id, e),
+    }
+    let _ = content_blobs::prune_store(db, &dir).await;
+}
+
 /// Sync gamification stats from a peer.
 /// `peer_shares_stats`:
 ///   - `Some(true)`:  peer confirmed it shares → fetch fresh stats
@@ -500,12 +567,12 @@ pub(crate) async fn sync_peer_gamification_stats(
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct PushRequest {
     operations: Vec<OperationDto>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
 pub struct OperationDto {
     entity_type: String,
     entity_id: String,
@@ -514,6 +581,15 @@ pub struct OperationDto {
     created_at: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/push",
+    tag = "peers",
+    request_body = PushRequest,
+    responses(
+        (status = 200, description = "Operations stored (legacy device sync)")
+    )
+)]
 pub async fn push_operations(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<PushRequest>,
@@ -537,14 +613,82 @@ pub async fn push_operations(
         .into_response()
 }
 
-pub async fn pull_operations(State(db): State<DatabaseConnection>) -> impl IntoResponse {
-    let ops = operation_log::Entity::find()
+/// Largest page of `GET /api/peers/pull`.
+const PULL_PAGE: u64 = 500;
+
+/// Cursor of a pull: the log ids are its sequence numbers. Without either
+/// field the whole log comes back as a bare array, as older devices expect.
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct PullQuery {
+    /// Last `operation_log.id` already seen
+    pub since: Option<i32>,
+    pub limit: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/peers/pull",
+    tag = "peers",
+    params(PullQuery),
+    responses(
+        (status = 200, description = "With `since` or `limit`: one page of the operation log after the cursor as `{operations, latest_cursor, has_more}`. Without either: the whole log as a bare array (legacy device sync)")
+    )
+)]
+pub async fn pull_operations(
+    State(db): State<DatabaseConnection>,
+    axum::extract::Query(query): axum::extract::Query<PullQuery>,
+) -> impl IntoResponse {
+    if query.since.is_none() && query.limit.is_none() {
+        let ops = operation_log::Entity::find()
+            .order_by_asc(operation_log::Column::Id)
+            .all(&db)
+            .await
+            .unwrap_or(vec![]);
+        return (StatusCode::OK, Json(ops)).into_response();
+    }
+    let since = query.since.unwrap_or(0);
+    let limit = query.limit.unwrap_or(PULL_PAGE).clamp(1, PULL_PAGE);
+    let ops = match operation_log::Entity::find()
+        .filter(operation_log::Column::Id.gt(since))
+        .order_by_asc(operation_log::Column::Id)
+        .limit(limit)
         .all(&db)
         .await
-        .unwrap_or(vec![]);
-    (StatusCode::OK, Json(ops)).into_response()
+    {
+        Ok(ops) => ops,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+    let has_more = ops.len() as u64 == limit;
+    let latest_cursor = ops.last().map_or(since, |op| op.id);
+    (
+        StatusCode::OK,
+        Json(json!({
+            "operations": ops,
+            "latest_cursor": latest_cursor,
+            "has_more": has_more,
+        })),
+    )
+        .into_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/peers/{id}/sync",
+    tag = "peer-catalogue",
+    params(("id" = i32, Path, description = "Local peer id")),
+    responses(
+        (status = 200, description = "Remote catalogue fetched and cached"),
+        (status = 403, description = "Peer not accepted"),
+        (status = 404, description = "Peer not found"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
 pub async fn sync_peer(
     State(db): State<DatabaseConnection>,
     Path(peer_id): Path<i32>,
@@ -579,7 +723,7 @@ pub async fn sync_peer(
             .into_response();
     }
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer.url);
 
     // Check peer config for gamification sharing
     let config_url = format!("{}/api/config", peer.url);
@@ -714,6 +858,19 @@ pub async fn sync_peer(
 }
 
 /// Sync peer by URL (solves ID mismatch between Hub and Backend)
+#[utoipa::path(
+    post,
+    path = "/api/peers/sync_by_url",
+    tag = "peer-catalogue",
+    request_body(content = Value, description = "`{\"url\": \"http://...\"}`"),
+    responses(
+        (status = 200, description = "Remote catalogue fetched and cached"),
+        (status = 400, description = "Missing URL"),
+        (status = 403, description = "Peer not accepted"),
+        (status = 404, description = "Peer not found"),
+        (status = 502, description = "Peer unreachable")
+    )
+)]
 pub async fn sync_peer_by_url(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<serde_json::Value>,
@@ -827,7 +984,7 @@ pub async fn sync_peer_by_url(
             .into_response();
     }
 
-    let client = get_safe_client();
+    let client = get_safe_client_for(&peer.url);
 
     // 4. Check peer's config for privacy consent flags.
     // Skip the network fetch + port scan if the peer is cached as unreachable --
@@ -1599,3 +1756,92 @@ mod added_at_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod pull_tests {
+    use super::*;
+    use crate::db;
+    use axum::extract::Query;
+
+    async fn setup(count: usize) -> DatabaseConnection {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        for i in 0..count {
+            operation_log::ActiveModel {
+                entity_type: Set("book".to_string()),
+                entity_id: Set(i.to_string()),
+                operation: Set("update".to_string()),
+                created_at: Set(chrono::Utc::now().to_rfc3339()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    async fn pull(
+        db: &DatabaseConnection,
+        since: Option<i32>,
+        limit: Option<u64>,
+    ) -> serde_json::Value {
+        let response = pull_operations(State(db.clone()), Query(PullQuery { since, limit }))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn ids(page: &serde_json::Value) -> Vec<i64> {
+        page["operations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| op["id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn a_pull_without_cursor_is_the_bare_log() {
+        let db = setup(3).await;
+        let body = pull(&db, None, None).await;
+        assert_eq!(body.as_array().expect("bare array").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn a_pull_pages_after_the_cursor() {
+        let db = setup(5).await;
+        let all = pull(&db, None, None).await;
+        let first_id = all[0]["id"].as_i64().unwrap() as i32;
+
+        let page = pull(&db, Some(first_id), Some(2)).await;
+        assert_eq!(ids(&page), vec![first_id as i64 + 1, first_id as i64 + 2]);
+        assert_eq!(page["latest_cursor"], first_id + 2);
+        assert_eq!(page["has_more"], true);
+
+        let rest = pull(&db, Some(first_id + 2), Some(10)).await;
+        assert_eq!(ids(&rest).len(), 2);
+        assert_eq!(rest["latest_cursor"], first_id + 4);
+        assert_eq!(rest["has_more"], false);
+
+        let empty = pull(&db, Some(first_id + 4), None).await;
+        assert!(ids(&empty).is_empty());
+        assert_eq!(empty["latest_cursor"], first_id + 4);
+        assert_eq!(empty["has_more"], false);
+    }
+
+    #[tokio::test]
+    async fn a_pull_limit_is_clamped() {
+        let db = setup(3).await;
+        let page = pull(&db, None, Some(0)).await;
+        assert_eq!(ids(&page).len(), 1);
+        assert_eq!(page["has_more"], true);
+
+        let page = pull(&db, Some(0), Some(PULL_PAGE * 10)).await;
+        assert_eq!(ids(&page).len(), 3);
+        assert_eq!(page["has_more"], false);
+    }
+}
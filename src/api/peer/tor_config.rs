@@ -0,0 +1,56 @@
+//! SOCKS5 proxy and onion address settings (see `services::tor`).
+
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::tor::{self, TorError, TorSettings};
+
+fn tor_error(e: TorError) -> Response {
+    let status = match e {
+        TorError::InvalidProxy(_) | TorError::InvalidOnion(_) => StatusCode::BAD_REQUEST,
+        TorError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/peers/tor/config - Proxy peer requests go through, onion address
+#[utoipa::path(
+    get,
+    path = "/api/peers/tor/config",
+    tag = "peers",
+    responses(
+        (status = 200, description = "Current settings; all empty when peers are reached directly", body = TorSettings)
+    )
+)]
+pub async fn get_tor_config(State(state): State<AppState>) -> Response {
+    match tor::get(state.db()).await {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => tor_error(e.into()),
+    }
+}
+
+/// PUT /api/peers/tor/config - Set the SOCKS5 proxy and onion address
+#[utoipa::path(
+    put,
+    path = "/api/peers/tor/config",
+    tag = "peers",
+    request_body = TorSettings,
+    responses(
+        (status = 200, description = "Settings as stored, normalized; they apply to the next peer request", body = TorSettings),
+        (status = 400, description = "Not a socks5h:// proxy, or not a v3 onion address")
+    )
+)]
+pub async fn update_tor_config(
+    State(state): State<AppState>,
+    Json(settings): Json<TorSettings>,
+) -> Response {
+    match tor::save(state.db(), settings).await {
+        Ok(saved) => Json(saved).into_response(),
+        Err(e) => tor_error(e),
+    }
+}
@@ -0,0 +1,185 @@
+//! Periodicals, issue check-in and holdings (see `services::periodicals`).
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::periodicals::{self, CheckIn, NewPeriodical, PeriodicalError};
+
+fn periodical_error(e: PeriodicalError) -> Response {
+    let status = match e {
+        PeriodicalError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        PeriodicalError::NotFound | PeriodicalError::IssueNotFound => StatusCode::NOT_FOUND,
+        PeriodicalError::DuplicateIssn(_) | PeriodicalError::DuplicateIssue => StatusCode::CONFLICT,
+        PeriodicalError::Invalid(_) => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Query parameters of `GET /api/periodicals`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct PeriodicalsQuery {
+    /// ISSN, typed or scanned from the cover's EAN-13
+    pub issn: Option<String>,
+}
+
+/// GET /api/periodicals - Periodicals by title
+#[utoipa::path(
+    get,
+    path = "/api/periodicals",
+    tag = "periodicals",
+    params(PeriodicalsQuery),
+    responses(
+        (status = 200, description = "Periodicals with their issues and holdings", body = [PeriodicalDetail]),
+        (status = 400, description = "Invalid ISSN")
+    )
+)]
+pub async fn list_periodicals(
+    State(state): State<AppState>,
+    Query(query): Query<PeriodicalsQuery>,
+) -> Response {
+    match periodicals::list_periodicals(state.db(), query.issn.as_deref()).await {
+        Ok(list) => Json(json!({ "periodicals": list })).into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
+
+/// POST /api/periodicals - Add a periodical
+#[utoipa::path(
+    post,
+    path = "/api/periodicals",
+    tag = "periodicals",
+    request_body = NewPeriodical,
+    responses(
+        (status = 201, description = "Periodical added", body = PeriodicalDetail),
+        (status = 400, description = "No title, invalid ISSN or unknown frequency"),
+        (status = 409, description = "A periodical with this ISSN exists")
+    )
+)]
+pub async fn create_periodical(
+    State(state): State<AppState>,
+    Json(new): Json<NewPeriodical>,
+) -> Response {
+    match periodicals::create_periodical(state.db(), new).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
+
+/// GET /api/periodicals/:id - One periodical with its holdings
+#[utoipa::path(
+    get,
+    path = "/api/periodicals/{id}",
+    tag = "periodicals",
+    params(("id" = String, Path, description = "Periodical id")),
+    responses(
+        (status = 200, description = "The periodical, its issues, holdings and next expected issue", body = PeriodicalDetail),
+        (status = 404, description = "Periodical not found")
+    )
+)]
+pub async fn get_periodical(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match periodicals::get_periodical(state.db(), &id).await {
+        Ok(Some(found)) => Json(found).into_response(),
+        Ok(None) => periodical_error(PeriodicalError::NotFound),
+        Err(e) => periodical_error(e.into()),
+    }
+}
+
+/// PUT /api/periodicals/:id - Edit a periodical
+#[utoipa::path(
+    put,
+    path = "/api/periodicals/{id}",
+    tag = "periodicals",
+    params(("id" = String, Path, description = "Periodical id")),
+    request_body = NewPeriodical,
+    responses(
+        (status = 200, description = "Periodical updated", body = PeriodicalDetail),
+        (status = 400, description = "No title, invalid ISSN or unknown frequency"),
+        (status = 404, description = "Periodical not found"),
+        (status = 409, description = "Another periodical has this ISSN")
+    )
+)]
+pub async fn update_periodical(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(new): Json<NewPeriodical>,
+) -> Response {
+    match periodicals::update_periodical(state.db(), &id, new).await {
+        Ok(updated) => Json(updated).into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
+
+/// DELETE /api/periodicals/:id - Delete a periodical and its issues
+#[utoipa::path(
+    delete,
+    path = "/api/periodicals/{id}",
+    tag = "periodicals",
+    params(("id" = String, Path, description = "Periodical id")),
+    responses(
+        (status = 204, description = "Periodical deleted"),
+        (status = 404, description = "Periodical not found")
+    )
+)]
+pub async fn delete_periodical(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match periodicals::delete_periodical(state.db(), &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
+
+/// POST /api/periodicals/:id/issues - Check in an issue
+#[utoipa::path(
+    post,
+    path = "/api/periodicals/{id}/issues",
+    tag = "periodicals",
+    params(("id" = String, Path, description = "Periodical id")),
+    request_body = CheckIn,
+    responses(
+        (status = 201, description = "Issue checked in; the periodical with its new holdings", body = PeriodicalDetail),
+        (status = 400, description = "Bad volume, number or date"),
+        (status = 404, description = "Periodical not found"),
+        (status = 409, description = "Issue already checked in")
+    )
+)]
+pub async fn check_in_issue(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Option<Json<CheckIn>>,
+) -> Response {
+    let issue = body.map(|Json(i)| i).unwrap_or_default();
+    match periodicals::check_in(state.db(), &id, issue).await {
+        Ok(updated) => (StatusCode::CREATED, Json(updated)).into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
+
+/// DELETE /api/periodicals/:id/issues/:issue_id - Remove an issue
+#[utoipa::path(
+    delete,
+    path = "/api/periodicals/{id}/issues/{issue_id}",
+    tag = "periodicals",
+    params(
+        ("id" = String, Path, description = "Periodical id"),
+        ("issue_id" = String, Path, description = "Issue id")
+    ),
+    responses(
+        (status = 200, description = "Issue removed; the periodical with its new holdings", body = PeriodicalDetail),
+        (status = 404, description = "Periodical or issue not found")
+    )
+)]
+pub async fn delete_issue(
+    State(state): State<AppState>,
+    Path((id, issue_id)): Path<(String, String)>,
+) -> Response {
+    match periodicals::delete_issue(state.db(), &id, &issue_id).await {
+        Ok(updated) => Json(updated).into_response(),
+        Err(e) => periodical_error(e),
+    }
+}
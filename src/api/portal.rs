@@ -0,0 +1,324 @@
+//! Contact portal: portal tokens (owner) and the patron endpoints a portal
+//! token opens (see `services::contact_portal`).
+//!
+//! `/portal/loans`, `/portal/loans/:id/renewal`, `/portal/catalog` and
+//! `/portal/suggestions` are reachable from the LAN and require
+//! `Authorization: Bearer <portal token>`.
+//! Issuing and revoking tokens is owner-only.
+
+use axum::{
+    Json, async_trait,
+    extract::{FromRequestParts, Path, Query, State},
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use sea_orm::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::infrastructure::AppState;
+use crate::models::{Book, book, contact};
+use crate::services::contact_portal::{self, PortalError};
+use crate::services::suggestions::{self, NewSuggestion, SuggestionError};
+
+/// Default and largest page of the portal catalogue.
+const CATALOG_PAGE: u64 = 20;
+const CATALOG_MAX_PAGE: u64 = 50;
+
+/// Extractor admitting a request that carries a valid portal token; holds the
+/// contact it belongs to.
+pub struct PortalSession(pub contact::Model);
+
+#[async_trait]
+impl FromRequestParts<AppState> for PortalSession {
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "Missing portal token" })),
+            ))?;
+
+        match contact_portal::authenticate(state.db(), token).await {
+            Ok(Some(holder)) => Ok(PortalSession(holder)),
+            Ok(None) => Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "Invalid portal token" })),
+            )),
+            Err(e) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )),
+        }
+    }
+}
+
+fn db_error(e: DbErr) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": e.to_string() })),
+    )
+        .into_response()
+}
+
+/// POST /api/contacts/:id/portal-token - Issue a contact's portal token
+#[utoipa::path(
+    post,
+    path = "/api/contacts/{id}/portal-token",
+    tag = "portal",
+    params(("id" = String, Path, description = "Contact id")),
+    responses(
+        (status = 201, description = "Token issued, replacing any previous one; `token` is not shown again"),
+        (status = 404, description = "Contact not found")
+    )
+)]
+pub async fn issue_token(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match contact_portal::issue_token(state.db(), &id).await {
+        Ok(Some((issued, token))) => (
+            StatusCode::CREATED,
+            Json(json!({ "portal": issued, "token": token })),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Contact not found" })),
+        )
+            .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// DELETE /api/contacts/:id/portal-token - Revoke a contact's portal token
+#[utoipa::path(
+    delete,
+    path = "/api/contacts/{id}/portal-token",
+    tag = "portal",
+    params(("id" = String, Path, description = "Contact id")),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "The contact has no portal token")
+    )
+)]
+pub async fn revoke_token(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match contact_portal::revoke_token(state.db(), &id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "No portal token for this contact" })),
+        )
+            .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// GET /api/portal/loans - The caller's open loans (portal token)
+#[utoipa::path(
+    get,
+    path = "/api/portal/loans",
+    tag = "portal",
+    responses(
+        (status = 200, description = "Open loans of the token's contact, soonest due first"),
+        (status = 401, description = "Missing or invalid portal token")
+    )
+)]
+pub async fn my_loans(
+    PortalSession(holder): PortalSession,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let settings = {
+        use crate::domain::LoanSettingsRepository;
+        crate::infrastructure::SeaOrmLoanSettingsRepository::new(state.db().clone())
+            .get_settings()
+            .await
+            .unwrap_or_default()
+    };
+    let zone = crate::utils::time_zone::library_zone(state.db())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let today = zone.today();
+
+    let loans = contact_portal::open_loans(state.db(), &holder.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let result: Vec<Value> = loans
+        .into_iter()
+        .map(|(loan, book)| {
+            let terms = crate::domain::LendingTerms::from_column(loan.lending_terms.as_deref());
+            json!({
+                "id": loan.id,
+                "loan_date": loan.loan_date,
+                "due_date": loan.due_date,
+                "due_at": zone.due_at(&loan.due_date),
+                "overdue": crate::services::loan_service::is_loan_overdue(
+                    &settings,
+                    &loan.status,
+                    &loan.due_date,
+                    today,
+                ),
+                "renewable": terms.is_none_or(|t| t.renewable),
+                "book_title": book.as_ref().map(|b| b.title.clone()),
+                "cover_url": book.and_then(|b| b.cover_url),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "name": holder.name, "loans": result })))
+}
+
+/// POST /api/portal/loans/:id/renewal - Ask the owner for a renewal (portal token)
+#[utoipa::path(
+    post,
+    path = "/api/portal/loans/{id}/renewal",
+    tag = "portal",
+    params(("id" = String, Path, description = "Loan id")),
+    responses(
+        (status = 202, description = "Renewal requested from the owner"),
+        (status = 401, description = "Missing or invalid portal token"),
+        (status = 404, description = "Not one of the caller's loans"),
+        (status = 409, description = "Loan returned, or not renewable under the lender's terms")
+    )
+)]
+pub async fn request_renewal(
+    PortalSession(holder): PortalSession,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    match contact_portal::request_renewal(state.db(), &holder, &id).await {
+        Ok(loan) => Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({ "loan_id": loan.id, "due_date": loan.due_date })),
+        )),
+        Err(e) => {
+            let status = match e {
+                PortalError::LoanNotFound => StatusCode::NOT_FOUND,
+                PortalError::Returned | PortalError::NotRenewable => StatusCode::CONFLICT,
+                PortalError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+/// Query parameters of the portal catalogue
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct PortalCatalogQuery {
+    /// Words of the title or an author's name
+    pub q: Option<String>,
+    /// Page number, from 0
+    pub page: Option<u64>,
+    /// Page size (at most 50)
+    pub limit: Option<u64>,
+}
+
+/// GET /api/portal/catalog - Browse the shared catalogue (portal token)
+#[utoipa::path(
+    get,
+    path = "/api/portal/catalog",
+    tag = "portal",
+    params(PortalCatalogQuery),
+    responses(
+        (status = 200, description = "Owned, shared books, as peers see them"),
+        (status = 401, description = "Missing or invalid portal token")
+    )
+)]
+pub async fn catalog(
+    _holder: PortalSession,
+    State(state): State<AppState>,
+    Query(query): Query<PortalCatalogQuery>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    use sea_orm::sea_query::Expr;
+
+    let db = state.db();
+    let mut select = book::Entity::find()
+        .filter(book::Column::Owned.eq(true))
+        .filter(Book::shared_condition(db).await)
+        .order_by_asc(book::Column::Title);
+    if let Some(q) = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        select = select.filter(
+            Condition::any()
+                .add(book::Column::Title.contains(q))
+                .add(Expr::col(book::Column::Id).in_subquery(Book::author_search_subquery(q))),
+        );
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(CATALOG_PAGE)
+        .clamp(1, CATALOG_MAX_PAGE);
+    let paginator = select.paginate(db, limit);
+    let total = paginator
+        .num_items()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let models = paginator
+        .fetch_page(query.page.unwrap_or(0))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut books = Book::populate_authors(db, models).await;
+    for b in &mut books {
+        b.redact_for_peer();
+    }
+    Book::rewrite_local_cover_urls(&mut books, None);
+
+    Ok(Json(json!({ "books": books, "total": total })))
+}
+
+/// GET /api/portal/suggestions - The caller's suggestions (portal token)
+#[utoipa::path(
+    get,
+    path = "/api/portal/suggestions",
+    tag = "portal",
+    responses(
+        (status = 200, description = "Books the caller suggested, latest first, with the owner's decision", body = [BookSuggestion]),
+        (status = 401, description = "Missing or invalid portal token")
+    )
+)]
+pub async fn my_suggestions(
+    PortalSession(holder): PortalSession,
+    State(state): State<AppState>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let mine = suggestions::for_contact(state.db(), &holder.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(json!({ "suggestions": mine })))
+}
+
+/// POST /api/portal/suggestions - Suggest a book to acquire (portal token)
+#[utoipa::path(
+    post,
+    path = "/api/portal/suggestions",
+    tag = "portal",
+    request_body = NewSuggestion,
+    responses(
+        (status = 201, description = "Suggestion sent to the owner"),
+        (status = 400, description = "Title missing, a field too long or a malformed ISBN"),
+        (status = 401, description = "Missing or invalid portal token"),
+        (status = 409, description = "Already suggested, or too many suggestions pending")
+    )
+)]
+pub async fn suggest_book(
+    PortalSession(holder): PortalSession,
+    State(state): State<AppState>,
+    Json(new): Json<NewSuggestion>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, String)> {
+    match suggestions::submit(state.db(), &holder, new).await {
+        Ok(saved) => Ok((StatusCode::CREATED, Json(json!({ "suggestion": saved })))),
+        Err(e) => {
+            let status = match e {
+                SuggestionError::Invalid(_) => StatusCode::BAD_REQUEST,
+                SuggestionError::Duplicate | SuggestionError::TooMany => StatusCode::CONFLICT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use sea_orm::{ActiveModelTrait, EntityTrait, Set};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::api::validation::{Validate, ValidatedJson, ValidationErrors};
 use crate::infrastructure::AppState;
 use crate::models::installation_profile::{ActiveModel, Entity as InstallationProfileEntity};
+use crate::modules::registry::{self, FeatureModule, REGISTRY};
 
-#[derive(Debug, Deserialize)]
+/// Profile types a client may choose (`PUT /api/profile`, setup wizard).
+pub const PROFILE_TYPES: [&str; 4] = ["individual", "professional", "librarian", "kid"];
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateProfileRequest {
     #[serde(default)]
     pub profile_type: Option<String>,
@@ -20,6 +25,17 @@ pub struct UpdateProfileRequest {
     pub api_keys: Option<std::collections::HashMap<String, String>>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/profile",
+    tag = "library",
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "Profile updated"),
+        (status = 400, description = "Unknown profile type"),
+        (status = 404, description = "Setup not done yet")
+    )
+)]
 pub async fn update_profile(
     State(state): State<AppState>,
     Json(req): Json<UpdateProfileRequest>,
@@ -27,10 +43,7 @@ pub async fn update_profile(
     let db = state.db().clone();
     // Validate profile type if provided
     if let Some(ref profile_type) = req.profile_type
-        && profile_type != "individual"
-        && profile_type != "professional"
-        && profile_type != "librarian"
-        && profile_type != "kid"
+        && !PROFILE_TYPES.contains(&profile_type.as_str())
     {
         return (
             StatusCode::BAD_REQUEST,
@@ -141,9 +154,14 @@ pub async fn update_profile(
         if let Some(ref profile_type) = req.profile_type
             && let Ok(Some(config)) = ConfigEntity::find_by_id(1).one(&db).await
         {
+            let before = config.clone();
             let mut active_config: ConfigActiveModel = config.into();
             active_config.show_borrowed_books = Set(Some(profile_type == "individual"));
-            let _ = active_config.update(&db).await;
+            if let Ok(saved) = active_config.update(&db).await {
+                let _ =
+                    crate::services::config_history::record(&db, Some(&before), &saved, "profile")
+                        .await;
+            }
         }
 
         // ADR-025: nudge peers so they pull the fresh avatar over E2EE
@@ -171,3 +189,392 @@ pub async fn update_profile(
             .into_response()
     }
 }
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ModuleStatus {
+    pub id: FeatureModule,
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// Module ids (see `GET /api/profile/modules`) to switch on or off.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateModulesRequest {
+    pub modules: std::collections::HashMap<String, bool>,
+}
+
+fn module_statuses(flags: &[String]) -> Vec<ModuleStatus> {
+    REGISTRY
+        .iter()
+        .map(|info| ModuleStatus {
+            id: info.module,
+            name: info.name.to_string(),
+            description: info.description.to_string(),
+            enabled: info.module.is_enabled_in(flags),
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/profile/modules",
+    tag = "library",
+    responses(
+        (status = 200, description = "Feature modules and whether each is enabled", body = [ModuleStatus])
+    )
+)]
+pub async fn list_modules(State(state): State<AppState>) -> impl IntoResponse {
+    match registry::load_flags(state.db()).await {
+        Ok(flags) => (StatusCode::OK, Json(module_statuses(&flags))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/profile/modules",
+    tag = "library",
+    request_body = UpdateModulesRequest,
+    responses(
+        (status = 200, description = "Modules updated; every module with its new state", body = [ModuleStatus]),
+        (status = 400, description = "Unknown module id"),
+        (status = 404, description = "Setup not done yet")
+    )
+)]
+pub async fn update_modules(
+    State(state): State<AppState>,
+    Json(req): Json<UpdateModulesRequest>,
+) -> impl IntoResponse {
+    let db = state.db();
+    let mut changes = Vec::with_capacity(req.modules.len());
+    for (id, enabled) in &req.modules {
+        match FeatureModule::from_id(id) {
+            Some(module) => changes.push((module, *enabled)),
+            None => {
+                let known: Vec<&str> = REGISTRY.iter().map(|i| i.module.id()).collect();
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": format!("Unknown module '{}'. Must be one of: {}", id, known.join(", "))})),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    let profile = match InstallationProfileEntity::find_by_id(1).one(db).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Installation profile not found"})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response();
+        }
+    };
+
+    let mut flags: Vec<String> = serde_json::from_str(&profile.enabled_modules).unwrap_or_default();
+    for (module, enabled) in changes {
+        module.set_enabled_in(&mut flags, enabled);
+    }
+
+    let mut active: ActiveModel = profile.into();
+    active.enabled_modules = Set(serde_json::to_string(&flags).unwrap_or_default());
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    if let Err(e) = active.update(db).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": format!("Failed to update modules: {}", e)})),
+        )
+            .into_response();
+    }
+
+    (StatusCode::OK, Json(module_statuses(&flags))).into_response()
+}
+
+/// Avatar of this library, as drawn by every client and shown to peers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AvatarConfig {
+    /// Avatar style, e.g. `lorelei` or `bottts-neutral`.
+    pub style: String,
+    /// Seed the avatar is generated from (1 to 64 characters).
+    pub seed: String,
+    /// Background colour as six hex digits, without `#`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct AppearanceResponse {
+    pub theme: String,
+    /// As stored; avatars saved before `AvatarConfig` existed may carry other keys.
+    #[schema(value_type = Option<AvatarConfig>)]
+    pub avatar_config: Option<serde_json::Value>,
+    /// Last change to the profile, so a client can tell whether its copy is stale.
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateAppearanceRequest {
+    #[serde(default)]
+    pub theme: Option<String>,
+    #[serde(default)]
+    pub avatar_config: Option<AvatarConfig>,
+    /// Remove the avatar (ignored when `avatar_config` is given).
+    #[serde(default)]
+    pub clear_avatar: bool,
+}
+
+/// Theme and style identifiers: 1 to 32 lowercase letters, digits, `-` or `_`,
+/// starting with a letter or digit.
+fn is_identifier(value: &str) -> bool {
+    value.len() <= 32
+        && value
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+impl Validate for UpdateAppearanceRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if let Some(theme) = &self.theme
+            && !is_identifier(theme)
+        {
+            errors.add(
+                "theme",
+                "must be 1-32 lowercase letters, digits, '-' or '_'",
+            );
+        }
+        if let Some(avatar) = &self.avatar_config {
+            if !is_identifier(&avatar.style) {
+                errors.add(
+                    "avatar_config.style",
+                    "must be 1-32 lowercase letters, digits, '-' or '_'",
+                );
+            }
+            let seed_len = avatar.seed.chars().count();
+            if !(1..=64).contains(&seed_len) || avatar.seed.chars().any(char::is_control) {
+                errors.add(
+                    "avatar_config.seed",
+                    "must be 1-64 characters, without control characters",
+                );
+            }
+            if let Some(color) = &avatar.background_color
+                && !(color.len() == 6 && color.chars().all(|c| c.is_ascii_hexdigit()))
+            {
+                errors.add("avatar_config.background_color", "must be six hex digits");
+            }
+        }
+        errors.into_result()
+    }
+}
+
+fn appearance_of(profile: crate::models::installation_profile::Model) -> AppearanceResponse {
+    AppearanceResponse {
+        theme: profile.theme.unwrap_or_else(|| "default".to_string()),
+        avatar_config: profile
+            .avatar_config
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok()),
+        updated_at: profile.updated_at,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/profile/appearance",
+    tag = "library",
+    responses(
+        (status = 200, description = "Theme and avatar", body = AppearanceResponse),
+        (status = 404, description = "Setup not done yet")
+    )
+)]
+pub async fn get_appearance(State(state): State<AppState>) -> impl IntoResponse {
+    match InstallationProfileEntity::find_by_id(1)
+        .one(state.db())
+        .await
+    {
+        Ok(Some(profile)) => (StatusCode::OK, Json(appearance_of(profile))).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Installation profile not found"})),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/profile/appearance",
+    tag = "library",
+    request_body = UpdateAppearanceRequest,
+    responses(
+        (status = 200, description = "Appearance updated", body = AppearanceResponse),
+        (status = 404, description = "Setup not done yet"),
+        (status = 422, description = "Invalid theme or avatar")
+    )
+)]
+pub async fn update_appearance(
+    State(state): State<AppState>,
+    ValidatedJson(req): ValidatedJson<UpdateAppearanceRequest>,
+) -> impl IntoResponse {
+    let db = state.db().clone();
+    let profile = match InstallationProfileEntity::find_by_id(1).one(&db).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({"error": "Installation profile not found"})),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": e.to_string()})),
+            )
+                .into_response();
+        }
+    };
+
+    let avatar_json = match (&req.avatar_config, req.clear_avatar) {
+        (Some(avatar), _) => Some(Some(serde_json::to_string(avatar).unwrap_or_default())),
+        (None, true) => Some(None),
+        (None, false) => None,
+    };
+    let avatar_changed = avatar_json
+        .as_ref()
+        .is_some_and(|json| *json != profile.avatar_config);
+
+    let mut active: ActiveModel = profile.into();
+    if let Some(theme) = req.theme {
+        active.theme = Set(Some(theme));
+    }
+    if let Some(json) = avatar_json {
+        active.avatar_config = Set(json);
+    }
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+
+    let updated = match active.update(&db).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Failed to update appearance: {}", e)})),
+            )
+                .into_response();
+        }
+    };
+
+    // ADR-025: peers pull the new avatar over E2EE, like `update_profile`.
+    if avatar_changed {
+        crate::services::profile_notification::schedule_profile_changed_notification(
+            state,
+            vec!["avatar".to_string()],
+        );
+    }
+
+    (StatusCode::OK, Json(appearance_of(updated))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: serde_json::Value) -> UpdateAppearanceRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn appearance_identifiers_and_avatar_are_validated() {
+        assert!(
+            request(json!({
+                "theme": "sepia-dark",
+                "avatar_config": {"style": "lorelei", "seed": "alice", "background_color": "ffd5dc"}
+            }))
+            .validate()
+            .is_ok()
+        );
+
+        let errors = request(json!({
+            "theme": "Sepia Dark",
+            "avatar_config": {"style": "../x", "seed": "", "background_color": "#ffd5dc"}
+        }))
+        .validate()
+        .unwrap_err();
+        let fields: Vec<&str> = errors.fields().iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "theme",
+                "avatar_config.style",
+                "avatar_config.seed",
+                "avatar_config.background_color"
+            ]
+        );
+
+        // The schema is closed, so every client draws the same avatar.
+        assert!(
+            serde_json::from_value::<UpdateAppearanceRequest>(json!({
+                "avatar_config": {"style": "lorelei", "seed": "a", "hair": "long"}
+            }))
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn appearance_round_trips_and_clears_the_avatar() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let state = AppState::new(db);
+
+        let update = |body: serde_json::Value| {
+            let state = state.clone();
+            async move {
+                let response = update_appearance(State(state), ValidatedJson(request(body)))
+                    .await
+                    .into_response();
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+            }
+        };
+
+        let body = update(
+            json!({"theme": "forest", "avatar_config": {"style": "adventurer", "seed": "bob"}}),
+        )
+        .await;
+        assert_eq!(body["theme"], "forest");
+        assert_eq!(body["avatar_config"]["seed"], "bob");
+
+        // Theme only: the avatar is kept.
+        let body = update(json!({"theme": "default"})).await;
+        assert_eq!(body["avatar_config"]["style"], "adventurer");
+
+        let body = update(json!({"clear_avatar": true})).await;
+        assert!(body["avatar_config"].is_null());
+        assert_eq!(body["theme"], "default");
+    }
+}
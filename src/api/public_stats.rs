@@ -16,6 +16,7 @@ use crate::utils::leaderboard_relay::build_local_stats_bundle;
 #[utoipa::path(
     get,
     path = "/api/public-stats-bundle",
+    tag = "gamification",
     responses(
         (status = 200, description = "Public leaderboard bundle for this library")
     )
@@ -0,0 +1,114 @@
+//! The "up next" reading queue (see `services::reading_queue`) and the
+//! dashboard built on it.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::reading_queue::{self, QueueBook, QueueOrder, ReadingQueueError};
+
+fn queue_error(e: ReadingQueueError) -> Response {
+    let status = match e {
+        ReadingQueueError::BookNotFound(_) | ReadingQueueError::NotQueued(_) => {
+            StatusCode::NOT_FOUND
+        }
+        ReadingQueueError::Invalid(_) => StatusCode::BAD_REQUEST,
+        ReadingQueueError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/reading-queue - The books to read next, in order
+#[utoipa::path(
+    get,
+    path = "/api/reading-queue",
+    tag = "books",
+    responses(
+        (status = 200, description = "Queued books, next first", body = [QueuedBook])
+    )
+)]
+pub async fn list_queue(State(state): State<AppState>) -> Response {
+    match reading_queue::list(state.db()).await {
+        Ok(queue) => Json(json!({ "queue": queue })).into_response(),
+        Err(e) => queue_error(e.into()),
+    }
+}
+
+/// POST /api/reading-queue - Queue a book, or move a queued one
+#[utoipa::path(
+    post,
+    path = "/api/reading-queue",
+    tag = "books",
+    request_body = QueueBook,
+    responses(
+        (status = 200, description = "The queue after the change", body = [QueuedBook]),
+        (status = 404, description = "No such book")
+    )
+)]
+pub async fn queue_book(State(state): State<AppState>, Json(input): Json<QueueBook>) -> Response {
+    match reading_queue::add(state.db(), &input.book_id, input.position).await {
+        Ok(queue) => Json(json!({ "queue": queue })).into_response(),
+        Err(e) => queue_error(e),
+    }
+}
+
+/// PUT /api/reading-queue - Reorder the queue
+#[utoipa::path(
+    put,
+    path = "/api/reading-queue",
+    tag = "books",
+    request_body = QueueOrder,
+    responses(
+        (status = 200, description = "The queue in its new order", body = [QueuedBook]),
+        (status = 400, description = "A book listed twice"),
+        (status = 404, description = "A listed book is not queued")
+    )
+)]
+pub async fn reorder_queue(
+    State(state): State<AppState>,
+    Json(input): Json<QueueOrder>,
+) -> Response {
+    match reading_queue::reorder(state.db(), &input.book_ids).await {
+        Ok(queue) => Json(json!({ "queue": queue })).into_response(),
+        Err(e) => queue_error(e),
+    }
+}
+
+/// DELETE /api/reading-queue/:book_id - Take a book off the queue
+#[utoipa::path(
+    delete,
+    path = "/api/reading-queue/{book_id}",
+    tag = "books",
+    params(("book_id" = String, Path, description = "Book ID")),
+    responses(
+        (status = 204, description = "Removed"),
+        (status = 404, description = "The book is not queued")
+    )
+)]
+pub async fn unqueue_book(State(state): State<AppState>, Path(book_id): Path<String>) -> Response {
+    match reading_queue::remove(state.db(), &book_id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => queue_error(e),
+    }
+}
+
+/// GET /api/dashboard - What is being read and what comes next
+#[utoipa::path(
+    get,
+    path = "/api/dashboard",
+    tag = "books",
+    responses(
+        (status = 200, description = "Books being read and the head of the queue", body = ReadingSummary)
+    )
+)]
+pub async fn get_dashboard(State(state): State<AppState>) -> Response {
+    match reading_queue::summary(state.db()).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => queue_error(e.into()),
+    }
+}
@@ -131,6 +131,14 @@ async fn maybe_cleanup(db: &sea_orm::DatabaseConnection) {
 /// POST /api/relay/mailbox — Create a new mailbox.
 /// No authentication required (anyone can create a mailbox).
 /// Returns { uuid, read_token, write_token }.
+#[utoipa::path(
+    post,
+    path = "/api/relay/mailbox",
+    tag = "relay",
+    responses(
+        (status = 201, description = "Mailbox uuid with its read and write tokens")
+    )
+)]
 pub async fn create_mailbox(
     State(state): State<crate::infrastructure::AppState>,
 ) -> impl IntoResponse {
@@ -175,6 +183,20 @@ pub async fn create_mailbox(
 
 /// POST /api/relay/mailbox/:uuid/messages — Deposit an encrypted blob.
 /// Requires: Authorization: Bearer {write_token}
+#[utoipa::path(
+    post,
+    path = "/api/relay/mailbox/{uuid}/messages",
+    tag = "relay",
+    params(("uuid" = String, Path, description = "Mailbox uuid")),
+    request_body(content = Vec<u8>, description = "Sealed message", content_type = "application/octet-stream"),
+    responses(
+        (status = 201, description = "Message stored"),
+        (status = 401, description = "Invalid write token"),
+        (status = 404, description = "Unknown mailbox"),
+        (status = 413, description = "Message too large"),
+        (status = 429, description = "Mailbox full or rate limited")
+    )
+)]
 pub async fn deposit_message(
     State(state): State<crate::infrastructure::AppState>,
     Path(uuid): Path<String>,
@@ -283,6 +305,17 @@ pub async fn deposit_message(
 
 /// GET /api/relay/mailbox/:uuid/messages — Collect pending messages.
 /// Requires: Authorization: Bearer {read_token}
+#[utoipa::path(
+    get,
+    path = "/api/relay/mailbox/{uuid}/messages",
+    tag = "relay",
+    params(("uuid" = String, Path, description = "Mailbox uuid")),
+    responses(
+        (status = 200, description = "Pending messages"),
+        (status = 401, description = "Invalid read token"),
+        (status = 404, description = "Unknown mailbox")
+    )
+)]
 pub async fn collect_messages(
     State(state): State<crate::infrastructure::AppState>,
     Path(uuid): Path<String>,
@@ -368,6 +401,20 @@ pub async fn collect_messages(
 
 /// DELETE /api/relay/mailbox/:uuid/messages/:id — Acknowledge and delete a message.
 /// Requires: Authorization: Bearer {read_token}
+#[utoipa::path(
+    delete,
+    path = "/api/relay/mailbox/{uuid}/messages/{id}",
+    tag = "relay",
+    params(
+        ("uuid" = String, Path, description = "Mailbox uuid"),
+        ("id" = i32, Path, description = "Message id")
+    ),
+    responses(
+        (status = 200, description = "Message deleted"),
+        (status = 401, description = "Invalid read token"),
+        (status = 404, description = "Unknown mailbox or message")
+    )
+)]
 pub async fn ack_message(
     State(state): State<crate::infrastructure::AppState>,
     Path((uuid, message_id)): Path<(String, i32)>,
@@ -461,6 +508,14 @@ pub async fn ack_message(
 ///
 /// Used by Flutter when awaiting a relay response to reduce latency
 /// from ~120s (background polling) to ~10-15s (adaptive fast-polling).
+#[utoipa::path(
+    post,
+    path = "/api/relay/poll_now",
+    tag = "relay",
+    responses(
+        (status = 200, description = "Mailbox polled")
+    )
+)]
 pub async fn poll_now(State(state): State<crate::infrastructure::AppState>) -> impl IntoResponse {
     use crate::services::nudge_events::NudgeSource;
     match crate::services::relay_poller::poll_once(&state, NudgeSource::Manual).await {
@@ -480,6 +535,14 @@ pub async fn poll_now(State(state): State<crate::infrastructure::AppState>) -> i
 ///
 /// Returns the local relay configuration and polls the mailbox to report
 /// how many messages are waiting. Useful for debugging relay issues.
+#[utoipa::path(
+    get,
+    path = "/api/relay/status",
+    tag = "relay",
+    responses(
+        (status = 200, description = "Relay configuration and last poll")
+    )
+)]
 pub async fn relay_status(
     State(state): State<crate::infrastructure::AppState>,
 ) -> impl IntoResponse {
@@ -0,0 +1,48 @@
+//! Catalogue reports (see `services::quality_report`).
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::quality_report::{self, DEFAULT_SAMPLE};
+
+const MAX_SAMPLE: usize = 1000;
+
+/// Query parameters of `GET /api/reports/quality`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct QualityQuery {
+    /// Books listed per group, 100 by default and at most 1000; counts
+    /// always cover every book
+    pub limit: Option<usize>,
+}
+
+/// GET /api/reports/quality - Records to clean up, grouped by problem
+#[utoipa::path(
+    get,
+    path = "/api/reports/quality",
+    tag = "reports",
+    params(QualityQuery),
+    responses(
+        (status = 200, description = "Missing ISBN, author, cover or classification, suspected duplicates, invalid dates and unauthorized subjects, with counts and bulk-fix endpoints", body = QualityReport)
+    )
+)]
+pub async fn get_quality_report(
+    State(state): State<AppState>,
+    Query(query): Query<QualityQuery>,
+) -> Response {
+    let sample = query.limit.unwrap_or(DEFAULT_SAMPLE).min(MAX_SAMPLE);
+    match quality_report::quality_report(state.db(), sample).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,229 @@
+// Request ID and access log middleware.
+//
+// Every request gets an ID: the caller's `X-Request-Id` when it is sane (so a
+// peer can pass its own and both logs line up), otherwise a fresh UUIDv7. The
+// ID is echoed in the `X-Request-Id` response header, added as `request_id` to
+// JSON error bodies, recorded on a tracing span around the handler, and sent
+// on outgoing peer calls made through `get_safe_client` (with the trace context
+// when OpenTelemetry export is on, see `infrastructure::telemetry`).
+//
+// One access log event per request is emitted on the `access` target, with the
+// method, path, status, latency and remote address as structured fields.
+// `LOG_FORMAT=json` turns all logs (access log included) into JSON lines;
+// `RUST_LOG=...,access=off` silences the access log.
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{ConnectInfo, OriginalUri},
+    http::{HeaderValue, Request, header},
+    middleware::Next,
+    response::Response,
+};
+use std::net::SocketAddr;
+use std::time::Instant;
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Error bodies larger than this are passed through without a `request_id`.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The ID of the request being handled, available to handlers as an extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// The ID of the request handled by the current task, if any.
+pub fn current() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Accept a caller-supplied ID only if it is short and plain ASCII, so it can
+/// go into logs and headers as-is.
+fn sanitize(candidate: &str) -> Option<&str> {
+    let ok = !candidate.is_empty()
+        && candidate.len() <= 64
+        && candidate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    ok.then_some(candidate)
+}
+
+pub async fn request_id_middleware(mut req: Request<Body>, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(sanitize)
+        .map(str::to_string)
+        .unwrap_or_else(crate::utils::uuid_gen::new_uuid_v7);
+
+    let method = req.method().clone();
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let remote = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ci| ci.0.to_string())
+        .unwrap_or_default();
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = tracing::info_span!("request", request_id = %id, %method, %path);
+    crate::infrastructure::telemetry::adopt_remote_parent(req.headers(), &span);
+    let started = Instant::now();
+    let response = CURRENT_REQUEST_ID
+        .scope(id.clone(), next.run(req).instrument(span))
+        .await;
+
+    let status = response.status();
+    let latency = started.elapsed();
+    crate::infrastructure::telemetry::record_request(method.as_str(), status.as_u16(), latency);
+    tracing::info!(
+        target: "access",
+        request_id = %id,
+        %method,
+        %path,
+        status = status.as_u16(),
+        latency_ms = latency.as_millis() as u64,
+        %remote,
+        "{} {} {}",
+        method,
+        path,
+        status.as_u16()
+    );
+
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        tag_error_body(response, &id).await
+    } else {
+        response
+    };
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Add `request_id` to a JSON object error body. Other bodies are untouched.
+async fn tag_error_body(response: Response, id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let small = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|len| len <= MAX_ERROR_BODY_BYTES as u64);
+    if !is_json || !small {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, Body::empty()),
+    };
+    let tagged = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut map)) => {
+            map.entry("request_id")
+                .or_insert_with(|| serde_json::Value::String(id.to_string()));
+            serde_json::to_vec(&map).ok()
+        }
+        _ => None,
+    };
+    match tagged {
+        Some(body) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(body))
+        }
+        None => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/fail",
+                get(|| async {
+                    (
+                        StatusCode::NOT_FOUND,
+                        Json(serde_json::json!({"error": "Book not found"})),
+                    )
+                }),
+            )
+            .route("/id", get(|| async { current().unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(request_id_middleware))
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn error_bodies_carry_the_request_id_of_the_header() {
+        let response = app()
+            .oneshot(Request::get("/fail").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(!id.is_empty());
+        let body = body_json(response).await;
+        assert_eq!(body["error"], "Book not found");
+        assert_eq!(body["request_id"], id);
+    }
+
+    #[tokio::test]
+    async fn a_sane_caller_id_is_kept_and_visible_to_the_handler() {
+        let response = app()
+            .oneshot(
+                Request::get("/id")
+                    .header(REQUEST_ID_HEADER, "peer-42.abc_DEF")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "peer-42.abc_DEF");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"peer-42.abc_DEF");
+    }
+
+    #[tokio::test]
+    async fn an_unsafe_caller_id_is_replaced() {
+        let response = app()
+            .oneshot(
+                Request::get("/id")
+                    .header(REQUEST_ID_HEADER, "a b\"<script>")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert_ne!(id, "a b\"<script>");
+        assert!(sanitize(id).is_some());
+    }
+}
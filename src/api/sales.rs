@@ -4,15 +4,15 @@ use axum::{
     http::StatusCode,
     response::IntoResponse,
 };
-use chrono::Local;
 use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use crate::services::sale_service::{self, SaleFilter};
+use crate::utils::money::{self, Money};
 
 /// Request body for creating a sale
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct CreateSaleRequest {
     pub copy_id: String,
     pub contact_id: Option<String>,
@@ -25,7 +25,7 @@ pub struct CreateSaleRequest {
 }
 
 /// Query parameters for listing sales
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
 pub struct ListSalesQuery {
     pub library_id: Option<i32>,
     pub status: Option<String>,
@@ -37,17 +37,31 @@ pub struct ListSalesQuery {
 pub struct SalesStatsResponse {
     pub total_sales: i64,
     pub completed_sales: i64,
-    pub total_revenue: f64,
-    pub average_price: f64,
+    pub total_revenue: Money,
+    pub average_price: Money,
 }
 
 /// POST /api/sales - Record a new sale
+#[utoipa::path(
+    post,
+    path = "/api/sales",
+    tag = "sales",
+    request_body = CreateSaleRequest,
+    responses(
+        (status = 201, description = "Sale recorded and copy marked as sold"),
+        (status = 400, description = "Copy not available, or price not valid in the library's currency"),
+        (status = 404, description = "Copy not found")
+    )
+)]
 pub async fn create_sale(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<CreateSaleRequest>,
 ) -> impl IntoResponse {
     // Default sale_date to now if not provided
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = crate::utils::time_zone::library_zone(&db)
+        .await
+        .unwrap_or(crate::utils::time_zone::LibraryZone::Host)
+        .now_local_string();
 
     let dto = crate::models::sale::SaleDto {
         id: None,
@@ -109,6 +123,15 @@ pub async fn create_sale(
 }
 
 /// GET /api/sales - List sales with optional filters
+#[utoipa::path(
+    get,
+    path = "/api/sales",
+    tag = "sales",
+    params(ListSalesQuery),
+    responses(
+        (status = 200, description = "Sales matching the filters")
+    )
+)]
 pub async fn list_sales(
     State(db): State<DatabaseConnection>,
     Query(params): Query<ListSalesQuery>,
@@ -156,6 +179,17 @@ pub async fn get_sale(
 }
 
 /// DELETE /api/sales/:id - Cancel a sale
+#[utoipa::path(
+    delete,
+    path = "/api/sales/{id}",
+    tag = "sales",
+    params(("id" = i32, Path, description = "Sale id")),
+    responses(
+        (status = 200, description = "Sale cancelled, copy available again"),
+        (status = 400, description = "Sale already cancelled"),
+        (status = 404, description = "Sale not found")
+    )
+)]
 pub async fn cancel_sale(
     State(db): State<DatabaseConnection>,
     Path(id): Path<i32>,
@@ -197,6 +231,14 @@ pub async fn cancel_sale(
 }
 
 /// GET /api/statistics/sales - Get sales statistics
+#[utoipa::path(
+    get,
+    path = "/api/statistics/sales",
+    tag = "sales",
+    responses(
+        (status = 200, description = "Revenue and counts")
+    )
+)]
 pub async fn get_sales_statistics(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     // Fetch all statistics in parallel
     let total_sales = sale_service::count_sales(&db).await.unwrap_or(0);
@@ -207,12 +249,15 @@ pub async fn get_sales_statistics(State(db): State<DatabaseConnection>) -> impl
     let average_price = sale_service::calculate_average_price(&db)
         .await
         .unwrap_or(0.0);
+    let currency = money::library_currency(&db)
+        .await
+        .unwrap_or_else(|_| money::or_default(None));
 
     let stats = SalesStatsResponse {
         total_sales,
         completed_sales,
-        total_revenue,
-        average_price,
+        total_revenue: Money::new(total_revenue, currency),
+        average_price: Money::new(average_price, currency),
     };
 
     (StatusCode::OK, Json(stats)).into_response()
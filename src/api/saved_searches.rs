@@ -0,0 +1,144 @@
+//! Saved searches and the matches they found (see
+//! `services::saved_searches`). A search belongs to the user of the token
+//! that saved it; the local app, calling without a token, has its own.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::auth::Claims;
+use crate::infrastructure::AppState;
+use crate::services::saved_searches::{self, NewSavedSearch, SavedSearchError};
+
+fn owner(claims: Option<Claims>) -> String {
+    claims.map(|c| c.sub).unwrap_or_default()
+}
+
+fn saved_search_error(e: SavedSearchError) -> Response {
+    let status = match e {
+        SavedSearchError::NotFound => StatusCode::NOT_FOUND,
+        SavedSearchError::Invalid(_) => StatusCode::BAD_REQUEST,
+        SavedSearchError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/saved-searches - The searches saved by the caller
+#[utoipa::path(
+    get,
+    path = "/api/saved-searches",
+    tag = "books",
+    responses(
+        (status = 200, description = "Saved searches, by name", body = [SavedSearch])
+    )
+)]
+pub async fn list_saved_searches(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+) -> Response {
+    match saved_searches::list(state.db(), &owner(claims)).await {
+        Ok(list) => Json(json!({ "saved_searches": list })).into_response(),
+        Err(e) => saved_search_error(e.into()),
+    }
+}
+
+/// POST /api/saved-searches - Save a search
+///
+/// What it already matches is recorded at once; only later matches are
+/// notified.
+#[utoipa::path(
+    post,
+    path = "/api/saved-searches",
+    tag = "books",
+    request_body = NewSavedSearch,
+    responses(
+        (status = 201, description = "The saved search", body = SavedSearch),
+        (status = 400, description = "Nothing searchable in the query, or an unknown source")
+    )
+)]
+pub async fn create_saved_search(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Json(input): Json<NewSavedSearch>,
+) -> Response {
+    match saved_searches::create(state.db(), &owner(claims), input).await {
+        Ok(search) => (StatusCode::CREATED, Json(search)).into_response(),
+        Err(e) => saved_search_error(e),
+    }
+}
+
+/// DELETE /api/saved-searches/:id - Delete a saved search and its matches
+#[utoipa::path(
+    delete,
+    path = "/api/saved-searches/{id}",
+    tag = "books",
+    params(("id" = i32, Path, description = "Saved search id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such saved search")
+    )
+)]
+pub async fn delete_saved_search(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(id): Path<i32>,
+) -> Response {
+    match saved_searches::delete(state.db(), &owner(claims), id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => saved_search_error(SavedSearchError::NotFound),
+        Err(e) => saved_search_error(e.into()),
+    }
+}
+
+/// GET /api/saved-searches/:id/matches - What a saved search found
+#[utoipa::path(
+    get,
+    path = "/api/saved-searches/{id}/matches",
+    tag = "books",
+    params(("id" = i32, Path, description = "Saved search id")),
+    responses(
+        (status = 200, description = "Matches, newest first", body = [SavedSearchMatch]),
+        (status = 404, description = "No such saved search")
+    )
+)]
+pub async fn list_saved_search_matches(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(id): Path<i32>,
+) -> Response {
+    match saved_searches::matches(state.db(), &owner(claims), id).await {
+        Ok(list) => Json(json!({ "matches": list })).into_response(),
+        Err(e) => saved_search_error(e),
+    }
+}
+
+/// POST /api/saved-searches/:id/run - Run a saved search now
+#[utoipa::path(
+    post,
+    path = "/api/saved-searches/{id}/run",
+    tag = "books",
+    params(("id" = i32, Path, description = "Saved search id")),
+    responses(
+        (status = 200, description = "The matches not seen before", body = [SavedSearchMatch]),
+        (status = 404, description = "No such saved search")
+    )
+)]
+pub async fn run_saved_search(
+    State(state): State<AppState>,
+    claims: Option<Claims>,
+    Path(id): Path<i32>,
+) -> Response {
+    let search = match saved_searches::get(state.db(), &owner(claims), id).await {
+        Ok(Some(search)) => search,
+        Ok(None) => return saved_search_error(SavedSearchError::NotFound),
+        Err(e) => return saved_search_error(e.into()),
+    };
+    match saved_searches::run(state.db(), &search).await {
+        Ok(fresh) => Json(json!({ "matches": fresh })).into_response(),
+        Err(e) => saved_search_error(e.into()),
+    }
+}
@@ -1,62 +1,166 @@
 use axum::{
     Json,
-    extract::{Multipart, State},
-    http::StatusCode,
+    extract::{Multipart, Query, State},
+    http::{StatusCode, header},
     response::IntoResponse,
 };
 use sea_orm::DatabaseConnection;
 use serde_json::json;
 use std::fs;
 
+use crate::modules::scanner::candidates;
+use crate::services::images::{self, ImageLimits, ImageOps, OutputFormat};
+use crate::services::scan_training::{self, ScanCorrection};
+
+#[utoipa::path(
+    post,
+    path = "/api/scan/image",
+    tag = "books",
+    params(ImageOps),
+    request_body(content = String, description = "Multipart upload of a photo", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The OCR text, with the ISBNs, titles and authors found in it, best first", body = ScanResult),
+        (status = 400, description = "Missing image, or a bad rotation or crop"),
+        (status = 413, description = "Image over `max_image_upload_mb` (10 MB by default)"),
+        (status = 415, description = "Not a JPEG, PNG or WebP image")
+    )
+)]
 pub async fn scan_image(
     State(_db): State<DatabaseConnection>,
+    Query(mut ops): Query<ImageOps>,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
-    while let Some(field) = multipart.next_field().await.unwrap_or(None) {
-        if field.name() == Some("file") {
-            let data = match field.bytes().await {
-                Ok(bytes) => bytes,
-                Err(e) => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(json!({ "error": e.to_string() })),
-                    )
-                        .into_response();
-                }
-            };
-
-            // Save to temp file
-            let temp_path = format!("/tmp/scan_{}.jpg", uuid::Uuid::new_v4());
-            if let Err(e) = fs::write(&temp_path, &data) {
+    let data = match crate::api::images::read_upload(&mut multipart).await {
+        Ok(data) => data,
+        Err(resp) => return resp,
+    };
+
+    // Upright, cropped to the ISBN area when asked, and within the
+    // configured size; PNG so OCR does not read JPEG artifacts.
+    ops.format = Some(OutputFormat::Png);
+    let limits = ImageLimits::runtime();
+    let processed =
+        match tokio::task::spawn_blocking(move || images::process(&data, &ops, &limits)).await {
+            Ok(Ok(processed)) => processed,
+            Ok(Err(e)) => {
+                return (
+                    crate::api::images::image_status(&e),
+                    Json(json!({ "error": e.to_string() })),
+                )
+                    .into_response();
+            }
+            Err(e) => {
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": format!("Failed to save image: {}", e) })),
+                    Json(json!({ "error": e.to_string() })),
                 )
                     .into_response();
             }
+        };
 
-            // Call scanner module
-            let result = crate::modules::scanner::scan_image(&temp_path);
-
-            // Cleanup
-            let _ = fs::remove_file(&temp_path);
-
-            match result {
-                Ok(text) => return (StatusCode::OK, Json(json!({ "text": text }))).into_response(),
-                Err(e) => {
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({ "error": e })),
-                    )
-                        .into_response();
-                }
-            }
-        }
+    // Save to temp file
+    let temp_path = format!("/tmp/scan_{}.png", uuid::Uuid::new_v4());
+    if let Err(e) = fs::write(&temp_path, &processed.bytes) {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("Failed to save image: {}", e) })),
+        )
+            .into_response();
+    }
+
+    // Call scanner module
+    let result = crate::modules::scanner::scan_image(&temp_path);
+
+    // Cleanup
+    let _ = fs::remove_file(&temp_path);
+
+    match result {
+        Ok(text) => (StatusCode::OK, Json(candidates::extract(&text))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e })),
+        )
+            .into_response(),
     }
+}
 
+fn training_data_off() -> axum::response::Response {
     (
-        StatusCode::BAD_REQUEST,
-        Json(json!({ "error": "No file uploaded" })),
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "Scan training data is off: set scan_training_data in the configuration"
+        })),
     )
         .into_response()
 }
+
+/// Record the title a user kept after a scan proposed another one.
+#[utoipa::path(
+    post,
+    path = "/api/scan/corrections",
+    tag = "books",
+    request_body = ScanCorrection,
+    responses(
+        (status = 201, description = "Correction kept, anonymized"),
+        (status = 400, description = "Empty title"),
+        (status = 403, description = "`scan_training_data` is off")
+    )
+)]
+pub async fn record_scan_correction(
+    State(db): State<DatabaseConnection>,
+    Json(correction): Json<ScanCorrection>,
+) -> impl IntoResponse {
+    if !scan_training::enabled() {
+        return training_data_off();
+    }
+    if correction.title.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "title is required" })),
+        )
+            .into_response();
+    }
+    match scan_training::record(&db, correction).await {
+        Ok(id) => (StatusCode::CREATED, Json(json!({ "id": id }))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Download the scan corrections kept, as JSON lines: `ocr_text`,
+/// `predicted_title`, `title`, `author`.
+#[utoipa::path(
+    get,
+    path = "/api/scan/corrections/export",
+    tag = "books",
+    responses(
+        (status = 200, description = "One anonymized correction per line, as an attachment", content_type = "application/x-ndjson"),
+        (status = 403, description = "`scan_training_data` is off")
+    )
+)]
+pub async fn export_scan_corrections(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    if !scan_training::enabled() {
+        return training_data_off();
+    }
+    match scan_training::export_jsonl(&db).await {
+        Ok(jsonl) => (
+            [
+                (header::CONTENT_TYPE, "application/x-ndjson"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"scan_corrections.jsonl\"",
+                ),
+            ],
+            jsonl,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -1,14 +1,19 @@
+use crate::infrastructure::config::Config;
 use crate::models::book;
+use crate::services::book_service;
+use crate::services::nl_query::{self, ParsedQuery};
+use crate::services::search_suggest::{self, Suggestion};
+use crate::services::speech::{self, SpeechBackend, SpeechError};
 use axum::{
     Json,
-    extract::{Query, State},
+    extract::{Multipart, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, utoipa::IntoParams)]
 pub struct SearchQuery {
     pub title: Option<String>,
     pub author: Option<String>,
@@ -20,6 +25,8 @@ pub struct SearchQuery {
     pub subjects: Option<String>, // Plural to match existing usage or "subject" singular? OL uses subject. Book model uses subjects. Let's use "subject" for query param for consistency with others.
     pub sources: Option<String>,  // "local,peers,public"
     pub autocomplete: Option<bool>,
+    /// Keep one edition per work (see `services::works`)
+    pub collapse_editions: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -28,10 +35,41 @@ pub struct SearchResponse {
     pub total: usize,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/books/search",
+    tag = "books",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matches from the selected sources")
+    )
+)]
 pub async fn search_books(
     State(db): State<DatabaseConnection>,
     Query(params): Query<SearchQuery>,
 ) -> impl IntoResponse {
+    match unified_search(&db, &params).await {
+        Ok(books) => (
+            StatusCode::OK,
+            Json(SearchResponse {
+                total: books.len(),
+                books,
+            }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e })),
+        )
+            .into_response(),
+    }
+}
+
+/// Search the sources `params` selects (local, peers, public).
+async fn unified_search(
+    db: &DatabaseConnection,
+    params: &SearchQuery,
+) -> Result<Vec<book::Book>, String> {
     let sources = params
         .sources
         .clone()
@@ -84,7 +122,7 @@ pub async fn search_books(
         if let Ok(local_books) = book::Entity::find()
             .filter(condition)
             .order_by_asc(book::Column::Title)
-            .all(&db)
+            .all(db)
             .await
         {
             let mut dtos: Vec<book::Book> = local_books.into_iter().map(|b| b.into()).collect();
@@ -94,7 +132,7 @@ pub async fn search_books(
 
     // 2. Public Search (Open Library)
     if source_list.contains(&"public") {
-        let external_models = crate::api::integrations::search_external(&params, &db).await;
+        let external_models = crate::api::integrations::search_external(params, db).await;
         let mut dtos: Vec<book::Book> = external_models
             .into_iter()
             .map(|m| {
@@ -111,16 +149,229 @@ pub async fn search_books(
         // We need to implement broadcast_search in api::peer
         // For now, let's assume it exists or implement it inline if simple
         // But better to call a helper.
-        let peer_books = crate::api::peer::broadcast_search(&db, &params).await;
+        let peer_books = crate::api::peer::broadcast_search(db, params).await;
         all_books.extend(peer_books);
     }
 
-    (
-        StatusCode::OK,
-        Json(SearchResponse {
-            total: all_books.len(),
-            books: all_books,
-        }),
+    if params.collapse_editions.unwrap_or(false) {
+        all_books = crate::services::works::collapse_editions(db, all_books)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(all_books)
+}
+
+/// Query parameters of `POST /api/search/voice`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct VoiceSearchQuery {
+    /// Sources to search, as in `/api/books/search` (default `local`)
+    pub sources: Option<String>,
+    /// Spoken language (ISO 639-1); guessed when absent
+    pub language: Option<String>,
+    /// Keep one edition per work
+    pub collapse_editions: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct VoiceSearchResponse {
+    /// What was heard, searched as `q`
+    pub transcript: String,
+    pub books: Vec<book::Book>,
+    pub total: usize,
+}
+
+fn speech_error(e: SpeechError) -> Response {
+    let status = match e {
+        SpeechError::NotConfigured(_) => StatusCode::SERVICE_UNAVAILABLE,
+        SpeechError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        SpeechError::Empty => StatusCode::BAD_REQUEST,
+        SpeechError::Backend(_) => StatusCode::BAD_GATEWAY,
+        SpeechError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+}
+
+/// POST /api/search/voice - Search by a spoken title, author or publisher
+///
+/// Transcribes a short clip with the configured speech backend (see
+/// `services::speech`) and runs the transcript through the same search as
+/// `/api/books/search`.
+#[utoipa::path(
+    post,
+    path = "/api/search/voice",
+    tag = "books",
+    params(VoiceSearchQuery),
+    request_body(content = String, description = "Multipart upload of a short audio clip (WAV, MP3, OGG, FLAC, M4A or WebM)", content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "The transcript and the books it matches"),
+        (status = 400, description = "No audio in request"),
+        (status = 413, description = "Clip over 5 MB"),
+        (status = 502, description = "The speech backend failed or heard nothing"),
+        (status = 503, description = "No speech backend configured")
+    )
+)]
+pub async fn voice_search(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<VoiceSearchQuery>,
+    mut multipart: Multipart,
+) -> Response {
+    let backend = match SpeechBackend::from_config(Config::runtime()) {
+        Ok(backend) => backend,
+        Err(e) => return speech_error(e),
+    };
+
+    let field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.file_name().is_some() => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return speech_error(SpeechError::Empty),
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "error": e.body_text() })),
+                )
+                    .into_response();
+            }
+        }
+    };
+    let extension = speech::clip_extension(field.file_name(), field.content_type());
+    let audio = match field.bytes().await {
+        Ok(bytes) => bytes.to_vec(),
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.body_text() })),
+            )
+                .into_response();
+        }
+    };
+
+    let transcript =
+        match speech::transcribe(&backend, audio, extension, query.language.as_deref()).await {
+            Ok(text) => text,
+            Err(e) => return speech_error(e),
+        };
+
+    let params = SearchQuery {
+        title: None,
+        author: None,
+        publisher: None,
+        year_min: None,
+        year_max: None,
+        tags: None,
+        q: Some(transcript.clone()),
+        subjects: None,
+        sources: query.sources,
+        autocomplete: None,
+        collapse_editions: query.collapse_editions,
+    };
+    match unified_search(&db, &params).await {
+        Ok(books) => Json(VoiceSearchResponse {
+            transcript,
+            total: books.len(),
+            books,
+        })
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct SuggestQuery {
+    /// What the user typed so far
+    pub q: String,
+    /// Suggestions returned, 10 by default, 50 at most
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SuggestResponse {
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// GET /api/search/suggest - Titles, authors and tags starting with `q`
+///
+/// Served from an in-memory index (see `services::search_suggest`), for
+/// autocomplete as the user types.
+#[utoipa::path(
+    get,
+    path = "/api/search/suggest",
+    tag = "books",
+    params(SuggestQuery),
+    responses(
+        (status = 200, description = "Best matches first", body = SuggestResponse)
     )
-        .into_response()
+)]
+pub async fn suggest(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<SuggestQuery>,
+) -> Response {
+    let limit = query.limit.unwrap_or(search_suggest::DEFAULT_LIMIT);
+    match search_suggest::suggest(&db, &query.q, limit).await {
+        Ok(suggestions) => Json(SuggestResponse { suggestions }).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct NlSearchQuery {
+    /// A query in words, e.g. "unread sci-fi from the 80s rated above 4"
+    pub q: String,
+}
+
+#[derive(Serialize)]
+pub struct NlSearchResponse {
+    /// The filters read from `q`
+    pub filters: ParsedQuery,
+    pub books: Vec<book::Book>,
+    pub total: usize,
+}
+
+/// GET /api/search/nl - Search the library with a query in words
+///
+/// Reading status, genre, publication years, rating and author are read out of
+/// `q` (see `services::nl_query`) and applied as the filters of the book list.
+#[utoipa::path(
+    get,
+    path = "/api/search/nl",
+    tag = "books",
+    params(NlSearchQuery),
+    responses(
+        (status = 200, description = "The filters read from the query and the books they keep"),
+        (status = 400, description = "Nothing searchable in the query")
+    )
+)]
+pub async fn nl_search(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<NlSearchQuery>,
+) -> Response {
+    let filters = nl_query::parse(&query.q);
+    if filters.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Nothing to search for in the query" })),
+        )
+            .into_response();
+    }
+    match book_service::list_books(&db, filters.clone().into_filter()).await {
+        Ok(books) => Json(NlSearchResponse {
+            filters,
+            total: books.len(),
+            books,
+        })
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("{:?}", e) })),
+        )
+            .into_response(),
+    }
 }
@@ -1,10 +1,13 @@
+use crate::infrastructure::seed::{self, SeedLocale, SeedProfile, SeedReport};
+use crate::infrastructure::setup_wizard::{self, WizardProgress, WizardStatus, WizardStep};
 use crate::models::{installation_profile, library_config};
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::Mutex;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct SetupRequest {
     pub profile_type: String, // "individual" or "professional"
     pub library_name: String,
@@ -15,9 +18,13 @@ pub struct SetupRequest {
     pub share_location: Option<bool>,
     pub admin_username: Option<String>,
     pub admin_password: Option<String>,
+    /// Seeds this demo catalogue once setup is saved.
+    pub seed_profile: Option<SeedProfile>,
+    /// Defaults to the system locale.
+    pub seed_locale: Option<SeedLocale>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct SetupResponse {
     pub success: bool,
     pub message: String,
@@ -25,6 +32,15 @@ pub struct SetupResponse {
     pub library_id: Option<i32>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/setup",
+    tag = "setup",
+    request_body = SetupRequest,
+    responses(
+        (status = 200, description = "Profile, library and admin user saved", body = SetupResponse)
+    )
+)]
 pub async fn setup(
     State(db): State<DatabaseConnection>,
     Json(req): Json<SetupRequest>,
@@ -79,6 +95,8 @@ pub async fn setup(
         longitude: Set(req.longitude),
         share_location: Set(req.share_location.or(Some(false))),
         show_borrowed_books: Set(Some(req.profile_type == "individual")),
+        currency: sea_orm::ActiveValue::NotSet,
+        timezone: sea_orm::ActiveValue::NotSet,
         updated_at: Set(now.to_rfc3339()),
         created_at: Set(now.to_rfc3339()),
     };
@@ -236,6 +254,37 @@ pub async fn setup(
         }
     }
 
+    if let Some(profile) = req.seed_profile {
+        let locale = req.seed_locale.unwrap_or_else(SeedLocale::detect);
+        match seed::seed_profile(&db, profile, locale).await {
+            Ok(report) => tracing::info!(
+                "Seeded {} demo books ({} {})",
+                report.books_created,
+                profile.as_str(),
+                locale.as_str()
+            ),
+            Err(e) => tracing::error!("Failed to seed demo data: {}", e),
+        }
+    }
+
+    // The one-shot setup covers every required wizard step.
+    let finished = async {
+        let mut progress = setup_wizard::load(&db).await?;
+        progress.done.extend([
+            WizardStep::Profile,
+            WizardStep::Admin,
+            WizardStep::Library,
+            WizardStep::Location,
+        ]);
+        progress
+            .completed_at
+            .get_or_insert_with(|| now.to_rfc3339());
+        setup_wizard::save(&db, &progress).await
+    };
+    if let Err(e) = finished.await {
+        tracing::error!("Failed to record setup wizard progress: {}", e);
+    }
+
     (
         StatusCode::OK,
         Json(SetupResponse {
@@ -248,6 +297,507 @@ pub async fn setup(
         .into_response()
 }
 
+/// One step of the setup wizard, tagged by `step`.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum SetupStepRequest {
+    /// One of [`super::profile::PROFILE_TYPES`].
+    Profile {
+        profile_type: String,
+        theme: Option<String>,
+    },
+    /// Creates the admin account, or updates it when the step is saved again.
+    Admin {
+        username: String,
+        password: String,
+    },
+    Library {
+        name: String,
+        description: Option<String>,
+    },
+    /// Both coordinates or neither.
+    Location {
+        latitude: Option<f64>,
+        longitude: Option<f64>,
+        #[serde(default)]
+        share_location: bool,
+    },
+    Modules {
+        enabled_modules: Vec<String>,
+    },
+    /// Seeds a few demo books when `seed` is set and the catalogue is empty.
+    /// `profile` defaults to the one matching the installation profile type,
+    /// `locale` to the system locale.
+    DemoData {
+        seed: bool,
+        #[serde(default)]
+        profile: Option<SeedProfile>,
+        #[serde(default)]
+        locale: Option<SeedLocale>,
+    },
+}
+
+impl SetupStepRequest {
+    fn step(&self) -> WizardStep {
+        match self {
+            SetupStepRequest::Profile { .. } => WizardStep::Profile,
+            SetupStepRequest::Admin { .. } => WizardStep::Admin,
+            SetupStepRequest::Library { .. } => WizardStep::Library,
+            SetupStepRequest::Location { .. } => WizardStep::Location,
+            SetupStepRequest::Modules { .. } => WizardStep::Modules,
+            SetupStepRequest::DemoData { .. } => WizardStep::DemoData,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SetupStepState {
+    pub step: WizardStep,
+    pub required: bool,
+    pub done: bool,
+}
+
+/// What is saved so far, to prefill the wizard when resuming.
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct SetupValues {
+    pub profile_type: Option<String>,
+    pub theme: Option<String>,
+    pub admin_username: Option<String>,
+    pub library_name: Option<String>,
+    pub library_description: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub share_location: bool,
+    pub enabled_modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SetupStateResponse {
+    pub status: WizardStatus,
+    /// The first step not saved yet; `null` once completed.
+    pub next_step: Option<WizardStep>,
+    pub steps: Vec<SetupStepState>,
+    pub completed_at: Option<String>,
+    pub values: SetupValues,
+}
+
+fn wizard_error(status: StatusCode, message: impl Into<String>) -> axum::response::Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn db_error(e: sea_orm::DbErr) -> axum::response::Response {
+    wizard_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// `(id, username)` of the first admin account, if any.
+async fn first_admin(db: &DatabaseConnection) -> Result<Option<(i32, String)>, sea_orm::DbErr> {
+    use sea_orm::ConnectionTrait;
+
+    // Raw query, like `setup`, to stay clear of the optional totp columns.
+    let row = db
+        .query_one(sea_orm::Statement::from_string(
+            db.get_database_backend(),
+            "SELECT id, username FROM users WHERE role = 'admin' ORDER BY id LIMIT 1".to_owned(),
+        ))
+        .await?;
+    row.map(|r| Ok((r.try_get("", "id")?, r.try_get("", "username")?)))
+        .transpose()
+}
+
+async fn wizard_state(
+    db: &DatabaseConnection,
+    progress: &WizardProgress,
+) -> Result<SetupStateResponse, sea_orm::DbErr> {
+    let profile = installation_profile::Entity::find_by_id(1).one(db).await?;
+    let config = library_config::Entity::find_by_id(1).one(db).await?;
+    let mut values = SetupValues {
+        admin_username: first_admin(db).await?.map(|(_, username)| username),
+        ..Default::default()
+    };
+    if let Some(p) = profile {
+        values.profile_type = Some(p.profile_type);
+        values.theme = p.theme;
+        values.enabled_modules = serde_json::from_str(&p.enabled_modules).unwrap_or_default();
+    }
+    if let Some(c) = config {
+        values.library_name = Some(c.name);
+        values.library_description = c.description;
+        values.latitude = c.latitude;
+        values.longitude = c.longitude;
+        values.share_location = c.share_location.unwrap_or(false);
+    }
+
+    Ok(SetupStateResponse {
+        status: progress.status(),
+        next_step: progress.next_step(),
+        steps: WizardStep::ALL
+            .into_iter()
+            .map(|step| SetupStepState {
+                step,
+                required: step.is_required(),
+                done: progress.done.contains(&step),
+            })
+            .collect(),
+        completed_at: progress.completed_at.clone(),
+        values,
+    })
+}
+
+async fn state_response(
+    db: &DatabaseConnection,
+    progress: &WizardProgress,
+) -> axum::response::Response {
+    match wizard_state(db, progress).await {
+        Ok(state) => (StatusCode::OK, Json(state)).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/setup/state",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Wizard progress and the values saved so far", body = SetupStateResponse)
+    )
+)]
+pub async fn get_setup_state(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    match setup_wizard::load(&db).await {
+        Ok(progress) => state_response(&db, &progress).await,
+        Err(e) => db_error(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/setup/steps",
+    tag = "setup",
+    request_body = SetupStepRequest,
+    responses(
+        (status = 200, description = "Step saved; the updated wizard state", body = SetupStateResponse),
+        (status = 400, description = "Invalid step values"),
+        (status = 409, description = "Setup already completed, an earlier required step is missing, or the username is taken")
+    )
+)]
+pub async fn save_setup_step(
+    State(db): State<DatabaseConnection>,
+    Json(req): Json<SetupStepRequest>,
+) -> impl IntoResponse {
+    let mut progress = match setup_wizard::load(&db).await {
+        Ok(progress) => progress,
+        Err(e) => return db_error(e),
+    };
+    if progress.completed_at.is_some() {
+        return wizard_error(StatusCode::CONFLICT, "Setup is already completed");
+    }
+    let step = req.step();
+    if let Some(blocker) = progress.blocker_of(step) {
+        return wizard_error(
+            StatusCode::CONFLICT,
+            format!("Save the '{}' step first", blocker.as_str()),
+        );
+    }
+
+    if let Err(response) = apply_step(&db, req).await {
+        return response;
+    }
+
+    progress.done.insert(step);
+    if let Err(e) = setup_wizard::save(&db, &progress).await {
+        return db_error(e);
+    }
+    tracing::info!("Setup wizard: '{}' step saved", step.as_str());
+    state_response(&db, &progress).await
+}
+
+async fn apply_step(
+    db: &DatabaseConnection,
+    req: SetupStepRequest,
+) -> Result<(), axum::response::Response> {
+    use crate::models::{library, user};
+    use sea_orm::ConnectionTrait;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    match req {
+        SetupStepRequest::Profile {
+            profile_type,
+            theme,
+        } => {
+            if !super::profile::PROFILE_TYPES.contains(&profile_type.as_str()) {
+                return Err(wizard_error(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Invalid profile type. Must be one of: {}",
+                        super::profile::PROFILE_TYPES.join(", ")
+                    ),
+                ));
+            }
+            installation_profile::ActiveModel {
+                id: Set(1),
+                profile_type: Set(profile_type),
+                theme: Set(theme.or(Some("default".to_string()))),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .update(db)
+            .await
+            .map_err(db_error)?;
+        }
+        SetupStepRequest::Admin { username, password } => {
+            let username = username.trim().to_string();
+            if username.is_empty() || password.is_empty() {
+                return Err(wizard_error(
+                    StatusCode::BAD_REQUEST,
+                    "Username and password are required",
+                ));
+            }
+            let password_hash = crate::auth::hash_password(&password)
+                .map_err(|e| wizard_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            let admin = first_admin(db).await.map_err(db_error)?;
+
+            let taken = db
+                .query_one(sea_orm::Statement::from_sql_and_values(
+                    db.get_database_backend(),
+                    "SELECT id FROM users WHERE username = ? AND id != ?",
+                    [
+                        username.clone().into(),
+                        admin.as_ref().map_or(-1, |(id, _)| *id).into(),
+                    ],
+                ))
+                .await
+                .map_err(db_error)?
+                .is_some();
+            if taken {
+                return Err(wizard_error(StatusCode::CONFLICT, "Username already taken"));
+            }
+
+            // Saving the step again (going back in the wizard) edits the same
+            // account instead of adding a second admin.
+            match admin {
+                Some((id, _)) => {
+                    db.execute(sea_orm::Statement::from_sql_and_values(
+                        db.get_database_backend(),
+                        "UPDATE users SET username = ?, password_hash = ?, updated_at = ? WHERE id = ?",
+                        [
+                            username.into(),
+                            password_hash.into(),
+                            now.into(),
+                            id.into(),
+                        ],
+                    ))
+                    .await
+                    .map_err(db_error)?;
+                }
+                None => {
+                    user::ActiveModel {
+                        username: Set(username),
+                        password_hash: Set(password_hash),
+                        role: Set("admin".to_string()),
+                        created_at: Set(now.clone()),
+                        updated_at: Set(now),
+                        ..Default::default()
+                    }
+                    .insert(db)
+                    .await
+                    .map_err(db_error)?;
+                }
+            }
+        }
+        SetupStepRequest::Library { name, description } => {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(wizard_error(
+                    StatusCode::BAD_REQUEST,
+                    "Library name is required",
+                ));
+            }
+            let individual = installation_profile::Entity::find_by_id(1)
+                .one(db)
+                .await
+                .map_err(db_error)?
+                .is_none_or(|p| p.profile_type == "individual");
+            library_config::ActiveModel {
+                id: Set(1),
+                name: Set(name.clone()),
+                description: Set(description.clone()),
+                show_borrowed_books: Set(Some(individual)),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .update(db)
+            .await
+            .map_err(db_error)?;
+
+            // The default library copies belong to (required, like in `setup`).
+            let (owner_id, _) = first_admin(db)
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| wizard_error(StatusCode::CONFLICT, "Save the 'admin' step first"))?;
+            library::Entity::insert(library::ActiveModel {
+                id: Set(1),
+                name: Set(name),
+                description: Set(description),
+                owner_id: Set(owner_id),
+                created_at: Set(now.clone()),
+                updated_at: Set(now),
+            })
+            .on_conflict(
+                sea_orm::sea_query::OnConflict::column(library::Column::Id)
+                    .update_columns([
+                        library::Column::Name,
+                        library::Column::Description,
+                        library::Column::OwnerId,
+                        library::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await
+            .map_err(db_error)?;
+        }
+        SetupStepRequest::Location {
+            latitude,
+            longitude,
+            share_location,
+        } => {
+            let valid = match (latitude, longitude) {
+                (Some(lat), Some(lon)) => {
+                    (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+                }
+                (None, None) => true,
+                _ => false,
+            };
+            if !valid {
+                return Err(wizard_error(
+                    StatusCode::BAD_REQUEST,
+                    "Give both latitude (-90..90) and longitude (-180..180), or neither",
+                ));
+            }
+            library_config::ActiveModel {
+                id: Set(1),
+                latitude: Set(latitude),
+                longitude: Set(longitude),
+                share_location: Set(Some(share_location && latitude.is_some())),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .update(db)
+            .await
+            .map_err(db_error)?;
+        }
+        SetupStepRequest::Modules { enabled_modules } => {
+            installation_profile::ActiveModel {
+                id: Set(1),
+                enabled_modules: Set(serde_json::to_string(&enabled_modules).unwrap_or_default()),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+            .update(db)
+            .await
+            .map_err(db_error)?;
+        }
+        SetupStepRequest::DemoData {
+            seed,
+            profile,
+            locale,
+        } => {
+            use sea_orm::PaginatorTrait;
+
+            // Only into an empty catalogue, so saving the step twice (or after
+            // importing books) never duplicates the demo books.
+            let empty = crate::models::book::Entity::find()
+                .count(db)
+                .await
+                .map_err(db_error)?
+                == 0;
+            if seed && empty {
+                let profile = match profile {
+                    Some(profile) => profile,
+                    None => default_seed_profile(db).await.map_err(db_error)?,
+                };
+                let locale = locale.unwrap_or_else(SeedLocale::detect);
+                seed::seed_profile(db, profile, locale)
+                    .await
+                    .map_err(db_error)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The demo catalogue matching the saved installation profile type.
+async fn default_seed_profile(db: &DatabaseConnection) -> Result<SeedProfile, sea_orm::DbErr> {
+    Ok(installation_profile::Entity::find_by_id(1)
+        .one(db)
+        .await?
+        .map(|p| SeedProfile::for_profile_type(&p.profile_type))
+        .unwrap_or_default())
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct SeedDemoRequest {
+    /// Defaults to the one matching the installation profile type.
+    pub profile: Option<SeedProfile>,
+    /// Defaults to the system locale.
+    pub locale: Option<SeedLocale>,
+}
+
+/// Seed (or top up) a demo catalogue. Idempotent: sample books already in the
+/// catalogue are skipped, so it can be called before every demo.
+#[utoipa::path(
+    post,
+    path = "/api/setup/demo-data",
+    tag = "setup",
+    request_body = SeedDemoRequest,
+    responses(
+        (status = 200, description = "What was created and skipped", body = SeedReport)
+    )
+)]
+pub async fn seed_demo_data(
+    State(db): State<DatabaseConnection>,
+    Json(req): Json<SeedDemoRequest>,
+) -> Result<Json<SeedReport>, axum::response::Response> {
+    let profile = match req.profile {
+        Some(profile) => profile,
+        None => default_seed_profile(&db).await.map_err(db_error)?,
+    };
+    let locale = req.locale.unwrap_or_else(SeedLocale::detect);
+    seed::seed_profile(&db, profile, locale)
+        .await
+        .map(Json)
+        .map_err(db_error)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/setup/complete",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Setup finished; the final wizard state", body = SetupStateResponse),
+        (status = 409, description = "A required step is missing")
+    )
+)]
+pub async fn complete_setup(State(db): State<DatabaseConnection>) -> impl IntoResponse {
+    let mut progress = match setup_wizard::load(&db).await {
+        Ok(progress) => progress,
+        Err(e) => return db_error(e),
+    };
+    if progress.completed_at.is_none() {
+        if let Some(missing) = progress.missing_required() {
+            return wizard_error(
+                StatusCode::CONFLICT,
+                format!("Save the '{}' step first", missing.as_str()),
+            );
+        }
+        progress.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        if let Err(e) = setup_wizard::save(&db, &progress).await {
+            return db_error(e);
+        }
+        tracing::info!("Setup wizard completed");
+    }
+    state_response(&db, &progress).await
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigResponse {
     pub id: i32,
@@ -287,8 +837,19 @@ pub struct ConfigResponse {
     /// JSON avatar configuration for this library's profile
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub avatar_config: Option<serde_json::Value>,
+    /// Onion service URL this library is also reachable at (if published)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub onion_url: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/config",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Public identity of this library, read during the peer handshake")
+    )
+)]
 pub async fn get_config(State(state): State<crate::infrastructure::AppState>) -> impl IntoResponse {
     use crate::models::{installation_profile, library_config};
 
@@ -400,6 +961,7 @@ pub async fn get_config(State(state): State<crate::infrastructure::AppState>) ->
             mailbox_id: relay_config.as_ref().map(|r| r.mailbox_uuid.clone()),
             relay_write_token: relay_config.as_ref().map(|r| r.write_token.clone()),
             avatar_config,
+            onion_url: crate::services::tor::onion_url(),
         }),
     )
         .into_response()
@@ -447,6 +1009,15 @@ pub async fn load_public_keys_from_db(db: &DatabaseConnection) -> (Option<String
 
 /// POST /api/identity/init — Initialize the node's E2EE identity (desktop/test mode).
 /// In FFI mode, Flutter calls init_identity_ffi() instead.
+#[utoipa::path(
+    post,
+    path = "/api/identity/init",
+    tag = "setup",
+    request_body = InitIdentityRequest,
+    responses(
+        (status = 200, description = "E2EE identity loaded or created")
+    )
+)]
 pub async fn init_identity(
     State(state): State<crate::infrastructure::AppState>,
     Json(payload): Json<InitIdentityRequest>,
@@ -474,25 +1045,172 @@ pub async fn init_identity(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct InitIdentityRequest {
     pub library_uuid: String,
 }
 
+/// How long a reset confirmation token stays valid.
+const RESET_TOKEN_TTL_SECS: i64 = 300;
+
+/// Token handed out by a dry run and required by the wipe that follows.
+struct PendingReset {
+    token: String,
+    username: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// At most one reset is pending: a new dry run replaces the previous token.
+static PENDING_RESET: Mutex<Option<PendingReset>> = Mutex::new(None);
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct ResetRequest {
+    /// Token from a previous dry run. Without it nothing is deleted.
+    pub confirm_token: Option<String>,
+}
+
+fn new_reset_token() -> String {
+    use base64::Engine as _;
+    use rand::RngCore;
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Take the pending token if it matches, was issued to `username` and has not
+/// expired. A mismatch leaves it in place; an expired one is dropped.
+fn take_reset_token(token: &str, username: &str) -> bool {
+    let mut pending = PENDING_RESET.lock().unwrap();
+    let Some(p) = pending.as_ref() else {
+        return false;
+    };
+    if (chrono::Utc::now() - p.created_at).num_seconds() >= RESET_TOKEN_TTL_SECS {
+        *pending = None;
+        return false;
+    }
+    if p.token != token || p.username != username {
+        return false;
+    }
+    *pending = None;
+    true
+}
+
+/// Row counts of what a reset would delete.
+async fn reset_preview(db: &DatabaseConnection) -> Result<serde_json::Value, sea_orm::DbErr> {
+    use crate::models::{
+        author, book, collection, contact, copy, loan, notification, p2p_outgoing_request,
+        p2p_request, peer, tag, user,
+    };
+    use sea_orm::PaginatorTrait;
+
+    Ok(json!({
+        "books": book::Entity::find().count(db).await?,
+        "copies": copy::Entity::find().count(db).await?,
+        "loans": loan::Entity::find().count(db).await?,
+        "collections": collection::Entity::find().count(db).await?,
+        "authors": author::Entity::find().count(db).await?,
+        "tags": tag::Entity::find().count(db).await?,
+        "peers": peer::Entity::find().count(db).await?,
+        "contacts": contact::Entity::find().count(db).await?,
+        "incoming_requests": p2p_request::Entity::find().count(db).await?,
+        "outgoing_requests": p2p_outgoing_request::Entity::find().count(db).await?,
+        "notifications": notification::Entity::find().count(db).await?,
+        "users": user::Entity::find().count(db).await?,
+    }))
+}
+
+/// POST /reset
+///
+/// Two steps, admin only. Without `confirm_token` this is a dry run: it
+/// reports what would be deleted and issues a token valid for five minutes.
+/// Posting that token back wipes the library, after snapshotting the DB as a
+/// rollback (see `backup::snapshot_rollback`); a failed snapshot aborts.
+#[utoipa::path(
+    post,
+    path = "/api/reset",
+    tag = "setup",
+    security(("bearer_auth" = [])),
+    request_body(content = Option<ResetRequest>, description = "Omit `confirm_token` for a dry run"),
+    responses(
+        (status = 200, description = "Dry-run report with a confirmation token, or the wipe result"),
+        (status = 403, description = "Caller is not an admin"),
+        (status = 409, description = "Invalid or expired confirmation token")
+    )
+)]
 pub async fn reset_app(
-    State(db): State<DatabaseConnection>,
-    _claims: crate::auth::Claims,
+    State(state): State<crate::infrastructure::AppState>,
+    claims: crate::auth::Claims,
+    body: Option<Json<ResetRequest>>,
 ) -> impl IntoResponse {
     use crate::models::{
         author, book, book_authors, book_tags, collection, collection_book, contact, copy,
         installation_profile, library, library_config, loan, notification, operation_log,
         p2p_outgoing_request, p2p_request, peer, peer_book, peer_gamification_stats, tag, user,
     };
+    let db = state.db();
+
+    if claims.role != "admin" {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({"error": "Only an admin can reset the app"})),
+        )
+            .into_response();
+    }
+
+    let Json(request) = body.unwrap_or_default();
+    let Some(token) = request.confirm_token else {
+        let will_delete = match reset_preview(db).await {
+            Ok(counts) => counts,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({"error": e.to_string()})),
+                )
+                    .into_response();
+            }
+        };
+        let token = new_reset_token();
+        *PENDING_RESET.lock().unwrap() = Some(PendingReset {
+            token: token.clone(),
+            username: claims.sub,
+            created_at: chrono::Utc::now(),
+        });
+        return (
+            StatusCode::OK,
+            Json(json!({
+                "dry_run": true,
+                "will_delete": will_delete,
+                "confirm_token": token,
+                "expires_in": RESET_TOKEN_TTL_SECS,
+            })),
+        )
+            .into_response();
+    };
+
+    if !take_reset_token(&token, &claims.sub) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({"error": "Invalid or expired confirmation token"})),
+        )
+            .into_response();
+    }
+
+    let backup_path = match crate::api::backup::snapshot_rollback(db, state.database_url()).await {
+        Ok(path) => path,
+        Err(e) => {
+            tracing::error!("Pre-reset backup failed, reset aborted: {e}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Pre-reset backup failed: {e}")})),
+            )
+                .into_response();
+        }
+    };
 
     // Helper macro to delete all from a table
     macro_rules! delete_all {
         ($entity:ident) => {
-            if let Err(e) = $entity::Entity::delete_many().exec(&db).await {
+            if let Err(e) = $entity::Entity::delete_many().exec(db).await {
                 tracing::error!("Failed to delete from {}: {}", stringify!($entity), e);
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -533,12 +1251,16 @@ pub async fn reset_app(
     // Let's delete everything to be safe and clean.
     delete_all!(user);
 
+    if let Err(e) = setup_wizard::clear(db).await {
+        tracing::error!("Failed to clear setup_wizard during reset_app: {e}");
+    }
+
     // Wipe the E2EE identity row so the next init takes the Ok(None) branch
     // and generates a fresh keypair. Without this, a Reset Entirely would
     // either reuse the cached identity (still in memory) on the current run
     // or, on the next launch, fail with DecryptionFailed once the new
     // library_uuid is generated by the setup flow.
-    if let Err(e) = crate::services::identity_service::delete_identity_from_db(&db).await {
+    if let Err(e) = crate::services::identity_service::delete_identity_from_db(db).await {
         tracing::error!("Failed to delete crypto_keys during reset_app: {e}");
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -551,7 +1273,11 @@ pub async fn reset_app(
 
     (
         StatusCode::OK,
-        Json(json!({"success": true, "message": "App reset successfully"})),
+        Json(json!({
+            "success": true,
+            "message": "App reset successfully",
+            "backup_path": backup_path.map(|p| p.to_string_lossy().into_owned()),
+        })),
     )
         .into_response()
 }
@@ -596,7 +1322,43 @@ mod tests {
             role: "admin".to_string(),
             exp: 0,
         };
-        let response = reset_app(axum::extract::State(db.clone()), claims).await;
+        // Step 1: a dry run reports and deletes nothing.
+        let response = reset_app(
+            axum::extract::State(crate::infrastructure::AppState::new(db.clone())),
+            claims.clone(),
+            None,
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let preview: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(preview["dry_run"], true);
+        assert!(preview["will_delete"]["books"].is_u64());
+        let token = preview["confirm_token"].as_str().unwrap().to_string();
+
+        // A wrong token is refused and keeps the real one valid.
+        let wrong = reset_app(
+            axum::extract::State(crate::infrastructure::AppState::new(db.clone())),
+            claims.clone(),
+            Some(Json(ResetRequest {
+                confirm_token: Some("guess".to_string()),
+            })),
+        )
+        .await;
+        assert_eq!(wrong.into_response().status(), StatusCode::CONFLICT);
+
+        // Step 2: the token from the dry run performs the wipe.
+        let response = reset_app(
+            axum::extract::State(crate::infrastructure::AppState::new(db.clone())),
+            claims,
+            Some(Json(ResetRequest {
+                confirm_token: Some(token),
+            })),
+        )
+        .await;
         let status = response.into_response().status();
         assert_eq!(status, StatusCode::OK, "reset_app must succeed");
 
@@ -613,4 +1375,127 @@ mod tests {
             .unwrap();
         assert_eq!(count_after, 0, "crypto_keys must be empty after reset_app");
     }
+
+    #[tokio::test]
+    async fn reset_app_requires_the_admin_role() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        crate::infrastructure::db::run_migrations(&db)
+            .await
+            .unwrap();
+        let claims = crate::infrastructure::auth::Claims {
+            sub: "reader".to_string(),
+            role: "user".to_string(),
+            exp: 0,
+        };
+        let response = reset_app(
+            axum::extract::State(crate::infrastructure::AppState::new(db)),
+            claims,
+            None,
+        )
+        .await;
+        assert_eq!(response.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    async fn step(
+        db: &DatabaseConnection,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let req: SetupStepRequest = serde_json::from_value(body).unwrap();
+        let response = save_setup_step(axum::extract::State(db.clone()), Json(req))
+            .await
+            .into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn wizard_saves_steps_in_order_and_resumes() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        crate::infrastructure::db::run_migrations(&db)
+            .await
+            .unwrap();
+
+        // A later required step needs the earlier ones.
+        let (status, _) = step(&db, json!({"step": "library", "name": "Shelf"})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, _) = step(&db, json!({"step": "profile", "profile_type": "wizard"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, state) = step(
+            &db,
+            json!({"step": "profile", "profile_type": "professional"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(state["status"], "in_progress");
+        assert_eq!(state["next_step"], "admin");
+
+        step(
+            &db,
+            json!({"step": "admin", "username": "ada", "password": "pw1"}),
+        )
+        .await;
+        // Going back to the admin step edits the same account.
+        let (status, state) = step(
+            &db,
+            json!({"step": "admin", "username": "grace", "password": "pw2"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(state["values"]["admin_username"], "grace");
+        let admins: i64 = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT COUNT(*) AS n FROM users WHERE role = 'admin'".to_owned(),
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get("", "n")
+            .unwrap();
+        assert_eq!(admins, 1);
+
+        // Not done yet: completing is refused.
+        let response = complete_setup(axum::extract::State(db.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let (_, state) = step(&db, json!({"step": "library", "name": "Shelf"})).await;
+        assert_eq!(state["status"], "ready");
+        assert_eq!(state["next_step"], "location");
+        let (status, _) = step(&db, json!({"step": "location", "latitude": 48.8})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (_, state) = step(&db, json!({"step": "demo_data", "seed": true})).await;
+        assert_eq!(state["values"]["library_name"], "Shelf");
+        let books = crate::models::book::Entity::find()
+            .all(&db)
+            .await
+            .unwrap()
+            .len();
+        assert!(books > 0);
+        step(&db, json!({"step": "demo_data", "seed": true})).await;
+        assert_eq!(
+            crate::models::book::Entity::find()
+                .all(&db)
+                .await
+                .unwrap()
+                .len(),
+            books
+        );
+
+        let response = complete_setup(axum::extract::State(db.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let (status, _) = step(&db, json!({"step": "modules", "enabled_modules": []})).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(
+            setup_wizard::load(&db).await.unwrap().status(),
+            WizardStatus::Completed
+        );
+    }
 }
@@ -0,0 +1,131 @@
+//! Reading statistics (see `services::reading_stats`).
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::{p2p_stats, reading_stats};
+use crate::utils::stats_card::{CardSize, CardTheme};
+
+fn year_out_of_range() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": "year must have four digits" })),
+    )
+        .into_response()
+}
+
+/// GET /api/stats/year/:year - Year in Books
+#[utoipa::path(
+    get,
+    path = "/api/stats/year/{year}",
+    tag = "gamification",
+    params(("year" = i32, Path, description = "Calendar year, e.g. 2025")),
+    responses(
+        (status = 200, description = "Books finished, pages, top tags and authors, rating, longest book and streaks for the year", body = YearInBooks),
+        (status = 400, description = "Year out of range")
+    )
+)]
+pub async fn get_year_in_books(State(state): State<AppState>, Path(year): Path<i32>) -> Response {
+    if !(1000..=9999).contains(&year) {
+        return year_out_of_range();
+    }
+    match reading_stats::year_in_books(state.db(), year).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Query parameters of the stats card
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct StatsCardQuery {
+    /// Year of a "year in books" card; without it, a "my library" card
+    pub year: Option<i32>,
+    /// `square` (default, 1080x1080), `wide` (1200x630) or `story` (1080x1920)
+    #[param(value_type = Option<String>)]
+    pub size: Option<CardSize>,
+    /// `light` (default) or `dark`
+    #[param(value_type = Option<String>)]
+    pub theme: Option<CardTheme>,
+}
+
+/// GET /api/stats/card.png - Shareable stats card
+#[utoipa::path(
+    get,
+    path = "/api/stats/card.png",
+    tag = "gamification",
+    params(StatsCardQuery),
+    responses(
+        (status = 200, description = "PNG card: counts, top tags and a mosaic of shared books' covers, no titles", content_type = "image/png"),
+        (status = 400, description = "Year out of range, or unknown size or theme")
+    )
+)]
+pub async fn get_stats_card(
+    State(state): State<AppState>,
+    Query(query): Query<StatsCardQuery>,
+) -> Response {
+    if query.year.is_some_and(|y| !(1000..=9999).contains(&y)) {
+        return year_out_of_range();
+    }
+    let card = match reading_stats::stats_card(state.db(), query.year).await {
+        Ok(card) => card,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let (size, theme) = (
+        query.size.unwrap_or_default(),
+        query.theme.unwrap_or_default(),
+    );
+    // Decoding covers and encoding the PNG is CPU-bound.
+    let png = tokio::task::spawn_blocking(move || {
+        crate::utils::stats_card::render_card(&card, size, theme)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+
+    match png {
+        Ok(png) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e })),
+        )
+            .into_response(),
+    }
+}
+
+/// GET /api/stats/p2p - Lending circle statistics
+#[utoipa::path(
+    get,
+    path = "/api/stats/p2p",
+    tag = "gamification",
+    responses(
+        (status = 200, description = "Books lent and borrowed per peer, average return time and most circulated titles, aggregated from request history", body = P2pStats)
+    )
+)]
+pub async fn get_p2p_stats(State(state): State<AppState>) -> Response {
+    match p2p_stats::p2p_stats(state.db()).await {
+        Ok(stats) => (StatusCode::OK, Json(stats)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,238 @@
+//! Subject authority endpoints: vocabulary imports, validation and
+//! auto-complete of subjects, and the report of unauthorized headings (see
+//! `services::subject_authority`).
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::subject_authority::{self, HeadingImport, ImportFormat};
+
+const DEFAULT_SUGGESTIONS: u64 = 10;
+const MAX_SUGGESTIONS: u64 = 50;
+const MAX_VALIDATE: usize = 200;
+
+fn db_error(e: sea_orm::DbErr) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({ "error": e.to_string() })),
+    )
+        .into_response()
+}
+
+fn bad_request(message: impl Into<String>) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "error": message.into() })),
+    )
+        .into_response()
+}
+
+/// GET /api/subject-authorities - Imported vocabularies
+#[utoipa::path(
+    get,
+    path = "/api/subject-authorities",
+    tag = "subject-authorities",
+    responses(
+        (status = 200, description = "Vocabularies with their heading and variant counts", body = [Vocabulary])
+    )
+)]
+pub async fn list_vocabularies(State(state): State<AppState>) -> Response {
+    match subject_authority::vocabularies(state.db()).await {
+        Ok(found) => Json(json!({ "vocabularies": found })).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Query parameters of `POST /api/subject-authorities/:vocabulary/import`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ImportQuery {
+    /// `lines` (default) or `ntriples`
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub format: ImportFormat,
+    /// With `ntriples`, keep only labels in this language, e.g. `fr`
+    pub lang: Option<String>,
+    /// Add to the vocabulary instead of replacing it
+    #[serde(default)]
+    pub append: bool,
+}
+
+/// POST /api/subject-authorities/:vocabulary/import - Import a vocabulary
+///
+/// The body is the raw list or N-Triples dump; it is read as it arrives, so
+/// full RAMEAU or LCSH exports can be sent. Nothing is kept if it fails.
+#[utoipa::path(
+    post,
+    path = "/api/subject-authorities/{vocabulary}/import",
+    tag = "subject-authorities",
+    params(
+        ("vocabulary" = String, Path, description = "Vocabulary name, e.g. `rameau` or `lcsh`"),
+        ImportQuery
+    ),
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "Import summary", body = ImportSummary),
+        (status = 400, description = "Invalid vocabulary name or unreadable body")
+    )
+)]
+pub async fn import_vocabulary(
+    State(state): State<AppState>,
+    Path(vocabulary): Path<String>,
+    Query(query): Query<ImportQuery>,
+    body: Body,
+) -> Response {
+    if !subject_authority::valid_vocabulary(&vocabulary) {
+        return bad_request("vocabulary names use up to 32 of a-z, 0-9, - and _");
+    }
+    let mut import = match HeadingImport::start(
+        state.db(),
+        &vocabulary,
+        query.format,
+        query.lang,
+        query.append,
+    )
+    .await
+    {
+        Ok(import) => import,
+        Err(e) => return db_error(e),
+    };
+
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => return bad_request(format!("could not read the upload: {e}")),
+        };
+        if let Err(e) = import.feed(&chunk).await {
+            return db_error(e);
+        }
+    }
+    match import.finish().await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// DELETE /api/subject-authorities/:vocabulary - Remove a vocabulary
+#[utoipa::path(
+    delete,
+    path = "/api/subject-authorities/{vocabulary}",
+    tag = "subject-authorities",
+    params(("vocabulary" = String, Path, description = "Vocabulary name")),
+    responses(
+        (status = 200, description = "Vocabulary removed"),
+        (status = 404, description = "No such vocabulary")
+    )
+)]
+pub async fn delete_vocabulary(
+    State(state): State<AppState>,
+    Path(vocabulary): Path<String>,
+) -> Response {
+    match subject_authority::delete_vocabulary(state.db(), &vocabulary).await {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "vocabulary not found" })),
+        )
+            .into_response(),
+        Ok(removed) => Json(json!({ "removed": removed })).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Query parameters of `GET /api/subject-authorities/suggest`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct SuggestQuery {
+    /// What the cataloguer typed so far
+    pub q: String,
+    /// Only this vocabulary; all when absent
+    pub vocabulary: Option<String>,
+    /// At most 50, 10 by default
+    pub limit: Option<u64>,
+}
+
+/// GET /api/subject-authorities/suggest - Auto-complete a subject
+#[utoipa::path(
+    get,
+    path = "/api/subject-authorities/suggest",
+    tag = "subject-authorities",
+    params(SuggestQuery),
+    responses(
+        (status = 200, description = "Authorized headings starting with the text, or with one of their variants", body = [HeadingSuggestion])
+    )
+)]
+pub async fn suggest_headings(
+    State(state): State<AppState>,
+    Query(query): Query<SuggestQuery>,
+) -> Response {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SUGGESTIONS)
+        .clamp(1, MAX_SUGGESTIONS);
+    match subject_authority::suggest(state.db(), &query.q, query.vocabulary.as_deref(), limit).await
+    {
+        Ok(found) => Json(json!({ "suggestions": found })).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Request body for validating subjects
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ValidateRequest {
+    pub subjects: Vec<String>,
+}
+
+/// POST /api/subject-authorities/validate - Check subjects against the vocabularies
+#[utoipa::path(
+    post,
+    path = "/api/subject-authorities/validate",
+    tag = "subject-authorities",
+    request_body = ValidateRequest,
+    responses(
+        (status = 200, description = "One result per subject, in order", body = [HeadingCheck]),
+        (status = 400, description = "More than 200 subjects")
+    )
+)]
+pub async fn validate_subjects(
+    State(state): State<AppState>,
+    Json(req): Json<ValidateRequest>,
+) -> Response {
+    if req.subjects.len() > MAX_VALIDATE {
+        return bad_request(format!("at most {MAX_VALIDATE} subjects at once"));
+    }
+    let mut results = Vec::with_capacity(req.subjects.len());
+    for subject in &req.subjects {
+        match subject_authority::check(state.db(), subject).await {
+            Ok(result) => results.push(result),
+            Err(e) => return db_error(e),
+        }
+    }
+    Json(json!({ "results": results })).into_response()
+}
+
+/// GET /api/subject-authorities/report - Book subjects no vocabulary authorizes
+#[utoipa::path(
+    get,
+    path = "/api/subject-authorities/report",
+    tag = "subject-authorities",
+    responses(
+        (status = 200, description = "Unauthorized subjects by book title; empty while no vocabulary is imported", body = [UnauthorizedHeading])
+    )
+)]
+pub async fn get_unauthorized_report(State(state): State<AppState>) -> Response {
+    match subject_authority::unauthorized_headings(state.db()).await {
+        Ok(found) => {
+            let total = found.len();
+            Json(json!({ "headings": found, "total": total })).into_response()
+        }
+        Err(e) => db_error(e),
+    }
+}
@@ -0,0 +1,94 @@
+//! Moderation of the suggestion box (see `services::suggestions`); patrons
+//! send suggestions through `/portal/suggestions`.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::suggestions::{self, SuggestionError};
+
+fn suggestion_error(e: SuggestionError) -> Response {
+    let status = match e {
+        SuggestionError::Db(_) | SuggestionError::Book(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        SuggestionError::NotFound => StatusCode::NOT_FOUND,
+        SuggestionError::Invalid(_) => StatusCode::BAD_REQUEST,
+        SuggestionError::Duplicate
+        | SuggestionError::TooMany
+        | SuggestionError::AlreadyDecided(_) => StatusCode::CONFLICT,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Query parameters of `GET /api/suggestions`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct SuggestionsQuery {
+    /// `pending`, `accepted` or `rejected`; all when absent
+    pub status: Option<String>,
+}
+
+/// GET /api/suggestions - Suggestions sent by patrons
+#[utoipa::path(
+    get,
+    path = "/api/suggestions",
+    tag = "acquisitions",
+    params(SuggestionsQuery),
+    responses(
+        (status = 200, description = "Suggestions, longest waiting first", body = [BookSuggestion])
+    )
+)]
+pub async fn list_suggestions(
+    State(state): State<AppState>,
+    Query(query): Query<SuggestionsQuery>,
+) -> Response {
+    match suggestions::list(state.db(), query.status.as_deref()).await {
+        Ok(found) => {
+            let total = found.len();
+            Json(json!({ "suggestions": found, "total": total })).into_response()
+        }
+        Err(e) => suggestion_error(e.into()),
+    }
+}
+
+/// POST /api/suggestions/:id/accept - Put a suggested book on the wishlist
+#[utoipa::path(
+    post,
+    path = "/api/suggestions/{id}/accept",
+    tag = "acquisitions",
+    params(("id" = String, Path, description = "Suggestion id")),
+    responses(
+        (status = 200, description = "Accepted; `book_id` is the wishlist book, or the catalogued book with that ISBN"),
+        (status = 404, description = "Suggestion not found"),
+        (status = 409, description = "Suggestion already accepted or rejected")
+    )
+)]
+pub async fn accept_suggestion(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match suggestions::accept(state.db(), &id).await {
+        Ok(accepted) => Json(json!({ "suggestion": accepted })).into_response(),
+        Err(e) => suggestion_error(e),
+    }
+}
+
+/// POST /api/suggestions/:id/reject - Turn a suggestion down
+#[utoipa::path(
+    post,
+    path = "/api/suggestions/{id}/reject",
+    tag = "acquisitions",
+    params(("id" = String, Path, description = "Suggestion id")),
+    responses(
+        (status = 200, description = "Rejected"),
+        (status = 404, description = "Suggestion not found"),
+        (status = 409, description = "Suggestion already accepted or rejected")
+    )
+)]
+pub async fn reject_suggestion(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match suggestions::reject(state.db(), &id).await {
+        Ok(rejected) => Json(json!({ "suggestion": rejected })).into_response(),
+        Err(e) => suggestion_error(e),
+    }
+}
@@ -9,17 +9,43 @@ use sea_orm::*;
 use serde::Deserialize;
 use serde_json::json;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, utoipa::ToSchema)]
 pub struct CreateTagRequest {
     name: String,
     parent_id: Option<String>,
+    /// Keep books with this tag out of peer catalogues, federated search,
+    /// the directory and exports
+    #[serde(default)]
+    private: bool,
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetTagPrivacyRequest {
+    private: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "tags",
+    responses(
+        (status = 200, description = "All tags")
+    )
+)]
 pub async fn list_tags(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     let tags = Tag::find().all(&db).await.unwrap_or(vec![]);
     (StatusCode::OK, Json(tags)).into_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tags",
+    tag = "tags",
+    request_body = CreateTagRequest,
+    responses(
+        (status = 201, description = "Tag created")
+    )
+)]
 pub async fn create_tag(
     State(db): State<DatabaseConnection>,
     Json(payload): Json<CreateTagRequest>,
@@ -44,6 +70,7 @@ pub async fn create_tag(
         name: Set(payload.name),
         parent_id: Set(payload.parent_id),
         path: Set(path),
+        private: Set(payload.private),
         created_at: Set(chrono::Utc::now().to_rfc3339()),
         updated_at: Set(chrono::Utc::now().to_rfc3339()),
         ..Default::default()
@@ -62,6 +89,16 @@ pub async fn create_tag(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = String, Path, description = "Tag id")),
+    responses(
+        (status = 200, description = "The tag"),
+        (status = 404, description = "Tag not found")
+    )
+)]
 pub async fn get_tag(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
@@ -77,51 +114,85 @@ pub async fn get_tag(
     }
 }
 
-pub async fn delete_tag(
+/// Mark a tag private or shared. A private tag also restricts the books of
+/// the tags nested under it.
+#[utoipa::path(
+    put,
+    path = "/api/tags/{id}/private",
+    tag = "tags",
+    params(("id" = String, Path, description = "Tag id")),
+    request_body = SetTagPrivacyRequest,
+    responses(
+        (status = 200, description = "The updated tag"),
+        (status = 404, description = "Tag not found")
+    )
+)]
+pub async fn set_tag_privacy(
     State(db): State<DatabaseConnection>,
     Path(id): Path<String>,
+    Json(payload): Json<SetTagPrivacyRequest>,
 ) -> impl IntoResponse {
-    // Cascade the tag's book links and re-parent its children in one
-    // transaction: the database no longer cascades these since the replicated
-    // tables lost their foreign keys (ADR-044).
-    let txn = match db.begin().await {
-        Ok(txn) => txn,
-        Err(e) => {
+    let tag = match Tag::find_by_id(id).one(&db).await {
+        Ok(Some(tag)) => tag,
+        Ok(None) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-                .into_response();
-        }
-    };
-    match crate::infrastructure::referential_integrity::delete_tag_cascade(&txn, &id).await {
-        Ok(true) => {
-            if let Err(e) = txn.commit().await {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-                    .into_response();
-            }
-            let _ = crate::sync::log_operation(&db, "tag", &id, "DELETE", None).await;
-            (StatusCode::OK, Json(json!({ "message": "Tag deleted" }))).into_response()
-        }
-        Ok(false) => {
-            txn.rollback().await.ok();
-            (
                 StatusCode::NOT_FOUND,
                 Json(json!({ "error": "Tag not found" })),
             )
-                .into_response()
+                .into_response();
         }
         Err(e) => {
-            txn.rollback().await.ok();
-            (
+            return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({ "error": e.to_string() })),
             )
-                .into_response()
+                .into_response();
         }
+    };
+
+    let mut active: tag::ActiveModel = tag.into();
+    active.private = Set(payload.private);
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    match active.update(&db).await {
+        Ok(model) => {
+            let _ = crate::sync::log_operation(&db, "tag", &model.id, "UPDATE", None).await;
+            (StatusCode::OK, Json(model)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = String, Path, description = "Tag id")),
+    responses(
+        (status = 200, description = "Tag deleted"),
+        (status = 404, description = "Tag not found")
+    )
+)]
+pub async fn delete_tag(
+    State(state): State<crate::infrastructure::AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    // The repository unlinks the tag's books and re-parents its children.
+    match state.tag_repo.delete(&id).await {
+        Ok(()) => (StatusCode::OK, Json(json!({ "message": "Tag deleted" }))).into_response(),
+        Err(crate::domain::DomainError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Tag not found" })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
     }
 }
 
@@ -138,6 +209,14 @@ pub struct TagTreeNode {
 }
 
 /// Get all tags as a tree structure
+#[utoipa::path(
+    get,
+    path = "/api/tags/tree",
+    tag = "tags",
+    responses(
+        (status = 200, description = "Tags as a hierarchy")
+    )
+)]
 pub async fn list_tags_tree(State(db): State<DatabaseConnection>) -> impl IntoResponse {
     let tags = Tag::find().all(&db).await.unwrap_or(vec![]);
 
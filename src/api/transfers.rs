@@ -0,0 +1,199 @@
+//! Transfers of copies between libraries (request, ship, receive, cancel).
+//! Business rules live in `services::copy_transfer_service`.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::models::copy_transfer;
+use crate::services::copy_transfer_service::{self, ServiceError, TransferFilter};
+
+/// Request body for moving a copy to another library
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateTransferRequest {
+    pub to_library_id: i32,
+    pub notes: Option<String>,
+}
+
+/// Query parameters for listing transfers
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ListTransfersQuery {
+    /// Transfers leaving or reaching this library
+    pub library_id: Option<i32>,
+    /// `requested`, `in_transit`, `completed` or `cancelled`
+    pub status: Option<String>,
+}
+
+fn error_response(e: ServiceError, not_found: &str) -> Response {
+    match e {
+        ServiceError::NotFound => {
+            (StatusCode::NOT_FOUND, Json(json!({"error": not_found}))).into_response()
+        }
+        ServiceError::InvalidState(msg) => {
+            (StatusCode::CONFLICT, Json(json!({"error": msg}))).into_response()
+        }
+        ServiceError::Database(msg) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+    }
+}
+
+/// Answer with the transfer, logging the copy change it made for sync.
+async fn transfer_response(
+    state: &AppState,
+    result: Result<copy_transfer::Model, ServiceError>,
+) -> Response {
+    match result {
+        Ok(transfer) => {
+            let _ =
+                crate::sync::log_operation(state.db(), "copy", &transfer.copy_id, "UPDATE", None)
+                    .await;
+            (StatusCode::OK, Json(json!({"transfer": transfer}))).into_response()
+        }
+        Err(e) => error_response(e, "Transfer not found"),
+    }
+}
+
+/// POST /api/copies/:id/transfers - Request moving a copy to another library
+#[utoipa::path(
+    post,
+    path = "/api/copies/{id}/transfers",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    request_body = CreateTransferRequest,
+    responses(
+        (status = 201, description = "Transfer requested"),
+        (status = 404, description = "Copy not found"),
+        (status = 409, description = "Copy not available, already in that library, unknown library or open transfer")
+    )
+)]
+pub async fn create_transfer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateTransferRequest>,
+) -> impl IntoResponse {
+    match copy_transfer_service::request_transfer(
+        state.db(),
+        &id,
+        payload.to_library_id,
+        payload.notes,
+    )
+    .await
+    {
+        Ok(transfer) => (StatusCode::CREATED, Json(json!({"transfer": transfer}))).into_response(),
+        Err(e) => error_response(e, "Copy not found"),
+    }
+}
+
+/// GET /api/copies/:id/transfers - Transfer history of a copy
+#[utoipa::path(
+    get,
+    path = "/api/copies/{id}/transfers",
+    tag = "copies",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "Transfers of the copy, most recent first"),
+        (status = 404, description = "Copy not found")
+    )
+)]
+pub async fn list_copy_transfers(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match copy_transfer_service::list_for_copy(state.db(), &id).await {
+        Ok(transfers) => (StatusCode::OK, Json(json!({"transfers": transfers}))).into_response(),
+        Err(e) => error_response(e, "Copy not found"),
+    }
+}
+
+/// GET /api/transfers - List transfers with optional filters
+#[utoipa::path(
+    get,
+    path = "/api/transfers",
+    tag = "copies",
+    params(ListTransfersQuery),
+    responses(
+        (status = 200, description = "Transfers matching the filters")
+    )
+)]
+pub async fn list_transfers(
+    State(state): State<AppState>,
+    Query(params): Query<ListTransfersQuery>,
+) -> impl IntoResponse {
+    let filter = TransferFilter {
+        library_id: params.library_id,
+        status: params.status,
+    };
+    match copy_transfer_service::list_transfers(state.db(), filter).await {
+        Ok(transfers) => (StatusCode::OK, Json(json!({"transfers": transfers}))).into_response(),
+        Err(e) => error_response(e, "Transfer not found"),
+    }
+}
+
+/// POST /api/transfers/:id/ship - The copy leaves its library
+#[utoipa::path(
+    post,
+    path = "/api/transfers/{id}/ship",
+    tag = "copies",
+    params(("id" = String, Path, description = "Transfer id")),
+    responses(
+        (status = 200, description = "Transfer in transit, copy marked in_transit"),
+        (status = 404, description = "Transfer not found"),
+        (status = 409, description = "Transfer not requested or copy no longer available")
+    )
+)]
+pub async fn ship_transfer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let result = copy_transfer_service::ship_transfer(state.db(), &id).await;
+    transfer_response(&state, result).await
+}
+
+/// POST /api/transfers/:id/receive - The destination library accepts the copy
+#[utoipa::path(
+    post,
+    path = "/api/transfers/{id}/receive",
+    tag = "copies",
+    params(("id" = String, Path, description = "Transfer id")),
+    responses(
+        (status = 200, description = "Transfer completed, copy moved and available"),
+        (status = 404, description = "Transfer not found"),
+        (status = 409, description = "Transfer not in transit")
+    )
+)]
+pub async fn receive_transfer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let result = copy_transfer_service::receive_transfer(state.db(), &id).await;
+    transfer_response(&state, result).await
+}
+
+/// POST /api/transfers/:id/cancel - Abandon a transfer not received yet
+#[utoipa::path(
+    post,
+    path = "/api/transfers/{id}/cancel",
+    tag = "copies",
+    params(("id" = String, Path, description = "Transfer id")),
+    responses(
+        (status = 200, description = "Transfer cancelled, copy available in its library"),
+        (status = 404, description = "Transfer not found"),
+        (status = 409, description = "Transfer already completed or cancelled")
+    )
+)]
+pub async fn cancel_transfer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let result = copy_transfer_service::cancel_transfer(state.db(), &id).await;
+    transfer_response(&state, result).await
+}
@@ -0,0 +1,307 @@
+//! Size and type checks for uploaded files.
+//!
+//! Upload routes carry [`body_limit`], so a body is cut off once it passes
+//! the configured size (`max_import_upload_mb`, `max_image_upload_mb`)
+//! instead of being buffered whole: a 2 GB upload is refused with 413 after
+//! reading a few MB. [`read_file`] then checks the file part: its declared
+//! content type and its first bytes must both fit the kind of file the route
+//! takes, or the upload is refused with 415 before any parsing.
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::infrastructure::config::Config;
+
+/// Room for the multipart framing around the file.
+const FRAMING_BYTES: usize = 64 * 1024;
+
+/// How far into an import file to look for binary content.
+const SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadKind {
+    /// A CSV, JSON, MARC or ISBN-list export (`import::parse_import_file`)
+    Import,
+    /// A JPEG, PNG or WebP image
+    Image,
+}
+
+impl UploadKind {
+    /// The largest file accepted, in bytes.
+    pub fn max_bytes(self) -> usize {
+        let config = Config::runtime();
+        let mb = match self {
+            UploadKind::Import => config.max_import_upload_mb,
+            UploadKind::Image => config.max_image_upload_mb,
+        };
+        usize::try_from(mb.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX)
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            UploadKind::Import => "a text export (CSV, JSON, MARC or a list of ISBNs)",
+            UploadKind::Image => "a JPEG, PNG or WebP image",
+        }
+    }
+}
+
+/// The body limit of a route taking `kind` uploads.
+pub fn body_limit(kind: UploadKind) -> DefaultBodyLimit {
+    DefaultBodyLimit::max(kind.max_bytes().saturating_add(FRAMING_BYTES))
+}
+
+#[derive(Debug)]
+pub enum UploadError {
+    /// No file part in the request
+    Missing,
+    TooLarge {
+        max_bytes: usize,
+    },
+    /// Declared or sniffed as something the route does not take
+    Unsupported {
+        kind: UploadKind,
+        found: String,
+    },
+    Malformed(String),
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "No file uploaded"),
+            Self::TooLarge { max_bytes } => {
+                write!(
+                    f,
+                    "File too large: the limit is {} MB",
+                    max_bytes / 1024 / 1024
+                )
+            }
+            Self::Unsupported { kind, found } => {
+                write!(f, "Expected {}, got {found}", kind.expected())
+            }
+            Self::Malformed(e) => write!(f, "Malformed upload: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl IntoResponse for UploadError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Missing | Self::Malformed(_) => StatusCode::BAD_REQUEST,
+            Self::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unsupported { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        };
+        let mut body = json!({ "error": self.to_string() });
+        if let Self::TooLarge { max_bytes } = self {
+            body["max_bytes"] = json!(max_bytes);
+        }
+        (status, Json(body)).into_response()
+    }
+}
+
+/// The image format the first bytes announce.
+fn image_format(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// What binary content the first bytes announce, if any.
+fn binary_format(bytes: &[u8]) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"%PDF", "a PDF document"),
+        (b"PK\x03\x04", "a ZIP archive"),
+        (b"\x1f\x8b", "a gzip archive"),
+        (b"7z\xbc\xaf\x27\x1c", "a 7z archive"),
+        (b"\x7fELF", "an executable"),
+        (b"MZ", "an executable"),
+        (b"GIF8", "an image"),
+    ];
+    if let Some(format) = image_format(bytes) {
+        return Some(format);
+    }
+    if let Some((_, name)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some(name);
+    }
+    // Text exports never hold NUL; binary files nearly always do early on.
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    head.contains(&0).then_some("binary data")
+}
+
+/// Whether a part declared as `content_type` may hold a `kind` file.
+/// Clients often label any file `application/octet-stream`.
+fn declared_fits(kind: UploadKind, content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if essence == "application/octet-stream" {
+        return true;
+    }
+    match kind {
+        UploadKind::Image => matches!(
+            essence.as_str(),
+            "image/jpeg" | "image/jpg" | "image/pjpeg" | "image/png" | "image/webp"
+        ),
+        UploadKind::Import => {
+            !["image/", "audio/", "video/", "font/"]
+                .iter()
+                .any(|prefix| essence.starts_with(prefix))
+                && !matches!(
+                    essence.as_str(),
+                    "application/pdf"
+                        | "application/zip"
+                        | "application/gzip"
+                        | "application/x-tar"
+                        | "application/x-7z-compressed"
+                )
+        }
+    }
+}
+
+/// Check the first bytes of a `kind` file.
+pub fn sniff(kind: UploadKind, bytes: &[u8]) -> Result<(), UploadError> {
+    let unsupported = |found: &str| UploadError::Unsupported {
+        kind,
+        found: found.to_string(),
+    };
+    match kind {
+        UploadKind::Image => match image_format(bytes) {
+            Some(_) => Ok(()),
+            None => Err(unsupported(
+                binary_format(bytes).unwrap_or("something else"),
+            )),
+        },
+        UploadKind::Import => match binary_format(bytes) {
+            Some(found) => Err(unsupported(found)),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Read the uploaded file, the first part named `file` or carrying a file
+/// name. Refuses it as soon as it passes the size limit or
+/// when its declared type or its content does not fit `kind`.
+pub async fn read_file(multipart: &mut Multipart, kind: UploadKind) -> Result<Bytes, UploadError> {
+    let max_bytes = kind.max_bytes();
+    let read_error = |e: axum::extract::multipart::MultipartError| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            UploadError::TooLarge { max_bytes }
+        } else {
+            UploadError::Malformed(e.body_text())
+        }
+    };
+    let mut field = loop {
+        match multipart.next_field().await.map_err(read_error)? {
+            Some(field) if field.name() == Some("file") || field.file_name().is_some() => {
+                break field;
+            }
+            Some(_) => continue,
+            None => return Err(UploadError::Missing),
+        }
+    };
+    if let Some(declared) = field.content_type()
+        && !declared_fits(kind, declared)
+    {
+        return Err(UploadError::Unsupported {
+            kind,
+            found: declared.to_string(),
+        });
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(read_error)? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(UploadError::TooLarge { max_bytes });
+        }
+        // Sniff the head before buffering the rest of a file refused anyway.
+        let sniffed = data.len() >= SNIFF_BYTES;
+        data.extend_from_slice(&chunk);
+        if !sniffed && data.len() >= SNIFF_BYTES {
+            sniff(kind, &data)?;
+        }
+    }
+    sniff(kind, &data)?;
+    Ok(Bytes::from(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    async fn upload(content_type: &str, bytes: &[u8]) -> Multipart {
+        let mut body = format!(
+            "--b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"f\"\r\n\
+             Content-Type: {content_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n--b--\r\n");
+        let request = Request::builder()
+            .method("POST")
+            .header("content-type", "multipart/form-data; boundary=b")
+            .body(Body::from(body))
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn mismatched_and_oversized_uploads_are_refused() {
+        let csv = b"Title,Author,ISBN\nMartin Eden,Jack London,9782264024848\n";
+        let file = read_file(&mut upload("text/csv", csv).await, UploadKind::Import).await;
+        assert_eq!(file.unwrap().as_ref(), csv);
+
+        // A PDF renamed to .csv, and a photo sent to the importer.
+        let pdf = read_file(
+            &mut upload("text/csv", b"%PDF-1.7\n...").await,
+            UploadKind::Import,
+        )
+        .await;
+        assert!(matches!(pdf, Err(UploadError::Unsupported { .. })));
+        let photo = read_file(
+            &mut upload("image/jpeg", b"\xFF\xD8\xFF\xE0").await,
+            UploadKind::Import,
+        )
+        .await;
+        assert!(matches!(photo, Err(UploadError::Unsupported { .. })));
+
+        // An image that is not one, even when labelled as one.
+        let fake = read_file(&mut upload("image/png", csv).await, UploadKind::Image).await;
+        assert_eq!(
+            fake.unwrap_err().into_response().status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let untyped = read_file(
+            &mut upload("application/octet-stream", png).await,
+            UploadKind::Image,
+        )
+        .await;
+        assert!(untyped.is_ok());
+
+        let mut huge = png.to_vec();
+        huge.resize(UploadKind::Image.max_bytes() + 1, 0);
+        let too_large = read_file(&mut upload("image/png", &huge).await, UploadKind::Image).await;
+        assert_eq!(
+            too_large.unwrap_err().into_response().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
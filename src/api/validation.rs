@@ -0,0 +1,265 @@
+//! Request body validation for the HTTP API.
+//!
+//! Handlers that accept a JSON body declare `ValidatedJson<T>` instead of
+//! `Json<T>`: the body is deserialized exactly as before, then checked by the
+//! payload's [`Validate`] impl. A failed check answers `422 Unprocessable
+//! Entity` with one entry per offending field, so the client can highlight the
+//! input instead of parsing a SeaORM error string or, worse, having the junk
+//! silently stored.
+//!
+//! ```json
+//! {
+//!   "error": "Validation failed",
+//!   "fields": [{ "field": "title", "message": "must not be empty" }]
+//! }
+//! ```
+
+use axum::{
+    Json, async_trait,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::models::Book;
+
+/// Lowest `publication_year` accepted on write. Generous on purpose: catalogs
+/// hold incunabula and reprints of ancient texts tagged with their original date.
+pub const MIN_PUBLICATION_YEAR: i32 = -3000;
+
+/// How far past the current year a `publication_year` may be (announced titles).
+pub const MAX_YEARS_AHEAD: i32 = 5;
+
+/// One failed check on one input field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// All the field errors collected while validating one payload.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn fields(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// `Ok(())` when nothing was recorded, `Err(self)` otherwise.
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "error": "Validation failed",
+                "fields": self.0,
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// Field-level checks on a deserialized request body.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// `Json<T>` extractor that also runs `T::validate`.
+///
+/// Deserialization failures keep axum's usual `JsonRejection` (400/415/422);
+/// only a body that parses but fails validation gets the field-level 422.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: serde::de::DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        value.validate().map_err(IntoResponse::into_response)?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+/// Record an error when `value` is blank (empty or whitespace only).
+pub fn require_non_blank(errors: &mut ValidationErrors, field: &str, value: &str) {
+    if value.trim().is_empty() {
+        errors.add(field, "must not be empty");
+    }
+}
+
+/// Record an error when `value` is set and outside `min..=max`.
+pub fn check_range(
+    errors: &mut ValidationErrors,
+    field: &str,
+    value: Option<i32>,
+    min: i32,
+    max: i32,
+) {
+    if let Some(v) = value
+        && !(min..=max).contains(&v)
+    {
+        errors.add(field, format!("must be between {} and {}", min, max));
+    }
+}
+
+/// Syntactic e-mail check: exactly one `@`, a non-empty local part, a dotted
+/// domain, and no whitespace. Deliverability is not our concern; catching a
+/// phone number typed into the e-mail box is.
+pub fn is_valid_email(email: &str) -> bool {
+    if email.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let mut parts = email.split('@');
+    let (Some(local), Some(domain), None) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains("..")
+}
+
+/// Record an error when an optional e-mail is set, non-empty and malformed.
+/// An empty string is treated as "no e-mail" (the Flutter forms send `""`).
+pub fn check_email(errors: &mut ValidationErrors, field: &str, value: Option<&str>) {
+    if let Some(email) = value.map(str::trim)
+        && !email.is_empty()
+        && !is_valid_email(email)
+    {
+        errors.add(field, "is not a valid e-mail address");
+    }
+}
+
+/// Record an error when a peer URL fails the SSRF allow-list
+/// ([`validate_url`](crate::api::peer::validate_url)). An empty URL is accepted:
+/// it is how relay-only peers are registered.
+pub fn check_peer_url(errors: &mut ValidationErrors, field: &str, url: &str) {
+    if !url.is_empty()
+        && let Err(e) = crate::api::peer::validate_url(url)
+    {
+        errors.add(field, e);
+    }
+}
+
+impl Validate for Book {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        use chrono::Datelike;
+
+        let mut errors = ValidationErrors::new();
+        require_non_blank(&mut errors, "title", &self.title);
+        let max_year = chrono::Utc::now().year() + MAX_YEARS_AHEAD;
+        check_range(
+            &mut errors,
+            "publication_year",
+            self.publication_year,
+            MIN_PUBLICATION_YEAR,
+            max_year,
+        );
+        check_range(&mut errors, "user_rating", self.user_rating, 0, 10);
+        check_range(&mut errors, "page_count", self.page_count, 0, i32::MAX);
+        if let Some(price) = self.price
+            && (!price.is_finite() || price < 0.0)
+        {
+            errors.add("price", "must be a non-negative number");
+        }
+        if let Some(format) = self.physical_format.as_deref()
+            && !crate::models::book::PHYSICAL_FORMATS.contains(&format)
+        {
+            errors.add(
+                "physical_format",
+                format!(
+                    "must be one of: {}",
+                    crate::models::book::PHYSICAL_FORMATS.join(", ")
+                ),
+            );
+        }
+        errors.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(title: &str) -> Book {
+        Book {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn email_syntax() {
+        assert!(is_valid_email("reader@example.org"));
+        assert!(is_valid_email("a.b+tag@mail.example.fr"));
+        assert!(!is_valid_email("reader"));
+        assert!(!is_valid_email("reader@localhost"));
+        assert!(!is_valid_email("@example.org"));
+        assert!(!is_valid_email("a@@example.org"));
+        assert!(!is_valid_email("a b@example.org"));
+        assert!(!is_valid_email("a@example..org"));
+    }
+
+    #[test]
+    fn book_with_title_only_is_valid() {
+        assert!(book("Le Petit Prince").validate().is_ok());
+    }
+
+    #[test]
+    fn book_collects_every_field_error() {
+        let b = Book {
+            publication_year: Some(99_999),
+            user_rating: Some(11),
+            page_count: Some(-1),
+            ..book("   ")
+        };
+        let errors = b.validate().unwrap_err();
+        let fields: Vec<&str> = errors.fields().iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["title", "publication_year", "user_rating", "page_count"]
+        );
+    }
+
+    #[test]
+    fn rating_bounds_are_inclusive() {
+        for rating in [0, 10] {
+            let b = Book {
+                user_rating: Some(rating),
+                ..book("T")
+            };
+            assert!(b.validate().is_ok(), "rating {rating} must be accepted");
+        }
+    }
+}
@@ -206,6 +206,14 @@ pub async fn record_follower_views(db: &DatabaseConnection, count: i64) -> Resul
 }
 
 /// HTTP handler for GET /api/stats/views
+#[utoipa::path(
+    get,
+    path = "/api/stats/views",
+    tag = "library",
+    responses(
+        (status = 200, description = "How often peers consulted this library")
+    )
+)]
 pub async fn get_view_stats_handler(
     axum::extract::State(state): axum::extract::State<crate::infrastructure::AppState>,
 ) -> Result<axum::Json<serde_json::Value>, (axum::http::StatusCode, axum::Json<serde_json::Value>)>
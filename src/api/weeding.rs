@@ -0,0 +1,211 @@
+//! Weeding endpoints: flagging copies, the review list, withdrawal and the
+//! report of withdrawn copies (see `services::weeding`).
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::weeding::{self, WeedingError, WeedingReason};
+
+fn weeding_error(e: WeedingError) -> Response {
+    let status = match e {
+        WeedingError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        WeedingError::NotFound(_) => StatusCode::NOT_FOUND,
+        WeedingError::AlreadyWithdrawn(_)
+        | WeedingError::NotFlagged(_)
+        | WeedingError::Out(_, _) => StatusCode::CONFLICT,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// Request body for flagging a copy
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct FlagCopyRequest {
+    pub reason: WeedingReason,
+    pub note: Option<String>,
+}
+
+/// POST /api/copies/:id/weeding - Flag a copy for weeding
+#[utoipa::path(
+    post,
+    path = "/api/copies/{id}/weeding",
+    tag = "weeding",
+    params(("id" = String, Path, description = "Copy id")),
+    request_body = FlagCopyRequest,
+    responses(
+        (status = 200, description = "Copy flagged; a flagged copy gets its reason replaced"),
+        (status = 404, description = "Copy not found"),
+        (status = 409, description = "Copy already withdrawn")
+    )
+)]
+pub async fn flag_copy(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<FlagCopyRequest>,
+) -> Response {
+    match weeding::flag(state.db(), &id, req.reason, req.note).await {
+        Ok(copy) => Json(json!({ "copy": copy })).into_response(),
+        Err(e) => weeding_error(e),
+    }
+}
+
+/// DELETE /api/copies/:id/weeding - Take a copy off the review list
+#[utoipa::path(
+    delete,
+    path = "/api/copies/{id}/weeding",
+    tag = "weeding",
+    params(("id" = String, Path, description = "Copy id")),
+    responses(
+        (status = 200, description = "Flag removed"),
+        (status = 404, description = "Copy not found"),
+        (status = 409, description = "Copy not flagged, or already withdrawn")
+    )
+)]
+pub async fn unflag_copy(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match weeding::unflag(state.db(), &id).await {
+        Ok(copy) => Json(json!({ "copy": copy })).into_response(),
+        Err(e) => weeding_error(e),
+    }
+}
+
+/// GET /api/weeding - Copies flagged for weeding
+#[utoipa::path(
+    get,
+    path = "/api/weeding",
+    tag = "weeding",
+    responses(
+        (status = 200, description = "Flagged copies, oldest flag first, with their loan count", body = [WeedingItem])
+    )
+)]
+pub async fn get_review_list(State(state): State<AppState>) -> Response {
+    match weeding::review_list(state.db()).await {
+        Ok(items) => {
+            let total = items.len();
+            Json(json!({ "items": items, "total": total })).into_response()
+        }
+        Err(e) => weeding_error(e.into()),
+    }
+}
+
+/// Request body for withdrawing copies
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct WithdrawRequest {
+    pub copy_ids: Vec<String>,
+}
+
+/// POST /api/weeding/withdraw - Withdraw flagged copies
+#[utoipa::path(
+    post,
+    path = "/api/weeding/withdraw",
+    tag = "weeding",
+    request_body = WithdrawRequest,
+    responses(
+        (status = 200, description = "Copies withdrawn; their loans are kept"),
+        (status = 404, description = "A copy was not found; none was withdrawn"),
+        (status = 409, description = "A copy is not flagged, already withdrawn or out; none was withdrawn")
+    )
+)]
+pub async fn withdraw_copies(
+    State(state): State<AppState>,
+    Json(req): Json<WithdrawRequest>,
+) -> Response {
+    match weeding::withdraw(state.db(), &req.copy_ids).await {
+        Ok(copies) => {
+            let withdrawn = copies.len();
+            Json(json!({ "copies": copies, "withdrawn": withdrawn })).into_response()
+        }
+        Err(e) => weeding_error(e),
+    }
+}
+
+/// Query parameters of `GET /api/weeding/report`
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct WeedingReportQuery {
+    /// First day, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Last day, `YYYY-MM-DD`
+    pub to: Option<String>,
+    /// `json` (default), `csv` or `pdf`
+    pub format: Option<String>,
+}
+
+/// GET /api/weeding/report - Copies withdrawn over a period
+#[utoipa::path(
+    get,
+    path = "/api/weeding/report",
+    tag = "weeding",
+    params(WeedingReportQuery),
+    responses(
+        (status = 200, description = "Withdrawn copies with dates and reasons, as JSON, or a CSV or PDF attachment", body = [WeedingItem]),
+        (status = 400, description = "Unknown format")
+    )
+)]
+pub async fn get_weeding_report(
+    State(state): State<AppState>,
+    Query(query): Query<WeedingReportQuery>,
+) -> Response {
+    let items =
+        match weeding::withdrawn_report(state.db(), query.from.as_deref(), query.to.as_deref())
+            .await
+        {
+            Ok(items) => items,
+            Err(e) => return weeding_error(e.into()),
+        };
+    let stamp = crate::utils::time_zone::library_zone(state.db())
+        .await
+        .unwrap_or(crate::utils::time_zone::LibraryZone::Host)
+        .today()
+        .format("%Y-%m-%d");
+    let attachment = |content_type: &str, extension: &str, body: Vec<u8>| {
+        (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"weeding_{stamp}.{extension}\""),
+                ),
+            ],
+            body,
+        )
+            .into_response()
+    };
+
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => {
+            let total = items.len();
+            Json(json!({ "items": items, "total": total })).into_response()
+        }
+        "csv" => match weeding::report_csv(&items) {
+            Ok(csv) => attachment("text/csv; charset=utf-8", "csv", csv.into_bytes()),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response(),
+        },
+        "pdf" => {
+            let title = match (&query.from, &query.to) {
+                (Some(from), Some(to)) => format!("Withdrawn copies, {from} to {to}"),
+                (Some(from), None) => format!("Withdrawn copies since {from}"),
+                (None, Some(to)) => format!("Withdrawn copies until {to}"),
+                (None, None) => "Withdrawn copies".to_string(),
+            };
+            attachment(
+                "application/pdf",
+                "pdf",
+                weeding::report_pdf(&items, &title),
+            )
+        }
+        other => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("unknown format: {other}") })),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,95 @@
+//! Works grouping the editions and translations of a text (see
+//! `services::works`).
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::services::works::{self, SetBookWork, WorkError};
+
+fn work_error(e: WorkError) -> Response {
+    let status = match e {
+        WorkError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        WorkError::BookNotFound | WorkError::NotFound => StatusCode::NOT_FOUND,
+    };
+    (status, Json(json!({ "error": e.to_string() }))).into_response()
+}
+
+/// GET /api/works - Works with their edition counts
+#[utoipa::path(
+    get,
+    path = "/api/works",
+    tag = "books",
+    responses(
+        (status = 200, description = "Works holding at least one edition, by title", body = [WorkSummary])
+    )
+)]
+pub async fn list_works(State(state): State<AppState>) -> Response {
+    match works::list_works(state.db()).await {
+        Ok(list) => Json(json!({ "works": list })).into_response(),
+        Err(e) => work_error(e.into()),
+    }
+}
+
+/// GET /api/works/:id - One work with its editions
+#[utoipa::path(
+    get,
+    path = "/api/works/{id}",
+    tag = "books",
+    params(("id" = String, Path, description = "Work id")),
+    responses(
+        (status = 200, description = "The work, its identifiers and editions", body = WorkDetail),
+        (status = 404, description = "Work not found")
+    )
+)]
+pub async fn get_work(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match works::get_work(state.db(), &id).await {
+        Ok(Some(found)) => Json(found).into_response(),
+        Ok(None) => work_error(WorkError::NotFound),
+        Err(e) => work_error(e.into()),
+    }
+}
+
+/// POST /api/works/cluster - Group editions into works by their identifiers
+#[utoipa::path(
+    post,
+    path = "/api/works/cluster",
+    tag = "books",
+    responses(
+        (status = 200, description = "What the pass changed", body = ClusterReport)
+    )
+)]
+pub async fn cluster_works(State(state): State<AppState>) -> Response {
+    match works::cluster(state.db()).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => work_error(e.into()),
+    }
+}
+
+/// PUT /api/books/:id/work - Put a book in a work, or out of any
+#[utoipa::path(
+    put,
+    path = "/api/books/{id}/work",
+    tag = "books",
+    params(("id" = String, Path, description = "Book ID")),
+    request_body = SetBookWork,
+    responses(
+        (status = 200, description = "The book's work, null when out of any", body = WorkDetail),
+        (status = 404, description = "Book or work not found")
+    )
+)]
+pub async fn set_book_work(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetBookWork>,
+) -> Response {
+    match works::set_book_work(state.db(), &id, req.work_id).await {
+        Ok(found) => Json(found).into_response(),
+        Err(e) => work_error(e),
+    }
+}
@@ -1,24 +1,869 @@
+//! OpenAPI description of the HTTP API, served at `/api-docs/openapi.json`
+//! (Swagger UI at `/api/docs`) and printed by `bibliogenius --openapi` so the
+//! Flutter app and third parties can generate a typed client.
+//!
+//! Every route of `api::api_router` is listed here except the mini-games and
+//! the operation log viewer, which are UI internals. The coverage test below
+//! fails when a route is added without its `#[utoipa::path]`.
+
 use crate::api;
-use utoipa::OpenApi;
+use crate::domain;
+use crate::infrastructure;
+use crate::models;
+use crate::modules;
+use crate::services;
+use crate::utils;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
 #[derive(OpenApi)]
 #[openapi(
+    info(title = "BiblioGenius API"),
     paths(
-        api::health::health_check,
+        api::admin::shutdown,
+        api::admin::maintenance_status,
+        api::admin::run_maintenance,
+        api::admin::check_consistency,
+        api::admin::repair_consistency,
+        api::admin::runtime_config,
+        api::admin::control_handshake,
+        api::auth::login,
+        api::auth::login_mfa,
+        api::auth::setup_2fa,
+        api::auth::verify_2fa,
+        api::auth::create_admin,
+        api::auth::get_me,
+        api::auth::pairing_generate_code,
+        api::auth::pairing_verify_code,
+        api::author::list_authors,
+        api::author::create_author,
+        api::author::match_authors,
+        api::author::normalize_proposals,
+        api::author::apply_normalization,
+        api::author::get_author,
+        api::author::delete_author,
+        api::author::get_author_works,
+        api::author::update_author_identifiers,
+        api::batch::batch_edit,
+        api::batch::batch_delete,
+        api::batch::batch_archive,
+        api::batch::batch_unarchive,
+        api::batch::batch_sort,
+        api::batch::find_duplicates,
         api::books::list_books,
         api::books::create_book,
-        api::books::update_book,
         api::books::delete_book,
-        // Add other endpoints here as we document them
+        api::books::update_book,
+        api::books::list_tags,
+        api::books::get_book,
+        api::books::get_book_cover,
+        api::books::upload_book_cover,
+        api::books::get_digital_editions,
+        api::books::get_book_relations,
+        api::books::add_book_relation,
+        api::books::remove_book_relation,
+        api::works::list_works,
+        api::works::get_work,
+        api::works::cluster_works,
+        api::works::set_book_work,
+        api::books::reorder_books,
+        api::chat::chat_handler,
+        api::collections::list_collections,
+        api::collections::create_collection,
+        api::collections::get_collection,
+        api::collections::delete_collection,
+        api::collections::deletion_preview,
+        api::collections::get_collection_books,
+        api::collections::add_book_to_collection,
+        api::collections::remove_book_from_collection,
+        api::collections::get_book_collections,
+        api::collections::update_book_collections,
+        api::collections::mark_collection_as_series,
+        api::collections::set_book_volume_number,
+        api::collections::import_collection,
+        api::collections::get_collection_source,
+        api::collections::sync_collection,
+        api::contact::list_contacts,
+        api::contact::get_contact,
+        api::contact::create_contact,
+        api::contact::update_contact,
+        api::contact::promote_contact,
+        api::contact::delete_contact,
+        api::contact::update_membership,
+        api::contact::list_members,
+        api::contact::geocode_contacts,
+        api::contact::nearby_contacts,
+        api::contact::plan_contact_route,
+        api::fees::get_contact_fees,
+        api::fees::record_fee_payment,
+        api::fees::get_fees_report,
+        api::acquisitions::list_orders,
+        api::acquisitions::create_order,
+        api::acquisitions::get_order,
+        api::acquisitions::receive_order,
+        api::acquisitions::cancel_order,
+        api::acquisitions::get_budget_report,
+        api::periodicals::list_periodicals,
+        api::periodicals::create_periodical,
+        api::periodicals::get_periodical,
+        api::periodicals::update_periodical,
+        api::periodicals::delete_periodical,
+        api::periodicals::check_in_issue,
+        api::periodicals::delete_issue,
+        api::reports::get_quality_report,
+        api::subject_authority::list_vocabularies,
+        api::subject_authority::import_vocabulary,
+        api::subject_authority::delete_vocabulary,
+        api::subject_authority::suggest_headings,
+        api::subject_authority::validate_subjects,
+        api::subject_authority::get_unauthorized_report,
+        api::suggestions::list_suggestions,
+        api::suggestions::accept_suggestion,
+        api::suggestions::reject_suggestion,
+        api::copy::list_copies,
+        api::copy::create_copy,
+        api::copy::get_copy,
+        api::copy::get_copy_history,
+        api::copy::get_copy_availability,
+        api::weeding::flag_copy,
+        api::weeding::unflag_copy,
+        api::weeding::get_review_list,
+        api::weeding::withdraw_copies,
+        api::weeding::get_weeding_report,
+        api::copy::get_book_copies,
+        api::copy::get_borrowed_copies,
+        api::copy::delete_copy,
+        api::copy::update_copy,
+        api::transfers::create_transfer,
+        api::transfers::list_copy_transfers,
+        api::transfers::list_transfers,
+        api::transfers::ship_transfer,
+        api::transfers::receive_transfer,
+        api::transfers::cancel_transfer,
+        api::devices::list_devices,
+        api::devices::start_pairing,
+        api::devices::pair_with,
+        api::devices::accept_pairing,
+        api::devices::sync_device,
+        api::devices::revoke_device,
+        api::devices::pull_operations,
+        api::devices::push_operations,
+        api::devices::receive_revocation,
+        api::devices::sync_status,
+        api::data::import_file,
+        api::discovery::list_local_peers,
+        api::discovery::mdns_status,
+        api::discovery::toggle_mdns,
+        api::e2ee::receive_encrypted_message,
+        api::export::export_data,
+        api::export::import_data,
+        api::export::import_data_upsert,
+        api::gamification::get_user_status,
+        api::gamification::get_public_stats,
+        api::gamification::get_leaderboard,
+        api::gamification::refresh_leaderboard,
+        api::stats::get_year_in_books,
+        api::stats::get_stats_card,
+        api::stats::get_p2p_stats,
+        api::health::health_check,
+        api::integrations::search_sudoc,
+        api::integrations::search_openlibrary,
+        api::integrations::search_unified,
+        api::integrations::mcp_config,
+        api::integrations::get_usage,
+        api::integrations::set_quotas,
+        api::kiosk::list_tokens,
+        api::kiosk::create_token,
+        api::kiosk::revoke_token,
+        api::kiosk::search,
+        api::kiosk::checkout,
+        api::kiosk::checkin,
+        api::portal::issue_token,
+        api::portal::revoke_token,
+        api::portal::my_loans,
+        api::portal::request_renewal,
+        api::portal::catalog,
+        api::portal::my_suggestions,
+        api::portal::suggest_book,
+        api::library::get_config,
+        api::library::update_config,
+        api::library::get_config_history,
+        api::library::rollback_config,
+        api::library::list_currencies,
+        api::loan::list_loans,
+        api::loan::create_loan,
+        api::loan::create_quick_loan,
+        api::loan::return_loan,
+        api::loan::renew_loan,
+        api::loan::get_loan_settings,
+        api::loan::update_loan_settings,
+        api::loan::get_effective_loan_duration,
+        api::loan::get_peer_loan_duration,
+        api::loan::set_peer_loan_duration,
+        api::lookup::lookup_book,
+        api::lookup::lookup_availability,
+        api::mcp::rpc_endpoint,
+        api::metadata_fill::get_stats,
+        api::metadata_fill::start,
+        api::metadata_fill::get_progress,
+        api::metadata_fill::cancel,
+        api::metadata_fill::get_recent,
+        api::metadata_fill::get_no_isbn,
+        api::metadata_fill::get_incomplete,
+        api::metadata_fill::undo_field,
+        api::metadata_fill::undo_book,
+        api::metadata_fill::undo_run,
+        api::peer::auto_approve_all_peers,
+        api::peer::list_peers,
+        api::peer::update_peer_status,
+        api::peer::update_peer_url,
+        api::peer::delete_peer,
+        api::peer::update_peer_display_name,
+        api::peer::cover_proxy,
+        api::peer::list_subscriptions,
+        api::peer::subscribe_peer,
+        api::peer::unsubscribe_peer,
+        api::peer::sync_subscription,
+        api::peer::get_peer_capabilities,
+        api::peer::negotiate_with_peer,
+        api::peer::list_peer_certificates,
+        api::peer::probe_peer_certificate,
+        api::peer::rotate_peer_certificate,
+        api::peer::unpin_peer_certificate,
+        api::peer::get_tor_config,
+        api::peer::update_tor_config,
+        api::network::get_network_mode,
+        api::network::set_network_mode,
+        api::announcements::list_announcements,
+        api::announcements::publish_announcement,
+        api::announcements::delete_announcement,
+        api::announcements::set_announcement_trust,
+        api::blobs::list_blobs,
+        api::blobs::get_manifest,
+        api::blobs::get_chunk,
+        api::blobs::list_peer_book_blobs,
+        api::blobs::get_peer_blob,
+        api::peer::list_peer_books,
+        api::peer::list_peer_books_by_url,
+        api::peer::get_cached_books_by_url,
+        api::peer::cleanup_stale_peer_books,
+        api::peer::cache_books_by_id,
+        api::peer::connect,
+        api::peer::import_peers,
+        api::peer::receive_connection_request,
+        api::peer::receive_disconnect_notification,
+        api::peer::verify_disconnect,
+        api::peer::handshake,
+        api::peer::offer_loan,
+        api::peer::receive_loan_confirmation,
+        api::peer::receive_loan_offer,
+        api::peer::setup_relay,
+        api::peer::get_relay_config_endpoint,
+        api::peer::delete_relay_config_endpoint,
+        api::peer::relay_library_request,
+        api::peer::await_relay_response,
+        api::peer::clear_incoming_requests,
+        api::peer::expire_requests,
+        api::peer::clear_resolved_requests,
+        api::peer::receive_request,
+        api::peer::list_requests,
+        api::peer::update_request_status,
+        api::peer::delete_request,
+        api::peer::query_request_status,
+        api::peer::receive_loan_request,
+        api::peer::request_book,
+        api::peer::request_book_by_url,
+        api::peer::list_outgoing_requests,
+        api::peer::clear_outgoing_requests,
+        api::peer::sync_outgoing_requests,
+        api::peer::delete_outgoing_request,
+        api::peer::cancel_request,
+        api::peer::update_outgoing_status,
+        api::peer::create_outgoing_request,
+        api::peer::list_outbox,
+        api::peer::retry_outbox,
+        api::peer::dismiss_outbox_entry,
+        api::peer::return_borrowed_book,
+        api::peer::receive_loan_returned,
+        api::peer::search_local,
+        api::peer::proxy_search,
+        api::peer::push_operations,
+        api::peer::pull_operations,
+        api::peer::sync_peer,
+        api::peer::sync_peer_by_url,
+        api::profile::update_profile,
+        api::profile::list_modules,
+        api::profile::update_modules,
+        api::profile::get_appearance,
+        api::profile::update_appearance,
+        api::public_stats::get_public_stats_bundle,
+        api::relay::create_mailbox,
+        api::relay::deposit_message,
+        api::relay::collect_messages,
+        api::relay::ack_message,
+        api::relay::poll_now,
+        api::relay::relay_status,
+        api::hub::hub_sync_status,
+        api::hub::sync_hub,
+        api::sales::create_sale,
+        api::sales::list_sales,
+        api::sales::cancel_sale,
+        api::sales::get_sales_statistics,
+        api::scan::scan_image,
+        api::scan::record_scan_correction,
+        api::scan::export_scan_corrections,
+        api::images::process_image,
+        api::search::search_books,
+        api::search::voice_search,
+        api::search::nl_search,
+        api::search::suggest,
+        api::reading_queue::list_queue,
+        api::reading_queue::queue_book,
+        api::reading_queue::reorder_queue,
+        api::reading_queue::unqueue_book,
+        api::reading_queue::get_dashboard,
+        api::saved_searches::list_saved_searches,
+        api::saved_searches::create_saved_search,
+        api::saved_searches::delete_saved_search,
+        api::saved_searches::list_saved_search_matches,
+        api::saved_searches::run_saved_search,
+        api::setup::setup,
+        api::setup::get_setup_state,
+        api::setup::save_setup_step,
+        api::setup::complete_setup,
+        api::setup::seed_demo_data,
+        api::setup::get_config,
+        api::setup::init_identity,
+        api::setup::reset_app,
+        api::tag::list_tags,
+        api::tag::create_tag,
+        api::tag::get_tag,
+        api::tag::delete_tag,
+        api::tag::set_tag_privacy,
+        api::tag::list_tags_tree,
+        api::view_counter::get_view_stats_handler,
+        modules::book_files::handlers::list_files,
+        modules::book_files::handlers::upload_file,
+        modules::book_files::handlers::download_file,
+        modules::book_files::handlers::delete_file,
+        modules::book_files::opds::catalog,
+        modules::book_files::opds::download,
+        modules::book_files::opds::list_tokens,
+        modules::book_files::opds::create_token,
+        modules::book_files::opds::revoke_token,
+        modules::book_club::handlers::list_clubs,
+        modules::book_club::handlers::create_club,
+        modules::book_club::handlers::get_club,
+        modules::book_club::handlers::leave_club,
+        modules::book_club::handlers::set_members,
+        modules::book_club::handlers::propose,
+        modules::book_club::handlers::withdraw,
+        modules::book_club::handlers::vote,
+        modules::book_club::handlers::select_read,
+        modules::book_club::handlers::set_schedule,
+        modules::book_notes::handlers::list_notes,
+        modules::book_notes::handlers::create_note,
+        modules::book_notes::handlers::update_note,
+        modules::book_notes::handlers::delete_note,
     ),
     components(
         schemas(
-            // We will need to derive ToSchema for our models
-            // crate::models::book::Book,
+            api::auth::LoginRequest,
+            api::auth::MfaLoginRequest,
+            api::auth::MfaVerifyRequest,
+            api::auth::CreateUserRequest,
+            api::author::CreateAuthorRequest,
+            api::author::MatchResponse,
+            services::author_match::AuthorMatch,
+            services::author_match::TitleMatch,
+            services::author_normalize::FixReason,
+            services::author_normalize::AuthorVariant,
+            services::author_normalize::AuthorFix,
+            services::author_normalize::AcceptFixes,
+            services::author_normalize::FixSummary,
+            api::batch::BatchEditRequest,
+            api::batch::BatchConfirmRequest,
+            api::batch::BatchSortRequest,
+            api::books::TagDto,
+            api::books::ReorderRequest,
+            modules::scanner::candidates::ScanResult,
+            modules::scanner::candidates::IsbnCandidate,
+            modules::scanner::candidates::TextCandidate,
+            services::scan_training::ScanCorrection,
+            api::chat::ChatRequest,
+            api::collections::CreateCollectionRequest,
+            api::collections::UpdateBookCollectionsRequest,
+            api::collections::MarkSeriesRequest,
+            api::collections::SetVolumeRequest,
+            api::contact::ContactDto,
+            services::geocoding::GeocodeReport,
+            services::contact_routes::LocatedContact,
+            services::contact_routes::Route,
+            services::contact_routes::RouteRequest,
+            services::config_history::ConfigVersion,
+            services::membership::Membership,
+            api::fees::FeePaymentRequest,
+            services::fines::ContactFees,
+            services::fines::FeesReport,
+            services::fines::Debtor,
+            utils::money::Money,
+            utils::money::Currency,
+            models::fee_ledger::Model,
+            models::purchase_order::Model,
+            models::purchase_order_line::Model,
+            services::acquisitions::NewOrder,
+            services::acquisitions::NewOrderLine,
+            services::acquisitions::OrderDetail,
+            services::acquisitions::Spending,
+            services::acquisitions::BudgetReport,
+            api::acquisitions::ReceiveOrderRequest,
+            models::periodical::Model,
+            models::periodical_issue::Model,
+            services::periodicals::NewPeriodical,
+            services::periodicals::CheckIn,
+            services::periodicals::IssueNumber,
+            services::periodicals::NextIssue,
+            services::periodicals::Holdings,
+            services::periodicals::PeriodicalDetail,
+            models::book_suggestion::Model,
+            models::collection_import_source::Model,
+            services::suggestions::NewSuggestion,
+            api::copy::CreateCopyRequest,
+            api::copy::UpdateCopyRequest,
+            services::copy_history::CopyEvent,
+            services::copy_calendar::BusyKind,
+            services::copy_calendar::BusyPeriod,
+            services::copy_calendar::CopyCalendar,
+            services::author_works::AuthorWorks,
+            services::author_works::AuthorIdentifiers,
+            services::author_works::ExternalLink,
+            services::author_works::PeerWork,
+            services::author_works::ExternalWork,
+            services::quality_report::QualityIssue,
+            services::quality_report::FixLink,
+            services::quality_report::QualityBook,
+            services::quality_report::QualityGroup,
+            services::quality_report::QualityReport,
+            services::content_blobs::BlobKind,
+            services::content_blobs::BlobEntry,
+            services::content_blobs::Manifest,
+            api::blobs::PeerBookBlob,
+            models::peer_subscription::Model,
+            services::peer_subscriptions::SubscriptionView,
+            api::peer::SubscribeRequest,
+            services::peer_protocol::Handshake,
+            services::peer_protocol::PeerCapabilities,
+            models::peer_certificate::Model,
+            services::cert_pins::Probe,
+            api::peer::RotateCertificateRequest,
+            services::tor::TorSettings,
+            services::network_mode::NetworkMode,
+            api::network::NetworkModeSetting,
+            models::announcement::Model,
+            services::announcements::Draft,
+            services::announcements::Delivery,
+            services::announcements::AnnouncementView,
+            api::announcements::AnnouncementTrustRequest,
+            services::subject_authority::Vocabulary,
+            services::subject_authority::ImportSummary,
+            services::subject_authority::HeadingCheck,
+            services::subject_authority::HeadingSuggestion,
+            services::subject_authority::UnauthorizedHeading,
+            api::subject_authority::ValidateRequest,
+            services::weeding::WeedingReason,
+            services::weeding::WeedingItem,
+            api::weeding::FlagCopyRequest,
+            api::weeding::WithdrawRequest,
+            services::reading_stats::YearInBooks,
+            services::reading_stats::YearStreak,
+            services::reading_stats::NameCount,
+            services::reading_stats::LongestBook,
+            services::p2p_stats::P2pStats,
+            services::p2p_stats::PeerLending,
+            services::p2p_stats::CirculatedTitle,
+            services::digital_editions::DigitalEdition,
+            models::book_relation::RelatedBook,
+            services::book_relations::NewBookRelation,
+            services::images::OutputFormat,
+            services::nl_query::ParsedQuery,
+            api::search::SuggestResponse,
+            services::search_suggest::Suggestion,
+            services::search_suggest::SuggestionKind,
+            models::saved_search::Model,
+            models::saved_search_match::Model,
+            services::saved_searches::NewSavedSearch,
+            services::reading_queue::QueuedBook,
+            services::reading_queue::QueueBook,
+            services::reading_queue::QueueOrder,
+            services::reading_queue::CurrentBook,
+            services::reading_queue::ReadingSummary,
+            services::availability::Availability,
+            services::availability::LocalHolding,
+            services::availability::PeerHolding,
+            services::availability::Suggestion,
+            services::availability::SuggestionKind,
+            modules::integrations::osm::BookPlace,
+            modules::integrations::osm::PlaceKind,
+            models::work::Model,
+            models::work_identifier::Model,
+            services::works::WorkEdition,
+            services::works::WorkDetail,
+            services::works::WorkSummary,
+            services::works::ClusterReport,
+            services::works::SetBookWork,
+            api::discovery::ToggleRequest,
+            services::mdns::AnnouncedRecord,
+            api::export::ImportResult,
+            api::export::SettingsMerge,
+            api::loan::QuickLoanRequest,
+            api::loan::RenewLoanRequest,
+            api::loan::UpdateLoanSettingsPayload,
+            api::loan::PeerLoanDurationPayload,
+            api::metadata_fill::StartBody,
+            api::metadata_fill::UndoBookBody,
+            api::peer::UpdatePeerStatusRequest,
+            api::peer::UpdatePeerUrlRequest,
+            api::peer::UpdatePeerDisplayNameRequest,
+            api::peer::ConnectRequest,
+            services::peer_import::PeerEntry,
+            services::peer_import::RowReport,
+            services::peer_import::ImportReport,
+            api::peer::IncomingConnectionRequest,
+            api::peer::DisconnectNotification,
+            api::peer::VerifyDisconnectRequest,
+            api::peer::OfferLoanRequest,
+            api::peer::LoanConfirmation,
+            api::peer::LoanOffer,
+            api::peer::SetupRelayRequest,
+            api::peer::RelayLibraryRequest,
+            api::peer::AwaitRelayResponse,
+            api::peer::ExpireRequestsPayload,
+            api::peer::IncomingRequest,
+            api::peer::RequestAction,
+            api::peer::RequestStatusQuery,
+            api::peer::IncomingLoanRequest,
+            api::peer::BookRequest,
+            api::peer::BookRequestByUrl,
+            api::peer::OutgoingLoanRequestDto,
+            api::peer::ReturnBorrowedBookPayload,
+            api::peer::LoanReturnedPayload,
+            api::peer::SearchRequest,
+            api::peer::ProxySearchRequest,
+            api::peer::PushRequest,
+            api::peer::OperationDto,
+            services::peer_outbox::OutboxEntry,
+            api::devices::PairWithRequest,
+            api::kiosk::CreateKioskTokenRequest,
+            api::kiosk::KioskCheckoutRequest,
+            api::kiosk::KioskCheckinRequest,
+            services::device_sync::PairingCode,
+            services::device_sync::DeviceHello,
+            services::device_sync::PairRequest,
+            services::device_sync::PairResponse,
+            services::device_sync::SyncOperation,
+            services::device_sync::OperationBatch,
+            services::device_sync::SyncReport,
+            services::oplog_compaction::LogStatus,
+            services::db_maintenance::MaintenanceReport,
+            services::db_maintenance::MaintenanceStatus,
+            infrastructure::referential_integrity::OrphanCounts,
+            services::consistency::ConsistencyReport,
+            services::consistency::ConsistencyIssue,
+            services::oplog_compaction::CompactionPolicy,
+            services::oplog_compaction::CompactionStats,
+            services::oplog_pruner::PrunePolicy,
+            services::hub_sync::HubSyncStatus,
+            services::api_quota::Provider,
+            services::api_quota::DayUsage,
+            services::api_quota::ProviderUsage,
+            api::integrations::QuotasUpdate,
+            api::profile::UpdateProfileRequest,
+            api::profile::ModuleStatus,
+            api::profile::UpdateModulesRequest,
+            api::profile::AvatarConfig,
+            api::profile::AppearanceResponse,
+            api::profile::UpdateAppearanceRequest,
+            modules::registry::FeatureModule,
+            api::sales::CreateSaleRequest,
+            api::setup::SetupRequest,
+            api::setup::SetupResponse,
+            api::setup::SetupStepRequest,
+            api::setup::SetupStepState,
+            api::setup::SetupValues,
+            api::setup::SetupStateResponse,
+            infrastructure::setup_wizard::WizardStep,
+            infrastructure::setup_wizard::WizardStatus,
+            api::setup::SeedDemoRequest,
+            infrastructure::seed::SeedProfile,
+            infrastructure::seed::SeedLocale,
+            infrastructure::seed::SeedReport,
+            api::setup::InitIdentityRequest,
+            api::setup::ResetRequest,
+            api::tag::CreateTagRequest,
+            api::tag::SetTagPrivacyRequest,
+            api::transfers::CreateTransferRequest,
+            models::copy_transfer::Model,
+            domain::copy_repository::LendingTerms,
+            models::book::Book,
+            models::library_config::LibraryConfig,
+            models::loan::LoanDto,
+            modules::book_files::domain::BookFile,
+            modules::book_files::opds::CreateOpdsTokenRequest,
+            modules::book_files::tokens::OpdsToken,
+            modules::book_club::domain::BookClub,
+            modules::book_club::domain::ClubMember,
+            modules::book_club::domain::Proposal,
+            modules::book_club::domain::Milestone,
+            modules::book_club::domain::ClubState,
+            modules::book_club::domain::CreateClubInput,
+            modules::book_club::domain::SetMembersInput,
+            modules::book_club::domain::ProposalInput,
+            modules::book_club::domain::VoteInput,
+            modules::book_club::domain::SelectReadInput,
+            modules::book_club::domain::MilestoneInput,
+            modules::book_club::domain::ScheduleInput,
+            modules::book_notes::domain::BookNote,
+            modules::book_notes::domain::CreateBookNoteInput,
+            modules::book_notes::domain::UpdateBookNoteInput,
+            api::batch::SortCriteria,
         )
     ),
+    modifiers(&BearerAuth),
     tags(
-        (name = "bibliogenius", description = "BiblioGenius API")
+        (name = "system", description = "Liveness"),
+        (name = "books", description = "Catalogue, covers, search and batch edits"),
+        (name = "copies", description = "Physical copies of a book"),
+        (name = "weeding", description = "Flagging copies for weeding, withdrawal and the deaccession report"),
+        (name = "collections", description = "Collections and series"),
+        (name = "authors", description = "Authors, their works and authority links"),
+        (name = "reports", description = "Catalogue quality: records to complete or clean up after an import"),
+        (name = "subject-authorities", description = "Controlled subject vocabularies (RAMEAU, LCSH): import, validation, auto-complete and unauthorized headings"),
+        (name = "tags", description = "Tag hierarchy"),
+        (name = "contacts", description = "Borrowers who are not BiblioGenius peers"),
+        (name = "loans", description = "Loans to contacts and loan duration settings"),
+        (name = "sales", description = "Bookseller sales"),
+        (name = "acquisitions", description = "Purchase orders, their receipt into the catalogue, budget reports, and patrons' suggestions"),
+        (name = "periodicals", description = "Magazines and journals: ISSN, issue check-in and holdings"),
+        (name = "files", description = "Ebook and audiobook attachments, OPDS feed"),
+        (name = "notes", description = "Reading notes"),
+        (name = "metadata", description = "Background metadata fill and its undo journal"),
+        (name = "integrations", description = "External catalogues, ISBN lookup and MCP"),
+        (name = "data", description = "Export and import"),
+        (name = "library", description = "Library configuration and profile"),
+        (name = "setup", description = "First run, identity, reset and shutdown"),
+        (name = "auth", description = "Login, 2FA and device pairing"),
+        (name = "discovery", description = "mDNS discovery on the LAN"),
+        (name = "network", description = "Network mode: full, LAN-only or offline"),
+        (name = "gamification", description = "Levels, achievements and leaderboards"),
+        (name = "peers", description = "Peer list and connection lifecycle (owner side)"),
+        (name = "peer-transport", description = "Handshake and encrypted messages between libraries"),
+        (name = "peer-catalogue", description = "Browsing, caching and searching peer catalogues"),
+        (name = "blobs", description = "Content-addressed covers and shared files, fetched by peers in resumable chunks"),
+        (name = "book_clubs", description = "Book clubs of connected libraries: proposals, votes and reading schedule"),
+        (name = "announcements", description = "Announcements published to connected peers and received from trusted ones"),
+        (name = "peer-requests", description = "Borrow requests, loan offers and returns between peers"),
+        (name = "relay", description = "Relay mailboxes for peers behind NAT"),
+        (name = "hub", description = "Background sync with the hub directory"),
+        (name = "devices", description = "Sync between one owner's installs: pairing, operation exchange, revocation"),
+        (name = "kiosk", description = "Restricted tokens for a shared lending-desk tablet: search, check-out, check-in"),
+        (name = "portal", description = "Per-contact tokens for patrons: their loans and due dates, renewal requests, the shared catalogue, book suggestions"),
     )
 )]
 pub struct ApiDoc;
+
+/// Declares the `bearer_auth` scheme used by handlers that take [`crate::auth::Claims`].
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+
+    /// `(method, path)` pairs declared by the `.route(...)` calls of a router
+    /// source file, with axum's `:param` rewritten to OpenAPI's `{param}`.
+    fn declared_routes(source: &str) -> Vec<(String, String)> {
+        let mut routes = Vec::new();
+        for chunk in source.split(".route(").skip(1) {
+            let Some(start) = chunk.find('"') else {
+                continue;
+            };
+            let rest = &chunk[start + 1..];
+            let Some(end) = rest.find('"') else {
+                continue;
+            };
+            let raw = &rest[..end];
+            let path = raw
+                .split('/')
+                .map(|seg| match seg.strip_prefix(':') {
+                    Some(name) => format!("{{{name}}}"),
+                    None => seg.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            let path = if path.starts_with("/api/") {
+                path
+            } else {
+                format!("/api{path}")
+            };
+
+            // The handlers sit between the path and the closing `)` of this
+            // `.route(`; the next `.route(` already starts a new chunk.
+            let handlers = &rest[end..];
+            let mut found: Vec<(usize, &str)> = Vec::new();
+            for method in METHODS {
+                let call = format!("{method}(");
+                for (at, _) in handlers.match_indices(&call) {
+                    let before = handlers[..at].chars().next_back();
+                    if !before.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        found.push((at, method));
+                    }
+                }
+            }
+            found.sort_unstable();
+            routes.extend(
+                found
+                    .into_iter()
+                    .map(|(_, method)| (method.to_string(), path.clone())),
+            );
+        }
+        routes
+    }
+
+    fn spec() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).expect("serialize spec")
+    }
+
+    #[test]
+    fn every_api_route_is_documented() {
+        let spec = spec();
+        let mut missing = Vec::new();
+        for source in [
+            include_str!("api/mod.rs"),
+            include_str!("modules/book_club/mod.rs"),
+            include_str!("modules/book_files/mod.rs"),
+            include_str!("modules/book_notes/mod.rs"),
+        ] {
+            for (method, path) in declared_routes(source) {
+                if spec["paths"][&path][&method].is_null() {
+                    missing.push(format!("{} {}", method.to_uppercase(), path));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "undocumented routes: {missing:#?}");
+    }
+
+    #[test]
+    fn route_parser_sees_chained_and_multiline_routes() {
+        let routes = declared_routes(
+            r#".route(
+                "/copies/:id",
+                get(copy::get_copy)
+                    .put(copy::update_copy)
+                    .delete(copy::delete_copy),
+            )
+            .route("/books/reorder", axum::routing::patch(books::reorder_books));"#,
+        );
+        assert_eq!(
+            routes,
+            vec![
+                ("get".to_string(), "/api/copies/{id}".to_string()),
+                ("put".to_string(), "/api/copies/{id}".to_string()),
+                ("delete".to_string(), "/api/copies/{id}".to_string()),
+                ("patch".to_string(), "/api/books/reorder".to_string()),
+            ]
+        );
+    }
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map {
+                    match v {
+                        Value::String(s) if key == "$ref" => refs.push(s.clone()),
+                        _ => collect_refs(v, refs),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        let spec = spec();
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        let dangling: Vec<_> = refs
+            .into_iter()
+            .filter(|r| {
+                let name = r.trim_start_matches("#/components/schemas/");
+                spec["components"]["schemas"][name].is_null()
+            })
+            .collect();
+        assert!(dangling.is_empty(), "unregistered schemas: {dangling:#?}");
+    }
+
+    #[test]
+    fn path_parameters_match_the_template() {
+        let spec = spec();
+        for (path, item) in spec["paths"].as_object().expect("paths") {
+            let expected: Vec<&str> = path
+                .split('/')
+                .filter_map(|seg| seg.strip_prefix('{')?.strip_suffix('}'))
+                .collect();
+            for (method, op) in item.as_object().expect("path item") {
+                let mut declared: Vec<&str> = op["parameters"]
+                    .as_array()
+                    .map(|params| {
+                        params
+                            .iter()
+                            .filter(|p| p["in"] == "path")
+                            .filter_map(|p| p["name"].as_str())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                declared.sort_unstable();
+                let mut want = expected.clone();
+                want.sort_unstable();
+                assert_eq!(declared, want, "{method} {path}");
+            }
+        }
+    }
+
+    #[test]
+    fn claims_handlers_declare_bearer_auth() {
+        let spec = spec();
+        assert!(!spec["components"]["securitySchemes"]["bearer_auth"].is_null());
+        for (method, path) in [
+            ("post", "/api/books"),
+            ("put", "/api/books/{id}"),
+            ("delete", "/api/books/{id}"),
+            ("get", "/api/auth/me"),
+            ("post", "/api/reset"),
+        ] {
+            assert_eq!(
+                spec["paths"][path][method]["security"][0]["bearer_auth"],
+                serde_json::json!([]),
+                "{method} {path}"
+            );
+        }
+    }
+}
@@ -31,6 +31,9 @@ pub struct BookFilter {
     /// Restrict to books belonging to a collection, identified by its uuid or,
     /// failing that, by its exact name (case-insensitive).
     pub collection: Option<String>,
+    /// `Some(true)` keeps only archived books. Otherwise they are left out:
+    /// an archived book is kept in the database but not listed by default.
+    pub archived: Option<bool>,
 }
 
 /// Paginated result with total count
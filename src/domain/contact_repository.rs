@@ -0,0 +1,88 @@
+//! Contact repository trait and related types
+
+use async_trait::async_trait;
+
+use super::DomainError;
+
+/// A contact (borrower, library, ...) as stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub id: String,
+    pub contact_type: String,
+    pub name: String,
+    pub first_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub street_address: Option<String>,
+    pub postal_code: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub notes: Option<String>,
+    pub user_id: Option<i32>,
+    pub library_owner_id: i32,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Membership of an association library (`services::membership`), all
+    /// `None` for a contact who is not a member. Contact writes leave it be.
+    pub member_number: Option<String>,
+    pub member_since: Option<String>,
+    pub membership_expires_at: Option<String>,
+    pub membership_fee_paid: Option<f64>,
+}
+
+/// Input for creating or updating a contact
+#[derive(Debug, Clone, Default)]
+pub struct ContactInput {
+    pub contact_type: String,
+    pub name: String,
+    pub first_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub street_address: Option<String>,
+    pub postal_code: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub notes: Option<String>,
+    pub user_id: Option<i32>,
+    /// Owning library. On create, None resolves to the local library; on
+    /// update, None keeps the current owner.
+    pub library_owner_id: Option<i32>,
+    pub is_active: bool,
+}
+
+/// Filter for listing active contacts
+#[derive(Debug, Clone, Default)]
+pub struct ContactQuery {
+    pub library_id: Option<i32>,
+    pub contact_type: Option<String>,
+}
+
+/// Repository trait for Contact entity
+#[async_trait]
+pub trait ContactRepository: Send + Sync {
+    /// List active contacts matching the query
+    async fn find_active(&self, query: &ContactQuery) -> Result<Vec<Contact>, DomainError>;
+
+    /// Find a contact by ID (its cross-device uuid)
+    async fn find_by_id(&self, id: &str) -> Result<Option<Contact>, DomainError>;
+
+    /// Create a contact. Fails with `Validation` when `library_owner_id`
+    /// names a library that does not exist.
+    async fn create(&self, input: ContactInput) -> Result<Contact, DomainError>;
+
+    /// Replace the fields of an existing contact
+    async fn update(&self, id: &str, input: ContactInput) -> Result<Contact, DomainError>;
+
+    /// Soft-delete a contact (`is_active = false`)
+    async fn deactivate(&self, id: &str) -> Result<(), DomainError>;
+
+    /// Count all contacts, active or not
+    async fn count(&self) -> Result<i64, DomainError>;
+}
@@ -36,6 +36,54 @@ impl std::str::FromStr for BorrowSource {
     }
 }
 
+/// Lending terms a lender attaches to a copy: how long it may be borrowed,
+/// whether the loan can be renewed and what replacing it would cost.
+///
+/// Stored as JSON in `copies.lending_terms` and snapshotted onto
+/// `loans.lending_terms` when the copy is lent, so later edits to the copy do
+/// not rewrite an agreement already in progress. The same snapshot travels in
+/// the P2P loan confirmation, so the borrower sees exactly the lender's terms.
+#[derive(
+    Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize, utoipa::ToSchema,
+)]
+pub struct LendingTerms {
+    /// Upper bound on the loan duration, in days. Caps the duration resolved
+    /// from the loan settings; `None` leaves it untouched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_days: Option<i32>,
+    /// Whether the borrower may ask for an extension.
+    #[serde(default)]
+    pub renewable: bool,
+    /// Replacement value, in the library currency (`library_config.currency`),
+    /// owed if the copy is lost or damaged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement_value: Option<f64>,
+    /// Free-text conditions ("no annotations", "keep away from the bath").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<String>,
+}
+
+impl LendingTerms {
+    /// Parse the JSON stored in a `lending_terms` column. Unreadable JSON is
+    /// treated as "no terms" rather than failing the read.
+    pub fn from_column(value: Option<&str>) -> Option<Self> {
+        serde_json::from_str(value?).ok()
+    }
+
+    /// Serialize for a `lending_terms` column.
+    pub fn to_column(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Apply `max_duration_days` to a resolved loan duration.
+    pub fn cap_duration(&self, days: i64) -> i64 {
+        match self.max_duration_days {
+            Some(max) if max > 0 => days.min(max as i64),
+            _ => days,
+        }
+    }
+}
+
 /// Copy data for API responses
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Copy {
@@ -56,6 +104,9 @@ pub struct Copy {
     pub lender_peer_id: Option<i32>,
     pub borrow_due_date: Option<String>,
     pub borrow_source: Option<String>,
+    /// Lending terms set by the owner, or received from the lender for a
+    /// borrowed copy.
+    pub lending_terms: Option<LendingTerms>,
 }
 
 /// Paginated copies result
@@ -96,6 +147,7 @@ pub struct UpdateCopyInput {
     pub lender_peer_id: Option<Option<i32>>,
     pub borrow_due_date: Option<Option<String>>,
     pub borrow_source: Option<Option<String>>,
+    pub lending_terms: Option<Option<LendingTerms>>,
 }
 
 /// Repository trait for Copy entity
@@ -120,6 +172,40 @@ pub trait CopyRepository: Send + Sync {
     /// Update a copy
     async fn update(&self, id: &str, input: UpdateCopyInput) -> Result<Copy, DomainError>;
 
+    /// Set the status of a copy ("loaned" when it is lent, "available" when
+    /// it comes back), recording the change for sync
+    async fn set_status(&self, id: &str, status: &str) -> Result<Copy, DomainError>;
+
     /// Delete a copy
     async fn delete(&self, id: &str) -> Result<(), DomainError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lending_terms_round_trip_through_column() {
+        let terms = LendingTerms {
+            max_duration_days: Some(14),
+            renewable: true,
+            replacement_value: Some(25.0),
+            conditions: Some("No annotations".to_string()),
+        };
+        let stored = terms.to_column();
+        assert_eq!(LendingTerms::from_column(Some(&stored)), Some(terms));
+        assert_eq!(LendingTerms::from_column(None), None);
+        assert_eq!(LendingTerms::from_column(Some("not json")), None);
+    }
+
+    #[test]
+    fn cap_duration_only_shortens() {
+        let terms = LendingTerms {
+            max_duration_days: Some(14),
+            ..Default::default()
+        };
+        assert_eq!(terms.cap_duration(21), 14);
+        assert_eq!(terms.cap_duration(7), 7);
+        assert_eq!(LendingTerms::default().cap_duration(21), 21);
+    }
+}
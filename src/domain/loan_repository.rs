@@ -0,0 +1,79 @@
+//! Loan repository trait and related types
+
+use async_trait::async_trait;
+
+use super::DomainError;
+
+/// A loan of a copy to a contact
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Loan {
+    pub id: String,
+    pub copy_id: String,
+    pub contact_id: String,
+    pub library_id: i32,
+    pub loan_date: String,
+    pub due_date: String,
+    pub return_date: Option<String>,
+    /// `active`, `returned`, `overdue` or `lost`
+    pub status: String,
+    pub notes: Option<String>,
+    /// JSON snapshot of the copy's lending terms when it was lent
+    pub lending_terms: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Input for recording an active loan
+#[derive(Debug, Clone, Default)]
+pub struct NewLoan {
+    pub copy_id: String,
+    pub contact_id: String,
+    pub library_id: i32,
+    pub loan_date: String,
+    pub due_date: String,
+    pub notes: Option<String>,
+    /// JSON snapshot of the copy's lending terms
+    pub lending_terms: Option<String>,
+    /// Creation time, in the library's time zone
+    pub created_at: String,
+}
+
+/// Filter for listing loans
+#[derive(Debug, Clone, Default)]
+pub struct LoanQuery {
+    pub library_id: Option<i32>,
+    pub status: Option<String>,
+    pub contact_id: Option<String>,
+    /// Cap the number of loans; `None` lists every match
+    pub limit: Option<u64>,
+    /// Loans to skip, applied with `limit`
+    pub offset: Option<u64>,
+}
+
+/// Repository trait for Loan entity
+#[async_trait]
+pub trait LoanRepository: Send + Sync {
+    /// Find a loan by ID (its cross-device uuid)
+    async fn find_by_id(&self, id: &str) -> Result<Option<Loan>, DomainError>;
+
+    /// List the loans of a contact, most recent first
+    async fn find_by_contact(&self, contact_id: &str) -> Result<Vec<Loan>, DomainError>;
+
+    /// List the loans matching the query, most recent first
+    async fn find_all(&self, query: &LoanQuery) -> Result<Vec<Loan>, DomainError>;
+
+    /// Record an active loan
+    async fn create(&self, input: NewLoan) -> Result<Loan, DomainError>;
+
+    /// Close a loan as returned at `at`
+    async fn mark_returned(&self, id: &str, at: &str) -> Result<Loan, DomainError>;
+
+    /// Move the due date of a loan, updated at `at`
+    async fn set_due_date(&self, id: &str, due_date: &str, at: &str) -> Result<Loan, DomainError>;
+
+    /// Count loans, all of them or only those with `status`
+    async fn count(&self, status: Option<&str>) -> Result<i64, DomainError>;
+
+    /// Delete every loan with `status`, returns the number of deleted rows
+    async fn delete_with_status(&self, status: &str) -> Result<u64, DomainError>;
+}
@@ -1,15 +1,125 @@
 //! Loan settings repository trait
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 
 use super::DomainError;
 
 /// Loan settings (global configuration)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct LoanSettings {
     pub default_loan_duration_days: i32,
     pub per_book_duration_enabled: bool,
     pub reminder_days_before_due: i32,
+    /// Days after the due date before a loan is reported as overdue.
+    pub grace_period_days: i32,
+    /// Loan duration per contact type (`Borrower`, `Library`, ...), overriding
+    /// the global default for borrowers of that type.
+    pub contact_type_durations: BTreeMap<String, i32>,
+    /// Days an accepted P2P request stays on the hold shelf before the
+    /// reserved copy is released back to available.
+    pub hold_expiry_days: i32,
+    /// Days a P2P request (incoming or outgoing) may stay pending before it
+    /// is marked expired. 0 disables expiry.
+    pub request_expiry_days: i32,
+    /// Days a resolved P2P request (rejected, returned, expired, ...) is kept
+    /// before the periodic cleanup deletes it. 0 keeps them until cleared by
+    /// hand.
+    pub resolved_request_retention_days: i32,
+    /// Fine accrued per day a loan is overdue, past the grace period. 0
+    /// disables fines.
+    pub fine_per_day: f64,
+    /// Cap on the fine of one loan. 0 means no cap.
+    pub max_fine: f64,
+}
+
+impl Default for LoanSettings {
+    fn default() -> Self {
+        Self {
+            default_loan_duration_days: 21,
+            per_book_duration_enabled: false,
+            reminder_days_before_due: 2,
+            grace_period_days: 0,
+            contact_type_durations: BTreeMap::new(),
+            hold_expiry_days: 7,
+            request_expiry_days: 30,
+            resolved_request_retention_days: 90,
+            fine_per_day: 0.0,
+            max_fine: 0.0,
+        }
+    }
+}
+
+impl LoanSettings {
+    /// Resolve a loan duration from the overrides that apply to one loan, most
+    /// specific first: the book (when per-book durations are enabled), the
+    /// peer, the borrower's contact type, then the global default.
+    pub fn resolve_duration(
+        &self,
+        book_days: Option<i32>,
+        peer_days: Option<i32>,
+        contact_type: Option<&str>,
+    ) -> i32 {
+        book_days
+            .filter(|_| self.per_book_duration_enabled)
+            .or(peer_days)
+            .or_else(|| contact_type.and_then(|t| self.contact_type_durations.get(t).copied()))
+            .unwrap_or(self.default_loan_duration_days)
+    }
+
+    /// The settings brought into their accepted ranges, as `update_settings`
+    /// stores them. Blank contact types are dropped.
+    pub fn normalized(self) -> Self {
+        Self {
+            default_loan_duration_days: self.default_loan_duration_days.clamp(1, 365),
+            per_book_duration_enabled: self.per_book_duration_enabled,
+            reminder_days_before_due: self.reminder_days_before_due.clamp(1, 10),
+            grace_period_days: self.grace_period_days.clamp(0, 30),
+            contact_type_durations: self
+                .contact_type_durations
+                .into_iter()
+                .filter(|(t, _)| !t.trim().is_empty())
+                .map(|(t, d)| (t.trim().to_string(), d.clamp(1, 365)))
+                .collect(),
+            hold_expiry_days: self.hold_expiry_days.clamp(1, 30),
+            request_expiry_days: self.request_expiry_days.clamp(0, 365),
+            resolved_request_retention_days: self.resolved_request_retention_days.clamp(0, 365),
+            fine_per_day: self.fine_per_day.max(0.0),
+            max_fine: self.max_fine.max(0.0),
+        }
+    }
+
+    /// Whether a loan due on `due` is overdue on `today`, once the grace
+    /// period has run out.
+    pub fn is_overdue(&self, due: chrono::NaiveDate, today: chrono::NaiveDate) -> bool {
+        (today - due).num_days() > self.grace_period_days as i64
+    }
+
+    /// Fine of a loan due on `due` and kept until `until` (its return, or
+    /// today while it is out): one `fine_per_day` per day past the grace
+    /// period, capped at `max_fine`, rounded to the cent.
+    pub fn fine(&self, due: chrono::NaiveDate, until: chrono::NaiveDate) -> f64 {
+        let days = (until - due).num_days() - self.grace_period_days as i64;
+        if self.fine_per_day <= 0.0 || days <= 0 {
+            return 0.0;
+        }
+        let mut fine = self.fine_per_day * days as f64;
+        if self.max_fine > 0.0 {
+            fine = fine.min(self.max_fine);
+        }
+        (fine * 100.0).round() / 100.0
+    }
+}
+
+/// The borrower side of a loan, as far as the duration policy cares.
+#[derive(Debug, Clone, Default)]
+pub struct LoanBorrower {
+    /// `contacts.type` of the borrower (P2P loans go to `Library` contacts).
+    pub contact_type: Option<String>,
+    /// `peers.id` when the borrower is a P2P peer.
+    pub peer_id: Option<i32>,
 }
 
 /// Repository trait for loan duration settings
@@ -31,7 +141,88 @@ pub trait LoanSettingsRepository: Send + Sync {
         days: Option<i32>,
     ) -> Result<(), DomainError>;
 
+    /// Get the per-peer loan duration override (None = no override)
+    async fn get_peer_loan_duration(&self, peer_id: i32) -> Result<Option<i32>, DomainError>;
+
+    /// Set the per-peer loan duration override (None = clear)
+    async fn set_peer_loan_duration(
+        &self,
+        peer_id: i32,
+        days: Option<i32>,
+    ) -> Result<(), DomainError>;
+
+    /// The library's time zone (`library_config.timezone`), in which loan
+    /// dates are counted. `None` when unset: the host's zone applies.
+    async fn time_zone(&self) -> Result<Option<String>, DomainError>;
+
+    /// Get the effective loan duration for lending a book to `borrower`
+    /// (see [`LoanSettings::resolve_duration`] for the precedence).
+    async fn get_effective_duration_for(
+        &self,
+        book_id: &str,
+        borrower: &LoanBorrower,
+    ) -> Result<i32, DomainError>;
+
     /// Get the effective loan duration for a book:
     /// per-book value (if per_book_duration_enabled and set), else global default.
-    async fn get_effective_duration(&self, book_id: &str) -> Result<i32, DomainError>;
+    async fn get_effective_duration(&self, book_id: &str) -> Result<i32, DomainError> {
+        self.get_effective_duration_for(book_id, &LoanBorrower::default())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> LoanSettings {
+        LoanSettings {
+            per_book_duration_enabled: true,
+            grace_period_days: 3,
+            contact_type_durations: BTreeMap::from([("Library".to_string(), 30)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_duration_prefers_the_most_specific_override() {
+        let s = settings();
+        assert_eq!(s.resolve_duration(Some(7), Some(14), Some("Library")), 7);
+        assert_eq!(s.resolve_duration(None, Some(14), Some("Library")), 14);
+        assert_eq!(s.resolve_duration(None, None, Some("Library")), 30);
+        assert_eq!(s.resolve_duration(None, None, Some("Borrower")), 21);
+        assert_eq!(s.resolve_duration(None, None, None), 21);
+
+        let no_per_book = LoanSettings {
+            per_book_duration_enabled: false,
+            ..settings()
+        };
+        assert_eq!(no_per_book.resolve_duration(Some(7), None, None), 21);
+    }
+
+    #[test]
+    fn overdue_only_after_the_grace_period() {
+        let s = settings();
+        let due = chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        assert!(!s.is_overdue(due, day(10)));
+        assert!(!s.is_overdue(due, day(13)));
+        assert!(s.is_overdue(due, day(14)));
+    }
+
+    #[test]
+    fn fines_start_after_the_grace_period_and_stop_at_the_cap() {
+        let due = chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let day = |d| chrono::NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        assert_eq!(settings().fine(due, day(20)), 0.0);
+
+        let s = LoanSettings {
+            fine_per_day: 0.15,
+            max_fine: 1.0,
+            ..settings()
+        };
+        assert_eq!(s.fine(due, day(13)), 0.0);
+        assert_eq!(s.fine(due, day(15)), 0.3);
+        assert_eq!(s.fine(due, day(30)), 1.0);
+    }
 }
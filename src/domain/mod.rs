@@ -8,21 +8,31 @@ pub mod errors;
 pub mod author_repository;
 pub mod book_repository;
 pub mod collection_repository;
+pub mod contact_repository;
 pub mod copy_repository;
 pub mod gamification_repository;
 pub mod linked_device_repository;
+pub mod loan_repository;
 pub mod loan_settings_repository;
 pub mod metadata_fill;
 pub mod notification_repository;
+pub mod p2p_request_repository;
+pub mod peer_repository;
+pub mod tag_repository;
 
 pub use errors::DomainError;
 
 pub use author_repository::*;
 pub use book_repository::*;
 pub use collection_repository::*;
+pub use contact_repository::*;
 pub use copy_repository::*;
 pub use gamification_repository::*;
 pub use linked_device_repository::*;
+pub use loan_repository::*;
 pub use loan_settings_repository::*;
 pub use metadata_fill::*;
 pub use notification_repository::*;
+pub use p2p_request_repository::*;
+pub use peer_repository::*;
+pub use tag_repository::*;
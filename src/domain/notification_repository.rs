@@ -56,13 +56,20 @@ pub enum NotificationEventType {
     BorrowRequest,
     BorrowAccepted,
     BorrowRejected,
+    BorrowReady,
+    BorrowHoldExpired,
     BookReturned,
     BookReclaimed,
+    ReturnConfirmed,
     LoanDueReminder,
     LoanDueToday,
+    RenewalRequest,
     // Discoveries
     NewBooks,
     WishlistMatch,
+    SavedSearchMatch,
+    BookSuggestion,
+    Announcement,
     // System
     Welcome,
 }
@@ -77,12 +84,19 @@ impl NotificationEventType {
             Self::BorrowRequest => "borrow_request",
             Self::BorrowAccepted => "borrow_accepted",
             Self::BorrowRejected => "borrow_rejected",
+            Self::BorrowReady => "borrow_ready",
+            Self::BorrowHoldExpired => "borrow_hold_expired",
             Self::BookReturned => "book_returned",
             Self::BookReclaimed => "book_reclaimed",
+            Self::ReturnConfirmed => "return_confirmed",
             Self::LoanDueReminder => "loan_due_reminder",
             Self::LoanDueToday => "loan_due_today",
+            Self::RenewalRequest => "renewal_request",
             Self::NewBooks => "new_books",
             Self::WishlistMatch => "wishlist_match",
+            Self::SavedSearchMatch => "saved_search_match",
+            Self::BookSuggestion => "book_suggestion",
+            Self::Announcement => "announcement",
             Self::Welcome => "welcome",
         }
     }
@@ -96,11 +110,19 @@ impl NotificationEventType {
             Self::BorrowRequest
             | Self::BorrowAccepted
             | Self::BorrowRejected
+            | Self::BorrowReady
+            | Self::BorrowHoldExpired
             | Self::BookReturned
             | Self::BookReclaimed
+            | Self::ReturnConfirmed
             | Self::LoanDueReminder
-            | Self::LoanDueToday => NotificationCategory::Loans,
-            Self::NewBooks | Self::WishlistMatch => NotificationCategory::Discoveries,
+            | Self::LoanDueToday
+            | Self::RenewalRequest => NotificationCategory::Loans,
+            Self::NewBooks
+            | Self::WishlistMatch
+            | Self::SavedSearchMatch
+            | Self::BookSuggestion
+            | Self::Announcement => NotificationCategory::Discoveries,
             Self::Welcome => NotificationCategory::System,
         }
     }
@@ -114,12 +136,19 @@ impl NotificationEventType {
             "borrow_request" => Some(Self::BorrowRequest),
             "borrow_accepted" => Some(Self::BorrowAccepted),
             "borrow_rejected" => Some(Self::BorrowRejected),
+            "borrow_ready" => Some(Self::BorrowReady),
+            "borrow_hold_expired" => Some(Self::BorrowHoldExpired),
             "book_returned" => Some(Self::BookReturned),
             "book_reclaimed" => Some(Self::BookReclaimed),
+            "return_confirmed" => Some(Self::ReturnConfirmed),
             "loan_due_reminder" => Some(Self::LoanDueReminder),
             "loan_due_today" => Some(Self::LoanDueToday),
+            "renewal_request" => Some(Self::RenewalRequest),
             "new_books" => Some(Self::NewBooks),
             "wishlist_match" => Some(Self::WishlistMatch),
+            "saved_search_match" => Some(Self::SavedSearchMatch),
+            "book_suggestion" => Some(Self::BookSuggestion),
+            "announcement" => Some(Self::Announcement),
             "welcome" => Some(Self::Welcome),
             _ => None,
         }
@@ -229,12 +258,19 @@ mod tests {
             NotificationEventType::BorrowRequest,
             NotificationEventType::BorrowAccepted,
             NotificationEventType::BorrowRejected,
+            NotificationEventType::BorrowReady,
+            NotificationEventType::BorrowHoldExpired,
             NotificationEventType::BookReturned,
             NotificationEventType::BookReclaimed,
+            NotificationEventType::ReturnConfirmed,
             NotificationEventType::LoanDueReminder,
             NotificationEventType::LoanDueToday,
+            NotificationEventType::RenewalRequest,
             NotificationEventType::NewBooks,
             NotificationEventType::WishlistMatch,
+            NotificationEventType::SavedSearchMatch,
+            NotificationEventType::BookSuggestion,
+            NotificationEventType::Announcement,
         ];
         for evt in all {
             let s = evt.as_str();
@@ -265,6 +301,14 @@ mod tests {
             NotificationEventType::BorrowAccepted.category(),
             NotificationCategory::Loans
         );
+        assert_eq!(
+            NotificationEventType::BorrowReady.category(),
+            NotificationCategory::Loans
+        );
+        assert_eq!(
+            NotificationEventType::BorrowHoldExpired.category(),
+            NotificationCategory::Loans
+        );
         assert_eq!(
             NotificationEventType::BookReturned.category(),
             NotificationCategory::Loans
@@ -277,6 +321,10 @@ mod tests {
             NotificationEventType::LoanDueToday.category(),
             NotificationCategory::Loans
         );
+        assert_eq!(
+            NotificationEventType::RenewalRequest.category(),
+            NotificationCategory::Loans
+        );
         assert_eq!(
             NotificationEventType::NewBooks.category(),
             NotificationCategory::Discoveries
@@ -285,6 +333,14 @@ mod tests {
             NotificationEventType::WishlistMatch.category(),
             NotificationCategory::Discoveries
         );
+        assert_eq!(
+            NotificationEventType::SavedSearchMatch.category(),
+            NotificationCategory::Discoveries
+        );
+        assert_eq!(
+            NotificationEventType::BookSuggestion.category(),
+            NotificationCategory::Discoveries
+        );
         assert_eq!(
             NotificationEventType::Welcome.category(),
             NotificationCategory::System
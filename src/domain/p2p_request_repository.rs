@@ -0,0 +1,36 @@
+//! P2P request repository trait
+//!
+//! Incoming requests are the ones peers sent us (`p2p_requests`), outgoing
+//! the ones we sent (`p2p_outgoing_requests`). A request is closed once
+//! nothing is left to act on; the history screens let the user clear those.
+
+use async_trait::async_trait;
+
+use super::DomainError;
+
+/// Statuses of an incoming request that is still open: pending, waitlisted,
+/// or on the hold shelf (`ready` still reserves a copy)
+pub const OPEN_INCOMING_STATUSES: [&str; 3] = ["pending", "waitlisted", "ready"];
+
+/// Statuses of an outgoing request that is still open: pending, waitlisted,
+/// or awaiting a return acknowledgment (which is matched to the request)
+pub const OPEN_OUTGOING_STATUSES: [&str; 3] = ["pending", "waitlisted", "return_pending"];
+
+/// Repository trait for P2P borrow requests
+#[async_trait]
+pub trait P2pRequestRepository: Send + Sync {
+    /// Count closed incoming requests
+    async fn count_closed_incoming(&self) -> Result<u64, DomainError>;
+
+    /// Delete closed incoming requests, returns the number of deleted rows
+    async fn delete_closed_incoming(&self) -> Result<u64, DomainError>;
+
+    /// Count closed outgoing requests
+    async fn count_closed_outgoing(&self) -> Result<u64, DomainError>;
+
+    /// Delete closed outgoing requests, returns the number of deleted rows
+    async fn delete_closed_outgoing(&self) -> Result<u64, DomainError>;
+
+    /// Set the status of an outgoing request
+    async fn set_outgoing_status(&self, id: &str, status: &str) -> Result<(), DomainError>;
+}
@@ -0,0 +1,40 @@
+//! Peer repository trait and related types
+
+use async_trait::async_trait;
+
+use super::DomainError;
+
+/// A remote library this node knows about. Transport details (keys, relay
+/// mailbox, tokens) stay on the storage model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub id: i32,
+    pub name: String,
+    pub display_name: Option<String>,
+    pub url: String,
+    pub library_uuid: Option<String>,
+    pub connection_status: String,
+    pub last_seen: Option<String>,
+    pub auto_approve: bool,
+    /// Loan duration override for loans to this peer
+    pub loan_duration_days: Option<i32>,
+}
+
+/// Repository trait for Peer entity
+#[async_trait]
+pub trait PeerRepository: Send + Sync {
+    /// List all peers
+    async fn find_all(&self) -> Result<Vec<Peer>, DomainError>;
+
+    /// Find a peer by ID
+    async fn find_by_id(&self, id: i32) -> Result<Option<Peer>, DomainError>;
+
+    /// Find a peer by name (P2P borrowers are `Library` contacts named after it)
+    async fn find_by_name(&self, name: &str) -> Result<Option<Peer>, DomainError>;
+
+    /// Find a peer by base URL
+    async fn find_by_url(&self, url: &str) -> Result<Option<Peer>, DomainError>;
+
+    /// Find a peer by the library UUID it announced
+    async fn find_by_library_uuid(&self, library_uuid: &str) -> Result<Option<Peer>, DomainError>;
+}
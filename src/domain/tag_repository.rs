@@ -0,0 +1,42 @@
+//! Tag repository trait and related types
+
+use async_trait::async_trait;
+
+use super::DomainError;
+
+/// A tag (shelf) in the tag hierarchy
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    /// Names of the ancestors, root first, joined by ` > ` (empty for a root tag)
+    pub path: String,
+    pub private: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Repository trait for Tag entity
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    /// List all tags, ordered by name
+    async fn find_all(&self) -> Result<Vec<Tag>, DomainError>;
+
+    /// Find a tag by ID (its cross-device uuid)
+    async fn find_by_id(&self, id: &str) -> Result<Option<Tag>, DomainError>;
+
+    /// Create a tag
+    async fn create(&self, name: String, parent_id: Option<String>) -> Result<Tag, DomainError>;
+
+    /// Rename and/or move a tag
+    async fn update(
+        &self,
+        id: &str,
+        name: String,
+        parent_id: Option<String>,
+    ) -> Result<Tag, DomainError>;
+
+    /// Delete a tag, unlinking its books and re-parenting its children to root
+    async fn delete(&self, id: &str) -> Result<(), DomainError>;
+}
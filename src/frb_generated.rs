@@ -7705,6 +7705,9 @@ impl SseDecode for crate::api::frb::FrbBook {
         let mut var_digitalFormats = <Option<Vec<String>>>::sse_decode(deserializer);
         let mut var_private = <bool>::sse_decode(deserializer);
         let mut var_pageCount = <Option<i32>>::sse_decode(deserializer);
+        let mut var_edition = <Option<String>>::sse_decode(deserializer);
+        let mut var_physicalFormat = <Option<String>>::sse_decode(deserializer);
+        let mut var_dimensions = <Option<String>>::sse_decode(deserializer);
         let mut var_addedAt = <Option<String>>::sse_decode(deserializer);
         let mut var_hubCoverUploadFailedAt = <Option<String>>::sse_decode(deserializer);
         let mut var_isBorrowed = <Option<bool>>::sse_decode(deserializer);
@@ -7732,6 +7735,9 @@ impl SseDecode for crate::api::frb::FrbBook {
             digital_formats: var_digitalFormats,
             private: var_private,
             page_count: var_pageCount,
+            edition: var_edition,
+            physical_format: var_physicalFormat,
+            dimensions: var_dimensions,
             added_at: var_addedAt,
             hub_cover_upload_failed_at: var_hubCoverUploadFailedAt,
             is_borrowed: var_isBorrowed,
@@ -10162,6 +10168,9 @@ impl flutter_rust_bridge::IntoDart for crate::api::frb::FrbBook {
             self.digital_formats.into_into_dart().into_dart(),
             self.private.into_into_dart().into_dart(),
             self.page_count.into_into_dart().into_dart(),
+            self.edition.into_into_dart().into_dart(),
+            self.physical_format.into_into_dart().into_dart(),
+            self.dimensions.into_into_dart().into_dart(),
             self.added_at.into_into_dart().into_dart(),
             self.hub_cover_upload_failed_at.into_into_dart().into_dart(),
             self.is_borrowed.into_into_dart().into_dart(),
@@ -11618,6 +11627,9 @@ impl SseEncode for crate::api::frb::FrbBook {
         <Option<Vec<String>>>::sse_encode(self.digital_formats, serializer);
         <bool>::sse_encode(self.private, serializer);
         <Option<i32>>::sse_encode(self.page_count, serializer);
+        <Option<String>>::sse_encode(self.edition, serializer);
+        <Option<String>>::sse_encode(self.physical_format, serializer);
+        <Option<String>>::sse_encode(self.dimensions, serializer);
         <Option<String>>::sse_encode(self.added_at, serializer);
         <Option<String>>::sse_encode(self.hub_cover_upload_failed_at, serializer);
         <Option<bool>>::sse_encode(self.is_borrowed, serializer);
@@ -15,7 +15,7 @@ use axum::{
 use serde_json::json;
 use std::net::SocketAddr;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // username
     pub role: String,
@@ -104,8 +104,26 @@ where
 ///
 /// Guard any loopback endpoint that returns owner data or secrets with this rather
 /// than with [`LoopbackOnly`] alone.
+///
+/// With the `admin-ui` feature, a browser page this very server serves on a
+/// loopback address (the embedded admin UI at `/admin`) is admitted too: see
+/// [`is_own_loopback_origin`].
 pub struct LoopbackNoBrowser;
 
+/// Whether `origin` is this server as reached on a loopback address, i.e. a
+/// page it served itself.
+///
+/// The `Origin` must equal `http://<Host>` and the host must be a loopback
+/// literal. A DNS-rebinding page carries its own domain, and a page of another
+/// local server another port, so neither matches.
+pub fn is_own_loopback_origin(origin: &str, host: &str) -> bool {
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(hostname, "127.0.0.1" | "localhost" | "[::1]") && origin == format!("http://{host}")
+}
+
 #[async_trait]
 impl<S> FromRequestParts<S> for LoopbackNoBrowser
 where
@@ -116,7 +134,15 @@ where
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         LoopbackOnly::from_request_parts(parts, state).await?;
 
-        if parts.headers.contains_key("origin") {
+        if let Some(origin) = parts.headers.get("origin") {
+            if cfg!(feature = "admin-ui") {
+                let host = parts.headers.get("host").and_then(|h| h.to_str().ok());
+                if let (Ok(origin), Some(host)) = (origin.to_str(), host)
+                    && is_own_loopback_origin(origin, host)
+                {
+                    return Ok(LoopbackNoBrowser);
+                }
+            }
             return Err((
                 StatusCode::FORBIDDEN,
                 Json(json!({ "error": "This endpoint is not callable from a browser" })),
@@ -260,3 +286,24 @@ pub fn decode_jwt(token: &str) -> Result<Claims, String> {
     .map(|data| data.claims)
     .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_this_server_on_loopback_is_its_own_origin() {
+        for host in ["127.0.0.1:8000", "localhost:8000", "[::1]:8000"] {
+            assert!(is_own_loopback_origin(&format!("http://{host}"), host));
+        }
+        // Another local server, a rebound domain, a LAN address, a sandbox.
+        for (origin, host) in [
+            ("http://localhost:3000", "localhost:8000"),
+            ("http://evil.example:8000", "evil.example:8000"),
+            ("http://192.168.1.2:8000", "192.168.1.2:8000"),
+            ("null", "127.0.0.1:8000"),
+        ] {
+            assert!(!is_own_loopback_origin(origin, host), "{origin}");
+        }
+    }
+}
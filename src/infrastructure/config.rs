@@ -1,38 +1,475 @@
+//! Server configuration, layered: built-in defaults < `config.toml` < environment.
+//!
+//! The file is `$BIBLIOGENIUS_CONFIG` when set, otherwise `config.toml` in the
+//! data directory (`$BIBLIOGENIUS_DATA_DIR`, or the working directory; see
+//! `paths`), where the default database lives too. A missing file is fine; a
+//! malformed one is an error, so a typo never silently falls back to defaults.
+//!
+//! ```toml
+//! profile = "default"
+//! database_url = "sqlite://bibliogenius.db?mode=rwc"
+//! port = 8000
+//! hub_url = "https://hub.example.org"
+//! cors_allowed_origins = ["http://localhost:3000"]
+//! image_max_dimension = 3000
+//! image_storage_quota_mb = 500
+//! max_import_upload_mb = 20
+//! max_image_upload_mb = 10
+//! scan_training_data = false
+//! speech_backend = "whisper"
+//! speech_model = "/opt/whisper/ggml-base.bin"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Config {
+    pub profile: String,
     pub database_url: String,
     pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hub_url: Option<String>,
     pub cors_allowed_origins: Vec<String>,
-    pub profile: String,
+    /// Longest edge, in pixels, an uploaded or scanned image is kept at.
+    pub image_max_dimension: u32,
+    /// Space the local covers may take, in MB; unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_storage_quota_mb: Option<u64>,
+    /// Largest import file (`/api/import/file`, collection imports), in MB.
+    pub max_import_upload_mb: u64,
+    /// Largest uploaded image (covers, scans), in MB; at most 10, the most
+    /// the image decoder accepts.
+    pub max_image_upload_mb: u64,
+    /// Keep the titles users correct after a scan, with the OCR text, for
+    /// export as training data (`services::scan_training`). Off by default.
+    pub scan_training_data: bool,
+    /// Speech-to-text for voice search: `whisper` (the whisper.cpp CLI) or
+    /// `api` (an OpenAI-compatible transcription endpoint); off when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_backend: Option<String>,
+    /// The whisper.cpp executable (default `whisper-cli`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_command: Option<String>,
+    /// The ggml model file for `whisper`, the model name for `api`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_model: Option<String>,
+    /// Base URL of the `api` backend, e.g. `https://api.openai.com/v1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech_api_url: Option<String>,
+    /// Where each value above came from, by key.
+    #[serde(skip)]
+    pub sources: BTreeMap<&'static str, ConfigSource>,
+    /// The file that was read, if any.
+    #[serde(skip)]
+    pub config_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+}
+
+/// The keys `config.toml` accepts; anything else is rejected.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    profile: Option<String>,
+    database_url: Option<String>,
+    port: Option<u16>,
+    hub_url: Option<String>,
+    cors_allowed_origins: Option<Vec<String>>,
+    image_max_dimension: Option<u32>,
+    image_storage_quota_mb: Option<u64>,
+    max_import_upload_mb: Option<u64>,
+    max_image_upload_mb: Option<u64>,
+    scan_training_data: Option<bool>,
+    speech_backend: Option<String>,
+    speech_command: Option<String>,
+    speech_model: Option<String>,
+    speech_api_url: Option<String>,
 }
 
+#[derive(Debug)]
+pub enum ConfigError {
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    Env {
+        key: &'static str,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read { path, source } => {
+                write!(f, "cannot read {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "invalid {}: {}", path.display(), source)
+            }
+            ConfigError::Env { key, value } => write!(f, "invalid {}: {:?}", key, value),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+static RUNTIME: OnceLock<Config> = OnceLock::new();
+
 impl Config {
-    pub fn from_env() -> Self {
-        let profile = env::var("PROFILE").unwrap_or_else(|_| "default".to_string());
+    /// Load the layered configuration from the config file and the environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = config_file_path();
+        let file = read_file(&path)?;
+        let found = file.is_some().then_some(path);
+        let mut config = Self::layered(file.unwrap_or_default(), |key| env::var(key).ok())?;
+        config.config_file = found;
+        Ok(config)
+    }
 
-        let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| {
-            if profile == "default" {
-                "sqlite://bibliogenius.db?mode=rwc".to_string()
+    /// Merge `file` over the defaults, then the environment (read through
+    /// `env`) over both.
+    fn layered(
+        file: FileConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut sources = BTreeMap::new();
+        let mut pick = |key: &'static str, from_file: Option<String>, env_key: &str| {
+            if let Some(value) = env(env_key) {
+                sources.insert(key, ConfigSource::Env);
+                Some(value)
+            } else if let Some(value) = from_file {
+                sources.insert(key, ConfigSource::File);
+                Some(value)
             } else {
-                format!("sqlite://bibliogenius_{}.db?mode=rwc", profile)
+                sources.insert(key, ConfigSource::Default);
+                None
             }
-        });
+        };
 
-        Self {
-            database_url,
-            port: env::var("PORT")
-                .ok()
-                .and_then(|p| p.parse().ok())
-                .unwrap_or(8000),
-            hub_url: env::var("HUB_URL").ok(),
-            cors_allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
-                .ok()
-                .map(|s| s.split(',').map(|s| s.trim().to_string()).collect())
-                .unwrap_or_default(),
+        let profile = pick("profile", file.profile, "PROFILE").unwrap_or_else(|| "default".into());
+        let database_url = pick("database_url", file.database_url, "DATABASE_URL")
+            .unwrap_or_else(|| default_database_url(&profile, env("BIBLIOGENIUS_DATA_DIR")));
+        let port = match pick("port", file.port.map(|p| p.to_string()), "PORT") {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map_err(|_| ConfigError::Env { key: "PORT", value })?,
+            None => 8000,
+        };
+        let hub_url = pick("hub_url", file.hub_url, "HUB_URL");
+        let cors_allowed_origins = pick(
+            "cors_allowed_origins",
+            file.cors_allowed_origins.map(|o| o.join(",")),
+            "CORS_ALLOWED_ORIGINS",
+        )
+        .map(|s| {
+            s.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+        let image_max_dimension = match pick(
+            "image_max_dimension",
+            file.image_max_dimension.map(|d| d.to_string()),
+            "IMAGE_MAX_DIMENSION",
+        ) {
+            Some(value) => match value.trim().parse() {
+                Ok(d) if d > 0 => d,
+                _ => {
+                    return Err(ConfigError::Env {
+                        key: "IMAGE_MAX_DIMENSION",
+                        value,
+                    });
+                }
+            },
+            None => DEFAULT_IMAGE_MAX_DIMENSION,
+        };
+        let image_storage_quota_mb = match pick(
+            "image_storage_quota_mb",
+            file.image_storage_quota_mb.map(|q| q.to_string()),
+            "IMAGE_STORAGE_QUOTA_MB",
+        ) {
+            Some(value) => Some(value.trim().parse().map_err(|_| ConfigError::Env {
+                key: "IMAGE_STORAGE_QUOTA_MB",
+                value,
+            })?),
+            None => None,
+        };
+        let max_import_upload_mb = match pick(
+            "max_import_upload_mb",
+            file.max_import_upload_mb.map(|m| m.to_string()),
+            "MAX_IMPORT_UPLOAD_MB",
+        ) {
+            Some(value) => match value.trim().parse() {
+                Ok(mb) if mb > 0 => mb,
+                _ => {
+                    return Err(ConfigError::Env {
+                        key: "MAX_IMPORT_UPLOAD_MB",
+                        value,
+                    });
+                }
+            },
+            None => DEFAULT_MAX_IMPORT_UPLOAD_MB,
+        };
+        let max_image_upload_mb = match pick(
+            "max_image_upload_mb",
+            file.max_image_upload_mb.map(|m| m.to_string()),
+            "MAX_IMAGE_UPLOAD_MB",
+        ) {
+            Some(value) => match value.trim().parse() {
+                Ok(mb) if (1..=MAX_IMAGE_UPLOAD_MB).contains(&mb) => mb,
+                _ => {
+                    return Err(ConfigError::Env {
+                        key: "MAX_IMAGE_UPLOAD_MB",
+                        value,
+                    });
+                }
+            },
+            None => MAX_IMAGE_UPLOAD_MB,
+        };
+        let scan_training_data = match pick(
+            "scan_training_data",
+            file.scan_training_data.map(|b| b.to_string()),
+            "SCAN_TRAINING_DATA",
+        ) {
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => true,
+                "false" | "0" | "no" => false,
+                _ => {
+                    return Err(ConfigError::Env {
+                        key: "SCAN_TRAINING_DATA",
+                        value,
+                    });
+                }
+            },
+            None => false,
+        };
+        let speech_backend = pick("speech_backend", file.speech_backend, "SPEECH_BACKEND");
+        if let Some(value) = &speech_backend
+            && !SPEECH_BACKENDS.contains(&value.as_str())
+        {
+            return Err(ConfigError::Env {
+                key: "SPEECH_BACKEND",
+                value: value.clone(),
+            });
+        }
+        let speech_command = pick("speech_command", file.speech_command, "SPEECH_COMMAND");
+        let speech_model = pick("speech_model", file.speech_model, "SPEECH_MODEL");
+        let speech_api_url = pick("speech_api_url", file.speech_api_url, "SPEECH_API_URL");
+
+        Ok(Self {
             profile,
+            database_url,
+            port,
+            hub_url,
+            cors_allowed_origins,
+            image_max_dimension,
+            image_storage_quota_mb,
+            max_import_upload_mb,
+            max_image_upload_mb,
+            scan_training_data,
+            speech_backend,
+            speech_command,
+            speech_model,
+            speech_api_url,
+            sources,
+            config_file: None,
+        })
+    }
+
+    /// Record this as the configuration the server runs with.
+    pub fn set_runtime(self) {
+        let _ = RUNTIME.set(self);
+    }
+
+    /// The configuration the server runs with. Loaded on first use when
+    /// `set_runtime` was never called, e.g. in the embedded FFI server.
+    pub fn runtime() -> &'static Config {
+        RUNTIME.get_or_init(|| {
+            Self::load().unwrap_or_else(|e| {
+                tracing::warn!("config: {e}; falling back to defaults");
+                Self::layered(FileConfig::default(), |_| None).expect("defaults are valid")
+            })
+        })
+    }
+
+    /// The effective configuration as TOML, as accepted by `config.toml`.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// The values `speech_backend` accepts.
+pub const SPEECH_BACKENDS: &[&str] = &["whisper", "api"];
+
+/// Large enough for OCR on a phone photo of a copyright page.
+const DEFAULT_IMAGE_MAX_DIMENSION: u32 = 3000;
+
+/// A Goodreads export of a few thousand books is a few MB.
+const DEFAULT_MAX_IMPORT_UPLOAD_MB: u64 = 20;
+
+/// `cover_image::COVER_MAX_INPUT_BYTES`, in MB: the default and the ceiling.
+const MAX_IMAGE_UPLOAD_MB: u64 = 10;
+
+/// The profile's database in the data directory; in the working directory
+/// (a relative URL, as before data directories) when none is set.
+fn default_database_url(profile: &str, data_dir: Option<String>) -> String {
+    let file = if profile == "default" {
+        "bibliogenius.db".to_string()
+    } else {
+        format!("bibliogenius_{}.db", profile)
+    };
+    match data_dir.filter(|dir| !dir.is_empty()) {
+        // Single-colon form: a double slash breaks on "Application Support".
+        Some(dir) => format!("sqlite:{}?mode=rwc", Path::new(&dir).join(file).display()),
+        None => format!("sqlite://{file}?mode=rwc"),
+    }
+}
+
+/// `$BIBLIOGENIUS_CONFIG`, else `config.toml` in the data directory.
+pub fn config_file_path() -> PathBuf {
+    if let Some(path) = env::var_os("BIBLIOGENIUS_CONFIG") {
+        return PathBuf::from(path);
+    }
+    super::paths::data_dir().join("config.toml")
+}
+
+fn read_file(path: &Path) -> Result<Option<FileConfig>, ConfigError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(ConfigError::Read {
+                path: path.to_path_buf(),
+                source,
+            });
         }
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_of<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn env_beats_file_beats_defaults() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            profile = "shop"
+            port = 9000
+            cors_allowed_origins = ["http://a", "http://b"]
+            "#,
+        )
+        .unwrap();
+        let config = Config::layered(file, env_of(&[("PORT", "9100")])).unwrap();
+
+        assert_eq!(config.port, 9100);
+        assert_eq!(config.profile, "shop");
+        assert_eq!(
+            config.database_url,
+            "sqlite://bibliogenius_shop.db?mode=rwc"
+        );
+        assert_eq!(config.cors_allowed_origins, vec!["http://a", "http://b"]);
+        assert_eq!(config.hub_url, None);
+        assert_eq!(config.image_max_dimension, DEFAULT_IMAGE_MAX_DIMENSION);
+        assert_eq!(config.image_storage_quota_mb, None);
+        assert_eq!(config.max_import_upload_mb, DEFAULT_MAX_IMPORT_UPLOAD_MB);
+        assert!(!config.scan_training_data);
+        assert_eq!(config.sources["port"], ConfigSource::Env);
+        assert_eq!(config.sources["profile"], ConfigSource::File);
+        assert_eq!(config.sources["database_url"], ConfigSource::Default);
+
+        let config = Config::layered(
+            FileConfig::default(),
+            env_of(&[("BIBLIOGENIUS_DATA_DIR", "/srv/library")]),
+        )
+        .unwrap();
+        assert_eq!(
+            config.database_url,
+            format!(
+                "sqlite:{}?mode=rwc",
+                Path::new("/srv/library").join("bibliogenius.db").display()
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_keys_and_bad_ports_are_errors() {
+        assert!(toml::from_str::<FileConfig>("prot = 8000").is_err());
+        assert!(matches!(
+            Config::layered(FileConfig::default(), env_of(&[("PORT", "eighty")])),
+            Err(ConfigError::Env { key: "PORT", .. })
+        ));
+        assert!(matches!(
+            Config::layered(FileConfig::default(), env_of(&[("SPEECH_BACKEND", "siri")])),
+            Err(ConfigError::Env {
+                key: "SPEECH_BACKEND",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Config::layered(
+                FileConfig::default(),
+                env_of(&[("IMAGE_MAX_DIMENSION", "0")])
+            ),
+            Err(ConfigError::Env {
+                key: "IMAGE_MAX_DIMENSION",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Config::layered(
+                FileConfig::default(),
+                env_of(&[("MAX_IMAGE_UPLOAD_MB", "50")])
+            ),
+            Err(ConfigError::Env {
+                key: "MAX_IMAGE_UPLOAD_MB",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn printed_config_reads_back_as_a_config_file() {
+        let config = Config::layered(
+            FileConfig::default(),
+            env_of(&[("HUB_URL", "https://hub.example.org")]),
+        )
+        .unwrap();
+        let file: FileConfig = toml::from_str(&config.to_toml()).unwrap();
+        assert_eq!(
+            Config::layered(file, env_of(&[])).unwrap().hub_url,
+            config.hub_url
+        );
     }
 }
@@ -0,0 +1,45 @@
+//! Per-launch secret of the desktop handshake.
+//!
+//! The port file alone told the Flutter app *a* port, not that its own backend
+//! was listening there: after a crash another local process could take the
+//! port, and any local account could read the file. At startup the backend
+//! draws a 256-bit token and publishes it with the port in the handshake file
+//! (`port_file`), readable only by the user. The app reads both, then calls
+//! `GET /api/control/handshake` with the token in `X-Control-Token`; only the
+//! process that wrote the file can answer it.
+//!
+//! Unlike the MCP token the value is not kept across restarts: nothing stores
+//! it but the handshake file, which is rewritten at every launch. An FFI host
+//! runs the backend in-process and needs no handshake.
+
+use std::sync::OnceLock;
+
+use super::mcp_token::{constant_time_eq, generate_token};
+
+/// Header carrying the token on `/api/control/*`.
+pub const CONTROL_TOKEN_HEADER: &str = "x-control-token";
+
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+/// The token of the running process, drawn on first use.
+pub fn token() -> &'static str {
+    TOKEN.get_or_init(generate_token)
+}
+
+/// Whether `candidate` is this process's token.
+pub fn verify(candidate: &str) -> bool {
+    constant_time_eq(candidate.as_bytes(), token().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_token_is_stable_for_the_process() {
+        assert_eq!(token(), token());
+        assert!(verify(token()));
+        assert!(!verify(""));
+        assert!(!verify(&generate_token()));
+    }
+}
@@ -160,6 +160,7 @@ mod tests {
             name: Set("Jack London".to_owned()),
             created_at: Set("2026-06-29T00:00:00Z".to_owned()),
             updated_at: Set("2026-06-29T00:00:00Z".to_owned()),
+            ..Default::default()
         }
         .insert(&db)
         .await
@@ -215,6 +216,7 @@ mod tests {
             name: Set("George Orwell".to_owned()),
             created_at: Set("2026-06-30T00:00:00Z".to_owned()),
             updated_at: Set("2026-06-30T00:00:00Z".to_owned()),
+            ..Default::default()
         }
         .insert(&db)
         .await
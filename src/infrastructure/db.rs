@@ -9,7 +9,7 @@ use crate::utils::default_library_name::compute_default_library_name_seed;
 /// can decide whether to migrate the archived DB forward or refuse a
 /// future-version archive. **Bump this constant whenever a new migration
 /// is appended to `run_migrations`.**
-pub const SCHEMA_VERSION: u32 = 82;
+pub const SCHEMA_VERSION: u32 = 132;
 
 pub async fn init_db(database_url: &str) -> Result<DatabaseConnection, DbErr> {
     let db = Database::connect(database_url).await?;
@@ -252,6 +252,20 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
                 .to_owned(),
         ))
         .await;
+    // Currency of prices, sales and fees (`utils::money`); NULL reads as EUR
+    let _ = db
+        .execute(Statement::from_string(
+            db.get_database_backend(),
+            "ALTER TABLE library_config ADD COLUMN currency TEXT".to_owned(),
+        ))
+        .await;
+    // Time zone due dates and reports are read in (`utils::time_zone`)
+    let _ = db
+        .execute(Statement::from_string(
+            db.get_database_backend(),
+            "ALTER TABLE library_config ADD COLUMN timezone TEXT".to_owned(),
+        ))
+        .await;
 
     // Migration: Add user_rating to books table (0-10 scale, NULL = not rated)
     let _ = db
@@ -2126,6 +2140,7 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
     crate::modules::sliding_puzzle::migrate(db).await?;
     crate::modules::hangman::migrate(db).await?;
     crate::modules::book_notes::migrate(db).await?;
+    crate::modules::book_files::migrate(db).await?;
 
     // Migration 079: one-shot sweep of rows orphaned by deletions that ran
     // while a pooled connection had `foreign_keys` disabled, so the
@@ -2373,95 +2388,304 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
     // `migrate_collection_book_added_at`.
     migrate_collection_book_added_at(db).await?;
 
+    // Migration 092: physical description of the edition on `books`: edition
+    // statement, binding/format (`PHYSICAL_FORMATS`) and dimensions, mapped from
+    // BNF UNIMARC 205/215 and the OpenLibrary edition record. `books` is a
+    // replicated CRR, so the columns go through cr-sqlite's alter protocol on an
+    // enrolled device, like migrations 089/090. See
+    // `migrate_book_physical_description`.
+    migrate_book_physical_description(db).await?;
+
+    // Migration 093: lending terms (max duration, renewable, replacement value,
+    // conditions) as a JSON `lending_terms` column on `copies`, where the owner
+    // sets them, and on `loans`, which snapshot them at lending time. Both are
+    // replicated CRRs, so each column goes through the alter protocol on an
+    // enrolled device. See `migrate_lending_terms`.
+    migrate_lending_terms(db).await?;
+
+    // Migration 094: loan policy. `loan_settings` gains a grace period before a
+    // loan is reported overdue and a JSON map of durations per contact type;
+    // `peers` gains a per-peer duration override. Both tables are local (not
+    // replicated), so plain ALTERs. See `migrate_loan_policy`.
+    migrate_loan_policy(db).await?;
+
+    // Migration 095: hold shelf. An accepted request can first be put on hold
+    // ("ready" for pickup or shipping): `p2p_requests` records the reserved
+    // copy and when the hold lapses, `loan_settings` how long holds last.
+    // Local tables, plain ALTERs. See `migrate_hold_shelf`.
+    migrate_hold_shelf(db).await?;
+
+    // Migration 096: P2P request cleanup policy. `loan_settings` gains how long
+    // a request may stay pending and how long resolved requests are kept.
+    // Local table, plain ALTERs. See `migrate_request_expiry`.
+    migrate_request_expiry(db).await?;
+
+    // Migration 097: first-run setup wizard progress. Local singleton table;
+    // an install that already has an admin is recorded as set up. See
+    // `migrate_setup_wizard`.
+    migrate_setup_wizard(db).await?;
+
+    // Migration 098: transfers of copies between libraries (request, in
+    // transit, received). Local table, like `sales`. See
+    // `migrate_copy_transfers`.
+    migrate_copy_transfers(db).await?;
+
+    // Migration 099: outbox of P2P messages a peer could not receive, retried
+    // with backoff by `services::peer_outbox`. Local table. See
+    // `migrate_peer_outbox`.
+    migrate_peer_outbox(db).await?;
+
+    // Migration 100: same-owner device sync. `linked_devices` gains the
+    // paired device's address, shared secret and operation-log cursors. See
+    // `migrate_device_sync`.
+    migrate_device_sync(db).await?;
+
+    // Migration 101: kiosk tokens, restricted credentials for a shared
+    // lending-desk tablet. Local table. See `migrate_kiosk_tokens`.
+    migrate_kiosk_tokens(db).await?;
+
+    // Migration 102: private tags. A book carrying one stays out of peer
+    // catalogues, federated search and exports. See `migrate_private_tags`.
+    migrate_private_tags(db).await?;
+
+    // Migration 103: contact portal tokens, one per contact, for the patron
+    // self-service endpoints. Local table. See `migrate_contact_tokens`.
+    migrate_contact_tokens(db).await?;
+
+    // Migration 104: archived books, kept in the database but out of the
+    // default lists and peer catalogues. See `migrate_book_archive`.
+    migrate_book_archive(db).await?;
+
+    // Migration 105: membership of association libraries on contacts: member
+    // number, join date, expiry, fee paid. See `migrate_contact_membership`.
+    migrate_contact_membership(db).await?;
+
+    // Migration 106: overdue fines: the fee policy on `loan_settings` and a
+    // local fee ledger. See `migrate_fees`.
+    migrate_fees(db).await?;
+
+    // Migration 107: purchase orders and their lines, for acquisition budgets.
+    // Local tables. See `migrate_purchase_orders`.
+    migrate_purchase_orders(db).await?;
+
+    // Migration 108: weeding: a reason and dates on copies flagged for
+    // removal or withdrawn. See `migrate_copy_weeding`.
+    migrate_copy_weeding(db).await?;
+
+    // Migration 109: acquisition suggestions sent by patrons through the
+    // contact portal. Local table. See `migrate_book_suggestions`.
+    migrate_book_suggestions(db).await?;
+
+    // Migration 110: authority identifiers on authors (BnF, VIAF, Wikidata,
+    // Open Library) for links out of author pages. See
+    // `migrate_author_identifiers`.
+    migrate_author_identifiers(db).await?;
+
+    // Migration 111: subject heading vocabularies (RAMEAU, LCSH, ...) for
+    // authority control. Local table. See `migrate_subject_headings`.
+    migrate_subject_headings(db).await?;
+
+    // Migration 112: the file a collection was imported from, and which
+    // book each of its rows became, for incremental re-imports. Local
+    // tables. See `migrate_collection_import_sources`.
+    migrate_collection_import_sources(db).await?;
+
+    // Migration 113: content-addressed covers and files, those this library
+    // shares with peers and those fetched from peers for offline browsing.
+    // Local tables. See `migrate_content_blobs`.
+    migrate_content_blobs(db).await?;
+
+    // Migration 114: subscriptions to peer catalogues, kept fresh in the
+    // background, with their sync status. Local table. See
+    // `migrate_peer_subscriptions`.
+    migrate_peer_subscriptions(db).await?;
+
+    // Migration 115: certificates of self-signed HTTPS peers, pinned on first
+    // connect. Local table. See `migrate_peer_certificates`.
+    migrate_peer_certificates(db).await?;
+
+    // Migration 116: SOCKS5 proxy (e.g. Tor) for peer requests and the onion
+    // address this node publishes, plus the onion address each peer
+    // publishes. Local tables. See `migrate_tor`.
+    migrate_tor(db).await?;
+
+    // Migration 117: announcements published to and received from peers,
+    // and which peers' announcements reach the inbox. Local tables. See
+    // `migrate_announcements`.
+    migrate_announcements(db).await?;
+
+    // Migration 118: book clubs, their members, proposals and votes, and
+    // reading schedule. Kept on every member node. See the extension module.
+    crate::modules::book_club::migrate(db).await?;
+
+    // Migration 119: `books.digital_source_url`, the free digitized edition
+    // (Gallica) a provider pointed to. See `migrate_book_digital_source_url`.
+    migrate_book_digital_source_url(db).await?;
+
+    // Migration 120: periodicals and their received issues, for holdings of
+    // magazines. Local tables. See `migrate_periodicals`.
+    migrate_periodicals(db).await?;
+
+    // Migration 121: typed relations between books (box sets, bound-with,
+    // adaptations, translations). Local table. See `migrate_book_relations`.
+    migrate_book_relations(db).await?;
+
+    // Migration 122: works clustering editions and translations by external
+    // work identifiers. Local tables. See `migrate_works`.
+    migrate_works(db).await?;
+
+    // Migration 123: saved searches and the matches already reported for
+    // each. Local tables. See `migrate_saved_searches`.
+    migrate_saved_searches(db).await?;
+
+    // Migration 124: answers to requests sent with an `Idempotency-Key`, so a
+    // retried delivery is answered instead of handled twice. Local table. See
+    // `migrate_idempotency_keys`.
+    migrate_idempotency_keys(db).await?;
+
+    // Migration 125: the "up next" reading queue, ordered apart from
+    // `shelf_position`. Local table. See `migrate_reading_queue`.
+    migrate_reading_queue(db).await?;
+
+    // Migration 126: daily call quotas per external metadata provider and the
+    // calls made each day. Local tables. See `migrate_api_quotas`.
+    migrate_api_quotas(db).await?;
+
+    // Migration 127: every saved version of `library_config`, with who saved
+    // it. Local table. See `migrate_library_config_history`.
+    migrate_library_config_history(db).await?;
+
+    // Migration 128: the network mode (offline, LAN-only, full). Local table.
+    // See `migrate_network_settings`.
+    migrate_network_settings(db).await?;
+
+    // Migration 129: titles corrected after a scan, kept as OCR training
+    // data when the owner opts in. Local table. See `migrate_scan_corrections`.
+    migrate_scan_corrections(db).await?;
+
+    // Migration 130: the protocol version and features each peer answered
+    // the handshake with. Local table. See `migrate_peer_capabilities`.
+    migrate_peer_capabilities(db).await?;
+
+    // Migration 131: one peer per library UUID, the identity that outlives
+    // URL changes. See `migrate_unique_peer_library_uuid`.
+    migrate_unique_peer_library_uuid(db).await?;
+
+    // Migration 132: `peer_certificates.previous_der`, the certificate a
+    // rotation replaced, still trusted for a while. See
+    // `migrate_peer_certificate_previous_der`.
+    migrate_peer_certificate_previous_der(db).await?;
+
     Ok(())
 }
 
-/// Migration 091: repair `collection_books.added_at` values that are empty or not
-/// ISO-8601.
-///
-/// The column is `TEXT NOT NULL DEFAULT ''` on a cr-sqlite CRR (the CRR-ready
-/// rebuild synthesized the empty-string default), so an insert that omits the
-/// column, a row replicated from a peer holding a legacy bad value, or one
-/// restored from an old backup can carry an unparseable `added_at`. The Flutter
-/// reader degrades such a value to null instead of crashing the Collections tab
-/// and remains the permanent guard (the schema default can still produce `''`
-/// between boots); this repairs the stored rows at each boot, backfilling to the
-/// parent collection's `created_at` (a good approximation of when the book
-/// joined) and falling back to the Unix epoch when that date is itself
-/// unparseable.
-///
-/// SQLite's `datetime()` returns NULL for an empty or non-ISO input and a
-/// non-NULL string for every RFC3339 form chrono emits (including the
-/// nanosecond-precision offset/`Z` forms), so `datetime(added_at) IS NULL`
-/// selects exactly the broken rows with no false positive on valid data.
-///
-/// This is a plain `UPDATE`, not DDL, so it needs no `crsql_begin_alter`
-/// protocol: on an enrolled device it flows through the CRR's update triggers
-/// like any write and replicates the repair to peers. Idempotent: after the
-/// first run no row matches the predicate, so every subsequent boot is a no-op.
-async fn migrate_collection_book_added_at(db: &DatabaseConnection) -> Result<(), DbErr> {
+/// Migration 117: create `announcements` (see `services::announcements`) and
+/// add `trust_announcements` to `peers`. An announcement with a NULL
+/// `peer_id` is one this library published; the others were received from
+/// the peer, under the id it chose so a redelivery is recognised. Only peers
+/// the owner trusts get their announcements stored. The column is gated on
+/// its absence.
+async fn migrate_announcements(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
     db.execute(Statement::from_string(
-        db.get_database_backend(),
+        backend,
         r#"
-        UPDATE collection_books
-        SET added_at = COALESCE(
-            (SELECT c.created_at FROM collections c
-             WHERE c.id = collection_books.collection_id
-               AND datetime(c.created_at) IS NOT NULL),
-            '1970-01-01T00:00:00+00:00'
-        )
-        WHERE added_at IS NULL OR datetime(added_at) IS NULL
+        CREATE TABLE IF NOT EXISTS announcements (
+            id TEXT PRIMARY KEY NOT NULL,
+            peer_id INTEGER,
+            kind TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT,
+            expires_at TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (peer_id) REFERENCES peers(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_announcements_peer ON announcements(peer_id);
         "#
         .to_owned(),
     ))
     .await?;
 
+    if !table_has_column(db, "peers", "trust_announcements").await? {
+        db.execute(Statement::from_string(
+            backend,
+            "ALTER TABLE peers ADD COLUMN trust_announcements INTEGER NOT NULL DEFAULT 0"
+                .to_owned(),
+        ))
+        .await?;
+    }
+
     Ok(())
 }
 
-/// Migration 090: add the nullable `volume_number` column to `collection_books`,
-/// evolving the table safely whether or not it is a live cr-sqlite CRR.
-///
-/// `collection_books` is in `crsqlite_crr::CRR_TABLES`, so on an enrolled device
-/// it is already promoted to a CRR when this runs (the `collection_books__crsql_clock`
-/// companion table exists). A bare `ALTER TABLE` on a CRR freezes its insert/update
-/// triggers at the old column count, breaking the next write; cr-sqlite's
-/// `crsql_begin_alter` / `crsql_commit_alter` protocol rebuilds that machinery
-/// around the DDL and preserves the clock rows. Off the sync path the `crsql_*`
-/// functions are not registered, so a plain `ALTER` is both correct and required.
-///
-/// Idempotent: gated on the column being absent, so a re-run (every boot on an
-/// enrolled device) is a no-op. The column is nullable with no backfill: a NULL
-/// `volume_number` means "unnumbered", ordered after the numbered volumes.
-async fn migrate_collection_book_volume_number(db: &DatabaseConnection) -> Result<(), DbErr> {
+/// Migration 116: create `my_tor_config` (see `services::tor`) and add
+/// `onion_url` to `peers`. The singleton row holds the SOCKS5 proxy peer
+/// requests go through and the onion service address `/api/config`
+/// advertises; `peers.onion_url` is the address a peer advertises, refreshed
+/// on every catalogue sync. The column is gated on its absence.
+async fn migrate_tor(db: &DatabaseConnection) -> Result<(), DbErr> {
     let backend = db.get_database_backend();
 
-    if table_has_column(db, "collection_books", "volume_number").await? {
-        return Ok(());
-    }
-
-    // Is `collection_books` a live CRR? The clock companion table exists only
-    // after `setup_crrs`; a plain `sqlite_master` read, feature-independent.
-    let is_crr = table_exists(db, "collection_books__crsql_clock").await?;
+    db.execute(Statement::from_string(
+        backend,
+        r#"
+        CREATE TABLE IF NOT EXISTS my_tor_config (
+            id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+            socks_proxy TEXT,
+            onion_address TEXT,
+            route_all_peers INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
 
-    if is_crr {
+    if !table_has_column(db, "peers", "onion_url").await? {
         db.execute(Statement::from_string(
             backend,
-            "SELECT crsql_begin_alter('collection_books')".to_owned(),
+            "ALTER TABLE peers ADD COLUMN onion_url TEXT".to_owned(),
         ))
         .await?;
     }
 
+    Ok(())
+}
+
+/// Migration 115: create `peer_certificates` (see `services::cert_pins`).
+/// One row per HTTPS origin whose self-signed certificate was trusted on
+/// first use: the certificate itself (DER, hex) and its fingerprint, plus
+/// the fingerprint it replaced when the owner last rotated it.
+async fn migrate_peer_certificates(db: &DatabaseConnection) -> Result<(), DbErr> {
     db.execute(Statement::from_string(
-        backend,
-        "ALTER TABLE collection_books ADD COLUMN volume_number INTEGER".to_owned(),
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS peer_certificates (
+            origin TEXT PRIMARY KEY NOT NULL,
+            sha256 TEXT NOT NULL,
+            der TEXT NOT NULL,
+            pinned_at TEXT NOT NULL,
+            previous_sha256 TEXT,
+            rotated_at TEXT
+        );
+        "#
+        .to_owned(),
     ))
     .await?;
 
-    if is_crr {
+    Ok(())
+}
+
+/// Migration 132: add `previous_der` to `peer_certificates` (see
+/// `services::cert_pins`): the certificate replaced by the last rotation,
+/// trusted alongside the new one until `ROTATION_OVERLAP_DAYS` after
+/// `rotated_at`. The column is gated on its absence.
+async fn migrate_peer_certificate_previous_der(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if !table_has_column(db, "peer_certificates", "previous_der").await? {
         db.execute(Statement::from_string(
-            backend,
-            "SELECT crsql_commit_alter('collection_books')".to_owned(),
+            db.get_database_backend(),
+            "ALTER TABLE peer_certificates ADD COLUMN previous_der TEXT".to_owned(),
         ))
         .await?;
     }
@@ -2469,101 +2693,1342 @@ async fn migrate_collection_book_volume_number(db: &DatabaseConnection) -> Resul
     Ok(())
 }
 
-/// Migration 089: add `lender_library_uuid` and `lender_request_id` to `copies`
-/// (ADR-049), evolving the table safely whether or not it is a live cr-sqlite CRR.
-///
-/// On an enrolled device `copies` is already a CRR when this runs: `init_db_account_sync`
-/// opens the pool with cr-sqlite loaded (the `copies__crsql_clock` companion table
-/// exists) and runs migrations before `setup_crrs`. A bare `ALTER TABLE` on a CRR
-/// freezes its insert/update triggers at the old column count, so the next write
-/// fails (`expected N values, got N-2`) and every subsequent write to the table
-/// breaks. cr-sqlite's `crsql_begin_alter` / `crsql_commit_alter` protocol drops
-/// and rebuilds that machinery around the DDL, preserving the clock rows and
-/// `db_version` (validated in `tests/copy_lender_identity_migration.rs`). Off the
-/// sync path there is no clock table and the `crsql_*` functions are not even
-/// registered, so a plain `ALTER` is both correct and required.
-///
-/// Idempotent: gated on the first column being absent, so a re-run (every boot on
-/// an enrolled device) is a no-op. The stable identity is backfilled from the
-/// local peer row where one resolves; `lender_request_id` has no local source and
-/// is populated at borrow time going forward.
-async fn migrate_copy_lender_identity(db: &DatabaseConnection) -> Result<(), DbErr> {
-    let backend = db.get_database_backend();
+/// Migration 114: create `peer_subscriptions` (see
+/// `services::peer_subscriptions`). One row per peer whose catalogue is kept
+/// in `peer_books` without a manual sync: when it is next due, and how the
+/// last attempts went.
+async fn migrate_peer_subscriptions(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS peer_subscriptions (
+            peer_id INTEGER PRIMARY KEY NOT NULL,
+            interval_minutes INTEGER NOT NULL DEFAULT 60,
+            status TEXT NOT NULL DEFAULT 'pending',
+            last_attempt_at TEXT,
+            last_success_at TEXT,
+            last_method TEXT,
+            last_error TEXT,
+            failures INTEGER NOT NULL DEFAULT 0,
+            books_cached INTEGER,
+            next_sync_at TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (peer_id) REFERENCES peers(id) ON DELETE CASCADE
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
 
-    // Both columns are added together, so one being present means 089 already ran.
-    if table_has_column(db, "copies", "lender_library_uuid").await? {
+    Ok(())
+}
+
+/// Migration 113: create `blob_sources` and `peer_blobs` (see
+/// `services::content_blobs`). A source is a cover thumbnail or a digital
+/// file this library offers to peers, indexed by its SHA-256 with the hashes
+/// of its chunks; `stamp` tells when a cover changed and must be re-hashed.
+/// A peer blob is a cover or file a peer listed for one of its books, fetched
+/// chunk by chunk into the local blob store.
+async fn migrate_content_blobs(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS blob_sources (
+            kind TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            book_id TEXT NOT NULL,
+            sha256 TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            chunk_hashes TEXT NOT NULL,
+            path TEXT NOT NULL,
+            stamp TEXT NOT NULL,
+            PRIMARY KEY (kind, source_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_blob_sources_sha256 ON blob_sources(sha256);
+        CREATE TABLE IF NOT EXISTS peer_blobs (
+            peer_id INTEGER NOT NULL,
+            remote_book_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            sha256 TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            format TEXT,
+            listed_at TEXT NOT NULL,
+            PRIMARY KEY (peer_id, remote_book_id, kind, sha256),
+            FOREIGN KEY (peer_id) REFERENCES peers(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_peer_blobs_sha256 ON peer_blobs(sha256);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 112: create `collection_import_sources` and
+/// `collection_import_rows` (see `services::collection_source`). A source
+/// records the detected format and column layout of the last file imported
+/// into a collection; its rows map each row's natural key to the book it
+/// created or matched, so a re-upload can tell new, updated and missing rows.
+async fn migrate_collection_import_sources(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS collection_import_sources (
+            collection_id TEXT PRIMARY KEY NOT NULL,
+            format TEXT NOT NULL,
+            layout TEXT NOT NULL,
+            file_sha256 TEXT NOT NULL,
+            owned INTEGER NOT NULL DEFAULT 0,
+            row_count INTEGER NOT NULL DEFAULT 0,
+            imported_at TEXT NOT NULL,
+            FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS collection_import_rows (
+            collection_id TEXT NOT NULL,
+            row_key TEXT NOT NULL,
+            book_id TEXT NOT NULL,
+            PRIMARY KEY (collection_id, row_key),
+            FOREIGN KEY (collection_id) REFERENCES collection_import_sources(collection_id) ON DELETE CASCADE
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 111: create `subject_headings` (see `services::subject_authority`).
+/// One row per label of a vocabulary: an authorized heading (`variant = 0`),
+/// or a variant whose `preferred` is the authorized form. `normalized` is the
+/// label folded for matching (case, accents, subdivision spacing).
+async fn migrate_subject_headings(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS subject_headings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            vocabulary TEXT NOT NULL,
+            authority_id TEXT,
+            heading TEXT NOT NULL,
+            normalized TEXT NOT NULL,
+            variant INTEGER NOT NULL DEFAULT 0,
+            preferred TEXT,
+            UNIQUE(vocabulary, normalized, variant)
+        );
+        CREATE INDEX IF NOT EXISTS idx_subject_headings_normalized ON subject_headings(normalized);
+        CREATE INDEX IF NOT EXISTS idx_subject_headings_authority ON subject_headings(vocabulary, authority_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 110: add `bnf_id`, `viaf_id`, `wikidata_id` and
+/// `openlibrary_id` to `authors` (see `services::author_works`), inside a
+/// `crsql_begin_alter` / `crsql_commit_alter` bracket when `authors` is a
+/// live CRR, like `migrate_copy_weeding`.
+async fn migrate_author_identifiers(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_has_column(db, "authors", "bnf_id").await? {
         return Ok(());
     }
 
-    // Is `copies` a live CRR? A plain `sqlite_master` read, feature-independent:
-    // the clock companion table exists only after `setup_crrs`.
-    let is_crr = table_exists(db, "copies__crsql_clock").await?;
+    let backend = db.get_database_backend();
+    let is_crr = table_exists(db, "authors__crsql_clock").await?;
 
     if is_crr {
         db.execute(Statement::from_string(
             backend,
-            "SELECT crsql_begin_alter('copies')".to_owned(),
+            "SELECT crsql_begin_alter('authors')".to_owned(),
         ))
         .await?;
     }
 
-    // The DDL. Column names are fixed literals. When `is_crr`, these run inside the
-    // `alter_crr` savepoint opened by `crsql_begin_alter`.
-    db.execute(Statement::from_string(
-        backend,
-        "ALTER TABLE copies ADD COLUMN lender_library_uuid TEXT".to_owned(),
-    ))
-    .await?;
-    db.execute(Statement::from_string(
-        backend,
-        "ALTER TABLE copies ADD COLUMN lender_request_id TEXT".to_owned(),
-    ))
-    .await?;
+    for column in ["bnf_id", "viaf_id", "wikidata_id", "openlibrary_id"] {
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE authors ADD COLUMN {column} TEXT"),
+        ))
+        .await?;
+    }
 
     if is_crr {
         db.execute(Statement::from_string(
             backend,
-            "SELECT crsql_commit_alter('copies')".to_owned(),
+            "SELECT crsql_commit_alter('authors')".to_owned(),
         ))
         .await?;
     }
 
-    // Best-effort backfill of the stable identity from the local peer row, keyed on
-    // the existing `lender_peer_id` (ADR-034). Only rows whose peer actually carries
-    // a `library_uuid` are written, so peers that never announced one stay NULL (a
-    // normal state, not an error) and no needless CRR change is emitted. When this
-    // runs on a device that lacks the peer, the value stays NULL here and arrives
-    // later by CRR replication from the device that does.
+    Ok(())
+}
+
+/// Migration 109: create `book_suggestions` (see `services::suggestions`).
+/// `contact_id` is the patron who sent it; `book_id` the wishlist book an
+/// accepted suggestion became.
+async fn migrate_book_suggestions(db: &DatabaseConnection) -> Result<(), DbErr> {
     db.execute(Statement::from_string(
-        backend,
-        "UPDATE copies \
-         SET lender_library_uuid = ( \
-             SELECT p.library_uuid FROM peers p WHERE p.id = copies.lender_peer_id \
-         ) \
-         WHERE lender_peer_id IS NOT NULL \
-           AND lender_library_uuid IS NULL \
-           AND ( \
-             SELECT p.library_uuid FROM peers p WHERE p.id = copies.lender_peer_id \
-           ) IS NOT NULL"
-            .to_owned(),
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS book_suggestions (
+            id TEXT PRIMARY KEY,
+            contact_id TEXT REFERENCES contacts(uuid) ON DELETE SET NULL,
+            title TEXT NOT NULL,
+            author TEXT,
+            isbn TEXT,
+            note TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            book_id TEXT,
+            decided_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_book_suggestions_status ON book_suggestions(status, created_at);
+        CREATE INDEX IF NOT EXISTS idx_book_suggestions_contact ON book_suggestions(contact_id);
+        "#
+        .to_owned(),
     ))
     .await?;
 
     Ok(())
 }
 
-/// True if a table named `name` exists in the main schema.
-async fn table_exists(db: &DatabaseConnection, name: &str) -> Result<bool, DbErr> {
-    let row = db
-        .query_one(Statement::from_sql_and_values(
-            db.get_database_backend(),
-            "SELECT 1 AS x FROM sqlite_master WHERE type = 'table' AND name = ?",
-            [name.into()],
-        ))
-        .await?;
-    Ok(row.is_some())
-}
-
+/// Migration 108: add `weeding_reason`, `weeding_note`, `weeding_flagged_at`
+/// and `withdrawn_at` to `copies` (see `services::weeding`), inside a
+/// `crsql_begin_alter` / `crsql_commit_alter` bracket when `copies` is a live
+/// CRR, like `migrate_contact_membership`.
+async fn migrate_copy_weeding(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_has_column(db, "copies", "weeding_reason").await? {
+        return Ok(());
+    }
+
+    let backend = db.get_database_backend();
+    let is_crr = table_exists(db, "copies__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('copies')".to_owned(),
+        ))
+        .await?;
+    }
+
+    for column in [
+        "weeding_reason TEXT",
+        "weeding_note TEXT",
+        "weeding_flagged_at TEXT",
+        "withdrawn_at TEXT",
+    ] {
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE copies ADD COLUMN {column}"),
+        ))
+        .await?;
+    }
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('copies')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 107: create `purchase_orders` and `purchase_order_lines` (see
+/// `services::acquisitions`). A line names a book to buy, by ISBN or title,
+/// or an existing book to buy more copies of; `book_id` is filled in when
+/// the order is received.
+async fn migrate_purchase_orders(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS purchase_orders (
+            id TEXT PRIMARY KEY,
+            supplier_contact_id TEXT,
+            status TEXT NOT NULL DEFAULT 'ordered',
+            ordered_at TEXT NOT NULL,
+            received_at TEXT,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_purchase_orders_ordered_at ON purchase_orders(ordered_at);
+        CREATE TABLE IF NOT EXISTS purchase_order_lines (
+            id TEXT PRIMARY KEY,
+            order_id TEXT NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+            book_id TEXT,
+            title TEXT NOT NULL,
+            author TEXT,
+            isbn TEXT,
+            quantity INTEGER NOT NULL DEFAULT 1,
+            unit_cost REAL NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_purchase_order_lines_order ON purchase_order_lines(order_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 106: add `fine_per_day` and `max_fine` to `loan_settings`, gated
+/// per column like `migrate_request_expiry`, and create `fee_ledger` (see
+/// `services::fines`): one `fine` row per overdue loan, one `payment` row
+/// per payment recorded.
+async fn migrate_fees(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for column in ["fine_per_day", "max_fine"] {
+        if table_has_column(db, "loan_settings", column).await? {
+            continue;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE loan_settings ADD COLUMN {column} REAL NOT NULL DEFAULT 0"),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        r#"
+        CREATE TABLE IF NOT EXISTS fee_ledger (
+            id TEXT PRIMARY KEY,
+            contact_id TEXT NOT NULL,
+            loan_id TEXT,
+            kind TEXT NOT NULL,
+            amount REAL NOT NULL,
+            note TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_fee_ledger_contact ON fee_ledger(contact_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_fee_ledger_loan_fine
+            ON fee_ledger(loan_id) WHERE kind = 'fine';
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 105: add the membership columns to `contacts` (see
+/// `services::membership`), inside a `crsql_begin_alter` /
+/// `crsql_commit_alter` bracket when `contacts` is a live CRR, like
+/// `migrate_book_archive`.
+async fn migrate_contact_membership(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_has_column(db, "contacts", "member_number").await? {
+        return Ok(());
+    }
+
+    let backend = db.get_database_backend();
+    let is_crr = table_exists(db, "contacts__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('contacts')".to_owned(),
+        ))
+        .await?;
+    }
+
+    for column in [
+        "member_number TEXT",
+        "member_since TEXT",
+        "membership_expires_at TEXT",
+        "membership_fee_paid REAL",
+    ] {
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE contacts ADD COLUMN {column}"),
+        ))
+        .await?;
+    }
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('contacts')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 104: add `archived` to `books` (see `api::batch::batch_archive`),
+/// inside a `crsql_begin_alter` / `crsql_commit_alter` bracket when `books`
+/// is a live CRR, like `migrate_private_tags`.
+async fn migrate_book_archive(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_has_column(db, "books", "archived").await? {
+        return Ok(());
+    }
+
+    let backend = db.get_database_backend();
+    let is_crr = table_exists(db, "books__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE books ADD COLUMN archived INTEGER NOT NULL DEFAULT 0".to_owned(),
+    ))
+    .await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 103: create `contact_tokens` (see `services::contact_portal`).
+/// Like `kiosk_tokens`, only the SHA-256 of each token is stored.
+async fn migrate_contact_tokens(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS contact_tokens (
+            contact_id TEXT PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 102: add `private` to `tags` (see `Book::restricted_ids`), inside
+/// a `crsql_begin_alter` / `crsql_commit_alter` bracket when `tags` is a live
+/// CRR, like `migrate_lending_terms`.
+async fn migrate_private_tags(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_has_column(db, "tags", "private").await? {
+        return Ok(());
+    }
+
+    let backend = db.get_database_backend();
+    let is_crr = table_exists(db, "tags__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('tags')".to_owned(),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE tags ADD COLUMN private INTEGER NOT NULL DEFAULT 0".to_owned(),
+    ))
+    .await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('tags')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 101: create `kiosk_tokens` (see `services::kiosk`). Only the
+/// SHA-256 of each token is stored; the token itself is shown once.
+async fn migrate_kiosk_tokens(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS kiosk_tokens (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 100: add `device_uuid`, `url`, `sync_secret`, `pull_cursor` and
+/// `push_cursor` to `linked_devices` (see `services::device_sync`). Gated per
+/// column, like `migrate_request_expiry`.
+async fn migrate_device_sync(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for (column, ddl) in [
+        ("device_uuid", "TEXT"),
+        ("url", "TEXT"),
+        ("sync_secret", "TEXT"),
+        ("pull_cursor", "INTEGER NOT NULL DEFAULT 0"),
+        ("push_cursor", "INTEGER NOT NULL DEFAULT 0"),
+    ] {
+        if table_has_column(db, "linked_devices", column).await? {
+            continue;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE linked_devices ADD COLUMN {column} {ddl}"),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_linked_devices_device_uuid \
+         ON linked_devices(device_uuid)"
+            .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 099: create `p2p_outbox`. One row per undelivered message: the
+/// E2EE message type and payload, plus the plaintext route used when the peer
+/// has no E2EE channel. Delivered rows are deleted; rows given up on stay as
+/// `failed` until the user dismisses them.
+async fn migrate_peer_outbox(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS p2p_outbox (
+            id TEXT PRIMARY KEY,
+            peer_id INTEGER NOT NULL,
+            message_type TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            fallback_method TEXT,
+            fallback_path TEXT,
+            fallback_body TEXT,
+            outgoing_request_id TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at TEXT NOT NULL,
+            last_error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_p2p_outbox_due ON p2p_outbox(status, next_attempt_at);
+        CREATE INDEX IF NOT EXISTS idx_p2p_outbox_peer_id ON p2p_outbox(peer_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 098: create `copy_transfers`, the history of copies moved between
+/// libraries (see `services::copy_transfer_service`). Rows reference copies by
+/// uuid without a FK, like every table rebuilt by ADR-044; the copy cascade in
+/// `referential_integrity` removes them.
+async fn migrate_copy_transfers(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS copy_transfers (
+            id TEXT PRIMARY KEY,
+            copy_id TEXT NOT NULL,
+            from_library_id INTEGER NOT NULL,
+            to_library_id INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'requested',
+            notes TEXT,
+            requested_at TEXT NOT NULL,
+            shipped_at TEXT,
+            received_at TEXT,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_copy_transfers_copy_id ON copy_transfers(copy_id);
+        CREATE INDEX IF NOT EXISTS idx_copy_transfers_status ON copy_transfers(status);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 097: create `setup_wizard` (see `infrastructure::setup_wizard`).
+/// On first creation, a database that already has an admin user went through
+/// the one-shot `POST /api/setup`, so it is marked completed rather than
+/// offering the wizard again.
+async fn migrate_setup_wizard(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if table_exists(db, "setup_wizard").await? {
+        return Ok(());
+    }
+    let backend = db.get_database_backend();
+
+    db.execute(Statement::from_string(
+        backend,
+        r#"CREATE TABLE setup_wizard (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            completed_steps TEXT NOT NULL DEFAULT '[]',
+            completed_at TEXT,
+            updated_at TEXT NOT NULL
+        )"#
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        backend,
+        r#"INSERT INTO setup_wizard (id, completed_steps, completed_at, updated_at)
+           SELECT 1, '["profile","admin","library"]', datetime('now'), datetime('now')
+           WHERE EXISTS (SELECT 1 FROM users WHERE role = 'admin')"#
+            .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 096: add `request_expiry_days` and `resolved_request_retention_days`
+/// to `loan_settings`. Gated per column, like `migrate_loan_policy`.
+async fn migrate_request_expiry(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for (column, ddl) in [
+        ("request_expiry_days", "INTEGER NOT NULL DEFAULT 30"),
+        (
+            "resolved_request_retention_days",
+            "INTEGER NOT NULL DEFAULT 90",
+        ),
+    ] {
+        if table_has_column(db, "loan_settings", column).await? {
+            continue;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE loan_settings ADD COLUMN {column} {ddl}"),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 095: add `held_copy_id` and `hold_expires_at` to `p2p_requests`,
+/// and `hold_expiry_days` to `loan_settings`. Gated per column, like
+/// `migrate_loan_policy`.
+async fn migrate_hold_shelf(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for (table, column, ddl) in [
+        ("p2p_requests", "held_copy_id", "TEXT"),
+        ("p2p_requests", "hold_expires_at", "TEXT"),
+        (
+            "loan_settings",
+            "hold_expiry_days",
+            "INTEGER NOT NULL DEFAULT 7",
+        ),
+    ] {
+        if table_has_column(db, table, column).await? {
+            continue;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE {table} ADD COLUMN {column} {ddl}"),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 094: add `grace_period_days` and `contact_type_durations` to
+/// `loan_settings`, and `loan_duration_days` to `peers`. Each column is gated
+/// on its own absence, so the migration is idempotent and resumable.
+async fn migrate_loan_policy(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for (table, column, ddl) in [
+        (
+            "loan_settings",
+            "grace_period_days",
+            "INTEGER NOT NULL DEFAULT 0",
+        ),
+        ("loan_settings", "contact_type_durations", "TEXT"),
+        ("peers", "loan_duration_days", "INTEGER"),
+    ] {
+        if table_has_column(db, table, column).await? {
+            continue;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE {table} ADD COLUMN {column} {ddl}"),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 093: add the nullable JSON `lending_terms` column to `copies` and
+/// `loans` (`domain::LendingTerms`).
+///
+/// Each table gets its own `crsql_begin_alter` / `crsql_commit_alter` bracket
+/// when it is a live CRR (same protocol as `migrate_book_physical_description`).
+/// Idempotent per table: gated on the column being absent, so a boot
+/// interrupted between the two tables finishes the job on the next one.
+async fn migrate_lending_terms(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    for table in ["copies", "loans"] {
+        if table_has_column(db, table, "lending_terms").await? {
+            continue;
+        }
+
+        let is_crr = table_exists(db, &format!("{table}__crsql_clock")).await?;
+
+        if is_crr {
+            db.execute(Statement::from_string(
+                backend,
+                format!("SELECT crsql_begin_alter('{table}')"),
+            ))
+            .await?;
+        }
+
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE {table} ADD COLUMN lending_terms TEXT"),
+        ))
+        .await?;
+
+        if is_crr {
+            db.execute(Statement::from_string(
+                backend,
+                format!("SELECT crsql_commit_alter('{table}')"),
+            ))
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Migration 092: add the nullable `edition`, `physical_format` and `dimensions`
+/// columns to `books`, evolving the table safely whether or not it is a live
+/// cr-sqlite CRR (same protocol as `migrate_collection_book_volume_number`).
+///
+/// The three columns are added inside a single `crsql_begin_alter` /
+/// `crsql_commit_alter` bracket so the CRR triggers are rebuilt once. Idempotent:
+/// gated on the first column being absent. No backfill: existing rows stay NULL
+/// until the user (or a metadata lookup) fills them in.
+async fn migrate_book_physical_description(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    if table_has_column(db, "books", "edition").await? {
+        return Ok(());
+    }
+
+    let is_crr = table_exists(db, "books__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    for column in ["edition", "physical_format", "dimensions"] {
+        db.execute(Statement::from_string(
+            backend,
+            format!("ALTER TABLE books ADD COLUMN {column} TEXT"),
+        ))
+        .await?;
+    }
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 119: add the nullable `books.digital_source_url`, set when a
+/// metadata provider (BNF) links the book to a free digitized edition on
+/// Gallica. Existing rows stay NULL.
+async fn migrate_book_digital_source_url(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    if table_has_column(db, "books", "digital_source_url").await? {
+        return Ok(());
+    }
+
+    let is_crr = table_exists(db, "books__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE books ADD COLUMN digital_source_url TEXT".to_owned(),
+    ))
+    .await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 120: create `periodicals` and `periodical_issues` (see
+/// `services::periodicals`). An issue is numbered by volume and number, or
+/// only labelled; the holdings statement is computed from them.
+async fn migrate_periodicals(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS periodicals (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            issn TEXT,
+            publisher TEXT,
+            frequency TEXT NOT NULL DEFAULT 'monthly',
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_periodicals_issn ON periodicals(issn);
+        CREATE TABLE IF NOT EXISTS periodical_issues (
+            id TEXT PRIMARY KEY,
+            periodical_id TEXT NOT NULL REFERENCES periodicals(id) ON DELETE CASCADE,
+            volume INTEGER,
+            number INTEGER,
+            label TEXT,
+            cover_date TEXT,
+            received_at TEXT NOT NULL,
+            notes TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_periodical_issues_periodical ON periodical_issues(periodical_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 121: create `book_relations` (see `services::book_relations`).
+/// A row goes with either of its books in
+/// `referential_integrity::delete_book_cascade`: like the other children of
+/// `books` since the UUID-PK rebuild (ADR-044), it has no foreign key.
+async fn migrate_book_relations(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS book_relations (
+            id TEXT PRIMARY KEY,
+            book_id TEXT NOT NULL,
+            related_book_id TEXT NOT NULL,
+            relation TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            UNIQUE (book_id, related_book_id, relation)
+        );
+        CREATE INDEX IF NOT EXISTS idx_book_relations_related ON book_relations(related_book_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 122: create `works`, `work_identifiers` and `work_books` (see
+/// `services::works`). A book has at most one `work_books` row; one with a
+/// NULL `work_id` and `manual` set keeps it out of clustering.
+async fn migrate_works(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS works (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS work_identifiers (
+            scheme TEXT NOT NULL,
+            value TEXT NOT NULL,
+            work_id TEXT NOT NULL REFERENCES works(id) ON DELETE CASCADE,
+            PRIMARY KEY (scheme, value)
+        );
+        CREATE INDEX IF NOT EXISTS idx_work_identifiers_work ON work_identifiers(work_id);
+        CREATE TABLE IF NOT EXISTS work_books (
+            book_id TEXT PRIMARY KEY,
+            work_id TEXT REFERENCES works(id) ON DELETE SET NULL,
+            manual INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_work_books_work ON work_books(work_id);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 123: create `saved_searches` and `saved_search_matches` (see
+/// `services::saved_searches`). `owner` is the username of the token that
+/// saved the search, empty for the local app.
+async fn migrate_saved_searches(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS saved_searches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            owner TEXT NOT NULL DEFAULT '',
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            sources TEXT NOT NULL DEFAULT 'local,peers',
+            notify INTEGER NOT NULL DEFAULT 1,
+            last_checked_at TEXT,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_saved_searches_owner ON saved_searches(owner);
+        CREATE TABLE IF NOT EXISTS saved_search_matches (
+            search_id INTEGER NOT NULL REFERENCES saved_searches(id) ON DELETE CASCADE,
+            match_key TEXT NOT NULL,
+            title TEXT NOT NULL,
+            author TEXT,
+            source TEXT,
+            first_seen_at TEXT NOT NULL,
+            PRIMARY KEY (search_id, match_key)
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 124: create `idempotency_keys` (see `api::idempotency`). Rows
+/// are pruned a day after they were written.
+async fn migrate_idempotency_keys(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS idempotency_keys (
+            key TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            request_hash TEXT NOT NULL,
+            status INTEGER,
+            content_type TEXT,
+            body BLOB,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (key, scope)
+        );
+        CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at
+            ON idempotency_keys(created_at);
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 125: create `reading_queue` (see `services::reading_queue`). A
+/// book is queued at most once; `position` runs from 0 at the head. Removed
+/// with its book in `referential_integrity::delete_book_cascade`.
+async fn migrate_reading_queue(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS reading_queue (
+            book_id TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            added_at TEXT NOT NULL
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 126: create `api_quotas` and `api_usage` (see
+/// `services::api_quota`). A provider without a row, or with a NULL
+/// `daily_limit`, is not capped. `day` is the UTC date, `YYYY-MM-DD`;
+/// `skipped` counts the calls refused once over quota.
+async fn migrate_api_quotas(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS api_quotas (
+            provider TEXT PRIMARY KEY,
+            daily_limit INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS api_usage (
+            provider TEXT NOT NULL,
+            day TEXT NOT NULL,
+            calls INTEGER NOT NULL DEFAULT 0,
+            skipped INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (provider, day)
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 127: create `library_config_history` (see
+/// `services::config_history`). One row per saved version, never updated
+/// or deleted: `config` is the whole `library_config` row as JSON,
+/// `changed_fields` a JSON array of the fields that differ from the version
+/// before, and `restored_version` the version a rollback brought back.
+async fn migrate_library_config_history(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS library_config_history (
+            version INTEGER PRIMARY KEY AUTOINCREMENT,
+            config TEXT NOT NULL,
+            changed_fields TEXT NOT NULL DEFAULT '[]',
+            changed_by TEXT NOT NULL,
+            restored_version INTEGER,
+            created_at TEXT NOT NULL
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 128: create `network_settings` (see `services::network_mode`),
+/// a single row (`id` 1) holding the network mode. No row means `full`.
+async fn migrate_network_settings(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS network_settings (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            mode TEXT NOT NULL DEFAULT 'full',
+            updated_at TEXT NOT NULL
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 129: create `scan_corrections` (see `services::scan_training`).
+/// One row per title a user corrected after a scan: the OCR text, already
+/// anonymized, what the scan proposed and what the user kept.
+async fn migrate_scan_corrections(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS scan_corrections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ocr_text TEXT NOT NULL,
+            predicted_title TEXT,
+            corrected_title TEXT NOT NULL,
+            corrected_author TEXT,
+            created_at TEXT NOT NULL
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 130: create `peer_capabilities` (see
+/// `services::peer_protocol`). One row per peer that answered a handshake:
+/// its protocol version and features, a JSON array of names. A peer without
+/// a row is taken to speak protocol 1.
+async fn migrate_peer_capabilities(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        CREATE TABLE IF NOT EXISTS peer_capabilities (
+            peer_id INTEGER PRIMARY KEY NOT NULL,
+            protocol_version INTEGER NOT NULL,
+            features TEXT NOT NULL DEFAULT '[]',
+            negotiated_at TEXT NOT NULL,
+            FOREIGN KEY (peer_id) REFERENCES peers(id) ON DELETE CASCADE
+        );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 131: make `peers.library_uuid` unique, so a library is one
+/// peer whatever URL it is reached at (`url` follows it when it moves, see
+/// `services::peer_identity_sync`). Pairing flows written before the lookups
+/// went UUID-first could leave several rows claiming one library: the most
+/// recently updated keeps the UUID and the older ones, stale addresses of the
+/// same library, lose it and are matched by URL only, as before.
+async fn migrate_unique_peer_library_uuid(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        UPDATE peers SET library_uuid = NULL
+        WHERE library_uuid IS NOT NULL
+          AND EXISTS (
+            SELECT 1 FROM peers AS newer
+            WHERE newer.library_uuid = peers.library_uuid
+              AND (newer.updated_at > peers.updated_at
+                   OR (newer.updated_at = peers.updated_at AND newer.id > peers.id))
+          );
+        "#
+        .to_owned(),
+    ))
+    .await?;
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_peers_library_uuid_unique \
+         ON peers(library_uuid) WHERE library_uuid IS NOT NULL"
+            .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 091: repair `collection_books.added_at` values that are empty or not
+/// ISO-8601.
+///
+/// The column is `TEXT NOT NULL DEFAULT ''` on a cr-sqlite CRR (the CRR-ready
+/// rebuild synthesized the empty-string default), so an insert that omits the
+/// column, a row replicated from a peer holding a legacy bad value, or one
+/// restored from an old backup can carry an unparseable `added_at`. The Flutter
+/// reader degrades such a value to null instead of crashing the Collections tab
+/// and remains the permanent guard (the schema default can still produce `''`
+/// between boots); this repairs the stored rows at each boot, backfilling to the
+/// parent collection's `created_at` (a good approximation of when the book
+/// joined) and falling back to the Unix epoch when that date is itself
+/// unparseable.
+///
+/// SQLite's `datetime()` returns NULL for an empty or non-ISO input and a
+/// non-NULL string for every RFC3339 form chrono emits (including the
+/// nanosecond-precision offset/`Z` forms), so `datetime(added_at) IS NULL`
+/// selects exactly the broken rows with no false positive on valid data.
+///
+/// This is a plain `UPDATE`, not DDL, so it needs no `crsql_begin_alter`
+/// protocol: on an enrolled device it flows through the CRR's update triggers
+/// like any write and replicates the repair to peers. Idempotent: after the
+/// first run no row matches the predicate, so every subsequent boot is a no-op.
+async fn migrate_collection_book_added_at(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        r#"
+        UPDATE collection_books
+        SET added_at = COALESCE(
+            (SELECT c.created_at FROM collections c
+             WHERE c.id = collection_books.collection_id
+               AND datetime(c.created_at) IS NOT NULL),
+            '1970-01-01T00:00:00+00:00'
+        )
+        WHERE added_at IS NULL OR datetime(added_at) IS NULL
+        "#
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Migration 090: add the nullable `volume_number` column to `collection_books`,
+/// evolving the table safely whether or not it is a live cr-sqlite CRR.
+///
+/// `collection_books` is in `crsqlite_crr::CRR_TABLES`, so on an enrolled device
+/// it is already promoted to a CRR when this runs (the `collection_books__crsql_clock`
+/// companion table exists). A bare `ALTER TABLE` on a CRR freezes its insert/update
+/// triggers at the old column count, breaking the next write; cr-sqlite's
+/// `crsql_begin_alter` / `crsql_commit_alter` protocol rebuilds that machinery
+/// around the DDL and preserves the clock rows. Off the sync path the `crsql_*`
+/// functions are not registered, so a plain `ALTER` is both correct and required.
+///
+/// Idempotent: gated on the column being absent, so a re-run (every boot on an
+/// enrolled device) is a no-op. The column is nullable with no backfill: a NULL
+/// `volume_number` means "unnumbered", ordered after the numbered volumes.
+async fn migrate_collection_book_volume_number(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    if table_has_column(db, "collection_books", "volume_number").await? {
+        return Ok(());
+    }
+
+    // Is `collection_books` a live CRR? The clock companion table exists only
+    // after `setup_crrs`; a plain `sqlite_master` read, feature-independent.
+    let is_crr = table_exists(db, "collection_books__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('collection_books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE collection_books ADD COLUMN volume_number INTEGER".to_owned(),
+    ))
+    .await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('collection_books')".to_owned(),
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Migration 089: add `lender_library_uuid` and `lender_request_id` to `copies`
+/// (ADR-049), evolving the table safely whether or not it is a live cr-sqlite CRR.
+///
+/// On an enrolled device `copies` is already a CRR when this runs: `init_db_account_sync`
+/// opens the pool with cr-sqlite loaded (the `copies__crsql_clock` companion table
+/// exists) and runs migrations before `setup_crrs`. A bare `ALTER TABLE` on a CRR
+/// freezes its insert/update triggers at the old column count, so the next write
+/// fails (`expected N values, got N-2`) and every subsequent write to the table
+/// breaks. cr-sqlite's `crsql_begin_alter` / `crsql_commit_alter` protocol drops
+/// and rebuilds that machinery around the DDL, preserving the clock rows and
+/// `db_version` (validated in `tests/copy_lender_identity_migration.rs`). Off the
+/// sync path there is no clock table and the `crsql_*` functions are not even
+/// registered, so a plain `ALTER` is both correct and required.
+///
+/// Idempotent: gated on the first column being absent, so a re-run (every boot on
+/// an enrolled device) is a no-op. The stable identity is backfilled from the
+/// local peer row where one resolves; `lender_request_id` has no local source and
+/// is populated at borrow time going forward.
+async fn migrate_copy_lender_identity(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    // Both columns are added together, so one being present means 089 already ran.
+    if table_has_column(db, "copies", "lender_library_uuid").await? {
+        return Ok(());
+    }
+
+    // Is `copies` a live CRR? A plain `sqlite_master` read, feature-independent:
+    // the clock companion table exists only after `setup_crrs`.
+    let is_crr = table_exists(db, "copies__crsql_clock").await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_begin_alter('copies')".to_owned(),
+        ))
+        .await?;
+    }
+
+    // The DDL. Column names are fixed literals. When `is_crr`, these run inside the
+    // `alter_crr` savepoint opened by `crsql_begin_alter`.
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE copies ADD COLUMN lender_library_uuid TEXT".to_owned(),
+    ))
+    .await?;
+    db.execute(Statement::from_string(
+        backend,
+        "ALTER TABLE copies ADD COLUMN lender_request_id TEXT".to_owned(),
+    ))
+    .await?;
+
+    if is_crr {
+        db.execute(Statement::from_string(
+            backend,
+            "SELECT crsql_commit_alter('copies')".to_owned(),
+        ))
+        .await?;
+    }
+
+    // Best-effort backfill of the stable identity from the local peer row, keyed on
+    // the existing `lender_peer_id` (ADR-034). Only rows whose peer actually carries
+    // a `library_uuid` are written, so peers that never announced one stay NULL (a
+    // normal state, not an error) and no needless CRR change is emitted. When this
+    // runs on a device that lacks the peer, the value stays NULL here and arrives
+    // later by CRR replication from the device that does.
+    db.execute(Statement::from_string(
+        backend,
+        "UPDATE copies \
+         SET lender_library_uuid = ( \
+             SELECT p.library_uuid FROM peers p WHERE p.id = copies.lender_peer_id \
+         ) \
+         WHERE lender_peer_id IS NOT NULL \
+           AND lender_library_uuid IS NULL \
+           AND ( \
+             SELECT p.library_uuid FROM peers p WHERE p.id = copies.lender_peer_id \
+           ) IS NOT NULL"
+            .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// True if a table named `name` exists in the main schema.
+async fn table_exists(db: &DatabaseConnection, name: &str) -> Result<bool, DbErr> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT 1 AS x FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [name.into()],
+        ))
+        .await?;
+    Ok(row.is_some())
+}
+
 /// True if `table` currently has a column named `column` (via `PRAGMA
 /// table_info`). Used to make column-dropping migrations idempotent.
 ///
@@ -3780,6 +5245,25 @@ pub async fn backfill_borrow_metadata(db: &DatabaseConnection) -> Result<Backfil
 mod tests {
     use super::*;
 
+    // `SCHEMA_VERSION` stamps `.bgbackup` manifests and gates restores, so it
+    // must follow the last `// Migration NNN` of `run_migrations`.
+    #[test]
+    fn schema_version_is_the_last_migration() {
+        let source = include_str!("db.rs");
+        let start = source
+            .find("pub async fn run_migrations(")
+            .expect("run_migrations");
+        let body = &source[start..];
+        let body = &body[..body.find("\n}\n").expect("end of run_migrations")];
+        let last = body
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("// Migration "))
+            .filter_map(|rest| rest.get(..3)?.parse::<u32>().ok())
+            .max()
+            .expect("numbered migrations");
+        assert_eq!(SCHEMA_VERSION, last);
+    }
+
     // --- Migration 078: stable UUIDs ---
 
     // After the full migration chain (including `migrate_uuid_pk`), every
@@ -3943,6 +5427,7 @@ mod tests {
             name: Set("Trigger Test".to_owned()),
             created_at: Set("2020".to_owned()),
             updated_at: Set("2020".to_owned()),
+            ..Default::default()
         })
         .exec(&db)
         .await
@@ -0,0 +1,237 @@
+//! Single-instance guard for the server binary.
+//!
+//! Two backends writing the same SQLite file (a systemd unit plus a manual
+//! `cargo run`, two units pointed at one data directory) corrupt each other's
+//! sync state, so every command that opens the database first takes an
+//! exclusive OS lock on `<database file>.lock` and records its PID in it.
+//!
+//! The OS drops the lock when the process dies, so "locked" always means a
+//! live owner. A record left in an unlocked file means the previous owner did
+//! not exit cleanly (crash, `SIGKILL`): that stale lock is only taken over on
+//! request ([`InstanceLock::acquire`] with `take_over_stale`), after the
+//! operator had a chance to look at the database. A clean exit empties the
+//! record; the file itself stays, see `port_file` for why.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Who holds (or last held) the lock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub started_at: String,
+    #[serde(default)]
+    pub hostname: String,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            hostname: hostname::get()
+                .map(|h| h.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl std::fmt::Display for LockOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PID {} started at {}", self.pid, self.started_at)?;
+        if !self.hostname.is_empty() {
+            write!(f, " on {}", self.hostname)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum InstanceLockError {
+    Io(io::Error),
+    /// Another running process owns the database.
+    Held {
+        lock_path: PathBuf,
+        owner: Option<LockOwner>,
+    },
+    /// The previous owner did not release the lock.
+    Stale {
+        lock_path: PathBuf,
+        owner: LockOwner,
+    },
+}
+
+impl std::fmt::Display for InstanceLockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "instance lock: {e}"),
+            Self::Held { lock_path, owner } => {
+                write!(
+                    f,
+                    "the database is already in use by another BiblioGenius instance"
+                )?;
+                if let Some(owner) = owner {
+                    write!(f, " ({owner})")?;
+                }
+                write!(f, "; stop it first (lock: {})", lock_path.display())
+            }
+            Self::Stale { lock_path, owner } => write!(
+                f,
+                "{} was left by an instance that did not shut down cleanly ({owner}); \
+                 check the database, then rerun with --take-over-stale-lock \
+                 (or BIBLIOGENIUS_TAKE_OVER_STALE_LOCK=1)",
+                lock_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InstanceLockError {}
+
+impl From<io::Error> for InstanceLockError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Lock file guarding the database file `db_path`.
+pub fn lock_path_for(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    db_path.with_file_name(name)
+}
+
+fn read_owner(file: &mut File) -> Option<LockOwner> {
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut content).ok()?;
+    serde_json::from_str(content.trim()).ok()
+}
+
+/// Exclusive ownership of a database file. Released (record emptied, OS lock
+/// dropped) when dropped.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl InstanceLock {
+    /// Take the lock for the database file `db_path`.
+    pub fn acquire(db_path: &Path, take_over_stale: bool) -> Result<Self, InstanceLockError> {
+        let path = lock_path_for(db_path);
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Read + write, never truncate on open: the record belongs to
+        // whoever holds the lock.
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let owner = read_owner(&mut file);
+                return Err(InstanceLockError::Held {
+                    lock_path: path,
+                    owner,
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        if let Some(owner) = read_owner(&mut file) {
+            if !take_over_stale {
+                return Err(InstanceLockError::Stale {
+                    lock_path: path,
+                    owner,
+                });
+            }
+            tracing::warn!("Taking over stale instance lock {:?} ({})", path, owner);
+        }
+
+        let record = serde_json::to_string(&LockOwner::current())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(record.as_bytes())?;
+        file.sync_all()?;
+
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Emptied record + released lock = clean shutdown.
+        if let Err(e) = self.file.set_len(0) {
+            tracing::warn!("Failed to clear instance lock {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_instance_is_refused_with_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("bibliogenius.db");
+
+        let _first = InstanceLock::acquire(&db, false).unwrap();
+        match InstanceLock::acquire(&db, true) {
+            Err(InstanceLockError::Held { owner, .. }) => {
+                assert_eq!(owner.unwrap().pid, std::process::id());
+            }
+            other => panic!("expected Held, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_clean_release_allows_next_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("bibliogenius.db");
+
+        drop(InstanceLock::acquire(&db, false).unwrap());
+        let lock = InstanceLock::acquire(&db, false).unwrap();
+        assert!(lock.path().ends_with("bibliogenius.db.lock"));
+    }
+
+    #[test]
+    fn test_stale_lock_needs_take_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("bibliogenius.db");
+        // What a killed process leaves behind: a record, no OS lock.
+        let stale = LockOwner {
+            pid: 4242,
+            started_at: "2026-01-01T00:00:00+00:00".to_owned(),
+            hostname: String::new(),
+        };
+        std::fs::write(lock_path_for(&db), serde_json::to_string(&stale).unwrap()).unwrap();
+
+        match InstanceLock::acquire(&db, false) {
+            Err(InstanceLockError::Stale { owner, .. }) => assert_eq!(owner, stale),
+            other => panic!("expected Stale, got {other:?}"),
+        }
+
+        let _lock = InstanceLock::acquire(&db, true).unwrap();
+        let record: LockOwner =
+            serde_json::from_str(&std::fs::read_to_string(lock_path_for(&db)).unwrap()).unwrap();
+        assert_eq!(record.pid, std::process::id());
+    }
+}
@@ -28,12 +28,17 @@ const TOKEN_FILE_NAME: &str = "mcp_token";
 /// endpoint to answer 503 until the process restarts.
 static TOKEN: OnceLock<String> = OnceLock::new();
 
+/// Database URL given by the standalone server, which knows it from its
+/// configuration. Takes precedence over `DATABASE_URL`, which only the FFI
+/// `init_backend` publishes.
+static DATABASE_URL: OnceLock<String> = OnceLock::new();
+
 /// Serializes resolution attempts, so two threads racing on a missing token file
 /// cannot each generate one and have the loser cache a value the file no longer holds.
 static RESOLVE_LOCK: Mutex<()> = Mutex::new(());
 
 /// Generate a 256-bit random token, base64url-encoded.
-fn generate_token() -> String {
+pub(crate) fn generate_token() -> String {
     use base64::Engine;
     use rand::RngCore;
     use rand::rngs::OsRng;
@@ -47,8 +52,8 @@ fn generate_token() -> String {
 /// Extract the database file path from a SeaORM SQLite URL.
 ///
 /// Returns `None` for in-memory databases (tests), which have no directory to
-/// store a token in.
-fn database_file_path(database_url: &str) -> Option<PathBuf> {
+/// store a token in (nor a file to snapshot).
+pub fn database_file_path(database_url: &str) -> Option<PathBuf> {
     let rest = database_url.strip_prefix("sqlite:")?;
     // Both `sqlite:<path>` and `sqlite://<path>` appear in the wild; the app itself
     // uses the single-colon form (a double slash breaks on "Application Support").
@@ -122,9 +127,18 @@ fn warn_if_readable_beyond_owner(path: &std::path::Path) {
     }
 }
 
-/// Resolve the token from `DATABASE_URL`, creating it on first use.
+/// Name the database the token lives next to. Only the first call counts; an
+/// in-memory URL leaves the endpoint without a token.
+pub fn set_database_url(database_url: &str) {
+    let _ = DATABASE_URL.set(database_url.to_string());
+}
+
+/// Resolve the token from the database URL, creating it on first use.
 fn resolve_token() -> Option<String> {
-    let database_url = std::env::var("DATABASE_URL").ok()?;
+    let database_url = match DATABASE_URL.get() {
+        Some(url) => url.clone(),
+        None => std::env::var("DATABASE_URL").ok()?,
+    };
     let path = token_file_path(&database_url)?;
     match load_or_create_at(&path) {
         Ok(token) => Some(token),
@@ -136,7 +150,7 @@ fn resolve_token() -> Option<String> {
     }
 }
 
-/// The token expected by this process, resolved from `DATABASE_URL` on first use.
+/// The token expected by this process, resolved from the database URL on first use.
 ///
 /// `None` when no token could be established. Callers MUST treat that as "reject
 /// every request" rather than "no authentication required". A failed resolution is
@@ -2,15 +2,23 @@
 //!
 //! This layer contains:
 //! - Database connection and migrations (db)
+//! - Single-instance guard on the database file (instance_lock)
 //! - HTTP server setup (server)
+//! - Desktop port file publication and locking (port_file)
+//! - Per-launch secret of the desktop handshake (control_token)
+//! - Data, cache, covers, media and backup directories (paths)
 //! - Configuration loading (config)
 //! - Authentication (auth)
 //! - Repository implementations (repositories)
+//! - Optional OpenTelemetry export (telemetry)
+//! - First-run setup wizard progress (setup_wizard)
+//! - Signal handling and the shutdown token (shutdown)
 //! - Application state (state)
 
 pub mod auth;
 pub mod book_local;
 pub mod config;
+pub mod control_token;
 pub mod cover_sync_state;
 #[cfg(any(feature = "crsqlite", feature = "crsqlite-static"))]
 pub mod crsqlite_crr;
@@ -19,13 +27,19 @@ pub mod crsqlite_dynamic;
 #[cfg(feature = "crsqlite-static")]
 pub mod crsqlite_static;
 pub mod db;
+pub mod instance_lock;
 pub mod mcp_token;
 pub mod nonce_store;
+pub mod paths;
+pub mod port_file;
 pub mod referential_integrity;
 pub mod repositories;
 pub mod seed;
 pub mod server;
+pub mod setup_wizard;
+pub mod shutdown;
 pub mod state;
+pub mod telemetry;
 pub mod uuid_lookup;
 
 pub use repositories::*;
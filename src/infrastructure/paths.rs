@@ -0,0 +1,134 @@
+//! Where the backend keeps its files.
+//!
+//! | directory | holds | resolved from |
+//! |-----------|-------|---------------|
+//! | data      | database, `config.toml`, debug log | FFI init, `$BIBLIOGENIUS_DATA_DIR`, the working directory |
+//! | covers    | local cover images | `$COVERS_DIR`, `<data>/covers` (next to the database) |
+//! | media     | ebook and audiobook files, blobs | `$MEDIA_DIR`, `<data>/media` |
+//! | backups   | `.bgbackup` archives | `$BIBLIOGENIUS_BACKUP_DIR`, `<data>/backups` |
+//! | cache     | the desktop port and handshake files | `$BIBLIOGENIUS_CACHE_DIR`, the platform cache directory |
+//!
+//! The Flutter app opens the database itself and hands its path to
+//! `init_backend`, which registers the database's directory as the data
+//! directory: on iOS the container path changes between installs, so nothing
+//! may be derived from the working directory there. The server binary has no
+//! such registration and falls back to the environment.
+//!
+//! The platform cache directory matches what the Flutter app reads the port
+//! file from; it must not move.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Data directory registered at FFI init.
+static REGISTERED_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Register the directory of the database the FFI host opened. First call
+/// wins, like `init_backend` itself.
+pub fn register_data_dir(dir: PathBuf) {
+    let _ = REGISTERED_DATA_DIR.set(dir);
+}
+
+/// The data directory registered at FFI init; `None` in the server binary.
+pub fn registered_data_dir() -> Option<&'static Path> {
+    REGISTERED_DATA_DIR.get().map(PathBuf::as_path)
+}
+
+fn env_dir(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The data directory. Empty (the working directory) when nothing is set,
+/// so relative paths stay relative as they always were.
+pub fn data_dir() -> PathBuf {
+    registered_data_dir()
+        .map(Path::to_path_buf)
+        .or_else(|| env_dir("BIBLIOGENIUS_DATA_DIR"))
+        .unwrap_or_default()
+}
+
+/// The covers directory of an FFI host, whose stored cover paths are
+/// re-based on it; `None` in the server binary.
+pub fn registered_covers_dir() -> Option<PathBuf> {
+    registered_data_dir().map(|dir| dir.join("covers"))
+}
+
+/// Local covers: next to the database unless `$COVERS_DIR` says otherwise.
+pub fn covers_dir() -> PathBuf {
+    if let Some(dir) = env_dir("COVERS_DIR") {
+        return dir;
+    }
+    if let Some(dir) = registered_covers_dir() {
+        return dir;
+    }
+    let database_url = &crate::infrastructure::config::Config::runtime().database_url;
+    crate::infrastructure::mcp_token::database_file_path(database_url)
+        .and_then(|file| file.parent().map(|dir| dir.join("covers")))
+        .unwrap_or_else(|| data_dir().join("covers"))
+}
+
+/// Ebook and audiobook attachments and the content blob store.
+pub fn media_dir() -> PathBuf {
+    env_dir("MEDIA_DIR").unwrap_or_else(|| data_dir().join("media"))
+}
+
+/// Default destination of backup archives.
+pub fn backups_dir() -> PathBuf {
+    env_dir("BIBLIOGENIUS_BACKUP_DIR").unwrap_or_else(|| data_dir().join("backups"))
+}
+
+/// Per-user cache directory, where the port file lives.
+///
+/// - macOS: `~/Library/Caches/BiblioGenius`
+/// - Linux: `~/.cache/bibliogenius`
+/// - Windows: `%LOCALAPPDATA%\BiblioGenius`
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = env_dir("BIBLIOGENIUS_CACHE_DIR") {
+        return dir;
+    }
+    platform_cache_dir()
+}
+
+#[cfg(target_os = "macos")]
+fn platform_cache_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_default();
+    PathBuf::from(home)
+        .join("Library")
+        .join("Caches")
+        .join("BiblioGenius")
+}
+
+#[cfg(target_os = "windows")]
+fn platform_cache_dir() -> PathBuf {
+    let appdata = std::env::var("LOCALAPPDATA").unwrap_or_default();
+    PathBuf::from(appdata).join("BiblioGenius")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn platform_cache_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".cache").join("bibliogenius")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directories_hang_off_the_data_directory() {
+        // No FFI registration in unit tests: the server-binary fallbacks.
+        assert!(registered_covers_dir().is_none());
+        if std::env::var_os("MEDIA_DIR").is_none() {
+            assert!(media_dir().ends_with("media"));
+        }
+        if std::env::var_os("BIBLIOGENIUS_BACKUP_DIR").is_none() {
+            assert!(backups_dir().ends_with("backups"));
+        }
+        if std::env::var_os("BIBLIOGENIUS_CACHE_DIR").is_none() {
+            let cache = cache_dir().to_string_lossy().to_lowercase();
+            assert!(cache.contains("bibliogenius"));
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! The port file the Flutter app reads to find the desktop backend.
+//!
+//! One file per profile (`backend_port.txt`, `backend_port_<profile>.txt`) in
+//! the platform cache directory. The instance that owns it holds an exclusive
+//! lock on a sibling `.lock` file for as long as it runs, so a second instance
+//! of the same profile cannot overwrite it, and a port file whose lock nobody
+//! holds was left behind by a crashed process.
+//!
+//! The port is written to a temporary file and renamed into place: readers
+//! never see a half-written number. The lock file itself is never deleted;
+//! removing it while another process waits on it would let two owners in.
+//!
+//! Next to it, the handshake file (`backend_port.json`) carries the port, the
+//! process id and the per-launch `control_token`, which the app presents to
+//! `GET /api/control/handshake` to make sure it reached its own backend. Both
+//! files are readable by the user only (`0600` on unix); the plain port file
+//! stays for app versions that do not read the handshake yet.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Path of the port file for `profile`, in `paths::cache_dir`
+/// (`~/.cache/bibliogenius/backend_port.txt` on Linux).
+pub fn port_file_path(profile: &str) -> PathBuf {
+    let filename = if profile == "default" {
+        "backend_port.txt".to_string()
+    } else {
+        format!("backend_port_{}.txt", profile)
+    };
+    super::paths::cache_dir().join(filename)
+}
+
+/// Path of the handshake file published alongside the port file at `path`.
+pub fn handshake_file_path(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+/// Write `contents` to `path` through a temporary file, readable by the
+/// owner only.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = sibling(path, ".tmp");
+    let mut options = OpenOptions::new();
+    options.create(true).truncate(true).write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    {
+        let mut file = options.open(&tmp)?;
+        // `mode` is ignored for a tmp file a crash left behind.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp, path)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// A port file owned by this process. Dropping it removes the port file and
+/// releases the lock.
+#[derive(Debug)]
+pub struct PortFile {
+    path: PathBuf,
+    handshake: PathBuf,
+    _lock: File,
+}
+
+impl PortFile {
+    /// Take the lock for `path`, clear a stale port file and publish `port`,
+    /// then the handshake file.
+    ///
+    /// Fails with [`io::ErrorKind::WouldBlock`] when another running instance
+    /// owns the file.
+    pub fn acquire(path: impl Into<PathBuf>, port: u16) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling(&path, ".lock"))?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!("{} is owned by another running instance", path.display()),
+                ));
+            }
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+
+        // We hold the lock, so whatever port file is there belongs to a
+        // process that is gone.
+        let handshake = handshake_file_path(&path);
+        for stale in [&path, &handshake] {
+            match std::fs::remove_file(stale) {
+                Ok(()) => tracing::info!("Removed stale port file {:?}", stale),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        // The handshake first: an app that sees the port finds its token.
+        let contents = serde_json::json!({
+            "port": port,
+            "pid": std::process::id(),
+            "token": super::control_token::token(),
+        });
+        write_private(&handshake, contents.to_string().as_bytes())?;
+        write_private(&path, port.to_string().as_bytes())?;
+
+        Ok(Self {
+            path,
+            handshake,
+            _lock: lock,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PortFile {
+    fn drop(&mut self) {
+        for path in [&self.path, &self.handshake] {
+            if let Err(e) = std::fs::remove_file(path)
+                && e.kind() != io::ErrorKind::NotFound
+            {
+                tracing::warn!("Failed to remove port file {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_writes_port_and_drop_removes_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backend_port.txt");
+
+        let port_file = PortFile::acquire(&path, 8123).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "8123");
+        assert!(!sibling(&path, ".tmp").exists());
+
+        drop(port_file);
+        assert!(!path.exists());
+        assert!(!handshake_file_path(&path).exists());
+    }
+
+    #[test]
+    fn test_handshake_carries_the_control_token_privately() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backend_port.txt");
+
+        let _port_file = PortFile::acquire(&path, 8124).unwrap();
+        let handshake = handshake_file_path(&path);
+        assert!(handshake.ends_with("backend_port.json"));
+        let contents: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&handshake).unwrap()).unwrap();
+        assert_eq!(contents["port"], 8124);
+        assert_eq!(contents["pid"], std::process::id());
+        assert!(crate::infrastructure::control_token::verify(
+            contents["token"].as_str().unwrap()
+        ));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for file in [&path, &handshake] {
+                let mode = std::fs::metadata(file).unwrap().permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+            }
+        }
+    }
+
+    #[test]
+    fn test_second_owner_is_refused_while_first_is_alive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backend_port.txt");
+
+        let first = PortFile::acquire(&path, 8000).unwrap();
+        let err = PortFile::acquire(&path, 8001).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "8000");
+
+        drop(first);
+        let _second = PortFile::acquire(&path, 8001).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "8001");
+    }
+
+    #[test]
+    fn test_stale_port_file_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backend_port_dev.txt");
+        std::fs::write(&path, "9999").unwrap();
+
+        let _port_file = PortFile::acquire(&path, 8002).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "8002");
+    }
+
+    #[test]
+    fn test_port_file_path_per_profile() {
+        assert!(port_file_path("default").ends_with("backend_port.txt"));
+        assert!(port_file_path("dev").ends_with("backend_port_dev.txt"));
+    }
+}
@@ -19,18 +19,23 @@
 
 use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect, QueryTrait,
+    ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    QuerySelect, QueryTrait,
 };
 
 use crate::models::{
-    author, book, book_authors, book_tags, collection, collection_book, copy, loan, sale, tag,
+    author, book, book_authors, book_relation, book_tags, collection, collection_book, copy,
+    copy_transfer, loan, reading_queue, sale, tag, work_book,
 };
+use crate::modules::book_files::models as book_file;
 use crate::modules::book_notes::models as book_note;
 
 /// Delete a book and every row that referenced it through a foreign key that
 /// existed before the UUID-PK rebuild (ADR-044): its copies (and, transitively,
 /// the loans and sales of those copies), its author/tag/collection junction
-/// rows, and its notes. Idempotent: deleting an unknown book is a no-op.
+/// rows, its relations to other books, its work and its place in the reading
+/// queue, its notes and its attached-file rows. Idempotent: deleting an
+/// unknown book is a no-op.
 ///
 /// Runs in the caller-provided connection so the whole cascade is one atomic
 /// unit; pass a transaction.
@@ -58,6 +63,27 @@ where
         .filter(book_note::Column::BookId.eq(book_uuid))
         .exec(conn)
         .await?;
+    book_relation::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(book_relation::Column::BookId.eq(book_uuid))
+                .add(book_relation::Column::RelatedBookId.eq(book_uuid)),
+        )
+        .exec(conn)
+        .await?;
+    work_book::Entity::delete_many()
+        .filter(work_book::Column::BookId.eq(book_uuid))
+        .exec(conn)
+        .await?;
+    reading_queue::Entity::delete_by_id(book_uuid.to_owned())
+        .exec(conn)
+        .await?;
+    // Rows only: the blobs are removed by `book_service::delete_book` once the
+    // transaction has committed.
+    book_file::Entity::delete_many()
+        .filter(book_file::Column::BookId.eq(book_uuid))
+        .exec(conn)
+        .await?;
 
     // Finally the book row itself.
     book::Entity::delete_by_id(book_uuid.to_owned())
@@ -73,7 +99,7 @@ where
 /// removed. Mirrors the `copy -> {loans, sales}` cascade dropped by the
 /// UUID-PK rebuild (ADR-044).
 ///
-/// Loans and sales are matched with a subquery on the book's copies so each
+/// Loans, sales and transfers are matched with a subquery on the book's copies so each
 /// delete is a single statement (an empty book naturally deletes nothing), then
 /// the copies go. Pass a transaction when atomicity matters.
 pub async fn delete_copies_of_book_cascade<C>(conn: &C, book_uuid: &str) -> Result<(), DbErr>
@@ -91,7 +117,11 @@ where
         .exec(conn)
         .await?;
     sale::Entity::delete_many()
-        .filter(sale::Column::CopyId.in_subquery(copies_of_book))
+        .filter(sale::Column::CopyId.in_subquery(copies_of_book.clone()))
+        .exec(conn)
+        .await?;
+    copy_transfer::Entity::delete_many()
+        .filter(copy_transfer::Column::CopyId.in_subquery(copies_of_book))
         .exec(conn)
         .await?;
     copy::Entity::delete_many()
@@ -102,9 +132,9 @@ where
     Ok(())
 }
 
-/// Delete a single copy together with the loans and sales that referenced it,
-/// in the caller-provided connection. Mirrors the `copy -> {loans, sales}`
-/// cascade dropped by the UUID-PK rebuild (ADR-044).
+/// Delete a single copy together with the loans, sales and transfers that
+/// referenced it, in the caller-provided connection. Mirrors the
+/// `copy -> {loans, sales}` cascade dropped by the UUID-PK rebuild (ADR-044).
 ///
 /// Returns `true` if a copy row was actually removed, so callers can keep
 /// their not-found semantics (and roll back if the copy did not exist). Pass a
@@ -121,6 +151,10 @@ where
         .filter(sale::Column::CopyId.eq(copy_uuid))
         .exec(conn)
         .await?;
+    copy_transfer::Entity::delete_many()
+        .filter(copy_transfer::Column::CopyId.eq(copy_uuid))
+        .exec(conn)
+        .await?;
 
     let result = copy::Entity::delete_by_id(copy_uuid.to_owned())
         .exec(conn)
@@ -267,6 +301,116 @@ where
     Ok(true)
 }
 
+/// Rows whose parent is missing, found (and removed) by [`sweep_orphans`].
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct OrphanCounts {
+    /// Copies of a book that no longer exists (their loans, sales and
+    /// transfers go with them)
+    pub copies: u64,
+    /// Tag links to a missing tag or book
+    pub book_tags: u64,
+    /// Author links to a missing author or book
+    pub book_authors: u64,
+}
+
+/// Count, and with `remove` delete, the rows left without a parent by a
+/// delete that skipped its cascade (older builds, an interrupted merge).
+///
+/// Unlike [`cascade_inbound_delete`] this IS the blanket sweep warned about
+/// there: a copy synced ahead of its book looks exactly like an orphan. Only
+/// call it with `remove` once no sync is in flight (no pending operation),
+/// from an owner-run maintenance. The deletes are local repairs and are not
+/// logged for sync. Pass a transaction.
+pub async fn sweep_orphans<C>(conn: &C, remove: bool) -> Result<OrphanCounts, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let books = || {
+        book::Entity::find()
+            .select_only()
+            .column(book::Column::Id)
+            .into_query()
+    };
+    let orphan_copies = || {
+        copy::Entity::find()
+            .select_only()
+            .column(copy::Column::Id)
+            .filter(copy::Column::BookId.not_in_subquery(books()))
+            .into_query()
+    };
+    let tag_orphan = || {
+        Condition::any()
+            .add(
+                book_tags::Column::TagId.not_in_subquery(
+                    tag::Entity::find()
+                        .select_only()
+                        .column(tag::Column::Id)
+                        .into_query(),
+                ),
+            )
+            .add(book_tags::Column::BookId.not_in_subquery(books()))
+    };
+    let author_orphan = || {
+        Condition::any()
+            .add(
+                book_authors::Column::AuthorId.not_in_subquery(
+                    author::Entity::find()
+                        .select_only()
+                        .column(author::Column::Id)
+                        .into_query(),
+                ),
+            )
+            .add(book_authors::Column::BookId.not_in_subquery(books()))
+    };
+
+    let mut counts = OrphanCounts {
+        copies: copy::Entity::find()
+            .filter(copy::Column::Id.in_subquery(orphan_copies()))
+            .count(conn)
+            .await?,
+        book_tags: book_tags::Entity::find()
+            .filter(tag_orphan())
+            .count(conn)
+            .await?,
+        book_authors: book_authors::Entity::find()
+            .filter(author_orphan())
+            .count(conn)
+            .await?,
+    };
+    if !remove {
+        return Ok(counts);
+    }
+
+    loan::Entity::delete_many()
+        .filter(loan::Column::CopyId.in_subquery(orphan_copies()))
+        .exec(conn)
+        .await?;
+    sale::Entity::delete_many()
+        .filter(sale::Column::CopyId.in_subquery(orphan_copies()))
+        .exec(conn)
+        .await?;
+    copy_transfer::Entity::delete_many()
+        .filter(copy_transfer::Column::CopyId.in_subquery(orphan_copies()))
+        .exec(conn)
+        .await?;
+    counts.copies = copy::Entity::delete_many()
+        .filter(copy::Column::BookId.not_in_subquery(books()))
+        .exec(conn)
+        .await?
+        .rows_affected;
+    counts.book_tags = book_tags::Entity::delete_many()
+        .filter(tag_orphan())
+        .exec(conn)
+        .await?
+        .rows_affected;
+    counts.book_authors = book_authors::Entity::delete_many()
+        .filter(author_orphan())
+        .exec(conn)
+        .await?
+        .rows_affected;
+    Ok(counts)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -367,6 +511,7 @@ mod tests {
             name: Set(name.to_owned()),
             created_at: Set(now()),
             updated_at: Set(now()),
+            ..Default::default()
         }
         .insert(db)
         .await
@@ -379,6 +524,7 @@ mod tests {
             name: Set(name.to_owned()),
             parent_id: Set(parent_id.map(|p| p.to_owned())),
             path: Set(name.to_owned()),
+            private: Set(false),
             created_at: Set(now()),
             updated_at: Set(now()),
         }
@@ -433,6 +579,22 @@ mod tests {
         .unwrap();
     }
 
+    async fn attach_file(db: &DatabaseConnection, book_id: &str) {
+        book_file::ActiveModel {
+            id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+            book_id: Set(book_id.to_owned()),
+            filename: Set("book.epub".to_owned()),
+            format: Set("epub".to_owned()),
+            mime_type: Set("application/epub+zip".to_owned()),
+            size_bytes: Set(1),
+            sha256: Set("00".repeat(32)),
+            created_at: Set(now()),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
     /// Seed a book with one of every dependent row, returning (book, copy).
     async fn seed_full_book(db: &DatabaseConnection, title: &str) -> (String, String) {
         let book_id = insert_book(db, title).await;
@@ -443,6 +605,7 @@ mod tests {
         attach_tag(db, &book_id, "tag-1").await;
         attach_collection(db, "collection-1", &book_id).await;
         attach_note(db, &book_id).await;
+        attach_file(db, &book_id).await;
         (book_id, copy_id)
     }
 
@@ -469,6 +632,7 @@ mod tests {
             "collection_books"
         );
         assert_eq!(count::<book_note::Entity>(&db).await, 0, "book_notes");
+        assert_eq!(count::<book_file::Entity>(&db).await, 0, "book_files");
     }
 
     #[tokio::test]
@@ -499,6 +663,7 @@ mod tests {
             "collection_books"
         );
         assert_eq!(count::<book_note::Entity>(&db).await, 1, "book_notes");
+        assert_eq!(count::<book_file::Entity>(&db).await, 1, "book_files");
     }
 
     #[tokio::test]
@@ -750,4 +915,39 @@ mod tests {
             .unwrap();
         assert!(!ran, "an unknown entity type must be a no-op");
     }
+
+    #[tokio::test]
+    async fn sweep_orphans_counts_then_removes_rows_without_parent() {
+        let db = setup_db().await;
+        insert_author(&db, "author-1", "Kept").await;
+        insert_tag(&db, "tag-1", "kept", None).await;
+        let (_book, _copy) = seed_full_book(&db, "intact").await;
+
+        let orphan = insert_copy(&db, "vanished-book").await;
+        insert_loan(&db, &orphan).await;
+        let other = insert_book(&db, "other").await;
+        attach_tag(&db, &other, "vanished-tag").await;
+        attach_author(&db, "vanished-book", "author-1").await;
+
+        let found = sweep_orphans(&db, false).await.unwrap();
+        assert_eq!(
+            (found.copies, found.book_tags, found.book_authors),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            count::<copy::Entity>(&db).await,
+            2,
+            "a dry run removes nothing"
+        );
+
+        let removed = sweep_orphans(&db, true).await.unwrap();
+        assert_eq!(
+            (removed.copies, removed.book_tags, removed.book_authors),
+            (1, 1, 1)
+        );
+        assert_eq!(count::<copy::Entity>(&db).await, 1, "copies");
+        assert_eq!(count::<loan::Entity>(&db).await, 1, "loans");
+        assert_eq!(count::<book_tags::Entity>(&db).await, 1, "book_tags");
+        assert_eq!(count::<book_authors::Entity>(&db).await, 1, "book_authors");
+    }
 }
@@ -23,7 +23,7 @@ impl SeaOrmBookRepository {
 
 /// Treat whitespace-only / empty ISBN strings as "no ISBN". Storing `""`
 /// breaks dedup queries that assume a single canonical "missing" value.
-fn normalize_isbn(isbn: Option<String>) -> Option<String> {
+pub(super) fn normalize_isbn(isbn: Option<String>) -> Option<String> {
     isbn.and_then(|s| {
         let trimmed = s.trim();
         if trimmed.is_empty() {
@@ -112,11 +112,14 @@ impl BookRepository for SeaOrmBookRepository {
             query = query.filter(cond);
         }
 
+        query = query.filter(Column::Archived.eq(filter.archived == Some(true)));
+
         // Filter owned books only (used by peer sync to exclude borrowed books)
         if filter.owned_only == Some(true) {
             query = query.filter(Column::Owned.eq(true));
-            // Also exclude private books from peer-facing queries
-            query = query.filter(Column::Private.eq(false));
+            // Also exclude private books, and books with a private tag, from
+            // peer-facing queries
+            query = query.filter(Book::shared_condition(&self.db).await);
         }
 
         // Owner-facing ownership predicate: unlike `owned_only`, it must not
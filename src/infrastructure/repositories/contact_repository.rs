@@ -0,0 +1,196 @@
+//! SeaORM implementation of ContactRepository
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    Set,
+};
+
+use crate::domain::{Contact, ContactInput, ContactQuery, ContactRepository, DomainError};
+use crate::models::contact::{self, ActiveModel, Entity as ContactEntity};
+
+/// SeaORM-based implementation of ContactRepository.
+///
+/// Writes go through the geocoding hook and the operation log, like every
+/// other write to a replicated table.
+pub struct SeaOrmContactRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmContactRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+fn model_to_domain(m: contact::Model) -> Contact {
+    Contact {
+        id: m.id,
+        contact_type: m.r#type,
+        name: m.name,
+        first_name: m.first_name,
+        email: m.email,
+        phone: m.phone,
+        address: m.address,
+        street_address: m.street_address,
+        postal_code: m.postal_code,
+        city: m.city,
+        country: m.country,
+        latitude: m.latitude,
+        longitude: m.longitude,
+        notes: m.notes,
+        user_id: m.user_id,
+        library_owner_id: m.library_owner_id,
+        is_active: m.is_active,
+        created_at: m.created_at,
+        updated_at: m.updated_at,
+        member_number: m.member_number,
+        member_since: m.member_since,
+        membership_expires_at: m.membership_expires_at,
+        membership_fee_paid: m.membership_fee_paid,
+    }
+}
+
+#[async_trait]
+impl ContactRepository for SeaOrmContactRepository {
+    async fn find_active(&self, query: &ContactQuery) -> Result<Vec<Contact>, DomainError> {
+        let mut select = ContactEntity::find().filter(contact::Column::IsActive.eq(true));
+
+        if let Some(library_id) = query.library_id {
+            select = select.filter(contact::Column::LibraryOwnerId.eq(library_id));
+        }
+
+        if let Some(contact_type) = &query.contact_type {
+            select = select.filter(contact::Column::Type.eq(contact_type.as_str()));
+        }
+
+        let contacts = select.all(&self.db).await?;
+        Ok(contacts.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Contact>, DomainError> {
+        let contact = ContactEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?;
+        Ok(contact.map(model_to_domain))
+    }
+
+    async fn create(&self, input: ContactInput) -> Result<Contact, DomainError> {
+        let db = &self.db;
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // Validate user_id FK: set to None if user doesn't exist
+        let user_id = if let Some(uid) = input.user_id {
+            use crate::models::user;
+            match user::Entity::find_by_id(uid).one(db).await {
+                Ok(Some(_)) => Some(uid),
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        // Resolve library_owner_id. The replicated `contacts` table no longer
+        // carries a foreign key into `libraries` (ADR-044), so reject a dangling
+        // owner id at the app layer, as the database constraint once did.
+        let library_owner_id = match input.library_owner_id {
+            Some(id) => {
+                if !crate::utils::library_helpers::library_exists(db, id).await? {
+                    return Err(DomainError::Validation(format!(
+                        "library {id} does not exist"
+                    )));
+                }
+                id
+            }
+            None => crate::utils::library_helpers::resolve_library_id(db).await?,
+        };
+
+        let new_contact = ActiveModel {
+            r#type: Set(input.contact_type),
+            name: Set(input.name),
+            first_name: Set(input.first_name),
+            email: Set(input.email),
+            phone: Set(input.phone),
+            address: Set(input.address),
+            street_address: Set(input.street_address),
+            postal_code: Set(input.postal_code),
+            city: Set(input.city),
+            country: Set(input.country),
+            latitude: Set(input.latitude),
+            longitude: Set(input.longitude),
+            notes: Set(input.notes),
+            user_id: Set(user_id),
+            library_owner_id: Set(library_owner_id),
+            is_active: Set(input.is_active),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+
+        let saved = new_contact.insert(db).await?;
+        let saved = crate::services::geocoding::after_save(db, None, saved).await;
+
+        let _ = crate::sync::log_operation(db, "contact", &saved.id, "INSERT", None).await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn update(&self, id: &str, input: ContactInput) -> Result<Contact, DomainError> {
+        let db = &self.db;
+        let existing = ContactEntity::find_by_id(id.to_owned())
+            .one(db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let before = existing.clone();
+        let mut active: ActiveModel = existing.into();
+
+        active.r#type = Set(input.contact_type);
+        active.name = Set(input.name);
+        active.first_name = Set(input.first_name);
+        active.email = Set(input.email);
+        active.phone = Set(input.phone);
+        active.address = Set(input.address);
+        active.street_address = Set(input.street_address);
+        active.postal_code = Set(input.postal_code);
+        active.city = Set(input.city);
+        active.country = Set(input.country);
+        active.latitude = Set(input.latitude);
+        active.longitude = Set(input.longitude);
+        active.notes = Set(input.notes);
+        active.user_id = Set(input.user_id);
+        if let Some(lid) = input.library_owner_id {
+            active.library_owner_id = Set(lid);
+        }
+        active.is_active = Set(input.is_active);
+        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+
+        let model = active.update(db).await?;
+        let model = crate::services::geocoding::after_save(db, Some(&before), model).await;
+
+        let _ = crate::sync::log_operation(db, "contact", &model.id, "UPDATE", None).await;
+
+        Ok(model_to_domain(model))
+    }
+
+    async fn deactivate(&self, id: &str) -> Result<(), DomainError> {
+        let existing = ContactEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let mut active: ActiveModel = existing.into();
+        active.is_active = Set(false);
+        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        active.update(&self.db).await?;
+
+        let _ = crate::sync::log_operation(&self.db, "contact", id, "DELETE", None).await;
+
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<i64, DomainError> {
+        let count = ContactEntity::find().count(&self.db).await?;
+        Ok(count as i64)
+    }
+}
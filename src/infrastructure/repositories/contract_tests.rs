@@ -0,0 +1,1355 @@
+//! Contract tests for the repository traits.
+//!
+//! Each contract is written once against the domain traits and runs twice:
+//! on the SeaORM implementations over a migrated in-memory SQLite database,
+//! and on the `memory` implementations. A service tested against the
+//! in-memory repositories can then rely on the ordering, uuid keys and
+//! not-found behaviour it will meet in production.
+//!
+//! Only what both backends promise is asserted. Side effects of the SeaORM
+//! implementations (operation log, geocoding, library checks) are left to
+//! their own tests.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+
+use super::memory::{
+    InMemoryAuthorRepository, InMemoryBookRepository, InMemoryCollectionRepository,
+    InMemoryContactRepository, InMemoryCopyRepository, InMemoryGamificationRepository,
+    InMemoryLinkedDeviceRepository, InMemoryLoanRepository, InMemoryLoanSettingsRepository,
+    InMemoryMetadataFillRepository, InMemoryNotificationRepository, InMemoryP2pRequestRepository,
+    InMemoryPeerRepository, InMemoryTagRepository, MemoryStore,
+};
+use super::*;
+use crate::domain::*;
+use crate::models::{Book, book_authors, p2p_outgoing_request, p2p_request, peer};
+
+/// One implementation of every repository trait, sharing one store.
+struct Backend {
+    /// The local library, as contacts, copies and loans reference it
+    library_id: i32,
+    authors: Box<dyn AuthorRepository>,
+    books: Box<dyn BookRepository>,
+    collections: Box<dyn CollectionRepository>,
+    contacts: Box<dyn ContactRepository>,
+    copies: Box<dyn CopyRepository>,
+    gamification: Box<dyn GamificationRepository>,
+    linked_devices: Box<dyn LinkedDeviceRepository>,
+    loans: Box<dyn LoanRepository>,
+    loan_settings: Box<dyn LoanSettingsRepository>,
+    metadata_fill: Box<dyn MetadataFillRepository>,
+    notifications: Box<dyn NotificationRepository>,
+    p2p_requests: Box<dyn P2pRequestRepository>,
+    peers: Box<dyn PeerRepository>,
+    tags: Box<dyn TagRepository>,
+    seed: Seed,
+}
+
+/// Where rows that no trait writes are inserted.
+enum Seed {
+    Db(DatabaseConnection),
+    Memory(Arc<MemoryStore>),
+}
+
+impl Backend {
+    async fn sea_orm() -> Self {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        // Bootstraps the admin user and the library that copies and
+        // contacts must reference.
+        let library_id = crate::utils::library_helpers::resolve_library_id(&db)
+            .await
+            .unwrap();
+        Self {
+            library_id,
+            authors: Box::new(SeaOrmAuthorRepository::new(db.clone())),
+            books: Box::new(SeaOrmBookRepository::new(db.clone())),
+            collections: Box::new(SeaOrmCollectionRepository::new(db.clone())),
+            contacts: Box::new(SeaOrmContactRepository::new(db.clone())),
+            copies: Box::new(SeaOrmCopyRepository::new(db.clone())),
+            gamification: Box::new(SeaOrmGamificationRepository::new(db.clone())),
+            linked_devices: Box::new(SeaOrmLinkedDeviceRepository::new(db.clone())),
+            loans: Box::new(SeaOrmLoanRepository::new(db.clone())),
+            loan_settings: Box::new(SeaOrmLoanSettingsRepository::new(db.clone())),
+            metadata_fill: Box::new(SeaOrmMetadataFillRepository::new(db.clone())),
+            notifications: Box::new(SeaOrmNotificationRepository::new(db.clone())),
+            p2p_requests: Box::new(SeaOrmP2pRequestRepository::new(db.clone())),
+            peers: Box::new(SeaOrmPeerRepository::new(db.clone())),
+            tags: Box::new(SeaOrmTagRepository::new(db.clone())),
+            seed: Seed::Db(db),
+        }
+    }
+
+    fn memory() -> Self {
+        let store = MemoryStore::new();
+        let gamification = InMemoryGamificationRepository::with_store(store.clone());
+        gamification.set_user_id(1);
+        Self {
+            library_id: 1,
+            authors: Box::new(InMemoryAuthorRepository::with_store(store.clone())),
+            books: Box::new(InMemoryBookRepository::with_store(store.clone())),
+            collections: Box::new(InMemoryCollectionRepository::with_store(store.clone())),
+            contacts: Box::new(InMemoryContactRepository::with_store(store.clone())),
+            copies: Box::new(InMemoryCopyRepository::with_store(store.clone())),
+            gamification: Box::new(gamification),
+            linked_devices: Box::new(InMemoryLinkedDeviceRepository::with_store(store.clone())),
+            loans: Box::new(InMemoryLoanRepository::with_store(store.clone())),
+            loan_settings: Box::new(InMemoryLoanSettingsRepository::with_store(store.clone())),
+            metadata_fill: Box::new(InMemoryMetadataFillRepository::with_store(store.clone())),
+            notifications: Box::new(InMemoryNotificationRepository::with_store(store.clone())),
+            p2p_requests: Box::new(InMemoryP2pRequestRepository::with_store(store.clone())),
+            peers: Box::new(InMemoryPeerRepository::with_store(store.clone())),
+            tags: Box::new(InMemoryTagRepository::with_store(store.clone())),
+            seed: Seed::Memory(store),
+        }
+    }
+
+    /// Insert a peer, returning its id
+    async fn insert_peer(&self, name: &str, url: &str, library_uuid: &str) -> i32 {
+        match &self.seed {
+            Seed::Db(db) => {
+                let now = chrono::Utc::now().to_rfc3339();
+                peer::ActiveModel {
+                    name: Set(name.to_owned()),
+                    url: Set(url.to_owned()),
+                    library_uuid: Set(Some(library_uuid.to_owned())),
+                    created_at: Set(now.clone()),
+                    updated_at: Set(now),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+                .unwrap()
+                .id
+            }
+            Seed::Memory(store) => {
+                let id = self.peers.find_all().await.unwrap().len() as i32 + 1;
+                InMemoryPeerRepository::with_store(store.clone()).insert(Peer {
+                    id,
+                    name: name.to_owned(),
+                    display_name: None,
+                    url: url.to_owned(),
+                    library_uuid: Some(library_uuid.to_owned()),
+                    connection_status: "accepted".to_owned(),
+                    last_seen: None,
+                    auto_approve: false,
+                    loan_duration_days: None,
+                });
+                id
+            }
+        }
+    }
+
+    /// Link a book to an author through `book_authors`
+    async fn link_author(&self, book_id: &str, author_id: &str) {
+        match &self.seed {
+            Seed::Db(db) => {
+                book_authors::ActiveModel {
+                    book_id: Set(book_id.to_owned()),
+                    author_id: Set(author_id.to_owned()),
+                }
+                .insert(db)
+                .await
+                .unwrap();
+            }
+            Seed::Memory(store) => store.link_author(book_id, author_id),
+        }
+    }
+
+    /// Insert an incoming and an outgoing P2P request with `status`,
+    /// returning the id of the outgoing one
+    async fn insert_requests(&self, peer_id: i32, status: &str) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        let incoming = p2p_request::Model {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            from_peer_id: peer_id,
+            book_isbn: String::new(),
+            book_title: "Dune".to_owned(),
+            status: status.to_owned(),
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            requester_request_id: None,
+            held_copy_id: None,
+            hold_expires_at: None,
+        };
+        let outgoing = p2p_outgoing_request::Model {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            to_peer_id: peer_id,
+            book_isbn: String::new(),
+            book_title: "Dune".to_owned(),
+            status: status.to_owned(),
+            lender_request_id: None,
+            book_id: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        let id = outgoing.id.clone();
+        match &self.seed {
+            Seed::Db(db) => {
+                p2p_request::ActiveModel::from(incoming)
+                    .insert(db)
+                    .await
+                    .unwrap();
+                p2p_outgoing_request::ActiveModel::from(outgoing)
+                    .insert(db)
+                    .await
+                    .unwrap();
+            }
+            Seed::Memory(store) => {
+                let requests = InMemoryP2pRequestRepository::with_store(store.clone());
+                requests.insert_incoming(incoming);
+                requests.insert_outgoing(outgoing);
+            }
+        }
+        id
+    }
+
+    async fn create_book(&self, book: Book) -> String {
+        self.books.create(book).await.unwrap().id.unwrap()
+    }
+}
+
+/// Runs a contract against both backends, as `<contract>::sea_orm` and
+/// `<contract>::memory`.
+macro_rules! contract {
+    ($name:ident) => {
+        mod $name {
+            use super::Backend;
+
+            #[tokio::test]
+            async fn sea_orm() {
+                super::$name(&Backend::sea_orm().await).await;
+            }
+
+            #[tokio::test]
+            async fn memory() {
+                super::$name(&Backend::memory()).await;
+            }
+        }
+    };
+}
+
+contract!(author_contract);
+contract!(book_contract);
+contract!(collection_contract);
+contract!(contact_contract);
+contract!(copy_contract);
+contract!(gamification_contract);
+contract!(linked_device_contract);
+contract!(loan_contract);
+contract!(loan_settings_contract);
+contract!(metadata_fill_contract);
+contract!(notification_contract);
+contract!(p2p_request_contract);
+contract!(peer_contract);
+contract!(tag_contract);
+
+/// Keys minted by the repositories are uuid v7, as on every replicated table.
+fn assert_uuid_v7(id: &str) {
+    let uuid = uuid::Uuid::parse_str(id).unwrap_or_else(|_| panic!("{id:?} is not a uuid"));
+    assert_eq!(uuid.get_version_num(), 7, "{id} is not a uuid v7");
+}
+
+/// A uuid no row holds
+fn missing_id() -> String {
+    crate::utils::uuid_gen::new_uuid_v7()
+}
+
+/// Lets the next timestamp sort strictly after the previous one.
+async fn tick() {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+fn book(title: &str) -> Book {
+    Book {
+        title: title.to_owned(),
+        ..Default::default()
+    }
+}
+
+async fn author_contract(b: &Backend) {
+    let herbert = b.authors.create("Frank Herbert".into()).await.unwrap();
+    assert_uuid_v7(&herbert.id);
+    assert_eq!(herbert.name, "Frank Herbert");
+    let le_guin = b.authors.create("Ursula K. Le Guin".into()).await.unwrap();
+
+    let found = b.authors.find_by_id(&herbert.id).await.unwrap().unwrap();
+    assert_eq!(found.name, "Frank Herbert");
+    assert!(b.authors.find_by_id(&missing_id()).await.unwrap().is_none());
+
+    let mut names: Vec<String> = b
+        .authors
+        .find_all()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|a| a.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, ["Frank Herbert", "Ursula K. Le Guin"]);
+
+    b.authors.delete(&herbert.id).await.unwrap();
+    assert!(b.authors.find_by_id(&herbert.id).await.unwrap().is_none());
+    assert!(b.authors.find_by_id(&le_guin.id).await.unwrap().is_some());
+    assert!(matches!(
+        b.authors.delete(&herbert.id).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+async fn book_contract(b: &Backend) {
+    let dune = b
+        .books
+        .create(Book {
+            isbn: Some(" 9780441013593 ".into()),
+            ..book("Dune")
+        })
+        .await
+        .unwrap();
+    let dune_id = dune.id.clone().unwrap();
+    assert_uuid_v7(&dune_id);
+    assert_eq!(dune.isbn.as_deref(), Some("9780441013593"));
+    assert_eq!(dune.reading_status.as_deref(), Some("to_read"));
+    assert_eq!(dune.owned, Some(true));
+    tick().await;
+    let children = b
+        .create_book(Book {
+            isbn: Some("9780441013593".into()),
+            ..book("Children of Dune")
+        })
+        .await;
+    let wanted = b
+        .books
+        .create(Book {
+            reading_status: Some("wanting".into()),
+            ..book("Wishlist")
+        })
+        .await
+        .unwrap();
+    assert_eq!(wanted.owned, Some(false));
+
+    let herbert = b.authors.create("Frank Herbert".into()).await.unwrap();
+    b.link_author(&dune_id, &herbert.id).await;
+    let found = b.books.find_by_id(&dune_id).await.unwrap().unwrap();
+    assert_eq!(found.author.as_deref(), Some("Frank Herbert"));
+    assert!(b.books.find_by_id(&missing_id()).await.unwrap().is_none());
+
+    // The oldest book with an ISBN wins.
+    let by_isbn = b
+        .books
+        .find_by_isbn("9780441013593")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_isbn.id.as_deref(), Some(dune_id.as_str()));
+    assert!(b.books.find_by_isbn("0000000000").await.unwrap().is_none());
+
+    let titles = |page: PaginatedBooks| -> (Vec<String>, u64) {
+        (
+            page.books.into_iter().map(|b| b.title).collect(),
+            page.total,
+        )
+    };
+    let by_title = |extra: BookFilter| BookFilter {
+        sort: Some("title_asc".into()),
+        ..extra
+    };
+    let all = b.books.find_all(by_title(BookFilter::default())).await;
+    assert_eq!(
+        titles(all.unwrap()),
+        (
+            vec!["Children of Dune".into(), "Dune".into(), "Wishlist".into()],
+            3
+        )
+    );
+    let page = b
+        .books
+        .find_all(by_title(BookFilter {
+            limit: Some(1),
+            page: Some(1),
+            ..Default::default()
+        }))
+        .await;
+    assert_eq!(titles(page.unwrap()), (vec!["Dune".into()], 3));
+    let matching = b
+        .books
+        .find_all(by_title(BookFilter {
+            title: Some("dune".into()),
+            ..Default::default()
+        }))
+        .await;
+    assert_eq!(
+        titles(matching.unwrap()),
+        (vec!["Children of Dune".into(), "Dune".into()], 2)
+    );
+    let by_author = b
+        .books
+        .find_all(BookFilter {
+            query: Some("herbert".into()),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(titles(by_author.unwrap()), (vec!["Dune".into()], 1));
+    let wishlist = b
+        .books
+        .find_all(BookFilter {
+            owned: Some(false),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(titles(wishlist.unwrap()), (vec!["Wishlist".into()], 1));
+
+    let updated = b.books.update(&dune_id, book("Dune (1965)")).await.unwrap();
+    assert_eq!(updated.title, "Dune (1965)");
+    assert!(matches!(
+        b.books.update(&missing_id(), book("Nothing")).await,
+        Err(DomainError::NotFound)
+    ));
+
+    // `update` replaced the ISBN, so only the sequel still lacks a cover.
+    let missing_covers = b.books.find_missing_covers().await.unwrap();
+    assert_eq!(
+        missing_covers,
+        [(children.clone(), "9780441013593".to_string())]
+    );
+    b.books
+        .update_cover_url(&children, "https://covers.example/children.jpg")
+        .await
+        .unwrap();
+    assert!(b.books.find_missing_covers().await.unwrap().is_empty());
+
+    b.books.delete(&dune_id).await.unwrap();
+    assert!(b.books.find_by_id(&dune_id).await.unwrap().is_none());
+    assert!(matches!(
+        b.books.delete(&dune_id).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+async fn collection_contract(b: &Backend) {
+    let owned = b.create_book(book("Dune")).await;
+    let wanted = b
+        .create_book(Book {
+            owned: Some(false),
+            ..book("Dune Messiah")
+        })
+        .await;
+
+    let cycle = b
+        .collections
+        .create(CreateCollectionInput {
+            name: "Dune cycle".into(),
+            description: None,
+            source: None,
+        })
+        .await
+        .unwrap();
+    assert!(uuid::Uuid::parse_str(&cycle.id).is_ok());
+    assert_eq!(cycle.source, "manual");
+    tick().await;
+    let later = b
+        .collections
+        .create(CreateCollectionInput {
+            name: "Later".into(),
+            description: None,
+            source: None,
+        })
+        .await
+        .unwrap();
+
+    // Newest first.
+    let ids: Vec<String> = b
+        .collections
+        .find_all()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    assert_eq!(ids, [later.id.clone(), cycle.id.clone()]);
+    assert!(
+        b.collections
+            .find_by_id(&missing_id())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    // Adding twice is a no-op.
+    b.collections.add_book(&cycle.id, &owned).await.unwrap();
+    b.collections.add_book(&cycle.id, &owned).await.unwrap();
+    b.collections.add_book(&cycle.id, &wanted).await.unwrap();
+    let counted = b.collections.find_by_id(&cycle.id).await.unwrap().unwrap();
+    assert_eq!((counted.total_books, counted.owned_books), (2, 1));
+
+    // Numbered volumes come before unnumbered ones.
+    b.collections
+        .set_book_volume(&cycle.id, &wanted, Some(1))
+        .await
+        .unwrap();
+    let books = b.collections.get_books(&cycle.id).await.unwrap();
+    let members: Vec<(&str, Option<i32>)> = books
+        .iter()
+        .map(|cb| (cb.book_id.as_str(), cb.volume_number))
+        .collect();
+    assert_eq!(
+        members,
+        [(wanted.as_str(), Some(1)), (owned.as_str(), None)]
+    );
+
+    let of_owned: Vec<String> = b
+        .collections
+        .get_book_collections(&owned)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+    assert_eq!(of_owned, std::slice::from_ref(&cycle.id));
+
+    // Replacing the memberships keeps the volume of a collection the book
+    // stays in.
+    b.collections
+        .update_book_collections(&wanted, vec![cycle.id.clone(), later.id.clone()])
+        .await
+        .unwrap();
+    let in_later = b.collections.get_books(&later.id).await.unwrap();
+    assert_eq!(in_later.len(), 1);
+    assert_eq!(in_later[0].volume_number, None);
+    let in_cycle = b.collections.get_books(&cycle.id).await.unwrap();
+    assert_eq!(in_cycle[0].book_id, wanted);
+    assert_eq!(in_cycle[0].volume_number, Some(1));
+
+    b.collections.remove_book(&cycle.id, &owned).await.unwrap();
+    assert_eq!(b.collections.get_books(&cycle.id).await.unwrap().len(), 1);
+
+    b.collections.set_source(&cycle.id, "series").await.unwrap();
+    let series = b.collections.find_by_id(&cycle.id).await.unwrap().unwrap();
+    assert_eq!(series.source, "series");
+    assert!(matches!(
+        b.collections.set_source(&missing_id(), "series").await,
+        Err(DomainError::NotFound)
+    ));
+
+    b.collections.delete(&later.id).await.unwrap();
+    assert!(b.collections.find_by_id(&later.id).await.unwrap().is_none());
+    assert!(matches!(
+        b.collections.delete(&later.id).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+fn contact_input(library_id: i32, contact_type: &str, name: &str) -> ContactInput {
+    ContactInput {
+        contact_type: contact_type.to_owned(),
+        name: name.to_owned(),
+        library_owner_id: Some(library_id),
+        is_active: true,
+        ..Default::default()
+    }
+}
+
+async fn contact_contract(b: &Backend) {
+    let ada = b
+        .contacts
+        .create(contact_input(b.library_id, "Borrower", "Ada"))
+        .await
+        .unwrap();
+    assert_uuid_v7(&ada.id);
+    assert_eq!(ada.library_owner_id, b.library_id);
+    let club = b
+        .contacts
+        .create(contact_input(b.library_id, "Library", "Book club"))
+        .await
+        .unwrap();
+
+    let names = |contacts: Vec<Contact>| -> Vec<String> {
+        let mut names: Vec<String> = contacts.into_iter().map(|c| c.name).collect();
+        names.sort();
+        names
+    };
+    let active = b.contacts.find_active(&ContactQuery::default()).await;
+    assert_eq!(names(active.unwrap()), ["Ada", "Book club"]);
+    let libraries = b
+        .contacts
+        .find_active(&ContactQuery {
+            contact_type: Some("Library".into()),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(names(libraries.unwrap()), ["Book club"]);
+    let elsewhere = b
+        .contacts
+        .find_active(&ContactQuery {
+            library_id: Some(b.library_id + 1),
+            ..Default::default()
+        })
+        .await;
+    assert!(elsewhere.unwrap().is_empty());
+
+    // Updating without an owner keeps the current one.
+    let renamed = b
+        .contacts
+        .update(
+            &ada.id,
+            ContactInput {
+                library_owner_id: None,
+                ..contact_input(b.library_id, "Borrower", "Ada Lovelace")
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(renamed.name, "Ada Lovelace");
+    assert_eq!(renamed.library_owner_id, b.library_id);
+    assert_eq!(renamed.created_at, ada.created_at);
+
+    // Deactivating is a soft delete.
+    b.contacts.deactivate(&club.id).await.unwrap();
+    let active = b.contacts.find_active(&ContactQuery::default()).await;
+    assert_eq!(names(active.unwrap()), ["Ada Lovelace"]);
+    let club = b.contacts.find_by_id(&club.id).await.unwrap().unwrap();
+    assert!(!club.is_active);
+    assert_eq!(b.contacts.count().await.unwrap(), 2);
+
+    assert!(
+        b.contacts
+            .find_by_id(&missing_id())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(matches!(
+        b.contacts
+            .update(&missing_id(), contact_input(b.library_id, "Borrower", "X"))
+            .await,
+        Err(DomainError::NotFound)
+    ));
+    assert!(matches!(
+        b.contacts.deactivate(&missing_id()).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+fn copy_input(library_id: i32, book_id: &str, status: &str) -> CreateCopyInput {
+    CreateCopyInput {
+        book_id: book_id.to_owned(),
+        library_id,
+        status: status.to_owned(),
+        ..Default::default()
+    }
+}
+
+async fn copy_contract(b: &Backend) {
+    let dune = b.create_book(book("Dune")).await;
+    let shelved = b
+        .copies
+        .create(copy_input(b.library_id, &dune, "available"))
+        .await
+        .unwrap();
+    let shelved_id = shelved.id.clone().unwrap();
+    assert_uuid_v7(&shelved_id);
+    assert_eq!(shelved.book_id, dune);
+    let borrowed = b
+        .copies
+        .create(copy_input(b.library_id, &dune, "borrowed"))
+        .await
+        .unwrap();
+
+    let found = b.copies.find_by_id(&shelved_id).await.unwrap().unwrap();
+    assert_eq!(found.book_title.as_deref(), Some("Dune"));
+    assert!(b.copies.find_by_id(&missing_id()).await.unwrap().is_none());
+    assert_eq!(b.copies.find_all().await.unwrap().total, 2);
+    assert_eq!(b.copies.find_by_book_id(&dune).await.unwrap().total, 2);
+
+    let on_loan_to_us = b.copies.find_borrowed().await.unwrap();
+    assert_eq!(on_loan_to_us.total, 1);
+    assert_eq!(on_loan_to_us.copies[0].id, borrowed.id);
+    assert_eq!(on_loan_to_us.copies[0].book_title.as_deref(), Some("Dune"));
+
+    let updated = b
+        .copies
+        .update(
+            &shelved_id,
+            UpdateCopyInput {
+                status: Some("damaged".into()),
+                notes: Some(Some("Torn cover".into())),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.status, "damaged");
+    assert_eq!(updated.notes.as_deref(), Some("Torn cover"));
+    assert!(matches!(
+        b.copies
+            .update(&missing_id(), UpdateCopyInput::default())
+            .await,
+        Err(DomainError::NotFound)
+    ));
+
+    let loaned = b.copies.set_status(&shelved_id, "loaned").await.unwrap();
+    assert_eq!(loaned.status, "loaned");
+    assert_eq!(loaned.notes.as_deref(), Some("Torn cover"));
+    assert!(matches!(
+        b.copies.set_status(&missing_id(), "loaned").await,
+        Err(DomainError::NotFound)
+    ));
+
+    b.copies.delete(&shelved_id).await.unwrap();
+    assert!(b.copies.find_by_id(&shelved_id).await.unwrap().is_none());
+    assert_eq!(b.copies.find_by_book_id(&dune).await.unwrap().total, 1);
+    assert!(matches!(
+        b.copies.delete(&shelved_id).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+async fn gamification_contract(b: &Backend) {
+    b.books
+        .create(Book {
+            reading_status: Some("read".into()),
+            finished_reading_at: Some(Some("2025-03-01T10:00:00+00:00".into())),
+            subjects: Some(vec!["SF".into()]),
+            ..book("Dune")
+        })
+        .await
+        .unwrap();
+    b.create_book(book("Dune Messiah")).await;
+    assert_eq!(b.gamification.count_books().await.unwrap(), 2);
+    assert_eq!(b.gamification.count_books_read().await.unwrap(), 1);
+    assert_eq!(
+        b.gamification
+            .count_books_read_in_year("2025")
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        b.gamification
+            .count_books_read_in_year("2024")
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(b.gamification.count_catalogued_books().await.unwrap(), 1);
+    assert_eq!(b.gamification.count_loans().await.unwrap(), 0);
+
+    let user = b.gamification.get_user_id().await.unwrap();
+    assert!(b.gamification.get_streak(user).await.unwrap().is_none());
+    b.gamification
+        .update_streak(user, 1, 1, "2026-01-01")
+        .await
+        .unwrap();
+    b.gamification
+        .update_streak(user, 2, 2, "2026-01-02")
+        .await
+        .unwrap();
+    assert_eq!(
+        b.gamification.get_streak(user).await.unwrap(),
+        Some((2, 2, Some("2026-01-02".into())))
+    );
+
+    // Unlocking is idempotent; the most recent comes first.
+    assert!(
+        b.gamification
+            .unlock_achievement(user, "first_book")
+            .await
+            .unwrap()
+    );
+    tick().await;
+    assert!(
+        b.gamification
+            .unlock_achievement(user, "first_loan")
+            .await
+            .unwrap()
+    );
+    assert!(
+        !b.gamification
+            .unlock_achievement(user, "first_book")
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        b.gamification
+            .get_recent_achievements(user, 5)
+            .await
+            .unwrap(),
+        ["first_loan", "first_book"]
+    );
+    assert_eq!(
+        b.gamification
+            .get_recent_achievements(user, 1)
+            .await
+            .unwrap(),
+        ["first_loan"]
+    );
+
+    b.gamification
+        .update_config(
+            user,
+            GamificationConfigUpdate {
+                reading_goal_yearly: Some(24),
+                achievements_style: None,
+            },
+        )
+        .await
+        .unwrap();
+    let config = b.gamification.get_config(user).await.unwrap().unwrap();
+    assert_eq!(config.reading_goal_yearly, 24);
+
+    assert!(
+        !b.gamification
+            .is_module_enabled("no-such-module")
+            .await
+            .unwrap()
+    );
+}
+
+fn device_input(name: &str, key: u8) -> CreateLinkedDeviceInput {
+    CreateLinkedDeviceInput {
+        name: name.to_owned(),
+        ed25519_public_key: vec![key; 32],
+        x25519_public_key: vec![key + 1; 32],
+        relay_url: None,
+        mailbox_id: None,
+        relay_write_token: None,
+    }
+}
+
+async fn linked_device_contract(b: &Backend) {
+    let phone = b
+        .linked_devices
+        .create(device_input("Phone", 1))
+        .await
+        .unwrap();
+    let tablet = b
+        .linked_devices
+        .create(device_input("Tablet", 3))
+        .await
+        .unwrap();
+    let phone_id = phone.id.unwrap();
+    assert_ne!(phone.id, tablet.id);
+    assert!(phone.created_at.is_some());
+    assert_eq!(phone.last_synced, None);
+    assert_eq!(b.linked_devices.find_all().await.unwrap().len(), 2);
+
+    let synced_at = "2026-01-01T10:00:00+00:00";
+    b.linked_devices
+        .update_last_synced(phone_id, synced_at)
+        .await
+        .unwrap();
+    let found = b
+        .linked_devices
+        .find_by_id(phone_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.name, "Phone");
+    assert_eq!(found.ed25519_public_key, vec![1; 32]);
+    assert_eq!(found.last_synced.as_deref(), Some(synced_at));
+
+    b.linked_devices.delete(phone_id).await.unwrap();
+    assert!(
+        b.linked_devices
+            .find_by_id(phone_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(matches!(
+        b.linked_devices.delete(phone_id).await,
+        Err(DomainError::NotFound)
+    ));
+    assert!(matches!(
+        b.linked_devices
+            .update_last_synced(phone_id, synced_at)
+            .await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+async fn loan_contract(b: &Backend) {
+    let dune = b.create_book(book("Dune")).await;
+    let copy = b
+        .copies
+        .create(copy_input(b.library_id, &dune, "available"))
+        .await
+        .unwrap()
+        .id
+        .unwrap();
+    let ada = b
+        .contacts
+        .create(contact_input(b.library_id, "Borrower", "Ada"))
+        .await
+        .unwrap()
+        .id;
+    let lend = |loan_date: &str, due_date: &str| NewLoan {
+        copy_id: copy.clone(),
+        contact_id: ada.clone(),
+        library_id: b.library_id,
+        loan_date: loan_date.to_owned(),
+        due_date: due_date.to_owned(),
+        created_at: loan_date.to_owned(),
+        ..Default::default()
+    };
+
+    let january = b
+        .loans
+        .create(lend("2026-01-01T10:00:00+00:00", "2026-01-22"))
+        .await
+        .unwrap();
+    assert_uuid_v7(&january.id);
+    assert_eq!(january.status, "active");
+    assert_eq!(january.return_date, None);
+    let february = b
+        .loans
+        .create(lend("2026-02-01T10:00:00+00:00", "2026-02-22"))
+        .await
+        .unwrap();
+
+    // Most recent first.
+    let ids = |loans: Vec<Loan>| -> Vec<String> { loans.into_iter().map(|l| l.id).collect() };
+    let newest_first = [february.id.clone(), january.id.clone()];
+    let all = b.loans.find_all(&LoanQuery::default()).await;
+    assert_eq!(ids(all.unwrap()), newest_first);
+    let of_ada = b.loans.find_by_contact(&ada).await;
+    assert_eq!(ids(of_ada.unwrap()), newest_first);
+    assert!(
+        b.loans
+            .find_by_contact(&missing_id())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let second_page = b
+        .loans
+        .find_all(&LoanQuery {
+            limit: Some(1),
+            offset: Some(1),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(ids(second_page.unwrap()), std::slice::from_ref(&january.id));
+
+    let at = "2026-01-20T10:00:00+00:00";
+    let renewed = b
+        .loans
+        .set_due_date(&january.id, "2026-02-05", at)
+        .await
+        .unwrap();
+    assert_eq!(renewed.due_date, "2026-02-05");
+    assert_eq!(renewed.updated_at, at);
+
+    let returned = b.loans.mark_returned(&january.id, at).await.unwrap();
+    assert_eq!(returned.status, "returned");
+    assert_eq!(returned.return_date.as_deref(), Some(at));
+    let found = b.loans.find_by_id(&january.id).await.unwrap().unwrap();
+    assert_eq!(found, returned);
+
+    let active = b
+        .loans
+        .find_all(&LoanQuery {
+            status: Some("active".into()),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(ids(active.unwrap()), std::slice::from_ref(&february.id));
+    assert_eq!(b.loans.count(None).await.unwrap(), 2);
+    assert_eq!(b.loans.count(Some("returned")).await.unwrap(), 1);
+
+    assert!(b.loans.find_by_id(&missing_id()).await.unwrap().is_none());
+    assert!(matches!(
+        b.loans.mark_returned(&missing_id(), at).await,
+        Err(DomainError::NotFound)
+    ));
+    assert!(matches!(
+        b.loans.set_due_date(&missing_id(), "2026-03-01", at).await,
+        Err(DomainError::NotFound)
+    ));
+
+    assert_eq!(b.loans.delete_with_status("returned").await.unwrap(), 1);
+    assert_eq!(b.loans.count(None).await.unwrap(), 1);
+}
+
+async fn loan_settings_contract(b: &Backend) {
+    // No zone configured: dates are read in the host's.
+    assert_eq!(b.loan_settings.time_zone().await.unwrap(), None);
+
+    let defaults = b.loan_settings.get_settings().await.unwrap();
+    assert_eq!(defaults.default_loan_duration_days, 21);
+    assert!(!defaults.per_book_duration_enabled);
+
+    // Stored normalized, and read back as stored.
+    let updated = b
+        .loan_settings
+        .update_settings(LoanSettings {
+            default_loan_duration_days: 500,
+            per_book_duration_enabled: true,
+            ..defaults
+        })
+        .await
+        .unwrap();
+    assert_eq!(updated.default_loan_duration_days, 365);
+    assert_eq!(b.loan_settings.get_settings().await.unwrap(), updated);
+
+    let dune = b.create_book(book("Dune")).await;
+    assert_eq!(
+        b.loan_settings.get_book_loan_duration(&dune).await.unwrap(),
+        None
+    );
+    b.loan_settings
+        .set_book_loan_duration(&dune, Some(400))
+        .await
+        .unwrap();
+    assert_eq!(
+        b.loan_settings.get_book_loan_duration(&dune).await.unwrap(),
+        Some(365)
+    );
+    b.loan_settings
+        .set_book_loan_duration(&dune, Some(14))
+        .await
+        .unwrap();
+    assert_eq!(
+        b.loan_settings.get_effective_duration(&dune).await.unwrap(),
+        14
+    );
+    assert!(matches!(
+        b.loan_settings.get_book_loan_duration(&missing_id()).await,
+        Err(DomainError::NotFound)
+    ));
+
+    let peer = b
+        .insert_peer("Bob", "http://bob.local:8080", &missing_id())
+        .await;
+    b.loan_settings
+        .set_peer_loan_duration(peer, Some(10))
+        .await
+        .unwrap();
+    assert_eq!(
+        b.loan_settings.get_peer_loan_duration(peer).await.unwrap(),
+        Some(10)
+    );
+    let to_peer = LoanBorrower {
+        contact_type: Some("Library".into()),
+        peer_id: Some(peer),
+    };
+    let other_book = b.create_book(book("Dune Messiah")).await;
+    assert_eq!(
+        b.loan_settings
+            .get_effective_duration_for(&other_book, &to_peer)
+            .await
+            .unwrap(),
+        10
+    );
+    assert!(matches!(
+        b.loan_settings.set_peer_loan_duration(peer + 1, None).await,
+        Err(DomainError::NotFound)
+    ));
+}
+
+async fn p2p_request_contract(b: &Backend) {
+    let peer = b
+        .insert_peer("Bob", "http://bob.local:8080", &missing_id())
+        .await;
+    let pending = b.insert_requests(peer, "pending").await;
+    b.insert_requests(peer, "waitlisted").await;
+    b.insert_requests(peer, "rejected").await;
+    b.insert_requests(peer, "returned").await;
+
+    assert_eq!(b.p2p_requests.count_closed_incoming().await.unwrap(), 2);
+    assert_eq!(b.p2p_requests.count_closed_outgoing().await.unwrap(), 2);
+
+    b.p2p_requests
+        .set_outgoing_status(&pending, "failed")
+        .await
+        .unwrap();
+    assert_eq!(b.p2p_requests.count_closed_outgoing().await.unwrap(), 3);
+    assert!(matches!(
+        b.p2p_requests
+            .set_outgoing_status(&missing_id(), "failed")
+            .await,
+        Err(DomainError::NotFound)
+    ));
+
+    // `ready` holds a copy and `return_pending` awaits an acknowledgment:
+    // both stay open on their side.
+    b.insert_requests(peer, "ready").await;
+    b.insert_requests(peer, "return_pending").await;
+    assert_eq!(b.p2p_requests.delete_closed_incoming().await.unwrap(), 3);
+    assert_eq!(b.p2p_requests.delete_closed_outgoing().await.unwrap(), 4);
+    assert_eq!(b.p2p_requests.count_closed_incoming().await.unwrap(), 0);
+    assert_eq!(b.p2p_requests.count_closed_outgoing().await.unwrap(), 0);
+}
+
+async fn metadata_fill_contract(b: &Backend) {
+    let dune = b
+        .create_book(Book {
+            isbn: Some("9780441013593".into()),
+            publisher: Some("Chilton".into()),
+            ..book("Dune")
+        })
+        .await;
+    assert_eq!(
+        b.metadata_fill.count_incomplete_with_isbn().await.unwrap(),
+        1
+    );
+    let before = b.metadata_fill.completeness_stats().await.unwrap();
+    assert_eq!((before.owned_total, before.incomplete), (1, 1));
+
+    // Only empty fields are filled.
+    let filled = b
+        .metadata_fill
+        .apply_fill(
+            "batch-1",
+            &dune,
+            GapValues {
+                summary: Some("Spice".into()),
+                publisher: Some("Ace".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        filled,
+        [FilledField {
+            field: "summary".into(),
+            value: "Spice".into(),
+        }]
+    );
+    let after = b.metadata_fill.completeness_stats().await.unwrap();
+    assert_eq!(after.empty_fields, before.empty_fields - 1);
+    let book = b.books.find_by_id(&dune).await.unwrap().unwrap();
+    assert_eq!(book.publisher.as_deref(), Some("Chilton"));
+
+    assert_eq!(b.metadata_fill.undo_run("batch-1").await.unwrap(), 1);
+    let undone = b.metadata_fill.completeness_stats().await.unwrap();
+    assert_eq!(undone.empty_fields, before.empty_fields);
+
+    // A run survives a kill as resumable, then closes.
+    b.metadata_fill.create_run("batch-2", 5).await.unwrap();
+    let mut run = b.metadata_fill.get_run("batch-2").await.unwrap().unwrap();
+    assert_eq!((run.status.as_str(), run.total), ("running", 5));
+    run.done = 2;
+    run.cursor_book_id = dune.clone();
+    b.metadata_fill.update_run_progress(&run).await.unwrap();
+    b.metadata_fill.mark_running_as_interrupted().await.unwrap();
+    let active = b.metadata_fill.get_active_run().await.unwrap().unwrap();
+    assert_eq!(active.status, "interrupted");
+    assert_eq!((active.done, active.cursor_book_id), (2, dune));
+
+    b.metadata_fill
+        .set_run_status("batch-2", "done")
+        .await
+        .unwrap();
+    assert!(b.metadata_fill.get_active_run().await.unwrap().is_none());
+    let last = b.metadata_fill.last_run().await.unwrap().unwrap();
+    assert_eq!(
+        (last.batch_id.as_str(), last.status.as_str()),
+        ("batch-2", "done")
+    );
+    assert!(b.metadata_fill.get_run("batch-3").await.unwrap().is_none());
+}
+
+fn notification(
+    event_type: NotificationEventType,
+    title: &str,
+    loan_id: Option<&str>,
+) -> CreateNotification {
+    CreateNotification {
+        event_type,
+        title: title.to_owned(),
+        body: None,
+        ref_type: loan_id.map(|_| "loan".to_owned()),
+        ref_id: loan_id.map(str::to_owned),
+    }
+}
+
+async fn notification_contract(b: &Backend) {
+    let reminder = b
+        .notifications
+        .create(notification(
+            NotificationEventType::LoanDueReminder,
+            "Due soon",
+            Some("loan-1"),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(reminder.category, "loans");
+    tick().await;
+    let returned = b
+        .notifications
+        .create(notification(
+            NotificationEventType::BookReturned,
+            "Returned",
+            None,
+        ))
+        .await
+        .unwrap();
+    tick().await;
+    let new_books = b
+        .notifications
+        .create(notification(
+            NotificationEventType::NewBooks,
+            "New books",
+            None,
+        ))
+        .await
+        .unwrap();
+
+    // Newest first, paginated, filterable by category.
+    let titles =
+        |rows: Vec<NotificationRow>| -> Vec<String> { rows.into_iter().map(|n| n.title).collect() };
+    let all = b.notifications.list(None, 0, 10).await;
+    assert_eq!(titles(all.unwrap()), ["New books", "Returned", "Due soon"]);
+    let second = b.notifications.list(None, 1, 1).await;
+    assert_eq!(titles(second.unwrap()), ["Returned"]);
+    let loans = b.notifications.list(Some("loans"), 0, 10).await;
+    assert_eq!(titles(loans.unwrap()), ["Returned", "Due soon"]);
+
+    assert_eq!(b.notifications.unread_count(None).await.unwrap(), 3);
+    assert!(b.notifications.mark_read(reminder.id).await.unwrap());
+    assert!(!b.notifications.mark_read(reminder.id + 100).await.unwrap());
+    assert_eq!(
+        b.notifications.unread_count(Some("loans")).await.unwrap(),
+        1
+    );
+
+    // Dismissing hides a notification but keeps it for deduplication.
+    assert!(b.notifications.dismiss(new_books.id).await.unwrap());
+    assert!(!b.notifications.dismiss(new_books.id).await.unwrap());
+    let all = b.notifications.list(None, 0, 10).await;
+    assert_eq!(titles(all.unwrap()), ["Returned", "Due soon"]);
+    assert_eq!(b.notifications.mark_all_read().await.unwrap(), 1);
+    assert_eq!(b.notifications.unread_count(None).await.unwrap(), 0);
+
+    assert!(
+        b.notifications
+            .exists("loan_due_reminder", "loan", "loan-1")
+            .await
+            .unwrap()
+    );
+    assert!(
+        !b.notifications
+            .exists("loan_due_reminder", "loan", "loan-2")
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        b.notifications
+            .dismiss_by_ref("loan", "loan-1")
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(
+        !b.notifications
+            .exists("loan_due_reminder", "loan", "loan-1")
+            .await
+            .unwrap()
+    );
+
+    assert_eq!(b.notifications.dismiss_all().await.unwrap(), 1);
+    assert!(b.notifications.list(None, 0, 10).await.unwrap().is_empty());
+    assert!(!b.notifications.dismiss(returned.id).await.unwrap());
+}
+
+async fn peer_contract(b: &Backend) {
+    let alice_uuid = missing_id();
+    let alice = b
+        .insert_peer("Alice", "http://alice.local:8000", &alice_uuid)
+        .await;
+    let bob = b
+        .insert_peer("Bob", "http://bob.local:8000", &missing_id())
+        .await;
+    assert_ne!(alice, bob);
+
+    let found = b.peers.find_by_id(alice).await.unwrap().unwrap();
+    assert_eq!(found.name, "Alice");
+    assert_eq!(found.library_uuid.as_deref(), Some(alice_uuid.as_str()));
+    assert!(b.peers.find_by_id(bob + 1).await.unwrap().is_none());
+
+    let id = |peer: Option<Peer>| peer.map(|p| p.id);
+    assert_eq!(id(b.peers.find_by_name("Bob").await.unwrap()), Some(bob));
+    assert_eq!(
+        id(b.peers
+            .find_by_url("http://alice.local:8000")
+            .await
+            .unwrap()),
+        Some(alice)
+    );
+    assert_eq!(
+        id(b.peers.find_by_library_uuid(&alice_uuid).await.unwrap()),
+        Some(alice)
+    );
+    assert!(b.peers.find_by_name("Carol").await.unwrap().is_none());
+    assert!(
+        b.peers
+            .find_by_url("http://carol.local")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        b.peers
+            .find_by_library_uuid(&missing_id())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(b.peers.find_all().await.unwrap().len(), 2);
+}
+
+async fn tag_contract(b: &Backend) {
+    let fiction = b.tags.create("Fiction".into(), None).await.unwrap();
+    assert_uuid_v7(&fiction.id);
+    assert_eq!(fiction.path, "");
+    let sf = b
+        .tags
+        .create("SF".into(), Some(fiction.id.clone()))
+        .await
+        .unwrap();
+    assert_eq!(sf.parent_id.as_deref(), Some(fiction.id.as_str()));
+    assert_eq!(sf.path, "Fiction");
+    // Ordered by name.
+    let horror = b.tags.create("Horror".into(), None).await.unwrap();
+    let names: Vec<String> = b
+        .tags
+        .find_all()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert_eq!(names, ["Fiction", "Horror", "SF"]);
+
+    // Moving a tag recomputes its path.
+    let moved = b
+        .tags
+        .update(&sf.id, "Science fiction".into(), Some(horror.id.clone()))
+        .await
+        .unwrap();
+    assert_eq!(moved.name, "Science fiction");
+    assert_eq!(moved.path, "Horror");
+    let renamed = b
+        .tags
+        .update(&horror.id, "Horror!".into(), None)
+        .await
+        .unwrap();
+    assert_eq!(renamed.path, "");
+    assert!(matches!(
+        b.tags.update(&missing_id(), "X".into(), None).await,
+        Err(DomainError::NotFound)
+    ));
+
+    // Deleting a tag moves its children to the root.
+    b.tags.delete(&horror.id).await.unwrap();
+    assert!(b.tags.find_by_id(&horror.id).await.unwrap().is_none());
+    let orphan = b.tags.find_by_id(&sf.id).await.unwrap().unwrap();
+    assert_eq!(orphan.parent_id, None);
+    assert!(matches!(
+        b.tags.delete(&horror.id).await,
+        Err(DomainError::NotFound)
+    ));
+}
@@ -15,7 +15,7 @@ use crate::models::copy::{self, ActiveModel, Column, Entity as CopyEntity};
 /// Single source of truth for `copy::Model` -> domain `Copy` mapping.
 /// Takes the optional joined book row so callers that do `find_also_related`
 /// and those that don't can share the same field list.
-fn to_domain(copy: copy::Model, book: Option<book::Model>) -> Copy {
+pub(super) fn to_domain(copy: copy::Model, book: Option<book::Model>) -> Copy {
     Copy {
         id: Some(copy.id),
         book_id: copy.book_id,
@@ -32,6 +32,7 @@ fn to_domain(copy: copy::Model, book: Option<book::Model>) -> Copy {
         lender_peer_id: copy.lender_peer_id,
         borrow_due_date: copy.borrow_due_date,
         borrow_source: copy.borrow_source,
+        lending_terms: crate::domain::LendingTerms::from_column(copy.lending_terms.as_deref()),
     }
 }
 
@@ -175,12 +176,38 @@ impl CopyRepository for SeaOrmCopyRepository {
         if let Some(source) = input.borrow_source {
             active.borrow_source = Set(source);
         }
+        if let Some(terms) = input.lending_terms {
+            active.lending_terms = Set(terms.map(|t| t.to_column()));
+        }
         active.updated_at = Set(chrono::Utc::now().to_rfc3339());
 
         let result = active.update(&self.db).await?;
         Ok(to_domain(result, None))
     }
 
+    async fn set_status(&self, id: &str, status: &str) -> Result<Copy, DomainError> {
+        let existing = CopyEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let mut active: ActiveModel = existing.into();
+        active.status = Set(status.to_owned());
+        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        let result = active.update(&self.db).await?;
+
+        let _ = crate::sync::log_operation(
+            &self.db,
+            "copy",
+            id,
+            "UPDATE",
+            Some(serde_json::json!({ "status": status })),
+        )
+        .await;
+
+        Ok(to_domain(result, None))
+    }
+
     async fn delete(&self, id: &str) -> Result<(), DomainError> {
         // Cascade the copy's dependents (loans, sales) in one transaction: the
         // database no longer does it since the replicated tables lost their
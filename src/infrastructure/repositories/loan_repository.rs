@@ -0,0 +1,167 @@
+//! SeaORM implementation of LoanRepository
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+
+use crate::domain::{DomainError, Loan, LoanQuery, LoanRepository, NewLoan};
+use crate::models::loan::{self, ActiveModel, Entity as LoanEntity};
+
+/// SeaORM-based implementation of LoanRepository
+pub struct SeaOrmLoanRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmLoanRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+fn model_to_domain(m: loan::Model) -> Loan {
+    Loan {
+        id: m.id,
+        copy_id: m.copy_id,
+        contact_id: m.contact_id,
+        library_id: m.library_id,
+        loan_date: m.loan_date,
+        due_date: m.due_date,
+        return_date: m.return_date,
+        status: m.status,
+        notes: m.notes,
+        lending_terms: m.lending_terms,
+        created_at: m.created_at,
+        updated_at: m.updated_at,
+    }
+}
+
+#[async_trait]
+impl LoanRepository for SeaOrmLoanRepository {
+    async fn find_by_id(&self, id: &str) -> Result<Option<Loan>, DomainError> {
+        let loan = LoanEntity::find_by_id(id.to_owned()).one(&self.db).await?;
+        Ok(loan.map(model_to_domain))
+    }
+
+    async fn find_by_contact(&self, contact_id: &str) -> Result<Vec<Loan>, DomainError> {
+        let loans = LoanEntity::find()
+            .filter(loan::Column::ContactId.eq(contact_id))
+            .order_by_desc(loan::Column::LoanDate)
+            .all(&self.db)
+            .await?;
+        Ok(loans.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn find_all(&self, query: &LoanQuery) -> Result<Vec<Loan>, DomainError> {
+        let mut select = LoanEntity::find();
+        if let Some(library_id) = query.library_id {
+            select = select.filter(loan::Column::LibraryId.eq(library_id));
+        }
+        if let Some(status) = &query.status {
+            select = select.filter(loan::Column::Status.eq(status.as_str()));
+        }
+        if let Some(contact_id) = &query.contact_id {
+            select = select.filter(loan::Column::ContactId.eq(contact_id.as_str()));
+        }
+        let mut select = select.order_by_desc(loan::Column::LoanDate);
+        if let Some(limit) = query.limit {
+            select = select.limit(limit).offset(query.offset.unwrap_or(0));
+        }
+        let loans = select.all(&self.db).await?;
+        Ok(loans.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn create(&self, input: NewLoan) -> Result<Loan, DomainError> {
+        let new_loan = ActiveModel {
+            copy_id: Set(input.copy_id),
+            contact_id: Set(input.contact_id),
+            library_id: Set(input.library_id),
+            loan_date: Set(input.loan_date),
+            due_date: Set(input.due_date),
+            return_date: Set(None),
+            status: Set("active".to_owned()),
+            notes: Set(input.notes),
+            lending_terms: Set(input.lending_terms),
+            created_at: Set(input.created_at.clone()),
+            updated_at: Set(input.created_at),
+            ..Default::default()
+        };
+        let saved = new_loan.insert(&self.db).await?;
+
+        let _ = crate::sync::log_operation(
+            &self.db,
+            "loan",
+            &saved.id,
+            "INSERT",
+            Some(serde_json::json!({ "copy_id": saved.copy_id })),
+        )
+        .await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn mark_returned(&self, id: &str, at: &str) -> Result<Loan, DomainError> {
+        let existing = LoanEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let mut active: ActiveModel = existing.into();
+        active.return_date = Set(Some(at.to_owned()));
+        active.status = Set("returned".to_owned());
+        active.updated_at = Set(at.to_owned());
+        let saved = active.update(&self.db).await?;
+
+        let _ = crate::sync::log_operation(
+            &self.db,
+            "loan",
+            &saved.id,
+            "UPDATE",
+            Some(serde_json::json!({ "status": "returned" })),
+        )
+        .await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn set_due_date(&self, id: &str, due_date: &str, at: &str) -> Result<Loan, DomainError> {
+        let existing = LoanEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let mut active: ActiveModel = existing.into();
+        active.due_date = Set(due_date.to_owned());
+        active.updated_at = Set(at.to_owned());
+        let saved = active.update(&self.db).await?;
+
+        let _ = crate::sync::log_operation(
+            &self.db,
+            "loan",
+            &saved.id,
+            "UPDATE",
+            Some(serde_json::json!({ "due_date": due_date })),
+        )
+        .await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn count(&self, status: Option<&str>) -> Result<i64, DomainError> {
+        let mut select = LoanEntity::find();
+        if let Some(status) = status {
+            select = select.filter(loan::Column::Status.eq(status));
+        }
+        let count = select.count(&self.db).await?;
+        Ok(count as i64)
+    }
+
+    async fn delete_with_status(&self, status: &str) -> Result<u64, DomainError> {
+        let result = LoanEntity::delete_many()
+            .filter(loan::Column::Status.eq(status))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
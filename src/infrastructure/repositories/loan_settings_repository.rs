@@ -1,10 +1,12 @@
 //! SeaORM implementation of LoanSettingsRepository
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 use sea_orm::{ActiveModelTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Set, Statement};
 
-use crate::domain::{DomainError, LoanSettings, LoanSettingsRepository};
-use crate::models::book;
+use crate::domain::{DomainError, LoanBorrower, LoanSettings, LoanSettingsRepository};
+use crate::models::{book, library_config, peer};
 
 /// SeaORM-based implementation of LoanSettingsRepository
 pub struct SeaOrmLoanSettingsRepository {
@@ -24,7 +26,7 @@ impl LoanSettingsRepository for SeaOrmLoanSettingsRepository {
             .db
             .query_one(Statement::from_string(
                 self.db.get_database_backend(),
-                "SELECT default_loan_duration_days, per_book_duration_enabled, reminder_days_before_due FROM loan_settings WHERE id = 1".to_owned(),
+                "SELECT default_loan_duration_days, per_book_duration_enabled, reminder_days_before_due, grace_period_days, contact_type_durations, hold_expiry_days, request_expiry_days, resolved_request_retention_days, fine_per_day, max_fine FROM loan_settings WHERE id = 1".to_owned(),
             ))
             .await
             .map_err(|e| DomainError::Database(e.to_string()))?
@@ -40,39 +42,78 @@ impl LoanSettingsRepository for SeaOrmLoanSettingsRepository {
         let reminder_days: i32 = row
             .try_get_by_index(2)
             .map_err(|e| DomainError::Database(e.to_string()))?;
+        let grace_days: i32 = row
+            .try_get_by_index(3)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+        // Unreadable JSON degrades to "no per-type durations" rather than
+        // failing every loan.
+        let type_durations: BTreeMap<String, i32> = row
+            .try_get_by_index::<Option<String>>(4)
+            .map_err(|e| DomainError::Database(e.to_string()))?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let hold_days: i32 = row
+            .try_get_by_index(5)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+        let request_expiry_days: i32 = row
+            .try_get_by_index(6)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+        let retention_days: i32 = row
+            .try_get_by_index(7)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+        let fine_per_day: f64 = row
+            .try_get_by_index(8)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+        let max_fine: f64 = row
+            .try_get_by_index(9)
+            .map_err(|e| DomainError::Database(e.to_string()))?;
 
         Ok(LoanSettings {
             default_loan_duration_days: days,
             per_book_duration_enabled: per_book,
             reminder_days_before_due: reminder_days,
+            grace_period_days: grace_days,
+            contact_type_durations: type_durations,
+            hold_expiry_days: hold_days,
+            request_expiry_days,
+            resolved_request_retention_days: retention_days,
+            fine_per_day,
+            max_fine,
         })
     }
 
     async fn update_settings(&self, settings: LoanSettings) -> Result<LoanSettings, DomainError> {
-        let days = settings.default_loan_duration_days.clamp(1, 365);
+        let settings = settings.normalized();
         let per_book = if settings.per_book_duration_enabled {
             1
         } else {
             0
         };
-        let reminder = settings.reminder_days_before_due.clamp(1, 10);
+        let type_durations_json = serde_json::to_string(&settings.contact_type_durations)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
 
+        // Contact types are user-supplied strings: bind the JSON as a value.
         self.db
-            .execute(Statement::from_string(
+            .execute(Statement::from_sql_and_values(
                 self.db.get_database_backend(),
-                format!(
-                    "UPDATE loan_settings SET default_loan_duration_days = {}, per_book_duration_enabled = {}, reminder_days_before_due = {} WHERE id = 1",
-                    days, per_book, reminder,
-                ),
+                "UPDATE loan_settings SET default_loan_duration_days = ?, per_book_duration_enabled = ?, reminder_days_before_due = ?, grace_period_days = ?, contact_type_durations = ?, hold_expiry_days = ?, request_expiry_days = ?, resolved_request_retention_days = ?, fine_per_day = ?, max_fine = ? WHERE id = 1",
+                [
+                    settings.default_loan_duration_days.into(),
+                    per_book.into(),
+                    settings.reminder_days_before_due.into(),
+                    settings.grace_period_days.into(),
+                    type_durations_json.into(),
+                    settings.hold_expiry_days.into(),
+                    settings.request_expiry_days.into(),
+                    settings.resolved_request_retention_days.into(),
+                    settings.fine_per_day.into(),
+                    settings.max_fine.into(),
+                ],
             ))
             .await
             .map_err(|e| DomainError::Database(e.to_string()))?;
 
-        Ok(LoanSettings {
-            default_loan_duration_days: days,
-            per_book_duration_enabled: settings.per_book_duration_enabled,
-            reminder_days_before_due: reminder,
-        })
+        Ok(settings)
     }
 
     async fn get_book_loan_duration(&self, book_id: &str) -> Result<Option<i32>, DomainError> {
@@ -107,16 +148,61 @@ impl LoanSettingsRepository for SeaOrmLoanSettingsRepository {
         Ok(())
     }
 
-    async fn get_effective_duration(&self, book_id: &str) -> Result<i32, DomainError> {
+    async fn get_peer_loan_duration(&self, peer_id: i32) -> Result<Option<i32>, DomainError> {
+        let peer = peer::Entity::find_by_id(peer_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| DomainError::Database(e.to_string()))?
+            .ok_or(DomainError::NotFound)?;
+
+        Ok(peer.loan_duration_days)
+    }
+
+    async fn set_peer_loan_duration(
+        &self,
+        peer_id: i32,
+        days: Option<i32>,
+    ) -> Result<(), DomainError> {
+        let clamped = days.map(|d| d.clamp(1, 365));
+
+        let peer = peer::Entity::find_by_id(peer_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| DomainError::Database(e.to_string()))?
+            .ok_or(DomainError::NotFound)?;
+
+        let mut active: peer::ActiveModel = peer.into();
+        active.loan_duration_days = Set(clamped);
+        ActiveModelTrait::update(active, &self.db)
+            .await
+            .map_err(|e| DomainError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn time_zone(&self) -> Result<Option<String>, DomainError> {
+        let config = library_config::Entity::find_by_id(1).one(&self.db).await?;
+        Ok(config.and_then(|c| c.timezone))
+    }
+
+    async fn get_effective_duration_for(
+        &self,
+        book_id: &str,
+        borrower: &LoanBorrower,
+    ) -> Result<i32, DomainError> {
         let settings = self.get_settings().await?;
 
-        if settings.per_book_duration_enabled
-            && let Ok(Some(days)) = self.get_book_loan_duration(book_id).await
-        {
-            return Ok(days);
-        }
+        let book_days = if settings.per_book_duration_enabled {
+            self.get_book_loan_duration(book_id).await.ok().flatten()
+        } else {
+            None
+        };
+        let peer_days = match borrower.peer_id {
+            Some(id) => self.get_peer_loan_duration(id).await.ok().flatten(),
+            None => None,
+        };
 
-        Ok(settings.default_loan_duration_days)
+        Ok(settings.resolve_duration(book_days, peer_days, borrower.contact_type.as_deref()))
     }
 }
 
@@ -156,6 +242,7 @@ mod tests {
                 default_loan_duration_days: 40,
                 per_book_duration_enabled: true,
                 reminder_days_before_due: 5,
+                ..Default::default()
             })
             .await
             .unwrap();
@@ -169,4 +256,66 @@ mod tests {
         assert!(reloaded.per_book_duration_enabled);
         assert_eq!(reloaded.reminder_days_before_due, 5);
     }
+
+    #[tokio::test]
+    async fn test_policy_round_trip_and_peer_override() {
+        let db = setup_test_db().await;
+        let repo = SeaOrmLoanSettingsRepository::new(db.clone());
+
+        let updated = repo
+            .update_settings(LoanSettings {
+                grace_period_days: 3,
+                hold_expiry_days: 60,
+                request_expiry_days: 0,
+                resolved_request_retention_days: 400,
+                contact_type_durations: BTreeMap::from([
+                    ("Library".to_string(), 30),
+                    ("Borrower's \"club\"".to_string(), 500),
+                ]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.contact_type_durations["Borrower's \"club\""], 365);
+        assert_eq!(updated.hold_expiry_days, 30);
+        assert_eq!(updated.request_expiry_days, 0);
+        assert_eq!(updated.resolved_request_retention_days, 365);
+
+        let reloaded = repo.get_settings().await.unwrap();
+        assert_eq!(reloaded, updated);
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let peer = peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://bob.local:8080".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let library = LoanBorrower {
+            contact_type: Some("Library".to_string()),
+            peer_id: Some(peer.id),
+        };
+        assert_eq!(
+            repo.get_effective_duration_for("no-book", &library)
+                .await
+                .unwrap(),
+            30
+        );
+
+        repo.set_peer_loan_duration(peer.id, Some(10))
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_effective_duration_for("no-book", &library)
+                .await
+                .unwrap(),
+            10
+        );
+        assert_eq!(repo.get_effective_duration("no-book").await.unwrap(), 21);
+    }
 }
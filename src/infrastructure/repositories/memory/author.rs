@@ -0,0 +1,64 @@
+//! In-memory AuthorRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{Author, AuthorRepository, DomainError};
+
+/// In-memory AuthorRepository
+#[derive(Default)]
+pub struct InMemoryAuthorRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryAuthorRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl AuthorRepository for InMemoryAuthorRepository {
+    async fn find_all(&self) -> Result<Vec<Author>, DomainError> {
+        Ok(self.store.lock().authors.clone())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Author>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .authors
+            .iter()
+            .find(|a| a.id == id)
+            .cloned())
+    }
+
+    async fn create(&self, name: String) -> Result<Author, DomainError> {
+        let now = now();
+        let author = Author {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            name,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.store.lock().authors.push(author.clone());
+        Ok(author)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.authors.len();
+        tables.authors.retain(|a| a.id != id);
+        if tables.authors.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        tables.book_authors.retain(|ba| ba.author_id != id);
+        Ok(())
+    }
+}
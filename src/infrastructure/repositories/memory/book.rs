@@ -0,0 +1,344 @@
+//! In-memory BookRepository
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, Tables, like, now};
+use crate::domain::{BookFilter, BookRepository, DomainError, PaginatedBooks};
+use crate::infrastructure::repositories::book_repository::normalize_isbn;
+use crate::models::Book;
+use crate::models::book;
+
+/// In-memory BookRepository. Rows are kept as `book::Model`, so reads go
+/// through the same `Book::from` conversion as the SeaORM implementation.
+#[derive(Default)]
+pub struct InMemoryBookRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryBookRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored book row as is
+    pub fn insert(&self, book: book::Model) {
+        self.store.lock().books.push(book);
+    }
+}
+
+impl Tables {
+    /// `Book::populate_authors` against the store: author names, available
+    /// copies and the `borrowed` / `lent` reading status overlay.
+    fn book_dto(&self, model: book::Model) -> Book {
+        let id = model.id.clone();
+        let mut dto = Book::from(model);
+
+        let names: Vec<String> = self
+            .book_authors
+            .iter()
+            .filter(|ba| ba.book_id == id)
+            .filter_map(|ba| self.authors.iter().find(|a| a.id == ba.author_id))
+            .map(|a| a.name.clone())
+            .collect();
+        if !names.is_empty() {
+            dto.author = Some(names.join(", "));
+            dto.authors = Some(names);
+        }
+
+        let copies = || self.copies.iter().filter(|c| c.book_id == id);
+        dto.available_copies = Some(copies().filter(|c| c.status == "available").count() as i32);
+        if copies().any(|c| c.status == "borrowed") {
+            dto.reading_status = Some("borrowed".to_string());
+        } else if copies().any(|c| c.status == "loaned") {
+            dto.reading_status = Some("lent".to_string());
+        }
+        dto
+    }
+
+    /// A collection's uuid from its uuid or, failing that, its name
+    /// (case-insensitive), like `SeaOrmBookRepository::resolve_collection_id`.
+    fn resolve_collection_id(&self, reference: &str) -> Option<String> {
+        self.collections
+            .iter()
+            .find(|c| c.id == reference)
+            .or_else(|| {
+                self.collections
+                    .iter()
+                    .find(|c| c.name.to_lowercase() == reference.to_lowercase())
+            })
+            .map(|c| c.id.clone())
+    }
+
+    /// `Book::tag_restricted_ids`: books naming a private tag, or a tag nested
+    /// under one, in their `subjects`.
+    fn tag_restricted_ids(&self) -> HashSet<String> {
+        let is_restricted = |tag: &crate::domain::Tag| {
+            let mut current = Some(tag);
+            while let Some(t) = current {
+                if t.private {
+                    return true;
+                }
+                current = t
+                    .parent_id
+                    .as_deref()
+                    .and_then(|parent| self.tags.iter().find(|p| p.id == parent));
+            }
+            false
+        };
+        let mut names = HashSet::new();
+        for tag in self.tags.iter().filter(|t| is_restricted(t)) {
+            names.insert(tag.name.clone());
+            if !tag.path.is_empty() {
+                names.insert(format!("{} > {}", tag.path, tag.name));
+            }
+        }
+        if names.is_empty() {
+            return HashSet::new();
+        }
+
+        self.books
+            .iter()
+            .filter(|b| {
+                b.subjects
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str::<Vec<String>>(s).ok())
+                    .is_some_and(|subjects| subjects.iter().any(|s| names.contains(s)))
+            })
+            .map(|b| b.id.clone())
+            .collect()
+    }
+
+    /// The `query` filter: title, ISBN, subjects or an author's name.
+    fn matches_query(&self, book: &book::Model, query: &str) -> bool {
+        like(&book.title, query)
+            || book.isbn.as_deref().is_some_and(|isbn| like(isbn, query))
+            || book.subjects.as_deref().is_some_and(|s| like(s, query))
+            || self
+                .book_authors
+                .iter()
+                .filter(|ba| ba.book_id == book.id)
+                .filter_map(|ba| self.authors.iter().find(|a| a.id == ba.author_id))
+                .any(|a| like(&a.name, query))
+    }
+}
+
+fn to_json(values: Option<&Vec<String>>) -> Option<String> {
+    values.map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
+}
+
+#[async_trait]
+impl BookRepository for InMemoryBookRepository {
+    async fn find_all(&self, filter: BookFilter) -> Result<PaginatedBooks, DomainError> {
+        let tables = self.store.lock();
+        let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+
+        let in_collection: Option<HashSet<String>> = match non_empty(&filter.collection) {
+            Some(reference) => match tables.resolve_collection_id(&reference) {
+                Some(collection_id) => Some(
+                    tables
+                        .collection_books
+                        .iter()
+                        .filter(|cb| cb.collection_id == collection_id)
+                        .map(|cb| cb.book_id.clone())
+                        .collect(),
+                ),
+                // An unknown collection holds nothing.
+                None => {
+                    return Ok(PaginatedBooks {
+                        books: vec![],
+                        total: 0,
+                    });
+                }
+            },
+            None => None,
+        };
+
+        let shared_only = filter.owned_only == Some(true);
+        let restricted = if shared_only {
+            tables.tag_restricted_ids()
+        } else {
+            HashSet::new()
+        };
+
+        let status = non_empty(&filter.status);
+        let title = non_empty(&filter.title);
+        let tag = non_empty(&filter.tag);
+        let query = non_empty(&filter.query);
+        let mut books: Vec<&book::Model> = tables
+            .books
+            .iter()
+            .filter(|b| status.as_ref().is_none_or(|s| &b.reading_status == s))
+            .filter(|b| title.as_ref().is_none_or(|t| like(&b.title, t)))
+            .filter(|b| {
+                tag.as_ref()
+                    .is_none_or(|t| b.subjects.as_deref().is_some_and(|s| like(s, t)))
+            })
+            .filter(|b| query.as_ref().is_none_or(|q| tables.matches_query(b, q)))
+            .filter(|b| b.archived == (filter.archived == Some(true)))
+            .filter(|b| {
+                !shared_only
+                    || (b.owned && !b.private && !b.archived && !restricted.contains(&b.id))
+            })
+            .filter(|b| filter.owned.is_none_or(|owned| b.owned == owned))
+            .filter(|b| in_collection.as_ref().is_none_or(|ids| ids.contains(&b.id)))
+            .collect();
+
+        match filter.sort.as_deref() {
+            Some("title_asc") => books.sort_by(|a, b| a.title.cmp(&b.title)),
+            Some("title_desc") => books.sort_by(|a, b| b.title.cmp(&a.title)),
+            Some("recent") => books.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+            // `None` first, as SQLite sorts NULL.
+            _ => books.sort_by_key(|b| b.shelf_position),
+        }
+
+        let total = books.len() as u64;
+        if let Some(limit) = filter.limit {
+            let page = filter.page.unwrap_or(0);
+            books = books
+                .into_iter()
+                .skip((page * limit) as usize)
+                .take(limit as usize)
+                .collect();
+        }
+
+        let books = books
+            .into_iter()
+            .map(|b| tables.book_dto(b.clone()))
+            .collect();
+        Ok(PaginatedBooks { books, total })
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Book>, DomainError> {
+        let tables = self.store.lock();
+        let found = tables.books.iter().find(|b| b.id == id).cloned();
+        Ok(found.map(|b| tables.book_dto(b)))
+    }
+
+    async fn find_by_isbn(&self, isbn: &str) -> Result<Option<Book>, DomainError> {
+        let tables = self.store.lock();
+        // Oldest wins, like the SeaORM implementation.
+        let found = tables
+            .books
+            .iter()
+            .filter(|b| b.isbn.as_deref() == Some(isbn))
+            .min_by(|a, b| a.created_at.cmp(&b.created_at))
+            .cloned();
+        Ok(found.map(|b| tables.book_dto(b)))
+    }
+
+    async fn create(&self, book: Book) -> Result<Book, DomainError> {
+        let now = now();
+        let reading_status = book
+            .reading_status
+            .clone()
+            .unwrap_or_else(|| "to_read".to_string());
+        let owned = book.owned.unwrap_or_else(|| reading_status != "wanting");
+
+        let model = book::Model {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            title: book.title,
+            isbn: normalize_isbn(book.isbn),
+            summary: book.summary,
+            publisher: book.publisher,
+            publication_year: book.publication_year,
+            dewey_decimal: book.dewey_decimal,
+            lcc: book.lcc,
+            subjects: to_json(book.subjects.as_ref()),
+            marc_record: book.marc_record,
+            cataloguing_notes: book.cataloguing_notes,
+            source_data: book.source_data,
+            shelf_position: book.shelf_position,
+            reading_status,
+            finished_reading_at: book.finished_reading_at.flatten(),
+            started_reading_at: book.started_reading_at.flatten(),
+            cover_url: book.cover_url,
+            created_at: now.clone(),
+            updated_at: now,
+            user_rating: book.user_rating,
+            owned,
+            price: book.price,
+            digital_formats: to_json(book.digital_formats.as_ref()),
+            private: book.private.unwrap_or(false),
+            page_count: None,
+            loan_duration_days: None,
+            edition: None,
+            physical_format: None,
+            dimensions: None,
+            archived: false,
+            digital_source_url: None,
+        };
+        self.store.lock().books.push(model.clone());
+        Ok(Book::from(model))
+    }
+
+    async fn update(&self, id: &str, book: Book) -> Result<Book, DomainError> {
+        let mut tables = self.store.lock();
+        let model = tables
+            .books
+            .iter_mut()
+            .find(|b| b.id == id)
+            .ok_or(DomainError::NotFound)?;
+
+        model.title = book.title;
+        model.isbn = normalize_isbn(book.isbn);
+        model.summary = book.summary;
+        model.publisher = book.publisher;
+        model.publication_year = book.publication_year;
+        model.dewey_decimal = book.dewey_decimal;
+        model.lcc = book.lcc;
+        model.cover_url = book.cover_url;
+        model.subjects = to_json(book.subjects.as_ref());
+        model.marc_record = book.marc_record;
+        model.cataloguing_notes = book.cataloguing_notes;
+        model.reading_status = book.reading_status.unwrap_or_else(|| "to_read".to_string());
+        model.shelf_position = book.shelf_position;
+        model.user_rating = book.user_rating;
+        model.owned = book.owned.unwrap_or(true);
+        model.price = book.price;
+        model.digital_formats = to_json(book.digital_formats.as_ref());
+        model.finished_reading_at = book.finished_reading_at.flatten();
+        model.started_reading_at = book.started_reading_at.flatten();
+        model.private = book.private.unwrap_or(false);
+        model.updated_at = now();
+
+        Ok(Book::from(model.clone()))
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.books.len();
+        tables.books.retain(|b| b.id != id);
+        if tables.books.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn find_missing_covers(&self) -> Result<Vec<(String, String)>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .books
+            .iter()
+            .filter(|b| b.cover_url.is_none())
+            .filter_map(|b| {
+                let isbn = b.isbn.as_deref()?.trim();
+                (!isbn.is_empty()).then(|| (b.id.clone(), isbn.to_string()))
+            })
+            .collect())
+    }
+
+    async fn update_cover_url(&self, id: &str, cover_url: &str) -> Result<(), DomainError> {
+        if let Some(book) = self.store.lock().books.iter_mut().find(|b| b.id == id) {
+            book.cover_url = Some(cover_url.to_string());
+        }
+        Ok(())
+    }
+}
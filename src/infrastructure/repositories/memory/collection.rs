@@ -0,0 +1,246 @@
+//! In-memory CollectionRepository
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, Tables, now};
+use crate::domain::{
+    Collection, CollectionBook, CollectionRepository, CreateCollectionInput, DomainError,
+};
+use crate::models::{collection, collection_book};
+
+/// In-memory CollectionRepository
+#[derive(Default)]
+pub struct InMemoryCollectionRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryCollectionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl Tables {
+    /// A collection with its total and owned book counts.
+    fn counted_collection(&self, col: &collection::Model) -> Collection {
+        let members = || {
+            self.collection_books
+                .iter()
+                .filter(|cb| cb.collection_id == col.id)
+        };
+        let owned = members()
+            .filter(|cb| self.books.iter().any(|b| b.id == cb.book_id && b.owned))
+            .count();
+        Collection {
+            total_books: members().count() as i64,
+            owned_books: owned as i64,
+            ..uncounted(col)
+        }
+    }
+}
+
+fn uncounted(col: &collection::Model) -> Collection {
+    Collection {
+        id: col.id.clone(),
+        name: col.name.clone(),
+        description: col.description.clone(),
+        source: col.source.clone(),
+        created_at: col.created_at.clone(),
+        updated_at: col.updated_at.clone(),
+        total_books: 0,
+        owned_books: 0,
+    }
+}
+
+#[async_trait]
+impl CollectionRepository for InMemoryCollectionRepository {
+    async fn find_all(&self) -> Result<Vec<Collection>, DomainError> {
+        let tables = self.store.lock();
+        let mut collections: Vec<&collection::Model> = tables.collections.iter().collect();
+        collections.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(collections
+            .into_iter()
+            .map(|c| tables.counted_collection(c))
+            .collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Collection>, DomainError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .collections
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| tables.counted_collection(c)))
+    }
+
+    async fn create(&self, input: CreateCollectionInput) -> Result<Collection, DomainError> {
+        let now = now();
+        let model = collection::Model {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: input.name,
+            description: input.description,
+            source: input.source.unwrap_or_else(|| "manual".to_string()),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        let created = uncounted(&model);
+        self.store.lock().collections.push(model);
+        Ok(created)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.collections.len();
+        tables.collections.retain(|c| c.id != id);
+        if tables.collections.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        tables.collection_books.retain(|cb| cb.collection_id != id);
+        Ok(())
+    }
+
+    async fn set_source(&self, id: &str, source: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let col = tables
+            .collections
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(DomainError::NotFound)?;
+        col.source = source.to_owned();
+        col.updated_at = now();
+        Ok(())
+    }
+
+    async fn get_books(&self, collection_id: &str) -> Result<Vec<CollectionBook>, DomainError> {
+        let tables = self.store.lock();
+        let mut members: Vec<&collection_book::Model> = tables
+            .collection_books
+            .iter()
+            .filter(|cb| cb.collection_id == collection_id)
+            .collect();
+        // Numbered volumes ascending, then unnumbered by `added_at`.
+        members.sort_by(|a, b| {
+            (a.volume_number.is_none(), a.volume_number)
+                .cmp(&(b.volume_number.is_none(), b.volume_number))
+                .then_with(|| a.added_at.cmp(&b.added_at))
+        });
+
+        Ok(members
+            .into_iter()
+            .filter_map(|cb| {
+                let book = tables.books.iter().find(|b| b.id == cb.book_id)?;
+                Some(CollectionBook {
+                    book_id: book.id.clone(),
+                    title: book.title.clone(),
+                    author: None,
+                    cover_url: book.cover_url.clone(),
+                    publisher: book.publisher.clone(),
+                    publication_year: book.publication_year,
+                    added_at: cb.added_at.clone(),
+                    is_owned: book.owned,
+                    digital_formats: book
+                        .digital_formats
+                        .as_deref()
+                        .and_then(|s| serde_json::from_str(s).ok()),
+                    reading_status: Some(book.reading_status.clone()),
+                    volume_number: cb.volume_number,
+                })
+            })
+            .collect())
+    }
+
+    async fn set_book_volume(
+        &self,
+        collection_id: &str,
+        book_id: &str,
+        volume_number: Option<i32>,
+    ) -> Result<(), DomainError> {
+        // No-op if the book is not a member.
+        if let Some(cb) = self
+            .store
+            .lock()
+            .collection_books
+            .iter_mut()
+            .find(|cb| cb.collection_id == collection_id && cb.book_id == book_id)
+        {
+            cb.volume_number = volume_number;
+        }
+        Ok(())
+    }
+
+    async fn add_book(&self, collection_id: &str, book_id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        if tables
+            .collection_books
+            .iter()
+            .any(|cb| cb.collection_id == collection_id && cb.book_id == book_id)
+        {
+            return Ok(());
+        }
+        tables.collection_books.push(collection_book::Model {
+            collection_id: collection_id.to_owned(),
+            book_id: book_id.to_owned(),
+            added_at: now(),
+            volume_number: None,
+        });
+        Ok(())
+    }
+
+    async fn remove_book(&self, collection_id: &str, book_id: &str) -> Result<(), DomainError> {
+        self.store
+            .lock()
+            .collection_books
+            .retain(|cb| !(cb.collection_id == collection_id && cb.book_id == book_id));
+        Ok(())
+    }
+
+    async fn get_book_collections(&self, book_id: &str) -> Result<Vec<Collection>, DomainError> {
+        let tables = self.store.lock();
+        Ok(tables
+            .collections
+            .iter()
+            .filter(|c| {
+                tables
+                    .collection_books
+                    .iter()
+                    .any(|cb| cb.collection_id == c.id && cb.book_id == book_id)
+            })
+            .map(uncounted)
+            .collect())
+    }
+
+    async fn update_book_collections(
+        &self,
+        book_id: &str,
+        collection_ids: Vec<String>,
+    ) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        // Volume numbers survive for the collections the book stays in.
+        let previous: HashMap<String, Option<i32>> = tables
+            .collection_books
+            .iter()
+            .filter(|cb| cb.book_id == book_id)
+            .map(|cb| (cb.collection_id.clone(), cb.volume_number))
+            .collect();
+        tables.collection_books.retain(|cb| cb.book_id != book_id);
+
+        let now = now();
+        for collection_id in collection_ids {
+            let volume_number = previous.get(&collection_id).copied().flatten();
+            tables.collection_books.push(collection_book::Model {
+                collection_id,
+                book_id: book_id.to_owned(),
+                added_at: now.clone(),
+                volume_number,
+            });
+        }
+        Ok(())
+    }
+}
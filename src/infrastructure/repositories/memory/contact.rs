@@ -0,0 +1,143 @@
+//! In-memory ContactRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{Contact, ContactInput, ContactQuery, ContactRepository, DomainError};
+
+/// In-memory ContactRepository. Contacts created without an owner belong to
+/// library 1.
+#[derive(Default)]
+pub struct InMemoryContactRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryContactRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored contact as is
+    pub fn insert(&self, contact: Contact) {
+        self.store.lock().contacts.push(contact);
+    }
+}
+
+#[async_trait]
+impl ContactRepository for InMemoryContactRepository {
+    async fn find_active(&self, query: &ContactQuery) -> Result<Vec<Contact>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .contacts
+            .iter()
+            .filter(|c| c.is_active)
+            .filter(|c| query.library_id.is_none_or(|id| c.library_owner_id == id))
+            .filter(|c| {
+                query
+                    .contact_type
+                    .as_ref()
+                    .is_none_or(|t| &c.contact_type == t)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Contact>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .contacts
+            .iter()
+            .find(|c| c.id == id)
+            .cloned())
+    }
+
+    async fn create(&self, input: ContactInput) -> Result<Contact, DomainError> {
+        let now = now();
+        let contact = Contact {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            contact_type: input.contact_type,
+            name: input.name,
+            first_name: input.first_name,
+            email: input.email,
+            phone: input.phone,
+            address: input.address,
+            street_address: input.street_address,
+            postal_code: input.postal_code,
+            city: input.city,
+            country: input.country,
+            latitude: input.latitude,
+            longitude: input.longitude,
+            notes: input.notes,
+            user_id: input.user_id,
+            library_owner_id: input.library_owner_id.unwrap_or(1),
+            is_active: input.is_active,
+            created_at: now.clone(),
+            updated_at: now,
+            member_number: None,
+            member_since: None,
+            membership_expires_at: None,
+            membership_fee_paid: None,
+        };
+        self.store.lock().contacts.push(contact.clone());
+        Ok(contact)
+    }
+
+    async fn update(&self, id: &str, input: ContactInput) -> Result<Contact, DomainError> {
+        let mut tables = self.store.lock();
+        let contact = tables
+            .contacts
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(DomainError::NotFound)?;
+        *contact = Contact {
+            id: contact.id.clone(),
+            contact_type: input.contact_type,
+            name: input.name,
+            first_name: input.first_name,
+            email: input.email,
+            phone: input.phone,
+            address: input.address,
+            street_address: input.street_address,
+            postal_code: input.postal_code,
+            city: input.city,
+            country: input.country,
+            latitude: input.latitude,
+            longitude: input.longitude,
+            notes: input.notes,
+            user_id: input.user_id,
+            library_owner_id: input.library_owner_id.unwrap_or(contact.library_owner_id),
+            is_active: input.is_active,
+            created_at: contact.created_at.clone(),
+            updated_at: now(),
+            member_number: contact.member_number.take(),
+            member_since: contact.member_since.take(),
+            membership_expires_at: contact.membership_expires_at.take(),
+            membership_fee_paid: contact.membership_fee_paid,
+        };
+        Ok(contact.clone())
+    }
+
+    async fn deactivate(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let contact = tables
+            .contacts
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(DomainError::NotFound)?;
+        contact.is_active = false;
+        contact.updated_at = now();
+        Ok(())
+    }
+
+    async fn count(&self) -> Result<i64, DomainError> {
+        Ok(self.store.lock().contacts.len() as i64)
+    }
+}
@@ -0,0 +1,178 @@
+//! In-memory CopyRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, Tables, now};
+use crate::domain::{
+    Copy, CopyRepository, CreateCopyInput, DomainError, PaginatedCopies, UpdateCopyInput,
+};
+use crate::infrastructure::repositories::copy_repository::to_domain;
+use crate::models::copy;
+
+/// In-memory CopyRepository. Creating a copy does not check its library: the
+/// store holds no `libraries` table.
+#[derive(Default)]
+pub struct InMemoryCopyRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryCopyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored copy row as is
+    pub fn insert(&self, copy: copy::Model) {
+        self.store.lock().copies.push(copy);
+    }
+}
+
+impl Tables {
+    /// Copies matching `keep`, with their book's title and cover.
+    fn copies_with_books(&self, keep: impl Fn(&copy::Model) -> bool) -> PaginatedCopies {
+        let copies: Vec<Copy> = self
+            .copies
+            .iter()
+            .filter(|c| keep(c))
+            .map(|c| {
+                let book = self.books.iter().find(|b| b.id == c.book_id).cloned();
+                to_domain(c.clone(), book)
+            })
+            .collect();
+        let total = copies.len();
+        PaginatedCopies { copies, total }
+    }
+}
+
+#[async_trait]
+impl CopyRepository for InMemoryCopyRepository {
+    async fn find_all(&self) -> Result<PaginatedCopies, DomainError> {
+        Ok(self.store.lock().copies_with_books(|_| true))
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Copy>, DomainError> {
+        let found = self.store.lock().copies_with_books(|c| c.id == id);
+        Ok(found.copies.into_iter().next())
+    }
+
+    async fn find_by_book_id(&self, book_id: &str) -> Result<PaginatedCopies, DomainError> {
+        let copies: Vec<Copy> = self
+            .store
+            .lock()
+            .copies
+            .iter()
+            .filter(|c| c.book_id == book_id)
+            .map(|c| to_domain(c.clone(), None))
+            .collect();
+        let total = copies.len();
+        Ok(PaginatedCopies { copies, total })
+    }
+
+    async fn find_borrowed(&self) -> Result<PaginatedCopies, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .copies_with_books(|c| c.status == "borrowed"))
+    }
+
+    async fn create(&self, input: CreateCopyInput) -> Result<Copy, DomainError> {
+        let now = now();
+        let model = copy::Model {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            book_id: input.book_id,
+            library_id: input.library_id,
+            acquisition_date: input.acquisition_date,
+            notes: input.notes,
+            status: input.status,
+            is_temporary: input.is_temporary,
+            created_at: now.clone(),
+            updated_at: now,
+            sold_at: None,
+            price: input.price,
+            lender_display_name: input.lender_display_name,
+            lender_peer_id: input.lender_peer_id,
+            borrow_due_date: input.borrow_due_date,
+            borrow_source: input.borrow_source,
+            lender_library_uuid: None,
+            lender_request_id: None,
+            lending_terms: None,
+            weeding_reason: None,
+            weeding_note: None,
+            weeding_flagged_at: None,
+            withdrawn_at: None,
+        };
+        self.store.lock().copies.push(model.clone());
+        Ok(to_domain(model, None))
+    }
+
+    async fn update(&self, id: &str, input: UpdateCopyInput) -> Result<Copy, DomainError> {
+        let mut tables = self.store.lock();
+        let copy = tables
+            .copies
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(DomainError::NotFound)?;
+
+        if let Some(status) = input.status {
+            copy.status = status;
+        }
+        if let Some(notes) = input.notes {
+            copy.notes = notes;
+        }
+        if let Some(date) = input.acquisition_date {
+            copy.acquisition_date = date;
+        }
+        if let Some(price) = input.price {
+            copy.price = price;
+        }
+        if let Some(name) = input.lender_display_name {
+            copy.lender_display_name = name;
+        }
+        if let Some(peer_id) = input.lender_peer_id {
+            copy.lender_peer_id = peer_id;
+        }
+        if let Some(due) = input.borrow_due_date {
+            copy.borrow_due_date = due;
+        }
+        if let Some(source) = input.borrow_source {
+            copy.borrow_source = source;
+        }
+        if let Some(terms) = input.lending_terms {
+            copy.lending_terms = terms.map(|t| t.to_column());
+        }
+        copy.updated_at = now();
+
+        Ok(to_domain(copy.clone(), None))
+    }
+
+    async fn set_status(&self, id: &str, status: &str) -> Result<Copy, DomainError> {
+        let mut tables = self.store.lock();
+        let copy = tables
+            .copies
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(DomainError::NotFound)?;
+        copy.status = status.to_owned();
+        copy.updated_at = now();
+
+        Ok(to_domain(copy.clone(), None))
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.copies.len();
+        tables.copies.retain(|c| c.id != id);
+        if tables.copies.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        // `delete_copy_cascade` also drops the copy's loans.
+        tables.loans.retain(|l| l.copy_id != id);
+        Ok(())
+    }
+}
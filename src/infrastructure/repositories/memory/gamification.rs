@@ -0,0 +1,211 @@
+//! In-memory GamificationRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{
+    DomainError, GamificationConfigRow, GamificationConfigUpdate, GamificationRepository,
+    PeerGamificationStatsRow,
+};
+
+/// In-memory GamificationRepository. Counts read the books and loans of the
+/// store; the profile, library name and user are seeded with the setters
+/// below.
+#[derive(Default)]
+pub struct InMemoryGamificationRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryGamificationRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Set the single local user returned by `get_user_id`
+    pub fn set_user_id(&self, user_id: i32) {
+        self.store.lock().user_id = Some(user_id);
+    }
+
+    /// Set the name returned by `get_library_name`
+    pub fn set_library_name(&self, name: &str) {
+        self.store.lock().library_name = Some(name.to_owned());
+    }
+
+    /// Enable a module of the installation profile
+    pub fn enable_module(&self, module: &str) {
+        self.store.lock().enabled_modules.push(module.to_owned());
+    }
+
+    /// Seed a peer's synced stats as is
+    pub fn insert_peer_stats(&self, stats: PeerGamificationStatsRow) {
+        self.store.lock().peer_stats.push(stats);
+    }
+}
+
+#[async_trait]
+impl GamificationRepository for InMemoryGamificationRepository {
+    async fn count_books(&self) -> Result<i64, DomainError> {
+        Ok(self.store.lock().books.len() as i64)
+    }
+
+    async fn count_books_read(&self) -> Result<i64, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .books
+            .iter()
+            .filter(|b| b.reading_status == "read")
+            .count() as i64)
+    }
+
+    async fn count_books_read_in_year(&self, year: &str) -> Result<i64, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .books
+            .iter()
+            .filter(|b| {
+                b.finished_reading_at
+                    .as_deref()
+                    .is_some_and(|at| at.starts_with(year))
+            })
+            .count() as i64)
+    }
+
+    async fn count_loans(&self) -> Result<i64, DomainError> {
+        Ok(self.store.lock().loans.len() as i64)
+    }
+
+    async fn count_catalogued_books(&self) -> Result<i64, DomainError> {
+        // Books are tagged through their `subjects` JSON column.
+        Ok(self
+            .store
+            .lock()
+            .books
+            .iter()
+            .filter(|b| {
+                b.subjects
+                    .as_deref()
+                    .is_some_and(|s| !matches!(s, "" | "[]" | "null"))
+            })
+            .count() as i64)
+    }
+
+    async fn get_streak(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<(i32, i32, Option<String>)>, DomainError> {
+        Ok(self.store.lock().streaks.get(&user_id).cloned())
+    }
+
+    async fn update_streak(
+        &self,
+        user_id: i32,
+        current: i32,
+        longest: i32,
+        last_date: &str,
+    ) -> Result<(), DomainError> {
+        self.store
+            .lock()
+            .streaks
+            .insert(user_id, (current, longest, Some(last_date.to_string())));
+        Ok(())
+    }
+
+    async fn get_recent_achievements(
+        &self,
+        user_id: i32,
+        limit: u32,
+    ) -> Result<Vec<String>, DomainError> {
+        let tables = self.store.lock();
+        let mut achievements: Vec<&(i32, String, String)> = tables
+            .achievements
+            .iter()
+            .filter(|(user, _, _)| *user == user_id)
+            .collect();
+        achievements.sort_by(|a, b| b.2.cmp(&a.2));
+        Ok(achievements
+            .into_iter()
+            .take(limit as usize)
+            .map(|(_, achievement_id, _)| achievement_id.clone())
+            .collect())
+    }
+
+    async fn unlock_achievement(
+        &self,
+        user_id: i32,
+        achievement_id: &str,
+    ) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        if tables
+            .achievements
+            .iter()
+            .any(|(user, id, _)| *user == user_id && id == achievement_id)
+        {
+            return Ok(false);
+        }
+        tables
+            .achievements
+            .push((user_id, achievement_id.to_string(), now()));
+        Ok(true)
+    }
+
+    async fn get_config(&self, user_id: i32) -> Result<Option<GamificationConfigRow>, DomainError> {
+        Ok(self.store.lock().gamification_config.get(&user_id).cloned())
+    }
+
+    async fn update_config(
+        &self,
+        user_id: i32,
+        config: GamificationConfigUpdate,
+    ) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        // A missing row is created with the defaults of the SeaORM implementation.
+        let row = tables
+            .gamification_config
+            .entry(user_id)
+            .or_insert_with(|| GamificationConfigRow {
+                achievements_style: "minimal".to_string(),
+                reading_goal_yearly: 12,
+            });
+        if let Some(goal) = config.reading_goal_yearly {
+            row.reading_goal_yearly = goal;
+        }
+        if let Some(style) = config.achievements_style {
+            row.achievements_style = style;
+        }
+        Ok(())
+    }
+
+    async fn get_peer_stats(&self) -> Result<Vec<PeerGamificationStatsRow>, DomainError> {
+        Ok(self.store.lock().peer_stats.clone())
+    }
+
+    async fn is_module_enabled(&self, module: &str) -> Result<bool, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .enabled_modules
+            .iter()
+            .any(|m| m == module))
+    }
+
+    async fn get_library_name(&self) -> Result<String, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .library_name
+            .clone()
+            .unwrap_or_else(|| "Unknown Library".to_string()))
+    }
+
+    async fn get_user_id(&self) -> Result<i32, DomainError> {
+        self.store.lock().user_id.ok_or(DomainError::NotFound)
+    }
+}
@@ -0,0 +1,86 @@
+//! In-memory LinkedDeviceRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{CreateLinkedDeviceInput, DomainError, LinkedDevice, LinkedDeviceRepository};
+
+/// In-memory LinkedDeviceRepository
+#[derive(Default)]
+pub struct InMemoryLinkedDeviceRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryLinkedDeviceRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl LinkedDeviceRepository for InMemoryLinkedDeviceRepository {
+    async fn find_all(&self) -> Result<Vec<LinkedDevice>, DomainError> {
+        Ok(self.store.lock().linked_devices.clone())
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<LinkedDevice>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .linked_devices
+            .iter()
+            .find(|d| d.id == Some(id))
+            .cloned())
+    }
+
+    async fn create(&self, input: CreateLinkedDeviceInput) -> Result<LinkedDevice, DomainError> {
+        let mut tables = self.store.lock();
+        let id = tables
+            .linked_devices
+            .iter()
+            .filter_map(|d| d.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let device = LinkedDevice {
+            id: Some(id),
+            name: input.name,
+            ed25519_public_key: input.ed25519_public_key,
+            x25519_public_key: input.x25519_public_key,
+            relay_url: input.relay_url,
+            mailbox_id: input.mailbox_id,
+            relay_write_token: input.relay_write_token,
+            last_synced: None,
+            created_at: Some(now()),
+        };
+        tables.linked_devices.push(device.clone());
+        Ok(device)
+    }
+
+    async fn update_last_synced(&self, id: i32, timestamp: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let device = tables
+            .linked_devices
+            .iter_mut()
+            .find(|d| d.id == Some(id))
+            .ok_or(DomainError::NotFound)?;
+        device.last_synced = Some(timestamp.to_owned());
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.linked_devices.len();
+        tables.linked_devices.retain(|d| d.id != Some(id));
+        if tables.linked_devices.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! In-memory LoanRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::MemoryStore;
+use crate::domain::{DomainError, Loan, LoanQuery, LoanRepository, NewLoan};
+
+/// In-memory LoanRepository
+#[derive(Default)]
+pub struct InMemoryLoanRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryLoanRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored loan as is
+    pub fn insert(&self, loan: Loan) {
+        self.store.lock().loans.push(loan);
+    }
+}
+
+#[async_trait]
+impl LoanRepository for InMemoryLoanRepository {
+    async fn find_by_id(&self, id: &str) -> Result<Option<Loan>, DomainError> {
+        Ok(self.store.lock().loans.iter().find(|l| l.id == id).cloned())
+    }
+
+    async fn find_by_contact(&self, contact_id: &str) -> Result<Vec<Loan>, DomainError> {
+        let mut loans: Vec<Loan> = self
+            .store
+            .lock()
+            .loans
+            .iter()
+            .filter(|l| l.contact_id == contact_id)
+            .cloned()
+            .collect();
+        loans.sort_by(|a, b| b.loan_date.cmp(&a.loan_date));
+        Ok(loans)
+    }
+
+    async fn find_all(&self, query: &LoanQuery) -> Result<Vec<Loan>, DomainError> {
+        let mut loans: Vec<Loan> = self
+            .store
+            .lock()
+            .loans
+            .iter()
+            .filter(|l| query.library_id.is_none_or(|id| l.library_id == id))
+            .filter(|l| query.status.as_ref().is_none_or(|s| &l.status == s))
+            .filter(|l| query.contact_id.as_ref().is_none_or(|c| &l.contact_id == c))
+            .cloned()
+            .collect();
+        loans.sort_by(|a, b| b.loan_date.cmp(&a.loan_date));
+        if let Some(limit) = query.limit {
+            let offset = query.offset.unwrap_or(0) as usize;
+            loans = loans
+                .into_iter()
+                .skip(offset)
+                .take(limit as usize)
+                .collect();
+        }
+        Ok(loans)
+    }
+
+    async fn create(&self, input: NewLoan) -> Result<Loan, DomainError> {
+        let loan = Loan {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            copy_id: input.copy_id,
+            contact_id: input.contact_id,
+            library_id: input.library_id,
+            loan_date: input.loan_date,
+            due_date: input.due_date,
+            return_date: None,
+            status: "active".to_owned(),
+            notes: input.notes,
+            lending_terms: input.lending_terms,
+            created_at: input.created_at.clone(),
+            updated_at: input.created_at,
+        };
+        self.store.lock().loans.push(loan.clone());
+        Ok(loan)
+    }
+
+    async fn mark_returned(&self, id: &str, at: &str) -> Result<Loan, DomainError> {
+        let mut tables = self.store.lock();
+        let loan = tables
+            .loans
+            .iter_mut()
+            .find(|l| l.id == id)
+            .ok_or(DomainError::NotFound)?;
+        loan.return_date = Some(at.to_owned());
+        loan.status = "returned".to_owned();
+        loan.updated_at = at.to_owned();
+        Ok(loan.clone())
+    }
+
+    async fn set_due_date(&self, id: &str, due_date: &str, at: &str) -> Result<Loan, DomainError> {
+        let mut tables = self.store.lock();
+        let loan = tables
+            .loans
+            .iter_mut()
+            .find(|l| l.id == id)
+            .ok_or(DomainError::NotFound)?;
+        loan.due_date = due_date.to_owned();
+        loan.updated_at = at.to_owned();
+        Ok(loan.clone())
+    }
+
+    async fn count(&self, status: Option<&str>) -> Result<i64, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .loans
+            .iter()
+            .filter(|l| status.is_none_or(|s| l.status == s))
+            .count() as i64)
+    }
+
+    async fn delete_with_status(&self, status: &str) -> Result<u64, DomainError> {
+        let mut tables = self.store.lock();
+        let loans = &mut tables.loans;
+        let before = loans.len();
+        loans.retain(|l| l.status != status);
+        Ok((before - loans.len()) as u64)
+    }
+}
@@ -0,0 +1,117 @@
+//! In-memory LoanSettingsRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::MemoryStore;
+use crate::domain::{DomainError, LoanBorrower, LoanSettings, LoanSettingsRepository};
+
+/// In-memory LoanSettingsRepository. Per-book and per-peer durations live on
+/// the books and peers of the store, as they do in the database.
+#[derive(Default)]
+pub struct InMemoryLoanSettingsRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryLoanSettingsRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Set the zone returned by `time_zone`
+    pub fn set_time_zone(&self, name: &str) {
+        self.store.lock().time_zone = Some(name.to_owned());
+    }
+}
+
+#[async_trait]
+impl LoanSettingsRepository for InMemoryLoanSettingsRepository {
+    async fn get_settings(&self) -> Result<LoanSettings, DomainError> {
+        Ok(self.store.lock().loan_settings.clone())
+    }
+
+    async fn update_settings(&self, settings: LoanSettings) -> Result<LoanSettings, DomainError> {
+        let settings = settings.normalized();
+        self.store.lock().loan_settings = settings.clone();
+        Ok(settings)
+    }
+
+    async fn get_book_loan_duration(&self, book_id: &str) -> Result<Option<i32>, DomainError> {
+        self.store
+            .lock()
+            .books
+            .iter()
+            .find(|b| b.id == book_id)
+            .map(|b| b.loan_duration_days)
+            .ok_or(DomainError::NotFound)
+    }
+
+    async fn set_book_loan_duration(
+        &self,
+        book_id: &str,
+        days: Option<i32>,
+    ) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let book = tables
+            .books
+            .iter_mut()
+            .find(|b| b.id == book_id)
+            .ok_or(DomainError::NotFound)?;
+        book.loan_duration_days = days.map(|d| d.clamp(1, 365));
+        Ok(())
+    }
+
+    async fn get_peer_loan_duration(&self, peer_id: i32) -> Result<Option<i32>, DomainError> {
+        self.store
+            .lock()
+            .peers
+            .iter()
+            .find(|p| p.id == peer_id)
+            .map(|p| p.loan_duration_days)
+            .ok_or(DomainError::NotFound)
+    }
+
+    async fn set_peer_loan_duration(
+        &self,
+        peer_id: i32,
+        days: Option<i32>,
+    ) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let peer = tables
+            .peers
+            .iter_mut()
+            .find(|p| p.id == peer_id)
+            .ok_or(DomainError::NotFound)?;
+        peer.loan_duration_days = days.map(|d| d.clamp(1, 365));
+        Ok(())
+    }
+
+    async fn time_zone(&self) -> Result<Option<String>, DomainError> {
+        Ok(self.store.lock().time_zone.clone())
+    }
+
+    async fn get_effective_duration_for(
+        &self,
+        book_id: &str,
+        borrower: &LoanBorrower,
+    ) -> Result<i32, DomainError> {
+        let settings = self.get_settings().await?;
+
+        let book_days = if settings.per_book_duration_enabled {
+            self.get_book_loan_duration(book_id).await.ok().flatten()
+        } else {
+            None
+        };
+        let peer_days = match borrower.peer_id {
+            Some(id) => self.get_peer_loan_duration(id).await.ok().flatten(),
+            None => None,
+        };
+
+        Ok(settings.resolve_duration(book_days, peer_days, borrower.contact_type.as_deref()))
+    }
+}
@@ -0,0 +1,503 @@
+//! In-memory MetadataFillRepository (ADR-041)
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, Tables, now};
+use crate::domain::DomainError;
+use crate::domain::metadata_fill::{
+    CompletenessStats, FILL_FIELDS, FillRun, FilledField, GapValues, IncompleteBook,
+    IncompleteBookDetail, MetadataFillRepository, RecentFilledBook, RecentFilledField, UndoOutcome,
+};
+use crate::models::book;
+
+/// A row of `metadata_fill_journal`.
+pub(super) struct JournalEntry {
+    id: i64,
+    batch_id: String,
+    book_id: String,
+    field: String,
+    value_set: String,
+    created_at: String,
+    undone_at: Option<String>,
+}
+
+/// In-memory MetadataFillRepository. Fills write the books of the store and
+/// keep the same undo journal as the SeaORM implementation, so the
+/// `None`-only and safe rollback invariants hold here too.
+#[derive(Default)]
+pub struct InMemoryMetadataFillRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryMetadataFillRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+/// A gap-fill field of `book` in its journal string form (integers are
+/// decimal-encoded), `None` when unset.
+fn field_value(book: &book::Model, field: &str) -> Option<String> {
+    match field {
+        "summary" => book.summary.clone(),
+        "publisher" => book.publisher.clone(),
+        "cover_url" => book.cover_url.clone(),
+        "publication_year" => book.publication_year.map(|v| v.to_string()),
+        "page_count" => book.page_count.map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+/// Text fields are empty when unset or blank, integer fields when unset.
+fn field_is_empty(book: &book::Model, field: &str) -> bool {
+    field_value(book, field).is_none_or(|v| v.trim().is_empty())
+}
+
+fn clear_field(book: &mut book::Model, field: &str) {
+    match field {
+        "summary" => book.summary = None,
+        "publisher" => book.publisher = None,
+        "cover_url" => book.cover_url = None,
+        "publication_year" => book.publication_year = None,
+        "page_count" => book.page_count = None,
+        _ => {}
+    }
+}
+
+fn missing_fields(book: &book::Model) -> Vec<String> {
+    FILL_FIELDS
+        .iter()
+        .filter(|f| field_is_empty(book, f))
+        .map(|f| f.to_string())
+        .collect()
+}
+
+fn is_incomplete(book: &book::Model) -> bool {
+    FILL_FIELDS.iter().any(|f| field_is_empty(book, f))
+}
+
+fn has_isbn(book: &book::Model) -> bool {
+    book.isbn
+        .as_deref()
+        .is_some_and(|isbn| !isbn.trim().is_empty())
+}
+
+fn incomplete_book(book: &book::Model) -> IncompleteBook {
+    IncompleteBook {
+        id: book.id.clone(),
+        title: book.title.clone(),
+        isbn: book.isbn.clone(),
+    }
+}
+
+impl Tables {
+    /// Owned, incomplete books, ordered by id.
+    fn incomplete_books(&self) -> Vec<&book::Model> {
+        let mut books: Vec<&book::Model> = self
+            .books
+            .iter()
+            .filter(|b| b.owned && is_incomplete(b))
+            .collect();
+        books.sort_by(|a, b| a.id.cmp(&b.id));
+        books
+    }
+
+    fn fill_run_mut(&mut self, batch_id: &str) -> Option<&mut FillRun> {
+        self.fill_runs.iter_mut().find(|r| r.batch_id == batch_id)
+    }
+
+    /// `undo_field` under a lock already held by the caller.
+    fn undo_fill(&mut self, journal_id: i64) -> UndoOutcome {
+        let Some(entry) = self
+            .fill_journal
+            .iter()
+            .find(|e| e.id == journal_id && e.undone_at.is_none())
+        else {
+            return UndoOutcome::NotFound;
+        };
+        let (book_id, field, value_set) = (
+            entry.book_id.clone(),
+            entry.field.clone(),
+            entry.value_set.clone(),
+        );
+
+        let now = now();
+        let book = self.books.iter_mut().find(|b| b.id == book_id);
+        let still_ours = book
+            .as_ref()
+            .is_some_and(|b| field_value(b, &field).as_deref() == Some(value_set.as_str()));
+        if still_ours && let Some(book) = book {
+            clear_field(book, &field);
+            book.updated_at = now.clone();
+        }
+        // Retired either way, so it leaves the "recently completed" list.
+        if let Some(entry) = self.fill_journal.iter_mut().find(|e| e.id == journal_id) {
+            entry.undone_at = Some(now);
+        }
+
+        if still_ours {
+            UndoOutcome::Reverted
+        } else {
+            UndoOutcome::Superseded
+        }
+    }
+
+    /// Undo every active journal entry matching `keep`, counting reverts.
+    fn undo_fills(&mut self, keep: impl Fn(&JournalEntry) -> bool) -> usize {
+        let ids: Vec<i64> = self
+            .fill_journal
+            .iter()
+            .filter(|e| e.undone_at.is_none() && keep(e))
+            .map(|e| e.id)
+            .collect();
+        ids.into_iter()
+            .filter(|id| self.undo_fill(*id) == UndoOutcome::Reverted)
+            .count()
+    }
+}
+
+#[async_trait]
+impl MetadataFillRepository for InMemoryMetadataFillRepository {
+    async fn completeness_stats(&self) -> Result<CompletenessStats, DomainError> {
+        let tables = self.store.lock();
+        let owned: Vec<&book::Model> = tables.books.iter().filter(|b| b.owned).collect();
+        let incomplete = owned.iter().filter(|b| is_incomplete(b)).count() as i64;
+        let no_isbn = owned
+            .iter()
+            .filter(|b| is_incomplete(b) && !has_isbn(b))
+            .count() as i64;
+        let empty_fields = owned.iter().map(|b| missing_fields(b).len() as i64).sum();
+        Ok(CompletenessStats {
+            owned_total: owned.len() as i64,
+            complete: owned.len() as i64 - incomplete,
+            incomplete,
+            no_isbn,
+            empty_fields,
+        })
+    }
+
+    async fn list_incomplete_with_isbn(
+        &self,
+        after_id: &str,
+        limit: u64,
+    ) -> Result<Vec<IncompleteBook>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .incomplete_books()
+            .into_iter()
+            .filter(|b| has_isbn(b) && b.id.as_str() > after_id)
+            .take(limit as usize)
+            .map(incomplete_book)
+            .collect())
+    }
+
+    async fn count_incomplete_with_isbn(&self) -> Result<i64, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .incomplete_books()
+            .into_iter()
+            .filter(|b| has_isbn(b))
+            .count() as i64)
+    }
+
+    async fn list_incomplete_without_isbn(&self) -> Result<Vec<IncompleteBook>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .incomplete_books()
+            .into_iter()
+            .filter(|b| !has_isbn(b))
+            .map(incomplete_book)
+            .collect())
+    }
+
+    async fn list_incomplete(&self, limit: u64) -> Result<Vec<IncompleteBookDetail>, DomainError> {
+        let tables = self.store.lock();
+        let mut books = tables.incomplete_books();
+        books.sort_by(|a, b| a.title.cmp(&b.title));
+
+        let mut out: Vec<IncompleteBookDetail> = books
+            .into_iter()
+            .take(limit as usize)
+            .map(|b| IncompleteBookDetail {
+                id: b.id.clone(),
+                title: b.title.clone(),
+                isbn: b.isbn.clone(),
+                cover_url: b.cover_url.clone(),
+                missing: missing_fields(b),
+            })
+            .collect();
+        // Closest-to-complete first (fewest missing fields), then alphabetical.
+        out.sort_by_key(|b| b.missing.len());
+        Ok(out)
+    }
+
+    async fn apply_fill(
+        &self,
+        batch_id: &str,
+        book_id: &str,
+        candidate: GapValues,
+    ) -> Result<Vec<FilledField>, DomainError> {
+        if candidate.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut tables = self.store.lock();
+        // Book vanished (deleted concurrently): nothing to do.
+        let Some(book) = tables.books.iter_mut().find(|b| b.id == book_id) else {
+            return Ok(vec![]);
+        };
+
+        let text = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+        let mut filled: Vec<FilledField> = Vec::new();
+        if field_is_empty(book, "summary")
+            && let Some(v) = text(candidate.summary)
+        {
+            book.summary = Some(v.clone());
+            filled.push(FilledField {
+                field: "summary".to_string(),
+                value: v,
+            });
+        }
+        if field_is_empty(book, "publisher")
+            && let Some(v) = text(candidate.publisher)
+        {
+            book.publisher = Some(v.clone());
+            filled.push(FilledField {
+                field: "publisher".to_string(),
+                value: v,
+            });
+        }
+        if field_is_empty(book, "cover_url")
+            && let Some(v) = text(candidate.cover_url)
+        {
+            book.cover_url = Some(v.clone());
+            filled.push(FilledField {
+                field: "cover_url".to_string(),
+                value: v,
+            });
+        }
+        if book.publication_year.is_none()
+            && let Some(v) = candidate.publication_year
+        {
+            book.publication_year = Some(v);
+            filled.push(FilledField {
+                field: "publication_year".to_string(),
+                value: v.to_string(),
+            });
+        }
+        if book.page_count.is_none()
+            && let Some(v) = candidate.page_count
+        {
+            book.page_count = Some(v);
+            filled.push(FilledField {
+                field: "page_count".to_string(),
+                value: v.to_string(),
+            });
+        }
+
+        let now = now();
+        if !filled.is_empty() {
+            book.updated_at = now.clone();
+        }
+        let first_id = tables.fill_journal.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        for (id, f) in (first_id..).zip(&filled) {
+            tables.fill_journal.push(JournalEntry {
+                id,
+                batch_id: batch_id.to_owned(),
+                book_id: book_id.to_owned(),
+                field: f.field.clone(),
+                value_set: f.value.clone(),
+                created_at: now.clone(),
+                undone_at: None,
+            });
+        }
+        Ok(filled)
+    }
+
+    async fn create_run(&self, batch_id: &str, total: i64) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        // `batch_id` is the primary key of `metadata_fill_run`.
+        if tables.fill_runs.iter().any(|r| r.batch_id == batch_id) {
+            return Err(DomainError::Database(format!(
+                "UNIQUE constraint failed: metadata_fill_run.batch_id ({batch_id})"
+            )));
+        }
+        tables.fill_runs.push(FillRun {
+            batch_id: batch_id.to_owned(),
+            status: "running".to_string(),
+            total,
+            done: 0,
+            filled: 0,
+            skipped: 0,
+            errored: 0,
+            cursor_book_id: String::new(),
+            current_title: None,
+        });
+        Ok(())
+    }
+
+    async fn get_active_run(&self) -> Result<Option<FillRun>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .fill_runs
+            .iter()
+            .rev()
+            .find(|r| r.status == "running" || r.status == "interrupted")
+            .cloned())
+    }
+
+    async fn last_run(&self) -> Result<Option<FillRun>, DomainError> {
+        Ok(self.store.lock().fill_runs.last().cloned())
+    }
+
+    async fn get_run(&self, batch_id: &str) -> Result<Option<FillRun>, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .fill_runs
+            .iter()
+            .find(|r| r.batch_id == batch_id)
+            .cloned())
+    }
+
+    async fn update_run_progress(&self, run: &FillRun) -> Result<(), DomainError> {
+        if let Some(stored) = self.store.lock().fill_run_mut(&run.batch_id) {
+            *stored = run.clone();
+        }
+        Ok(())
+    }
+
+    async fn set_run_status(&self, batch_id: &str, status: &str) -> Result<(), DomainError> {
+        if let Some(run) = self.store.lock().fill_run_mut(batch_id) {
+            run.status = status.to_owned();
+        }
+        Ok(())
+    }
+
+    async fn mark_running_as_interrupted(&self) -> Result<(), DomainError> {
+        for run in self
+            .store
+            .lock()
+            .fill_runs
+            .iter_mut()
+            .filter(|r| r.status == "running")
+        {
+            run.status = "interrupted".to_string();
+        }
+        Ok(())
+    }
+
+    async fn recent_filled(&self, limit: u64) -> Result<Vec<RecentFilledBook>, DomainError> {
+        let tables = self.store.lock();
+        let mut entries: Vec<&JournalEntry> = tables
+            .fill_journal
+            .iter()
+            .filter(|e| e.undone_at.is_none())
+            .collect();
+        entries.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
+
+        // The cap applies to distinct books, not entries.
+        let mut out: Vec<RecentFilledBook> = Vec::new();
+        for e in entries {
+            let field = RecentFilledField {
+                journal_id: e.id,
+                batch_id: e.batch_id.clone(),
+                field: e.field.clone(),
+                value: e.value_set.clone(),
+            };
+            if let Some(existing) = out.iter_mut().find(|b| b.book_id == e.book_id) {
+                existing.fields.push(field);
+            } else if (out.len() as u64) < limit {
+                let book = tables.books.iter().find(|b| b.id == e.book_id);
+                out.push(RecentFilledBook {
+                    book_id: e.book_id.clone(),
+                    title: book.map(|b| b.title.clone()).unwrap_or_default(),
+                    cover_url: book.and_then(|b| b.cover_url.clone()),
+                    fields: vec![field],
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    async fn undo_field(&self, journal_id: i64) -> Result<UndoOutcome, DomainError> {
+        Ok(self.store.lock().undo_fill(journal_id))
+    }
+
+    async fn undo_book(&self, batch_id: &str, book_id: &str) -> Result<usize, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .undo_fills(|e| e.batch_id == batch_id && e.book_id == book_id))
+    }
+
+    async fn undo_run(&self, batch_id: &str) -> Result<usize, DomainError> {
+        Ok(self.store.lock().undo_fills(|e| e.batch_id == batch_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::BookRepository;
+    use crate::infrastructure::repositories::memory::InMemoryRepositories;
+    use crate::models::Book;
+
+    #[tokio::test]
+    async fn fills_only_empty_fields_and_undo_spares_user_edits() {
+        let repos = InMemoryRepositories::new();
+        let book = repos
+            .books
+            .create(Book {
+                title: "Dune".into(),
+                isbn: Some("9780441013593".into()),
+                publisher: Some("Ace".into()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let book_id = book.id.unwrap();
+        let fill = &repos.metadata_fill;
+        assert_eq!(fill.count_incomplete_with_isbn().await.unwrap(), 1);
+
+        let filled = fill
+            .apply_fill(
+                "run-1",
+                &book_id,
+                GapValues {
+                    summary: Some("Arrakis".into()),
+                    publisher: Some("Chilton".into()),
+                    page_count: Some(412),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let fields: Vec<&str> = filled.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, ["summary", "page_count"]);
+
+        // The user rewrites the summary after the run.
+        let mut edited = repos.books.find_by_id(&book_id).await.unwrap().unwrap();
+        edited.summary = Some("Spice".into());
+        repos.books.update(&book_id, edited).await.unwrap();
+
+        assert_eq!(fill.undo_book("run-1", &book_id).await.unwrap(), 1);
+        let book = repos.books.find_by_id(&book_id).await.unwrap().unwrap();
+        assert_eq!(book.summary.as_deref(), Some("Spice"));
+        assert_eq!(book.publisher.as_deref(), Some("Ace"));
+        assert!(fill.recent_filled(10).await.unwrap().is_empty());
+    }
+}
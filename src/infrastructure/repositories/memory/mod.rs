@@ -0,0 +1,241 @@
+//! In-memory repository implementations for unit tests and `--demo`.
+//!
+//! Every repository holds an `Arc<MemoryStore>`. Repositories built on the
+//! same store see each other's rows the way the SeaORM ones share a database:
+//! copies carry their book's title, collections count owned books,
+//! gamification counts loans. [`InMemoryRepositories`] builds one of each on a
+//! single store.
+//!
+//! They honour the same contracts as the SeaORM implementations (ordering,
+//! `NotFound` on a missing row, soft deletes, cascades), without the side
+//! effects: no operation log, no geocoding, no referential checks against
+//! tables the store does not hold. A service written against the domain
+//! traits can be tested without opening a database, and `--demo` serves its
+//! catalogue from them (`AppState::in_memory`).
+
+mod author;
+mod book;
+mod collection;
+mod contact;
+mod copy;
+mod gamification;
+mod linked_device;
+mod loan;
+mod loan_settings;
+mod metadata_fill;
+mod notification;
+mod p2p_request;
+mod peer;
+mod tag;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub use author::InMemoryAuthorRepository;
+pub use book::InMemoryBookRepository;
+pub use collection::InMemoryCollectionRepository;
+pub use contact::InMemoryContactRepository;
+pub use copy::InMemoryCopyRepository;
+pub use gamification::InMemoryGamificationRepository;
+pub use linked_device::InMemoryLinkedDeviceRepository;
+pub use loan::InMemoryLoanRepository;
+pub use loan_settings::InMemoryLoanSettingsRepository;
+pub use metadata_fill::InMemoryMetadataFillRepository;
+pub use notification::InMemoryNotificationRepository;
+pub use p2p_request::InMemoryP2pRequestRepository;
+pub use peer::InMemoryPeerRepository;
+pub use tag::InMemoryTagRepository;
+
+use crate::domain::{
+    Author, Contact, FillRun, GamificationConfigRow, LinkedDevice, Loan, LoanSettings, Peer,
+    PeerGamificationStatsRow, Tag,
+};
+use crate::models;
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// `LIKE '%needle%'` as SQLite evaluates it: case-insensitive.
+fn like(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// The rows shared by the in-memory repositories, one field per table.
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Link a book to an author, as `book_authors` does. No trait writes this
+    /// table; books list their authors from it.
+    pub fn link_author(&self, book_id: &str, author_id: &str) {
+        self.lock().book_authors.push(models::book_authors::Model {
+            book_id: book_id.to_owned(),
+            author_id: author_id.to_owned(),
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap()
+    }
+}
+
+#[derive(Default)]
+struct Tables {
+    authors: Vec<Author>,
+    book_authors: Vec<models::book_authors::Model>,
+    books: Vec<models::book::Model>,
+    collections: Vec<models::collection::Model>,
+    collection_books: Vec<models::collection_book::Model>,
+    contacts: Vec<Contact>,
+    copies: Vec<models::copy::Model>,
+    linked_devices: Vec<LinkedDevice>,
+    loans: Vec<Loan>,
+    loan_settings: LoanSettings,
+    notifications: Vec<models::notification::Model>,
+    p2p_requests: Vec<models::p2p_request::Model>,
+    p2p_outgoing_requests: Vec<models::p2p_outgoing_request::Model>,
+    peers: Vec<Peer>,
+    tags: Vec<Tag>,
+
+    // Gamification
+    streaks: HashMap<i32, (i32, i32, Option<String>)>,
+    /// `(user_id, achievement_id, unlocked_at)`
+    achievements: Vec<(i32, String, String)>,
+    gamification_config: HashMap<i32, GamificationConfigRow>,
+    peer_stats: Vec<PeerGamificationStatsRow>,
+    enabled_modules: Vec<String>,
+    library_name: Option<String>,
+    user_id: Option<i32>,
+    time_zone: Option<String>,
+
+    // Metadata gap-fill (ADR-041), runs in the order they started
+    fill_runs: Vec<FillRun>,
+    fill_journal: Vec<metadata_fill::JournalEntry>,
+}
+
+/// One of each in-memory repository, all on the same store.
+pub struct InMemoryRepositories {
+    pub store: Arc<MemoryStore>,
+    pub authors: InMemoryAuthorRepository,
+    pub books: InMemoryBookRepository,
+    pub collections: InMemoryCollectionRepository,
+    pub contacts: InMemoryContactRepository,
+    pub copies: InMemoryCopyRepository,
+    pub gamification: InMemoryGamificationRepository,
+    pub linked_devices: InMemoryLinkedDeviceRepository,
+    pub loans: InMemoryLoanRepository,
+    pub loan_settings: InMemoryLoanSettingsRepository,
+    pub metadata_fill: InMemoryMetadataFillRepository,
+    pub notifications: InMemoryNotificationRepository,
+    pub p2p_requests: InMemoryP2pRequestRepository,
+    pub peers: InMemoryPeerRepository,
+    pub tags: InMemoryTagRepository,
+}
+
+impl InMemoryRepositories {
+    pub fn new() -> Self {
+        let store = MemoryStore::new();
+        Self {
+            authors: InMemoryAuthorRepository::with_store(store.clone()),
+            books: InMemoryBookRepository::with_store(store.clone()),
+            collections: InMemoryCollectionRepository::with_store(store.clone()),
+            contacts: InMemoryContactRepository::with_store(store.clone()),
+            copies: InMemoryCopyRepository::with_store(store.clone()),
+            gamification: InMemoryGamificationRepository::with_store(store.clone()),
+            linked_devices: InMemoryLinkedDeviceRepository::with_store(store.clone()),
+            loans: InMemoryLoanRepository::with_store(store.clone()),
+            loan_settings: InMemoryLoanSettingsRepository::with_store(store.clone()),
+            metadata_fill: InMemoryMetadataFillRepository::with_store(store.clone()),
+            notifications: InMemoryNotificationRepository::with_store(store.clone()),
+            p2p_requests: InMemoryP2pRequestRepository::with_store(store.clone()),
+            peers: InMemoryPeerRepository::with_store(store.clone()),
+            tags: InMemoryTagRepository::with_store(store.clone()),
+            store,
+        }
+    }
+}
+
+impl Default for InMemoryRepositories {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        AuthorRepository, BookRepository, CollectionRepository, CopyRepository,
+        CreateCollectionInput, CreateCopyInput, GamificationRepository,
+    };
+    use crate::models::Book;
+
+    #[tokio::test]
+    async fn repositories_on_one_store_see_each_others_rows() {
+        let repos = InMemoryRepositories::new();
+        let book = repos
+            .books
+            .create(Book {
+                title: "Dune".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let book_id = book.id.unwrap();
+        let author = repos.authors.create("Frank Herbert".into()).await.unwrap();
+        repos.store.link_author(&book_id, &author.id);
+
+        repos
+            .copies
+            .create(CreateCopyInput {
+                book_id: book_id.clone(),
+                library_id: 1,
+                status: "available".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let copies = repos.copies.find_all().await.unwrap();
+        assert_eq!(copies.copies[0].book_title.as_deref(), Some("Dune"));
+
+        let book = repos.books.find_by_id(&book_id).await.unwrap().unwrap();
+        assert_eq!(book.author.as_deref(), Some("Frank Herbert"));
+        assert_eq!(book.available_copies, Some(1));
+
+        let collection = repos
+            .collections
+            .create(CreateCollectionInput {
+                name: "Cycle".into(),
+                description: None,
+                source: None,
+            })
+            .await
+            .unwrap();
+        repos
+            .collections
+            .add_book(&collection.id, &book_id)
+            .await
+            .unwrap();
+        repos
+            .collections
+            .add_book(&collection.id, &book_id)
+            .await
+            .unwrap();
+        let collection = repos
+            .collections
+            .find_by_id(&collection.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((collection.total_books, collection.owned_books), (1, 1));
+
+        assert_eq!(repos.gamification.count_books().await.unwrap(), 1);
+    }
+}
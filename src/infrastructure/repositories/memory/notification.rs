@@ -0,0 +1,220 @@
+//! In-memory NotificationRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::DomainError;
+use crate::domain::notification_repository::{
+    CreateNotification, MAX_NOTIFICATIONS, NotificationRepository, NotificationRow,
+    TTL_GLOBAL_DAYS, TTL_READ_DAYS,
+};
+use crate::models::notification;
+
+/// In-memory NotificationRepository. Dismissing is a soft delete, as in the
+/// database, so `exists` still sees dismissed notifications.
+#[derive(Default)]
+pub struct InMemoryNotificationRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryNotificationRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+/// Oldest first, ties broken by insertion order.
+fn oldest_first(rows: &mut [&notification::Model]) {
+    rows.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+}
+
+#[async_trait]
+impl NotificationRepository for InMemoryNotificationRepository {
+    async fn create(&self, input: CreateNotification) -> Result<NotificationRow, DomainError> {
+        let row = {
+            let mut tables = self.store.lock();
+            let id = tables.notifications.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+            let row = notification::Model {
+                id,
+                event_type: input.event_type.as_str().to_string(),
+                category: input.event_type.category().as_str().to_string(),
+                title: input.title,
+                body: input.body,
+                ref_type: input.ref_type,
+                ref_id: input.ref_id,
+                read_at: None,
+                created_at: now(),
+                dismissed_at: None,
+            };
+            tables.notifications.push(row.clone());
+            row
+        };
+
+        let _ = self.prune().await;
+
+        Ok(row.into())
+    }
+
+    async fn list(
+        &self,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<NotificationRow>, DomainError> {
+        let tables = self.store.lock();
+        let mut rows: Vec<&notification::Model> = tables
+            .notifications
+            .iter()
+            .filter(|n| n.dismissed_at.is_none())
+            .filter(|n| category.is_none_or(|c| n.category == c))
+            .collect();
+        oldest_first(&mut rows);
+        Ok(rows
+            .into_iter()
+            .rev()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|n| n.clone().into())
+            .collect())
+    }
+
+    async fn unread_count(&self, category: Option<&str>) -> Result<i64, DomainError> {
+        Ok(self
+            .store
+            .lock()
+            .notifications
+            .iter()
+            .filter(|n| n.read_at.is_none() && n.dismissed_at.is_none())
+            .filter(|n| category.is_none_or(|c| n.category == c))
+            .count() as i64)
+    }
+
+    async fn mark_read(&self, id: i32) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        match tables.notifications.iter_mut().find(|n| n.id == id) {
+            Some(n) => {
+                n.read_at = Some(now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn mark_all_read(&self) -> Result<i64, DomainError> {
+        let now = now();
+        let mut count = 0;
+        for n in self
+            .store
+            .lock()
+            .notifications
+            .iter_mut()
+            .filter(|n| n.read_at.is_none() && n.dismissed_at.is_none())
+        {
+            n.read_at = Some(now.clone());
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn dismiss(&self, id: i32) -> Result<bool, DomainError> {
+        let mut tables = self.store.lock();
+        match tables
+            .notifications
+            .iter_mut()
+            .find(|n| n.id == id && n.dismissed_at.is_none())
+        {
+            Some(n) => {
+                n.dismissed_at = Some(now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn dismiss_all(&self) -> Result<i64, DomainError> {
+        let now = now();
+        let mut count = 0;
+        for n in self
+            .store
+            .lock()
+            .notifications
+            .iter_mut()
+            .filter(|n| n.dismissed_at.is_none())
+        {
+            n.dismissed_at = Some(now.clone());
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn prune(&self) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        let notifications = &mut tables.notifications;
+        let before = notifications.len();
+
+        // 1. TTL global, 2. TTL after being read or dismissed.
+        let cutoff_global =
+            (chrono::Utc::now() - chrono::Duration::days(TTL_GLOBAL_DAYS)).to_rfc3339();
+        let cutoff_read = (chrono::Utc::now() - chrono::Duration::days(TTL_READ_DAYS)).to_rfc3339();
+        let consumed_before = |at: &Option<String>| at.as_ref().is_some_and(|at| *at < cutoff_read);
+        notifications.retain(|n| {
+            n.created_at >= cutoff_global
+                && !consumed_before(&n.read_at)
+                && !consumed_before(&n.dismissed_at)
+        });
+
+        // 3. Cap, dropping the oldest consumed notifications first.
+        let excess = notifications
+            .len()
+            .saturating_sub(MAX_NOTIFICATIONS as usize);
+        if excess > 0 {
+            let mut consumed: Vec<&notification::Model> = notifications
+                .iter()
+                .filter(|n| n.read_at.is_some() || n.dismissed_at.is_some())
+                .collect();
+            oldest_first(&mut consumed);
+            let mut doomed: Vec<i32> = consumed.iter().take(excess).map(|n| n.id).collect();
+
+            if doomed.len() < excess {
+                let mut unread: Vec<&notification::Model> = notifications
+                    .iter()
+                    .filter(|n| !doomed.contains(&n.id))
+                    .collect();
+                oldest_first(&mut unread);
+                let remaining = excess - doomed.len();
+                doomed.extend(unread.iter().take(remaining).map(|n| n.id));
+            }
+            notifications.retain(|n| !doomed.contains(&n.id));
+        }
+
+        Ok((before - notifications.len()) as i64)
+    }
+
+    async fn exists(
+        &self,
+        event_type: &str,
+        ref_type: &str,
+        ref_id: &str,
+    ) -> Result<bool, DomainError> {
+        Ok(self.store.lock().notifications.iter().any(|n| {
+            n.event_type == event_type
+                && n.ref_type.as_deref() == Some(ref_type)
+                && n.ref_id.as_deref() == Some(ref_id)
+        }))
+    }
+
+    async fn dismiss_by_ref(&self, ref_type: &str, ref_id: &str) -> Result<i64, DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.notifications.len();
+        tables.notifications.retain(|n| {
+            !(n.ref_type.as_deref() == Some(ref_type) && n.ref_id.as_deref() == Some(ref_id))
+        });
+        Ok((before - tables.notifications.len()) as i64)
+    }
+}
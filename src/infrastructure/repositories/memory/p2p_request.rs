@@ -0,0 +1,96 @@
+//! In-memory P2pRequestRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{
+    DomainError, OPEN_INCOMING_STATUSES, OPEN_OUTGOING_STATUSES, P2pRequestRepository,
+};
+use crate::models::{p2p_outgoing_request, p2p_request};
+
+/// In-memory P2pRequestRepository. No trait creates requests: they arrive
+/// through the P2P protocol, so tests seed them with `insert_incoming` and
+/// `insert_outgoing`.
+#[derive(Default)]
+pub struct InMemoryP2pRequestRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryP2pRequestRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored incoming request row as is
+    pub fn insert_incoming(&self, request: p2p_request::Model) {
+        self.store.lock().p2p_requests.push(request);
+    }
+
+    /// Seed a stored outgoing request row as is
+    pub fn insert_outgoing(&self, request: p2p_outgoing_request::Model) {
+        self.store.lock().p2p_outgoing_requests.push(request);
+    }
+}
+
+fn closed(open: &[&str], status: &str) -> bool {
+    !open.contains(&status)
+}
+
+#[async_trait]
+impl P2pRequestRepository for InMemoryP2pRequestRepository {
+    async fn count_closed_incoming(&self) -> Result<u64, DomainError> {
+        let tables = self.store.lock();
+        let count = tables
+            .p2p_requests
+            .iter()
+            .filter(|r| closed(&OPEN_INCOMING_STATUSES, &r.status))
+            .count();
+        Ok(count as u64)
+    }
+
+    async fn delete_closed_incoming(&self) -> Result<u64, DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.p2p_requests.len();
+        tables
+            .p2p_requests
+            .retain(|r| !closed(&OPEN_INCOMING_STATUSES, &r.status));
+        Ok((before - tables.p2p_requests.len()) as u64)
+    }
+
+    async fn count_closed_outgoing(&self) -> Result<u64, DomainError> {
+        let tables = self.store.lock();
+        let count = tables
+            .p2p_outgoing_requests
+            .iter()
+            .filter(|r| closed(&OPEN_OUTGOING_STATUSES, &r.status))
+            .count();
+        Ok(count as u64)
+    }
+
+    async fn delete_closed_outgoing(&self) -> Result<u64, DomainError> {
+        let mut tables = self.store.lock();
+        let before = tables.p2p_outgoing_requests.len();
+        tables
+            .p2p_outgoing_requests
+            .retain(|r| !closed(&OPEN_OUTGOING_STATUSES, &r.status));
+        Ok((before - tables.p2p_outgoing_requests.len()) as u64)
+    }
+
+    async fn set_outgoing_status(&self, id: &str, status: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let request = tables
+            .p2p_outgoing_requests
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or(DomainError::NotFound)?;
+        request.status = status.to_owned();
+        request.updated_at = now();
+        Ok(())
+    }
+}
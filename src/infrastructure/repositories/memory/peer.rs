@@ -0,0 +1,56 @@
+//! In-memory PeerRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::MemoryStore;
+use crate::domain::{DomainError, Peer, PeerRepository};
+
+/// In-memory PeerRepository
+#[derive(Default)]
+pub struct InMemoryPeerRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryPeerRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    /// Seed a stored peer as is
+    pub fn insert(&self, peer: Peer) {
+        self.store.lock().peers.push(peer);
+    }
+
+    fn find_one(&self, matches: impl Fn(&Peer) -> bool) -> Option<Peer> {
+        self.store.lock().peers.iter().find(|p| matches(p)).cloned()
+    }
+}
+
+#[async_trait]
+impl PeerRepository for InMemoryPeerRepository {
+    async fn find_all(&self) -> Result<Vec<Peer>, DomainError> {
+        Ok(self.store.lock().peers.clone())
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Peer>, DomainError> {
+        Ok(self.find_one(|p| p.id == id))
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Peer>, DomainError> {
+        Ok(self.find_one(|p| p.name == name))
+    }
+
+    async fn find_by_url(&self, url: &str) -> Result<Option<Peer>, DomainError> {
+        Ok(self.find_one(|p| p.url == url))
+    }
+
+    async fn find_by_library_uuid(&self, library_uuid: &str) -> Result<Option<Peer>, DomainError> {
+        Ok(self.find_one(|p| p.library_uuid.as_deref() == Some(library_uuid)))
+    }
+}
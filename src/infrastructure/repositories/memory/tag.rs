@@ -0,0 +1,134 @@
+//! In-memory TagRepository
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{MemoryStore, now};
+use crate::domain::{DomainError, Tag, TagRepository};
+
+/// In-memory TagRepository
+#[derive(Default)]
+pub struct InMemoryTagRepository {
+    store: Arc<MemoryStore>,
+}
+
+impl InMemoryTagRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: Arc<MemoryStore>) -> Self {
+        Self { store }
+    }
+
+    fn path_under(tags: &[Tag], parent_id: Option<&str>) -> String {
+        match parent_id.and_then(|id| tags.iter().find(|t| t.id == id)) {
+            Some(parent) if parent.path.is_empty() => parent.name.clone(),
+            Some(parent) => format!("{} > {}", parent.path, parent.name),
+            None => String::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl TagRepository for InMemoryTagRepository {
+    async fn find_all(&self) -> Result<Vec<Tag>, DomainError> {
+        let mut tags = self.store.lock().tags.clone();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Tag>, DomainError> {
+        Ok(self.store.lock().tags.iter().find(|t| t.id == id).cloned())
+    }
+
+    async fn create(&self, name: String, parent_id: Option<String>) -> Result<Tag, DomainError> {
+        let mut tables = self.store.lock();
+        let tags = &mut tables.tags;
+        let now = now();
+        let tag = Tag {
+            id: crate::utils::uuid_gen::new_uuid_v7(),
+            path: Self::path_under(tags, parent_id.as_deref()),
+            name,
+            parent_id,
+            private: false,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        tags.push(tag.clone());
+        Ok(tag)
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        name: String,
+        parent_id: Option<String>,
+    ) -> Result<Tag, DomainError> {
+        let mut tables = self.store.lock();
+        let tags = &mut tables.tags;
+        let path = Self::path_under(tags, parent_id.as_deref());
+        let tag = tags
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or(DomainError::NotFound)?;
+        if tag.parent_id != parent_id {
+            tag.path = path;
+        }
+        tag.name = name;
+        tag.parent_id = parent_id;
+        tag.updated_at = now();
+        Ok(tag.clone())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let mut tables = self.store.lock();
+        let tags = &mut tables.tags;
+        let before = tags.len();
+        tags.retain(|t| t.id != id);
+        if tags.len() == before {
+            return Err(DomainError::NotFound);
+        }
+        for child in tags
+            .iter_mut()
+            .filter(|t| t.parent_id.as_deref() == Some(id))
+        {
+            child.parent_id = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tags_nest_and_children_move_to_root_when_the_parent_goes() {
+        let repo = InMemoryTagRepository::new();
+        let fiction = repo.create("Fiction".into(), None).await.unwrap();
+        let sf = repo
+            .create("SF".into(), Some(fiction.id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(sf.path, "Fiction");
+
+        let names: Vec<String> = repo
+            .find_all()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, ["Fiction", "SF"]);
+
+        repo.delete(&fiction.id).await.unwrap();
+        let sf = repo.find_by_id(&sf.id).await.unwrap().unwrap();
+        assert_eq!(sf.parent_id, None);
+        assert!(matches!(
+            repo.delete(&fiction.id).await,
+            Err(DomainError::NotFound)
+        ));
+    }
+}
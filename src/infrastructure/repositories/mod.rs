@@ -1,21 +1,35 @@
-//! Repository implementations using SeaORM
+//! Repository implementations using SeaORM, plus in-memory ones (`memory`)
+//! for tests and `--demo`
 
 pub mod author_repository;
 pub mod book_repository;
 pub mod collection_repository;
+pub mod contact_repository;
+#[cfg(test)]
+mod contract_tests;
 pub mod copy_repository;
 pub mod gamification_repository;
 pub mod linked_device_repository;
+pub mod loan_repository;
 pub mod loan_settings_repository;
+pub mod memory;
 pub mod metadata_fill_repository;
 pub mod notification_repository;
+pub mod p2p_request_repository;
+pub mod peer_repository;
+pub mod tag_repository;
 
 pub use author_repository::SeaOrmAuthorRepository;
 pub use book_repository::SeaOrmBookRepository;
 pub use collection_repository::SeaOrmCollectionRepository;
+pub use contact_repository::SeaOrmContactRepository;
 pub use copy_repository::SeaOrmCopyRepository;
 pub use gamification_repository::SeaOrmGamificationRepository;
 pub use linked_device_repository::SeaOrmLinkedDeviceRepository;
+pub use loan_repository::SeaOrmLoanRepository;
 pub use loan_settings_repository::SeaOrmLoanSettingsRepository;
 pub use metadata_fill_repository::SeaOrmMetadataFillRepository;
 pub use notification_repository::SeaOrmNotificationRepository;
+pub use p2p_request_repository::SeaOrmP2pRequestRepository;
+pub use peer_repository::SeaOrmPeerRepository;
+pub use tag_repository::SeaOrmTagRepository;
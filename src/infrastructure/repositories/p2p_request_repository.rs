@@ -0,0 +1,71 @@
+//! SeaORM implementation of P2pRequestRepository
+
+use async_trait::async_trait;
+use sea_orm::prelude::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+
+use crate::domain::{
+    DomainError, OPEN_INCOMING_STATUSES, OPEN_OUTGOING_STATUSES, P2pRequestRepository,
+};
+use crate::models::p2p_outgoing_request::{self, Entity as P2pOutgoingRequest};
+use crate::models::p2p_request::{self, Entity as P2pRequest};
+
+/// SeaORM-based implementation of P2pRequestRepository
+pub struct SeaOrmP2pRequestRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmP2pRequestRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl P2pRequestRepository for SeaOrmP2pRequestRepository {
+    async fn count_closed_incoming(&self) -> Result<u64, DomainError> {
+        Ok(P2pRequest::find()
+            .filter(p2p_request::Column::Status.is_not_in(OPEN_INCOMING_STATUSES))
+            .count(&self.db)
+            .await?)
+    }
+
+    async fn delete_closed_incoming(&self) -> Result<u64, DomainError> {
+        let result = P2pRequest::delete_many()
+            .filter(p2p_request::Column::Status.is_not_in(OPEN_INCOMING_STATUSES))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn count_closed_outgoing(&self) -> Result<u64, DomainError> {
+        Ok(P2pOutgoingRequest::find()
+            .filter(p2p_outgoing_request::Column::Status.is_not_in(OPEN_OUTGOING_STATUSES))
+            .count(&self.db)
+            .await?)
+    }
+
+    async fn delete_closed_outgoing(&self) -> Result<u64, DomainError> {
+        let result = P2pOutgoingRequest::delete_many()
+            .filter(p2p_outgoing_request::Column::Status.is_not_in(OPEN_OUTGOING_STATUSES))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn set_outgoing_status(&self, id: &str, status: &str) -> Result<(), DomainError> {
+        let result = P2pOutgoingRequest::update_many()
+            .col_expr(p2p_outgoing_request::Column::Status, Expr::value(status))
+            .col_expr(
+                p2p_outgoing_request::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().to_rfc3339()),
+            )
+            .filter(p2p_outgoing_request::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+}
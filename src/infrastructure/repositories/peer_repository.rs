@@ -0,0 +1,66 @@
+//! SeaORM implementation of PeerRepository
+
+use async_trait::async_trait;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+use crate::domain::{DomainError, Peer, PeerRepository};
+use crate::models::peer::{self, Entity as PeerEntity};
+
+/// SeaORM-based implementation of PeerRepository
+pub struct SeaOrmPeerRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmPeerRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    async fn find_one(
+        &self,
+        condition: sea_orm::sea_query::SimpleExpr,
+    ) -> Result<Option<Peer>, DomainError> {
+        let found = PeerEntity::find().filter(condition).one(&self.db).await?;
+        Ok(found.map(model_to_domain))
+    }
+}
+
+fn model_to_domain(m: peer::Model) -> Peer {
+    Peer {
+        id: m.id,
+        name: m.name,
+        display_name: m.display_name,
+        url: m.url,
+        library_uuid: m.library_uuid,
+        connection_status: m.connection_status,
+        last_seen: m.last_seen,
+        auto_approve: m.auto_approve,
+        loan_duration_days: m.loan_duration_days,
+    }
+}
+
+#[async_trait]
+impl PeerRepository for SeaOrmPeerRepository {
+    async fn find_all(&self) -> Result<Vec<Peer>, DomainError> {
+        let peers = PeerEntity::find().all(&self.db).await?;
+        Ok(peers.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Peer>, DomainError> {
+        let found = PeerEntity::find_by_id(id).one(&self.db).await?;
+        Ok(found.map(model_to_domain))
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<Peer>, DomainError> {
+        self.find_one(peer::Column::Name.eq(name)).await
+    }
+
+    async fn find_by_url(&self, url: &str) -> Result<Option<Peer>, DomainError> {
+        self.find_one(peer::Column::Url.eq(url)).await
+    }
+
+    async fn find_by_library_uuid(&self, library_uuid: &str) -> Result<Option<Peer>, DomainError> {
+        self.find_one(peer::Column::LibraryUuid.eq(library_uuid))
+            .await
+    }
+}
@@ -0,0 +1,127 @@
+//! SeaORM implementation of TagRepository
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, DatabaseConnection, EntityTrait, QueryOrder, Set, TransactionTrait,
+};
+
+use crate::domain::{DomainError, Tag, TagRepository};
+use crate::models::tag::{self, ActiveModel, Entity as TagEntity};
+
+/// SeaORM-based implementation of TagRepository
+pub struct SeaOrmTagRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmTagRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// `path` of a tag nested under `parent_id`: the parent's path followed
+    /// by the parent's name. Empty for a root tag or a vanished parent.
+    async fn path_under(&self, parent_id: Option<&str>) -> Result<String, DomainError> {
+        let Some(parent_id) = parent_id else {
+            return Ok(String::new());
+        };
+        let parent = TagEntity::find_by_id(parent_id.to_owned())
+            .one(&self.db)
+            .await?;
+        Ok(match parent {
+            Some(parent) if parent.path.is_empty() => parent.name,
+            Some(parent) => format!("{} > {}", parent.path, parent.name),
+            None => String::new(),
+        })
+    }
+}
+
+fn model_to_domain(m: tag::Model) -> Tag {
+    Tag {
+        id: m.id,
+        name: m.name,
+        parent_id: m.parent_id,
+        path: m.path,
+        private: m.private,
+        created_at: m.created_at,
+        updated_at: m.updated_at,
+    }
+}
+
+#[async_trait]
+impl TagRepository for SeaOrmTagRepository {
+    async fn find_all(&self) -> Result<Vec<Tag>, DomainError> {
+        let tags = TagEntity::find()
+            .order_by_asc(tag::Column::Name)
+            .all(&self.db)
+            .await?;
+        Ok(tags.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Tag>, DomainError> {
+        let found = TagEntity::find_by_id(id.to_owned()).one(&self.db).await?;
+        Ok(found.map(model_to_domain))
+    }
+
+    async fn create(&self, name: String, parent_id: Option<String>) -> Result<Tag, DomainError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let path = self.path_under(parent_id.as_deref()).await?;
+
+        let new_tag = ActiveModel {
+            name: Set(name),
+            parent_id: Set(parent_id),
+            path: Set(path),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        };
+
+        let saved = new_tag.insert(&self.db).await?;
+        let _ = crate::sync::log_operation(&self.db, "tag", &saved.id, "INSERT", None).await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        name: String,
+        parent_id: Option<String>,
+    ) -> Result<Tag, DomainError> {
+        let existing = TagEntity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+            .ok_or(DomainError::NotFound)?;
+
+        let moved = existing.parent_id != parent_id;
+        let mut active: ActiveModel = existing.into();
+        if moved {
+            active.path = Set(self.path_under(parent_id.as_deref()).await?);
+        }
+        active.name = Set(name);
+        active.parent_id = Set(parent_id);
+        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+
+        let saved = active.update(&self.db).await?;
+        let _ = crate::sync::log_operation(&self.db, "tag", &saved.id, "UPDATE", None).await;
+
+        Ok(model_to_domain(saved))
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        // Cascade the tag's book links and re-parent its children in one
+        // transaction: the database no longer cascades these since the
+        // replicated tables lost their foreign keys (ADR-044).
+        let txn = self.db.begin().await?;
+        let existed =
+            crate::infrastructure::referential_integrity::delete_tag_cascade(&txn, id).await?;
+        if !existed {
+            txn.rollback().await?;
+            return Err(DomainError::NotFound);
+        }
+        txn.commit().await?;
+
+        let _ = crate::sync::log_operation(&self.db, "tag", id, "DELETE", None).await;
+
+        Ok(())
+    }
+}
@@ -1,8 +1,497 @@
+//! Demo data for development and demos.
+//!
+//! Each [`SeedProfile`] (individual reader, school library, bookseller) has its
+//! own sample catalogue in French and English ([`SeedLocale`]). Seeding is
+//! idempotent: books are matched by title, authors and tags by name, so running
+//! it again only fills in what is missing and never duplicates a book.
+//!
+//! Selected with `SEED_PROFILE` / `SEED_LOCALE` at startup (with `SEED_DEMO`),
+//! the `demo_data` wizard step, `seed_profile` on `POST /api/setup`, or
+//! `POST /api/setup/demo-data`.
+
+use std::collections::{HashMap, HashSet};
+
 use crate::auth::hash_password;
-use crate::models::{author, book, library, tag, user};
+use crate::domain::{
+    AuthorRepository, BookFilter, BookRepository, CopyRepository, CreateCopyInput, DomainError,
+    TagRepository,
+};
+use crate::infrastructure::repositories::memory::InMemoryRepositories;
+use crate::models::{Book, author, book, book_authors, book_tags, copy, library, tag, user};
 use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedProfile {
+    /// A personal collection with reading statuses and ratings.
+    #[default]
+    Individual,
+    /// Class sets: several copies of each title.
+    School,
+    /// Priced stock.
+    Bookseller,
+}
+
+impl SeedProfile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SeedProfile::Individual => "individual",
+            SeedProfile::School => "school",
+            SeedProfile::Bookseller => "bookseller",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "individual" => Some(SeedProfile::Individual),
+            "school" => Some(SeedProfile::School),
+            "bookseller" => Some(SeedProfile::Bookseller),
+            _ => None,
+        }
+    }
+
+    /// The profile matching an installation `profile_type`.
+    pub fn for_profile_type(profile_type: &str) -> Self {
+        match profile_type {
+            "librarian" => SeedProfile::School,
+            "bookseller" => SeedProfile::Bookseller,
+            _ => SeedProfile::Individual,
+        }
+    }
+
+    /// `SEED_PROFILE`, falling back to [`SeedProfile::Individual`].
+    pub fn from_env() -> Self {
+        match std::env::var("SEED_PROFILE") {
+            Ok(raw) => Self::parse(&raw).unwrap_or_else(|| {
+                tracing::warn!("Unknown SEED_PROFILE '{}', using individual", raw);
+                SeedProfile::Individual
+            }),
+            Err(_) => SeedProfile::Individual,
+        }
+    }
+
+    fn copies_per_book(self) -> usize {
+        match self {
+            SeedProfile::School => 3,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedLocale {
+    En,
+    Fr,
+}
+
+impl SeedLocale {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SeedLocale::En => "en",
+            SeedLocale::Fr => "fr",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let lang = s.trim().to_ascii_lowercase();
+        if lang.starts_with("fr") {
+            Some(SeedLocale::Fr)
+        } else if lang.starts_with("en") {
+            Some(SeedLocale::En)
+        } else {
+            None
+        }
+    }
+
+    /// `SEED_LOCALE`, falling back to the system locale (`LC_ALL` / `LANG`).
+    pub fn from_env() -> Self {
+        std::env::var("SEED_LOCALE")
+            .ok()
+            .and_then(|raw| Self::parse(&raw))
+            .unwrap_or_else(Self::detect)
+    }
+
+    /// The system locale, English unless it is French.
+    pub fn detect() -> Self {
+        Self::parse(crate::utils::default_library_name::detect_locale_lang())
+            .unwrap_or(SeedLocale::En)
+    }
+}
+
+struct SampleBook {
+    title: &'static str,
+    author: &'static str,
+    isbn: Option<&'static str>,
+    publisher: &'static str,
+    year: i32,
+    summary: &'static str,
+    tags: &'static [&'static str],
+    /// Bookseller stock only.
+    price: Option<f64>,
+    /// Individual collection only: `(reading_status, user_rating)`.
+    reading: Option<(&'static str, Option<i32>)>,
+}
+
+const fn sample(
+    title: &'static str,
+    author: &'static str,
+    isbn: Option<&'static str>,
+    publisher: &'static str,
+    year: i32,
+    summary: &'static str,
+    tags: &'static [&'static str],
+) -> SampleBook {
+    SampleBook {
+        title,
+        author,
+        isbn,
+        publisher,
+        year,
+        summary,
+        tags,
+        price: None,
+        reading: None,
+    }
+}
+
+const fn priced(book: SampleBook, price: f64) -> SampleBook {
+    SampleBook {
+        price: Some(price),
+        ..book
+    }
+}
+
+const fn read(book: SampleBook, status: &'static str, rating: Option<i32>) -> SampleBook {
+    SampleBook {
+        reading: Some((status, rating)),
+        ..book
+    }
+}
+
+const INDIVIDUAL_EN: [SampleBook; 4] = [
+    read(
+        sample(
+            "Dune",
+            "Frank Herbert",
+            Some("9780441172719"),
+            "Ace Books",
+            1965,
+            "A spice planet story.",
+            &["Sci-Fi", "Classic"],
+        ),
+        "read",
+        Some(9),
+    ),
+    read(
+        sample(
+            "The Hobbit",
+            "J.R.R. Tolkien",
+            Some("9780547928227"),
+            "Houghton Mifflin Harcourt",
+            1937,
+            "Bilbo Baggins leaves the Shire for a dragon's treasure.",
+            &["Fantasy", "Classic"],
+        ),
+        "reading",
+        None,
+    ),
+    read(
+        sample(
+            "Foundation",
+            "Isaac Asimov",
+            Some("9780553293357"),
+            "Bantam Spectra",
+            1951,
+            "A mathematician plans for the fall of a galactic empire.",
+            &["Sci-Fi"],
+        ),
+        "to_read",
+        None,
+    ),
+    read(
+        sample(
+            "Pride and Prejudice",
+            "Jane Austen",
+            Some("9780141439518"),
+            "Penguin Classics",
+            1813,
+            "Elizabeth Bennet and Mr Darcy misjudge each other.",
+            &["Classic"],
+        ),
+        "read",
+        Some(8),
+    ),
+];
+
+const INDIVIDUAL_FR: [SampleBook; 4] = [
+    read(
+        sample(
+            "L'Étranger",
+            "Albert Camus",
+            Some("9782070360024"),
+            "Folio",
+            1942,
+            "Meursault, indifférent à tout, commet un meurtre sous le soleil d'Alger.",
+            &["Roman", "Classique"],
+        ),
+        "read",
+        Some(9),
+    ),
+    read(
+        sample(
+            "Le Petit Prince",
+            "Antoine de Saint-Exupéry",
+            Some("9782070612758"),
+            "Gallimard",
+            1943,
+            "Un aviateur perdu dans le désert rencontre un petit prince.",
+            &["Classique", "Jeunesse"],
+        ),
+        "read",
+        Some(10),
+    ),
+    read(
+        sample(
+            "Vingt mille lieues sous les mers",
+            "Jules Verne",
+            None,
+            "Hetzel",
+            1870,
+            "Le capitaine Nemo et le Nautilus parcourent les océans.",
+            &["Aventure", "Classique"],
+        ),
+        "reading",
+        None,
+    ),
+    read(
+        sample(
+            "Les Misérables",
+            "Victor Hugo",
+            None,
+            "Le Livre de Poche",
+            1862,
+            "Jean Valjean, ancien forçat, cherche la rédemption.",
+            &["Roman", "Classique"],
+        ),
+        "to_read",
+        None,
+    ),
+];
+
+const SCHOOL_EN: [SampleBook; 4] = [
+    sample(
+        "Charlotte's Web",
+        "E. B. White",
+        Some("9780064400558"),
+        "HarperCollins",
+        1952,
+        "A spider saves her friend Wilbur the pig.",
+        &["Children", "Classic"],
+    ),
+    sample(
+        "Holes",
+        "Louis Sachar",
+        Some("9780440414803"),
+        "Yearling",
+        1998,
+        "Stanley Yelnats digs holes at Camp Green Lake.",
+        &["Middle Grade"],
+    ),
+    sample(
+        "Wonder",
+        "R. J. Palacio",
+        Some("9780375869020"),
+        "Knopf",
+        2012,
+        "Auggie starts school for the first time.",
+        &["Middle Grade"],
+    ),
+    sample(
+        "The Giver",
+        "Lois Lowry",
+        Some("9780544336261"),
+        "Houghton Mifflin Harcourt",
+        1993,
+        "Jonas learns the truth about his perfect community.",
+        &["Middle Grade", "Sci-Fi"],
+    ),
+];
+
+const SCHOOL_FR: [SampleBook; 4] = [
+    sample(
+        "Le Petit Nicolas",
+        "René Goscinny",
+        None,
+        "Folio Junior",
+        1960,
+        "Les aventures de Nicolas et de ses copains d'école.",
+        &["Jeunesse"],
+    ),
+    sample(
+        "Vendredi ou la Vie sauvage",
+        "Michel Tournier",
+        None,
+        "Folio Junior",
+        1971,
+        "Robinson et Vendredi sur leur île.",
+        &["Jeunesse", "Aventure"],
+    ),
+    sample(
+        "L'Enfant et la Rivière",
+        "Henri Bosco",
+        None,
+        "Folio Junior",
+        1945,
+        "Pascalet rêve de la rivière interdite.",
+        &["Jeunesse"],
+    ),
+    sample(
+        "Le Lion",
+        "Joseph Kessel",
+        None,
+        "Folio",
+        1958,
+        "Patricia et le lion King dans une réserve du Kenya.",
+        &["Roman", "Aventure"],
+    ),
+];
+
+const BOOKSELLER_EN: [SampleBook; 4] = [
+    priced(
+        sample(
+            "The Great Gatsby",
+            "F. Scott Fitzgerald",
+            Some("9780743273565"),
+            "Scribner",
+            1925,
+            "Jay Gatsby's parties on Long Island.",
+            &["Classic"],
+        ),
+        12.0,
+    ),
+    priced(
+        sample(
+            "Nineteen Eighty-Four",
+            "George Orwell",
+            Some("9780141036144"),
+            "Penguin",
+            1949,
+            "Winston Smith under the eye of Big Brother.",
+            &["Classic", "Sci-Fi"],
+        ),
+        9.99,
+    ),
+    priced(
+        sample(
+            "Jane Eyre",
+            "Charlotte Brontë",
+            Some("9780141441146"),
+            "Penguin Classics",
+            1847,
+            "An orphan becomes governess at Thornfield Hall.",
+            &["Classic"],
+        ),
+        7.99,
+    ),
+    priced(
+        sample(
+            "Dune",
+            "Frank Herbert",
+            Some("9780441172719"),
+            "Ace Books",
+            1965,
+            "A spice planet story.",
+            &["Sci-Fi"],
+        ),
+        10.99,
+    ),
+];
+
+const BOOKSELLER_FR: [SampleBook; 4] = [
+    priced(
+        sample(
+            "Madame Bovary",
+            "Gustave Flaubert",
+            None,
+            "Folio",
+            1857,
+            "Emma Bovary s'ennuie en Normandie.",
+            &["Roman", "Classique"],
+        ),
+        8.4,
+    ),
+    priced(
+        sample(
+            "Germinal",
+            "Émile Zola",
+            None,
+            "Le Livre de Poche",
+            1885,
+            "Une grève de mineurs dans le Nord.",
+            &["Roman", "Classique"],
+        ),
+        6.9,
+    ),
+    priced(
+        sample(
+            "Le Comte de Monte-Cristo",
+            "Alexandre Dumas",
+            None,
+            "Folio",
+            1844,
+            "Edmond Dantès prépare sa vengeance.",
+            &["Aventure", "Classique"],
+        ),
+        11.5,
+    ),
+    priced(
+        sample(
+            "Bel-Ami",
+            "Guy de Maupassant",
+            None,
+            "Folio",
+            1885,
+            "L'ascension d'un journaliste ambitieux à Paris.",
+            &["Roman"],
+        ),
+        5.9,
+    ),
+];
+
+fn catalogue(profile: SeedProfile, locale: SeedLocale) -> &'static [SampleBook] {
+    match (profile, locale) {
+        (SeedProfile::Individual, SeedLocale::En) => &INDIVIDUAL_EN,
+        (SeedProfile::Individual, SeedLocale::Fr) => &INDIVIDUAL_FR,
+        (SeedProfile::School, SeedLocale::En) => &SCHOOL_EN,
+        (SeedProfile::School, SeedLocale::Fr) => &SCHOOL_FR,
+        (SeedProfile::Bookseller, SeedLocale::En) => &BOOKSELLER_EN,
+        (SeedProfile::Bookseller, SeedLocale::Fr) => &BOOKSELLER_FR,
+    }
+}
+
+/// What a seeding run did.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct SeedReport {
+    pub profile: SeedProfile,
+    pub locale: SeedLocale,
+    pub books_created: usize,
+    /// Sample books already in the catalogue, left untouched.
+    pub books_skipped: usize,
+    pub copies_created: usize,
+}
 
-pub async fn seed_demo_data(db: &DatabaseConnection) -> Result<(), DbErr> {
+/// Demo accounts (`admin`/`admin`, `user`/`user`), a library, then the demo
+/// catalogue selected by `SEED_PROFILE` / `SEED_LOCALE`.
+pub async fn seed_demo_data(db: &DatabaseConnection) -> Result<SeedReport, DbErr> {
+    seed_demo_accounts(db).await?;
+    seed_profile(db, SeedProfile::from_env(), SeedLocale::from_env()).await
+}
+
+/// The demo accounts (`admin`/`admin`, `user`/`user`) and their library, without
+/// a catalogue.
+pub async fn seed_demo_accounts(db: &DatabaseConnection) -> Result<(), DbErr> {
     // 1. Create Users
     let admin_password = hash_password("admin").unwrap();
     let user_password = hash_password("user").unwrap();
@@ -61,61 +550,317 @@ pub async fn seed_demo_data(db: &DatabaseConnection) -> Result<(), DbErr> {
         )
         .exec(db)
         .await?;
+    Ok(())
+}
 
-    // 2. Create Authors
-    let authors = vec!["J.R.R. Tolkien", "Isaac Asimov", "Frank Herbert"];
+/// Seed the catalogue of `profile` in `locale` (authors, tags, books and their
+/// copies), without demo accounts. Books already present by title are skipped.
+pub async fn seed_profile(
+    db: &DatabaseConnection,
+    profile: SeedProfile,
+    locale: SeedLocale,
+) -> Result<SeedReport, DbErr> {
+    let txn = db.begin().await?;
+    let library_id = crate::utils::library_helpers::resolve_library_id(&txn).await?;
+    let mut report = SeedReport {
+        profile,
+        locale,
+        books_created: 0,
+        books_skipped: 0,
+        copies_created: 0,
+    };
+
+    for sample in catalogue(profile, locale) {
+        let existing = book::Entity::find()
+            .filter(book::Column::Title.eq(sample.title))
+            .one(&txn)
+            .await?;
+        if existing.is_some() {
+            report.books_skipped += 1;
+            continue;
+        }
 
-    for name in authors {
-        let author = author::ActiveModel {
-            name: Set(name.to_owned()),
-            created_at: Set(chrono::Utc::now().to_rfc3339()),
-            updated_at: Set(chrono::Utc::now().to_rfc3339()),
+        let now = chrono::Utc::now().to_rfc3339();
+        let (reading_status, user_rating) = sample.reading.unwrap_or(("to_read", None));
+        let book = book::ActiveModel {
+            title: Set(sample.title.to_owned()),
+            isbn: Set(sample.isbn.map(str::to_owned)),
+            summary: Set(Some(sample.summary.to_owned())),
+            publisher: Set(Some(sample.publisher.to_owned())),
+            publication_year: Set(Some(sample.year)),
+            reading_status: Set(reading_status.to_owned()),
+            user_rating: Set(user_rating),
+            owned: Set(true),
+            price: Set(sample.price),
+            private: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
             ..Default::default()
-        };
-        let res = author::Entity::insert(author).exec(db).await;
-        if let Ok(_res) = res {
-            // author_ids.push(res.last_insert_id);
-        } else {
-            // Handle existing authors if needed, for now just skip or find
-            // Simplified: assume empty DB for seed or ignore errors
         }
+        .insert(&txn)
+        .await?;
+
+        let author_id = find_or_create_author(&txn, sample.author).await?;
+        book_authors::ActiveModel {
+            book_id: Set(book.id.clone()),
+            author_id: Set(author_id),
+        }
+        .insert(&txn)
+        .await?;
+        for name in sample.tags {
+            let tag_id = find_or_create_tag(&txn, name).await?;
+            book_tags::ActiveModel {
+                book_id: Set(book.id.clone()),
+                tag_id: Set(tag_id),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        for _ in 0..profile.copies_per_book() {
+            copy::ActiveModel {
+                book_id: Set(book.id.clone()),
+                library_id: Set(library_id),
+                status: Set("available".to_owned()),
+                is_temporary: Set(false),
+                price: Set(sample.price),
+                created_at: Set(now.clone()),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            report.copies_created += 1;
+        }
+        report.books_created += 1;
     }
 
-    // 3. Create Tags
-    let tags = vec!["Fantasy", "Sci-Fi", "Classic"];
+    txn.commit().await?;
+    Ok(report)
+}
 
-    for name in tags {
-        let tag = tag::ActiveModel {
-            name: Set(name.to_owned()),
-            created_at: Set(chrono::Utc::now().to_rfc3339()),
-            updated_at: Set(chrono::Utc::now().to_rfc3339()),
-            ..Default::default()
+/// Seed the catalogue of `profile` in `locale` into in-memory repositories, as
+/// [`seed_profile`] does into the database. Copies belong to library 1, the one
+/// [`seed_demo_accounts`] creates; tags are listed in the books' `subjects`.
+pub async fn seed_memory_profile(
+    repos: &InMemoryRepositories,
+    profile: SeedProfile,
+    locale: SeedLocale,
+) -> Result<SeedReport, DomainError> {
+    let mut report = SeedReport {
+        profile,
+        locale,
+        books_created: 0,
+        books_skipped: 0,
+        copies_created: 0,
+    };
+    let mut authors: HashMap<String, String> = repos
+        .authors
+        .find_all()
+        .await?
+        .into_iter()
+        .map(|a| (a.name, a.id))
+        .collect();
+    let mut tags: HashSet<String> = repos
+        .tags
+        .find_all()
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+
+    for sample in catalogue(profile, locale) {
+        let title_taken = repos
+            .books
+            .find_all(BookFilter {
+                title: Some(sample.title.to_owned()),
+                ..Default::default()
+            })
+            .await?
+            .books
+            .iter()
+            .any(|b| b.title == sample.title);
+        if title_taken {
+            report.books_skipped += 1;
+            continue;
+        }
+
+        let (reading_status, user_rating) = sample.reading.unwrap_or(("to_read", None));
+        let book = repos
+            .books
+            .create(Book {
+                title: sample.title.to_owned(),
+                isbn: sample.isbn.map(str::to_owned),
+                summary: Some(sample.summary.to_owned()),
+                publisher: Some(sample.publisher.to_owned()),
+                publication_year: Some(sample.year),
+                subjects: Some(sample.tags.iter().map(|t| t.to_string()).collect()),
+                reading_status: Some(reading_status.to_owned()),
+                user_rating,
+                owned: Some(true),
+                price: sample.price,
+                private: Some(false),
+                ..Default::default()
+            })
+            .await?;
+        let book_id = book.id.unwrap_or_default();
+
+        let author_id = match authors.get(sample.author) {
+            Some(id) => id.clone(),
+            None => {
+                let id = repos.authors.create(sample.author.to_owned()).await?.id;
+                authors.insert(sample.author.to_owned(), id.clone());
+                id
+            }
         };
-        let _res = tag::Entity::insert(tag)
-            .on_conflict(
-                sea_orm::sea_query::OnConflict::column(tag::Column::Name)
-                    .do_nothing()
-                    .to_owned(),
-            )
-            .exec(db)
-            .await;
-
-        // Retrieve ID (simplified, in real app we'd query back if exists)
-    }
-
-    // 4. Create Books (Simplified)
-    let book = book::ActiveModel {
-        title: Set("Dune".to_owned()),
-        isbn: Set(Some("978-0441172719".to_owned())),
-        summary: Set(Some("A spice planet story.".to_owned())),
-        publisher: Set(Some("Ace Books".to_owned())),
-        publication_year: Set(Some(1965)),
-        created_at: Set(chrono::Utc::now().to_rfc3339()),
-        updated_at: Set(chrono::Utc::now().to_rfc3339()),
+        repos.store.link_author(&book_id, &author_id);
+        for name in sample.tags {
+            if tags.insert(name.to_string()) {
+                repos.tags.create(name.to_string(), None).await?;
+            }
+        }
+
+        for _ in 0..profile.copies_per_book() {
+            repos
+                .copies
+                .create(CreateCopyInput {
+                    book_id: book_id.clone(),
+                    library_id: 1,
+                    status: "available".to_owned(),
+                    price: sample.price,
+                    ..Default::default()
+                })
+                .await?;
+            report.copies_created += 1;
+        }
+        report.books_created += 1;
+    }
+    Ok(report)
+}
+
+async fn find_or_create_author<C: ConnectionTrait>(conn: &C, name: &str) -> Result<String, DbErr> {
+    if let Some(existing) = author::Entity::find()
+        .filter(author::Column::Name.eq(name))
+        .one(conn)
+        .await?
+    {
+        return Ok(existing.id);
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let created = author::ActiveModel {
+        name: Set(name.to_owned()),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
         ..Default::default()
-    };
+    }
+    .insert(conn)
+    .await?;
+    Ok(created.id)
+}
+
+async fn find_or_create_tag<C: ConnectionTrait>(conn: &C, name: &str) -> Result<String, DbErr> {
+    if let Some(existing) = tag::Entity::find()
+        .filter(tag::Column::Name.eq(name))
+        .one(conn)
+        .await?
+    {
+        return Ok(existing.id);
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let created = tag::ActiveModel {
+        name: Set(name.to_owned()),
+        parent_id: Set(None),
+        path: Set(name.to_owned()),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(created.id)
+}
 
-    book::Entity::insert(book).exec(db).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn profiles_and_locales_parse_leniently() {
+        assert_eq!(SeedProfile::parse(" School "), Some(SeedProfile::School));
+        assert_eq!(SeedProfile::parse("museum"), None);
+        assert_eq!(SeedLocale::parse("fr_FR.UTF-8"), Some(SeedLocale::Fr));
+        assert_eq!(SeedLocale::parse("de"), None);
+        assert_eq!(
+            SeedProfile::for_profile_type("librarian"),
+            SeedProfile::School
+        );
+    }
+
+    #[tokio::test]
+    async fn reseeding_is_idempotent() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+
+        let first = seed_profile(&db, SeedProfile::School, SeedLocale::Fr)
+            .await
+            .unwrap();
+        assert_eq!(first.books_created, SCHOOL_FR.len());
+        assert_eq!(first.copies_created, SCHOOL_FR.len() * 3);
+
+        let again = seed_profile(&db, SeedProfile::School, SeedLocale::Fr)
+            .await
+            .unwrap();
+        assert_eq!(again.books_created, 0);
+        assert_eq!(again.books_skipped, SCHOOL_FR.len());
+        assert_eq!(
+            book::Entity::find().count(&db).await.unwrap() as usize,
+            SCHOOL_FR.len()
+        );
+        assert_eq!(
+            copy::Entity::find().count(&db).await.unwrap() as usize,
+            SCHOOL_FR.len() * 3
+        );
+        // Shared tags are created once.
+        assert_eq!(
+            tag::Entity::find()
+                .filter(tag::Column::Name.eq("Jeunesse"))
+                .count(&db)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn the_catalogue_seeds_into_memory_repositories() {
+        let repos = InMemoryRepositories::new();
+
+        let first = seed_memory_profile(&repos, SeedProfile::School, SeedLocale::Fr)
+            .await
+            .unwrap();
+        assert_eq!(first.books_created, SCHOOL_FR.len());
+        assert_eq!(first.copies_created, SCHOOL_FR.len() * 3);
+        let again = seed_memory_profile(&repos, SeedProfile::School, SeedLocale::Fr)
+            .await
+            .unwrap();
+        assert_eq!(again.books_skipped, SCHOOL_FR.len());
+
+        let books = repos
+            .books
+            .find_all(BookFilter {
+                limit: Some(1000),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .books;
+        assert_eq!(books.len(), SCHOOL_FR.len());
+        let book = books
+            .iter()
+            .find(|b| b.title == SCHOOL_FR[0].title)
+            .unwrap();
+        assert_eq!(book.author.as_deref(), Some(SCHOOL_FR[0].author));
+        assert_eq!(book.available_copies, Some(3));
+        let tags = repos.tags.find_all().await.unwrap();
+        assert_eq!(tags.iter().filter(|t| t.name == "Jeunesse").count(), 1);
+    }
 }
@@ -4,8 +4,9 @@
 use axum::Router;
 use axum::routing::get;
 use sea_orm::DatabaseConnection;
-use std::net::{SocketAddr, TcpListener};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::api;
@@ -30,27 +31,33 @@ pub fn build_router(db: DatabaseConnection) -> Router {
         .allow_methods(Any)
         .allow_headers(Any);
 
-    Router::new()
+    let router = Router::new()
         .route("/invite", get(api::invite_page::invite_page))
-        .nest("/api", api_router)
-        .layer(cors)
+        .nest("/api", api_router);
+    #[cfg(feature = "admin-ui")]
+    let router = router.route("/admin", get(api::admin_ui::admin_page));
+    router.layer(cors)
 }
 
-/// Find an available port starting from the preferred port on a specific IP
-pub fn find_available_port_on_ip(preferred_port: u16, ip: &str) -> Option<u16> {
-    // Try preferred port first
-    if TcpListener::bind((ip, preferred_port)).is_ok() {
-        return Some(preferred_port);
+/// How many ports after the preferred one are tried before giving up.
+pub const PORT_SCAN_RANGE: u16 = 100;
+
+/// Bind the first free port in `preferred_port..preferred_port + PORT_SCAN_RANGE`
+/// on `ip` and return the listener.
+///
+/// The listener is kept and served as is: probing a port and binding it again
+/// later leaves a window for another instance to take it in between.
+pub async fn bind_available_port(preferred_port: u16, ip: IpAddr) -> Option<TcpListener> {
+    for offset in 0..PORT_SCAN_RANGE {
+        let Some(port) = preferred_port.checked_add(offset) else {
+            break;
+        };
+        match TcpListener::bind(SocketAddr::new(ip, port)).await {
+            Ok(listener) => return Some(listener),
+            Err(e) => tracing::debug!("Port {}:{} unavailable: {}", ip, port, e),
+        }
     }
-
-    // Scan next 100 ports
-    ((preferred_port + 1)..(preferred_port + 100))
-        .find(|&port| TcpListener::bind((ip, port)).is_ok())
-}
-
-/// Find an available port starting from the preferred port (0.0.0.0)
-pub fn find_available_port(preferred_port: u16) -> Option<u16> {
-    find_available_port_on_ip(preferred_port, "0.0.0.0")
+    None
 }
 
 /// Start the HTTP server on a background task
@@ -62,24 +69,25 @@ pub async fn start_server(db: DatabaseConnection, preferred_port: u16) -> Result
         return Err("HTTP server is already running".to_string());
     }
 
-    // Find available port - try 0.0.0.0 first, then fallback to 127.0.0.1
-    let (port, addr) = if let Some(p) = find_available_port_on_ip(preferred_port, "0.0.0.0") {
-        (p, SocketAddr::from(([0, 0, 0, 0], p)))
-    } else if let Some(p) = find_available_port_on_ip(preferred_port, "127.0.0.1") {
+    // Bind an available port - try 0.0.0.0 first, then fallback to 127.0.0.1
+    let listener = if let Some(l) =
+        bind_available_port(preferred_port, Ipv4Addr::UNSPECIFIED.into()).await
+    {
+        l
+    } else if let Some(l) = bind_available_port(preferred_port, Ipv4Addr::LOCALHOST.into()).await {
         tracing::warn!("⚠️ Falling back to 127.0.0.1 binding (P2P may not work)");
-        (p, SocketAddr::from(([127, 0, 0, 1], p)))
+        l
     } else {
         return Err("Failed to find available port on 0.0.0.0 or 127.0.0.1".to_string());
     };
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get local address: {}", e))?;
+    let port = addr.port();
 
     // Build router
     let app = build_router(db);
 
-    // Bind listener
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .map_err(|e| format!("Failed to bind to {}: {}", addr, e))?;
-
     // Mark as running
     SERVER_RUNNING.store(true, Ordering::SeqCst);
 
@@ -106,3 +114,21 @@ pub fn stop_server() {
     // TODO: Implement graceful shutdown with tokio::sync::watch or similar
     SERVER_RUNNING.store(false, Ordering::SeqCst);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bind_available_port_skips_taken_port() {
+        let taken = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let taken_port = taken.local_addr().unwrap().port();
+
+        let listener = bind_available_port(taken_port, Ipv4Addr::LOCALHOST.into())
+            .await
+            .expect("a free port in range");
+        let port = listener.local_addr().unwrap().port();
+        assert_ne!(port, taken_port);
+        assert!(port > taken_port && port < taken_port.saturating_add(PORT_SCAN_RANGE));
+    }
+}
@@ -0,0 +1,219 @@
+//! Progress of the first-run setup wizard (`/api/setup/state`,
+//! `/api/setup/steps`, `/api/setup/complete`).
+//!
+//! `setup_wizard` is a local singleton row recording which steps were saved and
+//! when the wizard was finished. The values entered live where the rest of the
+//! app reads them (`installation_profile`, `users`, `library_config`), so a
+//! client resuming an interrupted setup reads them back from there.
+//!
+//! Steps are saved in [`WizardStep::ALL`] order: a step is accepted once every
+//! required step before it is done, and may be saved again until the wizard is
+//! completed. Optional steps can be skipped.
+
+use std::collections::BTreeSet;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, Statement};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardStep {
+    Profile,
+    Admin,
+    Library,
+    Location,
+    Modules,
+    DemoData,
+}
+
+impl WizardStep {
+    pub const ALL: [WizardStep; 6] = [
+        WizardStep::Profile,
+        WizardStep::Admin,
+        WizardStep::Library,
+        WizardStep::Location,
+        WizardStep::Modules,
+        WizardStep::DemoData,
+    ];
+
+    /// Steps that must be saved before the wizard can be completed.
+    pub fn is_required(self) -> bool {
+        matches!(
+            self,
+            WizardStep::Profile | WizardStep::Admin | WizardStep::Library
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WizardStep::Profile => "profile",
+            WizardStep::Admin => "admin",
+            WizardStep::Library => "library",
+            WizardStep::Location => "location",
+            WizardStep::Modules => "modules",
+            WizardStep::DemoData => "demo_data",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardStatus {
+    /// No step saved yet.
+    NotStarted,
+    /// Some steps saved, a required one still missing.
+    InProgress,
+    /// Every required step saved; the wizard can be completed.
+    Ready,
+    /// Setup finished; steps can no longer be saved.
+    Completed,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WizardProgress {
+    pub done: BTreeSet<WizardStep>,
+    pub completed_at: Option<String>,
+}
+
+impl WizardProgress {
+    pub fn status(&self) -> WizardStatus {
+        if self.completed_at.is_some() {
+            WizardStatus::Completed
+        } else if self.missing_required().is_none() {
+            WizardStatus::Ready
+        } else if self.done.is_empty() {
+            WizardStatus::NotStarted
+        } else {
+            WizardStatus::InProgress
+        }
+    }
+
+    /// The step a client should show next: the first one not saved yet.
+    pub fn next_step(&self) -> Option<WizardStep> {
+        if self.completed_at.is_some() {
+            return None;
+        }
+        WizardStep::ALL
+            .into_iter()
+            .find(|step| !self.done.contains(step))
+    }
+
+    /// The first required step not saved yet.
+    pub fn missing_required(&self) -> Option<WizardStep> {
+        WizardStep::ALL
+            .into_iter()
+            .find(|step| step.is_required() && !self.done.contains(step))
+    }
+
+    /// The required step that must be saved before `step` can be, if any.
+    pub fn blocker_of(&self, step: WizardStep) -> Option<WizardStep> {
+        WizardStep::ALL
+            .into_iter()
+            .take_while(|s| *s != step)
+            .find(|s| s.is_required() && !self.done.contains(s))
+    }
+}
+
+/// The saved progress. A missing row means the wizard was never started.
+pub async fn load(db: &DatabaseConnection) -> Result<WizardProgress, DbErr> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT completed_steps, completed_at FROM setup_wizard WHERE id = 1".to_owned(),
+        ))
+        .await?;
+    let Some(row) = row else {
+        return Ok(WizardProgress::default());
+    };
+    let steps: String = row.try_get("", "completed_steps")?;
+    // Unknown step names (from a newer build) are dropped rather than failing.
+    let done = serde_json::from_str::<Vec<serde_json::Value>>(&steps)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| serde_json::from_value(v).ok())
+        .collect();
+    Ok(WizardProgress {
+        done,
+        completed_at: row.try_get("", "completed_at")?,
+    })
+}
+
+pub async fn save(db: &DatabaseConnection, progress: &WizardProgress) -> Result<(), DbErr> {
+    let steps: Vec<&str> = progress.done.iter().map(|s| s.as_str()).collect();
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "INSERT INTO setup_wizard (id, completed_steps, completed_at, updated_at) \
+         VALUES (1, ?, ?, ?) \
+         ON CONFLICT(id) DO UPDATE SET completed_steps = excluded.completed_steps, \
+         completed_at = excluded.completed_at, updated_at = excluded.updated_at",
+        [
+            serde_json::to_string(&steps).unwrap_or_default().into(),
+            progress.completed_at.clone().into(),
+            chrono::Utc::now().to_rfc3339().into(),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Forget all progress, so the next launch offers the wizard again.
+pub async fn clear(db: &DatabaseConnection) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        "DELETE FROM setup_wizard".to_owned(),
+    ))
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_steps_can_be_skipped_but_required_ones_gate_later_steps() {
+        let mut progress = WizardProgress::default();
+        assert_eq!(progress.status(), WizardStatus::NotStarted);
+        assert_eq!(progress.next_step(), Some(WizardStep::Profile));
+        assert_eq!(
+            progress.blocker_of(WizardStep::Library),
+            Some(WizardStep::Profile)
+        );
+
+        progress
+            .done
+            .extend([WizardStep::Profile, WizardStep::Admin]);
+        assert_eq!(progress.status(), WizardStatus::InProgress);
+        assert_eq!(progress.blocker_of(WizardStep::Library), None);
+        assert_eq!(
+            progress.blocker_of(WizardStep::Modules),
+            Some(WizardStep::Library)
+        );
+
+        progress.done.insert(WizardStep::Library);
+        assert_eq!(progress.status(), WizardStatus::Ready);
+        assert_eq!(progress.next_step(), Some(WizardStep::Location));
+
+        progress.completed_at = Some("2026-01-01T00:00:00Z".into());
+        assert_eq!(progress.status(), WizardStatus::Completed);
+        assert_eq!(progress.next_step(), None);
+    }
+
+    #[tokio::test]
+    async fn progress_round_trips_and_clears() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        assert_eq!(load(&db).await.unwrap(), WizardProgress::default());
+
+        let progress = WizardProgress {
+            done: [WizardStep::Profile, WizardStep::DemoData].into(),
+            completed_at: None,
+        };
+        save(&db, &progress).await.unwrap();
+        assert_eq!(load(&db).await.unwrap(), progress);
+
+        clear(&db).await.unwrap();
+        assert_eq!(load(&db).await.unwrap(), WizardProgress::default());
+    }
+}
@@ -0,0 +1,105 @@
+//! Process shutdown: OS signal handling and the token background workers
+//! watch to stop at a safe point.
+//!
+//! The desktop binary waits on [`wait_for_signal`], triggers the [`Shutdown`]
+//! it handed to its workers, lets them drain, then closes the database. A
+//! worker never stops in the middle of a unit of work (one operation-log
+//! entry, one transaction): it checks the token between units.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+/// How long in-flight work gets to finish before the process exits anyway.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cloneable shutdown token. Every clone observes [`Shutdown::trigger`].
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: watch::Sender<bool>,
+    rx: watch::Receiver<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (tx, rx) = watch::channel(false);
+        Self { tx, rx }
+    }
+
+    /// Ask every holder of this token to stop.
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolve once [`Shutdown::trigger`] has been called (immediately if it
+    /// already was).
+    pub async fn triggered(&self) {
+        let mut rx = self.rx.clone();
+        // The sender lives in `self`, so the channel cannot close under us.
+        let _ = rx.wait_for(|stop| *stop).await;
+    }
+}
+
+/// Resolve when the process receives a shutdown signal: Ctrl-C (SIGINT) or, on
+/// Unix, SIGTERM (the signal `docker stop` / systemd send).
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            // If the handler cannot be installed, never resolve on this arm: Ctrl-C
+            // still triggers shutdown.
+            Err(e) => {
+                tracing::warn!("Failed to install SIGTERM handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received SIGINT, shutting down"),
+        _ = terminate => tracing::info!("Received SIGTERM, shutting down"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trigger_wakes_every_clone() {
+        let shutdown = Shutdown::new();
+        let worker = shutdown.clone();
+        assert!(!worker.is_triggered());
+
+        let handle = tokio::spawn(async move { worker.triggered().await });
+        shutdown.trigger();
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("worker woke up")
+            .unwrap();
+        assert!(shutdown.is_triggered());
+        // Late waiters resolve immediately.
+        shutdown.triggered().await;
+    }
+}
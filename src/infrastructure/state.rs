@@ -5,14 +5,19 @@ use std::sync::Arc;
 use tokio::sync::OnceCell;
 
 use crate::domain::{
-    AuthorRepository, BookRepository, CollectionRepository, CopyRepository, GamificationRepository,
-    LinkedDeviceRepository, LoanSettingsRepository, MetadataFillRepository, NotificationRepository,
+    AuthorRepository, BookRepository, CollectionRepository, ContactRepository, CopyRepository,
+    GamificationRepository, LinkedDeviceRepository, LoanRepository, LoanSettingsRepository,
+    MetadataFillRepository, NotificationRepository, P2pRequestRepository, PeerRepository,
+    TagRepository,
 };
 use crate::infrastructure::nonce_store::SqliteNonceStore;
+use crate::infrastructure::repositories::memory::InMemoryRepositories;
 use crate::infrastructure::{
-    SeaOrmAuthorRepository, SeaOrmBookRepository, SeaOrmCollectionRepository, SeaOrmCopyRepository,
-    SeaOrmGamificationRepository, SeaOrmLinkedDeviceRepository, SeaOrmLoanSettingsRepository,
-    SeaOrmMetadataFillRepository, SeaOrmNotificationRepository,
+    SeaOrmAuthorRepository, SeaOrmBookRepository, SeaOrmCollectionRepository,
+    SeaOrmContactRepository, SeaOrmCopyRepository, SeaOrmGamificationRepository,
+    SeaOrmLinkedDeviceRepository, SeaOrmLoanRepository, SeaOrmLoanSettingsRepository,
+    SeaOrmMetadataFillRepository, SeaOrmNotificationRepository, SeaOrmP2pRequestRepository,
+    SeaOrmPeerRepository, SeaOrmTagRepository,
 };
 use crate::services::IdentityService;
 use crate::services::crypto_service::CryptoService;
@@ -50,6 +55,9 @@ const PEER_UNREACHABLE_TTL: std::time::Duration = std::time::Duration::from_secs
 pub struct AppState {
     /// Database connection (for backward compatibility)
     db: DatabaseConnection,
+    /// URL `db` was opened from; names the file snapshotted before a reset.
+    /// `None` when unknown (tests) or in memory.
+    database_url: Option<String>,
     /// Actual HTTP server port (may differ from 8000 if occupied)
     server_port: Arc<std::sync::atomic::AtomicU16>,
     /// Book repository
@@ -60,12 +68,22 @@ pub struct AppState {
     pub copy_repo: Arc<dyn CopyRepository>,
     /// Collection repository
     pub collection_repo: Arc<dyn CollectionRepository>,
+    /// Contact repository
+    pub contact_repo: Arc<dyn ContactRepository>,
+    /// Loan repository
+    pub loan_repo: Arc<dyn LoanRepository>,
+    /// Peer repository
+    pub peer_repo: Arc<dyn PeerRepository>,
+    /// Tag repository
+    pub tag_repo: Arc<dyn TagRepository>,
     /// Gamification repository
     pub gamification_repo: Arc<dyn GamificationRepository>,
     /// Linked device repository (multi-device sync)
     pub linked_device_repo: Arc<dyn LinkedDeviceRepository>,
     /// Notification repository (activity feed)
     pub notification_repo: Arc<dyn NotificationRepository>,
+    /// P2P request repository (incoming and outgoing borrow requests)
+    pub p2p_request_repo: Arc<dyn P2pRequestRepository>,
     /// Loan settings repository (loan duration configuration)
     pub loan_settings_repo: Arc<dyn LoanSettingsRepository>,
     /// Bulk metadata gap-fill repository (ADR-041): completeness stat, work-list,
@@ -110,22 +128,33 @@ impl AppState {
         let author_repo = Arc::new(SeaOrmAuthorRepository::new(db.clone()));
         let copy_repo = Arc::new(SeaOrmCopyRepository::new(db.clone()));
         let collection_repo = Arc::new(SeaOrmCollectionRepository::new(db.clone()));
+        let contact_repo = Arc::new(SeaOrmContactRepository::new(db.clone()));
+        let loan_repo = Arc::new(SeaOrmLoanRepository::new(db.clone()));
+        let peer_repo = Arc::new(SeaOrmPeerRepository::new(db.clone()));
+        let tag_repo = Arc::new(SeaOrmTagRepository::new(db.clone()));
         let gamification_repo = Arc::new(SeaOrmGamificationRepository::new(db.clone()));
         let linked_device_repo = Arc::new(SeaOrmLinkedDeviceRepository::new(db.clone()));
         let notification_repo = Arc::new(SeaOrmNotificationRepository::new(db.clone()));
+        let p2p_request_repo = Arc::new(SeaOrmP2pRequestRepository::new(db.clone()));
         let loan_settings_repo = Arc::new(SeaOrmLoanSettingsRepository::new(db.clone()));
         let metadata_fill_repo = Arc::new(SeaOrmMetadataFillRepository::new(db.clone()));
 
         Self {
             db,
+            database_url: None,
             server_port: Arc::new(std::sync::atomic::AtomicU16::new(8000)),
             book_repo,
             author_repo,
             copy_repo,
             collection_repo,
+            contact_repo,
+            loan_repo,
+            peer_repo,
+            tag_repo,
             gamification_repo,
             linked_device_repo,
             notification_repo,
+            p2p_request_repo,
             loan_settings_repo,
             metadata_fill_repo,
             metadata_fill: Arc::new(MetadataFillManager::new()),
@@ -140,6 +169,40 @@ impl AppState {
         }
     }
 
+    /// Create an AppState whose repositories are the in-memory ones (`--demo`).
+    /// `db` still backs what has no repository yet: accounts, settings, peers'
+    /// messages, the operation log.
+    pub fn in_memory(db: DatabaseConnection, repos: InMemoryRepositories) -> Self {
+        Self {
+            book_repo: Arc::new(repos.books),
+            author_repo: Arc::new(repos.authors),
+            copy_repo: Arc::new(repos.copies),
+            collection_repo: Arc::new(repos.collections),
+            contact_repo: Arc::new(repos.contacts),
+            loan_repo: Arc::new(repos.loans),
+            peer_repo: Arc::new(repos.peers),
+            tag_repo: Arc::new(repos.tags),
+            gamification_repo: Arc::new(repos.gamification),
+            linked_device_repo: Arc::new(repos.linked_devices),
+            notification_repo: Arc::new(repos.notifications),
+            p2p_request_repo: Arc::new(repos.p2p_requests),
+            loan_settings_repo: Arc::new(repos.loan_settings),
+            metadata_fill_repo: Arc::new(repos.metadata_fill),
+            ..Self::new(db)
+        }
+    }
+
+    /// Record the URL the database was opened from.
+    pub fn with_database_url(mut self, database_url: impl Into<String>) -> Self {
+        self.database_url = Some(database_url.into());
+        self
+    }
+
+    /// The URL the database was opened from, when known.
+    pub fn database_url(&self) -> Option<&str> {
+        self.database_url.as_deref()
+    }
+
     /// Get the CryptoService, lazily initializing it from the IdentityService.
     /// Returns None if identity hasn't been initialized yet.
     pub fn crypto_service(&self) -> Option<&Arc<CryptoService<SqliteNonceStore>>> {
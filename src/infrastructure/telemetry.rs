@@ -0,0 +1,261 @@
+//! Optional OpenTelemetry export (cargo feature `otel`).
+//!
+//! When built with `otel` and `OTEL_EXPORTER_OTLP_ENDPOINT` is set, tracing
+//! spans are exported over OTLP/HTTP (protobuf), together with a request
+//! counter and a latency histogram. What ends up in a trace:
+//!
+//! - one `request` span per HTTP request (see `api::request_id`), continued
+//!   from the caller's W3C `traceparent` header when a peer sends one;
+//! - the `sqlx::query` events of that request, as span events;
+//! - spans around external catalogue calls and P2P sync/search, whose outgoing
+//!   peer requests carry `traceparent` (see `get_safe_client`).
+//!
+//! So a federated search run from node A shows up as one trace spanning every
+//! peer that answered. The standard `OTEL_*` variables (`OTEL_SERVICE_NAME`,
+//! `OTEL_EXPORTER_OTLP_HEADERS`, ...) are honoured by the exporter.
+//!
+//! Without the feature every function here is a no-op, so call sites need no
+//! `cfg`.
+
+use axum::http::HeaderMap;
+use tracing::Subscriber;
+use tracing_subscriber::{Layer, registry::LookupSpan};
+
+/// Boxed tracing layer, as returned by [`layer`].
+pub type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync + 'static>;
+
+/// The tracing layer exporting spans, or `None` when export is not configured.
+/// Also installs the metrics pipeline and the trace-context propagator.
+pub fn layer<S>() -> Option<BoxedLayer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    #[cfg(feature = "otel")]
+    {
+        otel::layer()
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        None
+    }
+}
+
+/// Write the current span's trace context into outgoing request headers.
+pub fn inject_context(headers: &mut HeaderMap) {
+    #[cfg(feature = "otel")]
+    otel::inject_context(headers);
+    #[cfg(not(feature = "otel"))]
+    let _ = headers;
+}
+
+/// Make `span` a child of the remote trace described by incoming `headers`.
+pub fn adopt_remote_parent(headers: &HeaderMap, span: &tracing::Span) {
+    #[cfg(feature = "otel")]
+    otel::adopt_remote_parent(headers, span);
+    #[cfg(not(feature = "otel"))]
+    let _ = (headers, span);
+}
+
+/// Count a served request and record its latency.
+pub fn record_request(method: &str, status: u16, latency: std::time::Duration) {
+    #[cfg(feature = "otel")]
+    otel::record_request(method, status, latency);
+    #[cfg(not(feature = "otel"))]
+    let _ = (method, status, latency);
+}
+
+/// Flush and stop the exporters. Call once, before the process exits.
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    otel::shutdown();
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::BoxedLayer;
+    use axum::http::{HeaderMap, HeaderName, HeaderValue};
+    use opentelemetry::metrics::{Counter, Histogram};
+    use opentelemetry::propagation::{Extractor, Injector};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::{KeyValue, global};
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use std::sync::OnceLock;
+    use tracing::{Level, Subscriber};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::{Layer, registry::LookupSpan};
+
+    static PROVIDERS: OnceLock<(SdkTracerProvider, SdkMeterProvider)> = OnceLock::new();
+
+    struct RequestInstruments {
+        count: Counter<u64>,
+        duration: Histogram<f64>,
+    }
+
+    static INSTRUMENTS: OnceLock<RequestInstruments> = OnceLock::new();
+
+    pub(super) fn layer<S>() -> Option<BoxedLayer<S>>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+    {
+        std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|endpoint| !endpoint.trim().is_empty())?;
+
+        let mut resource = Resource::builder();
+        if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+            resource = resource.with_service_name("bibliogenius");
+        }
+        let resource = resource.build();
+
+        let spans = match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("telemetry: OTLP span exporter disabled: {e}");
+                return None;
+            }
+        };
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(spans)
+            .with_resource(resource.clone())
+            .build();
+
+        let meter_provider = match opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => SdkMeterProvider::builder()
+                .with_periodic_exporter(exporter)
+                .with_resource(resource)
+                .build(),
+            Err(e) => {
+                eprintln!("telemetry: OTLP metric exporter disabled: {e}");
+                SdkMeterProvider::builder().build()
+            }
+        };
+
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        global::set_meter_provider(meter_provider.clone());
+        let tracer = tracer_provider.tracer("bibliogenius");
+        let _ = PROVIDERS.set((tracer_provider, meter_provider));
+
+        // Independent of RUST_LOG: the app's own spans, the SQL statements
+        // sqlx logs per query, and the SSRF audit events. The access log is
+        // redundant with the request span.
+        let targets = Targets::new()
+            .with_target("rust_lib_app", Level::INFO)
+            .with_target("sqlx::query", Level::INFO)
+            .with_target("ssrf", Level::WARN)
+            .with_target("access", tracing::level_filters::LevelFilter::OFF);
+
+        Some(
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer)
+                .with_filter(targets)
+                .boxed(),
+        )
+    }
+
+    struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+    impl Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    pub(super) fn inject_context(headers: &mut HeaderMap) {
+        if PROVIDERS.get().is_none() {
+            return;
+        }
+        let cx = tracing::Span::current().context();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut HeaderInjector(headers))
+        });
+    }
+
+    pub(super) fn adopt_remote_parent(headers: &HeaderMap, span: &tracing::Span) {
+        if PROVIDERS.get().is_none() || !headers.contains_key("traceparent") {
+            return;
+        }
+        let cx = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        let _ = span.set_parent(cx);
+    }
+
+    pub(super) fn record_request(method: &str, status: u16, latency: std::time::Duration) {
+        if PROVIDERS.get().is_none() {
+            return;
+        }
+        let instruments = INSTRUMENTS.get_or_init(|| {
+            let meter = global::meter("bibliogenius");
+            RequestInstruments {
+                count: meter.u64_counter("http.server.request.count").build(),
+                duration: meter
+                    .f64_histogram("http.server.request.duration")
+                    .with_unit("s")
+                    .build(),
+            }
+        });
+        let attributes = [
+            KeyValue::new("http.request.method", method.to_string()),
+            KeyValue::new("http.response.status_code", i64::from(status)),
+        ];
+        instruments.count.add(1, &attributes);
+        instruments
+            .duration
+            .record(latency.as_secs_f64(), &attributes);
+    }
+
+    pub(super) fn shutdown() {
+        if let Some((tracer_provider, meter_provider)) = PROVIDERS.get() {
+            if let Err(e) = tracer_provider.shutdown() {
+                eprintln!("telemetry: span exporter shutdown: {e}");
+            }
+            if let Err(e) = meter_provider.shutdown() {
+                eprintln!("telemetry: metric exporter shutdown: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_an_endpoint_nothing_is_exported_or_injected() {
+        if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_some() {
+            return;
+        }
+        assert!(layer::<tracing_subscriber::Registry>().is_none());
+
+        let mut headers = HeaderMap::new();
+        inject_context(&mut headers);
+        assert!(headers.is_empty());
+    }
+}
@@ -20,6 +20,8 @@ pub mod models;
 pub mod modules;
 pub mod services;
 pub mod sync;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod utils;
 
 // Re-exports for backward compatibility during migration
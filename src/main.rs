@@ -1,73 +1,108 @@
 use axum::Router;
 use axum::routing::get;
-use std::net::{SocketAddr, TcpListener};
+use clap::{Parser, Subcommand};
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use tower_http::cors::{Any, CorsLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-use sea_orm::EntityTrait;
-
-use rust_lib_app::{api, config, db, seed};
-
-/// Find an available port starting from the preferred port
-fn find_available_port(preferred_port: u16) -> Option<u16> {
-    // Try preferred port first
-    if TcpListener::bind(("0.0.0.0", preferred_port)).is_ok() {
-        return Some(preferred_port);
-    }
-
-    // Scan next 100 ports
-    ((preferred_port + 1)..(preferred_port + 100))
-        .find(|&port| TcpListener::bind(("0.0.0.0", port)).is_ok())
-}
-
-/// Write the selected port to a file for the Flutter app to read
-fn write_port_file(port: u16, profile: &str) -> std::io::Result<()> {
-    let port_file = get_port_file_path(profile);
-
-    // Create parent directory if it doesn't exist
-    if let Some(parent) = port_file.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
+use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
+
+use sea_orm::{DatabaseConnection, EntityTrait};
+
+use rust_lib_app::infrastructure::instance_lock::InstanceLock;
+use rust_lib_app::infrastructure::port_file::{self, PortFile};
+use rust_lib_app::infrastructure::repositories::memory::InMemoryRepositories;
+use rust_lib_app::infrastructure::shutdown::{self, Shutdown};
+use rust_lib_app::seed::{SeedLocale, SeedProfile};
+use rust_lib_app::{api, config, db, seed, server};
+
+/// The database of `--demo` (accounts, settings; the catalogue is held by the
+/// in-memory repositories): in memory, never written to disk.
+const DEMO_DATABASE_URL: &str = "sqlite::memory:";
+
+/// BiblioGenius library server and maintenance commands.
+///
+/// Without a subcommand, runs the HTTP server (`serve`).
+#[derive(Parser)]
+#[command(name = "bibliogenius", version)]
+struct Cli {
+    /// Profile to run as (selects the default database and the port file).
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Print the OpenAPI document and exit, for client generation.
+    #[arg(long)]
+    openapi: bool,
+
+    /// Print the effective configuration (defaults < config.toml < environment)
+    /// in config.toml format and exit.
+    #[arg(long)]
+    print_config: bool,
+
+    /// Start even though the previous instance did not release its lock on the
+    /// database (crash, SIGKILL). Also `BIBLIOGENIUS_TAKE_OVER_STALE_LOCK=1`.
+    #[arg(long, global = true)]
+    take_over_stale_lock: bool,
+
+    /// Serve a throwaway library: the demo catalogue in in-memory repositories,
+    /// the demo accounts in an in-memory database, without mDNS. Nothing touches
+    /// the disk and the library is gone when the server stops. Only valid for
+    /// `serve`. Also `BIBLIOGENIUS_DEMO=1`.
+    #[arg(long, global = true)]
+    demo: bool,
+
+    /// Legacy spelling of the `mcp` subcommand, still written in MCP client
+    /// configs (see `/api/integrations/mcp-config`).
+    #[cfg(feature = "mcp")]
+    #[arg(long, hide = true)]
+    mcp: bool,
 
-    std::fs::write(port_file, port.to_string())
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-/// Get the path to the port file
-fn get_port_file_path(profile: &str) -> PathBuf {
-    let filename = if profile == "default" {
-        "backend_port.txt".to_string()
-    } else {
-        format!("backend_port_{}.txt", profile)
-    };
-    // On macOS: ~/Library/Caches/BiblioGenius/backend_port.txt
-    // On Linux: ~/.cache/bibliogenius/backend_port.txt
-    // On Windows: %LOCALAPPDATA%\BiblioGenius\backend_port.txt
-
-    #[cfg(target_os = "macos")]
-    {
-        let home = std::env::var("HOME").expect("HOME not set");
-        PathBuf::from(home)
-            .join("Library")
-            .join("Caches")
-            .join("BiblioGenius")
-            .join(filename)
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        let home = std::env::var("HOME").expect("HOME not set");
-        PathBuf::from(home)
-            .join(".cache")
-            .join("bibliogenius")
-            .join(filename)
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let appdata = std::env::var("LOCALAPPDATA").expect("LOCALAPPDATA not set");
-        PathBuf::from(appdata).join("BiblioGenius").join(filename)
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Run the HTTP server (the default).
+    Serve,
+    /// Import books from a Goodreads, LibraryThing, Babelio or Inventaire
+    /// export, an ISBN list, or a JSON export of another library (merged).
+    Import {
+        file: PathBuf,
+        /// Rows matching a catalogued book: skip, merge or create-anyway.
+        #[arg(long, default_value = "skip")]
+        dedup: rust_lib_app::import::DedupPolicy,
+    },
+    /// Write a JSON export of the library (same format as `GET /api/export`).
+    Export {
+        file: PathBuf,
+        /// Also export private books and books with a private tag.
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Write an encrypted `.bgbackup` archive. The node identity is not included.
+    Backup {
+        /// Archive to write (default: a dated file in the backups directory,
+        /// `BIBLIOGENIUS_BACKUP_DIR`, else `backups` in the data directory).
+        output: Option<PathBuf>,
+        /// Library UUID recorded in the manifest.
+        #[arg(long)]
+        library_uuid: String,
+        /// Environment variable holding the passphrase, so it stays out of the
+        /// process list and shell history.
+        #[arg(long, default_value = "BIBLIOGENIUS_BACKUP_PASSPHRASE")]
+        passphrase_env: String,
+        /// Directory of local covers (default: `COVERS_DIR`, else `covers`
+        /// next to the database).
+        #[arg(long)]
+        cover_dir: Option<PathBuf>,
+    },
+    /// Apply pending database migrations and exit.
+    Migrate,
+    /// Seed the demo catalogue (`SEED_PROFILE` / `SEED_LOCALE`) and exit.
+    Seed,
+    /// Serve MCP over stdio, proxying to the running app.
+    #[cfg(feature = "mcp")]
+    Mcp,
 }
 
 #[tokio::main]
@@ -76,79 +111,444 @@ async fn main() {
     // Filter targets the lib crate name "rust_lib_app" (not the package name "bibliogenius").
     // The `ssrf` target family (ADR-026) is explicitly included so SSRF audit events
     // are always emitted — they live outside the `rust_lib_app` namespace.
+    // `LOG_FORMAT=json` emits one JSON object per line (access log included, see
+    // `api::request_id`) for log shippers; anything else keeps the text format.
+    // RUST_LOG filters the console output only: the OpenTelemetry layer (feature
+    // `otel`) selects its own targets, see `infrastructure::telemetry`.
+    let json_logs = std::env::var("LOG_FORMAT").is_ok_and(|v| v.eq_ignore_ascii_case("json"));
+    let console_filter = || {
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| "rust_lib_app=debug,tower_http=debug,ssrf=warn,access=info".into())
+    };
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "rust_lib_app=debug,tower_http=debug,ssrf=warn".into()),
-        )
-        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(json_logs.then(|| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(std::io::stderr)
+                .with_filter(console_filter())
+        }))
+        .with((!json_logs).then(|| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(console_filter())
+        }))
+        .with(rust_lib_app::infrastructure::telemetry::layer())
         .init();
 
     // Load configuration
     dotenvy::dotenv().ok();
 
-    // Check for --profile CLI argument
-    let args: Vec<String> = std::env::args().collect();
-    if let Some(pos) = args.iter().position(|arg| arg == "--profile")
-        && let Some(val) = args.get(pos + 1)
-    {
+    let cli = Cli::parse();
+    if let Some(profile) = &cli.profile {
         // TODO: Audit that the environment access only happens in single-threaded code.
-        unsafe { std::env::set_var("PROFILE", val) };
+        unsafe { std::env::set_var("PROFILE", profile) };
+    }
+
+    if cli.openapi {
+        use utoipa::OpenApi;
+        match rust_lib_app::api_docs::ApiDoc::openapi().to_pretty_json() {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("Failed to serialize OpenAPI document: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Configuration error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if cli.print_config {
+        match &config.config_file {
+            Some(path) => println!("# read from {}", path.display()),
+            None => println!(
+                "# no config file ({})",
+                config::config_file_path().display()
+            ),
+        }
+        print!("{}", config.to_toml());
+        return;
+    }
+
+    let demo = cli.demo
+        || std::env::var("BIBLIOGENIUS_DEMO")
+            .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    if demo {
+        config.database_url = DEMO_DATABASE_URL.to_string();
     }
+    config.clone().set_runtime();
 
-    let config = config::Config::from_env();
+    let command = cli.command.unwrap_or(Command::Serve);
+    if demo && !matches!(command, Command::Serve) {
+        eprintln!("Error: --demo only applies to `serve`");
+        std::process::exit(1);
+    }
 
     // [MCP] Short-circuit before the database init: the helper is a transport shim
     // that proxies to the running app and never opens the database itself. Opening
     // the same SQLite file from a second process was the root of a whole class of
     // bugs (sandbox paths, CRR promotion, feature mismatch), so it no longer happens.
     #[cfg(feature = "mcp")]
-    {
-        if std::env::args().any(|arg| arg == "--mcp") {
-            tracing::info!("Starting in MCP Mode (Stdio)...");
-            api::mcp::start_server().await;
-            return;
+    if cli.mcp || matches!(command, Command::Mcp) {
+        tracing::info!("Starting in MCP Mode (Stdio)...");
+        api::mcp::start_server().await;
+        return;
+    }
+
+    let take_over_stale_lock = cli.take_over_stale_lock
+        || std::env::var("BIBLIOGENIUS_TAKE_OVER_STALE_LOCK")
+            .is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    let result = run(command, config, take_over_stale_lock, demo).await;
+
+    rust_lib_app::infrastructure::telemetry::shutdown();
+
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Run a database command while holding the instance lock. The lock is
+/// released before returning, so an error exit does not leave it stale.
+async fn run(
+    command: Command,
+    config: config::Config,
+    take_over_stale_lock: bool,
+    demo: bool,
+) -> Result<(), String> {
+    // In-memory databases have nothing to guard.
+    let _instance_lock =
+        match rust_lib_app::infrastructure::mcp_token::database_file_path(&config.database_url) {
+            Some(db_path) => Some(
+                InstanceLock::acquire(&db_path, take_over_stale_lock).map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+
+    let db = if demo {
+        open_demo_db().await?
+    } else {
+        open_db(&config).await?
+    };
+
+    match command {
+        Command::Serve => {
+            serve(config, db, demo).await;
+            Ok(())
+        }
+        Command::Import { file, dedup } => import_file(&db, &file, dedup).await,
+        Command::Export {
+            file,
+            include_private,
+        } => export_file(&db, &file, include_private).await,
+        Command::Backup {
+            output,
+            library_uuid,
+            passphrase_env,
+            cover_dir,
+        } => {
+            let output = output.unwrap_or_else(|| {
+                rust_lib_app::infrastructure::paths::backups_dir().join(format!(
+                    "bibliogenius-{}.bgbackup",
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                ))
+            });
+            let cover_dir =
+                cover_dir.unwrap_or_else(rust_lib_app::infrastructure::paths::covers_dir);
+            backup(&db, &output, &library_uuid, &passphrase_env, &cover_dir).await
         }
+        // `open_db` already ran them.
+        Command::Migrate => {
+            println!("Database at schema version {}", db::SCHEMA_VERSION);
+            Ok(())
+        }
+        Command::Seed => seed_demo(&db).await,
+        #[cfg(feature = "mcp")]
+        Command::Mcp => unreachable!("handled before the database init"),
     }
+}
 
-    // Initialize database. Account-sync builds open a single cr-sqlite connection
-    // with the replicated tables promoted to CRRs; default builds use a plain pool.
+/// Open the database and apply pending migrations. Account-sync builds open a
+/// single cr-sqlite connection with the replicated tables promoted to CRRs;
+/// default builds use a plain pool.
+async fn open_db(config: &config::Config) -> Result<DatabaseConnection, String> {
     #[cfg(feature = "account_sync")]
-    let db = db::init_db_account_sync(&config.database_url)
-        .await
-        .expect("Failed to initialize database");
+    let db = db::init_db_account_sync(&config.database_url).await;
     #[cfg(not(feature = "account_sync"))]
-    let db = db::init_db(&config.database_url)
+    let db = db::init_db(&config.database_url).await;
+
+    db.map_err(|e| format!("failed to initialize database: {e}"))
+}
+
+/// Open the in-memory database of `--demo` and apply the migrations. An
+/// in-memory SQLite database lives as long as a connection to it, so the pool
+/// is pinned to one connection that is never closed for idleness or age.
+async fn open_demo_db() -> Result<DatabaseConnection, String> {
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use std::str::FromStr;
+
+    let conn_err = |e: sqlx::Error| format!("failed to open the demo database: {e}");
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(SqliteConnectOptions::from_str(DEMO_DATABASE_URL).map_err(conn_err)?)
         .await
-        .expect("Failed to initialize database");
+        .map_err(conn_err)?;
+    let db = sea_orm::SqlxSqliteConnector::from_sqlx_sqlite_pool(pool);
+    db::run_migrations(&db)
+        .await
+        .map_err(|e| format!("failed to initialize database: {e}"))?;
+    Ok(db)
+}
+
+async fn import_file(
+    db: &DatabaseConnection,
+    file: &std::path::Path,
+    dedup: rust_lib_app::import::DedupPolicy,
+) -> Result<(), String> {
+    let content =
+        std::fs::read(file).map_err(|e| format!("cannot read {}: {e}", file.display()))?;
+
+    // A JSON export of a library is merged like `POST /api/import-upsert`.
+    if let Ok(backup) = serde_json::from_slice::<api::export::ImportBackupData>(&content)
+        && backup.books.is_some()
+    {
+        let result =
+            api::export::run_import_upsert(db, backup, api::export::SettingsMerge::Overwrite).await;
+        if !result.success {
+            return Err(result.message);
+        }
+        println!("{}", result.message);
+        return Ok(());
+    }
+
+    let books = rust_lib_app::import::parse_import_file(&content)?;
+    let summary = rust_lib_app::import::import_books(db, books, dedup, None).await;
+    println!(
+        "Imported {} book(s): {} created, {} merged, {} already there",
+        summary.imported, summary.created, summary.merged, summary.skipped
+    );
+    for error in &summary.errors {
+        eprintln!("  {error}");
+    }
+    if summary.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} book(s) could not be imported",
+            summary.errors.len()
+        ))
+    }
+}
 
-    // Check for seed flag
-    if std::env::var("SEED_DEMO").is_ok() {
+async fn export_file(
+    db: &DatabaseConnection,
+    file: &std::path::Path,
+    include_private: bool,
+) -> Result<(), String> {
+    let mut backup = api::export::collect_backup_data(db).await;
+    if !include_private {
+        backup
+            .exclude_private(db)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_vec_pretty(&backup).map_err(|e| e.to_string())?;
+    std::fs::write(file, json).map_err(|e| format!("cannot write {}: {e}", file.display()))?;
+    println!(
+        "Exported {} book(s) to {}",
+        backup.books.len(),
+        file.display()
+    );
+    Ok(())
+}
+
+async fn backup(
+    db: &DatabaseConnection,
+    output: &std::path::Path,
+    library_uuid: &str,
+    passphrase_env: &str,
+    cover_dir: &std::path::Path,
+) -> Result<(), String> {
+    let passphrase = zeroize::Zeroizing::new(
+        std::env::var(passphrase_env).map_err(|_| format!("{passphrase_env} is not set"))?,
+    );
+    let summary = api::backup::write_backup(
+        db,
+        output,
+        passphrase.as_bytes(),
+        api::backup::UnlockKind::Passphrase,
+        library_uuid,
+        None,
+        "{}",
+        cover_dir,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    println!(
+        "Wrote {} ({} bytes, {} book(s))",
+        summary.archive_path.display(),
+        summary.archive_size_bytes,
+        summary.manifest.counts.books
+    );
+    Ok(())
+}
+
+async fn seed_demo(db: &DatabaseConnection) -> Result<(), String> {
+    let report = seed::seed_demo_data(db)
+        .await
+        .map_err(|e| format!("failed to seed data: {e}"))?;
+    println!(
+        "Demo data seeded ({} {}: {} books created, {} already present)",
+        report.profile.as_str(),
+        report.locale.as_str(),
+        report.books_created,
+        report.books_skipped
+    );
+    Ok(())
+}
+
+/// Seed the demo accounts into `db` and the demo catalogue into a fresh set of
+/// in-memory repositories, which `--demo` serves it from.
+async fn seed_demo_memory(db: &DatabaseConnection) -> Result<InMemoryRepositories, String> {
+    seed::seed_demo_accounts(db)
+        .await
+        .map_err(|e| format!("failed to seed demo accounts: {e}"))?;
+    let repos = InMemoryRepositories::new();
+    let report = seed::seed_memory_profile(&repos, SeedProfile::from_env(), SeedLocale::from_env())
+        .await
+        .map_err(|e| format!("failed to seed data: {e}"))?;
+    println!(
+        "Demo data seeded in memory ({} {}: {} books created)",
+        report.profile.as_str(),
+        report.locale.as_str(),
+        report.books_created
+    );
+    Ok(repos)
+}
+
+/// Run the HTTP server and its background workers until a shutdown signal.
+async fn serve(config: config::Config, db: DatabaseConnection, demo: bool) {
+    // `SEED_DEMO` seeds on startup, like the `seed` command. A demo server
+    // always starts from the demo catalogue, held by in-memory repositories.
+    let demo_repos = if demo {
         tracing::info!("Seeding demo data...");
-        match seed::seed_demo_data(&db).await {
+        match seed_demo_memory(&db).await {
+            Ok(repos) => Some(repos),
             Err(e) => {
-                tracing::error!("Failed to seed data: {}", e);
+                tracing::error!("{}", e);
+                None
             }
-            _ => {
-                tracing::info!("Demo data seeded successfully.");
+        }
+    } else {
+        if std::env::var("SEED_DEMO").is_ok() {
+            tracing::info!("Seeding demo data...");
+            if let Err(e) = seed_demo(&db).await {
+                tracing::error!("{}", e);
             }
         }
+        None
+    };
+
+    // Triggered by SIGINT/SIGTERM; everything that must stop at a safe point
+    // (HTTP server, operation processor) watches it.
+    let shutdown = Shutdown::new();
+    {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown::wait_for_signal().await;
+            shutdown.trigger();
+        });
     }
 
     // [P2P] Start Operation Processor
     let processor_db = db.clone();
-    tokio::spawn(async move {
+    let processor_shutdown = shutdown.clone();
+    let processor = tokio::spawn(async move {
         // We use the fully qualified path to ensure we hit the right module
-        rust_lib_app::sync::processor::run_processor(processor_db).await;
+        rust_lib_app::sync::processor::run_processor_until(processor_db, processor_shutdown).await;
     });
 
     // [Delta sync] Hybrid retention pruner for operation_log (ADR-028 D5).
     rust_lib_app::services::oplog_pruner::spawn(db.clone());
 
+    // The MCP token and the pre-reset snapshot live next to the database
+    // file; a demo names none, so neither touches the on-disk library.
+    rust_lib_app::infrastructure::mcp_token::set_database_url(&config.database_url);
+
     // Build API router with explicit AppState (needed for relay poller)
-    let state = rust_lib_app::infrastructure::AppState::new(db);
+    let state = match demo_repos {
+        Some(repos) => rust_lib_app::infrastructure::AppState::in_memory(db, repos),
+        None => rust_lib_app::infrastructure::AppState::new(db),
+    }
+    .with_database_url(config.database_url.clone());
     let api_router = api::api_router_with_state(state.clone());
 
+    // Release P2P requests left unclaimed on the hold shelf.
+    rust_lib_app::services::hold_expiry::spawn(state.clone());
+
+    // Accrue fines on overdue loans (loan_settings fee policy).
+    rust_lib_app::services::fines::spawn(state.db().clone());
+
+    // Poll lenders for outgoing requests whose status callback never arrived.
+    rust_lib_app::services::outgoing_request_sync::spawn(state.clone());
+
+    // Expire stale P2P requests and purge resolved ones (loan_settings policy).
+    rust_lib_app::services::request_cleanup::spawn(state.clone());
+
+    // Retry P2P messages queued while their peer was unreachable.
+    rust_lib_app::services::peer_outbox::spawn(state.clone());
+
+    // Exchange operation logs with this user's other paired installs.
+    rust_lib_app::services::device_sync::spawn(state.clone());
+
+    // Refresh hub directory follows in the background (cached for /api/peers).
+    rust_lib_app::services::hub_sync::spawn(state.clone());
+
+    // Keep subscribed peer catalogues fresh.
+    rust_lib_app::services::peer_subscriptions::spawn(state.clone());
+
+    // Run saved searches and notify their new matches.
+    rust_lib_app::services::saved_searches::spawn(state.db().clone());
+
+    // Weekly integrity check, orphan sweep and VACUUM.
+    rust_lib_app::services::db_maintenance::spawn(state.db().clone());
+
+    // Peer nudges following library configuration changes.
+    rust_lib_app::services::config_history::spawn(state.clone());
+
+    // Re-announce over mDNS after a rename or a change of network addresses.
+    rust_lib_app::services::mdns::spawn_announcer(state.db().clone());
+
+    // Certificates pinned for self-signed HTTPS peers, read on every peer request.
+    if let Err(e) = rust_lib_app::services::cert_pins::load(state.db()).await {
+        tracing::warn!("Could not load pinned peer certificates: {}", e);
+    }
+
+    // Daily quotas of the external metadata providers, and today's counts.
+    if let Err(e) = rust_lib_app::services::api_quota::load(state.db()).await {
+        tracing::warn!("Could not load metadata provider quotas: {}", e);
+    }
+    rust_lib_app::services::api_quota::spawn(state.db().clone());
+
+    // Network mode (full, LAN-only, offline), read by every outbound HTTP client.
+    if let Err(e) = rust_lib_app::services::network_mode::load(state.db()).await {
+        tracing::warn!("Could not load the network mode: {}", e);
+    }
+
+    // SOCKS5 proxy (e.g. Tor) for peer requests and the onion address to publish.
+    if let Err(e) = rust_lib_app::services::tor::load(state.db()).await {
+        tracing::warn!("Could not load the Tor settings: {}", e);
+    }
+
     // Spawn relay poller (checks for incoming relay messages in the background)
     {
         let poller_state = state.clone();
@@ -187,7 +587,11 @@ async fn main() {
         // Invite landing page at root level (not under /api)
         // Serves HTML redirect to bibliogenius:// custom scheme
         .route("/invite", get(api::invite_page::invite_page))
-        .nest("/api", api_router)
+        .nest("/api", api_router);
+    // Embedded admin page, loopback only
+    #[cfg(feature = "admin-ui")]
+    let app = app.route("/admin", get(api::admin_ui::admin_page));
+    let app = app
         // CORS
         .layer(
             CorsLayer::new()
@@ -196,8 +600,13 @@ async fn main() {
                 .allow_headers(Any),
         );
 
-    // Find available port
-    let port = find_available_port(config.port).expect("Failed to find available port");
+    // Bind once and keep the listener: probing then re-binding races with other
+    // instances starting at the same time.
+    let listener = server::bind_available_port(config.port, Ipv4Addr::UNSPECIFIED.into())
+        .await
+        .expect("Failed to find available port");
+    let addr = listener.local_addr().expect("Failed to read bound address");
+    let port = addr.port();
 
     if port != config.port {
         tracing::warn!(
@@ -207,22 +616,30 @@ async fn main() {
         );
     }
 
-    // Write port to file for Flutter app
-    if let Err(e) = write_port_file(port, &config.profile) {
-        tracing::error!("Failed to write port file: {}", e);
+    // Publish the port for the Flutter app. Held until exit, then removed. A
+    // demo server is not the profile's backend: the app must not attach to it.
+    let port_file = if demo {
+        None
     } else {
-        tracing::info!(
-            "Port file written: {:?}",
-            get_port_file_path(&config.profile)
-        );
-    }
+        match PortFile::acquire(port_file::port_file_path(&config.profile), port) {
+            Ok(port_file) => {
+                tracing::info!("Port file written: {:?}", port_file.path());
+                Some(port_file)
+            }
+            Err(e) => {
+                tracing::error!("Failed to write port file: {}", e);
+                None
+            }
+        }
+    };
 
     // Initialize mDNS for local network discovery (if enabled)
     let mdns_enabled = std::env::var("MDNS_ENABLED")
         .map(|v| v != "false" && v != "0")
         .unwrap_or(false); // Disabled by default (opt-in)
 
-    if mdns_enabled {
+    // A demo library is not announced on the network.
+    if mdns_enabled && !demo {
         let library_name = rust_lib_app::models::library_config::Entity::find_by_id(1)
             .one(state.db())
             .await
@@ -231,7 +648,9 @@ async fn main() {
             .map(|c| c.name)
             .unwrap_or_else(|| "BiblioGenius Library".to_string());
 
-        match rust_lib_app::services::init_mdns(&library_name, port, None, None, None) {
+        let library_uuid = state.identity_service.library_uuid().map(str::to_string);
+        match rust_lib_app::services::init_mdns(&library_name, port, None, library_uuid, None, None)
+        {
             Ok(()) => {
                 tracing::info!("📡 mDNS service started - library discoverable on local network");
             }
@@ -247,66 +666,62 @@ async fn main() {
     }
 
     // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
     tracing::info!("BiblioGenius server listening on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .expect("Failed to bind to address");
-
     let serve = axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     );
 
-    // On account-sync builds the pool is a single cr-sqlite connection that must run
-    // `crsql_finalize()` before it is closed. Serve until a shutdown signal, then
-    // finalize.
-    #[cfg(feature = "account_sync")]
-    {
+    // Graceful: once triggered, stop accepting and let in-flight requests (an
+    // import mid-transaction) complete.
+    let serve_shutdown = shutdown.clone();
+    let mut server = tokio::spawn(async move {
         serve
-            .with_graceful_shutdown(shutdown_signal())
+            .with_graceful_shutdown(async move { serve_shutdown.triggered().await })
             .await
-            .expect("Failed to start server");
-        if let Err(e) = rust_lib_app::infrastructure::crsqlite_crr::finalize(state.db()).await {
-            tracing::warn!("crsql_finalize on shutdown failed: {}", e);
-        }
-    }
-
-    #[cfg(not(feature = "account_sync"))]
-    serve.await.expect("Failed to start server");
-}
+    });
 
-/// Resolve when the process receives a shutdown signal: Ctrl-C (SIGINT) or, on
-/// Unix, SIGTERM (the signal `docker stop` / systemd send). Used to drive the
-/// account-sync graceful shutdown so `crsql_finalize` runs on the normal stop path,
-/// not only on an interactive Ctrl-C.
-#[cfg(feature = "account_sync")]
-async fn shutdown_signal() {
-    let ctrl_c = async {
-        let _ = tokio::signal::ctrl_c().await;
+    let server_stopped = tokio::select! {
+        _ = shutdown.triggered() => false,
+        result = &mut server => {
+            // The server only returns on its own when it failed.
+            tracing::error!("Server stopped unexpectedly: {:?}", result);
+            shutdown.trigger();
+            true
+        }
     };
 
-    #[cfg(unix)]
-    let terminate = async {
-        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
-            Ok(mut sig) => {
-                sig.recv().await;
-            }
-            // If the handler cannot be installed, never resolve on this arm: Ctrl-C
-            // still triggers shutdown.
-            Err(e) => {
-                tracing::warn!("Failed to install SIGTERM handler: {}", e);
-                std::future::pending::<()>().await;
-            }
+    // Stop announcing before the port goes away.
+    rust_lib_app::services::stop_mdns();
+
+    let drained = tokio::time::timeout(shutdown::DRAIN_TIMEOUT, async {
+        if !server_stopped {
+            let _ = server.await;
         }
-    };
+        let _ = processor.await;
+    })
+    .await;
+    if drained.is_err() {
+        tracing::warn!(
+            "Shutdown: in-flight work did not finish within {:?}, exiting anyway",
+            shutdown::DRAIN_TIMEOUT
+        );
+    }
 
-    #[cfg(not(unix))]
-    let terminate = std::future::pending::<()>();
+    drop(port_file);
+
+    // On account-sync builds the pool is a single cr-sqlite connection that must run
+    // `crsql_finalize()` before it is closed.
+    #[cfg(feature = "account_sync")]
+    if let Err(e) = rust_lib_app::infrastructure::crsqlite_crr::finalize(state.db()).await {
+        tracing::warn!("crsql_finalize on shutdown failed: {}", e);
+    }
 
-    tokio::select! {
-        _ = ctrl_c => {}
-        _ = terminate => {}
+    // Closing waits for connections still checked out by background tasks, so
+    // none is cut mid-transaction.
+    if let Err(e) = state.db().clone().close().await {
+        tracing::warn!("Failed to close database on shutdown: {}", e);
     }
+    tracing::info!("BiblioGenius server stopped");
 }
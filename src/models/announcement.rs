@@ -0,0 +1,41 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An announcement published to peers or received from one (migration 117).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "announcements")]
+#[schema(as = Announcement)]
+pub struct Model {
+    /// Chosen by the publisher, shared by every copy
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// Sender, NULL for announcements this library published
+    pub peer_id: Option<i32>,
+    /// `new_arrivals`, `opening_hours`, `book_sale` or `other`
+    pub kind: String,
+    pub title: String,
+    pub body: Option<String>,
+    /// After this date the announcement is no longer current
+    pub expires_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::peer::Entity",
+        from = "Column::PeerId",
+        to = "super::peer::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Peer,
+}
+
+impl Related<super::peer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Peer.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
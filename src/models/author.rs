@@ -12,6 +12,18 @@ pub struct Model {
     pub name: String,
     pub created_at: String,
     pub updated_at: String,
+    /// BnF authority record, `cb` + digits and check character (migration 110).
+    #[serde(default)]
+    pub bnf_id: Option<String>,
+    /// VIAF cluster number.
+    #[serde(default)]
+    pub viaf_id: Option<String>,
+    /// Wikidata item, `Q` + digits.
+    #[serde(default)]
+    pub wikidata_id: Option<String>,
+    /// Open Library author key, `OL` + digits + `A`.
+    #[serde(default)]
+    pub openlibrary_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
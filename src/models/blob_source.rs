@@ -0,0 +1,29 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A cover thumbnail or digital file this library offers to peers, by
+/// content hash (migration 113).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "blob_sources")]
+pub struct Model {
+    /// `cover` or `file`
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub kind: String,
+    /// The book id for a cover, the book file id for a file
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub source_id: String,
+    pub book_id: String,
+    pub sha256: String,
+    pub size: i64,
+    /// JSON array of the SHA-256 of each chunk
+    pub chunk_hashes: String,
+    /// Where the bytes are read from
+    pub path: String,
+    /// What the blob was computed from; a different stamp means stale
+    pub stamp: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
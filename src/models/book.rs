@@ -2,6 +2,7 @@ use sea_orm::entity::prelude::*;
 use sea_orm::{ConnectionTrait, ModelTrait, NotSet, Set};
 use serde::{Deserialize, Serialize};
 
+use super::book_relation::RelatedBook;
 use crate::utils::cover_url::{self, ResolveScope};
 
 /// Backward-compatible alias so existing callers and tests that name the
@@ -26,6 +27,51 @@ pub type CoverRewriteError = cover_url::CoverResolveError;
 /// column must tolerate values outside it rather than assume completeness.
 pub const READING_STATUSES: [&str; 5] = ["to_read", "reading", "read", "wanting", "abandoned"];
 
+/// The values `books.physical_format` may hold (binding / carrier of the
+/// edition). Provider data is mapped onto this vocabulary by
+/// [`normalize_physical_format`]; anything it cannot place is dropped rather
+/// than stored verbatim, so the column stays filterable.
+pub const PHYSICAL_FORMATS: [&str; 5] = ["paperback", "hardcover", "ebook", "audiobook", "other"];
+
+/// Map a free-text format from a metadata provider ("Mass Market Paperback",
+/// "relié", "Audio CD", ...) onto [`PHYSICAL_FORMATS`]. `None` when the input
+/// is empty or unrecognized.
+pub fn normalize_physical_format(raw: &str) -> Option<&'static str> {
+    let s = raw.trim().to_lowercase();
+    if s.is_empty() {
+        return None;
+    }
+    if let Some(known) = PHYSICAL_FORMATS.iter().find(|f| **f == s) {
+        return Some(known);
+    }
+    if s.contains("audio") || s.contains("cd") || s.contains("mp3") || s.contains("livre lu") {
+        Some("audiobook")
+    } else if s.contains("ebook")
+        || s.contains("e-book")
+        || s.contains("kindle")
+        || s.contains("epub")
+        || s.contains("electronic")
+        || s.contains("numérique")
+    {
+        Some("ebook")
+    } else if s.contains("hardcover")
+        || s.contains("hardback")
+        || s.contains("hard cover")
+        || s.contains("relié")
+        || s.contains("cartonné")
+    {
+        Some("hardcover")
+    } else if s.contains("paperback")
+        || s.contains("softcover")
+        || s.contains("broché")
+        || s.contains("poche")
+    {
+        Some("paperback")
+    } else {
+        None
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "books")]
 pub struct Model {
@@ -79,6 +125,21 @@ pub struct Model {
     pub private: bool,
     pub page_count: Option<i32>,
     pub loan_duration_days: Option<i32>,
+    /// Edition statement as printed ("2e éd. revue et augmentée", "Folio").
+    pub edition: Option<String>,
+    /// Binding / carrier, one of [`PHYSICAL_FORMATS`].
+    pub physical_format: Option<String>,
+    /// Free-text dimensions as catalogued ("18 cm", "21 x 14 cm").
+    pub dimensions: Option<String>,
+    /// Archived books stay in the database but leave the default lists and
+    /// peer catalogues (see `api::batch::batch_archive`).
+    #[sea_orm(default_value = "false")]
+    #[serde(default)]
+    pub archived: bool,
+    /// Free digitized edition of this book (a Gallica ARK URL), as linked by
+    /// a metadata provider. See `services::digital_editions`.
+    #[serde(default)]
+    pub digital_source_url: Option<String>,
     // The device-local hub-cover-upload retry flag is NOT a column of `books`:
     // it lives in the sibling non-CRR `book_local` table so it never replicates
     // across account-sync devices (ADR-044). Read it via
@@ -126,7 +187,7 @@ impl ActiveModelBehavior for ActiveModel {
 }
 
 // DTO for API responses
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Book {
     pub id: Option<String>,
     pub title: String,
@@ -179,6 +240,17 @@ pub struct Book {
     pub page_count: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub loan_duration_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub edition: Option<String>,
+    /// One of [`PHYSICAL_FORMATS`]; checked on write by `api::validation`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub physical_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dimensions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub digital_source_url: Option<String>,
     /// When this book was added to its owner's library (ISO 8601, maps to
     /// `books.created_at`). Broadcast to peers so every viewer sees the
     /// same "new" badge regardless of when they first discovered the book.
@@ -213,6 +285,10 @@ pub struct Book {
     /// See `is_borrowed` for the axis and the `None` semantics.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub is_lent: Option<bool>,
+    /// Box set, bound-with, adaptation and translation links to other books
+    /// (see `services::book_relations`). Only the book detail fills it in.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub related: Option<Vec<RelatedBook>>,
 }
 
 impl From<Model> for Book {
@@ -269,6 +345,11 @@ impl From<Model> for Book {
             private: Some(model.private),
             page_count: model.page_count,
             loan_duration_days: model.loan_duration_days,
+            edition: model.edition,
+            physical_format: model.physical_format,
+            dimensions: model.dimensions,
+            archived: Some(model.archived),
+            digital_source_url: model.digital_source_url,
             added_at: Some(model.created_at),
             updated_at: Some(model.updated_at),
             // Device-local; not on the model. Owner-facing read paths populate
@@ -279,6 +360,7 @@ impl From<Model> for Book {
             // read paths populate them (see `book_service::list_books`).
             is_borrowed: None,
             is_lent: None,
+            related: None,
         }
     }
 }
@@ -451,6 +533,7 @@ impl Book {
         self.user_rating = None;
         self.price = None;
         self.private = None;
+        self.archived = None;
         // Internal sync state: peers have no business knowing our retry backlog.
         self.hub_cover_upload_failed_at = None;
         // Possession state is personal: a visitor learns what we hold, not that
@@ -539,6 +622,74 @@ impl Book {
         hex::encode(hasher.finalize())
     }
 
+    /// Ids of the books carrying a private tag, or a tag nested under one
+    /// (see `tag::restricting_tags`), whether linked through `book_tags` or
+    /// named in `subjects`. Together with `books.private` this is what stays
+    /// out of peer catalogues, federated search, public mode and exports.
+    pub async fn tag_restricted_ids<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<std::collections::HashSet<String>, DbErr> {
+        use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QuerySelect};
+
+        let tags = super::tag::restricting_tags(db).await?;
+        if tags.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let mut ids: std::collections::HashSet<String> = super::book_tags::Entity::find()
+            .filter(super::book_tags::Column::TagId.is_in(tags.iter().map(|t| t.id.clone())))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|bt| bt.book_id)
+            .collect();
+
+        // `subjects` is a JSON array of names; match whole elements only, by
+        // name or by full path ("Parent > Child").
+        let mut by_subject = Condition::any();
+        for t in &tags {
+            by_subject = by_subject.add(Column::Subjects.contains(format!("\"{}\"", t.name)));
+            if !t.path.is_empty() {
+                by_subject = by_subject
+                    .add(Column::Subjects.contains(format!("\"{} > {}\"", t.path, t.name)));
+            }
+        }
+        let tagged: Vec<String> = super::book::Entity::find()
+            .select_only()
+            .column(Column::Id)
+            .filter(by_subject)
+            .into_tuple()
+            .all(db)
+            .await?;
+        ids.extend(tagged);
+
+        Ok(ids)
+    }
+
+    /// Condition selecting the books that may be shown to anyone but the
+    /// owner: not private, not archived, and not carrying a private tag.
+    ///
+    /// On DB error the condition matches nothing, which is safe: a peer sees
+    /// an empty catalogue rather than books the owner meant to keep.
+    pub async fn shared_condition<C: ConnectionTrait>(db: &C) -> sea_orm::Condition {
+        use sea_orm::sea_query::Expr;
+        use sea_orm::{ColumnTrait, Condition};
+
+        match Self::tag_restricted_ids(db).await {
+            Ok(hidden) if hidden.is_empty() => Condition::all()
+                .add(Column::Private.eq(false))
+                .add(Column::Archived.eq(false)),
+            Ok(hidden) => Condition::all()
+                .add(Column::Private.eq(false))
+                .add(Column::Archived.eq(false))
+                .add(Column::Id.is_not_in(hidden)),
+            Err(e) => {
+                tracing::error!("Failed to load private tags: {e}");
+                Condition::all().add(Expr::val(1).eq(0))
+            }
+        }
+    }
+
     /// Builds the hub cover URL prefix (`{hub_url}/api/directory/{node_id}/covers`)
     /// from the current hub configuration.  Returns `None` when the hub is not
     /// configured or the node is not registered.
@@ -646,6 +797,11 @@ impl From<Book> for ActiveModel {
             private: book.private.map_or(NotSet, Set),
             page_count: book.page_count.map_or(NotSet, |p| Set(Some(p))),
             loan_duration_days: book.loan_duration_days.map_or(NotSet, |d| Set(Some(d))),
+            edition: book.edition.map_or(NotSet, |e| Set(Some(e))),
+            physical_format: book.physical_format.map_or(NotSet, |f| Set(Some(f))),
+            dimensions: book.dimensions.map_or(NotSet, |d| Set(Some(d))),
+            archived: book.archived.map_or(NotSet, Set),
+            digital_source_url: book.digital_source_url.map_or(NotSet, |u| Set(Some(u))),
         }
     }
 }
@@ -900,4 +1056,19 @@ mod tests {
              get rewritten to hub URLs (or stripped to None)"
         );
     }
+
+    #[test]
+    fn normalize_physical_format_maps_provider_vocabulary() {
+        assert_eq!(normalize_physical_format("Paperback"), Some("paperback"));
+        assert_eq!(
+            normalize_physical_format("Mass Market Paperback"),
+            Some("paperback")
+        );
+        assert_eq!(normalize_physical_format("Relié"), Some("hardcover"));
+        assert_eq!(normalize_physical_format("Audio CD"), Some("audiobook"));
+        assert_eq!(normalize_physical_format("Kindle Edition"), Some("ebook"));
+        assert_eq!(normalize_physical_format("other"), Some("other"));
+        assert_eq!(normalize_physical_format("Calendar"), None);
+        assert_eq!(normalize_physical_format("  "), None);
+    }
 }
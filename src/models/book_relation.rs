@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Relation types, each with how it reads from the related book's side.
+/// `bound_with` is symmetric.
+pub const RELATIONS: &[(&str, &str)] = &[
+    ("contained_in", "contains"),
+    ("bound_with", "bound_with"),
+    ("adaptation_of", "adapted_as"),
+    ("translated_from", "translated_as"),
+];
+
+/// A typed link from one book to another (migration 121): `book_id` is
+/// `relation` of `related_book_id`, e.g. a volume `contained_in` its box set.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "book_relations")]
+#[schema(as = BookRelation)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub book_id: String,
+    pub related_book_id: String,
+    /// One of [`RELATIONS`]
+    pub relation: String,
+    pub created_at: String,
+}
+
+/// A related book as seen from one side of a relation, on the book detail
+/// payload.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RelatedBook {
+    /// The `book_relations` row, to remove it
+    pub relation_id: String,
+    /// How this book relates to the other one: a relation type or its
+    /// reverse (`contains`, `adapted_as`, `translated_as`)
+    pub relation: String,
+    pub book_id: String,
+    pub title: String,
+    pub publication_year: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
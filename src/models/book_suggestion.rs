@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Waiting for the owner's decision.
+pub const STATUS_PENDING: &str = "pending";
+/// Accepted: the book is on the wishlist (`book_id`).
+pub const STATUS_ACCEPTED: &str = "accepted";
+pub const STATUS_REJECTED: &str = "rejected";
+
+/// A book a patron suggested the library acquire (migration 109).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "book_suggestions")]
+#[schema(as = BookSuggestion)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// The patron who sent it; `None` once the contact is deleted.
+    pub contact_id: Option<String>,
+    pub title: String,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    /// The patron's note to the owner.
+    pub note: Option<String>,
+    /// `pending`, `accepted` or `rejected`
+    pub status: String,
+    /// The wishlist book an accepted suggestion became.
+    pub book_id: Option<String>,
+    pub decided_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
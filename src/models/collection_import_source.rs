@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The file a collection was last imported from (migration 112).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "collection_import_sources")]
+#[schema(as = CollectionImportSource)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub collection_id: String,
+    /// Detected format, e.g. `goodreads` or `isbn_list`
+    pub format: String,
+    /// SHA-256 of the column layout (the header line of a CSV export)
+    pub layout: String,
+    /// SHA-256 of the whole file, to spot a re-upload of the same file
+    pub file_sha256: String,
+    /// Whether its books were imported as owned, with a copy
+    pub owned: bool,
+    pub row_count: i32,
+    pub imported_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
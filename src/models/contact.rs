@@ -2,6 +2,10 @@ use sea_orm::entity::prelude::*;
 use sea_orm::{ConnectionTrait, Set};
 use serde::{Deserialize, Serialize};
 
+/// Type of the lightweight contacts made by a quick loan: a name typed at
+/// lending time, to be promoted to a full contact later.
+pub const QUICK_CONTACT_TYPE: &str = "Quick";
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "contacts")]
 pub struct Model {
@@ -27,6 +31,19 @@ pub struct Model {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Membership of an association library (see `services::membership`);
+    /// all `None` for a contact who is not a member.
+    #[serde(default)]
+    pub member_number: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub member_since: Option<String>,
+    /// `YYYY-MM-DD`, last day of the membership; a lapsed member cannot borrow.
+    #[serde(default)]
+    pub membership_expires_at: Option<String>,
+    /// Fee paid for the current membership period
+    #[serde(default)]
+    pub membership_fee_paid: Option<f64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
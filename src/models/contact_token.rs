@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A contact's credential for the self-service portal (migration 103). A
+/// contact has at most one; issuing a new one replaces it.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "contact_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub contact_id: String,
+    /// Hex SHA-256 of the token; the token itself is never stored.
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
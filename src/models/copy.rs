@@ -17,10 +17,14 @@ pub struct Model {
     /// Valid values:
     /// - `available`: On shelf, can be loaned
     /// - `loaned`: Currently lent to someone (has active Loan)
+    /// - `reserved`: Held for a P2P requester awaiting pickup (`p2p_requests.held_copy_id`)
     /// - `borrowed`: Borrowed from another library (P2P)
+    /// - `returning`: Borrowed copy given back, archived until the lender confirms
+    /// - `in_transit`: Being moved to another library (`copy_transfers`)
     /// - `lost`: Copy is lost
     /// - `wanted`: Wishlist - don't own yet
     /// - `sold`: Already sold (bookseller module)
+    /// - `withdrawn`: Deaccessioned after weeding (`services::weeding`)
     pub status: String,
     pub is_temporary: bool,
     pub created_at: String,
@@ -56,6 +60,22 @@ pub struct Model {
     /// copied here at borrow time so the return notification survives on a device
     /// that never held the outgoing request (ADR-049). NULL for non-peer copies.
     pub lender_request_id: Option<String>,
+    /// Lending terms as JSON (`domain::LendingTerms`, migration 093): set by
+    /// the owner on their own copies, or copied from the lender's
+    /// confirmation onto a borrowed copy. NULL when no terms were defined.
+    pub lending_terms: Option<String>,
+    /// Why the copy is flagged for weeding (`services::weeding::WeedingReason`,
+    /// migration 108). NULL when it is not.
+    #[serde(default)]
+    pub weeding_reason: Option<String>,
+    #[serde(default)]
+    pub weeding_note: Option<String>,
+    #[serde(default)]
+    pub weeding_flagged_at: Option<String>,
+    /// When the copy was deaccessioned (`status = 'withdrawn'`). The row and
+    /// its loans are kept for the history.
+    #[serde(default)]
+    pub withdrawn_at: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{ConnectionTrait, Set};
+use serde::{Deserialize, Serialize};
+
+/// A move of one copy from a library to another (migration 098).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "copy_transfers")]
+#[schema(as = CopyTransfer)]
+pub struct Model {
+    /// UUID v7, minted by `before_save` when not provided.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub copy_id: String,
+    pub from_library_id: i32,
+    pub to_library_id: i32,
+    /// `requested` -> `in_transit` -> `completed`, or `cancelled` from
+    /// either of the first two. While `in_transit` the copy has the status
+    /// `in_transit` and still belongs to `from_library_id`.
+    pub status: String,
+    pub notes: Option<String>,
+    pub requested_at: String,
+    pub shipped_at: Option<String>,
+    pub received_at: Option<String>,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::copy::Entity",
+        from = "Column::CopyId",
+        to = "super::copy::Column::Id"
+    )]
+    Copy,
+}
+
+impl Related<super::copy::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Copy.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert && self.id.is_not_set() {
+            self.id = Set(crate::utils::uuid_gen::new_uuid_v7());
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Kind of a fine entry: accrued on an overdue loan, one per loan.
+pub const KIND_FINE: &str = "fine";
+/// Kind of a payment entry.
+pub const KIND_PAYMENT: &str = "payment";
+
+/// An entry of a contact's fee ledger (migration 106). Amounts are positive
+/// either way; a contact owes the sum of their fines less their payments.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "fee_ledger")]
+#[schema(as = FeeLedgerEntry)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub contact_id: String,
+    /// The overdue loan behind a fine.
+    pub loan_id: Option<String>,
+    /// `fine` or `payment`
+    pub kind: String,
+    pub amount: f64,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The answer given to a request sent with an `Idempotency-Key` header
+/// (migration 124), replayed when the same request comes again.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "idempotency_keys")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key: String,
+    /// `POST /api/peers/request`: a key only covers the endpoint it was sent to
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub scope: String,
+    /// SHA-256 of the request body, hex
+    pub request_hash: String,
+    /// NULL while the first request is still being handled
+    pub status: Option<i32>,
+    pub content_type: Option<String>,
+    /// NULL when the answer was too large to keep (the status is replayed alone)
+    pub body: Option<Vec<u8>>,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
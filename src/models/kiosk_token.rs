@@ -0,0 +1,22 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A kiosk credential for a shared lending-desk tablet (migration 101).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "kiosk_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// Label chosen by the owner, e.g. "Front desk tablet".
+    pub name: String,
+    /// Hex SHA-256 of the token; the token itself is never stored.
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -13,6 +13,10 @@ pub struct Model {
     pub longitude: Option<f64>,
     pub share_location: Option<bool>,
     pub show_borrowed_books: Option<bool>,
+    /// ISO 4217 code of prices, sales and fees; EUR when unset
+    pub currency: Option<String>,
+    /// IANA time zone ("Europe/Zurich") dates are read in; the host's when unset
+    pub timezone: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -23,7 +27,7 @@ pub enum Relation {}
 impl ActiveModelBehavior for ActiveModel {}
 
 // DTO for API
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct LibraryConfig {
     pub name: String,
     pub description: Option<String>,
@@ -32,6 +36,13 @@ pub struct LibraryConfig {
     pub longitude: Option<f64>,
     pub share_location: bool,
     pub show_borrowed_books: bool,
+    /// ISO 4217 code ("CHF"); left unchanged when omitted on update
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// IANA time zone ("Europe/Zurich"); left unchanged when omitted on
+    /// update, `null` in responses when the host's zone is used
+    #[serde(default)]
+    pub timezone: Option<String>,
 }
 
 impl From<Model> for LibraryConfig {
@@ -45,6 +56,14 @@ impl From<Model> for LibraryConfig {
             longitude: model.longitude,
             share_location: model.share_location.unwrap_or(false),
             show_borrowed_books: model.show_borrowed_books.unwrap_or(false),
+            currency: Some(
+                crate::utils::money::or_default(model.currency.as_deref())
+                    .code
+                    .to_string(),
+            ),
+            timezone: crate::utils::time_zone::zone_or_host(model.timezone.as_deref())
+                .name()
+                .map(str::to_string),
         }
     }
 }
@@ -16,6 +16,19 @@ pub struct Model {
     pub relay_write_token: Option<String>,
     pub last_synced: Option<String>,
     pub created_at: String,
+    /// The paired install's `library_uuid` (migration 100). NULL on rows
+    /// predating device sync.
+    pub device_uuid: Option<String>,
+    /// Base URL the paired install serves its API on.
+    pub url: Option<String>,
+    /// Secret shared by both installs at pairing; each presents it to the
+    /// other. Never leaves this table except in sync requests to `url`.
+    #[serde(skip_serializing)]
+    pub sync_secret: Option<String>,
+    /// Last `operation_log.id` of the paired install we imported.
+    pub pull_cursor: i32,
+    /// Last local `operation_log.id` the paired install acknowledged.
+    pub push_cursor: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -19,6 +19,10 @@ pub struct Model {
     pub notes: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Snapshot of the copy's lending terms (JSON, `domain::LendingTerms`)
+    /// taken when the loan was created (migration 093). NULL when the copy
+    /// had none.
+    pub lending_terms: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -66,7 +70,7 @@ impl ActiveModelBehavior for ActiveModel {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct LoanDto {
     pub id: Option<String>,
     pub copy_id: String,
@@ -1,31 +1,58 @@
+pub mod announcement;
 pub mod author;
+pub mod blob_source;
 pub mod book;
 pub mod book_authors;
+pub mod book_relation;
+pub mod book_suggestion;
 pub mod book_tags;
 pub mod collection;
 pub mod collection_book;
+pub mod collection_import_row;
+pub mod collection_import_source;
 pub mod contact;
+pub mod contact_token;
 pub mod copy;
+pub mod copy_transfer;
+pub mod fee_ledger;
 pub mod gamification_achievements;
 pub mod gamification_config;
 pub mod gamification_progress;
 pub mod gamification_streaks;
+pub mod idempotency_key;
 pub mod installation_profile;
+pub mod kiosk_token;
 pub mod library;
 pub mod library_config;
 pub mod linked_device;
 pub mod loan;
 pub mod notification;
 pub mod operation_log;
+pub mod p2p_outbox;
 pub mod p2p_outgoing_request;
 pub mod p2p_request;
 pub mod peer;
+pub mod peer_blob;
 pub mod peer_book;
+pub mod peer_certificate;
 pub mod peer_gamification_stats;
+pub mod peer_subscription;
+pub mod periodical;
+pub mod periodical_issue;
+pub mod purchase_order;
+pub mod purchase_order_line;
+pub mod reading_queue;
 pub mod relay_config;
 pub mod sale; // Nouveau module pour les ventes (profil Libraire)
+pub mod saved_search;
+pub mod saved_search_match;
+pub mod subject_heading;
 pub mod tag;
+pub mod tor_config;
 pub mod user;
+pub mod work;
+pub mod work_book;
+pub mod work_identifier;
 
 pub use book::Book;
 pub use installation_profile::ProfileConfig;
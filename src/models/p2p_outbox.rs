@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A P2P message waiting to be delivered to a peer (migration 099).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "p2p_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub peer_id: i32,
+    /// E2EE message type (`loan_request`, `loan_confirmation`, `status_update`).
+    pub message_type: String,
+    /// E2EE payload, JSON.
+    pub payload: String,
+    /// Plaintext route for peers without an E2EE channel: HTTP method, path
+    /// under the peer URL and JSON body. NULL when there is none.
+    pub fallback_method: Option<String>,
+    pub fallback_path: Option<String>,
+    pub fallback_body: Option<String>,
+    /// Our `p2p_outgoing_requests.id` for a queued `loan_request`.
+    pub outgoing_request_id: Option<String>,
+    /// `pending` or `failed` (given up). Delivered rows are deleted.
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "crate::models::peer::Entity",
+        from = "Column::PeerId",
+        to = "crate::models::peer::Column::Id"
+    )]
+    Peer,
+}
+
+impl Related<crate::models::peer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Peer.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
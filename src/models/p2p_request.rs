@@ -13,6 +13,11 @@ pub struct Model {
     pub created_at: String,
     pub updated_at: String,
     pub requester_request_id: Option<String>,
+    /// Copy reserved for the requester while the request is `ready` (on the
+    /// hold shelf, awaiting pickup or shipping). Migration 095.
+    pub held_copy_id: Option<String>,
+    /// RFC 3339 instant after which an unclaimed hold is released.
+    pub hold_expires_at: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -9,9 +9,12 @@ pub struct Model {
     pub name: String,
     /// User-defined display name (overrides `name` in the UI)
     pub display_name: Option<String>,
+    /// Where the library was last reached; follows it to a new address
+    /// (`services::peer_identity_sync`)
     #[sea_orm(unique)]
     pub url: String,
-    /// Stable UUID for P2P deduplication (survives IP changes)
+    /// Stable UUID for P2P deduplication (survives IP changes). Unique
+    /// (migration 131): the identity of the peer
     pub library_uuid: Option<String>,
     pub public_key: Option<String>,
     /// Hex-encoded X25519 public key for E2EE key exchange
@@ -36,7 +39,8 @@ pub struct Model {
     pub longitude: Option<f64>,
     #[sea_orm(default_value = "false")]
     pub auto_approve: bool,
-    /// Connection status: "pending" or "accepted"
+    /// Connection status: "pending", "accepted" or "blocked" (refused until
+    /// the owner unblocks it)
     #[sea_orm(default_value = "accepted")]
     pub connection_status: String,
     pub last_seen: Option<String>,
@@ -50,6 +54,18 @@ pub struct Model {
     /// (ADR-028 delta sync). NULL means no successful sync yet — the next
     /// pull will be a full GET.
     pub last_delta_cursor: Option<i32>,
+    /// Loan duration (days) for books lent to this peer, overriding the
+    /// contact-type and global defaults of `loan_settings` (migration 094).
+    /// NULL = no override.
+    pub loan_duration_days: Option<i32>,
+    /// Onion service URL the peer advertises in its `/api/config`, reachable
+    /// through the SOCKS5 proxy of `services::tor` (migration 116)
+    pub onion_url: Option<String>,
+    /// Whether announcements from this peer reach the notification inbox
+    /// (`services::announcements`, migration 117); off until the owner opts in
+    #[sea_orm(default_value = "0")]
+    #[serde(default)]
+    pub trust_announcements: bool,
     pub created_at: String,
     pub updated_at: String,
 }
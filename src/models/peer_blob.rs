@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A cover or digital file a peer lists for one of its books (migration
+/// 113). The bytes live in the local blob store once fetched.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "peer_blobs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub peer_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub remote_book_id: String,
+    /// `cover` or `file`
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub kind: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sha256: String,
+    pub size: i64,
+    /// File format (`epub`, `pdf`, ...), `None` for covers
+    pub format: Option<String>,
+    pub listed_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::peer::Entity",
+        from = "Column::PeerId",
+        to = "super::peer::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Peer,
+}
+
+impl Related<super::peer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Peer.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
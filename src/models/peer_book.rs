@@ -87,7 +87,12 @@ impl From<Model> for super::Book {
             available_copies: pb.available_copies,
             private: None,
             page_count: None,
+            edition: None,
+            physical_format: None,
+            dimensions: None,
             loan_duration_days: None,
+            archived: None,
+            digital_source_url: None,
             added_at: pb.added_at,
             // Peer-cached rows have no meaningful local updated_at for
             // cover versioning: the owner's timestamp is what matters
@@ -99,6 +104,7 @@ impl From<Model> for super::Book {
             // and the owner's loan state is redacted from what they send us.
             is_borrowed: None,
             is_lent: None,
+            related: None,
         }
     }
 }
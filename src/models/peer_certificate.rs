@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A self-signed certificate pinned for an HTTPS peer (migration 115).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "peer_certificates")]
+#[schema(as = PeerCertificate)]
+pub struct Model {
+    /// `https://host:port`
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub origin: String,
+    /// SHA-256 fingerprint of the pinned certificate, lowercase hex
+    pub sha256: String,
+    /// The pinned certificate, DER as hex
+    #[serde(skip_serializing)]
+    pub der: String,
+    pub pinned_at: String,
+    /// Fingerprint replaced by the last rotation
+    pub previous_sha256: Option<String>,
+    /// The certificate replaced by the last rotation, DER as hex; still
+    /// trusted for a while after `rotated_at` (migration 132)
+    #[serde(skip_serializing)]
+    pub previous_der: Option<String>,
+    pub rotated_at: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A peer whose catalogue is kept fresh in the background (migration 114).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "peer_subscriptions")]
+#[schema(as = PeerSubscription)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub peer_id: i32,
+    /// Minutes between two scheduled syncs
+    pub interval_minutes: i32,
+    /// `pending` (never synced), `syncing`, `ok` or `error`
+    pub status: String,
+    pub last_attempt_at: Option<String>,
+    pub last_success_at: Option<String>,
+    /// `delta` or `full`, how the last successful sync went
+    pub last_method: Option<String>,
+    /// Why the last attempt failed; cleared by a success
+    pub last_error: Option<String>,
+    /// Failed attempts in a row, which space out the retries
+    pub failures: i32,
+    /// Books of the peer in the local cache after the last success
+    pub books_cached: Option<i32>,
+    pub next_sync_at: String,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::peer::Entity",
+        from = "Column::PeerId",
+        to = "super::peer::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Peer,
+}
+
+impl Related<super::peer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Peer.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
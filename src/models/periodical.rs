@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Issue frequencies, each with the days between two issues.
+pub const FREQUENCIES: &[(&str, i64)] = &[
+    ("daily", 1),
+    ("weekly", 7),
+    ("biweekly", 14),
+    ("monthly", 30),
+    ("bimonthly", 61),
+    ("quarterly", 91),
+    ("semiannual", 182),
+    ("annual", 365),
+    ("irregular", 0),
+];
+
+/// A magazine or journal the library subscribes to (migration 120); its
+/// issues are in `periodical_issues`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "periodicals")]
+#[schema(as = Periodical)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub title: String,
+    /// `NNNN-NNNC`, check digit verified
+    pub issn: Option<String>,
+    pub publisher: Option<String>,
+    /// One of [`FREQUENCIES`]
+    pub frequency: String,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::periodical_issue::Entity")]
+    Issues,
+}
+
+impl Related<super::periodical_issue::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Issues.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
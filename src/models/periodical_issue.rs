@@ -0,0 +1,40 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One received issue of a periodical (migration 120).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "periodical_issues")]
+#[schema(as = PeriodicalIssue)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub periodical_id: String,
+    pub volume: Option<i32>,
+    pub number: Option<i32>,
+    /// Free label for issues without numbering, or special issues
+    pub label: Option<String>,
+    /// `YYYY-MM-DD`, the date printed on the issue
+    pub cover_date: Option<String>,
+    /// `YYYY-MM-DD`
+    pub received_at: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::periodical::Entity",
+        from = "Column::PeriodicalId",
+        to = "super::periodical::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Periodical,
+}
+
+impl Related<super::periodical::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Periodical.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
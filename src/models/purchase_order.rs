@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Order placed with a supplier, not delivered yet.
+pub const STATUS_ORDERED: &str = "ordered";
+/// Delivered: its books and copies are in the catalogue.
+pub const STATUS_RECEIVED: &str = "received";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// A purchase order (migration 107); what it buys is in
+/// `purchase_order_lines`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "purchase_orders")]
+#[schema(as = PurchaseOrder)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    /// The supplier, a contact.
+    pub supplier_contact_id: Option<String>,
+    /// `ordered`, `received` or `cancelled`
+    pub status: String,
+    /// `YYYY-MM-DD`
+    pub ordered_at: String,
+    /// `YYYY-MM-DD`
+    pub received_at: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::purchase_order_line::Entity")]
+    Lines,
+}
+
+impl Related<super::purchase_order_line::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Lines.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One book of a purchase order (migration 107), in `quantity` copies.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "purchase_order_lines")]
+#[schema(as = PurchaseOrderLine)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: String,
+    pub order_id: String,
+    /// The catalogue book: set up front to buy more copies of it, filled in
+    /// on receipt otherwise.
+    pub book_id: Option<String>,
+    pub title: String,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    pub quantity: i32,
+    pub unit_cost: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::purchase_order::Entity",
+        from = "Column::OrderId",
+        to = "super::purchase_order::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Order,
+}
+
+impl Related<super::purchase_order::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
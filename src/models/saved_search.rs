@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A search kept to be run again (migration 123). New matches are reported
+/// in the activity feed; the ones already seen are in `saved_search_matches`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "saved_searches")]
+#[schema(as = SavedSearch)]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Username of the token that saved it; empty for the local app
+    #[serde(skip)]
+    pub owner: String,
+    pub name: String,
+    /// The query in words, read by `services::nl_query`
+    pub query: String,
+    /// `local`, `peers` or both, comma-separated
+    pub sources: String,
+    /// Whether new matches are notified
+    pub notify: bool,
+    /// When the matches were last looked for; `None` until the first run
+    pub last_checked_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
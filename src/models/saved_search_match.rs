@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A book a saved search found (migration 123), kept so it is reported once.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "saved_search_matches")]
+#[schema(as = SavedSearchMatch)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    #[serde(skip)]
+    pub search_id: i32,
+    /// `book:<id>` for a book of this library, `peer:<peer id>:<book id>`
+    /// for one in a peer's catalogue
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub match_key: String,
+    pub title: String,
+    pub author: Option<String>,
+    /// Name of the peer holding it; `None` for this library
+    pub source: Option<String>,
+    pub first_seen_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
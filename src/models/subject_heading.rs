@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A label of a subject vocabulary (migration 111): an authorized heading,
+/// or a variant pointing to one.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "subject_headings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// e.g. `rameau`, `lcsh`
+    pub vocabulary: String,
+    /// The concept's URI or record number in the vocabulary.
+    pub authority_id: Option<String>,
+    pub heading: String,
+    /// `heading` folded for matching.
+    pub normalized: String,
+    /// A non-authorized form ("use for").
+    pub variant: bool,
+    /// The authorized form of a variant.
+    pub preferred: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -14,6 +14,10 @@ pub struct Model {
     pub parent_id: Option<String>,
     #[serde(default)]
     pub path: String,
+    /// Books carrying this tag, or a tag nested under it, are kept out of
+    /// everything shared with others (see `Book::tag_restricted_ids`).
+    #[serde(default)]
+    pub private: bool,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -48,3 +52,33 @@ impl ActiveModelBehavior for ActiveModel {
         Ok(self)
     }
 }
+
+/// Private tags and every tag nested under one, at any depth.
+pub async fn restricting_tags<C: ConnectionTrait>(db: &C) -> Result<Vec<Model>, DbErr> {
+    let all = Entity::find().all(db).await?;
+    let mut restricted: std::collections::HashSet<String> = all
+        .iter()
+        .filter(|t| t.private)
+        .map(|t| t.id.clone())
+        .collect();
+
+    // Propagate down the hierarchy until no new child is reached.
+    loop {
+        let before = restricted.len();
+        for t in &all {
+            if let Some(parent) = &t.parent_id
+                && restricted.contains(parent)
+            {
+                restricted.insert(t.id.clone());
+            }
+        }
+        if restricted.len() == before {
+            break;
+        }
+    }
+
+    Ok(all
+        .into_iter()
+        .filter(|t| restricted.contains(&t.id))
+        .collect())
+}
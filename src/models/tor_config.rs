@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Singleton row storing this node's SOCKS5 proxy and onion service address.
+/// The `id` column is constrained to 1 (CHECK constraint in migration 116).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "my_tor_config")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i32,
+    /// `socks5h://host:port`, NULL when peer requests go out directly
+    pub socks_proxy: Option<String>,
+    /// Onion service URL advertised to peers
+    pub onion_address: Option<String>,
+    /// Send every peer request through the proxy, not only those to onions
+    pub route_all_peers: bool,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
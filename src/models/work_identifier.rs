@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Identifier schemes a work is clustered by.
+pub const SCHEME_OPENLIBRARY: &str = "openlibrary";
+pub const SCHEME_INVENTAIRE: &str = "inventaire";
+pub const SCHEME_BNF: &str = "bnf";
+
+/// An external identifier of a work (migration 122): an Open Library work
+/// key (`OL45883W`), an Inventaire work URI (`wd:Q180736`) or a BNF work
+/// authority record. Belongs to one work only.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, utoipa::ToSchema)]
+#[sea_orm(table_name = "work_identifiers")]
+#[schema(as = WorkIdentifier)]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub scheme: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub value: String,
+    pub work_id: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
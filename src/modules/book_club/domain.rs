@@ -0,0 +1,519 @@
+//! Book Club - domain types, club rules and repository trait
+//!
+//! Framework-free layer: no SeaORM, no Axum.
+//!
+//! A club is hosted by the library that created it. The host holds the
+//! reference state and bumps its `revision` on every change. Members apply
+//! their own proposals and votes locally and send them to the host, which
+//! applies them in turn and pushes the whole state back to every member.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::DomainError;
+
+/// Maximum length for a club name (in characters).
+pub const MAX_NAME_LENGTH: usize = 120;
+/// Maximum length for a proposed title or a milestone title (in characters).
+pub const MAX_TITLE_LENGTH: usize = 300;
+/// Maximum number of milestones in a reading schedule.
+pub const MAX_MILESTONES: usize = 52;
+
+/// Error type for club operations
+#[derive(Debug)]
+pub enum ClubError {
+    NotFound,
+    /// The library is not a member of the club.
+    NotMember,
+    /// Only the host may do this.
+    NotHost,
+    Invalid(String),
+    /// This library has no identity yet, so it cannot take part in a club.
+    NoIdentity,
+    Storage(DomainError),
+}
+
+impl std::fmt::Display for ClubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClubError::NotFound => write!(f, "Book club not found"),
+            ClubError::NotMember => write!(f, "Not a member of this book club"),
+            ClubError::NotHost => write!(f, "Only the host of the book club can do this"),
+            ClubError::Invalid(msg) => write!(f, "{}", msg),
+            ClubError::NoIdentity => write!(f, "Library identity not initialized"),
+            ClubError::Storage(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<DomainError> for ClubError {
+    fn from(e: DomainError) -> Self {
+        ClubError::Storage(e)
+    }
+}
+
+/// A book club, as listed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BookClub {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Library UUID of the host
+    pub host_uuid: String,
+    /// Proposal chosen as the current read
+    pub current_proposal_id: Option<String>,
+    /// Bumped by the host on every change
+    pub revision: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A library taking part in a club.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ClubMember {
+    pub library_uuid: String,
+    pub name: String,
+}
+
+/// A book proposed as a next read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Proposal {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    /// Library UUID of the member who proposed it
+    pub proposed_by: String,
+    pub created_at: String,
+    /// Library UUIDs of the members who voted for it
+    pub voters: Vec<String>,
+}
+
+/// A step of the reading schedule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Milestone {
+    pub position: i32,
+    pub title: String,
+    /// YYYY-MM-DD
+    pub due_date: String,
+}
+
+/// Everything about a club: what the host sends to its members.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ClubState {
+    pub club: BookClub,
+    pub members: Vec<ClubMember>,
+    pub proposals: Vec<Proposal>,
+    pub milestones: Vec<Milestone>,
+}
+
+/// Input for creating a club.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateClubInput {
+    pub name: String,
+    pub description: Option<String>,
+    /// Connected peers invited to the club
+    #[serde(default)]
+    pub peer_ids: Vec<i32>,
+}
+
+/// Input for replacing the members of a club.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SetMembersInput {
+    /// Connected peers in the club, besides the host
+    pub peer_ids: Vec<i32>,
+}
+
+/// Input for proposing a book.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ProposalInput {
+    pub title: String,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+}
+
+/// Input for voting on a proposal.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct VoteInput {
+    /// `false` takes the vote back
+    pub vote: bool,
+}
+
+/// Input for choosing the current read.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SelectReadInput {
+    pub proposal_id: String,
+}
+
+/// A milestone as entered by the host.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct MilestoneInput {
+    pub title: String,
+    /// YYYY-MM-DD
+    pub due_date: String,
+}
+
+/// Input for replacing the reading schedule.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ScheduleInput {
+    pub milestones: Vec<MilestoneInput>,
+}
+
+/// What a member may do in a club, sent to the host to be applied there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClubAction {
+    /// The proposal id is chosen by the member, so the host keeps it.
+    Propose {
+        id: String,
+        title: String,
+        author: Option<String>,
+        isbn: Option<String>,
+        created_at: String,
+    },
+    Vote {
+        proposal_id: String,
+        vote: bool,
+    },
+    Withdraw {
+        proposal_id: String,
+    },
+}
+
+fn optional(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Trim a required title and check its length.
+pub fn checked_title(title: &str, what: &str) -> Result<String, ClubError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(ClubError::Invalid(format!("{} cannot be empty", what)));
+    }
+    if title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(ClubError::Invalid(format!(
+            "{} exceeds {} characters",
+            what, MAX_TITLE_LENGTH
+        )));
+    }
+    Ok(title.to_string())
+}
+
+impl ProposalInput {
+    /// The `Propose` action for this input, with a fresh id.
+    pub fn into_action(self, now: &str) -> Result<ClubAction, ClubError> {
+        Ok(ClubAction::Propose {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: checked_title(&self.title, "Title")?,
+            author: optional(self.author),
+            isbn: optional(self.isbn),
+            created_at: now.to_string(),
+        })
+    }
+}
+
+impl ClubState {
+    /// A new club hosted by `host`, with `members` besides the host.
+    pub fn new(
+        id: String,
+        name: &str,
+        description: Option<String>,
+        host: ClubMember,
+        members: Vec<ClubMember>,
+        now: &str,
+    ) -> Result<Self, ClubError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ClubError::Invalid("Name cannot be empty".to_string()));
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(ClubError::Invalid(format!(
+                "Name exceeds {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        let mut state = ClubState {
+            club: BookClub {
+                id,
+                name: name.to_string(),
+                description: optional(description),
+                host_uuid: host.library_uuid.clone(),
+                current_proposal_id: None,
+                revision: 1,
+                created_at: now.to_string(),
+                updated_at: now.to_string(),
+            },
+            members: vec![host],
+            proposals: Vec::new(),
+            milestones: Vec::new(),
+        };
+        state.set_members(members);
+        Ok(state)
+    }
+
+    pub fn is_member(&self, library_uuid: &str) -> bool {
+        self.members.iter().any(|m| m.library_uuid == library_uuid)
+    }
+
+    pub fn is_host(&self, library_uuid: &str) -> bool {
+        self.club.host_uuid == library_uuid
+    }
+
+    /// Record a change made by the host.
+    pub fn bump(&mut self, now: &str) {
+        self.club.revision += 1;
+        self.club.updated_at = now.to_string();
+    }
+
+    /// Replace the members besides the host. Votes of the members who left
+    /// are dropped with them.
+    pub fn set_members(&mut self, members: Vec<ClubMember>) {
+        let host = self.club.host_uuid.clone();
+        self.members.retain(|m| m.library_uuid == host);
+        for member in members {
+            if !self.is_member(&member.library_uuid) {
+                self.members.push(member);
+            }
+        }
+        let members: Vec<String> = self
+            .members
+            .iter()
+            .map(|m| m.library_uuid.clone())
+            .collect();
+        for proposal in &mut self.proposals {
+            proposal.voters.retain(|v| members.contains(v));
+        }
+    }
+
+    /// Apply what `actor` did. Proposing an id already known is a no-op, so
+    /// a redelivered action changes nothing.
+    pub fn apply(&mut self, actor: &str, action: ClubAction) -> Result<(), ClubError> {
+        if !self.is_member(actor) {
+            return Err(ClubError::NotMember);
+        }
+        match action {
+            ClubAction::Propose {
+                id,
+                title,
+                author,
+                isbn,
+                created_at,
+            } => {
+                if self.proposals.iter().any(|p| p.id == id) {
+                    return Ok(());
+                }
+                self.proposals.push(Proposal {
+                    id,
+                    title: checked_title(&title, "Title")?,
+                    author: optional(author),
+                    isbn: optional(isbn),
+                    proposed_by: actor.to_string(),
+                    created_at,
+                    voters: vec![actor.to_string()],
+                });
+            }
+            ClubAction::Vote { proposal_id, vote } => {
+                let proposal = self
+                    .proposals
+                    .iter_mut()
+                    .find(|p| p.id == proposal_id)
+                    .ok_or(ClubError::NotFound)?;
+                let voted = proposal.voters.iter().any(|v| v == actor);
+                if vote && !voted {
+                    proposal.voters.push(actor.to_string());
+                } else if !vote {
+                    proposal.voters.retain(|v| v != actor);
+                }
+            }
+            ClubAction::Withdraw { proposal_id } => {
+                let proposal = self
+                    .proposals
+                    .iter()
+                    .find(|p| p.id == proposal_id)
+                    .ok_or(ClubError::NotFound)?;
+                if proposal.proposed_by != actor && !self.is_host(actor) {
+                    return Err(ClubError::NotHost);
+                }
+                if self.club.current_proposal_id.as_deref() == Some(proposal_id.as_str()) {
+                    return Err(ClubError::Invalid(
+                        "The current read cannot be withdrawn".to_string(),
+                    ));
+                }
+                self.proposals.retain(|p| p.id != proposal_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Make a proposal the current read. The schedule of the previous read
+    /// no longer applies, so it is cleared.
+    pub fn select(&mut self, proposal_id: &str) -> Result<(), ClubError> {
+        if !self.proposals.iter().any(|p| p.id == proposal_id) {
+            return Err(ClubError::NotFound);
+        }
+        if self.club.current_proposal_id.as_deref() != Some(proposal_id) {
+            self.club.current_proposal_id = Some(proposal_id.to_string());
+            self.milestones.clear();
+        }
+        Ok(())
+    }
+
+    /// Replace the reading schedule, kept in date order.
+    pub fn set_schedule(&mut self, milestones: Vec<MilestoneInput>) -> Result<(), ClubError> {
+        if milestones.len() > MAX_MILESTONES {
+            return Err(ClubError::Invalid(format!(
+                "A schedule has at most {} milestones",
+                MAX_MILESTONES
+            )));
+        }
+        let mut checked = Vec::with_capacity(milestones.len());
+        for m in milestones {
+            let title = checked_title(&m.title, "Milestone title")?;
+            let due_date = chrono::NaiveDate::parse_from_str(m.due_date.trim(), "%Y-%m-%d")
+                .map_err(|_| {
+                    ClubError::Invalid(format!("Invalid due date (YYYY-MM-DD): {}", m.due_date))
+                })?;
+            checked.push((due_date, title));
+        }
+        checked.sort_by_key(|(due_date, _)| *due_date);
+        self.milestones = checked
+            .into_iter()
+            .enumerate()
+            .map(|(i, (due_date, title))| Milestone {
+                position: i as i32,
+                title,
+                due_date: due_date.format("%Y-%m-%d").to_string(),
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait BookClubRepository: Send + Sync {
+    /// List the clubs this library takes part in, by name.
+    async fn list(&self) -> Result<Vec<BookClub>, DomainError>;
+
+    /// Load the whole state of a club.
+    async fn load(&self, id: &str) -> Result<Option<ClubState>, DomainError>;
+
+    /// Store the whole state of a club, replacing what was stored.
+    async fn save(&self, state: &ClubState) -> Result<(), DomainError>;
+
+    /// Delete a club. Returns true if found.
+    async fn delete(&self, id: &str) -> Result<bool, DomainError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(uuid: &str) -> ClubMember {
+        ClubMember {
+            library_uuid: uuid.to_string(),
+            name: uuid.to_uppercase(),
+        }
+    }
+
+    fn club() -> ClubState {
+        ClubState::new(
+            "c1".to_string(),
+            " Thursday readers ",
+            None,
+            member("host"),
+            vec![member("ann"), member("bob")],
+            "2026-10-01T18:00:00Z",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn members_propose_and_vote_and_the_host_picks_the_read() {
+        let mut state = club();
+        assert_eq!(state.club.name, "Thursday readers");
+        assert_eq!(state.members.len(), 3);
+
+        let propose = ProposalInput {
+            title: "Les Misérables".to_string(),
+            author: Some("Victor Hugo".to_string()),
+            isbn: Some(" ".to_string()),
+        }
+        .into_action("2026-10-02T09:00:00Z")
+        .unwrap();
+        let ClubAction::Propose { id, .. } = propose.clone() else {
+            unreachable!()
+        };
+        state.apply("ann", propose.clone()).unwrap();
+        // Redelivered: no duplicate.
+        state.apply("ann", propose).unwrap();
+        assert_eq!(state.proposals.len(), 1);
+        assert_eq!(state.proposals[0].isbn, None);
+        assert_eq!(state.proposals[0].voters, ["ann"]);
+
+        let vote = |vote| ClubAction::Vote {
+            proposal_id: id.clone(),
+            vote,
+        };
+        state.apply("bob", vote(true)).unwrap();
+        state.apply("bob", vote(true)).unwrap();
+        assert_eq!(state.proposals[0].voters, ["ann", "bob"]);
+        state.apply("bob", vote(false)).unwrap();
+        assert_eq!(state.proposals[0].voters, ["ann"]);
+        assert!(matches!(
+            state.apply("eve", vote(true)),
+            Err(ClubError::NotMember)
+        ));
+        assert!(matches!(
+            state.apply(
+                "bob",
+                ClubAction::Withdraw {
+                    proposal_id: id.clone()
+                }
+            ),
+            Err(ClubError::NotHost)
+        ));
+
+        state
+            .set_schedule(vec![MilestoneInput {
+                title: "Old schedule".to_string(),
+                due_date: "2026-10-05".to_string(),
+            }])
+            .unwrap();
+        state.select(&id).unwrap();
+        assert_eq!(state.club.current_proposal_id.as_deref(), Some(id.as_str()));
+        assert!(state.milestones.is_empty());
+
+        state
+            .set_schedule(vec![
+                MilestoneInput {
+                    title: "Part two".to_string(),
+                    due_date: "2026-11-20".to_string(),
+                },
+                MilestoneInput {
+                    title: "Part one".to_string(),
+                    due_date: "2026-11-06".to_string(),
+                },
+            ])
+            .unwrap();
+        let titles: Vec<&str> = state.milestones.iter().map(|m| m.title.as_str()).collect();
+        assert_eq!(titles, ["Part one", "Part two"]);
+        assert!(
+            state
+                .set_schedule(vec![MilestoneInput {
+                    title: "Soon".to_string(),
+                    due_date: "next week".to_string(),
+                }])
+                .is_err()
+        );
+
+        // Ann leaves: her vote goes with her, the host stays.
+        state.set_members(vec![member("bob")]);
+        assert!(state.is_member("host") && !state.is_member("ann"));
+        assert!(state.proposals[0].voters.is_empty());
+
+        state.bump("2026-10-03T10:00:00Z");
+        assert_eq!(state.club.revision, 2);
+    }
+}
@@ -0,0 +1,244 @@
+//! Axum handlers for book clubs.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use super::domain::{
+    BookClubRepository, ClubAction, ClubError, CreateClubInput, ProposalInput, ScheduleInput,
+    SelectReadInput, SetMembersInput, VoteInput,
+};
+use super::repository::SeaOrmBookClubRepository;
+use super::service;
+use crate::infrastructure::AppState;
+
+fn club_error(e: ClubError) -> Response {
+    let status = match e {
+        ClubError::NotFound => StatusCode::NOT_FOUND,
+        ClubError::NotMember | ClubError::NotHost => StatusCode::FORBIDDEN,
+        ClubError::Invalid(_) => StatusCode::BAD_REQUEST,
+        ClubError::NoIdentity => StatusCode::SERVICE_UNAVAILABLE,
+        ClubError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(json!({"error": e.to_string()}))).into_response()
+}
+
+fn club_response(result: Result<super::domain::ClubState, ClubError>) -> Response {
+    match result {
+        Ok(club) => (StatusCode::OK, Json(club)).into_response(),
+        Err(e) => club_error(e),
+    }
+}
+
+/// GET /book-clubs
+#[utoipa::path(
+    get,
+    path = "/api/book-clubs",
+    tag = "book_clubs",
+    responses(
+        (status = 200, description = "Clubs this library hosts or is a member of", body = [BookClub])
+    )
+)]
+pub async fn list_clubs(State(state): State<AppState>) -> impl IntoResponse {
+    match SeaOrmBookClubRepository::new(state.db().clone())
+        .list()
+        .await
+    {
+        Ok(clubs) => (StatusCode::OK, Json(clubs)).into_response(),
+        Err(e) => club_error(e.into()),
+    }
+}
+
+/// POST /book-clubs
+#[utoipa::path(
+    post,
+    path = "/api/book-clubs",
+    tag = "book_clubs",
+    request_body = CreateClubInput,
+    responses(
+        (status = 201, description = "Club created, hosted by this library and sent to its members", body = ClubState),
+        (status = 400, description = "Invalid name, or a peer that is not connected")
+    )
+)]
+pub async fn create_club(
+    State(state): State<AppState>,
+    Json(input): Json<CreateClubInput>,
+) -> impl IntoResponse {
+    match service::create(&state, input).await {
+        Ok(club) => (StatusCode::CREATED, Json(club)).into_response(),
+        Err(e) => club_error(e),
+    }
+}
+
+/// GET /book-clubs/:id
+#[utoipa::path(
+    get,
+    path = "/api/book-clubs/{id}",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    responses(
+        (status = 200, description = "Members, proposals with their votes, current read and schedule", body = ClubState),
+        (status = 404, description = "Unknown club")
+    )
+)]
+pub async fn get_club(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    club_response(service::get(&state, &id).await)
+}
+
+/// DELETE /book-clubs/:id
+#[utoipa::path(
+    delete,
+    path = "/api/book-clubs/{id}",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    responses(
+        (status = 204, description = "Club left; closed for every member when this library hosts it"),
+        (status = 404, description = "Unknown club")
+    )
+)]
+pub async fn leave_club(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match service::leave(&state, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => club_error(e),
+    }
+}
+
+/// PUT /book-clubs/:id/members
+#[utoipa::path(
+    put,
+    path = "/api/book-clubs/{id}/members",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    request_body = SetMembersInput,
+    responses(
+        (status = 200, description = "Members replaced", body = ClubState),
+        (status = 403, description = "Only the host manages members")
+    )
+)]
+pub async fn set_members(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<SetMembersInput>,
+) -> impl IntoResponse {
+    club_response(service::set_members(&state, &id, &input.peer_ids).await)
+}
+
+/// POST /book-clubs/:id/proposals
+#[utoipa::path(
+    post,
+    path = "/api/book-clubs/{id}/proposals",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    request_body = ProposalInput,
+    responses(
+        (status = 200, description = "Book proposed as a next read, with this library's vote", body = ClubState),
+        (status = 400, description = "Empty or too long title")
+    )
+)]
+pub async fn propose(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<ProposalInput>,
+) -> impl IntoResponse {
+    let action = match input.into_action(&chrono::Utc::now().to_rfc3339()) {
+        Ok(action) => action,
+        Err(e) => return club_error(e),
+    };
+    club_response(service::act(&state, &id, action).await)
+}
+
+/// DELETE /book-clubs/:id/proposals/:proposal_id
+#[utoipa::path(
+    delete,
+    path = "/api/book-clubs/{id}/proposals/{proposal_id}",
+    tag = "book_clubs",
+    params(
+        ("id" = String, Path, description = "Club id"),
+        ("proposal_id" = String, Path, description = "Proposal id")
+    ),
+    responses(
+        (status = 200, description = "Proposal withdrawn", body = ClubState),
+        (status = 403, description = "Neither its proposer nor the host")
+    )
+)]
+pub async fn withdraw(
+    State(state): State<AppState>,
+    Path((id, proposal_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    club_response(service::act(&state, &id, ClubAction::Withdraw { proposal_id }).await)
+}
+
+/// PUT /book-clubs/:id/proposals/:proposal_id/vote
+#[utoipa::path(
+    put,
+    path = "/api/book-clubs/{id}/proposals/{proposal_id}/vote",
+    tag = "book_clubs",
+    params(
+        ("id" = String, Path, description = "Club id"),
+        ("proposal_id" = String, Path, description = "Proposal id")
+    ),
+    request_body = VoteInput,
+    responses(
+        (status = 200, description = "Vote cast or taken back", body = ClubState),
+        (status = 404, description = "Unknown club or proposal")
+    )
+)]
+pub async fn vote(
+    State(state): State<AppState>,
+    Path((id, proposal_id)): Path<(String, String)>,
+    Json(input): Json<VoteInput>,
+) -> impl IntoResponse {
+    let action = ClubAction::Vote {
+        proposal_id,
+        vote: input.vote,
+    };
+    club_response(service::act(&state, &id, action).await)
+}
+
+/// PUT /book-clubs/:id/current
+#[utoipa::path(
+    put,
+    path = "/api/book-clubs/{id}/current",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    request_body = SelectReadInput,
+    responses(
+        (status = 200, description = "Current read chosen; the previous schedule is cleared", body = ClubState),
+        (status = 403, description = "Only the host chooses the read")
+    )
+)]
+pub async fn select_read(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<SelectReadInput>,
+) -> impl IntoResponse {
+    club_response(service::select_read(&state, &id, &input.proposal_id).await)
+}
+
+/// PUT /book-clubs/:id/schedule
+#[utoipa::path(
+    put,
+    path = "/api/book-clubs/{id}/schedule",
+    tag = "book_clubs",
+    params(("id" = String, Path, description = "Club id")),
+    request_body = ScheduleInput,
+    responses(
+        (status = 200, description = "Schedule replaced, milestones in date order", body = ClubState),
+        (status = 400, description = "Invalid title or date"),
+        (status = 403, description = "Only the host sets the schedule")
+    )
+)]
+pub async fn set_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(input): Json<ScheduleInput>,
+) -> impl IntoResponse {
+    club_response(service::set_schedule(&state, &id, input.milestones).await)
+}
@@ -0,0 +1,138 @@
+//! Book Club - self-contained extension module
+//!
+//! Clubs of connected libraries reading together: members propose and vote
+//! on their next read, the host picks it and sets a reading schedule with
+//! milestones. The club state is kept on every member node and synced over
+//! E2EE peer messages (see `service`).
+//!
+//! This module follows the "extension plugin" pattern (ADR-005):
+//! all domain types, models, repository, service, and handlers are
+//! contained within this folder.
+//!
+//! Integration points (besides the E2EE dispatch of its two messages):
+//!   - `api/mod.rs`:  .merge(modules::book_club::routes())
+//!   - `infrastructure/db.rs`:  modules::book_club::migrate(&db).await?;
+
+pub mod domain;
+pub(crate) mod handlers;
+mod models;
+pub mod repository;
+pub mod service;
+
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+
+use crate::infrastructure::AppState;
+
+/// Returns the Axum routes for this module.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/book-clubs", get(handlers::list_clubs))
+        .route("/book-clubs", post(handlers::create_club))
+        .route("/book-clubs/:id", get(handlers::get_club))
+        .route("/book-clubs/:id", delete(handlers::leave_club))
+        .route("/book-clubs/:id/members", put(handlers::set_members))
+        .route("/book-clubs/:id/proposals", post(handlers::propose))
+        .route(
+            "/book-clubs/:id/proposals/:proposal_id",
+            delete(handlers::withdraw),
+        )
+        .route(
+            "/book-clubs/:id/proposals/:proposal_id/vote",
+            put(handlers::vote),
+        )
+        .route("/book-clubs/:id/current", put(handlers::select_read))
+        .route("/book-clubs/:id/schedule", put(handlers::set_schedule))
+}
+
+/// Run database migrations for this module.
+pub async fn migrate(db: &DatabaseConnection) -> Result<(), sea_orm::DbErr> {
+    let backend = db.get_database_backend();
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE TABLE IF NOT EXISTS book_clubs (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT,
+            host_uuid TEXT NOT NULL,
+            current_proposal_id TEXT,
+            revision INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE TABLE IF NOT EXISTS book_club_members (
+            club_id TEXT NOT NULL,
+            library_uuid TEXT NOT NULL,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (club_id, library_uuid),
+            FOREIGN KEY (club_id) REFERENCES book_clubs(id) ON DELETE CASCADE
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE TABLE IF NOT EXISTS book_club_proposals (
+            id TEXT PRIMARY KEY NOT NULL,
+            club_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            author TEXT,
+            isbn TEXT,
+            proposed_by TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (club_id) REFERENCES book_clubs(id) ON DELETE CASCADE
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE TABLE IF NOT EXISTS book_club_votes (
+            proposal_id TEXT NOT NULL,
+            voter TEXT NOT NULL,
+            club_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (proposal_id, voter),
+            FOREIGN KEY (proposal_id) REFERENCES book_club_proposals(id) ON DELETE CASCADE
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        backend,
+        "CREATE TABLE IF NOT EXISTS book_club_milestones (
+            club_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            due_date TEXT NOT NULL,
+            PRIMARY KEY (club_id, position),
+            FOREIGN KEY (club_id) REFERENCES book_clubs(id) ON DELETE CASCADE
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    for index in [
+        "CREATE INDEX IF NOT EXISTS idx_book_club_proposals_club ON book_club_proposals(club_id, created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_book_club_votes_club ON book_club_votes(club_id)",
+    ] {
+        db.execute(Statement::from_string(backend, index.to_owned()))
+            .await?;
+    }
+
+    Ok(())
+}
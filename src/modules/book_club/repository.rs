@@ -0,0 +1,205 @@
+//! SeaORM implementation of BookClubRepository.
+
+use async_trait::async_trait;
+use sea_orm::*;
+
+use super::domain::{BookClub, BookClubRepository, ClubMember, ClubState, Milestone, Proposal};
+use super::models::{
+    book_club, book_club_member, book_club_milestone, book_club_proposal, book_club_vote,
+};
+use crate::domain::DomainError;
+
+pub struct SeaOrmBookClubRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmBookClubRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+fn model_to_domain(m: book_club::Model) -> BookClub {
+    BookClub {
+        id: m.id,
+        name: m.name,
+        description: m.description,
+        host_uuid: m.host_uuid,
+        current_proposal_id: m.current_proposal_id,
+        revision: m.revision,
+        created_at: m.created_at,
+        updated_at: m.updated_at,
+    }
+}
+
+/// Delete the rows of a club, children first: the cascade is not relied on,
+/// as a pooled connection may have `foreign_keys` off.
+async fn delete_rows<C: ConnectionTrait>(db: &C, id: &str) -> Result<u64, DbErr> {
+    book_club_vote::Entity::delete_many()
+        .filter(book_club_vote::Column::ClubId.eq(id))
+        .exec(db)
+        .await?;
+    book_club_proposal::Entity::delete_many()
+        .filter(book_club_proposal::Column::ClubId.eq(id))
+        .exec(db)
+        .await?;
+    book_club_milestone::Entity::delete_many()
+        .filter(book_club_milestone::Column::ClubId.eq(id))
+        .exec(db)
+        .await?;
+    book_club_member::Entity::delete_many()
+        .filter(book_club_member::Column::ClubId.eq(id))
+        .exec(db)
+        .await?;
+    Ok(book_club::Entity::delete_by_id(id.to_owned())
+        .exec(db)
+        .await?
+        .rows_affected)
+}
+
+#[async_trait]
+impl BookClubRepository for SeaOrmBookClubRepository {
+    async fn list(&self) -> Result<Vec<BookClub>, DomainError> {
+        let clubs = book_club::Entity::find()
+            .order_by_asc(book_club::Column::Name)
+            .all(&self.db)
+            .await?;
+        Ok(clubs.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<ClubState>, DomainError> {
+        let Some(club) = book_club::Entity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let members = book_club_member::Entity::find()
+            .filter(book_club_member::Column::ClubId.eq(id))
+            .order_by_asc(book_club_member::Column::Position)
+            .all(&self.db)
+            .await?;
+        let proposals = book_club_proposal::Entity::find()
+            .filter(book_club_proposal::Column::ClubId.eq(id))
+            .order_by_asc(book_club_proposal::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        let votes = book_club_vote::Entity::find()
+            .filter(book_club_vote::Column::ClubId.eq(id))
+            .order_by_asc(book_club_vote::Column::Position)
+            .all(&self.db)
+            .await?;
+        let milestones = book_club_milestone::Entity::find()
+            .filter(book_club_milestone::Column::ClubId.eq(id))
+            .order_by_asc(book_club_milestone::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        Ok(Some(ClubState {
+            club: model_to_domain(club),
+            members: members
+                .into_iter()
+                .map(|m| ClubMember {
+                    library_uuid: m.library_uuid,
+                    name: m.name,
+                })
+                .collect(),
+            proposals: proposals
+                .into_iter()
+                .map(|p| Proposal {
+                    voters: votes
+                        .iter()
+                        .filter(|v| v.proposal_id == p.id)
+                        .map(|v| v.voter.clone())
+                        .collect(),
+                    id: p.id,
+                    title: p.title,
+                    author: p.author,
+                    isbn: p.isbn,
+                    proposed_by: p.proposed_by,
+                    created_at: p.created_at,
+                })
+                .collect(),
+            milestones: milestones
+                .into_iter()
+                .map(|m| Milestone {
+                    position: m.position,
+                    title: m.title,
+                    due_date: m.due_date,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn save(&self, state: &ClubState) -> Result<(), DomainError> {
+        let club = &state.club;
+        let txn = self.db.begin().await?;
+        delete_rows(&txn, &club.id).await?;
+
+        book_club::ActiveModel {
+            id: Set(club.id.clone()),
+            name: Set(club.name.clone()),
+            description: Set(club.description.clone()),
+            host_uuid: Set(club.host_uuid.clone()),
+            current_proposal_id: Set(club.current_proposal_id.clone()),
+            revision: Set(club.revision),
+            created_at: Set(club.created_at.clone()),
+            updated_at: Set(club.updated_at.clone()),
+        }
+        .insert(&txn)
+        .await?;
+        for (i, m) in state.members.iter().enumerate() {
+            book_club_member::ActiveModel {
+                club_id: Set(club.id.clone()),
+                library_uuid: Set(m.library_uuid.clone()),
+                name: Set(m.name.clone()),
+                position: Set(i as i32),
+            }
+            .insert(&txn)
+            .await?;
+        }
+        for p in &state.proposals {
+            book_club_proposal::ActiveModel {
+                id: Set(p.id.clone()),
+                club_id: Set(club.id.clone()),
+                title: Set(p.title.clone()),
+                author: Set(p.author.clone()),
+                isbn: Set(p.isbn.clone()),
+                proposed_by: Set(p.proposed_by.clone()),
+                created_at: Set(p.created_at.clone()),
+            }
+            .insert(&txn)
+            .await?;
+            for (i, voter) in p.voters.iter().enumerate() {
+                book_club_vote::ActiveModel {
+                    proposal_id: Set(p.id.clone()),
+                    voter: Set(voter.clone()),
+                    club_id: Set(club.id.clone()),
+                    position: Set(i as i32),
+                }
+                .insert(&txn)
+                .await?;
+            }
+        }
+        for m in &state.milestones {
+            book_club_milestone::ActiveModel {
+                club_id: Set(club.id.clone()),
+                position: Set(m.position),
+                title: Set(m.title.clone()),
+                due_date: Set(m.due_date.clone()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<bool, DomainError> {
+        let txn = self.db.begin().await?;
+        let deleted = delete_rows(&txn, id).await?;
+        txn.commit().await?;
+        Ok(deleted > 0)
+    }
+}
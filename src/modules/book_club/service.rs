@@ -0,0 +1,479 @@
+//! Book Club service — club lifecycle and P2P sync
+//!
+//! Changes made here are stored, then sent over E2EE messages:
+//!   - `book_club_state`: the host pushes the whole club to every member;
+//!     a member keeps it when its revision is newer than the one it has.
+//!   - `book_club_action`: a member sends its proposal or vote to the host,
+//!     which applies it and pushes the new state.
+//!
+//! A peer that cannot be reached gets the message from the peer outbox.
+
+use std::collections::HashSet;
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use super::domain::{
+    BookClubRepository, ClubAction, ClubError, ClubMember, ClubState, CreateClubInput,
+    MilestoneInput,
+};
+use super::repository::SeaOrmBookClubRepository;
+use crate::infrastructure::AppState;
+use crate::models::{library_config, peer};
+
+/// What became of a club message received from a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Received {
+    Applied,
+    /// Older than what is stored.
+    Stale,
+    /// Not from the host, or from a library outside the club.
+    Ignored,
+}
+
+impl Received {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Received::Applied => "applied",
+            Received::Stale => "stale",
+            Received::Ignored => "ignored",
+        }
+    }
+}
+
+/// Payload of a `book_club_action` message.
+#[derive(Debug, Serialize, Deserialize)]
+struct ActionMessage {
+    club_id: String,
+    #[serde(flatten)]
+    action: ClubAction,
+}
+
+fn repo(state: &AppState) -> SeaOrmBookClubRepository {
+    SeaOrmBookClubRepository::new(state.db().clone())
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn our_uuid(state: &AppState) -> Result<String, ClubError> {
+    state
+        .identity_service
+        .library_uuid()
+        .map(str::to_string)
+        .ok_or(ClubError::NoIdentity)
+}
+
+async fn load(state: &AppState, id: &str) -> Result<ClubState, ClubError> {
+    repo(state).load(id).await?.ok_or(ClubError::NotFound)
+}
+
+/// The state of a club, for a member of it.
+pub async fn get(state: &AppState, id: &str) -> Result<ClubState, ClubError> {
+    load(state, id).await
+}
+
+/// Members for the given connected peers. Every peer must have shared its
+/// library UUID, which identifies it in the club.
+async fn members_for(state: &AppState, peer_ids: &[i32]) -> Result<Vec<ClubMember>, ClubError> {
+    let peers = peer::Entity::find()
+        .filter(peer::Column::Id.is_in(peer_ids.iter().copied()))
+        .filter(peer::Column::ConnectionStatus.eq("accepted"))
+        .all(state.db())
+        .await
+        .map_err(crate::domain::DomainError::from)?;
+    peer_ids
+        .iter()
+        .map(|id| {
+            let p = peers
+                .iter()
+                .find(|p| p.id == *id)
+                .ok_or_else(|| ClubError::Invalid(format!("Peer {} is not connected", id)))?;
+            let library_uuid = p.library_uuid.clone().ok_or_else(|| {
+                ClubError::Invalid(format!("Peer {} has not shared its library id yet", id))
+            })?;
+            Ok(ClubMember {
+                library_uuid,
+                name: p.display_name.clone().unwrap_or_else(|| p.name.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Send a club message to the library `library_uuid`, or queue it.
+async fn send(
+    state: &AppState,
+    library_uuid: &str,
+    message_type: &str,
+    payload: serde_json::Value,
+) {
+    let db = state.db();
+    let found = peer::Entity::find()
+        .filter(peer::Column::LibraryUuid.eq(library_uuid))
+        .filter(peer::Column::ConnectionStatus.eq("accepted"))
+        .one(db)
+        .await;
+    let Ok(Some(p)) = found else {
+        tracing::warn!("book_club: no connected peer for library {library_uuid}");
+        return;
+    };
+    match crate::api::peer::try_send_e2ee(state, &p, message_type, payload.clone()).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            tracing::warn!(
+                "book_club: no E2EE channel with peer {}, '{message_type}' not sent",
+                p.id
+            )
+        }
+        Err(e) => {
+            crate::services::peer_outbox::enqueue_or_log(db, p.id, message_type, &payload, None, &e)
+                .await
+        }
+    }
+}
+
+/// Push the state of a hosted club to its members, and to `former` members
+/// so they learn they left. Runs in the background.
+fn push_state(state: &AppState, club: &ClubState, former: Vec<String>) {
+    let Ok(payload) = serde_json::to_value(club) else {
+        return;
+    };
+    let host = club.club.host_uuid.clone();
+    let recipients: HashSet<String> = club
+        .members
+        .iter()
+        .map(|m| m.library_uuid.clone())
+        .chain(former)
+        .filter(|uuid| *uuid != host)
+        .collect();
+    let state = state.clone();
+    tokio::spawn(async move {
+        for uuid in recipients {
+            send(&state, &uuid, "book_club_state", payload.clone()).await;
+        }
+    });
+}
+
+/// Store a change to a hosted club and push it to the members.
+async fn commit(
+    state: &AppState,
+    mut club: ClubState,
+    former: Vec<String>,
+) -> Result<ClubState, ClubError> {
+    club.bump(&now());
+    repo(state).save(&club).await?;
+    push_state(state, &club, former);
+    Ok(club)
+}
+
+/// Load a club this library hosts.
+async fn hosted(state: &AppState, id: &str) -> Result<ClubState, ClubError> {
+    let club = load(state, id).await?;
+    if !club.is_host(&our_uuid(state)?) {
+        return Err(ClubError::NotHost);
+    }
+    Ok(club)
+}
+
+/// Create a club hosted by this library and invite the given peers.
+pub async fn create(state: &AppState, input: CreateClubInput) -> Result<ClubState, ClubError> {
+    let host_name = library_config::Entity::find_by_id(1)
+        .one(state.db())
+        .await
+        .map_err(crate::domain::DomainError::from)?
+        .map(|c| c.name)
+        .unwrap_or_else(|| "Unknown Library".to_string());
+    let host = ClubMember {
+        library_uuid: our_uuid(state)?,
+        name: host_name,
+    };
+    let members = members_for(state, &input.peer_ids).await?;
+    let club = ClubState::new(
+        uuid::Uuid::new_v4().to_string(),
+        &input.name,
+        input.description,
+        host,
+        members,
+        &now(),
+    )?;
+    repo(state).save(&club).await?;
+    push_state(state, &club, Vec::new());
+    Ok(club)
+}
+
+/// Replace the members of a hosted club.
+pub async fn set_members(
+    state: &AppState,
+    id: &str,
+    peer_ids: &[i32],
+) -> Result<ClubState, ClubError> {
+    let mut club = hosted(state, id).await?;
+    let before: Vec<String> = club
+        .members
+        .iter()
+        .map(|m| m.library_uuid.clone())
+        .collect();
+    club.set_members(members_for(state, peer_ids).await?);
+    let former = before
+        .into_iter()
+        .filter(|uuid| !club.is_member(uuid))
+        .collect();
+    commit(state, club, former).await
+}
+
+/// Propose, vote or withdraw as this library. The host applies it right
+/// away; a member applies it locally and sends it to the host.
+pub async fn act(state: &AppState, id: &str, action: ClubAction) -> Result<ClubState, ClubError> {
+    let me = our_uuid(state)?;
+    let mut club = load(state, id).await?;
+    club.apply(&me, action.clone())?;
+    if club.is_host(&me) {
+        return commit(state, club, Vec::new()).await;
+    }
+    repo(state).save(&club).await?;
+    let message = ActionMessage {
+        club_id: id.to_string(),
+        action,
+    };
+    if let Ok(payload) = serde_json::to_value(&message) {
+        let state = state.clone();
+        let host = club.club.host_uuid.clone();
+        tokio::spawn(async move { send(&state, &host, "book_club_action", payload).await });
+    }
+    Ok(club)
+}
+
+/// Choose the current read of a hosted club.
+pub async fn select_read(
+    state: &AppState,
+    id: &str,
+    proposal_id: &str,
+) -> Result<ClubState, ClubError> {
+    let mut club = hosted(state, id).await?;
+    club.select(proposal_id)?;
+    commit(state, club, Vec::new()).await
+}
+
+/// Replace the reading schedule of a hosted club.
+pub async fn set_schedule(
+    state: &AppState,
+    id: &str,
+    milestones: Vec<MilestoneInput>,
+) -> Result<ClubState, ClubError> {
+    let mut club = hosted(state, id).await?;
+    club.set_schedule(milestones)?;
+    commit(state, club, Vec::new()).await
+}
+
+/// Forget a club locally. When this library hosts it, the members are told
+/// the club is closed (an empty member list); otherwise the host keeps the
+/// club as it is until it removes this library.
+pub async fn leave(state: &AppState, id: &str) -> Result<(), ClubError> {
+    let mut club = load(state, id).await?;
+    if club.is_host(&our_uuid(state)?) {
+        let former = club
+            .members
+            .iter()
+            .map(|m| m.library_uuid.clone())
+            .collect();
+        club.set_members(Vec::new());
+        club.bump(&now());
+        push_state(state, &club, former);
+    }
+    repo(state).delete(id).await?;
+    Ok(())
+}
+
+/// Handle a `book_club_state` message. Only the host of a club may send
+/// its state; a state leaving this library out removes the club here.
+pub async fn receive_state(
+    state: &AppState,
+    sender: &peer::Model,
+    payload: &serde_json::Value,
+) -> Result<Received, ClubError> {
+    let incoming: ClubState = serde_json::from_value(payload.clone())
+        .map_err(|e| ClubError::Invalid(format!("Invalid book club state: {}", e)))?;
+    let me = our_uuid(state)?;
+    if sender.library_uuid.as_deref() != Some(incoming.club.host_uuid.as_str())
+        || incoming.is_host(&me)
+    {
+        return Ok(Received::Ignored);
+    }
+    let stored = repo(state).load(&incoming.club.id).await?;
+    if let Some(stored) = &stored {
+        if stored.club.host_uuid != incoming.club.host_uuid {
+            return Ok(Received::Ignored);
+        }
+        if stored.club.revision >= incoming.club.revision {
+            return Ok(Received::Stale);
+        }
+    }
+    if !incoming.is_member(&me) {
+        if stored.is_some() {
+            repo(state).delete(&incoming.club.id).await?;
+            return Ok(Received::Applied);
+        }
+        return Ok(Received::Ignored);
+    }
+    repo(state).save(&incoming).await?;
+    Ok(Received::Applied)
+}
+
+/// Handle a `book_club_action` message sent by a member of a club this
+/// library hosts.
+pub async fn receive_action(
+    state: &AppState,
+    sender: &peer::Model,
+    payload: &serde_json::Value,
+) -> Result<Received, ClubError> {
+    let message: ActionMessage = serde_json::from_value(payload.clone())
+        .map_err(|e| ClubError::Invalid(format!("Invalid book club action: {}", e)))?;
+    let Some(actor) = sender.library_uuid.as_deref() else {
+        return Ok(Received::Ignored);
+    };
+    let mut club = match hosted(state, &message.club_id).await {
+        Ok(club) => club,
+        Err(ClubError::NotFound | ClubError::NotHost) => return Ok(Received::Ignored),
+        Err(e) => return Err(e),
+    };
+    match club.apply(actor, message.action) {
+        Ok(()) => {}
+        // Already withdrawn, the member left meanwhile, or not theirs to
+        // withdraw: nothing to apply.
+        Err(ClubError::NotMember | ClubError::NotFound | ClubError::NotHost) => {
+            return Ok(Received::Ignored);
+        }
+        Err(e) => return Err(e),
+    }
+    commit(state, club, Vec::new()).await?;
+    Ok(Received::Applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::book_club::domain::ProposalInput;
+
+    async fn state_as(library_uuid: &str) -> AppState {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let state = AppState::new(db);
+        state.identity_service.init(library_uuid).await.unwrap();
+        state
+    }
+
+    async fn peer_for(state: &AppState, library_uuid: &str) -> peer::Model {
+        let now = now();
+        peer::ActiveModel {
+            name: Set(library_uuid.to_uppercase()),
+            url: Set(format!("http://{library_uuid}.local:8000")),
+            library_uuid: Set(Some(library_uuid.to_string())),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(state.db())
+        .await
+        .unwrap()
+    }
+
+    fn member(library_uuid: &str) -> ClubMember {
+        ClubMember {
+            library_uuid: library_uuid.to_string(),
+            name: library_uuid.to_uppercase(),
+        }
+    }
+
+    fn club() -> ClubState {
+        ClubState::new(
+            "c1".to_string(),
+            "Thursday readers",
+            None,
+            member("host"),
+            vec![member("ann"), member("bob")],
+            "2026-10-01T18:00:00Z",
+        )
+        .unwrap()
+    }
+
+    fn propose(title: &str) -> serde_json::Value {
+        let action = ProposalInput {
+            title: title.to_string(),
+            author: None,
+            isbn: None,
+        }
+        .into_action("2026-10-02T09:00:00Z")
+        .unwrap();
+        serde_json::to_value(ActionMessage {
+            club_id: "c1".to_string(),
+            action,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_member_keeps_only_newer_states_sent_by_the_host() {
+        let state = state_as("ann").await;
+        let host = peer_for(&state, "host").await;
+        let bob = peer_for(&state, "bob").await;
+        let mut sent = club();
+
+        // Another member cannot speak for the host.
+        let payload = serde_json::to_value(&sent).unwrap();
+        let received = receive_state(&state, &bob, &payload).await.unwrap();
+        assert_eq!(received, Received::Ignored);
+        assert!(repo(&state).load("c1").await.unwrap().is_none());
+
+        let received = receive_state(&state, &host, &payload).await.unwrap();
+        assert_eq!(received, Received::Applied);
+        assert_eq!(get(&state, "c1").await.unwrap(), sent);
+
+        // Redelivered, or overtaken by a later state: kept as it is.
+        let received = receive_state(&state, &host, &payload).await.unwrap();
+        assert_eq!(received, Received::Stale);
+        let mut older = sent.clone();
+        older.club.revision -= 1;
+        older.club.name = "Older name".to_string();
+        let payload = serde_json::to_value(&older).unwrap();
+        let received = receive_state(&state, &host, &payload).await.unwrap();
+        assert_eq!(received, Received::Stale);
+        assert_eq!(
+            get(&state, "c1").await.unwrap().club.name,
+            "Thursday readers"
+        );
+
+        sent.club.name = "Friday readers".to_string();
+        sent.bump("2026-10-03T18:00:00Z");
+        let payload = serde_json::to_value(&sent).unwrap();
+        let received = receive_state(&state, &host, &payload).await.unwrap();
+        assert_eq!(received, Received::Applied);
+        assert_eq!(get(&state, "c1").await.unwrap(), sent);
+    }
+
+    #[tokio::test]
+    async fn the_host_applies_actions_of_members_only() {
+        let state = state_as("host").await;
+        let ann = peer_for(&state, "ann").await;
+        let eve = peer_for(&state, "eve").await;
+        repo(&state).save(&club()).await.unwrap();
+
+        let received = receive_action(&state, &eve, &propose("Le Horla"))
+            .await
+            .unwrap();
+        assert_eq!(received, Received::Ignored);
+        let stored = get(&state, "c1").await.unwrap();
+        assert!(stored.proposals.is_empty());
+        assert_eq!(stored.club.revision, 1);
+
+        let received = receive_action(&state, &ann, &propose("Bel-Ami"))
+            .await
+            .unwrap();
+        assert_eq!(received, Received::Applied);
+        let stored = get(&state, "c1").await.unwrap();
+        assert_eq!(stored.proposals.len(), 1);
+        assert_eq!(stored.proposals[0].title, "Bel-Ami");
+        assert_eq!(stored.proposals[0].proposed_by, "ann");
+        assert_eq!(stored.club.revision, 2);
+    }
+}
@@ -0,0 +1,110 @@
+//! Book Files - domain types and repository trait
+//!
+//! Framework-free layer: no SeaORM, no Axum.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::DomainError;
+
+/// Largest accepted upload (2 GiB): room for a long unabridged audiobook.
+pub const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Accepted file extensions with their MIME type. The extension is the only
+/// signal used: clients send unreliable `Content-Type`s for these formats.
+pub const ATTACHMENT_FORMATS: [(&str, &str); 11] = [
+    ("epub", "application/epub+zip"),
+    ("pdf", "application/pdf"),
+    ("mobi", "application/x-mobipocket-ebook"),
+    ("azw3", "application/vnd.amazon.ebook"),
+    ("cbz", "application/vnd.comicbook+zip"),
+    ("mp3", "audio/mpeg"),
+    ("m4a", "audio/mp4"),
+    ("m4b", "audio/mp4"),
+    ("ogg", "audio/ogg"),
+    ("opus", "audio/ogg"),
+    ("flac", "audio/flac"),
+];
+
+/// `(format, mime_type)` for an uploaded file name, from its extension
+/// (case-insensitive). `None` for anything outside [`ATTACHMENT_FORMATS`].
+pub fn format_for_filename(filename: &str) -> Option<(&'static str, &'static str)> {
+    let (_, ext) = filename.rsplit_once('.')?;
+    let ext = ext.to_ascii_lowercase();
+    ATTACHMENT_FORMATS
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .copied()
+}
+
+/// Reduce a client-supplied file name to its last path component, so it is
+/// safe to echo in `Content-Disposition`. The name is display-only: blobs are
+/// stored under the generated file id, never under this name.
+pub fn sanitize_filename(raw: &str) -> String {
+    let name = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    name.chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// A file attached to a book.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BookFile {
+    pub id: String,
+    pub book_id: String,
+    /// Original file name, as uploaded.
+    pub filename: String,
+    /// Extension from [`ATTACHMENT_FORMATS`] (`epub`, `m4b`, ...).
+    pub format: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    /// Lowercase hex SHA-256 of the stored bytes (also served as the `ETag`).
+    pub sha256: String,
+    pub created_at: String,
+}
+
+#[async_trait]
+pub trait BookFileRepository: Send + Sync {
+    /// List the files of a book, oldest first.
+    async fn find_by_book_id(&self, book_id: &str) -> Result<Vec<BookFile>, DomainError>;
+
+    /// Find a single file by its ID.
+    async fn find_by_id(&self, id: &str) -> Result<Option<BookFile>, DomainError>;
+
+    /// Every attached file, grouped by book (ordered by book, then upload).
+    async fn find_all(&self) -> Result<Vec<BookFile>, DomainError>;
+
+    /// Record a stored file.
+    async fn create(&self, file: BookFile) -> Result<BookFile, DomainError>;
+
+    /// Delete a file row by ID.
+    async fn delete(&self, id: &str) -> Result<(), DomainError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_is_taken_from_the_extension() {
+        assert_eq!(
+            format_for_filename("Les Misérables.EPUB"),
+            Some(("epub", "application/epub+zip"))
+        );
+        assert_eq!(
+            format_for_filename("part.1.m4b"),
+            Some(("m4b", "audio/mp4"))
+        );
+        assert_eq!(format_for_filename("notes.txt"), None);
+        assert_eq!(format_for_filename("epub"), None);
+    }
+
+    #[test]
+    fn sanitize_keeps_only_the_last_component() {
+        assert_eq!(sanitize_filename("../../etc/passwd.epub"), "passwd.epub");
+        assert_eq!(sanitize_filename("C:\\Books\\a\"b.pdf"), "ab.pdf");
+        assert_eq!(sanitize_filename("  livre.mp3 "), "livre.mp3");
+    }
+}
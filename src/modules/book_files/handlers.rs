@@ -0,0 +1,301 @@
+//! Axum handlers for book file attachments.
+
+use axum::{
+    Json,
+    extract::{Multipart, Path, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sea_orm::EntityTrait;
+use serde_json::json;
+use tower::util::ServiceExt;
+
+use super::domain::{BookFile, BookFileRepository, format_for_filename, sanitize_filename};
+use super::repository::SeaOrmBookFileRepository;
+use super::storage::{self, StorageError};
+use crate::infrastructure::AppState;
+
+fn repo(state: &AppState) -> SeaOrmBookFileRepository {
+    SeaOrmBookFileRepository::new(state.db().clone())
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({"error": message.into()}))).into_response()
+}
+
+async fn book_exists(state: &AppState, book_id: &str) -> Result<bool, Response> {
+    crate::models::book::Entity::find_by_id(book_id.to_owned())
+        .one(state.db())
+        .await
+        .map(|b| b.is_some())
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Resolve `file_id` and check it belongs to `book_id`, so a file cannot be
+/// reached through another book's URL.
+async fn find_file(state: &AppState, book_id: &str, file_id: &str) -> Result<BookFile, Response> {
+    match repo(state).find_by_id(file_id).await {
+        Ok(Some(file)) if file.book_id == book_id => Ok(file),
+        Ok(_) => Err(error(StatusCode::NOT_FOUND, "File not found")),
+        Err(e) => Err(error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// `Content-Disposition` for a download: an ASCII `filename` fallback plus the
+/// exact UTF-8 name as an RFC 5987 `filename*`.
+fn content_disposition(filename: &str) -> HeaderValue {
+    let ascii: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let value = format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        ascii,
+        urlencoding::encode(filename)
+    );
+    HeaderValue::from_str(&value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
+}
+
+/// GET /books/:book_id/files
+#[utoipa::path(
+    get,
+    path = "/api/books/{book_id}/files",
+    tag = "files",
+    params(("book_id" = String, Path, description = "Book id")),
+    responses(
+        (status = 200, description = "Files attached to the book", body = [BookFile]),
+        (status = 404, description = "Book not found")
+    )
+)]
+pub async fn list_files(
+    State(state): State<AppState>,
+    Path(book_id): Path<String>,
+) -> impl IntoResponse {
+    match book_exists(&state, &book_id).await {
+        Ok(true) => {}
+        Ok(false) => return error(StatusCode::NOT_FOUND, "Book not found"),
+        Err(resp) => return resp,
+    }
+    match repo(&state).find_by_book_id(&book_id).await {
+        Ok(files) => (StatusCode::OK, Json(json!(files))).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// POST /books/:book_id/files (multipart, first part carrying a file name)
+#[utoipa::path(
+    post,
+    path = "/api/books/{book_id}/files",
+    tag = "files",
+    params(("book_id" = String, Path, description = "Book id")),
+    request_body(content = String, description = "Multipart upload of an ebook or audiobook file", content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "File attached", body = BookFile),
+        (status = 404, description = "Book not found"),
+        (status = 409, description = "The same file is already attached"),
+        (status = 413, description = "File too large"),
+        (status = 415, description = "Unsupported file type")
+    )
+)]
+pub async fn upload_file(
+    State(state): State<AppState>,
+    Path(book_id): Path<String>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    match book_exists(&state, &book_id).await {
+        Ok(true) => {}
+        Ok(false) => return error(StatusCode::NOT_FOUND, "Book not found"),
+        Err(resp) => return resp,
+    }
+
+    let field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.file_name().is_some() => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return error(StatusCode::BAD_REQUEST, "No file in request"),
+            Err(e) => return error(StatusCode::BAD_REQUEST, e.body_text()),
+        }
+    };
+
+    let filename = sanitize_filename(field.file_name().unwrap_or_default());
+    let Some((format, mime_type)) = format_for_filename(&filename) else {
+        return error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Unsupported file type (expected an ebook or audiobook file)",
+        );
+    };
+
+    let root = storage::media_root();
+    let file_id = crate::utils::uuid_gen::new_uuid_v7();
+    let dest = storage::blob_path(&root, &book_id, &file_id, format);
+    let blob = match storage::write_blob(&dest, field).await {
+        Ok(blob) => blob,
+        Err(e) => {
+            let status = match e {
+                StorageError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+                StorageError::Empty | StorageError::Stream(_) => StatusCode::BAD_REQUEST,
+                StorageError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            return error(status, e.to_string());
+        }
+    };
+
+    // The same bytes attached twice to one book are almost always a double
+    // tap: keep the first copy and point the client at it.
+    let existing = match repo(&state).find_by_book_id(&book_id).await {
+        Ok(files) => files,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&dest).await;
+            return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    };
+    if let Some(dup) = existing.into_iter().find(|f| f.sha256 == blob.sha256) {
+        let _ = tokio::fs::remove_file(&dest).await;
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({"error": "This file is already attached", "file": dup})),
+        )
+            .into_response();
+    }
+
+    let file = BookFile {
+        id: file_id,
+        book_id,
+        filename,
+        format: format.to_string(),
+        mime_type: mime_type.to_string(),
+        size_bytes: blob.size_bytes as i64,
+        sha256: blob.sha256,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    match repo(&state).create(file).await {
+        Ok(file) => (StatusCode::CREATED, Json(json!(file))).into_response(),
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&dest).await;
+            error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+/// GET /books/:book_id/files/:file_id
+///
+/// Served through `tower_http::ServeFile`, so `Range` requests work and audio
+/// players can seek without downloading the whole file.
+#[utoipa::path(
+    get,
+    path = "/api/books/{book_id}/files/{file_id}",
+    tag = "files",
+    params(
+        ("book_id" = String, Path, description = "Book id"),
+        ("file_id" = String, Path, description = "File id")
+    ),
+    responses(
+        (status = 200, description = "File bytes; `Range` requests are honoured"),
+        (status = 404, description = "File not found")
+    )
+)]
+pub async fn download_file(
+    State(state): State<AppState>,
+    Path((book_id, file_id)): Path<(String, String)>,
+    request: Request,
+) -> Response {
+    match find_file(&state, &book_id, &file_id).await {
+        Ok(file) => serve_file(&file, request).await,
+        Err(resp) => resp,
+    }
+}
+
+/// The stored bytes of `file`, with its type, name and checksum as headers.
+pub(super) async fn serve_file(file: &BookFile, request: Request) -> Response {
+    let path = storage::blob_path(
+        &storage::media_root(),
+        &file.book_id,
+        &file.id,
+        &file.format,
+    );
+
+    let mut response = match tower_http::services::ServeFile::new(path)
+        .oneshot(request)
+        .await
+    {
+        Ok(resp) => resp.map(axum::body::Body::new),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if response.status() == StatusCode::NOT_FOUND {
+        tracing::warn!("book file {}: blob missing from media dir", file.id);
+        return error(StatusCode::NOT_FOUND, "File not found");
+    }
+
+    let headers = response.headers_mut();
+    if let Ok(mime) = HeaderValue::from_str(&file.mime_type) {
+        headers.insert(header::CONTENT_TYPE, mime);
+    }
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        content_disposition(&file.filename),
+    );
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", file.sha256)) {
+        headers.insert(header::ETAG, etag);
+    }
+    response
+}
+
+/// DELETE /books/:book_id/files/:file_id
+#[utoipa::path(
+    delete,
+    path = "/api/books/{book_id}/files/{file_id}",
+    tag = "files",
+    params(
+        ("book_id" = String, Path, description = "Book id"),
+        ("file_id" = String, Path, description = "File id")
+    ),
+    responses(
+        (status = 200, description = "File deleted"),
+        (status = 404, description = "File not found")
+    )
+)]
+pub async fn delete_file(
+    State(state): State<AppState>,
+    Path((book_id, file_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let file = match find_file(&state, &book_id, &file_id).await {
+        Ok(file) => file,
+        Err(resp) => return resp,
+    };
+    match repo(&state).delete(&file.id).await {
+        Ok(()) => {
+            let path = storage::blob_path(&storage::media_root(), &book_id, &file.id, &file.format);
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!("book file {}: failed to remove blob: {e}", file.id);
+            }
+            (
+                StatusCode::OK,
+                Json(json!({"message": "File deleted successfully"})),
+            )
+                .into_response()
+        }
+        Err(crate::domain::DomainError::NotFound) => error(StatusCode::NOT_FOUND, "File not found"),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_disposition_keeps_utf8_name_in_extended_parameter() {
+        let value = content_disposition("Les Misérables.epub");
+        assert_eq!(
+            value.to_str().unwrap(),
+            "attachment; filename=\"Les Mis_rables.epub\"; \
+             filename*=UTF-8''Les%20Mis%C3%A9rables.epub"
+        );
+    }
+}
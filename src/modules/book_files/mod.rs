@@ -0,0 +1,112 @@
+//! Book Files - self-contained extension module
+//!
+//! Attaches actual ebook and audiobook files (epub, pdf, mp3, m4b, ...) to a
+//! book, where `books.digital_formats` only records which formats exist.
+//! Files are stored under the media directory with their SHA-256, listed and
+//! downloaded via `/books/:book_id/files`, and published as an OPDS
+//! acquisition feed at `/opds` so e-reader apps can browse and fetch them.
+//! The feed and its downloads are reachable from the LAN with a reader token
+//! (see `tokens`); everything else is owner-only.
+//!
+//! `book_files` is a LOCAL table (like `book_notes`): the blobs stay on the
+//! device that received them and are never replicated by account sync.
+//!
+//! This module follows the "extension plugin" pattern (ADR-005).
+//!
+//! Integration points:
+//!   - `api/mod.rs`:  .merge(modules::book_files::routes()) among the owner
+//!     routes, .merge(modules::book_files::public_routes()) among the public ones
+//!   - `infrastructure/db.rs`:  modules::book_files::migrate(&db).await?;
+//!   - `referential_integrity::delete_book_cascade` drops the rows, and
+//!     `book_service::delete_book` removes the stored blobs.
+
+pub mod domain;
+pub(crate) mod handlers;
+pub mod models;
+pub(crate) mod opds;
+pub mod repository;
+pub mod storage;
+pub mod tokens;
+
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+};
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+
+use crate::infrastructure::AppState;
+
+/// Returns the owner-only Axum routes for this module.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/books/:book_id/files", get(handlers::list_files))
+        .route(
+            "/books/:book_id/files",
+            // Audiobooks run to hundreds of MB: lift axum's 2 MB default for
+            // this route only; the handler enforces `MAX_FILE_SIZE` itself.
+            post(handlers::upload_file).layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/books/:book_id/files/:file_id",
+            get(handlers::download_file).delete(handlers::delete_file),
+        )
+        .route(
+            "/opds/tokens",
+            get(opds::list_tokens).post(opds::create_token),
+        )
+        .route(
+            "/opds/tokens/:id",
+            axum::routing::delete(opds::revoke_token),
+        )
+}
+
+/// Returns the routes e-readers on the LAN reach: the reader token is checked
+/// in the handlers.
+pub fn public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/opds", get(opds::catalog))
+        .route("/opds/files/:file_id", get(opds::download))
+}
+
+/// Run database migrations for this module.
+pub async fn migrate(db: &DatabaseConnection) -> Result<(), sea_orm::DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        "CREATE TABLE IF NOT EXISTS book_files (
+            id TEXT PRIMARY KEY NOT NULL,
+            book_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            format TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        "CREATE INDEX IF NOT EXISTS idx_book_files_book_id ON book_files(book_id, created_at)"
+            .to_owned(),
+    ))
+    .await?;
+
+    // OPDS reader tokens (see `tokens`): local, only the SHA-256 is stored.
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        "CREATE TABLE IF NOT EXISTS opds_tokens (
+            id TEXT PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            last_used_at TEXT
+        )"
+        .to_owned(),
+    ))
+    .await?;
+
+    Ok(())
+}
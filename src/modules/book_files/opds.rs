@@ -0,0 +1,394 @@
+//! OPDS 1.2 acquisition feed of the books that have attached files.
+//!
+//! One Atom entry per book, with one acquisition link per attached file, so an
+//! OPDS-capable reader (KOReader, Thorium, ...) pointed at `/api/opds` can
+//! browse the library's ebooks and audiobooks and download them directly.
+//! The reader logs in with a reader token as its password (see `tokens`),
+//! created by the owner at `/api/opds/tokens`.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use axum::{
+    Json,
+    extract::{FromRequestParts, Path, Request, State},
+    http::{StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use quick_xml::escape::escape;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::domain::{BookFile, BookFileRepository};
+use super::handlers::serve_file;
+use super::repository::SeaOrmBookFileRepository;
+use super::tokens;
+use crate::infrastructure::AppState;
+use crate::models::{Book, book, library_config};
+
+/// Media type of an OPDS acquisition feed.
+pub const ACQUISITION_FEED_TYPE: &str =
+    "application/atom+xml;profile=opds-catalog;kind=acquisition";
+
+/// Extractor admitting a request that carries a valid reader token.
+pub struct OpdsReader;
+
+#[async_trait]
+impl FromRequestParts<AppState> for OpdsReader {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // The challenge makes e-readers prompt for the credentials.
+        let unauthorized = |message: &str| {
+            (
+                StatusCode::UNAUTHORIZED,
+                [(
+                    header::WWW_AUTHENTICATE,
+                    r#"Basic realm="BiblioGenius OPDS""#,
+                )],
+                Json(json!({ "error": message })),
+            )
+                .into_response()
+        };
+        let Some(token) = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(tokens::token_from_authorization)
+        else {
+            return Err(unauthorized("Missing reader token"));
+        };
+
+        match tokens::authenticate(state.db(), &token).await {
+            Ok(true) => Ok(OpdsReader),
+            Ok(false) => Err(unauthorized("Invalid reader token")),
+            Err(e) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+                .into_response()),
+        }
+    }
+}
+
+/// GET /opds
+#[utoipa::path(
+    get,
+    path = "/api/opds",
+    tag = "files",
+    responses(
+        (status = 200, description = "OPDS 1.2 acquisition feed", content_type = "application/atom+xml"),
+        (status = 401, description = "Missing or invalid reader token")
+    )
+)]
+pub async fn catalog(_reader: OpdsReader, State(state): State<AppState>) -> Response {
+    let db = state.db();
+    let files = match SeaOrmBookFileRepository::new(db.clone()).find_all().await {
+        Ok(files) => files,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut by_book: HashMap<String, Vec<BookFile>> = HashMap::new();
+    for file in files {
+        by_book.entry(file.book_id.clone()).or_default().push(file);
+    }
+    let book_ids: Vec<String> = by_book.keys().cloned().collect();
+
+    let models = if book_ids.is_empty() {
+        Vec::new()
+    } else {
+        match book::Entity::find()
+            .filter(book::Column::Id.is_in(book_ids))
+            .order_by_asc(book::Column::Title)
+            .all(db)
+            .await
+        {
+            Ok(models) => models,
+            Err(e) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        }
+    };
+    let mut books = Book::populate_authors(db, models).await;
+    Book::rewrite_local_cover_urls(&mut books, None);
+
+    let title = library_config::Entity::find_by_id(1)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|c| c.name)
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| "BiblioGenius".to_string());
+
+    let xml = render_feed(&title, &books, &by_book, &chrono::Utc::now().to_rfc3339());
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, ACQUISITION_FEED_TYPE)],
+        xml,
+    )
+        .into_response()
+}
+
+/// GET /opds/files/:file_id
+///
+/// The acquisition link of the feed; `Range` requests work as on the owner's
+/// `/books/:book_id/files/:file_id`.
+#[utoipa::path(
+    get,
+    path = "/api/opds/files/{file_id}",
+    tag = "files",
+    params(("file_id" = String, Path, description = "File id")),
+    responses(
+        (status = 200, description = "File bytes; `Range` requests are honoured"),
+        (status = 401, description = "Missing or invalid reader token"),
+        (status = 404, description = "File not found")
+    )
+)]
+pub async fn download(
+    _reader: OpdsReader,
+    State(state): State<AppState>,
+    Path(file_id): Path<String>,
+    request: Request,
+) -> Response {
+    match SeaOrmBookFileRepository::new(state.db().clone())
+        .find_by_id(&file_id)
+        .await
+    {
+        Ok(Some(file)) => serve_file(&file, request).await,
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "File not found" })),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Request body for creating a reader token
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateOpdsTokenRequest {
+    /// Label of the reader, e.g. "Kobo in the reading room"
+    pub name: String,
+}
+
+/// GET /opds/tokens - Reader tokens (without the tokens themselves)
+#[utoipa::path(
+    get,
+    path = "/api/opds/tokens",
+    tag = "files",
+    responses(
+        (status = 200, description = "Reader tokens, oldest first", body = [OpdsToken])
+    )
+)]
+pub async fn list_tokens(State(state): State<AppState>) -> Response {
+    match tokens::list_tokens(state.db()).await {
+        Ok(list) => Json(list).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// POST /opds/tokens - Create a reader token, shown once
+#[utoipa::path(
+    post,
+    path = "/api/opds/tokens",
+    tag = "files",
+    request_body = CreateOpdsTokenRequest,
+    responses(
+        (status = 201, description = "Token created; `token` is not shown again"),
+        (status = 400, description = "Blank name")
+    )
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    Json(req): Json<CreateOpdsTokenRequest>,
+) -> Response {
+    if req.name.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "name must not be blank" })),
+        )
+            .into_response();
+    }
+    match tokens::create_token(state.db(), &req.name).await {
+        Ok((created, token)) => (
+            StatusCode::CREATED,
+            Json(json!({
+                "id": created.id,
+                "name": created.name,
+                "created_at": created.created_at,
+                "token": token,
+            })),
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// DELETE /opds/tokens/:id - Revoke a reader token
+#[utoipa::path(
+    delete,
+    path = "/api/opds/tokens/{id}",
+    tag = "files",
+    params(("id" = String, Path, description = "Token id")),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "No such token")
+    )
+)]
+pub async fn revoke_token(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match tokens::revoke_token(state.db(), &id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Render the feed. Books without an id or without files are skipped.
+pub fn render_feed(
+    title: &str,
+    books: &[Book],
+    files_by_book: &HashMap<String, Vec<BookFile>>,
+    updated: &str,
+) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(
+        r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/" xmlns:opds="http://opds-spec.org/2010/catalog">"#,
+    );
+    xml.push_str("<id>urn:bibliogenius:opds</id>");
+    xml.push_str(&format!("<title>{}</title>", escape(title)));
+    xml.push_str(&format!("<updated>{}</updated>", escape(updated)));
+    for rel in ["self", "start"] {
+        xml.push_str(&format!(
+            r#"<link rel="{}" href="/api/opds" type="{}"/>"#,
+            rel, ACQUISITION_FEED_TYPE
+        ));
+    }
+
+    for book in books {
+        let Some(id) = book.id.as_deref() else {
+            continue;
+        };
+        let Some(files) = files_by_book.get(id).filter(|f| !f.is_empty()) else {
+            continue;
+        };
+        xml.push_str(&render_entry(id, book, files, updated));
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn render_entry(id: &str, book: &Book, files: &[BookFile], fallback_updated: &str) -> String {
+    let mut entry = String::from("<entry>");
+    entry.push_str(&format!("<title>{}</title>", escape(book.title.as_str())));
+    entry.push_str(&format!("<id>urn:uuid:{}</id>", escape(id)));
+    let updated = book.updated_at.as_deref().unwrap_or(fallback_updated);
+    entry.push_str(&format!("<updated>{}</updated>", escape(updated)));
+    for name in book.authors.iter().flatten() {
+        entry.push_str(&format!(
+            "<author><name>{}</name></author>",
+            escape(name.as_str())
+        ));
+    }
+    if let Some(isbn) = book.isbn.as_deref().filter(|s| !s.is_empty()) {
+        entry.push_str(&format!(
+            "<dc:identifier>urn:isbn:{}</dc:identifier>",
+            escape(isbn)
+        ));
+    }
+    if let Some(language) = book.language.as_deref().filter(|s| !s.is_empty()) {
+        entry.push_str(&format!("<dc:language>{}</dc:language>", escape(language)));
+    }
+    if let Some(publisher) = book.publisher.as_deref().filter(|s| !s.is_empty()) {
+        entry.push_str(&format!(
+            "<dc:publisher>{}</dc:publisher>",
+            escape(publisher)
+        ));
+    }
+    if let Some(year) = book.publication_year {
+        entry.push_str(&format!("<dc:issued>{}</dc:issued>", year));
+    }
+    if let Some(summary) = book.summary.as_deref().filter(|s| !s.is_empty()) {
+        entry.push_str(&format!("<summary>{}</summary>", escape(summary)));
+    }
+    if let Some(cover) = book.cover_url.as_deref().filter(|s| !s.is_empty()) {
+        entry.push_str(&format!(
+            r#"<link rel="http://opds-spec.org/image" href="{}"/>"#,
+            escape(cover)
+        ));
+    }
+    for file in files {
+        entry.push_str(&format!(
+            r#"<link rel="http://opds-spec.org/acquisition" href="/api/opds/files/{}" type="{}" title="{}" length="{}"/>"#,
+            escape(file.id.as_str()),
+            escape(file.mime_type.as_str()),
+            escape(file.filename.as_str()),
+            file.size_bytes
+        ));
+    }
+    entry.push_str("</entry>");
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(id: &str, book_id: &str, filename: &str, mime: &str) -> BookFile {
+        BookFile {
+            id: id.to_string(),
+            book_id: book_id.to_string(),
+            filename: filename.to_string(),
+            format: filename.rsplit('.').next().unwrap().to_string(),
+            mime_type: mime.to_string(),
+            size_bytes: 42,
+            sha256: "00".repeat(32),
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn feed_has_one_acquisition_link_per_file_and_skips_bare_books() {
+        let with_files = Book {
+            id: Some("b1".to_string()),
+            title: "Tom & Jerry <1>".to_string(),
+            authors: Some(vec!["Hanna".to_string(), "Barbera".to_string()]),
+            isbn: Some("9780000000001".to_string()),
+            ..Default::default()
+        };
+        let without_files = Book {
+            id: Some("b2".to_string()),
+            title: "Not digital".to_string(),
+            ..Default::default()
+        };
+        let mut files = HashMap::new();
+        files.insert(
+            "b1".to_string(),
+            vec![
+                file("f1", "b1", "tom.epub", "application/epub+zip"),
+                file("f2", "b1", "tom.m4b", "audio/mp4"),
+            ],
+        );
+
+        let xml = render_feed(
+            "Ma bibliothèque",
+            &[with_files, without_files],
+            &files,
+            "2026-01-02T00:00:00+00:00",
+        );
+
+        assert!(xml.contains("<title>Tom &amp; Jerry &lt;1&gt;</title>"));
+        assert!(xml.contains("<author><name>Hanna</name></author>"));
+        assert!(xml.contains("<dc:identifier>urn:isbn:9780000000001</dc:identifier>"));
+        assert_eq!(xml.matches("http://opds-spec.org/acquisition").count(), 2);
+        assert!(xml.contains(r#"href="/api/opds/files/f2" type="audio/mp4""#));
+        assert!(!xml.contains("Not digital"));
+        assert_eq!(xml.matches("<entry>").count(), 1);
+    }
+}
@@ -0,0 +1,86 @@
+//! SeaORM implementation of BookFileRepository.
+
+use async_trait::async_trait;
+use sea_orm::*;
+
+use super::domain::{BookFile, BookFileRepository};
+use super::models;
+use crate::domain::DomainError;
+
+pub struct SeaOrmBookFileRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmBookFileRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+fn model_to_domain(m: models::Model) -> BookFile {
+    BookFile {
+        id: m.id,
+        book_id: m.book_id,
+        filename: m.filename,
+        format: m.format,
+        mime_type: m.mime_type,
+        size_bytes: m.size_bytes,
+        sha256: m.sha256,
+        created_at: m.created_at,
+    }
+}
+
+#[async_trait]
+impl BookFileRepository for SeaOrmBookFileRepository {
+    async fn find_by_book_id(&self, book_id: &str) -> Result<Vec<BookFile>, DomainError> {
+        let files = models::Entity::find()
+            .filter(models::Column::BookId.eq(book_id))
+            .order_by_asc(models::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(files.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<BookFile>, DomainError> {
+        let file = models::Entity::find_by_id(id.to_owned())
+            .one(&self.db)
+            .await?;
+        Ok(file.map(model_to_domain))
+    }
+
+    async fn find_all(&self) -> Result<Vec<BookFile>, DomainError> {
+        let files = models::Entity::find()
+            .order_by_asc(models::Column::BookId)
+            .order_by_asc(models::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(files.into_iter().map(model_to_domain).collect())
+    }
+
+    async fn create(&self, file: BookFile) -> Result<BookFile, DomainError> {
+        let active = models::ActiveModel {
+            id: Set(file.id.clone()),
+            book_id: Set(file.book_id.clone()),
+            filename: Set(file.filename.clone()),
+            format: Set(file.format.clone()),
+            mime_type: Set(file.mime_type.clone()),
+            size_bytes: Set(file.size_bytes),
+            sha256: Set(file.sha256.clone()),
+            created_at: Set(file.created_at.clone()),
+        };
+        models::Entity::insert(active)
+            .exec_without_returning(&self.db)
+            .await?;
+        Ok(file)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), DomainError> {
+        let result = models::Entity::delete_by_id(id.to_owned())
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(DomainError::NotFound);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+//! On-disk storage for book file attachments.
+//!
+//! Layout: `<media dir>/books/<book_id>/<file_id>.<format>`. The blob is named
+//! after the generated file id, never after the client's file name, so an
+//! upload cannot choose where it lands. Writes go to a `.part` sibling that is
+//! renamed into place only once the whole body was received and hashed.
+
+use std::path::{Path, PathBuf};
+
+use axum::body::Bytes;
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use super::domain::MAX_FILE_SIZE;
+
+/// Error type for blob storage operations
+#[derive(Debug)]
+pub enum StorageError {
+    /// The body exceeded [`MAX_FILE_SIZE`].
+    TooLarge,
+    /// The body was empty.
+    Empty,
+    /// The upload stream failed mid-way (client disconnect, bad multipart).
+    Stream(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::TooLarge => {
+                write!(f, "File exceeds {} bytes", MAX_FILE_SIZE)
+            }
+            StorageError::Empty => write!(f, "File is empty"),
+            StorageError::Stream(msg) => write!(f, "Upload failed: {}", msg),
+            StorageError::Io(e) => write!(f, "Storage error: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+/// Size and checksum of a blob that was written to disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBlob {
+    pub size_bytes: u64,
+    /// Lowercase hex SHA-256.
+    pub sha256: String,
+}
+
+/// Root of the media directory (`infrastructure::paths::media_dir`).
+pub fn media_root() -> PathBuf {
+    crate::infrastructure::paths::media_dir()
+}
+
+/// Directory holding every file of one book.
+pub fn book_dir(root: &Path, book_id: &str) -> PathBuf {
+    root.join("books").join(book_id)
+}
+
+/// Path of one stored file.
+pub fn blob_path(root: &Path, book_id: &str, file_id: &str, format: &str) -> PathBuf {
+    book_dir(root, book_id).join(format!("{}.{}", file_id, format))
+}
+
+/// Stream `body` into `dest`, hashing as it goes. On any failure the partial
+/// file is removed and `dest` is left untouched.
+pub async fn write_blob<S, E>(dest: &Path, body: S) -> Result<StoredBlob, StorageError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let mut part = dest.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+
+    let result = write_part(&part, body).await;
+    match result {
+        Ok(blob) => {
+            tokio::fs::rename(&part, dest).await?;
+            Ok(blob)
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part).await;
+            Err(e)
+        }
+    }
+}
+
+async fn write_part<S, E>(part: &Path, body: S) -> Result<StoredBlob, StorageError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    let mut file = tokio::fs::File::create(part).await?;
+    let mut hasher = Sha256::new();
+    let mut size: u64 = 0;
+
+    futures::pin_mut!(body);
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| StorageError::Stream(e.to_string()))?;
+        size += chunk.len() as u64;
+        if size > MAX_FILE_SIZE {
+            return Err(StorageError::TooLarge);
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+    if size == 0 {
+        return Err(StorageError::Empty);
+    }
+    file.flush().await?;
+
+    Ok(StoredBlob {
+        size_bytes: size,
+        sha256: hex::encode(hasher.finalize()),
+    })
+}
+
+/// Best-effort removal of a book's media directory (after the book was
+/// deleted). A missing directory is not an error.
+pub async fn remove_book_dir(root: &Path, book_id: &str) {
+    let dir = book_dir(root, book_id);
+    if let Err(e) = tokio::fs::remove_dir_all(&dir).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("book {book_id}: failed to remove media dir: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(parts: &[&'static [u8]]) -> impl Stream<Item = Result<Bytes, String>> {
+        futures::stream::iter(
+            parts
+                .iter()
+                .map(|p| Ok(Bytes::from_static(p)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn write_blob_hashes_and_renames_into_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = blob_path(dir.path(), "book-1", "file-1", "epub");
+
+        let blob = write_blob(&dest, chunks(&[b"hello ", b"world"]))
+            .await
+            .unwrap();
+
+        assert_eq!(blob.size_bytes, 11);
+        assert_eq!(
+            blob.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(tokio::fs::read(&dest).await.unwrap(), b"hello world");
+    }
+
+    #[tokio::test]
+    async fn failed_stream_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = blob_path(dir.path(), "book-1", "file-1", "mp3");
+        let body = futures::stream::iter(vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err("connection reset".to_string()),
+        ]);
+
+        let err = write_blob(&dest, body).await.unwrap_err();
+
+        assert!(matches!(err, StorageError::Stream(_)));
+        let leftovers = std::fs::read_dir(book_dir(dir.path(), "book-1"))
+            .unwrap()
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn empty_body_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = blob_path(dir.path(), "book-1", "file-1", "pdf");
+        let err = write_blob(&dest, chunks(&[])).await.unwrap_err();
+        assert!(matches!(err, StorageError::Empty));
+        assert!(!dest.exists());
+    }
+}
@@ -0,0 +1,143 @@
+//! OPDS reader tokens.
+//!
+//! The owner API is loopback-only, but an e-reader browsing the OPDS feed sits
+//! elsewhere on the LAN. A reader token opens the feed and the file downloads
+//! (`/api/opds/*`) and nothing else. Readers send it as the password of HTTP
+//! Basic auth, the only scheme KOReader and Thorium ask for; a `Bearer` header
+//! works too. Like kiosk tokens, only the SHA-256 of a token is stored.
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::services::kiosk::{generate_token, hash_token};
+
+/// An OPDS reader token, without the token itself.
+#[derive(Debug, Clone, Serialize, FromQueryResult, utoipa::ToSchema)]
+pub struct OpdsToken {
+    pub id: String,
+    /// Label of the reader, e.g. "Kobo in the reading room"
+    pub name: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+/// Create a reader token. The returned token is the only copy of it.
+pub async fn create_token(
+    db: &DatabaseConnection,
+    name: &str,
+) -> Result<(OpdsToken, String), DbErr> {
+    let token = generate_token();
+    let created = OpdsToken {
+        id: crate::utils::uuid_gen::new_uuid_v7(),
+        name: name.trim().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        last_used_at: None,
+    };
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "INSERT INTO opds_tokens (id, name, token_hash, created_at) VALUES (?, ?, ?, ?)",
+        [
+            created.id.clone().into(),
+            created.name.clone().into(),
+            hash_token(&token).into(),
+            created.created_at.clone().into(),
+        ],
+    ))
+    .await?;
+    Ok((created, token))
+}
+
+/// Reader tokens, oldest first.
+pub async fn list_tokens(db: &DatabaseConnection) -> Result<Vec<OpdsToken>, DbErr> {
+    OpdsToken::find_by_statement(Statement::from_string(
+        db.get_database_backend(),
+        "SELECT id, name, created_at, last_used_at FROM opds_tokens ORDER BY created_at",
+    ))
+    .all(db)
+    .await
+}
+
+/// Revoke a reader token. Returns false when there was no such token.
+pub async fn revoke_token(db: &DatabaseConnection, id: &str) -> Result<bool, DbErr> {
+    let res = db
+        .execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "DELETE FROM opds_tokens WHERE id = ?",
+            [id.into()],
+        ))
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// Whether `token` is a reader token, its last use recorded when it is.
+pub async fn authenticate(db: &DatabaseConnection, token: &str) -> Result<bool, DbErr> {
+    if token.is_empty() {
+        return Ok(false);
+    }
+    let res = db
+        .execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "UPDATE opds_tokens SET last_used_at = ? WHERE token_hash = ?",
+            [
+                chrono::Utc::now().to_rfc3339().into(),
+                hash_token(token).into(),
+            ],
+        ))
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// The token carried by an `Authorization` header: the password of `Basic`
+/// credentials (the user name is ignored), or a `Bearer` token.
+pub fn token_from_authorization(header: &str) -> Option<String> {
+    use base64::Engine;
+
+    if let Some(token) = header.strip_prefix("Bearer ") {
+        return Some(token.trim().to_string());
+    }
+    let encoded = header.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let credentials = String::from_utf8(decoded).ok()?;
+    let (_, password) = credentials.split_once(':')?;
+    Some(password.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_is_the_basic_password_or_the_bearer() {
+        // "kobo:s3cr:et" — the password may itself hold a colon.
+        assert_eq!(
+            token_from_authorization("Basic a29ibzpzM2NyOmV0").as_deref(),
+            Some("s3cr:et")
+        );
+        assert_eq!(
+            token_from_authorization("Bearer abc").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(token_from_authorization("Basic !!!"), None);
+        assert_eq!(token_from_authorization("Digest x"), None);
+    }
+
+    #[tokio::test]
+    async fn only_live_tokens_authenticate() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let (created, token) = create_token(&db, " Kobo ").await.unwrap();
+        assert_eq!(created.name, "Kobo");
+
+        assert!(authenticate(&db, &token).await.unwrap());
+        assert!(!authenticate(&db, "guess").await.unwrap());
+        assert!(!authenticate(&db, "").await.unwrap());
+        let listed = list_tokens(&db).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_used_at.is_some());
+
+        assert!(revoke_token(&db, &created.id).await.unwrap());
+        assert!(!authenticate(&db, &token).await.unwrap());
+        assert!(!revoke_token(&db, &created.id).await.unwrap());
+    }
+}
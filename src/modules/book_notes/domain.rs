@@ -11,7 +11,7 @@ use crate::domain::DomainError;
 pub const MAX_CONTENT_LENGTH: usize = 2000;
 
 /// A reading note attached to a book.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct BookNote {
     pub id: i32,
     pub book_id: String,
@@ -22,14 +22,14 @@ pub struct BookNote {
 }
 
 /// Input for creating a new note.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct CreateBookNoteInput {
     pub content: String,
     pub page: Option<i32>,
 }
 
 /// Input for updating an existing note.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateBookNoteInput {
     pub content: String,
     pub page: Option<i32>,
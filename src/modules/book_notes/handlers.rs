@@ -19,6 +19,15 @@ fn repo(state: &AppState) -> SeaOrmBookNoteRepository {
 }
 
 /// GET /books/:id/notes
+#[utoipa::path(
+    get,
+    path = "/api/books/{book_id}/notes",
+    tag = "notes",
+    params(("book_id" = String, Path, description = "Book id")),
+    responses(
+        (status = 200, description = "Notes of the book, newest first", body = [BookNote])
+    )
+)]
 pub async fn list_notes(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
@@ -34,6 +43,17 @@ pub async fn list_notes(
 }
 
 /// POST /books/:id/notes
+#[utoipa::path(
+    post,
+    path = "/api/books/{book_id}/notes",
+    tag = "notes",
+    params(("book_id" = String, Path, description = "Book id")),
+    request_body = CreateBookNoteInput,
+    responses(
+        (status = 201, description = "Note created", body = BookNote),
+        (status = 400, description = "Empty or too long")
+    )
+)]
 pub async fn create_note(
     State(state): State<AppState>,
     Path(book_id): Path<String>,
@@ -81,6 +101,18 @@ pub async fn create_note(
 }
 
 /// PUT /book-notes/:id
+#[utoipa::path(
+    put,
+    path = "/api/book-notes/{id}",
+    tag = "notes",
+    params(("id" = i32, Path, description = "Note id")),
+    request_body = UpdateBookNoteInput,
+    responses(
+        (status = 200, description = "Note updated", body = BookNote),
+        (status = 400, description = "Empty or too long"),
+        (status = 404, description = "Note not found")
+    )
+)]
 pub async fn update_note(
     State(state): State<AppState>,
     Path(id): Path<i32>,
@@ -132,6 +164,16 @@ pub async fn update_note(
 }
 
 /// DELETE /book-notes/:id
+#[utoipa::path(
+    delete,
+    path = "/api/book-notes/{id}",
+    tag = "notes",
+    params(("id" = i32, Path, description = "Note id")),
+    responses(
+        (status = 200, description = "Note deleted"),
+        (status = 404, description = "Note not found")
+    )
+)]
 pub async fn delete_note(State(state): State<AppState>, Path(id): Path<i32>) -> impl IntoResponse {
     match repo(&state).delete(id).await {
         Ok(()) => {
@@ -251,7 +251,7 @@ pub(crate) async fn sync_all_peer_scores(state: &AppState) {
         return;
     }
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()
         .unwrap_or_default();
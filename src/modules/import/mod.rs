@@ -1,4 +1,13 @@
-use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use strsim::jaro_winkler;
+
+use crate::models::{author, book, book_authors};
+use crate::utils::dedup_key::{book_dedup_key, normalize_text};
+use crate::utils::isbn::to_isbn13;
 
 #[derive(Debug, Deserialize)]
 pub struct CreateBookRequest {
@@ -6,6 +15,10 @@ pub struct CreateBookRequest {
     pub isbn: Option<String>,
     pub publisher: Option<String>,
     pub publication_year: Option<i32>,
+    /// First author as the export names it; used to spot duplicates and
+    /// linked to the books created.
+    #[serde(default)]
+    pub author: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,7 +26,7 @@ struct GoodreadsBook {
     #[serde(rename = "Title")]
     title: String,
     #[serde(rename = "Author")]
-    _author: String,
+    author: String,
     #[serde(rename = "ISBN13")]
     isbn13: Option<String>,
     #[serde(rename = "ISBN")]
@@ -29,7 +42,7 @@ struct LibraryThingBook {
     #[serde(rename = "Title")]
     title: String,
     #[serde(rename = "Primary Author")]
-    _author: String,
+    author: String,
     #[serde(rename = "ISBN")]
     isbn: Option<String>,
     #[serde(rename = "Publication")]
@@ -43,7 +56,7 @@ struct BabelioBook {
     #[serde(rename = "Titre")]
     title: String,
     #[serde(rename = "Auteur")]
-    _author: String,
+    author: String,
     #[serde(rename = "EAN")]
     ean: Option<String>, // ISBN 13
     #[serde(rename = "Editeur")]
@@ -52,36 +65,483 @@ struct BabelioBook {
     date_publication: Option<String>,
 }
 
-pub fn parse_import_file(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
-    // 1. Try to detect format based on headers
+/// The export formats [`parse_import_file`] recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    Goodreads,
+    LibraryThing,
+    Babelio,
+    InventaireCsv,
+    InventaireJson,
+    IsbnList,
+}
+
+impl SourceFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Goodreads => "goodreads",
+            Self::LibraryThing => "library_thing",
+            Self::Babelio => "babelio",
+            Self::InventaireCsv => "inventaire_csv",
+            Self::InventaireJson => "inventaire_json",
+            Self::IsbnList => "isbn_list",
+        }
+    }
+}
+
+/// The format of `content`, from its header line or its shape.
+pub fn detect_format(content: &[u8]) -> Option<SourceFormat> {
     let content_str = String::from_utf8_lossy(content);
     let first_line = content_str.lines().next().unwrap_or("").trim();
 
     if first_line.contains("ISBN13") && first_line.contains("Title") {
-        return parse_goodreads_csv(content);
+        return Some(SourceFormat::Goodreads);
     } else if first_line.contains("Primary Author") && first_line.contains("ISBN") {
-        return parse_librarything_csv(content);
+        return Some(SourceFormat::LibraryThing);
     } else if first_line.contains("Titre") && first_line.contains("EAN") {
-        return parse_babelio_csv(content);
+        return Some(SourceFormat::Babelio);
     } else if first_line.contains("Item URL") && first_line.contains("Edition ISBN-13") {
-        return parse_inventaire_csv(content);
+        return Some(SourceFormat::InventaireCsv);
     } else if content_str.trim_start().starts_with('{') && content_str.contains("\"items\"") {
-        return parse_inventaire_json(content);
+        return Some(SourceFormat::InventaireJson);
     }
 
-    // 2. Fallback: Treat as raw ISBN list if it looks like a list of numbers
-    // Check if first few lines look like ISBNs (10 or 13 digits)
+    // Fallback: a raw ISBN list if the first lines look like ISBNs
     let is_isbn_list = content_str.lines().take(5).all(|line| {
         line.trim()
             .chars()
             .all(|c| c.is_numeric() || c == '-' || c == 'X')
     });
+    is_isbn_list.then_some(SourceFormat::IsbnList)
+}
+
+/// The column layout of `content`: the header line of a CSV export, the
+/// format name for headerless ones. Two files with the same layout map
+/// their columns the same way.
+pub fn layout_signature(content: &[u8], format: SourceFormat) -> String {
+    match format {
+        SourceFormat::InventaireJson | SourceFormat::IsbnList => format.as_str().to_string(),
+        _ => String::from_utf8_lossy(content)
+            .lines()
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_start_matches('\u{feff}')
+            .to_string(),
+    }
+}
+
+pub fn parse_import_file(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
+    let books = match detect_format(content) {
+        Some(SourceFormat::Goodreads) => parse_goodreads_csv(content),
+        Some(SourceFormat::LibraryThing) => parse_librarything_csv(content),
+        Some(SourceFormat::Babelio) => parse_babelio_csv(content),
+        Some(SourceFormat::InventaireCsv) => parse_inventaire_csv(content),
+        Some(SourceFormat::InventaireJson) => parse_inventaire_json(content),
+        Some(SourceFormat::IsbnList) => parse_isbn_list(content),
+        None => Err(
+            "Unknown file format. Supported: Goodreads, LibraryThing, Babelio, ISBN List"
+                .to_string(),
+        ),
+    }?;
+    Ok(books.into_iter().map(sanitize_row).collect())
+}
+
+/// Export files are edited by hand and by other tools: see `utils::sanitize`.
+fn sanitize_row(req: CreateBookRequest) -> CreateBookRequest {
+    use crate::utils::sanitize::{MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_TITLE_CHARS, line, opt_line};
+    CreateBookRequest {
+        title: line(&req.title, MAX_TITLE_CHARS),
+        isbn: opt_line(req.isbn, MAX_CODE_CHARS),
+        publisher: opt_line(req.publisher, MAX_NAME_CHARS),
+        author: opt_line(req.author, MAX_NAME_CHARS),
+        ..req
+    }
+}
+
+/// A row's natural key, stable across re-uploads of an edited file: its
+/// ISBN, or its title and author (see [`book_dedup_key`]).
+pub fn row_key(req: &CreateBookRequest) -> String {
+    book_dedup_key(req.isbn.as_deref(), &req.title, req.author.as_deref(), None)
+}
+
+/// What to do with a row that matches a book already in the catalogue (or
+/// an earlier row of the same file).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupPolicy {
+    /// Keep the existing book as it is.
+    #[default]
+    Skip,
+    /// Fill the fields the existing book lacks from the row.
+    Merge,
+    /// Create the book anyway; the match is still reported.
+    CreateAnyway,
+    /// Overwrite the matched book's ISBN, publisher and year with the
+    /// row's, where the row has them (incremental re-imports).
+    Update,
+}
+
+impl FromStr for DedupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "merge" => Ok(Self::Merge),
+            "create_anyway" | "create-anyway" => Ok(Self::CreateAnyway),
+            "update" => Ok(Self::Update),
+            other => Err(format!(
+                "unknown dedup policy `{other}` (skip, merge, create-anyway or update)"
+            )),
+        }
+    }
+}
+
+/// Why a row was taken for a book already there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupRule {
+    /// Same ISBN, the ISBN-10 and ISBN-13 forms being equal.
+    Isbn,
+    /// No ISBN match, but a close title by a close author.
+    TitleAuthor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RowAction {
+    Created,
+    Skipped,
+    Merged,
+    Updated,
+    Failed,
+}
+
+/// The fate of one row of the file.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RowReport {
+    /// 1-based, in file order
+    pub row: usize,
+    pub title: String,
+    pub action: RowAction,
+    /// The rule that matched an existing book, if one did
+    pub rule: Option<DedupRule>,
+    /// The book created, or the one matched
+    pub book_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Outcome of [`import_books`].
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    /// Books created, plus rows that matched a book already there.
+    pub imported: usize,
+    /// One `"<title>: <error>"` line per book that could not be inserted.
+    pub errors: Vec<String>,
+    pub created: usize,
+    pub skipped: usize,
+    pub merged: usize,
+    pub updated: usize,
+    pub rows: Vec<RowReport>,
+}
+
+/// Titles at least this close (Jaro-Winkler, normalized) may be the same book.
+const TITLE_SIMILARITY: f64 = 0.92;
+/// Same threshold the catalogue lookups use for author surnames; met by
+/// Jaro-Winkler or by `utils::fuzzy::name_similarity`, which also forgives
+/// swapped letters and inverted names ("Tolkien, J.R.R.").
+const AUTHOR_SIMILARITY: f64 = 0.88;
+
+/// A catalogued book, as far as duplicate detection is concerned.
+struct Known {
+    id: String,
+    isbn13: Option<String>,
+    title: String,
+    author: Option<String>,
+}
 
-    if is_isbn_list {
-        return parse_isbn_list(content);
+fn canonical_isbn(isbn: Option<&str>) -> Option<String> {
+    let isbn = isbn?.trim();
+    if isbn.is_empty() {
+        return None;
     }
+    Some(to_isbn13(isbn).unwrap_or_else(|| isbn.replace(['-', ' '], "")))
+}
 
-    Err("Unknown file format. Supported: Goodreads, LibraryThing, Babelio, ISBN List".to_string())
+fn authors_match(a: &str, b: &str) -> bool {
+    jaro_winkler(a, b) >= AUTHOR_SIMILARITY
+        || crate::utils::fuzzy::name_similarity(a, b) >= AUTHOR_SIMILARITY
+}
+
+fn normalized(value: Option<&str>) -> Option<String> {
+    value.map(normalize_text).filter(|v| !v.is_empty())
+}
+
+/// The catalogue indexed for matching, grown with the books an import
+/// creates so a file repeating a row does not duplicate it either.
+struct Catalogue {
+    books: Vec<Known>,
+    by_isbn: HashMap<String, usize>,
+}
+
+impl Catalogue {
+    async fn load(db: &DatabaseConnection) -> Result<Self, sea_orm::DbErr> {
+        let names: HashMap<String, String> = author::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|a| (a.id, a.name))
+            .collect();
+        let mut authors: HashMap<String, String> = HashMap::new();
+        for link in book_authors::Entity::find().all(db).await? {
+            if let Some(name) = names.get(&link.author_id) {
+                authors.entry(link.book_id).or_insert_with(|| name.clone());
+            }
+        }
+        let mut catalogue = Self {
+            books: Vec::new(),
+            by_isbn: HashMap::new(),
+        };
+        for b in book::Entity::find().all(db).await? {
+            let author = authors.remove(&b.id);
+            catalogue.add(b.id, b.isbn.as_deref(), &b.title, author.as_deref());
+        }
+        Ok(catalogue)
+    }
+
+    fn add(&mut self, id: String, isbn: Option<&str>, title: &str, author: Option<&str>) {
+        let isbn13 = canonical_isbn(isbn);
+        if let Some(isbn13) = &isbn13 {
+            self.by_isbn
+                .entry(isbn13.clone())
+                .or_insert(self.books.len());
+        }
+        self.books.push(Known {
+            id,
+            isbn13,
+            title: normalize_text(title),
+            author: normalized(author),
+        });
+    }
+
+    /// The book `req` duplicates, and the rule that says so. An ISBN decides
+    /// alone; two different ISBNs are two editions, never a match. Without
+    /// one, titles must be close and authors too; a side with no author
+    /// matches only on the exact title.
+    fn find(&self, req: &CreateBookRequest) -> Option<(usize, DedupRule)> {
+        let isbn13 = canonical_isbn(req.isbn.as_deref());
+        if let Some(isbn13) = &isbn13
+            && let Some(&i) = self.by_isbn.get(isbn13)
+        {
+            return Some((i, DedupRule::Isbn));
+        }
+        let title = normalize_text(&req.title);
+        if title.is_empty() {
+            return None;
+        }
+        let author = normalized(req.author.as_deref());
+        self.books
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| isbn13.is_none() || k.isbn13.is_none())
+            .filter_map(|(i, k)| {
+                let title_score = jaro_winkler(&title, &k.title);
+                let matches = match (&author, &k.author) {
+                    (Some(a), Some(b)) => title_score >= TITLE_SIMILARITY && authors_match(a, b),
+                    _ => title == k.title,
+                };
+                matches.then_some((i, title_score))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| (i, DedupRule::TitleAuthor))
+    }
+}
+
+/// Fill what the existing book lacks from `req`, or with `overwrite` replace
+/// what differs; `owned: Some(true)` marks it owned. Returns whether
+/// anything changed.
+async fn merge_into(
+    db: &DatabaseConnection,
+    existing: book::Model,
+    req: &CreateBookRequest,
+    owned: Option<bool>,
+    overwrite: bool,
+) -> Result<bool, sea_orm::DbErr> {
+    let blank = |v: &Option<String>| v.as_deref().is_none_or(|v| v.trim().is_empty());
+    let id = existing.id.clone();
+    let mut active: book::ActiveModel = existing.clone().into();
+    let mut changed = false;
+    let isbn_differs =
+        canonical_isbn(existing.isbn.as_deref()) != canonical_isbn(req.isbn.as_deref());
+    if !blank(&req.isbn) && (blank(&existing.isbn) || (overwrite && isbn_differs)) {
+        active.isbn = Set(req.isbn.clone());
+        changed = true;
+    }
+    if !blank(&req.publisher)
+        && (blank(&existing.publisher) || (overwrite && existing.publisher != req.publisher))
+    {
+        active.publisher = Set(req.publisher.clone());
+        changed = true;
+    }
+    if req.publication_year.is_some()
+        && (existing.publication_year.is_none()
+            || (overwrite && existing.publication_year != req.publication_year))
+    {
+        active.publication_year = Set(req.publication_year);
+        changed = true;
+    }
+    if owned == Some(true) && !existing.owned {
+        active.owned = Set(true);
+        changed = true;
+    }
+    if changed {
+        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        active.update(db).await?;
+        let _ = crate::sync::log_operation(db, "book", &id, "UPDATE", None).await;
+    }
+
+    if let Some(name) = req.author.as_deref().filter(|n| !n.trim().is_empty()) {
+        let has_author = book_authors::Entity::find()
+            .filter(book_authors::Column::BookId.eq(id.as_str()))
+            .one(db)
+            .await?
+            .is_some();
+        if !has_author {
+            link_author(db, &id, name).await;
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+async fn link_author(db: &DatabaseConnection, book_id: &str, name: &str) {
+    if let Err(e) =
+        crate::services::book_service::create_or_link_author(db, book_id, name.trim()).await
+    {
+        tracing::warn!("import: could not link author {name} to {book_id}: {e:?}");
+    }
+}
+
+/// Insert parsed books. Rows matching a book already in the catalogue, or
+/// an earlier row, are handled by `policy`; each row's fate and the rule
+/// that matched it are reported. `owned` sets ownership of the books
+/// created (the column default otherwise), and `Some(true)` lets a merge
+/// mark a matched book owned.
+pub async fn import_books(
+    db: &DatabaseConnection,
+    books: Vec<CreateBookRequest>,
+    policy: DedupPolicy,
+    owned: Option<bool>,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    let mut catalogue = match Catalogue::load(db).await {
+        Ok(catalogue) => catalogue,
+        Err(e) => {
+            summary
+                .errors
+                .push(format!("cannot read the catalogue: {e}"));
+            return summary;
+        }
+    };
+
+    for (i, req) in books.into_iter().enumerate() {
+        let mut report = RowReport {
+            row: i + 1,
+            title: req.title.clone(),
+            action: RowAction::Created,
+            rule: None,
+            book_id: None,
+            error: None,
+        };
+        let matched = catalogue.find(&req);
+        if let Some((known, rule)) = matched {
+            report.rule = Some(rule);
+            let id = catalogue.books[known].id.clone();
+            match policy {
+                DedupPolicy::Skip => {
+                    report.action = RowAction::Skipped;
+                    report.book_id = Some(id);
+                }
+                DedupPolicy::Merge | DedupPolicy::Update => {
+                    let overwrite = policy == DedupPolicy::Update;
+                    let merged = match book::Entity::find_by_id(id.clone()).one(db).await {
+                        Ok(Some(existing)) => {
+                            merge_into(db, existing, &req, owned, overwrite).await
+                        }
+                        Ok(None) => Ok(false),
+                        Err(e) => Err(e),
+                    };
+                    match merged {
+                        Ok(changed) => {
+                            report.action = match (changed, overwrite) {
+                                (false, _) => RowAction::Skipped,
+                                (true, false) => RowAction::Merged,
+                                (true, true) => RowAction::Updated,
+                            };
+                            report.book_id = Some(id);
+                        }
+                        Err(e) => {
+                            report.action = RowAction::Failed;
+                            report.error = Some(e.to_string());
+                        }
+                    }
+                }
+                DedupPolicy::CreateAnyway => {}
+            }
+        }
+
+        if report.action == RowAction::Created {
+            let now = chrono::Utc::now().to_rfc3339();
+            let new_book = book::ActiveModel {
+                title: Set(req.title.clone()),
+                isbn: Set(req.isbn.clone()),
+                summary: Set(None),
+                publisher: Set(req.publisher.clone()),
+                publication_year: Set(req.publication_year),
+                owned: owned.map_or(sea_orm::NotSet, Set),
+                created_at: Set(now.clone()),
+                updated_at: Set(now),
+                ..Default::default()
+            };
+            match new_book.insert(db).await {
+                Ok(created) => {
+                    let _ =
+                        crate::sync::log_operation(db, "book", &created.id, "INSERT", None).await;
+                    if let Some(name) = req.author.as_deref().filter(|n| !n.trim().is_empty()) {
+                        link_author(db, &created.id, name).await;
+                    }
+                    catalogue.add(
+                        created.id.clone(),
+                        req.isbn.as_deref(),
+                        &req.title,
+                        req.author.as_deref(),
+                    );
+                    report.book_id = Some(created.id);
+                }
+                Err(e) => {
+                    report.action = RowAction::Failed;
+                    report.error = Some(e.to_string());
+                }
+            }
+        }
+
+        match report.action {
+            RowAction::Created => summary.created += 1,
+            RowAction::Skipped => summary.skipped += 1,
+            RowAction::Merged => summary.merged += 1,
+            RowAction::Updated => summary.updated += 1,
+            RowAction::Failed => summary.errors.push(format!(
+                "{}: {}",
+                report.title,
+                report.error.as_deref().unwrap_or_default()
+            )),
+        }
+        summary.rows.push(report);
+    }
+    summary.imported = summary.created + summary.skipped + summary.merged + summary.updated;
+    summary
 }
 
 fn parse_goodreads_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
@@ -100,6 +560,7 @@ fn parse_goodreads_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, String>
             isbn,
             publisher: record.publisher,
             publication_year: record.year_published,
+            author: Some(record.author),
         });
     }
     Ok(books)
@@ -126,6 +587,7 @@ fn parse_librarything_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, Stri
             isbn,
             publisher: record.publication, // Rough mapping
             publication_year: year,
+            author: Some(record.author),
         });
     }
     Ok(books)
@@ -154,6 +616,7 @@ fn parse_babelio_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
             isbn,
             publisher: record.editeur,
             publication_year: year,
+            author: Some(record.author),
         });
     }
     Ok(books)
@@ -171,6 +634,7 @@ fn parse_isbn_list(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
                 isbn: Some(isbn),
                 publisher: None,
                 publication_year: None,
+                author: None,
             });
         }
     }
@@ -200,6 +664,9 @@ struct InventaireBook {
     publisher: Option<String>,
     #[serde(rename = "Edition publication date")]
     publication_date: Option<String>,
+    /// Comma-separated when several
+    #[serde(rename = "Authors labels")]
+    authors: Option<String>,
 }
 
 fn parse_inventaire_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, String> {
@@ -224,6 +691,12 @@ fn parse_inventaire_csv(content: &[u8]) -> Result<Vec<CreateBookRequest>, String
             isbn,
             publisher: record.publisher,
             publication_year: year,
+            author: record
+                .authors
+                .as_deref()
+                .and_then(|a| a.split(',').next())
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty()),
         });
     }
     Ok(books)
@@ -246,7 +719,7 @@ fn parse_inventaire_json(content: &[u8]) -> Result<Vec<CreateBookRequest>, Strin
         #[serde(rename = "entity:title")]
         title: Option<String>,
         #[serde(rename = "entity:authors")]
-        _authors: Option<serde_json::Value>,
+        authors: Option<serde_json::Value>,
     }
 
     let root: Root =
@@ -267,11 +740,21 @@ fn parse_inventaire_json(content: &[u8]) -> Result<Vec<CreateBookRequest>, Strin
                 None
             };
 
+            // A single name or a list of names
+            let author = match snapshot.authors {
+                Some(serde_json::Value::String(name)) => Some(name),
+                Some(serde_json::Value::Array(names)) => names
+                    .into_iter()
+                    .find_map(|n| n.as_str().map(str::to_string)),
+                _ => None,
+            };
+
             books.push(CreateBookRequest {
                 title,
                 isbn: clean_isbn(isbn),
                 publisher: None,
                 publication_year: None,
+                author,
             });
         }
     }
@@ -323,4 +806,101 @@ https://inventaire.io/items/123,,,"friends,groups",inventorying,2025-12-05T06:10
         assert_eq!(result[1].title, "Mille petits riens");
         assert_eq!(result[1].isbn, Some("9782330124298".to_string()));
     }
+
+    #[test]
+    fn hostile_rows_are_sanitized() {
+        let author = "A".repeat(10_000);
+        let json_content = format!(
+            r#"{{"items": [{{
+                "entity": "isbn:9782264024848",
+                "snapshot": {{
+                    "entity:title": "Martin\u0000 Eden\u202e\n<b>",
+                    "entity:authors": "{author}"
+                }}
+            }}]}}"#
+        );
+
+        let result = parse_import_file(json_content.as_bytes()).unwrap();
+        assert_eq!(result[0].title, "Martin Eden <b>");
+        assert_eq!(
+            result[0].author.as_deref().map(|a| a.chars().count()),
+            Some(crate::utils::sanitize::MAX_NAME_CHARS)
+        );
+    }
+
+    fn row(
+        title: &str,
+        isbn: Option<&str>,
+        author: &str,
+        publisher: Option<&str>,
+    ) -> CreateBookRequest {
+        CreateBookRequest {
+            title: title.to_string(),
+            isbn: isbn.map(str::to_string),
+            publisher: publisher.map(str::to_string),
+            publication_year: None,
+            author: Some(author.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn reuploads_match_existing_books_by_isbn_then_title_and_author() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let file = || {
+            vec![
+                row("Martin Eden", Some("9782264024848"), "Jack London", None),
+                row("L'Étranger", None, "Albert Camus", None),
+            ]
+        };
+
+        let first = import_books(&db, file(), DedupPolicy::Skip, None).await;
+        assert_eq!((first.created, first.skipped), (2, 0));
+
+        let again = import_books(&db, file(), DedupPolicy::Skip, None).await;
+        assert_eq!((again.created, again.skipped), (0, 2));
+        let rules: Vec<_> = again.rows.iter().map(|r| r.rule).collect();
+        assert_eq!(rules, [Some(DedupRule::Isbn), Some(DedupRule::TitleAuthor)]);
+        assert_eq!(again.rows[0].book_id, first.rows[0].book_id);
+
+        // The ISBN-10 form, a slightly different spelling of the author and
+        // the author's name inverted.
+        let merged = import_books(
+            &db,
+            vec![
+                row(
+                    "Martin Eden",
+                    Some("2-264-02484-4"),
+                    "Jack London",
+                    Some("10/18"),
+                ),
+                row("L'Etranger", None, "Albert Camus", Some("Gallimard")),
+                row("L'étranger", None, "Albert Kamus", None),
+                row("L'Étranger", None, "Camus, Albert", None),
+            ],
+            DedupPolicy::Merge,
+            None,
+        )
+        .await;
+        let actions: Vec<_> = merged.rows.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            [
+                RowAction::Merged,
+                RowAction::Merged,
+                RowAction::Skipped,
+                RowAction::Skipped
+            ]
+        );
+        let eden = book::Entity::find_by_id(first.rows[0].book_id.clone().unwrap())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(eden.publisher.as_deref(), Some("10/18"));
+
+        let forced = import_books(&db, file(), DedupPolicy::CreateAnyway, None).await;
+        assert_eq!(forced.created, 2);
+        assert_eq!(forced.rows[0].rule, Some(DedupRule::Isbn));
+        assert_eq!(book::Entity::find().all(&db).await.unwrap().len(), 4);
+    }
 }
@@ -1,7 +1,11 @@
-//! BNF (Bibliothèque nationale de France) integration via data.bnf.fr SPARQL endpoint
+//! BNF (Bibliothèque nationale de France) integration
 //!
-//! This module provides search functionality for French books via the BNF's
-//! Linked Open Data SPARQL endpoint.
+//! One provider over the two BNF endpoints: the catalogue.bnf.fr SRU API
+//! (UNIMARC records, best coverage for recent books) is tried first, and the
+//! data.bnf.fr Linked Open Data SPARQL endpoint when SRU fails or finds
+//! nothing. Both answer through the same cache, with author and publisher
+//! names normalized the same way, and every result tells which endpoint it
+//! came from (`BnfBook::source`).
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -9,8 +13,10 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+use crate::services::api_quota::{self, Provider};
+
 /// Simple in-memory cache with TTL for BNF queries
-/// Avoids repeated slow SPARQL queries for the same search terms
+/// Avoids repeated slow queries for the same search terms or ISBN
 struct CacheEntry {
     data: Vec<BnfBook>,
     created_at: Instant,
@@ -19,13 +25,64 @@ struct CacheEntry {
 static BNF_CACHE: Lazy<Mutex<HashMap<String, CacheEntry>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Cache for BNF SRU queries (catalogue.bnf.fr)
-static BNF_SRU_CACHE: Lazy<Mutex<HashMap<String, CacheEntry>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
-
 const CACHE_TTL: Duration = Duration::from_secs(3600); // 1 hour
 const MAX_CACHE_ENTRIES: usize = 100; // Limit memory usage
 
+fn cache_get(key: &str) -> Option<Vec<BnfBook>> {
+    // Use try_lock to avoid blocking - the cache is optional
+    let cache = BNF_CACHE.try_lock().ok()?;
+    let entry = cache.get(key)?;
+    (entry.created_at.elapsed() < CACHE_TTL).then(|| entry.data.clone())
+}
+
+fn cache_put(key: String, books: &[BnfBook]) {
+    let Ok(mut cache) = BNF_CACHE.try_lock() else {
+        return;
+    };
+    // Evict oldest entries if cache is full
+    if cache.len() >= MAX_CACHE_ENTRIES {
+        // Remove expired entries first
+        cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
+
+        // If still full, remove oldest entry
+        if cache.len() >= MAX_CACHE_ENTRIES
+            && let Some(oldest_key) = cache
+                .iter()
+                .min_by_key(|(_, e)| e.created_at)
+                .map(|(k, _)| k.clone())
+        {
+            cache.remove(&oldest_key);
+        }
+    }
+    cache.insert(
+        key,
+        CacheEntry {
+            data: books.to_vec(),
+            created_at: Instant::now(),
+        },
+    );
+}
+
+/// The BNF endpoint a result came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BnfSource {
+    /// catalogue.bnf.fr SRU API
+    #[default]
+    Sru,
+    /// data.bnf.fr SPARQL endpoint
+    Sparql,
+}
+
+impl BnfSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BnfSource::Sru => "sru",
+            BnfSource::Sparql => "sparql",
+        }
+    }
+}
+
 /// A book result from BNF search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BnfBook {
@@ -37,6 +94,25 @@ pub struct BnfBook {
     pub cover_url: Option<String>,
     pub bnf_uri: String,
     pub description: Option<String>,
+    /// UNIMARC `205 $a` edition statement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    /// Page count parsed from UNIMARC `215 $a` ("1 vol. (349 p.)").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+    /// UNIMARC `215 $d` dimensions ("18 cm").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<String>,
+    /// Endpoint that answered.
+    #[serde(default)]
+    pub source: BnfSource,
+    /// Gallica page of the digitized document, when BNF has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digital_source_url: Option<String>,
+    /// Authority record of the work (UNIMARC `500 $3`), the same for every
+    /// edition and translation BNF linked to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_id: Option<String>,
 }
 
 /// SPARQL response structures
@@ -76,22 +152,10 @@ struct SparqlValue {
 ///
 /// # Returns
 /// A vector of BnfBook results
-pub async fn search_bnf(query: &str) -> Result<Vec<BnfBook>, String> {
-    let cache_key = query.to_lowercase().trim().to_string();
+async fn sparql_search(query: &str) -> Result<Vec<BnfBook>, String> {
+    tracing::debug!("BNF SPARQL search for '{}'", query);
 
-    // Check cache first (use try_lock to avoid blocking/panics)
-    if let Ok(cache) = BNF_CACHE.try_lock()
-        && let Some(entry) = cache.get(&cache_key)
-        && entry.created_at.elapsed() < CACHE_TTL
-    {
-        tracing::debug!("BNF cache hit for query: {}", query);
-        return Ok(entry.data.clone());
-    }
-
-    tracing::debug!("BNF cache miss for query: {}", query);
-    println!("DEBUG BNF: Starting search for '{}'", query);
-
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(30)) // Increased to 30s to avoid CI timeouts
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -209,6 +273,7 @@ LIMIT 30
         // Generate cover URL from BNF if available
         // BNF provides covers via their Gallica service for some works
         let cover_url = generate_bnf_cover_url(&uri);
+        let digital_source_url = gallica_url(&[], Some(&uri));
 
         let book = BnfBook {
             title,
@@ -219,40 +284,17 @@ LIMIT 30
             cover_url,
             bnf_uri: uri,
             description: binding.description.map(|d| d.value),
+            edition: None,
+            page_count: None,
+            dimensions: None,
+            source: BnfSource::Sparql,
+            digital_source_url,
+            work_id: None,
         };
 
         books.push(book);
     }
 
-    // Store in cache (with LRU-style eviction if full)
-    // Use try_lock to avoid blocking - cache write is optional
-    if let Ok(mut cache) = BNF_CACHE.try_lock() {
-        // Evict oldest entries if cache is full
-        if cache.len() >= MAX_CACHE_ENTRIES {
-            // Remove expired entries first
-            cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
-
-            // If still full, remove oldest entry
-            if cache.len() >= MAX_CACHE_ENTRIES
-                && let Some(oldest_key) = cache
-                    .iter()
-                    .min_by_key(|(_, e)| e.created_at)
-                    .map(|(k, _)| k.clone())
-            {
-                cache.remove(&oldest_key);
-            }
-        }
-
-        cache.insert(
-            cache_key,
-            CacheEntry {
-                data: books.clone(),
-                created_at: Instant::now(),
-            },
-        );
-    }
-
-    println!("DEBUG BNF: Search completed with {} results", books.len());
     Ok(books)
 }
 
@@ -268,7 +310,7 @@ fn catalogue_cover_url_from_ark(ark: &str) -> String {
 /// Validate a catalogue.bnf.fr cover URL with a HEAD request.
 /// Returns `Some(url)` only if the server responds with a success status and an image content type.
 async fn validate_bnf_cover_url(url: &str) -> Option<String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(3))
         .build()
         .ok()?;
@@ -291,6 +333,20 @@ async fn validate_bnf_cover_url(url: &str) -> Option<String> {
     }
 }
 
+/// Gallica page of a digitized document: a Gallica link of the record
+/// (UNIMARC `856 $u`), or the ARK itself when it names a digitized document
+/// (`bpt6k`, `btv1b`) rather than a catalogue notice (`cb`).
+pub fn gallica_url(links: &[String], ark: Option<&str>) -> Option<String> {
+    if let Some(link) = links.iter().find(|l| l.contains("gallica.bnf.fr/ark:")) {
+        return Some(link.clone());
+    }
+    let ark = ark?;
+    let start = ark.find("ark:/12148/")? + "ark:/12148/".len();
+    let id = ark[start..].split('/').next().unwrap_or_default();
+    (id.starts_with("bpt6k") || id.starts_with("btv1b"))
+        .then(|| format!("https://gallica.bnf.fr/ark:/12148/{}", id))
+}
+
 /// Generate a potential cover URL from BNF
 /// BNF doesn't always have covers, but we can try the Gallica thumbnail service
 fn generate_bnf_cover_url(bnf_uri: &str) -> Option<String> {
@@ -314,8 +370,8 @@ fn generate_bnf_cover_url(bnf_uri: &str) -> Option<String> {
 }
 
 /// Search for a book by ISBN on data.bnf.fr
-pub async fn lookup_bnf_isbn(isbn: &str) -> Result<Option<BnfBook>, String> {
-    let client = reqwest::Client::builder()
+async fn sparql_lookup(isbn: &str) -> Result<Option<BnfBook>, String> {
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -402,6 +458,7 @@ LIMIT 1
         });
 
         let cover_url = generate_bnf_cover_url(&uri);
+        let digital_source_url = gallica_url(&[], Some(&uri));
 
         Ok(Some(BnfBook {
             title,
@@ -412,6 +469,12 @@ LIMIT 1
             cover_url,
             bnf_uri: uri,
             description: binding.description.as_ref().map(|d| d.value.clone()),
+            edition: None,
+            page_count: None,
+            dimensions: None,
+            source: BnfSource::Sparql,
+            digital_source_url,
+            work_id: None,
         }))
     } else {
         Ok(None)
@@ -420,19 +483,8 @@ LIMIT 1
 
 /// Search for a book by ISBN on catalogue.bnf.fr (SRU API)
 /// This has better coverage than the SPARQL endpoint for recent books
-pub async fn lookup_bnf_sru(isbn: &str) -> Result<Option<BnfBook>, String> {
-    let clean_isbn = isbn.replace('-', "");
-    let cache_key = format!("isbn:{}", clean_isbn);
-
-    // Check cache first
-    if let Ok(cache) = BNF_SRU_CACHE.try_lock()
-        && let Some(entry) = cache.get(&cache_key)
-        && entry.created_at.elapsed() < CACHE_TTL
-    {
-        return Ok(entry.data.first().cloned());
-    }
-
-    let client = reqwest::Client::builder()
+async fn sru_lookup(clean_isbn: &str) -> Result<Option<BnfBook>, String> {
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(8))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -461,7 +513,7 @@ pub async fn lookup_bnf_sru(isbn: &str) -> Result<Option<BnfBook>, String> {
         return Ok(None);
     }
 
-    let parsed = match parse_bnf_sru_record(&xml, &clean_isbn)? {
+    let parsed = match parse_bnf_sru_record(&xml, clean_isbn)? {
         Some(p) => p,
         None => return Ok(None),
     };
@@ -472,161 +524,31 @@ pub async fn lookup_bnf_sru(isbn: &str) -> Result<Option<BnfBook>, String> {
         book.cover_url = validate_bnf_cover_url(&url).await;
     }
 
-    // Store in cache
-    if let Ok(mut cache) = BNF_SRU_CACHE.try_lock() {
-        if cache.len() >= MAX_CACHE_ENTRIES {
-            cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
-        }
-        cache.insert(
-            cache_key,
-            CacheEntry {
-                data: vec![book.clone()],
-                created_at: Instant::now(),
-            },
-        );
-    }
-
     Ok(Some(book))
 }
 
 /// Parse a single BNF SRU record (one-result XML response) into a `BnfBook`,
 /// alongside an unvalidated cover URL the caller may want to HEAD-check.
 ///
-/// Sync, no I/O — extracted for testability with fixture XML.
-fn parse_bnf_sru_record(
+/// Sync, no I/O — extracted for testability with fixture XML (and driven by the
+/// `hot_paths` benchmark).
+pub fn parse_bnf_sru_record(
     xml: &str,
     clean_isbn: &str,
 ) -> Result<Option<(BnfBook, Option<String>)>, String> {
-    use quick_xml::events::Event;
-    use quick_xml::reader::Reader;
-
-    let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
-
-    let mut builder = BnfBookBuilder::default();
-    let mut buf = Vec::new();
-    let mut current_tag = String::new();
-    let mut current_code = String::new();
-    let mut in_datafield = false;
-    let mut in_subfield = false;
-    let mut in_controlfield = false;
-    let mut controlfield_tag = String::new();
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-
-                if name.ends_with("datafield") {
-                    in_datafield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"tag" {
-                            current_tag = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                } else if name.ends_with("subfield") && in_datafield {
-                    in_subfield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"code" {
-                            current_code = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                } else if name.ends_with("controlfield") {
-                    in_controlfield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"tag" {
-                            controlfield_tag = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                }
-            }
-            Ok(Event::Text(e)) => {
-                let text = super::xml_text_content(&e);
-
-                if in_controlfield
-                    && controlfield_tag == "003"
-                    && let Some(ark_start) = text.find("ark:/12148/")
-                {
-                    builder.ark_id = Some(text[ark_start..].to_string());
-                }
-
-                if in_subfield {
-                    match (current_tag.as_str(), current_code.as_str()) {
-                        ("200", "a") => builder.title = text,
-                        ("200", "f") if builder.responsibility_200f.is_none() => {
-                            builder.responsibility_200f = Some(text)
-                        }
-                        ("700", "a") if builder.author_700.1.is_none() => {
-                            builder.author_700.1 = Some(text)
-                        }
-                        ("700", "b") if builder.author_700.0.is_none() => {
-                            builder.author_700.0 = Some(text)
-                        }
-                        ("701", "a") if builder.author_701.1.is_none() => {
-                            builder.author_701.1 = Some(text)
-                        }
-                        ("701", "b") if builder.author_701.0.is_none() => {
-                            builder.author_701.0 = Some(text)
-                        }
-                        ("702", "a") if builder.author_702.1.is_none() => {
-                            builder.author_702.1 = Some(text)
-                        }
-                        ("702", "b") if builder.author_702.0.is_none() => {
-                            builder.author_702.0 = Some(text)
-                        }
-                        ("214", "c") | ("210", "c") => builder.publisher = Some(text),
-                        ("214", "d") | ("210", "d") => {
-                            builder.year = text
-                                .chars()
-                                .filter(|c| c.is_ascii_digit())
-                                .collect::<String>()
-                                .get(0..4)
-                                .and_then(|y| y.parse::<i32>().ok());
-                        }
-                        ("330", "a") => builder.description = Some(text),
-                        _ => {}
-                    }
-                }
-            }
-            Ok(Event::End(e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                if name.ends_with("datafield") {
-                    in_datafield = false;
-                    current_tag.clear();
-                } else if name.ends_with("subfield") {
-                    in_subfield = false;
-                    current_code.clear();
-                } else if name.ends_with("controlfield") {
-                    in_controlfield = false;
-                    controlfield_tag.clear();
-                }
-            }
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(format!("XML parse error: {}", e)),
-            _ => {}
-        }
-        buf.clear();
-    }
-
-    // Default the ISBN to the lookup key when the record didn't carry it.
-    if builder.isbn.is_none() {
-        builder.isbn = Some(clean_isbn.to_string());
-    }
+    let Some(record) = super::unimarc::parse_records(xml)?.into_iter().next() else {
+        return Ok(None);
+    };
+    let mut builder = BnfBookBuilder::from(record);
+    // The lookup key is the ISBN asked for, whatever form the record gives.
+    builder.isbn = Some(clean_isbn.to_string());
 
     Ok(builder.build())
 }
 
-/// Search books by title/author on catalogue.bnf.fr (SRU API)
-/// Returns up to 20 results for French books
-pub async fn search_bnf_sru(
-    query: &str,
-    title: Option<&str>,
-    author: Option<&str>,
-) -> Result<Vec<BnfBook>, String> {
-    use quick_xml::events::Event;
-    use quick_xml::reader::Reader;
-
-    // Build SRU query for cache key
+/// CQL query for the SRU API: `bib.title` and `bib.author` when given,
+/// otherwise the free query on `bib.anywhere`.
+fn sru_search_query(query: &str, title: Option<&str>, author: Option<&str>) -> Option<String> {
     let mut query_parts = Vec::new();
 
     if let Some(t) = title
@@ -646,27 +568,18 @@ pub async fn search_bnf_sru(
         query_parts.push(format!("bib.anywhere adj \"{}\"", query.replace('"', " ")));
     }
 
-    if query_parts.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let sru_query = query_parts.join(" and ");
-    let cache_key = format!("search:{}", sru_query.to_lowercase());
-
-    // Check cache first
-    if let Ok(cache) = BNF_SRU_CACHE.try_lock()
-        && let Some(entry) = cache.get(&cache_key)
-        && entry.created_at.elapsed() < CACHE_TTL
-    {
-        return Ok(entry.data.clone());
-    }
+    (!query_parts.is_empty()).then(|| query_parts.join(" and "))
+}
 
-    let client = reqwest::Client::builder()
+/// Search books on catalogue.bnf.fr (SRU API) with a query from
+/// `sru_search_query`. Returns up to 20 results.
+async fn sru_search(sru_query: &str) -> Result<Vec<BnfBook>, String> {
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(8))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
-    let encoded_query = urlencoding::encode(&sru_query);
+    let encoded_query = urlencoding::encode(sru_query);
 
     let url = format!(
         "https://catalogue.bnf.fr/api/SRU?version=1.2&operation=searchRetrieve&query={}&maximumRecords=20&recordSchema=unimarcxchange",
@@ -693,134 +606,10 @@ pub async fn search_bnf_sru(
         return Ok(Vec::new());
     }
 
-    // Parse multiple MARC records
-    let mut books = Vec::new();
-    let mut reader = Reader::from_str(&xml);
-    reader.config_mut().trim_text(true);
-
-    let mut current_book = BnfBookBuilder::default();
-    let mut buf = Vec::new();
-    let mut current_tag = String::new();
-    let mut current_code = String::new();
-    let mut in_datafield = false;
-    let mut in_subfield = false;
-    let mut in_controlfield = false;
-    let mut in_record = false;
-    let mut controlfield_tag = String::new();
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-
-                if name.ends_with("record") {
-                    in_record = true;
-                } else if name.ends_with("datafield") && in_record {
-                    in_datafield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"tag" {
-                            current_tag = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                } else if name.ends_with("subfield") && in_datafield {
-                    in_subfield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"code" {
-                            current_code = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                } else if name.ends_with("controlfield") && in_record {
-                    in_controlfield = true;
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"tag" {
-                            controlfield_tag = String::from_utf8_lossy(&attr.value).to_string();
-                        }
-                    }
-                }
-            }
-            Ok(Event::Text(e)) => {
-                let text = super::xml_text_content(&e);
-
-                if in_controlfield
-                    && controlfield_tag == "003"
-                    && let Some(ark_start) = text.find("ark:/12148/")
-                {
-                    current_book.ark_id = Some(text[ark_start..].to_string());
-                }
-
-                if in_subfield {
-                    match (current_tag.as_str(), current_code.as_str()) {
-                        ("200", "a") => current_book.title = text,
-                        ("200", "f") if current_book.responsibility_200f.is_none() => {
-                            current_book.responsibility_200f = Some(text)
-                        }
-                        ("700", "a") if current_book.author_700.1.is_none() => {
-                            current_book.author_700.1 = Some(text)
-                        }
-                        ("700", "b") if current_book.author_700.0.is_none() => {
-                            current_book.author_700.0 = Some(text)
-                        }
-                        ("701", "a") if current_book.author_701.1.is_none() => {
-                            current_book.author_701.1 = Some(text)
-                        }
-                        ("701", "b") if current_book.author_701.0.is_none() => {
-                            current_book.author_701.0 = Some(text)
-                        }
-                        ("702", "a") if current_book.author_702.1.is_none() => {
-                            current_book.author_702.1 = Some(text)
-                        }
-                        ("702", "b") if current_book.author_702.0.is_none() => {
-                            current_book.author_702.0 = Some(text)
-                        }
-                        ("214", "c") | ("210", "c") => current_book.publisher = Some(text),
-                        ("214", "d") | ("210", "d") => {
-                            current_book.year = text
-                                .chars()
-                                .filter(|c| c.is_ascii_digit())
-                                .collect::<String>()
-                                .get(0..4)
-                                .and_then(|y| y.parse::<i32>().ok());
-                        }
-                        ("010", "a") | ("073", "a") => {
-                            // ISBN
-                            let isbn = text.replace('-', "");
-                            if isbn.len() == 13 || isbn.len() == 10 {
-                                current_book.isbn = Some(isbn);
-                            }
-                        }
-                        ("330", "a") => current_book.description = Some(text),
-                        _ => {}
-                    }
-                }
-            }
-            Ok(Event::End(e)) => {
-                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                if name.ends_with("record") {
-                    in_record = false;
-                    // Build and save book if it has a title
-                    let book = std::mem::take(&mut current_book);
-                    if !book.title.is_empty()
-                        && let Some((b, pending_cover)) = book.build()
-                    {
-                        books.push((b, pending_cover));
-                    }
-                } else if name.ends_with("datafield") {
-                    in_datafield = false;
-                    current_tag.clear();
-                } else if name.ends_with("subfield") {
-                    in_subfield = false;
-                    current_code.clear();
-                } else if name.ends_with("controlfield") {
-                    in_controlfield = false;
-                    controlfield_tag.clear();
-                }
-            }
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(format!("XML parse error: {}", e)),
-            _ => {}
-        }
-        buf.clear();
-    }
+    let books: Vec<(BnfBook, Option<String>)> = super::unimarc::parse_records(&xml)?
+        .into_iter()
+        .filter_map(|record| BnfBookBuilder::from(record).build())
+        .collect();
 
     // Set pending cover URLs directly without validation to avoid burning
     // the search time budget on HEAD requests to catalogue.bnf.fr (which often
@@ -833,56 +622,224 @@ pub async fn search_bnf_sru(
         })
         .collect();
 
-    // Store in cache
-    if let Ok(mut cache) = BNF_SRU_CACHE.try_lock() {
-        if cache.len() >= MAX_CACHE_ENTRIES {
-            cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
+    Ok(validated_books)
+}
+
+/// The SRU result, or the SPARQL one when SRU failed or found nothing.
+/// `sparql` is `None` when the (slow) SPARQL endpoint must not be tried.
+async fn sru_then_sparql<F, Fut>(
+    sru: Result<Vec<BnfBook>, String>,
+    sparql: Option<F>,
+) -> Result<Vec<BnfBook>, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<BnfBook>, String>>,
+{
+    let sru_error = match sru {
+        Ok(books) if !books.is_empty() => return Ok(books),
+        Ok(_) => None,
+        Err(e) => Some(e),
+    };
+    let Some(sparql) = sparql else {
+        return sru_error.map_or(Ok(Vec::new()), Err);
+    };
+    if let Some(e) = &sru_error {
+        tracing::debug!("BNF SRU failed, falling back to SPARQL: {}", e);
+    }
+    match (sparql().await, sru_error) {
+        (Ok(books), _) => Ok(books),
+        (Err(e), Some(sru_e)) => Err(format!("{}; {}", sru_e, e)),
+        (Err(e), None) => {
+            tracing::debug!("BNF SPARQL fallback failed: {}", e);
+            Ok(Vec::new())
         }
-        cache.insert(
-            cache_key,
-            CacheEntry {
-                data: validated_books.clone(),
-                created_at: Instant::now(),
-            },
-        );
     }
+}
 
-    Ok(validated_books)
+/// Search BNF by title and/or author, or by a free query. SRU first, SPARQL
+/// when SRU has nothing and `sparql_fallback` is set (leave it off where
+/// latency matters, e.g. autocomplete).
+#[tracing::instrument(skip_all, fields(provider = "bnf"))]
+pub async fn search(
+    query: &str,
+    title: Option<&str>,
+    author: Option<&str>,
+    sparql_fallback: bool,
+) -> Result<Vec<BnfBook>, String> {
+    let Some(sru_query) = sru_search_query(query, title, author) else {
+        return Ok(Vec::new());
+    };
+    let cache_key = format!("search:{}", sru_query.to_lowercase());
+    if let Some(books) = cache_get(&cache_key) {
+        return Ok(books);
+    }
+    if !api_quota::try_call(Provider::Bnf) {
+        return Err(api_quota::over_quota(Provider::Bnf));
+    }
+
+    // SPARQL matches one string against titles and author names.
+    let sparql_query = [Some(query), title, author]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    let sparql = sparql_fallback.then_some(|| async move { sparql_search(&sparql_query).await });
+    let books: Vec<BnfBook> = sru_then_sparql(sru_search(&sru_query).await, sparql)
+        .await?
+        .into_iter()
+        .map(normalized)
+        .collect();
+
+    // Empty results are not kept: the next call may be allowed the fallback.
+    if !books.is_empty() {
+        cache_put(cache_key, &books);
+    }
+    Ok(books)
+}
+
+/// Look a book up by ISBN: SRU first, then SPARQL.
+#[tracing::instrument(skip_all, fields(provider = "bnf", %isbn))]
+pub async fn lookup_isbn(isbn: &str) -> Result<Option<BnfBook>, String> {
+    let clean_isbn = isbn.replace('-', "");
+    let cache_key = format!("isbn:{}", clean_isbn);
+    if let Some(books) = cache_get(&cache_key) {
+        return Ok(books.into_iter().next());
+    }
+    if !api_quota::try_call(Provider::Bnf) {
+        return Err(api_quota::over_quota(Provider::Bnf));
+    }
+
+    let sru = sru_lookup(&clean_isbn).await.map(Vec::from_iter);
+    let sparql = || async move { sparql_lookup(&clean_isbn).await.map(Vec::from_iter) };
+    let book = sru_then_sparql(sru, Some(sparql))
+        .await?
+        .into_iter()
+        .next()
+        .map(normalized);
+
+    if let Some(book) = &book {
+        cache_put(cache_key, std::slice::from_ref(book));
+    }
+    Ok(book)
+}
+
+/// Same formatting whichever endpoint answered.
+fn normalized(mut book: BnfBook) -> BnfBook {
+    book.author = book.author.as_deref().and_then(normalize_author);
+    book.publisher = book.publisher.as_deref().and_then(normalize_publisher);
+    book
+}
+
+/// "Hugo, Victor (1802-1885)" → "Victor Hugo": dates, in parentheses or as
+/// a trailing ", 1802-1885", are dropped and an inverted "Surname, Firstname"
+/// put back in reading order.
+pub(crate) fn normalize_author(name: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut rest = name;
+    while let Some(open) = rest.find('(') {
+        let Some(close) = rest[open..].find(')') else {
+            break;
+        };
+        let inner = &rest[open + 1..open + close];
+        text.push_str(&rest[..open]);
+        if !inner.chars().any(|c| c.is_ascii_digit()) {
+            text.push_str(&rest[open..=open + close]);
+        }
+        rest = &rest[open + close + 1..];
+    }
+    text.push_str(rest);
+
+    let is_dates = |part: &str| {
+        part.chars().any(|c| c.is_ascii_digit())
+            && part
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '-' | '?' | '.' | ' '))
+    };
+    let parts: Vec<&str> = text
+        .trim()
+        .trim_end_matches([',', ';', ':', '/'])
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !is_dates(p))
+        .collect();
+    // Only short, capitalized parts: a `200 $f` responsibility statement
+    // ("transcrite, présentée & annotée par ...") is free text, left as is.
+    let name_like = |part: &str| {
+        part.chars().next().is_some_and(char::is_uppercase) && part.split_whitespace().count() <= 3
+    };
+    let text = match parts.as_slice() {
+        [surname, firstname] if name_like(surname) && name_like(firstname) => {
+            format!("{} {}", firstname, surname)
+        }
+        _ => parts.join(", "),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// "[Gallimard] ," → "Gallimard": cataloguer brackets and trailing ISBD
+/// punctuation removed.
+fn normalize_publisher(name: &str) -> Option<String> {
+    let text: String = name.chars().filter(|c| !matches!(c, '[' | ']')).collect();
+    let text = text
+        .trim()
+        .trim_end_matches([',', ';', ':', '/'])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Page count from a UNIMARC `215 $a` extent statement: the number right
+/// before the first "p." ("1 vol. (349 p.)", "XII-253 p."). Roman-numbered
+/// preliminary pages are not counted.
+fn page_count_from_extent(extent: &str) -> Option<u32> {
+    let idx = extent.find(" p.").or_else(|| extent.find("p."))?;
+    let digits: String = extent[..idx]
+        .trim_end()
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.chars().rev().collect::<String>().parse().ok()
 }
 
 /// Helper struct for building BnfBook from MARC fields
-#[derive(Default)]
 struct BnfBookBuilder {
-    title: String,
-    /// UNIMARC `200 $f` — statement of responsibility (free text). Used as a
-    /// last-resort fallback only; structured `7XX` fields take precedence.
-    responsibility_200f: Option<String>,
-    /// (firstname, surname) from UNIMARC `700` (main author).
-    author_700: (Option<String>, Option<String>),
-    /// `701` (alternative author).
-    author_701: (Option<String>, Option<String>),
-    /// `702` (secondary contributor — translator/editor/annotator).
-    author_702: (Option<String>, Option<String>),
-    publisher: Option<String>,
-    year: Option<i32>,
+    record: super::unimarc::Record,
     isbn: Option<String>,
-    description: Option<String>,
+    /// ARK identifier, read from control field `003`.
     ark_id: Option<String>,
 }
 
+impl From<super::unimarc::Record> for BnfBookBuilder {
+    fn from(record: super::unimarc::Record) -> Self {
+        let ark_id = record
+            .permalink
+            .as_deref()
+            .and_then(|p| p.find("ark:/12148/").map(|start| p[start..].to_string()));
+        BnfBookBuilder {
+            isbn: record.isbn.clone(),
+            record,
+            ark_id,
+        }
+    }
+}
+
 impl BnfBookBuilder {
     /// Returns the book and an optional pending cover URL to validate asynchronously.
     fn build(self) -> Option<(BnfBook, Option<String>)> {
-        if self.title.is_empty() {
+        if self.record.title.is_empty() {
             return None;
         }
 
-        let author = super::unimarc::compose_author(
-            self.author_700,
-            self.author_701,
-            self.author_702,
-            self.responsibility_200f,
-        );
+        // UNIMARC `200 $f` is a last-resort fallback only; structured `7XX`
+        // fields take precedence.
+        let author = self.record.author();
+        let digital_source_url = gallica_url(&self.record.links, self.ark_id.as_deref());
+        let record = self.record;
 
         // Cover URL is NOT set here (sync context). The caller must validate
         // asynchronously via `validate_bnf_cover_url` after build.
@@ -893,17 +850,23 @@ impl BnfBookBuilder {
 
         Some((
             BnfBook {
-                title: self.title,
+                title: record.title,
                 author,
-                publisher: self.publisher,
-                publication_year: self.year,
+                publisher: record.publisher,
+                publication_year: record.year,
                 isbn: self.isbn,
                 cover_url: None, // validated later
                 bnf_uri: self
                     .ark_id
                     .map(|a| format!("https://catalogue.bnf.fr/{}", a))
                     .unwrap_or_default(),
-                description: self.description,
+                description: record.summary,
+                edition: record.edition,
+                page_count: record.extent.as_deref().and_then(page_count_from_extent),
+                dimensions: record.dimensions,
+                source: BnfSource::Sru,
+                digital_source_url,
+                work_id: record.work_authority,
             },
             pending_cover_url,
         ))
@@ -936,34 +899,153 @@ mod tests {
              not the 200$f responsibility statement",
         );
         assert_eq!(book.isbn.as_deref(), Some("9782367321257"));
+        assert_eq!(
+            book.edition.as_deref(),
+            Some("Nouv. éd. abrégée avec mises à jour")
+        );
+        assert_eq!(book.page_count, Some(349));
+        assert_eq!(book.dimensions.as_deref(), Some("18 cm"));
+    }
+
+    #[test]
+    fn page_count_from_extent_reads_number_before_p() {
+        assert_eq!(page_count_from_extent("1 vol. (349 p.)"), Some(349));
+        assert_eq!(page_count_from_extent("XII-253 p."), Some(253));
+        assert_eq!(page_count_from_extent("1 disque compact"), None);
     }
 
     #[test]
     fn bnf_book_builder_prefers_700_over_responsibility() {
-        let builder = BnfBookBuilder {
+        let builder = BnfBookBuilder::from(super::super::unimarc::Record {
             title: "T".into(),
             author_700: (Some("Antonio".into()), Some("Pigafetta".into())),
-            responsibility_200f: Some("présenté par Xavier de Castro".into()),
+            responsibility: Some("présenté par Xavier de Castro".into()),
             ..Default::default()
-        };
+        });
         let (book, _) = builder.build().expect("build should succeed");
         assert_eq!(book.author.as_deref(), Some("Antonio Pigafetta"));
     }
 
     #[test]
     fn bnf_book_builder_uses_responsibility_when_no_7xx() {
-        let builder = BnfBookBuilder {
+        let builder = BnfBookBuilder::from(super::super::unimarc::Record {
             title: "T".into(),
-            responsibility_200f: Some("anonymous".into()),
+            responsibility: Some("anonymous".into()),
             ..Default::default()
-        };
+        });
         let (book, _) = builder.build().expect("build should succeed");
         assert_eq!(book.author.as_deref(), Some("anonymous"));
     }
 
+    #[test]
+    fn gallica_url_from_links_or_digitized_ark() {
+        let links = vec!["https://gallica.bnf.fr/ark:/12148/bpt6k5842x".to_string()];
+        assert_eq!(
+            gallica_url(&links, Some("ark:/12148/cb45425087g")).as_deref(),
+            Some("https://gallica.bnf.fr/ark:/12148/bpt6k5842x")
+        );
+        assert_eq!(
+            gallica_url(
+                &[],
+                Some("http://data.bnf.fr/ark:/12148/bpt6k101396v/f1.item")
+            )
+            .as_deref(),
+            Some("https://gallica.bnf.fr/ark:/12148/bpt6k101396v")
+        );
+        // A catalogue notice is not a digitized document.
+        assert_eq!(gallica_url(&[], Some("ark:/12148/cb45425087g")), None);
+        assert_eq!(gallica_url(&[], None), None);
+    }
+
+    #[test]
+    fn author_and_publisher_are_normalized() {
+        assert_eq!(
+            normalize_author("Hugo, Victor (1802-1885)").as_deref(),
+            Some("Victor Hugo")
+        );
+        assert_eq!(
+            normalize_author("La Fontaine, Jean de").as_deref(),
+            Some("Jean de La Fontaine")
+        );
+        assert_eq!(
+            normalize_author("Xavier  de Castro").as_deref(),
+            Some("Xavier de Castro")
+        );
+        assert_eq!(
+            normalize_author("transcrite, présentée & annotée par Xavier de Castro").as_deref(),
+            Some("transcrite, présentée & annotée par Xavier de Castro")
+        );
+        assert_eq!(
+            normalize_author("Hugo, Victor, 1802-1885").as_deref(),
+            Some("Victor Hugo")
+        );
+        assert_eq!(normalize_author(" (1900-1950) "), None);
+        assert_eq!(
+            normalize_publisher("[Gallimard] ,").as_deref(),
+            Some("Gallimard")
+        );
+        assert_eq!(
+            normalize_publisher("Éd. du Seuil").as_deref(),
+            Some("Éd. du Seuil")
+        );
+    }
+
+    fn book(title: &str, source: BnfSource) -> BnfBook {
+        BnfBook {
+            title: title.to_string(),
+            author: None,
+            publisher: None,
+            publication_year: None,
+            isbn: None,
+            cover_url: None,
+            bnf_uri: String::new(),
+            description: None,
+            edition: None,
+            page_count: None,
+            dimensions: None,
+            source,
+            digital_source_url: None,
+            work_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sparql_is_only_tried_when_sru_has_nothing() {
+        let sparql = || async { Ok(vec![book("B", BnfSource::Sparql)]) };
+
+        let found = sru_then_sparql(Ok(vec![book("A", BnfSource::Sru)]), Some(sparql))
+            .await
+            .unwrap();
+        assert_eq!(found[0].source, BnfSource::Sru);
+
+        let found = sru_then_sparql(Ok(Vec::new()), Some(sparql)).await.unwrap();
+        assert_eq!(found[0].source, BnfSource::Sparql);
+        let found = sru_then_sparql(Err("down".into()), Some(sparql))
+            .await
+            .unwrap();
+        assert_eq!(found[0].source, BnfSource::Sparql);
+
+        // Without the fallback, an SRU error is the error.
+        #[allow(clippy::type_complexity)]
+        let no_fallback: Option<fn() -> std::future::Ready<Result<Vec<BnfBook>, String>>> = None;
+        assert!(
+            sru_then_sparql(Err("down".into()), no_fallback)
+                .await
+                .is_err()
+        );
+        // Both down: both errors reported.
+        let failing = || async { Err::<Vec<BnfBook>, _>("timeout".to_string()) };
+        assert_eq!(
+            sru_then_sparql(Err("down".into()), Some(failing))
+                .await
+                .unwrap_err(),
+            "down; timeout"
+        );
+    }
+
     #[tokio::test]
     async fn test_search_bnf() {
-        let results = search_bnf("Victor Hugo").await;
+        let results = sparql_search("Victor Hugo").await;
         if let Err(e) = &results {
             println!("BNF Search failed: {}", e);
         }
@@ -973,7 +1055,7 @@ mod tests {
     #[tokio::test]
     async fn test_lookup_bnf_sru() {
         // ISBN not found in SPARQL but available in SRU
-        let result = lookup_bnf_sru("9782226468345").await;
+        let result = sru_lookup("9782226468345").await;
         assert!(result.is_ok(), "BNF SRU lookup failed: {:?}", result.err());
         let book = result.unwrap();
         assert!(book.is_some(), "Book should be found via BNF SRU");
@@ -985,7 +1067,7 @@ mod tests {
     #[tokio::test]
     async fn test_search_bnf_sru() {
         // Search by title
-        let result = search_bnf_sru("", Some("Geronimo Stilton"), None).await;
+        let result = search("", Some("Geronimo Stilton"), None, false).await;
         assert!(result.is_ok(), "BNF SRU search failed: {:?}", result.err());
         let books = result.unwrap();
         println!("Found {} books for 'Geronimo Stilton'", books.len());
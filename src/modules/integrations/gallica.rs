@@ -0,0 +1,177 @@
+//! Gallica (BNF digital library) integration via its SRU API
+//!
+//! Finds digitized documents by title and author. Only public-domain
+//! documents are kept: Gallica also lists in-copyright works readable on
+//! BNF premises or sold by partners, which are not free editions.
+
+use serde::{Deserialize, Serialize};
+
+/// A digitized document on Gallica.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GallicaDocument {
+    pub title: String,
+    pub author: Option<String>,
+    /// `dc:date` as given ("1862", "1862-1863").
+    pub date: Option<String>,
+    /// `https://gallica.bnf.fr/ark:/12148/...`
+    pub url: String,
+}
+
+/// CQL query for the Gallica SRU API: monographs whose title (and author,
+/// when given) contain all the words.
+fn sru_query(title: &str, author: Option<&str>) -> String {
+    let mut parts = vec![format!("dc.title all \"{}\"", title.replace('"', " "))];
+    if let Some(a) = author.filter(|a| !a.trim().is_empty()) {
+        parts.push(format!("dc.creator all \"{}\"", a.replace('"', " ")));
+    }
+    parts.push("dc.type all \"monographie\"".to_string());
+    parts.join(" and ")
+}
+
+/// Search public-domain documents on Gallica. Returns up to 10 results.
+#[tracing::instrument(skip_all, fields(provider = "gallica"))]
+pub async fn search_digitized(
+    title: &str,
+    author: Option<&str>,
+) -> Result<Vec<GallicaDocument>, String> {
+    if title.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(8))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let url = format!(
+        "https://gallica.bnf.fr/SRU?version=1.2&operation=searchRetrieve&query={}&maximumRecords=10",
+        urlencoding::encode(&sru_query(title, author))
+    );
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Gallica SRU request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Gallica SRU returned error: {}", response.status()));
+    }
+    let xml = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Gallica SRU response: {}", e))?;
+    parse_sru_response(&xml)
+}
+
+/// Public-domain documents of a Gallica SRU response (Dublin Core records).
+/// Sync, no I/O.
+pub fn parse_sru_response(xml: &str) -> Result<Vec<GallicaDocument>, String> {
+    use quick_xml::events::Event;
+    use quick_xml::reader::Reader;
+
+    #[derive(Default)]
+    struct Fields {
+        title: Option<String>,
+        creator: Option<String>,
+        date: Option<String>,
+        url: Option<String>,
+        public_domain: bool,
+    }
+
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut documents = Vec::new();
+    let mut current = Fields::default();
+    let mut element = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => element = super::unimarc::local_name(e.name().as_ref()),
+            Ok(Event::Text(e)) => {
+                let text = super::xml_text_content(&e);
+                match element.as_str() {
+                    "title" if current.title.is_none() => current.title = Some(text),
+                    "creator" if current.creator.is_none() => current.creator = Some(text),
+                    "date" if current.date.is_none() => current.date = Some(text),
+                    "identifier"
+                        if current.url.is_none() && text.contains("gallica.bnf.fr/ark:") =>
+                    {
+                        current.url = Some(text)
+                    }
+                    "rights" => {
+                        let rights = text.to_lowercase();
+                        current.public_domain |=
+                            rights.contains("domaine public") || rights.contains("public domain");
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                if super::unimarc::local_name(e.name().as_ref()) == "record" {
+                    let fields = std::mem::take(&mut current);
+                    if let (Some(title), Some(url), true) =
+                        (fields.title, fields.url, fields.public_domain)
+                    {
+                        documents.push(GallicaDocument {
+                            title,
+                            // "Hugo, Victor (1802-1885). Auteur du texte"
+                            author: fields
+                                .creator
+                                .map(|c| c.split(". ").next().unwrap_or_default().to_string()),
+                            date: fields.date,
+                            url,
+                        });
+                    }
+                }
+                element.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("XML parse error: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(documents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"<srw:searchRetrieveResponse xmlns:srw="http://www.loc.gov/zing/srw/">
+<srw:records>
+<srw:record><srw:recordData><oai_dc:dc xmlns:oai_dc="http://www.openarchives.org/OAI/2.0/oai_dc/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:identifier>https://gallica.bnf.fr/ark:/12148/bpt6k5842x</dc:identifier>
+<dc:title>Les misérables. Tome 1</dc:title>
+<dc:creator>Hugo, Victor (1802-1885). Auteur du texte</dc:creator>
+<dc:date>1862</dc:date>
+<dc:rights xml:lang="fre">domaine public</dc:rights>
+<dc:rights xml:lang="eng">public domain</dc:rights>
+</oai_dc:dc></srw:recordData></srw:record>
+<srw:record><srw:recordData><oai_dc:dc xmlns:oai_dc="http://www.openarchives.org/OAI/2.0/oai_dc/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:identifier>https://gallica.bnf.fr/ark:/12148/bpt6k3300000</dc:identifier>
+<dc:title>Les misérables, adaptation</dc:title>
+<dc:rights xml:lang="fre">Consultable en ligne par abonnement</dc:rights>
+</oai_dc:dc></srw:recordData></srw:record>
+</srw:records>
+</srw:searchRetrieveResponse>"#;
+
+    #[test]
+    fn keeps_public_domain_documents_only() {
+        let documents = parse_sru_response(RESPONSE).unwrap();
+        assert_eq!(
+            documents,
+            vec![GallicaDocument {
+                title: "Les misérables. Tome 1".to_string(),
+                author: Some("Hugo, Victor (1802-1885)".to_string()),
+                date: Some("1862".to_string()),
+                url: "https://gallica.bnf.fr/ark:/12148/bpt6k5842x".to_string(),
+            }]
+        );
+        assert_eq!(
+            sru_query("Les Misérables", Some("Hugo")),
+            "dc.title all \"Les Misérables\" and dc.creator all \"Hugo\" and dc.type all \"monographie\""
+        );
+    }
+}
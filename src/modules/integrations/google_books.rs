@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::services::api_quota::{self, Provider};
+
 #[derive(Debug, Deserialize)]
 struct GoogleBooksResponse {
     items: Option<Vec<GoogleBookItem>>,
@@ -53,17 +55,21 @@ fn append_api_key(url: &str, api_key: Option<&str>) -> String {
     }
 }
 
+#[tracing::instrument(skip_all, fields(provider = "google_books", %isbn))]
 pub async fn fetch_book_metadata(
     isbn: &str,
     api_key: Option<&str>,
 ) -> Result<BookMetadata, String> {
+    if !api_quota::try_call(Provider::GoogleBooks) {
+        return Err(api_quota::over_quota(Provider::GoogleBooks));
+    }
     let base_url = format!(
         "https://www.googleapis.com/books/v1/volumes?q=isbn:{}",
         isbn
     );
     let url = append_api_key(&base_url, api_key);
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()
         .map_err(|e| e.to_string())?;
@@ -128,6 +134,9 @@ pub async fn fetch_book_metadata(
             cover_url,
             summary: info.description.clone(),
             page_count: info.page_count,
+            edition: None,
+            physical_format: None,
+            dimensions: None,
         });
     }
 
@@ -135,13 +144,16 @@ pub async fn fetch_book_metadata(
 }
 
 pub async fn fetch_cover_url(isbn: &str, api_key: Option<&str>) -> Option<String> {
+    if !api_quota::try_call(Provider::GoogleBooks) {
+        return None;
+    }
     let base_url = format!(
         "https://www.googleapis.com/books/v1/volumes?q=isbn:{}",
         isbn
     );
     let url = append_api_key(&base_url, api_key);
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()
         .ok()?;
@@ -192,7 +204,8 @@ pub async fn fetch_cover_url(isbn: &str, api_key: Option<&str>) -> Option<String
 /// anonymous requests share a single global Google project whose daily quota is
 /// routinely saturated, so an empty `books` list is otherwise indistinguishable
 /// from "no match". Callers use this flag to surface an honest "limite atteinte"
-/// notice instead of a silent empty result.
+/// notice instead of a silent empty result. It is also set when our own daily
+/// quota for Google Books (`services::api_quota`) is spent.
 #[derive(Debug, Default)]
 pub struct GoogleBooksSearchResult {
     pub books: Vec<crate::models::book::Model>,
@@ -201,10 +214,17 @@ pub struct GoogleBooksSearchResult {
 
 const GOOGLE_BOOKS_VOLUMES_URL: &str = "https://www.googleapis.com/books/v1/volumes";
 
+#[tracing::instrument(skip_all, fields(provider = "google_books"))]
 pub async fn search_books(
     query: &crate::api::search::SearchQuery,
     api_key: Option<&str>,
 ) -> GoogleBooksSearchResult {
+    if !api_quota::try_call(Provider::GoogleBooks) {
+        return GoogleBooksSearchResult {
+            quota_exceeded: true,
+            ..Default::default()
+        };
+    }
     search_books_at(GOOGLE_BOOKS_VOLUMES_URL, query, api_key).await
 }
 
@@ -248,10 +268,10 @@ async fn search_books_at(
     let base_url = format!("{}?q={}&maxResults={}", volumes_url, q_str, max_results);
     let url = append_api_key(&base_url, api_key);
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(5))
         .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
+        .unwrap_or_else(|_| crate::services::network_mode::client());
 
     let resp = match client.get(&url).send().await {
         Ok(r) => r,
@@ -344,7 +364,12 @@ async fn search_books_at(
                 digital_formats: None,
                 private: false,
                 page_count: info.page_count.map(|p| p as i32),
+                edition: None,
+                physical_format: None,
+                dimensions: None,
                 loan_duration_days: None,
+                archived: false,
+                digital_source_url: None,
             };
             result.books.push(book);
         }
@@ -389,6 +414,7 @@ mod tests {
             subjects: None,
             sources: None,
             autocomplete: None,
+            collapse_editions: None,
         }
     }
 
@@ -450,6 +476,7 @@ mod tests {
             subjects: None,
             sources: None,
             autocomplete: None,
+            collapse_editions: None,
         };
         let result = search_books_at("http://127.0.0.1:0/books/v1/volumes", &empty, None).await;
         assert!(!result.quota_exceeded);
@@ -0,0 +1,141 @@
+//! Internet Archive integration via its advanced search API
+//!
+//! Finds digitized texts by title and author. Items of the lending library
+//! (`inlibrary`, `printdisabled`) are left out: they are borrowed, not free.
+
+use serde::{Deserialize, Serialize};
+
+/// A text on the Internet Archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveText {
+    pub identifier: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub year: Option<String>,
+}
+
+impl ArchiveText {
+    pub fn url(&self) -> String {
+        format!("https://archive.org/details/{}", self.identifier)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    response: SearchDocs,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchDocs {
+    docs: Vec<serde_json::Value>,
+}
+
+/// Lucene query for the advanced search: free texts matching the title (and
+/// author, when given).
+fn search_query(title: &str, author: Option<&str>) -> String {
+    let phrase = |s: &str| s.replace(['"', '(', ')', ':'], " ");
+    let mut query = format!("title:({})", phrase(title));
+    if let Some(a) = author.filter(|a| !a.trim().is_empty()) {
+        query.push_str(&format!(" AND creator:({})", phrase(a)));
+    }
+    query.push_str(" AND mediatype:texts AND NOT collection:(inlibrary OR printdisabled)");
+    query
+}
+
+/// Search free texts on the Internet Archive. Returns up to 10 results.
+#[tracing::instrument(skip_all, fields(provider = "internet_archive"))]
+pub async fn search_texts(title: &str, author: Option<&str>) -> Result<Vec<ArchiveText>, String> {
+    if title.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(8))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get("https://archive.org/advancedsearch.php")
+        .query(&[
+            ("q", search_query(title, author).as_str()),
+            ("fl[]", "identifier"),
+            ("fl[]", "title"),
+            ("fl[]", "creator"),
+            ("fl[]", "year"),
+            ("rows", "10"),
+            ("output", "json"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Internet Archive request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Internet Archive returned error: {}",
+            response.status()
+        ));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Internet Archive response: {}", e))?;
+    parse_search_response(body)
+}
+
+/// Texts of an advanced search response. `creator` and `year` come as a
+/// string, a number or a list depending on the item.
+pub fn parse_search_response(body: serde_json::Value) -> Result<Vec<ArchiveText>, String> {
+    let parsed: SearchResponse = serde_json::from_value(body)
+        .map_err(|e| format!("Unexpected Internet Archive response: {}", e))?;
+    let first = |v: Option<&serde_json::Value>| -> Option<String> {
+        match v? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            serde_json::Value::Array(a) => a.first().and_then(|x| x.as_str()).map(str::to_string),
+            _ => None,
+        }
+    };
+    Ok(parsed
+        .response
+        .docs
+        .iter()
+        .filter_map(|doc| {
+            Some(ArchiveText {
+                identifier: first(doc.get("identifier"))?,
+                title: first(doc.get("title"))?,
+                author: first(doc.get("creator")),
+                year: first(doc.get("year")),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_docs_whatever_the_field_shapes() {
+        let body = serde_json::json!({
+            "responseHeader": {"status": 0},
+            "response": {"numFound": 3, "docs": [
+                {"identifier": "lesmisrables01hugo", "title": "Les misérables",
+                 "creator": ["Hugo, Victor, 1802-1885"], "year": 1862},
+                {"identifier": "lesmiserables00hugo", "title": "Les misérables",
+                 "creator": "Victor Hugo", "year": "1887"},
+                {"identifier": "untitled-scan"}
+            ]}
+        });
+        let texts = parse_search_response(body).unwrap();
+        assert_eq!(texts.len(), 2);
+        assert_eq!(texts[0].author.as_deref(), Some("Hugo, Victor, 1802-1885"));
+        assert_eq!(texts[0].year.as_deref(), Some("1862"));
+        assert_eq!(
+            texts[1].url(),
+            "https://archive.org/details/lesmiserables00hugo"
+        );
+        assert_eq!(
+            search_query("Les Misérables", Some("Hugo")),
+            "title:(Les Misérables) AND creator:(Hugo) AND mediatype:texts \
+             AND NOT collection:(inlibrary OR printdisabled)"
+        );
+    }
+}
@@ -101,8 +101,9 @@ pub struct Claims {
     pub page_count: Option<Vec<String>>,
 }
 
+#[tracing::instrument(skip_all, fields(provider = "inventaire", %isbn))]
 pub async fn fetch_inventaire_metadata(isbn: &str) -> Result<InventaireMetadata, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
@@ -377,11 +378,12 @@ pub async fn search_inventaire(query: &str) -> Result<Vec<InventaireSearchResult
     search_inventaire_with_lang(query, None).await
 }
 
+#[tracing::instrument(skip_all, fields(provider = "inventaire"))]
 pub async fn search_inventaire_with_lang(
     query: &str,
     lang: Option<&str>,
 ) -> Result<Vec<InventaireSearchResult>, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
@@ -442,7 +444,7 @@ pub async fn enrich_search_results(
         return Ok(results);
     }
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
@@ -783,7 +785,7 @@ pub struct EditionCover {
 /// Lighter than `enrich_search_results`: only fetches edition entities and extracts images.
 /// Used by cover search to get multiple cover variants for a single work.
 pub async fn fetch_work_edition_covers(work_uri: &str) -> Vec<EditionCover> {
-    let client = match reqwest::Client::builder()
+    let client = match crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
@@ -846,7 +848,7 @@ pub async fn fetch_work_edition_covers(work_uri: &str) -> Vec<EditionCover> {
 
 /// Lightweight cover-only lookup: fetch the edition entity and extract its image URL.
 pub async fn fetch_cover_url(isbn: &str) -> Option<String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(3))
         .build()
@@ -862,7 +864,7 @@ pub async fn fetch_cover_url(isbn: &str) -> Option<String> {
 /// title-matched work actually belongs to the expected author before its cover is
 /// accepted on a silent/automatic path. Returns an empty vec on any failure.
 pub async fn fetch_work_authors(work_uri: &str) -> Vec<String> {
-    let client = match reqwest::Client::builder()
+    let client = match crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
@@ -1,9 +1,14 @@
 pub mod bnf;
+pub mod gallica;
 pub mod google_books;
+pub mod internet_archive;
 pub mod inventaire;
+pub mod nominatim;
 pub mod openlibrary;
+pub mod osm;
 pub mod sudoc;
 pub mod unimarc;
+pub mod wikisource;
 
 /// Identifying User-Agent sent on outbound requests to external bibliographic
 /// APIs. A non-empty UA is REQUIRED by OpenLibrary — it returns 403 on its
@@ -0,0 +1,197 @@
+//! OpenStreetMap Nominatim geocoding
+//!
+//! Turns a postal address into coordinates. The public instance allows one
+//! request per second and asks clients to cache, so calls are spaced by
+//! `MIN_INTERVAL` (queued behind each other, never sent in parallel) and
+//! answers, "not found" included, are kept for `CACHE_TTL`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+const NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org/search";
+
+/// Nominatim usage policy: at most one request per second.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+const CACHE_TTL: Duration = Duration::from_secs(24 * 3600);
+const MAX_CACHE_ENTRIES: usize = 500;
+
+/// The address fields sent as a structured query; blank ones are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressQuery {
+    pub street: Option<String>,
+    pub postal_code: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+}
+
+impl AddressQuery {
+    fn params(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("street", &self.street),
+            ("postalcode", &self.postal_code),
+            ("city", &self.city),
+            ("country", &self.country),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let value = value.as_deref()?.trim();
+            (!value.is_empty()).then_some((key, value))
+        })
+        .collect()
+    }
+
+    /// Nothing to look up: Nominatim needs at least a city or a postal code.
+    pub fn is_too_vague(&self) -> bool {
+        !self
+            .params()
+            .iter()
+            .any(|(key, _)| matches!(*key, "city" | "postalcode"))
+    }
+
+    fn cache_key(&self) -> String {
+        self.params()
+            .iter()
+            .map(|(key, value)| format!("{key}={}", value.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+#[derive(Deserialize)]
+struct Place {
+    lat: String,
+    lon: String,
+}
+
+struct CacheEntry {
+    coordinates: Option<(f64, f64)>,
+    created_at: Instant,
+}
+
+static CACHE: Lazy<std::sync::Mutex<HashMap<String, CacheEntry>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// When the last request went out; held while a request is in flight so
+/// callers queue.
+static LAST_REQUEST: Lazy<tokio::sync::Mutex<Option<Instant>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+fn cache_get(key: &str) -> Option<Option<(f64, f64)>> {
+    let cache = CACHE.lock().ok()?;
+    let entry = cache.get(key)?;
+    (entry.created_at.elapsed() < CACHE_TTL).then_some(entry.coordinates)
+}
+
+fn cache_put(key: String, coordinates: Option<(f64, f64)>) {
+    let Ok(mut cache) = CACHE.lock() else {
+        return;
+    };
+    if cache.len() >= MAX_CACHE_ENTRIES {
+        cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.clear();
+        }
+    }
+    cache.insert(
+        key,
+        CacheEntry {
+            coordinates,
+            created_at: Instant::now(),
+        },
+    );
+}
+
+/// `(latitude, longitude)` of the best match of a Nominatim `jsonv2` answer.
+fn parse_first_place(body: &str) -> Result<Option<(f64, f64)>, String> {
+    let places: Vec<Place> =
+        serde_json::from_str(body).map_err(|e| format!("Invalid Nominatim response: {}", e))?;
+    let Some(place) = places.into_iter().next() else {
+        return Ok(None);
+    };
+    match (place.lat.parse::<f64>(), place.lon.parse::<f64>()) {
+        (Ok(lat), Ok(lon)) => Ok(Some((lat, lon))),
+        _ => Err(format!(
+            "Invalid Nominatim coordinates: {}, {}",
+            place.lat, place.lon
+        )),
+    }
+}
+
+/// Coordinates of an address, `None` when Nominatim does not know it.
+#[tracing::instrument(skip_all, fields(provider = "nominatim"))]
+pub async fn geocode(address: &AddressQuery) -> Result<Option<(f64, f64)>, String> {
+    if address.is_too_vague() {
+        return Ok(None);
+    }
+    let key = address.cache_key();
+    if let Some(coordinates) = cache_get(&key) {
+        return Ok(coordinates);
+    }
+
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut last = LAST_REQUEST.lock().await;
+    if let Some(wait) = last.and_then(|at| MIN_INTERVAL.checked_sub(at.elapsed())) {
+        tokio::time::sleep(wait).await;
+    }
+    *last = Some(Instant::now());
+    let mut params = address.params();
+    params.extend([("format", "jsonv2"), ("limit", "1")]);
+    let response = client
+        .get(NOMINATIM_URL)
+        .query(&params)
+        .send()
+        .await
+        .map_err(|e| format!("Nominatim request failed: {}", e))?;
+    drop(last);
+
+    if !response.status().is_success() {
+        return Err(format!("Nominatim returned error: {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Nominatim response: {}", e))?;
+    let coordinates = parse_first_place(&body)?;
+    cache_put(key, coordinates);
+    Ok(coordinates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_place_gives_the_coordinates() {
+        let body = r#"[{"place_id": 1, "lat": "48.8566", "lon": "2.3522",
+                        "display_name": "Paris, France"},
+                       {"place_id": 2, "lat": "33.66", "lon": "-95.55"}]"#;
+        assert_eq!(parse_first_place(body).unwrap(), Some((48.8566, 2.3522)));
+        assert_eq!(parse_first_place("[]").unwrap(), None);
+
+        let street_only = AddressQuery {
+            street: Some("1 rue de Rivoli".to_string()),
+            country: Some("France".to_string()),
+            ..Default::default()
+        };
+        assert!(street_only.is_too_vague());
+        let with_city = AddressQuery {
+            city: Some(" Paris ".to_string()),
+            postal_code: Some("".to_string()),
+            ..street_only
+        };
+        assert!(!with_city.is_too_vague());
+        assert_eq!(
+            with_city.cache_key(),
+            "street=1 rue de rivoli&city=paris&country=france"
+        );
+    }
+}
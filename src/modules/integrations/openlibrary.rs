@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use super::API_USER_AGENT;
+use crate::services::api_quota::{self, Provider};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BookMetadata {
@@ -13,6 +14,15 @@ pub struct BookMetadata {
     pub cover_url: Option<String>,
     pub summary: Option<String>,
     pub page_count: Option<u32>,
+    /// Edition statement ("2nd ed.", "Folio").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<String>,
+    /// Binding, normalized onto `models::book::PHYSICAL_FORMATS`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physical_format: Option<String>,
+    /// Free-text dimensions as the provider states them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,13 +57,17 @@ struct OpenLibraryCover {
     large: Option<String>,
 }
 
+#[tracing::instrument(skip_all, fields(provider = "openlibrary", %isbn))]
 pub async fn fetch_book_metadata(isbn: &str) -> Result<BookMetadata, String> {
+    if !api_quota::try_call(Provider::OpenLibrary) {
+        return Err(api_quota::over_quota(Provider::OpenLibrary));
+    }
     let url = format!(
         "https://openlibrary.org/api/books?bibkeys=ISBN:{}&format=json&jscmd=data",
         isbn
     );
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(8))
         .build()
@@ -112,8 +126,8 @@ pub async fn fetch_book_metadata(isbn: &str) -> Result<BookMetadata, String> {
             .as_ref()
             .and_then(|c| c.large.clone().or(c.medium.clone()));
 
-        // Fetch description from edition/work API
-        let summary = fetch_description(isbn).await;
+        // Fetch description and physical description from edition/work API
+        let details = fetch_edition_details(isbn).await;
 
         Ok(BookMetadata {
             title: book.title.clone(),
@@ -121,28 +135,80 @@ pub async fn fetch_book_metadata(isbn: &str) -> Result<BookMetadata, String> {
             publisher,
             publication_year: book.publish_date.clone(),
             cover_url,
-            summary,
+            summary: details.summary,
             page_count: book.number_of_pages,
+            edition: details.edition,
+            physical_format: details.physical_format,
+            dimensions: details.dimensions,
         })
     } else {
         Err("Book not found".to_string())
     }
 }
 
-/// Fetch description from Open Library edition and/or work API.
-/// Tries edition-level description first, then follows to the parent work.
-async fn fetch_description(isbn: &str) -> Option<String> {
-    let client = reqwest::Client::builder()
+/// Edition-level fields read from `/isbn/{isbn}.json`.
+#[derive(Debug, Default)]
+struct EditionDetails {
+    summary: Option<String>,
+    edition: Option<String>,
+    physical_format: Option<String>,
+    dimensions: Option<String>,
+}
+
+/// Fetch the edition record once and pull the description and physical
+/// description from it. Any network or parse failure yields empty details.
+async fn fetch_edition_details(isbn: &str) -> EditionDetails {
+    let Ok(client) = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(5))
         .build()
-        .ok()?;
+    else {
+        return EditionDetails::default();
+    };
 
     let url = format!("https://openlibrary.org/isbn/{}.json", isbn);
-    let edition: serde_json::Value = client.get(&url).send().await.ok()?.json().await.ok()?;
+    let edition: serde_json::Value = match client.get(&url).send().await {
+        Ok(resp) => match resp.json().await {
+            Ok(v) => v,
+            Err(_) => return EditionDetails::default(),
+        },
+        Err(_) => return EditionDetails::default(),
+    };
+
+    let mut details = parse_edition_physical(&edition);
+    details.summary = fetch_description(&client, &edition).await;
+    details
+}
+
+/// Read `edition_name`, `physical_format` and `physical_dimensions` from an
+/// Open Library edition record.
+fn parse_edition_physical(edition: &serde_json::Value) -> EditionDetails {
+    let text = |key: &str| {
+        edition
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    EditionDetails {
+        summary: None,
+        edition: text("edition_name"),
+        physical_format: text("physical_format")
+            .and_then(|f| crate::models::book::normalize_physical_format(&f))
+            .map(String::from),
+        dimensions: text("physical_dimensions"),
+    }
+}
 
+/// Description from an Open Library edition record, or from its parent work.
+/// Tries edition-level description first, then follows to the parent work.
+async fn fetch_description(
+    client: &reqwest::Client,
+    edition: &serde_json::Value,
+) -> Option<String> {
     // Try edition-level description first
-    if let Some(desc) = extract_ol_description(&edition) {
+    if let Some(desc) = extract_ol_description(edition) {
         return Some(desc);
     }
 
@@ -172,18 +238,35 @@ fn extract_ol_description(json: &serde_json::Value) -> Option<String> {
     }
 }
 
+#[tracing::instrument(skip_all, fields(provider = "openlibrary"))]
 pub async fn search_books(query: &str) -> Result<Vec<BookMetadata>, String> {
-    let url = format!(
+    run_search(&format!(
         "https://openlibrary.org/search.json?q={}&limit=10&fields=title,author_name,first_publish_year,cover_i,key,publisher",
         query
-    );
+    ))
+    .await
+}
+
+/// Works Open Library credits to `author`, most relevant first.
+#[tracing::instrument(skip_all, fields(provider = "openlibrary"))]
+pub async fn search_author_works(author: &str) -> Result<Vec<BookMetadata>, String> {
+    run_search(&format!(
+        "https://openlibrary.org/search.json?author={}&limit=50&fields=title,author_name,first_publish_year,cover_i,key,publisher",
+        urlencoding::encode(author)
+    ))
+    .await
+}
 
-    let client = reqwest::Client::builder()
+async fn run_search(url: &str) -> Result<Vec<BookMetadata>, String> {
+    if !api_quota::try_call(Provider::OpenLibrary) {
+        return Err(api_quota::over_quota(Provider::OpenLibrary));
+    }
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .map_err(|e| format!("Failed to send request: {}", e))?;
@@ -233,6 +316,9 @@ pub async fn search_books(query: &str) -> Result<Vec<BookMetadata>, String> {
                 cover_url,
                 summary: None,
                 page_count: None,
+                edition: None,
+                physical_format: None,
+                dimensions: None,
             }
         })
         .collect();
@@ -258,10 +344,13 @@ struct OpenLibrarySearchDoc {
 /// Uses `?default=false` so OpenLibrary returns 404 for missing covers
 /// instead of redirecting to a 1x1 transparent placeholder.
 pub async fn fetch_cover_url(isbn: &str) -> Option<String> {
+    if !api_quota::try_call(Provider::OpenLibrary) {
+        return None;
+    }
     let cover_url = format!("https://covers.openlibrary.org/b/isbn/{}-L.jpg", isbn);
     let check_url = format!("{}?default=false", &cover_url);
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .user_agent(API_USER_AGENT)
         .timeout(std::time::Duration::from_secs(3))
         .build()
@@ -278,6 +367,22 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_parse_edition_physical() {
+        let data = json!({
+            "edition_name": "1st ed.",
+            "physical_format": "Hardcover",
+            "physical_dimensions": "24 x 16 x 3 centimeters"
+        });
+        let details = parse_edition_physical(&data);
+        assert_eq!(details.edition.as_deref(), Some("1st ed."));
+        assert_eq!(details.physical_format.as_deref(), Some("hardcover"));
+        assert_eq!(
+            details.dimensions.as_deref(),
+            Some("24 x 16 x 3 centimeters")
+        );
+    }
+
     #[test]
     fn test_extract_ol_description_plain_string() {
         let data = json!({ "description": "A classic novel about identity." });
@@ -0,0 +1,208 @@
+//! OpenStreetMap integration via the Overpass API
+//!
+//! Finds the libraries and bookshops around a point. OSM knows places, not
+//! their stock: a place found here is somewhere to ask, not a copy on hold.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+const OVERPASS_URL: &str = "https://overpass-api.de/api/interpreter";
+
+/// Places returned by one query, nearest first.
+const MAX_PLACES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceKind {
+    /// `amenity=library`
+    Library,
+    /// `shop=books`
+    Bookshop,
+}
+
+/// A library or bookshop mapped on OpenStreetMap.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BookPlace {
+    pub name: String,
+    pub kind: PlaceKind,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Metres from the point searched around, as the crow flies
+    pub distance_m: u32,
+    /// "12 rue de la Paix, Paris", when mapped
+    pub address: Option<String>,
+    /// OSM `opening_hours` syntax ("Mo-Fr 10:00-19:00")
+    pub opening_hours: Option<String>,
+    pub website: Option<String>,
+    /// `https://www.openstreetmap.org/node/...`
+    pub osm_url: String,
+}
+
+/// Overpass QL for named libraries and bookshops within `radius_m`. Ways
+/// and relations (a library building) come with their centre.
+fn overpass_query(lat: f64, lon: f64, radius_m: u32) -> String {
+    format!(
+        "[out:json][timeout:10];(\
+         nwr[\"amenity\"=\"library\"][\"name\"](around:{radius_m},{lat},{lon});\
+         nwr[\"shop\"=\"books\"][\"name\"](around:{radius_m},{lat},{lon}););\
+         out center tags;"
+    )
+}
+
+#[derive(Deserialize)]
+struct OverpassResponse {
+    elements: Vec<Element>,
+}
+
+#[derive(Deserialize)]
+struct Element {
+    #[serde(rename = "type")]
+    element_type: String,
+    id: i64,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    center: Option<Center>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct Center {
+    lat: f64,
+    lon: f64,
+}
+
+/// Great-circle distance in metres (haversine).
+pub fn distance_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+fn address(tags: &HashMap<String, String>) -> Option<String> {
+    let street = match (tags.get("addr:housenumber"), tags.get("addr:street")) {
+        (Some(number), Some(street)) => Some(format!("{number} {street}")),
+        (None, Some(street)) => Some(street.clone()),
+        _ => None,
+    };
+    let parts: Vec<String> = [street, tags.get("addr:city").cloned()]
+        .into_iter()
+        .flatten()
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// The places of an Overpass JSON answer, nearest to (`lat`, `lon`) first.
+fn parse_places(body: &str, lat: f64, lon: f64) -> Result<Vec<BookPlace>, String> {
+    let response: OverpassResponse =
+        serde_json::from_str(body).map_err(|e| format!("Invalid Overpass response: {}", e))?;
+    let mut places: Vec<BookPlace> = response
+        .elements
+        .into_iter()
+        .filter_map(|el| {
+            let (plat, plon) = match (el.lat, el.lon, &el.center) {
+                (Some(plat), Some(plon), _) => (plat, plon),
+                (_, _, Some(c)) => (c.lat, c.lon),
+                _ => return None,
+            };
+            let kind = if el.tags.get("amenity").map(String::as_str) == Some("library") {
+                PlaceKind::Library
+            } else if el.tags.get("shop").map(String::as_str) == Some("books") {
+                PlaceKind::Bookshop
+            } else {
+                return None;
+            };
+            Some(BookPlace {
+                name: el.tags.get("name")?.clone(),
+                kind,
+                latitude: plat,
+                longitude: plon,
+                distance_m: distance_m(lat, lon, plat, plon).round() as u32,
+                address: address(&el.tags),
+                opening_hours: el.tags.get("opening_hours").cloned(),
+                website: el
+                    .tags
+                    .get("website")
+                    .or_else(|| el.tags.get("contact:website"))
+                    .cloned(),
+                osm_url: format!(
+                    "https://www.openstreetmap.org/{}/{}",
+                    el.element_type, el.id
+                ),
+            })
+        })
+        .collect();
+    places.sort_by_key(|p| p.distance_m);
+    places.truncate(MAX_PLACES);
+    Ok(places)
+}
+
+/// Libraries and bookshops within `radius_m` metres of (`lat`, `lon`).
+#[tracing::instrument(skip_all, fields(provider = "osm"))]
+pub async fn nearby_book_places(
+    lat: f64,
+    lon: f64,
+    radius_m: u32,
+) -> Result<Vec<BookPlace>, String> {
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(12))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let url = format!(
+        "{}?data={}",
+        OVERPASS_URL,
+        urlencoding::encode(&overpass_query(lat, lon, radius_m))
+    );
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Overpass request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Overpass returned error: {}", response.status()));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read Overpass response: {}", e))?;
+    parse_places(&body, lat, lon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_come_nearest_first_with_their_details() {
+        let body = r#"{"elements": [
+            {"type": "node", "id": 1, "lat": 48.8600, "lon": 2.3500,
+             "tags": {"shop": "books", "name": "Librairie du Marais",
+                      "addr:housenumber": "12", "addr:street": "rue des Archives",
+                      "addr:city": "Paris", "opening_hours": "Tu-Sa 10:00-19:00"}},
+            {"type": "way", "id": 2, "center": {"lat": 48.8570, "lon": 2.3520},
+             "tags": {"amenity": "library", "name": "Bibliothèque Forney",
+                      "contact:website": "https://example.org"}},
+            {"type": "node", "id": 3, "lat": 48.8571, "lon": 2.3521,
+             "tags": {"amenity": "cafe", "name": "Not a library"}}
+        ]}"#;
+
+        let places = parse_places(body, 48.8566, 2.3522).unwrap();
+
+        assert_eq!(places.len(), 2);
+        assert_eq!(places[0].kind, PlaceKind::Library);
+        assert_eq!(places[0].osm_url, "https://www.openstreetmap.org/way/2");
+        assert_eq!(places[0].website.as_deref(), Some("https://example.org"));
+        assert!(places[0].distance_m < 100);
+        assert_eq!(places[1].name, "Librairie du Marais");
+        assert_eq!(
+            places[1].address.as_deref(),
+            Some("12 rue des Archives, Paris")
+        );
+        assert!((300..500).contains(&places[1].distance_m));
+    }
+}
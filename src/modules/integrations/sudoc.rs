@@ -1,6 +1,4 @@
 use once_cell::sync::Lazy;
-use quick_xml::events::Event;
-use quick_xml::reader::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
@@ -15,6 +13,15 @@ struct CacheEntry {
 static SUDOC_CACHE: Lazy<Mutex<HashMap<String, CacheEntry>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Cache entry for SUDOC SRU searches, keyed by CQL query
+struct SearchCacheEntry {
+    data: Vec<SudocBook>,
+    created_at: Instant,
+}
+
+static SUDOC_SEARCH_CACHE: Lazy<Mutex<HashMap<String, SearchCacheEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 const CACHE_TTL: Duration = Duration::from_secs(3600); // 1 hour
 const MAX_CACHE_ENTRIES: usize = 100;
 
@@ -27,10 +34,14 @@ pub struct SudocBook {
     pub dewey: Option<String>,
     pub subjects: Vec<String>,
     pub summary: Option<String>,
+    /// Set on search results; a lookup by ISBN already has it.
+    #[serde(default)]
+    pub isbn: Option<String>,
     pub ppn: String,
     pub raw_data: Option<String>,
 }
 
+#[tracing::instrument(skip_all, fields(provider = "sudoc", %isbn))]
 pub async fn fetch_by_isbn(isbn: &str) -> Result<SudocBook, String> {
     let clean_isbn = isbn.replace('-', "");
 
@@ -46,7 +57,7 @@ pub async fn fetch_by_isbn(isbn: &str) -> Result<SudocBook, String> {
     // URL: https://www.sudoc.fr/services/isbn2ppn/{isbn}
     // Response is JSON: {"sudoc":{"query":{"isbn":"..."},"result":[{"ppn":"..."}]}}
 
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(8))
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
@@ -108,127 +119,120 @@ pub async fn fetch_by_isbn(isbn: &str) -> Result<SudocBook, String> {
     Ok(book)
 }
 
-fn parse_sudoc_xml(xml: &str, ppn: &str) -> Result<SudocBook, String> {
-    let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
+/// Parse a SUDOC UNIMARC record. Sync, no I/O (also driven by the `hot_paths`
+/// benchmark).
+pub fn parse_sudoc_xml(xml: &str, ppn: &str) -> Result<SudocBook, String> {
+    let record = super::unimarc::parse_records(xml)?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let mut book = SudocBook::from_record(record);
+    book.ppn = ppn.to_string();
+    book.raw_data = Some(xml.to_string());
+    Ok(book)
+}
+
+impl SudocBook {
+    fn from_record(record: super::unimarc::Record) -> Self {
+        SudocBook {
+            author: record.author(),
+            title: record.title,
+            publisher: record.publisher,
+            publication_year: record.year,
+            dewey: record.dewey,
+            subjects: record.subjects,
+            summary: record.summary,
+            isbn: record.isbn,
+            ppn: record.id.unwrap_or_default(),
+            raw_data: None,
+        }
+    }
+}
 
-    let mut title = String::new();
-    let mut publisher = None;
-    let mut year = None;
-    let mut dewey = None;
-    let mut subjects = Vec::new();
-    let mut summary = None;
+/// CQL query for the SUDOC SRU endpoint: every word of the title must be in
+/// the title index (`mti`), every word of the author in the person index
+/// (`per`). The free query goes to all indexes (`tou`) when neither is set.
+fn sru_query(query: &str, title: Option<&str>, author: Option<&str>) -> Option<String> {
+    let words = |index: &str, text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| format!("{}={}", index, w.to_lowercase()))
+            .collect()
+    };
+    let mut parts = Vec::new();
+    if let Some(t) = title {
+        parts.extend(words("mti", t));
+    }
+    if let Some(a) = author {
+        parts.extend(words("per", a));
+    }
+    if parts.is_empty() {
+        parts.extend(words("tou", query));
+    }
+    (!parts.is_empty()).then(|| parts.join(" and "))
+}
 
-    // UNIMARC author candidates, in priority order.
-    // 700 = main author, 701 = alternative, 702 = secondary (translator/editor).
-    // 200 $f is the free-text statement of responsibility and must only be a
-    // last-resort fallback (it contains sentences like "présenté par X", not
-    // a clean author name).
-    let mut author_700: (Option<String>, Option<String>) = (None, None);
-    let mut author_701: (Option<String>, Option<String>) = (None, None);
-    let mut author_702: (Option<String>, Option<String>) = (None, None);
-    let mut responsibility_200f: Option<String> = None;
+/// Search SUDOC by title and/or author (SRU API). Returns up to 20 records,
+/// those without a title left out.
+#[tracing::instrument(skip_all, fields(provider = "sudoc"))]
+pub async fn search_sudoc_sru(
+    query: &str,
+    title: Option<&str>,
+    author: Option<&str>,
+) -> Result<Vec<SudocBook>, String> {
+    let Some(cql) = sru_query(query, title, author) else {
+        return Ok(Vec::new());
+    };
 
-    let mut buf = Vec::new();
-    let mut current_tag = String::new();
-    let mut current_code = String::new();
+    if let Ok(cache) = SUDOC_SEARCH_CACHE.try_lock()
+        && let Some(entry) = cache.get(&cql)
+        && entry.created_at.elapsed() < CACHE_TTL
+    {
+        return Ok(entry.data.clone());
+    }
 
-    // Simple parser state machine
-    // Note: SUDOC XML is MARCXML-like but specific (UNIMARC).
-    // We look for specific datafields.
-    // 200 $a = Title
-    // 200 $f = Statement of responsibility (free text, fallback only)
-    // 210 $c = Publisher
-    // 210 $d = Year
-    // 330 $a = Summary / abstract (4ème de couverture)
-    // 676 $a = Dewey
-    // 606 $a = Subject (RAMEAU)
-    // 700/701/702 $a = Author surname, $b = Author firstname
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(8))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let url = format!(
+        "https://www.sudoc.abes.fr/cbs/sru/?version=1.1&operation=searchRetrieve&query={}&maximumRecords=20&recordSchema=unimarc",
+        urlencoding::encode(&cql)
+    );
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("SUDOC SRU search failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("SUDOC SRU returned error: {}", response.status()));
+    }
+    let xml = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read SUDOC SRU response: {}", e))?;
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(e)) => {
-                let qname = e.name();
-                let name = std::str::from_utf8(qname.as_ref()).unwrap_or("");
-                if name == "datafield" {
-                    // Extract 'tag' attribute
-                    for a in e.attributes().flatten() {
-                        if a.key.as_ref() == b"tag" {
-                            current_tag = String::from_utf8_lossy(&a.value).to_string();
-                        }
-                    }
-                } else if name == "subfield" {
-                    // Extract 'code' attribute
-                    for a in e.attributes().flatten() {
-                        if a.key.as_ref() == b"code" {
-                            current_code = String::from_utf8_lossy(&a.value).to_string();
-                        }
-                    }
-                }
-            }
-            Ok(Event::Text(e)) => {
-                let text = super::xml_text_content(&e);
+    let books: Vec<SudocBook> = super::unimarc::parse_records(&xml)?
+        .into_iter()
+        .filter(|r| !r.title.is_empty())
+        .map(SudocBook::from_record)
+        .collect();
 
-                match (current_tag.as_str(), current_code.as_str()) {
-                    ("200", "a") => title = text,
-                    ("200", "f") if responsibility_200f.is_none() => {
-                        responsibility_200f = Some(text)
-                    }
-                    ("700", "a") if author_700.1.is_none() => author_700.1 = Some(text),
-                    ("700", "b") if author_700.0.is_none() => author_700.0 = Some(text),
-                    ("701", "a") if author_701.1.is_none() => author_701.1 = Some(text),
-                    ("701", "b") if author_701.0.is_none() => author_701.0 = Some(text),
-                    ("702", "a") if author_702.1.is_none() => author_702.1 = Some(text),
-                    ("702", "b") if author_702.0.is_none() => author_702.0 = Some(text),
-                    ("210", "c") => publisher = Some(text),
-                    ("210", "d") => {
-                        // Extract year (first 4 digits)
-                        if let Ok(y) = text
-                            .chars()
-                            .filter(|c| c.is_ascii_digit())
-                            .take(4)
-                            .collect::<String>()
-                            .parse::<i32>()
-                        {
-                            year = Some(y);
-                        }
-                    }
-                    ("330", "a") => summary = Some(text),
-                    ("676", "a") => dewey = Some(text),
-                    ("606", "a") => subjects.push(text),
-                    _ => {}
-                }
-            }
-            Ok(Event::End(e)) => {
-                let qname = e.name();
-                let name = std::str::from_utf8(qname.as_ref()).unwrap_or("");
-                if name == "datafield" {
-                    current_tag.clear();
-                } else if name == "subfield" {
-                    current_code.clear();
-                }
-            }
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(format!("XML Parse Error: {}", e)),
-            _ => (),
+    if let Ok(mut cache) = SUDOC_SEARCH_CACHE.try_lock() {
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.retain(|_, entry| entry.created_at.elapsed() < CACHE_TTL);
         }
-        buf.clear();
+        cache.insert(
+            cql,
+            SearchCacheEntry {
+                data: books.clone(),
+                created_at: Instant::now(),
+            },
+        );
     }
 
-    let author =
-        super::unimarc::compose_author(author_700, author_701, author_702, responsibility_200f);
-
-    Ok(SudocBook {
-        title,
-        author,
-        publisher,
-        publication_year: year,
-        dewey,
-        subjects,
-        summary,
-        ppn: ppn.to_string(),
-        raw_data: Some(xml.to_string()),
-    })
+    Ok(books)
 }
 
 #[cfg(test)]
@@ -253,4 +257,17 @@ mod tests {
             "Author must come from UNIMARC 700, not the 200$f responsibility statement",
         );
     }
+
+    #[test]
+    fn search_words_go_to_the_title_and_person_indexes() {
+        assert_eq!(
+            sru_query("", Some("L'Étranger"), Some("Camus")).as_deref(),
+            Some("mti=l and mti=étranger and per=camus")
+        );
+        assert_eq!(
+            sru_query("droit civil", None, None).as_deref(),
+            Some("tou=droit and tou=civil")
+        );
+        assert_eq!(sru_query(" ", Some(""), None), None);
+    }
 }
@@ -34,6 +34,178 @@ fn join_name(parts: AuthorParts) -> Option<String> {
     }
 }
 
+/// The fields of one UNIMARC record read by the SUDOC and BNF integrations.
+#[derive(Debug, Default, Clone)]
+pub struct Record {
+    /// Control field `001`: the record identifier (the PPN on SUDOC).
+    pub id: Option<String>,
+    /// Control field `003`: the record permalink (holds the ARK on BNF).
+    pub permalink: Option<String>,
+    /// `010 $a` or `073 $a`, without hyphens, when 10 or 13 characters long.
+    pub isbn: Option<String>,
+    /// `200 $a`.
+    pub title: String,
+    /// `200 $f`, free text: see [`compose_author`].
+    pub responsibility: Option<String>,
+    /// `700`, `701` and `702` (`$b` firstname, `$a` surname).
+    pub author_700: AuthorParts,
+    pub author_701: AuthorParts,
+    pub author_702: AuthorParts,
+    /// `500 $3`: authority record of the work (uniform title), shared by
+    /// its editions and translations.
+    pub work_authority: Option<String>,
+    /// `205 $a`.
+    pub edition: Option<String>,
+    /// `210 $c` or `214 $c`.
+    pub publisher: Option<String>,
+    /// First four digits of `210 $d` or `214 $d`.
+    pub year: Option<i32>,
+    /// `215 $a` (material extent), raw.
+    pub extent: Option<String>,
+    /// `215 $d`.
+    pub dimensions: Option<String>,
+    /// `330 $a`.
+    pub summary: Option<String>,
+    /// Every `606 $a` (RAMEAU subject).
+    pub subjects: Vec<String>,
+    /// `676 $a`.
+    pub dewey: Option<String>,
+    /// Every `856 $u` that is a URL (electronic location, e.g. Gallica).
+    pub links: Vec<String>,
+}
+
+impl Record {
+    /// The author label, see [`compose_author`].
+    pub fn author(&self) -> Option<String> {
+        compose_author(
+            self.author_700.clone(),
+            self.author_701.clone(),
+            self.author_702.clone(),
+            self.responsibility.clone(),
+        )
+    }
+
+    fn read(&mut self, tag: &str, code: &str, text: String) {
+        match (tag, code) {
+            ("010", "a") | ("073", "a") => {
+                let isbn = text.replace('-', "");
+                if isbn.len() == 13 || isbn.len() == 10 {
+                    self.isbn = Some(isbn);
+                }
+            }
+            ("200", "a") => self.title = text,
+            ("200", "f") if self.responsibility.is_none() => self.responsibility = Some(text),
+            ("700", "a") if self.author_700.1.is_none() => self.author_700.1 = Some(text),
+            ("700", "b") if self.author_700.0.is_none() => self.author_700.0 = Some(text),
+            ("701", "a") if self.author_701.1.is_none() => self.author_701.1 = Some(text),
+            ("701", "b") if self.author_701.0.is_none() => self.author_701.0 = Some(text),
+            ("702", "a") if self.author_702.1.is_none() => self.author_702.1 = Some(text),
+            ("702", "b") if self.author_702.0.is_none() => self.author_702.0 = Some(text),
+            ("500", "3") if self.work_authority.is_none() => self.work_authority = Some(text),
+            ("205", "a") if self.edition.is_none() => self.edition = Some(text),
+            ("210", "c") | ("214", "c") => self.publisher = Some(text),
+            ("210", "d") | ("214", "d") => {
+                self.year = text
+                    .chars()
+                    .filter(|c| c.is_ascii_digit())
+                    .collect::<String>()
+                    .get(0..4)
+                    .and_then(|y| y.parse::<i32>().ok());
+            }
+            ("215", "a") if self.extent.is_none() => self.extent = Some(text),
+            ("215", "d") if self.dimensions.is_none() => self.dimensions = Some(text),
+            ("330", "a") => self.summary = Some(text),
+            ("606", "a") => self.subjects.push(text),
+            ("676", "a") => self.dewey = Some(text),
+            ("856", "u") if text.starts_with("http") => self.links.push(text),
+            _ => {}
+        }
+    }
+}
+
+/// Local name of an XML element (`mxc:record` -> `record`).
+pub(super) fn local_name(name: &[u8]) -> String {
+    let name = String::from_utf8_lossy(name);
+    name.rsplit(':').next().unwrap_or_default().to_string()
+}
+
+fn attribute(e: &quick_xml::events::BytesStart, key: &[u8]) -> String {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == key)
+        .map(|a| String::from_utf8_lossy(&a.value).to_string())
+        .unwrap_or_default()
+}
+
+/// Every record of a UNIMARC XML document: a bare record (SUDOC `{ppn}.xml`)
+/// or an SRU response wrapping several (SUDOC and BNF SRU). Records without
+/// any field are left out. Sync, no I/O.
+pub fn parse_records(xml: &str) -> Result<Vec<Record>, String> {
+    use quick_xml::events::Event;
+    use quick_xml::reader::Reader;
+
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut records = Vec::new();
+    let mut current = Record::default();
+    let mut touched = false;
+    let mut buf = Vec::new();
+    // Datafield tag and subfield code, or controlfield tag with no code.
+    let mut tag = String::new();
+    let mut code = String::new();
+    let mut in_controlfield = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match local_name(e.name().as_ref()).as_str() {
+                "datafield" => tag = attribute(&e, b"tag"),
+                "subfield" => code = attribute(&e, b"code"),
+                "controlfield" => {
+                    in_controlfield = true;
+                    tag = attribute(&e, b"tag");
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                let text = super::xml_text_content(&e);
+                if in_controlfield {
+                    match tag.as_str() {
+                        "001" => current.id = Some(text),
+                        "003" => current.permalink = Some(text),
+                        _ => {}
+                    }
+                    touched = true;
+                } else if !tag.is_empty() && !code.is_empty() {
+                    current.read(&tag, &code, text);
+                    touched = true;
+                }
+            }
+            Ok(Event::End(e)) => match local_name(e.name().as_ref()).as_str() {
+                "record" => {
+                    let record = std::mem::take(&mut current);
+                    if std::mem::take(&mut touched) {
+                        records.push(record);
+                    }
+                }
+                "datafield" => tag.clear(),
+                "subfield" => code.clear(),
+                "controlfield" => {
+                    in_controlfield = false;
+                    tag.clear();
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("XML parse error: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(records)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,6 +260,36 @@ mod tests {
         assert!(author.is_none());
     }
 
+    #[test]
+    fn parses_every_record_of_an_sru_response() {
+        let xml = r#"<srw:searchRetrieveResponse xmlns:srw="http://www.loc.gov/zing/srw/">
+  <srw:records>
+    <srw:record><srw:recordData><record>
+      <controlfield tag="001">123456789</controlfield>
+      <datafield tag="010" ind1=" " ind2=" "><subfield code="a">978-2-07-040850-4</subfield></datafield>
+      <datafield tag="200" ind1="1" ind2=" "><subfield code="a">L'étranger</subfield></datafield>
+      <datafield tag="210" ind1=" " ind2=" "><subfield code="c">Gallimard</subfield><subfield code="d">DL 1972</subfield></datafield>
+      <datafield tag="606" ind1=" " ind2=" "><subfield code="a">Absurde</subfield></datafield>
+      <datafield tag="700" ind1=" " ind2="1"><subfield code="a">Camus</subfield><subfield code="b">Albert</subfield></datafield>
+    </record></srw:recordData></srw:record>
+    <srw:record><srw:recordData><record>
+      <controlfield tag="001">987654321</controlfield>
+      <datafield tag="200" ind1="1" ind2=" "><subfield code="a">La peste</subfield></datafield>
+    </record></srw:recordData></srw:record>
+  </srw:records>
+</srw:searchRetrieveResponse>"#;
+        let records = parse_records(xml).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id.as_deref(), Some("123456789"));
+        assert_eq!(records[0].isbn.as_deref(), Some("9782070408504"));
+        assert_eq!(records[0].title, "L'étranger");
+        assert_eq!(records[0].author().as_deref(), Some("Albert Camus"));
+        assert_eq!(records[0].year, Some(1972));
+        assert_eq!(records[0].subjects, vec!["Absurde"]);
+        assert_eq!(records[1].title, "La peste");
+        assert_eq!(records[1].author(), None);
+    }
+
     #[test]
     fn handles_surname_only() {
         let author = compose_author(
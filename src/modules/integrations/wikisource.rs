@@ -0,0 +1,113 @@
+//! Wikisource integration via the MediaWiki search API
+//!
+//! Finds transcribed texts by title on the Wikisource of a given language.
+//! Everything on Wikisource is free to read.
+
+use serde::{Deserialize, Serialize};
+
+/// A page of a Wikisource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WikisourcePage {
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    query: SearchResults,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    search: Vec<SearchHit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchHit {
+    title: String,
+}
+
+/// Search the main namespace of the `lang` Wikisource ("fr", "en", ...).
+/// Returns up to 5 pages.
+#[tracing::instrument(skip_all, fields(provider = "wikisource", %lang))]
+pub async fn search_pages(
+    lang: &str,
+    title: &str,
+    author: Option<&str>,
+) -> Result<Vec<WikisourcePage>, String> {
+    // A language code is a host label: anything else cannot be one.
+    if title.trim().is_empty() || lang.is_empty() || !lang.chars().all(|c| c.is_ascii_lowercase()) {
+        return Ok(Vec::new());
+    }
+    let search = match author {
+        Some(a) if !a.trim().is_empty() => format!("{} {}", title, a),
+        _ => title.to_string(),
+    };
+    let client = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(8))
+        .user_agent(super::API_USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(format!("https://{}.wikisource.org/w/api.php", lang))
+        .query(&[
+            ("action", "query"),
+            ("list", "search"),
+            ("srsearch", search.as_str()),
+            ("srnamespace", "0"),
+            ("srlimit", "5"),
+            ("format", "json"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Wikisource request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Wikisource returned error: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Wikisource response: {}", e))?;
+    parse_search_response(lang, body)
+}
+
+/// Pages of a `list=search` response, with their URL on the `lang` Wikisource.
+pub fn parse_search_response(
+    lang: &str,
+    body: serde_json::Value,
+) -> Result<Vec<WikisourcePage>, String> {
+    let parsed: SearchResponse = serde_json::from_value(body)
+        .map_err(|e| format!("Unexpected Wikisource response: {}", e))?;
+    Ok(parsed
+        .query
+        .search
+        .into_iter()
+        .map(|hit| WikisourcePage {
+            url: format!(
+                "https://{}.wikisource.org/wiki/{}",
+                lang,
+                urlencoding::encode(&hit.title.replace(' ', "_"))
+            ),
+            title: hit.title,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_urls_use_underscores() {
+        let body = serde_json::json!({
+            "batchcomplete": "",
+            "query": {"search": [{"ns": 0, "title": "Les Misérables", "pageid": 1}]}
+        });
+        let pages = parse_search_response("fr", body).unwrap();
+        assert_eq!(pages[0].title, "Les Misérables");
+        assert_eq!(
+            pages[0].url,
+            "https://fr.wikisource.org/wiki/Les_Mis%C3%A9rables"
+        );
+    }
+}
@@ -1,8 +1,11 @@
+pub mod book_club;
+pub mod book_files;
 pub mod book_notes;
 pub mod hangman;
 pub mod import;
 pub mod integrations;
 pub mod memory_game;
 pub mod operation_log_viewer;
+pub mod registry;
 pub mod scanner;
 pub mod sliding_puzzle;
@@ -0,0 +1,250 @@
+//! Feature modules an installation can switch off, and the HTTP gate enforcing
+//! it.
+//!
+//! A module is on unless `installation_profile.enabled_modules` carries its
+//! `disable_module:<id>` flag, the same opt-out convention as
+//! `disable_fallback:<provider>`, so installations created before the registry
+//! keep every feature. [`module_gate_middleware`] answers 403 `module_disabled`
+//! on the routes a disabled module owns, owner and peer-facing alike: turning
+//! off `p2p` also stops answering peers. FFI calls are not gated.
+//!
+//! Flags are managed through `GET/PUT /api/profile/modules`.
+
+use axum::{
+    Json,
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::models::installation_profile;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureModule {
+    ProCataloguing,
+    Gamification,
+    P2p,
+    Bookseller,
+}
+
+pub struct ModuleInfo {
+    pub module: FeatureModule,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// API paths (without `/api`) owned by the module, matched per segment.
+    pub route_prefixes: &'static [&'static str],
+}
+
+pub const REGISTRY: [ModuleInfo; 4] = [
+    ModuleInfo {
+        module: FeatureModule::ProCataloguing,
+        name: "Professional cataloguing",
+        description: "SUDOC lookups, bulk metadata fill and subject authority control",
+        route_prefixes: &[
+            "/integrations/sudoc",
+            "/metadata-fill",
+            "/subject-authorities",
+        ],
+    },
+    ModuleInfo {
+        module: FeatureModule::Gamification,
+        name: "Gamification",
+        description: "Achievements, streaks and leaderboards",
+        route_prefixes: &["/gamification", "/user/status"],
+    },
+    ModuleInfo {
+        module: FeatureModule::P2p,
+        name: "Peer-to-peer",
+        description: "Peers, discovery, relay, loans and requests between libraries",
+        route_prefixes: &["/peers", "/discovery", "/relay", "/e2ee"],
+    },
+    ModuleInfo {
+        module: FeatureModule::Bookseller,
+        name: "Bookseller",
+        description: "Sales and sales statistics",
+        route_prefixes: &["/sales", "/statistics/sales"],
+    },
+];
+
+impl FeatureModule {
+    pub fn id(self) -> &'static str {
+        match self {
+            FeatureModule::ProCataloguing => "pro_cataloguing",
+            FeatureModule::Gamification => "gamification",
+            FeatureModule::P2p => "p2p",
+            FeatureModule::Bookseller => "bookseller",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        REGISTRY
+            .iter()
+            .map(|info| info.module)
+            .find(|module| module.id() == id)
+    }
+
+    pub fn info(self) -> &'static ModuleInfo {
+        REGISTRY
+            .iter()
+            .find(|info| info.module == self)
+            .expect("every module is registered")
+    }
+
+    fn disable_flag(self) -> String {
+        format!("disable_module:{}", self.id())
+    }
+
+    /// Whether the module is on, given the raw `enabled_modules` flags.
+    pub fn is_enabled_in(self, flags: &[String]) -> bool {
+        !flags.contains(&self.disable_flag())
+    }
+
+    /// Switch the module on or off in the raw flags, leaving other flags alone.
+    pub fn set_enabled_in(self, flags: &mut Vec<String>, enabled: bool) {
+        let flag = self.disable_flag();
+        flags.retain(|f| *f != flag);
+        if !enabled {
+            flags.push(flag);
+        }
+    }
+}
+
+/// The module owning an API path, with or without the `/api` prefix.
+pub fn module_for_path(path: &str) -> Option<FeatureModule> {
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    REGISTRY
+        .iter()
+        .find(|info| {
+            info.route_prefixes.iter().any(|prefix| {
+                path.strip_prefix(prefix)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+        })
+        .map(|info| info.module)
+}
+
+/// The raw `enabled_modules` flags of the installation profile.
+pub async fn load_flags(db: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+    Ok(installation_profile::Entity::find_by_id(1)
+        .one(db)
+        .await?
+        .map(|p| serde_json::from_str(&p.enabled_modules).unwrap_or_default())
+        .unwrap_or_default())
+}
+
+pub async fn is_enabled(db: &DatabaseConnection, module: FeatureModule) -> Result<bool, DbErr> {
+    Ok(module.is_enabled_in(&load_flags(db).await?))
+}
+
+/// Rejects requests to routes owned by a disabled module. Reads the
+/// `DatabaseConnection` request extension; without it, everything passes.
+pub async fn module_gate_middleware(request: Request<Body>, next: Next) -> Response {
+    let Some(module) = module_for_path(request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let Some(db) = request.extensions().get::<DatabaseConnection>().cloned() else {
+        return next.run(request).await;
+    };
+
+    match is_enabled(&db, module).await {
+        Ok(true) => next.run(request).await,
+        Ok(false) => (
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": format!("The {} module is disabled", module.info().name),
+                "code": "module_disabled",
+                "module": module.id(),
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            // Fail open: a profile read error must not take the API down.
+            tracing::warn!("module gate: cannot read profile: {e}");
+            next.run(request).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use sea_orm::ActiveModelTrait;
+    use tower::ServiceExt;
+
+    #[test]
+    fn paths_map_to_their_module_per_segment() {
+        assert_eq!(
+            module_for_path("/api/peers/1/sync"),
+            Some(FeatureModule::P2p)
+        );
+        assert_eq!(module_for_path("/sales"), Some(FeatureModule::Bookseller));
+        assert_eq!(
+            module_for_path("/statistics/sales"),
+            Some(FeatureModule::Bookseller)
+        );
+        assert_eq!(module_for_path("/salesforce"), None);
+        assert_eq!(module_for_path("/books"), None);
+        assert_eq!(
+            module_for_path("/integrations/sudoc/search"),
+            Some(FeatureModule::ProCataloguing)
+        );
+        assert_eq!(module_for_path("/integrations/openlibrary/search"), None);
+    }
+
+    #[test]
+    fn toggling_keeps_unrelated_flags() {
+        let mut flags = vec!["hangman".to_string(), "disable_fallback:bnf".to_string()];
+        assert!(FeatureModule::P2p.is_enabled_in(&flags));
+
+        FeatureModule::P2p.set_enabled_in(&mut flags, false);
+        FeatureModule::P2p.set_enabled_in(&mut flags, false);
+        assert!(!FeatureModule::P2p.is_enabled_in(&flags));
+        assert_eq!(flags.len(), 3);
+
+        FeatureModule::P2p.set_enabled_in(&mut flags, true);
+        assert_eq!(flags, vec!["hangman", "disable_fallback:bnf"]);
+        assert_eq!(FeatureModule::from_id("p2p"), Some(FeatureModule::P2p));
+        assert_eq!(FeatureModule::from_id("chess"), None);
+    }
+
+    #[tokio::test]
+    async fn disabled_modules_answer_403() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let app = Router::new()
+            .route("/sales", get(|| async { "sales" }))
+            .route("/books", get(|| async { "books" }))
+            .layer(axum::middleware::from_fn(module_gate_middleware))
+            .layer(axum::Extension(db.clone()));
+        let get_status = |path: &'static str| {
+            let app = app.clone();
+            async move {
+                app.oneshot(Request::get(path).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        assert_eq!(get_status("/sales").await, StatusCode::OK);
+
+        let mut flags = load_flags(&db).await.unwrap();
+        FeatureModule::Bookseller.set_enabled_in(&mut flags, false);
+        installation_profile::ActiveModel {
+            id: sea_orm::Set(1),
+            enabled_modules: sea_orm::Set(serde_json::to_string(&flags).unwrap()),
+            ..Default::default()
+        }
+        .update(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(get_status("/sales").await, StatusCode::FORBIDDEN);
+        assert_eq!(get_status("/books").await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,402 @@
+//! What a scanned page says: ISBNs, title and author candidates.
+//!
+//! `/api/scan/image` used to return the OCR text alone and leave the client
+//! to dig the ISBN out of it. The text is now read here, line by line:
+//!
+//! - ISBNs: runs of digits, with hyphens and spaces between groups and the
+//!   letters OCR mistakes for digits (`O` for `0`, `l` for `1`, ...), whose
+//!   check digit holds. Found next to an `ISBN` or `EAN` label, or without
+//!   corrections, they score higher.
+//! - Authors: lines introduced by `par`, `by`, `Auteur :`... or shaped like
+//!   a name (two to four capitalized words).
+//! - Titles: the other lines of words, the first ones and the capitalized
+//!   ones scoring higher; legal and printer lines (`Dépôt légal`, `©`,
+//!   `Imprimé`, URLs) are left out.
+//!
+//! Confidences run from 0 to 1 and only rank candidates against each other;
+//! each list comes best first.
+
+use serde::Serialize;
+
+use crate::utils::isbn;
+
+/// Candidates kept per list.
+const MAX_CANDIDATES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct IsbnCandidate {
+    pub isbn13: String,
+    /// `None` for 979 ISBNs, which have no ISBN-10 form
+    pub isbn10: Option<String>,
+    /// As read, before clean-up
+    pub raw: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct TextCandidate {
+    pub text: String,
+    pub confidence: f32,
+}
+
+/// The OCR text and what was found in it.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ScanResult {
+    /// The raw OCR text
+    pub text: String,
+    pub isbns: Vec<IsbnCandidate>,
+    pub titles: Vec<TextCandidate>,
+    pub authors: Vec<TextCandidate>,
+}
+
+/// The digit OCR most likely read as `c`, if any.
+fn as_digit(c: char) -> Option<(char, bool)> {
+    match c {
+        '0'..='9' => Some((c, false)),
+        'O' | 'o' | 'D' | 'Q' => Some(('0', true)),
+        'I' | 'l' | '|' | 'i' => Some(('1', true)),
+        'Z' | 'z' => Some(('2', true)),
+        'S' | 's' => Some(('5', true)),
+        'G' | 'b' => Some(('6', true)),
+        'B' => Some(('8', true)),
+        'g' => Some(('9', true)),
+        _ => None,
+    }
+}
+
+fn round(confidence: f32) -> f32 {
+    (confidence.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
+/// A run of digits read from a line: the digits, whether each one needed a
+/// correction, and the text it was read from.
+struct DigitRun {
+    digits: Vec<char>,
+    corrected: Vec<bool>,
+    raw: String,
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '‐' | '–' | '.')
+}
+
+/// The runs of digits in `line`. A run starts at a real digit and goes on
+/// over digits, hyphens, spaces before a digit, and letters misread for
+/// digits between two digits or separators; an `X` is kept as an ISBN-10
+/// check digit.
+fn digit_runs(line: &str) -> Vec<DigitRun> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut run = DigitRun {
+            digits: Vec::new(),
+            corrected: Vec::new(),
+            raw: String::new(),
+        };
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            let misread = || {
+                let prev = chars[i - 1];
+                (prev.is_ascii_digit() || is_separator(prev))
+                    && next.is_none_or(|n| n.is_ascii_digit() || is_separator(n) || n == ' ')
+            };
+            if let Some((digit, corrected)) = as_digit(c)
+                && (!corrected || misread())
+            {
+                run.digits.push(digit);
+                run.corrected.push(corrected);
+            } else if matches!(c, 'X' | 'x') {
+                run.digits.push('X');
+                run.corrected.push(false);
+            } else if is_separator(c) || (c == ' ' && next.is_some_and(|n| n.is_ascii_digit())) {
+                // A separator between groups
+            } else {
+                break;
+            }
+            i += 1;
+        }
+        run.raw = chars[start..i]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        runs.push(run);
+    }
+    runs
+}
+
+/// Whether the line labels an ISBN (`ISBN`, `ISBN-13`, `EAN13`...).
+fn has_isbn_label(line: &str) -> bool {
+    line.split(|c: char| !c.is_alphabetic())
+        .any(|w| w.eq_ignore_ascii_case("isbn") || w.eq_ignore_ascii_case("ean"))
+}
+
+/// The ISBNs read from `text`, best first.
+pub fn isbns(text: &str) -> Vec<IsbnCandidate> {
+    let mut found: Vec<IsbnCandidate> = Vec::new();
+    for line in text.lines() {
+        let labelled = has_isbn_label(line);
+        for run in digit_runs(line) {
+            let n = run.digits.len();
+            let mut i = 0;
+            while i + 10 <= n {
+                let window = |len: usize| -> Option<(String, bool)> {
+                    let digits: String = run.digits.get(i..i + len)?.iter().collect();
+                    let corrected = run.corrected[i..i + len].iter().any(|c| *c);
+                    Some((digits, corrected))
+                };
+                // A 13-digit ISBN is an EAN starting 978 or 979; an X is
+                // only ever the last character of an ISBN-10.
+                let isbn13 = window(13).filter(|(d, _)| {
+                    (d.starts_with("978") || d.starts_with("979")) && !d.contains('X')
+                });
+                let (digits, corrected, len) =
+                    match isbn13.and_then(|(d, c)| isbn::to_isbn13(&d).map(|_| (d, c))) {
+                        Some((d, c)) => (d, c, 13),
+                        None => match window(10).filter(|(d, _)| !d[..9].contains('X')) {
+                            // Inside a longer run, ten digits are likely a
+                            // barcode or a phone number that happens to check.
+                            Some((d, c)) if n == 10 || labelled => (d, c, 10),
+                            _ => {
+                                i += 1;
+                                continue;
+                            }
+                        },
+                    };
+                let Some(isbn13) = isbn::to_isbn13(&digits) else {
+                    i += 1;
+                    continue;
+                };
+                let mut confidence = 0.5;
+                if labelled {
+                    confidence += 0.3;
+                }
+                if len == 13 {
+                    confidence += 0.1;
+                }
+                if n == len {
+                    confidence += 0.1;
+                }
+                if corrected {
+                    confidence -= 0.2;
+                }
+                let candidate = IsbnCandidate {
+                    isbn10: isbn::to_isbn10(&isbn13),
+                    isbn13,
+                    raw: run.raw.clone(),
+                    confidence: round(confidence),
+                };
+                match found.iter_mut().find(|c| c.isbn13 == candidate.isbn13) {
+                    Some(seen) if seen.confidence < candidate.confidence => *seen = candidate,
+                    Some(_) => {}
+                    None => found.push(candidate),
+                }
+                i += len;
+            }
+        }
+    }
+    found.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    found.truncate(MAX_CANDIDATES);
+    found
+}
+
+/// Words that introduce an author's name, as a line prefix.
+const AUTHOR_PREFIXES: &[&str] = &["par ", "by ", "de ", "von ", "di ", "door "];
+
+/// Labels an author's name follows.
+const AUTHOR_LABELS: &[&str] = &["auteur", "auteurs", "author", "authors", "autor", "autore"];
+
+/// Words of lines that are neither a title nor an author: legal notices,
+/// printer and publisher boilerplate.
+const BOILERPLATE_WORDS: &[&str] = &[
+    "isbn",
+    "ean",
+    "copyright",
+    "imprimé",
+    "imprime",
+    "printed",
+    "prix",
+    "price",
+    "traduit",
+    "translated",
+];
+
+/// The same, for what is not a word on its own: symbols, phrases, addresses.
+const BOILERPLATE_FRAGMENTS: &[&str] = &[
+    "©",
+    "(c)",
+    "dépôt légal",
+    "depot legal",
+    "all rights reserved",
+    "tous droits",
+    "www.",
+    "http",
+    "@",
+    "€",
+];
+
+/// The name an author line gives, if it is one.
+fn labelled_author(line: &str) -> Option<(String, f32)> {
+    if let Some((label, name)) = line.split_once(':') {
+        let label = label.trim().to_lowercase();
+        if AUTHOR_LABELS.contains(&label.as_str()) && !name.trim().is_empty() {
+            return Some((name.trim().to_string(), 0.9));
+        }
+    }
+    AUTHOR_PREFIXES.iter().find_map(|prefix| {
+        let head = line.get(..prefix.len())?;
+        let name = line[prefix.len()..].trim();
+        (head.eq_ignore_ascii_case(prefix) && looks_like_name(name))
+            .then(|| (name.to_string(), 0.8))
+    })
+}
+
+/// Two to four words, each starting with a capital (or an initial), with
+/// no digits: "Victor Hugo", "J. R. R. Tolkien", "Marguerite YOURCENAR".
+fn looks_like_name(s: &str) -> bool {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    (2..=4).contains(&words.len())
+        && !s.chars().any(|c| c.is_ascii_digit())
+        && words.iter().all(|w| {
+            let particle = matches!(*w, "de" | "du" | "von" | "van" | "da" | "di" | "le" | "la");
+            particle || w.chars().next().is_some_and(char::is_uppercase)
+        })
+}
+
+fn is_boilerplate(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    BOILERPLATE_FRAGMENTS.iter().any(|b| lower.contains(b))
+        || lower
+            .split(|c: char| !c.is_alphabetic())
+            .any(|w| BOILERPLATE_WORDS.contains(&w))
+}
+
+/// Share of the characters that are letters or spaces.
+fn wordiness(line: &str) -> f32 {
+    let total = line.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let words = line
+        .chars()
+        .filter(|c| c.is_alphabetic() || *c == ' ' || *c == '\'' || *c == '’')
+        .count();
+    words as f32 / total as f32
+}
+
+fn push_best(list: &mut Vec<TextCandidate>, text: String, confidence: f32) {
+    let confidence = round(confidence);
+    match list.iter_mut().find(|c| c.text.eq_ignore_ascii_case(&text)) {
+        Some(seen) if seen.confidence < confidence => seen.confidence = confidence,
+        Some(_) => {}
+        None => list.push(TextCandidate { text, confidence }),
+    }
+}
+
+/// Title and author candidates read from `text`, best first.
+pub fn titles_and_authors(text: &str) -> (Vec<TextCandidate>, Vec<TextCandidate>) {
+    let mut titles = Vec::new();
+    let mut authors = Vec::new();
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.chars().filter(|c| c.is_alphabetic()).count() >= 3)
+        .collect();
+    for (rank, line) in lines.iter().enumerate() {
+        if is_boilerplate(line) {
+            continue;
+        }
+        if let Some((name, confidence)) = labelled_author(line) {
+            push_best(&mut authors, name, confidence);
+            continue;
+        }
+        if wordiness(line) < 0.8 || line.chars().count() > 120 {
+            continue;
+        }
+        // Early lines are more likely the title on a cover or title page.
+        let position = 0.3 * (1.0 - (rank as f32 / 6.0).min(1.0));
+        if looks_like_name(line) {
+            push_best(&mut authors, line.to_string(), 0.4 + position / 2.0);
+        }
+        let capitalized = line.chars().next().is_some_and(char::is_uppercase);
+        let confidence = 0.3 + position + if capitalized { 0.1 } else { 0.0 };
+        push_best(&mut titles, line.to_string(), confidence);
+    }
+    // A line read as a name is a weaker title.
+    for title in &mut titles {
+        if authors.iter().any(|a| a.text == title.text) {
+            title.confidence = round(title.confidence - 0.2);
+        }
+    }
+    for list in [&mut titles, &mut authors] {
+        list.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        list.truncate(MAX_CANDIDATES);
+    }
+    (titles, authors)
+}
+
+/// Everything found in the OCR text of a scan.
+pub fn extract(text: &str) -> ScanResult {
+    let (titles, authors) = titles_and_authors(text);
+    ScanResult {
+        text: text.to_string(),
+        isbns: isbns(text),
+        titles,
+        authors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isbns_are_read_through_ocr_noise() {
+        let text = "Gallimard\n\
+                    ISBN 978-2-07-036822-8\n\
+                    Imprimé en France  ISBN  2-07-O36822-X Gallimard\n\
+                    Tél. 01 42 86 55 10 — 12,50 €\n\
+                    ean 9780306406157 ";
+        let found = isbns(text);
+        let isbn13s: Vec<&str> = found.iter().map(|c| c.isbn13.as_str()).collect();
+        assert_eq!(isbn13s, ["9782070368228", "9780306406157"]);
+        assert_eq!(found[0].isbn10.as_deref(), Some("207036822X"));
+        assert_eq!(found[0].raw, "978-2-07-036822-8");
+        assert_eq!(found[0].confidence, 1.0);
+
+        // A misread, unlabelled ISBN still counts, with less confidence;
+        // a wrong check digit does not.
+        let misread = isbns("978-O-306-40615-7");
+        assert_eq!(misread[0].isbn13, "9780306406157");
+        assert!(misread[0].confidence < 0.7);
+        assert!(isbns("9780306406150 et 0306406153").is_empty());
+    }
+
+    #[test]
+    fn title_and_author_lines_are_told_apart() {
+        let result = extract(
+            "Marguerite Yourcenar\n\
+             Mémoires d'Hadrien\n\
+             roman\n\
+             Gallimard\n\
+             Dépôt légal : 1974\n\
+             ISBN 978-2-07-036822-8",
+        );
+        assert_eq!(result.authors[0].text, "Marguerite Yourcenar");
+        assert_eq!(result.titles[0].text, "Mémoires d'Hadrien");
+        assert!(result.titles.iter().all(|t| !t.text.contains("Dépôt")));
+        assert_eq!(result.isbns.len(), 1);
+
+        let labelled = extract("Le Petit Prince\npar Antoine de Saint-Exupéry");
+        assert_eq!(labelled.authors[0].text, "Antoine de Saint-Exupéry");
+        assert_eq!(labelled.authors[0].confidence, 0.8);
+        assert_eq!(labelled.titles[0].text, "Le Petit Prince");
+    }
+}
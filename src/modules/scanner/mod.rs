@@ -1,3 +1,5 @@
+pub mod candidates;
+
 use std::process::Command;
 
 pub fn scan_image(image_path: &str) -> Result<String, String> {
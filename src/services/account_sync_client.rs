@@ -293,7 +293,7 @@ impl AccountSyncClient {
 
     /// Build a client against an explicit base URL (no trailing slash). Used by tests.
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
-        let http = Client::builder()
+        let http = crate::services::network_mode::client_builder()
             .user_agent("BiblioGenius/1.0")
             .timeout(std::time::Duration::from_secs(15))
             .redirect(reqwest::redirect::Policy::none())
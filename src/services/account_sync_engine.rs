@@ -2198,6 +2198,7 @@ mod tests {
                 name: Set(format!("author {author_uuid}")),
                 created_at: Set("2026-06-29T00:00:00Z".to_owned()),
                 updated_at: Set("2026-06-29T00:00:00Z".to_owned()),
+                ..Default::default()
             }
             .insert(eng.db())
             .await
@@ -0,0 +1,677 @@
+//! Acquisitions: purchase orders and the budget they draw on.
+//!
+//! An order names a supplier (a contact) and the books bought from them,
+//! each with a quantity and a unit cost. While `ordered`, its total is
+//! committed; once `received`, it is spent, and receiving it puts the books
+//! in the catalogue: a line naming a book, or whose ISBN matches one, adds
+//! copies to it, any other line creates the book first. Copies carry the
+//! receipt date as acquisition date and the unit cost as price.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Local, NaiveDate, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    book, contact, copy,
+    purchase_order::{self, STATUS_CANCELLED, STATUS_ORDERED, STATUS_RECEIVED},
+    purchase_order_line,
+};
+
+/// Largest quantity on one line.
+const MAX_QUANTITY: i32 = 1_000;
+
+#[derive(Debug)]
+pub enum AcquisitionError {
+    Db(DbErr),
+    NotFound,
+    SupplierNotFound,
+    BookNotFound(String),
+    /// The order is not `ordered` any more.
+    NotOpen(String),
+    Invalid(String),
+}
+
+impl std::fmt::Display for AcquisitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::NotFound => write!(f, "order not found"),
+            Self::SupplierNotFound => write!(f, "supplier contact not found"),
+            Self::BookNotFound(id) => write!(f, "book {id} not found"),
+            Self::NotOpen(status) => write!(f, "order is already {status}"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AcquisitionError {}
+
+impl From<DbErr> for AcquisitionError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// A line of a new order: an existing `book_id`, or a title.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewOrderLine {
+    pub book_id: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    /// Defaults to 1
+    pub quantity: Option<i32>,
+    pub unit_cost: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewOrder {
+    pub supplier_contact_id: Option<String>,
+    /// `YYYY-MM-DD`, defaults to today
+    pub ordered_at: Option<String>,
+    pub notes: Option<String>,
+    pub lines: Vec<NewOrderLine>,
+}
+
+/// An order with its lines.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct OrderDetail {
+    #[schema(value_type = PurchaseOrder)]
+    pub order: purchase_order::Model,
+    pub supplier_name: Option<String>,
+    #[schema(value_type = Vec<PurchaseOrderLine>)]
+    pub lines: Vec<purchase_order_line::Model>,
+    pub total: f64,
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+fn line_total(lines: &[purchase_order_line::Model]) -> f64 {
+    cents(lines.iter().map(|l| l.quantity as f64 * l.unit_cost).sum())
+}
+
+fn clean_isbn(isbn: &str) -> String {
+    isbn.chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn check_day(field: &str, value: &str) -> Result<(), AcquisitionError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| AcquisitionError::Invalid(format!("{field} must be a YYYY-MM-DD date")))
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+async fn detail(
+    db: &DatabaseConnection,
+    order: purchase_order::Model,
+) -> Result<OrderDetail, DbErr> {
+    let lines = purchase_order_line::Entity::find()
+        .filter(purchase_order_line::Column::OrderId.eq(order.id.as_str()))
+        .order_by_asc(purchase_order_line::Column::Id)
+        .all(db)
+        .await?;
+    let supplier_name = match &order.supplier_contact_id {
+        Some(id) => contact::Entity::find_by_id(id.clone())
+            .one(db)
+            .await?
+            .map(|c| c.name),
+        None => None,
+    };
+    Ok(OrderDetail {
+        total: line_total(&lines),
+        order,
+        supplier_name,
+        lines,
+    })
+}
+
+/// Place an order.
+pub async fn create_order(
+    db: &DatabaseConnection,
+    new: NewOrder,
+) -> Result<OrderDetail, AcquisitionError> {
+    if new.lines.is_empty() {
+        return Err(AcquisitionError::Invalid(
+            "an order needs at least one line".to_string(),
+        ));
+    }
+    if let Some(supplier) = &new.supplier_contact_id
+        && contact::Entity::find_by_id(supplier.clone())
+            .one(db)
+            .await?
+            .is_none()
+    {
+        return Err(AcquisitionError::SupplierNotFound);
+    }
+    let ordered_at = new.ordered_at.unwrap_or_else(today);
+    check_day("ordered_at", &ordered_at)?;
+
+    let order_id = crate::utils::uuid_gen::new_uuid_v7();
+    let mut lines = Vec::with_capacity(new.lines.len());
+    for line in new.lines {
+        let quantity = line.quantity.unwrap_or(1);
+        if !(1..=MAX_QUANTITY).contains(&quantity) {
+            return Err(AcquisitionError::Invalid(format!(
+                "quantity must be between 1 and {MAX_QUANTITY}"
+            )));
+        }
+        if !(line.unit_cost.is_finite() && line.unit_cost >= 0.0) {
+            return Err(AcquisitionError::Invalid(
+                "unit_cost must not be negative".to_string(),
+            ));
+        }
+        let (title, author, isbn) = match &line.book_id {
+            Some(book_id) => {
+                let found = book::Entity::find_by_id(book_id.clone())
+                    .one(db)
+                    .await?
+                    .ok_or_else(|| AcquisitionError::BookNotFound(book_id.clone()))?;
+                (found.title, line.author, found.isbn)
+            }
+            None => match line.title.as_deref().map(str::trim) {
+                Some(title) if !title.is_empty() => (title.to_string(), line.author, line.isbn),
+                _ => {
+                    return Err(AcquisitionError::Invalid(
+                        "each line needs a book_id or a title".to_string(),
+                    ));
+                }
+            },
+        };
+        lines.push(purchase_order_line::ActiveModel {
+            id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+            order_id: Set(order_id.clone()),
+            book_id: Set(line.book_id),
+            title: Set(title),
+            author: Set(author),
+            isbn: Set(isbn),
+            quantity: Set(quantity),
+            unit_cost: Set(cents(line.unit_cost)),
+        });
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let txn = db.begin().await?;
+    purchase_order::ActiveModel {
+        id: Set(order_id.clone()),
+        supplier_contact_id: Set(new.supplier_contact_id),
+        status: Set(STATUS_ORDERED.to_string()),
+        ordered_at: Set(ordered_at),
+        received_at: Set(None),
+        notes: Set(new.notes),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+    }
+    .insert(&txn)
+    .await?;
+    purchase_order_line::Entity::insert_many(lines)
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    get_order(db, &order_id)
+        .await?
+        .ok_or(AcquisitionError::NotFound)
+}
+
+/// One order with its lines.
+pub async fn get_order(db: &DatabaseConnection, id: &str) -> Result<Option<OrderDetail>, DbErr> {
+    match purchase_order::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+    {
+        Some(order) => Ok(Some(detail(db, order).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Orders, latest first, optionally of one status.
+pub async fn list_orders(
+    db: &DatabaseConnection,
+    status: Option<&str>,
+) -> Result<Vec<OrderDetail>, DbErr> {
+    let mut query = purchase_order::Entity::find();
+    if let Some(status) = status {
+        query = query.filter(purchase_order::Column::Status.eq(status));
+    }
+    let orders = query
+        .order_by_desc(purchase_order::Column::OrderedAt)
+        .order_by_desc(purchase_order::Column::CreatedAt)
+        .all(db)
+        .await?;
+    let mut details = Vec::with_capacity(orders.len());
+    for order in orders {
+        details.push(detail(db, order).await?);
+    }
+    Ok(details)
+}
+
+async fn open_order(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<purchase_order::Model, AcquisitionError> {
+    let order = purchase_order::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(AcquisitionError::NotFound)?;
+    if order.status != STATUS_ORDERED {
+        return Err(AcquisitionError::NotOpen(order.status));
+    }
+    Ok(order)
+}
+
+/// Cancel an order not received yet.
+pub async fn cancel_order(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<OrderDetail, AcquisitionError> {
+    let order = open_order(db, id).await?;
+    let mut active: purchase_order::ActiveModel = order.into();
+    active.status = Set(STATUS_CANCELLED.to_string());
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let order = active.update(db).await?;
+    Ok(detail(db, order).await?)
+}
+
+/// The catalogue book a line stands for, created when there is none.
+async fn book_for_line(
+    db: &DatabaseConnection,
+    line: &purchase_order_line::Model,
+) -> Result<book::Model, AcquisitionError> {
+    if let Some(id) = &line.book_id {
+        return book::Entity::find_by_id(id.clone())
+            .one(db)
+            .await?
+            .ok_or_else(|| AcquisitionError::BookNotFound(id.clone()));
+    }
+    if let Some(isbn) = line
+        .isbn
+        .as_deref()
+        .map(clean_isbn)
+        .filter(|i| !i.is_empty())
+        && let Some(found) = book::Entity::find()
+            .filter(book::Column::Isbn.eq(isbn))
+            .one(db)
+            .await?
+    {
+        return Ok(found);
+    }
+
+    // Not owned yet: `create_book` would add a copy of its own.
+    let created = crate::services::book_service::create_book(
+        db,
+        book::Book {
+            title: line.title.clone(),
+            author: line.author.clone(),
+            isbn: line.isbn.clone(),
+            owned: Some(false),
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(|e| AcquisitionError::Invalid(format!("{e:?}")))?;
+    let id = created.id.unwrap_or_default();
+    book::Entity::find_by_id(id.clone())
+        .one(db)
+        .await?
+        .ok_or(AcquisitionError::BookNotFound(id))
+}
+
+/// Mark an order received and put its books on the shelf. `received_at`
+/// defaults to today.
+pub async fn receive_order(
+    db: &DatabaseConnection,
+    id: &str,
+    received_at: Option<String>,
+) -> Result<OrderDetail, AcquisitionError> {
+    let order = open_order(db, id).await?;
+    let received_at = received_at.unwrap_or_else(today);
+    check_day("received_at", &received_at)?;
+    let library_id = crate::utils::library_helpers::resolve_library_id(db).await?;
+    let lines = purchase_order_line::Entity::find()
+        .filter(purchase_order_line::Column::OrderId.eq(id))
+        .all(db)
+        .await?;
+
+    for line in lines {
+        let found = book_for_line(db, &line).await?;
+        let now = Utc::now().to_rfc3339();
+        if !found.owned {
+            let mut active: book::ActiveModel = found.clone().into();
+            active.owned = Set(true);
+            active.updated_at = Set(now.clone());
+            active.update(db).await?;
+            let _ = crate::sync::log_operation(db, "book", &found.id, "UPDATE", None).await;
+        }
+        for _ in 0..line.quantity {
+            let saved = copy::ActiveModel {
+                book_id: Set(found.id.clone()),
+                library_id: Set(library_id),
+                acquisition_date: Set(Some(received_at.clone())),
+                status: Set("available".to_string()),
+                is_temporary: Set(false),
+                price: Set(Some(line.unit_cost)),
+                created_at: Set(now.clone()),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+            let _ = crate::sync::log_operation(
+                db,
+                "copy",
+                &saved.id,
+                "INSERT",
+                Some(serde_json::json!({ "book_id": found.id })),
+            )
+            .await;
+        }
+        if line.book_id.is_none() {
+            let mut active: purchase_order_line::ActiveModel = line.into();
+            active.book_id = Set(Some(found.id));
+            active.update(db).await?;
+        }
+    }
+
+    let mut active: purchase_order::ActiveModel = order.into();
+    active.status = Set(STATUS_RECEIVED.to_string());
+    active.received_at = Set(Some(received_at));
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let order = active.update(db).await?;
+    Ok(detail(db, order).await?)
+}
+
+/// Length of the periods of a budget report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl BudgetPeriod {
+    /// `2026-03`, `2026-Q1` or `2026` for the `YYYY-MM-DD` date `day`.
+    fn key(self, day: &str) -> String {
+        match self {
+            Self::Month => day.get(..7).unwrap_or(day).to_string(),
+            Self::Quarter => {
+                let month: u32 = day.get(5..7).and_then(|m| m.parse().ok()).unwrap_or(1);
+                format!("{}-Q{}", day.get(..4).unwrap_or(day), month.div_ceil(3))
+            }
+            Self::Year => day.get(..4).unwrap_or(day).to_string(),
+        }
+    }
+}
+
+/// Committed and spent amounts over one period or for one supplier.
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct Spending {
+    /// Period key, or supplier name
+    pub label: String,
+    pub supplier_contact_id: Option<String>,
+    /// Total of the orders still `ordered`
+    pub committed: f64,
+    /// Total of the orders `received`
+    pub spent: f64,
+    pub orders: u32,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct BudgetReport {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub committed: f64,
+    pub spent: f64,
+    pub budget: Option<f64>,
+    /// Budget less committed and spent, when a budget is given
+    pub remaining: Option<f64>,
+    pub periods: Vec<Spending>,
+    pub suppliers: Vec<Spending>,
+}
+
+/// Spending between `from` and `to` (inclusive `YYYY-MM-DD` bounds, either
+/// optional), by period and by supplier. A received order counts on its
+/// receipt date, an open one on its order date; cancelled orders do not
+/// count.
+pub async fn budget_report(
+    db: &DatabaseConnection,
+    from: Option<String>,
+    to: Option<String>,
+    period: BudgetPeriod,
+    budget: Option<f64>,
+) -> Result<BudgetReport, AcquisitionError> {
+    for (field, value) in [("from", &from), ("to", &to)] {
+        if let Some(value) = value {
+            check_day(field, value)?;
+        }
+    }
+    let orders = purchase_order::Entity::find()
+        .filter(purchase_order::Column::Status.ne(STATUS_CANCELLED))
+        .all(db)
+        .await?;
+    let order_ids: Vec<String> = orders.iter().map(|o| o.id.clone()).collect();
+    let mut lines: HashMap<String, Vec<purchase_order_line::Model>> = HashMap::new();
+    for line in purchase_order_line::Entity::find()
+        .filter(purchase_order_line::Column::OrderId.is_in(order_ids))
+        .all(db)
+        .await?
+    {
+        lines.entry(line.order_id.clone()).or_default().push(line);
+    }
+
+    let mut periods: BTreeMap<String, Spending> = BTreeMap::new();
+    let mut suppliers: HashMap<Option<String>, Spending> = HashMap::new();
+    let (mut committed, mut spent) = (0.0, 0.0);
+    for order in &orders {
+        let received = order.status == STATUS_RECEIVED;
+        let day = match (&order.received_at, received) {
+            (Some(day), true) => day.as_str(),
+            _ => order.ordered_at.as_str(),
+        };
+        if from.as_deref().is_some_and(|f| day < f) || to.as_deref().is_some_and(|t| day > t) {
+            continue;
+        }
+        let total = line_total(lines.get(&order.id).map(Vec::as_slice).unwrap_or_default());
+        let key = period.key(day);
+        for entry in [
+            periods.entry(key.clone()).or_insert_with(|| Spending {
+                label: key,
+                ..Default::default()
+            }),
+            suppliers
+                .entry(order.supplier_contact_id.clone())
+                .or_insert_with(|| Spending {
+                    supplier_contact_id: order.supplier_contact_id.clone(),
+                    ..Default::default()
+                }),
+        ] {
+            if received {
+                entry.spent += total;
+            } else {
+                entry.committed += total;
+            }
+            entry.orders += 1;
+        }
+        if received {
+            spent += total;
+        } else {
+            committed += total;
+        }
+    }
+
+    let names: HashMap<String, String> = contact::Entity::find()
+        .filter(contact::Column::Id.is_in(suppliers.keys().flatten().cloned().collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+    let round = |mut s: Spending| {
+        s.committed = cents(s.committed);
+        s.spent = cents(s.spent);
+        s
+    };
+    let mut suppliers: Vec<Spending> = suppliers
+        .into_values()
+        .map(|mut s| {
+            s.label = s
+                .supplier_contact_id
+                .as_ref()
+                .and_then(|id| names.get(id).cloned())
+                .unwrap_or_default();
+            round(s)
+        })
+        .collect();
+    suppliers.sort_by(|a, b| {
+        (b.spent + b.committed)
+            .total_cmp(&(a.spent + a.committed))
+            .then(a.label.cmp(&b.label))
+    });
+
+    let (committed, spent) = (cents(committed), cents(spent));
+    Ok(BudgetReport {
+        from,
+        to,
+        committed,
+        spent,
+        budget,
+        remaining: budget.map(|b| cents(b - committed - spent)),
+        periods: periods.into_values().map(round).collect(),
+        suppliers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    fn line(title: &str, isbn: Option<&str>, quantity: i32, unit_cost: f64) -> NewOrderLine {
+        NewOrderLine {
+            book_id: None,
+            title: Some(title.to_string()),
+            author: None,
+            isbn: isbn.map(str::to_string),
+            quantity: Some(quantity),
+            unit_cost,
+        }
+    }
+
+    #[tokio::test]
+    async fn receiving_an_order_adds_books_and_copies() {
+        let db = setup().await;
+        let now = Utc::now().to_rfc3339();
+        let existing = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some("9780441013593".to_string())),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let order = create_order(
+            &db,
+            NewOrder {
+                supplier_contact_id: None,
+                ordered_at: Some("2026-02-10".to_string()),
+                notes: None,
+                lines: vec![
+                    line("Dune", Some("978-0-441-01359-3"), 2, 9.5),
+                    line("Hyperion", None, 1, 12.0),
+                ],
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(order.total, 31.0);
+
+        let received = receive_order(&db, &order.order.id, Some("2026-03-02".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(received.order.status, STATUS_RECEIVED);
+        assert!(matches!(
+            receive_order(&db, &order.order.id, None).await,
+            Err(AcquisitionError::NotOpen(_))
+        ));
+
+        let dune_copies = copy::Entity::find()
+            .filter(copy::Column::BookId.eq(existing.id.as_str()))
+            .filter(copy::Column::AcquisitionDate.eq("2026-03-02"))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(dune_copies.len(), 2);
+        assert_eq!(dune_copies[0].price, Some(9.5));
+        let hyperion = book::Entity::find()
+            .filter(book::Column::Title.eq("Hyperion"))
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(hyperion.owned);
+        assert_eq!(
+            copy::Entity::find()
+                .filter(copy::Column::BookId.eq(hyperion.id.as_str()))
+                .count(&db)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn budget_report_splits_committed_and_spent_by_period() {
+        let db = setup().await;
+        let place = |day: &str, cost: f64| NewOrder {
+            supplier_contact_id: None,
+            ordered_at: Some(day.to_string()),
+            notes: None,
+            lines: vec![line("Some book", None, 1, cost)],
+        };
+        let first = create_order(&db, place("2026-01-15", 40.0)).await.unwrap();
+        receive_order(&db, &first.order.id, Some("2026-02-01".to_string()))
+            .await
+            .unwrap();
+        create_order(&db, place("2026-02-20", 25.0)).await.unwrap();
+        let dropped = create_order(&db, place("2026-02-21", 99.0)).await.unwrap();
+        cancel_order(&db, &dropped.order.id).await.unwrap();
+        create_order(&db, place("2025-12-30", 10.0)).await.unwrap();
+
+        let report = budget_report(
+            &db,
+            Some("2026-01-01".to_string()),
+            Some("2026-12-31".to_string()),
+            BudgetPeriod::Month,
+            Some(100.0),
+        )
+        .await
+        .unwrap();
+        assert_eq!((report.spent, report.committed), (40.0, 25.0));
+        assert_eq!(report.remaining, Some(35.0));
+        let periods: Vec<(&str, f64, f64)> = report
+            .periods
+            .iter()
+            .map(|p| (p.label.as_str(), p.spent, p.committed))
+            .collect();
+        assert_eq!(periods, [("2026-02", 40.0, 25.0)]);
+        assert_eq!(BudgetPeriod::Quarter.key("2026-08-14"), "2026-Q3");
+    }
+}
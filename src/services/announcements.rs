@@ -0,0 +1,406 @@
+//! Community announcements between connected libraries.
+//!
+//! The owner publishes a short announcement (new arrivals, opening hours, a
+//! book sale...) and it is sent to every accepted peer as an `announcement`
+//! E2EE message, direct or through the relay like any other peer message. A
+//! peer that cannot be reached gets it from the outbox (`peer_outbox`) once
+//! it is back. Announcements only travel over E2EE: the signature is what
+//! tells the receiver who really sent one.
+//!
+//! On the receiving side announcements are moderated: only those from peers
+//! the owner trusts (`peers.trust_announcements`, off by default) are stored
+//! and land in the notification inbox; the others are acknowledged and
+//! dropped, so the sender does not retry them.
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::domain::notification_repository::{CreateNotification, NotificationEventType};
+use crate::infrastructure::AppState;
+use crate::models::{announcement, peer};
+
+/// Announcement kinds, in the order the UI offers them.
+pub const KINDS: &[&str] = &["new_arrivals", "opening_hours", "book_sale", "other"];
+
+const MAX_TITLE_CHARS: usize = 120;
+const MAX_BODY_CHARS: usize = 2_000;
+const MAX_ID_CHARS: usize = 64;
+
+/// Error type for announcement operations
+#[derive(Debug)]
+pub enum AnnouncementError {
+    Invalid(String),
+    PeerNotFound,
+    NotFound,
+    Db(DbErr),
+}
+
+impl std::fmt::Display for AnnouncementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnnouncementError::Invalid(e) => write!(f, "Invalid announcement: {}", e),
+            AnnouncementError::PeerNotFound => write!(f, "Peer not found"),
+            AnnouncementError::NotFound => write!(f, "Announcement not found"),
+            AnnouncementError::Db(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<DbErr> for AnnouncementError {
+    fn from(e: DbErr) -> Self {
+        AnnouncementError::Db(e)
+    }
+}
+
+/// An announcement to publish
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct Draft {
+    /// One of `new_arrivals`, `opening_hours`, `book_sale`, `other`
+    pub kind: String,
+    /// Up to 120 characters
+    pub title: String,
+    /// Up to 2000 characters
+    #[serde(default)]
+    pub body: Option<String>,
+    /// RFC 3339 date after which it is no longer current
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl Draft {
+    /// Trimmed copy, or why it cannot be sent or stored.
+    fn checked(self) -> Result<Self, AnnouncementError> {
+        let invalid = |e: &str| AnnouncementError::Invalid(e.to_string());
+        if !KINDS.contains(&self.kind.as_str()) {
+            return Err(AnnouncementError::Invalid(format!(
+                "kind must be one of: {}",
+                KINDS.join(", ")
+            )));
+        }
+        let title = self.title.trim().to_string();
+        if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+            return Err(invalid("title must have 1 to 120 characters"));
+        }
+        let body = self
+            .body
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty());
+        if body
+            .as_ref()
+            .is_some_and(|b| b.chars().count() > MAX_BODY_CHARS)
+        {
+            return Err(invalid("body must have at most 2000 characters"));
+        }
+        let expires_at = match self.expires_at.filter(|e| !e.trim().is_empty()) {
+            Some(e) => Some(
+                chrono::DateTime::parse_from_rfc3339(e.trim())
+                    .map_err(|_| invalid("expires_at must be an RFC 3339 date"))?
+                    .with_timezone(&chrono::Utc)
+                    .to_rfc3339(),
+            ),
+            None => None,
+        };
+        Ok(Self {
+            kind: self.kind,
+            title,
+            body,
+            expires_at,
+        })
+    }
+}
+
+/// How an announcement was handed to one peer.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Delivery {
+    pub peer_id: i32,
+    pub peer_name: String,
+    /// `sent`, `queued` (peer unreachable, retried from the outbox) or
+    /// `no_channel` (no E2EE with this peer)
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// An announcement with the name of the peer that sent it.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AnnouncementView {
+    #[serde(flatten)]
+    #[schema(value_type = Announcement)]
+    pub announcement: announcement::Model,
+    /// None for announcements this library published
+    pub peer_name: Option<String>,
+}
+
+/// What became of a received announcement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Received {
+    Stored,
+    /// Already received (a redelivery from the outbox or the relay).
+    Duplicate,
+    /// The sender is not trusted for announcements.
+    Ignored,
+}
+
+impl Received {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Received::Stored => "stored",
+            Received::Duplicate => "duplicate",
+            Received::Ignored => "ignored",
+        }
+    }
+}
+
+fn peer_name(p: &peer::Model) -> String {
+    p.display_name.clone().unwrap_or_else(|| p.name.clone())
+}
+
+/// Store an announcement and send it to every accepted peer.
+pub async fn publish(
+    state: &AppState,
+    draft: Draft,
+) -> Result<(announcement::Model, Vec<Delivery>), AnnouncementError> {
+    let db = state.db();
+    let draft = draft.checked()?;
+    let published = announcement::ActiveModel {
+        id: Set(uuid::Uuid::new_v4().to_string()),
+        peer_id: Set(None),
+        kind: Set(draft.kind),
+        title: Set(draft.title),
+        body: Set(draft.body),
+        expires_at: Set(draft.expires_at),
+        created_at: Set(chrono::Utc::now().to_rfc3339()),
+    }
+    .insert(db)
+    .await?;
+
+    let payload = json!({
+        "id": published.id,
+        "kind": published.kind,
+        "title": published.title,
+        "body": published.body,
+        "expires_at": published.expires_at,
+        "created_at": published.created_at,
+    });
+    let peers = peer::Entity::find()
+        .filter(peer::Column::ConnectionStatus.eq("accepted"))
+        .all(db)
+        .await?;
+    let deliveries = futures::future::join_all(peers.iter().map(|p| {
+        let payload = payload.clone();
+        async move {
+            let (status, error) =
+                match crate::api::peer::try_send_e2ee(state, p, "announcement", payload.clone())
+                    .await
+                {
+                    Ok(Some(_)) => ("sent", None),
+                    Ok(None) => ("no_channel", None),
+                    Err(e) => {
+                        crate::services::peer_outbox::enqueue_or_log(
+                            state.db(),
+                            p.id,
+                            "announcement",
+                            &payload,
+                            None,
+                            &e,
+                        )
+                        .await;
+                        ("queued", Some(e))
+                    }
+                };
+            Delivery {
+                peer_id: p.id,
+                peer_name: peer_name(p),
+                status: status.to_string(),
+                error,
+            }
+        }
+    }))
+    .await;
+    Ok((published, deliveries))
+}
+
+/// Handle an `announcement` message from `sender`.
+pub async fn receive(
+    db: &DatabaseConnection,
+    sender: &peer::Model,
+    payload: &serde_json::Value,
+) -> Result<Received, AnnouncementError> {
+    if !sender.trust_announcements {
+        tracing::info!(
+            "Announcements: ignoring one from untrusted peer {} ({})",
+            sender.name,
+            sender.id
+        );
+        return Ok(Received::Ignored);
+    }
+    let id = payload
+        .get("id")
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_ID_CHARS)
+        .ok_or_else(|| AnnouncementError::Invalid("missing id".to_string()))?
+        .to_string();
+    let draft: Draft = serde_json::from_value(payload.clone())
+        .map_err(|e| AnnouncementError::Invalid(e.to_string()))?;
+    let draft = draft.checked()?;
+    if announcement::Entity::find_by_id(id.clone())
+        .one(db)
+        .await?
+        .is_some()
+    {
+        return Ok(Received::Duplicate);
+    }
+
+    let stored = announcement::ActiveModel {
+        id: Set(id),
+        peer_id: Set(Some(sender.id)),
+        kind: Set(draft.kind),
+        title: Set(draft.title),
+        body: Set(draft.body),
+        expires_at: Set(draft.expires_at),
+        created_at: Set(chrono::Utc::now().to_rfc3339()),
+    }
+    .insert(db)
+    .await?;
+    crate::services::notification_service::emit(
+        db,
+        CreateNotification {
+            event_type: NotificationEventType::Announcement,
+            title: format!("{}: {}", peer_name(sender), stored.title),
+            body: stored.body.clone(),
+            ref_type: Some("announcement".to_string()),
+            ref_id: Some(stored.id.clone()),
+        },
+    )
+    .await;
+    Ok(Received::Stored)
+}
+
+/// Announcements, newest first: `sent` ones, `received` ones or both, and
+/// only those not expired yet when `current_only`.
+pub async fn list(
+    db: &DatabaseConnection,
+    scope: Option<&str>,
+    current_only: bool,
+) -> Result<Vec<AnnouncementView>, AnnouncementError> {
+    let mut query = announcement::Entity::find().order_by_desc(announcement::Column::CreatedAt);
+    query = match scope {
+        Some("sent") => query.filter(announcement::Column::PeerId.is_null()),
+        Some("received") => query.filter(announcement::Column::PeerId.is_not_null()),
+        None => query,
+        Some(other) => {
+            return Err(AnnouncementError::Invalid(format!(
+                "unknown scope '{other}', expected sent or received"
+            )));
+        }
+    };
+    if current_only {
+        query = query.filter(
+            Condition::any()
+                .add(announcement::Column::ExpiresAt.is_null())
+                .add(announcement::Column::ExpiresAt.gt(chrono::Utc::now().to_rfc3339())),
+        );
+    }
+    let found = query.find_also_related(peer::Entity).all(db).await?;
+    Ok(found
+        .into_iter()
+        .map(|(announcement, sender)| AnnouncementView {
+            announcement,
+            peer_name: sender.as_ref().map(peer_name),
+        })
+        .collect())
+}
+
+/// Remove an announcement locally. Peers keep their copy.
+pub async fn delete(db: &DatabaseConnection, id: &str) -> Result<(), AnnouncementError> {
+    let res = announcement::Entity::delete_by_id(id.to_string())
+        .exec(db)
+        .await?;
+    if res.rows_affected == 0 {
+        return Err(AnnouncementError::NotFound);
+    }
+    Ok(())
+}
+
+/// Accept or ignore the announcements of `peer_id` from now on.
+pub async fn set_trust(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    trusted: bool,
+) -> Result<peer::Model, AnnouncementError> {
+    let found = peer::Entity::find_by_id(peer_id)
+        .one(db)
+        .await?
+        .ok_or(AnnouncementError::PeerNotFound)?;
+    let mut active: peer::ActiveModel = found.into();
+    active.trust_announcements = Set(trusted);
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    Ok(active.update(db).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn only_trusted_peers_reach_the_inbox_and_once() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        let sender = peer::ActiveModel {
+            name: Set("Corner Library".to_string()),
+            url: Set("http://192.168.1.30:8000".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let payload = json!({
+            "id": "a1",
+            "kind": "book_sale",
+            "title": "  Book sale on Saturday  ",
+            "body": "Everything at 1 euro",
+        });
+
+        assert_eq!(
+            receive(&db, &sender, &payload).await.unwrap(),
+            Received::Ignored
+        );
+        assert!(list(&db, None, false).await.unwrap().is_empty());
+
+        let sender = set_trust(&db, sender.id, true).await.unwrap();
+        assert_eq!(
+            receive(&db, &sender, &payload).await.unwrap(),
+            Received::Stored
+        );
+        assert_eq!(
+            receive(&db, &sender, &payload).await.unwrap(),
+            Received::Duplicate
+        );
+        assert!(matches!(
+            receive(
+                &db,
+                &sender,
+                &json!({ "id": "a2", "kind": "spam", "title": "x" })
+            )
+            .await,
+            Err(AnnouncementError::Invalid(_))
+        ));
+
+        let received = list(&db, Some("received"), true).await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].announcement.title, "Book sale on Saturday");
+        assert_eq!(received[0].peer_name.as_deref(), Some("Corner Library"));
+        assert!(list(&db, Some("sent"), false).await.unwrap().is_empty());
+
+        let inbox = crate::models::notification::Entity::find()
+            .filter(crate::models::notification::Column::EventType.eq("announcement"))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].title, "Corner Library: Book sale on Saturday");
+        assert_eq!(inbox[0].ref_id.as_deref(), Some("a1"));
+    }
+}
@@ -0,0 +1,340 @@
+//! Daily call quotas for the external metadata providers.
+//!
+//! A shared or public instance looking books up for many users can hammer
+//! BnF, Open Library and Google Books (whose keyless quota is shared by
+//! everyone behind the same address). Each provider can be given a daily
+//! call budget; once it is spent, the provider's lookups return nothing
+//! without calling out, so the lookup chains fall through to the next
+//! source, and the refusal is counted. Answers served from a provider's
+//! cache cost nothing.
+//!
+//! Calls are counted on every lookup, so the counters are kept in memory,
+//! loaded at startup (`load`) and written to `api_usage` every minute
+//! (`spawn`) and before being reported (`usage`). Days are UTC dates.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::NaiveDate;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+/// Time between two writes of the counters.
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Days of past usage reported next to today's.
+const HISTORY_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum Provider {
+    #[serde(rename = "bnf")]
+    Bnf,
+    #[serde(rename = "openlibrary")]
+    OpenLibrary,
+    #[serde(rename = "google_books")]
+    GoogleBooks,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::Bnf, Provider::OpenLibrary, Provider::GoogleBooks];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Provider::Bnf => "bnf",
+            Provider::OpenLibrary => "openlibrary",
+            Provider::GoogleBooks => "google_books",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    calls: u64,
+    skipped: u64,
+    /// Changed since the last write
+    dirty: bool,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    day: Option<NaiveDate>,
+    limits: HashMap<Provider, u64>,
+    counters: HashMap<Provider, Counter>,
+    /// Counters of a day that ended before they were written
+    unflushed: Vec<(NaiveDate, Provider, Counter)>,
+}
+
+impl Registry {
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.day == Some(today) {
+            return;
+        }
+        if let Some(day) = self.day {
+            for (p, c) in self.counters.drain() {
+                if c.dirty {
+                    self.unflushed.push((day, p, c));
+                }
+            }
+        }
+        self.day = Some(today);
+    }
+
+    /// Count one call to `provider`; `false` when its budget is spent.
+    fn take(&mut self, provider: Provider, today: NaiveDate) -> bool {
+        self.roll_over(today);
+        let limit = self.limits.get(&provider).copied();
+        let counter = self.counters.entry(provider).or_default();
+        counter.dirty = true;
+        if limit.is_some_and(|l| counter.calls >= l) {
+            counter.skipped += 1;
+            false
+        } else {
+            counter.calls += 1;
+            true
+        }
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(|| Mutex::new(Registry::default()));
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+/// Count a call about to be made to `provider`. `false` when today's budget
+/// is spent: skip the call.
+pub fn try_call(provider: Provider) -> bool {
+    let allowed = registry().take(provider, today());
+    if !allowed {
+        tracing::debug!("api_quota: {} over its daily quota", provider.as_str());
+    }
+    allowed
+}
+
+/// The error a provider returns in place of a call refused by its quota.
+pub fn over_quota(provider: Provider) -> String {
+    format!("{} daily quota reached", provider.as_str())
+}
+
+/// Read the limits and today's counters.
+pub async fn load(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+    let day = today();
+    let limits = db
+        .query_all(Statement::from_string(
+            backend,
+            "SELECT provider, daily_limit FROM api_quotas WHERE daily_limit IS NOT NULL",
+        ))
+        .await?;
+    let usage = db
+        .query_all(Statement::from_sql_and_values(
+            backend,
+            "SELECT provider, calls, skipped FROM api_usage WHERE day = ?",
+            [day.to_string().into()],
+        ))
+        .await?;
+
+    let mut reg = registry();
+    reg.limits.clear();
+    for row in limits {
+        let provider: String = row.try_get("", "provider")?;
+        let limit: i64 = row.try_get("", "daily_limit")?;
+        if let Some(p) = Provider::parse(&provider) {
+            reg.limits.insert(p, limit.max(0) as u64);
+        }
+    }
+    reg.day = Some(day);
+    reg.counters.clear();
+    for row in usage {
+        let provider: String = row.try_get("", "provider")?;
+        if let Some(p) = Provider::parse(&provider) {
+            let calls: i64 = row.try_get("", "calls")?;
+            let skipped: i64 = row.try_get("", "skipped")?;
+            reg.counters.insert(
+                p,
+                Counter {
+                    calls: calls.max(0) as u64,
+                    skipped: skipped.max(0) as u64,
+                    dirty: false,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Write the counters changed since the last write.
+pub async fn flush(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let pending: Vec<(NaiveDate, Provider, Counter)> = {
+        let mut reg = registry();
+        let mut pending = std::mem::take(&mut reg.unflushed);
+        if let Some(day) = reg.day {
+            for (p, c) in reg.counters.iter_mut().filter(|(_, c)| c.dirty) {
+                c.dirty = false;
+                pending.push((day, *p, *c));
+            }
+        }
+        pending
+    };
+    for (day, provider, counter) in pending {
+        db.execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "INSERT INTO api_usage (provider, day, calls, skipped) VALUES (?, ?, ?, ?) \
+             ON CONFLICT(provider, day) DO UPDATE SET calls = excluded.calls, skipped = excluded.skipped",
+            [
+                provider.as_str().into(),
+                day.to_string().into(),
+                (counter.calls as i64).into(),
+                (counter.skipped as i64).into(),
+            ],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+/// Spawn the background task writing the counters every minute.
+pub fn spawn(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = flush(&db).await {
+                tracing::warn!("api_quota: {e}");
+            }
+        }
+    });
+}
+
+/// Calls made to a provider on one day.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DayUsage {
+    /// `YYYY-MM-DD`, UTC
+    pub day: String,
+    pub calls: u64,
+    /// Calls refused once over quota
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ProviderUsage {
+    pub provider: Provider,
+    /// Calls allowed per UTC day; `None` when not capped
+    pub daily_limit: Option<u64>,
+    pub today: DayUsage,
+    /// Calls left today; `None` when not capped
+    pub remaining: Option<u64>,
+    /// The previous days, most recent first
+    pub history: Vec<DayUsage>,
+}
+
+/// Today's usage and budget of every provider, with the last days.
+pub async fn usage(db: &DatabaseConnection) -> Result<Vec<ProviderUsage>, DbErr> {
+    flush(db).await?;
+    let day = today();
+    let since = day - chrono::Duration::days(HISTORY_DAYS);
+    let rows = db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT provider, day, calls, skipped FROM api_usage \
+             WHERE day >= ? AND day < ? ORDER BY day DESC",
+            [since.to_string().into(), day.to_string().into()],
+        ))
+        .await?;
+    let mut history: HashMap<Provider, Vec<DayUsage>> = HashMap::new();
+    for row in rows {
+        let provider: String = row.try_get("", "provider")?;
+        let Some(p) = Provider::parse(&provider) else {
+            continue;
+        };
+        let calls: i64 = row.try_get("", "calls")?;
+        let skipped: i64 = row.try_get("", "skipped")?;
+        history.entry(p).or_default().push(DayUsage {
+            day: row.try_get("", "day")?,
+            calls: calls.max(0) as u64,
+            skipped: skipped.max(0) as u64,
+        });
+    }
+
+    let mut reg = registry();
+    reg.roll_over(day);
+    Ok(Provider::ALL
+        .into_iter()
+        .map(|p| {
+            let counter = reg.counters.get(&p).copied().unwrap_or_default();
+            let daily_limit = reg.limits.get(&p).copied();
+            ProviderUsage {
+                provider: p,
+                daily_limit,
+                today: DayUsage {
+                    day: day.to_string(),
+                    calls: counter.calls,
+                    skipped: counter.skipped,
+                },
+                remaining: daily_limit.map(|l| l.saturating_sub(counter.calls)),
+                history: history.remove(&p).unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// Set the daily limit of the providers named, `None` lifting it. The
+/// others keep theirs.
+pub async fn set_limits(
+    db: &DatabaseConnection,
+    limits: &HashMap<Provider, Option<u64>>,
+) -> Result<(), DbErr> {
+    for (provider, limit) in limits {
+        db.execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "INSERT INTO api_quotas (provider, daily_limit) VALUES (?, ?) \
+             ON CONFLICT(provider) DO UPDATE SET daily_limit = excluded.daily_limit",
+            [
+                provider.as_str().into(),
+                limit.map(|l| l.min(i64::MAX as u64) as i64).into(),
+            ],
+        ))
+        .await?;
+    }
+    let mut reg = registry();
+    for (provider, limit) in limits {
+        match limit {
+            Some(l) => reg.limits.insert(*provider, *l),
+            None => reg.limits.remove(provider),
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_spent_budget_refuses_calls_until_the_next_day() {
+        let mut reg = Registry::default();
+        reg.limits.insert(Provider::GoogleBooks, 2);
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        assert!(reg.take(Provider::GoogleBooks, day));
+        assert!(reg.take(Provider::GoogleBooks, day));
+        assert!(!reg.take(Provider::GoogleBooks, day));
+        assert!(reg.take(Provider::Bnf, day));
+        let google = reg.counters[&Provider::GoogleBooks];
+        assert_eq!((google.calls, google.skipped), (2, 1));
+
+        let next = day.succ_opt().unwrap();
+        assert!(reg.take(Provider::GoogleBooks, next));
+        // The finished day waits to be written.
+        assert_eq!(reg.unflushed.len(), 2);
+        assert!(reg.unflushed.iter().all(|(d, _, _)| *d == day));
+    }
+}
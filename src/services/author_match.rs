@@ -0,0 +1,121 @@
+//! Existing authors and books close to what the user is typing.
+//!
+//! Typed names drift: "J.R.R. Tolkein", "Tolkien, J.R.R.", "Emile Zola" for
+//! "Émile Zola". Creating a book under such a name used to add a second
+//! author. These lookups rank the catalogue by `utils::fuzzy` similarity so
+//! the app can offer the existing spelling before it creates anything; the
+//! import's duplicate detection compares author names the same way.
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{author, book};
+use crate::utils::fuzzy;
+
+/// Scores below this are not worth offering.
+pub const DEFAULT_MIN_SCORE: f64 = 0.75;
+pub const DEFAULT_LIMIT: usize = 5;
+pub const MAX_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AuthorMatch {
+    pub id: String,
+    pub name: String,
+    /// 1.0 for the same name, case, accents and word order aside
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct TitleMatch {
+    pub id: String,
+    pub title: String,
+    pub score: f64,
+}
+
+fn best<T>(mut found: Vec<(f64, T)>, limit: usize) -> Vec<(f64, T)> {
+    found.sort_by(|a, b| b.0.total_cmp(&a.0));
+    found.truncate(limit.clamp(1, MAX_LIMIT));
+    found
+}
+
+/// Authors whose name is close to `name`, best first.
+pub async fn match_authors(
+    db: &DatabaseConnection,
+    name: &str,
+    min_score: f64,
+    limit: usize,
+) -> Result<Vec<AuthorMatch>, DbErr> {
+    let found = author::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (fuzzy::name_similarity(name, &a.name), a))
+        .filter(|(score, _)| *score >= min_score)
+        .collect();
+    Ok(best(found, limit)
+        .into_iter()
+        .map(|(score, a)| AuthorMatch {
+            id: a.id,
+            name: a.name,
+            score,
+        })
+        .collect())
+}
+
+/// Books whose title is close to `title`, best first.
+pub async fn match_titles(
+    db: &DatabaseConnection,
+    title: &str,
+    min_score: f64,
+    limit: usize,
+) -> Result<Vec<TitleMatch>, DbErr> {
+    let titles: Vec<(String, String)> = book::Entity::find()
+        .select_only()
+        .columns([book::Column::Id, book::Column::Title])
+        .into_tuple()
+        .all(db)
+        .await?;
+    let found = titles
+        .into_iter()
+        .map(|(id, t)| (fuzzy::title_similarity(title, &t), (id, t)))
+        .filter(|(score, _)| *score >= min_score)
+        .collect();
+    Ok(best(found, limit)
+        .into_iter()
+        .map(|(score, (id, title))| TitleMatch { id, title, score })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_misspelt_name_finds_the_catalogued_author() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        for name in ["J.R.R. Tolkien", "Jack London"] {
+            author::ActiveModel {
+                name: Set(name.to_string()),
+                created_at: Set(now.clone()),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let found = match_authors(&db, "J.R.R. Tolkein", DEFAULT_MIN_SCORE, DEFAULT_LIMIT)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "J.R.R. Tolkien");
+        assert!(
+            match_authors(&db, "Virginia Woolf", DEFAULT_MIN_SCORE, DEFAULT_LIMIT)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Bulk clean-up of author names.
+//!
+//! Imports and hand entry leave one writer under several names: "Tolkien,
+//! J.R.R." next to "J.R.R. Tolkien", "Emile Zola" next to "Émile Zola",
+//! "Victor  Hugo" with two spaces. [`proposals`] lists one fix per writer:
+//! every author whose name, once spaces are collapsed and a "Last, First"
+//! form turned around, folds (`utils::fuzzy::fold`) to the same text, and the
+//! name they should all carry. The accented spelling wins over the bare one,
+//! then the one on the most books.
+//!
+//! [`apply`] carries out the fixes the user accepted: the kept author takes
+//! the name, the books of the others move to it, and the others go. Each
+//! step is logged so paired devices follow (`author` UPDATE with
+//! `previous_name`, `book_author` DELETE and INSERT by name, `author`
+//! DELETE).
+
+use std::collections::{BTreeMap, HashMap};
+
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+use crate::models::{author, book_authors};
+use crate::utils::fuzzy;
+
+/// Name suffixes a comma does not invert ("King, Jr.").
+const SUFFIXES: [&str; 7] = ["jr", "jr.", "sr", "sr.", "ii", "iii", "iv"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FixReason {
+    /// Leading, trailing or repeated spaces
+    Whitespace,
+    /// "Last, First"
+    Inverted,
+    /// The same name with and without accents
+    Diacritics,
+    /// Several authors for one name
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AuthorVariant {
+    pub id: String,
+    pub name: String,
+    pub books: u64,
+}
+
+/// One writer whose authors need a fix.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct AuthorFix {
+    /// Identifies the fix when accepting it
+    pub key: String,
+    /// The name every variant will carry
+    pub name: String,
+    pub reasons: Vec<FixReason>,
+    /// The authors concerned, the one kept first
+    pub authors: Vec<AuthorVariant>,
+}
+
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct AcceptFixes {
+    /// Keys of the fixes to apply, as listed by `GET /api/authors/normalize`
+    pub accept: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct FixSummary {
+    pub applied: u64,
+    /// Authors given a new name
+    pub renamed: u64,
+    /// Authors merged into another and removed
+    pub merged: u64,
+    /// Book links moved to the kept author
+    pub books_moved: u64,
+}
+
+fn strip_accents(s: &str) -> String {
+    s.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+/// `name` with its spaces collapsed and a "Last, First" form turned around,
+/// and what that changed.
+fn cleaned(name: &str) -> (String, Vec<FixReason>) {
+    let mut reasons = Vec::new();
+    let mut fixed = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if fixed != name {
+        reasons.push(FixReason::Whitespace);
+    }
+    if let Some((last, first)) = fixed.split_once(", ").or_else(|| fixed.split_once(','))
+        && !first.contains(',')
+        && !last.trim().is_empty()
+        && !first.trim().is_empty()
+        && !SUFFIXES.contains(&first.trim().to_lowercase().as_str())
+    {
+        fixed = format!("{} {}", first.trim(), last.trim());
+        reasons.push(FixReason::Inverted);
+    }
+    (fixed, reasons)
+}
+
+/// Fixes for the authors in the database, largest groups first.
+pub async fn proposals(db: &DatabaseConnection) -> Result<Vec<AuthorFix>, DbErr> {
+    let mut books: HashMap<String, u64> = HashMap::new();
+    for link in book_authors::Entity::find().all(db).await? {
+        *books.entry(link.author_id).or_default() += 1;
+    }
+
+    struct Member {
+        variant: AuthorVariant,
+        cleaned: String,
+        reasons: Vec<FixReason>,
+    }
+    let mut groups: BTreeMap<String, Vec<Member>> = BTreeMap::new();
+    for a in author::Entity::find().all(db).await? {
+        let (cleaned, reasons) = cleaned(&a.name);
+        let key = fuzzy::fold(&cleaned);
+        if key.is_empty() {
+            continue;
+        }
+        groups.entry(key).or_default().push(Member {
+            variant: AuthorVariant {
+                books: books.get(&a.id).copied().unwrap_or(0),
+                id: a.id,
+                name: a.name,
+            },
+            cleaned,
+            reasons,
+        });
+    }
+
+    let mut fixes = Vec::new();
+    for (key, mut members) in groups {
+        // Kept first: accented spelling, most books, already clean.
+        members.sort_by_key(|m| {
+            let accents = m.cleaned.nfd().filter(|c| is_combining_mark(*c)).count();
+            (
+                std::cmp::Reverse(accents),
+                std::cmp::Reverse(m.variant.books),
+                m.reasons.len(),
+                m.variant.name.clone(),
+            )
+        });
+        let name = members[0].cleaned.clone();
+        if members.len() == 1 && members[0].variant.name == name {
+            continue;
+        }
+        let mut reasons: Vec<FixReason> = members
+            .iter()
+            .flat_map(|m| m.reasons.iter().copied())
+            .collect();
+        if members.len() > 1 {
+            reasons.push(FixReason::Duplicate);
+        }
+        if members
+            .iter()
+            .any(|m| m.cleaned != name && strip_accents(&m.cleaned) == strip_accents(&name))
+        {
+            reasons.push(FixReason::Diacritics);
+        }
+        reasons.sort();
+        reasons.dedup();
+        fixes.push(AuthorFix {
+            key,
+            name,
+            reasons,
+            authors: members.into_iter().map(|m| m.variant).collect(),
+        });
+    }
+    // Stable: groups of one size stay in key order.
+    fixes.sort_by_key(|f| std::cmp::Reverse(f.authors.len()));
+    Ok(fixes)
+}
+
+/// Operations to log once a fix is committed.
+enum Logged {
+    Renamed {
+        id: String,
+        name: String,
+        previous_name: String,
+    },
+    Moved {
+        book_id: String,
+        from: (String, String),
+        to: (String, String),
+    },
+    Removed {
+        id: String,
+        name: String,
+    },
+}
+
+async fn apply_fix(
+    txn: &DatabaseTransaction,
+    fix: &AuthorFix,
+    logged: &mut Vec<Logged>,
+    summary: &mut FixSummary,
+) -> Result<(), DbErr> {
+    let Some((kept, others)) = fix.authors.split_first() else {
+        return Ok(());
+    };
+    if kept.name != fix.name {
+        let now = chrono::Utc::now().to_rfc3339();
+        author::Entity::update_many()
+            .col_expr(author::Column::Name, Expr::value(fix.name.clone()))
+            .col_expr(author::Column::UpdatedAt, Expr::value(now))
+            .filter(author::Column::Id.eq(&kept.id))
+            .exec(txn)
+            .await?;
+        logged.push(Logged::Renamed {
+            id: kept.id.clone(),
+            name: fix.name.clone(),
+            previous_name: kept.name.clone(),
+        });
+        summary.renamed += 1;
+    }
+
+    for other in others {
+        let links = book_authors::Entity::find()
+            .filter(book_authors::Column::AuthorId.eq(&other.id))
+            .all(txn)
+            .await?;
+        for link in links {
+            txn.execute(Statement::from_sql_and_values(
+                txn.get_database_backend(),
+                "INSERT OR IGNORE INTO book_authors (book_id, author_id) VALUES ($1, $2)",
+                [link.book_id.clone().into(), kept.id.clone().into()],
+            ))
+            .await?;
+            logged.push(Logged::Moved {
+                book_id: link.book_id,
+                from: (other.id.clone(), other.name.clone()),
+                to: (kept.id.clone(), fix.name.clone()),
+            });
+            summary.books_moved += 1;
+        }
+        book_authors::Entity::delete_many()
+            .filter(book_authors::Column::AuthorId.eq(&other.id))
+            .exec(txn)
+            .await?;
+        author::Entity::delete_by_id(other.id.clone())
+            .exec(txn)
+            .await?;
+        logged.push(Logged::Removed {
+            id: other.id.clone(),
+            name: other.name.clone(),
+        });
+        summary.merged += 1;
+    }
+    summary.applied += 1;
+    Ok(())
+}
+
+/// Apply the fixes whose key is in `accept`, recomputed from the current
+/// names so a stale list cannot merge what no longer matches.
+pub async fn apply(db: &DatabaseConnection, accept: &[String]) -> Result<FixSummary, DbErr> {
+    let fixes: Vec<AuthorFix> = proposals(db)
+        .await?
+        .into_iter()
+        .filter(|f| accept.contains(&f.key))
+        .collect();
+
+    let mut summary = FixSummary::default();
+    let mut logged = Vec::new();
+    let txn = db.begin().await?;
+    for fix in &fixes {
+        apply_fix(&txn, fix, &mut logged, &mut summary).await?;
+    }
+    txn.commit().await?;
+
+    for op in logged {
+        let _ = match op {
+            Logged::Renamed {
+                id,
+                name,
+                previous_name,
+            } => {
+                crate::sync::log_operation(
+                    db,
+                    "author",
+                    &id,
+                    "UPDATE",
+                    Some(serde_json::json!({ "name": name, "previous_name": previous_name })),
+                )
+                .await
+            }
+            Logged::Moved { book_id, from, to } => {
+                let _ = crate::sync::log_operation(
+                    db,
+                    "book_author",
+                    &book_id,
+                    "DELETE",
+                    Some(serde_json::json!({
+                        "book_id": book_id, "author_id": from.0, "author_name": from.1,
+                    })),
+                )
+                .await;
+                crate::sync::log_operation(
+                    db,
+                    "book_author",
+                    &book_id,
+                    "INSERT",
+                    Some(serde_json::json!({
+                        "book_id": book_id, "author_id": to.0, "author_name": to.1,
+                    })),
+                )
+                .await
+            }
+            Logged::Removed { id, name } => {
+                crate::sync::log_operation(
+                    db,
+                    "author",
+                    &id,
+                    "DELETE",
+                    Some(serde_json::json!({ "name": name })),
+                )
+                .await
+            }
+        };
+    }
+    if summary.applied > 0 {
+        tracing::info!(
+            "author_normalize: {} fix(es), {} renamed, {} merged",
+            summary.applied,
+            summary.renamed,
+            summary.merged
+        );
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::book;
+
+    #[test]
+    fn cleaning_collapses_spaces_and_turns_names_around() {
+        assert_eq!(
+            cleaned("  Victor   Hugo "),
+            ("Victor Hugo".to_string(), vec![FixReason::Whitespace])
+        );
+        assert_eq!(
+            cleaned("Tolkien, J.R.R."),
+            ("J.R.R. Tolkien".to_string(), vec![FixReason::Inverted])
+        );
+        assert_eq!(cleaned("King, Jr.").1, []);
+        assert_eq!(cleaned("Zola, Émile, dir.").1, []);
+    }
+
+    async fn add_author(db: &DatabaseConnection, name: &str) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        author::ActiveModel {
+            name: Set(name.to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn add_book(db: &DatabaseConnection, title: &str, author_id: &str) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set(title.to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        book_authors::ActiveModel {
+            book_id: Set(book.id.clone()),
+            author_id: Set(author_id.to_string()),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        book.id
+    }
+
+    #[tokio::test]
+    async fn variants_of_a_name_merge_into_the_accented_one() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let bare = add_author(&db, "Emile Zola").await;
+        let accented = add_author(&db, "Émile Zola").await;
+        let inverted = add_author(&db, "Zola, Emile").await;
+        add_author(&db, "Victor Hugo").await;
+        let germinal = add_book(&db, "Germinal", &bare).await;
+        add_book(&db, "Nana", &accented).await;
+        add_book(&db, "L'Assommoir", &inverted).await;
+
+        let fixes = proposals(&db).await.unwrap();
+        assert_eq!(fixes.len(), 1);
+        let fix = &fixes[0];
+        assert_eq!(fix.name, "Émile Zola");
+        assert_eq!(fix.authors[0].id, accented);
+        assert_eq!(
+            fix.reasons,
+            [
+                FixReason::Inverted,
+                FixReason::Diacritics,
+                FixReason::Duplicate
+            ]
+        );
+
+        let summary = apply(&db, std::slice::from_ref(&fix.key)).await.unwrap();
+        assert_eq!((summary.merged, summary.books_moved), (2, 2));
+        assert_eq!(author::Entity::find().count(&db).await.unwrap(), 2);
+        let link = book_authors::Entity::find()
+            .filter(book_authors::Column::BookId.eq(germinal))
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(link.author_id, accented);
+        assert!(proposals(&db).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,442 @@
+//! Author pages: an author's books across the library, the peers' cached
+//! catalogues and, on request, Open Library, plus links out to the author's
+//! authority records.
+//!
+//! Local books are found through `book_authors`. Peer books only carry an
+//! author string, so they match when one of the names in it equals the
+//! author's name, case aside. Open Library results that share a title with
+//! a local book are left out.
+//!
+//! The authority identifiers (BnF, VIAF, Wikidata, Open Library) are stored
+//! on the author and replicate with it; they are checked and put in their
+//! canonical form before they are saved.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Book, author, book, book_authors, peer, peer_book};
+
+/// The authority identifiers of an author.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuthorIdentifiers {
+    /// BnF authority record, e.g. `cb118826182`
+    pub bnf_id: Option<String>,
+    /// VIAF cluster, e.g. `27066848`
+    pub viaf_id: Option<String>,
+    /// Wikidata item, e.g. `Q7934`
+    pub wikidata_id: Option<String>,
+    /// Open Library author, e.g. `OL79034A`
+    pub openlibrary_id: Option<String>,
+}
+
+impl From<&author::Model> for AuthorIdentifiers {
+    fn from(a: &author::Model) -> Self {
+        Self {
+            bnf_id: a.bnf_id.clone(),
+            viaf_id: a.viaf_id.clone(),
+            wikidata_id: a.wikidata_id.clone(),
+            openlibrary_id: a.openlibrary_id.clone(),
+        }
+    }
+}
+
+/// Check characters of BnF ark identifiers.
+const BNF_CHECK: &str = "0123456789bcdfghjkmnpqrstvwxz";
+
+fn canonical_bnf(raw: &str) -> Option<String> {
+    let id = raw.trim().to_lowercase();
+    let id = id.rsplit('/').next().unwrap_or(&id);
+    let id = if id.starts_with("cb") {
+        id.to_string()
+    } else {
+        format!("cb{id}")
+    };
+    if !id.is_ascii() {
+        return None;
+    }
+    let body = id.get(2..)?;
+    let (digits, check) = body.split_at(body.len().checked_sub(1)?);
+    (digits.len() == 8 && digits.chars().all(|c| c.is_ascii_digit()) && BNF_CHECK.contains(check))
+        .then_some(id)
+}
+
+fn canonical_viaf(raw: &str) -> Option<String> {
+    let id = raw.trim().trim_end_matches('/');
+    let id = id.rsplit('/').next().unwrap_or(id);
+    (!id.is_empty() && id.len() <= 22 && id.chars().all(|c| c.is_ascii_digit()))
+        .then(|| id.to_string())
+}
+
+/// `prefix` + digits (+ `suffix`), the prefix and suffix in capitals.
+fn canonical_key(raw: &str, prefix: &str, suffix: &str) -> Option<String> {
+    let id = raw.trim().trim_end_matches('/').to_uppercase();
+    let id = id.rsplit('/').next().unwrap_or(&id);
+    let digits = id.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+impl AuthorIdentifiers {
+    /// The identifiers in canonical form; pasted record URLs are accepted.
+    /// Blank ones become `None`. `Err` names the first malformed one.
+    pub fn canonical(self) -> Result<Self, String> {
+        fn check(
+            name: &str,
+            value: Option<String>,
+            canonical: impl Fn(&str) -> Option<String>,
+        ) -> Result<Option<String>, String> {
+            match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                None => Ok(None),
+                Some(v) => canonical(v)
+                    .map(Some)
+                    .ok_or_else(|| format!("{name} is not a valid identifier: {v}")),
+            }
+        }
+        Ok(Self {
+            bnf_id: check("bnf_id", self.bnf_id, canonical_bnf)?,
+            viaf_id: check("viaf_id", self.viaf_id, canonical_viaf)?,
+            wikidata_id: check("wikidata_id", self.wikidata_id, |v| {
+                canonical_key(v, "Q", "")
+            })?,
+            openlibrary_id: check("openlibrary_id", self.openlibrary_id, |v| {
+                canonical_key(v, "OL", "A")
+            })?,
+        })
+    }
+
+    /// Links to the records the identifiers name.
+    pub fn links(&self) -> Vec<ExternalLink> {
+        let link = |source: &str, id: &Option<String>, url: &dyn Fn(&str) -> String| {
+            id.as_deref().map(|id| ExternalLink {
+                source: source.to_string(),
+                id: id.to_string(),
+                url: url(id),
+            })
+        };
+        [
+            link("bnf", &self.bnf_id, &|id| {
+                format!("https://catalogue.bnf.fr/ark:/12148/{id}")
+            }),
+            link("viaf", &self.viaf_id, &|id| {
+                format!("https://viaf.org/viaf/{id}")
+            }),
+            link("wikidata", &self.wikidata_id, &|id| {
+                format!("https://www.wikidata.org/wiki/{id}")
+            }),
+            link("openlibrary", &self.openlibrary_id, &|id| {
+                format!("https://openlibrary.org/authors/{id}")
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// A link to an authority record.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ExternalLink {
+    /// `bnf`, `viaf`, `wikidata` or `openlibrary`
+    pub source: String,
+    pub id: String,
+    pub url: String,
+}
+
+/// A book of the author in a peer's catalogue.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PeerWork {
+    pub peer_id: i32,
+    pub peer_name: String,
+    /// The book's id in the peer's library
+    pub remote_book_id: String,
+    pub title: String,
+    pub isbn: Option<String>,
+    pub cover_url: Option<String>,
+    pub available_copies: Option<i32>,
+}
+
+/// A work of the author known to Open Library and not in the library.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ExternalWork {
+    pub title: String,
+    pub publication_year: Option<String>,
+    pub publisher: Option<String>,
+    pub cover_url: Option<String>,
+}
+
+/// Everything known about an author's books.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct AuthorWorks {
+    pub id: String,
+    pub name: String,
+    pub identifiers: AuthorIdentifiers,
+    pub links: Vec<ExternalLink>,
+    /// Books of the library, owned or wished for, by title
+    pub books: Vec<Book>,
+    /// Only when peers were asked for
+    pub peer_books: Vec<PeerWork>,
+    /// Only when Open Library was asked; `None` when it could not be reached
+    pub external: Option<Vec<ExternalWork>>,
+}
+
+/// Whether `credit`, a peer book's author string, names `name`.
+fn credits(credit: &str, name: &str) -> bool {
+    credit
+        .split([',', ';', '&', '/'])
+        .any(|part| part.trim().to_lowercase() == name)
+}
+
+fn title_key(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+async fn peer_works(db: &DatabaseConnection, name: &str) -> Result<Vec<PeerWork>, DbErr> {
+    let name = name.trim().to_lowercase();
+    let candidates = peer_book::Entity::find()
+        .filter(peer_book::Column::Author.like(format!("%{name}%")))
+        .order_by_asc(peer_book::Column::Title)
+        .all(db)
+        .await?;
+    let peer_names: HashMap<i32, String> = peer::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.display_name.unwrap_or(p.name)))
+        .collect();
+    Ok(candidates
+        .into_iter()
+        .filter(|pb| pb.author.as_deref().is_some_and(|a| credits(a, &name)))
+        .map(|pb| PeerWork {
+            peer_name: peer_names.get(&pb.peer_id).cloned().unwrap_or_default(),
+            peer_id: pb.peer_id,
+            remote_book_id: pb.remote_book_id,
+            title: pb.title,
+            isbn: pb.isbn,
+            cover_url: pb.cover_url,
+            available_copies: pb.available_copies,
+        })
+        .collect())
+}
+
+/// The author's works. `None` when there is no such author.
+pub async fn author_works(
+    db: &DatabaseConnection,
+    author_id: &str,
+    with_peers: bool,
+    with_external: bool,
+) -> Result<Option<AuthorWorks>, DbErr> {
+    let Some(found) = author::Entity::find_by_id(author_id.to_string())
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let book_ids: Vec<String> = book_authors::Entity::find()
+        .filter(book_authors::Column::AuthorId.eq(author_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|ba| ba.book_id)
+        .collect();
+    let models = book::Entity::find()
+        .filter(book::Column::Id.is_in(book_ids))
+        .order_by_asc(book::Column::Title)
+        .all(db)
+        .await?;
+    let books = Book::populate_authors(db, models).await;
+
+    let peer_books = if with_peers {
+        peer_works(db, &found.name).await?
+    } else {
+        Vec::new()
+    };
+
+    let external = if with_external {
+        let known: HashSet<String> = books.iter().map(|b| title_key(&b.title)).collect();
+        match crate::modules::integrations::openlibrary::search_author_works(&found.name).await {
+            Ok(results) => {
+                let mut seen = known;
+                Some(
+                    results
+                        .into_iter()
+                        .filter(|r| seen.insert(title_key(&r.title)))
+                        .map(|r| ExternalWork {
+                            title: r.title,
+                            publication_year: r.publication_year,
+                            publisher: r.publisher,
+                            cover_url: r.cover_url,
+                        })
+                        .collect(),
+                )
+            }
+            Err(e) => {
+                tracing::warn!("author works: Open Library search failed: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let identifiers = AuthorIdentifiers::from(&found);
+    Ok(Some(AuthorWorks {
+        links: identifiers.links(),
+        identifiers,
+        id: found.id,
+        name: found.name,
+        books,
+        peer_books,
+        external,
+    }))
+}
+
+/// Replace the identifiers of an author; they must be canonical already
+/// (see [`AuthorIdentifiers::canonical`]). `None` when there is no such
+/// author.
+pub async fn set_identifiers(
+    db: &DatabaseConnection,
+    author_id: &str,
+    identifiers: AuthorIdentifiers,
+) -> Result<Option<author::Model>, DbErr> {
+    let Some(found) = author::Entity::find_by_id(author_id.to_string())
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut active: author::ActiveModel = found.into();
+    active.bnf_id = Set(identifiers.bnf_id.clone());
+    active.viaf_id = Set(identifiers.viaf_id.clone());
+    active.wikidata_id = Set(identifiers.wikidata_id.clone());
+    active.openlibrary_id = Set(identifiers.openlibrary_id.clone());
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let saved = active.update(db).await?;
+
+    // The name resolves the author on devices where it has another id.
+    let _ = crate::sync::log_operation(
+        db,
+        "author",
+        &saved.id,
+        "UPDATE",
+        Some(serde_json::json!({ "name": saved.name, "identifiers": identifiers })),
+    )
+    .await;
+    Ok(Some(saved))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_put_in_canonical_form() {
+        let pasted = AuthorIdentifiers {
+            bnf_id: Some("https://catalogue.bnf.fr/ark:/12148/cb118826182".to_string()),
+            viaf_id: Some("https://viaf.org/viaf/27066848/".to_string()),
+            wikidata_id: Some("q7934".to_string()),
+            openlibrary_id: Some(" ".to_string()),
+        }
+        .canonical()
+        .unwrap();
+        assert_eq!(
+            pasted,
+            AuthorIdentifiers {
+                bnf_id: Some("cb118826182".to_string()),
+                viaf_id: Some("27066848".to_string()),
+                wikidata_id: Some("Q7934".to_string()),
+                openlibrary_id: None,
+            }
+        );
+        assert_eq!(pasted.links()[2].url, "https://www.wikidata.org/wiki/Q7934");
+
+        let bad = AuthorIdentifiers {
+            openlibrary_id: Some("OL79034M".to_string()),
+            ..Default::default()
+        };
+        assert!(bad.canonical().unwrap_err().starts_with("openlibrary_id"));
+    }
+
+    #[tokio::test]
+    async fn works_gather_local_and_peer_books() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = Utc::now().to_rfc3339();
+        let herbert = author::ActiveModel {
+            name: Set("Frank Herbert".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let dune = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        book_authors::ActiveModel {
+            book_id: Set(dune.id.clone()),
+            author_id: Set(herbert.id.clone()),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let friend = peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://192.168.1.20:8000".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        for (remote, title, credit) in [
+            ("r1", "Dune Messiah", "Frank Herbert"),
+            (
+                "r2",
+                "The Dosadi Experiment",
+                "Brian Herbert; Frank Herbert",
+            ),
+            ("r3", "Dune: House Atreides", "Brian Herbert"),
+        ] {
+            peer_book::ActiveModel {
+                peer_id: Set(friend.id),
+                remote_book_id: Set(remote.to_string()),
+                title: Set(title.to_string()),
+                author: Set(Some(credit.to_string())),
+                synced_at: Set(now.clone()),
+                owned: Set(true),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let works = author_works(&db, &herbert.id, true, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(works.books.len(), 1);
+        assert_eq!(works.books[0].title, "Dune");
+        let titles: Vec<&str> = works.peer_books.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Dune Messiah", "The Dosadi Experiment"]);
+        assert_eq!(works.peer_books[0].peer_name, "Bob");
+        assert!(works.external.is_none());
+        assert!(works.links.is_empty());
+    }
+}
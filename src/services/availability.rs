@@ -0,0 +1,450 @@
+//! "Who has this book?": where the owner can get a book, by ISBN, fastest
+//! way first.
+//!
+//! Looks at this library's copies, the cached catalogues of peers, a live
+//! search of the peers when asked (a peer's answer is fresher than its last
+//! catalogue sync), and the libraries and bookshops around the library's
+//! location on OpenStreetMap. The suggestion ranks a copy on the shelf, then
+//! a peer with a copy free, a peer whose copies are not counted, the nearest
+//! library or bookshop, and last a copy of this library coming back from a
+//! loan.
+
+use std::collections::HashMap;
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{book, copy, library_config, loan, peer, peer_book};
+use crate::modules::integrations::osm::{self, BookPlace, PlaceKind};
+use crate::utils::isbn;
+
+pub const DEFAULT_RADIUS_M: u32 = 5_000;
+pub const MAX_RADIUS_M: u32 = 25_000;
+
+#[derive(Debug)]
+pub enum AvailabilityError {
+    InvalidIsbn,
+    Db(DbErr),
+}
+
+impl std::fmt::Display for AvailabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidIsbn => write!(f, "not a valid ISBN"),
+            Self::Db(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AvailabilityError {}
+
+impl From<DbErr> for AvailabilityError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// Which sources to look at beyond this library and the peer caches.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AvailabilityOptions {
+    /// Ask every peer now (a few seconds)
+    pub live_peers: bool,
+    /// Look for libraries and bookshops around `location`
+    pub nearby: bool,
+    pub radius_m: u32,
+    /// Latitude and longitude; the library's own location when `None`
+    pub location: Option<(f64, f64)>,
+}
+
+/// A book of this library with the ISBN.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LocalHolding {
+    pub book_id: String,
+    pub title: String,
+    /// Copies on the shelf, borrowed ones included
+    pub available_copies: u32,
+    pub total_copies: u32,
+    /// Earliest due date of a copy lent out, when none is on the shelf
+    pub back_by: Option<String>,
+}
+
+/// A peer holding the book.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PeerHolding {
+    pub peer_id: i32,
+    pub peer_name: String,
+    /// The book's id in the peer's library, for a borrow request
+    pub book_id: String,
+    pub title: String,
+    /// `None` when the peer does not say
+    pub available_copies: Option<i32>,
+    /// `live` (asked now) or `cache` (last catalogue sync)
+    pub source: String,
+    /// When the cached catalogue was synced
+    pub synced_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    OnShelf,
+    BorrowFromPeer,
+    AskPeer,
+    Library,
+    Bookshop,
+    WaitForReturn,
+}
+
+/// The fastest way found to get the book.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    /// The book, peer or place to go to
+    pub label: String,
+    /// Due date, distance in metres or copies free, depending on `kind`
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Availability {
+    /// ISBN-13 form of the ISBN asked for
+    pub isbn: String,
+    pub title: Option<String>,
+    pub suggestion: Option<Suggestion>,
+    pub local: Vec<LocalHolding>,
+    pub peers: Vec<PeerHolding>,
+    pub places: Vec<BookPlace>,
+    /// Sources that could not be checked, and why
+    pub warnings: Vec<String>,
+}
+
+/// The ISBN-13 and ISBN-10 forms a catalogue may store the book under.
+fn isbn_forms(isbn_input: &str) -> Option<(String, Vec<String>)> {
+    let isbn13 = isbn::to_isbn13(isbn_input)?;
+    let mut forms = vec![isbn13.clone()];
+    forms.extend(isbn::to_isbn10(isbn_input));
+    Some((isbn13, forms))
+}
+
+async fn local_holdings(
+    db: &DatabaseConnection,
+    forms: &[String],
+) -> Result<Vec<LocalHolding>, DbErr> {
+    let books = book::Entity::find()
+        .filter(book::Column::Isbn.is_in(forms.to_vec()))
+        .filter(book::Column::Archived.eq(false))
+        .all(db)
+        .await?;
+    if books.is_empty() {
+        return Ok(Vec::new());
+    }
+    let copies = copy::Entity::find()
+        .filter(copy::Column::BookId.is_in(books.iter().map(|b| b.id.clone())))
+        .all(db)
+        .await?;
+    let lent: Vec<String> = copies
+        .iter()
+        .filter(|c| c.status == "loaned")
+        .map(|c| c.id.clone())
+        .collect();
+    let mut due_dates: HashMap<String, String> = HashMap::new();
+    if !lent.is_empty() {
+        for l in loan::Entity::find()
+            .filter(loan::Column::CopyId.is_in(lent))
+            .filter(loan::Column::Status.is_in(["active", "overdue"]))
+            .all(db)
+            .await?
+        {
+            due_dates.insert(l.copy_id, l.due_date);
+        }
+    }
+
+    let mut holdings = Vec::new();
+    for b in books {
+        let own: Vec<&copy::Model> = copies.iter().filter(|c| c.book_id == b.id).collect();
+        // A wished-for book has a row but no copy.
+        if own.is_empty() {
+            continue;
+        }
+        let available = own
+            .iter()
+            .filter(|c| c.status == "available" || c.status == "borrowed")
+            .count() as u32;
+        let back_by = if available == 0 {
+            own.iter()
+                .filter_map(|c| due_dates.get(&c.id))
+                .min()
+                .cloned()
+        } else {
+            None
+        };
+        holdings.push(LocalHolding {
+            book_id: b.id,
+            title: b.title,
+            available_copies: available,
+            total_copies: own.len() as u32,
+            back_by,
+        });
+    }
+    Ok(holdings)
+}
+
+async fn cached_peer_holdings(
+    db: &DatabaseConnection,
+    forms: &[String],
+    names: &HashMap<i32, String>,
+) -> Result<Vec<PeerHolding>, DbErr> {
+    Ok(peer_book::Entity::find()
+        .filter(peer_book::Column::Isbn.is_in(forms.to_vec()))
+        .filter(peer_book::Column::Owned.eq(true))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|pb| PeerHolding {
+            peer_name: names.get(&pb.peer_id).cloned().unwrap_or_default(),
+            peer_id: pb.peer_id,
+            book_id: pb.remote_book_id,
+            title: pb.title,
+            available_copies: pb.available_copies,
+            source: "cache".to_string(),
+            synced_at: Some(pb.synced_at),
+        })
+        .collect())
+}
+
+/// Ask every peer for `title` and keep the answers with the ISBN.
+async fn live_peer_holdings(
+    db: &DatabaseConnection,
+    title: &str,
+    isbn13: &str,
+    names: &HashMap<i32, String>,
+) -> Vec<PeerHolding> {
+    let params = crate::api::search::SearchQuery {
+        title: Some(title.to_string()),
+        author: None,
+        publisher: None,
+        year_min: None,
+        year_max: None,
+        tags: None,
+        q: None,
+        subjects: None,
+        sources: Some("peers".to_string()),
+        autocomplete: None,
+        collapse_editions: None,
+    };
+    crate::api::peer::broadcast_search(db, &params)
+        .await
+        .into_iter()
+        .filter(|b| b.isbn.as_deref().and_then(isbn::to_isbn13).as_deref() == Some(isbn13))
+        .filter_map(|b| {
+            let peer_id = b
+                .source_data
+                .as_deref()
+                .and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok())
+                .and_then(|d| d["peer_id"].as_i64())? as i32;
+            Some(PeerHolding {
+                peer_id,
+                peer_name: names.get(&peer_id).cloned().unwrap_or_default(),
+                book_id: b.id?,
+                title: b.title,
+                available_copies: b.available_copies,
+                source: "live".to_string(),
+                synced_at: None,
+            })
+        })
+        .collect()
+}
+
+/// The fastest way to the book among what was found.
+fn suggest(
+    local: &[LocalHolding],
+    peers: &[PeerHolding],
+    places: &[BookPlace],
+) -> Option<Suggestion> {
+    if let Some(h) = local.iter().find(|h| h.available_copies > 0) {
+        return Some(Suggestion {
+            kind: SuggestionKind::OnShelf,
+            label: h.title.clone(),
+            detail: None,
+        });
+    }
+    // A live answer over a cached one, then the most copies free.
+    let free = peers
+        .iter()
+        .filter(|p| p.available_copies.is_some_and(|n| n > 0))
+        .max_by_key(|p| (p.source == "live", p.available_copies));
+    if let Some(p) = free {
+        return Some(Suggestion {
+            kind: SuggestionKind::BorrowFromPeer,
+            label: p.peer_name.clone(),
+            detail: p.available_copies.map(|n| n.to_string()),
+        });
+    }
+    if let Some(p) = peers.iter().find(|p| p.available_copies.is_none()) {
+        return Some(Suggestion {
+            kind: SuggestionKind::AskPeer,
+            label: p.peer_name.clone(),
+            detail: None,
+        });
+    }
+    if let Some(place) = places.first() {
+        return Some(Suggestion {
+            kind: match place.kind {
+                PlaceKind::Library => SuggestionKind::Library,
+                PlaceKind::Bookshop => SuggestionKind::Bookshop,
+            },
+            label: place.name.clone(),
+            detail: Some(place.distance_m.to_string()),
+        });
+    }
+    local
+        .iter()
+        .filter_map(|h| h.back_by.as_ref().map(|d| (d, h)))
+        .min_by_key(|(d, _)| *d)
+        .map(|(d, h)| Suggestion {
+            kind: SuggestionKind::WaitForReturn,
+            label: h.title.clone(),
+            detail: Some(d.clone()),
+        })
+}
+
+/// Where the book with `isbn_input` can be had.
+pub async fn resolve(
+    db: &DatabaseConnection,
+    isbn_input: &str,
+    options: AvailabilityOptions,
+) -> Result<Availability, AvailabilityError> {
+    let (isbn13, forms) = isbn_forms(isbn_input).ok_or(AvailabilityError::InvalidIsbn)?;
+    let mut warnings = Vec::new();
+
+    let names: HashMap<i32, String> = peer::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let local = local_holdings(db, &forms).await?;
+    let mut peers = cached_peer_holdings(db, &forms, &names).await?;
+
+    let mut title = local
+        .first()
+        .map(|h| h.title.clone())
+        .or_else(|| peers.first().map(|p| p.title.clone()));
+
+    if options.live_peers && !names.is_empty() {
+        if title.is_none() {
+            match crate::services::lookup_service::lookup_metadata_by_isbn(db, &isbn13, None).await
+            {
+                Ok(found) => title = found.map(|m| m.title),
+                Err(e) => warnings.push(format!("title lookup: {e}")),
+            }
+        }
+        match &title {
+            Some(t) => {
+                let live = live_peer_holdings(db, t, &isbn13, &names).await;
+                // A live answer replaces the cached entry of the same peer.
+                peers.retain(|p| !live.iter().any(|l| l.peer_id == p.peer_id));
+                peers.extend(live);
+            }
+            None => warnings.push("live peers: no title to search the peers for".to_string()),
+        }
+    }
+
+    let mut places = Vec::new();
+    if options.nearby {
+        let location = match options.location {
+            Some(point) => Some(point),
+            None => library_config::Entity::find_by_id(1)
+                .one(db)
+                .await?
+                .and_then(|c| c.latitude.zip(c.longitude)),
+        };
+        match location {
+            Some((lat, lon)) => {
+                let radius = options.radius_m.clamp(1, MAX_RADIUS_M);
+                match osm::nearby_book_places(lat, lon, radius).await {
+                    Ok(found) => places = found,
+                    Err(e) => warnings.push(format!("nearby places: {e}")),
+                }
+            }
+            None => warnings.push("nearby places: the library has no location".to_string()),
+        }
+    }
+
+    Ok(Availability {
+        suggestion: suggest(&local, &peers, &places),
+        isbn: isbn13,
+        title,
+        local,
+        peers,
+        places,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer_holding(name: &str, copies: Option<i32>, source: &str) -> PeerHolding {
+        PeerHolding {
+            peer_id: 1,
+            peer_name: name.to_string(),
+            book_id: "b1".to_string(),
+            title: "Dune".to_string(),
+            available_copies: copies,
+            source: source.to_string(),
+            synced_at: None,
+        }
+    }
+
+    #[test]
+    fn the_fastest_way_wins() {
+        let lent = LocalHolding {
+            book_id: "l1".to_string(),
+            title: "Dune".to_string(),
+            available_copies: 0,
+            total_copies: 1,
+            back_by: Some("2026-11-02".to_string()),
+        };
+        let place = BookPlace {
+            name: "Médiathèque".to_string(),
+            kind: PlaceKind::Library,
+            latitude: 0.0,
+            longitude: 0.0,
+            distance_m: 850,
+            address: None,
+            opening_hours: None,
+            website: None,
+            osm_url: String::new(),
+        };
+
+        let only_lent = suggest(std::slice::from_ref(&lent), &[], &[]).unwrap();
+        assert_eq!(only_lent.kind, SuggestionKind::WaitForReturn);
+        assert_eq!(only_lent.detail.as_deref(), Some("2026-11-02"));
+
+        let with_place = suggest(
+            std::slice::from_ref(&lent),
+            &[],
+            std::slice::from_ref(&place),
+        );
+        assert_eq!(with_place.unwrap().kind, SuggestionKind::Library);
+
+        let peers = [
+            peer_holding("Ana", Some(0), "cache"),
+            peer_holding("Ben", Some(2), "cache"),
+            peer_holding("Chloé", Some(1), "live"),
+        ];
+        let best = suggest(std::slice::from_ref(&lent), &peers, &[place]).unwrap();
+        assert_eq!(best.kind, SuggestionKind::BorrowFromPeer);
+        assert_eq!(best.label, "Chloé");
+
+        assert!(
+            isbn_forms("978-0-306-40615-7")
+                .unwrap()
+                .1
+                .contains(&"0306406152".to_string())
+        );
+        assert!(isbn_forms("not an isbn").is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Cover photos uploaded for a book.
+//!
+//! An upload goes through the image pipeline (`services::images`: turned,
+//! cropped, stripped of EXIF, within the storage quota) and becomes the
+//! book's own cover: the large size is written as the
+//! local cover file `<uuid>.jpg` and `cover_url` points at it, replacing
+//! whatever URL a provider derived (usually an OpenLibrary one). Backups, the
+//! hub catalog and peer payloads already carry local covers from there. The
+//! thumbnail and medium sizes are cached next to it and rebuilt from it when
+//! missing or older than the cover file.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use sea_orm::*;
+
+use crate::models::book;
+use crate::services::images::{self, ImageError, ImageLimits, ImageOps};
+use crate::utils::cover_image::{self, CoverSize};
+use crate::utils::cover_url;
+
+#[derive(Debug)]
+pub enum BookCoverError {
+    Db(DbErr),
+    BookNotFound,
+    Image(ImageError),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for BookCoverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::BookNotFound => write!(f, "book not found"),
+            Self::Image(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "storage error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BookCoverError {}
+
+impl From<DbErr> for BookCoverError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl From<ImageError> for BookCoverError {
+    fn from(e: ImageError) -> Self {
+        Self::Image(e)
+    }
+}
+
+impl From<std::io::Error> for BookCoverError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Write `bytes` to `path` through a temp sibling, so a reader never sees a
+/// half-written cover.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Make `image`, after `ops`, the cover of `book_id`: resize it to every size,
+/// store them in `dir` and point `cover_url` at the large one.
+pub async fn store_cover(
+    db: &DatabaseConnection,
+    dir: &Path,
+    book_id: &str,
+    image: Vec<u8>,
+    ops: ImageOps,
+    limits: ImageLimits,
+) -> Result<book::Model, BookCoverError> {
+    let found = book::Entity::find_by_id(book_id.to_string())
+        .one(db)
+        .await?
+        .ok_or(BookCoverError::BookNotFound)?;
+    // Book ids are uuids, but the id is still a path component here.
+    if book_id.contains(['/', '\\']) || book_id.starts_with('.') {
+        return Err(BookCoverError::BookNotFound);
+    }
+
+    let variants = tokio::task::spawn_blocking(move || -> Result<_, ImageError> {
+        let img = images::transform(&image, &ops, &limits)?;
+        cover_image::cover_variants_of(&img, &CoverSize::ALL).map_err(ImageError::Invalid)
+    })
+    .await
+    .map_err(|e| BookCoverError::Io(std::io::Error::other(e)))??;
+
+    let incoming = variants.iter().map(|(_, jpeg)| jpeg.len() as u64).sum();
+    let mut replacing = 0;
+    for size in CoverSize::ALL {
+        let path = dir.join(cover_url::cover_variant_filename(book_id, size));
+        if let Ok(meta) = tokio::fs::metadata(&path).await {
+            replacing += meta.len();
+        }
+    }
+    images::check_quota(dir, incoming, replacing, &limits).await?;
+
+    let dir = dir.to_path_buf();
+    let id = book_id.to_string();
+    tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        std::fs::create_dir_all(&dir)?;
+        // The cover file first, then the sizes derived from it: their later
+        // mtimes are what marks them fresh for `read_cover`.
+        for (size, jpeg) in variants.iter().rev() {
+            write_atomic(
+                &dir.join(cover_url::cover_variant_filename(&id, *size)),
+                jpeg,
+            )?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| BookCoverError::Io(std::io::Error::other(e)))??;
+
+    let mut active: book::ActiveModel = found.into();
+    active.cover_url = Set(Some(cover_url::local_cover_filename(book_id)));
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let updated = active.update(db).await?;
+    let _ = crate::sync::log_operation(db, "book", book_id, "UPDATE", None).await;
+    Ok(updated)
+}
+
+/// One size of a book's local cover, rebuilt from the cover file when the
+/// cached copy is missing or older. `Large` is the cover file as stored.
+pub async fn read_cover(
+    cover_path: PathBuf,
+    book_id: &str,
+    size: CoverSize,
+) -> Result<Vec<u8>, BookCoverError> {
+    let cached = match (size, cover_path.parent()) {
+        (CoverSize::Large, _) | (_, None) => return Ok(tokio::fs::read(&cover_path).await?),
+        (_, Some(dir)) => dir.join(cover_url::cover_variant_filename(book_id, size)),
+    };
+
+    let source_mtime = tokio::fs::metadata(&cover_path).await?.modified()?;
+    if let Ok(meta) = tokio::fs::metadata(&cached).await
+        && meta.modified().is_ok_and(|t| t >= source_mtime)
+    {
+        return Ok(tokio::fs::read(&cached).await?);
+    }
+
+    let raw = tokio::fs::read(&cover_path).await?;
+    tokio::task::spawn_blocking(move || -> Result<Vec<u8>, BookCoverError> {
+        let jpeg = cover_image::resize_cover(&raw, size).map_err(ImageError::Unreadable)?;
+        // Caching is best-effort: a read-only covers dir still serves.
+        if let Err(e) = write_atomic(&cached, &jpeg) {
+            tracing::debug!("cover variant {} not cached: {e}", cached.display());
+        }
+        Ok(jpeg)
+    })
+    .await
+    .map_err(|e| BookCoverError::Io(std::io::Error::other(e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(w: u32, h: u32) -> Vec<u8> {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(w, h, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        }));
+        let mut buf = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut buf, image::ImageFormat::Png).unwrap();
+        buf.into_inner()
+    }
+
+    #[tokio::test]
+    async fn upload_replaces_the_derived_cover_url() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = Utc::now().to_rfc3339();
+        let id = book::ActiveModel {
+            title: Set("Le Horla".to_string()),
+            cover_url: Set(Some(
+                "https://covers.openlibrary.org/b/isbn/9782070360024-L.jpg".to_string(),
+            )),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap()
+        .id;
+        let dir = std::env::temp_dir().join(format!("bg_book_covers_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let limits = ImageLimits {
+            max_dimension: 3000,
+            storage_quota_bytes: None,
+        };
+        assert!(matches!(
+            store_cover(
+                &db,
+                &dir,
+                &id,
+                b"not an image".to_vec(),
+                ImageOps::default(),
+                limits
+            )
+            .await,
+            Err(BookCoverError::Image(ImageError::Unreadable(_)))
+        ));
+        let updated = store_cover(&db, &dir, &id, png(800, 1000), ImageOps::default(), limits)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            updated.cover_url.as_deref(),
+            Some(format!("{id}.jpg").as_str())
+        );
+        for size in CoverSize::ALL {
+            let jpeg = read_cover(dir.join(format!("{id}.jpg")), &id, size)
+                .await
+                .unwrap();
+            let decoded = image::load_from_memory(&jpeg).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), size.dimensions());
+        }
+        assert!(!dir.join(format!("{id}.jpg.tmp")).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,293 @@
+//! Typed relations between books: a volume `contained_in` its box set, two
+//! texts `bound_with` each other in one binding, a film tie-in
+//! `adaptation_of` a novel, a translation `translated_from` its original.
+//!
+//! A relation is stored once, from the book it was added on; each side reads
+//! it in its own direction (the box set `contains` the volume).
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::*;
+use serde::Deserialize;
+
+use crate::models::{
+    book,
+    book_relation::{self, RELATIONS, RelatedBook},
+};
+
+#[derive(Debug)]
+pub enum BookRelationError {
+    Db(DbErr),
+    BookNotFound(String),
+    NotFound,
+    /// The two books are already related this way.
+    Duplicate,
+    Invalid(String),
+}
+
+impl std::fmt::Display for BookRelationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::BookNotFound(id) => write!(f, "book {id} not found"),
+            Self::NotFound => write!(f, "relation not found"),
+            Self::Duplicate => write!(f, "these books are already related this way"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for BookRelationError {}
+
+impl From<DbErr> for BookRelationError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewBookRelation {
+    pub related_book_id: String,
+    /// `contained_in`, `bound_with`, `adaptation_of` or `translated_from`:
+    /// what this book is to the related one
+    pub relation: String,
+}
+
+/// How `relation` reads from the related book's side.
+fn reverse(relation: &str) -> &str {
+    RELATIONS
+        .iter()
+        .find(|(name, _)| *name == relation)
+        .map(|(_, reverse)| *reverse)
+        .unwrap_or(relation)
+}
+
+async fn relations_of<C: ConnectionTrait>(
+    db: &C,
+    book_id: &str,
+) -> Result<Vec<book_relation::Model>, DbErr> {
+    book_relation::Entity::find()
+        .filter(
+            Condition::any()
+                .add(book_relation::Column::BookId.eq(book_id))
+                .add(book_relation::Column::RelatedBookId.eq(book_id)),
+        )
+        .order_by_asc(book_relation::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// The books related to `book_id`, each relation read from its side. With
+/// `visible_only`, books hidden from peers (private, archived or carrying a
+/// private tag) are left out.
+pub async fn related_books<C: ConnectionTrait>(
+    db: &C,
+    book_id: &str,
+    visible_only: bool,
+) -> Result<Vec<RelatedBook>, DbErr> {
+    let relations = relations_of(db, book_id).await?;
+    if relations.is_empty() {
+        return Ok(Vec::new());
+    }
+    let other = |r: &book_relation::Model| {
+        if r.book_id == book_id {
+            r.related_book_id.clone()
+        } else {
+            r.book_id.clone()
+        }
+    };
+    let ids: Vec<String> = relations.iter().map(other).collect();
+    let books: HashMap<String, book::Model> = book::Entity::find()
+        .filter(book::Column::Id.is_in(ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| (b.id.clone(), b))
+        .collect();
+    let hidden = if visible_only {
+        book::Book::tag_restricted_ids(db).await?
+    } else {
+        Default::default()
+    };
+
+    Ok(relations
+        .iter()
+        .filter_map(|r| {
+            let found = books.get(&other(r))?;
+            if visible_only && (found.private || found.archived || hidden.contains(&found.id)) {
+                return None;
+            }
+            Some(RelatedBook {
+                relation_id: r.id.clone(),
+                relation: if r.book_id == book_id {
+                    r.relation.clone()
+                } else {
+                    reverse(&r.relation).to_string()
+                },
+                book_id: found.id.clone(),
+                title: found.title.clone(),
+                publication_year: found.publication_year,
+            })
+        })
+        .collect())
+}
+
+async fn check_book(db: &DatabaseConnection, id: &str) -> Result<(), BookRelationError> {
+    match book::Entity::find_by_id(id.to_string()).one(db).await? {
+        Some(_) => Ok(()),
+        None => Err(BookRelationError::BookNotFound(id.to_string())),
+    }
+}
+
+/// Relate `book_id` to another book.
+pub async fn add_relation(
+    db: &DatabaseConnection,
+    book_id: &str,
+    new: NewBookRelation,
+) -> Result<Vec<RelatedBook>, BookRelationError> {
+    if !RELATIONS.iter().any(|(name, _)| *name == new.relation) {
+        let known: Vec<&str> = RELATIONS.iter().map(|(name, _)| *name).collect();
+        return Err(BookRelationError::Invalid(format!(
+            "relation must be one of {}",
+            known.join(", ")
+        )));
+    }
+    if new.related_book_id == book_id {
+        return Err(BookRelationError::Invalid(
+            "a book cannot be related to itself".to_string(),
+        ));
+    }
+    check_book(db, book_id).await?;
+    check_book(db, &new.related_book_id).await?;
+
+    // The same link in either direction, read from this book's side, is a
+    // duplicate: `A contained_in B` and `B contains A` say the same thing,
+    // and `A contains B` on top of it would be a cycle.
+    let existing = related_books(db, book_id, false).await?;
+    if existing.iter().any(|r| {
+        r.book_id == new.related_book_id
+            && (r.relation == new.relation || r.relation == reverse(&new.relation))
+    }) {
+        return Err(BookRelationError::Duplicate);
+    }
+
+    book_relation::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        book_id: Set(book_id.to_string()),
+        related_book_id: Set(new.related_book_id),
+        relation: Set(new.relation),
+        created_at: Set(Utc::now().to_rfc3339()),
+    }
+    .insert(db)
+    .await?;
+    Ok(related_books(db, book_id, false).await?)
+}
+
+/// Remove a relation, from either of its books.
+pub async fn remove_relation(
+    db: &DatabaseConnection,
+    book_id: &str,
+    relation_id: &str,
+) -> Result<Vec<RelatedBook>, BookRelationError> {
+    let deleted = book_relation::Entity::delete_many()
+        .filter(book_relation::Column::Id.eq(relation_id))
+        .filter(
+            Condition::any()
+                .add(book_relation::Column::BookId.eq(book_id))
+                .add(book_relation::Column::RelatedBookId.eq(book_id)),
+        )
+        .exec(db)
+        .await?;
+    if deleted.rows_affected == 0 {
+        return Err(BookRelationError::NotFound);
+    }
+    Ok(related_books(db, book_id, false).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_book(db: &DatabaseConnection, title: &str, private: bool) -> String {
+        let now = Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            private: Set(private),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[tokio::test]
+    async fn relations_read_from_both_sides() {
+        let db = setup().await;
+        let box_set = insert_book(&db, "The Lord of the Rings", false).await;
+        let volume = insert_book(&db, "The Two Towers", false).await;
+        let film = insert_book(&db, "Film companion", true).await;
+
+        let related = add_relation(
+            &db,
+            &volume,
+            NewBookRelation {
+                related_book_id: box_set.clone(),
+                relation: "contained_in".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(related[0].relation, "contained_in");
+        assert!(matches!(
+            add_relation(
+                &db,
+                &box_set,
+                NewBookRelation {
+                    related_book_id: volume.clone(),
+                    relation: "contained_in".to_string(),
+                },
+            )
+            .await,
+            Err(BookRelationError::Duplicate)
+        ));
+        add_relation(
+            &db,
+            &film,
+            NewBookRelation {
+                related_book_id: box_set.clone(),
+                relation: "adaptation_of".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let from_box_set = related_books(&db, &box_set, false).await.unwrap();
+        let seen: Vec<(&str, &str)> = from_box_set
+            .iter()
+            .map(|r| (r.relation.as_str(), r.title.as_str()))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                ("contains", "The Two Towers"),
+                ("adapted_as", "Film companion")
+            ]
+        );
+        assert_eq!(related_books(&db, &box_set, true).await.unwrap().len(), 1);
+
+        let txn = db.begin().await.unwrap();
+        crate::infrastructure::referential_integrity::delete_book_cascade(&txn, &volume)
+            .await
+            .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(related_books(&db, &box_set, false).await.unwrap().len(), 1);
+    }
+}
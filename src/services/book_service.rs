@@ -5,8 +5,8 @@
 #![allow(clippy::needless_update)] // SeaORM ActiveModels require ..Default::default()
 
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
-    QueryOrder, Set, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set, TransactionTrait,
 };
 use std::collections::HashMap;
 
@@ -20,6 +20,13 @@ pub struct BookFilter {
     pub author: Option<String>,
     pub title: Option<String>,
     pub tag: Option<String>,
+    /// `Some(true)` lists archived books instead of the unarchived ones.
+    pub archived: Option<bool>,
+    /// Publication year range, both ends included
+    pub year_min: Option<i32>,
+    pub year_max: Option<i32>,
+    /// Lowest `user_rating` (0-10) kept; unrated books are left out
+    pub min_rating: Option<i32>,
 }
 
 /// Tag with count for UI display
@@ -127,9 +134,29 @@ pub async fn list_books(
     if let Some(tag) = &filter.tag
         && !tag.is_empty()
     {
-        query = query.filter(crate::models::book::Column::Subjects.contains(tag));
+        // Subjects spell a genre both ways ("Science Fiction" from Open
+        // Library, "Science-fiction" from the BnF).
+        let mut spellings = Condition::any();
+        for spelling in [tag.clone(), tag.replace(' ', "-"), tag.replace('-', " ")] {
+            spellings = spellings.add(crate::models::book::Column::Subjects.contains(&spelling));
+        }
+        query = query.filter(spellings);
+    }
+
+    if let Some(min) = filter.year_min {
+        query = query.filter(crate::models::book::Column::PublicationYear.gte(min));
+    }
+
+    if let Some(max) = filter.year_max {
+        query = query.filter(crate::models::book::Column::PublicationYear.lte(max));
+    }
+
+    if let Some(min) = filter.min_rating {
+        query = query.filter(crate::models::book::Column::UserRating.gte(min));
     }
 
+    query = query.filter(crate::models::book::Column::Archived.eq(filter.archived == Some(true)));
+
     // Eager-load authors: 2 queries instead of N+1
     let books_with_authors: Vec<(
         crate::models::book::Model,
@@ -328,6 +355,7 @@ pub async fn create_book(db: &DatabaseConnection, book: Book) -> Result<Book, Se
         finished_reading_at: Set(book.finished_reading_at.clone().flatten()),
         owned: Set(book.owned.unwrap_or(true)),
         price: Set(book.price),
+        digital_source_url: Set(book.digital_source_url.clone()),
         created_at: Set(now.to_rfc3339()),
         updated_at: Set(now.to_rfc3339()),
         ..Default::default()
@@ -415,6 +443,7 @@ pub async fn update_book(
         .await?
         .ok_or(ServiceError::NotFound)?;
 
+    let previous_status = book_model.reading_status.clone();
     let mut book: BookActiveModel = book_model.into();
 
     book.title = Set(book_data.title);
@@ -446,6 +475,13 @@ pub async fn update_book(
     }
     book.price = Set(book_data.price);
     book.page_count = Set(book_data.page_count);
+    book.edition = Set(book_data.edition);
+    book.physical_format = Set(book_data.physical_format);
+    book.dimensions = Set(book_data.dimensions);
+    // Only set by a provider: an edit form that does not send it keeps it.
+    if let Some(url) = book_data.digital_source_url {
+        book.digital_source_url = Set(Some(url));
+    }
     book.digital_formats = Set(book_data
         .digital_formats
         .map(|f| serde_json::to_string(&f).unwrap_or_else(|_| "[]".to_string())));
@@ -456,6 +492,14 @@ pub async fn update_book(
 
     let _ = crate::sync::log_operation(db, "book", id, "UPDATE", None).await;
 
+    let _ = crate::services::reading_queue::on_status_change(
+        db,
+        id,
+        &previous_status,
+        &model.reading_status,
+    )
+    .await;
+
     // Handle author update. The caller signals "I am updating the authors"
     // by setting either `authors` (Vec form) or `author` (comma-joined string).
     // When the caller leaves both as `None`, existing links are preserved.
@@ -549,19 +593,34 @@ pub async fn update_book(
 /// replicated tables were rebuilt without foreign keys (ADR-044), so the
 /// cascade is performed at the application level.
 pub async fn delete_book(db: &DatabaseConnection, id: &str) -> Result<(), ServiceError> {
+    delete_books(db, &[id.to_string()]).await
+}
+
+/// Delete several books, each with the cascade of [`delete_book`], in one
+/// transaction: either all of them go or none does.
+pub async fn delete_books(db: &DatabaseConnection, ids: &[String]) -> Result<(), ServiceError> {
     let txn = db.begin().await?;
-    crate::infrastructure::referential_integrity::delete_book_cascade(&txn, id).await?;
+    for id in ids {
+        crate::infrastructure::referential_integrity::delete_book_cascade(&txn, id).await?;
+    }
     txn.commit().await?;
 
-    let _ = crate::sync::log_operation(db, "book", id, "DELETE", None).await;
-
-    // Best-effort: remove the orphaned cover from the hub so storage
-    // does not grow indefinitely. A failure here (hub unreachable, not
-    // registered, cover never existed) must not fail the deletion
-    // itself — the book is already gone from the local DB.
+    let media_root = crate::modules::book_files::storage::media_root();
     let hub_svc = crate::services::hub_directory_service::HubDirectoryService::new();
-    if let Err(e) = hub_svc.delete_cover(db, id).await {
-        tracing::debug!("hub cover cleanup skipped for book {id}: {e}");
+    for id in ids {
+        let _ = crate::sync::log_operation(db, "book", id, "DELETE", None).await;
+
+        // Attached ebook/audiobook files are local blobs; their rows went with
+        // the cascade above.
+        crate::modules::book_files::storage::remove_book_dir(&media_root, id).await;
+
+        // Best-effort: remove the orphaned cover from the hub so storage
+        // does not grow indefinitely. A failure here (hub unreachable, not
+        // registered, cover never existed) must not fail the deletion
+        // itself — the book is already gone from the local DB.
+        if let Err(e) = hub_svc.delete_cover(db, id).await {
+            tracing::debug!("hub cover cleanup skipped for book {id}: {e}");
+        }
     }
 
     Ok(())
@@ -747,7 +806,7 @@ pub async fn search_cover_for_book(
     let is_french = clean_isbn.starts_with("9782") || clean_isbn.starts_with("97910");
     if enable_bnf
         && is_french
-        && let Ok(Some(bnf_book)) = crate::modules::integrations::bnf::lookup_bnf_isbn(isbn).await
+        && let Ok(Some(bnf_book)) = crate::modules::integrations::bnf::lookup_isbn(isbn).await
         && let Some(url) = bnf_book.cover_url
     {
         return Ok(Some(url));
@@ -815,6 +874,7 @@ pub async fn search_cover_by_title(
             subjects: None,
             sources: None,
             autocomplete: Some(true),
+            collapse_editions: None,
         };
         let books =
             crate::modules::integrations::google_books::search_books(&query, google_api_key)
@@ -911,7 +971,7 @@ pub async fn search_all_covers_for_book(
         if !enable_bnf || !is_french {
             return None;
         }
-        crate::modules::integrations::bnf::lookup_bnf_isbn(isbn)
+        crate::modules::integrations::bnf::lookup_isbn(isbn)
             .await
             .ok()
             .flatten()
@@ -1090,6 +1150,7 @@ pub async fn search_all_covers_by_title(
                 subjects: None,
                 sources: None,
                 autocomplete: Some(true),
+                collapse_editions: None,
             };
             let books =
                 crate::modules::integrations::google_books::search_books(&query, gb_key.as_deref())
@@ -1297,7 +1358,7 @@ async fn cleanup_stale_openlibrary_covers(db: &DatabaseConnection) {
         return;
     }
 
-    let client = match reqwest::Client::builder()
+    let client = match crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(3))
         .build()
     {
@@ -1361,7 +1422,7 @@ async fn load_google_books_api_key(db: &DatabaseConnection) -> Option<String> {
 }
 
 // Helper: Create or link author to book
-async fn create_or_link_author(
+pub(crate) async fn create_or_link_author(
     db: &DatabaseConnection,
     book_id: &str,
     author_name: &str,
@@ -1840,6 +1901,8 @@ mod tests {
             title: None,
             tag: None,
             author: None,
+            archived: None,
+            ..Default::default()
         };
         let mut books = list_books(db, filter).await.unwrap();
         assert_eq!(
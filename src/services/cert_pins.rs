@@ -0,0 +1,452 @@
+//! Trust-on-first-use certificate pinning for HTTPS peers.
+//!
+//! A peer serving HTTPS with a self-signed certificate cannot be checked
+//! against a certificate authority. Instead, the certificate it presents the
+//! first time we connect to it (`POST /api/peers/connect`) is recorded for
+//! its origin (`https://host:port`), and every later request to that origin
+//! trusts that certificate only (`get_safe_client_for`): a different one,
+//! e.g. from a machine in the middle, fails the TLS handshake. Peers whose
+//! certificate a CA vouches for are not pinned, so their renewals go through.
+//!
+//! A peer that legitimately changes its certificate is re-pinned by the
+//! owner (`POST /api/peers/certificates/rotate`), after comparing the new
+//! fingerprint with the one the peer shows on its own screen. The replaced
+//! certificate stays trusted for `ROTATION_OVERLAP_DAYS`, so a peer still
+//! serving it from another address or process is not cut off at once.
+//!
+//! Pins are read on every peer request, so they are kept in memory too,
+//! loaded at startup (`load`) and updated with the table.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::models::peer_certificate;
+
+/// Days the certificate replaced by a rotation is still trusted.
+pub const ROTATION_OVERLAP_DAYS: i64 = 7;
+
+/// A pin as kept in memory.
+#[derive(Clone)]
+struct Pin {
+    der: Vec<u8>,
+    /// The certificate replaced by the last rotation, and until when it is
+    /// still trusted.
+    previous: Option<(Vec<u8>, DateTime<Utc>)>,
+}
+
+impl Pin {
+    fn from_row(row: &peer_certificate::Model) -> Option<Self> {
+        let der = hex::decode(&row.der).ok()?;
+        let previous = row.previous_der.as_deref().and_then(|previous| {
+            let rotated_at = DateTime::parse_from_rfc3339(row.rotated_at.as_deref()?).ok()?;
+            let until = rotated_at.with_timezone(&Utc) + Duration::days(ROTATION_OVERLAP_DAYS);
+            Some((hex::decode(previous).ok()?, until))
+        });
+        Some(Pin { der, previous })
+    }
+}
+
+/// Pinned certificates by origin.
+static PINS: LazyLock<RwLock<HashMap<String, Pin>>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Error type for pinning operations
+#[derive(Debug)]
+pub enum PinError {
+    /// Not an `https://` URL.
+    NotHttps,
+    NotPinned,
+    /// The peer did not complete a TLS handshake.
+    Unreachable(String),
+    /// The peer presents a certificate other than the one expected.
+    Mismatch {
+        presented: String,
+    },
+    Db(DbErr),
+}
+
+impl std::fmt::Display for PinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PinError::NotHttps => write!(f, "Only HTTPS peers have certificates"),
+            PinError::NotPinned => write!(f, "No certificate pinned for this peer"),
+            PinError::Unreachable(e) => write!(f, "Peer unreachable: {}", e),
+            PinError::Mismatch { presented } => {
+                write!(f, "The peer presents another certificate ({})", presented)
+            }
+            PinError::Db(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<DbErr> for PinError {
+    fn from(e: DbErr) -> Self {
+        PinError::Db(e)
+    }
+}
+
+/// What a peer presents, compared with what is pinned for it.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Probe {
+    pub origin: String,
+    /// SHA-256 of the certificate the peer presents now
+    pub presented_sha256: String,
+    pub pinned_sha256: Option<String>,
+    pub matches: bool,
+}
+
+/// `https://host:port` for an HTTPS URL, `None` for anything else.
+pub fn origin_of(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    if url.scheme() != "https" {
+        return None;
+    }
+    Some(format!(
+        "https://{}:{}",
+        url.host_str()?.to_ascii_lowercase(),
+        url.port_or_known_default()?
+    ))
+}
+
+/// SHA-256 fingerprint of a DER certificate, lowercase hex.
+pub fn fingerprint(der: &[u8]) -> String {
+    hex::encode(Sha256::digest(der))
+}
+
+/// The certificates (DER) trusted for the origin of `url`: the pinned one,
+/// then the one it replaced while the rotation overlap lasts. Empty when
+/// nothing is pinned.
+pub fn pinned_ders(url: &str) -> Vec<Vec<u8>> {
+    let Some(pin) = origin_of(url).and_then(|origin| PINS.read().ok()?.get(&origin).cloned())
+    else {
+        return Vec::new();
+    };
+    let mut ders = vec![pin.der];
+    if let Some((previous, until)) = pin.previous
+        && Utc::now() < until
+    {
+        ders.push(previous);
+    }
+    ders
+}
+
+fn cache(origin: &str, pin: Option<Pin>) {
+    if let Ok(mut pins) = PINS.write() {
+        match pin {
+            Some(pin) => pins.insert(origin.to_string(), pin),
+            None => pins.remove(origin),
+        };
+    }
+}
+
+/// Load the pins into memory. Called once the database is migrated.
+pub async fn load(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let rows = peer_certificate::Entity::find().all(db).await?;
+    let pins: HashMap<String, Pin> = rows
+        .iter()
+        .filter_map(|r| Some((r.origin.clone(), Pin::from_row(r)?)))
+        .collect();
+    let count = pins.len();
+    if let Ok(mut cached) = PINS.write() {
+        *cached = pins;
+    }
+    Ok(count)
+}
+
+/// Every pinned certificate.
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<peer_certificate::Model>, DbErr> {
+    peer_certificate::Entity::find()
+        .order_by_asc(peer_certificate::Column::Origin)
+        .all(db)
+        .await
+}
+
+/// The certificate the peer at `url` presents, whoever signed it.
+async fn presented_certificate(url: &str) -> Result<Vec<u8>, PinError> {
+    let mut builder = crate::services::network_mode::client_builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .redirect(reqwest::redirect::Policy::none())
+        // Only to read the certificate: nothing is trusted from this answer.
+        .danger_accept_invalid_certs(true)
+        .tls_info(true);
+    if let Some(proxy) = crate::services::tor::proxy_for(Some(url)) {
+        builder = builder
+            .proxy(proxy)
+            .timeout(std::time::Duration::from_secs(30));
+    }
+    let client = builder
+        .build()
+        .map_err(|e| PinError::Unreachable(e.to_string()))?;
+    let res = client
+        .get(format!("{}/api/health", url.trim_end_matches('/')))
+        .send()
+        .await
+        .map_err(|e| PinError::Unreachable(e.to_string()))?;
+    res.extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| PinError::Unreachable("no certificate presented".to_string()))
+}
+
+/// Whether a CA vouches for the peer's certificate.
+async fn ca_trusted(url: &str) -> bool {
+    crate::api::peer::get_safe_client_for(url)
+        .get(format!("{}/api/health", url.trim_end_matches('/')))
+        .send()
+        .await
+        .is_ok()
+}
+
+async fn save(
+    db: &DatabaseConnection,
+    origin: &str,
+    der: Vec<u8>,
+    previous: Option<peer_certificate::Model>,
+) -> Result<peer_certificate::Model, DbErr> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let sha256 = fingerprint(&der);
+    let saved = match previous {
+        Some(previous) => {
+            let previous_sha256 = previous.sha256.clone();
+            let previous_der = previous.der.clone();
+            let mut active: peer_certificate::ActiveModel = previous.into();
+            active.sha256 = Set(sha256);
+            active.der = Set(hex::encode(&der));
+            active.previous_sha256 = Set(Some(previous_sha256));
+            active.previous_der = Set(Some(previous_der));
+            active.rotated_at = Set(Some(now));
+            active.update(db).await?
+        }
+        None => {
+            peer_certificate::ActiveModel {
+                origin: Set(origin.to_string()),
+                sha256: Set(sha256),
+                der: Set(hex::encode(&der)),
+                pinned_at: Set(now),
+                previous_sha256: Set(None),
+                previous_der: Set(None),
+                rotated_at: Set(None),
+            }
+            .insert(db)
+            .await?
+        }
+    };
+    cache(origin, Pin::from_row(&saved));
+    Ok(saved)
+}
+
+/// Pin the certificate of the HTTPS peer at `url` if it is the first
+/// contact and no CA vouches for it. Returns the new pin, `None` when
+/// nothing was pinned (plain HTTP, already pinned, CA-signed or offline).
+pub async fn pin_on_first_use(
+    db: &DatabaseConnection,
+    url: &str,
+) -> Option<peer_certificate::Model> {
+    let origin = origin_of(url)?;
+    if !pinned_ders(url).is_empty() || ca_trusted(url).await {
+        return None;
+    }
+    let der = presented_certificate(url).await.ok()?;
+    match save(db, &origin, der, None).await {
+        Ok(pin) => {
+            tracing::info!("Pinned certificate {} for {}", pin.sha256, origin);
+            Some(pin)
+        }
+        Err(e) => {
+            tracing::warn!("Could not pin the certificate of {}: {}", origin, e);
+            None
+        }
+    }
+}
+
+/// Compare what the peer at `url` presents now with its pin.
+pub async fn probe(db: &DatabaseConnection, url: &str) -> Result<Probe, PinError> {
+    let origin = origin_of(url).ok_or(PinError::NotHttps)?;
+    let presented = fingerprint(&presented_certificate(url).await?);
+    let pinned = peer_certificate::Entity::find_by_id(origin.clone())
+        .one(db)
+        .await?
+        .map(|p| p.sha256);
+    Ok(Probe {
+        matches: pinned.as_deref() == Some(presented.as_str()),
+        origin,
+        presented_sha256: presented,
+        pinned_sha256: pinned,
+    })
+}
+
+/// Replace the pin of the peer at `url` with the certificate it presents
+/// now, provided its fingerprint is `expected_sha256` (checked with the peer's
+/// owner). The replaced fingerprint is kept as `previous_sha256`.
+pub async fn rotate(
+    db: &DatabaseConnection,
+    url: &str,
+    expected_sha256: &str,
+) -> Result<peer_certificate::Model, PinError> {
+    let origin = origin_of(url).ok_or(PinError::NotHttps)?;
+    let der = presented_certificate(url).await?;
+    let presented = fingerprint(&der);
+    if !presented.eq_ignore_ascii_case(expected_sha256.trim()) {
+        return Err(PinError::Mismatch { presented });
+    }
+    let previous = peer_certificate::Entity::find_by_id(origin.clone())
+        .one(db)
+        .await?;
+    Ok(save(db, &origin, der, previous).await?)
+}
+
+/// Forget the pin of the peer at `url`: the next connect pins again.
+pub async fn unpin(db: &DatabaseConnection, url: &str) -> Result<(), PinError> {
+    let origin = origin_of(url).ok_or(PinError::NotHttps)?;
+    let res = peer_certificate::Entity::delete_by_id(origin.clone())
+        .exec(db)
+        .await?;
+    if res.rows_affected == 0 {
+        return Err(PinError::NotPinned);
+    }
+    cache(&origin, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pins_are_kept_by_origin_and_rotation_keeps_the_previous_one() {
+        assert_eq!(
+            origin_of("https://Library.local/api/config").as_deref(),
+            Some("https://library.local:443")
+        );
+        assert_eq!(
+            origin_of("https://192.168.1.20:8443").as_deref(),
+            Some("https://192.168.1.20:8443")
+        );
+        assert_eq!(origin_of("http://192.168.1.20:8000"), None);
+
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let url = "https://10.9.8.7:8443";
+        let first = save(&db, &origin_of(url).unwrap(), b"first".to_vec(), None)
+            .await
+            .unwrap();
+        assert_eq!(first.sha256, fingerprint(b"first"));
+        assert_eq!(
+            pinned_ders(&format!("{url}/api/books")),
+            vec![b"first".to_vec()]
+        );
+
+        let rotated = save(&db, &first.origin.clone(), b"second".to_vec(), Some(first))
+            .await
+            .unwrap();
+        assert_eq!(rotated.previous_sha256, Some(fingerprint(b"first")));
+        assert!(rotated.rotated_at.is_some());
+        // The replaced certificate is still trusted during the overlap.
+        assert_eq!(
+            pinned_ders(url),
+            vec![b"second".to_vec(), b"first".to_vec()]
+        );
+
+        unpin(&db, url).await.unwrap();
+        assert!(pinned_ders(url).is_empty());
+        assert!(matches!(unpin(&db, url).await, Err(PinError::NotPinned)));
+        assert!(list(&db).await.unwrap().is_empty());
+    }
+
+    fn acceptor(cert: &[u8], key: &[u8]) -> native_tls::TlsAcceptor {
+        let identity = native_tls::Identity::from_pkcs8(cert, key).unwrap();
+        native_tls::TlsAcceptor::new(identity).unwrap()
+    }
+
+    /// A local HTTPS peer answering 200 to everything, with the certificate
+    /// currently in `presented`.
+    fn serve(presented: Arc<Mutex<native_tls::TlsAcceptor>>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "https://127.0.0.1:{}",
+            listener.local_addr().unwrap().port()
+        );
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let acceptor = presented.lock().unwrap().clone();
+                std::thread::spawn(move || {
+                    let Ok(mut tls) = acceptor.accept(stream) else {
+                        return;
+                    };
+                    let _ = tls.read(&mut [0u8; 4096]);
+                    let _ = tls.write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                    );
+                    let _ = tls.shutdown();
+                });
+            }
+        });
+        url
+    }
+
+    async fn reaches(url: &str) -> bool {
+        crate::api::peer::get_safe_client_for(url)
+            .get(format!("{url}/api/health"))
+            .send()
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn only_pinned_certificates_complete_the_handshake() {
+        let first = acceptor(
+            include_bytes!("../../tests/fixtures/tls/peer_first.crt"),
+            include_bytes!("../../tests/fixtures/tls/peer_first.key"),
+        );
+        let second = acceptor(
+            include_bytes!("../../tests/fixtures/tls/peer_second.crt"),
+            include_bytes!("../../tests/fixtures/tls/peer_second.key"),
+        );
+        let presented = Arc::new(Mutex::new(first.clone()));
+        let url = serve(presented.clone());
+
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+
+        // No CA vouches for it: trusted once pinned only.
+        assert!(!reaches(&url).await);
+        let pin = pin_on_first_use(&db, &url).await.expect("pinned");
+        assert!(reaches(&url).await);
+
+        // Another certificate at the same origin fails the handshake.
+        *presented.lock().unwrap() = second.clone();
+        assert!(!reaches(&url).await);
+        let probed = probe(&db, &url).await.unwrap();
+        assert!(!probed.matches);
+
+        // Rotated: the new certificate is trusted, and the replaced one too
+        // while the overlap lasts.
+        let rotated = rotate(&db, &url, &probed.presented_sha256).await.unwrap();
+        assert_eq!(
+            rotated.previous_sha256.as_deref(),
+            Some(pin.sha256.as_str())
+        );
+        assert!(reaches(&url).await);
+        *presented.lock().unwrap() = first;
+        assert!(reaches(&url).await);
+
+        // Past the overlap, only the new one is.
+        let mut expired: peer_certificate::ActiveModel = rotated.into();
+        expired.rotated_at = Set(Some(
+            (Utc::now() - Duration::days(ROTATION_OVERLAP_DAYS + 1)).to_rfc3339(),
+        ));
+        let expired = expired.update(&db).await.unwrap();
+        cache(&expired.origin, Pin::from_row(&expired));
+        assert!(!reaches(&url).await);
+        *presented.lock().unwrap() = second;
+        assert!(reaches(&url).await);
+
+        unpin(&db, &url).await.unwrap();
+    }
+}
@@ -292,6 +292,7 @@ mod tests {
             name: Set(name.to_owned()),
             parent_id: Set(None),
             path: Set(name.to_owned()),
+            private: Set(false),
             created_at: Set(now.clone()),
             updated_at: Set(now),
         }
@@ -0,0 +1,182 @@
+//! Import sources of collections, for incremental re-imports.
+//!
+//! Importing a file into a collection records its format, its column layout
+//! (the header line of a CSV export) and, for each row, the book it created
+//! or matched, keyed by the row's natural key (`import::row_key`). Uploading
+//! an updated version of the file then adds its new rows, updates the books
+//! of rows still there and reports the rows that disappeared, which can be
+//! taken out of the collection. A file whose layout differs from the
+//! source's is refused: its columns would not mean the same thing.
+
+use std::collections::HashSet;
+
+use sea_orm::*;
+use sha2::{Digest, Sha256};
+
+use crate::import::{self, SourceFormat};
+use crate::models::{collection_import_row, collection_import_source};
+
+/// What identifies an uploaded file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub format: SourceFormat,
+    /// SHA-256 of the column layout
+    pub layout: String,
+    pub file_sha256: String,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// The fingerprint of `content`, `None` when its format is unknown.
+pub fn fingerprint(content: &[u8]) -> Option<Fingerprint> {
+    let format = import::detect_format(content)?;
+    Some(Fingerprint {
+        format,
+        layout: sha256(import::layout_signature(content, format).as_bytes()),
+        file_sha256: sha256(content),
+    })
+}
+
+/// Whether `file` can update the collection whose source is `source`.
+pub fn same_layout(source: &collection_import_source::Model, file: &Fingerprint) -> bool {
+    source.format == file.format.as_str() && source.layout == file.layout
+}
+
+pub async fn get(
+    db: &DatabaseConnection,
+    collection_id: &str,
+) -> Result<Option<collection_import_source::Model>, DbErr> {
+    collection_import_source::Entity::find_by_id(collection_id.to_string())
+        .one(db)
+        .await
+}
+
+/// The rows the source knows about.
+pub async fn rows(
+    db: &DatabaseConnection,
+    collection_id: &str,
+) -> Result<Vec<collection_import_row::Model>, DbErr> {
+    collection_import_row::Entity::find()
+        .filter(collection_import_row::Column::CollectionId.eq(collection_id))
+        .all(db)
+        .await
+}
+
+/// The rows the source knows about whose key is not in `keys`.
+pub async fn missing_rows(
+    db: &DatabaseConnection,
+    collection_id: &str,
+    keys: &HashSet<String>,
+) -> Result<Vec<collection_import_row::Model>, DbErr> {
+    Ok(rows(db, collection_id)
+        .await?
+        .into_iter()
+        .filter(|r| !keys.contains(&r.row_key))
+        .collect())
+}
+
+/// Record `file` as the collection's source, with `rows` as `(row key,
+/// book id)`: they replace the rows known so far, except those in `keep`
+/// (rows missing from the file but left in the collection).
+pub async fn remember(
+    db: &DatabaseConnection,
+    collection_id: &str,
+    file: &Fingerprint,
+    owned: bool,
+    rows: Vec<(String, String)>,
+    keep: Vec<collection_import_row::Model>,
+) -> Result<collection_import_source::Model, DbErr> {
+    let txn = db.begin().await?;
+    collection_import_source::Entity::delete_by_id(collection_id.to_string())
+        .exec(&txn)
+        .await?;
+    let source = collection_import_source::ActiveModel {
+        collection_id: Set(collection_id.to_string()),
+        format: Set(file.format.as_str().to_string()),
+        layout: Set(file.layout.clone()),
+        file_sha256: Set(file.file_sha256.clone()),
+        owned: Set(owned),
+        row_count: Set(rows.len() as i32),
+        imported_at: Set(chrono::Utc::now().to_rfc3339()),
+    }
+    .insert(&txn)
+    .await?;
+
+    // Rows cascade with the source: insert them afresh. The same key twice
+    // in a file keeps its first book.
+    let mut seen = HashSet::new();
+    let models: Vec<collection_import_row::ActiveModel> = rows
+        .into_iter()
+        .chain(keep.into_iter().map(|r| (r.row_key, r.book_id)))
+        .filter(|(key, _)| seen.insert(key.clone()))
+        .map(|(row_key, book_id)| collection_import_row::ActiveModel {
+            collection_id: Set(collection_id.to_string()),
+            row_key: Set(row_key),
+            book_id: Set(book_id),
+        })
+        .collect();
+    for chunk in models.chunks(100) {
+        collection_import_row::Entity::insert_many(chunk.to_vec())
+            .exec_without_returning(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sources_remember_rows_and_refuse_other_layouts() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        crate::models::collection::ActiveModel {
+            id: Set("c1".to_string()),
+            name: Set("Club".to_string()),
+            source: Set("manual".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let v1 = b"Titre;Auteur;EAN;Editeur;Date de publication\nDune;Frank Herbert;9780441013593;Ace;01/01/1965\n";
+        let v2 = b"Titre;Auteur;EAN;Editeur;Date de publication\nDune;Frank Herbert;9780441013593;Pocket;01/01/1965\n";
+        let goodreads = b"Title,Author,ISBN,ISBN13,Publisher,Year Published\n";
+        let first = fingerprint(v1).unwrap();
+        let second = fingerprint(v2).unwrap();
+        assert_eq!(first.format, SourceFormat::Babelio);
+        assert_ne!(first.file_sha256, second.file_sha256);
+
+        let key = import::row_key(&import::parse_import_file(v1).unwrap()[0]);
+        let source = remember(
+            &db,
+            "c1",
+            &first,
+            true,
+            vec![(key.clone(), "b1".into()), ("isbn:old".into(), "b0".into())],
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        assert!(same_layout(&source, &second));
+        assert!(!same_layout(&source, &fingerprint(goodreads).unwrap()));
+
+        let keys: HashSet<String> = [key.clone()].into();
+        let missing = missing_rows(&db, "c1", &keys).await.unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].book_id, "b0");
+
+        // Kept rows survive the next upload.
+        remember(&db, "c1", &second, true, vec![(key, "b1".into())], missing)
+            .await
+            .unwrap();
+        assert_eq!(rows(&db, "c1").await.unwrap().len(), 2);
+    }
+}
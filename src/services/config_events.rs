@@ -0,0 +1,84 @@
+//! Process-wide event bus for library configuration changes.
+//!
+//! Emitted by `config_history::record` each time a new version of
+//! `library_config` is saved, and consumed by the services that depend on
+//! it (`config_history::spawn`, `mdns::spawn_announcer`).
+//!
+//! Follows the same design as `catalog_events.rs`:
+//!   - Singleton broadcast bus (lock-free emit).
+//!   - Slow subscribers lag without blocking emitters.
+
+use std::sync::OnceLock;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// Maximum buffered events per subscriber. Lagging subscribers skip ahead
+/// rather than blocking the emitter (same policy as CatalogEventBus).
+const CHANNEL_CAPACITY: usize = 16;
+
+/// A new version of the library configuration was saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigChangedEvent {
+    /// `library_config_history.version` of the saved configuration
+    pub version: i64,
+    /// Fields that differ from the previous version (`name`, `tags`, ...)
+    pub changed_fields: Vec<String>,
+    /// `api`, `app`, `device_sync`, ...
+    pub changed_by: String,
+    /// The library name as of this version
+    pub name: String,
+}
+
+/// Process-wide configuration-change event bus.
+pub struct ConfigEventBus {
+    tx: Sender<ConfigChangedEvent>,
+}
+
+impl ConfigEventBus {
+    pub(crate) fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx }
+    }
+
+    /// Emit an event. Non-blocking, never panics. Silently dropped when no
+    /// subscribers are active.
+    pub fn emit(&self, event: ConfigChangedEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Subscribe a fresh receiver. Drop the receiver to unsubscribe.
+    pub fn subscribe(&self) -> Receiver<ConfigChangedEvent> {
+        self.tx.subscribe()
+    }
+}
+
+/// Get the process-wide configuration event bus. Lazily initialised on first call.
+pub fn bus() -> &'static ConfigEventBus {
+    static INSTANCE: OnceLock<ConfigEventBus> = OnceLock::new();
+    INSTANCE.get_or_init(ConfigEventBus::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_receive_the_changed_fields() {
+        let bus = ConfigEventBus::new();
+        bus.emit(ConfigChangedEvent {
+            version: 1,
+            changed_fields: vec![],
+            changed_by: "api".to_string(),
+            name: "Before".to_string(),
+        });
+        let mut rx = bus.subscribe();
+        bus.emit(ConfigChangedEvent {
+            version: 2,
+            changed_fields: vec!["name".to_string()],
+            changed_by: "api".to_string(),
+            name: "After".to_string(),
+        });
+        let received = rx.recv().await.unwrap();
+        assert_eq!(received.version, 2);
+        assert_eq!(received.changed_fields, ["name"]);
+    }
+}
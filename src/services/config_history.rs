@@ -0,0 +1,346 @@
+//! Versions of the library configuration, and rollback to one of them.
+//!
+//! Saving `library_config` used to overwrite it with no trace. Each save
+//! that changes something now appends the whole row, as JSON, to
+//! `library_config_history` with the fields that changed, who saved it
+//! (`api`, `app`, `device_sync`, `profile`) and when. Versions are never
+//! updated or deleted: rolling back writes the old values as a new version
+//! that names the one it restored, so a rollback can itself be undone.
+//!
+//! Each new version is announced on `config_events`. mDNS follows the name
+//! (`mdns::spawn_announcer`), and `spawn` nudges peers to refresh our profile
+//! when the name or location changed and our catalogue when borrowed books
+//! are shown or hidden. Changes replicated from a paired device leave nudging
+//! peers to the device where they were made.
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::infrastructure::AppState;
+use crate::models::library_config::{self, LibraryConfig};
+use crate::services::config_events::{self, ConfigChangedEvent};
+
+/// Versions `GET /api/library/config/history` returns by default.
+pub const DEFAULT_HISTORY_LIMIT: u64 = 50;
+
+/// The configurable fields that differ between two versions.
+fn changed_fields(before: &library_config::Model, after: &library_config::Model) -> Vec<String> {
+    let mut changed = Vec::new();
+    let mut check = |field: &str, differs: bool| {
+        if differs {
+            changed.push(field.to_string());
+        }
+    };
+    check("name", before.name != after.name);
+    check("description", before.description != after.description);
+    check("tags", before.tags != after.tags);
+    check("latitude", before.latitude != after.latitude);
+    check("longitude", before.longitude != after.longitude);
+    check(
+        "share_location",
+        before.share_location != after.share_location,
+    );
+    check(
+        "show_borrowed_books",
+        before.show_borrowed_books != after.show_borrowed_books,
+    );
+    check("currency", before.currency != after.currency);
+    check("timezone", before.timezone != after.timezone);
+    changed
+}
+
+async fn insert_version<C: ConnectionTrait>(
+    db: &C,
+    config: &library_config::Model,
+    changed_fields: &[String],
+    changed_by: &str,
+    restored_version: Option<i64>,
+) -> Result<i64, DbErr> {
+    let config_json = serde_json::to_string(config).map_err(|e| DbErr::Custom(e.to_string()))?;
+    let fields_json = serde_json::to_string(changed_fields).unwrap_or_else(|_| "[]".to_string());
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "INSERT INTO library_config_history \
+             (config, changed_fields, changed_by, restored_version, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+            [
+                config_json.into(),
+                fields_json.into(),
+                changed_by.into(),
+                restored_version.into(),
+                chrono::Utc::now().to_rfc3339().into(),
+            ],
+        ))
+        .await?;
+    Ok(result.last_insert_id() as i64)
+}
+
+/// Record `after`, just saved over `before`, as a new version and announce
+/// it. Nothing is recorded when no field changed. The first time, `before`
+/// is recorded first so there is a version to go back to.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    before: Option<&library_config::Model>,
+    after: &library_config::Model,
+    changed_by: &str,
+) -> Result<Option<i64>, DbErr> {
+    record_version(db, before, after, changed_by, None).await
+}
+
+async fn record_version<C: ConnectionTrait>(
+    db: &C,
+    before: Option<&library_config::Model>,
+    after: &library_config::Model,
+    changed_by: &str,
+    restored_version: Option<i64>,
+) -> Result<Option<i64>, DbErr> {
+    let changed = match before {
+        Some(before) => changed_fields(before, after),
+        None => vec!["name".to_string()],
+    };
+    if changed.is_empty() {
+        return Ok(None);
+    }
+    if let Some(before) = before {
+        let recorded = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT 1 FROM library_config_history LIMIT 1",
+            ))
+            .await?;
+        if recorded.is_none() {
+            insert_version(db, before, &[], "baseline", None).await?;
+        }
+    }
+    let version = insert_version(db, after, &changed, changed_by, restored_version).await?;
+    config_events::bus().emit(ConfigChangedEvent {
+        version,
+        changed_fields: changed,
+        changed_by: changed_by.to_string(),
+        name: after.name.clone(),
+    });
+    Ok(Some(version))
+}
+
+/// One saved version of the configuration.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ConfigVersion {
+    pub version: i64,
+    /// `api`, `app`, `device_sync`, `profile`, or `baseline` for the
+    /// configuration found before the first recorded change
+    pub changed_by: String,
+    /// Fields that differ from the version before
+    pub changed_fields: Vec<String>,
+    /// The version a rollback brought back
+    pub restored_version: Option<i64>,
+    /// RFC 3339
+    pub created_at: String,
+    pub config: LibraryConfig,
+}
+
+fn version_from_row(row: &QueryResult) -> Result<(ConfigVersion, library_config::Model), DbErr> {
+    let config_json: String = row.try_get("", "config")?;
+    let config: library_config::Model =
+        serde_json::from_str(&config_json).map_err(|e| DbErr::Custom(e.to_string()))?;
+    let fields_json: String = row.try_get("", "changed_fields")?;
+    Ok((
+        ConfigVersion {
+            version: row.try_get("", "version")?,
+            changed_by: row.try_get("", "changed_by")?,
+            changed_fields: serde_json::from_str(&fields_json).unwrap_or_default(),
+            restored_version: row.try_get("", "restored_version")?,
+            created_at: row.try_get("", "created_at")?,
+            config: LibraryConfig::from(config.clone()),
+        },
+        config,
+    ))
+}
+
+/// The last `limit` versions, newest first.
+pub async fn history(db: &DatabaseConnection, limit: u64) -> Result<Vec<ConfigVersion>, DbErr> {
+    let rows = db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT * FROM library_config_history ORDER BY version DESC LIMIT ?",
+            [(limit.min(i64::MAX as u64) as i64).into()],
+        ))
+        .await?;
+    rows.iter()
+        .map(|row| version_from_row(row).map(|(version, _)| version))
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum RollbackError {
+    /// No such version
+    NotFound(i64),
+    /// There is no configuration to roll back
+    NoConfig,
+    Db(DbErr),
+}
+
+impl std::fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(v) => write!(f, "no configuration version {v}"),
+            Self::NoConfig => write!(f, "the library has no configuration yet"),
+            Self::Db(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RollbackError {}
+
+impl From<DbErr> for RollbackError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// Put the configuration back as it was in `version`, recorded as a new
+/// version. Returns the configuration now saved.
+pub async fn rollback(
+    db: &DatabaseConnection,
+    version: i64,
+    changed_by: &str,
+) -> Result<library_config::Model, RollbackError> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT * FROM library_config_history WHERE version = ?",
+            [version.into()],
+        ))
+        .await?
+        .ok_or(RollbackError::NotFound(version))?;
+    let (_, old) = version_from_row(&row)?;
+    let current = library_config::Entity::find()
+        .one(db)
+        .await?
+        .ok_or(RollbackError::NoConfig)?;
+
+    let mut active: library_config::ActiveModel = current.clone().into();
+    active.name = Set(old.name);
+    active.description = Set(old.description);
+    active.tags = Set(old.tags);
+    active.latitude = Set(old.latitude);
+    active.longitude = Set(old.longitude);
+    active.share_location = Set(old.share_location);
+    active.show_borrowed_books = Set(old.show_borrowed_books);
+    active.currency = Set(old.currency);
+    active.timezone = Set(old.timezone);
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    let saved = active.update(db).await?;
+
+    let _ = crate::sync::log_operation(db, "library_config", &saved.id.to_string(), "UPDATE", None)
+        .await;
+    record_version(db, Some(&current), &saved, changed_by, Some(version)).await?;
+    Ok(saved)
+}
+
+/// Run the reactions to configuration changes for as long as the process
+/// lives.
+pub fn spawn(state: AppState) {
+    let mut rx = config_events::bus().subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("config_history: {n} configuration changes missed");
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            let changed = |field: &str| event.changed_fields.iter().any(|f| f == field);
+
+            if event.changed_by == "device_sync" {
+                continue;
+            }
+            let mut profile_fields = Vec::new();
+            if changed("name") {
+                profile_fields.push("library_name".to_string());
+            }
+            if changed("share_location") || changed("latitude") || changed("longitude") {
+                profile_fields.push("location".to_string());
+            }
+            if !profile_fields.is_empty() {
+                crate::services::profile_notification::schedule_profile_changed_notification(
+                    state.clone(),
+                    profile_fields,
+                );
+            }
+            if changed("show_borrowed_books") {
+                crate::services::catalog_notification::schedule_catalog_changed_notification(
+                    state.clone(),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn rename(db: &DatabaseConnection, name: &str) -> library_config::Model {
+        let before = library_config::Entity::find()
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut active: library_config::ActiveModel = before.clone().into();
+        active.name = Set(name.to_string());
+        let after = active.update(db).await.unwrap();
+        record(db, Some(&before), &after, "api").await.unwrap();
+        after
+    }
+
+    #[tokio::test]
+    async fn a_rollback_restores_an_old_version_as_a_new_one() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let original = library_config::Entity::find()
+            .one(&db)
+            .await
+            .unwrap()
+            .expect("seeded configuration")
+            .name;
+
+        rename(&db, "Médiathèque").await;
+        let same = library_config::Entity::find()
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record(&db, Some(&same), &same, "api").await.unwrap(), None);
+        rename(&db, "Bibliothèque de quartier").await;
+
+        let versions = history(&db, 10).await.unwrap();
+        let summary: Vec<_> = versions
+            .iter()
+            .map(|v| (v.version, v.changed_by.as_str(), v.config.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (3, "api", "Bibliothèque de quartier"),
+                (2, "api", "Médiathèque"),
+                (1, "baseline", original.as_str()),
+            ]
+        );
+        assert_eq!(versions[0].changed_fields, ["name"]);
+
+        let mut events = config_events::bus().subscribe();
+        let restored = rollback(&db, 1, "api").await.unwrap();
+        assert_eq!(restored.name, original);
+        let latest = &history(&db, 1).await.unwrap()[0];
+        assert_eq!((latest.version, latest.restored_version), (4, Some(1)));
+        let event = events.recv().await.unwrap();
+        assert_eq!((event.version, event.name), (4, original));
+
+        assert!(matches!(
+            rollback(&db, 99, "api").await,
+            Err(RollbackError::NotFound(99))
+        ));
+    }
+}
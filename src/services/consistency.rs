@@ -0,0 +1,303 @@
+//! Consistency checker: invariants the schema no longer enforces.
+//!
+//! Since the UUID-PK rebuild (ADR-044) the catalogue tables carry no foreign
+//! keys, and the peer tables' `ON DELETE CASCADE` did not fire while a
+//! connection had `foreign_keys` off (migration 079). Each check below counts
+//! the rows breaking one invariant and, on request, repairs them:
+//!
+//! - an owned book has a copy: one is added in the first library, as
+//!   `create_book` does;
+//! - a loan's copy exists: the loan goes, as `delete_copy_cascade` would
+//!   have removed it;
+//! - a cached peer book's peer exists (`peer_id = 0` is the directory cache
+//!   and is left alone): the row goes;
+//! - an incoming or outgoing request's peer exists: the request goes.
+//!
+//! Books and loans arrive from paired devices in any order, so while sync
+//! operations are pending their checks are reported but not repaired.
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{copy, library, operation_log};
+
+/// Rows listed per check.
+const SAMPLE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repair {
+    AddCopy,
+    Delete,
+}
+
+struct Check {
+    name: &'static str,
+    description: &'static str,
+    /// Table, its key column and the condition selecting the broken rows.
+    /// Catalogue tables are keyed by `uuid` since migration 078.
+    table: &'static str,
+    key: &'static str,
+    condition: &'static str,
+    /// Catalogue rows that sync may still complete
+    synced: bool,
+    repair: Repair,
+}
+
+const CHECKS: [Check; 5] = [
+    Check {
+        name: "owned_books_without_copies",
+        description: "Owned books with no copy",
+        table: "books",
+        key: "uuid",
+        condition: "owned = 1 AND uuid NOT IN (SELECT book_id FROM copies)",
+        synced: true,
+        repair: Repair::AddCopy,
+    },
+    Check {
+        name: "loans_on_missing_copies",
+        description: "Loans whose copy was deleted",
+        table: "loans",
+        key: "uuid",
+        condition: "copy_id NOT IN (SELECT uuid FROM copies)",
+        synced: true,
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "peer_books_of_missing_peers",
+        description: "Cached peer books of a deleted peer",
+        table: "peer_books",
+        key: "id",
+        condition: "peer_id <> 0 AND peer_id NOT IN (SELECT id FROM peers)",
+        synced: false,
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "incoming_requests_of_missing_peers",
+        description: "Incoming loan requests from a deleted peer",
+        table: "p2p_requests",
+        key: "id",
+        condition: "from_peer_id NOT IN (SELECT id FROM peers)",
+        synced: false,
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "outgoing_requests_to_missing_peers",
+        description: "Outgoing loan requests to a deleted peer",
+        table: "p2p_outgoing_requests",
+        key: "id",
+        condition: "to_peer_id NOT IN (SELECT id FROM peers)",
+        synced: false,
+        repair: Repair::Delete,
+    },
+];
+
+/// One broken invariant.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ConsistencyIssue {
+    pub check: String,
+    pub description: String,
+    /// Rows found breaking it
+    pub count: u64,
+    /// Ids of the first rows found
+    pub sample: Vec<String>,
+    /// Rows repaired, when a repair was asked
+    pub repaired: u64,
+    /// Why a repair was not attempted
+    pub skipped: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ConsistencyReport {
+    /// RFC 3339
+    pub checked_at: String,
+    pub repair: bool,
+    /// Broken rows over all checks, before any repair
+    pub total: u64,
+    /// Every check, broken or not
+    pub issues: Vec<ConsistencyIssue>,
+}
+
+async fn ids(db: &DatabaseConnection, check: &Check) -> Result<Vec<String>, DbErr> {
+    let sql = format!(
+        "SELECT CAST({} AS TEXT) AS id FROM {} WHERE {}",
+        check.key, check.table, check.condition
+    );
+    db.query_all(Statement::from_string(db.get_database_backend(), sql))
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String>("", "id"))
+        .collect()
+}
+
+/// A copy for each book, in the first library, logged for sync.
+async fn add_copies(db: &DatabaseConnection, book_ids: &[String]) -> Result<u64, DbErr> {
+    let Some(library) = library::Entity::find().one(db).await? else {
+        return Ok(0);
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut added = 0;
+    for book_id in book_ids {
+        let saved = copy::ActiveModel {
+            book_id: Set(book_id.clone()),
+            library_id: Set(library.id),
+            status: Set("available".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        let _ = crate::sync::log_operation(
+            db,
+            "copy",
+            &saved.id,
+            "INSERT",
+            Some(serde_json::json!({ "book_id": book_id })),
+        )
+        .await;
+        added += 1;
+    }
+    Ok(added)
+}
+
+/// Run every check, repairing what can be when `repair` is set.
+pub async fn check(db: &DatabaseConnection, repair: bool) -> Result<ConsistencyReport, DbErr> {
+    let pending = operation_log::Entity::find()
+        .filter(operation_log::Column::Status.is_in(["pending", "pending_review"]))
+        .count(db)
+        .await?;
+
+    let mut issues = Vec::with_capacity(CHECKS.len());
+    for check in &CHECKS {
+        let found = ids(db, check).await?;
+        let mut issue = ConsistencyIssue {
+            check: check.name.to_string(),
+            description: check.description.to_string(),
+            count: found.len() as u64,
+            sample: found.iter().take(SAMPLE).cloned().collect(),
+            repaired: 0,
+            skipped: None,
+        };
+        if repair && !found.is_empty() {
+            if check.synced && pending > 0 {
+                issue.skipped = Some(format!("{pending} sync operation(s) pending"));
+            } else {
+                issue.repaired = match check.repair {
+                    Repair::AddCopy => add_copies(db, &found).await?,
+                    Repair::Delete => db
+                        .execute(Statement::from_string(
+                            db.get_database_backend(),
+                            format!("DELETE FROM {} WHERE {}", check.table, check.condition),
+                        ))
+                        .await?
+                        .rows_affected(),
+                };
+                if issue.repaired > 0 {
+                    tracing::info!("consistency: repaired {} {}", issue.repaired, check.name);
+                }
+            }
+        }
+        issues.push(issue);
+    }
+
+    Ok(ConsistencyReport {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        repair,
+        total: issues.iter().map(|i| i.count).sum(),
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{book, loan};
+
+    async fn exec(db: &DatabaseConnection, sql: &str) {
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            sql.to_owned(),
+        ))
+        .await
+        .unwrap();
+    }
+
+    fn issue<'a>(report: &'a ConsistencyReport, name: &str) -> &'a ConsistencyIssue {
+        report.issues.iter().find(|i| i.check == name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn finds_then_repairs_broken_invariants() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        exec(&db, "PRAGMA foreign_keys = OFF").await;
+        let now = chrono::Utc::now().to_rfc3339();
+        library::ActiveModel {
+            name: Set("Main".to_string()),
+            owner_id: Set(1),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let owned = book::ActiveModel {
+            title: Set("Copyless".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        loan::ActiveModel {
+            copy_id: Set("gone".to_string()),
+            contact_id: Set("contact-1".to_string()),
+            library_id: Set(1),
+            loan_date: Set(now.clone()),
+            due_date: Set(now.clone()),
+            status: Set("active".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        exec(
+            &db,
+            &format!(
+                "INSERT INTO peer_books (peer_id, remote_book_id, title, synced_at) \
+                 VALUES (42, 'r1', 'Stale', '{now}'), (0, 'r2', 'Directory', '{now}')"
+            ),
+        )
+        .await;
+
+        let found = check(&db, false).await.unwrap();
+        assert_eq!(
+            issue(&found, "owned_books_without_copies").sample,
+            std::slice::from_ref(&owned.id)
+        );
+        assert_eq!(issue(&found, "loans_on_missing_copies").count, 1);
+        assert_eq!(issue(&found, "peer_books_of_missing_peers").count, 1);
+        assert_eq!(found.total, 3);
+
+        // Nothing waits in the operation log: repairs may run.
+        exec(&db, "UPDATE operation_log SET status = 'applied'").await;
+        let repaired = check(&db, true).await.unwrap();
+        assert_eq!(issue(&repaired, "owned_books_without_copies").repaired, 1);
+        assert_eq!(issue(&repaired, "loans_on_missing_copies").repaired, 1);
+        assert_eq!(issue(&repaired, "peer_books_of_missing_peers").repaired, 1);
+        assert_eq!(check(&db, false).await.unwrap().total, 0);
+        assert_eq!(
+            copy::Entity::find()
+                .filter(copy::Column::BookId.eq(owned.id))
+                .count(&db)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
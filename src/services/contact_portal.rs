@@ -0,0 +1,295 @@
+//! Contact portal: a patron's own view of their borrowings.
+//!
+//! The owner issues a token to a contact (a family member, a patron of an
+//! association library) and passes it on, e.g. as a link. With it, the
+//! contact reaches `/api/portal/*`: their open loans and due dates, a renewal
+//! request, and the shared catalogue. Nothing else is reachable with it, and
+//! no other contact's data ever is.
+//!
+//! A contact has at most one token; issuing a new one replaces it. As with
+//! kiosk tokens, only the SHA-256 of a token is stored.
+
+use chrono::Utc;
+use sea_orm::*;
+
+use crate::domain::{CreateNotification, LendingTerms, NotificationEventType};
+use crate::models::{book, contact, contact_token, copy, loan};
+use crate::services::kiosk::{generate_token, hash_token};
+
+/// Why a renewal request was refused.
+#[derive(Debug)]
+pub enum PortalError {
+    Db(DbErr),
+    /// No such loan among the contact's own.
+    LoanNotFound,
+    /// The loan is closed already.
+    Returned,
+    /// The lender's terms for this copy rule out renewals.
+    NotRenewable,
+}
+
+impl std::fmt::Display for PortalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::LoanNotFound => write!(f, "loan not found"),
+            Self::Returned => write!(f, "this loan is already returned"),
+            Self::NotRenewable => write!(f, "this loan cannot be renewed"),
+        }
+    }
+}
+
+impl std::error::Error for PortalError {}
+
+impl From<DbErr> for PortalError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// Issue a portal token to a contact, replacing any previous one. `None`
+/// when there is no such contact. The returned token is the only copy of it.
+pub async fn issue_token(
+    db: &DatabaseConnection,
+    contact_id: &str,
+) -> Result<Option<(contact_token::Model, String)>, DbErr> {
+    if contact::Entity::find_by_id(contact_id.to_string())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+
+    revoke_token(db, contact_id).await?;
+    let token = generate_token();
+    let model = contact_token::ActiveModel {
+        contact_id: Set(contact_id.to_string()),
+        token_hash: Set(hash_token(&token)),
+        created_at: Set(Utc::now().to_rfc3339()),
+        last_used_at: Set(None),
+    }
+    .insert(db)
+    .await?;
+    Ok(Some((model, token)))
+}
+
+/// Revoke a contact's portal token. Returns false when they had none.
+pub async fn revoke_token(db: &DatabaseConnection, contact_id: &str) -> Result<bool, DbErr> {
+    let res = contact_token::Entity::delete_by_id(contact_id.to_string())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// The active contact holding `token`, with the token's last use recorded.
+pub async fn authenticate(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<Option<contact::Model>, DbErr> {
+    if token.is_empty() {
+        return Ok(None);
+    }
+    let Some(found) = contact_token::Entity::find()
+        .filter(contact_token::Column::TokenHash.eq(hash_token(token)))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let Some(holder) = contact::Entity::find_by_id(found.contact_id.clone())
+        .one(db)
+        .await?
+        .filter(|c| c.is_active)
+    else {
+        return Ok(None);
+    };
+    let mut active: contact_token::ActiveModel = found.into();
+    active.last_used_at = Set(Some(Utc::now().to_rfc3339()));
+    active.update(db).await?;
+    Ok(Some(holder))
+}
+
+/// A contact's open loans with the borrowed book, soonest due first.
+pub async fn open_loans(
+    db: &DatabaseConnection,
+    contact_id: &str,
+) -> Result<Vec<(loan::Model, Option<book::Model>)>, DbErr> {
+    let loans = loan::Entity::find()
+        .filter(loan::Column::ContactId.eq(contact_id))
+        .filter(loan::Column::Status.ne("returned"))
+        .order_by_asc(loan::Column::DueDate)
+        .all(db)
+        .await?;
+
+    let mut result = Vec::with_capacity(loans.len());
+    for l in loans {
+        let book = match copy::Entity::find_by_id(l.copy_id.clone()).one(db).await? {
+            Some(c) => book::Entity::find_by_id(c.book_id).one(db).await?,
+            None => None,
+        };
+        result.push((l, book));
+    }
+    Ok(result)
+}
+
+/// Ask the owner to renew one of the contact's open loans. The request is a
+/// notification in the owner's activity feed, sent once per loan; the owner
+/// grants it with `PUT /api/loans/:id/renew`.
+pub async fn request_renewal(
+    db: &DatabaseConnection,
+    holder: &contact::Model,
+    loan_id: &str,
+) -> Result<loan::Model, PortalError> {
+    let found = loan::Entity::find_by_id(loan_id.to_string())
+        .one(db)
+        .await?
+        .filter(|l| l.contact_id == holder.id)
+        .ok_or(PortalError::LoanNotFound)?;
+    if found.status == "returned" {
+        return Err(PortalError::Returned);
+    }
+    if LendingTerms::from_column(found.lending_terms.as_deref()).is_some_and(|t| !t.renewable) {
+        return Err(PortalError::NotRenewable);
+    }
+
+    let title = match copy::Entity::find_by_id(found.copy_id.clone())
+        .one(db)
+        .await?
+    {
+        Some(c) => book::Entity::find_by_id(c.book_id)
+            .one(db)
+            .await?
+            .map(|b| b.title),
+        None => None,
+    };
+    crate::services::notification_service::emit_unique(
+        db,
+        CreateNotification {
+            event_type: NotificationEventType::RenewalRequest,
+            title: title.unwrap_or_default(),
+            body: Some(holder.name.clone()),
+            ref_type: Some("loan".to_string()),
+            ref_id: Some(found.id.clone()),
+        },
+    )
+    .await;
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_contact(db: &DatabaseConnection, name: &str) -> contact::Model {
+        let now = Utc::now().to_rfc3339();
+        contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set(name.to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    async fn insert_loan(
+        db: &DatabaseConnection,
+        contact_id: &str,
+        terms: Option<LendingTerms>,
+    ) -> loan::Model {
+        let now = Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        let copy = copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(1),
+            status: Set("loaned".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        loan::ActiveModel {
+            copy_id: Set(copy.id),
+            contact_id: Set(contact_id.to_string()),
+            library_id: Set(1),
+            loan_date: Set("2026-10-01".to_string()),
+            due_date: Set("2026-10-22".to_string()),
+            status: Set("active".to_string()),
+            lending_terms: Set(terms.map(|t| t.to_column())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn token_opens_only_its_contact_until_replaced() {
+        let db = setup().await;
+        let alice = insert_contact(&db, "Alice").await;
+        assert!(issue_token(&db, "nobody").await.unwrap().is_none());
+
+        let (_, first) = issue_token(&db, &alice.id).await.unwrap().unwrap();
+        let holder = authenticate(&db, &first).await.unwrap().unwrap();
+        assert_eq!(holder.id, alice.id);
+
+        let (_, second) = issue_token(&db, &alice.id).await.unwrap().unwrap();
+        assert!(authenticate(&db, &first).await.unwrap().is_none());
+        assert!(authenticate(&db, &second).await.unwrap().is_some());
+
+        assert!(revoke_token(&db, &alice.id).await.unwrap());
+        assert!(authenticate(&db, &second).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn renewal_is_requested_for_own_renewable_loans_only() {
+        let db = setup().await;
+        let alice = insert_contact(&db, "Alice").await;
+        let bob = insert_contact(&db, "Bob").await;
+        let open = insert_loan(&db, &alice.id, None).await;
+        let strict = insert_loan(
+            &db,
+            &alice.id,
+            Some(LendingTerms {
+                renewable: false,
+                ..Default::default()
+            }),
+        )
+        .await;
+
+        assert_eq!(open_loans(&db, &alice.id).await.unwrap().len(), 2);
+        assert!(request_renewal(&db, &alice, &open.id).await.is_ok());
+        assert!(matches!(
+            request_renewal(&db, &bob, &open.id).await,
+            Err(PortalError::LoanNotFound)
+        ));
+        assert!(matches!(
+            request_renewal(&db, &alice, &strict.id).await,
+            Err(PortalError::NotRenewable)
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+//! Contacts around a point, and the order to visit a set of them in.
+//!
+//! For a community library doing book drop-offs and pickups at home: which
+//! contacts live near a place, and, for a round of deliveries, a visiting
+//! order. The order is the greedy nearest-neighbour tour from the start
+//! (the library's location unless given): always go to the closest contact
+//! not visited yet. It is no optimal tour, but on the dozen stops of a
+//! volunteer's round it is close enough and easy to follow. Distances are
+//! as the crow flies (`osm::distance_m`), from the coordinates filled in by
+//! `services::geocoding` or typed by hand.
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{contact, library_config};
+use crate::modules::integrations::osm::distance_m;
+
+/// Widest radius `nearby` searches.
+pub const MAX_RADIUS_M: u32 = 100_000;
+
+#[derive(Debug)]
+pub enum RouteError {
+    /// No start given and the library has no location
+    NoOrigin,
+    /// Contact ids that match no contact
+    UnknownContacts(Vec<String>),
+    Db(DbErr),
+}
+
+impl std::fmt::Display for RouteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoOrigin => write!(f, "no start point given and the library has no location"),
+            Self::UnknownContacts(ids) => write!(f, "unknown contacts: {}", ids.join(", ")),
+            Self::Db(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+impl From<DbErr> for RouteError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// A contact with where it is.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LocatedContact {
+    pub id: String,
+    pub name: String,
+    pub first_name: Option<String>,
+    pub street_address: Option<String>,
+    pub city: Option<String>,
+    pub phone: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Metres from the point searched around, or from the previous stop
+    pub distance_m: u32,
+}
+
+impl LocatedContact {
+    fn new(c: contact::Model, (latitude, longitude): (f64, f64), distance_m: u32) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            first_name: c.first_name,
+            street_address: c.street_address,
+            city: c.city,
+            phone: c.phone,
+            latitude,
+            longitude,
+            distance_m,
+        }
+    }
+}
+
+fn located(c: &contact::Model) -> Option<(f64, f64)> {
+    c.latitude.zip(c.longitude)
+}
+
+/// `point`, else the library's location.
+async fn origin(
+    db: &DatabaseConnection,
+    point: Option<(f64, f64)>,
+) -> Result<Option<(f64, f64)>, DbErr> {
+    if point.is_some() {
+        return Ok(point);
+    }
+    Ok(library_config::Entity::find_by_id(1)
+        .one(db)
+        .await?
+        .and_then(|c| c.latitude.zip(c.longitude)))
+}
+
+/// Active contacts within `radius_m` of `point` (the library's location
+/// when `None`), nearest first.
+pub async fn nearby(
+    db: &DatabaseConnection,
+    point: Option<(f64, f64)>,
+    radius_m: u32,
+    limit: usize,
+) -> Result<Vec<LocatedContact>, RouteError> {
+    let (lat, lon) = origin(db, point).await?.ok_or(RouteError::NoOrigin)?;
+    let radius_m = radius_m.clamp(1, MAX_RADIUS_M);
+    let mut found: Vec<LocatedContact> = contact::Entity::find()
+        .filter(contact::Column::IsActive.eq(true))
+        .filter(contact::Column::Latitude.is_not_null())
+        .filter(contact::Column::Longitude.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|c| {
+            let at = located(&c)?;
+            let d = distance_m(lat, lon, at.0, at.1).round() as u32;
+            (d <= radius_m).then(|| LocatedContact::new(c, at, d))
+        })
+        .collect();
+    found.sort_by_key(|c| c.distance_m);
+    found.truncate(limit);
+    Ok(found)
+}
+
+/// Visiting order of `points` from `start`, greedy nearest neighbour:
+/// indexes into `points`.
+fn greedy_order(start: (f64, f64), points: &[(f64, f64)]) -> Vec<usize> {
+    let mut left: Vec<usize> = (0..points.len()).collect();
+    let mut order = Vec::with_capacity(points.len());
+    let mut here = start;
+    while !left.is_empty() {
+        let (pos, _) = left
+            .iter()
+            .enumerate()
+            .map(|(pos, &i)| (pos, distance_m(here.0, here.1, points[i].0, points[i].1)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("not empty");
+        let next = left.remove(pos);
+        here = points[next];
+        order.push(next);
+    }
+    order
+}
+
+/// A round of visits.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Route {
+    pub start_latitude: f64,
+    pub start_longitude: f64,
+    /// In visiting order; `distance_m` is the leg from the previous stop
+    pub stops: Vec<LocatedContact>,
+    /// Back to the start after the last stop, when asked for
+    pub return_m: Option<u32>,
+    pub total_m: u32,
+    /// Contacts asked for that have no coordinates, left out of the round
+    pub unlocated: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct RouteRequest {
+    pub contact_ids: Vec<String>,
+    /// Where the round starts; the library's location when not given, else
+    /// the first contact listed that has coordinates
+    pub start_latitude: Option<f64>,
+    pub start_longitude: Option<f64>,
+    #[serde(default)]
+    pub return_to_start: bool,
+}
+
+/// Order the contacts of `request` into a round.
+pub async fn plan_route(
+    db: &DatabaseConnection,
+    request: &RouteRequest,
+) -> Result<Route, RouteError> {
+    let mut contacts = Vec::new();
+    let mut unknown = Vec::new();
+    for id in &request.contact_ids {
+        if contacts.iter().any(|c: &contact::Model| &c.id == id) {
+            continue;
+        }
+        match contact::Entity::find_by_id(id.clone()).one(db).await? {
+            Some(c) => contacts.push(c),
+            None => unknown.push(id.clone()),
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(RouteError::UnknownContacts(unknown));
+    }
+
+    let (located_contacts, unlocated): (Vec<_>, Vec<_>) =
+        contacts.into_iter().partition(|c| located(c).is_some());
+    let points: Vec<(f64, f64)> = located_contacts.iter().filter_map(located).collect();
+    let given = request.start_latitude.zip(request.start_longitude);
+    let start = match origin(db, given).await? {
+        Some(start) => start,
+        None => *points.first().ok_or(RouteError::NoOrigin)?,
+    };
+
+    let order = greedy_order(start, &points);
+    let mut slots: Vec<Option<contact::Model>> = located_contacts.into_iter().map(Some).collect();
+    let mut stops = Vec::with_capacity(order.len());
+    let mut here = start;
+    let mut total_m = 0;
+    for i in order {
+        let at = points[i];
+        let leg = distance_m(here.0, here.1, at.0, at.1).round() as u32;
+        total_m += leg;
+        here = at;
+        let c = slots[i].take().expect("each stop visited once");
+        stops.push(LocatedContact::new(c, at, leg));
+    }
+    let return_m = request
+        .return_to_start
+        .then(|| distance_m(here.0, here.1, start.0, start.1).round() as u32);
+    total_m += return_m.unwrap_or(0);
+
+    Ok(Route {
+        start_latitude: start.0,
+        start_longitude: start.1,
+        stops,
+        return_m,
+        total_m,
+        unlocated: unlocated.into_iter().map(|c| c.id).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_round_goes_to_the_closest_stop_next() {
+        // Along a line of latitude, 0.01° of longitude apart, listed out of order.
+        let points = [(48.0, 2.03), (48.0, 2.01), (48.0, 2.05), (48.0, 2.02)];
+        assert_eq!(greedy_order((48.0, 2.0), &points), [1, 3, 0, 2]);
+        // Starting at the far end walks back.
+        assert_eq!(greedy_order((48.0, 2.06), &points), [2, 0, 3, 1]);
+        assert!(greedy_order((48.0, 2.0), &[]).is_empty());
+    }
+}
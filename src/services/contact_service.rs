@@ -1,8 +1,9 @@
 //! Contact Service - Pure business logic without HTTP layer
+//!
+//! Depends on [`ContactRepository`] only: callers pass the SeaORM repository
+//! (`SeaOrmContactRepository`), tests an in-memory one.
 
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
-
-use crate::models::contact::{self as contact_model, Entity as Contact};
+use crate::domain::{Contact, ContactInput, ContactQuery, ContactRepository, DomainError};
 
 /// Error type for service operations
 #[derive(Debug)]
@@ -12,9 +13,13 @@ pub enum ServiceError {
     Validation(String),
 }
 
-impl From<sea_orm::DbErr> for ServiceError {
-    fn from(e: sea_orm::DbErr) -> Self {
-        ServiceError::Database(e.to_string())
+impl From<DomainError> for ServiceError {
+    fn from(e: DomainError) -> Self {
+        match e {
+            DomainError::NotFound => ServiceError::NotFound,
+            DomainError::Validation(msg) => ServiceError::Validation(msg),
+            other => ServiceError::Database(other.to_string()),
+        }
     }
 }
 
@@ -43,12 +48,12 @@ pub struct ContactDto {
     pub uuid: Option<String>,
 }
 
-impl From<contact_model::Model> for ContactDto {
-    fn from(model: contact_model::Model) -> Self {
+impl From<Contact> for ContactDto {
+    fn from(model: Contact) -> Self {
         Self {
             uuid: Some(model.id.clone()),
             id: Some(model.id),
-            contact_type: model.r#type,
+            contact_type: model.contact_type,
             name: model.name,
             first_name: model.first_name,
             email: model.email,
@@ -75,177 +80,171 @@ pub struct ContactFilter {
     pub contact_type: Option<String>,
 }
 
+impl From<ContactDto> for ContactInput {
+    fn from(dto: ContactDto) -> Self {
+        Self {
+            contact_type: dto.contact_type,
+            name: dto.name,
+            first_name: dto.first_name,
+            email: dto.email,
+            phone: dto.phone,
+            address: dto.address,
+            street_address: dto.street_address,
+            postal_code: dto.postal_code,
+            city: dto.city,
+            country: dto.country,
+            latitude: dto.latitude,
+            longitude: dto.longitude,
+            notes: dto.notes,
+            user_id: dto.user_id,
+            library_owner_id: dto.library_owner_id,
+            is_active: dto.is_active,
+        }
+    }
+}
+
 /// List all contacts with optional filters
 pub async fn list_contacts(
-    db: &DatabaseConnection,
+    repo: &dyn ContactRepository,
     filter: ContactFilter,
 ) -> Result<Vec<ContactDto>, ServiceError> {
-    // Start with only active contacts
-    let mut query = Contact::find().filter(contact_model::Column::IsActive.eq(true));
-
-    if let Some(library_id) = filter.library_id {
-        query = query.filter(contact_model::Column::LibraryOwnerId.eq(library_id));
-    }
-
-    if let Some(contact_type) = filter.contact_type {
-        query = query.filter(contact_model::Column::Type.eq(contact_type));
-    }
-
-    let contacts = query.all(db).await?;
+    // Only active contacts
+    let query = ContactQuery {
+        library_id: filter.library_id,
+        contact_type: filter.contact_type,
+    };
+    let contacts = repo.find_active(&query).await?;
     Ok(contacts.into_iter().map(ContactDto::from).collect())
 }
 
 /// Get a single contact by ID
-pub async fn get_contact(db: &DatabaseConnection, id: &str) -> Result<ContactDto, ServiceError> {
-    let contact = Contact::find_by_id(id.to_owned())
-        .one(db)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
+pub async fn get_contact(
+    repo: &dyn ContactRepository,
+    id: &str,
+) -> Result<ContactDto, ServiceError> {
+    let contact = repo.find_by_id(id).await?.ok_or(ServiceError::NotFound)?;
     Ok(ContactDto::from(contact))
 }
 
 /// Fetch a contact by its cross-device uuid (the device-local `id` cannot
-/// identify a row across devices). Single fetch, no id round-trip.
+/// identify a row across devices). The uuid is the primary key, so this is
+/// the same single fetch as [`get_contact`].
 pub async fn get_contact_by_uuid(
-    db: &DatabaseConnection,
+    repo: &dyn ContactRepository,
     uuid: &str,
 ) -> Result<ContactDto, ServiceError> {
-    let contact = crate::infrastructure::uuid_lookup::find_contact_by_uuid(db, uuid)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-    Ok(ContactDto::from(contact))
+    get_contact(repo, uuid).await
 }
 
-/// Create a new contact
+/// Create a new contact. An unknown `user_id` is dropped; an unknown
+/// `library_owner_id` is a validation error; no owner means the local library.
 pub async fn create_contact(
-    db: &DatabaseConnection,
+    repo: &dyn ContactRepository,
     dto: ContactDto,
 ) -> Result<ContactDto, ServiceError> {
-    let now = chrono::Utc::now().to_rfc3339();
-
-    // Validate user_id FK: set to None if user doesn't exist
-    let user_id = if let Some(uid) = dto.user_id {
-        use crate::models::user;
-        match user::Entity::find_by_id(uid).one(db).await {
-            Ok(Some(_)) => Some(uid),
-            _ => None,
-        }
-    } else {
-        None
-    };
-
-    // Resolve library_owner_id. The replicated `contacts` table no longer
-    // carries a foreign key into `libraries` (ADR-044), so reject a dangling
-    // owner id at the app layer, as the database constraint once did.
-    let library_owner_id = match dto.library_owner_id {
-        Some(id) => {
-            if !crate::utils::library_helpers::library_exists(db, id).await? {
-                return Err(ServiceError::Validation(format!(
-                    "library {id} does not exist"
-                )));
-            }
-            id
-        }
-        None => crate::utils::library_helpers::resolve_library_id(db)
-            .await
-            .map_err(|e| ServiceError::Database(e.to_string()))?,
-    };
-
-    let new_contact = contact_model::ActiveModel {
-        r#type: Set(dto.contact_type),
-        name: Set(dto.name),
-        first_name: Set(dto.first_name),
-        email: Set(dto.email),
-        phone: Set(dto.phone),
-        address: Set(dto.address),
-        street_address: Set(dto.street_address),
-        postal_code: Set(dto.postal_code),
-        city: Set(dto.city),
-        country: Set(dto.country),
-        latitude: Set(dto.latitude),
-        longitude: Set(dto.longitude),
-        notes: Set(dto.notes),
-        user_id: Set(user_id),
-        library_owner_id: Set(library_owner_id),
-        is_active: Set(dto.is_active),
-        created_at: Set(now.clone()),
-        updated_at: Set(now),
-        ..Default::default()
-    };
-
-    let saved_contact = new_contact.insert(db).await?;
-
-    let _ = crate::sync::log_operation(db, "contact", &saved_contact.id, "INSERT", None).await;
-
-    Ok(ContactDto::from(saved_contact))
+    let created = repo.create(dto.into()).await?;
+    Ok(ContactDto::from(created))
 }
 
 /// Update an existing contact
 pub async fn update_contact(
-    db: &DatabaseConnection,
+    repo: &dyn ContactRepository,
     dto: ContactDto,
 ) -> Result<ContactDto, ServiceError> {
-    let id = dto.id.ok_or(ServiceError::Database(
+    let id = dto.id.clone().ok_or(ServiceError::Database(
         "Contact ID is required for update".to_string(),
     ))?;
 
-    let contact = Contact::find_by_id(id)
-        .one(db)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
-    let mut active_model: contact_model::ActiveModel = contact.into();
-    let now = chrono::Utc::now().to_rfc3339();
-
-    active_model.r#type = Set(dto.contact_type);
-    active_model.name = Set(dto.name);
-    active_model.first_name = Set(dto.first_name);
-    active_model.email = Set(dto.email);
-    active_model.phone = Set(dto.phone);
-    active_model.address = Set(dto.address);
-    active_model.street_address = Set(dto.street_address);
-    active_model.postal_code = Set(dto.postal_code);
-    active_model.city = Set(dto.city);
-    active_model.country = Set(dto.country);
-    active_model.latitude = Set(dto.latitude);
-    active_model.longitude = Set(dto.longitude);
-    active_model.notes = Set(dto.notes);
-    active_model.user_id = Set(dto.user_id);
-    if let Some(lid) = dto.library_owner_id {
-        active_model.library_owner_id = Set(lid);
-    }
-    active_model.is_active = Set(dto.is_active);
-    active_model.updated_at = Set(now);
-
-    let model = active_model.update(db).await?;
-
-    let _ = crate::sync::log_operation(db, "contact", &model.id, "UPDATE", None).await;
-
-    Ok(ContactDto::from(model))
+    let updated = repo.update(&id, dto.into()).await?;
+    Ok(ContactDto::from(updated))
 }
 
 /// Delete a contact (soft delete)
-pub async fn delete_contact(db: &DatabaseConnection, id: &str) -> Result<(), ServiceError> {
-    let contact = Contact::find_by_id(id.to_owned())
-        .one(db)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
-    let mut active_model: contact_model::ActiveModel = contact.into();
-    active_model.is_active = Set(false);
-    active_model.updated_at = Set(chrono::Utc::now().to_rfc3339());
-
-    active_model.update(db).await?;
-
-    let _ = crate::sync::log_operation(db, "contact", id, "DELETE", None).await;
-
+pub async fn delete_contact(repo: &dyn ContactRepository, id: &str) -> Result<(), ServiceError> {
+    repo.deactivate(id).await?;
     Ok(())
 }
 
 /// Count total contacts
-pub async fn count_contacts(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    use sea_orm::PaginatorTrait;
-    let count = Contact::find().count(db).await?;
-    Ok(count as i64)
+pub async fn count_contacts(repo: &dyn ContactRepository) -> Result<i64, ServiceError> {
+    Ok(repo.count().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::repositories::memory::InMemoryContactRepository;
+
+    fn dto(name: &str, contact_type: &str) -> ContactDto {
+        ContactDto {
+            id: None,
+            contact_type: contact_type.to_string(),
+            name: name.to_string(),
+            first_name: None,
+            email: None,
+            phone: None,
+            address: None,
+            street_address: None,
+            postal_code: None,
+            city: None,
+            country: None,
+            latitude: None,
+            longitude: None,
+            notes: None,
+            user_id: None,
+            library_owner_id: None,
+            is_active: true,
+            uuid: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn deleted_contacts_leave_the_list_but_still_count() {
+        let repo = InMemoryContactRepository::new();
+        let alice = create_contact(&repo, dto("Alice", "Borrower"))
+            .await
+            .unwrap();
+        create_contact(&repo, dto("Town library", "Library"))
+            .await
+            .unwrap();
+
+        let borrowers = list_contacts(
+            &repo,
+            ContactFilter {
+                contact_type: Some("Borrower".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(borrowers.len(), 1);
+        assert_eq!(borrowers[0].uuid, alice.id);
+
+        let id = alice.id.clone().unwrap();
+        delete_contact(&repo, &id).await.unwrap();
+        let all = list_contacts(&repo, ContactFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(count_contacts(&repo).await.unwrap(), 2);
+
+        let updated = update_contact(
+            &repo,
+            ContactDto {
+                id: Some(id.clone()),
+                ..dto("Alice B.", "Borrower")
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.name, "Alice B.");
+        assert!(matches!(
+            get_contact(&repo, "missing").await,
+            Err(ServiceError::NotFound)
+        ));
+        assert!(matches!(
+            update_contact(&repo, dto("No id", "Borrower")).await,
+            Err(ServiceError::Database(_))
+        ));
+    }
 }
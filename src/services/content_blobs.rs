@@ -0,0 +1,831 @@
+//! Content-addressed covers and digital files, exchanged between peers.
+//!
+//! A library offers peers the cover thumbnails of the books it shares and,
+//! when the owner enabled the `share_digital_files` module, their digital
+//! files. Each blob is addressed by the SHA-256 of its bytes and cut into
+//! fixed-size chunks, each with its own hash (the manifest). A peer syncing
+//! the catalogue lists them (`GET /api/blobs`) and fetches the blobs it lacks
+//! chunk by chunk, verifying every chunk as it lands: a transfer cut short
+//! resumes at the first missing chunk on the next sync, and the chunks of a
+//! blob several peers list can come from any of them. Fetched blobs live in
+//! the blob store (`<media dir>/blobs/<sha256>`), so browsing a peer's
+//! catalogue shows its covers even when the peer is offline.
+//!
+//! Covers are offered as the 300x450 thumbnail `GET /api/books/:id/cover`
+//! serves: the resize is deterministic, so the same cover always hashes the
+//! same way.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::models::{blob_source, book, installation_profile, peer, peer_blob};
+use crate::modules::book_files::{self, storage};
+
+/// Size of a chunk, the unit of transfer and of resumption.
+pub const CHUNK_SIZE: u64 = 256 * 1024;
+
+/// Module flag under which the owner lets peers fetch digital files.
+pub const SHARE_FILES_MODULE: &str = "share_digital_files";
+
+/// Bytes a single sync fetches from a peer at most; the rest resumes on the
+/// next sync.
+const MAX_SYNC_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Chunk sizes accepted from a peer's manifest.
+const MIN_CHUNK_SIZE: u64 = 16 * 1024;
+const MAX_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Error type for blob indexing and transfers
+#[derive(Debug)]
+pub enum BlobError {
+    Db(DbErr),
+    Io(std::io::Error),
+    /// No source answered, or answered with something unusable.
+    Peer(String),
+    /// The bytes received do not match the manifest.
+    Corrupt(String),
+}
+
+impl std::fmt::Display for BlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlobError::Db(e) => write!(f, "Database error: {}", e),
+            BlobError::Io(e) => write!(f, "Storage error: {}", e),
+            BlobError::Peer(msg) => write!(f, "Transfer failed: {}", msg),
+            BlobError::Corrupt(msg) => write!(f, "Corrupt blob: {}", msg),
+        }
+    }
+}
+
+impl From<DbErr> for BlobError {
+    fn from(e: DbErr) -> Self {
+        BlobError::Db(e)
+    }
+}
+
+impl From<std::io::Error> for BlobError {
+    fn from(e: std::io::Error) -> Self {
+        BlobError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobKind {
+    Cover,
+    File,
+}
+
+impl BlobKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlobKind::Cover => "cover",
+            BlobKind::File => "file",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cover" => Some(BlobKind::Cover),
+            "file" => Some(BlobKind::File),
+            _ => None,
+        }
+    }
+}
+
+/// A blob a library offers for one of its books.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BlobEntry {
+    pub book_id: String,
+    pub kind: BlobKind,
+    pub sha256: String,
+    pub size: u64,
+    /// File format (`epub`, `pdf`, ...), absent for covers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// How a blob is cut into chunks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Manifest {
+    pub sha256: String,
+    pub size: u64,
+    pub chunk_size: u64,
+    /// SHA-256 of each chunk, in order
+    pub chunks: Vec<String>,
+}
+
+impl Manifest {
+    /// The manifest of `bytes`, cut into [`CHUNK_SIZE`] chunks.
+    pub fn of(bytes: &[u8]) -> Self {
+        Manifest {
+            sha256: sha256_hex(bytes),
+            size: bytes.len() as u64,
+            chunk_size: CHUNK_SIZE,
+            chunks: bytes.chunks(CHUNK_SIZE as usize).map(sha256_hex).collect(),
+        }
+    }
+
+    /// Length of chunk `index`: the last one may be short.
+    pub fn chunk_len(&self, index: usize) -> u64 {
+        let start = index as u64 * self.chunk_size;
+        self.chunk_size.min(self.size.saturating_sub(start))
+    }
+
+    /// Whether a manifest received from a peer is usable for `sha256`.
+    fn is_consistent(&self, sha256: &str, max_size: u64) -> bool {
+        self.sha256 == sha256
+            && self.size > 0
+            && self.size <= max_size
+            && (MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&self.chunk_size)
+            && self.chunks.len() as u64 == self.size.div_ceil(self.chunk_size)
+            && self.chunks.iter().all(|c| valid_sha256(c))
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Whether `s` is a lowercase hex SHA-256, the only names the store uses.
+pub fn valid_sha256(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// The blob store: `<media dir>/blobs`.
+pub fn store_dir() -> PathBuf {
+    storage::media_root().join("blobs")
+}
+
+/// Where a complete blob lives in the store.
+pub fn store_path(dir: &Path, sha256: &str) -> PathBuf {
+    dir.join(sha256)
+}
+
+fn part_path(dir: &Path, sha256: &str) -> PathBuf {
+    dir.join(format!("{sha256}.part"))
+}
+
+/// Fill `buf` from `reader`, short only at the end of the stream.
+async fn read_full<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// The manifest of the file at `path`, read one chunk at a time.
+async fn hash_file(path: &Path) -> std::io::Result<Manifest> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut whole = Sha256::new();
+    let mut chunks = Vec::new();
+    let mut size = 0u64;
+    let mut buf = vec![0u8; CHUNK_SIZE as usize];
+    loop {
+        let n = read_full(&mut file, &mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        whole.update(&buf[..n]);
+        chunks.push(sha256_hex(&buf[..n]));
+        size += n as u64;
+        if n < buf.len() {
+            break;
+        }
+    }
+    Ok(Manifest {
+        sha256: format!("{:x}", whole.finalize()),
+        size,
+        chunk_size: CHUNK_SIZE,
+        chunks,
+    })
+}
+
+/// Whether the owner lets peers fetch digital files.
+pub async fn shares_files(db: &DatabaseConnection) -> Result<bool, DbErr> {
+    Ok(installation_profile::Entity::find_by_id(1)
+        .one(db)
+        .await?
+        .and_then(|p| serde_json::from_str::<Vec<String>>(&p.enabled_modules).ok())
+        .is_some_and(|modules| modules.iter().any(|m| m == SHARE_FILES_MODULE)))
+}
+
+/// The books peers see: owned, neither private, archived nor carrying a
+/// private tag (the same rule as the peer catalogue).
+async fn shared_books(db: &DatabaseConnection) -> Result<Vec<book::Model>, DbErr> {
+    let hidden = book::Book::tag_restricted_ids(db).await?;
+    Ok(book::Entity::find()
+        .filter(book::Column::Owned.eq(true))
+        .filter(book::Column::Private.eq(false))
+        .filter(book::Column::Archived.eq(false))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|b| !hidden.contains(&b.id))
+        .collect())
+}
+
+fn cover_stamp(path: &Path, meta: &std::fs::Metadata) -> String {
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!("{}:{}:{}", path.display(), meta.len(), modified)
+}
+
+fn to_entry(source: &blob_source::Model, format: Option<String>) -> Option<BlobEntry> {
+    Some(BlobEntry {
+        book_id: source.book_id.clone(),
+        kind: BlobKind::parse(&source.kind)?,
+        sha256: source.sha256.clone(),
+        size: source.size as u64,
+        format,
+    })
+}
+
+async fn save_source(
+    db: &DatabaseConnection,
+    kind: BlobKind,
+    source_id: &str,
+    book_id: &str,
+    manifest: &Manifest,
+    path: &Path,
+    stamp: String,
+) -> Result<blob_source::Model, DbErr> {
+    blob_source::Entity::delete_by_id((kind.as_str().to_string(), source_id.to_string()))
+        .exec(db)
+        .await?;
+    blob_source::ActiveModel {
+        kind: Set(kind.as_str().to_string()),
+        source_id: Set(source_id.to_string()),
+        book_id: Set(book_id.to_string()),
+        sha256: Set(manifest.sha256.clone()),
+        size: Set(manifest.size as i64),
+        chunk_hashes: Set(serde_json::to_string(&manifest.chunks).unwrap_or_default()),
+        path: Set(path.to_string_lossy().into_owned()),
+        stamp: Set(stamp),
+    }
+    .insert(db)
+    .await
+}
+
+/// The thumbnail of a book's local cover, written to the store.
+async fn index_cover(
+    db: &DatabaseConnection,
+    dir: &Path,
+    book: &book::Model,
+    existing: Option<&blob_source::Model>,
+) -> Result<Option<blob_source::Model>, BlobError> {
+    let Some(path) = book
+        .cover_url
+        .as_deref()
+        .and_then(|url| crate::utils::cover_url::local_cover_read_path(url, &book.id))
+    else {
+        return Ok(None);
+    };
+    let Ok(meta) = tokio::fs::metadata(&path).await else {
+        return Ok(None);
+    };
+    let stamp = cover_stamp(&path, &meta);
+    if let Some(source) = existing
+        && source.stamp == stamp
+        && Path::new(&source.path).exists()
+    {
+        return Ok(Some(source.clone()));
+    }
+
+    let raw = tokio::fs::read(&path).await?;
+    let thumbnail = match tokio::task::spawn_blocking(move || {
+        crate::utils::cover_image::resize_to_jpeg_thumbnail(&raw)
+    })
+    .await
+    {
+        Ok(Ok(jpeg)) => jpeg,
+        _ => {
+            tracing::warn!("blob index: cover of book {} could not be resized", book.id);
+            return Ok(None);
+        }
+    };
+    let manifest = Manifest::of(&thumbnail);
+    tokio::fs::create_dir_all(dir).await?;
+    let blob = store_path(dir, &manifest.sha256);
+    if !blob.exists() {
+        tokio::fs::write(&blob, &thumbnail).await?;
+    }
+    Ok(Some(
+        save_source(
+            db,
+            BlobKind::Cover,
+            &book.id,
+            &book.id,
+            &manifest,
+            &blob,
+            stamp,
+        )
+        .await?,
+    ))
+}
+
+/// A book's digital file, hashed where it is stored.
+async fn index_file(
+    db: &DatabaseConnection,
+    file: &book_files::models::Model,
+    existing: Option<&blob_source::Model>,
+) -> Result<Option<blob_source::Model>, BlobError> {
+    // Stored files never change: their checksum is a stable stamp.
+    if let Some(source) = existing
+        && source.stamp == file.sha256
+    {
+        return Ok(Some(source.clone()));
+    }
+    let path = storage::blob_path(
+        &storage::media_root(),
+        &file.book_id,
+        &file.id,
+        &file.format,
+    );
+    let manifest = match hash_file(&path).await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::warn!("blob index: file {} unreadable: {}", file.id, e);
+            return Ok(None);
+        }
+    };
+    Ok(Some(
+        save_source(
+            db,
+            BlobKind::File,
+            &file.id,
+            &file.book_id,
+            &manifest,
+            &path,
+            file.sha256.clone(),
+        )
+        .await?,
+    ))
+}
+
+/// Bring the index of shared blobs up to date and list them: the covers of
+/// the shared books, and their files when the owner shares files. Covers
+/// that changed are re-hashed; blobs no longer shared leave the index.
+pub async fn refresh_index(
+    db: &DatabaseConnection,
+    dir: &Path,
+) -> Result<Vec<BlobEntry>, BlobError> {
+    let books = shared_books(db).await?;
+    let mut existing: HashMap<(String, String), blob_source::Model> = blob_source::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| ((s.kind.clone(), s.source_id.clone()), s))
+        .collect();
+    let mut entries = Vec::new();
+    let mut kept = HashSet::new();
+
+    for book in &books {
+        let key = (BlobKind::Cover.as_str().to_string(), book.id.clone());
+        if let Some(source) = index_cover(db, dir, book, existing.get(&key)).await? {
+            entries.extend(to_entry(&source, None));
+            kept.insert(key);
+        }
+    }
+
+    if shares_files(db).await? {
+        let ids: Vec<String> = books.iter().map(|b| b.id.clone()).collect();
+        for ids in ids.chunks(500) {
+            let files = book_files::models::Entity::find()
+                .filter(book_files::models::Column::BookId.is_in(ids.to_vec()))
+                .all(db)
+                .await?;
+            for file in files {
+                let key = (BlobKind::File.as_str().to_string(), file.id.clone());
+                if let Some(source) = index_file(db, &file, existing.get(&key)).await? {
+                    entries.extend(to_entry(&source, Some(file.format.clone())));
+                    kept.insert(key);
+                }
+            }
+        }
+    }
+
+    existing.retain(|key, _| !kept.contains(key));
+    for (kind, source_id) in existing.into_keys() {
+        blob_source::Entity::delete_by_id((kind, source_id))
+            .exec(db)
+            .await?;
+    }
+    Ok(entries)
+}
+
+/// The indexed source of `sha256`, if its book is still shared (and, for a
+/// file, files still are).
+pub async fn shared_source(
+    db: &DatabaseConnection,
+    sha256: &str,
+) -> Result<Option<blob_source::Model>, DbErr> {
+    let sources = blob_source::Entity::find()
+        .filter(blob_source::Column::Sha256.eq(sha256))
+        .all(db)
+        .await?;
+    if sources.is_empty() {
+        return Ok(None);
+    }
+    let hidden = book::Book::tag_restricted_ids(db).await?;
+    let files_shared = shares_files(db).await?;
+    for source in sources {
+        if source.kind == BlobKind::File.as_str() && !files_shared {
+            continue;
+        }
+        let shared = book::Entity::find_by_id(source.book_id.clone())
+            .one(db)
+            .await?
+            .is_some_and(|b| b.owned && !b.private && !b.archived && !hidden.contains(&b.id));
+        if shared {
+            return Ok(Some(source));
+        }
+    }
+    Ok(None)
+}
+
+/// The manifest of an indexed source.
+pub fn source_manifest(source: &blob_source::Model) -> Manifest {
+    Manifest {
+        sha256: source.sha256.clone(),
+        size: source.size as u64,
+        chunk_size: CHUNK_SIZE,
+        chunks: serde_json::from_str(&source.chunk_hashes).unwrap_or_default(),
+    }
+}
+
+/// Chunk `index` of an indexed source, `None` past its end.
+pub async fn read_chunk(
+    source: &blob_source::Model,
+    index: usize,
+) -> Result<Option<Vec<u8>>, BlobError> {
+    let manifest = source_manifest(source);
+    if index >= manifest.chunks.len() {
+        return Ok(None);
+    }
+    let mut file = tokio::fs::File::open(&source.path).await?;
+    file.seek(std::io::SeekFrom::Start(index as u64 * CHUNK_SIZE))
+        .await?;
+    let mut buf = vec![0u8; manifest.chunk_len(index) as usize];
+    let n = read_full(&mut file, &mut buf).await?;
+    buf.truncate(n);
+    // The file changed under the index: refuse rather than serve bytes the
+    // manifest does not describe.
+    if sha256_hex(&buf) != manifest.chunks[index] {
+        return Err(BlobError::Corrupt(format!(
+            "chunk {index} of {}",
+            source.sha256
+        )));
+    }
+    Ok(Some(buf))
+}
+
+/// Replace what a peer lists with `entries`, its complete list.
+pub async fn record_peer_blobs(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    entries: Vec<BlobEntry>,
+) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let txn = db.begin().await?;
+    peer_blob::Entity::delete_many()
+        .filter(peer_blob::Column::PeerId.eq(peer_id))
+        .exec(&txn)
+        .await?;
+    let mut seen = HashSet::new();
+    let models: Vec<peer_blob::ActiveModel> = entries
+        .into_iter()
+        .filter(|e| valid_sha256(&e.sha256))
+        .filter(|e| seen.insert((e.book_id.clone(), e.kind, e.sha256.clone())))
+        .map(|e| peer_blob::ActiveModel {
+            peer_id: Set(peer_id),
+            remote_book_id: Set(e.book_id),
+            kind: Set(e.kind.as_str().to_string()),
+            sha256: Set(e.sha256),
+            size: Set(e.size as i64),
+            format: Set(e.format),
+            listed_at: Set(now.clone()),
+        })
+        .collect();
+    for chunk in models.chunks(100) {
+        peer_blob::Entity::insert_many(chunk.to_vec())
+            .exec_without_returning(&txn)
+            .await?;
+    }
+    txn.commit().await
+}
+
+/// Outcome of fetching a peer's blobs.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FetchSummary {
+    /// Blobs now complete in the store
+    pub fetched: usize,
+    /// Blobs left partial or failed, to resume on the next sync
+    pub pending: usize,
+    pub bytes: u64,
+}
+
+/// Fetch the blobs `peer_id` lists that the store lacks, covers first,
+/// within the per-sync byte budget. Chunks come from the peer and from the
+/// other accepted peers listing the same blob.
+pub async fn fetch_peer_blobs(
+    db: &DatabaseConnection,
+    client: &reqwest::Client,
+    peer_id: i32,
+    peer_url: &str,
+    dir: &Path,
+) -> Result<FetchSummary, BlobError> {
+    let mut wanted = peer_blob::Entity::find()
+        .filter(peer_blob::Column::PeerId.eq(peer_id))
+        .all(db)
+        .await?;
+    wanted.retain(|b| !store_path(dir, &b.sha256).exists());
+    wanted.sort_by_key(|b| (b.kind != BlobKind::Cover.as_str(), b.size));
+    let mut seen = HashSet::new();
+    wanted.retain(|b| seen.insert(b.sha256.clone()));
+
+    let mut summary = FetchSummary::default();
+    if wanted.is_empty() {
+        return Ok(summary);
+    }
+    tokio::fs::create_dir_all(dir).await?;
+    let mut budget = MAX_SYNC_BYTES;
+    for blob in wanted {
+        let size = blob.size.max(0) as u64;
+        if size > budget {
+            summary.pending += 1;
+            continue;
+        }
+        let sources = blob_sources(db, peer_url, &blob.sha256).await?;
+        let max_size = match BlobKind::parse(&blob.kind) {
+            Some(BlobKind::File) => book_files::domain::MAX_FILE_SIZE,
+            _ => 10 * 1024 * 1024,
+        };
+        match fetch_blob(client, &sources, &blob.sha256, max_size, dir).await {
+            Ok(received) => {
+                summary.fetched += 1;
+                summary.bytes += received;
+                budget = budget.saturating_sub(received);
+            }
+            Err(e) => {
+                tracing::warn!("blob {} from peer {}: {}", blob.sha256, peer_id, e);
+                summary.pending += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// The URLs a blob can be fetched from: `peer_url` first, then the other
+/// accepted peers listing it.
+async fn blob_sources(
+    db: &DatabaseConnection,
+    peer_url: &str,
+    sha256: &str,
+) -> Result<Vec<String>, DbErr> {
+    let mut sources = vec![peer_url.trim_end_matches('/').to_string()];
+    let holders: Vec<i32> = peer_blob::Entity::find()
+        .filter(peer_blob::Column::Sha256.eq(sha256))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| b.peer_id)
+        .collect();
+    let others = peer::Entity::find()
+        .filter(peer::Column::Id.is_in(holders))
+        .filter(peer::Column::ConnectionStatus.eq("accepted"))
+        .all(db)
+        .await?;
+    for p in others {
+        let url = p.url.trim_end_matches('/').to_string();
+        if !sources.contains(&url) && crate::api::peer::validate_url(&url).is_ok() {
+            sources.push(url);
+        }
+    }
+    Ok(sources)
+}
+
+/// Fetch one blob into the store, resuming a partial download. Returns the
+/// bytes received.
+async fn fetch_blob(
+    client: &reqwest::Client,
+    sources: &[String],
+    sha256: &str,
+    max_size: u64,
+    dir: &Path,
+) -> Result<u64, BlobError> {
+    let manifest = fetch_manifest(client, sources, sha256, max_size).await?;
+    let part = part_path(dir, sha256);
+    let (mut file, done) = open_part(&part, &manifest).await?;
+
+    let mut received = 0u64;
+    for index in done..manifest.chunks.len() {
+        let bytes = fetch_chunk(client, sources, &manifest, index).await?;
+        file.write_all(&bytes).await?;
+        received += bytes.len() as u64;
+    }
+    file.flush().await?;
+    drop(file);
+
+    let whole = hash_file(&part).await?;
+    if whole.sha256 != sha256 {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(BlobError::Corrupt(format!(
+            "{sha256} hashes to {}",
+            whole.sha256
+        )));
+    }
+    tokio::fs::rename(&part, store_path(dir, sha256)).await?;
+    Ok(received)
+}
+
+/// Open the partial download of a blob for appending, and the index of the
+/// first chunk it lacks. Chunks are appended only once verified, so the file
+/// is always a run of good chunks: a torn last one is cut off.
+async fn open_part(part: &Path, manifest: &Manifest) -> std::io::Result<(tokio::fs::File, usize)> {
+    let on_disk = tokio::fs::metadata(part)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let done = ((on_disk / manifest.chunk_size) as usize).min(manifest.chunks.len());
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(part)
+        .await?;
+    file.set_len(done as u64 * manifest.chunk_size).await?;
+    file.seek(std::io::SeekFrom::End(0)).await?;
+    Ok((file, done))
+}
+
+async fn fetch_manifest(
+    client: &reqwest::Client,
+    sources: &[String],
+    sha256: &str,
+    max_size: u64,
+) -> Result<Manifest, BlobError> {
+    for source in sources {
+        let url = format!("{source}/api/blobs/{sha256}");
+        let Ok(res) = client.get(&url).send().await else {
+            continue;
+        };
+        if !res.status().is_success() {
+            continue;
+        }
+        if let Ok(manifest) = res.json::<Manifest>().await
+            && manifest.is_consistent(sha256, max_size)
+        {
+            return Ok(manifest);
+        }
+    }
+    Err(BlobError::Peer(format!("no manifest for {sha256}")))
+}
+
+/// Chunk `index`, from the sources in turn starting with a different one for
+/// each chunk, so a blob several peers hold is spread across them.
+async fn fetch_chunk(
+    client: &reqwest::Client,
+    sources: &[String],
+    manifest: &Manifest,
+    index: usize,
+) -> Result<Vec<u8>, BlobError> {
+    let expected = manifest.chunk_len(index);
+    for n in 0..sources.len() {
+        let source = &sources[(index + n) % sources.len()];
+        let url = format!("{source}/api/blobs/{}/chunks/{index}", manifest.sha256);
+        let Ok(res) = client.get(&url).send().await else {
+            continue;
+        };
+        if !res.status().is_success() || res.content_length().is_some_and(|l| l > expected) {
+            continue;
+        }
+        let Ok(bytes) = res.bytes().await else {
+            continue;
+        };
+        if bytes.len() as u64 == expected && sha256_hex(&bytes) == manifest.chunks[index] {
+            return Ok(bytes.to_vec());
+        }
+        tracing::warn!("blob {}: bad chunk {index} from {source}", manifest.sha256);
+    }
+    Err(BlobError::Peer(format!(
+        "chunk {index} of {} unavailable",
+        manifest.sha256
+    )))
+}
+
+/// A fetched blob a peer lists for one of its books, if complete.
+pub async fn cached_peer_blob(
+    db: &DatabaseConnection,
+    dir: &Path,
+    peer_id: i32,
+    remote_book_id: &str,
+    kind: BlobKind,
+) -> Result<Option<(peer_blob::Model, PathBuf)>, DbErr> {
+    let listed = peer_blob::Entity::find()
+        .filter(peer_blob::Column::PeerId.eq(peer_id))
+        .filter(peer_blob::Column::RemoteBookId.eq(remote_book_id))
+        .filter(peer_blob::Column::Kind.eq(kind.as_str()))
+        .all(db)
+        .await?;
+    Ok(listed.into_iter().find_map(|b| {
+        let path = store_path(dir, &b.sha256);
+        path.exists().then_some((b, path))
+    }))
+}
+
+/// Remove from the store the blobs, complete or partial, that no peer lists
+/// and this library does not share. Returns how many were removed.
+pub async fn prune_store(db: &DatabaseConnection, dir: &Path) -> Result<usize, BlobError> {
+    let Ok(mut listing) = tokio::fs::read_dir(dir).await else {
+        return Ok(0);
+    };
+    let mut referenced: HashSet<String> = peer_blob::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| b.sha256)
+        .collect();
+    referenced.extend(
+        blob_source::Entity::find()
+            .filter(blob_source::Column::Kind.eq(BlobKind::Cover.as_str()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.sha256),
+    );
+    let mut removed = 0;
+    while let Some(entry) = listing.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let sha = name.strip_suffix(".part").unwrap_or(&name);
+        if valid_sha256(sha) && !referenced.contains(sha) {
+            tokio::fs::remove_file(entry.path()).await?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn partial_downloads_resume_after_the_last_whole_chunk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        let bytes: Vec<u8> = (0..(CHUNK_SIZE * 2 + 1000))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let manifest = Manifest::of(&bytes);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunk_len(2), 1000);
+        assert!(manifest.is_consistent(&manifest.sha256, 10 * 1024 * 1024));
+        assert!(!manifest.is_consistent(&sha256_hex(b"other"), 10 * 1024 * 1024));
+        assert!(!manifest.is_consistent(&manifest.sha256, 1024));
+
+        // One whole chunk and a torn second one: resume at the second.
+        let part = part_path(dir, &manifest.sha256);
+        tokio::fs::write(&part, &bytes[..(CHUNK_SIZE as usize + 10)])
+            .await
+            .unwrap();
+        let (mut file, done) = open_part(&part, &manifest).await.unwrap();
+        assert_eq!(done, 1);
+        for index in done..manifest.chunks.len() {
+            let start = index * CHUNK_SIZE as usize;
+            let end = start + manifest.chunk_len(index) as usize;
+            file.write_all(&bytes[start..end]).await.unwrap();
+        }
+        file.flush().await.unwrap();
+        drop(file);
+        assert_eq!(hash_file(&part).await.unwrap(), manifest);
+
+        // No source answers: the transfer fails and the partial file stays
+        // for the next sync, until nothing references the blob any more.
+        let client = crate::services::network_mode::client();
+        let err = fetch_blob(&client, &[], &manifest.sha256, u64::MAX, dir)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlobError::Peer(_)));
+        assert!(part.exists());
+
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        assert_eq!(prune_store(&db, dir).await.unwrap(), 1);
+        assert!(!part.exists());
+    }
+}
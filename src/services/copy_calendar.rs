@@ -0,0 +1,315 @@
+//! When a copy is likely to be free: the availability calendar behind
+//! `GET /api/copies/:id/availability`.
+//!
+//! The calendar strings together what keeps a copy off the shelf: the loan
+//! it is out on, until its due date, a copy held for a peer's pickup, then
+//! the peer requests waiting for the book, each read as one loan of the
+//! usual length. Waiting requests are shared among the book's lendable
+//! copies, each going to the copy free first, so a popular title with three
+//! copies is not promised three times too late. Only dates are given, never
+//! who borrowed or asked.
+
+use chrono::{Duration, NaiveDate};
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::domain::loan_settings_repository::LoanSettings;
+use crate::models::{book, copy, loan, p2p_request};
+use crate::utils::time_zone;
+
+/// Copy statuses that never come back to the shelf for lending: gone, not
+/// owned yet, or another library's.
+const NOT_LENDABLE: &[&str] = &[
+    "lost",
+    "wanted",
+    "sold",
+    "withdrawn",
+    "borrowed",
+    "returning",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BusyKind {
+    /// Out on a loan
+    Loan,
+    /// Held for a peer, then lent to them
+    Hold,
+    /// A peer request waiting for a copy
+    Request,
+}
+
+/// Days the copy is off the shelf, both ends included.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct BusyPeriod {
+    pub kind: BusyKind,
+    /// `YYYY-MM-DD`
+    pub from: String,
+    /// `YYYY-MM-DD`
+    pub until: String,
+    /// Projected from the usual loan length rather than a due date
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CopyCalendar {
+    pub copy_id: String,
+    pub book_id: String,
+    /// `false` for a copy lost, sold, withdrawn or borrowed from elsewhere
+    pub lendable: bool,
+    /// On the shelf today
+    pub available_now: bool,
+    /// The first day after the periods below, `YYYY-MM-DD`; `None` when not
+    /// lendable
+    pub free_from: Option<String>,
+    pub periods: Vec<BusyPeriod>,
+    /// Peer requests waiting for the book, whichever copy they will get
+    pub waiting: u32,
+}
+
+/// One lendable copy of the book, as it is booked up so far.
+struct Lane {
+    copy_id: String,
+    free: NaiveDate,
+    periods: Vec<BusyPeriod>,
+}
+
+impl Lane {
+    fn book(&mut self, kind: BusyKind, days: i64) {
+        let until = self.free + Duration::days(days - 1);
+        self.periods.push(BusyPeriod {
+            kind,
+            from: day(self.free),
+            until: day(until),
+            estimated: true,
+        });
+        self.free = until + Duration::days(1);
+    }
+}
+
+fn day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// The calendar of copy `copy_id`; `None` when there is no such copy.
+pub async fn calendar(
+    db: &DatabaseConnection,
+    copy_id: &str,
+    settings: &LoanSettings,
+) -> Result<Option<CopyCalendar>, DbErr> {
+    let Some(target) = copy::Entity::find_by_id(copy_id.to_owned()).one(db).await? else {
+        return Ok(None);
+    };
+    let Some(book) = book::Entity::find_by_id(target.book_id.clone())
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let today = time_zone::library_zone(db).await?.today();
+    // Peer requests are lent for the P2P duration: peers are `Library`
+    // contacts.
+    let loan_days = settings
+        .resolve_duration(book.loan_duration_days, None, Some("Library"))
+        .max(1) as i64;
+
+    let mut waiting_filter = Condition::any().add(p2p_request::Column::BookTitle.eq(&book.title));
+    if let Some(isbn) = book.isbn.as_deref().filter(|i| !i.is_empty()) {
+        waiting_filter = waiting_filter.add(p2p_request::Column::BookIsbn.eq(isbn));
+    }
+    let waiting = p2p_request::Entity::find()
+        .filter(p2p_request::Column::Status.is_in(["pending", "waitlisted"]))
+        .filter(waiting_filter)
+        .order_by_asc(p2p_request::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let lendable = |c: &copy::Model| !c.is_temporary && !NOT_LENDABLE.contains(&c.status.as_str());
+    if !lendable(&target) {
+        return Ok(Some(CopyCalendar {
+            copy_id: target.id,
+            book_id: book.id,
+            lendable: false,
+            available_now: false,
+            free_from: None,
+            periods: Vec::new(),
+            waiting: waiting.len() as u32,
+        }));
+    }
+
+    let copies: Vec<copy::Model> = copy::Entity::find()
+        .filter(copy::Column::BookId.eq(&book.id))
+        .order_by_asc(copy::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(lendable)
+        .collect();
+    let mut lanes = Vec::with_capacity(copies.len());
+    for c in &copies {
+        let mut lane = Lane {
+            copy_id: c.id.clone(),
+            free: today,
+            periods: Vec::new(),
+        };
+        let current = loan::Entity::find()
+            .filter(loan::Column::CopyId.eq(&c.id))
+            .filter(loan::Column::ReturnDate.is_null())
+            .filter(loan::Column::Status.is_in(["active", "overdue"]))
+            .one(db)
+            .await?;
+        if let Some(l) = current
+            && let Some(due) = parse_day(&l.due_date)
+        {
+            lane.periods.push(BusyPeriod {
+                kind: BusyKind::Loan,
+                from: day(parse_day(&l.loan_date).unwrap_or(today).min(due)),
+                until: day(due),
+                estimated: false,
+            });
+            // An overdue copy may come back any day.
+            lane.free = (due + Duration::days(1)).max(today);
+        }
+        let held = p2p_request::Entity::find()
+            .filter(p2p_request::Column::HeldCopyId.eq(&c.id))
+            .filter(p2p_request::Column::Status.eq("ready"))
+            .count(db)
+            .await?;
+        if held > 0 {
+            lane.book(BusyKind::Hold, loan_days);
+        }
+        lanes.push(lane);
+    }
+
+    for _ in &waiting {
+        if let Some(lane) = lanes.iter_mut().min_by_key(|l| l.free) {
+            lane.book(BusyKind::Request, loan_days);
+        }
+    }
+
+    let lane = lanes
+        .into_iter()
+        .find(|l| l.copy_id == target.id)
+        .ok_or_else(|| DbErr::Custom(format!("copy {} vanished", target.id)))?;
+    Ok(Some(CopyCalendar {
+        available_now: target.status == "available",
+        copy_id: target.id,
+        book_id: book.id,
+        lendable: true,
+        free_from: Some(day(lane.free)),
+        periods: lane.periods,
+        waiting: waiting.len() as u32,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::peer;
+
+    async fn add_copy(db: &DatabaseConnection, book_id: &str, status: &str) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        copy::ActiveModel {
+            book_id: Set(book_id.to_string()),
+            library_id: Set(crate::utils::library_helpers::resolve_library_id(db)
+                .await
+                .unwrap()),
+            status: Set(status.to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[tokio::test]
+    async fn waiting_requests_go_to_the_copy_free_first() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        let today = time_zone::library_zone(&db).await.unwrap().today();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some("9780441013593".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let on_shelf = add_copy(&db, &book.id, "available").await;
+        let lent = add_copy(&db, &book.id, "loaned").await;
+        let lost = add_copy(&db, &book.id, "lost").await;
+        loan::ActiveModel {
+            copy_id: Set(lent.clone()),
+            contact_id: Set("contact".to_string()),
+            library_id: Set(1),
+            loan_date: Set(day(today - Duration::days(10))),
+            due_date: Set(day(today + Duration::days(4))),
+            status: Set("active".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let peer = peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://127.0.0.1:9".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        for i in 0..3 {
+            p2p_request::ActiveModel {
+                id: Set(format!("req-{i}")),
+                from_peer_id: Set(peer.id),
+                book_isbn: Set("9780441013593".to_string()),
+                book_title: Set("Dune".to_string()),
+                status: Set("pending".to_string()),
+                created_at: Set(format!("2026-01-0{}T00:00:00+00:00", i + 1)),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        let settings = LoanSettings::default();
+        let days = |n: i64| day(today + Duration::days(n));
+
+        // 21-day loans: the shelf copy takes the first and third requests,
+        // the lent one the second once it is back.
+        let shelf = calendar(&db, &on_shelf, &settings).await.unwrap().unwrap();
+        assert!(shelf.available_now);
+        assert_eq!(shelf.waiting, 3);
+        let spans: Vec<(String, String)> = shelf
+            .periods
+            .into_iter()
+            .map(|p| (p.from, p.until))
+            .collect();
+        assert_eq!(spans, [(days(0), days(20)), (days(21), days(41))]);
+        assert_eq!(shelf.free_from, Some(days(42)));
+
+        let out = calendar(&db, &lent, &settings).await.unwrap().unwrap();
+        assert_eq!(out.periods[0].kind, BusyKind::Loan);
+        assert!(!out.periods[0].estimated);
+        assert_eq!(out.periods[1].from, days(5));
+        assert_eq!(out.free_from, Some(days(26)));
+
+        let gone = calendar(&db, &lost, &settings).await.unwrap().unwrap();
+        assert!(!gone.lendable);
+        assert_eq!(gone.free_from, None);
+        assert!(calendar(&db, "missing", &settings).await.unwrap().is_none());
+    }
+}
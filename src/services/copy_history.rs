@@ -0,0 +1,304 @@
+//! Copy history: where one physical copy has been, in chronological order.
+//!
+//! Nothing is recorded for it specifically. The timeline is assembled from
+//! what the other tables already keep: the copy itself (acquisition, sale,
+//! withdrawal), its loans, its transfers between libraries, and the status
+//! changes still present in the operation log. The log is pruned, so status changes older
+//! than its retention window are gone; loans and transfers are not.
+
+use std::collections::HashSet;
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{contact, copy, copy_transfer, loan, operation_log};
+
+/// One event of a copy's history.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct CopyEvent {
+    /// When it happened: a date, or an RFC 3339 timestamp.
+    pub at: String,
+    /// `acquired`, `loaned`, `returned`, `transfer_requested`,
+    /// `transfer_shipped`, `transfer_received`, `transfer_cancelled`,
+    /// `status_changed`, `sold` or `withdrawn`.
+    pub kind: String,
+    /// Borrower's name, destination library, new status, ...
+    pub detail: Option<String>,
+    /// Id of the loan or transfer behind the event.
+    pub ref_id: Option<String>,
+}
+
+impl CopyEvent {
+    fn new(at: &str, kind: &str, detail: Option<String>, ref_id: Option<&str>) -> Self {
+        Self {
+            at: at.to_string(),
+            kind: kind.to_string(),
+            detail,
+            ref_id: ref_id.map(str::to_string),
+        }
+    }
+}
+
+/// The history of a copy, oldest event first. `None` when there is no such
+/// copy.
+pub async fn copy_history(
+    db: &DatabaseConnection,
+    copy_id: &str,
+) -> Result<Option<Vec<CopyEvent>>, DbErr> {
+    let Some(found) = copy::Entity::find_by_id(copy_id.to_string())
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut events = vec![CopyEvent::new(
+        found
+            .acquisition_date
+            .as_deref()
+            .unwrap_or(&found.created_at),
+        "acquired",
+        None,
+        None,
+    )];
+
+    let loans = loan::Entity::find()
+        .filter(loan::Column::CopyId.eq(copy_id))
+        .all(db)
+        .await?;
+    let contact_ids: Vec<String> = loans.iter().map(|l| l.contact_id.clone()).collect();
+    let names: std::collections::HashMap<String, String> = contact::Entity::find()
+        .filter(contact::Column::Id.is_in(contact_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+    for l in &loans {
+        let borrower = names.get(&l.contact_id).cloned();
+        events.push(CopyEvent::new(
+            &l.loan_date,
+            "loaned",
+            borrower.clone(),
+            Some(&l.id),
+        ));
+        if let Some(returned) = &l.return_date {
+            events.push(CopyEvent::new(returned, "returned", borrower, Some(&l.id)));
+        }
+    }
+
+    let transfers = copy_transfer::Entity::find()
+        .filter(copy_transfer::Column::CopyId.eq(copy_id))
+        .all(db)
+        .await?;
+    for t in &transfers {
+        let route = Some(format!(
+            "library {} -> library {}",
+            t.from_library_id, t.to_library_id
+        ));
+        events.push(CopyEvent::new(
+            &t.requested_at,
+            "transfer_requested",
+            route.clone(),
+            Some(&t.id),
+        ));
+        if let Some(shipped) = &t.shipped_at {
+            events.push(CopyEvent::new(
+                shipped,
+                "transfer_shipped",
+                route.clone(),
+                Some(&t.id),
+            ));
+        }
+        if let Some(received) = &t.received_at {
+            events.push(CopyEvent::new(
+                received,
+                "transfer_received",
+                route.clone(),
+                Some(&t.id),
+            ));
+        }
+        if t.status == "cancelled" {
+            events.push(CopyEvent::new(
+                &t.updated_at,
+                "transfer_cancelled",
+                route,
+                Some(&t.id),
+            ));
+        }
+    }
+
+    if let Some(sold) = &found.sold_at {
+        events.push(CopyEvent::new(sold, "sold", None, None));
+    }
+    if let Some(withdrawn) = &found.withdrawn_at {
+        events.push(CopyEvent::new(
+            withdrawn,
+            "withdrawn",
+            found.weeding_reason.clone(),
+            None,
+        ));
+    }
+
+    // A loan or a transfer changes the copy's status as well; that change is
+    // already on the timeline under its cause.
+    let implied: HashSet<(String, &str)> = events
+        .iter()
+        .filter_map(|e| {
+            let status = match e.kind.as_str() {
+                "loaned" => "loaned",
+                "transfer_shipped" => "in_transit",
+                "returned" | "transfer_received" | "transfer_cancelled" => "available",
+                "sold" => "sold",
+                "withdrawn" => "withdrawn",
+                _ => return None,
+            };
+            Some((day(&e.at).to_string(), status))
+        })
+        .collect();
+
+    let logged = operation_log::Entity::find()
+        .filter(operation_log::Column::EntityType.eq("copy"))
+        .filter(operation_log::Column::EntityId.eq(copy_id))
+        .filter(operation_log::Column::Operation.eq("UPDATE"))
+        .order_by_asc(operation_log::Column::Id)
+        .all(db)
+        .await?;
+    for op in &logged {
+        let Some(status) = op
+            .payload
+            .as_deref()
+            .and_then(|p| serde_json::from_str::<serde_json::Value>(p).ok())
+            .and_then(|p| p["status"].as_str().map(str::to_string))
+        else {
+            continue;
+        };
+        if implied.contains(&(day(&op.created_at).to_string(), status.as_str())) {
+            continue;
+        }
+        events.push(CopyEvent::new(
+            &op.created_at,
+            "status_changed",
+            Some(status),
+            None,
+        ));
+    }
+
+    // Dates and timestamps share their leading `YYYY-MM-DD`, so they sort
+    // together; the sort is stable, keeping a loan before its return.
+    events.sort_by(|a, b| a.at.cmp(&b.at));
+    Ok(Some(events))
+}
+
+/// The `YYYY-MM-DD` part of a date or timestamp.
+fn day(at: &str) -> &str {
+    at.get(..10).unwrap_or(at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn history_merges_loans_transfers_and_status_changes() {
+        let db = setup().await;
+        assert!(copy_history(&db, "nope").await.unwrap().is_none());
+
+        let now = Utc::now().to_rfc3339();
+        let book = crate::models::book::ActiveModel {
+            title: Set("Dune".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let shelved = copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(1),
+            acquisition_date: Set(Some("2024-01-05".to_string())),
+            status: Set("available".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let alice = contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set("Alice".to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        loan::ActiveModel {
+            copy_id: Set(shelved.id.clone()),
+            contact_id: Set(alice.id),
+            library_id: Set(1),
+            loan_date: Set("2024-03-01".to_string()),
+            due_date: Set("2024-03-22".to_string()),
+            return_date: Set(Some("2024-03-20".to_string())),
+            status: Set("returned".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        copy_transfer::ActiveModel {
+            copy_id: Set(shelved.id.clone()),
+            from_library_id: Set(1),
+            to_library_id: Set(2),
+            status: Set("completed".to_string()),
+            requested_at: Set("2024-06-01T09:00:00+00:00".to_string()),
+            shipped_at: Set(Some("2024-06-02T09:00:00+00:00".to_string())),
+            received_at: Set(Some("2024-06-04T09:00:00+00:00".to_string())),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        crate::sync::log_operation(
+            &db,
+            "copy",
+            &shelved.id,
+            "UPDATE",
+            Some(serde_json::json!({ "status": "lost" })),
+        )
+        .await
+        .unwrap();
+
+        let events = copy_history(&db, &shelved.id).await.unwrap().unwrap();
+        let kinds: Vec<&str> = events.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "acquired",
+                "loaned",
+                "returned",
+                "transfer_requested",
+                "transfer_shipped",
+                "transfer_received",
+                "status_changed",
+            ]
+        );
+        assert_eq!(events[1].detail.as_deref(), Some("Alice"));
+        assert_eq!(events[6].detail.as_deref(), Some("lost"));
+    }
+}
@@ -0,0 +1,415 @@
+//! Copy Transfer Service - moving copies between libraries
+//! Mirrored from sale_service.rs
+//!
+//! A transfer is requested for an available copy, shipped (the copy becomes
+//! `in_transit`) and received at the destination, which moves the copy to the
+//! destination library and makes it available again. A transfer can be
+//! cancelled until it is received. Completed and cancelled transfers stay as
+//! the copy's transfer history.
+
+use sea_orm::*;
+
+use crate::models::copy::{self, Entity as Copy};
+use crate::models::copy_transfer::{self, Entity as CopyTransfer};
+use crate::models::library::Entity as Library;
+
+pub const STATUS_REQUESTED: &str = "requested";
+pub const STATUS_IN_TRANSIT: &str = "in_transit";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// Error type for service operations
+#[derive(Debug)]
+pub enum ServiceError {
+    Database(String),
+    NotFound,
+    InvalidState(String),
+}
+
+impl From<sea_orm::DbErr> for ServiceError {
+    fn from(e: sea_orm::DbErr) -> Self {
+        ServiceError::Database(e.to_string())
+    }
+}
+
+/// Filter parameters for listing transfers
+#[derive(Debug, Default, Clone)]
+pub struct TransferFilter {
+    /// Transfers leaving or reaching this library.
+    pub library_id: Option<i32>,
+    pub status: Option<String>,
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// List transfers, most recent request first
+pub async fn list_transfers(
+    db: &DatabaseConnection,
+    filter: TransferFilter,
+) -> Result<Vec<copy_transfer::Model>, ServiceError> {
+    let mut condition = Condition::all();
+
+    if let Some(library_id) = filter.library_id {
+        condition = condition.add(
+            Condition::any()
+                .add(copy_transfer::Column::FromLibraryId.eq(library_id))
+                .add(copy_transfer::Column::ToLibraryId.eq(library_id)),
+        );
+    }
+
+    if let Some(status) = filter.status {
+        condition = condition.add(copy_transfer::Column::Status.eq(status));
+    }
+
+    Ok(CopyTransfer::find()
+        .filter(condition)
+        .order_by_desc(copy_transfer::Column::RequestedAt)
+        .all(db)
+        .await?)
+}
+
+/// Transfer history of one copy, most recent request first
+pub async fn list_for_copy(
+    db: &DatabaseConnection,
+    copy_id: &str,
+) -> Result<Vec<copy_transfer::Model>, ServiceError> {
+    if Copy::find_by_id(copy_id.to_owned())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Err(ServiceError::NotFound);
+    }
+    Ok(CopyTransfer::find()
+        .filter(copy_transfer::Column::CopyId.eq(copy_id))
+        .order_by_desc(copy_transfer::Column::RequestedAt)
+        .all(db)
+        .await?)
+}
+
+/// Request moving an available copy to another library
+pub async fn request_transfer(
+    db: &DatabaseConnection,
+    copy_id: &str,
+    to_library_id: i32,
+    notes: Option<String>,
+) -> Result<copy_transfer::Model, ServiceError> {
+    let copy = Copy::find_by_id(copy_id.to_owned())
+        .one(db)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+
+    if copy.status != "available" {
+        return Err(ServiceError::InvalidState(format!(
+            "Only available copies can be transferred (copy is {})",
+            copy.status
+        )));
+    }
+    if copy.library_id == to_library_id {
+        return Err(ServiceError::InvalidState(
+            "Copy is already in the destination library".to_owned(),
+        ));
+    }
+    if Library::find_by_id(to_library_id).one(db).await?.is_none() {
+        return Err(ServiceError::InvalidState(format!(
+            "Library {to_library_id} does not exist"
+        )));
+    }
+    if open_transfer(db, copy_id).await?.is_some() {
+        return Err(ServiceError::InvalidState(
+            "Copy already has an open transfer".to_owned(),
+        ));
+    }
+
+    let now = now();
+    let transfer = copy_transfer::ActiveModel {
+        copy_id: Set(copy_id.to_owned()),
+        from_library_id: Set(copy.library_id),
+        to_library_id: Set(to_library_id),
+        status: Set(STATUS_REQUESTED.to_owned()),
+        notes: Set(notes),
+        requested_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    };
+    Ok(transfer.insert(db).await?)
+}
+
+/// Mark a requested transfer as shipped; the copy becomes `in_transit`
+pub async fn ship_transfer(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<copy_transfer::Model, ServiceError> {
+    let transfer = find_transfer(db, id).await?;
+    if transfer.status != STATUS_REQUESTED {
+        return Err(ServiceError::InvalidState(format!(
+            "Only requested transfers can be shipped (transfer is {})",
+            transfer.status
+        )));
+    }
+
+    let copy = Copy::find_by_id(transfer.copy_id.clone())
+        .one(db)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+    // The copy may have been lent or sold since the request.
+    if copy.status != "available" {
+        return Err(ServiceError::InvalidState(format!(
+            "Copy is no longer available (copy is {})",
+            copy.status
+        )));
+    }
+
+    let now = now();
+    let txn = db.begin().await?;
+    let mut copy_active: copy::ActiveModel = copy.into();
+    copy_active.status = Set(STATUS_IN_TRANSIT.to_owned());
+    copy_active.updated_at = Set(now.clone());
+    copy_active.update(&txn).await?;
+
+    let mut transfer_active: copy_transfer::ActiveModel = transfer.into();
+    transfer_active.status = Set(STATUS_IN_TRANSIT.to_owned());
+    transfer_active.shipped_at = Set(Some(now.clone()));
+    transfer_active.updated_at = Set(now);
+    let transfer = transfer_active.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(transfer)
+}
+
+/// Accept an in-transit transfer at its destination: the copy moves to the
+/// destination library and is available again
+pub async fn receive_transfer(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<copy_transfer::Model, ServiceError> {
+    let transfer = find_transfer(db, id).await?;
+    if transfer.status != STATUS_IN_TRANSIT {
+        return Err(ServiceError::InvalidState(format!(
+            "Only in-transit transfers can be received (transfer is {})",
+            transfer.status
+        )));
+    }
+
+    let copy = Copy::find_by_id(transfer.copy_id.clone())
+        .one(db)
+        .await?
+        .ok_or(ServiceError::NotFound)?;
+
+    let now = now();
+    let txn = db.begin().await?;
+    let mut copy_active: copy::ActiveModel = copy.into();
+    copy_active.library_id = Set(transfer.to_library_id);
+    copy_active.status = Set("available".to_owned());
+    copy_active.updated_at = Set(now.clone());
+    copy_active.update(&txn).await?;
+
+    let mut transfer_active: copy_transfer::ActiveModel = transfer.into();
+    transfer_active.status = Set(STATUS_COMPLETED.to_owned());
+    transfer_active.received_at = Set(Some(now.clone()));
+    transfer_active.updated_at = Set(now);
+    let transfer = transfer_active.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(transfer)
+}
+
+/// Cancel a transfer that was not received yet. A copy already shipped goes
+/// back to `available` in its original library.
+pub async fn cancel_transfer(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<copy_transfer::Model, ServiceError> {
+    let transfer = find_transfer(db, id).await?;
+    if transfer.status != STATUS_REQUESTED && transfer.status != STATUS_IN_TRANSIT {
+        return Err(ServiceError::InvalidState(format!(
+            "Transfer is already {}",
+            transfer.status
+        )));
+    }
+
+    let now = now();
+    let txn = db.begin().await?;
+    if transfer.status == STATUS_IN_TRANSIT
+        && let Some(copy_model) = Copy::find_by_id(transfer.copy_id.clone()).one(&txn).await?
+    {
+        let mut copy_active: copy::ActiveModel = copy_model.into();
+        copy_active.status = Set("available".to_owned());
+        copy_active.updated_at = Set(now.clone());
+        copy_active.update(&txn).await?;
+    }
+
+    let mut transfer_active: copy_transfer::ActiveModel = transfer.into();
+    transfer_active.status = Set(STATUS_CANCELLED.to_owned());
+    transfer_active.updated_at = Set(now);
+    let transfer = transfer_active.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(transfer)
+}
+
+async fn find_transfer(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<copy_transfer::Model, ServiceError> {
+    CopyTransfer::find_by_id(id.to_owned())
+        .one(db)
+        .await?
+        .ok_or(ServiceError::NotFound)
+}
+
+/// The requested or in-transit transfer of a copy, if any
+async fn open_transfer(
+    db: &DatabaseConnection,
+    copy_id: &str,
+) -> Result<Option<copy_transfer::Model>, ServiceError> {
+    Ok(CopyTransfer::find()
+        .filter(copy_transfer::Column::CopyId.eq(copy_id))
+        .filter(copy_transfer::Column::Status.is_in([STATUS_REQUESTED, STATUS_IN_TRANSIT]))
+        .one(db)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::book;
+
+    async fn setup_db() -> DatabaseConnection {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        // No owner user is seeded; keep the libraries.owner_id FK out of the way.
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            "PRAGMA foreign_keys = OFF".to_owned(),
+        ))
+        .await
+        .unwrap();
+        let now = now();
+        for (id, name) in [(1, "Main"), (2, "Annex")] {
+            db.execute(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                "INSERT INTO libraries (id, name, owner_id, created_at, updated_at) \
+                 VALUES (?, ?, 1, ?, ?)",
+                [
+                    id.into(),
+                    name.into(),
+                    now.clone().into(),
+                    now.clone().into(),
+                ],
+            ))
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    async fn insert_copy(db: &DatabaseConnection) -> String {
+        let now = now();
+        let book_id = crate::utils::uuid_gen::new_uuid_v7();
+        book::Entity::insert(book::ActiveModel {
+            id: Set(book_id.clone()),
+            title: Set("Transferred".to_owned()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        let copy_id = crate::utils::uuid_gen::new_uuid_v7();
+        copy::Entity::insert(copy::ActiveModel {
+            id: Set(copy_id.clone()),
+            book_id: Set(book_id),
+            library_id: Set(1),
+            status: Set("available".to_owned()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        })
+        .exec(db)
+        .await
+        .unwrap();
+        copy_id
+    }
+
+    async fn copy_state(db: &DatabaseConnection, id: &str) -> (i32, String) {
+        let copy = Copy::find_by_id(id.to_owned())
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap();
+        (copy.library_id, copy.status)
+    }
+
+    #[tokio::test]
+    async fn transfer_moves_copy_on_receipt_and_keeps_history() {
+        let db = setup_db().await;
+        let copy_id = insert_copy(&db).await;
+
+        let transfer = request_transfer(&db, &copy_id, 2, Some("for the annex".into()))
+            .await
+            .unwrap();
+        assert_eq!(transfer.status, STATUS_REQUESTED);
+        assert_eq!(transfer.from_library_id, 1);
+        assert!(matches!(
+            request_transfer(&db, &copy_id, 2, None).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+
+        ship_transfer(&db, &transfer.id).await.unwrap();
+        assert_eq!(copy_state(&db, &copy_id).await, (1, "in_transit".into()));
+        assert!(matches!(
+            ship_transfer(&db, &transfer.id).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+
+        let received = receive_transfer(&db, &transfer.id).await.unwrap();
+        assert_eq!(received.status, STATUS_COMPLETED);
+        assert!(received.shipped_at.is_some() && received.received_at.is_some());
+        assert_eq!(copy_state(&db, &copy_id).await, (2, "available".into()));
+
+        let history = list_for_copy(&db, &copy_id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        let to_annex = list_transfers(
+            &db,
+            TransferFilter {
+                library_id: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(to_annex.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_shipped_transfer_restores_the_copy() {
+        let db = setup_db().await;
+        let copy_id = insert_copy(&db).await;
+
+        assert!(matches!(
+            request_transfer(&db, &copy_id, 1, None).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+        assert!(matches!(
+            request_transfer(&db, &copy_id, 99, None).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+
+        let transfer = request_transfer(&db, &copy_id, 2, None).await.unwrap();
+        ship_transfer(&db, &transfer.id).await.unwrap();
+        let cancelled = cancel_transfer(&db, &transfer.id).await.unwrap();
+        assert_eq!(cancelled.status, STATUS_CANCELLED);
+        assert_eq!(copy_state(&db, &copy_id).await, (1, "available".into()));
+        assert!(matches!(
+            receive_transfer(&db, &transfer.id).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+
+        // A new transfer can be requested once the previous one is closed.
+        request_transfer(&db, &copy_id, 2, None).await.unwrap();
+    }
+}
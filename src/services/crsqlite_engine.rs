@@ -346,6 +346,7 @@ mod tests {
             name: Set("Jack London".to_owned()),
             created_at: Set("2026-06-29T00:00:00Z".to_owned()),
             updated_at: Set("2026-06-29T00:00:00Z".to_owned()),
+            ..Default::default()
         }
         .insert(eng.db())
         .await
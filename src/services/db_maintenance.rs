@@ -0,0 +1,214 @@
+//! Database upkeep: integrity check, orphan sweep, `ANALYZE` and `VACUUM`.
+//!
+//! Deleting books and copies leaves free pages in the SQLite file, and the
+//! query planner's statistics age as the catalogue grows. A maintenance run
+//! checks the file first (`PRAGMA integrity_check`) and stops there if it is
+//! damaged: repacking a corrupt database can only make recovery harder.
+//! Otherwise it removes orphans (`referential_integrity::sweep_orphans`),
+//! refreshes the statistics, rewrites the file and reports the space won.
+//!
+//! Orphans are only removed while the operation log has nothing pending: a
+//! copy received before its book is not an orphan yet. Runs on request
+//! (`POST /api/admin/maintenance`) and every `DB_MAINTENANCE_INTERVAL_DAYS`
+//! (7 by default, 0 to disable), never two at once.
+
+use std::sync::Mutex;
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::infrastructure::referential_integrity::{self, OrphanCounts};
+use crate::models::operation_log;
+
+const DEFAULT_INTERVAL_DAYS: u64 = 7;
+
+/// Leave the first scheduled run out of the startup rush.
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(3_600);
+
+/// What one run did.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MaintenanceReport {
+    /// RFC 3339
+    pub started_at: String,
+    pub finished_at: String,
+    /// `true` when `integrity_check` answered "ok"
+    pub integrity_ok: bool,
+    /// The problems `integrity_check` listed, empty when ok
+    pub integrity_errors: Vec<String>,
+    /// Orphans found, or removed when `orphans_removed`
+    pub orphans: OrphanCounts,
+    pub orphans_removed: bool,
+    /// Why orphans were only counted
+    pub orphans_skipped: Option<String>,
+    pub analyzed: bool,
+    pub vacuumed: bool,
+    /// Database size before and after, in bytes
+    pub size_before: i64,
+    pub size_after: i64,
+    pub reclaimed_bytes: i64,
+}
+
+/// How often the scheduled run comes, for the status endpoint.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct MaintenanceStatus {
+    /// 0: no scheduled run
+    pub interval_days: u64,
+    pub running: bool,
+    /// `None` until the first run since startup
+    pub last_run: Option<MaintenanceReport>,
+}
+
+static RUNNING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+static LAST_RUN: Mutex<Option<MaintenanceReport>> = Mutex::new(None);
+
+pub fn interval_days() -> u64 {
+    std::env::var("DB_MAINTENANCE_INTERVAL_DAYS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_DAYS)
+}
+
+pub fn status() -> MaintenanceStatus {
+    MaintenanceStatus {
+        interval_days: interval_days(),
+        running: RUNNING.try_lock().is_err(),
+        last_run: LAST_RUN.lock().ok().and_then(|last| last.clone()),
+    }
+}
+
+async fn pragma_i64(db: &DatabaseConnection, pragma: &str) -> Result<i64, DbErr> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            format!("PRAGMA {pragma}"),
+        ))
+        .await?
+        .ok_or_else(|| DbErr::Custom(format!("PRAGMA {pragma} returned no row")))?;
+    row.try_get_by_index::<i64>(0)
+}
+
+async fn database_size(db: &DatabaseConnection) -> Result<i64, DbErr> {
+    Ok(pragma_i64(db, "page_count").await? * pragma_i64(db, "page_size").await?)
+}
+
+async fn execute(db: &DatabaseConnection, sql: &str) -> Result<(), DbErr> {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Run maintenance now. `None` when a run is already under way.
+pub async fn run(db: &DatabaseConnection) -> Result<Option<MaintenanceReport>, DbErr> {
+    let Ok(_running) = RUNNING.try_lock() else {
+        return Ok(None);
+    };
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let size_before = database_size(db).await?;
+
+    let integrity_errors: Vec<String> = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "PRAGMA integrity_check".to_owned(),
+        ))
+        .await?
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .filter(|line| line != "ok")
+        .collect();
+    let integrity_ok = integrity_errors.is_empty();
+
+    let pending = operation_log::Entity::find()
+        .filter(operation_log::Column::Status.is_in(["pending", "pending_review"]))
+        .count(db)
+        .await?;
+    let orphans_skipped = if !integrity_ok {
+        Some("integrity check failed".to_string())
+    } else if pending > 0 {
+        Some(format!("{pending} sync operation(s) pending"))
+    } else {
+        None
+    };
+    let orphans_removed = orphans_skipped.is_none();
+    let orphans = {
+        let txn = db.begin().await?;
+        let counts = referential_integrity::sweep_orphans(&txn, orphans_removed).await?;
+        txn.commit().await?;
+        counts
+    };
+
+    if integrity_ok {
+        execute(db, "ANALYZE").await?;
+        execute(db, "VACUUM").await?;
+        // In WAL mode the file only shrinks once the log is folded back in.
+        let _ = execute(db, "PRAGMA wal_checkpoint(TRUNCATE)").await;
+    } else {
+        tracing::error!(
+            "db_maintenance: integrity check failed, database left untouched: {}",
+            integrity_errors.join("; ")
+        );
+    }
+
+    let size_after = database_size(db).await?;
+    let report = MaintenanceReport {
+        started_at,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+        integrity_ok,
+        integrity_errors,
+        orphans,
+        orphans_removed,
+        orphans_skipped,
+        analyzed: integrity_ok,
+        vacuumed: integrity_ok,
+        size_before,
+        size_after,
+        reclaimed_bytes: (size_before - size_after).max(0),
+    };
+    tracing::info!(
+        "db_maintenance: reclaimed {} byte(s), {} orphan cop(ies) {}",
+        report.reclaimed_bytes,
+        report.orphans.copies,
+        if orphans_removed { "removed" } else { "found" }
+    );
+    if let Ok(mut last) = LAST_RUN.lock() {
+        *last = Some(report.clone());
+    }
+    Ok(Some(report))
+}
+
+/// Spawn the scheduled run, unless `DB_MAINTENANCE_INTERVAL_DAYS` is 0.
+pub fn spawn(db: DatabaseConnection) {
+    let days = interval_days();
+    if days == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + STARTUP_DELAY;
+        let mut ticker =
+            tokio::time::interval_at(start, std::time::Duration::from_secs(days * 86_400));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run(&db).await {
+                tracing::warn!("db_maintenance: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_run_checks_sweeps_and_repacks() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let report = run(&db).await.unwrap().expect("no other run");
+        assert!(report.integrity_ok, "{:?}", report.integrity_errors);
+        assert!(report.vacuumed);
+        assert!(report.size_after > 0);
+        assert_eq!(status().last_run.unwrap().started_at, report.started_at);
+        assert!(!status().running);
+    }
+}
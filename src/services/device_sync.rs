@@ -0,0 +1,869 @@
+//! Same-owner device sync: one user's installs (phone, laptop, desktop)
+//! replicate each other's catalogue, reading progress and library settings.
+//!
+//! This is neither library-to-library P2P (`api::peer`, where the other side
+//! is someone else's library) nor hub-based account sync
+//! (`account_sync_engine`): two installs that trust each other exchange their
+//! operation logs directly, over the network they share.
+//!
+//! Pairing: one install shows a short-lived code ([`start_pairing`]), the
+//! other sends it back with its own address ([`pair_with`]). Both then keep
+//! the other in `linked_devices` with a shared secret that authenticates every
+//! later sync request (`x-device-uuid` + `x-device-secret`).
+//!
+//! Sync: each side pulls the other's local operations past its `pull_cursor`
+//! and pushes its own past its `push_cursor`. The log mostly records ids, so an
+//! exported INSERT or UPDATE carries a snapshot of the entity, stamped with
+//! `_origin_at`; the receiver queues it as a remote operation and
+//! `sync::processor` applies it, settling conflicts by last write wins on
+//! `updated_at`. Only operations made on an install are exported, never the
+//! ones it imported: with three installs, pair every one with every other.
+//!
+//! Revocation deletes the pairing on both sides; when the other side missed
+//! the notice, its next sync is refused with 401 and it drops the pairing too.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rand::Rng;
+use rand::rngs::OsRng;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::infrastructure::AppState;
+use crate::infrastructure::mcp_token::constant_time_eq;
+use crate::models::{
+    author, book, contact, copy, library_config, linked_device, loan, operation_log, tag,
+};
+
+/// How long a pairing code stays valid.
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(600);
+
+/// Pairing code length, from [`CODE_ALPHABET`].
+const PAIRING_CODE_LEN: usize = 8;
+
+/// No 0/O or 1/I: the code is read off one screen and typed on another.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Wrong guesses after which the current code is invalidated.
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+
+/// Operations per pull or push request.
+pub const SYNC_BATCH: u64 = 500;
+
+/// Time between two background syncs with every paired device.
+const SYNC_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Header carrying the sender's `library_uuid`.
+pub const DEVICE_UUID_HEADER: &str = "x-device-uuid";
+
+/// Header carrying the secret shared at pairing.
+pub const DEVICE_SECRET_HEADER: &str = "x-device-secret";
+
+/// Entity types replicated between paired devices.
+const SYNCED_ENTITY_TYPES: [&str; 12] = [
+    "book",
+    "copy",
+    "contact",
+    "loan",
+    "tag",
+    "author",
+    "book_author",
+    "book_tag",
+    "collection",
+    "collection_book",
+    "book_note",
+    "library_config",
+];
+
+#[derive(Debug)]
+pub enum DeviceSyncError {
+    Db(DbErr),
+    /// Unknown, expired or already used pairing code.
+    InvalidCode,
+    /// This install has no identity (library uuid, keys) yet.
+    NotIdentified,
+    /// Both ends of the pairing are the same install.
+    SelfPairing,
+    /// Unknown device or wrong secret, on either side.
+    Unauthorized,
+    NotFound,
+    /// The other device could not be reached.
+    Unreachable(String),
+    /// The other device answered, but not with what we expected.
+    Rejected(String),
+}
+
+impl std::fmt::Display for DeviceSyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::InvalidCode => write!(f, "invalid or expired pairing code"),
+            Self::NotIdentified => write!(f, "this device has no identity yet"),
+            Self::SelfPairing => write!(f, "a device cannot be paired with itself"),
+            Self::Unauthorized => write!(f, "device not paired"),
+            Self::NotFound => write!(f, "device not found"),
+            Self::Unreachable(e) => write!(f, "device unreachable: {e}"),
+            Self::Rejected(e) => write!(f, "device refused: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DeviceSyncError {}
+
+impl From<DbErr> for DeviceSyncError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+// ── Pairing ──────────────────────────────────────────────────────────
+
+struct PendingCode {
+    code: String,
+    expires_at: Instant,
+    failed_attempts: u32,
+}
+
+/// The code currently shown on this device, if any. Starting a new pairing
+/// replaces it.
+static PENDING_CODE: Mutex<Option<PendingCode>> = Mutex::new(None);
+
+/// A pairing code to show to the user.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_in_secs: u64,
+}
+
+/// What one device tells the other about itself when pairing.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeviceHello {
+    pub device_uuid: String,
+    pub name: String,
+    pub url: String,
+    /// Hex-encoded.
+    pub ed25519_public_key: String,
+    /// Hex-encoded.
+    pub x25519_public_key: String,
+}
+
+/// Body of `POST /api/devices/pair`.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PairRequest {
+    pub code: String,
+    pub device: DeviceHello,
+}
+
+/// Answer to `POST /api/devices/pair`.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PairResponse {
+    pub device: DeviceHello,
+    pub sync_secret: String,
+}
+
+/// Start pairing: a new single-use code, valid for ten minutes.
+pub fn start_pairing() -> PairingCode {
+    let code: String = (0..PAIRING_CODE_LEN)
+        .map(|_| CODE_ALPHABET[OsRng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect();
+    *PENDING_CODE.lock().unwrap_or_else(|e| e.into_inner()) = Some(PendingCode {
+        code: code.clone(),
+        expires_at: Instant::now() + PAIRING_CODE_TTL,
+        failed_attempts: 0,
+    });
+    PairingCode {
+        code,
+        expires_in_secs: PAIRING_CODE_TTL.as_secs(),
+    }
+}
+
+/// Consume the pending code if `code` matches it. Too many wrong guesses
+/// invalidate it.
+fn redeem_code(code: &str) -> Result<(), DeviceSyncError> {
+    let mut pending = PENDING_CODE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(current) = pending.as_mut() else {
+        return Err(DeviceSyncError::InvalidCode);
+    };
+    if current.expires_at <= Instant::now() {
+        *pending = None;
+        return Err(DeviceSyncError::InvalidCode);
+    }
+    let code = code.trim().to_uppercase();
+    if !constant_time_eq(code.as_bytes(), current.code.as_bytes()) {
+        current.failed_attempts += 1;
+        if current.failed_attempts >= MAX_PAIRING_ATTEMPTS {
+            *pending = None;
+        }
+        return Err(DeviceSyncError::InvalidCode);
+    }
+    *pending = None;
+    Ok(())
+}
+
+fn generate_secret() -> String {
+    use base64::Engine;
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn our_hello(state: &AppState) -> Result<DeviceHello, DeviceSyncError> {
+    let device_uuid = state
+        .identity_service
+        .library_uuid()
+        .ok_or(DeviceSyncError::NotIdentified)?
+        .to_string();
+    let (ed25519_public_key, x25519_public_key) = state
+        .identity_service
+        .get_public_keys_hex()
+        .map_err(|_| DeviceSyncError::NotIdentified)?;
+    let name = hostname::get()
+        .map(|h| h.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "BiblioGenius".to_string());
+    Ok(DeviceHello {
+        device_uuid,
+        name,
+        url: state.our_public_url(),
+        ed25519_public_key,
+        x25519_public_key,
+    })
+}
+
+/// Store (or refresh, when re-pairing) the other device. A fresh pairing
+/// restarts both cursors: replaying the whole log is safe, the import
+/// deduplicates.
+async fn upsert_device(
+    db: &DatabaseConnection,
+    hello: &DeviceHello,
+    secret: &str,
+) -> Result<linked_device::Model, DeviceSyncError> {
+    let invalid_key = |_| DeviceSyncError::Rejected("invalid public key".to_string());
+    let ed25519 = hex::decode(&hello.ed25519_public_key).map_err(invalid_key)?;
+    let x25519 = hex::decode(&hello.x25519_public_key).map_err(invalid_key)?;
+
+    let existing = linked_device::Entity::find()
+        .filter(linked_device::Column::DeviceUuid.eq(hello.device_uuid.as_str()))
+        .one(db)
+        .await?;
+    let mut active: linked_device::ActiveModel = match existing {
+        Some(device) => device.into(),
+        None => linked_device::ActiveModel {
+            device_uuid: Set(Some(hello.device_uuid.clone())),
+            created_at: Set(Utc::now().to_rfc3339()),
+            ..Default::default()
+        },
+    };
+    active.name = Set(hello.name.clone());
+    active.url = Set(Some(hello.url.trim_end_matches('/').to_string()));
+    active.ed25519_public_key = Set(ed25519);
+    active.x25519_public_key = Set(x25519);
+    active.sync_secret = Set(Some(secret.to_string()));
+    active.pull_cursor = Set(0);
+    active.push_cursor = Set(0);
+    Ok(active.save(db).await?.try_into_model()?)
+}
+
+/// Handle `POST /api/devices/pair` on the device showing the code.
+pub async fn accept_pairing(
+    state: &AppState,
+    request: PairRequest,
+) -> Result<PairResponse, DeviceSyncError> {
+    let hello = our_hello(state)?;
+    if request.device.device_uuid == hello.device_uuid {
+        return Err(DeviceSyncError::SelfPairing);
+    }
+    redeem_code(&request.code)?;
+
+    let secret = generate_secret();
+    let device = upsert_device(state.db(), &request.device, &secret).await?;
+    tracing::info!(
+        "Paired with device {} ({})",
+        device.name,
+        request.device.url
+    );
+    Ok(PairResponse {
+        device: hello,
+        sync_secret: secret,
+    })
+}
+
+/// Pair with the device at `url` showing `code`.
+pub async fn pair_with(
+    state: &AppState,
+    url: &str,
+    code: &str,
+) -> Result<linked_device::Model, DeviceSyncError> {
+    let url = crate::api::peer::validate_url(url.trim_end_matches('/'))
+        .map_err(DeviceSyncError::Unreachable)?;
+    let hello = our_hello(state)?;
+
+    let res = crate::api::peer::get_safe_client()
+        .post(format!("{url}/api/devices/pair"))
+        .json(&PairRequest {
+            code: code.to_string(),
+            device: hello.clone(),
+        })
+        .send()
+        .await
+        .map_err(|e| DeviceSyncError::Unreachable(e.to_string()))?;
+    match res.status() {
+        s if s.is_success() => {}
+        reqwest::StatusCode::UNAUTHORIZED => return Err(DeviceSyncError::InvalidCode),
+        s => return Err(DeviceSyncError::Rejected(format!("pairing answered {s}"))),
+    }
+    let answer: PairResponse = res
+        .json()
+        .await
+        .map_err(|e| DeviceSyncError::Rejected(e.to_string()))?;
+    if answer.device.device_uuid == hello.device_uuid {
+        return Err(DeviceSyncError::SelfPairing);
+    }
+
+    // Reach the device where we just did, whatever address it believes it has.
+    let mut device = answer.device;
+    device.url = url;
+    upsert_device(state.db(), &device, &answer.sync_secret).await
+}
+
+/// The paired device presenting `device_uuid` and `secret`.
+pub async fn authenticate(
+    db: &DatabaseConnection,
+    device_uuid: &str,
+    secret: &str,
+) -> Result<linked_device::Model, DeviceSyncError> {
+    let device = linked_device::Entity::find()
+        .filter(linked_device::Column::DeviceUuid.eq(device_uuid))
+        .one(db)
+        .await?
+        .ok_or(DeviceSyncError::Unauthorized)?;
+    match device.sync_secret.as_deref() {
+        Some(expected) if constant_time_eq(expected.as_bytes(), secret.as_bytes()) => Ok(device),
+        _ => Err(DeviceSyncError::Unauthorized),
+    }
+}
+
+/// Paired devices, oldest pairing first. Secrets are not serialized.
+pub async fn list_devices(db: &DatabaseConnection) -> Result<Vec<linked_device::Model>, DbErr> {
+    linked_device::Entity::find()
+        .order_by_asc(linked_device::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+// ── Operation exchange ───────────────────────────────────────────────
+
+/// One operation as exchanged between paired devices.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SyncOperation {
+    /// `operation_log.id` on the origin device.
+    pub id: i32,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub operation: String,
+    #[schema(value_type = Option<Object>)]
+    pub payload: Option<Value>,
+    pub created_at: String,
+}
+
+/// A page of operations. `latest_cursor` is the last `operation_log.id`
+/// covered, including operations left out because their entity is gone.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OperationBatch {
+    pub operations: Vec<SyncOperation>,
+    pub latest_cursor: i32,
+    pub has_more: bool,
+}
+
+/// Result of one sync with a device.
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct SyncReport {
+    pub pulled: usize,
+    pub pushed: usize,
+}
+
+/// Local operations after `since`, at most `limit`, with entity snapshots.
+pub async fn export_operations(
+    db: &DatabaseConnection,
+    since: i32,
+    limit: u64,
+) -> Result<OperationBatch, DbErr> {
+    let rows = operation_log::Entity::find()
+        .filter(operation_log::Column::Id.gt(since))
+        .filter(operation_log::Column::Source.eq("local"))
+        .filter(operation_log::Column::EntityType.is_in(SYNCED_ENTITY_TYPES))
+        .order_by_asc(operation_log::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    let has_more = rows.len() as u64 == limit;
+    let latest_cursor = rows.last().map_or(since, |op| op.id);
+    let mut operations = Vec::with_capacity(rows.len());
+    for op in rows {
+        let Some(mut payload) = snapshot_payload(db, &op).await? else {
+            continue;
+        };
+        if let Value::Object(ref mut map) = payload {
+            map.insert(
+                "_origin_at".to_string(),
+                Value::String(op.created_at.clone()),
+            );
+        }
+        operations.push(SyncOperation {
+            id: op.id,
+            entity_type: op.entity_type,
+            entity_id: op.entity_id,
+            operation: op.operation,
+            payload: Some(payload),
+            created_at: op.created_at,
+        });
+    }
+
+    Ok(OperationBatch {
+        operations,
+        latest_cursor,
+        has_more,
+    })
+}
+
+/// The payload to send for `op`. INSERTs and UPDATEs of row entities get the
+/// row as it is now; `None` when it has been deleted since (its DELETE
+/// follows in the log). Other operations keep their logged payload.
+async fn snapshot_payload(
+    db: &DatabaseConnection,
+    op: &operation_log::Model,
+) -> Result<Option<Value>, DbErr> {
+    let logged = || {
+        op.payload
+            .as_deref()
+            .and_then(|p| serde_json::from_str(p).ok())
+            .unwrap_or_else(|| Value::Object(serde_json::Map::new()))
+    };
+    if op.operation.eq_ignore_ascii_case("delete") {
+        return Ok(Some(logged()));
+    }
+
+    let id = op.entity_id.clone();
+    let snapshot = match op.entity_type.as_str() {
+        "book" => match book::Entity::find_by_id(id).one(db).await? {
+            Some(book) => {
+                let authors: Vec<String> = book
+                    .find_related(author::Entity)
+                    .all(db)
+                    .await?
+                    .into_iter()
+                    .map(|a| a.name)
+                    .collect();
+                let tags: Vec<String> = book
+                    .find_related(tag::Entity)
+                    .all(db)
+                    .await?
+                    .into_iter()
+                    .map(|t| t.name)
+                    .collect();
+                let mut value = serde_json::to_value(&book).unwrap_or_default();
+                value["authors"] = serde_json::json!(authors);
+                value["tags"] = serde_json::json!(tags);
+                Some(value)
+            }
+            None => None,
+        },
+        "copy" => match copy::Entity::find_by_id(id).one(db).await? {
+            Some(copy) => {
+                // The receiver resolves the book by ISBN or title first, in
+                // case it already had that book under another id.
+                let parent = book::Entity::find_by_id(copy.book_id.clone())
+                    .one(db)
+                    .await?;
+                let mut value = serde_json::to_value(&copy).unwrap_or_default();
+                if let Some(parent) = parent {
+                    value["book_isbn"] = serde_json::json!(parent.isbn);
+                    value["book_title"] = serde_json::json!(parent.title);
+                }
+                Some(value)
+            }
+            None => None,
+        },
+        "contact" => contact::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|c| serde_json::to_value(c).unwrap_or_default()),
+        "loan" => loan::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|l| serde_json::to_value(l).unwrap_or_default()),
+        "library_config" => library_config::Entity::find()
+            .one(db)
+            .await?
+            .map(|c| serde_json::to_value(c).unwrap_or_default()),
+        _ => Some(logged()),
+    };
+    Ok(snapshot)
+}
+
+/// Queue operations received from `device` for the processor. Returns how
+/// many were new (replays are deduplicated by `log_remote_operation`).
+pub async fn import_operations(
+    db: &DatabaseConnection,
+    device: &linked_device::Model,
+    operations: Vec<SyncOperation>,
+) -> Result<usize, DbErr> {
+    // `log_remote_operation` answers a replay with the id of the row it
+    // already has: anything newer than the current last row is new.
+    let newest_before = operation_log::Entity::find()
+        .order_by_desc(operation_log::Column::Id)
+        .one(db)
+        .await?
+        .map_or(0, |op| op.id);
+    let mut imported = std::collections::HashSet::new();
+    for op in operations {
+        if !SYNCED_ENTITY_TYPES.contains(&op.entity_type.as_str()) {
+            continue;
+        }
+        let id = crate::sync::log_remote_operation(
+            db,
+            &op.entity_type,
+            &op.entity_id,
+            &op.operation,
+            op.payload,
+            device.id,
+            false,
+        )
+        .await?;
+        if id > newest_before {
+            imported.insert(id);
+        }
+    }
+    Ok(imported.len())
+}
+
+/// Map a sync answer to an error. 401/403 mean the device dropped us.
+fn check_status(res: &reqwest::Response) -> Result<(), DeviceSyncError> {
+    match res.status() {
+        s if s.is_success() => Ok(()),
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            Err(DeviceSyncError::Unauthorized)
+        }
+        s => Err(DeviceSyncError::Rejected(format!("device answered {s}"))),
+    }
+}
+
+/// Pull the device's new operations, then push ours.
+pub async fn sync_with_device(
+    state: &AppState,
+    device: &linked_device::Model,
+) -> Result<SyncReport, DeviceSyncError> {
+    let (Some(url), Some(secret)) = (device.url.as_deref(), device.sync_secret.as_deref()) else {
+        return Err(DeviceSyncError::Rejected(
+            "linked without device sync; pair it again".to_string(),
+        ));
+    };
+    let our_uuid = state
+        .identity_service
+        .library_uuid()
+        .ok_or(DeviceSyncError::NotIdentified)?;
+    crate::api::peer::validate_url(url).map_err(DeviceSyncError::Unreachable)?;
+
+    let result = exchange(state.db(), device, url, our_uuid, secret).await;
+    if matches!(result, Err(DeviceSyncError::Unauthorized)) {
+        // The other side revoked the pairing while we could not be told.
+        tracing::warn!(
+            "Device {} no longer accepts us, dropping the pairing",
+            device.name
+        );
+        linked_device::Entity::delete_by_id(device.id)
+            .exec(state.db())
+            .await?;
+    }
+    result
+}
+
+async fn exchange(
+    db: &DatabaseConnection,
+    device: &linked_device::Model,
+    url: &str,
+    our_uuid: &str,
+    secret: &str,
+) -> Result<SyncReport, DeviceSyncError> {
+    let client = crate::api::peer::get_safe_client();
+    let endpoint = format!("{url}/api/devices/sync/operations");
+    let mut report = SyncReport::default();
+
+    let mut cursor = device.pull_cursor;
+    loop {
+        let res = client
+            .get(format!("{endpoint}?since={cursor}&limit={SYNC_BATCH}"))
+            .header(DEVICE_UUID_HEADER, our_uuid)
+            .header(DEVICE_SECRET_HEADER, secret)
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| DeviceSyncError::Unreachable(e.to_string()))?;
+        check_status(&res)?;
+        let batch: OperationBatch = res
+            .json()
+            .await
+            .map_err(|e| DeviceSyncError::Rejected(e.to_string()))?;
+
+        report.pulled += import_operations(db, device, batch.operations).await?;
+        if batch.latest_cursor <= cursor {
+            break;
+        }
+        cursor = batch.latest_cursor;
+        let mut active: linked_device::ActiveModel = device.clone().into();
+        active.pull_cursor = Set(cursor);
+        active.update(db).await?;
+        if !batch.has_more {
+            break;
+        }
+    }
+
+    let mut cursor = device.push_cursor;
+    loop {
+        let batch = export_operations(db, cursor, SYNC_BATCH).await?;
+        if batch.latest_cursor <= cursor {
+            break;
+        }
+        if !batch.operations.is_empty() {
+            let res = client
+                .post(&endpoint)
+                .header(DEVICE_UUID_HEADER, our_uuid)
+                .header(DEVICE_SECRET_HEADER, secret)
+                .json(&batch.operations)
+                .timeout(Duration::from_secs(30))
+                .send()
+                .await
+                .map_err(|e| DeviceSyncError::Unreachable(e.to_string()))?;
+            check_status(&res)?;
+            report.pushed += batch.operations.len();
+        }
+        cursor = batch.latest_cursor;
+        let mut active: linked_device::ActiveModel = device.clone().into();
+        active.push_cursor = Set(cursor);
+        active.update(db).await?;
+        if !batch.has_more {
+            break;
+        }
+    }
+
+    let mut active: linked_device::ActiveModel = device.clone().into();
+    active.last_synced = Set(Some(Utc::now().to_rfc3339()));
+    active.update(db).await?;
+    Ok(report)
+}
+
+/// Sync with the paired device `id` now.
+pub async fn sync_device(state: &AppState, id: i32) -> Result<SyncReport, DeviceSyncError> {
+    let device = linked_device::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or(DeviceSyncError::NotFound)?;
+    sync_with_device(state, &device).await
+}
+
+/// Sync with every device paired for device sync. Failures are logged: an
+/// offline laptop must not stop the phone from syncing with the desktop.
+pub async fn sync_all(state: &AppState) -> Result<(), DbErr> {
+    let devices = linked_device::Entity::find()
+        .filter(linked_device::Column::SyncSecret.is_not_null())
+        .filter(linked_device::Column::Url.is_not_null())
+        .all(state.db())
+        .await?;
+    for device in devices {
+        match sync_with_device(state, &device).await {
+            Ok(report) if report.pulled + report.pushed > 0 => tracing::info!(
+                "device_sync: {} pulled {}, pushed {}",
+                device.name,
+                report.pulled,
+                report.pushed
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("device_sync: {}: {e}", device.name),
+        }
+    }
+    Ok(())
+}
+
+// ── Revocation ───────────────────────────────────────────────────────
+
+/// Unpair device `id`: tell it (best effort), then forget it.
+pub async fn revoke(state: &AppState, id: i32) -> Result<(), DeviceSyncError> {
+    let device = linked_device::Entity::find_by_id(id)
+        .one(state.db())
+        .await?
+        .ok_or(DeviceSyncError::NotFound)?;
+
+    if let (Some(url), Some(secret), Some(our_uuid)) = (
+        device.url.as_deref(),
+        device.sync_secret.as_deref(),
+        state.identity_service.library_uuid(),
+    ) && crate::api::peer::validate_url(url).is_ok()
+    {
+        let notice = crate::api::peer::get_safe_client()
+            .post(format!("{url}/api/devices/sync/revoke"))
+            .header(DEVICE_UUID_HEADER, our_uuid)
+            .header(DEVICE_SECRET_HEADER, secret)
+            .send()
+            .await;
+        if let Err(e) = notice {
+            tracing::info!(
+                "Device {} not told about the revocation ({e}); its next sync will be refused",
+                device.name
+            );
+        }
+    }
+
+    linked_device::Entity::delete_by_id(device.id)
+        .exec(state.db())
+        .await?;
+    Ok(())
+}
+
+/// Handle the other side's revocation notice.
+pub async fn receive_revocation(
+    db: &DatabaseConnection,
+    device: &linked_device::Model,
+) -> Result<(), DbErr> {
+    tracing::info!("Device {} revoked the pairing", device.name);
+    linked_device::Entity::delete_by_id(device.id)
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Spawn the background task: a sync with every paired device every five
+/// minutes.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SYNC_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = sync_all(&state).await {
+                tracing::warn!("device_sync: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    async fn paired_device(db: &DatabaseConnection, secret: &str) -> linked_device::Model {
+        linked_device::ActiveModel {
+            name: Set("laptop".to_string()),
+            ed25519_public_key: Set(vec![1; 32]),
+            x25519_public_key: Set(vec![2; 32]),
+            device_uuid: Set(Some("laptop-uuid".to_string())),
+            url: Set(Some("http://192.168.1.20:8000".to_string())),
+            sync_secret: Set(Some(secret.to_string())),
+            created_at: Set(Utc::now().to_rfc3339()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert device")
+    }
+
+    #[test]
+    fn pairing_code_is_single_use_and_locks_after_failed_attempts() {
+        let code = start_pairing().code;
+        assert_eq!(code.len(), PAIRING_CODE_LEN);
+        assert!(matches!(
+            redeem_code("WRONG123"),
+            Err(DeviceSyncError::InvalidCode)
+        ));
+        redeem_code(&code.to_lowercase()).expect("right code, any case");
+        assert!(matches!(
+            redeem_code(&code),
+            Err(DeviceSyncError::InvalidCode)
+        ));
+
+        let code = start_pairing().code;
+        for _ in 0..MAX_PAIRING_ATTEMPTS {
+            let _ = redeem_code("WRONG123");
+        }
+        assert!(matches!(
+            redeem_code(&code),
+            Err(DeviceSyncError::InvalidCode)
+        ));
+    }
+
+    #[tokio::test]
+    async fn authenticate_checks_the_shared_secret() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let device = paired_device(&db, "s3cret").await;
+
+        let found = authenticate(&db, "laptop-uuid", "s3cret").await.unwrap();
+        assert_eq!(found.id, device.id);
+        assert!(matches!(
+            authenticate(&db, "laptop-uuid", "guess").await,
+            Err(DeviceSyncError::Unauthorized)
+        ));
+        assert!(matches!(
+            authenticate(&db, "other-uuid", "s3cret").await,
+            Err(DeviceSyncError::Unauthorized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn exported_book_is_replicated_under_the_same_id() {
+        let phone = db::init_db("sqlite::memory:").await.expect("init db");
+        let laptop = db::init_db("sqlite::memory:").await.expect("init db");
+
+        let book = book::ActiveModel {
+            title: Set("The Left Hand of Darkness".to_string()),
+            isbn: Set(Some("9780441478125".to_string())),
+            reading_status: Set("reading".to_string()),
+            owned: Set(false),
+            created_at: Set(Utc::now().to_rfc3339()),
+            updated_at: Set(Utc::now().to_rfc3339()),
+            ..Default::default()
+        }
+        .insert(&phone)
+        .await
+        .unwrap();
+        crate::sync::log_operation(&phone, "book", &book.id, "INSERT", None)
+            .await
+            .unwrap();
+        // Deleted since: its INSERT is left out, the cursor still covers it.
+        crate::sync::log_operation(&phone, "book", "gone", "INSERT", None)
+            .await
+            .unwrap();
+
+        let batch = export_operations(&phone, 0, SYNC_BATCH).await.unwrap();
+        assert_eq!(batch.operations.len(), 1);
+        assert!(!batch.has_more);
+        let payload = batch.operations[0].payload.as_ref().unwrap();
+        assert_eq!(payload["id"], book.id.as_str());
+        assert!(payload["_origin_at"].is_string());
+
+        let device = paired_device(&laptop, "s3cret").await;
+        let imported = import_operations(&laptop, &device, batch.operations.clone())
+            .await
+            .unwrap();
+        assert_eq!(imported, 1);
+        // Pulling the same page again queues nothing new.
+        let replayed = import_operations(&laptop, &device, batch.operations)
+            .await
+            .unwrap();
+        assert_eq!(replayed, 0);
+
+        crate::sync::processor::process_pending(&laptop)
+            .await
+            .unwrap();
+        let replica = book::Entity::find_by_id(book.id.clone())
+            .one(&laptop)
+            .await
+            .unwrap()
+            .expect("book replicated under its id");
+        assert_eq!(replica.title, book.title);
+        assert_eq!(replica.reading_status, "reading");
+    }
+}
@@ -0,0 +1,189 @@
+//! Free digitized editions of a book.
+//!
+//! Gathered from the providers that have them: the Gallica page a BNF result
+//! linked to the book (`books.digital_source_url`), then Gallica, the
+//! Internet Archive and Wikisource searched by title and author in parallel.
+//! A provider that fails only leaves its own editions out.
+//!
+//! Searches match loosely, so a result is kept only when its title contains
+//! the book's title (or the other way round, for a book catalogued with a
+//! subtitle).
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{author, book};
+use crate::modules::integrations::{bnf, gallica, internet_archive, wikisource};
+
+/// A free digitized edition.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct DigitalEdition {
+    /// `gallica`, `internet_archive` or `wikisource`
+    pub provider: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub year: Option<String>,
+    pub url: String,
+}
+
+/// Lowercased words, punctuation dropped.
+fn fold(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn same_title(book_title: &str, title: &str) -> bool {
+    let (a, b) = (fold(book_title), fold(title));
+    !a.is_empty() && !b.is_empty() && (b.contains(&a) || a.contains(&b))
+}
+
+/// Language of the Wikisource to search: the ISBN group's, else the title's,
+/// else English.
+fn wikisource_lang(isbn: Option<&str>, title: &str) -> String {
+    crate::utils::lang::target_summary_language(isbn.unwrap_or_default(), title, &[])
+        .map(|l| crate::utils::lang::to_iso639_1(&l))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Keep the editions matching the title, the first of each URL.
+fn merge(book_title: &str, editions: Vec<DigitalEdition>) -> Vec<DigitalEdition> {
+    let mut seen = std::collections::HashSet::new();
+    editions
+        .into_iter()
+        .filter(|e| same_title(book_title, &e.title))
+        .filter(|e| seen.insert(e.url.clone()))
+        .collect()
+}
+
+/// Free digitized editions of the book `id`; `None` when there is no such
+/// book.
+pub async fn digital_editions(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<Option<Vec<DigitalEdition>>, DbErr> {
+    let Some(model) = book::Entity::find_by_id(id.to_string()).one(db).await? else {
+        return Ok(None);
+    };
+    let author = model
+        .find_related(author::Entity)
+        .one(db)
+        .await?
+        .map(|a| a.name);
+    let title = model.title.as_str();
+    let author = author.as_deref();
+    let lang = wikisource_lang(model.isbn.as_deref(), title);
+
+    let (gallica_res, archive_res, wikisource_res) = tokio::join!(
+        gallica::search_digitized(title, author),
+        internet_archive::search_texts(title, author),
+        wikisource::search_pages(&lang, title, author),
+    );
+
+    let mut editions = Vec::new();
+    if let Some(url) = model.digital_source_url.clone() {
+        editions.push(DigitalEdition {
+            provider: "gallica".to_string(),
+            title: model.title.clone(),
+            author: author.map(str::to_string),
+            year: model.publication_year.map(|y| y.to_string()),
+            url,
+        });
+    }
+    match gallica_res {
+        Ok(documents) => editions.extend(documents.into_iter().map(|d| DigitalEdition {
+            provider: "gallica".to_string(),
+            title: d.title,
+            author: d.author.as_deref().and_then(bnf::normalize_author),
+            year: d.date,
+            url: d.url,
+        })),
+        Err(e) => tracing::debug!("Gallica search failed for book {}: {}", id, e),
+    }
+    match archive_res {
+        Ok(texts) => editions.extend(texts.into_iter().map(|t| DigitalEdition {
+            provider: "internet_archive".to_string(),
+            url: t.url(),
+            title: t.title,
+            author: t.author.as_deref().and_then(bnf::normalize_author),
+            year: t.year,
+        })),
+        Err(e) => tracing::debug!("Internet Archive search failed for book {}: {}", id, e),
+    }
+    match wikisource_res {
+        Ok(pages) => editions.extend(pages.into_iter().map(|p| DigitalEdition {
+            provider: "wikisource".to_string(),
+            title: p.title,
+            author: None,
+            year: None,
+            url: p.url,
+        })),
+        Err(e) => tracing::debug!("Wikisource search failed for book {}: {}", id, e),
+    }
+
+    Ok(Some(merge(&model.title, editions)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edition(provider: &str, title: &str, url: &str) -> DigitalEdition {
+        DigitalEdition {
+            provider: provider.to_string(),
+            title: title.to_string(),
+            author: None,
+            year: None,
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_matching_titles_once_per_url() {
+        let merged = merge(
+            "Les Misérables",
+            vec![
+                edition(
+                    "gallica",
+                    "Les Misérables",
+                    "https://gallica.bnf.fr/ark:/12148/a",
+                ),
+                edition(
+                    "gallica",
+                    "Les misérables. Tome 1",
+                    "https://gallica.bnf.fr/ark:/12148/a",
+                ),
+                edition(
+                    "gallica",
+                    "Les misérables. Tome 2",
+                    "https://gallica.bnf.fr/ark:/12148/b",
+                ),
+                edition(
+                    "internet_archive",
+                    "Notre-Dame de Paris",
+                    "https://archive.org/details/c",
+                ),
+                edition(
+                    "wikisource",
+                    "Les Misérables/Tome 1",
+                    "https://fr.wikisource.org/wiki/d",
+                ),
+            ],
+        );
+        let urls: Vec<&str> = merged.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://gallica.bnf.fr/ark:/12148/a",
+                "https://gallica.bnf.fr/ark:/12148/b",
+                "https://fr.wikisource.org/wiki/d",
+            ]
+        );
+        assert_eq!(
+            wikisource_lang(Some("9782070409228"), "Les Misérables"),
+            "fr"
+        );
+    }
+}
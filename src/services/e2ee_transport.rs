@@ -103,7 +103,7 @@ impl DirectTransport {
         crypto_service: Arc<CryptoService<SqliteNonceStore>>,
         timeout: std::time::Duration,
     ) -> Self {
-        let http_client = reqwest::Client::builder()
+        let http_client = crate::services::network_mode::client_builder()
             .timeout(timeout)
             .redirect(reqwest::redirect::Policy::none())
             .build()
@@ -0,0 +1,414 @@
+//! Overdue fines and the fee ledger.
+//!
+//! With a `fine_per_day` set in the loan settings, an overdue loan accrues a
+//! fine once its grace period is over, capped at `max_fine`. Each such loan
+//! has one `fine` entry in its borrower's ledger, kept up to date by an
+//! hourly sweep while the loan is out and settled by `settle_returned_loan`
+//! when it comes back. Payments are recorded by hand as `payment` entries. Setting
+//! `fine_per_day` back to 0 stops new accrual; fines already in the ledger
+//! stay. Amounts are in the library's currency (`utils::money`).
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::domain::{LoanSettings, LoanSettingsRepository};
+use crate::models::{contact, fee_ledger, loan};
+use crate::utils::money::{self, Money};
+use crate::utils::time_zone;
+
+/// A contact's ledger, oldest entry first.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ContactFees {
+    pub contact_id: String,
+    pub fined: Money,
+    pub paid: Money,
+    /// What the contact still owes; negative when they paid in advance.
+    pub balance: Money,
+    #[schema(value_type = Vec<FeeLedgerEntry>)]
+    pub entries: Vec<fee_ledger::Model>,
+}
+
+/// A contact who owes something.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Debtor {
+    pub contact_id: String,
+    pub name: String,
+    pub balance: Money,
+}
+
+/// Totals over every ledger, with the contacts who still owe, largest
+/// balance first.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct FeesReport {
+    pub fined: Money,
+    pub paid: Money,
+    pub outstanding: Money,
+    pub debtors: Vec<Debtor>,
+}
+
+/// The `YYYY-MM-DD` date of a loan date, whatever its stored format.
+fn day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+async fn settings(db: &DatabaseConnection) -> Result<LoanSettings, DbErr> {
+    crate::infrastructure::SeaOrmLoanSettingsRepository::new(db.clone())
+        .get_settings()
+        .await
+        .map_err(|e| DbErr::Custom(e.to_string()))
+}
+
+/// Bring the fine of one loan up to date: counted until its return, or until
+/// `today` while it is out. Returns whether the ledger changed.
+pub async fn accrue_loan(
+    db: &DatabaseConnection,
+    settings: &LoanSettings,
+    loan: &loan::Model,
+    today: NaiveDate,
+) -> Result<bool, DbErr> {
+    let Some(due) = day(&loan.due_date) else {
+        return Ok(false);
+    };
+    let until = loan.return_date.as_deref().and_then(day).unwrap_or(today);
+    let amount = settings.fine(due, until);
+    if amount <= 0.0 {
+        return Ok(false);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let existing = fee_ledger::Entity::find()
+        .filter(fee_ledger::Column::LoanId.eq(loan.id.as_str()))
+        .filter(fee_ledger::Column::Kind.eq(fee_ledger::KIND_FINE))
+        .one(db)
+        .await?;
+    match existing {
+        Some(fine) if fine.amount == amount => Ok(false),
+        Some(fine) => {
+            let mut active: fee_ledger::ActiveModel = fine.into();
+            active.amount = Set(amount);
+            active.updated_at = Set(now);
+            active.update(db).await?;
+            Ok(true)
+        }
+        None => {
+            fee_ledger::ActiveModel {
+                id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+                contact_id: Set(loan.contact_id.clone()),
+                loan_id: Set(Some(loan.id.clone())),
+                kind: Set(fee_ledger::KIND_FINE.to_string()),
+                amount: Set(amount),
+                note: Set(None),
+                created_at: Set(now.clone()),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?;
+            Ok(true)
+        }
+    }
+}
+
+/// Settle the fine of a loan just returned.
+pub async fn settle_returned_loan(
+    db: &DatabaseConnection,
+    loan: &loan::Model,
+) -> Result<(), DbErr> {
+    let settings = settings(db).await?;
+    let today = time_zone::library_zone(db).await?.today();
+    accrue_loan(db, &settings, loan, today).await?;
+    Ok(())
+}
+
+/// Run one sweep over the loans still out. Returns the number of fines
+/// created or raised.
+pub async fn accrue_once(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let settings = settings(db).await?;
+    if settings.fine_per_day <= 0.0 {
+        return Ok(0);
+    }
+    let today = time_zone::library_zone(db).await?.today();
+    let out = loan::Entity::find()
+        .filter(loan::Column::Status.is_in(["active", "overdue"]))
+        .filter(loan::Column::DueDate.lt(today.format("%Y-%m-%d").to_string()))
+        .all(db)
+        .await?;
+
+    let mut changed = 0;
+    for l in &out {
+        if accrue_loan(db, &settings, l, today).await? {
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Spawn the background task: one sweep at startup, then hourly.
+pub fn spawn(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(3_600));
+        loop {
+            ticker.tick().await;
+            match accrue_once(&db).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("fines: {n} fine(s) accrued"),
+                Err(e) => tracing::warn!("fines: {e}"),
+            }
+        }
+    });
+}
+
+fn totals(entries: &[fee_ledger::Model]) -> (f64, f64) {
+    entries.iter().fold((0.0, 0.0), |(fined, paid), e| {
+        if e.kind == fee_ledger::KIND_PAYMENT {
+            (fined, paid + e.amount)
+        } else {
+            (fined + e.amount, paid)
+        }
+    })
+}
+
+/// The ledger of a contact. `None` when there is no such contact.
+pub async fn contact_fees(
+    db: &DatabaseConnection,
+    contact_id: &str,
+) -> Result<Option<ContactFees>, DbErr> {
+    if contact::Entity::find_by_id(contact_id.to_string())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let mut entries = fee_ledger::Entity::find()
+        .filter(fee_ledger::Column::ContactId.eq(contact_id))
+        .order_by_asc(fee_ledger::Column::CreatedAt)
+        .all(db)
+        .await?;
+    let (fined, paid) = totals(&entries);
+    let currency = money::library_currency(db).await?;
+    // Dates as the library reads them, with their offset.
+    let zone = time_zone::library_zone(db).await?;
+    for e in &mut entries {
+        e.created_at = zone.format(&e.created_at);
+        e.updated_at = zone.format(&e.updated_at);
+    }
+    Ok(Some(ContactFees {
+        contact_id: contact_id.to_string(),
+        fined: Money::new(fined, currency),
+        paid: Money::new(paid, currency),
+        balance: Money::new(fined - paid, currency),
+        entries,
+    }))
+}
+
+/// What a contact still owes.
+pub async fn balance(db: &DatabaseConnection, contact_id: &str) -> Result<Money, DbErr> {
+    let entries = fee_ledger::Entity::find()
+        .filter(fee_ledger::Column::ContactId.eq(contact_id))
+        .all(db)
+        .await?;
+    let (fined, paid) = totals(&entries);
+    Ok(Money::new(fined - paid, money::library_currency(db).await?))
+}
+
+/// Record a payment by a contact. `None` when there is no such contact.
+pub async fn record_payment(
+    db: &DatabaseConnection,
+    contact_id: &str,
+    amount: f64,
+    note: Option<String>,
+) -> Result<Option<fee_ledger::Model>, DbErr> {
+    if contact::Entity::find_by_id(contact_id.to_string())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let currency = money::library_currency(db).await?;
+    let now = Utc::now().to_rfc3339();
+    let entry = fee_ledger::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        contact_id: Set(contact_id.to_string()),
+        loan_id: Set(None),
+        kind: Set(fee_ledger::KIND_PAYMENT.to_string()),
+        amount: Set(money::round(amount, currency)),
+        note: Set(note),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+    Ok(Some(entry))
+}
+
+/// Totals over every ledger, and who still owes.
+pub async fn report(db: &DatabaseConnection) -> Result<FeesReport, DbErr> {
+    let entries = fee_ledger::Entity::find().all(db).await?;
+    let mut by_contact: HashMap<String, Vec<fee_ledger::Model>> = HashMap::new();
+    for e in entries {
+        by_contact.entry(e.contact_id.clone()).or_default().push(e);
+    }
+    let names: HashMap<String, String> = contact::Entity::find()
+        .filter(contact::Column::Id.is_in(by_contact.keys().cloned().collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let currency = money::library_currency(db).await?;
+    let (mut total_fined, mut total_paid, mut outstanding) = (0.0, 0.0, 0.0);
+    let mut debtors = Vec::new();
+    for (contact_id, entries) in by_contact {
+        let (fined, paid) = totals(&entries);
+        total_fined += fined;
+        total_paid += paid;
+        let balance = Money::new(fined - paid, currency);
+        if balance.amount > 0.0 {
+            outstanding += balance.amount;
+            debtors.push(Debtor {
+                name: names.get(&contact_id).cloned().unwrap_or_default(),
+                contact_id,
+                balance,
+            });
+        }
+    }
+    debtors.sort_by(|a, b| {
+        b.balance
+            .amount
+            .total_cmp(&a.balance.amount)
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(FeesReport {
+        fined: Money::new(total_fined, currency),
+        paid: Money::new(total_paid, currency),
+        outstanding: Money::new(outstanding, currency),
+        debtors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn overdue_loans_accrue_one_fine_each_and_payments_reduce_the_balance() {
+        let db = setup().await;
+        let now = Utc::now().to_rfc3339();
+        crate::infrastructure::SeaOrmLoanSettingsRepository::new(db.clone())
+            .update_settings(LoanSettings {
+                fine_per_day: 0.5,
+                max_fine: 5.0,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let alice = contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set("Alice".to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let book = crate::models::book::ActiveModel {
+            title: Set("Dune".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let mut copy_ids = Vec::new();
+        for _ in 0..2 {
+            let shelved = crate::models::copy::ActiveModel {
+                book_id: Set(book.id.clone()),
+                library_id: Set(1),
+                status: Set("loaned".to_string()),
+                is_temporary: Set(false),
+                created_at: Set(now.clone()),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            copy_ids.push(shelved.id);
+        }
+
+        let today = time_zone::LibraryZone::Host.today();
+        let due = (today - chrono::Duration::days(4))
+            .format("%Y-%m-%d")
+            .to_string();
+        let late = loan::ActiveModel {
+            copy_id: Set(copy_ids[0].clone()),
+            contact_id: Set(alice.id.clone()),
+            library_id: Set(1),
+            loan_date: Set("2020-01-01".to_string()),
+            due_date: Set(due),
+            status: Set("active".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        loan::ActiveModel {
+            copy_id: Set(copy_ids[1].clone()),
+            contact_id: Set(alice.id.clone()),
+            library_id: Set(1),
+            loan_date: Set("2020-01-01".to_string()),
+            due_date: Set("2020-01-21".to_string()),
+            status: Set("active".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(accrue_once(&db).await.unwrap(), 2);
+        // Nothing new on a second sweep the same day.
+        assert_eq!(accrue_once(&db).await.unwrap(), 0);
+        assert_eq!(balance(&db, &alice.id).await.unwrap().amount, 7.0);
+
+        record_payment(&db, &alice.id, 4.0, Some("cash".to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+        let fees = contact_fees(&db, &alice.id).await.unwrap().unwrap();
+        assert_eq!(
+            (fees.fined.amount, fees.paid.amount, fees.balance.amount),
+            (7.0, 4.0, 3.0)
+        );
+        assert_eq!(fees.balance.currency.code, "EUR");
+        assert_eq!(fees.entries.len(), 3);
+        assert!(
+            fees.entries
+                .iter()
+                .any(|e| e.loan_id.as_deref() == Some(late.id.as_str()) && e.amount == 2.0)
+        );
+
+        let report = report(&db).await.unwrap();
+        assert_eq!(report.outstanding.amount, 3.0);
+        assert_eq!(report.debtors.len(), 1);
+        assert_eq!(report.debtors[0].name, "Alice");
+    }
+}
@@ -0,0 +1,189 @@
+//! Contact coordinates from their postal address.
+//!
+//! Contacts carry `latitude`/`longitude` for the map, but nobody types
+//! coordinates. When the `contact_geocoding` flag is set in the installation
+//! profile's `enabled_modules` (off by default: it sends contact addresses to
+//! OpenStreetMap's Nominatim), a contact saved with an address and without
+//! coordinates, or whose address changed while its coordinates did not, is
+//! geocoded right after the save. `POST /api/contacts/geocode` fills in the
+//! contacts saved before, or all of them with `force`.
+//!
+//! Geocoding never fails a save: a lookup error is logged and the contact
+//! kept as saved.
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{contact, installation_profile};
+use crate::modules::integrations::nominatim::{self, AddressQuery};
+
+/// `enabled_modules` flag turning geocoding on.
+pub const MODULE_FLAG: &str = "contact_geocoding";
+
+/// Whether the owner opted into geocoding.
+pub async fn is_enabled(db: &DatabaseConnection) -> bool {
+    if let Ok(Some(profile)) = installation_profile::Entity::find().one(db).await {
+        return profile.enabled_modules.contains(MODULE_FLAG);
+    }
+    false
+}
+
+fn address_of(c: &contact::Model) -> AddressQuery {
+    AddressQuery {
+        street: c.street_address.clone(),
+        postal_code: c.postal_code.clone(),
+        city: c.city.clone(),
+        country: c.country.clone(),
+    }
+}
+
+/// Whether `after`, as just saved over `before` (`None` for a new contact),
+/// should be geocoded.
+fn needs_geocoding(before: Option<&contact::Model>, after: &contact::Model) -> bool {
+    if address_of(after).is_too_vague() {
+        return false;
+    }
+    let missing = after.latitude.is_none() || after.longitude.is_none();
+    match before {
+        None => missing,
+        Some(before) => {
+            let moved = address_of(before) != address_of(after);
+            let kept_coordinates =
+                (before.latitude, before.longitude) == (after.latitude, after.longitude);
+            missing || (moved && kept_coordinates)
+        }
+    }
+}
+
+async fn store(
+    db: &DatabaseConnection,
+    contact: contact::Model,
+    (latitude, longitude): (f64, f64),
+) -> Result<contact::Model, DbErr> {
+    let mut active: contact::ActiveModel = contact.into();
+    active.latitude = Set(Some(latitude));
+    active.longitude = Set(Some(longitude));
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    active.update(db).await
+}
+
+/// Geocode `contact`, just saved over `before`, when enabled and needed.
+/// Returns the contact as it now stands.
+pub async fn after_save(
+    db: &DatabaseConnection,
+    before: Option<&contact::Model>,
+    contact: contact::Model,
+) -> contact::Model {
+    if !needs_geocoding(before, &contact) || !is_enabled(db).await {
+        return contact;
+    }
+    match nominatim::geocode(&address_of(&contact)).await {
+        Ok(Some(coordinates)) => {
+            let fallback = contact.clone();
+            store(db, contact, coordinates).await.unwrap_or_else(|e| {
+                tracing::warn!("geocoding: cannot store coordinates: {e}");
+                fallback
+            })
+        }
+        Ok(None) => contact,
+        Err(e) => {
+            tracing::warn!("geocoding: contact {}: {e}", contact.id);
+            contact
+        }
+    }
+}
+
+/// Outcome of a batch run.
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct GeocodeReport {
+    pub geocoded: usize,
+    /// Addresses Nominatim does not know
+    pub not_found: usize,
+    pub failed: usize,
+    /// Contacts whose address lacks a city and a postal code
+    pub skipped: usize,
+}
+
+/// Geocode the active contacts without coordinates, or every active contact
+/// with an address when `force`. Requests are spaced by Nominatim's rate
+/// limit, so this takes about a second per contact.
+pub async fn geocode_all(db: &DatabaseConnection, force: bool) -> Result<GeocodeReport, DbErr> {
+    let mut query = contact::Entity::find().filter(contact::Column::IsActive.eq(true));
+    if !force {
+        query = query.filter(
+            Condition::any()
+                .add(contact::Column::Latitude.is_null())
+                .add(contact::Column::Longitude.is_null()),
+        );
+    }
+    let mut report = GeocodeReport::default();
+    for contact in query.all(db).await? {
+        let address = address_of(&contact);
+        if address.is_too_vague() {
+            report.skipped += 1;
+            continue;
+        }
+        match nominatim::geocode(&address).await {
+            Ok(Some(coordinates)) => {
+                let stored = store(db, contact, coordinates).await?;
+                let _ = crate::sync::log_operation(db, "contact", &stored.id, "UPDATE", None).await;
+                report.geocoded += 1;
+            }
+            Ok(None) => report.not_found += 1,
+            Err(e) => {
+                tracing::warn!("geocoding: contact {}: {e}", contact.id);
+                report.failed += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact_at(city: Option<&str>, coordinates: Option<(f64, f64)>) -> contact::Model {
+        contact::Model {
+            id: "c1".to_string(),
+            r#type: "Borrower".to_string(),
+            name: "Alice".to_string(),
+            first_name: None,
+            email: None,
+            phone: None,
+            address: None,
+            street_address: Some("3 rue Oberkampf".to_string()),
+            postal_code: None,
+            city: city.map(str::to_string),
+            country: Some("France".to_string()),
+            latitude: coordinates.map(|c| c.0),
+            longitude: coordinates.map(|c| c.1),
+            notes: None,
+            user_id: None,
+            library_owner_id: 1,
+            is_active: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+            member_number: None,
+            member_since: None,
+            membership_expires_at: None,
+            membership_fee_paid: None,
+        }
+    }
+
+    #[test]
+    fn only_new_or_moved_addresses_are_geocoded() {
+        let paris = contact_at(Some("Paris"), Some((48.86, 2.37)));
+
+        assert!(needs_geocoding(None, &contact_at(Some("Paris"), None)));
+        assert!(!needs_geocoding(None, &contact_at(None, None)));
+        assert!(!needs_geocoding(None, &paris));
+        assert!(!needs_geocoding(Some(&paris), &paris));
+        // Moved, coordinates left as they were: stale.
+        let lyon = contact_at(Some("Lyon"), Some((48.86, 2.37)));
+        assert!(needs_geocoding(Some(&paris), &lyon));
+        // Moved with coordinates given by the client: kept.
+        let placed = contact_at(Some("Lyon"), Some((45.76, 4.83)));
+        assert!(!needs_geocoding(Some(&paris), &placed));
+    }
+}
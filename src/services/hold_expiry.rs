@@ -0,0 +1,177 @@
+//! Releases P2P requests left unclaimed on the hold shelf.
+//!
+//! An owner can mark an incoming request `ready` (pickup or shipping pending)
+//! instead of accepting it outright: a copy is reserved and the request gets a
+//! `hold_expires_at` from `loan_settings.hold_expiry_days`. Once that instant
+//! has passed without a handover, this task moves the request to `expired`,
+//! puts the copy back to `available`, tells the requester and offers the copy
+//! to the next waitlisted request (`p2p_holds`). Expiry instants are compared
+//! as times, not as text: one written with an offset other than UTC still
+//! expires when it should.
+
+use crate::infrastructure::AppState;
+use crate::models::{p2p_request, peer};
+use crate::utils::time_zone;
+use sea_orm::*;
+
+/// Run one sweep. Returns the number of holds released.
+pub async fn expire_holds_once(state: &AppState) -> Result<u64, DbErr> {
+    let db = state.db();
+    let now_at = chrono::Utc::now();
+    let now = now_at.to_rfc3339();
+    let zone = time_zone::library_zone(db).await?;
+    let expired: Vec<p2p_request::Model> = p2p_request::Entity::find()
+        .filter(p2p_request::Column::Status.eq("ready"))
+        .filter(p2p_request::Column::HoldExpiresAt.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|r| {
+            r.hold_expires_at
+                .as_deref()
+                .and_then(|t| time_zone::parse_timestamp(t, zone))
+                .is_some_and(|t| t < now_at)
+        })
+        .collect();
+
+    let mut released = 0;
+    for req in expired {
+        crate::api::peer::release_held_copy(db, req.held_copy_id.as_deref()).await;
+
+        let mut active: p2p_request::ActiveModel = req.clone().into();
+        active.status = Set("expired".to_string());
+        active.held_copy_id = Set(None);
+        active.hold_expires_at = Set(None);
+        active.updated_at = Set(now.clone());
+        active.update(db).await?;
+        released += 1;
+        tracing::info!("hold_expiry: released hold for request {}", req.id);
+
+        if let Some(peer) = peer::Entity::find_by_id(req.from_peer_id).one(db).await? {
+            crate::api::peer::send_request_status_update(
+                state,
+                &peer,
+                &req,
+                "expired",
+                serde_json::json!({}),
+            )
+            .await;
+        }
+    }
+    // Released copies go to the peers waiting in line for them.
+    if released > 0 {
+        crate::services::p2p_holds::serve_waitlist(state).await?;
+    }
+    Ok(released)
+}
+
+/// Spawn the background task: one sweep at startup, then hourly.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(3_600));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = expire_holds_once(&state).await {
+                tracing::warn!("hold_expiry: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::models::{book, copy};
+
+    async fn held_request(db: &DatabaseConnection, expires_in_days: i64) -> (String, String) {
+        let now = chrono::Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some("978-1".to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert book");
+        let copy = copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(crate::utils::library_helpers::resolve_library_id(db)
+                .await
+                .expect("library")),
+            status: Set("reserved".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert copy");
+        let peer = peer::ActiveModel {
+            name: Set(format!("Bob {expires_in_days}")),
+            url: Set(format!("http://127.0.0.1:9/{expires_in_days}")),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert peer");
+        let expires_at =
+            (chrono::Utc::now() + chrono::Duration::days(expires_in_days)).to_rfc3339();
+        let req = p2p_request::ActiveModel {
+            id: Set(format!("req-{expires_in_days}")),
+            from_peer_id: Set(peer.id),
+            book_isbn: Set("978-1".to_string()),
+            book_title: Set("Dune".to_string()),
+            status: Set("ready".to_string()),
+            held_copy_id: Set(Some(copy.id.clone())),
+            hold_expires_at: Set(Some(expires_at)),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert request");
+        (req.id, copy.id)
+    }
+
+    #[tokio::test]
+    async fn expired_hold_releases_the_copy_and_live_hold_stays() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let state = AppState::new(db.clone());
+        let (lapsed_id, lapsed_copy) = held_request(&db, -1).await;
+        let (live_id, live_copy) = held_request(&db, 3).await;
+
+        assert_eq!(expire_holds_once(&state).await.unwrap(), 1);
+
+        let lapsed = p2p_request::Entity::find_by_id(lapsed_id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lapsed.status, "expired");
+        assert_eq!(lapsed.held_copy_id, None);
+        let copy = copy::Entity::find_by_id(lapsed_copy)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.status, "available");
+
+        let live = p2p_request::Entity::find_by_id(live_id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(live.status, "ready");
+        let copy = copy::Entity::find_by_id(live_copy)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.status, "reserved");
+    }
+}
@@ -216,7 +216,7 @@ fn parse_catalog_entries(payload: &str) -> Result<Vec<CatalogEntry>, serde_json:
         .into_iter()
         .filter_map(
             |value| match serde_json::from_value::<CatalogEntry>(value.clone()) {
-                Ok(entry) => Some(entry),
+                Ok(entry) => Some(sanitize_entry(entry)),
                 Err(e) => {
                     let isbn = match value.get("isbn") {
                         Some(serde_json::Value::String(s)) if !s.is_empty() => s.clone(),
@@ -227,20 +227,35 @@ fn parse_catalog_entries(payload: &str) -> Result<Vec<CatalogEntry>, serde_json:
                         }
                     };
                     tracing::warn!("directory catalog entry {isbn} degraded to ISBN-only: {e}");
-                    Some(CatalogEntry {
+                    Some(sanitize_entry(CatalogEntry {
                         isbn,
                         book_id: None,
                         title: String::new(),
                         author: None,
                         cover_url: None,
                         added_at: None,
-                    })
+                    }))
                 }
             },
         )
         .collect())
 }
 
+/// Catalogs are written by other libraries: see `utils::sanitize`.
+fn sanitize_entry(entry: CatalogEntry) -> CatalogEntry {
+    use crate::utils::sanitize::{
+        MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_TITLE_CHARS, MAX_URL_CHARS, line, opt_line,
+    };
+    CatalogEntry {
+        isbn: line(&entry.isbn, MAX_CODE_CHARS),
+        book_id: opt_line(entry.book_id, MAX_CODE_CHARS),
+        title: line(&entry.title, MAX_TITLE_CHARS),
+        author: opt_line(entry.author, MAX_NAME_CHARS),
+        cover_url: opt_line(entry.cover_url, MAX_URL_CHARS),
+        added_at: opt_line(entry.added_at, MAX_CODE_CHARS),
+    }
+}
+
 /// Result of `push_catalog`: whether the catalog was actually sent or the
 /// push was skipped because the hub already has the same content.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -391,7 +406,7 @@ pub struct HubDirectoryService {
 
 impl HubDirectoryService {
     pub fn new() -> Self {
-        let http_client = Client::builder()
+        let http_client = crate::services::network_mode::client_builder()
             .user_agent("BiblioGenius/1.0")
             .timeout(std::time::Duration::from_secs(10))
             .redirect(reqwest::redirect::Policy::none())
@@ -0,0 +1,192 @@
+//! Background refresh of this library's hub directory follows.
+//!
+//! Listing peers used to wait on the hub and quietly return less when it was
+//! slow or down. The hub is now only called from here: every fifteen minutes,
+//! or on demand through `POST /api/hub/sync`, the libraries we follow and
+//! those following us are fetched, each call bounded by `CALL_TIMEOUT` and
+//! retried with backoff on network errors and hub 5xx. The outcome is cached
+//! with the time and error of the last attempt, which `GET /api/peers`
+//! reports as `meta.hub` instead of swallowing it.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::infrastructure::AppState;
+use crate::services::hub_directory_service::{HubDirectoryError, HubFollow};
+
+/// Time between two refreshes.
+const SYNC_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Delay before the first refresh, so startup (relay setup, which sets
+/// `HUB_URL`) settles first.
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// Upper bound on one hub call, retries aside.
+const CALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Tries per call, the first included.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the second try, doubled before each next one.
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Where the last refresh left us.
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct HubSyncStatus {
+    /// `false` until this library is registered with a hub directory
+    pub configured: bool,
+    /// RFC 3339
+    pub last_attempt_at: Option<String>,
+    /// RFC 3339
+    pub last_success_at: Option<String>,
+    /// Why the last attempt failed; `None` once one succeeds
+    pub last_error: Option<String>,
+    /// Libraries we follow, as of the last success
+    pub following: usize,
+    /// Libraries following us, as of the last success
+    pub followers: usize,
+}
+
+#[derive(Default)]
+struct Cache {
+    status: HubSyncStatus,
+    following: Vec<HubFollow>,
+    followers: Vec<HubFollow>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn with_cache<T>(f: impl FnOnce(&mut Cache) -> T) -> T {
+    let mut guard = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(Cache::default))
+}
+
+/// The state of the last refresh, without calling the hub.
+pub fn status() -> HubSyncStatus {
+    with_cache(|c| c.status.clone())
+}
+
+/// Follows as of the last successful refresh: `(following, followers)`.
+pub fn cached_follows() -> (Vec<HubFollow>, Vec<HubFollow>) {
+    with_cache(|c| (c.following.clone(), c.followers.clone()))
+}
+
+/// Worth another try: the hub was unreachable, slow or failing, not refusing.
+fn retryable(e: &HubDirectoryError) -> bool {
+    match e {
+        HubDirectoryError::Network(_) => true,
+        HubDirectoryError::Hub(code, _) => *code >= 500,
+        HubDirectoryError::NotRegistered | HubDirectoryError::Config(_) => false,
+    }
+}
+
+/// Run `call` with a timeout, retrying what `retryable` allows.
+async fn with_retries<T, F, Fut>(what: &str, mut call: F) -> Result<T, HubDirectoryError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, HubDirectoryError>>,
+{
+    let mut backoff = RETRY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let result = match tokio::time::timeout(CALL_TIMEOUT, call()).await {
+            Ok(result) => result,
+            Err(_) => Err(HubDirectoryError::Network(format!(
+                "{what}: no answer within {}s",
+                CALL_TIMEOUT.as_secs()
+            ))),
+        };
+        match result {
+            Err(e) if attempt < MAX_ATTEMPTS && retryable(&e) => {
+                tracing::debug!("hub_sync: {what} failed (attempt {attempt}): {e}");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            other => return other,
+        }
+    }
+}
+
+/// Refresh the cache from the hub now. Returns the new status; a failure is
+/// recorded in it as well as returned.
+pub async fn refresh(state: &AppState) -> Result<HubSyncStatus, HubDirectoryError> {
+    let db = state.db();
+    let hub = &state.hub_directory;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let fetched = async {
+        let following = with_retries("following", || hub.list_following(db)).await?;
+        let followers = with_retries("followers", || hub.list_followers(db)).await?;
+        Ok::<_, HubDirectoryError>((following, followers))
+    }
+    .await;
+
+    with_cache(|c| {
+        c.status.last_attempt_at = Some(now.clone());
+        match fetched {
+            Ok((following, followers)) => {
+                c.status.configured = true;
+                c.status.last_success_at = Some(now);
+                c.status.last_error = None;
+                c.status.following = following.len();
+                c.status.followers = followers.len();
+                c.following = following;
+                c.followers = followers;
+                Ok(c.status.clone())
+            }
+            // Not registered, or no hub configured: nothing to sync, which
+            // is no failure.
+            Err(HubDirectoryError::NotRegistered | HubDirectoryError::Config(_)) => {
+                c.status = HubSyncStatus {
+                    last_attempt_at: c.status.last_attempt_at.take(),
+                    ..HubSyncStatus::default()
+                };
+                c.following.clear();
+                c.followers.clear();
+                Ok(c.status.clone())
+            }
+            Err(e) => {
+                c.status.configured = true;
+                c.status.last_error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    })
+}
+
+/// Spawn the background task: a first refresh shortly after startup, then
+/// every fifteen minutes.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + STARTUP_DELAY;
+        let mut ticker = tokio::time::interval_at(start, SYNC_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = refresh(&state).await {
+                tracing::warn!("hub_sync: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn an_unregistered_library_has_nothing_to_sync() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let state = AppState::new(db);
+
+        let status = refresh(&state).await.unwrap();
+        assert!(!status.configured);
+        assert!(status.last_attempt_at.is_some());
+        assert_eq!(status.last_error, None);
+
+        assert!(retryable(&HubDirectoryError::Hub(503, String::new())));
+        assert!(!retryable(&HubDirectoryError::Hub(401, String::new())));
+    }
+}
@@ -118,6 +118,12 @@ impl IdentityService {
             })
             .await?;
 
+        if let Some(uuid) = self.library_uuid()
+            && let Err(e) = crate::services::mdns::announce_library_uuid(uuid)
+        {
+            tracing::warn!("mDNS: cannot announce the library UUID: {}", e);
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,343 @@
+//! Image processing shared by cover uploads and the scanner endpoints:
+//! rotate, crop, resize, EXIF strip and conversion to JPEG, PNG or WebP.
+//!
+//! Every image is decoded with its EXIF orientation baked in and re-encoded
+//! from pixels, so no metadata (GPS position, camera serial) survives. The
+//! longest edge kept and the space stored covers may take come from the
+//! configuration (`image_max_dimension`, `image_storage_quota_mb`).
+
+use std::io::Cursor;
+use std::path::Path;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::config::Config;
+use crate::utils::cover_image;
+
+/// JPEG quality of processed images (0-100).
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug)]
+pub enum ImageError {
+    TooLarge,
+    /// Not an image we can decode.
+    Unreadable(String),
+    Invalid(String),
+    QuotaExceeded {
+        used: u64,
+        quota: u64,
+    },
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge => write!(
+                f,
+                "image exceeds {} bytes",
+                cover_image::COVER_MAX_INPUT_BYTES
+            ),
+            Self::Unreadable(e) => write!(f, "unreadable image: {e}"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+            Self::QuotaExceeded { used, quota } => write!(
+                f,
+                "image storage quota reached ({used} of {quota} bytes used)"
+            ),
+            Self::Io(e) => write!(f, "storage error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Jpeg,
+    Png,
+    /// Lossless WebP
+    Webp,
+}
+
+impl OutputFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// What to do to an image, in this order: rotate, crop, fit within the box.
+/// Read from the query string of the endpoints that take an image.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams)]
+pub struct ImageOps {
+    /// Clockwise turn after the EXIF orientation: 90, 180 or 270
+    pub rotate: Option<u16>,
+    /// `x,y,width,height` in pixels of the turned image
+    pub crop: Option<String>,
+    /// Fit within this width, keeping the aspect ratio
+    pub max_width: Option<u32>,
+    /// Fit within this height, keeping the aspect ratio
+    pub max_height: Option<u32>,
+    /// `jpeg` (default), `png` or `webp`
+    pub format: Option<OutputFormat>,
+}
+
+/// The configured limits images are processed within.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageLimits {
+    pub max_dimension: u32,
+    pub storage_quota_bytes: Option<u64>,
+}
+
+impl ImageLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_dimension: config.image_max_dimension,
+            storage_quota_bytes: config.image_storage_quota_mb.map(|mb| mb * 1024 * 1024),
+        }
+    }
+
+    pub fn runtime() -> Self {
+        Self::from_config(Config::runtime())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub bytes: Vec<u8>,
+    pub format: OutputFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn parse_crop(crop: &str) -> Result<[u32; 4], ImageError> {
+    let parts: Vec<u32> = crop
+        .split(',')
+        .map(|p| p.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ImageError::Invalid("crop must be x,y,width,height".to_string()))?;
+    match parts[..] {
+        [x, y, w, h] if w > 0 && h > 0 => Ok([x, y, w, h]),
+        _ => Err(ImageError::Invalid(
+            "crop must be x,y,width,height".to_string(),
+        )),
+    }
+}
+
+/// Decode `input` upright and apply `ops`, never keeping an edge longer than
+/// the configured maximum. CPU-bound; run it on a blocking thread.
+pub fn transform(
+    input: &[u8],
+    ops: &ImageOps,
+    limits: &ImageLimits,
+) -> Result<DynamicImage, ImageError> {
+    if input.len() > cover_image::COVER_MAX_INPUT_BYTES {
+        return Err(ImageError::TooLarge);
+    }
+    let mut img = cover_image::decode_oriented(input).map_err(ImageError::Unreadable)?;
+
+    img = match ops.rotate.unwrap_or(0) {
+        0 => img,
+        90 => img.rotate90(),
+        180 => img.rotate180(),
+        270 => img.rotate270(),
+        _ => {
+            return Err(ImageError::Invalid(
+                "rotate must be 90, 180 or 270".to_string(),
+            ));
+        }
+    };
+
+    if let Some(crop) = &ops.crop {
+        let [x, y, w, h] = parse_crop(crop)?;
+        let (width, height) = img.dimensions();
+        if x.saturating_add(w) > width || y.saturating_add(h) > height {
+            return Err(ImageError::Invalid(format!(
+                "crop falls outside the {width}x{height} image"
+            )));
+        }
+        img = img.crop_imm(x, y, w, h);
+    }
+
+    let box_w = ops.max_width.unwrap_or(u32::MAX).min(limits.max_dimension);
+    let box_h = ops.max_height.unwrap_or(u32::MAX).min(limits.max_dimension);
+    if box_w == 0 || box_h == 0 {
+        return Err(ImageError::Invalid(
+            "max_width and max_height must be positive".to_string(),
+        ));
+    }
+    let (width, height) = img.dimensions();
+    if width > box_w || height > box_h {
+        img = img.resize(box_w, box_h, FilterType::Lanczos3);
+    }
+    Ok(img)
+}
+
+/// Encode `img` as `format`. The encoders write pixels only: no EXIF.
+pub fn encode(img: &DynamicImage, format: OutputFormat) -> Result<Vec<u8>, ImageError> {
+    let mut buf = Cursor::new(Vec::new());
+    let encoded = match format {
+        // JPEG has no alpha channel.
+        OutputFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, JPEG_QUALITY)),
+        OutputFormat::Png => img.write_with_encoder(PngEncoder::new(&mut buf)),
+        OutputFormat::Webp => DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut buf)),
+    };
+    encoded.map_err(|e| ImageError::Invalid(format!("encode {}: {e}", format.extension())))?;
+    Ok(buf.into_inner())
+}
+
+/// `transform` then `encode`, in the format `ops` asks for.
+pub fn process(
+    input: &[u8],
+    ops: &ImageOps,
+    limits: &ImageLimits,
+) -> Result<ProcessedImage, ImageError> {
+    let img = transform(input, ops, limits)?;
+    let format = ops.format.unwrap_or_default();
+    Ok(ProcessedImage {
+        bytes: encode(&img, format)?,
+        format,
+        width: img.width(),
+        height: img.height(),
+    })
+}
+
+/// Bytes taken by the files directly inside `dir`; 0 when it does not exist.
+pub async fn storage_used(dir: &Path) -> Result<u64, ImageError> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let mut used = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let meta = entry.metadata().await?;
+        if meta.is_file() {
+            used += meta.len();
+        }
+    }
+    Ok(used)
+}
+
+/// Refuse to store `incoming` more bytes in `dir` past the configured quota.
+/// `replacing` is what the write frees (the files it overwrites).
+pub async fn check_quota(
+    dir: &Path,
+    incoming: u64,
+    replacing: u64,
+    limits: &ImageLimits,
+) -> Result<(), ImageError> {
+    let Some(quota) = limits.storage_quota_bytes else {
+        return Ok(());
+    };
+    let used = storage_used(dir).await?;
+    if used.saturating_sub(replacing) + incoming > quota {
+        return Err(ImageError::QuotaExceeded { used, quota });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: ImageLimits = ImageLimits {
+        max_dimension: 500,
+        storage_quota_bytes: Some(1000),
+    };
+
+    fn png(w: u32, h: u32) -> Vec<u8> {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(w, h, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 90])
+        }));
+        encode(&img, OutputFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn rotates_crops_and_fits_within_the_limit() {
+        let ops = ImageOps {
+            rotate: Some(90),
+            crop: Some("0,0,600,800".to_string()),
+            format: Some(OutputFormat::Webp),
+            ..Default::default()
+        };
+
+        // 1000x600 turned is 600x1000; the crop keeps 600x800, the 500px
+        // limit scales it to 375x500.
+        let out = process(&png(1000, 600), &ops, &LIMITS).unwrap();
+
+        assert_eq!((out.width, out.height), (375, 500));
+        assert_eq!(&out.bytes[..4], b"RIFF");
+        let decoded = image::load_from_memory(&out.bytes).unwrap();
+        assert_eq!(decoded.dimensions(), (375, 500));
+    }
+
+    #[test]
+    fn rejects_bad_operations() {
+        let input = png(100, 100);
+        for ops in [
+            ImageOps {
+                rotate: Some(45),
+                ..Default::default()
+            },
+            ImageOps {
+                crop: Some("50,50,60,10".to_string()),
+                ..Default::default()
+            },
+            ImageOps {
+                crop: Some("1,2,3".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                process(&input, &ops, &LIMITS),
+                Err(ImageError::Invalid(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn quota_counts_what_a_write_replaces() {
+        let dir = std::env::temp_dir().join(format!("bg_image_quota_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(check_quota(&dir, 900, 0, &LIMITS).await.is_ok());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.jpg"), vec![0u8; 600]).unwrap();
+
+        assert!(matches!(
+            check_quota(&dir, 500, 0, &LIMITS).await,
+            Err(ImageError::QuotaExceeded {
+                used: 600,
+                quota: 1000
+            })
+        ));
+        assert!(check_quota(&dir, 500, 600, &LIMITS).await.is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
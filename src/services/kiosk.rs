@@ -0,0 +1,261 @@
+//! Kiosk mode: restricted tokens for a shared tablet at a lending desk.
+//!
+//! The owner API is loopback-only, so a tablet on the LAN cannot use it. A
+//! kiosk token opens a small surface instead (`/api/kiosk/*`): search the
+//! catalogue, check a copy out to a typed name and check it back in, both by
+//! scanning a barcode. Nothing else is reachable with it.
+//!
+//! A scanned code is either a copy id (printed as a label) or an ISBN (the
+//! book's own barcode). Only the SHA-256 of a token is stored, so a leaked
+//! database does not leak working tokens.
+
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use sha2::{Digest, Sha256};
+
+use crate::models::{book, copy, kiosk_token, loan};
+
+/// Why a scanned code did not lead to one copy.
+#[derive(Debug)]
+pub enum KioskError {
+    Db(DbErr),
+    /// Neither a copy id nor a known ISBN.
+    UnknownCode,
+    /// The book is known but has no copy in the wanted status.
+    NoCopy(&'static str),
+    /// An ISBN scanned at check-in while several copies of it are out.
+    Ambiguous,
+}
+
+impl std::fmt::Display for KioskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::UnknownCode => write!(f, "no copy matches this code"),
+            Self::NoCopy(status) => write!(f, "no copy of this book is {status}"),
+            Self::Ambiguous => write!(
+                f,
+                "several copies of this book are out, scan the copy label instead"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KioskError {}
+
+impl From<DbErr> for KioskError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+pub(crate) fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub(crate) fn generate_token() -> String {
+    use base64::Engine;
+    use rand::RngCore;
+    use rand::rngs::OsRng;
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Create a kiosk token. The returned token is the only copy of it.
+pub async fn create_token(
+    db: &DatabaseConnection,
+    name: &str,
+) -> Result<(kiosk_token::Model, String), DbErr> {
+    let token = generate_token();
+    let model = kiosk_token::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        name: Set(name.trim().to_string()),
+        token_hash: Set(hash_token(&token)),
+        created_at: Set(Utc::now().to_rfc3339()),
+        last_used_at: Set(None),
+    }
+    .insert(db)
+    .await?;
+    Ok((model, token))
+}
+
+/// Kiosk tokens, oldest first.
+pub async fn list_tokens(db: &DatabaseConnection) -> Result<Vec<kiosk_token::Model>, DbErr> {
+    kiosk_token::Entity::find()
+        .order_by_asc(kiosk_token::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// Revoke a kiosk token. Returns false when there was no such token.
+pub async fn revoke_token(db: &DatabaseConnection, id: &str) -> Result<bool, DbErr> {
+    let res = kiosk_token::Entity::delete_by_id(id.to_string())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// The kiosk token matching `token`, with its last use recorded.
+pub async fn authenticate(
+    db: &DatabaseConnection,
+    token: &str,
+) -> Result<Option<kiosk_token::Model>, DbErr> {
+    if token.is_empty() {
+        return Ok(None);
+    }
+    let Some(found) = kiosk_token::Entity::find()
+        .filter(kiosk_token::Column::TokenHash.eq(hash_token(token)))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let mut active: kiosk_token::ActiveModel = found.into();
+    active.last_used_at = Set(Some(Utc::now().to_rfc3339()));
+    active.update(db).await.map(Some)
+}
+
+/// Books whose ISBN, written with or without separators, is `code`.
+async fn books_by_isbn(db: &DatabaseConnection, code: &str) -> Result<Vec<String>, DbErr> {
+    Ok(book::Entity::find()
+        .filter(Expr::cust_with_values(
+            "REPLACE(REPLACE(isbn, '-', ''), ' ', '') = ?",
+            [code.replace(['-', ' '], "")],
+        ))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| b.id)
+        .collect())
+}
+
+/// The copy a scanned code designates, among the copies in `status`.
+///
+/// A copy id designates that copy whatever its status (the caller reports a
+/// wrong status). An ISBN designates the first copy in `status`; for a
+/// check-in it must be the only one, since returning the wrong copy would
+/// close someone else's loan.
+pub async fn copy_for_code(
+    db: &DatabaseConnection,
+    code: &str,
+    status: &'static str,
+) -> Result<copy::Model, KioskError> {
+    let code = code.trim();
+    if let Some(copy) = copy::Entity::find_by_id(code.to_string()).one(db).await? {
+        return Ok(copy);
+    }
+
+    let book_ids = books_by_isbn(db, code).await?;
+    if book_ids.is_empty() {
+        return Err(KioskError::UnknownCode);
+    }
+    let mut copies = copy::Entity::find()
+        .filter(copy::Column::BookId.is_in(book_ids))
+        .filter(copy::Column::Status.eq(status))
+        .order_by_asc(copy::Column::CreatedAt)
+        .limit(2)
+        .all(db)
+        .await?;
+    match (copies.len(), status) {
+        (0, _) => Err(KioskError::NoCopy(status)),
+        (2, "loaned") => Err(KioskError::Ambiguous),
+        _ => Ok(copies.remove(0)),
+    }
+}
+
+/// The open loan of a copy, if any.
+pub async fn active_loan(
+    db: &DatabaseConnection,
+    copy_id: &str,
+) -> Result<Option<loan::Model>, DbErr> {
+    loan::Entity::find()
+        .filter(loan::Column::CopyId.eq(copy_id))
+        .filter(loan::Column::Status.ne("returned"))
+        .order_by_desc(loan::Column::LoanDate)
+        .one(db)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_copy(db: &DatabaseConnection, isbn: &str, status: &str) -> copy::Model {
+        let now = Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some(isbn.to_string())),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(1),
+            status: Set(status.to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn token_authenticates_until_revoked() {
+        let db = setup().await;
+        let (created, token) = create_token(&db, " Front desk ").await.unwrap();
+        assert_eq!(created.name, "Front desk");
+        assert_ne!(created.token_hash, token, "only the hash is stored");
+
+        let found = authenticate(&db, &token).await.unwrap().unwrap();
+        assert_eq!(found.id, created.id);
+        assert!(found.last_used_at.is_some());
+        assert!(authenticate(&db, "guess").await.unwrap().is_none());
+        assert!(authenticate(&db, "").await.unwrap().is_none());
+
+        assert!(revoke_token(&db, &created.id).await.unwrap());
+        assert!(authenticate(&db, &token).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn scanned_isbn_or_label_designates_a_copy() {
+        let db = setup().await;
+        let shelved = insert_copy(&db, "978-2-07-036822-8", "available").await;
+
+        let by_isbn = copy_for_code(&db, "9782070368228", "available")
+            .await
+            .unwrap();
+        assert_eq!(by_isbn.id, shelved.id, "separators are ignored");
+        let by_label = copy_for_code(&db, &shelved.id, "loaned").await.unwrap();
+        assert_eq!(by_label.id, shelved.id);
+        assert!(matches!(
+            copy_for_code(&db, "9782070368228", "loaned").await,
+            Err(KioskError::NoCopy("loaned"))
+        ));
+        assert!(matches!(
+            copy_for_code(&db, "0000000000000", "available").await,
+            Err(KioskError::UnknownCode)
+        ));
+
+        insert_copy(&db, "9780441013593", "loaned").await;
+        insert_copy(&db, "9780441013593", "loaned").await;
+        assert!(matches!(
+            copy_for_code(&db, "9780441013593", "loaned").await,
+            Err(KioskError::Ambiguous)
+        ));
+    }
+}
@@ -1,15 +1,20 @@
 //! Loan Service - Pure business logic without HTTP layer
 
-use chrono::Local;
 use sea_orm::*;
 use std::collections::HashMap;
 
+use crate::domain::{
+    BookRepository, ContactRepository, CopyRepository, DomainError, Loan, LoanBorrower, LoanQuery,
+    LoanRepository, LoanSettingsRepository, NewLoan, P2pRequestRepository, PeerRepository,
+};
+use crate::infrastructure::{
+    AppState, SeaOrmBookRepository, SeaOrmContactRepository, SeaOrmCopyRepository,
+    SeaOrmLoanRepository, SeaOrmLoanSettingsRepository, SeaOrmPeerRepository,
+};
 use crate::models::book::Entity as Book;
-use crate::models::contact::Entity as Contact;
 use crate::models::copy::{self, Entity as Copy};
-use crate::models::loan::{self, Entity as Loan, LoanDto};
-use crate::models::p2p_outgoing_request::{self, Entity as P2pOutgoingRequest};
-use crate::models::p2p_request::{self, Entity as P2pRequest};
+use crate::models::loan::LoanDto;
+use crate::utils::time_zone::{LibraryZone, zone_or_host};
 
 /// Error type for service operations
 #[derive(Debug)]
@@ -25,6 +30,80 @@ impl From<sea_orm::DbErr> for ServiceError {
     }
 }
 
+impl From<DomainError> for ServiceError {
+    fn from(e: DomainError) -> Self {
+        match e {
+            DomainError::NotFound => ServiceError::NotFound,
+            other => ServiceError::Database(other.to_string()),
+        }
+    }
+}
+
+/// The repositories behind the loan flows. Handlers borrow them from
+/// `AppState`; callers without one (FFI, MCP tools) use [`SeaOrmLoanRepos`].
+#[derive(Clone, Copy)]
+pub struct LoanRepos<'a> {
+    pub loans: &'a dyn LoanRepository,
+    pub copies: &'a dyn CopyRepository,
+    pub books: &'a dyn BookRepository,
+    pub contacts: &'a dyn ContactRepository,
+    pub peers: &'a dyn PeerRepository,
+    pub loan_settings: &'a dyn LoanSettingsRepository,
+}
+
+impl<'a> LoanRepos<'a> {
+    pub fn from_state(state: &'a AppState) -> Self {
+        Self {
+            loans: state.loan_repo.as_ref(),
+            copies: state.copy_repo.as_ref(),
+            books: state.book_repo.as_ref(),
+            contacts: state.contact_repo.as_ref(),
+            peers: state.peer_repo.as_ref(),
+            loan_settings: state.loan_settings_repo.as_ref(),
+        }
+    }
+
+    /// The library's time zone, in which loan dates are counted
+    async fn zone(&self) -> Result<LibraryZone, ServiceError> {
+        let name = self.loan_settings.time_zone().await?;
+        Ok(zone_or_host(name.as_deref()))
+    }
+}
+
+/// SeaORM repositories for [`LoanRepos`], for callers without an `AppState`
+pub struct SeaOrmLoanRepos {
+    loans: SeaOrmLoanRepository,
+    copies: SeaOrmCopyRepository,
+    books: SeaOrmBookRepository,
+    contacts: SeaOrmContactRepository,
+    peers: SeaOrmPeerRepository,
+    loan_settings: SeaOrmLoanSettingsRepository,
+}
+
+impl SeaOrmLoanRepos {
+    pub fn new(db: &DatabaseConnection) -> Self {
+        Self {
+            loans: SeaOrmLoanRepository::new(db.clone()),
+            copies: SeaOrmCopyRepository::new(db.clone()),
+            books: SeaOrmBookRepository::new(db.clone()),
+            contacts: SeaOrmContactRepository::new(db.clone()),
+            peers: SeaOrmPeerRepository::new(db.clone()),
+            loan_settings: SeaOrmLoanSettingsRepository::new(db.clone()),
+        }
+    }
+
+    pub fn repos(&self) -> LoanRepos<'_> {
+        LoanRepos {
+            loans: &self.loans,
+            copies: &self.copies,
+            books: &self.books,
+            contacts: &self.contacts,
+            peers: &self.peers,
+            loan_settings: &self.loan_settings,
+        }
+    }
+}
+
 /// Enriched loan with related data
 #[derive(Debug, Clone)]
 pub struct LoanWithDetails {
@@ -62,65 +141,60 @@ pub struct LoanFilter {
 
 /// List all loans with related contact and book info
 pub async fn list_loans(
-    db: &DatabaseConnection,
+    repos: LoanRepos<'_>,
     filter: LoanFilter,
 ) -> Result<Vec<LoanWithDetails>, ServiceError> {
-    let mut condition = Condition::all();
-
-    if let Some(library_id) = filter.library_id {
-        condition = condition.add(loan::Column::LibraryId.eq(library_id));
-    }
-
-    if let Some(status) = filter.status {
-        condition = condition.add(loan::Column::Status.eq(status));
-    }
-
-    if let Some(contact_id) = filter.contact_id {
-        condition = condition.add(loan::Column::ContactId.eq(contact_id));
-    }
-
-    let mut query = Loan::find()
-        .filter(condition)
-        .order_by_desc(loan::Column::LoanDate);
+    let loans = repos
+        .loans
+        .find_all(&LoanQuery {
+            library_id: filter.library_id,
+            status: filter.status,
+            contact_id: filter.contact_id.map(|id| id.to_string()),
+            limit: filter.limit,
+            offset: filter.offset,
+        })
+        .await?;
 
-    // Applied before `find_also_related`: the relation is many-to-one, so it
-    // cannot multiply loan rows and the cap stays exact.
-    if let Some(limit) = filter.limit {
-        query = query.limit(limit).offset(filter.offset.unwrap_or(0));
+    // Borrower names, one lookup per distinct contact
+    let mut contact_names: HashMap<String, Option<String>> = HashMap::new();
+    for loan in &loans {
+        if !contact_names.contains_key(&loan.contact_id) {
+            let name = repos
+                .contacts
+                .find_by_id(&loan.contact_id)
+                .await?
+                .map(|c| c.name);
+            contact_names.insert(loan.contact_id.clone(), name);
+        }
     }
 
-    let loans_with_contacts = query.find_also_related(Contact).all(db).await?;
-
-    // Collect copy IDs to fetch books
-    let copy_ids: Vec<String> = loans_with_contacts
-        .iter()
-        .map(|(l, _)| l.copy_id.clone())
-        .collect();
-
-    // Fetch copies with books (title, id, cover_url, isbn)
+    // Books behind the loaned copies (title, id, cover_url, isbn), one lookup
+    // per distinct copy
     let mut copy_book_map: HashMap<String, (String, String, Option<String>, Option<String>)> =
         HashMap::new();
-
-    if !copy_ids.is_empty() {
-        let copies_with_books = Copy::find()
-            .filter(copy::Column::Id.is_in(copy_ids))
-            .find_also_related(Book)
-            .all(db)
-            .await?;
-
-        for (copy, book) in copies_with_books {
-            if let Some(book) = book {
-                copy_book_map.insert(copy.id, (book.title, book.id, book.cover_url, book.isbn));
-            }
+    let mut seen_copies = std::collections::HashSet::new();
+    for loan in &loans {
+        if !seen_copies.insert(loan.copy_id.as_str()) {
+            continue;
+        }
+        let Some(copy) = repos.copies.find_by_id(&loan.copy_id).await? else {
+            continue;
+        };
+        if let Some(book) = repos.books.find_by_id(&copy.book_id).await? {
+            copy_book_map.insert(
+                loan.copy_id.clone(),
+                (book.title, copy.book_id, book.cover_url, book.isbn),
+            );
         }
     }
 
-    let result: Vec<LoanWithDetails> = loans_with_contacts
+    let result: Vec<LoanWithDetails> = loans
         .into_iter()
-        .map(|(loan, contact)| {
-            let contact_name = contact
-                .as_ref()
-                .map(|c| c.name.clone())
+        .map(|loan| {
+            let contact_name = contact_names
+                .get(&loan.contact_id)
+                .cloned()
+                .flatten()
                 .unwrap_or_else(|| "Unknown".to_string());
             let book_info = copy_book_map.get(&loan.copy_id);
             let book_title = book_info
@@ -153,16 +227,71 @@ pub async fn list_loans(
     Ok(result)
 }
 
+/// Whether a loan is overdue under the policy's grace period. Only active
+/// loans can be overdue; an unparseable due date never is. `today` is the
+/// library's local date (`utils::time_zone`): a loan is due until the end
+/// of its due day there.
+pub fn is_loan_overdue(
+    settings: &crate::domain::LoanSettings,
+    status: &str,
+    due_date: &str,
+    today: chrono::NaiveDate,
+) -> bool {
+    // Due dates are stored as "YYYY-MM-DD", "YYYY-MM-DD HH:MM:SS" or RFC 3339.
+    let day = due_date.get(..10).unwrap_or(due_date);
+    status == "active"
+        && chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .is_ok_and(|due| settings.is_overdue(due, today))
+}
+
+/// The borrower behind `contact_id`, for the loan duration policy. P2P
+/// borrowers are `Library` contacts named after the peer.
+pub async fn loan_borrower(
+    contacts: &dyn ContactRepository,
+    peers: &dyn PeerRepository,
+    contact_id: &str,
+) -> Result<LoanBorrower, ServiceError> {
+    let contact = contacts.find_by_id(contact_id).await?;
+    let peer_id = match &contact {
+        Some(c) if c.contact_type == "Library" => peers.find_by_name(&c.name).await?.map(|p| p.id),
+        _ => None,
+    };
+    Ok(LoanBorrower {
+        contact_type: contact.map(|c| c.contact_type),
+        peer_id,
+    })
+}
+
+/// Due date (`YYYY-MM-DD`) of a loan of a copy of `book_id` to `contact_id`
+/// starting today, from the loan policy: per-book, per-peer (for a `Library`
+/// contact named after a peer), per-contact-type, then global duration.
+pub async fn policy_due_date(
+    repos: LoanRepos<'_>,
+    book_id: &str,
+    contact_id: &str,
+) -> Result<String, ServiceError> {
+    let borrower = loan_borrower(repos.contacts, repos.peers, contact_id).await?;
+    let days = repos
+        .loan_settings
+        .get_effective_duration_for(book_id, &borrower)
+        .await
+        .map_err(|e| ServiceError::Database(e.to_string()))?;
+
+    // Counted from the library's today: due at the end of that local day.
+    let today = repos.zone().await?.today();
+    Ok((today + chrono::Duration::days(days as i64))
+        .format("%Y-%m-%d")
+        .to_string())
+}
+
 /// Create a new loan
-pub async fn create_loan(
-    db: &DatabaseConnection,
-    dto: LoanDto,
-) -> Result<loan::Model, ServiceError> {
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+pub async fn create_loan(repos: LoanRepos<'_>, dto: LoanDto) -> Result<Loan, ServiceError> {
+    let now = repos.zone().await?.now_local_string();
 
     // 1. Check if copy exists and is available
-    let copy = Copy::find_by_id(dto.copy_id.clone())
-        .one(db)
+    let copy = repos
+        .copies
+        .find_by_id(&dto.copy_id)
         .await?
         .ok_or(ServiceError::NotFound)?;
 
@@ -173,56 +302,43 @@ pub async fn create_loan(
         )));
     }
 
-    // 2. Create Loan
-    let new_loan = loan::ActiveModel {
-        copy_id: Set(dto.copy_id.clone()),
-        contact_id: Set(dto.contact_id),
-        library_id: Set(dto.library_id),
-        loan_date: Set(dto.loan_date),
-        due_date: Set(dto.due_date),
-        return_date: Set(None),
-        status: Set("active".to_owned()),
-        notes: Set(dto.notes),
-        created_at: Set(now.clone()),
-        updated_at: Set(now),
-        ..Default::default()
+    // No due date from the caller: apply the loan policy for this borrower.
+    let due_date = if dto.due_date.trim().is_empty() {
+        policy_due_date(repos, &copy.book_id, &dto.contact_id).await?
+    } else {
+        dto.due_date
     };
 
-    let saved_loan = new_loan.insert(db).await?;
-
-    let _ = crate::sync::log_operation(
-        db,
-        "loan",
-        &saved_loan.id,
-        "INSERT",
-        Some(serde_json::json!({ "copy_id": saved_loan.copy_id })),
-    )
-    .await;
+    // 2. Create Loan
+    let saved_loan = repos
+        .loans
+        .create(NewLoan {
+            copy_id: dto.copy_id.clone(),
+            contact_id: dto.contact_id,
+            library_id: dto.library_id,
+            loan_date: dto.loan_date,
+            due_date,
+            notes: dto.notes,
+            // Snapshot the copy's terms: later edits to the copy leave this loan alone.
+            lending_terms: copy.lending_terms.as_ref().map(|t| t.to_column()),
+            created_at: now,
+        })
+        .await?;
 
     // 3. Update Copy status to 'loaned'
-    let mut copy_active: copy::ActiveModel = copy.into();
-    copy_active.status = Set("loaned".to_owned());
-    copy_active.update(db).await?;
-
-    let _ = crate::sync::log_operation(
-        db,
-        "copy",
-        &dto.copy_id,
-        "UPDATE",
-        Some(serde_json::json!({ "status": "loaned" })),
-    )
-    .await;
+    repos.copies.set_status(&dto.copy_id, "loaned").await?;
 
     Ok(saved_loan)
 }
 
 /// Return a loan
-pub async fn return_loan(db: &DatabaseConnection, id: &str) -> Result<loan::Model, ServiceError> {
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+pub async fn return_loan(repos: LoanRepos<'_>, id: &str) -> Result<Loan, ServiceError> {
+    let now = repos.zone().await?.now_local_string();
 
     // 1. Find Loan
-    let loan = Loan::find_by_id(id.to_owned())
-        .one(db)
+    let loan = repos
+        .loans
+        .find_by_id(id)
         .await?
         .ok_or(ServiceError::NotFound)?;
 
@@ -233,102 +349,98 @@ pub async fn return_loan(db: &DatabaseConnection, id: &str) -> Result<loan::Mode
     }
 
     // 2. Update Loan
-    let mut loan_active: loan::ActiveModel = loan.clone().into();
-    loan_active.return_date = Set(Some(now.clone()));
-    loan_active.status = Set("returned".to_owned());
-    loan_active.updated_at = Set(now);
-
-    let updated_loan = loan_active.update(db).await?;
-
-    let _ = crate::sync::log_operation(
-        db,
-        "loan",
-        &updated_loan.id,
-        "UPDATE",
-        Some(serde_json::json!({ "status": "returned" })),
-    )
-    .await;
+    let updated_loan = repos.loans.mark_returned(id, &now).await?;
 
     // 3. Update Copy status to 'available'
-    let copy = Copy::find_by_id(loan.copy_id.clone())
-        .one(db)
+    repos.copies.set_status(&loan.copy_id, "available").await?;
+
+    Ok(updated_loan)
+}
+
+/// Renew an open loan. Without an explicit `due_date` the loan period of the
+/// policy restarts today, but a renewal never brings the due date forward.
+pub async fn renew_loan(
+    repos: LoanRepos<'_>,
+    id: &str,
+    due_date: Option<String>,
+) -> Result<Loan, ServiceError> {
+    let loan = repos
+        .loans
+        .find_by_id(id)
         .await?
         .ok_or(ServiceError::NotFound)?;
 
-    let mut copy_active: copy::ActiveModel = copy.into();
-    copy_active.status = Set("available".to_owned());
-    copy_active.update(db).await?;
+    if loan.status == "returned" {
+        return Err(ServiceError::InvalidState(
+            "Loan is already returned".to_string(),
+        ));
+    }
 
-    let _ = crate::sync::log_operation(
-        db,
-        "copy",
-        &loan.copy_id,
-        "UPDATE",
-        Some(serde_json::json!({ "status": "available" })),
-    )
-    .await;
+    let due_date = match due_date.filter(|d| !d.trim().is_empty()) {
+        Some(due_date) => due_date,
+        None => {
+            let copy = repos
+                .copies
+                .find_by_id(&loan.copy_id)
+                .await?
+                .ok_or(ServiceError::NotFound)?;
+            let policy = policy_due_date(repos, &copy.book_id, &loan.contact_id).await?;
+            // Both sides start with "YYYY-MM-DD", so text order is date order.
+            let current = loan.due_date.get(..10).unwrap_or(&loan.due_date);
+            if policy.as_str() > current {
+                policy
+            } else {
+                current.to_string()
+            }
+        }
+    };
 
-    Ok(updated_loan)
+    let now = repos.zone().await?.now_local_string();
+    Ok(repos.loans.set_due_date(id, &due_date, &now).await?)
 }
 
 /// Count total loans
-pub async fn count_loans(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    let count = Loan::find().count(db).await?;
-    Ok(count as i64)
+pub async fn count_loans(repo: &dyn LoanRepository) -> Result<i64, ServiceError> {
+    Ok(repo.count(None).await?)
 }
 
 /// Count active loans
-pub async fn count_active_loans(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    let count = Loan::find()
-        .filter(loan::Column::Status.eq("active"))
-        .count(db)
-        .await?;
-    Ok(count as i64)
+pub async fn count_active_loans(repo: &dyn LoanRepository) -> Result<i64, ServiceError> {
+    Ok(repo.count(Some("active")).await?)
 }
 
 /// Count returned loans
-pub async fn count_returned_loans(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    let count = Loan::find()
-        .filter(loan::Column::Status.eq("returned"))
-        .count(db)
-        .await?;
-    Ok(count as i64)
+pub async fn count_returned_loans(repo: &dyn LoanRepository) -> Result<i64, ServiceError> {
+    Ok(repo.count(Some("returned")).await?)
 }
 
 /// Delete all returned loans, returns the number of deleted rows
-pub async fn delete_returned_loans(db: &DatabaseConnection) -> Result<u64, ServiceError> {
-    let result = Loan::delete_many()
-        .filter(loan::Column::Status.eq("returned"))
-        .exec(db)
-        .await?;
-    Ok(result.rows_affected)
+pub async fn delete_returned_loans(repo: &dyn LoanRepository) -> Result<u64, ServiceError> {
+    Ok(repo.delete_with_status("returned").await?)
 }
 
-/// Count closed incoming P2P requests (not pending)
-pub async fn count_closed_incoming_requests(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    let count = P2pRequest::find()
-        .filter(p2p_request::Column::Status.ne("pending"))
-        .count(db)
-        .await?;
-    Ok(count as i64)
+/// Count closed incoming P2P requests (neither pending, waitlisted nor on the
+/// hold shelf)
+pub async fn count_closed_incoming_requests(
+    requests: &dyn P2pRequestRepository,
+) -> Result<i64, ServiceError> {
+    Ok(requests.count_closed_incoming().await? as i64)
 }
 
-/// Delete all closed incoming P2P requests (not pending)
-pub async fn delete_closed_incoming_requests(db: &DatabaseConnection) -> Result<u64, ServiceError> {
-    let result = P2pRequest::delete_many()
-        .filter(p2p_request::Column::Status.ne("pending"))
-        .exec(db)
-        .await?;
-    Ok(result.rows_affected)
+/// Delete all closed incoming P2P requests (neither pending, waitlisted nor on
+/// the hold shelf: a `ready` request still holds a reserved copy)
+pub async fn delete_closed_incoming_requests(
+    requests: &dyn P2pRequestRepository,
+) -> Result<u64, ServiceError> {
+    Ok(requests.delete_closed_incoming().await?)
 }
 
-/// Count closed outgoing P2P requests (not pending)
-pub async fn count_closed_outgoing_requests(db: &DatabaseConnection) -> Result<i64, ServiceError> {
-    let count = P2pOutgoingRequest::find()
-        .filter(p2p_outgoing_request::Column::Status.ne("pending"))
-        .count(db)
-        .await?;
-    Ok(count as i64)
+/// Count closed outgoing P2P requests (neither pending, waitlisted nor
+/// awaiting a return acknowledgment)
+pub async fn count_closed_outgoing_requests(
+    requests: &dyn P2pRequestRepository,
+) -> Result<i64, ServiceError> {
+    Ok(requests.count_closed_outgoing().await? as i64)
 }
 
 /// Mark an outgoing P2P borrow request as failed after a delivery error.
@@ -340,22 +452,10 @@ pub async fn count_closed_outgoing_requests(db: &DatabaseConnection) -> Result<i
 /// block a retry, and are purged by `delete_closed_outgoing_requests`.
 ///
 /// Best-effort: delivery failure reporting must never mask the original
-/// error, so DB errors are logged and swallowed.
-pub async fn mark_outgoing_request_failed(db: &DatabaseConnection, outgoing_id: &str) {
-    let result = P2pOutgoingRequest::update_many()
-        .col_expr(
-            p2p_outgoing_request::Column::Status,
-            sea_orm::prelude::Expr::value("failed"),
-        )
-        .col_expr(
-            p2p_outgoing_request::Column::UpdatedAt,
-            sea_orm::prelude::Expr::value(chrono::Utc::now().to_rfc3339()),
-        )
-        .filter(p2p_outgoing_request::Column::Id.eq(outgoing_id))
-        .exec(db)
-        .await;
-    match result {
-        Ok(_) => tracing::info!("Outgoing request {} marked as failed", outgoing_id),
+/// error, so errors are logged and swallowed.
+pub async fn mark_outgoing_request_failed(requests: &dyn P2pRequestRepository, outgoing_id: &str) {
+    match requests.set_outgoing_status(outgoing_id, "failed").await {
+        Ok(()) => tracing::info!("Outgoing request {} marked as failed", outgoing_id),
         Err(e) => tracing::warn!(
             "Failed to mark outgoing request {} as failed: {e}",
             outgoing_id
@@ -363,13 +463,13 @@ pub async fn mark_outgoing_request_failed(db: &DatabaseConnection, outgoing_id:
     }
 }
 
-/// Delete all closed outgoing P2P requests (not pending)
-pub async fn delete_closed_outgoing_requests(db: &DatabaseConnection) -> Result<u64, ServiceError> {
-    let result = P2pOutgoingRequest::delete_many()
-        .filter(p2p_outgoing_request::Column::Status.ne("pending"))
-        .exec(db)
-        .await?;
-    Ok(result.rows_affected)
+/// Delete all closed outgoing P2P requests (neither pending, waitlisted nor
+/// awaiting a return acknowledgment: the acknowledgment is matched to the
+/// request)
+pub async fn delete_closed_outgoing_requests(
+    requests: &dyn P2pRequestRepository,
+) -> Result<u64, ServiceError> {
+    Ok(requests.delete_closed_outgoing().await?)
 }
 
 // ============ RECLAIM: a lender takes their book back ============
@@ -498,7 +598,10 @@ pub(crate) async fn purge_copies_lent_by(
 #[cfg(test)]
 mod tests {
     use crate::domain::DomainError;
-    use crate::domain::loan_settings_repository::{LoanSettings, LoanSettingsRepository};
+    use crate::domain::loan_settings_repository::{
+        LoanBorrower, LoanSettings, LoanSettingsRepository,
+    };
+    use crate::models::loan::LoanDto;
     use async_trait::async_trait;
     use std::sync::Mutex;
 
@@ -514,7 +617,7 @@ mod tests {
                 settings: Mutex::new(LoanSettings {
                     default_loan_duration_days: default_days,
                     per_book_duration_enabled: per_book_enabled,
-                    reminder_days_before_due: 2,
+                    ..Default::default()
                 }),
                 book_durations: Mutex::new(std::collections::HashMap::new()),
             }
@@ -540,8 +643,8 @@ mod tests {
         ) -> Result<LoanSettings, DomainError> {
             let clamped = LoanSettings {
                 default_loan_duration_days: settings.default_loan_duration_days.clamp(1, 365),
-                per_book_duration_enabled: settings.per_book_duration_enabled,
                 reminder_days_before_due: settings.reminder_days_before_due.clamp(1, 10),
+                ..settings
             };
             *self.settings.lock().unwrap() = clamped.clone();
             Ok(clamped)
@@ -569,14 +672,30 @@ mod tests {
             Ok(())
         }
 
-        async fn get_effective_duration(&self, book_id: &str) -> Result<i32, DomainError> {
+        async fn get_peer_loan_duration(&self, _peer_id: i32) -> Result<Option<i32>, DomainError> {
+            Ok(None)
+        }
+
+        async fn set_peer_loan_duration(
+            &self,
+            _peer_id: i32,
+            _days: Option<i32>,
+        ) -> Result<(), DomainError> {
+            Ok(())
+        }
+
+        async fn time_zone(&self) -> Result<Option<String>, DomainError> {
+            Ok(None)
+        }
+
+        async fn get_effective_duration_for(
+            &self,
+            book_id: &str,
+            borrower: &LoanBorrower,
+        ) -> Result<i32, DomainError> {
             let settings = self.get_settings().await?;
-            if settings.per_book_duration_enabled
-                && let Ok(Some(days)) = self.get_book_loan_duration(book_id).await
-            {
-                return Ok(days);
-            }
-            Ok(settings.default_loan_duration_days)
+            let book_days = self.get_book_loan_duration(book_id).await?;
+            Ok(settings.resolve_duration(book_days, None, borrower.contact_type.as_deref()))
         }
     }
 
@@ -618,8 +737,7 @@ mod tests {
         let updated = repo
             .update_settings(LoanSettings {
                 default_loan_duration_days: 0,
-                per_book_duration_enabled: false,
-                reminder_days_before_due: 2,
+                ..Default::default()
             })
             .await
             .unwrap();
@@ -628,8 +746,7 @@ mod tests {
         let updated = repo
             .update_settings(LoanSettings {
                 default_loan_duration_days: 500,
-                per_book_duration_enabled: false,
-                reminder_days_before_due: 2,
+                ..Default::default()
             })
             .await
             .unwrap();
@@ -655,4 +772,147 @@ mod tests {
         let duration = repo.get_effective_duration("book-1").await.unwrap();
         assert_eq!(duration, 21);
     }
+
+    #[tokio::test]
+    async fn loans_run_on_the_in_memory_repositories() {
+        use super::{LoanFilter, LoanRepos, ServiceError};
+        use crate::domain::{
+            BookRepository, ContactInput, ContactRepository, CopyRepository, CreateCopyInput,
+        };
+        use crate::infrastructure::repositories::memory::InMemoryRepositories;
+
+        let r = InMemoryRepositories::new();
+        r.loan_settings.set_time_zone("Pacific/Auckland");
+        let repos = LoanRepos {
+            loans: &r.loans,
+            copies: &r.copies,
+            books: &r.books,
+            contacts: &r.contacts,
+            peers: &r.peers,
+            loan_settings: &r.loan_settings,
+        };
+        let book = r
+            .books
+            .create(crate::models::Book {
+                title: "Dune".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let copy = r
+            .copies
+            .create(CreateCopyInput {
+                book_id: book.id.unwrap(),
+                library_id: 1,
+                status: "available".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let copy_id = copy.id.unwrap();
+        let alice = r
+            .contacts
+            .create(ContactInput {
+                name: "Alice".into(),
+                contact_type: "Borrower".into(),
+                is_active: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let dto = || LoanDto {
+            id: None,
+            copy_id: copy_id.clone(),
+            contact_id: alice.id.clone(),
+            library_id: 1,
+            loan_date: "2026-10-01".into(),
+            due_date: String::new(),
+            return_date: None,
+            status: None,
+            notes: None,
+        };
+
+        let loan = super::create_loan(repos, dto()).await.unwrap();
+        // The policy's 21 days, from today where the library is.
+        let today = crate::utils::time_zone::zone_or_host(Some("Pacific/Auckland")).today();
+        assert_eq!(
+            loan.due_date,
+            (today + chrono::Duration::days(21))
+                .format("%Y-%m-%d")
+                .to_string()
+        );
+        let copy = r.copies.find_by_id(&copy_id).await.unwrap().unwrap();
+        assert_eq!(copy.status, "loaned");
+        assert!(matches!(
+            super::create_loan(repos, dto()).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+
+        let listed = super::list_loans(repos, LoanFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].book_title, "Dune");
+        assert_eq!(listed[0].contact_name, "Alice");
+
+        super::return_loan(repos, &loan.id).await.unwrap();
+        let copy = r.copies.find_by_id(&copy_id).await.unwrap().unwrap();
+        assert_eq!(copy.status, "available");
+        assert!(matches!(
+            super::return_loan(repos, &loan.id).await,
+            Err(ServiceError::InvalidState(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn library_contacts_borrow_as_the_peer_of_the_same_name() {
+        use crate::domain::{ContactInput, ContactRepository, Peer};
+        use crate::infrastructure::repositories::memory::{
+            InMemoryContactRepository, InMemoryPeerRepository,
+        };
+
+        let contacts = InMemoryContactRepository::new();
+        let peers = InMemoryPeerRepository::new();
+        peers.insert(Peer {
+            id: 7,
+            name: "Town library".to_string(),
+            display_name: None,
+            url: "http://192.168.1.20:8000".to_string(),
+            library_uuid: None,
+            connection_status: "connected".to_string(),
+            last_seen: None,
+            auto_approve: false,
+            loan_duration_days: Some(30),
+        });
+        let contact = |name: &str, contact_type: &str| ContactInput {
+            name: name.to_string(),
+            contact_type: contact_type.to_string(),
+            is_active: true,
+            ..Default::default()
+        };
+        let library = contacts
+            .create(contact("Town library", "Library"))
+            .await
+            .unwrap();
+        let namesake = contacts
+            .create(contact("Town library", "Borrower"))
+            .await
+            .unwrap();
+
+        let borrower = super::loan_borrower(&contacts, &peers, &library.id)
+            .await
+            .unwrap();
+        assert_eq!(borrower.contact_type.as_deref(), Some("Library"));
+        assert_eq!(borrower.peer_id, Some(7));
+
+        let borrower = super::loan_borrower(&contacts, &peers, &namesake.id)
+            .await
+            .unwrap();
+        assert_eq!(borrower.peer_id, None);
+
+        let borrower = super::loan_borrower(&contacts, &peers, "missing")
+            .await
+            .unwrap();
+        assert!(borrower.contact_type.is_none());
+    }
 }
@@ -10,7 +10,7 @@ use crate::openlibrary::BookMetadata;
 /// Resolve book metadata by ISBN, querying multiple sources in priority order.
 ///
 /// Source priority depends on ISBN origin and user language:
-/// - French ISBNs (978-2…): BNF SPARQL → SUDOC → BNF SRU → Inventaire → OpenLibrary → Google
+/// - French ISBNs (978-2…): BNF (SRU, then SPARQL) → SUDOC → Inventaire → OpenLibrary → Google
 /// - Others: Inventaire → OpenLibrary → BNF (if user lang is French) → Google
 ///
 /// Returns `Ok(None)` when no source has data for this ISBN.
@@ -69,7 +69,7 @@ pub async fn lookup_metadata_by_isbn(
     // For French ISBNs, try BNF first (better coverage for French publishers)
     if enable_bnf && is_french_isbn {
         if primary.is_none()
-            && let Some(m) = try_bnf_sparql(
+            && let Some(m) = try_bnf(
                 &clean_isbn,
                 isbn,
                 enable_openlibrary,
@@ -94,19 +94,6 @@ pub async fn lookup_metadata_by_isbn(
         {
             primary = Some((m, Source::Sudoc));
         }
-        if primary.is_none()
-            && let Some(m) = try_bnf_sru(
-                &clean_isbn,
-                isbn,
-                enable_openlibrary,
-                enable_google,
-                enable_inventaire,
-                google_api_key.as_deref(),
-            )
-            .await
-        {
-            primary = Some((m, Source::BnfSru));
-        }
     }
 
     // 1. Try Inventaire
@@ -136,7 +123,7 @@ pub async fn lookup_metadata_by_isbn(
         && enable_bnf
         && user_lang_is_french
         && !is_french_isbn
-        && let Some(m) = try_bnf_sparql(
+        && let Some(m) = try_bnf(
             &clean_isbn,
             isbn,
             enable_openlibrary,
@@ -192,7 +179,6 @@ pub async fn lookup_metadata_by_isbn(
 enum Source {
     Bnf,
     Sudoc,
-    BnfSru,
     Inventaire,
     OpenLibrary,
     Google,
@@ -451,7 +437,7 @@ where
     }
 }
 
-async fn try_bnf_sparql(
+async fn try_bnf(
     clean_isbn: &str,
     isbn: &str,
     enable_openlibrary: bool,
@@ -459,13 +445,27 @@ async fn try_bnf_sparql(
     enable_inventaire: bool,
     google_api_key: Option<&str>,
 ) -> Option<BookMetadata> {
-    tracing::debug!("Trying BNF SPARQL for ISBN {}", isbn);
-    match crate::modules::integrations::bnf::lookup_bnf_isbn(clean_isbn).await {
+    use crate::modules::integrations::bnf::{self, BnfSource};
+
+    tracing::debug!("Trying BNF for ISBN {}", isbn);
+    match bnf::lookup_isbn(clean_isbn).await {
         Ok(Some(bnf_book)) => {
-            tracing::info!("BNF found book for ISBN {}: {}", isbn, bnf_book.title);
+            tracing::info!(
+                "BNF ({}) found book for ISBN {}: {}",
+                bnf_book.source.as_str(),
+                isbn,
+                bnf_book.title
+            );
+            // BNF SRU cover URLs (catalogue.bnf.fr/couverture?...) are speculative:
+            // they are generated from the ARK ID but often return a placeholder,
+            // not a real cover. Pass None so enrich_cover() can try Inventaire/OpenLibrary/Google.
+            let bnf_cover = match bnf_book.source {
+                BnfSource::Sru => None,
+                BnfSource::Sparql => bnf_book.cover_url,
+            };
             let cover_url = enrich_cover(
                 isbn,
-                bnf_book.cover_url,
+                bnf_cover,
                 enable_openlibrary,
                 enable_google,
                 enable_inventaire,
@@ -479,7 +479,10 @@ async fn try_bnf_sparql(
                 publication_year: bnf_book.publication_year.map(|y| y.to_string()),
                 cover_url,
                 summary: bnf_book.description,
-                page_count: None,
+                page_count: bnf_book.page_count,
+                edition: bnf_book.edition,
+                physical_format: None,
+                dimensions: bnf_book.dimensions,
             })
         }
         Ok(None) => {
@@ -522,6 +525,9 @@ async fn try_sudoc(
                 cover_url,
                 summary: None,
                 page_count: None,
+                edition: None,
+                physical_format: None,
+                dimensions: None,
             })
         }
         Err(e) => {
@@ -531,51 +537,6 @@ async fn try_sudoc(
     }
 }
 
-async fn try_bnf_sru(
-    clean_isbn: &str,
-    isbn: &str,
-    enable_openlibrary: bool,
-    enable_google: bool,
-    enable_inventaire: bool,
-    google_api_key: Option<&str>,
-) -> Option<BookMetadata> {
-    tracing::debug!("Trying BNF SRU for French ISBN {}", isbn);
-    match crate::modules::integrations::bnf::lookup_bnf_sru(clean_isbn).await {
-        Ok(Some(bnf_book)) => {
-            tracing::info!("BNF SRU found book for ISBN {}: {}", isbn, bnf_book.title);
-            // BNF SRU cover URLs (catalogue.bnf.fr/couverture?...) are speculative:
-            // they are generated from the ARK ID but often return a placeholder,
-            // not a real cover. Pass None so enrich_cover() can try Inventaire/OpenLibrary/Google.
-            let cover_url = enrich_cover(
-                isbn,
-                None,
-                enable_openlibrary,
-                enable_google,
-                enable_inventaire,
-                google_api_key,
-            )
-            .await;
-            Some(BookMetadata {
-                title: bnf_book.title,
-                authors: make_authors_from_name(bnf_book.author),
-                publisher: bnf_book.publisher,
-                publication_year: bnf_book.publication_year.map(|y| y.to_string()),
-                cover_url,
-                summary: bnf_book.description,
-                page_count: None,
-            })
-        }
-        Ok(None) => {
-            tracing::debug!("BNF SRU returned no result for ISBN {}", isbn);
-            None
-        }
-        Err(e) => {
-            tracing::debug!("BNF SRU lookup failed for {}: {}", isbn, e);
-            None
-        }
-    }
-}
-
 async fn try_inventaire(
     isbn: &str,
     enable_openlibrary: bool,
@@ -607,6 +568,9 @@ async fn try_inventaire(
                 cover_url,
                 summary: inv_metadata.summary,
                 page_count: inv_metadata.page_count,
+                edition: None,
+                physical_format: None,
+                dimensions: None,
             })
         }
         Err(e) => {
@@ -668,6 +632,9 @@ mod tests {
             cover_url: cover.map(str::to_string),
             summary: summary.map(str::to_string),
             page_count,
+            edition: None,
+            physical_format: None,
+            dimensions: None,
         }
     }
 
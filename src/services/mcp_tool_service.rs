@@ -24,7 +24,9 @@ use sea_orm::{
 use serde_json::{Value, json};
 
 use crate::domain::{BookFilter, BookRepository, CollectionRepository};
-use crate::infrastructure::repositories::{SeaOrmBookRepository, SeaOrmCollectionRepository};
+use crate::infrastructure::repositories::{
+    SeaOrmBookRepository, SeaOrmCollectionRepository, SeaOrmLoanRepository,
+};
 use crate::models::Book;
 use crate::models::book::{self, READING_STATUSES};
 use crate::services::loan_service::{self, LoanFilter};
@@ -212,6 +214,8 @@ fn book_detail(book: &Book, collections: Value) -> Value {
         "summary": book.summary,
         "publisher": book.publisher,
         "page_count": book.page_count,
+        "edition": book.edition,
+        "physical_format": book.physical_format,
         "language": book.language,
         "subjects": book.subjects.clone().unwrap_or_default(),
         "collections": collections,
@@ -362,14 +366,15 @@ async fn get_statistics(db: &DatabaseConnection) -> Result<Value, ToolError> {
         by_reading_status.insert(status, json!(count));
     }
 
+    let loans = SeaOrmLoanRepository::new(db.clone());
     Ok(json!({
         "total_books": total_books,
         "owned": owned,
         "wishlist": total_books.saturating_sub(owned),
         "by_reading_status": Value::Object(by_reading_status),
         "loans": {
-            "active": loan_service::count_active_loans(db).await.map_err(|e| internal(format!("{:?}", e)))?,
-            "returned": loan_service::count_returned_loans(db).await.map_err(|e| internal(format!("{:?}", e)))?,
+            "active": loan_service::count_active_loans(&loans).await.map_err(|e| internal(format!("{:?}", e)))?,
+            "returned": loan_service::count_returned_loans(&loans).await.map_err(|e| internal(format!("{:?}", e)))?,
         }
     }))
 }
@@ -479,15 +484,16 @@ async fn list_loans(db: &DatabaseConnection, args: &Value) -> Result<Value, Tool
     // The loan history grows without bound, so this list is paginated like every
     // other one. `total` comes from a COUNT rather than from the truncated page,
     // otherwise the assistant would report the page size as the history size.
+    let repo = SeaOrmLoanRepository::new(db.clone());
     let total = match scope.as_str() {
-        "active" => loan_service::count_active_loans(db).await,
-        "history" => loan_service::count_returned_loans(db).await,
-        _ => loan_service::count_loans(db).await,
+        "active" => loan_service::count_active_loans(&repo).await,
+        "history" => loan_service::count_returned_loans(&repo).await,
+        _ => loan_service::count_loans(&repo).await,
     }
     .map_err(|e| internal(format!("{:?}", e)))?;
 
     let loans = loan_service::list_loans(
-        db,
+        loan_service::SeaOrmLoanRepos::new(db).repos(),
         LoanFilter {
             status,
             limit: Some(limit),
@@ -1179,7 +1185,7 @@ mod tests {
         let (copy_id, contact_id) = a_lendable_book(&db, "Lent out").await;
 
         loan_service::create_loan(
-            &db,
+            loan_service::SeaOrmLoanRepos::new(&db).repos(),
             crate::models::loan::LoanDto {
                 id: None,
                 copy_id,
@@ -1225,7 +1231,7 @@ mod tests {
         for i in 0..3 {
             let (copy_id, contact_id) = a_lendable_book(&db, &format!("Lent {}", i)).await;
             loan_service::create_loan(
-                &db,
+                loan_service::SeaOrmLoanRepos::new(&db).repos(),
                 crate::models::loan::LoanDto {
                     id: None,
                     copy_id,
@@ -1358,7 +1364,7 @@ mod tests {
         let db = db().await;
         let (copy_id, contact_id) = a_lendable_book(&db, "Lent out").await;
         loan_service::create_loan(
-            &db,
+            loan_service::SeaOrmLoanRepos::new(&db).repos(),
             crate::models::loan::LoanDto {
                 id: None,
                 copy_id,
@@ -7,8 +7,12 @@
 //! - Announce own library on the network
 //! - Discover other libraries on the same WiFi
 //! - Thread-safe management of discovered peers
+//! - Re-announce when the library is renamed or the host's addresses change
+//! - Follow paired libraries to a new address, by the library UUID they
+//!   announce
 
 use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use sea_orm::EntityTrait;
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
@@ -29,6 +33,10 @@ pub struct DiscoveredPeer {
     pub port: u16,
     pub addresses: Vec<String>,
     pub library_id: Option<String>,
+    /// Library UUID from mDNS TXT record, the peer's identity whatever
+    /// address it is found at
+    #[serde(default)]
+    pub library_uuid: Option<String>,
     /// Ed25519 public key (hex-encoded) from mDNS TXT record
     pub ed25519_public_key: Option<String>,
     /// X25519 public key (hex-encoded) from mDNS TXT record
@@ -36,12 +44,33 @@ pub struct DiscoveredPeer {
     pub discovered_at: String,
 }
 
+/// How often `spawn_announcer` looks for changed interface addresses.
+const ADDRESS_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The record this library currently announces.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, utoipa::ToSchema)]
+pub struct AnnouncedRecord {
+    /// Instance name, the library name with mDNS-unsafe characters replaced
+    pub instance_name: String,
+    pub service_fullname: String,
+    pub hostname: String,
+    pub port: u16,
+    /// Non-loopback interface addresses when the record was registered
+    pub addresses: Vec<String>,
+    pub library_id: Option<String>,
+    pub library_uuid: Option<String>,
+    /// Whether the E2EE public keys are in the TXT record
+    pub e2ee: bool,
+    pub announced_at: String,
+}
+
 /// Stored configuration for restarting mDNS without requiring callers to re-supply parameters.
 #[derive(Clone)]
 struct MdnsConfig {
     library_name: String,
     port: u16,
     library_id: Option<String>,
+    library_uuid: Option<String>,
     ed25519_public_key: Option<String>,
     x25519_public_key: Option<String>,
 }
@@ -65,10 +94,40 @@ fn evict_oldest_peer_if_over_limit(
     oldest_key
 }
 
+/// Sanitize the library name for mDNS (alphanumeric, spaces and hyphens only)
+fn instance_name(library_name: &str) -> String {
+    library_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Non-loopback addresses of the host's interfaces, sorted.
+fn interface_addresses() -> Vec<String> {
+    let mut addresses: Vec<String> = local_ip_address::list_afinet_netifas()
+        .map(|ifas| {
+            ifas.into_iter()
+                .filter(|(_, ip)| !ip.is_loopback())
+                .map(|(_, ip)| ip.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
 /// Manages mDNS service announcement and discovery
 pub struct MdnsService {
     daemon: ServiceDaemon,
     service_fullname: Option<String>,
+    announced: Option<AnnouncedRecord>,
     discovered_peers: Arc<RwLock<HashMap<String, DiscoveredPeer>>>,
     is_running: Arc<RwLock<bool>>,
 }
@@ -80,12 +139,14 @@ impl MdnsService {
     /// * `library_name` - The name to announce on the network
     /// * `port` - The port the Axum server is listening on
     /// * `library_id` - Optional unique identifier for the library
+    /// * `library_uuid` - The library UUID set up with its identity
     /// * `ed25519_public_key` - Optional hex-encoded Ed25519 public key for E2EE
     /// * `x25519_public_key` - Optional hex-encoded X25519 public key for E2EE
     pub fn new(
         library_name: &str,
         port: u16,
         library_id: Option<String>,
+        library_uuid: Option<String>,
         ed25519_public_key: Option<String>,
         x25519_public_key: Option<String>,
     ) -> Result<Self, String> {
@@ -98,6 +159,7 @@ impl MdnsService {
         let mut service = Self {
             daemon,
             service_fullname: None,
+            announced: None,
             discovered_peers,
             is_running,
         };
@@ -107,6 +169,7 @@ impl MdnsService {
             library_name,
             port,
             library_id,
+            library_uuid,
             ed25519_public_key,
             x25519_public_key,
         )?;
@@ -123,20 +186,11 @@ impl MdnsService {
         library_name: &str,
         port: u16,
         library_id: Option<String>,
+        library_uuid: Option<String>,
         ed25519_public_key: Option<String>,
         x25519_public_key: Option<String>,
     ) -> Result<(), String> {
-        // Sanitize the library name for mDNS (alphanumeric and hyphens only)
-        let safe_name: String = library_name
-            .chars()
-            .map(|c| {
-                if c.is_alphanumeric() || c == ' ' {
-                    c
-                } else {
-                    '-'
-                }
-            })
-            .collect();
+        let safe_name = instance_name(library_name);
 
         let hostname = hostname::get()
             .map(|h| h.to_string_lossy().to_string())
@@ -151,6 +205,12 @@ impl MdnsService {
             properties.push(("library_id", &lib_id_string));
         }
 
+        let uuid_string;
+        if let Some(ref uuid) = library_uuid {
+            uuid_string = uuid.clone();
+            properties.push(("library_uuid", &uuid_string));
+        }
+
         let ed_key_string;
         if let Some(ref key) = ed25519_public_key {
             ed_key_string = key.clone();
@@ -173,12 +233,25 @@ impl MdnsService {
         )
         .map_err(|e| format!("Failed to create service info: {}", e))?;
 
-        self.service_fullname = Some(service_info.get_fullname().to_string());
+        let service_fullname = service_info.get_fullname().to_string();
+        self.service_fullname = Some(service_fullname.clone());
 
         self.daemon
             .register(service_info)
             .map_err(|e| format!("Failed to register mDNS service: {}", e))?;
 
+        self.announced = Some(AnnouncedRecord {
+            instance_name: safe_name.clone(),
+            service_fullname,
+            hostname: format!("{}.local.", hostname),
+            port,
+            addresses: interface_addresses(),
+            library_id,
+            library_uuid,
+            e2ee: ed25519_public_key.is_some(),
+            announced_at: chrono::Utc::now().to_rfc3339(),
+        });
+
         tracing::info!(
             "mDNS: Announcing library '{}' on port {} (e2ee={})",
             safe_name,
@@ -231,6 +304,9 @@ impl MdnsService {
                                     library_id: info
                                         .get_property_val_str("library_id")
                                         .map(|s| s.to_string()),
+                                    library_uuid: info
+                                        .get_property_val_str("library_uuid")
+                                        .map(|s| s.to_string()),
                                     ed25519_public_key: info
                                         .get_property_val_str("ed25519")
                                         .map(|s| s.to_string()),
@@ -338,6 +414,7 @@ pub fn init_mdns(
     library_name: &str,
     port: u16,
     library_id: Option<String>,
+    library_uuid: Option<String>,
     ed25519_public_key: Option<String>,
     x25519_public_key: Option<String>,
 ) -> Result<(), String> {
@@ -346,6 +423,7 @@ pub fn init_mdns(
         library_name: library_name.to_string(),
         port,
         library_id: library_id.clone(),
+        library_uuid: library_uuid.clone(),
         ed25519_public_key: ed25519_public_key.clone(),
         x25519_public_key: x25519_public_key.clone(),
     };
@@ -356,6 +434,7 @@ pub fn init_mdns(
         library_name,
         port,
         library_id,
+        library_uuid,
         ed25519_public_key,
         x25519_public_key,
     )?;
@@ -383,11 +462,129 @@ pub fn restart_mdns() -> Result<(), String> {
         &config.library_name,
         config.port,
         config.library_id,
+        config.library_uuid,
         config.ed25519_public_key,
         config.x25519_public_key,
     )
 }
 
+/// Announce under a new library name and/or port: the stored
+/// configuration takes them, and a running service is re-registered with
+/// them. Nothing else happens when mDNS was never started or nothing changed.
+pub fn reannounce_mdns(library_name: Option<&str>, port: Option<u16>) -> Result<(), String> {
+    let Some(lock) = MDNS_CONFIG.get() else {
+        return Ok(());
+    };
+    {
+        let mut config = lock.write().map_err(|e| e.to_string())?;
+        let Some(config) = config.as_mut() else {
+            return Ok(());
+        };
+        let renamed = library_name.is_some_and(|name| name != config.library_name);
+        let moved = port.is_some_and(|port| port != config.port);
+        if !renamed && !moved {
+            return Ok(());
+        }
+        if let Some(name) = library_name {
+            config.library_name = name.to_string();
+        }
+        if let Some(port) = port {
+            config.port = port;
+        }
+    }
+    if is_mdns_active() {
+        restart_mdns()?;
+    }
+    Ok(())
+}
+
+/// Announce the library UUID, once the identity is set up: a library can
+/// start announcing before its first setup. Nothing happens when mDNS was
+/// never started or already announces it.
+pub fn announce_library_uuid(library_uuid: &str) -> Result<(), String> {
+    let Some(lock) = MDNS_CONFIG.get() else {
+        return Ok(());
+    };
+    {
+        let mut config = lock.write().map_err(|e| e.to_string())?;
+        let Some(config) = config.as_mut() else {
+            return Ok(());
+        };
+        if config.library_uuid.as_deref() == Some(library_uuid) {
+            return Ok(());
+        }
+        config.library_uuid = Some(library_uuid.to_string());
+    }
+    if is_mdns_active() {
+        restart_mdns()?;
+    }
+    Ok(())
+}
+
+/// The record currently announced, `None` while mDNS is stopped.
+pub fn announced_record() -> Option<AnnouncedRecord> {
+    MDNS_SERVICE
+        .get()
+        .and_then(|lock| lock.read().ok())
+        .and_then(|opt| opt.as_ref().and_then(|s| s.announced.clone()))
+}
+
+/// Keep the announcement current for as long as the process lives:
+/// re-register under the new name when the library is renamed
+/// (`config_events`), and when the host's interface addresses change (Wi-Fi
+/// roaming, DHCP renewal) so peers resolve the library where it now is.
+/// Paired libraries announced at a new address are followed there
+/// (`peer_identity_sync::follow_discovered_peers`).
+pub fn spawn_announcer(db: sea_orm::DatabaseConnection) {
+    let mut config_changes = crate::services::config_events::bus().subscribe();
+    tokio::spawn(async move {
+        let mut addresses = interface_addresses();
+        let mut ticker = tokio::time::interval(ADDRESS_POLL_INTERVAL);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                event = config_changes.recv() => match event {
+                    Ok(event) => {
+                        if event.changed_fields.iter().any(|f| f == "name")
+                            && let Err(e) = reannounce_mdns(Some(&event.name), None)
+                        {
+                            tracing::warn!("mDNS: cannot re-announce under the new name: {}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                        // Renames may be among the missed events: take the name as saved.
+                        if let Ok(Some(config)) =
+                            crate::models::library_config::Entity::find().one(&db).await
+                            && let Err(e) = reannounce_mdns(Some(&config.name), None)
+                        {
+                            tracing::warn!("mDNS: cannot re-announce under the new name: {}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = ticker.tick() => {
+                    if is_mdns_active() {
+                        crate::services::peer_identity_sync::follow_discovered_peers(&db).await;
+                    }
+                    let now = tokio::task::spawn_blocking(interface_addresses)
+                        .await
+                        .unwrap_or_default();
+                    if now == addresses {
+                        continue;
+                    }
+                    tracing::info!("mDNS: addresses changed {:?} -> {:?}", addresses, now);
+                    addresses = now;
+                    if is_mdns_active()
+                        && let Err(e) = restart_mdns()
+                    {
+                        tracing::warn!("mDNS: cannot re-announce on the new addresses: {}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
 /// Get discovered peers from the global service
 pub fn get_local_peers() -> Vec<DiscoveredPeer> {
     MDNS_SERVICE
@@ -440,6 +637,7 @@ mod tests {
             port: 8000,
             addresses: vec!["192.168.1.1".to_string()],
             library_id: None,
+            library_uuid: None,
             ed25519_public_key: None,
             x25519_public_key: None,
             discovered_at: discovered_at.to_string(),
@@ -560,6 +758,7 @@ mod tests {
             library_name: "My Library".to_string(),
             port: 8080,
             library_id: Some("lib-123".to_string()),
+            library_uuid: Some("6f1d1a4e-0000-4000-8000-0000000000a1".to_string()),
             ed25519_public_key: Some("aabbcc".to_string()),
             x25519_public_key: None,
         };
@@ -571,6 +770,19 @@ mod tests {
         assert!(cloned.x25519_public_key.is_none());
     }
 
+    #[test]
+    fn test_instance_name_replaces_unsafe_characters() {
+        assert_eq!(instance_name("Bibliothèque d'Anne"), "Bibliothèque d-Anne");
+        assert_eq!(instance_name("Club.lecture/2026"), "Club-lecture-2026");
+    }
+
+    #[test]
+    fn test_reannounce_before_init_does_nothing() {
+        assert!(reannounce_mdns(Some("Renamed"), Some(9000)).is_ok());
+        assert!(!is_mdns_active());
+        assert!(announced_record().is_none());
+    }
+
     #[test]
     fn test_discovered_peer_serialization_roundtrip() {
         let peer = make_peer("test-lib", "2026-02-27T10:00:00Z");
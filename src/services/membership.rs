@@ -0,0 +1,280 @@
+//! Membership of association libraries.
+//!
+//! A contact becomes a member when given a membership: a member number, a
+//! join date, the last day of the membership and the fee paid for it. These
+//! live on the contact itself and replicate with it. Once the expiry date is
+//! past, the member has lapsed and `api::loan::lend_copy` refuses new loans
+//! to them until the membership is renewed; loans already open are left
+//! alone. Contacts without a membership are not affected.
+
+use chrono::NaiveDate;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::contact;
+
+/// The membership fields of a contact.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Membership {
+    pub member_number: Option<String>,
+    /// `YYYY-MM-DD`
+    pub member_since: Option<String>,
+    /// `YYYY-MM-DD`, last day of the membership
+    pub expires_at: Option<String>,
+    pub fee_paid: Option<f64>,
+}
+
+impl From<&contact::Model> for Membership {
+    fn from(c: &contact::Model) -> Self {
+        Self {
+            member_number: c.member_number.clone(),
+            member_since: c.member_since.clone(),
+            expires_at: c.membership_expires_at.clone(),
+            fee_paid: c.membership_fee_paid,
+        }
+    }
+}
+
+/// Which members to list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberStatus {
+    #[default]
+    All,
+    /// Membership not expired (or without an expiry date)
+    Current,
+    Lapsed,
+}
+
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Whether `c` is a member.
+pub fn is_member(c: &contact::Model) -> bool {
+    c.member_number.is_some() || c.membership_expires_at.is_some()
+}
+
+/// The membership of a contact read through the `ContactRepository`, `None`
+/// when [`is_member`] would say they are not a member.
+pub fn membership_of(c: &crate::domain::Contact) -> Option<Membership> {
+    (c.member_number.is_some() || c.membership_expires_at.is_some()).then(|| Membership {
+        member_number: c.member_number.clone(),
+        member_since: c.member_since.clone(),
+        expires_at: c.membership_expires_at.clone(),
+        fee_paid: c.membership_fee_paid,
+    })
+}
+
+/// Whether `c`'s membership ended before `today`. An unreadable expiry date
+/// does not count as lapsed.
+pub fn lapsed_on(c: &contact::Model, today: NaiveDate) -> bool {
+    c.membership_expires_at
+        .as_deref()
+        .and_then(parse_day)
+        .is_some_and(|expiry| expiry < today)
+}
+
+/// The expiry date of `contact_id`'s membership if it has lapsed: `None`
+/// for a current member, a non-member or an unknown contact.
+pub async fn lapsed_membership(
+    db: &DatabaseConnection,
+    contact_id: &str,
+) -> Result<Option<String>, DbErr> {
+    let today = crate::utils::time_zone::library_zone(db).await?.today();
+    Ok(contact::Entity::find_by_id(contact_id.to_string())
+        .one(db)
+        .await?
+        .filter(|c| lapsed_on(c, today))
+        .and_then(|c| c.membership_expires_at))
+}
+
+/// Replace the membership of a contact; all fields `None` ends it. `None`
+/// when there is no such contact.
+pub async fn set_membership(
+    db: &DatabaseConnection,
+    contact_id: &str,
+    membership: Membership,
+) -> Result<Option<contact::Model>, DbErr> {
+    let Some(found) = contact::Entity::find_by_id(contact_id.to_string())
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut active: contact::ActiveModel = found.into();
+    active.member_number = Set(membership.member_number.clone());
+    active.member_since = Set(membership.member_since.clone());
+    active.membership_expires_at = Set(membership.expires_at.clone());
+    active.membership_fee_paid = Set(membership.fee_paid);
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    let model = active.update(db).await?;
+
+    let _ = crate::sync::log_operation(
+        db,
+        "contact",
+        &model.id,
+        "UPDATE",
+        Some(serde_json::json!({ "membership": membership })),
+    )
+    .await;
+    Ok(Some(model))
+}
+
+/// Active contacts holding a membership, by member number then name.
+pub async fn list_members(
+    db: &DatabaseConnection,
+    status: MemberStatus,
+) -> Result<Vec<contact::Model>, DbErr> {
+    let today = crate::utils::time_zone::library_zone(db).await?.today();
+    let mut members: Vec<contact::Model> = contact::Entity::find()
+        .filter(contact::Column::IsActive.eq(true))
+        .filter(
+            Condition::any()
+                .add(contact::Column::MemberNumber.is_not_null())
+                .add(contact::Column::MembershipExpiresAt.is_not_null()),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|c| match status {
+            MemberStatus::All => true,
+            MemberStatus::Current => !lapsed_on(c, today),
+            MemberStatus::Lapsed => lapsed_on(c, today),
+        })
+        .collect();
+    members.sort_by(|a, b| {
+        (a.member_number.is_none(), &a.member_number, &a.name).cmp(&(
+            b.member_number.is_none(),
+            &b.member_number,
+            &b.name,
+        ))
+    });
+    Ok(members)
+}
+
+/// The members list as CSV, one row per member, for printing or a
+/// spreadsheet.
+pub fn members_csv(members: &[contact::Model], today: NaiveDate) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "member_number",
+        "name",
+        "first_name",
+        "email",
+        "phone",
+        "member_since",
+        "expires_at",
+        "fee_paid",
+        "status",
+    ])?;
+    for m in members {
+        writer.write_record([
+            m.member_number.as_deref().unwrap_or(""),
+            &m.name,
+            m.first_name.as_deref().unwrap_or(""),
+            m.email.as_deref().unwrap_or(""),
+            m.phone.as_deref().unwrap_or(""),
+            m.member_since.as_deref().unwrap_or(""),
+            m.membership_expires_at.as_deref().unwrap_or(""),
+            &m.membership_fee_paid
+                .map(|fee| format!("{fee:.2}"))
+                .unwrap_or_default(),
+            if lapsed_on(m, today) {
+                "lapsed"
+            } else {
+                "current"
+            },
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_contact(db: &DatabaseConnection, name: &str) -> contact::Model {
+        let now = chrono::Utc::now().to_rfc3339();
+        contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set(name.to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn lapsed_members_are_told_apart_from_current_ones() {
+        let db = setup().await;
+        let alice = insert_contact(&db, "Alice").await;
+        let bob = insert_contact(&db, "Bob").await;
+        insert_contact(&db, "Carol").await;
+
+        set_membership(
+            &db,
+            &alice.id,
+            Membership {
+                member_number: Some("A-002".to_string()),
+                member_since: Some("2020-09-01".to_string()),
+                expires_at: Some("2999-08-31".to_string()),
+                fee_paid: Some(15.0),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        set_membership(
+            &db,
+            &bob.id,
+            Membership {
+                member_number: Some("A-001".to_string()),
+                member_since: Some("2019-09-01".to_string()),
+                expires_at: Some("2020-08-31".to_string()),
+                fee_paid: None,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(lapsed_membership(&db, &alice.id).await.unwrap(), None);
+        assert_eq!(
+            lapsed_membership(&db, &bob.id).await.unwrap().as_deref(),
+            Some("2020-08-31")
+        );
+
+        let names = |members: Vec<contact::Model>| -> Vec<String> {
+            members.into_iter().map(|m| m.name).collect()
+        };
+        assert_eq!(
+            names(list_members(&db, MemberStatus::All).await.unwrap()),
+            ["Bob", "Alice"]
+        );
+        assert_eq!(
+            names(list_members(&db, MemberStatus::Lapsed).await.unwrap()),
+            ["Bob"]
+        );
+
+        let members = list_members(&db, MemberStatus::Current).await.unwrap();
+        let csv = members_csv(&members, chrono::Local::now().date_naive()).unwrap();
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("A-002,Alice,,,,2020-09-01,2999-08-31,15.00,current")
+        );
+    }
+}
@@ -445,6 +445,9 @@ mod tests {
             cover_url: None,
             summary: Some("S".into()),
             page_count: Some(250),
+            edition: None,
+            physical_format: None,
+            dimensions: None,
         };
         let g = gap_values_from(meta);
         assert_eq!(g.publisher.as_deref(), Some("P"));
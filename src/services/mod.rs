@@ -9,44 +9,98 @@ pub mod account_session_service;
 pub mod account_signup_service;
 pub mod account_sync_client;
 pub mod account_sync_engine;
+pub mod acquisitions;
+pub mod announcements;
+pub mod api_quota;
+pub mod author_match;
+pub mod author_normalize;
+pub mod author_works;
+pub mod availability;
+pub mod book_covers;
+pub mod book_relations;
 pub mod book_service;
 pub mod catalog_events;
 pub mod catalog_notification;
+pub mod cert_pins;
 pub mod collection_service;
+pub mod collection_source;
+pub mod config_events;
+pub mod config_history;
+pub mod consistency;
+pub mod contact_portal;
+pub mod content_blobs;
+pub mod contact_routes;
 pub mod contact_service;
+pub mod copy_calendar;
+pub mod copy_history;
+pub mod copy_transfer_service;
 #[cfg(feature = "account_sync")]
 pub mod cover_sync;
 #[cfg(any(feature = "crsqlite", feature = "crsqlite-static"))]
 pub mod crsqlite_engine;
 pub mod crypto_service;
+pub mod db_maintenance;
 pub mod delta_service;
+pub mod device_sync;
+pub mod digital_editions;
 pub mod e2ee_transport;
+pub mod fines;
 pub mod gamification_service;
+pub mod geocoding;
+pub mod hold_expiry;
 pub mod hub_directory_service;
+pub mod hub_sync;
 pub mod identity_service;
+pub mod images;
+pub mod kiosk;
 pub mod leaderboard_events;
 pub mod loan_service;
 pub mod lookup_service;
+pub mod membership;
 pub mod mcp_tool_service;
 pub mod mdns;
+pub mod network_mode;
+pub mod nl_query;
 pub mod metadata_fill_service;
 pub mod notification_service;
 pub mod nudge_events;
+pub mod oplog_compaction;
 pub mod oplog_pruner;
+pub mod outgoing_request_sync;
+pub mod p2p_holds;
+pub mod p2p_stats;
+pub mod peer_outbox;
+pub mod peer_import;
+pub mod peer_protocol;
+pub mod peer_subscriptions;
 pub mod peer_delta_sync;
 pub mod peer_identity_sync;
+pub mod periodicals;
 pub mod profile_events;
 pub mod profile_notification;
+pub mod quality_report;
+pub mod reading_queue;
+pub mod reading_stats;
 pub mod relay_poller;
 pub mod relay_session;
 pub mod relay_transport;
+pub mod request_cleanup;
 pub mod sale_service; // Service de vente pour profil Libraire
+pub mod saved_searches;
+pub mod scan_training;
+pub mod search_suggest;
+pub mod speech;
+pub mod subject_authority;
+pub mod suggestions;
+pub mod tor;
+pub mod weeding;
+pub mod works;
 pub mod ws_nudge;
 
 // Re-export for convenience
 pub use book_service::*;
 pub use identity_service::IdentityService;
 pub use mdns::{
-    DiscoveredPeer, MAX_DISCOVERED_PEERS, get_local_peer_count, get_local_peers, init_mdns,
-    is_mdns_active, restart_mdns, stop_mdns,
+    AnnouncedRecord, DiscoveredPeer, MAX_DISCOVERED_PEERS, announced_record, get_local_peer_count,
+    get_local_peers, init_mdns, is_mdns_active, restart_mdns, stop_mdns,
 };
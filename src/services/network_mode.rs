@@ -0,0 +1,309 @@
+//! Network mode: how far outbound requests may go.
+//!
+//! - `full`: anywhere (the default).
+//! - `lan_only`: only hosts on the local network (private, link-local and
+//!   loopback addresses). Metadata providers, the hub and the relay are cut
+//!   off while peers in the same building stay reachable: a classroom can run
+//!   without anything leaving the school network.
+//! - `offline`: nothing but the machine itself (loopback).
+//!
+//! The mode is enforced where requests leave, in the HTTP clients built by
+//! `client_builder`: their DNS resolver only hands out the addresses the mode
+//! allows (and, offline, does not query DNS at all), and URLs naming an IP
+//! address the mode does not allow are sent to a proxy that cannot be
+//! reached. System proxies are ignored outside `full`, as they would relay
+//! requests to any host. A blocked request fails like an unreachable host
+//! would, so callers need no special handling. Connections opened outside
+//! reqwest (the relay WebSocket) go to the addresses of `allowed_addrs`.
+//!
+//! The mode is read by every client built, so it is kept in memory, loaded at
+//! startup (`load`) and updated with the table (`set`).
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+static MODE: RwLock<NetworkMode> = RwLock::new(NetworkMode::Full);
+
+/// Host of the proxy blocked requests are sent to; never resolved.
+const BLOCKED_PROXY_HOST: &str = "blocked.network-mode.invalid";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkMode {
+    /// Only the machine itself
+    Offline,
+    /// Only the local network
+    LanOnly,
+    /// Anywhere
+    #[default]
+    Full,
+}
+
+impl NetworkMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NetworkMode::Offline => "offline",
+            NetworkMode::LanOnly => "lan_only",
+            NetworkMode::Full => "full",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        [
+            NetworkMode::Offline,
+            NetworkMode::LanOnly,
+            NetworkMode::Full,
+        ]
+        .into_iter()
+        .find(|m| m.as_str() == s)
+    }
+
+    /// Whether a request may be sent to `ip`.
+    pub fn allows(self, ip: IpAddr) -> bool {
+        match self {
+            NetworkMode::Full => true,
+            NetworkMode::LanOnly => is_local(ip),
+            NetworkMode::Offline => ip.is_loopback(),
+        }
+    }
+}
+
+/// Loopback, private (RFC 1918, IPv6 unique local) and link-local addresses.
+pub fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_local(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// The mode in force.
+pub fn current() -> NetworkMode {
+    MODE.read().map(|m| *m).unwrap_or_default()
+}
+
+/// Load the mode into memory. Called once the database is migrated.
+pub async fn load(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT mode FROM network_settings WHERE id = 1",
+        ))
+        .await?;
+    let mode = match row {
+        Some(row) => {
+            let mode: String = row.try_get("", "mode")?;
+            NetworkMode::parse(&mode).unwrap_or_default()
+        }
+        None => NetworkMode::Full,
+    };
+    if let Ok(mut current) = MODE.write() {
+        *current = mode;
+    }
+    Ok(())
+}
+
+/// Store and apply a new mode. Clients already built keep the mode they
+/// were built with; most are built per request.
+pub async fn set(db: &DatabaseConnection, mode: NetworkMode) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "INSERT INTO network_settings (id, mode, updated_at) VALUES (1, ?, ?) \
+         ON CONFLICT(id) DO UPDATE SET mode = excluded.mode, updated_at = excluded.updated_at",
+        [mode.as_str().into(), chrono::Utc::now().to_rfc3339().into()],
+    ))
+    .await?;
+    if let Ok(mut current) = MODE.write() {
+        *current = mode;
+    }
+    tracing::info!("network mode: {}", mode.as_str());
+    Ok(())
+}
+
+/// Whether a request to `url` is refused by `mode` before any lookup:
+/// URLs naming a disallowed IP address, and, offline, any host name but
+/// `localhost`.
+fn blocks_url(mode: NetworkMode, url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Ipv4(ip)) => !mode.allows(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => !mode.allows(IpAddr::V6(ip)),
+        Some(url::Host::Domain(name)) => mode == NetworkMode::Offline && !is_localhost(name),
+        None => false,
+    }
+}
+
+fn is_localhost(name: &str) -> bool {
+    let name = name.trim_end_matches('.');
+    name.eq_ignore_ascii_case("localhost") || name.to_ascii_lowercase().ends_with(".localhost")
+}
+
+#[derive(Debug)]
+struct Blocked {
+    host: String,
+    mode: NetworkMode,
+}
+
+impl std::fmt::Display for Blocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.host == BLOCKED_PROXY_HOST {
+            write!(
+                f,
+                "request blocked by the {} network mode",
+                self.mode.as_str()
+            )
+        } else {
+            write!(
+                f,
+                "{} is not reachable in the {} network mode",
+                self.host,
+                self.mode.as_str()
+            )
+        }
+    }
+}
+
+impl std::error::Error for Blocked {}
+
+/// The addresses of `host` that `mode` allows. Fails with
+/// `ErrorKind::PermissionDenied` when there is none, and, offline, without
+/// querying DNS for any host but `localhost`.
+pub async fn allowed_addrs(
+    mode: NetworkMode,
+    host: &str,
+    port: u16,
+) -> std::io::Result<Vec<SocketAddr>> {
+    let blocked = || {
+        std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            Blocked {
+                host: host.to_string(),
+                mode,
+            },
+        )
+    };
+    if host == BLOCKED_PROXY_HOST || (mode == NetworkMode::Offline && !is_localhost(host)) {
+        return Err(blocked());
+    }
+    let allowed: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await?
+        .filter(|addr| mode.allows(addr.ip()))
+        .collect();
+    if allowed.is_empty() {
+        return Err(blocked());
+    }
+    Ok(allowed)
+}
+
+/// Resolves host names to the addresses the network mode allows.
+struct ModeResolver;
+
+impl reqwest::dns::Resolve for ModeResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let allowed = allowed_addrs(current(), &host, 0).await?;
+            Ok(Box::new(allowed.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// `reqwest::Client::builder()` bound to the network mode in force. Every
+/// outbound HTTP client starts from here.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder().dns_resolver(Arc::new(ModeResolver));
+    let mode = current();
+    if mode == NetworkMode::Full {
+        return builder;
+    }
+    builder.no_proxy().proxy(reqwest::Proxy::custom(move |url| {
+        blocks_url(mode, url).then(|| format!("http://{BLOCKED_PROXY_HOST}"))
+    }))
+}
+
+/// A client with default settings, bound to the network mode.
+pub fn client() -> reqwest::Client {
+    client_builder().build().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lan_only_keeps_requests_on_the_local_network() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        for local in [
+            "192.168.1.20",
+            "10.0.0.5",
+            "172.20.1.1",
+            "169.254.3.4",
+            "fd12::1",
+        ] {
+            assert!(NetworkMode::LanOnly.allows(ip(local)), "{local}");
+            assert!(!NetworkMode::Offline.allows(ip(local)), "{local}");
+        }
+        for public in [
+            "93.184.216.34",
+            "172.32.0.1",
+            "2001:db8::1",
+            "::ffff:8.8.8.8",
+        ] {
+            assert!(!NetworkMode::LanOnly.allows(ip(public)), "{public}");
+            assert!(NetworkMode::Full.allows(ip(public)), "{public}");
+        }
+        assert!(NetworkMode::Offline.allows(ip("127.0.0.1")));
+        assert!(NetworkMode::Offline.allows(ip("::1")));
+
+        let url = |s: &str| url::Url::parse(s).unwrap();
+        assert!(blocks_url(NetworkMode::LanOnly, &url("http://8.8.8.8/")));
+        assert!(!blocks_url(
+            NetworkMode::LanOnly,
+            &url("http://192.168.1.20:8000/api")
+        ));
+        // Names are left to the resolver, except offline.
+        assert!(!blocks_url(
+            NetworkMode::LanOnly,
+            &url("https://openlibrary.org/")
+        ));
+        assert!(blocks_url(
+            NetworkMode::Offline,
+            &url("https://openlibrary.org/")
+        ));
+        assert!(!blocks_url(
+            NetworkMode::Offline,
+            &url("http://localhost:8000/")
+        ));
+    }
+
+    #[tokio::test]
+    async fn only_allowed_addresses_are_handed_out() {
+        let refused = allowed_addrs(NetworkMode::LanOnly, "93.184.216.34", 443)
+            .await
+            .unwrap_err();
+        assert_eq!(refused.kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            allowed_addrs(NetworkMode::LanOnly, "192.168.1.20", 8000)
+                .await
+                .unwrap(),
+            ["192.168.1.20:8000".parse::<SocketAddr>().unwrap()]
+        );
+        assert!(
+            allowed_addrs(NetworkMode::Full, "93.184.216.34", 443)
+                .await
+                .is_ok()
+        );
+        // Offline, a name is refused before any lookup.
+        let offline = allowed_addrs(NetworkMode::Offline, "hub.bibliogenius.org", 443)
+            .await
+            .unwrap_err();
+        assert_eq!(offline.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,621 @@
+//! Natural-language book queries: "unread sci-fi from the 80s rated above 4"
+//! becomes the structured filters of `book_service::list_books` (reading
+//! status, tag, publication year range, lowest rating, author).
+//!
+//! English and French keywords are understood. Words that match no rule are
+//! kept as a title search, so "unread dune" still finds Dune. Used by
+//! `GET /api/search/nl` and by the chat assistant (`api::chat`).
+
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::services::book_service::BookFilter;
+
+/// Filters read from a query, ready for `list_books`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct ParsedQuery {
+    /// `to_read`, `reading`, `read`, `wanting` or `abandoned`
+    pub status: Option<String>,
+    /// Matched against the subjects
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    /// Publication year range, both ends included
+    pub year_min: Option<i32>,
+    pub year_max: Option<i32>,
+    /// Lowest rating on the 0-10 scale of `user_rating`
+    pub min_rating: Option<i32>,
+    /// Words no rule understood, searched in the title
+    pub title: Option<String>,
+}
+
+impl ParsedQuery {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether anything beyond title words was read.
+    pub fn has_filters(&self) -> bool {
+        self.status.is_some()
+            || self.tag.is_some()
+            || self.author.is_some()
+            || self.year_min.is_some()
+            || self.year_max.is_some()
+            || self.min_rating.is_some()
+    }
+
+    pub fn into_filter(self) -> BookFilter {
+        BookFilter {
+            status: self.status,
+            author: self.author,
+            title: self.title,
+            tag: self.tag,
+            archived: None,
+            year_min: self.year_min,
+            year_max: self.year_max,
+            min_rating: self.min_rating,
+        }
+    }
+}
+
+/// Reading statuses, longest phrase first so "not read" wins over "read".
+const STATUSES: &[(&[&str], &str)] = &[
+    (&["pas", "encore", "lu"], "to_read"),
+    (&["pas", "encore", "lus"], "to_read"),
+    (&["not", "yet", "read"], "to_read"),
+    (&["have", "not", "read"], "to_read"),
+    (&["en", "cours", "de", "lecture"], "reading"),
+    (&["not", "read"], "to_read"),
+    (&["haven't", "read"], "to_read"),
+    (&["never", "read"], "to_read"),
+    (&["to", "read"], "to_read"),
+    (&["non", "lu"], "to_read"),
+    (&["non", "lus"], "to_read"),
+    (&["pas", "lu"], "to_read"),
+    (&["pas", "lus"], "to_read"),
+    (&["a", "lire"], "to_read"),
+    (&["currently", "reading"], "reading"),
+    (&["en", "cours"], "reading"),
+    (&["already", "read"], "read"),
+    (&["deja", "lu"], "read"),
+    (&["deja", "lus"], "read"),
+    (&["a", "acheter"], "wanting"),
+    (&["unread"], "to_read"),
+    (&["reading"], "reading"),
+    (&["read"], "read"),
+    (&["finished"], "read"),
+    (&["lu"], "read"),
+    (&["lus"], "read"),
+    (&["lue"], "read"),
+    (&["lues"], "read"),
+    (&["termines"], "read"),
+    (&["abandoned"], "abandoned"),
+    (&["dnf"], "abandoned"),
+    (&["abandonne"], "abandoned"),
+    (&["abandonnes"], "abandoned"),
+    (&["wishlist"], "wanting"),
+    (&["wanted"], "wanting"),
+    (&["envies"], "wanting"),
+];
+
+/// Genre words and the subject each is searched as.
+const GENRES: &[(&[&str], &str)] = &[
+    (&["science", "fiction"], "science fiction"),
+    (&["bande", "dessinee"], "bande dessinée"),
+    (&["bandes", "dessinees"], "bande dessinée"),
+    (&["sci-fi"], "science fiction"),
+    (&["scifi"], "science fiction"),
+    (&["science-fiction"], "science fiction"),
+    (&["sf"], "science fiction"),
+    (&["fantasy"], "fantasy"),
+    (&["fantastique"], "fantastique"),
+    (&["horror"], "horror"),
+    (&["horreur"], "horreur"),
+    (&["mystery"], "mystery"),
+    (&["mysteries"], "mystery"),
+    (&["crime"], "crime"),
+    (&["polar"], "policier"),
+    (&["polars"], "policier"),
+    (&["policier"], "policier"),
+    (&["policiers"], "policier"),
+    (&["thriller"], "thriller"),
+    (&["thrillers"], "thriller"),
+    (&["romance"], "romance"),
+    (&["poetry"], "poetry"),
+    (&["poesie"], "poésie"),
+    (&["biography"], "biography"),
+    (&["biographies"], "biography"),
+    (&["biographie"], "biographie"),
+    (&["comics"], "comics"),
+    (&["bd"], "bande dessinée"),
+    (&["manga"], "manga"),
+    (&["mangas"], "manga"),
+    (&["history"], "history"),
+    (&["histoire"], "histoire"),
+    (&["philosophy"], "philosophy"),
+    (&["philosophie"], "philosophie"),
+    (&["dystopia"], "dystopia"),
+    (&["dystopian"], "dystopia"),
+    (&["dystopie"], "dystopie"),
+    (&["cookbook"], "cooking"),
+    (&["cookbooks"], "cooking"),
+    (&["cuisine"], "cuisine"),
+    (&["jeunesse"], "jeunesse"),
+    (&["classics"], "classics"),
+    (&["classiques"], "classiques"),
+];
+
+/// Words that introduce a rating.
+const RATING_WORDS: &[&str] = &[
+    "rated", "rating", "ratings", "note", "notes", "notee", "notees",
+];
+/// Words that make a rating bound strict ("above 4").
+const STRICT_WORDS: &[&str] = &[
+    "above",
+    "over",
+    "more",
+    "greater",
+    "higher",
+    "better",
+    ">",
+    "plus",
+    "dessus",
+    "superieur",
+    "superieure",
+];
+const STAR_WORDS: &[&str] = &["star", "stars", "etoile", "etoiles"];
+
+/// Words that may sit before a year or decade ("from the 80s", "dans les
+/// annees 80") and go with it.
+const YEAR_LEADS: &[&str] = &["from", "in", "the", "of", "des", "les", "dans", "de", "en"];
+
+/// Words that end an author name ("by Asimov from the 80s").
+const AUTHOR_ENDS: &[&str] = &[
+    "from",
+    "in",
+    "since",
+    "before",
+    "after",
+    "between",
+    "rated",
+    "with",
+    "that",
+    "published",
+    "depuis",
+    "avant",
+    "apres",
+    "entre",
+    "avec",
+    "parus",
+    "publies",
+    "notes",
+];
+
+/// Filler trimmed from the edges of the leftover title words.
+const STOPWORDS: &[&str] = &[
+    "a",
+    "all",
+    "an",
+    "and",
+    "any",
+    "are",
+    "book",
+    "books",
+    "du",
+    "de",
+    "des",
+    "et",
+    "find",
+    "for",
+    "i",
+    "in",
+    "l",
+    "la",
+    "le",
+    "les",
+    "list",
+    "livre",
+    "livres",
+    "me",
+    "mes",
+    "my",
+    "novel",
+    "novels",
+    "of",
+    "or",
+    "ou",
+    "published",
+    "parus",
+    "publies",
+    "roman",
+    "romans",
+    "show",
+    "some",
+    "that",
+    "the",
+    "trouve",
+    "un",
+    "une",
+    "what",
+    "which",
+    "with",
+];
+
+struct Token {
+    /// As typed, for names and titles
+    raw: String,
+    /// Lowercase without accents, for matching
+    key: String,
+    used: bool,
+}
+
+fn fold(word: &str) -> String {
+    word.to_lowercase()
+        .replace('’', "'")
+        .nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .collect()
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    // A closing full stop, not the one of "K." in "Ursula K. Le Guin".
+    text.trim_end()
+        .trim_end_matches(['.', '!', '?'])
+        .split(|c: char| c.is_whitespace() || ",;:!?()\"«»".contains(c))
+        .filter(|w| !w.is_empty())
+        .map(|w| Token {
+            raw: w.to_string(),
+            key: fold(w),
+            used: false,
+        })
+        .collect()
+}
+
+/// The phrase starting at `i`, when none of its words is taken yet.
+fn phrase_at(tokens: &[Token], i: usize, phrase: &[&str]) -> bool {
+    tokens.len() >= i + phrase.len()
+        && tokens[i..i + phrase.len()]
+            .iter()
+            .zip(phrase)
+            .all(|(t, p)| !t.used && t.key == *p)
+}
+
+fn take(tokens: &mut [Token], from: usize, to: usize) {
+    for t in &mut tokens[from..to] {
+        t.used = true;
+    }
+}
+
+/// Also take the lead words right before `i` ("from the" in "from the 80s").
+fn take_leads(tokens: &mut [Token], mut i: usize) {
+    while i > 0 && !tokens[i - 1].used && YEAR_LEADS.contains(&tokens[i - 1].key.as_str()) {
+        i -= 1;
+        tokens[i].used = true;
+    }
+}
+
+fn parse_year(key: &str) -> Option<i32> {
+    if key.len() != 4 {
+        return None;
+    }
+    key.parse().ok().filter(|y| (1000..=2100).contains(y))
+}
+
+/// "80s", "'80s", "1980s" or "eighties" as a year range.
+fn parse_decade(key: &str) -> Option<(i32, i32)> {
+    let named = match key {
+        "fifties" => Some(1950),
+        "sixties" => Some(1960),
+        "seventies" => Some(1970),
+        "eighties" => Some(1980),
+        "nineties" => Some(1990),
+        _ => None,
+    };
+    if let Some(start) = named {
+        return Some((start, start + 9));
+    }
+    let digits = key.replace('\'', "");
+    let digits = digits.strip_suffix('s')?;
+    decade_number(digits)
+}
+
+/// "80" or "1980" as a decade (the French "annees 80").
+fn decade_number(digits: &str) -> Option<(i32, i32)> {
+    let start = match digits.len() {
+        2 => {
+            let n: i32 = digits.parse().ok()?;
+            // "20s" is this century's; "50s" the last.
+            if n <= 20 { 2000 + n } else { 1900 + n }
+        }
+        4 => parse_year(digits)?,
+        _ => return None,
+    };
+    (start % 10 == 0).then_some((start, start + 9))
+}
+
+/// A rating as typed ("4", "4.5", "4+", "8/10", "4/5") on the 0-10 scale.
+/// Bare numbers up to 5 are stars.
+fn parse_rating(key: &str) -> Option<(i32, bool)> {
+    let (number, or_more) = match key.strip_suffix('+') {
+        Some(n) => (n, true),
+        None => (key, false),
+    };
+    let (value, scale) = match number.split_once('/') {
+        Some((n, "10")) => (n.parse::<f32>().ok()?, 10.0),
+        Some((n, "5")) => (n.parse::<f32>().ok()?, 5.0),
+        Some(_) => return None,
+        None => {
+            let n = number.parse::<f32>().ok()?;
+            (n, if n <= 5.0 { 5.0 } else { 10.0 })
+        }
+    };
+    if !(0.0..=scale).contains(&value) {
+        return None;
+    }
+    Some(((value * 10.0 / scale).round() as i32, or_more))
+}
+
+fn parse_ratings(tokens: &mut [Token], query: &mut ParsedQuery) {
+    for j in 0..tokens.len() {
+        if tokens[j].used || query.min_rating.is_some() {
+            continue;
+        }
+        let Some((value, or_more)) = parse_rating(&tokens[j].key) else {
+            continue;
+        };
+        let stars_next = tokens
+            .get(j + 1)
+            .is_some_and(|t| STAR_WORDS.contains(&t.key.as_str()));
+        // The rating word up to four words back: "with a rating of at least 8".
+        let lead = (j.saturating_sub(4)..j)
+            .rev()
+            .find(|&k| !tokens[k].used && RATING_WORDS.contains(&tokens[k].key.as_str()));
+        if lead.is_none() && !stars_next {
+            continue;
+        }
+        let from = lead.unwrap_or(j.saturating_sub(3));
+        let strict = !or_more
+            && tokens[from..j]
+                .iter()
+                .any(|t| !t.used && STRICT_WORDS.contains(&t.key.as_str()));
+        let mut to = j + 1;
+        if stars_next {
+            to += 1;
+        }
+        // "4 stars or more"
+        if tokens.len() >= to + 2
+            && ["or", "ou"].contains(&tokens[to].key.as_str())
+            && ["more", "better", "plus", "above"].contains(&tokens[to + 1].key.as_str())
+        {
+            to += 2;
+        }
+        let start = match lead {
+            Some(k) => k,
+            None => (from..j)
+                .find(|&k| {
+                    tokens[k..j].iter().all(|t| {
+                        !t.used
+                            && (STRICT_WORDS.contains(&t.key.as_str())
+                                || ["than", "at", "least", "with", "de", "a", "au", "moins"]
+                                    .contains(&t.key.as_str()))
+                    })
+                })
+                .unwrap_or(j),
+        };
+        take(tokens, start, to);
+        let min = if strict { value + 1 } else { value };
+        query.min_rating = Some(min.min(10));
+    }
+}
+
+fn parse_years(tokens: &mut [Token], query: &mut ParsedQuery) {
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].used {
+            i += 1;
+            continue;
+        }
+        let key = tokens[i].key.clone();
+        let next_year = tokens
+            .get(i + 1)
+            .filter(|t| !t.used)
+            .and_then(|t| parse_year(&t.key));
+
+        // "between 1990 and 2000", "entre 1990 et 2000"
+        if ["between", "entre"].contains(&key.as_str())
+            && let Some(a) = next_year
+            && tokens
+                .get(i + 2)
+                .is_some_and(|t| ["and", "et"].contains(&t.key.as_str()))
+            && let Some(b) = tokens.get(i + 3).and_then(|t| parse_year(&t.key))
+        {
+            query.year_min = Some(a.min(b));
+            query.year_max = Some(a.max(b));
+            take(tokens, i, i + 4);
+            i += 4;
+            continue;
+        }
+
+        // "since 1990", "after 1990", "from 1990 to 2000", "in 1995"
+        if let Some(year) = next_year {
+            match key.as_str() {
+                "since" | "depuis" => query.year_min = Some(year),
+                "after" | "apres" => query.year_min = Some(year + 1),
+                "before" | "avant" => query.year_max = Some(year - 1),
+                "until" | "till" | "jusqu'en" | "jusqu'a" => query.year_max = Some(year),
+                "from" | "in" | "en" | "de" => {
+                    let end = tokens
+                        .get(i + 2)
+                        .filter(|t| ["to", "until", "a", "au", "-"].contains(&t.key.as_str()))
+                        .and_then(|_| tokens.get(i + 3))
+                        .and_then(|t| parse_year(&t.key));
+                    query.year_min = Some(year);
+                    query.year_max = Some(end.unwrap_or(year));
+                    if end.is_some() {
+                        take(tokens, i, i + 4);
+                        i += 4;
+                        continue;
+                    }
+                }
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            take(tokens, i, i + 2);
+            take_leads(tokens, i);
+            i += 2;
+            continue;
+        }
+
+        // "1980-1990"
+        if let Some((a, b)) = key.split_once('-')
+            && let (Some(a), Some(b)) = (parse_year(a), parse_year(b))
+        {
+            query.year_min = Some(a.min(b));
+            query.year_max = Some(a.max(b));
+            take(tokens, i, i + 1);
+            take_leads(tokens, i);
+            i += 1;
+            continue;
+        }
+
+        // "the 80s", "les annees 80"
+        let decade = if key == "annees" {
+            tokens
+                .get(i + 1)
+                .and_then(|t| decade_number(&t.key))
+                .map(|d| (d, 2))
+        } else {
+            parse_decade(&key).map(|d| (d, 1))
+        };
+        if let Some(((start, end), len)) = decade {
+            query.year_min = Some(start);
+            query.year_max = Some(end);
+            take(tokens, i, i + len);
+            take_leads(tokens, i);
+            i += len;
+            continue;
+        }
+        i += 1;
+    }
+}
+
+/// The first phrase of `table` found in the free words, taken.
+fn find_phrase(tokens: &mut [Token], table: &[(&[&str], &'static str)]) -> Option<&'static str> {
+    for i in 0..tokens.len() {
+        for (phrase, value) in table {
+            if phrase_at(tokens, i, phrase) {
+                take(tokens, i, i + phrase.len());
+                return Some(*value);
+            }
+        }
+    }
+    None
+}
+
+fn parse_author(tokens: &mut [Token], query: &mut ParsedQuery) {
+    let Some(by) = tokens
+        .iter()
+        .position(|t| !t.used && ["by", "par"].contains(&t.key.as_str()))
+    else {
+        return;
+    };
+    let end = (by + 1..tokens.len())
+        .find(|&k| tokens[k].used || AUTHOR_ENDS.contains(&tokens[k].key.as_str()))
+        .unwrap_or(tokens.len());
+    if end > by + 1 {
+        let name: Vec<&str> = tokens[by + 1..end].iter().map(|t| t.raw.as_str()).collect();
+        query.author = Some(name.join(" "));
+        take(tokens, by, end);
+    }
+}
+
+/// "about cats", "tagged cats": a tag no genre word names.
+fn parse_free_tag(tokens: &mut [Token], query: &mut ParsedQuery) {
+    for i in 0..tokens.len().saturating_sub(1) {
+        if !tokens[i].used
+            && ["about", "tagged", "tag"].contains(&tokens[i].key.as_str())
+            && !tokens[i + 1].used
+            && !STOPWORDS.contains(&tokens[i + 1].key.as_str())
+        {
+            query.tag = Some(tokens[i + 1].raw.clone());
+            take(tokens, i, i + 2);
+            return;
+        }
+    }
+}
+
+/// Read the filters out of `text`. Never fails: an empty result means the
+/// query held nothing searchable.
+pub fn parse(text: &str) -> ParsedQuery {
+    let mut tokens = tokenize(text);
+    let mut query = ParsedQuery::default();
+
+    // Ratings first: their numbers are not years.
+    parse_ratings(&mut tokens, &mut query);
+    parse_years(&mut tokens, &mut query);
+    query.status = find_phrase(&mut tokens, STATUSES).map(str::to_string);
+    query.tag = find_phrase(&mut tokens, GENRES).map(str::to_string);
+    if query.tag.is_none() {
+        parse_free_tag(&mut tokens, &mut query);
+    }
+    parse_author(&mut tokens, &mut query);
+
+    let rest: Vec<&Token> = tokens.iter().filter(|t| !t.used).collect();
+    let is_filler = |t: &&Token| STOPWORDS.contains(&t.key.as_str());
+    let start = rest.iter().position(|t| !is_filler(t));
+    let end = rest.iter().rposition(|t| !is_filler(t));
+    if let (Some(start), Some(end)) = (start, end) {
+        let words: Vec<&str> = rest[start..=end].iter().map(|t| t.raw.as_str()).collect();
+        query.title = Some(words.join(" "));
+    }
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_status_genre_decade_and_rating() {
+        let q = parse("unread sci-fi from the 80s rated above 4");
+        assert_eq!(
+            q,
+            ParsedQuery {
+                status: Some("to_read".to_string()),
+                tag: Some("science fiction".to_string()),
+                year_min: Some(1980),
+                year_max: Some(1989),
+                min_rating: Some(9),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn reads_french_queries() {
+        let q = parse("romans policiers déjà lus des années 90 notés au moins 8/10");
+        assert_eq!(q.status.as_deref(), Some("read"));
+        assert_eq!(q.tag.as_deref(), Some("policier"));
+        assert_eq!((q.year_min, q.year_max), (Some(1990), Some(1999)));
+        assert_eq!(q.min_rating, Some(8));
+        assert_eq!(q.title, None);
+    }
+
+    #[test]
+    fn reads_year_bounds_authors_and_titles() {
+        let q = parse("fantasy by Ursula K. Le Guin published before 1975");
+        assert_eq!(q.author.as_deref(), Some("Ursula K. Le Guin"));
+        assert_eq!((q.year_min, q.year_max), (None, Some(1974)));
+
+        let q = parse("books between 2001 and 1995 with 4 stars or more");
+        assert_eq!((q.year_min, q.year_max), (Some(1995), Some(2001)));
+        assert_eq!(q.min_rating, Some(8));
+
+        // Unknown words are a title; a bare year is part of it.
+        let q = parse("not read 1984");
+        assert_eq!(q.status.as_deref(), Some("to_read"));
+        assert_eq!(q.title.as_deref(), Some("1984"));
+        assert!(parse("the books").is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+//! Per-entity compaction of `operation_log`.
+//!
+//! Ten edits to one book leave ten rows, yet a paired device pulling them
+//! gets the book's *current* row for each (`device_sync::snapshot_payload`),
+//! so every update but the newest says nothing new. Compaction keeps, per
+//! entity, the newest row plus the `INSERT` that created it, or only the
+//! `DELETE` when the entity is gone (its tombstone, kept so a device that
+//! still holds the entity learns of the deletion). The ids are the sequence
+//! cursors pull from, and the newest row of an entity is never removed, so a
+//! device whatever its cursor still reaches every entity's last state.
+//!
+//! Only local, already processed, unpinned rows of snapshotted entity types
+//! older than `OPLOG_COMPACT_AFTER_HOURS` (24 by default) are touched: the
+//! recent history stays readable in the operation log viewer. Runs before
+//! each `oplog_pruner` cycle.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::models::operation_log;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::Serialize;
+
+use super::oplog_pruner::PrunePolicy;
+
+const DEFAULT_COMPACT_AFTER_HOURS: i64 = 24;
+
+/// Types whose pulled payload is a snapshot of the current row; the others
+/// carry the change itself and cannot lose a step.
+const COMPACTED_ENTITY_TYPES: [&str; 5] = ["book", "copy", "contact", "loan", "library_config"];
+
+/// Rows deleted per statement (SQLite bound-parameter limit).
+const DELETE_CHUNK: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, utoipa::ToSchema)]
+pub struct CompactionPolicy {
+    /// Rows younger than this are left as logged
+    pub compact_after_hours: i64,
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self {
+            compact_after_hours: DEFAULT_COMPACT_AFTER_HOURS,
+        }
+    }
+}
+
+impl CompactionPolicy {
+    pub fn from_env() -> Self {
+        let compact_after_hours = std::env::var("OPLOG_COMPACT_AFTER_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_COMPACT_AFTER_HOURS);
+        Self {
+            compact_after_hours,
+        }
+    }
+}
+
+/// Outcome of one compaction run.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CompactionStats {
+    /// RFC 3339
+    pub ran_at: String,
+    /// Superseded rows deleted
+    pub rows_removed: u64,
+    /// Entities that lost at least one row
+    pub entities_compacted: u64,
+    /// Entities whose last row is a `DELETE`, after the run
+    pub tombstones: u64,
+}
+
+static LAST_RUN: Mutex<Option<CompactionStats>> = Mutex::new(None);
+
+/// The last run since startup, if any.
+pub fn last_run() -> Option<CompactionStats> {
+    LAST_RUN.lock().ok().and_then(|last| last.clone())
+}
+
+struct Row {
+    id: i32,
+    operation: String,
+    eligible: bool,
+}
+
+/// Ids of the rows of one entity (ascending) that its newest row supersedes.
+fn superseded(rows: &[Row]) -> Vec<i32> {
+    let Some(newest) = rows.last() else {
+        return Vec::new();
+    };
+    let gone = newest.operation.eq_ignore_ascii_case("delete");
+    let creation = if gone {
+        None
+    } else {
+        rows.iter()
+            .rev()
+            .find(|r| r.operation.eq_ignore_ascii_case("insert"))
+            .map(|r| r.id)
+    };
+    rows[..rows.len() - 1]
+        .iter()
+        .filter(|r| r.eligible && Some(r.id) != creation)
+        .map(|r| r.id)
+        .collect()
+}
+
+/// Run one compaction.
+pub async fn compact_once(
+    db: &DatabaseConnection,
+    policy: &CompactionPolicy,
+) -> Result<CompactionStats, DbErr> {
+    let cutoff =
+        (chrono::Utc::now() - chrono::Duration::hours(policy.compact_after_hours)).to_rfc3339();
+
+    // Every local row of the types, eligible or not: the newest row of an
+    // entity decides what its older rows are worth.
+    let rows: Vec<(i32, String, String, String, String, i32, String)> =
+        operation_log::Entity::find()
+            .select_only()
+            .columns([
+                operation_log::Column::Id,
+                operation_log::Column::EntityType,
+                operation_log::Column::EntityId,
+                operation_log::Column::Operation,
+                operation_log::Column::Status,
+                operation_log::Column::Pinned,
+                operation_log::Column::CreatedAt,
+            ])
+            .filter(operation_log::Column::Source.eq("local"))
+            .filter(operation_log::Column::EntityType.is_in(COMPACTED_ENTITY_TYPES))
+            .order_by_asc(operation_log::Column::Id)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+    let mut entities: HashMap<(String, String), Vec<Row>> = HashMap::new();
+    for (id, entity_type, entity_id, operation, status, pinned, created_at) in rows {
+        let eligible =
+            matches!(status.as_str(), "applied" | "skipped") && pinned == 0 && created_at < cutoff;
+        entities
+            .entry((entity_type, entity_id))
+            .or_default()
+            .push(Row {
+                id,
+                operation,
+                eligible,
+            });
+    }
+
+    let mut doomed = Vec::new();
+    let mut entities_compacted = 0;
+    let mut tombstones = 0;
+    for rows in entities.values() {
+        let ids = superseded(rows);
+        if !ids.is_empty() {
+            entities_compacted += 1;
+            doomed.extend(ids);
+        }
+        if rows
+            .last()
+            .is_some_and(|r| r.operation.eq_ignore_ascii_case("delete"))
+        {
+            tombstones += 1;
+        }
+    }
+
+    let mut rows_removed = 0;
+    for chunk in doomed.chunks(DELETE_CHUNK) {
+        rows_removed += operation_log::Entity::delete_many()
+            .filter(operation_log::Column::Id.is_in(chunk.iter().copied()))
+            .exec(db)
+            .await?
+            .rows_affected;
+    }
+    if rows_removed > 0 {
+        tracing::info!(
+            "oplog_compaction: removed {} superseded row(s) across {} entit(ies)",
+            rows_removed,
+            entities_compacted
+        );
+    }
+
+    let stats = CompactionStats {
+        ran_at: chrono::Utc::now().to_rfc3339(),
+        rows_removed,
+        entities_compacted,
+        tombstones,
+    };
+    if let Ok(mut last) = LAST_RUN.lock() {
+        *last = Some(stats.clone());
+    }
+    Ok(stats)
+}
+
+/// Size of the log and how it is kept, for `GET /api/sync/status`.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LogStatus {
+    pub rows: u64,
+    /// Rows per status (`pending`, `applied`, ...)
+    pub by_status: BTreeMap<String, u64>,
+    /// Bytes of logged payloads
+    pub payload_bytes: i64,
+    /// Lowest id still in the log: a cursor below it may have missed pruned
+    /// rows
+    pub oldest_sequence: Option<i32>,
+    /// Highest id: the cursor of a caller up to date
+    pub latest_sequence: Option<i32>,
+    pub oldest_at: Option<String>,
+    pub retention: PrunePolicy,
+    pub compaction: CompactionPolicy,
+    /// `None` until the first run since startup
+    pub last_compaction: Option<CompactionStats>,
+}
+
+pub async fn log_status(db: &DatabaseConnection) -> Result<LogStatus, DbErr> {
+    let counts: Vec<(String, i64)> = operation_log::Entity::find()
+        .select_only()
+        .column(operation_log::Column::Status)
+        .column_as(Expr::col(operation_log::Column::Id).count(), "rows")
+        .group_by(operation_log::Column::Status)
+        .into_tuple()
+        .all(db)
+        .await?;
+    let by_status: BTreeMap<String, u64> = counts
+        .into_iter()
+        .map(|(status, rows)| (status, rows as u64))
+        .collect();
+
+    let payload_bytes: Option<i64> = operation_log::Entity::find()
+        .select_only()
+        .column_as(
+            Expr::cust("COALESCE(SUM(LENGTH(payload)), 0)"),
+            "payload_bytes",
+        )
+        .into_tuple()
+        .one(db)
+        .await?;
+
+    let oldest = operation_log::Entity::find()
+        .order_by_asc(operation_log::Column::Id)
+        .one(db)
+        .await?;
+    let latest = operation_log::Entity::find()
+        .order_by_desc(operation_log::Column::Id)
+        .one(db)
+        .await?;
+
+    Ok(LogStatus {
+        rows: by_status.values().sum(),
+        by_status,
+        payload_bytes: payload_bytes.unwrap_or(0),
+        oldest_sequence: oldest.as_ref().map(|r| r.id),
+        latest_sequence: latest.map(|r| r.id),
+        oldest_at: oldest.map(|r| r.created_at),
+        retention: PrunePolicy::from_env(),
+        compaction: CompactionPolicy::from_env(),
+        last_compaction: last_run(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use sea_orm::Set;
+
+    async fn log(db: &DatabaseConnection, entity_id: &str, operation: &str, hours_ago: i64) -> i32 {
+        let row = operation_log::ActiveModel {
+            entity_type: Set("book".to_owned()),
+            entity_id: Set(entity_id.to_owned()),
+            operation: Set(operation.to_owned()),
+            payload: Set(None),
+            source: Set("local".to_owned()),
+            status: Set("applied".to_owned()),
+            created_at: Set((chrono::Utc::now() - chrono::Duration::hours(hours_ago)).to_rfc3339()),
+            ..Default::default()
+        };
+        operation_log::Entity::insert(row)
+            .exec(db)
+            .await
+            .unwrap()
+            .last_insert_id
+    }
+
+    async fn ids(db: &DatabaseConnection) -> Vec<i32> {
+        operation_log::Entity::find()
+            .order_by_asc(operation_log::Column::Id)
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn keeps_the_last_state_and_tombstones() {
+        let db = db::init_db("sqlite::memory:").await.unwrap();
+        let created = log(&db, "1", "INSERT", 72).await;
+        log(&db, "1", "UPDATE", 60).await;
+        log(&db, "1", "UPDATE", 50).await;
+        let last = log(&db, "1", "UPDATE", 48).await;
+
+        log(&db, "2", "INSERT", 72).await;
+        log(&db, "2", "UPDATE", 60).await;
+        let tombstone = log(&db, "2", "DELETE", 48).await;
+
+        // Recent history is left alone.
+        let old = log(&db, "3", "INSERT", 72).await;
+        let recent = log(&db, "3", "UPDATE", 1).await;
+        let newest = log(&db, "3", "UPDATE", 0).await;
+
+        let stats = compact_once(&db, &CompactionPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.rows_removed, 4);
+        assert_eq!(stats.entities_compacted, 2);
+        assert_eq!(stats.tombstones, 1);
+        assert_eq!(
+            ids(&db).await,
+            vec![created, last, tombstone, old, recent, newest]
+        );
+        assert_eq!(last_run().unwrap().rows_removed, 4);
+
+        let status = log_status(&db).await.unwrap();
+        assert_eq!(status.rows, 6);
+        assert_eq!(status.by_status["applied"], 6);
+        assert_eq!(status.latest_sequence, Some(newest));
+
+        let again = compact_once(&db, &CompactionPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(again.rows_removed, 0);
+    }
+}
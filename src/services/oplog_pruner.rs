@@ -8,6 +8,8 @@
 //! Designed to coexist with the legacy inline prune in `crate::sync`: the
 //! inline cap was raised to act as a runaway safety net only, while this
 //! module enforces the actual policy on a daily schedule plus once at boot.
+//! Each cycle first compacts the log (`oplog_compaction`), so the rows
+//! counted against `min_rows` are last states rather than superseded edits.
 
 use crate::models::operation_log;
 use sea_orm::*;
@@ -16,7 +18,7 @@ use sea_orm::*;
 const DEFAULT_RETENTION_DAYS: i64 = 90;
 const DEFAULT_MIN_ROWS: i64 = 10_000;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, serde::Serialize, utoipa::ToSchema)]
 pub struct PrunePolicy {
     pub retention_days: i64,
     pub min_rows: i64,
@@ -106,7 +108,18 @@ pub async fn prune_once(db: &DatabaseConnection, policy: &PrunePolicy) -> Result
     Ok(result.rows_affected)
 }
 
-/// Spawn the background task: one prune at startup, then daily.
+/// One maintenance cycle: compaction, then the retention prune.
+async fn run_cycle(db: &DatabaseConnection, policy: &PrunePolicy, when: &str) {
+    let compaction = super::oplog_compaction::CompactionPolicy::from_env();
+    if let Err(e) = super::oplog_compaction::compact_once(db, &compaction).await {
+        tracing::warn!("oplog_compaction {when}: {e}");
+    }
+    if let Err(e) = prune_once(db, policy).await {
+        tracing::warn!("oplog_pruner {when}: {e}");
+    }
+}
+
+/// Spawn the background task: one cycle at startup, then daily.
 ///
 /// The interval timer fires immediately on first tick, so we await it
 /// once before entering the loop to space out the daily run from the
@@ -114,16 +127,12 @@ pub async fn prune_once(db: &DatabaseConnection, policy: &PrunePolicy) -> Result
 pub fn spawn(db: DatabaseConnection) {
     let policy = PrunePolicy::from_env();
     tokio::spawn(async move {
-        if let Err(e) = prune_once(&db, &policy).await {
-            tracing::warn!("oplog_pruner startup: {e}");
-        }
+        run_cycle(&db, &policy, "startup").await;
         let mut ticker = tokio::time::interval(std::time::Duration::from_secs(86_400));
         ticker.tick().await; // consume the immediate first tick
         loop {
             ticker.tick().await;
-            if let Err(e) = prune_once(&db, &policy).await {
-                tracing::warn!("oplog_pruner daily: {e}");
-            }
+            run_cycle(&db, &policy, "daily").await;
         }
     });
 }
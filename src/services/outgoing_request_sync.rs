@@ -0,0 +1,32 @@
+//! Keeps our outgoing P2P requests in step with the lenders.
+//!
+//! A lender reports accept/reject/return through callbacks that are sent once
+//! and never retried, so a borrower that was offline or unreachable at the
+//! time kept the request `pending` forever. This task polls the lender of
+//! every open outgoing request (see `api::peer::sync_open_outgoing_requests`)
+//! and applies the answer, notifications included.
+
+use crate::infrastructure::AppState;
+
+/// Time between two polls.
+const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Delay before the first poll, so startup (relay registration, peer
+/// discovery) settles before lenders are queried.
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Spawn the background task: a first poll shortly after startup, then every
+/// fifteen minutes.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + STARTUP_DELAY;
+        let mut ticker = tokio::time::interval_at(start, SYNC_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let (queried, updated) = crate::api::peer::sync_open_outgoing_requests(&state).await;
+            if updated > 0 {
+                tracing::info!("outgoing_request_sync: {updated}/{queried} requests updated");
+            }
+        }
+    });
+}
@@ -0,0 +1,206 @@
+//! Serves peer requests waiting in line for a copy.
+//!
+//! A peer asking for a book whose copies are all out can opt into a hold: the
+//! request is recorded `waitlisted` instead of being turned down. Whenever a
+//! copy comes back on the shelf (a loan returned, a hold withdrawn or
+//! expired), the oldest waitlisted request for that book gets it: the copy is
+//! reserved and the request moves to `ready`, exactly as if the owner had put
+//! it on the hold shelf, and the requester is told. From there `hold_expiry` and the usual
+//! handover take over.
+
+use crate::infrastructure::AppState;
+use crate::models::{book, copy, p2p_request, peer};
+use sea_orm::*;
+
+/// The book a waitlisted request names: by ISBN, else by title.
+async fn requested_book(
+    db: &DatabaseConnection,
+    req: &p2p_request::Model,
+) -> Result<Option<book::Model>, DbErr> {
+    if !req.book_isbn.is_empty()
+        && let Some(b) = book::Entity::find()
+            .filter(book::Column::Isbn.eq(&req.book_isbn))
+            .one(db)
+            .await?
+    {
+        return Ok(Some(b));
+    }
+    book::Entity::find()
+        .filter(book::Column::Title.eq(&req.book_title))
+        .one(db)
+        .await
+}
+
+/// Run one pass. Returns the number of waitlisted requests put on the hold shelf.
+pub async fn serve_waitlist(state: &AppState) -> Result<u64, DbErr> {
+    let db = state.db();
+    let waiting = p2p_request::Entity::find()
+        .filter(p2p_request::Column::Status.eq("waitlisted"))
+        .order_by_asc(p2p_request::Column::CreatedAt)
+        .all(db)
+        .await?;
+    if waiting.is_empty() {
+        return Ok(0);
+    }
+
+    let hold_days = state
+        .loan_settings_repo
+        .get_settings()
+        .await
+        .map(|s| s.hold_expiry_days)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to read loan settings, using 7-day hold: {e}");
+            7
+        });
+
+    let mut served = 0;
+    for req in waiting {
+        let Some(book) = requested_book(db, &req).await? else {
+            continue;
+        };
+        let Some(free) = copy::Entity::find()
+            .filter(copy::Column::BookId.eq(&book.id))
+            .filter(copy::Column::Status.eq("available"))
+            .filter(copy::Column::IsTemporary.eq(false))
+            .order_by_asc(copy::Column::CreatedAt)
+            .one(db)
+            .await?
+        else {
+            continue;
+        };
+
+        let now = chrono::Utc::now();
+        let expires_at = (now + chrono::Duration::days(hold_days as i64)).to_rfc3339();
+        let copy_id = free.id.clone();
+        let mut active_copy: copy::ActiveModel = free.into();
+        active_copy.status = Set("reserved".to_string());
+        active_copy.updated_at = Set(now.to_rfc3339());
+        active_copy.update(db).await?;
+
+        let mut active: p2p_request::ActiveModel = req.clone().into();
+        active.status = Set("ready".to_string());
+        active.held_copy_id = Set(Some(copy_id.clone()));
+        active.hold_expires_at = Set(Some(expires_at.clone()));
+        active.updated_at = Set(now.to_rfc3339());
+        active.update(db).await?;
+        served += 1;
+        tracing::info!(
+            "p2p_holds: copy {} held for waitlisted request {} until {}",
+            copy_id,
+            req.id,
+            expires_at
+        );
+
+        if let Some(peer) = peer::Entity::find_by_id(req.from_peer_id).one(db).await? {
+            crate::api::peer::send_request_status_update(
+                state,
+                &peer,
+                &req,
+                "ready",
+                serde_json::json!({ "hold_expires_at": expires_at }),
+            )
+            .await;
+        }
+    }
+    Ok(served)
+}
+
+/// Serve the waitlist without holding up the caller: notifying a peer can
+/// wait on the relay.
+pub fn spawn_serve(state: &AppState) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = serve_waitlist(&state).await {
+            tracing::warn!("p2p_holds: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[tokio::test]
+    async fn a_returned_copy_goes_to_the_oldest_waitlisted_request() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let state = AppState::new(db.clone());
+        let now = chrono::Utc::now().to_rfc3339();
+        let book = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            isbn: Set(Some("978-1".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert book");
+        let lent = copy::ActiveModel {
+            book_id: Set(book.id),
+            library_id: Set(crate::utils::library_helpers::resolve_library_id(&db)
+                .await
+                .expect("library")),
+            status: Set("loaned".to_string()),
+            is_temporary: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert copy");
+        let peer = peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://127.0.0.1:9".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .expect("insert peer");
+        for (id, day) in [("second", 2), ("first", 1)] {
+            p2p_request::ActiveModel {
+                id: Set(id.to_string()),
+                from_peer_id: Set(peer.id),
+                book_isbn: Set("978-1".to_string()),
+                book_title: Set("Dune".to_string()),
+                status: Set("waitlisted".to_string()),
+                created_at: Set(format!("2026-01-0{day}T00:00:00+00:00")),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .expect("insert request");
+        }
+
+        // Still out: nothing to serve.
+        assert_eq!(serve_waitlist(&state).await.unwrap(), 0);
+
+        let mut back: copy::ActiveModel = lent.clone().into();
+        back.status = Set("available".to_string());
+        back.update(&db).await.unwrap();
+        assert_eq!(serve_waitlist(&state).await.unwrap(), 1);
+
+        let first = p2p_request::Entity::find_by_id("first")
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.status, "ready");
+        assert_eq!(first.held_copy_id.as_deref(), Some(lent.id.as_str()));
+        assert!(first.hold_expires_at.is_some());
+        let second = p2p_request::Entity::find_by_id("second")
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.status, "waitlisted");
+        let copy = copy::Entity::find_by_id(lent.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(copy.status, "reserved");
+    }
+}
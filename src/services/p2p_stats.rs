@@ -0,0 +1,310 @@
+//! Lending statistics across peers ("lending circle").
+//!
+//! Computed from the request history: a book counts as lent once an
+//! incoming request was accepted, as borrowed once an outgoing one was, and
+//! stays counted after it is returned. Rejected, cancelled and still pending
+//! requests are left out. Return time runs from the request to its return,
+//! the only two dates the history keeps.
+//!
+//! The figures are aggregates only, never individual loans:
+//! - a peer's average return time is shown once it returned
+//!   `MIN_RETURNS_FOR_AVERAGE` books, so a single loan cannot be read back
+//!   from it;
+//! - a title is listed among the most circulated only when it went to or
+//!   came from at least `MIN_PEERS_PER_TITLE` different peers, so the list
+//!   does not tell what one given peer reads.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{p2p_outgoing_request, p2p_request, peer};
+
+/// How many titles the most-circulated list holds.
+const TOP_TITLES: usize = 10;
+/// Returns needed before an average return time is shown.
+const MIN_RETURNS_FOR_AVERAGE: usize = 3;
+/// Different peers a title must have circulated with to be listed.
+const MIN_PEERS_PER_TITLE: usize = 2;
+
+/// Statuses of a request whose book actually changed hands.
+const CIRCULATED: [&str; 2] = ["accepted", "returned"];
+
+/// Lending with one peer.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct PeerLending {
+    pub peer_id: i32,
+    pub peer_name: String,
+    /// Books this library lent to the peer
+    pub lent: u32,
+    /// Books this library borrowed from the peer
+    pub borrowed: u32,
+    /// Loans between us, either way, that ended with a return
+    pub returned: u32,
+    /// Mean days from request to return; `None` under the minimum of returns
+    pub average_return_days: Option<f64>,
+}
+
+/// A title and how many times it changed hands.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct CirculatedTitle {
+    pub title: String,
+    pub isbn: Option<String>,
+    pub loans: u32,
+}
+
+/// Lending statistics across all peers.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct P2pStats {
+    pub lent: u32,
+    pub borrowed: u32,
+    pub returned: u32,
+    /// Mean days from request to return over every return; `None` under the
+    /// minimum of returns
+    pub average_return_days: Option<f64>,
+    /// Peers this library lent to or borrowed from, the most active first
+    pub peers: Vec<PeerLending>,
+    pub most_circulated: Vec<CirculatedTitle>,
+}
+
+/// One book that changed hands.
+struct Circulation {
+    peer_id: i32,
+    lent: bool,
+    isbn: String,
+    title: String,
+    return_days: Option<f64>,
+}
+
+fn parse_instant(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|d| d.and_utc())
+        })
+}
+
+/// Days from `created_at` to `updated_at` of a returned request.
+fn return_days(status: &str, created_at: &str, updated_at: &str) -> Option<f64> {
+    if status != "returned" {
+        return None;
+    }
+    let days =
+        (parse_instant(updated_at)? - parse_instant(created_at)?).num_seconds() as f64 / 86_400.0;
+    (days >= 0.0).then_some(days)
+}
+
+/// Mean of the days, to one decimal, once there are enough of them.
+fn average(days: &[f64]) -> Option<f64> {
+    if days.len() < MIN_RETURNS_FOR_AVERAGE {
+        return None;
+    }
+    let mean = days.iter().sum::<f64>() / days.len() as f64;
+    Some((mean * 10.0).round() / 10.0)
+}
+
+/// Lending statistics from the request history.
+pub async fn p2p_stats(db: &DatabaseConnection) -> Result<P2pStats, DbErr> {
+    let incoming = p2p_request::Entity::find()
+        .filter(p2p_request::Column::Status.is_in(CIRCULATED))
+        .all(db)
+        .await?;
+    let outgoing = p2p_outgoing_request::Entity::find()
+        .filter(p2p_outgoing_request::Column::Status.is_in(CIRCULATED))
+        .all(db)
+        .await?;
+
+    let circulations: Vec<Circulation> = incoming
+        .into_iter()
+        .map(|r| Circulation {
+            peer_id: r.from_peer_id,
+            lent: true,
+            return_days: return_days(&r.status, &r.created_at, &r.updated_at),
+            isbn: r.book_isbn,
+            title: r.book_title,
+        })
+        .chain(outgoing.into_iter().map(|r| Circulation {
+            peer_id: r.to_peer_id,
+            lent: false,
+            return_days: return_days(&r.status, &r.created_at, &r.updated_at),
+            isbn: r.book_isbn,
+            title: r.book_title,
+        }))
+        .collect();
+
+    let names: HashMap<i32, String> = peer::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p.display_name.unwrap_or(p.name)))
+        .collect();
+
+    Ok(aggregate(&circulations, &names))
+}
+
+fn aggregate(circulations: &[Circulation], names: &HashMap<i32, String>) -> P2pStats {
+    let mut by_peer: HashMap<i32, (u32, u32, Vec<f64>)> = HashMap::new();
+    // Keyed by ISBN, or by lowercased title when the ISBN is missing.
+    let mut by_title: HashMap<String, (String, Option<String>, u32, HashSet<i32>)> = HashMap::new();
+    for c in circulations {
+        let entry = by_peer.entry(c.peer_id).or_default();
+        if c.lent {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+        entry.2.extend(c.return_days);
+
+        let isbn = Some(c.isbn.trim().to_string()).filter(|i| !i.is_empty());
+        let key = isbn
+            .clone()
+            .unwrap_or_else(|| c.title.trim().to_lowercase());
+        let title = by_title
+            .entry(key)
+            .or_insert_with(|| (c.title.clone(), isbn, 0, HashSet::new()));
+        title.2 += 1;
+        title.3.insert(c.peer_id);
+    }
+
+    let all_days: Vec<f64> = circulations.iter().filter_map(|c| c.return_days).collect();
+    let mut peers: Vec<PeerLending> = by_peer
+        .into_iter()
+        .map(|(peer_id, (lent, borrowed, days))| PeerLending {
+            peer_id,
+            peer_name: names
+                .get(&peer_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Peer {}", peer_id)),
+            lent,
+            borrowed,
+            returned: days.len() as u32,
+            average_return_days: average(&days),
+        })
+        .collect();
+    peers.sort_by(|a, b| {
+        (b.lent + b.borrowed)
+            .cmp(&(a.lent + a.borrowed))
+            .then_with(|| a.peer_name.cmp(&b.peer_name))
+    });
+
+    let mut most_circulated: Vec<CirculatedTitle> = by_title
+        .into_values()
+        .filter(|(_, _, _, peers)| peers.len() >= MIN_PEERS_PER_TITLE)
+        .map(|(title, isbn, loans, _)| CirculatedTitle { title, isbn, loans })
+        .collect();
+    most_circulated.sort_by(|a, b| b.loans.cmp(&a.loans).then_with(|| a.title.cmp(&b.title)));
+    most_circulated.truncate(TOP_TITLES);
+
+    P2pStats {
+        lent: circulations.iter().filter(|c| c.lent).count() as u32,
+        borrowed: circulations.iter().filter(|c| !c.lent).count() as u32,
+        returned: all_days.len() as u32,
+        average_return_days: average(&all_days),
+        peers,
+        most_circulated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_peer(db: &DatabaseConnection, name: &str) -> i32 {
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set(name.to_string()),
+            url: Set(format!("http://{}.local:8000", name.to_lowercase())),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn lend(db: &DatabaseConnection, peer_id: i32, isbn: &str, status: &str, days: i64) {
+        let created = chrono::Utc::now() - chrono::Duration::days(30);
+        p2p_request::ActiveModel {
+            id: Set(uuid::Uuid::new_v4().to_string()),
+            from_peer_id: Set(peer_id),
+            book_isbn: Set(isbn.to_string()),
+            book_title: Set(format!("Book {}", isbn)),
+            status: Set(status.to_string()),
+            created_at: Set(created.to_rfc3339()),
+            updated_at: Set((created + chrono::Duration::days(days)).to_rfc3339()),
+            requester_request_id: Set(None),
+            held_copy_id: Set(None),
+            hold_expires_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    async fn borrow(db: &DatabaseConnection, peer_id: i32, isbn: &str, status: &str) {
+        let now = chrono::Utc::now().to_rfc3339();
+        p2p_outgoing_request::ActiveModel {
+            id: Set(uuid::Uuid::new_v4().to_string()),
+            to_peer_id: Set(peer_id),
+            book_isbn: Set(isbn.to_string()),
+            book_title: Set(format!("Book {}", isbn)),
+            status: Set(status.to_string()),
+            lender_request_id: Set(None),
+            book_id: Set(None),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn aggregates_per_peer_and_hides_what_a_single_loan_would_reveal() {
+        let db = setup().await;
+        let ann = insert_peer(&db, "Ann").await;
+        let bob = insert_peer(&db, "Bob").await;
+
+        lend(&db, ann, "111", "returned", 10).await;
+        lend(&db, ann, "222", "returned", 20).await;
+        lend(&db, ann, "333", "returned", 30).await;
+        lend(&db, ann, "444", "pending", 0).await;
+        lend(&db, bob, "111", "accepted", 0).await;
+        lend(&db, bob, "555", "rejected", 0).await;
+        borrow(&db, bob, "666", "returned").await;
+        borrow(&db, bob, "222", "cancelled").await;
+
+        let stats = p2p_stats(&db).await.unwrap();
+        assert_eq!((stats.lent, stats.borrowed, stats.returned), (4, 1, 4));
+        assert_eq!(stats.average_return_days, Some(15.0));
+
+        assert_eq!(stats.peers[0].peer_name, "Ann");
+        assert_eq!((stats.peers[0].lent, stats.peers[0].borrowed), (3, 0));
+        assert_eq!(stats.peers[0].average_return_days, Some(20.0));
+        // One return with Bob: no average.
+        assert_eq!(stats.peers[1].peer_id, bob);
+        assert_eq!((stats.peers[1].lent, stats.peers[1].borrowed), (1, 1));
+        assert_eq!(stats.peers[1].average_return_days, None);
+
+        // Only 111 went to two different peers.
+        assert_eq!(
+            stats.most_circulated,
+            vec![CirculatedTitle {
+                title: "Book 111".to_string(),
+                isbn: Some("111".to_string()),
+                loans: 2,
+            }]
+        );
+    }
+}
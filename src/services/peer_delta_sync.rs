@@ -26,6 +26,7 @@ use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, Qu
 
 use crate::infrastructure::AppState;
 use crate::models::{peer, peer_book};
+use crate::services::peer_protocol;
 
 /// Outcome of one call to `fetch_and_apply_peer_delta`.
 ///
@@ -58,7 +59,9 @@ pub enum DeltaSyncOutcome {
     /// Responder did not reply within the transport timeout or sent a
     /// response the orchestrator could not parse. Treat as a fallback
     /// trigger: the peer is likely on an older codebase that does not know
-    /// `catalog_delta_request`.
+    /// `catalog_delta_request`. Also returned without asking when the
+    /// peer's handshake did not advertise `incremental_sync`
+    /// (`services::peer_protocol`).
     FallbackRequired,
     /// E2EE is not available for this peer (no keys or crypto not
     /// initialised). Caller should stay on the legacy plaintext path.
@@ -87,6 +90,14 @@ pub async fn fetch_and_apply_peer_delta(
         .map_err(|e| format!("load peer {peer_id}: {e}"))?
         .ok_or_else(|| format!("peer {peer_id} not found"))?;
 
+    if !peer_protocol::supports(db, peer_id, peer_protocol::INCREMENTAL_SYNC).await {
+        tracing::info!(
+            "peer_delta_sync: peer {} does not advertise incremental sync, fallback required",
+            peer_model.name
+        );
+        return Ok(DeltaSyncOutcome::FallbackRequired);
+    }
+
     let since: Option<i64> = peer_model.last_delta_cursor.map(|c| c as i64);
 
     let payload = serde_json::json!({
@@ -204,7 +215,7 @@ pub async fn apply_peer_delta_operations(
                     continue;
                 };
                 let book: crate::models::Book = match serde_json::from_value(book_value.clone()) {
-                    Ok(b) => b,
+                    Ok(b) => crate::utils::sanitize::book(b),
                     Err(e) => {
                         tracing::warn!(
                             "peer_delta_sync: failed to decode book payload for peer {peer_id}: {e}"
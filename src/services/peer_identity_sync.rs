@@ -15,10 +15,26 @@
 //! (the enclosing manifest sync is about to rewrite them by `peer_id`, and
 //! a purge would flash an empty library in the UI), no event emission
 //! (callers log context; observers rely on the downstream manifest sync).
+//!
+//! The UUID, not the URL, is what identifies a peer (`library_uuid` is
+//! unique since migration 131): the URL is where the library was last
+//! reached, and follows it when DHCP hands it another address or it moves to
+//! another port. When a paired library shows up at a new URL (announced over
+//! mDNS, `follow_discovered_peers`; or in a connection request, direct or
+//! relayed, or a loan request it sends), `relink` moves it there once the new
+//! address has answered as the same library. The row, and the `peer_books`,
+//! loans and requests keyed by its id, stay. A row created at the new URL
+//! before the library was recognised is merged into it (`merge_duplicate`)
+//! rather than left as a second entry for the same library.
 
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    Set, Statement, TransactionTrait,
+};
 
+use crate::api::peer::is_peer_blocked;
 use crate::models::peer;
+use crate::services::mdns::DiscoveredPeer;
 
 /// Overwrite `peers.library_uuid` for a peer with a value learned from a
 /// cryptographically verified source (E2EE manifest, ed25519-signed
@@ -29,6 +45,9 @@ use crate::models::peer;
 /// - Returns `Ok(true)` when the row was updated.
 /// - Returns `Err(DbErr::RecordNotFound)` if `peer_id` is unknown.
 ///
+/// Another row holding `new_uuid`, a stale address of the same library,
+/// loses it: one row per library.
+///
 /// The caller is responsible for trust: only invoke this with a uuid read
 /// from a payload whose envelope signature verified against the peer's
 /// stored `public_key` (ed25519). Relay forwarders MUST NOT be trusted for
@@ -48,6 +67,21 @@ pub async fn persist_peer_library_uuid(
         return Ok(false);
     }
 
+    let stale = peer::Entity::find()
+        .filter(peer::Column::LibraryUuid.eq(new_uuid))
+        .filter(peer::Column::Id.ne(peer_id))
+        .all(db)
+        .await?;
+    for other in stale {
+        tracing::warn!(
+            "peer_identity_sync: peer {} no longer claims library {new_uuid}",
+            other.id
+        );
+        let mut active: peer::ActiveModel = other.into();
+        active.library_uuid = Set(None);
+        active.update(db).await?;
+    }
+
     let mut active: peer::ActiveModel = existing.into();
     active.library_uuid = Set(Some(new_uuid.to_owned()));
     active.updated_at = Set(chrono::Utc::now().to_rfc3339());
@@ -55,6 +89,197 @@ pub async fn persist_peer_library_uuid(
     Ok(true)
 }
 
+/// Where a peer stored at `stored_url` now is, when mDNS found it (`found`)
+/// at another address or port. `None` when it has not moved, or when the
+/// stored URL names a host rather than an address: a host name follows the
+/// library on its own.
+fn moved_url(stored_url: &str, found: &DiscoveredPeer) -> Option<String> {
+    let stored = url::Url::parse(stored_url).ok()?;
+    let stored_ip: std::net::IpAddr = match stored.host()? {
+        url::Host::Ipv4(ip) => ip.into(),
+        url::Host::Ipv6(ip) => ip.into(),
+        url::Host::Domain(_) => return None,
+    };
+    let addresses: Vec<std::net::IpAddr> = found
+        .addresses
+        .iter()
+        .filter_map(|a| a.parse().ok())
+        .collect();
+    if stored.port_or_known_default() == Some(found.port) && addresses.contains(&stored_ip) {
+        return None;
+    }
+    let address = addresses
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.first())?;
+    let host = match address {
+        std::net::IpAddr::V4(ip) => ip.to_string(),
+        std::net::IpAddr::V6(ip) => format!("[{ip}]"),
+    };
+    Some(format!("{}://{host}:{}", stored.scheme(), found.port))
+}
+
+/// Whether `url` answers as `known`: its `/api/config` gives the same
+/// library UUID and, once keys were exchanged, the same signing key. The
+/// same check pairing relies on.
+async fn answers_as(url: &str, known: &peer::Model) -> bool {
+    if crate::api::peer::validate_url(url).is_err() {
+        return false;
+    }
+    let config = match crate::api::peer::get_safe_client_for(url)
+        .get(format!("{url}/api/config"))
+        .send()
+        .await
+    {
+        Ok(res) if res.status().is_success() => {
+            res.json::<crate::api::setup::ConfigResponse>().await.ok()
+        }
+        _ => None,
+    };
+    config.is_some_and(|config| {
+        config.library_uuid.is_some()
+            && config.library_uuid == known.library_uuid
+            && (known.public_key.is_none() || config.ed25519_public_key == known.public_key)
+    })
+}
+
+/// Fold `duplicate` into `peer_id`: its cached books the peer lacks, its
+/// requests both ways, queued messages, announcements, borrowed copies and
+/// its catalogue subscription, unless the peer has one, move over, then the
+/// duplicate is deleted (its caches, such as leaderboard
+/// stats or fetched covers, go with it and are fetched again).
+async fn merge_duplicate(
+    db: &DatabaseConnection,
+    duplicate: i32,
+    peer_id: i32,
+) -> Result<(), sea_orm::DbErr> {
+    let moves = [
+        "UPDATE peer_books SET peer_id = ?2 WHERE peer_id = ?1 AND remote_book_id NOT IN \
+         (SELECT remote_book_id FROM peer_books WHERE peer_id = ?2)",
+        "UPDATE p2p_requests SET from_peer_id = ?2 WHERE from_peer_id = ?1",
+        "UPDATE p2p_outgoing_requests SET to_peer_id = ?2 WHERE to_peer_id = ?1",
+        "UPDATE p2p_outbox SET peer_id = ?2 WHERE peer_id = ?1",
+        "UPDATE announcements SET peer_id = ?2 WHERE peer_id = ?1",
+        "UPDATE copies SET lender_peer_id = ?2 WHERE lender_peer_id = ?1",
+        "UPDATE OR IGNORE peer_subscriptions SET peer_id = ?2 WHERE peer_id = ?1",
+    ];
+    let deletes = [
+        "DELETE FROM peer_books WHERE peer_id = ?",
+        "DELETE FROM peers WHERE id = ?",
+    ];
+    let txn = db.begin().await?;
+    for sql in moves {
+        txn.execute(Statement::from_sql_and_values(
+            txn.get_database_backend(),
+            sql,
+            [duplicate.into(), peer_id.into()],
+        ))
+        .await?;
+    }
+    for sql in deletes {
+        txn.execute(Statement::from_sql_and_values(
+            txn.get_database_backend(),
+            sql,
+            [duplicate.into()],
+        ))
+        .await?;
+    }
+    txn.commit().await
+}
+
+/// Move a peer to `new_url`. A row holding that URL without naming a library,
+/// a duplicate created before the library was recognised there, is merged
+/// into the peer. Returns `Ok(false)` when the peer is already there or
+/// another library holds the URL: that row is left for the owner to remove.
+pub async fn follow_peer_url(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    new_url: &str,
+) -> Result<bool, sea_orm::DbErr> {
+    let Some(existing) = peer::Entity::find_by_id(peer_id).one(db).await? else {
+        return Err(sea_orm::DbErr::RecordNotFound(format!(
+            "peer {peer_id} not found"
+        )));
+    };
+    if existing.url == new_url {
+        return Ok(false);
+    }
+    let holder = peer::Entity::find()
+        .filter(peer::Column::Url.eq(new_url))
+        .one(db)
+        .await?;
+    if let Some(holder) = holder {
+        if holder.library_uuid.is_some() {
+            tracing::warn!(
+                "peer_identity_sync: {new_url} is held by peer {}, peer {peer_id} stays at {}",
+                holder.id,
+                existing.url
+            );
+            return Ok(false);
+        }
+        tracing::info!(
+            "peer_identity_sync: merging peer {} ({new_url}) into peer {} ({})",
+            holder.id,
+            existing.name,
+            existing.url
+        );
+        merge_duplicate(db, holder.id, peer_id).await?;
+    }
+
+    tracing::info!(
+        "peer_identity_sync: peer {} moved {} -> {new_url}",
+        existing.name,
+        existing.url
+    );
+    let mut active: peer::ActiveModel = existing.into();
+    active.url = Set(new_url.to_owned());
+    active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+    active.update(db).await?;
+    Ok(true)
+}
+
+/// Move `known` to `url`, where it was just seen, once `url` answers as the
+/// same library. Returns whether it moved.
+pub async fn relink(db: &DatabaseConnection, known: &peer::Model, url: &str) -> bool {
+    let url = url.trim_end_matches('/');
+    if url.is_empty() || known.url.trim_end_matches('/') == url || is_peer_blocked(known) {
+        return false;
+    }
+    if !answers_as(url, known).await {
+        tracing::debug!(
+            "peer_identity_sync: {url} does not answer as peer {}",
+            known.id
+        );
+        return false;
+    }
+    match follow_peer_url(db, known.id, url).await {
+        Ok(moved) => moved,
+        Err(e) => {
+            tracing::warn!("peer_identity_sync: cannot move peer {}: {e}", known.id);
+            false
+        }
+    }
+}
+
+/// Follow the paired libraries mDNS currently finds at a new address.
+pub async fn follow_discovered_peers(db: &DatabaseConnection) {
+    for found in crate::services::mdns::get_local_peers() {
+        let Some(uuid) = found.library_uuid.as_deref() else {
+            continue;
+        };
+        let known = peer::Entity::find()
+            .filter(peer::Column::LibraryUuid.eq(uuid))
+            .one(db)
+            .await;
+        let Ok(Some(known)) = known else {
+            continue;
+        };
+        if let Some(candidate) = moved_url(&known.url, &found) {
+            relink(db, &known, &candidate).await;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +361,163 @@ mod tests {
         assert_eq!(reloaded.library_uuid.as_deref(), Some("fresh-uuid"));
     }
 
+    #[tokio::test]
+    async fn persist_takes_the_uuid_from_a_stale_row() {
+        let db = setup().await;
+        let stale = create_peer(&db, Some("lib-uuid")).await;
+        let current = create_peer(&db, None).await;
+
+        assert!(
+            persist_peer_library_uuid(&db, current, "lib-uuid")
+                .await
+                .unwrap()
+        );
+        let stale = peer::Entity::find_by_id(stale)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stale.library_uuid.is_none());
+    }
+
+    #[test]
+    fn moved_url_follows_a_new_address_or_port() {
+        let found = DiscoveredPeer {
+            name: "marie".to_string(),
+            host: "marie.local.".to_string(),
+            port: 8000,
+            addresses: vec!["fe80::1".to_string(), "192.168.1.42".to_string()],
+            library_id: None,
+            library_uuid: Some("lib-uuid".to_string()),
+            ed25519_public_key: None,
+            x25519_public_key: None,
+            discovered_at: "2026-10-17T09:00:00Z".to_string(),
+        };
+        assert_eq!(moved_url("http://192.168.1.42:8000", &found), None);
+        assert_eq!(
+            moved_url("http://192.168.1.17:8000", &found).as_deref(),
+            Some("http://192.168.1.42:8000")
+        );
+        assert_eq!(
+            moved_url("https://192.168.1.42:8443", &found).as_deref(),
+            Some("https://192.168.1.42:8000")
+        );
+        assert_eq!(moved_url("http://marie.local:8000", &found), None);
+    }
+
+    #[tokio::test]
+    async fn follow_keeps_a_url_another_peer_holds() {
+        let db = setup().await;
+        let moving = create_peer(&db, Some("lib-a")).await;
+        let holder = create_peer(&db, Some("lib-b")).await;
+        let held = peer::Entity::find_by_id(holder)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .url;
+
+        assert!(!follow_peer_url(&db, moving, &held).await.unwrap());
+        assert!(
+            follow_peer_url(&db, moving, "http://192.168.1.42:8000")
+                .await
+                .unwrap()
+        );
+        let moved = peer::Entity::find_by_id(moving)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(moved.url, "http://192.168.1.42:8000");
+    }
+
+    #[tokio::test]
+    async fn follow_merges_a_duplicate_created_at_the_new_url() {
+        let db = setup().await;
+        let known = create_peer(&db, Some("lib-a")).await;
+        let duplicate = create_peer(&db, None).await;
+        let new_url = peer::Entity::find_by_id(duplicate)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .url;
+        let now = chrono::Utc::now().to_rfc3339();
+        for (sql, values) in [
+            (
+                "INSERT INTO p2p_requests \
+                 (id, from_peer_id, book_isbn, book_title, status, created_at, updated_at) \
+                 VALUES ('req-1', ?, '9782070368228', 'Mémoires d''Hadrien', 'pending', ?, ?)",
+                vec![duplicate.into(), now.clone().into(), now.clone().into()],
+            ),
+            (
+                "INSERT INTO peer_books (peer_id, remote_book_id, title, synced_at) \
+                 VALUES (?, 'b1', 'Known', ?), (?, 'b1', 'Stale', ?), (?, 'b2', 'New', ?)",
+                vec![
+                    known.into(),
+                    now.clone().into(),
+                    duplicate.into(),
+                    now.clone().into(),
+                    duplicate.into(),
+                    now.clone().into(),
+                ],
+            ),
+            (
+                "INSERT INTO peer_subscriptions (peer_id, interval_minutes, next_sync_at, created_at) \
+                 VALUES (?, 30, ?, ?)",
+                vec![duplicate.into(), now.clone().into(), now.clone().into()],
+            ),
+        ] {
+            db.execute(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                sql,
+                values,
+            ))
+            .await
+            .unwrap();
+        }
+
+        assert!(follow_peer_url(&db, known, &new_url).await.unwrap());
+
+        assert!(
+            peer::Entity::find_by_id(duplicate)
+                .one(&db)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let titles: Vec<String> = db
+            .query_all(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                "SELECT title FROM peer_books WHERE peer_id = ? ORDER BY remote_book_id",
+                [known.into()],
+            ))
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.try_get("", "title").unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Known", "New"]);
+        let request = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT from_peer_id FROM p2p_requests WHERE id = 'req-1'",
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.try_get::<i32>("", "from_peer_id").unwrap(), known);
+        let subscription = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT peer_id FROM peer_subscriptions WHERE interval_minutes = 30",
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(subscription.try_get::<i32>("", "peer_id").unwrap(), known);
+    }
+
     #[tokio::test]
     async fn persist_fails_on_unknown_peer() {
         let db = setup().await;
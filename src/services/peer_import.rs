@@ -0,0 +1,392 @@
+//! Bulk peer provisioning from a list or a hub-exported bundle.
+//!
+//! Adding libraries one invite at a time does not scale to a network set up
+//! centrally (a district provisioning thirty school nodes). `POST
+//! /api/peers/import` takes the whole list instead, as CSV with a header row
+//! or as JSON: a bare array of peers, or a bundle `{"peers": [...]}` as
+//! exported from a hub directory, whose `display_name`, `node_id` and
+//! `ed25519_public_key` spellings are accepted for the name, library UUID and
+//! public key.
+//!
+//! Every row is validated before anything is written: a row that fails is
+//! reported with its field errors and skipped, the others are created, or
+//! update the peer with the same library UUID, else the same URL. Nothing is
+//! fetched from the peers: keys and relay details come from the list, and a
+//! row without keys exchanges them on first contact as usual. New peers are
+//! accepted; an existing peer keeps its connection status, so a blocked one
+//! stays blocked.
+
+use std::collections::HashSet;
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::api::validation::{FieldError, ValidationErrors, check_peer_url};
+use crate::models::peer;
+
+/// One peer as listed in an import.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::ToSchema)]
+pub struct PeerEntry {
+    #[serde(default, alias = "display_name")]
+    pub name: String,
+    /// Empty for a peer reached through its relay only
+    #[serde(default)]
+    pub url: String,
+    /// Ed25519 public key, hex
+    #[serde(default, alias = "ed25519_public_key")]
+    pub public_key: Option<String>,
+    /// X25519 public key, hex
+    #[serde(default)]
+    pub x25519_public_key: Option<String>,
+    #[serde(default, alias = "node_id")]
+    pub library_uuid: Option<String>,
+    #[serde(default)]
+    pub relay_url: Option<String>,
+    #[serde(default)]
+    pub mailbox_id: Option<String>,
+    #[serde(default)]
+    pub relay_write_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonImport {
+    List(Vec<PeerEntry>),
+    Bundle { peers: Vec<PeerEntry> },
+}
+
+/// What became of one row.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RowReport {
+    /// 1-based, header excluded
+    pub row: usize,
+    pub name: String,
+    pub url: String,
+    /// `created`, `updated`, `invalid`, or with `dry_run`, `would_create` /
+    /// `would_update`
+    pub action: String,
+    pub peer_id: Option<i32>,
+    #[schema(value_type = Vec<Object>)]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub invalid: usize,
+    pub dry_run: bool,
+    pub rows: Vec<RowReport>,
+}
+
+/// Parse an import: JSON when it starts with `[` or `{`, CSV otherwise.
+pub fn parse(input: &str) -> Result<Vec<PeerEntry>, String> {
+    let trimmed = input.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return match serde_json::from_str::<JsonImport>(trimmed) {
+            Ok(JsonImport::List(peers) | JsonImport::Bundle { peers }) => {
+                Ok(peers.into_iter().map(sanitize_entry).collect())
+            }
+            Err(_) => Err("expected a JSON array of peers or an object with `peers`".to_string()),
+        };
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(trimmed.as_bytes());
+    reader
+        .deserialize::<PeerEntry>()
+        .enumerate()
+        .map(|(i, row)| {
+            row.map(sanitize_entry)
+                .map_err(|e| format!("CSV row {}: {e}", i + 1))
+        })
+        .collect()
+}
+
+/// Names and addresses as the import file gives them, cleaned (see
+/// `utils::sanitize`). Keys and tokens are left to `validate`.
+fn sanitize_entry(entry: PeerEntry) -> PeerEntry {
+    use crate::utils::sanitize::{MAX_CODE_CHARS, MAX_NAME_CHARS, MAX_URL_CHARS, line, opt_line};
+    PeerEntry {
+        name: line(&entry.name, MAX_NAME_CHARS),
+        url: line(&entry.url, MAX_URL_CHARS),
+        library_uuid: opt_line(entry.library_uuid, MAX_CODE_CHARS),
+        relay_url: opt_line(entry.relay_url, MAX_URL_CHARS),
+        mailbox_id: opt_line(entry.mailbox_id, MAX_CODE_CHARS),
+        ..entry
+    }
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn check_key(errors: &mut ValidationErrors, field: &str, key: Option<&str>) {
+    if let Some(key) = key
+        && (key.len() != 64 || !key.bytes().all(|b| b.is_ascii_hexdigit()))
+    {
+        errors.add(field, "must be 64 hexadecimal characters");
+    }
+}
+
+impl PeerEntry {
+    fn normalized(mut self) -> Self {
+        self.name = self.name.trim().to_string();
+        self.url = self.url.trim().trim_end_matches('/').to_string();
+        self.public_key = non_blank(self.public_key).map(|k| k.to_lowercase());
+        self.x25519_public_key = non_blank(self.x25519_public_key).map(|k| k.to_lowercase());
+        self.library_uuid = non_blank(self.library_uuid);
+        self.relay_url = non_blank(self.relay_url);
+        self.mailbox_id = non_blank(self.mailbox_id);
+        self.relay_write_token = non_blank(self.relay_write_token);
+        self
+    }
+
+    fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self.name.is_empty() {
+            errors.add("name", "is required");
+        }
+        check_peer_url(&mut errors, "url", &self.url);
+        if self.url.is_empty()
+            && (self.library_uuid.is_none()
+                || self.relay_url.is_none()
+                || self.mailbox_id.is_none())
+        {
+            errors.add(
+                "url",
+                "is required unless library_uuid, relay_url and mailbox_id are given",
+            );
+        }
+        check_key(&mut errors, "public_key", self.public_key.as_deref());
+        check_key(
+            &mut errors,
+            "x25519_public_key",
+            self.x25519_public_key.as_deref(),
+        );
+        errors
+    }
+
+    /// The `peers.url` to store: relay-only peers get the same
+    /// `relay://<uuid>` placeholder as `connect`.
+    fn stored_url(&self) -> String {
+        match &self.library_uuid {
+            Some(uuid) if self.url.is_empty() => format!("relay://{uuid}"),
+            _ => crate::api::peer::translate_url_for_docker(&self.url),
+        }
+    }
+}
+
+async fn existing_peer(
+    db: &impl ConnectionTrait,
+    entry: &PeerEntry,
+    url: &str,
+) -> Result<Option<peer::Model>, DbErr> {
+    if let Some(uuid) = &entry.library_uuid
+        && let Some(p) = peer::Entity::find()
+            .filter(peer::Column::LibraryUuid.eq(uuid))
+            .one(db)
+            .await?
+    {
+        return Ok(Some(p));
+    }
+    peer::Entity::find()
+        .filter(peer::Column::Url.eq(url))
+        .one(db)
+        .await
+}
+
+/// Validate then write `entries`, in one transaction. With `dry_run`
+/// nothing is written and the report says what would be.
+pub async fn import_peers(
+    db: &DatabaseConnection,
+    entries: Vec<PeerEntry>,
+    dry_run: bool,
+) -> Result<ImportReport, DbErr> {
+    let mut report = ImportReport {
+        dry_run,
+        ..Default::default()
+    };
+    let mut seen_urls = HashSet::new();
+    let mut seen_uuids = HashSet::new();
+    let txn = db.begin().await?;
+
+    for (i, entry) in entries.into_iter().enumerate() {
+        let entry = entry.normalized();
+        let url = entry.stored_url();
+        let mut errors = entry.validate();
+        if errors.is_empty() && !seen_urls.insert(url.clone()) {
+            errors.add("url", "listed twice");
+        }
+        if let Some(uuid) = &entry.library_uuid
+            && errors.is_empty()
+            && !seen_uuids.insert(uuid.clone())
+        {
+            errors.add("library_uuid", "listed twice");
+        }
+        let mut row = RowReport {
+            row: i + 1,
+            name: entry.name.clone(),
+            url: entry.url.clone(),
+            action: String::new(),
+            peer_id: None,
+            errors: errors.fields().to_vec(),
+        };
+        if !errors.is_empty() {
+            row.action = "invalid".to_string();
+            report.invalid += 1;
+            report.rows.push(row);
+            continue;
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let key_exchange_done = entry.public_key.is_some() && entry.x25519_public_key.is_some();
+        match existing_peer(&txn, &entry, &url).await? {
+            Some(existing) => {
+                row.peer_id = Some(existing.id);
+                report.updated += 1;
+                if dry_run {
+                    row.action = "would_update".to_string();
+                    report.rows.push(row);
+                    continue;
+                }
+                let mut active: peer::ActiveModel = existing.into();
+                active.name = Set(entry.name);
+                active.url = Set(url);
+                if entry.library_uuid.is_some() {
+                    active.library_uuid = Set(entry.library_uuid);
+                }
+                if entry.public_key.is_some() {
+                    active.public_key = Set(entry.public_key);
+                }
+                if entry.x25519_public_key.is_some() {
+                    active.x25519_public_key = Set(entry.x25519_public_key);
+                }
+                if key_exchange_done {
+                    active.key_exchange_done = Set(true);
+                }
+                if entry.relay_url.is_some() {
+                    active.relay_url = Set(entry.relay_url);
+                }
+                if entry.mailbox_id.is_some() {
+                    active.mailbox_id = Set(entry.mailbox_id);
+                }
+                if entry.relay_write_token.is_some() {
+                    active.relay_write_token = Set(entry.relay_write_token);
+                    active.relay_write_token_invalid_at = Set(None);
+                }
+                active.updated_at = Set(now);
+                active.update(&txn).await?;
+                row.action = "updated".to_string();
+            }
+            None => {
+                report.created += 1;
+                if dry_run {
+                    row.action = "would_create".to_string();
+                    report.rows.push(row);
+                    continue;
+                }
+                let created = peer::ActiveModel {
+                    name: Set(entry.name),
+                    url: Set(url),
+                    library_uuid: Set(entry.library_uuid),
+                    public_key: Set(entry.public_key),
+                    x25519_public_key: Set(entry.x25519_public_key),
+                    key_exchange_done: Set(key_exchange_done),
+                    relay_url: Set(entry.relay_url),
+                    mailbox_id: Set(entry.mailbox_id),
+                    relay_write_token: Set(entry.relay_write_token),
+                    connection_status: Set("accepted".to_string()),
+                    created_at: Set(now.clone()),
+                    updated_at: Set(now),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?;
+                row.peer_id = Some(created.id);
+                row.action = "created".to_string();
+            }
+        }
+        report.rows.push(row);
+    }
+
+    if dry_run {
+        txn.rollback().await?;
+    } else {
+        txn.commit().await?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_csv_list_creates_updates_and_reports_bad_rows() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set("Old name".to_string()),
+            url: Set("http://192.168.1.20:8000".to_string()),
+            connection_status: Set("blocked".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let key = "ab".repeat(32);
+        let csv = format!(
+            "name,url,public_key\n\
+             École Jules Verne,http://192.168.1.10:8000,{key}\n\
+             Collège Colette,http://192.168.1.20:8000/,\n\
+             ,http://192.168.1.30:8000,not-a-key\n"
+        );
+        let entries = parse(&csv).unwrap();
+
+        let preview = import_peers(&db, entries.clone(), true).await.unwrap();
+        assert_eq!(
+            (preview.created, preview.updated, preview.invalid),
+            (1, 1, 1)
+        );
+        assert_eq!(peer::Entity::find().count(&db).await.unwrap(), 1);
+
+        let report = import_peers(&db, entries, false).await.unwrap();
+        let actions: Vec<_> = report.rows.iter().map(|r| r.action.as_str()).collect();
+        assert_eq!(actions, ["created", "updated", "invalid"]);
+        let fields: Vec<_> = report.rows[2]
+            .errors
+            .iter()
+            .map(|e| e.field.as_str())
+            .collect();
+        assert_eq!(fields, ["name", "public_key"]);
+
+        let verne = peer::Entity::find_by_id(report.rows[0].peer_id.unwrap())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(verne.public_key.as_deref(), Some(key.as_str()));
+        assert_eq!(verne.connection_status, "accepted");
+        let colette = peer::Entity::find_by_id(report.rows[1].peer_id.unwrap())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(colette.name, "Collège Colette");
+        assert_eq!(colette.connection_status, "blocked");
+
+        let bundle = r#"{"peers": [{"display_name": "Relay only", "node_id": "lib-1",
+            "relay_url": "https://hub.example", "mailbox_id": "box-1"}]}"#;
+        let entries = parse(bundle).unwrap();
+        assert_eq!(entries[0].library_uuid.as_deref(), Some("lib-1"));
+        let report = import_peers(&db, entries, false).await.unwrap();
+        assert_eq!(report.created, 1);
+    }
+}
@@ -0,0 +1,523 @@
+//! Outbox for P2P messages a peer could not receive.
+//!
+//! Loan requests, loan confirmations and status updates used to be sent once:
+//! a peer that was offline at that moment never heard of them. When a send
+//! fails on transport, the caller queues the message here instead and this
+//! task retries it with exponential backoff: E2EE first, then the plaintext
+//! route recorded with the message when the peer has no E2EE channel, exactly
+//! like the live paths.
+//!
+//! Messages to one peer are retried oldest first and a sweep stops at the
+//! first failure for that peer, so a status update never overtakes the
+//! confirmation it follows. Receiving anything from a peer makes its queue due
+//! at once (see [`retry_peer_now`]). Replays are safe: plaintext attempts
+//! carry the entry id as `Idempotency-Key`, and the receiving handlers ignore
+//! a loan request or confirmation they already processed.
+
+use crate::infrastructure::AppState;
+use crate::models::{p2p_outbox, p2p_outgoing_request, peer};
+use chrono::Utc;
+use sea_orm::*;
+use serde::Serialize;
+
+/// Time between two sweeps.
+const SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Delay before the first retry; doubled on every failed attempt.
+const BASE_BACKOFF_SECS: i64 = 30;
+
+/// Upper bound of the delay between two attempts.
+const MAX_BACKOFF_SECS: i64 = 3_600;
+
+/// A message still undelivered this long after it was queued is given up on.
+const GIVE_UP_AFTER_DAYS: i64 = 7;
+
+/// Plaintext route for a peer without an E2EE channel.
+#[derive(Debug, Clone)]
+pub struct PlaintextFallback {
+    /// `POST` or `PUT`.
+    pub method: &'static str,
+    /// Path under the peer URL, e.g. `/api/peers/request`.
+    pub path: String,
+    pub body: serde_json::Value,
+}
+
+/// A queued message, as listed by `GET /api/peers/outbox`.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub peer_id: i32,
+    pub peer_name: Option<String>,
+    pub message_type: String,
+    pub outgoing_request_id: Option<String>,
+    /// `pending` or `failed`.
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// Delay before the attempt following `attempts` failed ones.
+fn backoff(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.clamp(0, 16) as u32;
+    let secs = BASE_BACKOFF_SECS.saturating_mul(1 << exponent);
+    chrono::Duration::seconds(secs.min(MAX_BACKOFF_SECS))
+}
+
+/// Queue `message_type` for `peer_id` after a failed send. `error` is the
+/// transport error of that first attempt.
+pub async fn enqueue(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    message_type: &str,
+    payload: &serde_json::Value,
+    fallback: Option<PlaintextFallback>,
+    outgoing_request_id: Option<&str>,
+    error: &str,
+) -> Result<p2p_outbox::Model, DbErr> {
+    let now = Utc::now();
+    let (fallback_method, fallback_path, fallback_body) = match fallback {
+        Some(f) => (
+            Some(f.method.to_string()),
+            Some(f.path),
+            Some(f.body.to_string()),
+        ),
+        None => (None, None, None),
+    };
+    let entry = p2p_outbox::ActiveModel {
+        id: Set(uuid::Uuid::new_v4().to_string()),
+        peer_id: Set(peer_id),
+        message_type: Set(message_type.to_string()),
+        payload: Set(payload.to_string()),
+        fallback_method: Set(fallback_method),
+        fallback_path: Set(fallback_path),
+        fallback_body: Set(fallback_body),
+        outgoing_request_id: Set(outgoing_request_id.map(str::to_string)),
+        status: Set("pending".to_string()),
+        attempts: Set(1),
+        next_attempt_at: Set((now + backoff(1)).to_rfc3339()),
+        last_error: Set(Some(error.to_string())),
+        created_at: Set(now.to_rfc3339()),
+        updated_at: Set(now.to_rfc3339()),
+    }
+    .insert(db)
+    .await?;
+    tracing::info!(
+        "peer_outbox: queued '{}' for peer {} ({})",
+        message_type,
+        peer_id,
+        error
+    );
+    Ok(entry)
+}
+
+/// Best-effort [`enqueue`] for send paths that have nothing to report to:
+/// a queueing failure is logged.
+pub async fn enqueue_or_log(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    message_type: &str,
+    payload: &serde_json::Value,
+    fallback: Option<PlaintextFallback>,
+    error: &str,
+) {
+    if let Err(e) = enqueue(db, peer_id, message_type, payload, fallback, None, error).await {
+        tracing::warn!("peer_outbox: failed to queue '{message_type}' for peer {peer_id}: {e}");
+    }
+}
+
+/// Pending and failed messages, oldest first.
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<OutboxEntry>, DbErr> {
+    let rows = p2p_outbox::Entity::find()
+        .find_also_related(peer::Entity)
+        .order_by_asc(p2p_outbox::Column::CreatedAt)
+        .all(db)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(entry, peer)| OutboxEntry {
+            id: entry.id,
+            peer_id: entry.peer_id,
+            peer_name: peer.map(|p| p.name),
+            message_type: entry.message_type,
+            outgoing_request_id: entry.outgoing_request_id,
+            status: entry.status,
+            attempts: entry.attempts,
+            next_attempt_at: entry.next_attempt_at,
+            last_error: entry.last_error,
+            created_at: entry.created_at,
+        })
+        .collect())
+}
+
+/// Make every pending message for `peer_id` due now. Called when the peer
+/// shows signs of life.
+pub async fn retry_peer_now(db: &DatabaseConnection, peer_id: i32) {
+    let result = p2p_outbox::Entity::update_many()
+        .col_expr(
+            p2p_outbox::Column::NextAttemptAt,
+            sea_orm::prelude::Expr::value(Utc::now().to_rfc3339()),
+        )
+        .filter(p2p_outbox::Column::PeerId.eq(peer_id))
+        .filter(p2p_outbox::Column::Status.eq("pending"))
+        .exec(db)
+        .await;
+    if let Err(e) = result {
+        tracing::warn!("peer_outbox: failed to reschedule peer {peer_id}: {e}");
+    }
+}
+
+/// Make every pending message due now.
+pub async fn retry_all_now(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let res = p2p_outbox::Entity::update_many()
+        .col_expr(
+            p2p_outbox::Column::NextAttemptAt,
+            sea_orm::prelude::Expr::value(Utc::now().to_rfc3339()),
+        )
+        .filter(p2p_outbox::Column::Status.eq("pending"))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+/// Drop a message, delivered or not. Returns whether it existed.
+pub async fn dismiss(db: &DatabaseConnection, id: &str) -> Result<bool, DbErr> {
+    let res = p2p_outbox::Entity::delete_by_id(id.to_owned())
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Try every due message once. Returns the number delivered.
+pub async fn deliver_due(state: &AppState) -> Result<u64, DbErr> {
+    let db = state.db();
+    let now = Utc::now().to_rfc3339();
+    let due = p2p_outbox::Entity::find()
+        .filter(p2p_outbox::Column::Status.eq("pending"))
+        .order_by_asc(p2p_outbox::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    // Peers whose queue is blocked for this sweep: a later message must not
+    // overtake one that could not be delivered.
+    let mut blocked: Vec<i32> = Vec::new();
+    let mut delivered = 0;
+    for entry in due {
+        if blocked.contains(&entry.peer_id) {
+            continue;
+        }
+        if entry.next_attempt_at > now {
+            blocked.push(entry.peer_id);
+            continue;
+        }
+
+        let Some(peer) = peer::Entity::find_by_id(entry.peer_id).one(db).await? else {
+            tracing::info!(
+                "peer_outbox: peer {} is gone, dropping {}",
+                entry.peer_id,
+                entry.id
+            );
+            p2p_outbox::Entity::delete_by_id(entry.id).exec(db).await?;
+            continue;
+        };
+        if crate::api::peer::is_peer_blocked(&peer) || !still_wanted(db, &entry).await? {
+            p2p_outbox::Entity::delete_by_id(entry.id).exec(db).await?;
+            continue;
+        }
+
+        match send(state, &peer, &entry).await {
+            Ok(response) => {
+                p2p_outbox::Entity::delete_by_id(entry.id.clone())
+                    .exec(db)
+                    .await?;
+                delivered += 1;
+                tracing::info!(
+                    "peer_outbox: delivered '{}' to {} after {} attempt(s)",
+                    entry.message_type,
+                    peer.name,
+                    entry.attempts + 1
+                );
+                if let Some(response) = response {
+                    apply_response(db, &peer, &entry, &response).await?;
+                }
+            }
+            Err(e) => {
+                blocked.push(entry.peer_id);
+                record_failure(db, entry, &e).await?;
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// A queued loan request is only worth sending while our outgoing request is
+/// still pending: the user may have cancelled it meanwhile.
+async fn still_wanted(db: &DatabaseConnection, entry: &p2p_outbox::Model) -> Result<bool, DbErr> {
+    let Some(outgoing_id) = entry.outgoing_request_id.as_deref() else {
+        return Ok(true);
+    };
+    Ok(
+        p2p_outgoing_request::Entity::find_by_id(outgoing_id.to_owned())
+            .one(db)
+            .await?
+            .is_some_and(|o| o.status == "pending"),
+    )
+}
+
+/// One delivery attempt. `Ok` carries the peer's answer, if any.
+async fn send(
+    state: &AppState,
+    peer: &peer::Model,
+    entry: &p2p_outbox::Model,
+) -> Result<Option<serde_json::Value>, String> {
+    let payload: serde_json::Value =
+        serde_json::from_str(&entry.payload).map_err(|e| format!("corrupt payload: {e}"))?;
+
+    match crate::api::peer::try_send_e2ee(state, peer, &entry.message_type, payload).await {
+        Ok(Some(response)) => return Ok(response.map(|m| m.payload)),
+        Ok(None) => {}
+        Err(e) => return Err(e),
+    }
+
+    let (Some(method), Some(path)) = (entry.fallback_method.as_deref(), &entry.fallback_path)
+    else {
+        return Err("no E2EE channel to this peer".to_string());
+    };
+    crate::api::peer::validate_url(&peer.url)?;
+    let body: serde_json::Value = entry
+        .fallback_body
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|e| format!("corrupt fallback body: {e}"))?
+        .unwrap_or(serde_json::Value::Null);
+
+    let client = crate::api::peer::get_safe_client_for(&peer.url);
+    let url = format!("{}{}", peer.url, path);
+    let request = match method {
+        "PUT" => client.put(&url),
+        _ => client.post(&url),
+    };
+    // The entry id keys every attempt: a retry after a lost answer is
+    // replayed by the peer, not handled twice (`api::idempotency`).
+    let res = request
+        .header(crate::api::idempotency::IDEMPOTENCY_KEY_HEADER, &entry.id)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // A 4xx is an answer (the peer processed and refused the message);
+    // only a 5xx is worth another try.
+    if res.status().is_server_error() {
+        return Err(format!("peer answered {}", res.status()));
+    }
+    Ok(res.json().await.ok())
+}
+
+/// Apply the peer's synchronous answer to a queued loan request: it may have
+/// been auto-accepted or auto-rejected, as in `request_book`.
+async fn apply_response(
+    db: &DatabaseConnection,
+    peer: &peer::Model,
+    entry: &p2p_outbox::Model,
+    response: &serde_json::Value,
+) -> Result<(), DbErr> {
+    let Some(outgoing_id) = entry.outgoing_request_id.as_deref() else {
+        return Ok(());
+    };
+    if let Some(outgoing) = p2p_outgoing_request::Entity::find_by_id(outgoing_id.to_owned())
+        .one(db)
+        .await?
+    {
+        crate::api::peer::apply_polled_status(db, &outgoing, peer, response).await;
+    }
+    Ok(())
+}
+
+/// Reschedule `entry` after a failed attempt, or give up on it.
+async fn record_failure(
+    db: &DatabaseConnection,
+    entry: p2p_outbox::Model,
+    error: &str,
+) -> Result<(), DbErr> {
+    let now = Utc::now();
+    let attempts = entry.attempts + 1;
+    let give_up = chrono::DateTime::parse_from_rfc3339(&entry.created_at)
+        .map(|created| {
+            now - created.with_timezone(&Utc) > chrono::Duration::days(GIVE_UP_AFTER_DAYS)
+        })
+        .unwrap_or(false);
+
+    let outgoing_request_id = entry.outgoing_request_id.clone();
+    let mut active: p2p_outbox::ActiveModel = entry.into();
+    active.attempts = Set(attempts);
+    active.last_error = Set(Some(error.to_string()));
+    active.updated_at = Set(now.to_rfc3339());
+    if give_up {
+        active.status = Set("failed".to_string());
+    } else {
+        active.next_attempt_at = Set((now + backoff(attempts)).to_rfc3339());
+    }
+    let entry = active.update(db).await?;
+
+    if give_up {
+        tracing::warn!(
+            "peer_outbox: giving up on '{}' to peer {} after {} attempts: {}",
+            entry.message_type,
+            entry.peer_id,
+            attempts,
+            error
+        );
+        if let Some(outgoing_id) = outgoing_request_id {
+            let requests = crate::infrastructure::SeaOrmP2pRequestRepository::new(db.clone());
+            crate::services::loan_service::mark_outgoing_request_failed(&requests, &outgoing_id)
+                .await;
+        }
+    } else {
+        tracing::debug!(
+            "peer_outbox: '{}' to peer {} failed ({}), next attempt at {}",
+            entry.message_type,
+            entry.peer_id,
+            error,
+            entry.next_attempt_at
+        );
+    }
+    Ok(())
+}
+
+/// Spawn the background task: a sweep every thirty seconds.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = deliver_due(&state).await {
+                tracing::warn!("peer_outbox: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    async fn unreachable_peer(db: &DatabaseConnection) -> peer::Model {
+        let now = Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            // Discard port: nothing listens, the send fails on transport.
+            url: Set("http://127.0.0.1:9".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert peer")
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(0).num_seconds(), 30);
+        assert_eq!(backoff(1).num_seconds(), 60);
+        assert_eq!(backoff(3).num_seconds(), 240);
+        assert_eq!(backoff(7).num_seconds(), MAX_BACKOFF_SECS);
+        assert_eq!(backoff(500).num_seconds(), MAX_BACKOFF_SECS);
+    }
+
+    #[tokio::test]
+    async fn queued_message_is_listed_and_rescheduled_on_failure() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let state = AppState::new(db.clone());
+        let peer = unreachable_peer(&db).await;
+
+        let payload = serde_json::json!({ "loan_id": "l-1", "status": "accepted" });
+        let queued = enqueue(
+            &db,
+            peer.id,
+            "status_update",
+            &payload,
+            Some(PlaintextFallback {
+                method: "PUT",
+                path: "/api/peers/requests/status/l-1".to_string(),
+                body: serde_json::json!({ "status": "accepted" }),
+            }),
+            None,
+            "connection refused",
+        )
+        .await
+        .unwrap();
+
+        let listed = list(&db).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].peer_name.as_deref(), Some("Bob"));
+        assert_eq!(listed[0].status, "pending");
+
+        // Not due yet: nothing is attempted.
+        assert_eq!(deliver_due(&state).await.unwrap(), 0);
+        let row = p2p_outbox::Entity::find_by_id(queued.id.clone())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.attempts, 1);
+
+        // Due now, still unreachable: one more attempt, pushed back.
+        retry_peer_now(&db, peer.id).await;
+        assert_eq!(deliver_due(&state).await.unwrap(), 0);
+        let row = p2p_outbox::Entity::find_by_id(queued.id.clone())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.attempts, 2);
+        assert_eq!(row.status, "pending");
+        assert!(row.next_attempt_at > Utc::now().to_rfc3339());
+
+        assert!(dismiss(&db, &queued.id).await.unwrap());
+        assert!(list(&db).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn queued_loan_request_is_dropped_once_the_request_is_closed() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let state = AppState::new(db.clone());
+        let peer = unreachable_peer(&db).await;
+        let now = Utc::now().to_rfc3339();
+        p2p_outgoing_request::ActiveModel {
+            id: Set("out-1".to_string()),
+            to_peer_id: Set(peer.id),
+            book_isbn: Set("978-1".to_string()),
+            book_title: Set("Dune".to_string()),
+            status: Set("cancelled".to_string()),
+            lender_request_id: Set(None),
+            book_id: Set(None),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        enqueue(
+            &db,
+            peer.id,
+            "loan_request",
+            &serde_json::json!({ "requester_request_id": "out-1" }),
+            None,
+            Some("out-1"),
+            "connection refused",
+        )
+        .await
+        .unwrap();
+        retry_all_now(&db).await.unwrap();
+
+        assert_eq!(deliver_due(&state).await.unwrap(), 0);
+        assert!(list(&db).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Peer protocol version and features, exchanged at `/api/peers/handshake`.
+//!
+//! Peers run whatever build their owner last installed, so a network mixes
+//! versions. Before using a behavior a peer might not have, the app checks
+//! the features that peer advertised (`supports`). They are asked for with a
+//! handshake to the peer's URL (`negotiate`), kept per peer in
+//! `peer_capabilities` and asked for again a day later, at the next
+//! catalogue sync (`refresh`).
+//!
+//! A peer that never answered a handshake, because its build predates it or
+//! it is only reachable through the relay, is taken to speak protocol 1: the
+//! features every build had before the handshake ([`LEGACY_FEATURES`]).
+//! Features added from now on are used only with peers that advertise them.
+//! Feature names this build does not know (`iroh`, from a build reachable
+//! over iroh) are kept as received.
+//!
+//! Only the answers to handshakes this library sends are stored, as they
+//! come from the peer's own URL. A handshake a peer sends us is answered,
+//! and when it differs from what we hold for that library, only makes us
+//! ask again: anyone can claim a library UUID.
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::AppState;
+use crate::models::peer;
+use crate::utils::sanitize;
+
+/// The protocol this build speaks. Builds without the handshake speak 1.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The protocol of a peer that never answered a handshake.
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// Catalogue delta windows over E2EE (`catalog_delta_request`, ADR-029).
+pub const INCREMENTAL_SYNC: &str = "incremental_sync";
+
+/// Requests sent in Ed25519-signed E2EE envelopes.
+pub const SIGNED_REQUESTS: &str = "signed_requests";
+
+/// Features of every build before the handshake. Never grows: a new feature
+/// goes to [`FEATURES`] only, so it is used only with peers advertising it.
+pub const LEGACY_FEATURES: &[&str] = &[INCREMENTAL_SYNC, SIGNED_REQUESTS];
+
+/// Features this build advertises.
+pub const FEATURES: &[&str] = &[INCREMENTAL_SYNC, SIGNED_REQUESTS];
+
+/// Most features kept from a handshake.
+const MAX_FEATURES: usize = 64;
+
+/// Hours before a peer is asked for its features again.
+const REFRESH_AFTER_HOURS: i64 = 24;
+
+/// What a library tells a peer about itself, and what it answers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Handshake {
+    pub protocol_version: u32,
+    /// Names of the optional behaviors the library supports
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub library_uuid: Option<String>,
+}
+
+impl Handshake {
+    /// This library's handshake.
+    pub fn ours(state: &AppState) -> Self {
+        Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            features: FEATURES.iter().map(|f| f.to_string()).collect(),
+            library_uuid: state.identity_service.library_uuid().map(str::to_string),
+        }
+    }
+
+    /// The handshake of a peer that predates it.
+    fn legacy() -> Self {
+        Handshake {
+            protocol_version: LEGACY_PROTOCOL_VERSION,
+            features: LEGACY_FEATURES.iter().map(|f| f.to_string()).collect(),
+            library_uuid: None,
+        }
+    }
+
+    /// A received handshake with its strings bounded and features deduplicated.
+    pub fn sanitized(self) -> Self {
+        let mut features: Vec<String> = Vec::new();
+        for feature in self.features {
+            let feature = sanitize::line(&feature, sanitize::MAX_CODE_CHARS);
+            if !feature.is_empty() && !features.contains(&feature) {
+                features.push(feature);
+            }
+            if features.len() == MAX_FEATURES {
+                break;
+            }
+        }
+        Handshake {
+            protocol_version: self.protocol_version,
+            features,
+            library_uuid: sanitize::opt_line(self.library_uuid, sanitize::MAX_CODE_CHARS),
+        }
+    }
+}
+
+/// What a peer supports, as last negotiated.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct PeerCapabilities {
+    pub peer_id: i32,
+    pub protocol_version: u32,
+    pub features: Vec<String>,
+    /// When the peer answered a handshake; null when it never did and
+    /// protocol 1 is assumed
+    pub negotiated_at: Option<String>,
+}
+
+impl PeerCapabilities {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    fn assumed(peer_id: i32) -> Self {
+        let legacy = Handshake::legacy();
+        PeerCapabilities {
+            peer_id,
+            protocol_version: legacy.protocol_version,
+            features: legacy.features,
+            negotiated_at: None,
+        }
+    }
+}
+
+/// What `peer_id` supports: as negotiated, or protocol 1 when it never was.
+pub async fn capabilities(
+    db: &DatabaseConnection,
+    peer_id: i32,
+) -> Result<PeerCapabilities, DbErr> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT protocol_version, features, negotiated_at \
+             FROM peer_capabilities WHERE peer_id = ?",
+            [peer_id.into()],
+        ))
+        .await?;
+    let Some(row) = row else {
+        return Ok(PeerCapabilities::assumed(peer_id));
+    };
+    let protocol_version: i64 = row.try_get("", "protocol_version")?;
+    let features: String = row.try_get("", "features")?;
+    Ok(PeerCapabilities {
+        peer_id,
+        protocol_version: u32::try_from(protocol_version).unwrap_or(LEGACY_PROTOCOL_VERSION),
+        features: serde_json::from_str(&features).unwrap_or_default(),
+        negotiated_at: row.try_get("", "negotiated_at")?,
+    })
+}
+
+/// Whether `peer_id` supports `feature`. Protocol 1 is assumed when its
+/// capabilities cannot be read.
+pub async fn supports(db: &DatabaseConnection, peer_id: i32, feature: &str) -> bool {
+    match capabilities(db, peer_id).await {
+        Ok(found) => found.supports(feature),
+        Err(e) => {
+            tracing::warn!("peer_protocol: capabilities of peer {peer_id}: {e}");
+            PeerCapabilities::assumed(peer_id).supports(feature)
+        }
+    }
+}
+
+async fn store(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    handshake: &Handshake,
+) -> Result<PeerCapabilities, DbErr> {
+    let negotiated_at = chrono::Utc::now().to_rfc3339();
+    let features = serde_json::to_string(&handshake.features).unwrap_or_else(|_| "[]".into());
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "INSERT INTO peer_capabilities (peer_id, protocol_version, features, negotiated_at) \
+         VALUES (?, ?, ?, ?) \
+         ON CONFLICT(peer_id) DO UPDATE SET protocol_version = excluded.protocol_version, \
+         features = excluded.features, negotiated_at = excluded.negotiated_at",
+        [
+            peer_id.into(),
+            i64::from(handshake.protocol_version).into(),
+            features.into(),
+            negotiated_at.clone().into(),
+        ],
+    ))
+    .await?;
+    Ok(PeerCapabilities {
+        peer_id,
+        protocol_version: handshake.protocol_version,
+        features: handshake.features.clone(),
+        negotiated_at: Some(negotiated_at),
+    })
+}
+
+/// Handshake with `peer` and keep what it supports. A peer without the
+/// endpoint (404) is kept as protocol 1.
+pub async fn negotiate(state: &AppState, peer: &peer::Model) -> Result<PeerCapabilities, String> {
+    crate::api::peer::validate_url(&peer.url)?;
+    let response = crate::api::peer::get_safe_client_for(&peer.url)
+        .post(format!("{}/api/peers/handshake", peer.url))
+        .json(&Handshake::ours(state))
+        .send()
+        .await
+        .map_err(|e| format!("handshake with {}: {e}", peer.url))?;
+
+    let theirs = match response.status() {
+        status if status.is_success() => response
+            .json::<Handshake>()
+            .await
+            .map_err(|e| format!("handshake with {}: {e}", peer.url))?
+            .sanitized(),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::METHOD_NOT_ALLOWED => {
+            Handshake::legacy()
+        }
+        status => return Err(format!("handshake with {}: status {status}", peer.url)),
+    };
+    if let (Some(known), Some(claimed)) = (&peer.library_uuid, &theirs.library_uuid)
+        && known != claimed
+    {
+        return Err(format!(
+            "handshake with {}: answered as another library ({claimed})",
+            peer.url
+        ));
+    }
+    store(state.db(), peer.id, &theirs)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// What `peer` supports, asked for again when the last answer is a day old.
+/// Keeps what is known when the peer cannot be asked.
+pub async fn refresh(state: &AppState, peer: &peer::Model) -> PeerCapabilities {
+    let db = state.db();
+    let known = capabilities(db, peer.id)
+        .await
+        .unwrap_or_else(|_| PeerCapabilities::assumed(peer.id));
+    let fresh = known
+        .negotiated_at
+        .as_deref()
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+        .is_some_and(|at| {
+            chrono::Utc::now() - at.with_timezone(&chrono::Utc)
+                < chrono::Duration::hours(REFRESH_AFTER_HOURS)
+        });
+    if fresh || crate::api::peer::validate_url(&peer.url).is_err() {
+        return known;
+    }
+    match negotiate(state, peer).await {
+        Ok(negotiated) => negotiated,
+        Err(e) => {
+            tracing::info!("peer_protocol: {e}");
+            known
+        }
+    }
+}
+
+/// A handshake received from a peer. When it does not match what we hold for
+/// the library it claims to be, that library is asked again at its next sync.
+pub async fn received(db: &DatabaseConnection, theirs: &Handshake) -> Result<(), DbErr> {
+    let Some(uuid) = theirs.library_uuid.as_deref() else {
+        return Ok(());
+    };
+    let peers = peer::Entity::find()
+        .filter(peer::Column::LibraryUuid.eq(uuid))
+        .all(db)
+        .await?;
+    for found in peers {
+        let known = capabilities(db, found.id).await?;
+        if known.negotiated_at.is_some()
+            && (known.protocol_version != theirs.protocol_version
+                || known.features != theirs.features)
+        {
+            db.execute(Statement::from_sql_and_values(
+                db.get_database_backend(),
+                "DELETE FROM peer_capabilities WHERE peer_id = ?",
+                [found.id.into()],
+            ))
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn peer_row(db: &DatabaseConnection, uuid: &str) -> i32 {
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set("Médiathèque".to_string()),
+            url: Set("http://192.168.1.20:8000".to_string()),
+            library_uuid: Set(Some(uuid.to_string())),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[tokio::test]
+    async fn unknown_peers_speak_protocol_one_until_negotiated() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let id = peer_row(&db, "lib-a").await;
+
+        let assumed = capabilities(&db, id).await.unwrap();
+        assert_eq!(assumed.protocol_version, LEGACY_PROTOCOL_VERSION);
+        assert!(assumed.negotiated_at.is_none());
+        assert!(supports(&db, id, INCREMENTAL_SYNC).await);
+
+        // A newer peer without incremental sync, advertising a feature this
+        // build does not know.
+        let theirs = Handshake {
+            protocol_version: 3,
+            features: vec!["iroh".into(), "iroh".into(), SIGNED_REQUESTS.into()],
+            library_uuid: Some("lib-a".into()),
+        }
+        .sanitized();
+        store(&db, id, &theirs).await.unwrap();
+        let stored = capabilities(&db, id).await.unwrap();
+        assert_eq!(stored.protocol_version, 3);
+        assert_eq!(stored.features, vec!["iroh", SIGNED_REQUESTS]);
+        assert!(!supports(&db, id, INCREMENTAL_SYNC).await);
+
+        // The same handshake sent to us keeps it; a different one drops it.
+        received(&db, &theirs).await.unwrap();
+        assert!(capabilities(&db, id).await.unwrap().negotiated_at.is_some());
+        let upgraded = Handshake {
+            protocol_version: 4,
+            ..theirs
+        };
+        received(&db, &upgraded).await.unwrap();
+        assert!(capabilities(&db, id).await.unwrap().negotiated_at.is_none());
+    }
+}
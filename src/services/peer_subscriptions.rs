@@ -0,0 +1,436 @@
+//! Subscriptions to peer catalogues.
+//!
+//! Subscribing to a peer keeps its `peer_books` cache fresh without a manual
+//! `/peers/:id/sync`: a background task syncs each subscription when it is
+//! due, every `interval_minutes`, and a `catalog_changed` notification from
+//! the peer (relay push, see `catalog_notification`) makes it due at once.
+//!
+//! A sync asks for a delta first (ADR-029) when keys were exchanged with the
+//! peer, and falls back to the full catalogue over HTTP (`sync_peer_internal`)
+//! when the peer cannot answer one. Each attempt is recorded on the
+//! subscription: status, method, books cached and the last error. Failed
+//! attempts are retried sooner than the interval, backing off with each
+//! failure in a row.
+
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::infrastructure::AppState;
+use crate::models::{peer, peer_book, peer_subscription};
+use crate::services::peer_delta_sync::{self, DeltaSyncOutcome};
+use crate::services::peer_protocol;
+
+pub const DEFAULT_INTERVAL_MINUTES: i32 = 60;
+pub const MIN_INTERVAL_MINUTES: i32 = 5;
+pub const MAX_INTERVAL_MINUTES: i32 = 7 * 24 * 60;
+
+/// Time between two looks at the due subscriptions.
+const TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Delay before the first look, so startup (relay registration, peer
+/// discovery) settles before peers are queried.
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(90);
+
+/// Delta windows applied in one sync; a longer backlog continues next time.
+const MAX_DELTA_PASSES: usize = 20;
+
+/// First retry after a failure; doubles with each failure in a row, up to
+/// the subscription's interval.
+const RETRY_BASE_MINUTES: i64 = 2;
+
+/// Peers being synced right now, so the scheduler and a manual sync never
+/// run the same peer twice at once.
+static IN_FLIGHT: LazyLock<Mutex<HashSet<i32>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Error type for subscription operations
+#[derive(Debug)]
+pub enum SubscriptionError {
+    PeerNotFound,
+    NotSubscribed,
+    /// The peer has not accepted the connection yet.
+    NotApproved,
+    /// A sync of this peer is already running.
+    Busy,
+    Db(DbErr),
+}
+
+impl std::fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubscriptionError::PeerNotFound => write!(f, "Peer not found"),
+            SubscriptionError::NotSubscribed => write!(f, "Not subscribed to this peer"),
+            SubscriptionError::NotApproved => write!(f, "Peer connection pending approval"),
+            SubscriptionError::Busy => write!(f, "A sync of this peer is already running"),
+            SubscriptionError::Db(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<DbErr> for SubscriptionError {
+    fn from(e: DbErr) -> Self {
+        SubscriptionError::Db(e)
+    }
+}
+
+/// A subscription with the name of its peer.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SubscriptionView {
+    pub peer_name: String,
+    pub peer_url: String,
+    #[serde(flatten)]
+    #[schema(value_type = PeerSubscription)]
+    pub subscription: peer_subscription::Model,
+}
+
+/// How a successful sync went.
+#[derive(Debug, Clone, PartialEq)]
+struct SyncReport {
+    method: &'static str,
+    books_cached: i32,
+}
+
+fn clamp_interval(minutes: Option<i32>) -> i32 {
+    minutes
+        .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+        .clamp(MIN_INTERVAL_MINUTES, MAX_INTERVAL_MINUTES)
+}
+
+/// When to try again after `failures` failed attempts in a row.
+fn retry_at(now: DateTime<Utc>, failures: i32, interval_minutes: i32) -> DateTime<Utc> {
+    let backoff = RETRY_BASE_MINUTES.saturating_mul(1 << failures.clamp(1, 16).saturating_sub(1));
+    now + Duration::minutes(backoff.min(interval_minutes as i64))
+}
+
+/// Subscribe to `peer_id`, or change the interval of an existing
+/// subscription. A new subscription is due at once.
+pub async fn subscribe(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    interval_minutes: Option<i32>,
+) -> Result<peer_subscription::Model, SubscriptionError> {
+    let peer = peer::Entity::find_by_id(peer_id)
+        .one(db)
+        .await?
+        .ok_or(SubscriptionError::PeerNotFound)?;
+    if !crate::api::peer::is_peer_approved(db, &peer).await {
+        return Err(SubscriptionError::NotApproved);
+    }
+    let interval = clamp_interval(interval_minutes);
+
+    if let Some(existing) = peer_subscription::Entity::find_by_id(peer_id)
+        .one(db)
+        .await?
+    {
+        let mut active: peer_subscription::ActiveModel = existing.into();
+        active.interval_minutes = Set(interval);
+        return Ok(active.update(db).await?);
+    }
+    let now = Utc::now().to_rfc3339();
+    Ok(peer_subscription::ActiveModel {
+        peer_id: Set(peer_id),
+        interval_minutes: Set(interval),
+        status: Set("pending".to_string()),
+        last_attempt_at: Set(None),
+        last_success_at: Set(None),
+        last_method: Set(None),
+        last_error: Set(None),
+        failures: Set(0),
+        books_cached: Set(None),
+        next_sync_at: Set(now.clone()),
+        created_at: Set(now),
+    }
+    .insert(db)
+    .await?)
+}
+
+/// Stop keeping `peer_id` fresh. Its cached books stay.
+pub async fn unsubscribe(db: &DatabaseConnection, peer_id: i32) -> Result<bool, DbErr> {
+    let res = peer_subscription::Entity::delete_by_id(peer_id)
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Every subscription, with its peer.
+pub async fn list(db: &DatabaseConnection) -> Result<Vec<SubscriptionView>, DbErr> {
+    Ok(peer_subscription::Entity::find()
+        .find_also_related(peer::Entity)
+        .order_by_asc(peer_subscription::Column::PeerId)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|(subscription, peer)| {
+            let peer = peer?;
+            Some(SubscriptionView {
+                peer_name: peer.display_name.unwrap_or(peer.name),
+                peer_url: peer.url,
+                subscription,
+            })
+        })
+        .collect())
+}
+
+/// Make a subscription due now, when its peer reports a catalogue change.
+/// Does nothing for a peer without a subscription.
+pub async fn mark_due(db: &DatabaseConnection, peer_id: i32) -> Result<bool, DbErr> {
+    let res = peer_subscription::Entity::update_many()
+        .col_expr(
+            peer_subscription::Column::NextSyncAt,
+            sea_query::Expr::value(Utc::now().to_rfc3339()),
+        )
+        .filter(peer_subscription::Column::PeerId.eq(peer_id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+/// Subscriptions due at `now`, the longest overdue first.
+async fn due(db: &DatabaseConnection, now: DateTime<Utc>) -> Result<Vec<i32>, DbErr> {
+    Ok(peer_subscription::Entity::find()
+        .filter(peer_subscription::Column::NextSyncAt.lte(now.to_rfc3339()))
+        .order_by_asc(peer_subscription::Column::NextSyncAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| s.peer_id)
+        .collect())
+}
+
+/// Sync a subscribed peer now and record how it went.
+pub async fn sync_now(
+    state: &AppState,
+    peer_id: i32,
+) -> Result<peer_subscription::Model, SubscriptionError> {
+    let db = state.db();
+    let subscription = peer_subscription::Entity::find_by_id(peer_id)
+        .one(db)
+        .await?
+        .ok_or(SubscriptionError::NotSubscribed)?;
+    if !IN_FLIGHT.lock().unwrap().insert(peer_id) {
+        return Err(SubscriptionError::Busy);
+    }
+
+    let started = Utc::now();
+    let mut active: peer_subscription::ActiveModel = subscription.clone().into();
+    active.status = Set("syncing".to_string());
+    active.last_attempt_at = Set(Some(started.to_rfc3339()));
+    let recorded = match active.update(db).await {
+        Ok(_) => {
+            let result = match peer::Entity::find_by_id(peer_id).one(db).await {
+                Ok(Some(p)) => {
+                    if crate::api::peer::is_peer_approved(db, &p).await {
+                        sync_peer_catalog(state, &p).await
+                    } else {
+                        Err(SubscriptionError::NotApproved.to_string())
+                    }
+                }
+                Ok(None) => Err(SubscriptionError::PeerNotFound.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            record(db, subscription, result).await
+        }
+        Err(e) => Err(e),
+    };
+    IN_FLIGHT.lock().unwrap().remove(&peer_id);
+    Ok(recorded?)
+}
+
+/// Store the outcome of a sync on the subscription and schedule the next.
+async fn record(
+    db: &DatabaseConnection,
+    subscription: peer_subscription::Model,
+    result: Result<SyncReport, String>,
+) -> Result<peer_subscription::Model, DbErr> {
+    let now = Utc::now();
+    let interval = subscription.interval_minutes;
+    let failures = subscription.failures;
+    let mut active: peer_subscription::ActiveModel = subscription.into();
+    match result {
+        Ok(report) => {
+            active.status = Set("ok".to_string());
+            active.last_success_at = Set(Some(now.to_rfc3339()));
+            active.last_method = Set(Some(report.method.to_string()));
+            active.last_error = Set(None);
+            active.failures = Set(0);
+            active.books_cached = Set(Some(report.books_cached));
+            active.next_sync_at = Set((now + Duration::minutes(interval as i64)).to_rfc3339());
+        }
+        Err(error) => {
+            let failures = failures.saturating_add(1);
+            tracing::warn!("peer subscription: sync failed ({failures} in a row): {error}");
+            active.status = Set("error".to_string());
+            active.last_error = Set(Some(error));
+            active.failures = Set(failures);
+            active.next_sync_at = Set(retry_at(now, failures, interval).to_rfc3339());
+        }
+    }
+    active.update(db).await
+}
+
+/// Bring the peer's cached catalogue up to date: delta windows when the peer
+/// answers them, else the full catalogue over HTTP.
+async fn sync_peer_catalog(state: &AppState, peer: &peer::Model) -> Result<SyncReport, String> {
+    let db = state.db();
+    // Learn what the peer supports before picking the method.
+    peer_protocol::refresh(state, peer).await;
+    let mut reset_cursor = None;
+    let mut delta_error = None;
+
+    if peer.key_exchange_done {
+        for _ in 0..MAX_DELTA_PASSES {
+            match peer_delta_sync::fetch_and_apply_peer_delta(state, peer.id).await {
+                Ok(DeltaSyncOutcome::Applied { has_more: true, .. }) => continue,
+                Ok(DeltaSyncOutcome::Applied { .. }) => {
+                    return Ok(SyncReport {
+                        method: "delta",
+                        books_cached: cached_books(db, peer.id).await?,
+                    });
+                }
+                Ok(DeltaSyncOutcome::ResetRequired { current_cursor }) => {
+                    reset_cursor = current_cursor;
+                    break;
+                }
+                Ok(DeltaSyncOutcome::FallbackRequired | DeltaSyncOutcome::E2eeUnavailable) => {
+                    break;
+                }
+                Err(e) => {
+                    delta_error = Some(e);
+                    break;
+                }
+            }
+        }
+        // Still more after the last pass: keep what was applied, the rest
+        // comes with the next sync.
+        if delta_error.is_none() && reset_cursor.is_none() {
+            let probe = peer::Entity::find_by_id(peer.id)
+                .one(db)
+                .await
+                .map_err(|e| e.to_string())?;
+            if probe.is_some_and(|p| p.last_delta_cursor != peer.last_delta_cursor) {
+                return Ok(SyncReport {
+                    method: "delta",
+                    books_cached: cached_books(db, peer.id).await?,
+                });
+            }
+        }
+    }
+
+    if crate::api::peer::validate_url(&peer.url).is_err() {
+        return Err(delta_error.unwrap_or_else(|| {
+            "peer is only reachable through the relay and answered no delta request".to_string()
+        }));
+    }
+    crate::api::peer::sync_peer_internal(db, peer.id, &peer.url).await?;
+    // The full catalogue now matches the cursor the peer reported: resume
+    // with deltas from there.
+    if let Some(cursor) = reset_cursor {
+        let _ = peer_delta_sync::set_peer_last_delta_cursor(db, peer.id, cursor).await;
+    }
+    Ok(SyncReport {
+        method: "full",
+        books_cached: cached_books(db, peer.id).await?,
+    })
+}
+
+async fn cached_books(db: &DatabaseConnection, peer_id: i32) -> Result<i32, String> {
+    peer_book::Entity::find()
+        .filter(peer_book::Column::PeerId.eq(peer_id))
+        .count(db)
+        .await
+        .map(|n| n as i32)
+        .map_err(|e| e.to_string())
+}
+
+/// Spawn the background task: sync every due subscription, one peer at a
+/// time.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + STARTUP_DELAY;
+        let mut ticker = tokio::time::interval_at(start, TICK);
+        loop {
+            ticker.tick().await;
+            let peer_ids = match due(state.db(), Utc::now()).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("peer subscriptions: could not load due subscriptions: {e}");
+                    continue;
+                }
+            };
+            for peer_id in peer_ids {
+                match sync_now(&state, peer_id).await {
+                    Ok(s) if s.status == "ok" => tracing::info!(
+                        "peer subscriptions: peer {peer_id} synced ({} books)",
+                        s.books_cached.unwrap_or_default()
+                    ),
+                    Ok(_) | Err(SubscriptionError::Busy) => {}
+                    Err(e) => tracing::warn!("peer subscriptions: peer {peer_id}: {e}"),
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failures_back_off_and_notifications_make_a_subscription_due() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = Utc::now().to_rfc3339();
+        let peer = peer::ActiveModel {
+            name: Set("Marie".to_string()),
+            url: Set("http://192.168.1.20:8000".to_string()),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let sub = subscribe(&db, peer.id, Some(1)).await.unwrap();
+        assert_eq!(sub.interval_minutes, MIN_INTERVAL_MINUTES);
+        assert_eq!(sub.status, "pending");
+        assert_eq!(due(&db, Utc::now()).await.unwrap(), vec![peer.id]);
+
+        let failed = record(&db, sub, Err("peer unreachable".into()))
+            .await
+            .unwrap();
+        assert_eq!(failed.status, "error");
+        assert_eq!(failed.failures, 1);
+        assert_eq!(failed.last_error.as_deref(), Some("peer unreachable"));
+        assert!(due(&db, Utc::now()).await.unwrap().is_empty());
+
+        // The peer pushes a change: due again, whatever the backoff said.
+        assert!(mark_due(&db, peer.id).await.unwrap());
+        assert_eq!(due(&db, Utc::now()).await.unwrap(), vec![peer.id]);
+
+        let ok = record(
+            &db,
+            failed,
+            Ok(SyncReport {
+                method: "full",
+                books_cached: 12,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!((ok.status.as_str(), ok.failures), ("ok", 0));
+        assert_eq!(ok.last_error, None);
+        assert_eq!(ok.books_cached, Some(12));
+
+        let t = Utc::now();
+        assert_eq!(retry_at(t, 1, 60), t + Duration::minutes(2));
+        assert_eq!(retry_at(t, 3, 60), t + Duration::minutes(8));
+        assert_eq!(retry_at(t, 10, 60), t + Duration::minutes(60));
+
+        assert!(unsubscribe(&db, peer.id).await.unwrap());
+        assert!(!mark_due(&db, peer.id).await.unwrap());
+    }
+}
@@ -0,0 +1,592 @@
+//! Periodicals: magazines and journals, and the issues checked in.
+//!
+//! Issues do not fit the book model: a periodical is catalogued once, by
+//! title and ISSN, and each issue received is checked in under it with its
+//! volume and number (or a label for special issues). The holdings are
+//! computed from the issues: a summary statement such as
+//! `v.3:no.1-12; v.4:no.1-3,5` and the numbers missing in between.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Duration, Local, NaiveDate, Utc};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    periodical::{self, FREQUENCIES},
+    periodical_issue,
+};
+
+/// Largest volume or issue number.
+const MAX_NUMBER: i32 = 100_000;
+
+#[derive(Debug)]
+pub enum PeriodicalError {
+    Db(DbErr),
+    NotFound,
+    IssueNotFound,
+    /// Another periodical has this ISSN.
+    DuplicateIssn(String),
+    /// This volume and number are already checked in.
+    DuplicateIssue,
+    Invalid(String),
+}
+
+impl std::fmt::Display for PeriodicalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::NotFound => write!(f, "periodical not found"),
+            Self::IssueNotFound => write!(f, "issue not found"),
+            Self::DuplicateIssn(issn) => write!(f, "a periodical with ISSN {issn} already exists"),
+            Self::DuplicateIssue => write!(f, "this issue is already checked in"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for PeriodicalError {}
+
+impl From<DbErr> for PeriodicalError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewPeriodical {
+    pub title: String,
+    /// `NNNN-NNNC`, with or without the hyphen, or the EAN-13 (`977...`)
+    /// printed on the cover
+    pub issn: Option<String>,
+    pub publisher: Option<String>,
+    /// `daily`, `weekly`, `biweekly`, `monthly` (default), `bimonthly`,
+    /// `quarterly`, `semiannual`, `annual` or `irregular`
+    pub frequency: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// An issue to check in. Without volume, number or label, it is the next
+/// expected issue.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::ToSchema)]
+pub struct CheckIn {
+    pub volume: Option<i32>,
+    pub number: Option<i32>,
+    pub label: Option<String>,
+    /// `YYYY-MM-DD`
+    pub cover_date: Option<String>,
+    /// `YYYY-MM-DD`, defaults to today
+    pub received_at: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A numbered issue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct IssueNumber {
+    pub volume: Option<i32>,
+    pub number: i32,
+}
+
+/// The issue expected after the last one checked in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct NextIssue {
+    pub volume: Option<i32>,
+    pub number: i32,
+    /// `YYYY-MM-DD`, from the last cover date and the frequency; none for
+    /// irregular periodicals
+    pub expected_on: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct Holdings {
+    /// Summary, e.g. `v.3:no.1-12; v.4:no.1-3,5`
+    pub statement: String,
+    /// Issues checked in
+    pub held: u32,
+    /// Numbers missing between the first and last held of each volume
+    pub missing: Vec<IssueNumber>,
+    /// Issues checked in with a label only
+    pub unnumbered: u32,
+}
+
+/// A periodical with its issues, latest first, and holdings.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct PeriodicalDetail {
+    #[schema(value_type = Periodical)]
+    pub periodical: periodical::Model,
+    #[schema(value_type = Vec<PeriodicalIssue>)]
+    pub issues: Vec<periodical_issue::Model>,
+    pub holdings: Holdings,
+    pub next_issue: Option<NextIssue>,
+}
+
+fn issn_check_digit(base: &[u32]) -> char {
+    let sum: u32 = base.iter().zip((2..=8).rev()).map(|(d, w)| d * w).sum();
+    match (11 - sum % 11) % 11 {
+        10 => 'X',
+        d => char::from_digit(d, 10).unwrap_or('0'),
+    }
+}
+
+/// `NNNN-NNNC` for an ISSN typed with or without hyphen, or read off the
+/// EAN-13 of a cover (`977` + the first seven digits + price code + check);
+/// `None` when malformed or the check digit is wrong.
+pub fn normalize_issn(raw: &str) -> Option<String> {
+    let chars: Vec<char> = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let (digits, check) = match chars.len() {
+        8 => (&chars[..7], Some(chars[7])),
+        13 if chars.starts_with(&['9', '7', '7']) => (&chars[3..10], None),
+        _ => return None,
+    };
+    let base: Vec<u32> = digits
+        .iter()
+        .map(|c| c.to_digit(10))
+        .collect::<Option<_>>()?;
+    let computed = issn_check_digit(&base);
+    if check.is_some_and(|c| c != computed) {
+        return None;
+    }
+    let digits: String = digits.iter().collect();
+    Some(format!("{}-{}{}", &digits[..4], &digits[4..], computed))
+}
+
+fn frequency_days(frequency: &str) -> Option<i64> {
+    FREQUENCIES
+        .iter()
+        .find(|(name, _)| *name == frequency)
+        .map(|(_, days)| *days)
+}
+
+fn check_day(field: &str, value: &str) -> Result<NaiveDate, PeriodicalError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| PeriodicalError::Invalid(format!("{field} must be a YYYY-MM-DD date")))
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// `1-3,5` for the numbers 1, 2, 3 and 5.
+fn ranges(numbers: &BTreeSet<i32>) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut iter = numbers.iter().copied().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end += 1;
+            iter.next();
+        }
+        parts.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        });
+    }
+    parts.join(",")
+}
+
+fn holdings(issues: &[periodical_issue::Model]) -> Holdings {
+    let mut volumes: BTreeMap<Option<i32>, BTreeSet<i32>> = BTreeMap::new();
+    let mut unnumbered = 0;
+    for issue in issues {
+        match issue.number {
+            Some(number) => {
+                volumes.entry(issue.volume).or_default().insert(number);
+            }
+            None => unnumbered += 1,
+        }
+    }
+    let mut statement = Vec::new();
+    let mut missing = Vec::new();
+    for (volume, numbers) in &volumes {
+        let held = ranges(numbers);
+        statement.push(match volume {
+            Some(v) => format!("v.{v}:no.{held}"),
+            None => format!("no.{held}"),
+        });
+        if let (Some(first), Some(last)) = (numbers.first(), numbers.last()) {
+            missing.extend(
+                (*first..=*last)
+                    .filter(|n| !numbers.contains(n))
+                    .map(|number| IssueNumber {
+                        volume: *volume,
+                        number,
+                    }),
+            );
+        }
+    }
+    Holdings {
+        statement: statement.join("; "),
+        held: issues.len() as u32,
+        missing,
+        unnumbered,
+    }
+}
+
+/// The issue after the highest numbered one, due a period after its cover
+/// (or receipt) date.
+fn next_issue(frequency: &str, issues: &[periodical_issue::Model]) -> Option<NextIssue> {
+    let last = issues
+        .iter()
+        .filter(|i| i.number.is_some())
+        .max_by_key(|i| (i.volume, i.number))?;
+    let expected_on = frequency_days(frequency)
+        .filter(|days| *days > 0)
+        .and_then(|days| {
+            let from = last.cover_date.as_deref().unwrap_or(&last.received_at);
+            NaiveDate::parse_from_str(from, "%Y-%m-%d")
+                .ok()
+                .map(|d| (d + Duration::days(days)).format("%Y-%m-%d").to_string())
+        });
+    Some(NextIssue {
+        volume: last.volume,
+        number: last.number.unwrap_or_default() + 1,
+        expected_on,
+    })
+}
+
+async fn detail(
+    db: &DatabaseConnection,
+    periodical: periodical::Model,
+) -> Result<PeriodicalDetail, DbErr> {
+    let issues = periodical_issue::Entity::find()
+        .filter(periodical_issue::Column::PeriodicalId.eq(periodical.id.as_str()))
+        .order_by_desc(periodical_issue::Column::Volume)
+        .order_by_desc(periodical_issue::Column::Number)
+        .order_by_desc(periodical_issue::Column::ReceivedAt)
+        .all(db)
+        .await?;
+    Ok(PeriodicalDetail {
+        holdings: holdings(&issues),
+        next_issue: next_issue(&periodical.frequency, &issues),
+        periodical,
+        issues,
+    })
+}
+
+/// Checked and normalized fields of a new or edited periodical.
+async fn validate(
+    db: &DatabaseConnection,
+    id: Option<&str>,
+    new: NewPeriodical,
+) -> Result<NewPeriodical, PeriodicalError> {
+    let title = new.title.trim().to_string();
+    if title.is_empty() {
+        return Err(PeriodicalError::Invalid("title is required".to_string()));
+    }
+    let issn = match new.issn.as_deref().map(str::trim).filter(|i| !i.is_empty()) {
+        Some(raw) => Some(
+            normalize_issn(raw)
+                .ok_or_else(|| PeriodicalError::Invalid(format!("invalid ISSN {raw}")))?,
+        ),
+        None => None,
+    };
+    if let Some(issn) = &issn
+        && let Some(other) = periodical::Entity::find()
+            .filter(periodical::Column::Issn.eq(issn.as_str()))
+            .one(db)
+            .await?
+        && Some(other.id.as_str()) != id
+    {
+        return Err(PeriodicalError::DuplicateIssn(issn.clone()));
+    }
+    let frequency = new.frequency.unwrap_or_else(|| "monthly".to_string());
+    if frequency_days(&frequency).is_none() {
+        return Err(PeriodicalError::Invalid(format!(
+            "unknown frequency {frequency}"
+        )));
+    }
+    Ok(NewPeriodical {
+        title,
+        issn,
+        publisher: new.publisher,
+        frequency: Some(frequency),
+        notes: new.notes,
+    })
+}
+
+/// Add a periodical.
+pub async fn create_periodical(
+    db: &DatabaseConnection,
+    new: NewPeriodical,
+) -> Result<PeriodicalDetail, PeriodicalError> {
+    let new = validate(db, None, new).await?;
+    let now = Utc::now().to_rfc3339();
+    let saved = periodical::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        title: Set(new.title),
+        issn: Set(new.issn),
+        publisher: Set(new.publisher),
+        frequency: Set(new.frequency.unwrap_or_default()),
+        notes: Set(new.notes),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+    Ok(detail(db, saved).await?)
+}
+
+/// Replace a periodical's title, ISSN, publisher, frequency and notes.
+pub async fn update_periodical(
+    db: &DatabaseConnection,
+    id: &str,
+    new: NewPeriodical,
+) -> Result<PeriodicalDetail, PeriodicalError> {
+    let found = periodical::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(PeriodicalError::NotFound)?;
+    let new = validate(db, Some(id), new).await?;
+    let mut active: periodical::ActiveModel = found.into();
+    active.title = Set(new.title);
+    active.issn = Set(new.issn);
+    active.publisher = Set(new.publisher);
+    active.frequency = Set(new.frequency.unwrap_or_default());
+    active.notes = Set(new.notes);
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let saved = active.update(db).await?;
+    Ok(detail(db, saved).await?)
+}
+
+/// Delete a periodical and its issues.
+pub async fn delete_periodical(db: &DatabaseConnection, id: &str) -> Result<(), PeriodicalError> {
+    let txn = db.begin().await?;
+    periodical_issue::Entity::delete_many()
+        .filter(periodical_issue::Column::PeriodicalId.eq(id))
+        .exec(&txn)
+        .await?;
+    let deleted = periodical::Entity::delete_by_id(id.to_string())
+        .exec(&txn)
+        .await?;
+    if deleted.rows_affected == 0 {
+        return Err(PeriodicalError::NotFound);
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+/// One periodical with its issues and holdings.
+pub async fn get_periodical(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<Option<PeriodicalDetail>, DbErr> {
+    match periodical::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+    {
+        Some(found) => Ok(Some(detail(db, found).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Periodicals by title, or the one with an ISSN (typed or scanned).
+pub async fn list_periodicals(
+    db: &DatabaseConnection,
+    issn: Option<&str>,
+) -> Result<Vec<PeriodicalDetail>, PeriodicalError> {
+    let mut query = periodical::Entity::find();
+    if let Some(raw) = issn {
+        let issn = normalize_issn(raw)
+            .ok_or_else(|| PeriodicalError::Invalid(format!("invalid ISSN {raw}")))?;
+        query = query.filter(periodical::Column::Issn.eq(issn));
+    }
+    let found = query
+        .order_by_asc(periodical::Column::Title)
+        .all(db)
+        .await?;
+    let mut details = Vec::with_capacity(found.len());
+    for periodical in found {
+        details.push(detail(db, periodical).await?);
+    }
+    Ok(details)
+}
+
+/// Check in an issue received; the next expected one when `issue` names
+/// none.
+pub async fn check_in(
+    db: &DatabaseConnection,
+    id: &str,
+    issue: CheckIn,
+) -> Result<PeriodicalDetail, PeriodicalError> {
+    let found = periodical::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(PeriodicalError::NotFound)?;
+    let issues = periodical_issue::Entity::find()
+        .filter(periodical_issue::Column::PeriodicalId.eq(id))
+        .all(db)
+        .await?;
+
+    let label = issue
+        .label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    let (volume, number) = if issue.volume.is_none() && issue.number.is_none() && label.is_none() {
+        match next_issue(&found.frequency, &issues) {
+            Some(next) => (next.volume, Some(next.number)),
+            None => (None, Some(1)),
+        }
+    } else {
+        (issue.volume, issue.number)
+    };
+    for value in [volume, number].into_iter().flatten() {
+        if !(1..=MAX_NUMBER).contains(&value) {
+            return Err(PeriodicalError::Invalid(format!(
+                "volume and number must be between 1 and {MAX_NUMBER}"
+            )));
+        }
+    }
+    if number.is_some()
+        && issues
+            .iter()
+            .any(|i| i.volume == volume && i.number == number)
+    {
+        return Err(PeriodicalError::DuplicateIssue);
+    }
+    if let Some(cover_date) = &issue.cover_date {
+        check_day("cover_date", cover_date)?;
+    }
+    let received_at = issue.received_at.unwrap_or_else(today);
+    check_day("received_at", &received_at)?;
+
+    periodical_issue::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        periodical_id: Set(found.id.clone()),
+        volume: Set(volume),
+        number: Set(number),
+        label: Set(label),
+        cover_date: Set(issue.cover_date),
+        received_at: Set(received_at),
+        notes: Set(issue.notes),
+    }
+    .insert(db)
+    .await?;
+    Ok(detail(db, found).await?)
+}
+
+/// Remove an issue checked in by mistake.
+pub async fn delete_issue(
+    db: &DatabaseConnection,
+    id: &str,
+    issue_id: &str,
+) -> Result<PeriodicalDetail, PeriodicalError> {
+    let found = periodical::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(PeriodicalError::NotFound)?;
+    let deleted = periodical_issue::Entity::delete_many()
+        .filter(periodical_issue::Column::Id.eq(issue_id))
+        .filter(periodical_issue::Column::PeriodicalId.eq(id))
+        .exec(db)
+        .await?;
+    if deleted.rows_affected == 0 {
+        return Err(PeriodicalError::IssueNotFound);
+    }
+    Ok(detail(db, found).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    #[test]
+    fn normalizes_typed_and_scanned_issns() {
+        assert_eq!(normalize_issn("0317-8471").as_deref(), Some("0317-8471"));
+        assert_eq!(normalize_issn("03178471").as_deref(), Some("0317-8471"));
+        assert_eq!(
+            normalize_issn("9770317847001").as_deref(),
+            Some("0317-8471")
+        );
+        assert_eq!(normalize_issn("1050-124x").as_deref(), Some("1050-124X"));
+        assert_eq!(normalize_issn("0317-8472"), None);
+        assert_eq!(normalize_issn("9780317847001"), None);
+    }
+
+    #[tokio::test]
+    async fn check_in_follows_numbering_and_reports_gaps() {
+        let db = setup().await;
+        let created = create_periodical(
+            &db,
+            NewPeriodical {
+                title: "La Hulotte".to_string(),
+                issn: Some("9770317847001".to_string()),
+                publisher: None,
+                frequency: Some("quarterly".to_string()),
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+        let id = created.periodical.id.clone();
+        assert_eq!(created.periodical.issn.as_deref(), Some("0317-8471"));
+        assert!(created.next_issue.is_none());
+
+        let numbered = |number: i32, cover_date: &str| CheckIn {
+            volume: Some(3),
+            number: Some(number),
+            cover_date: Some(cover_date.to_string()),
+            received_at: Some("2026-01-05".to_string()),
+            ..Default::default()
+        };
+        check_in(&db, &id, numbered(1, "2026-01-01")).await.unwrap();
+        check_in(&db, &id, numbered(2, "2026-04-01")).await.unwrap();
+        check_in(&db, &id, numbered(5, "2027-01-01")).await.unwrap();
+        assert!(matches!(
+            check_in(&db, &id, numbered(2, "2026-04-01")).await,
+            Err(PeriodicalError::DuplicateIssue)
+        ));
+        check_in(
+            &db,
+            &id,
+            CheckIn {
+                label: Some("Hors-série".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let detail = check_in(&db, &id, CheckIn::default()).await.unwrap();
+
+        assert_eq!(detail.holdings.statement, "v.3:no.1-2,5-6");
+        assert_eq!(detail.holdings.held, 5);
+        assert_eq!(detail.holdings.unnumbered, 1);
+        assert_eq!(
+            detail.holdings.missing,
+            vec![
+                IssueNumber {
+                    volume: Some(3),
+                    number: 3
+                },
+                IssueNumber {
+                    volume: Some(3),
+                    number: 4
+                },
+            ]
+        );
+        let next = detail.next_issue.unwrap();
+        assert_eq!((next.volume, next.number), (Some(3), 7));
+        assert!(matches!(
+            create_periodical(
+                &db,
+                NewPeriodical {
+                    title: "Duplicate".to_string(),
+                    issn: Some("0317-8471".to_string()),
+                    publisher: None,
+                    frequency: None,
+                    notes: None,
+                },
+            )
+            .await,
+            Err(PeriodicalError::DuplicateIssn(_))
+        ));
+    }
+}
@@ -0,0 +1,396 @@
+//! Catalogue quality report: records an import or a hurried entry left
+//! incomplete or inconsistent, grouped by problem.
+//!
+//! Each group carries its full count, the first books concerned and the
+//! endpoints that fix them in bulk. Archived books are left out. Suspected
+//! duplicates share a natural key ([`crate::utils::dedup_key::book_dedup_key`]):
+//! the same ISBN, whatever its form, or without one the same title, first
+//! author and year.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{author, book, book_authors};
+use crate::services::subject_authority;
+use crate::utils::dedup_key::book_dedup_key;
+
+/// Books listed per group unless the caller asks otherwise.
+pub const DEFAULT_SAMPLE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityIssue {
+    MissingIsbn,
+    MissingAuthor,
+    NoCover,
+    /// Neither a Dewey number nor an LCC class
+    NoClassification,
+    SuspectedDuplicate,
+    /// Publication year or reading dates that cannot be right
+    InvalidDate,
+    /// Subjects no imported vocabulary authorizes (see
+    /// `services::subject_authority`)
+    UnauthorizedSubject,
+}
+
+/// An endpoint that fixes a group in bulk.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct FixLink {
+    pub label: String,
+    pub method: String,
+    pub href: String,
+}
+
+fn fix(label: &str, method: &str, href: &str) -> FixLink {
+    FixLink {
+        label: label.to_string(),
+        method: method.to_string(),
+        href: href.to_string(),
+    }
+}
+
+impl QualityIssue {
+    fn fixes(self) -> Vec<FixLink> {
+        match self {
+            Self::MissingIsbn => vec![fix(
+                "Books the metadata fill cannot look up",
+                "GET",
+                "/api/metadata-fill/no-isbn",
+            )],
+            Self::MissingAuthor | Self::NoCover | Self::NoClassification => vec![
+                fix(
+                    "Fill missing metadata from their ISBN",
+                    "POST",
+                    "/api/metadata-fill/start",
+                ),
+                fix(
+                    "Books still incomplete after a fill",
+                    "GET",
+                    "/api/metadata-fill/incomplete",
+                ),
+            ],
+            Self::SuspectedDuplicate => vec![
+                fix("Duplicates by ISBN", "GET", "/api/books/duplicates"),
+                fix(
+                    "Delete the extra records",
+                    "POST",
+                    "/api/books/batch/delete",
+                ),
+            ],
+            Self::InvalidDate => vec![fix("Edit the book", "PUT", "/api/books/{id}")],
+            Self::UnauthorizedSubject => vec![
+                fix(
+                    "Authorized headings and variants",
+                    "GET",
+                    "/api/subject-authorities/report",
+                ),
+                fix(
+                    "Check corrected subjects",
+                    "POST",
+                    "/api/subject-authorities/validate",
+                ),
+            ],
+        }
+    }
+}
+
+/// A book with a problem; `detail` says what is wrong when the group name
+/// alone does not.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct QualityBook {
+    pub id: String,
+    pub title: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct QualityGroup {
+    pub issue: QualityIssue,
+    /// Books concerned, all of them
+    pub count: usize,
+    /// The first of them, by title
+    pub books: Vec<QualityBook>,
+    pub fixes: Vec<FixLink>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct QualityReport {
+    /// Books examined (archived ones are not)
+    pub total_books: usize,
+    /// Books with at least one problem
+    pub books_with_issues: usize,
+    pub groups: Vec<QualityGroup>,
+}
+
+fn blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+/// Reading dates are stored as RFC 3339 timestamps or plain dates.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.date_naive())
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+}
+
+/// What is wrong with the dates of `b`, if anything.
+fn date_problem(b: &book::Model, today: NaiveDate) -> Option<String> {
+    if let Some(year) = b.publication_year
+        && (year < 1 || year > today.year() + 1)
+    {
+        return Some(format!("publication year {year}"));
+    }
+    let mut dates = [None, None];
+    for (slot, (name, value)) in dates.iter_mut().zip([
+        ("started_reading_at", &b.started_reading_at),
+        ("finished_reading_at", &b.finished_reading_at),
+    ]) {
+        let Some(value) = value.as_deref().filter(|v| !v.trim().is_empty()) else {
+            continue;
+        };
+        match parse_date(value.trim()) {
+            None => return Some(format!("{name} is not a date: {value}")),
+            Some(date) if date > today => return Some(format!("{name} in the future: {date}")),
+            Some(date) => *slot = Some(date),
+        }
+    }
+    if let [Some(started), Some(finished)] = dates
+        && finished < started
+    {
+        return Some(format!("finished ({finished}) before started ({started})"));
+    }
+    None
+}
+
+/// Build the report, listing at most `sample` books per group.
+pub async fn quality_report(
+    db: &DatabaseConnection,
+    sample: usize,
+) -> Result<QualityReport, DbErr> {
+    let books = book::Entity::find()
+        .filter(book::Column::Archived.eq(false))
+        .order_by_asc(book::Column::Title)
+        .all(db)
+        .await?;
+
+    // First author of each book, alphabetically, for the duplicate key.
+    let names: HashMap<String, String> = author::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (a.id, a.name))
+        .collect();
+    let mut first_author: HashMap<String, String> = HashMap::new();
+    for link in book_authors::Entity::find().all(db).await? {
+        let Some(name) = names.get(&link.author_id) else {
+            continue;
+        };
+        first_author
+            .entry(link.book_id)
+            .and_modify(|current| {
+                if *name < *current {
+                    *current = name.clone();
+                }
+            })
+            .or_insert_with(|| name.clone());
+    }
+
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, b) in books.iter().enumerate() {
+        let key = book_dedup_key(
+            b.isbn.as_deref(),
+            &b.title,
+            first_author.get(&b.id).map(String::as_str),
+            b.publication_year,
+        );
+        by_key.entry(key).or_default().push(i);
+    }
+
+    let issues = [
+        QualityIssue::MissingIsbn,
+        QualityIssue::MissingAuthor,
+        QualityIssue::NoCover,
+        QualityIssue::NoClassification,
+        QualityIssue::SuspectedDuplicate,
+        QualityIssue::InvalidDate,
+        QualityIssue::UnauthorizedSubject,
+    ];
+    let mut found: HashMap<QualityIssue, Vec<QualityBook>> = HashMap::new();
+    let today = Utc::now().date_naive();
+    let flag = |found: &mut HashMap<QualityIssue, Vec<QualityBook>>,
+                issue: QualityIssue,
+                b: &book::Model,
+                detail: Option<String>| {
+        found.entry(issue).or_default().push(QualityBook {
+            id: b.id.clone(),
+            title: b.title.clone(),
+            detail,
+        });
+    };
+
+    for b in &books {
+        if blank(&b.isbn) {
+            flag(&mut found, QualityIssue::MissingIsbn, b, None);
+        }
+        if !first_author.contains_key(&b.id) {
+            flag(&mut found, QualityIssue::MissingAuthor, b, None);
+        }
+        if blank(&b.cover_url) {
+            flag(&mut found, QualityIssue::NoCover, b, None);
+        }
+        if blank(&b.dewey_decimal) && blank(&b.lcc) {
+            flag(&mut found, QualityIssue::NoClassification, b, None);
+        }
+        if let Some(problem) = date_problem(b, today) {
+            flag(&mut found, QualityIssue::InvalidDate, b, Some(problem));
+        }
+    }
+    for group in by_key.values().filter(|g| g.len() > 1) {
+        for &i in group {
+            let others: Vec<&str> = group
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| books[j].id.as_str())
+                .collect();
+            let detail = format!("same as {}", others.join(", "));
+            flag(
+                &mut found,
+                QualityIssue::SuspectedDuplicate,
+                &books[i],
+                Some(detail),
+            );
+        }
+    }
+    if let Some(dups) = found.get_mut(&QualityIssue::SuspectedDuplicate) {
+        dups.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    // One entry per book, naming its unauthorized subjects.
+    let mut unauthorized: Vec<QualityBook> = Vec::new();
+    for heading in subject_authority::unauthorized_headings(db).await? {
+        match unauthorized.last_mut() {
+            Some(last) if last.id == heading.book_id => {
+                if let Some(detail) = last.detail.as_mut() {
+                    detail.push_str("; ");
+                    detail.push_str(&heading.subject);
+                }
+            }
+            _ => unauthorized.push(QualityBook {
+                id: heading.book_id,
+                title: heading.title,
+                detail: Some(heading.subject),
+            }),
+        }
+    }
+    found.insert(QualityIssue::UnauthorizedSubject, unauthorized);
+
+    let mut with_issues: std::collections::HashSet<&str> = std::collections::HashSet::new();
+    for flagged in found.values() {
+        with_issues.extend(flagged.iter().map(|b| b.id.as_str()));
+    }
+    let books_with_issues = with_issues.len();
+
+    let groups = issues
+        .into_iter()
+        .map(|issue| {
+            let mut flagged = found.remove(&issue).unwrap_or_default();
+            let count = flagged.len();
+            flagged.truncate(sample);
+            QualityGroup {
+                issue,
+                count,
+                books: flagged,
+                fixes: issue.fixes(),
+            }
+        })
+        .collect();
+
+    Ok(QualityReport {
+        total_books: books.len(),
+        books_with_issues,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(report: &QualityReport, issue: QualityIssue) -> &QualityGroup {
+        report.groups.iter().find(|g| g.issue == issue).unwrap()
+    }
+
+    #[tokio::test]
+    async fn report_groups_incomplete_and_suspicious_records() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let now = Utc::now().to_rfc3339();
+        let add = |title: &str, isbn: Option<&str>, year: Option<i32>| book::ActiveModel {
+            title: Set(title.to_string()),
+            isbn: Set(isbn.map(str::to_string)),
+            publication_year: Set(year),
+            cover_url: Set(Some("https://covers.example/1.jpg".to_string())),
+            dewey_decimal: Set(Some("843".to_string())),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        };
+
+        let clean = add("Dune", Some("9780441013593"), Some(1965))
+            .insert(&db)
+            .await
+            .unwrap();
+        let writer = author::ActiveModel {
+            name: Set("Frank Herbert".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        book_authors::ActiveModel {
+            book_id: Set(clean.id.clone()),
+            author_id: Set(writer.id),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        // The ISBN-10 form of the same edition.
+        let twin = add("Dune", Some("0-441-01359-7"), Some(1965))
+            .insert(&db)
+            .await
+            .unwrap();
+        let mut sloppy = add("Imported", None, Some(19650));
+        sloppy.cover_url = Set(None);
+        sloppy.dewey_decimal = Set(None);
+        sloppy.insert(&db).await.unwrap();
+
+        let report = quality_report(&db, DEFAULT_SAMPLE).await.unwrap();
+        assert_eq!(report.total_books, 3);
+        assert_eq!(report.books_with_issues, 3);
+        assert_eq!(group(&report, QualityIssue::MissingIsbn).count, 1);
+        assert_eq!(group(&report, QualityIssue::MissingAuthor).count, 2);
+        assert_eq!(group(&report, QualityIssue::NoCover).count, 1);
+        assert_eq!(group(&report, QualityIssue::NoClassification).count, 1);
+        let dates = group(&report, QualityIssue::InvalidDate);
+        assert_eq!(
+            dates.books[0].detail.as_deref(),
+            Some("publication year 19650")
+        );
+        // The ISBN decides, whatever its form and the authors.
+        assert_eq!(group(&report, QualityIssue::SuspectedDuplicate).count, 2);
+        let dups = &group(&report, QualityIssue::SuspectedDuplicate).books;
+        assert!(dups.iter().any(|b| b.id == twin.id));
+        assert_eq!(group(&report, QualityIssue::UnauthorizedSubject).count, 0);
+        assert!(!group(&report, QualityIssue::NoCover).fixes.is_empty());
+
+        let sampled = quality_report(&db, 1).await.unwrap();
+        let missing = group(&sampled, QualityIssue::MissingAuthor);
+        assert_eq!((missing.count, missing.books.len()), (2, 1));
+    }
+}
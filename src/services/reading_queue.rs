@@ -0,0 +1,374 @@
+//! The "up next" reading queue: the books the owner means to read next, in
+//! the order they mean to read them.
+//!
+//! It is kept apart from `shelf_position`, which orders the shelf view: a
+//! book can sit at the end of a shelf and at the head of the queue. Finishing
+//! the book being read takes it off the queue and starts the next one, so the
+//! dashboard always shows what is being read and what follows.
+
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{book, reading_queue};
+
+#[derive(Debug)]
+pub enum ReadingQueueError {
+    Db(DbErr),
+    BookNotFound(String),
+    /// The book is not in the queue.
+    NotQueued(String),
+    Invalid(String),
+}
+
+impl std::fmt::Display for ReadingQueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::BookNotFound(id) => write!(f, "book {id} not found"),
+            Self::NotQueued(id) => write!(f, "book {id} is not in the reading queue"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadingQueueError {}
+
+impl From<DbErr> for ReadingQueueError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct QueuedBook {
+    pub book_id: String,
+    /// 0 for the next book to read
+    pub position: i32,
+    pub title: String,
+    /// Authors, comma-separated
+    pub author: Option<String>,
+    pub cover_url: Option<String>,
+    pub reading_status: String,
+    pub added_at: String,
+}
+
+/// Body of `POST /api/reading-queue`.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct QueueBook {
+    pub book_id: String,
+    /// Where to put it, 0 for next; the end when absent. A queued book is
+    /// moved there.
+    pub position: Option<usize>,
+}
+
+/// Body of `PUT /api/reading-queue`.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct QueueOrder {
+    /// Queued books in their new order; those left out follow, in the
+    /// order they had
+    pub book_ids: Vec<String>,
+}
+
+/// The queue, next book first.
+pub async fn list<C: ConnectionTrait>(db: &C) -> Result<Vec<QueuedBook>, DbErr> {
+    let rows = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT q.book_id, q.position, q.added_at, b.title, b.cover_url, b.reading_status, \
+                    (SELECT GROUP_CONCAT(a.name, ', ') FROM book_authors ba \
+                     JOIN authors a ON a.uuid = ba.author_id \
+                     WHERE ba.book_id = q.book_id) AS author \
+             FROM reading_queue q JOIN books b ON b.uuid = q.book_id \
+             ORDER BY q.position"
+                .to_owned(),
+        ))
+        .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(QueuedBook {
+                book_id: row.try_get("", "book_id")?,
+                position: row.try_get("", "position")?,
+                title: row.try_get("", "title")?,
+                author: row.try_get("", "author")?,
+                cover_url: row.try_get("", "cover_url")?,
+                reading_status: row.try_get("", "reading_status")?,
+                added_at: row.try_get("", "added_at")?,
+            })
+        })
+        .collect()
+}
+
+/// Queued books shown on the dashboard after the ones being read.
+pub const UP_NEXT_SHOWN: usize = 3;
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct CurrentBook {
+    pub book_id: String,
+    pub title: String,
+    /// Authors, comma-separated
+    pub author: Option<String>,
+    pub cover_url: Option<String>,
+    pub started_reading_at: Option<String>,
+}
+
+/// The reading part of the dashboard.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ReadingSummary {
+    /// Books being read, most recently started first
+    pub reading: Vec<CurrentBook>,
+    /// The first queued books not being read yet
+    pub up_next: Vec<QueuedBook>,
+    /// Books in the queue, including any being read
+    pub queued: usize,
+}
+
+/// What is being read and what comes next.
+pub async fn summary(db: &DatabaseConnection) -> Result<ReadingSummary, DbErr> {
+    let rows = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT b.uuid AS book_id, b.title, b.cover_url, b.started_reading_at, \
+                    (SELECT GROUP_CONCAT(a.name, ', ') FROM book_authors ba \
+                     JOIN authors a ON a.uuid = ba.author_id \
+                     WHERE ba.book_id = b.uuid) AS author \
+             FROM books b WHERE b.reading_status = 'reading' AND b.archived = 0 \
+             ORDER BY b.started_reading_at DESC"
+                .to_owned(),
+        ))
+        .await?;
+    let reading = rows
+        .iter()
+        .map(|row| {
+            Ok(CurrentBook {
+                book_id: row.try_get("", "book_id")?,
+                title: row.try_get("", "title")?,
+                author: row.try_get("", "author")?,
+                cover_url: row.try_get("", "cover_url")?,
+                started_reading_at: row.try_get("", "started_reading_at")?,
+            })
+        })
+        .collect::<Result<Vec<_>, DbErr>>()?;
+    let queue = list(db).await?;
+    let queued = queue.len();
+    let up_next = queue
+        .into_iter()
+        .filter(|q| q.reading_status != "reading")
+        .take(UP_NEXT_SHOWN)
+        .collect();
+    Ok(ReadingSummary {
+        reading,
+        up_next,
+        queued,
+    })
+}
+
+async fn queued_ids<C: ConnectionTrait>(db: &C) -> Result<Vec<String>, DbErr> {
+    reading_queue::Entity::find()
+        .select_only()
+        .column(reading_queue::Column::BookId)
+        .order_by_asc(reading_queue::Column::Position)
+        .into_tuple()
+        .all(db)
+        .await
+}
+
+/// Number the queued books 0, 1, 2... in `ids` order.
+async fn renumber<C: ConnectionTrait>(db: &C, ids: &[String]) -> Result<(), DbErr> {
+    for (position, id) in ids.iter().enumerate() {
+        reading_queue::Entity::update_many()
+            .col_expr(
+                reading_queue::Column::Position,
+                Expr::value(position as i32),
+            )
+            .filter(reading_queue::Column::BookId.eq(id.as_str()))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Queue a book at `position` (the end when `None`), or move it there when it
+/// is queued already.
+pub async fn add(
+    db: &DatabaseConnection,
+    book_id: &str,
+    position: Option<usize>,
+) -> Result<Vec<QueuedBook>, ReadingQueueError> {
+    if book::Entity::find_by_id(book_id.to_owned())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Err(ReadingQueueError::BookNotFound(book_id.to_string()));
+    }
+    let txn = db.begin().await?;
+    let mut ids = queued_ids(&txn).await?;
+    match ids.iter().position(|id| id == book_id) {
+        Some(current) => {
+            ids.remove(current);
+        }
+        None => {
+            reading_queue::ActiveModel {
+                book_id: Set(book_id.to_string()),
+                position: Set(ids.len() as i32),
+                added_at: Set(Utc::now().to_rfc3339()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+    }
+    let at = position.unwrap_or(ids.len()).min(ids.len());
+    ids.insert(at, book_id.to_string());
+    renumber(&txn, &ids).await?;
+    txn.commit().await?;
+    Ok(list(db).await?)
+}
+
+/// Take a book off the queue.
+pub async fn remove(db: &DatabaseConnection, book_id: &str) -> Result<(), ReadingQueueError> {
+    let txn = db.begin().await?;
+    let deleted = reading_queue::Entity::delete_by_id(book_id.to_owned())
+        .exec(&txn)
+        .await?;
+    if deleted.rows_affected == 0 {
+        return Err(ReadingQueueError::NotQueued(book_id.to_string()));
+    }
+    renumber(&txn, &queued_ids(&txn).await?).await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Put the queue in the order of `book_ids`.
+pub async fn reorder(
+    db: &DatabaseConnection,
+    book_ids: &[String],
+) -> Result<Vec<QueuedBook>, ReadingQueueError> {
+    let txn = db.begin().await?;
+    let queued = queued_ids(&txn).await?;
+    let mut ids: Vec<String> = Vec::with_capacity(queued.len());
+    for id in book_ids {
+        if !queued.contains(id) {
+            return Err(ReadingQueueError::NotQueued(id.clone()));
+        }
+        if ids.contains(id) {
+            return Err(ReadingQueueError::Invalid(format!(
+                "book {id} listed twice"
+            )));
+        }
+        ids.push(id.clone());
+    }
+    let rest: Vec<String> = queued.into_iter().filter(|id| !ids.contains(id)).collect();
+    ids.extend(rest);
+    renumber(&txn, &ids).await?;
+    txn.commit().await?;
+    Ok(list(db).await?)
+}
+
+/// Follow a book's reading status change. A finished book leaves the queue;
+/// when it was the one being read, the head of the queue is started. Returns
+/// the book started, if any.
+pub async fn on_status_change(
+    db: &DatabaseConnection,
+    book_id: &str,
+    previous: &str,
+    current: &str,
+) -> Result<Option<String>, DbErr> {
+    if current != "read" || previous == "read" {
+        return Ok(None);
+    }
+    reading_queue::Entity::delete_by_id(book_id.to_owned())
+        .exec(db)
+        .await?;
+    let remaining = queued_ids(db).await?;
+    renumber(db, &remaining).await?;
+    if previous != "reading" {
+        return Ok(None);
+    }
+    let Some(next_id) = remaining.first() else {
+        return Ok(None);
+    };
+    let Some(next) = book::Entity::find_by_id(next_id.clone()).one(db).await? else {
+        return Ok(None);
+    };
+    if next.reading_status == "reading" {
+        return Ok(None);
+    }
+    let now = Utc::now().to_rfc3339();
+    let started = next.started_reading_at.is_none();
+    let mut active: book::ActiveModel = next.into();
+    active.reading_status = Set("reading".to_string());
+    if started {
+        active.started_reading_at = Set(Some(now.clone()));
+    }
+    active.updated_at = Set(now);
+    active.update(db).await?;
+    let _ = crate::sync::log_operation(db, "book", next_id, "UPDATE", None).await;
+    Ok(Some(next_id.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn add_book(db: &DatabaseConnection, title: &str, status: &str) -> String {
+        let now = Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            reading_status: Set(status.to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    fn titles(queue: &[QueuedBook]) -> Vec<&str> {
+        queue.iter().map(|q| q.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn queue_orders_books_and_advances_when_one_is_finished() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let dune = add_book(&db, "Dune", "reading").await;
+        let emma = add_book(&db, "Emma", "to_read").await;
+        let ulysses = add_book(&db, "Ulysses", "to_read").await;
+
+        add(&db, &dune, None).await.unwrap();
+        add(&db, &ulysses, None).await.unwrap();
+        let queue = add(&db, &emma, Some(1)).await.unwrap();
+        assert_eq!(titles(&queue), ["Dune", "Emma", "Ulysses"]);
+
+        let queue = reorder(&db, std::slice::from_ref(&ulysses)).await.unwrap();
+        assert_eq!(titles(&queue), ["Ulysses", "Dune", "Emma"]);
+        assert!(matches!(
+            add(&db, "missing", None).await,
+            Err(ReadingQueueError::BookNotFound(_))
+        ));
+
+        // Dune was being read: finishing it starts the head of the queue.
+        let started = on_status_change(&db, &dune, "reading", "read")
+            .await
+            .unwrap();
+        assert_eq!(started.as_deref(), Some(ulysses.as_str()));
+        let queue = list(&db).await.unwrap();
+        assert_eq!(titles(&queue), ["Ulysses", "Emma"]);
+        assert_eq!(queue[0].reading_status, "reading");
+        assert_eq!(queue[1].position, 1);
+
+        let dashboard = summary(&db).await.unwrap();
+        assert_eq!(dashboard.reading[0].title, "Ulysses");
+        assert_eq!(titles(&dashboard.up_next), ["Emma"]);
+        assert_eq!(dashboard.queued, 2);
+
+        remove(&db, &emma).await.unwrap();
+        assert!(matches!(
+            remove(&db, &emma).await,
+            Err(ReadingQueueError::NotQueued(_))
+        ));
+    }
+}
@@ -0,0 +1,433 @@
+//! Reading statistics for one calendar year ("Year in Books").
+//!
+//! A book counts for a year when its `finished_reading_at` falls in it,
+//! whatever its ownership or archive state: a borrowed book read in March
+//! was still read. Pages are only summed over books with a page count, and
+//! `books_with_page_count` says how many those were.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, NaiveDate};
+use sea_orm::*;
+use serde::Serialize;
+
+use crate::models::{author, book, book_authors};
+
+/// How many tags and authors the year's top lists hold.
+const TOP_LIMIT: usize = 5;
+
+/// A name with how many of the year's books carry it.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct NameCount {
+    pub name: String,
+    pub count: u32,
+}
+
+/// The longest book finished in the year.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct LongestBook {
+    pub id: String,
+    pub title: String,
+    pub page_count: i32,
+}
+
+/// How regular the year's reading was.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct YearStreak {
+    /// Weeks (Monday to Sunday) in which at least one book was finished.
+    pub active_weeks: u32,
+    /// Longest run of consecutive such weeks.
+    pub longest_weekly_streak: u32,
+    /// Month (1-12) with the most books finished, the earliest on a tie.
+    pub busiest_month: Option<u32>,
+}
+
+/// A year of reading, as shown on the year-in-review card.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct YearInBooks {
+    pub year: i32,
+    pub books_finished: u32,
+    /// Books finished counted once per work: two editions of one text read
+    /// in the year count as one (see `services::works`).
+    pub works_finished: u32,
+    /// Sum of the page counts known; `None` when no finished book has one.
+    pub pages_read: Option<i64>,
+    pub books_with_page_count: u32,
+    pub top_tags: Vec<NameCount>,
+    pub top_authors: Vec<NameCount>,
+    /// Mean of the ratings given (0-10 scale), to one decimal.
+    pub average_rating: Option<f64>,
+    pub longest_book: Option<LongestBook>,
+    /// Books finished per month, January first.
+    pub by_month: [u32; 12],
+    pub streak: YearStreak,
+}
+
+/// The `YYYY-MM-DD` date at the start of a date or timestamp.
+fn finish_date(at: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(at.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Most frequent names first, then alphabetical; at most `TOP_LIMIT`.
+fn top(counts: HashMap<String, u32>) -> Vec<NameCount> {
+    let mut list: Vec<NameCount> = counts
+        .into_iter()
+        .map(|(name, count)| NameCount { name, count })
+        .collect();
+    list.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    list.truncate(TOP_LIMIT);
+    list
+}
+
+/// Active weeks and the longest run of consecutive ones.
+fn weekly_streak(dates: &[NaiveDate]) -> (u32, u32) {
+    // Weeks numbered from a Monday, so that consecutive weeks differ by one.
+    let monday = NaiveDate::from_ymd_opt(1970, 1, 5).expect("valid date");
+    let weeks: std::collections::BTreeSet<i64> = dates
+        .iter()
+        .map(|d| (*d - monday).num_days().div_euclid(7))
+        .collect();
+
+    let (mut longest, mut run, mut previous) = (0u32, 0u32, None);
+    for w in &weeks {
+        run = if previous == Some(w - 1) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(*w);
+    }
+    (weeks.len() as u32, longest)
+}
+
+/// Reading statistics for `year`.
+pub async fn year_in_books(db: &DatabaseConnection, year: i32) -> Result<YearInBooks, DbErr> {
+    let finished: Vec<(book::Model, NaiveDate)> = book::Entity::find()
+        .filter(book::Column::FinishedReadingAt.starts_with(format!("{year:04}-")))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|b| {
+            let date = b.finished_reading_at.as_deref().and_then(finish_date)?;
+            Some((b, date))
+        })
+        .collect();
+
+    let mut by_month = [0u32; 12];
+    let mut tag_counts: HashMap<String, u32> = HashMap::new();
+    let mut ratings = Vec::new();
+    let mut pages: Option<i64> = None;
+    let mut books_with_page_count = 0;
+    let mut longest_book: Option<LongestBook> = None;
+
+    for (b, date) in &finished {
+        by_month[date.month0() as usize] += 1;
+        if let Some(r) = b.user_rating {
+            ratings.push(r);
+        }
+        if let Some(p) = b.page_count.filter(|p| *p > 0) {
+            books_with_page_count += 1;
+            *pages.get_or_insert(0) += i64::from(p);
+            if longest_book.as_ref().is_none_or(|l| p > l.page_count) {
+                longest_book = Some(LongestBook {
+                    id: b.id.clone(),
+                    title: b.title.clone(),
+                    page_count: p,
+                });
+            }
+        }
+        // A tag listed twice on one book counts once.
+        let tags: HashSet<String> = b
+            .subjects
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<Vec<String>>(s).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        for t in tags {
+            *tag_counts.entry(t).or_insert(0) += 1;
+        }
+    }
+
+    let book_ids: Vec<String> = finished.iter().map(|(b, _)| b.id.clone()).collect();
+    let mut author_counts: HashMap<String, u32> = HashMap::new();
+    if !book_ids.is_empty() {
+        let links = book_authors::Entity::find()
+            .filter(book_authors::Column::BookId.is_in(book_ids.iter().cloned()))
+            .all(db)
+            .await?;
+        let names: HashMap<String, String> = author::Entity::find()
+            .filter(author::Column::Id.is_in(links.iter().map(|l| l.author_id.clone())))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|a| (a.id, a.name))
+            .collect();
+        for link in links {
+            if let Some(name) = names.get(&link.author_id) {
+                *author_counts.entry(name.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let works = crate::services::works::works_of(db, &book_ids).await?;
+    let works_finished = book_ids
+        .iter()
+        .map(|id| works.get(id).unwrap_or(id))
+        .collect::<HashSet<_>>()
+        .len() as u32;
+
+    let average_rating = (!ratings.is_empty()).then(|| {
+        let mean = f64::from(ratings.iter().sum::<i32>()) / ratings.len() as f64;
+        (mean * 10.0).round() / 10.0
+    });
+
+    let dates: Vec<NaiveDate> = finished.iter().map(|(_, d)| *d).collect();
+    let (active_weeks, longest_weekly_streak) = weekly_streak(&dates);
+    let busiest_month = by_month
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| **n > 0)
+        .max_by(|(ia, a), (ib, b)| a.cmp(b).then_with(|| ib.cmp(ia)))
+        .map(|(i, _)| i as u32 + 1);
+
+    Ok(YearInBooks {
+        year,
+        books_finished: finished.len() as u32,
+        works_finished,
+        pages_read: pages,
+        books_with_page_count,
+        top_tags: top(tag_counts),
+        top_authors: top(author_counts),
+        average_rating,
+        longest_book,
+        by_month,
+        streak: YearStreak {
+            active_weeks,
+            longest_weekly_streak,
+            busiest_month,
+        },
+    })
+}
+
+/// Covers at most put on a stats card.
+const CARD_COVERS: u64 = 40;
+
+/// Local cover files of the shared books among `select`, most recently
+/// updated first. External covers are not fetched.
+async fn card_covers(db: &DatabaseConnection, select: Select<book::Entity>) -> Vec<Vec<u8>> {
+    let books = select
+        .filter(crate::models::Book::shared_condition(db).await)
+        .filter(book::Column::CoverUrl.is_not_null())
+        .order_by_desc(book::Column::UpdatedAt)
+        .limit(CARD_COVERS)
+        .all(db)
+        .await
+        .unwrap_or_default();
+
+    let mut covers = Vec::new();
+    for b in books {
+        let Some(path) = b
+            .cover_url
+            .as_deref()
+            .and_then(|url| crate::utils::cover_url::local_cover_read_path(url, &b.id))
+        else {
+            continue;
+        };
+        if let Ok(bytes) = tokio::fs::read(path).await {
+            covers.push(bytes);
+        }
+    }
+    covers
+}
+
+/// Names of the private tags (and those nested under one), which a card
+/// must not show.
+async fn private_tag_names(db: &DatabaseConnection) -> Result<HashSet<String>, DbErr> {
+    Ok(crate::models::tag::restricting_tags(db)
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        .collect())
+}
+
+/// `tags` without the private ones, named alone or as the end of a path.
+fn shareable(tags: Vec<String>, private: &HashSet<String>) -> Vec<String> {
+    tags.into_iter()
+        .filter(|t| !private.contains(t.rsplit(" > ").next().unwrap_or(t)))
+        .collect()
+}
+
+/// The content of a stats card: the year in books for `year`, otherwise the
+/// library as a whole. Only counts, tag names and covers of shared books go
+/// on it.
+pub async fn stats_card(
+    db: &DatabaseConnection,
+    year: Option<i32>,
+) -> Result<crate::utils::stats_card::StatsCard, DbErr> {
+    use crate::utils::stats_card::StatsCard;
+
+    let private = private_tag_names(db).await?;
+
+    if let Some(year) = year {
+        let stats = year_in_books(db, year).await?;
+        let mut figures = vec![("Books read".to_string(), stats.books_finished.to_string())];
+        if let Some(pages) = stats.pages_read {
+            figures.push(("Pages read".to_string(), pages.to_string()));
+        }
+        if let Some(rating) = stats.average_rating {
+            figures.push(("Average rating".to_string(), format!("{rating}/10")));
+        }
+        if let Some(longest) = &stats.longest_book {
+            figures.push((
+                "Pages in the longest book".to_string(),
+                longest.page_count.to_string(),
+            ));
+        }
+        figures.push((
+            "Weeks with a finished book".to_string(),
+            stats.streak.active_weeks.to_string(),
+        ));
+        let tags = stats.top_tags.into_iter().map(|t| t.name).collect();
+
+        return Ok(StatsCard {
+            heading: format!("My {year} in books"),
+            figures,
+            tags: shareable(tags, &private),
+            covers: card_covers(
+                db,
+                book::Entity::find()
+                    .filter(book::Column::FinishedReadingAt.starts_with(format!("{year:04}-"))),
+            )
+            .await,
+        });
+    }
+
+    let shelved = book::Entity::find()
+        .filter(book::Column::Owned.eq(true))
+        .filter(book::Column::Archived.eq(false));
+    let books = shelved.clone().count(db).await?;
+    let read = shelved
+        .clone()
+        .filter(book::Column::ReadingStatus.eq("read"))
+        .count(db)
+        .await?;
+    let authors = author::Entity::find().count(db).await?;
+    let lent = crate::models::loan::Entity::find()
+        .filter(crate::models::loan::Column::Status.ne("returned"))
+        .count(db)
+        .await?;
+
+    let mut tag_counts: HashMap<String, u32> = HashMap::new();
+    for subjects in shelved
+        .clone()
+        .filter(crate::models::Book::shared_condition(db).await)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|b| b.subjects)
+    {
+        let tags: HashSet<String> = serde_json::from_str::<Vec<String>>(&subjects)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        for t in tags {
+            *tag_counts.entry(t).or_insert(0) += 1;
+        }
+    }
+    let tags = top(tag_counts).into_iter().map(|t| t.name).collect();
+
+    Ok(StatsCard {
+        heading: "My library".to_string(),
+        figures: vec![
+            ("Books".to_string(), books.to_string()),
+            ("Read".to_string(), read.to_string()),
+            ("Authors".to_string(), authors.to_string()),
+            ("On loan now".to_string(), lent.to_string()),
+        ],
+        tags: shareable(tags, &private),
+        covers: card_covers(db, shelved).await,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_read(
+        db: &DatabaseConnection,
+        title: &str,
+        finished: &str,
+        pages: Option<i32>,
+        rating: Option<i32>,
+        subjects: &str,
+    ) {
+        let now = chrono::Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            finished_reading_at: Set(Some(finished.to_string())),
+            page_count: Set(pages),
+            user_rating: Set(rating),
+            subjects: Set(Some(subjects.to_string())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn weekly_streak_spans_the_new_year() {
+        let d = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        // Mon 29 Dec 2025 and Thu 1 Jan 2026 share a week.
+        let dates = [
+            d("2025-12-22"),
+            d("2025-12-29"),
+            d("2026-01-01"),
+            d("2026-01-12"),
+        ];
+        assert_eq!(weekly_streak(&dates), (3, 2));
+        assert_eq!(weekly_streak(&[]), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn year_counts_only_books_finished_in_it() {
+        let db = setup().await;
+        insert_read(&db, "Dune", "2025-03-02", Some(600), Some(9), r#"["SF"]"#).await;
+        insert_read(
+            &db,
+            "Hyperion",
+            "2025-03-10T20:00:00+00:00",
+            None,
+            Some(6),
+            r#"["SF", "Classic"]"#,
+        )
+        .await;
+        insert_read(&db, "Emma", "2025-07-01", Some(400), None, r#"["Classic"]"#).await;
+        insert_read(&db, "Ulysses", "2024-12-31", Some(900), Some(2), "[]").await;
+
+        let stats = year_in_books(&db, 2025).await.unwrap();
+        assert_eq!(stats.books_finished, 3);
+        assert_eq!(stats.pages_read, Some(1000));
+        assert_eq!(stats.books_with_page_count, 2);
+        assert_eq!(stats.average_rating, Some(7.5));
+        assert_eq!(stats.longest_book.unwrap().title, "Dune");
+        assert_eq!(stats.top_tags[0].name, "Classic");
+        assert_eq!(stats.top_tags[0].count, 2);
+        assert_eq!(stats.by_month[2], 2);
+        assert_eq!(stats.streak.busiest_month, Some(3));
+        assert_eq!(stats.streak.active_weeks, 3);
+
+        let empty = year_in_books(&db, 2030).await.unwrap();
+        assert_eq!(empty.books_finished, 0);
+        assert_eq!(empty.pages_read, None);
+        assert_eq!(empty.streak.busiest_month, None);
+    }
+}
@@ -163,6 +163,7 @@ pub(crate) async fn poll_once_wait(state: &AppState, source: NudgeSource) -> Res
 }
 
 /// Inner poll logic, called with the relay poll lock already held.
+#[tracing::instrument(skip_all, fields(source = ?source))]
 async fn poll_inner(state: &AppState, source: NudgeSource) -> Result<(), String> {
     let db = state.db();
 
@@ -457,6 +458,10 @@ async fn process_relay_message(
             sender_peer.id,
             peer_library_uuid
         );
+        // A subscribed peer is synced by the next scheduler tick.
+        if let Err(e) = crate::services::peer_subscriptions::mark_due(db, sender_peer.id).await {
+            tracing::warn!("Relay poller: could not mark subscription due: {e}");
+        }
         catalog_events::bus().emit(CatalogChangedEvent {
             peer_library_uuid,
             peer_id: sender_peer.id,
@@ -632,7 +637,7 @@ async fn handle_relay_request_response(
         ),
         "request_status_query" => (
             "request_status_response",
-            crate::api::e2ee::handle_request_status_query(db, clear_message).await,
+            crate::api::e2ee::handle_request_status_query(db, clear_message, sender_peer).await,
         ),
         // ADR-022: leaderboard relay sync - bundle all four leaderboard stats in one round-trip
         "public_stats_request" => (
@@ -724,7 +729,7 @@ pub async fn recreate_mailbox(
     db: &sea_orm::DatabaseConnection,
     relay_url: &str,
 ) -> Result<String, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::services::network_mode::client_builder()
         .timeout(std::time::Duration::from_secs(15))
         .redirect(reqwest::redirect::Policy::none())
         .build()
@@ -1094,6 +1099,19 @@ async fn handle_connection_request(
     let existing = existing_by_uuid.or(existing_by_key).or(existing_by_url);
 
     if let Some(existing_peer) = existing {
+        if crate::api::peer::is_peer_blocked(&existing_peer) {
+            tracing::info!(
+                "Relay poller: ignoring connection_request from blocked peer '{}'",
+                existing_peer.name
+            );
+            return Ok(());
+        }
+
+        // A known library writing from a new address: follow it there.
+        if !peer_url.is_empty() && !peer_url.starts_with("relay://") {
+            crate::services::peer_identity_sync::relink(db, &existing_peer, &peer_url).await;
+        }
+
         // Update existing peer with new keys/credentials
         let mut active: peer::ActiveModel = existing_peer.into();
         active.name = Set(name.to_string());
@@ -36,7 +36,7 @@ pub struct RelayTransport {
 
 impl RelayTransport {
     pub fn new(crypto_service: Option<Arc<CryptoService<SqliteNonceStore>>>) -> Self {
-        let http_client = reqwest::Client::builder()
+        let http_client = crate::services::network_mode::client_builder()
             .timeout(std::time::Duration::from_secs(15))
             .redirect(reqwest::redirect::Policy::none())
             .build()
@@ -0,0 +1,302 @@
+//! Expires stale P2P requests and purges resolved ones.
+//!
+//! Two knobs in `loan_settings`: `request_expiry_days` moves a request still
+//! `pending` after that many days to `expired` (incoming requests also tell
+//! the requester), and `resolved_request_retention_days` deletes requests
+//! that reached a final status that long ago. 0 disables either. The same
+//! sweeps back the bulk endpoints `/api/peers/requests/expire` and
+//! `/api/peers/requests/clear-resolved`.
+
+use crate::infrastructure::AppState;
+use crate::models::{p2p_outgoing_request, p2p_request, peer};
+use sea_orm::*;
+use serde::Serialize;
+
+/// Final statuses of an incoming request.
+pub const RESOLVED_INCOMING_STATUSES: [&str; 4] = ["rejected", "returned", "expired", "cancelled"];
+
+/// Final statuses of an outgoing request (`failed`: never delivered).
+pub const RESOLVED_OUTGOING_STATUSES: [&str; 5] =
+    ["rejected", "returned", "expired", "cancelled", "failed"];
+
+/// Requests touched by a sweep, per side.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RequestCounts {
+    pub incoming: u64,
+    pub outgoing: u64,
+}
+
+fn cutoff(days: i32) -> String {
+    (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339()
+}
+
+/// Expire requests created more than `older_than_days` days ago and still
+/// `pending`. A request on the hold shelf is left to `hold_expiry`.
+pub async fn expire_stale_requests(
+    state: &AppState,
+    older_than_days: i32,
+) -> Result<RequestCounts, DbErr> {
+    let db = state.db();
+    let cutoff = cutoff(older_than_days);
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut counts = RequestCounts::default();
+
+    let stale = p2p_request::Entity::find()
+        .filter(p2p_request::Column::Status.eq("pending"))
+        .filter(p2p_request::Column::CreatedAt.lt(cutoff.clone()))
+        .all(db)
+        .await?;
+    for req in stale {
+        let mut active: p2p_request::ActiveModel = req.clone().into();
+        active.status = Set("expired".to_string());
+        active.updated_at = Set(now.clone());
+        active.update(db).await?;
+        counts.incoming += 1;
+        tracing::info!("request_cleanup: expired incoming request {}", req.id);
+
+        if let Some(peer) = peer::Entity::find_by_id(req.from_peer_id).one(db).await? {
+            crate::api::peer::send_request_status_update(
+                state,
+                &peer,
+                &req,
+                "expired",
+                serde_json::json!({}),
+            )
+            .await;
+        }
+    }
+
+    // Our own requests only change locally: the lender runs its own expiry.
+    let result = p2p_outgoing_request::Entity::update_many()
+        .col_expr(
+            p2p_outgoing_request::Column::Status,
+            sea_orm::prelude::Expr::value("expired"),
+        )
+        .col_expr(
+            p2p_outgoing_request::Column::UpdatedAt,
+            sea_orm::prelude::Expr::value(now),
+        )
+        .filter(p2p_outgoing_request::Column::Status.eq("pending"))
+        .filter(p2p_outgoing_request::Column::CreatedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+    counts.outgoing = result.rows_affected;
+
+    Ok(counts)
+}
+
+/// Delete resolved requests, all of them or only those resolved more than
+/// `older_than_days` days ago.
+pub async fn purge_resolved_requests(
+    db: &DatabaseConnection,
+    older_than_days: Option<i32>,
+) -> Result<RequestCounts, DbErr> {
+    let mut incoming = p2p_request::Entity::delete_many()
+        .filter(p2p_request::Column::Status.is_in(RESOLVED_INCOMING_STATUSES));
+    let mut outgoing = p2p_outgoing_request::Entity::delete_many()
+        .filter(p2p_outgoing_request::Column::Status.is_in(RESOLVED_OUTGOING_STATUSES));
+    if let Some(days) = older_than_days {
+        let cutoff = cutoff(days);
+        incoming = incoming.filter(p2p_request::Column::UpdatedAt.lt(cutoff.clone()));
+        outgoing = outgoing.filter(p2p_outgoing_request::Column::UpdatedAt.lt(cutoff));
+    }
+
+    Ok(RequestCounts {
+        incoming: incoming.exec(db).await?.rows_affected,
+        outgoing: outgoing.exec(db).await?.rows_affected,
+    })
+}
+
+/// Run one sweep with the configured policy. Returns `(expired, purged)`.
+pub async fn run_once(state: &AppState) -> Result<(RequestCounts, RequestCounts), DbErr> {
+    let settings = state
+        .loan_settings_repo
+        .get_settings()
+        .await
+        .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+    let expired = if settings.request_expiry_days > 0 {
+        expire_stale_requests(state, settings.request_expiry_days).await?
+    } else {
+        RequestCounts::default()
+    };
+    let purged = if settings.resolved_request_retention_days > 0 {
+        purge_resolved_requests(state.db(), Some(settings.resolved_request_retention_days)).await?
+    } else {
+        RequestCounts::default()
+    };
+    Ok((expired, purged))
+}
+
+/// Spawn the background task: one sweep at startup, then every six hours.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(6 * 3_600));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = run_once(&state).await {
+                tracing::warn!("request_cleanup: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    async fn insert_peer(db: &DatabaseConnection) -> i32 {
+        let now = chrono::Utc::now().to_rfc3339();
+        peer::ActiveModel {
+            name: Set("Bob".to_string()),
+            url: Set("http://127.0.0.1:9".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert peer")
+        .id
+    }
+
+    async fn insert_incoming(
+        db: &DatabaseConnection,
+        id: &str,
+        peer_id: i32,
+        status: &str,
+        age: i64,
+    ) {
+        let at = (chrono::Utc::now() - chrono::Duration::days(age)).to_rfc3339();
+        p2p_request::ActiveModel {
+            id: Set(id.to_string()),
+            from_peer_id: Set(peer_id),
+            book_isbn: Set("978-1".to_string()),
+            book_title: Set("Dune".to_string()),
+            status: Set(status.to_string()),
+            created_at: Set(at.clone()),
+            updated_at: Set(at),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .expect("insert request");
+    }
+
+    async fn insert_outgoing(
+        db: &DatabaseConnection,
+        id: &str,
+        peer_id: i32,
+        status: &str,
+        age: i64,
+    ) {
+        let at = (chrono::Utc::now() - chrono::Duration::days(age)).to_rfc3339();
+        p2p_outgoing_request::ActiveModel {
+            id: Set(id.to_string()),
+            to_peer_id: Set(peer_id),
+            book_isbn: Set("978-1".to_string()),
+            book_title: Set("Dune".to_string()),
+            status: Set(status.to_string()),
+            lender_request_id: Set(None),
+            book_id: Set(None),
+            created_at: Set(at.clone()),
+            updated_at: Set(at),
+        }
+        .insert(db)
+        .await
+        .expect("insert outgoing request");
+    }
+
+    async fn incoming_status(db: &DatabaseConnection, id: &str) -> Option<String> {
+        p2p_request::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .unwrap()
+            .map(|r| r.status)
+    }
+
+    async fn outgoing_status(db: &DatabaseConnection, id: &str) -> Option<String> {
+        p2p_outgoing_request::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .unwrap()
+            .map(|r| r.status)
+    }
+
+    #[tokio::test]
+    async fn only_old_pending_requests_expire() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let peer_id = insert_peer(&db).await;
+        insert_incoming(&db, "in-old", peer_id, "pending", 40).await;
+        insert_incoming(&db, "in-new", peer_id, "pending", 5).await;
+        insert_incoming(&db, "in-held", peer_id, "ready", 40).await;
+        insert_outgoing(&db, "out-old", peer_id, "pending", 40).await;
+        insert_outgoing(&db, "out-loan", peer_id, "accepted", 40).await;
+        let state = AppState::new(db.clone());
+
+        let counts = expire_stale_requests(&state, 30).await.expect("expire");
+
+        assert_eq!(
+            counts,
+            RequestCounts {
+                incoming: 1,
+                outgoing: 1
+            }
+        );
+        assert_eq!(
+            incoming_status(&db, "in-old").await.as_deref(),
+            Some("expired")
+        );
+        assert_eq!(
+            incoming_status(&db, "in-new").await.as_deref(),
+            Some("pending")
+        );
+        assert_eq!(
+            incoming_status(&db, "in-held").await.as_deref(),
+            Some("ready")
+        );
+        assert_eq!(
+            outgoing_status(&db, "out-old").await.as_deref(),
+            Some("expired")
+        );
+        assert_eq!(
+            outgoing_status(&db, "out-loan").await.as_deref(),
+            Some("accepted")
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_keeps_open_and_recently_resolved_requests() {
+        let db = db::init_db("sqlite::memory:").await.expect("init db");
+        let peer_id = insert_peer(&db).await;
+        insert_incoming(&db, "in-rejected", peer_id, "rejected", 100).await;
+        insert_incoming(&db, "in-recent", peer_id, "returned", 10).await;
+        insert_incoming(&db, "in-loan", peer_id, "accepted", 100).await;
+        insert_outgoing(&db, "out-failed", peer_id, "failed", 100).await;
+        insert_outgoing(&db, "out-returning", peer_id, "return_pending", 100).await;
+
+        let counts = purge_resolved_requests(&db, Some(90)).await.expect("purge");
+        assert_eq!(
+            counts,
+            RequestCounts {
+                incoming: 1,
+                outgoing: 1
+            }
+        );
+        assert_eq!(incoming_status(&db, "in-rejected").await, None);
+        assert!(incoming_status(&db, "in-recent").await.is_some());
+        assert!(incoming_status(&db, "in-loan").await.is_some());
+        assert!(outgoing_status(&db, "out-returning").await.is_some());
+
+        let counts = purge_resolved_requests(&db, None).await.expect("purge all");
+        assert_eq!(
+            counts,
+            RequestCounts {
+                incoming: 1,
+                outgoing: 0
+            }
+        );
+        assert!(incoming_status(&db, "in-loan").await.is_some());
+    }
+}
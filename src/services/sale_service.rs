@@ -1,7 +1,6 @@
 //! Sale Service - Business logic for sales transactions (bookseller profile)
 //! Mirrored from loan_service.rs
 
-use chrono::Local;
 use sea_orm::*;
 use std::collections::HashMap;
 
@@ -128,7 +127,15 @@ pub async fn record_sale(
     db: &DatabaseConnection,
     dto: SaleDto,
 ) -> Result<sale::Model, ServiceError> {
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = crate::utils::time_zone::library_zone(db)
+        .await?
+        .now_local_string();
+
+    // Prices are in the library's currency; refuse what it cannot express
+    let currency = crate::utils::money::library_currency(db).await?;
+    if let Some(reason) = crate::utils::money::check_amount(dto.sale_price, currency) {
+        return Err(ServiceError::InvalidState(format!("sale_price {reason}")));
+    }
 
     // 1. Check if copy exists and is available
     let _copy = Copy::find_by_id(dto.copy_id.clone()) // Prefixed with _ to avoid warning
@@ -166,7 +173,9 @@ pub async fn record_sale(
 
 /// Cancel a sale
 pub async fn cancel_sale(db: &DatabaseConnection, id: i32) -> Result<sale::Model, ServiceError> {
-    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let now = crate::utils::time_zone::library_zone(db)
+        .await?
+        .now_local_string();
 
     // 1. Find Sale
     let sale = Sale::find_by_id(id)
@@ -0,0 +1,442 @@
+//! Saved searches: a query in words (read by `services::nl_query`) kept
+//! with the sources it runs against, this library and the cached catalogues
+//! of peers.
+//!
+//! A background task runs every search on a schedule and records what it
+//! finds in `saved_search_matches`. A match not seen before, such as a peer
+//! adding a wanted title or an import bringing in a book that fits, is
+//! notified in the activity feed (`saved_search_match`). The first run of a
+//! search only takes stock of what already matches.
+//!
+//! Peer catalogues are cached with their titles and authors only, so a
+//! search naming a status, genre, year or rating looks at this library alone.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sea_orm::*;
+use serde::Deserialize;
+
+use crate::domain::notification_repository::{CreateNotification, NotificationEventType};
+use crate::models::{peer, peer_book, saved_search, saved_search_match};
+use crate::services::{book_service, nl_query, notification_service};
+
+pub const SOURCES: &[&str] = &["local", "peers"];
+const DEFAULT_SOURCES: &str = "local,peers";
+
+/// Time between two runs of every search.
+const TICK: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Delay before the first run, so peer catalogue syncs land first.
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// New matches of one run notified one by one; past this, a single summary.
+const MAX_NOTIFIED_PER_RUN: usize = 5;
+
+/// Rows per insert, well under SQLite's bound-variable limit.
+const INSERT_CHUNK: usize = 500;
+
+#[derive(Debug)]
+pub enum SavedSearchError {
+    NotFound,
+    Invalid(String),
+    Db(DbErr),
+}
+
+impl std::fmt::Display for SavedSearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "saved search not found"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+            Self::Db(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SavedSearchError {}
+
+impl From<DbErr> for SavedSearchError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// Body of `POST /api/saved-searches`.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewSavedSearch {
+    /// Defaults to the query
+    pub name: Option<String>,
+    /// The query in words, e.g. "unread sci-fi by Asimov"
+    pub query: String,
+    /// `local`, `peers` or both (default), comma-separated
+    pub sources: Option<String>,
+    /// Notify new matches (default true)
+    pub notify: Option<bool>,
+}
+
+fn normalize_sources(sources: Option<&str>) -> Result<String, SavedSearchError> {
+    let Some(sources) = sources else {
+        return Ok(DEFAULT_SOURCES.to_string());
+    };
+    let mut picked: Vec<&str> = Vec::new();
+    for source in sources.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !SOURCES.contains(&source) {
+            return Err(SavedSearchError::Invalid(format!(
+                "unknown source {source}: use local or peers"
+            )));
+        }
+        if !picked.contains(&source) {
+            picked.push(source);
+        }
+    }
+    if picked.is_empty() {
+        return Err(SavedSearchError::Invalid(
+            "sources must name local, peers or both".to_string(),
+        ));
+    }
+    Ok(picked.join(","))
+}
+
+/// Save a search for `owner` and take stock of what it already matches.
+pub async fn create(
+    db: &DatabaseConnection,
+    owner: &str,
+    input: NewSavedSearch,
+) -> Result<saved_search::Model, SavedSearchError> {
+    let query = input.query.trim().to_string();
+    if nl_query::parse(&query).is_empty() {
+        return Err(SavedSearchError::Invalid(
+            "nothing to search for in the query".to_string(),
+        ));
+    }
+    let sources = normalize_sources(input.sources.as_deref())?;
+    let name = input
+        .name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| query.clone());
+
+    let search = saved_search::ActiveModel {
+        owner: Set(owner.to_string()),
+        name: Set(name),
+        query: Set(query),
+        sources: Set(sources),
+        notify: Set(input.notify.unwrap_or(true)),
+        last_checked_at: Set(None),
+        created_at: Set(Utc::now().to_rfc3339()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    run(db, &search).await?;
+    get(db, owner, search.id)
+        .await?
+        .ok_or(SavedSearchError::NotFound)
+}
+
+/// The searches `owner` saved, by name.
+pub async fn list(db: &DatabaseConnection, owner: &str) -> Result<Vec<saved_search::Model>, DbErr> {
+    saved_search::Entity::find()
+        .filter(saved_search::Column::Owner.eq(owner))
+        .order_by_asc(saved_search::Column::Name)
+        .all(db)
+        .await
+}
+
+pub async fn get(
+    db: &DatabaseConnection,
+    owner: &str,
+    id: i32,
+) -> Result<Option<saved_search::Model>, DbErr> {
+    saved_search::Entity::find_by_id(id)
+        .filter(saved_search::Column::Owner.eq(owner))
+        .one(db)
+        .await
+}
+
+/// Delete a search of `owner` with its matches. `false` when there is none.
+pub async fn delete(db: &DatabaseConnection, owner: &str, id: i32) -> Result<bool, DbErr> {
+    if get(db, owner, id).await?.is_none() {
+        return Ok(false);
+    }
+    let txn = db.begin().await?;
+    saved_search_match::Entity::delete_many()
+        .filter(saved_search_match::Column::SearchId.eq(id))
+        .exec(&txn)
+        .await?;
+    saved_search::Entity::delete_by_id(id).exec(&txn).await?;
+    txn.commit().await?;
+    Ok(true)
+}
+
+/// What a search of `owner` found so far, newest first.
+pub async fn matches(
+    db: &DatabaseConnection,
+    owner: &str,
+    id: i32,
+) -> Result<Vec<saved_search_match::Model>, SavedSearchError> {
+    if get(db, owner, id).await?.is_none() {
+        return Err(SavedSearchError::NotFound);
+    }
+    Ok(saved_search_match::Entity::find()
+        .filter(saved_search_match::Column::SearchId.eq(id))
+        .order_by_desc(saved_search_match::Column::FirstSeenAt)
+        .all(db)
+        .await?)
+}
+
+/// Every book the search matches now, keyed as in `saved_search_matches`.
+async fn find_matches(
+    db: &DatabaseConnection,
+    search: &saved_search::Model,
+) -> Result<Vec<saved_search_match::Model>, DbErr> {
+    let parsed = nl_query::parse(&search.query);
+    let sources: Vec<&str> = search.sources.split(',').collect();
+    let now = Utc::now().to_rfc3339();
+    let found = |match_key: String,
+                 title: String,
+                 author: Option<String>,
+                 source: Option<String>| saved_search_match::Model {
+        search_id: search.id,
+        match_key,
+        title,
+        author,
+        source,
+        first_seen_at: now.clone(),
+    };
+    let mut all = Vec::new();
+
+    if sources.contains(&"local") {
+        let books = book_service::list_books(db, parsed.clone().into_filter())
+            .await
+            .map_err(|e| DbErr::Custom(format!("{e:?}")))?;
+        for book in books {
+            if let Some(id) = book.id {
+                all.push(found(format!("book:{id}"), book.title, book.author, None));
+            }
+        }
+    }
+
+    let title_or_author_only = parsed.status.is_none()
+        && parsed.tag.is_none()
+        && parsed.year_min.is_none()
+        && parsed.year_max.is_none()
+        && parsed.min_rating.is_none()
+        && (parsed.title.is_some() || parsed.author.is_some());
+    if sources.contains(&"peers") && title_or_author_only {
+        let mut query = peer_book::Entity::find().filter(peer_book::Column::Owned.eq(true));
+        if let Some(title) = &parsed.title {
+            query = query.filter(peer_book::Column::Title.contains(title));
+        }
+        if let Some(author) = &parsed.author {
+            query = query.filter(peer_book::Column::Author.contains(author));
+        }
+        let books = query.all(db).await?;
+        if !books.is_empty() {
+            let names: HashMap<i32, String> = peer::Entity::find()
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|p| (p.id, p.name))
+                .collect();
+            for book in books {
+                all.push(found(
+                    format!("peer:{}:{}", book.peer_id, book.remote_book_id),
+                    book.title,
+                    book.author,
+                    names.get(&book.peer_id).cloned(),
+                ));
+            }
+        }
+    }
+    Ok(all)
+}
+
+/// Run `search` once: record the matches not seen before and, unless this is
+/// its first run, notify them. Returns the new matches.
+pub async fn run(
+    db: &DatabaseConnection,
+    search: &saved_search::Model,
+) -> Result<Vec<saved_search_match::Model>, DbErr> {
+    let mut seen: HashSet<String> = saved_search_match::Entity::find()
+        .select_only()
+        .column(saved_search_match::Column::MatchKey)
+        .filter(saved_search_match::Column::SearchId.eq(search.id))
+        .into_tuple::<String>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+    let fresh: Vec<saved_search_match::Model> = find_matches(db, search)
+        .await?
+        .into_iter()
+        .filter(|m| seen.insert(m.match_key.clone()))
+        .collect();
+
+    for chunk in fresh.chunks(INSERT_CHUNK) {
+        saved_search_match::Entity::insert_many(
+            chunk
+                .iter()
+                .cloned()
+                .map(IntoActiveModel::into_active_model),
+        )
+        .exec(db)
+        .await?;
+    }
+    let mut checked: saved_search::ActiveModel = search.clone().into();
+    checked.last_checked_at = Set(Some(Utc::now().to_rfc3339()));
+    checked.update(db).await?;
+
+    if search.notify && search.last_checked_at.is_some() {
+        notify(db, search, &fresh).await;
+    }
+    Ok(fresh)
+}
+
+async fn notify(
+    db: &DatabaseConnection,
+    search: &saved_search::Model,
+    fresh: &[saved_search_match::Model],
+) {
+    if fresh.len() > MAX_NOTIFIED_PER_RUN {
+        notification_service::emit(
+            db,
+            CreateNotification {
+                event_type: NotificationEventType::SavedSearchMatch,
+                title: search.name.clone(),
+                body: Some(format!("{} new matches", fresh.len())),
+                ref_type: Some("saved_search".to_string()),
+                ref_id: Some(search.id.to_string()),
+            },
+        )
+        .await;
+        return;
+    }
+    for m in fresh {
+        notification_service::emit_unique(
+            db,
+            CreateNotification {
+                event_type: NotificationEventType::SavedSearchMatch,
+                title: m.title.clone(),
+                body: Some(match &m.source {
+                    Some(peer) => format!("{} ({peer})", search.name),
+                    None => search.name.clone(),
+                }),
+                ref_type: Some("saved_search".to_string()),
+                ref_id: Some(format!("{}:{}", search.id, m.match_key)),
+            },
+        )
+        .await;
+    }
+}
+
+/// Run every saved search once. Returns how many new matches were found.
+pub async fn run_all(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let mut total = 0;
+    for search in saved_search::Entity::find().all(db).await? {
+        match run(db, &search).await {
+            Ok(fresh) => total += fresh.len(),
+            Err(e) => tracing::warn!("saved searches: search {}: {e}", search.id),
+        }
+    }
+    Ok(total)
+}
+
+/// Spawn the background task: every saved search, every quarter of an hour.
+pub fn spawn(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + STARTUP_DELAY;
+        let mut ticker = tokio::time::interval_at(start, TICK);
+        loop {
+            ticker.tick().await;
+            match run_all(&db).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("saved searches: {n} new match(es)"),
+                Err(e) => tracing::warn!("saved searches: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{book, notification};
+
+    async fn insert_book(db: &DatabaseConnection, title: &str) {
+        let now = Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_matches_after_the_first_run_are_notified() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        insert_book(&db, "Dune").await;
+        let now = Utc::now().to_rfc3339();
+        let peer = peer::ActiveModel {
+            name: Set("Marie".to_string()),
+            url: Set("http://192.168.1.20:8000".to_string()),
+            connection_status: Set("accepted".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let search = create(
+            &db,
+            "",
+            NewSavedSearch {
+                name: None,
+                query: "dune".to_string(),
+                sources: Some("peers, local".to_string()),
+                notify: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(search.sources, "peers,local");
+        assert!(search.last_checked_at.is_some());
+        assert_eq!(matches(&db, "", search.id).await.unwrap().len(), 1);
+
+        peer_book::ActiveModel {
+            peer_id: Set(peer.id),
+            remote_book_id: Set("b-42".to_string()),
+            title: Set("Dune Messiah".to_string()),
+            synced_at: Set(now),
+            owned: Set(true),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let fresh = run(&db, &search).await.unwrap();
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].match_key, format!("peer:{}:b-42", peer.id));
+        assert_eq!(fresh[0].source.as_deref(), Some("Marie"));
+        assert!(run(&db, &search).await.unwrap().is_empty());
+
+        let notified = notification::Entity::find()
+            .filter(notification::Column::EventType.eq("saved_search_match"))
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(notified.len(), 1);
+        assert_eq!(notified[0].title, "Dune Messiah");
+        // Another user's searches are out of reach.
+        assert!(matches!(
+            matches(&db, "alice", search.id).await,
+            Err(SavedSearchError::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+//! Scan corrections kept as training data for title recognition.
+//!
+//! When a scan proposes the wrong title (a spine photo read sideways, a
+//! publisher's name taken for the title), the title the user keeps instead,
+//! with the OCR text it was read from, is what a better matching model
+//! learns from. With `scan_training_data` on in the configuration, the app
+//! records each such correction (`record`) and the owner can download them
+//! all as JSON lines (`export_jsonl`) to send to the project.
+//!
+//! Nothing leaves the library on its own, and what is kept is anonymized
+//! first (`anonymize`): e-mail and web addresses are replaced, and the
+//! digits of long numbers (phone, customer or account numbers) masked;
+//! ISBNs are kept. No user, library or image is stored with it, and the
+//! export carries no dates.
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::infrastructure::config::Config;
+use crate::modules::scanner::candidates;
+use crate::utils::sanitize;
+
+/// Digits in a line from which it is taken to hold a personal number.
+const MASKED_DIGITS: usize = 8;
+
+/// Whether corrections are collected (`scan_training_data`).
+pub fn enabled() -> bool {
+    Config::runtime().scan_training_data
+}
+
+/// A title corrected after a scan.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct ScanCorrection {
+    /// The OCR text the scan returned
+    pub ocr_text: String,
+    /// The title the user kept
+    pub title: String,
+    pub author: Option<String>,
+}
+
+/// One line of the export.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrainingExample {
+    pub ocr_text: String,
+    /// The best title candidate the scan proposed
+    pub predicted_title: Option<String>,
+    pub title: String,
+    pub author: Option<String>,
+}
+
+fn anonymize_word(word: &str) -> &str {
+    let lower = word.to_ascii_lowercase();
+    if word.contains('@') {
+        "[email]"
+    } else if lower.starts_with("http://")
+        || lower.starts_with("https://")
+        || lower.starts_with("www.")
+    {
+        "[url]"
+    } else {
+        word
+    }
+}
+
+/// `line` without addresses, and with its digits masked when it holds a
+/// long number that is not an ISBN.
+fn anonymize_line(line: &str) -> String {
+    let line = line
+        .split(' ')
+        .map(anonymize_word)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let digits = line.chars().filter(char::is_ascii_digit).count();
+    if digits >= MASKED_DIGITS && candidates::isbns(&line).is_empty() {
+        line.chars()
+            .map(|c| if c.is_ascii_digit() { '#' } else { c })
+            .collect()
+    } else {
+        line
+    }
+}
+
+/// OCR text stripped of what could identify a person.
+pub fn anonymize(text: &str) -> String {
+    text.lines()
+        .map(anonymize_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Keep a correction. Returns its id.
+pub async fn record(db: &DatabaseConnection, correction: ScanCorrection) -> Result<i64, DbErr> {
+    let ocr_text = anonymize(&sanitize::text(
+        &correction.ocr_text,
+        sanitize::MAX_TEXT_CHARS,
+    ));
+    let predicted_title = candidates::extract(&ocr_text)
+        .titles
+        .into_iter()
+        .next()
+        .map(|c| c.text);
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "INSERT INTO scan_corrections \
+             (ocr_text, predicted_title, corrected_title, corrected_author, created_at) \
+             VALUES (?, ?, ?, ?, ?)",
+            [
+                ocr_text.into(),
+                predicted_title.into(),
+                sanitize::line(&correction.title, sanitize::MAX_TITLE_CHARS).into(),
+                sanitize::opt_line(correction.author, sanitize::MAX_NAME_CHARS).into(),
+                chrono::Utc::now().to_rfc3339().into(),
+            ],
+        ))
+        .await?;
+    Ok(result.last_insert_id() as i64)
+}
+
+/// Every correction kept, oldest first.
+pub async fn examples(db: &DatabaseConnection) -> Result<Vec<TrainingExample>, DbErr> {
+    let rows = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT ocr_text, predicted_title, corrected_title, corrected_author \
+             FROM scan_corrections ORDER BY id",
+        ))
+        .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(TrainingExample {
+                ocr_text: row.try_get("", "ocr_text")?,
+                predicted_title: row.try_get("", "predicted_title")?,
+                title: row.try_get("", "corrected_title")?,
+                author: row.try_get("", "corrected_author")?,
+            })
+        })
+        .collect()
+}
+
+/// The corrections as JSON lines, one example per line.
+pub async fn export_jsonl(db: &DatabaseConnection) -> Result<String, DbErr> {
+    let mut out = String::new();
+    for example in examples(db).await? {
+        if let Ok(line) = serde_json::to_string(&example) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn personal_details_are_removed_and_isbns_kept() {
+        let text = "Mémoires d'Hadrien\n\
+                    Contact : jeanne.martin@example.org, https://example.org/moi\n\
+                    Tél. 06 12 34 56 78\n\
+                    ISBN 978-2-07-036822-8\n\
+                    Dépôt légal : 1974";
+        assert_eq!(
+            anonymize(text),
+            "Mémoires d'Hadrien\n\
+             Contact : [email] [url]\n\
+             Tél. ## ## ## ## ##\n\
+             ISBN 978-2-07-036822-8\n\
+             Dépôt légal : 1974"
+        );
+    }
+
+    #[tokio::test]
+    async fn corrections_export_as_json_lines() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        record(
+            &db,
+            ScanCorrection {
+                ocr_text: "GALLIMARD\nMémoires d'Hadrien\nappel 0612345678".to_string(),
+                title: "Mémoires d'Hadrien".to_string(),
+                author: Some("Marguerite Yourcenar".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let jsonl = export_jsonl(&db).await.unwrap();
+        let lines: Vec<&str> = jsonl.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let example: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(
+            example["ocr_text"],
+            "GALLIMARD\nMémoires d'Hadrien\nappel ##########"
+        );
+        assert_eq!(example["predicted_title"], "GALLIMARD");
+        assert_eq!(example["title"], "Mémoires d'Hadrien");
+    }
+}
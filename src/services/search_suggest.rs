@@ -0,0 +1,354 @@
+//! In-memory prefix index behind `GET /api/search/suggest`.
+//!
+//! Autocomplete asks on every keystroke, so it cannot afford a `LIKE` scan of
+//! the catalogue. Titles, author names and tag names are folded (no case, no
+//! accents, punctuation as spaces) and indexed from every word start, so
+//! "lord" finds "The Lord of the Rings"; a lookup is a binary search into one
+//! sorted vector.
+//!
+//! The index remembers the catalogue it was built from: the last
+//! `operation_log` id and the book, author and tag counts, read in one cheap
+//! query per lookup. When they moved the stale index still answers and a
+//! rebuild runs in the background, so a lookup never waits on one except the
+//! very first.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use sea_orm::*;
+use serde::Serialize;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 50;
+
+/// Index entries scanned per lookup: a one-letter prefix matches thousands,
+/// and ranking them all would cost more than it brings.
+const MAX_SCANNED: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    Title,
+    Author,
+    Tag,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub text: String,
+    /// Books behind the suggestion: 1 for a title
+    pub books: u32,
+}
+
+/// What the catalogue looked like when the index was built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Signature {
+    last_operation: i64,
+    books: i64,
+    authors: i64,
+    tags: i64,
+}
+
+struct Entry {
+    key: String,
+    term: usize,
+    /// `key` is the whole text, not from a later word
+    leading: bool,
+}
+
+struct SuggestIndex {
+    signature: Signature,
+    terms: Vec<Suggestion>,
+    /// Sorted by `key`
+    entries: Vec<Entry>,
+}
+
+static INDEX: RwLock<Option<Arc<SuggestIndex>>> = RwLock::new(None);
+static REBUILDING: AtomicBool = AtomicBool::new(false);
+
+/// `text` folded for matching: lowercase, no accents, words separated by one
+/// space.
+pub fn fold(text: &str) -> String {
+    let folded: String = text
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl SuggestIndex {
+    fn new(signature: Signature, terms: Vec<Suggestion>) -> Self {
+        let mut entries = Vec::new();
+        for (term, suggestion) in terms.iter().enumerate() {
+            let key = fold(&suggestion.text);
+            let starts = std::iter::once(0).chain(key.match_indices(' ').map(|(i, _)| i + 1));
+            for start in starts {
+                entries.push(Entry {
+                    key: key[start..].to_string(),
+                    term,
+                    leading: start == 0,
+                });
+            }
+        }
+        entries.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        Self {
+            signature,
+            terms,
+            entries,
+        }
+    }
+
+    fn lookup(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        let start = self.entries.partition_point(|e| e.key.as_str() < prefix);
+        let mut matched: HashMap<usize, bool> = HashMap::new();
+        for entry in self.entries[start..]
+            .iter()
+            .take_while(|e| e.key.starts_with(prefix))
+            .take(MAX_SCANNED)
+        {
+            *matched.entry(entry.term).or_default() |= entry.leading;
+        }
+        let mut found: Vec<(bool, &Suggestion)> = matched
+            .into_iter()
+            .map(|(term, leading)| (leading, &self.terms[term]))
+            .collect();
+        // Whole-text matches first, then the most used, then the shortest.
+        found.sort_by(|(a_leading, a), (b_leading, b)| {
+            b_leading
+                .cmp(a_leading)
+                .then(b.books.cmp(&a.books))
+                .then(a.text.len().cmp(&b.text.len()))
+                .then(a.text.cmp(&b.text))
+        });
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(_, s)| s.clone())
+            .collect()
+    }
+}
+
+async fn signature(db: &DatabaseConnection) -> Result<Signature, DbErr> {
+    let row = db
+        .query_one(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT (SELECT COALESCE(MAX(id), 0) FROM operation_log) AS last_operation, \
+                    (SELECT COUNT(*) FROM books) AS books, \
+                    (SELECT COUNT(*) FROM authors) AS authors, \
+                    (SELECT COUNT(*) FROM tags) AS tags"
+                .to_owned(),
+        ))
+        .await?
+        .ok_or_else(|| DbErr::Custom("suggest signature returned no row".into()))?;
+    Ok(Signature {
+        last_operation: row.try_get("", "last_operation")?,
+        books: row.try_get("", "books")?,
+        authors: row.try_get("", "authors")?,
+        tags: row.try_get("", "tags")?,
+    })
+}
+
+async fn terms(
+    db: &DatabaseConnection,
+    kind: SuggestionKind,
+    sql: &str,
+) -> Result<Vec<Suggestion>, DbErr> {
+    db.query_all(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_owned(),
+    ))
+    .await?
+    .iter()
+    .map(|row| {
+        Ok(Suggestion {
+            kind,
+            text: row.try_get("", "text")?,
+            books: row.try_get::<i64>("", "books")? as u32,
+        })
+    })
+    .collect()
+}
+
+/// Build the index from the database and make it the current one.
+pub async fn rebuild(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let index = build(db).await?;
+    if let Ok(mut current) = INDEX.write() {
+        *current = Some(Arc::new(index));
+    }
+    Ok(())
+}
+
+async fn build(db: &DatabaseConnection) -> Result<SuggestIndex, DbErr> {
+    let signature = signature(db).await?;
+    let mut all = terms(
+        db,
+        SuggestionKind::Title,
+        "SELECT title AS text, COUNT(*) AS books FROM books \
+         WHERE archived = 0 AND TRIM(title) <> '' GROUP BY title",
+    )
+    .await?;
+    all.extend(
+        terms(
+            db,
+            SuggestionKind::Author,
+            "SELECT a.name AS text, COUNT(ba.book_id) AS books FROM authors a \
+             LEFT JOIN book_authors ba ON ba.author_id = a.uuid \
+             WHERE TRIM(a.name) <> '' GROUP BY a.uuid",
+        )
+        .await?,
+    );
+    all.extend(
+        terms(
+            db,
+            SuggestionKind::Tag,
+            "SELECT t.name AS text, COUNT(bt.book_id) AS books FROM tags t \
+             LEFT JOIN book_tags bt ON bt.tag_id = t.uuid \
+             WHERE TRIM(t.name) <> '' GROUP BY t.uuid",
+        )
+        .await?,
+    );
+    Ok(SuggestIndex::new(signature, all))
+}
+
+fn current() -> Option<Arc<SuggestIndex>> {
+    INDEX.read().ok().and_then(|index| index.clone())
+}
+
+/// Suggestions for what the user typed so far.
+pub async fn suggest(
+    db: &DatabaseConnection,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<Suggestion>, DbErr> {
+    let prefix = fold(query);
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    let signature = signature(db).await?;
+    let index = match current() {
+        Some(index) => {
+            if index.signature != signature && !REBUILDING.swap(true, Ordering::SeqCst) {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = rebuild(&db).await {
+                        tracing::warn!("search_suggest: {e}");
+                    }
+                    REBUILDING.store(false, Ordering::SeqCst);
+                });
+            }
+            index
+        }
+        None => {
+            rebuild(db).await?;
+            current().ok_or_else(|| DbErr::Custom("suggest index unavailable".into()))?
+        }
+    };
+    Ok(index.lookup(&prefix, limit.clamp(1, MAX_LIMIT)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{author, book, book_authors, book_tags, tag};
+
+    async fn add_book(db: &DatabaseConnection, title: &str) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    fn texts(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn folding_drops_case_accents_and_punctuation() {
+        assert_eq!(fold("  Les Misérables, tome I "), "les miserables tome i");
+        assert_eq!(fold("L'Étranger"), "l etranger");
+    }
+
+    #[tokio::test]
+    async fn suggests_from_any_word_start() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        add_book(&db, "The Lord of the Rings").await;
+        add_book(&db, "Lorca: poèmes").await;
+        add_book(&db, "L'Étranger").await;
+
+        let found = suggest(&db, "lor", 10).await.unwrap();
+        // The whole title starting with the prefix comes first.
+        assert_eq!(texts(&found), ["Lorca: poèmes", "The Lord of the Rings"]);
+        assert_eq!(found[0].kind, SuggestionKind::Title);
+        assert_eq!(
+            texts(&suggest(&db, "etra", 10).await.unwrap()),
+            ["L'Étranger"]
+        );
+        assert!(suggest(&db, "  ", 10).await.unwrap().is_empty());
+
+        add_book(&db, "Lorem ipsum").await;
+        rebuild(&db).await.unwrap();
+        assert_eq!(suggest(&db, "lore", 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn authors_and_tags_count_their_books() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+        let book_id = add_book(&db, "Dune").await;
+        let now = chrono::Utc::now().to_rfc3339();
+        let author = author::ActiveModel {
+            name: Set("Frank Herbert".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        book_authors::ActiveModel {
+            book_id: Set(book_id.clone()),
+            author_id: Set(author.id),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let tag = tag::ActiveModel {
+            name: Set("Science-fiction".to_string()),
+            path: Set(String::new()),
+            private: Set(false),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        book_tags::ActiveModel {
+            book_id: Set(book_id),
+            tag_id: Set(tag.id),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        // Built directly: the shared index may belong to another test's database.
+        let index = build(&db).await.unwrap();
+        let author = index.lookup("frank", 10);
+        assert_eq!(author[0].kind, SuggestionKind::Author);
+        assert_eq!(author[0].books, 1);
+        let tag = index.lookup("science", 10);
+        assert_eq!(tag[0].kind, SuggestionKind::Tag);
+        assert_eq!(tag[0].books, 1);
+    }
+}
@@ -0,0 +1,290 @@
+//! Speech-to-text for voice search, through the backend the configuration
+//! names (`speech_backend`):
+//!
+//! - `whisper`: the whisper.cpp command-line tool (`speech_command`, default
+//!   `whisper-cli`) run on the clip with the ggml model at `speech_model`.
+//!   Nothing leaves the machine.
+//! - `api`: an OpenAI-compatible `/audio/transcriptions` endpoint under
+//!   `speech_api_url`, with the key in the `SPEECH_API_KEY` environment
+//!   variable and the model in `speech_model` (default `whisper-1`).
+
+use std::time::Duration;
+
+use crate::infrastructure::config::Config;
+
+/// Longest clip accepted: a spoken title or author is a few seconds.
+pub const MAX_CLIP_BYTES: usize = 5 * 1024 * 1024;
+
+/// How long a transcription may take before the search gives up.
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum SpeechError {
+    /// No backend configured, or one missing its model or URL.
+    NotConfigured(String),
+    TooLarge,
+    Empty,
+    /// The backend ran but failed, or heard nothing.
+    Backend(String),
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SpeechError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotConfigured(msg) => write!(f, "voice search is not configured: {msg}"),
+            Self::TooLarge => write!(f, "audio clip exceeds {MAX_CLIP_BYTES} bytes"),
+            Self::Empty => write!(f, "audio clip is empty"),
+            Self::Backend(msg) => write!(f, "transcription failed: {msg}"),
+            Self::Io(e) => write!(f, "transcription failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SpeechError {}
+
+impl From<std::io::Error> for SpeechError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpeechBackend {
+    WhisperCpp {
+        command: String,
+        model: String,
+    },
+    Api {
+        url: String,
+        api_key: Option<String>,
+        model: String,
+    },
+}
+
+impl SpeechBackend {
+    pub fn from_config(config: &Config) -> Result<Self, SpeechError> {
+        match config.speech_backend.as_deref() {
+            Some("whisper") => Ok(Self::WhisperCpp {
+                command: config
+                    .speech_command
+                    .clone()
+                    .unwrap_or_else(|| "whisper-cli".to_string()),
+                model: config.speech_model.clone().ok_or_else(|| {
+                    SpeechError::NotConfigured("speech_model must name a ggml model".to_string())
+                })?,
+            }),
+            Some("api") => Ok(Self::Api {
+                url: config.speech_api_url.clone().ok_or_else(|| {
+                    SpeechError::NotConfigured("speech_api_url is not set".to_string())
+                })?,
+                api_key: std::env::var("SPEECH_API_KEY").ok(),
+                model: config
+                    .speech_model
+                    .clone()
+                    .unwrap_or_else(|| "whisper-1".to_string()),
+            }),
+            _ => Err(SpeechError::NotConfigured(
+                "set speech_backend to whisper or api".to_string(),
+            )),
+        }
+    }
+}
+
+/// A file extension for a clip, from its name or content type: both
+/// backends go by it to pick a decoder.
+pub fn clip_extension(file_name: Option<&str>, content_type: Option<&str>) -> &'static str {
+    const KNOWN: &[&str] = &["wav", "mp3", "ogg", "flac", "m4a", "webm"];
+    let from_name = file_name
+        .and_then(|n| n.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    if let Some(ext) = from_name
+        && let Some(known) = KNOWN.iter().find(|k| **k == ext)
+    {
+        return known;
+    }
+    match content_type.unwrap_or_default() {
+        "audio/mpeg" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "audio/mp4" | "audio/x-m4a" | "audio/aac" => "m4a",
+        "audio/webm" => "webm",
+        _ => "wav",
+    }
+}
+
+/// Transcribe `audio` (`extension` names its format), in `language` (ISO
+/// 639-1) when given, else the backend guesses.
+pub async fn transcribe(
+    backend: &SpeechBackend,
+    audio: Vec<u8>,
+    extension: &str,
+    language: Option<&str>,
+) -> Result<String, SpeechError> {
+    if audio.is_empty() {
+        return Err(SpeechError::Empty);
+    }
+    if audio.len() > MAX_CLIP_BYTES {
+        return Err(SpeechError::TooLarge);
+    }
+    let text = match backend {
+        SpeechBackend::WhisperCpp { command, model } => {
+            whisper_cpp(command, model, audio, extension, language).await?
+        }
+        SpeechBackend::Api {
+            url,
+            api_key,
+            model,
+        } => transcription_api(url, api_key.as_deref(), model, audio, extension, language).await?,
+    };
+    let text = clean_transcript(&text);
+    if text.is_empty() {
+        return Err(SpeechError::Backend("no speech recognized".to_string()));
+    }
+    Ok(text)
+}
+
+/// One line of plain words: whisper adds `[BLANK_AUDIO]`-style markers, line
+/// breaks and a closing full stop nobody searches for.
+fn clean_transcript(raw: &str) -> String {
+    raw.split_whitespace()
+        .filter(|word| !word.starts_with(['[', '(']))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['.', '!', '?'])
+        .to_string()
+}
+
+async fn whisper_cpp(
+    command: &str,
+    model: &str,
+    audio: Vec<u8>,
+    extension: &str,
+    language: Option<&str>,
+) -> Result<String, SpeechError> {
+    let clip = std::env::temp_dir().join(format!(
+        "voice_{}.{extension}",
+        crate::utils::uuid_gen::new_uuid_v7()
+    ));
+    tokio::fs::write(&clip, &audio).await?;
+
+    let mut cmd = tokio::process::Command::new(command);
+    cmd.arg("-m")
+        .arg(model)
+        .arg("-f")
+        .arg(&clip)
+        // Plain text on stdout: no timestamps, no progress.
+        .arg("-nt")
+        .arg("-np")
+        .arg("-l")
+        .arg(language.unwrap_or("auto"))
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(TRANSCRIBE_TIMEOUT, cmd.output()).await;
+    let _ = tokio::fs::remove_file(&clip).await;
+
+    let output = output
+        .map_err(|_| SpeechError::Backend("whisper.cpp timed out".to_string()))?
+        .map_err(|e| SpeechError::Backend(format!("cannot run {command}: {e}")))?;
+    if !output.status.success() {
+        return Err(SpeechError::Backend(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(serde::Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+async fn transcription_api(
+    url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    audio: Vec<u8>,
+    extension: &str,
+    language: Option<&str>,
+) -> Result<String, SpeechError> {
+    let part = reqwest::multipart::Part::bytes(audio).file_name(format!("clip.{extension}"));
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", part)
+        .text("model", model.to_string());
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+
+    let client = crate::services::network_mode::client_builder()
+        .timeout(TRANSCRIBE_TIMEOUT)
+        .build()
+        .map_err(|e| SpeechError::Backend(e.to_string()))?;
+    let mut request = client
+        .post(format!(
+            "{}/audio/transcriptions",
+            url.trim_end_matches('/')
+        ))
+        .multipart(form);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| SpeechError::Backend(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(SpeechError::Backend(format!("{status}: {body}")));
+    }
+    response
+        .json::<TranscriptionResponse>()
+        .await
+        .map(|r| r.text)
+        .map_err(|e| SpeechError::Backend(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn transcripts_are_cleaned_for_search() {
+        assert_eq!(
+            clean_transcript(" [BLANK_AUDIO]\n Le Petit Prince.\n"),
+            "Le Petit Prince"
+        );
+        assert_eq!(clip_extension(Some("memo.M4A"), None), "m4a");
+        assert_eq!(clip_extension(Some("blob"), Some("audio/ogg")), "ogg");
+    }
+
+    #[tokio::test]
+    async fn api_backend_posts_the_clip() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/audio/transcriptions"))
+            .and(header("authorization", "Bearer k"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "text": "Victor Hugo." })),
+            )
+            .mount(&server)
+            .await;
+        let backend = SpeechBackend::Api {
+            url: format!("{}/v1/", server.uri()),
+            api_key: Some("k".to_string()),
+            model: "whisper-1".to_string(),
+        };
+
+        let text = transcribe(&backend, b"RIFF....".to_vec(), "wav", Some("fr"))
+            .await
+            .unwrap();
+
+        assert_eq!(text, "Victor Hugo");
+        assert!(matches!(
+            transcribe(&backend, Vec::new(), "wav", None).await,
+            Err(SpeechError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,567 @@
+//! Subject heading authority control (pro cataloguing).
+//!
+//! Vocabularies such as RAMEAU or LCSH are imported into `subject_headings`,
+//! either as a plain list (one authorized heading per line, optionally
+//! followed by a tab and its record id) or as the SKOS N-Triples dumps the
+//! BnF and the Library of Congress publish: `skos:prefLabel` gives the
+//! authorized heading of a concept, `skos:altLabel` its variants. Imports
+//! are streamed and run in one transaction; a vocabulary is replaced unless
+//! appended to.
+//!
+//! Headings match case and accents aside, and whatever the spacing around
+//! `--` subdivisions. A subject is authorized when a vocabulary holds it, or
+//! else when every one of its `--` parts is authorized on its own. Variants
+//! are not authorized, but they name the heading to use instead.
+
+use std::collections::HashMap;
+
+use sea_orm::sea_query::OnConflict;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+use crate::models::{book, subject_heading};
+
+/// Rows per insert while importing.
+const IMPORT_BATCH: usize = 100;
+
+const SKOS: &str = "http://www.w3.org/2004/02/skos/core#";
+
+/// `heading` folded for matching: no case, no accents, single spaces, and
+/// `--` subdivisions without spaces around them.
+pub fn normalize(heading: &str) -> String {
+    let folded: String = heading
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect();
+    folded
+        .split("--")
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("--")
+}
+
+/// Vocabulary names: up to 32 lowercase letters, digits, `-` or `_`.
+pub fn valid_vocabulary(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Layout of an import.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// One authorized heading per line, optionally `<TAB>record id`
+    #[default]
+    Lines,
+    /// SKOS N-Triples: `prefLabel` and `altLabel` statements
+    Ntriples,
+}
+
+/// What an import left in the vocabulary.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ImportSummary {
+    pub vocabulary: String,
+    pub headings: u64,
+    pub variants: u64,
+    /// Lines that brought nothing in: other statements, labels in other
+    /// languages
+    pub skipped_lines: u64,
+}
+
+/// The literal of an N-Triples line: `(subject, predicate, text, language)`.
+fn parse_ntriple(line: &str) -> Option<(&str, &str, String, Option<&str>)> {
+    let line = line.trim();
+    let (subject, rest) = line.strip_prefix('<')?.split_once('>')?;
+    let (predicate, rest) = rest.trim_start().strip_prefix('<')?.split_once('>')?;
+    let mut chars = rest.trim_start().strip_prefix('"')?.char_indices();
+    let mut text = String::new();
+    let end = loop {
+        match chars.next()? {
+            (i, '"') => break i,
+            (_, '\\') => match chars.next()?.1 {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                'r' => text.push('\r'),
+                'u' => {
+                    let hex: String = (0..4)
+                        .filter_map(|_| chars.next().map(|(_, c)| c))
+                        .collect();
+                    text.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                'U' => {
+                    let hex: String = (0..8)
+                        .filter_map(|_| chars.next().map(|(_, c)| c))
+                        .collect();
+                    text.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => text.push(c),
+            },
+            (_, c) => text.push(c),
+        }
+    };
+    let tail = &rest.trim_start()[1 + end + 1..];
+    let lang = tail.strip_prefix('@').map(|t| {
+        t.split(|c: char| c.is_whitespace() || c == '.')
+            .next()
+            .unwrap_or("")
+    });
+    Some((subject, predicate, text, lang))
+}
+
+/// A streamed import into one vocabulary. Feed it the body in chunks of any
+/// size, then `finish` it; dropping it unfinished rolls everything back.
+pub struct HeadingImport {
+    txn: DatabaseTransaction,
+    vocabulary: String,
+    format: ImportFormat,
+    lang: Option<String>,
+    pending: Vec<u8>,
+    batch: Vec<subject_heading::ActiveModel>,
+    skipped_lines: u64,
+}
+
+impl HeadingImport {
+    /// Start importing into `vocabulary`, emptying it first unless `append`.
+    /// With N-Triples, `lang` (`fr`, `en`...) keeps the labels in that
+    /// language and those without one.
+    pub async fn start(
+        db: &DatabaseConnection,
+        vocabulary: &str,
+        format: ImportFormat,
+        lang: Option<String>,
+        append: bool,
+    ) -> Result<Self, DbErr> {
+        let txn = db.begin().await?;
+        if !append {
+            subject_heading::Entity::delete_many()
+                .filter(subject_heading::Column::Vocabulary.eq(vocabulary))
+                .exec(&txn)
+                .await?;
+        }
+        Ok(Self {
+            txn,
+            vocabulary: vocabulary.to_string(),
+            format,
+            lang: lang.map(|l| l.to_lowercase()),
+            pending: Vec::new(),
+            batch: Vec::with_capacity(IMPORT_BATCH),
+            skipped_lines: 0,
+        })
+    }
+
+    /// Import the complete lines of `chunk`; a partial last line waits for
+    /// the next chunk.
+    pub async fn feed(&mut self, chunk: &[u8]) -> Result<(), DbErr> {
+        self.pending.extend_from_slice(chunk);
+        let Some(last) = self.pending.iter().rposition(|b| *b == b'\n') else {
+            return Ok(());
+        };
+        let complete: Vec<u8> = self.pending.drain(..=last).collect();
+        for line in String::from_utf8_lossy(&complete).lines() {
+            self.line(line).await?;
+        }
+        Ok(())
+    }
+
+    async fn line(&mut self, line: &str) -> Result<(), DbErr> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Ok(());
+        }
+        let entry = match self.format {
+            ImportFormat::Lines => {
+                let (heading, id) = match trimmed.split_once('\t') {
+                    Some((heading, id)) => (heading.trim(), Some(id.trim().to_string())),
+                    None => (trimmed, None),
+                };
+                Some((heading.to_string(), id, false))
+            }
+            ImportFormat::Ntriples => parse_ntriple(trimmed).and_then(|(s, p, text, lang)| {
+                let variant = match p.strip_prefix(SKOS)? {
+                    "prefLabel" => false,
+                    "altLabel" => true,
+                    _ => return None,
+                };
+                let wanted = match (&self.lang, lang) {
+                    (Some(want), Some(lang)) => lang
+                        .split('-')
+                        .next()
+                        .is_some_and(|primary| primary.eq_ignore_ascii_case(want)),
+                    _ => true,
+                };
+                wanted.then(|| (text.trim().to_string(), Some(s.to_string()), variant))
+            }),
+        };
+        let Some((heading, authority_id, variant)) = entry.filter(|(h, _, _)| !h.is_empty()) else {
+            self.skipped_lines += 1;
+            return Ok(());
+        };
+
+        self.batch.push(subject_heading::ActiveModel {
+            vocabulary: Set(self.vocabulary.clone()),
+            authority_id: Set(authority_id),
+            normalized: Set(normalize(&heading)),
+            heading: Set(heading),
+            variant: Set(variant),
+            preferred: Set(None),
+            ..Default::default()
+        });
+        if self.batch.len() >= IMPORT_BATCH {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), DbErr> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        subject_heading::Entity::insert_many(std::mem::take(&mut self.batch))
+            .on_conflict(
+                OnConflict::columns([
+                    subject_heading::Column::Vocabulary,
+                    subject_heading::Column::Normalized,
+                    subject_heading::Column::Variant,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.txn)
+            .await?;
+        Ok(())
+    }
+
+    /// Import the last line, point variants to their authorized heading
+    /// (dropping those whose concept has none) and commit.
+    pub async fn finish(mut self) -> Result<ImportSummary, DbErr> {
+        let rest = std::mem::take(&mut self.pending);
+        for line in String::from_utf8_lossy(&rest).lines() {
+            self.line(line).await?;
+        }
+        self.flush().await?;
+
+        let backend = self.txn.get_database_backend();
+        self.txn
+            .execute(Statement::from_sql_and_values(
+                backend,
+                "UPDATE subject_headings SET preferred = (
+                    SELECT p.heading FROM subject_headings p
+                    WHERE p.vocabulary = subject_headings.vocabulary
+                      AND p.authority_id = subject_headings.authority_id
+                      AND p.variant = 0
+                    LIMIT 1)
+                 WHERE vocabulary = ? AND variant = 1 AND preferred IS NULL",
+                [self.vocabulary.clone().into()],
+            ))
+            .await?;
+        subject_heading::Entity::delete_many()
+            .filter(subject_heading::Column::Vocabulary.eq(self.vocabulary.as_str()))
+            .filter(subject_heading::Column::Variant.eq(true))
+            .filter(subject_heading::Column::Preferred.is_null())
+            .exec(&self.txn)
+            .await?;
+
+        let count = |variant: bool| {
+            subject_heading::Entity::find()
+                .filter(subject_heading::Column::Vocabulary.eq(self.vocabulary.as_str()))
+                .filter(subject_heading::Column::Variant.eq(variant))
+                .count(&self.txn)
+        };
+        let headings = count(false).await?;
+        let variants = count(true).await?;
+        self.txn.commit().await?;
+        Ok(ImportSummary {
+            vocabulary: self.vocabulary,
+            headings,
+            variants,
+            skipped_lines: self.skipped_lines,
+        })
+    }
+}
+
+/// A vocabulary and its size.
+#[derive(Debug, Clone, Serialize, FromQueryResult, utoipa::ToSchema)]
+pub struct Vocabulary {
+    pub vocabulary: String,
+    pub headings: i64,
+    pub variants: i64,
+}
+
+/// The vocabularies imported, by name.
+pub async fn vocabularies(db: &DatabaseConnection) -> Result<Vec<Vocabulary>, DbErr> {
+    Vocabulary::find_by_statement(Statement::from_string(
+        db.get_database_backend(),
+        "SELECT vocabulary,
+                SUM(CASE WHEN variant = 0 THEN 1 ELSE 0 END) AS headings,
+                SUM(CASE WHEN variant = 1 THEN 1 ELSE 0 END) AS variants
+         FROM subject_headings GROUP BY vocabulary ORDER BY vocabulary"
+            .to_owned(),
+    ))
+    .all(db)
+    .await
+}
+
+/// Remove a vocabulary; the number of labels removed.
+pub async fn delete_vocabulary(db: &DatabaseConnection, vocabulary: &str) -> Result<u64, DbErr> {
+    Ok(subject_heading::Entity::delete_many()
+        .filter(subject_heading::Column::Vocabulary.eq(vocabulary))
+        .exec(db)
+        .await?
+        .rows_affected)
+}
+
+/// How a subject fares against the vocabularies.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct HeadingCheck {
+    pub subject: String,
+    pub authorized: bool,
+    /// The vocabulary authorizing it, or holding it as a variant
+    pub vocabulary: Option<String>,
+    /// For a variant, the authorized heading to use instead
+    pub preferred: Option<String>,
+}
+
+async fn labels(
+    db: &DatabaseConnection,
+    normalized: &str,
+) -> Result<Vec<subject_heading::Model>, DbErr> {
+    subject_heading::Entity::find()
+        .filter(subject_heading::Column::Normalized.eq(normalized))
+        .order_by_asc(subject_heading::Column::Variant)
+        .order_by_asc(subject_heading::Column::Vocabulary)
+        .all(db)
+        .await
+}
+
+/// Check one subject.
+pub async fn check(db: &DatabaseConnection, subject: &str) -> Result<HeadingCheck, DbErr> {
+    let normalized = normalize(subject);
+    let mut result = HeadingCheck {
+        subject: subject.to_string(),
+        authorized: false,
+        vocabulary: None,
+        preferred: None,
+    };
+
+    if let Some(found) = labels(db, &normalized).await?.into_iter().next() {
+        result.authorized = !found.variant;
+        result.vocabulary = Some(found.vocabulary);
+        result.preferred = found.preferred;
+        return Ok(result);
+    }
+
+    if normalized.contains("--") {
+        let mut vocabulary = None;
+        for part in normalized.split("--") {
+            match labels(db, part).await?.into_iter().find(|l| !l.variant) {
+                Some(found) => {
+                    vocabulary.get_or_insert(found.vocabulary);
+                }
+                None => return Ok(result),
+            }
+        }
+        result.authorized = true;
+        result.vocabulary = vocabulary;
+    }
+    Ok(result)
+}
+
+/// An authorized heading offered while typing.
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct HeadingSuggestion {
+    pub heading: String,
+    pub vocabulary: String,
+    /// The variant the text matched, when it led to `heading`
+    pub matched: Option<String>,
+}
+
+/// Authorized headings starting with `prefix`, directly or through one of
+/// their variants.
+pub async fn suggest(
+    db: &DatabaseConnection,
+    prefix: &str,
+    vocabulary: Option<&str>,
+    limit: u64,
+) -> Result<Vec<HeadingSuggestion>, DbErr> {
+    let normalized = normalize(prefix);
+    if normalized.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut query = subject_heading::Entity::find()
+        .filter(subject_heading::Column::Normalized.starts_with(&normalized));
+    if let Some(vocabulary) = vocabulary {
+        query = query.filter(subject_heading::Column::Vocabulary.eq(vocabulary));
+    }
+    let found = query
+        .order_by_asc(subject_heading::Column::Variant)
+        .order_by_asc(subject_heading::Column::Normalized)
+        .limit(limit * 2)
+        .all(db)
+        .await?;
+
+    let mut suggestions: Vec<HeadingSuggestion> = Vec::new();
+    for label in found {
+        let (heading, matched) = match label.preferred {
+            Some(preferred) if label.variant => (preferred, Some(label.heading)),
+            _ => (label.heading, None),
+        };
+        if suggestions
+            .iter()
+            .any(|s| s.heading == heading && s.vocabulary == label.vocabulary)
+        {
+            continue;
+        }
+        suggestions.push(HeadingSuggestion {
+            heading,
+            vocabulary: label.vocabulary,
+            matched,
+        });
+        if suggestions.len() as u64 >= limit {
+            break;
+        }
+    }
+    Ok(suggestions)
+}
+
+/// A book subject no vocabulary authorizes.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct UnauthorizedHeading {
+    pub book_id: String,
+    pub title: String,
+    pub subject: String,
+    /// The authorized form, when the subject is a known variant
+    pub preferred: Option<String>,
+}
+
+/// Book subjects that no imported vocabulary authorizes, by title. Empty
+/// while no vocabulary is imported.
+pub async fn unauthorized_headings(
+    db: &DatabaseConnection,
+) -> Result<Vec<UnauthorizedHeading>, DbErr> {
+    if subject_heading::Entity::find().one(db).await?.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let books = book::Entity::find()
+        .filter(book::Column::Subjects.is_not_null())
+        .order_by_asc(book::Column::Title)
+        .all(db)
+        .await?;
+    let mut checked: HashMap<String, HeadingCheck> = HashMap::new();
+    let mut flagged = Vec::new();
+    for b in books {
+        let subjects: Vec<String> = b
+            .subjects
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        for subject in subjects.into_iter().filter(|s| !s.trim().is_empty()) {
+            let key = normalize(&subject);
+            if !checked.contains_key(&key) {
+                let result = check(db, &subject).await?;
+                checked.insert(key.clone(), result);
+            }
+            let result = &checked[&key];
+            if !result.authorized {
+                flagged.push(UnauthorizedHeading {
+                    book_id: b.id.clone(),
+                    title: b.title.clone(),
+                    subject,
+                    preferred: result.preferred.clone(),
+                });
+            }
+        }
+    }
+    Ok(flagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAMEAU: &str = r#"
+<http://data.bnf.fr/ark:/12148/cb11933232c> <http://www.w3.org/2004/02/skos/core#prefLabel> "Chats"@fr .
+<http://data.bnf.fr/ark:/12148/cb11933232c> <http://www.w3.org/2004/02/skos/core#altLabel> "Félins domestiques"@fr .
+<http://data.bnf.fr/ark:/12148/cb11933232c> <http://www.w3.org/2004/02/skos/core#altLabel> "Cats"@en .
+<http://data.bnf.fr/ark:/12148/cb11933232c> <http://www.w3.org/2004/02/skos/core#note> "Domestic cats"@fr .
+<http://data.bnf.fr/ark:/12148/cb119352990> <http://www.w3.org/2004/02/skos/core#prefLabel> "Histoire"@fr .
+<http://data.bnf.fr/ark:/12148/cb11975995h> <http://www.w3.org/2004/02/skos/core#prefLabel> "Chats dans l'art"@fr .
+"#;
+
+    #[test]
+    fn headings_fold_case_accents_and_subdivision_spacing() {
+        assert_eq!(
+            normalize("  Félins   domestiques -- Histoire "),
+            "felins domestiques--histoire"
+        );
+        assert_eq!(
+            parse_ntriple(r#"<s> <p> "a \"b\"é"@fr-FR ."#),
+            Some(("s", "p", "a \"b\"é".to_string(), Some("fr-FR")))
+        );
+    }
+
+    #[tokio::test]
+    async fn imported_vocabularies_authorize_and_correct_subjects() {
+        let db = crate::db::init_db("sqlite::memory:").await.unwrap();
+
+        // Fed in uneven chunks, as a request body arrives.
+        let mut import = HeadingImport::start(
+            &db,
+            "rameau",
+            ImportFormat::Ntriples,
+            Some("fr".into()),
+            false,
+        )
+        .await
+        .unwrap();
+        for chunk in RAMEAU.as_bytes().chunks(37) {
+            import.feed(chunk).await.unwrap();
+        }
+        let summary = import.finish().await.unwrap();
+        assert_eq!((summary.headings, summary.variants), (3, 1));
+        assert_eq!(summary.skipped_lines, 2);
+
+        let chats = check(&db, "chats -- histoire").await.unwrap();
+        assert!(chats.authorized);
+        let felins = check(&db, "Félins domestiques").await.unwrap();
+        assert!(!felins.authorized);
+        assert_eq!(felins.preferred.as_deref(), Some("Chats"));
+        assert!(!check(&db, "Chiens").await.unwrap().authorized);
+
+        let offered = suggest(&db, "fel", None, 10).await.unwrap();
+        assert_eq!(offered[0].heading, "Chats");
+        assert_eq!(offered[0].matched.as_deref(), Some("Félins domestiques"));
+        assert_eq!(suggest(&db, "chat", None, 10).await.unwrap().len(), 2);
+
+        let now = chrono::Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set("Le chat".to_string()),
+            subjects: Set(Some(
+                r#"["Chats","Félins domestiques","Chiens"]"#.to_string(),
+            )),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let flagged = unauthorized_headings(&db).await.unwrap();
+        let subjects: Vec<(&str, Option<&str>)> = flagged
+            .iter()
+            .map(|f| (f.subject.as_str(), f.preferred.as_deref()))
+            .collect();
+        assert_eq!(
+            subjects,
+            [("Félins domestiques", Some("Chats")), ("Chiens", None)]
+        );
+
+        assert_eq!(vocabularies(&db).await.unwrap()[0].headings, 3);
+        assert_eq!(delete_vocabulary(&db, "rameau").await.unwrap(), 4);
+    }
+}
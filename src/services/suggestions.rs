@@ -0,0 +1,340 @@
+//! Suggestion box: books patrons would like the library to acquire.
+//!
+//! A patron holding a portal token (see `services::contact_portal`) sends a
+//! title, an author, an ISBN and a note; the owner gets a notification and
+//! reviews pending suggestions. Accepting one puts the book on the wishlist
+//! (`owned = false`, reading status `wanting`), or links the book already in
+//! the catalogue under that ISBN. The patron sees the decision in their own
+//! list. Suggestions are local: they are not replicated to other devices.
+
+use chrono::Utc;
+use sea_orm::*;
+use serde::Deserialize;
+
+use crate::domain::{CreateNotification, NotificationEventType};
+use crate::models::book_suggestion::{self, STATUS_ACCEPTED, STATUS_PENDING, STATUS_REJECTED};
+use crate::models::{book, contact};
+
+/// Pending suggestions a patron may have at once.
+pub const MAX_PENDING_PER_CONTACT: u64 = 20;
+
+const MAX_TITLE: usize = 300;
+const MAX_AUTHOR: usize = 200;
+const MAX_NOTE: usize = 1000;
+
+#[derive(Debug)]
+pub enum SuggestionError {
+    Db(DbErr),
+    NotFound,
+    Invalid(String),
+    /// The patron already suggested this book and it is still pending.
+    Duplicate,
+    /// The patron has `MAX_PENDING_PER_CONTACT` suggestions pending.
+    TooMany,
+    /// Accepted or rejected already: the status is attached.
+    AlreadyDecided(String),
+    /// Creating the wishlist book failed.
+    Book(String),
+}
+
+impl std::fmt::Display for SuggestionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::NotFound => write!(f, "suggestion not found"),
+            Self::Invalid(reason) => write!(f, "{reason}"),
+            Self::Duplicate => write!(f, "this book is already suggested"),
+            Self::TooMany => write!(
+                f,
+                "at most {MAX_PENDING_PER_CONTACT} suggestions can wait for a decision"
+            ),
+            Self::AlreadyDecided(status) => write!(f, "suggestion already {status}"),
+            Self::Book(e) => write!(f, "could not add the book: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SuggestionError {}
+
+impl From<DbErr> for SuggestionError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// What a patron sends.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct NewSuggestion {
+    pub title: String,
+    pub author: Option<String>,
+    pub isbn: Option<String>,
+    pub note: Option<String>,
+}
+
+fn clean_isbn(isbn: &str) -> String {
+    isbn.chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'X' || *c == 'x')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Trimmed, `None` when blank; too long is an error.
+fn field(name: &str, value: Option<String>, max: usize) -> Result<Option<String>, SuggestionError> {
+    let value = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    match value {
+        Some(v) if v.chars().count() > max => Err(SuggestionError::Invalid(format!(
+            "{name} is longer than {max} characters"
+        ))),
+        v => Ok(v),
+    }
+}
+
+/// Record a patron's suggestion and notify the owner.
+pub async fn submit(
+    db: &DatabaseConnection,
+    patron: &contact::Model,
+    new: NewSuggestion,
+) -> Result<book_suggestion::Model, SuggestionError> {
+    let title = field("title", Some(new.title), MAX_TITLE)?
+        .ok_or_else(|| SuggestionError::Invalid("title is required".to_string()))?;
+    let author = field("author", new.author, MAX_AUTHOR)?;
+    let note = field("note", new.note, MAX_NOTE)?;
+    let isbn = new
+        .isbn
+        .as_deref()
+        .map(clean_isbn)
+        .filter(|i| !i.is_empty());
+    if isbn.as_ref().is_some_and(|i| !matches!(i.len(), 10 | 13)) {
+        return Err(SuggestionError::Invalid(
+            "isbn must have 10 or 13 digits".to_string(),
+        ));
+    }
+
+    let pending = book_suggestion::Entity::find()
+        .filter(book_suggestion::Column::ContactId.eq(patron.id.as_str()))
+        .filter(book_suggestion::Column::Status.eq(STATUS_PENDING))
+        .all(db)
+        .await?;
+    if pending.iter().any(|s| match (&isbn, &s.isbn) {
+        (Some(a), Some(b)) => a == b,
+        _ => s.title.to_lowercase() == title.to_lowercase(),
+    }) {
+        return Err(SuggestionError::Duplicate);
+    }
+    if pending.len() as u64 >= MAX_PENDING_PER_CONTACT {
+        return Err(SuggestionError::TooMany);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let saved = book_suggestion::ActiveModel {
+        id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+        contact_id: Set(Some(patron.id.clone())),
+        title: Set(title),
+        author: Set(author),
+        isbn: Set(isbn),
+        note: Set(note),
+        status: Set(STATUS_PENDING.to_string()),
+        book_id: Set(None),
+        decided_at: Set(None),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+
+    crate::services::notification_service::emit_unique(
+        db,
+        CreateNotification {
+            event_type: NotificationEventType::BookSuggestion,
+            title: saved.title.clone(),
+            body: Some(patron.name.clone()),
+            ref_type: Some("book_suggestion".to_string()),
+            ref_id: Some(saved.id.clone()),
+        },
+    )
+    .await;
+    Ok(saved)
+}
+
+/// A patron's own suggestions, latest first.
+pub async fn for_contact(
+    db: &DatabaseConnection,
+    contact_id: &str,
+) -> Result<Vec<book_suggestion::Model>, DbErr> {
+    book_suggestion::Entity::find()
+        .filter(book_suggestion::Column::ContactId.eq(contact_id))
+        .order_by_desc(book_suggestion::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// Suggestions, optionally of one status, oldest first so the longest
+/// waiting come first.
+pub async fn list(
+    db: &DatabaseConnection,
+    status: Option<&str>,
+) -> Result<Vec<book_suggestion::Model>, DbErr> {
+    let mut query = book_suggestion::Entity::find();
+    if let Some(status) = status {
+        query = query.filter(book_suggestion::Column::Status.eq(status));
+    }
+    query
+        .order_by_asc(book_suggestion::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+async fn pending(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<book_suggestion::Model, SuggestionError> {
+    let found = book_suggestion::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or(SuggestionError::NotFound)?;
+    if found.status != STATUS_PENDING {
+        return Err(SuggestionError::AlreadyDecided(found.status));
+    }
+    Ok(found)
+}
+
+async fn decide(
+    db: &DatabaseConnection,
+    found: book_suggestion::Model,
+    status: &str,
+    book_id: Option<String>,
+) -> Result<book_suggestion::Model, DbErr> {
+    let now = Utc::now().to_rfc3339();
+    let mut active: book_suggestion::ActiveModel = found.into();
+    active.status = Set(status.to_string());
+    active.book_id = Set(book_id);
+    active.decided_at = Set(Some(now.clone()));
+    active.updated_at = Set(now);
+    active.update(db).await
+}
+
+/// Accept a suggestion: the book already catalogued under its ISBN, owned
+/// or wished for, is linked as is; otherwise a wishlist book is created.
+pub async fn accept(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<book_suggestion::Model, SuggestionError> {
+    let found = pending(db, id).await?;
+
+    let existing = match &found.isbn {
+        Some(isbn) => {
+            book::Entity::find()
+                .filter(book::Column::Isbn.eq(isbn.as_str()))
+                .one(db)
+                .await?
+        }
+        None => None,
+    };
+    let book_id = match existing {
+        Some(b) => b.id,
+        None => crate::services::book_service::create_book(
+            db,
+            book::Book {
+                title: found.title.clone(),
+                author: found.author.clone(),
+                isbn: found.isbn.clone(),
+                reading_status: Some("wanting".to_string()),
+                owned: Some(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| SuggestionError::Book(format!("{e:?}")))?
+        .id
+        .unwrap_or_default(),
+    };
+
+    Ok(decide(db, found, STATUS_ACCEPTED, Some(book_id)).await?)
+}
+
+/// Turn a suggestion down.
+pub async fn reject(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<book_suggestion::Model, SuggestionError> {
+    let found = pending(db, id).await?;
+    Ok(decide(db, found, STATUS_REJECTED, None).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    fn suggestion(title: &str, isbn: Option<&str>) -> NewSuggestion {
+        NewSuggestion {
+            title: title.to_string(),
+            author: Some("Frank Herbert".to_string()),
+            isbn: isbn.map(str::to_string),
+            note: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn accepted_suggestions_land_on_the_wishlist() {
+        let db = setup().await;
+        let now = Utc::now().to_rfc3339();
+        let patron = contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set("Alice".to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let dune = submit(&db, &patron, suggestion("Dune", Some("978-0-441-01359-3")))
+            .await
+            .unwrap();
+        assert_eq!(dune.isbn.as_deref(), Some("9780441013593"));
+        assert!(matches!(
+            submit(
+                &db,
+                &patron,
+                suggestion("Dune (reissue)", Some("9780441013593"))
+            )
+            .await,
+            Err(SuggestionError::Duplicate)
+        ));
+        assert!(matches!(
+            submit(&db, &patron, suggestion("  ", None)).await,
+            Err(SuggestionError::Invalid(_))
+        ));
+        let messiah = submit(&db, &patron, suggestion("Dune Messiah", None))
+            .await
+            .unwrap();
+
+        let accepted = accept(&db, &dune.id).await.unwrap();
+        assert_eq!(accepted.status, STATUS_ACCEPTED);
+        let wished = book::Entity::find_by_id(accepted.book_id.unwrap())
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!wished.owned);
+        assert_eq!(wished.reading_status, "wanting");
+        assert!(matches!(
+            accept(&db, &dune.id).await,
+            Err(SuggestionError::AlreadyDecided(_))
+        ));
+
+        reject(&db, &messiah.id).await.unwrap();
+        assert!(list(&db, Some(STATUS_PENDING)).await.unwrap().is_empty());
+        let mine = for_contact(&db, &patron.id).await.unwrap();
+        assert_eq!(mine.len(), 2);
+    }
+}
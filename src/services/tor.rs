@@ -0,0 +1,281 @@
+//! Peer requests over Tor (or any SOCKS5 proxy), and the onion service
+//! address this node publishes to its peers.
+//!
+//! With a proxy configured, requests to `.onion` peers always go through it,
+//! and every other peer request does too when `route_all_peers` is set, so
+//! peers and the relay hub never see this node's home IP. The proxy must be
+//! `socks5h://`: names are resolved by the proxy, which is the only way to
+//! reach an onion service.
+//!
+//! The node's own onion address (its hidden service in `torrc`, pointing at
+//! the local server) is advertised in `/api/config` as `onion_url`; peers
+//! record it in `peers.onion_url` on their next sync and can switch to it
+//! with `PUT /api/peers/:id/url`.
+//!
+//! Settings are read on every peer request, so they are kept in memory too,
+//! loaded at startup (`load`) and updated with the table.
+
+use std::sync::{LazyLock, RwLock};
+
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{peer, tor_config};
+
+static SETTINGS: LazyLock<RwLock<TorSettings>> =
+    LazyLock::new(|| RwLock::new(TorSettings::default()));
+
+/// Error type for proxy settings
+#[derive(Debug)]
+pub enum TorError {
+    InvalidProxy(String),
+    InvalidOnion(String),
+    Db(DbErr),
+}
+
+impl std::fmt::Display for TorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TorError::InvalidProxy(e) => write!(f, "Invalid SOCKS5 proxy: {}", e),
+            TorError::InvalidOnion(e) => write!(f, "Invalid onion address: {}", e),
+            TorError::Db(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<DbErr> for TorError {
+    fn from(e: DbErr) -> Self {
+        TorError::Db(e)
+    }
+}
+
+/// SOCKS5 proxy and onion address settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TorSettings {
+    /// `socks5h://host:port` (a bare `host:port` is accepted), e.g. Tor's
+    /// `127.0.0.1:9050`; none for direct requests
+    #[serde(default)]
+    pub socks_proxy: Option<String>,
+    /// This node's onion service URL, advertised to peers
+    #[serde(default)]
+    pub onion_address: Option<String>,
+    /// Send every peer request through the proxy, not only those to onions
+    #[serde(default)]
+    pub route_all_peers: bool,
+}
+
+impl From<tor_config::Model> for TorSettings {
+    fn from(m: tor_config::Model) -> Self {
+        Self {
+            socks_proxy: m.socks_proxy,
+            onion_address: m.onion_address,
+            route_all_peers: m.route_all_peers,
+        }
+    }
+}
+
+impl TorSettings {
+    /// The proxy a request to `url` goes through; `None` (no URL) is a peer
+    /// request whose target is not known yet.
+    fn proxy_url_for(&self, url: Option<&str>) -> Option<&str> {
+        let proxy = self.socks_proxy.as_deref()?;
+        (self.route_all_peers || url.is_some_and(is_onion)).then_some(proxy)
+    }
+}
+
+/// Whether `url` points at an onion service.
+pub fn is_onion(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str()
+                .map(|h| h.to_ascii_lowercase().ends_with(".onion"))
+        })
+        .unwrap_or(false)
+}
+
+/// `socks5h://host:port` from what the owner typed.
+pub fn normalize_proxy(input: &str) -> Result<String, TorError> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("socks5h://{input}")
+    };
+    let url = url::Url::parse(&with_scheme).map_err(|e| TorError::InvalidProxy(e.to_string()))?;
+    if url.scheme() != "socks5h" {
+        return Err(TorError::InvalidProxy(
+            "use socks5h://, so the proxy resolves peer names".to_string(),
+        ));
+    }
+    let (Some(host), Some(port)) = (url.host_str(), url.port()) else {
+        return Err(TorError::InvalidProxy("host and port required".to_string()));
+    };
+    Ok(format!("socks5h://{host}:{port}"))
+}
+
+/// `http://<56 chars>.onion[:port]` from an onion host or URL. Only v3
+/// addresses are accepted, v2 ones no longer resolve.
+pub fn normalize_onion(input: &str) -> Result<String, TorError> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("http://{input}")
+    };
+    let url = url::Url::parse(&with_scheme).map_err(|e| TorError::InvalidOnion(e.to_string()))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(TorError::InvalidOnion("only http and https".to_string()));
+    }
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    let valid = host.strip_suffix(".onion").is_some_and(|label| {
+        label.len() == 56
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || (b'2'..=b'7').contains(&b))
+    });
+    if !valid {
+        return Err(TorError::InvalidOnion(
+            "expected a 56-character v3 .onion host".to_string(),
+        ));
+    }
+    Ok(match url.port() {
+        Some(port) => format!("{}://{host}:{port}", url.scheme()),
+        None => format!("{}://{host}", url.scheme()),
+    })
+}
+
+fn cache(settings: TorSettings) {
+    if let Ok(mut cached) = SETTINGS.write() {
+        *cached = settings;
+    }
+}
+
+/// Load the settings into memory. Called once the database is migrated.
+pub async fn load(db: &DatabaseConnection) -> Result<(), DbErr> {
+    cache(get(db).await?);
+    Ok(())
+}
+
+/// The stored settings.
+pub async fn get(db: &DatabaseConnection) -> Result<TorSettings, DbErr> {
+    Ok(tor_config::Entity::find_by_id(1)
+        .one(db)
+        .await?
+        .map(TorSettings::from)
+        .unwrap_or_default())
+}
+
+/// Validate, store and apply new settings. Blank values clear them.
+pub async fn save(db: &DatabaseConnection, settings: TorSettings) -> Result<TorSettings, TorError> {
+    let blank = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+    let settings = TorSettings {
+        socks_proxy: blank(settings.socks_proxy)
+            .map(|p| normalize_proxy(&p))
+            .transpose()?,
+        onion_address: blank(settings.onion_address)
+            .map(|o| normalize_onion(&o))
+            .transpose()?,
+        route_all_peers: settings.route_all_peers,
+    };
+    let row = tor_config::ActiveModel {
+        id: Set(1),
+        socks_proxy: Set(settings.socks_proxy.clone()),
+        onion_address: Set(settings.onion_address.clone()),
+        route_all_peers: Set(settings.route_all_peers),
+        updated_at: Set(chrono::Utc::now().to_rfc3339()),
+    };
+    tor_config::Entity::insert(row)
+        .on_conflict(
+            sea_query::OnConflict::column(tor_config::Column::Id)
+                .update_columns([
+                    tor_config::Column::SocksProxy,
+                    tor_config::Column::OnionAddress,
+                    tor_config::Column::RouteAllPeers,
+                    tor_config::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    cache(settings.clone());
+    Ok(settings)
+}
+
+/// The SOCKS5 proxy for a peer request to `url`, if it must use one. None
+/// outside the `full` network mode: Tor would carry requests out of the
+/// network the mode confines them to (see `services::network_mode`).
+pub fn proxy_for(url: Option<&str>) -> Option<reqwest::Proxy> {
+    if crate::services::network_mode::current() != crate::services::network_mode::NetworkMode::Full
+    {
+        return None;
+    }
+    let settings = SETTINGS.read().ok()?;
+    reqwest::Proxy::all(settings.proxy_url_for(url)?).ok()
+}
+
+/// The onion service URL this node advertises, if any.
+pub fn onion_url() -> Option<String> {
+    SETTINGS.read().ok()?.onion_address.clone()
+}
+
+/// Record the onion URL a peer advertises in its `/api/config`. Invalid
+/// addresses are ignored, and a peer that stopped advertising one loses it.
+pub async fn record_peer_onion(
+    db: &DatabaseConnection,
+    peer_id: i32,
+    advertised: Option<&str>,
+) -> Result<(), DbErr> {
+    let onion_url = advertised.and_then(|a| normalize_onion(a).ok());
+    let Some(found) = peer::Entity::find_by_id(peer_id).one(db).await? else {
+        return Ok(());
+    };
+    if found.onion_url == onion_url {
+        return Ok(());
+    }
+    let mut active: peer::ActiveModel = found.into();
+    active.onion_url = Set(onion_url);
+    active.update(db).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONION: &str = "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion";
+
+    #[test]
+    fn onions_go_through_the_proxy_and_the_rest_only_when_asked() {
+        assert_eq!(
+            normalize_proxy("127.0.0.1:9050").unwrap(),
+            "socks5h://127.0.0.1:9050"
+        );
+        assert!(normalize_proxy("socks5://127.0.0.1:9050").is_err());
+        assert!(normalize_proxy("socks5h://127.0.0.1").is_err());
+
+        assert_eq!(
+            normalize_onion(&format!("{}:8000", ONION.to_uppercase())).unwrap(),
+            format!("http://{ONION}:8000")
+        );
+        assert!(normalize_onion("expyuzz4wqqyqhjn.onion").is_err());
+        assert!(normalize_onion("http://192.168.1.20:8000").is_err());
+
+        let onion_peer = format!("http://{ONION}");
+        let lan_peer = "http://192.168.1.20:8000";
+        let mut settings = TorSettings::default();
+        assert_eq!(settings.proxy_url_for(Some(&onion_peer)), None);
+
+        settings.socks_proxy = Some("socks5h://127.0.0.1:9050".to_string());
+        assert_eq!(
+            settings.proxy_url_for(Some(&onion_peer)),
+            Some("socks5h://127.0.0.1:9050")
+        );
+        assert_eq!(settings.proxy_url_for(Some(lan_peer)), None);
+        assert_eq!(settings.proxy_url_for(None), None);
+
+        settings.route_all_peers = true;
+        assert!(settings.proxy_url_for(Some(lan_peer)).is_some());
+        assert!(settings.proxy_url_for(None).is_some());
+    }
+}
@@ -0,0 +1,460 @@
+//! Weeding (deaccessioning) of copies.
+//!
+//! A copy is first flagged with a reason code; flagged copies make up the
+//! review list, with how much each has been lent. Withdrawing a flagged copy
+//! sets its status to `withdrawn` and dates it. The copy row stays, and so
+//! do its loans: they still count in the loan history and statistics, and
+//! the withdrawn copies make up the deaccession report.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{book, copy, loan};
+
+pub const STATUS_WITHDRAWN: &str = "withdrawn";
+
+/// Copies in these states are elsewhere; they cannot be withdrawn until back.
+const OUT_STATUSES: &[&str] = &["loaned", "reserved", "borrowed", "returning", "in_transit"];
+
+/// Why a copy is weeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeedingReason {
+    Worn,
+    Damaged,
+    Outdated,
+    Duplicate,
+    LowUse,
+    Lost,
+    Other,
+}
+
+impl WeedingReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Worn => "worn",
+            Self::Damaged => "damaged",
+            Self::Outdated => "outdated",
+            Self::Duplicate => "duplicate",
+            Self::LowUse => "low_use",
+            Self::Lost => "lost",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WeedingError {
+    Db(DbErr),
+    NotFound(String),
+    AlreadyWithdrawn(String),
+    NotFlagged(String),
+    /// The copy is out (on loan, in transit, ...): the status is attached.
+    Out(String, String),
+}
+
+impl std::fmt::Display for WeedingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::NotFound(id) => write!(f, "copy {id} not found"),
+            Self::AlreadyWithdrawn(id) => write!(f, "copy {id} is already withdrawn"),
+            Self::NotFlagged(id) => write!(f, "copy {id} is not flagged for weeding"),
+            Self::Out(id, status) => write!(f, "copy {id} is {status}"),
+        }
+    }
+}
+
+impl std::error::Error for WeedingError {}
+
+impl From<DbErr> for WeedingError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// A flagged or withdrawn copy, with its book and its lending record.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WeedingItem {
+    pub copy_id: String,
+    pub book_id: String,
+    pub title: String,
+    pub isbn: Option<String>,
+    pub status: String,
+    pub reason: Option<String>,
+    pub note: Option<String>,
+    pub flagged_at: Option<String>,
+    pub withdrawn_at: Option<String>,
+    pub acquisition_date: Option<String>,
+    pub loan_count: u32,
+    pub last_loaned: Option<String>,
+}
+
+fn weeding_payload(c: &copy::Model) -> serde_json::Value {
+    serde_json::json!({
+        "weeding": {
+            "reason": c.weeding_reason,
+            "note": c.weeding_note,
+            "flagged_at": c.weeding_flagged_at,
+            "withdrawn_at": c.withdrawn_at,
+        }
+    })
+}
+
+async fn find_copy<C: ConnectionTrait>(db: &C, id: &str) -> Result<copy::Model, WeedingError> {
+    copy::Entity::find_by_id(id.to_string())
+        .one(db)
+        .await?
+        .ok_or_else(|| WeedingError::NotFound(id.to_string()))
+}
+
+/// Flag a copy for weeding, or change the reason of a flagged one.
+pub async fn flag(
+    db: &DatabaseConnection,
+    copy_id: &str,
+    reason: WeedingReason,
+    note: Option<String>,
+) -> Result<copy::Model, WeedingError> {
+    let found = find_copy(db, copy_id).await?;
+    if found.status == STATUS_WITHDRAWN {
+        return Err(WeedingError::AlreadyWithdrawn(found.id));
+    }
+    let now = Utc::now().to_rfc3339();
+    let flagged_at = found
+        .weeding_flagged_at
+        .clone()
+        .unwrap_or_else(|| now.clone());
+    let mut active: copy::ActiveModel = found.into();
+    active.weeding_reason = Set(Some(reason.as_str().to_string()));
+    active.weeding_note = Set(note);
+    active.weeding_flagged_at = Set(Some(flagged_at));
+    active.updated_at = Set(now);
+    let saved = active.update(db).await?;
+    let _ = crate::sync::log_operation(
+        db,
+        "copy",
+        &saved.id,
+        "UPDATE",
+        Some(weeding_payload(&saved)),
+    )
+    .await;
+    Ok(saved)
+}
+
+/// Take a copy off the review list.
+pub async fn unflag(db: &DatabaseConnection, copy_id: &str) -> Result<copy::Model, WeedingError> {
+    let found = find_copy(db, copy_id).await?;
+    if found.status == STATUS_WITHDRAWN {
+        return Err(WeedingError::AlreadyWithdrawn(found.id));
+    }
+    if found.weeding_reason.is_none() {
+        return Err(WeedingError::NotFlagged(found.id));
+    }
+    let mut active: copy::ActiveModel = found.into();
+    active.weeding_reason = Set(None);
+    active.weeding_note = Set(None);
+    active.weeding_flagged_at = Set(None);
+    active.updated_at = Set(Utc::now().to_rfc3339());
+    let saved = active.update(db).await?;
+    let _ = crate::sync::log_operation(
+        db,
+        "copy",
+        &saved.id,
+        "UPDATE",
+        Some(weeding_payload(&saved)),
+    )
+    .await;
+    Ok(saved)
+}
+
+/// Withdraw flagged copies, all or none: any copy not found, not flagged,
+/// already withdrawn or out aborts the whole batch.
+pub async fn withdraw(
+    db: &DatabaseConnection,
+    copy_ids: &[String],
+) -> Result<Vec<copy::Model>, WeedingError> {
+    let now = Utc::now().to_rfc3339();
+    let txn = db.begin().await?;
+    let mut withdrawn = Vec::with_capacity(copy_ids.len());
+    for id in copy_ids {
+        let found = find_copy(&txn, id).await?;
+        if found.status == STATUS_WITHDRAWN {
+            return Err(WeedingError::AlreadyWithdrawn(found.id));
+        }
+        if found.weeding_reason.is_none() {
+            return Err(WeedingError::NotFlagged(found.id));
+        }
+        if OUT_STATUSES.contains(&found.status.as_str()) {
+            return Err(WeedingError::Out(found.id, found.status));
+        }
+        let mut active: copy::ActiveModel = found.into();
+        active.status = Set(STATUS_WITHDRAWN.to_string());
+        active.withdrawn_at = Set(Some(now.clone()));
+        active.updated_at = Set(now.clone());
+        withdrawn.push(active.update(&txn).await?);
+    }
+    txn.commit().await?;
+
+    for c in &withdrawn {
+        let mut payload = weeding_payload(c);
+        payload["status"] = serde_json::json!(STATUS_WITHDRAWN);
+        let _ = crate::sync::log_operation(db, "copy", &c.id, "UPDATE", Some(payload)).await;
+    }
+    Ok(withdrawn)
+}
+
+async fn items(
+    db: &DatabaseConnection,
+    copies: Vec<copy::Model>,
+) -> Result<Vec<WeedingItem>, DbErr> {
+    let book_ids: Vec<String> = copies.iter().map(|c| c.book_id.clone()).collect();
+    let books: HashMap<String, book::Model> = book::Entity::find()
+        .filter(book::Column::Id.is_in(book_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| (b.id.clone(), b))
+        .collect();
+    let copy_ids: Vec<String> = copies.iter().map(|c| c.id.clone()).collect();
+    let mut lending: HashMap<String, (u32, Option<String>)> = HashMap::new();
+    for l in loan::Entity::find()
+        .filter(loan::Column::CopyId.is_in(copy_ids))
+        .all(db)
+        .await?
+    {
+        let entry = lending.entry(l.copy_id).or_default();
+        entry.0 += 1;
+        if entry
+            .1
+            .as_deref()
+            .is_none_or(|last| l.loan_date.as_str() > last)
+        {
+            entry.1 = Some(l.loan_date);
+        }
+    }
+
+    Ok(copies
+        .into_iter()
+        .map(|c| {
+            let found = books.get(&c.book_id);
+            let (loan_count, last_loaned) = lending.remove(&c.id).unwrap_or_default();
+            WeedingItem {
+                title: found.map(|b| b.title.clone()).unwrap_or_default(),
+                isbn: found.and_then(|b| b.isbn.clone()),
+                copy_id: c.id,
+                book_id: c.book_id,
+                status: c.status,
+                reason: c.weeding_reason,
+                note: c.weeding_note,
+                flagged_at: c.weeding_flagged_at,
+                withdrawn_at: c.withdrawn_at,
+                acquisition_date: c.acquisition_date,
+                loan_count,
+                last_loaned,
+            }
+        })
+        .collect())
+}
+
+/// Copies flagged and not withdrawn yet, oldest flag first.
+pub async fn review_list(db: &DatabaseConnection) -> Result<Vec<WeedingItem>, DbErr> {
+    let flagged = copy::Entity::find()
+        .filter(copy::Column::WeedingReason.is_not_null())
+        .filter(copy::Column::Status.ne(STATUS_WITHDRAWN))
+        .order_by_asc(copy::Column::WeedingFlaggedAt)
+        .all(db)
+        .await?;
+    items(db, flagged).await
+}
+
+/// Copies withdrawn between `from` and `to` (inclusive `YYYY-MM-DD` bounds,
+/// either optional), in withdrawal order.
+pub async fn withdrawn_report(
+    db: &DatabaseConnection,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<WeedingItem>, DbErr> {
+    let mut query = copy::Entity::find().filter(copy::Column::Status.eq(STATUS_WITHDRAWN));
+    if let Some(from) = from {
+        query = query.filter(copy::Column::WithdrawnAt.gte(from));
+    }
+    if let Some(to) = to {
+        // Timestamps of that last day sort after the bare date.
+        query = query.filter(copy::Column::WithdrawnAt.lt(format!("{to}~")));
+    }
+    let withdrawn = query
+        .order_by_asc(copy::Column::WithdrawnAt)
+        .all(db)
+        .await?;
+    items(db, withdrawn).await
+}
+
+fn day(value: &Option<String>) -> &str {
+    value.as_deref().map_or("", |v| v.get(..10).unwrap_or(v))
+}
+
+/// The report as CSV.
+pub fn report_csv(items: &[WeedingItem]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "withdrawn_at",
+        "reason",
+        "title",
+        "isbn",
+        "copy_id",
+        "acquisition_date",
+        "loan_count",
+        "last_loaned",
+        "note",
+    ])?;
+    for item in items {
+        writer.write_record([
+            day(&item.withdrawn_at),
+            item.reason.as_deref().unwrap_or(""),
+            &item.title,
+            item.isbn.as_deref().unwrap_or(""),
+            &item.copy_id,
+            day(&item.acquisition_date),
+            &item.loan_count.to_string(),
+            day(&item.last_loaned),
+            item.note.as_deref().unwrap_or(""),
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The report as a printable PDF, one line per copy.
+pub fn report_pdf(items: &[WeedingItem], title: &str) -> Vec<u8> {
+    let mut lines = vec![
+        format!(
+            "{:<10}  {:<9}  {:>5}  {:<10}  {}",
+            "Withdrawn", "Reason", "Loans", "Last loan", "Title"
+        ),
+        "-".repeat(70),
+    ];
+    lines.extend(items.iter().map(|item| {
+        format!(
+            "{:<10}  {:<9}  {:>5}  {:<10}  {}",
+            day(&item.withdrawn_at),
+            item.reason.as_deref().unwrap_or(""),
+            item.loan_count,
+            day(&item.last_loaned),
+            item.title
+        )
+    }));
+    lines.push(String::new());
+    lines.push(format!("{} cop(ies) withdrawn", items.len()));
+    crate::utils::text_pdf::text_pdf(title, &lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn withdrawing_keeps_the_copy_and_its_loans() {
+        let db = setup().await;
+        let now = Utc::now().to_rfc3339();
+        let dune = book::ActiveModel {
+            title: Set("Dune".to_string()),
+            owned: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for status in ["available", "loaned"] {
+            let c = copy::ActiveModel {
+                book_id: Set(dune.id.clone()),
+                library_id: Set(1),
+                status: Set(status.to_string()),
+                is_temporary: Set(false),
+                created_at: Set(now.clone()),
+                updated_at: Set(now.clone()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            ids.push(c.id);
+        }
+        let contact = crate::models::contact::ActiveModel {
+            r#type: Set("Borrower".to_string()),
+            name: Set("Alice".to_string()),
+            library_owner_id: Set(1),
+            is_active: Set(true),
+            created_at: Set(now.clone()),
+            updated_at: Set(now.clone()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        loan::ActiveModel {
+            copy_id: Set(ids[0].clone()),
+            contact_id: Set(contact.id),
+            library_id: Set(1),
+            loan_date: Set("2019-05-01".to_string()),
+            due_date: Set("2019-05-22".to_string()),
+            return_date: Set(Some("2019-05-20".to_string())),
+            status: Set("returned".to_string()),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            withdraw(&db, &ids[..1]).await,
+            Err(WeedingError::NotFlagged(_))
+        ));
+        for id in &ids {
+            flag(&db, id, WeedingReason::Worn, None).await.unwrap();
+        }
+        let review = review_list(&db).await.unwrap();
+        assert_eq!(review.len(), 2);
+        let first = review.iter().find(|i| i.copy_id == ids[0]).unwrap();
+        assert_eq!(
+            (first.loan_count, first.last_loaned.as_deref()),
+            (1, Some("2019-05-01"))
+        );
+
+        // The loaned copy blocks the batch; nothing is withdrawn.
+        assert!(matches!(
+            withdraw(&db, &ids).await,
+            Err(WeedingError::Out(_, _))
+        ));
+        assert!(withdrawn_report(&db, None, None).await.unwrap().is_empty());
+
+        withdraw(&db, &ids[..1]).await.unwrap();
+        let report = withdrawn_report(&db, None, None).await.unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].reason.as_deref(), Some("worn"));
+        assert_eq!(report[0].loan_count, 1);
+        assert_eq!(
+            loan::Entity::find()
+                .filter(loan::Column::CopyId.eq(ids[0].as_str()))
+                .count(&db)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(review_list(&db).await.unwrap().len(), 1);
+        assert!(report_csv(&report).unwrap().contains(",worn,Dune,"));
+    }
+}
@@ -0,0 +1,492 @@
+//! Works: the editions and translations of one text, grouped (FRBR-lite).
+//!
+//! A book is clustered by the work identifiers its catalogue source gave it,
+//! kept in `books.source_data`: the Open Library work key, the Inventaire
+//! work URI, the BNF work authority record. Books sharing any of them end up
+//! in one work, and a work whose identifiers meet another's is merged into
+//! it. The owner can move a book to another work or out of any; clustering
+//! then leaves it where it was put.
+//!
+//! Search (`collapse_editions`) and the year in books use works to count a
+//! text once, however many editions of it the library holds.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    book, work, work_book,
+    work_identifier::{self, SCHEME_BNF, SCHEME_INVENTAIRE, SCHEME_OPENLIBRARY},
+};
+
+#[derive(Debug)]
+pub enum WorkError {
+    Db(DbErr),
+    BookNotFound,
+    NotFound,
+}
+
+impl std::fmt::Display for WorkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "database error: {e}"),
+            Self::BookNotFound => write!(f, "book not found"),
+            Self::NotFound => write!(f, "work not found"),
+        }
+    }
+}
+
+impl std::error::Error for WorkError {}
+
+impl From<DbErr> for WorkError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// One edition of a work.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkEdition {
+    pub book_id: String,
+    pub title: String,
+    pub publisher: Option<String>,
+    pub publication_year: Option<i32>,
+}
+
+/// A work with its identifiers and editions, oldest first.
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkDetail {
+    #[schema(value_type = Work)]
+    pub work: work::Model,
+    #[schema(value_type = Vec<WorkIdentifier>)]
+    pub identifiers: Vec<work_identifier::Model>,
+    pub editions: Vec<WorkEdition>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WorkSummary {
+    #[schema(value_type = Work)]
+    pub work: work::Model,
+    pub editions: u32,
+}
+
+/// What a clustering pass changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ClusterReport {
+    /// Books put in a work, or moved to another one
+    pub books_clustered: u32,
+    pub works_created: u32,
+    /// Works folded into another that shared an identifier
+    pub works_merged: u32,
+}
+
+/// The book's work, or `None` to take it out of any.
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct SetBookWork {
+    pub work_id: Option<String>,
+}
+
+/// Last path segment of `/works/OL45883W`, or the key itself.
+fn openlibrary_work(key: &str) -> Option<String> {
+    let id = key.rsplit('/').next().unwrap_or(key);
+    let digits = id.strip_prefix("OL")?.strip_suffix('W')?;
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+/// The work identifiers in a book's `source_data`: `work_key`, or `key`
+/// when it names a work (Open Library search results); `work_uri`, or `uri`
+/// on an Inventaire result (a work); `bnf_work`.
+pub fn identifiers(source_data: &str) -> Vec<(String, String)> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(source_data) else {
+        return Vec::new();
+    };
+    let text = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    let mut found = Vec::new();
+    if let Some(key) = text("work_key")
+        .or_else(|| text("key").filter(|k| k.contains("/works/")))
+        .and_then(openlibrary_work)
+    {
+        found.push((SCHEME_OPENLIBRARY.to_string(), key));
+    }
+    let inventaire_uri =
+        text("work_uri").or_else(|| text("uri").filter(|_| text("source") == Some("inventaire")));
+    if let Some(uri) = inventaire_uri.filter(|u| u.starts_with("wd:") || u.starts_with("inv:")) {
+        found.push((SCHEME_INVENTAIRE.to_string(), uri.to_string()));
+    }
+    if let Some(record) = text("bnf_work") {
+        found.push((SCHEME_BNF.to_string(), record.to_string()));
+    }
+    found
+}
+
+/// Move the identifiers and editions of `from` to `into`, then drop `from`.
+async fn merge<C: ConnectionTrait>(db: &C, from: &str, into: &str) -> Result<(), DbErr> {
+    work_identifier::Entity::update_many()
+        .col_expr(work_identifier::Column::WorkId, Expr::value(into))
+        .filter(work_identifier::Column::WorkId.eq(from))
+        .exec(db)
+        .await?;
+    work_book::Entity::update_many()
+        .col_expr(work_book::Column::WorkId, Expr::value(into))
+        .filter(work_book::Column::WorkId.eq(from))
+        .exec(db)
+        .await?;
+    work::Entity::delete_by_id(from.to_string())
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+async fn link<C: ConnectionTrait>(
+    db: &C,
+    book_id: &str,
+    work_id: Option<String>,
+    manual: bool,
+) -> Result<(), DbErr> {
+    work_book::Entity::delete_by_id(book_id.to_string())
+        .exec(db)
+        .await?;
+    work_book::ActiveModel {
+        book_id: Set(book_id.to_string()),
+        work_id: Set(work_id),
+        manual: Set(manual),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Cluster every book not placed by hand, oldest first, by its work
+/// identifiers.
+pub async fn cluster(db: &DatabaseConnection) -> Result<ClusterReport, DbErr> {
+    let books = book::Entity::find()
+        .filter(book::Column::SourceData.is_not_null())
+        .order_by_asc(book::Column::CreatedAt)
+        .all(db)
+        .await?;
+    let mut links: HashMap<String, work_book::Model> = work_book::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|l| (l.book_id.clone(), l))
+        .collect();
+    let mut owners: HashMap<(String, String), String> = work_identifier::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|i| ((i.scheme, i.value), i.work_id))
+        .collect();
+
+    let mut report = ClusterReport::default();
+    let txn = db.begin().await?;
+    for b in books {
+        let current = match links.get(&b.id) {
+            Some(l) if l.manual => continue,
+            Some(l) => l.work_id.clone(),
+            None => None,
+        };
+        let ids = identifiers(b.source_data.as_deref().unwrap_or_default());
+        if ids.is_empty() {
+            continue;
+        }
+
+        let mut works: Vec<String> = Vec::new();
+        for work_id in ids
+            .iter()
+            .filter_map(|k| owners.get(k))
+            .chain(current.iter())
+        {
+            if !works.contains(work_id) {
+                works.push(work_id.clone());
+            }
+        }
+        let target = match works.first() {
+            Some(work_id) => work_id.clone(),
+            None => {
+                let now = Utc::now().to_rfc3339();
+                let created = work::ActiveModel {
+                    id: Set(crate::utils::uuid_gen::new_uuid_v7()),
+                    title: Set(b.title.clone()),
+                    created_at: Set(now.clone()),
+                    updated_at: Set(now),
+                }
+                .insert(&txn)
+                .await?;
+                report.works_created += 1;
+                created.id
+            }
+        };
+        for other in works.iter().skip(1) {
+            merge(&txn, other, &target).await?;
+            for owner in owners.values_mut().filter(|w| **w == *other) {
+                *owner = target.clone();
+            }
+            for l in links.values_mut() {
+                if l.work_id.as_ref() == Some(other) {
+                    l.work_id = Some(target.clone());
+                }
+            }
+            report.works_merged += 1;
+        }
+        for key in ids {
+            if let Entry::Vacant(owner) = owners.entry(key) {
+                work_identifier::ActiveModel {
+                    scheme: Set(owner.key().0.clone()),
+                    value: Set(owner.key().1.clone()),
+                    work_id: Set(target.clone()),
+                }
+                .insert(&txn)
+                .await?;
+                owner.insert(target.clone());
+            }
+        }
+        if links.get(&b.id).and_then(|l| l.work_id.as_ref()) != Some(&target) {
+            link(&txn, &b.id, Some(target.clone()), false).await?;
+            links.insert(
+                b.id.clone(),
+                work_book::Model {
+                    book_id: b.id,
+                    work_id: Some(target),
+                    manual: false,
+                },
+            );
+            report.books_clustered += 1;
+        }
+    }
+    txn.commit().await?;
+    Ok(report)
+}
+
+/// The work of each of `book_ids` that is in one.
+pub async fn works_of(
+    db: &DatabaseConnection,
+    book_ids: &[String],
+) -> Result<HashMap<String, String>, DbErr> {
+    if book_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(work_book::Entity::find()
+        .filter(work_book::Column::BookId.is_in(book_ids.iter().cloned()))
+        .filter(work_book::Column::WorkId.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|l| Some((l.book_id, l.work_id?)))
+        .collect())
+}
+
+/// `books` with only the first edition of each work; books outside any
+/// work, or not in this library, are all kept.
+pub async fn collapse_editions(
+    db: &DatabaseConnection,
+    books: Vec<book::Book>,
+) -> Result<Vec<book::Book>, DbErr> {
+    let ids: Vec<String> = books.iter().filter_map(|b| b.id.clone()).collect();
+    let works = works_of(db, &ids).await?;
+    let mut seen = HashSet::new();
+    Ok(books
+        .into_iter()
+        .filter(|b| match b.id.as_ref().and_then(|id| works.get(id)) {
+            Some(work_id) => seen.insert(work_id.clone()),
+            None => true,
+        })
+        .collect())
+}
+
+/// Works holding at least one edition, by title.
+pub async fn list_works(db: &DatabaseConnection) -> Result<Vec<WorkSummary>, DbErr> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for l in work_book::Entity::find()
+        .filter(work_book::Column::WorkId.is_not_null())
+        .all(db)
+        .await?
+    {
+        *counts.entry(l.work_id.unwrap_or_default()).or_insert(0) += 1;
+    }
+    Ok(work::Entity::find()
+        .order_by_asc(work::Column::Title)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|w| {
+            let editions = *counts.get(&w.id)?;
+            Some(WorkSummary { work: w, editions })
+        })
+        .collect())
+}
+
+/// One work with its identifiers and editions.
+pub async fn get_work(db: &DatabaseConnection, id: &str) -> Result<Option<WorkDetail>, DbErr> {
+    let Some(found) = work::Entity::find_by_id(id.to_string()).one(db).await? else {
+        return Ok(None);
+    };
+    let identifiers = work_identifier::Entity::find()
+        .filter(work_identifier::Column::WorkId.eq(id))
+        .order_by_asc(work_identifier::Column::Scheme)
+        .all(db)
+        .await?;
+    let book_ids: Vec<String> = work_book::Entity::find()
+        .filter(work_book::Column::WorkId.eq(id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|l| l.book_id)
+        .collect();
+    let editions = book::Entity::find()
+        .filter(book::Column::Id.is_in(book_ids))
+        .order_by_asc(book::Column::PublicationYear)
+        .order_by_asc(book::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| WorkEdition {
+            book_id: b.id,
+            title: b.title,
+            publisher: b.publisher,
+            publication_year: b.publication_year,
+        })
+        .collect();
+    Ok(Some(WorkDetail {
+        work: found,
+        identifiers,
+        editions,
+    }))
+}
+
+/// Put a book in a work, or out of any, by hand. Returns the book's work.
+pub async fn set_book_work(
+    db: &DatabaseConnection,
+    book_id: &str,
+    work_id: Option<String>,
+) -> Result<Option<WorkDetail>, WorkError> {
+    if book::Entity::find_by_id(book_id.to_string())
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Err(WorkError::BookNotFound);
+    }
+    if let Some(id) = &work_id
+        && work::Entity::find_by_id(id.clone())
+            .one(db)
+            .await?
+            .is_none()
+    {
+        return Err(WorkError::NotFound);
+    }
+    link(db, book_id, work_id.clone(), true).await?;
+    match work_id {
+        Some(id) => Ok(get_work(db, &id).await?),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> DatabaseConnection {
+        crate::db::init_db("sqlite::memory:").await.unwrap()
+    }
+
+    async fn insert_book(db: &DatabaseConnection, title: &str, source_data: &str) -> String {
+        let now = Utc::now().to_rfc3339();
+        book::ActiveModel {
+            title: Set(title.to_string()),
+            source_data: Set(Some(source_data.to_string())),
+            created_at: Set(now.clone()),
+            updated_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn reads_work_identifiers_from_source_data() {
+        assert_eq!(
+            identifiers(r#"{"source":"openlibrary","key":"/works/OL45883W"}"#),
+            [("openlibrary".to_string(), "OL45883W".to_string())]
+        );
+        assert_eq!(
+            identifiers(r#"{"source":"inventaire","uri":"wd:Q180736"}"#),
+            [("inventaire".to_string(), "wd:Q180736".to_string())]
+        );
+        assert!(
+            identifiers(r#"{"source":"google","uri":"wd:Q180736","key":"/books/OL1M"}"#).is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn editions_sharing_an_identifier_become_one_work() {
+        let db = setup().await;
+        let folio = insert_book(&db, "Le Petit Prince", r#"{"bnf_work":"11934938"}"#).await;
+        let english = insert_book(
+            &db,
+            "The Little Prince",
+            r#"{"source":"inventaire","uri":"wd:Q25338"}"#,
+        )
+        .await;
+        let bridge = insert_book(
+            &db,
+            "Le Petit Prince (poche)",
+            r#"{"bnf_work":"11934938","work_uri":"wd:Q25338"}"#,
+        )
+        .await;
+        let other = insert_book(&db, "Vol de nuit", r#"{"bnf_work":"12000000"}"#).await;
+
+        let report = cluster(&db).await.unwrap();
+        assert_eq!(report.works_created, 3);
+        assert_eq!(report.works_merged, 1);
+        let works = works_of(
+            &db,
+            &[
+                folio.clone(),
+                english.clone(),
+                bridge.clone(),
+                other.clone(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(works[&folio], works[&english]);
+        assert_ne!(works[&folio], works[&other]);
+        assert_eq!(cluster(&db).await.unwrap(), ClusterReport::default());
+
+        set_book_work(&db, &english, None).await.unwrap();
+        cluster(&db).await.unwrap();
+        assert!(
+            !works_of(&db, std::slice::from_ref(&english))
+                .await
+                .unwrap()
+                .contains_key(&english)
+        );
+
+        // The bridge edition goes: the first edition already stands for
+        // its work.
+        let collapsed = collapse_editions(
+            &db,
+            [&folio, &english, &bridge, &other]
+                .into_iter()
+                .map(|id| book::Book {
+                    id: Some(id.clone()),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(collapsed.len(), 3);
+    }
+}
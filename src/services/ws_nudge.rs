@@ -5,8 +5,13 @@
 //! of waiting for the next polling interval. The existing 20s polling loop
 //! remains as fallback.
 //!
+//! The connection honours the network mode like the HTTP clients do: it is
+//! only opened to relay addresses the mode allows, so a LAN-only or offline
+//! library never reaches a public hub.
+//!
 //! See ADR-017 for architecture details.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -16,6 +21,7 @@ use tokio_tungstenite::tungstenite;
 
 use crate::api::relay::get_my_relay_config;
 use crate::infrastructure::AppState;
+use crate::services::network_mode::{self, NetworkMode};
 use crate::services::nudge_events::NudgeSource;
 use crate::services::relay_poller::poll_once_wait;
 
@@ -32,7 +38,8 @@ const NO_CONFIG_RETRY: Duration = Duration::from_secs(60);
 ///
 /// Runs forever. Connects to the Hub's WS sidecar, listens for nudge signals,
 /// and triggers `poll_once_wait()` on each nudge. Reconnects with exponential backoff
-/// on disconnection.
+/// on disconnection. While the network mode forbids the relay's addresses it
+/// retries every 60s without connecting.
 ///
 /// A single background poll worker is spawned once and shared across all WS
 /// connections. The WS message loop is non-blocking: nudges signal the worker
@@ -96,9 +103,24 @@ pub async fn start_ws_nudge(state: AppState) {
             }
         };
 
+        let addrs = match relay_addrs(network_mode::current(), &ws_url).await {
+            Ok(addrs) => addrs,
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                tracing::debug!("WS nudge: {e}, retrying in 60s");
+                tokio::time::sleep(NO_CONFIG_RETRY).await;
+                continue;
+            }
+            Err(e) => {
+                tracing::warn!("WS nudge: cannot resolve relay: {e}, retrying in {backoff:?}");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+        };
+
         tracing::info!("WS nudge: connecting to {ws_url}");
 
-        match connect_and_listen(&ws_url, &config.read_token, &poll_notify).await {
+        match connect_and_listen(&ws_url, &addrs, &config.read_token, &poll_notify).await {
             Ok(()) => {
                 // Clean disconnect (server closed). Reconnect immediately.
                 tracing::info!("WS nudge: connection closed, reconnecting");
@@ -158,13 +180,25 @@ fn build_ws_url(relay_url: &str, mailbox_uuid: &str) -> Option<String> {
     Some(format!("{base}/ws?mailbox_id={mailbox_uuid}"))
 }
 
-/// Connect to the WS sidecar and listen for nudges.
+/// The relay addresses `mode` allows for `ws_url`, failing with
+/// `ErrorKind::PermissionDenied` when it allows none.
+async fn relay_addrs(mode: NetworkMode, ws_url: &str) -> std::io::Result<Vec<SocketAddr>> {
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidInput, ws_url.to_string());
+    let url = url::Url::parse(ws_url).map_err(|_| invalid())?;
+    let host = url.host_str().ok_or_else(invalid)?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().ok_or_else(invalid)?;
+    network_mode::allowed_addrs(mode, host, port).await
+}
+
+/// Connect to the WS sidecar at one of `addrs` and listen for nudges.
 ///
 /// Returns `Ok(())` on clean disconnect, `Err` on connection or protocol error.
 /// The `notify` reference is passed to `handle_nudge_message` so each nudge
 /// signals the poll worker without blocking this loop.
 async fn connect_and_listen(
     ws_url: &str,
+    addrs: &[SocketAddr],
     read_token: &str,
     notify: &Arc<Notify>,
 ) -> Result<(), String> {
@@ -183,7 +217,12 @@ async fn connect_and_listen(
         .body(())
         .map_err(|e| format!("failed to build request: {e}"))?;
 
-    let (ws_stream, _response) = tokio_tungstenite::connect_async(request)
+    // Connect to the vetted addresses ourselves rather than letting
+    // tungstenite resolve the host again.
+    let stream = tokio::net::TcpStream::connect(addrs)
+        .await
+        .map_err(|e| format!("connection failed: {e}"))?;
+    let (ws_stream, _response) = tokio_tungstenite::client_async_tls(request, stream)
         .await
         .map_err(|e| format!("connection failed: {e}"))?;
 
@@ -304,6 +343,31 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn relay_is_only_reached_where_the_network_mode_allows() {
+        let public = "wss://93.184.216.34/ws?mailbox_id=abc";
+        assert_eq!(
+            relay_addrs(NetworkMode::Full, public).await.unwrap(),
+            ["93.184.216.34:443".parse::<SocketAddr>().unwrap()]
+        );
+        for mode in [NetworkMode::LanOnly, NetworkMode::Offline] {
+            let err = relay_addrs(mode, public).await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        }
+        let err = relay_addrs(NetworkMode::Offline, "wss://hub.bibliogenius.org/ws")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+        // A relay on the LAN stays reachable in LAN-only mode.
+        assert_eq!(
+            relay_addrs(NetworkMode::LanOnly, "ws://192.168.1.5:9091/ws")
+                .await
+                .unwrap(),
+            ["192.168.1.5:9091".parse::<SocketAddr>().unwrap()]
+        );
+    }
+
     // --- Poll worker coalescing behavior ---
 
     /// Tokio's Notify stores at most one pending token regardless of how many
@@ -161,17 +161,26 @@ pub async fn log_remote_operation(
     // collection_book, etc.) - their identity is in the payload, not entity_id.
     // Dedup by (entity_type, 0, operation) would incorrectly merge all of them.
     // Skip dedup for these; the processor handles duplicates via INSERT OR IGNORE.
+    //
+    // UPDATEs and DELETEs only count as duplicates with an identical payload:
+    // the second edit of an entity is a new change, not a replay of the first.
     let op_upper = operation.to_uppercase();
+    let payload = payload.map(|v| v.to_string());
 
     if !entity_id.is_empty() {
-        let already_exists = operation_log::Entity::find()
+        let mut query = operation_log::Entity::find()
             .filter(operation_log::Column::EntityType.eq(entity_type))
             .filter(operation_log::Column::EntityId.eq(entity_id))
             .filter(operation_log::Column::Operation.eq(operation))
             .filter(operation_log::Column::Status.is_in(["pending", "pending_review", "applied"]))
-            .filter(operation_log::Column::Source.ne("local"))
-            .one(db)
-            .await?;
+            .filter(operation_log::Column::Source.ne("local"));
+        if op_upper != "INSERT" {
+            query = match &payload {
+                Some(p) => query.filter(operation_log::Column::Payload.eq(p.as_str())),
+                None => query.filter(operation_log::Column::Payload.is_null()),
+            };
+        }
+        let already_exists = query.one(db).await?;
 
         if let Some(existing) = already_exists {
             // For INSERTs: allow re-creation if the entity was deleted locally since
@@ -223,7 +232,7 @@ pub async fn log_remote_operation(
         entity_type: Set(entity_type.to_owned()),
         entity_id: Set(entity_id.to_owned()),
         operation: Set(operation.to_owned()),
-        payload: Set(payload.map(|v| v.to_string())),
+        payload: Set(payload),
         pinned: Set(0),
         source: Set(format!("device:{source_device_id}")),
         status: Set(status.to_owned()),
@@ -1,28 +1,59 @@
+use crate::infrastructure::shutdown::Shutdown;
 use crate::models::{
-    author, book, collection, collection_book, contact, copy, loan, operation_log, tag,
+    author, book, collection, collection_book, contact, copy, library_config, loan, operation_log,
+    tag,
 };
 use sea_orm::*;
 use serde_json::Value;
 use std::time::Duration;
 
 pub async fn run_processor(db: DatabaseConnection) {
+    run_processor_until(db, Shutdown::new()).await;
+}
+
+/// [`run_processor`] until `shutdown` is triggered, then apply what is still
+/// pending and return. An operation being applied when the signal arrives is
+/// finished first.
+pub async fn run_processor_until(db: DatabaseConnection, shutdown: Shutdown) {
     tracing::info!("🔄 Operation Processor started");
 
-    loop {
-        match process_next_batch(&db).await {
+    while !shutdown.is_triggered() {
+        // Only the idle waits race the shutdown signal: an operation, once
+        // picked, is applied to the end.
+        let idle = match process_one(&db).await {
+            Ok(true) => continue,
+            Ok(false) => Duration::from_secs(2),
             Err(e) => {
                 tracing::error!("❌ Error processing operations: {}", e);
-                tokio::time::sleep(Duration::from_secs(5)).await;
-            }
-            _ => {
-                // If no operations were found, verify inside process_next_batch if we should sleep
-                // For simplicity here, we assume process_next_batch handles the sleep if empty
+                Duration::from_secs(5)
             }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(idle) => {}
+            _ = shutdown.triggered() => break,
         }
     }
+
+    match process_pending(&db).await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!("🔄 Operation Processor flushed {} pending operation(s)", n),
+        Err(e) => tracing::warn!("Operation Processor flush on shutdown failed: {}", e),
+    }
+    tracing::info!("🔄 Operation Processor stopped");
 }
 
-async fn process_next_batch(db: &DatabaseConnection) -> Result<(), DbErr> {
+/// Apply every pending operation now, without the idle sleep of
+/// [`run_processor`]. Returns how many operations were processed.
+pub async fn process_pending(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let mut processed = 0;
+    while process_one(db).await? {
+        processed += 1;
+    }
+    Ok(processed)
+}
+
+/// Process the oldest pending operation, if any. Returns whether there was one.
+async fn process_one(db: &DatabaseConnection) -> Result<bool, DbErr> {
     // Fetch one pending operation (FIFO, deterministic: created_at then id)
     let pending_op = operation_log::Entity::find()
         .filter(operation_log::Column::Status.eq("pending"))
@@ -31,40 +62,46 @@ async fn process_next_batch(db: &DatabaseConnection) -> Result<(), DbErr> {
         .one(db)
         .await?;
 
-    match pending_op {
-        Some(op) => {
-            // Skip local operations — they are already applied by the handler that created them.
-            // Only operations received from peers (source != "local") need to be replayed.
-            if op.source == "local" {
-                let mut active_op: operation_log::ActiveModel = op.into();
-                active_op.status = Set("applied".to_string());
-                active_op.save(db).await?;
-                return Ok(());
-            }
+    let Some(op) = pending_op else {
+        return Ok(false);
+    };
 
-            tracing::info!(
-                "⚙️ Processing Op #{}: {} on {} {}",
-                op.id,
-                op.operation,
-                op.entity_type,
-                op.entity_id
-            );
-            apply_operation(db, op).await?;
-            // Don't sleep, process next immediately
-        }
-        None => {
-            // No pending operations, sleep a bit
-            tokio::time::sleep(Duration::from_secs(2)).await;
-        }
+    // Skip local operations — they are already applied by the handler that created them.
+    // Only operations received from peers (source != "local") need to be replayed.
+    if op.source == "local" {
+        let mut active_op: operation_log::ActiveModel = op.into();
+        active_op.status = Set("applied".to_string());
+        active_op.save(db).await?;
+        return Ok(true);
     }
 
-    Ok(())
+    tracing::info!(
+        "⚙️ Processing Op #{}: {} on {} {}",
+        op.id,
+        op.operation,
+        op.entity_type,
+        op.entity_id
+    );
+    apply_operation(db, op).await?;
+    Ok(true)
 }
 
 async fn apply_operation(db: &DatabaseConnection, op: operation_log::Model) -> Result<(), DbErr> {
     // Start transaction
     let txn = db.begin().await?;
 
+    // Last write wins: an update made before the local row last changed is
+    // dropped, not applied over the newer local state.
+    if let Some(reason) = update_conflict(&txn, &op).await? {
+        tracing::info!("Skipping Op #{}: {}", op.id, reason);
+        let mut active_op: operation_log::ActiveModel = op.into();
+        active_op.status = Set("skipped".to_string());
+        active_op.error_message = Set(Some(reason));
+        active_op.save(&txn).await?;
+        txn.commit().await?;
+        return Ok(());
+    }
+
     let result = match (
         op.entity_type.to_lowercase().as_str(),
         op.operation.to_lowercase().as_str(),
@@ -86,9 +123,11 @@ async fn apply_operation(db: &DatabaseConnection, op: operation_log::Model) -> R
         ("loan", "update") => apply_loan_update(&txn, &op).await,
         // Tags
         ("tag", "insert") => apply_tag_create(&txn, &op).await,
+        ("tag", "update") => apply_tag_update(&txn, &op).await,
         ("tag", "delete") => apply_delete::<tag::Entity>(&txn, op.entity_id.clone()).await,
         // Authors
         ("author", "insert") => apply_author_create(&txn, &op).await,
+        ("author", "update") => apply_author_update(&txn, &op).await,
         ("author", "delete") => apply_delete::<author::Entity>(&txn, op.entity_id.clone()).await,
         // Book-Author / Book-Tag junction tables (resolved by natural keys)
         ("book_author", "insert") => apply_book_author_insert(&txn, &op).await,
@@ -103,6 +142,8 @@ async fn apply_operation(db: &DatabaseConnection, op: operation_log::Model) -> R
         // Book notes (device sync only)
         ("book_note", "insert") => apply_book_note_create(&txn, &op).await,
         ("book_note", "update") => apply_book_note_update(&txn, &op).await,
+        // Library settings (device sync only)
+        ("library_config", "update") => apply_library_config_update(&txn, &op).await,
         ("book_note", "delete") => {
             // book_notes keeps an integer PK; entity_id is the stringified int id.
             let nid: i32 = op.entity_id.parse().unwrap_or(0);
@@ -154,6 +195,17 @@ async fn apply_book_create(
 
     let title = payload["title"].as_str().unwrap_or("Unknown").to_string();
     let isbn = payload["isbn"].as_str().map(|s| s.to_string());
+    let remote_id = remote_id(&payload);
+
+    // Deduplication: skip if the book was already replicated under its id
+    if let Some(ref id) = remote_id
+        && book::Entity::find_by_id(id.clone())
+            .one(db)
+            .await?
+            .is_some()
+    {
+        return Ok(());
+    }
 
     // Deduplication: skip if a book with the same ISBN already exists
     if let Some(ref isbn_val) = isbn
@@ -192,26 +244,47 @@ async fn apply_book_create(
     let publisher = payload["publisher"].as_str().map(|s| s.to_string());
     let publication_year = payload["publication_year"].as_i64().map(|v| v as i32);
     let page_count = payload["page_count"].as_i64().map(|v| v as i32);
+    let edition = payload["edition"].as_str().map(|s| s.to_string());
+    let physical_format = payload["physical_format"]
+        .as_str()
+        .and_then(crate::models::book::normalize_physical_format)
+        .map(str::to_string);
+    let dimensions = payload["dimensions"].as_str().map(|s| s.to_string());
+    let digital_source_url = payload["digital_source_url"]
+        .as_str()
+        .map(|s| s.to_string());
     let now = chrono::Utc::now().to_rfc3339();
 
     // Restore subjects (shelf/tag assignments stored as JSON array in the book)
-    let subjects = payload.get("subjects").and_then(|v| {
-        if v.is_null() {
-            None
-        } else {
-            Some(v.to_string())
-        }
-    });
+    let subjects = subjects_column(&payload);
 
+    let started_reading_at = payload["started_reading_at"]
+        .as_str()
+        .map(|s| s.to_string());
+    let finished_reading_at = payload["finished_reading_at"]
+        .as_str()
+        .map(|s| s.to_string());
+    let user_rating = payload["user_rating"].as_i64().map(|v| v as i32);
+
+    // A device-sync snapshot keeps the origin's id, so later updates, copies
+    // and junction rows find the book under the same key here.
     let new_book = book::ActiveModel {
+        id: remote_id.clone().map_or(NotSet, Set),
         title: Set(title),
         isbn: Set(isbn),
         summary: Set(summary),
         publisher: Set(publisher),
         publication_year: Set(publication_year),
         page_count: Set(page_count),
+        edition: Set(edition),
+        physical_format: Set(physical_format),
+        dimensions: Set(dimensions),
+        digital_source_url: Set(digital_source_url),
         owned: Set(owned),
         reading_status: Set(reading_status.clone()),
+        started_reading_at: Set(started_reading_at),
+        finished_reading_at: Set(finished_reading_at),
+        user_rating: Set(user_rating),
         cover_url: Set(cover_url),
         subjects: Set(subjects),
         created_at: Set(now.clone()),
@@ -264,8 +337,9 @@ async fn apply_book_create(
         }
     }
 
-    // Create default copy for owned books (matches local book creation behavior)
-    if owned {
+    // Create default copy for owned books (matches local book creation behavior).
+    // Device sync replicates the origin's copies themselves, under their ids.
+    if owned && remote_id.is_none() {
         let lib_id = crate::utils::library_helpers::resolve_library_id(db).await?;
         // Dedup: skip if a copy already exists for this book
         let existing_copy = copy::Entity::find()
@@ -345,6 +419,104 @@ fn parse_payload(op: &operation_log::Model) -> Result<Value, DbErr> {
     serde_json::from_str(payload_str).map_err(|e| DbErr::Custom(e.to_string()))
 }
 
+/// Id of the entity on the device that made the change, carried by device
+/// sync snapshots (`services::device_sync`). `None` for other payloads.
+fn remote_id(payload: &Value) -> Option<String> {
+    payload["id"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// When the change was made on its origin device (`_origin_at`, set by
+/// device sync), falling back to now. Stored as the row's `updated_at` so
+/// the last-write-wins check compares like with like.
+fn origin_at(payload: &Value) -> String {
+    payload["_origin_at"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339())
+}
+
+/// A nullable text field of an update payload: `None` when the key is
+/// absent (leave the column alone), `Some(None)` when it is null.
+fn optional_str(payload: &Value, key: &str) -> Option<Option<String>> {
+    payload.get(key).map(|v| v.as_str().map(str::to_string))
+}
+
+/// Same as [`optional_str`] for integer fields.
+fn optional_i32(payload: &Value, key: &str) -> Option<Option<i32>> {
+    payload.get(key).map(|v| v.as_i64().map(|n| n as i32))
+}
+
+/// `books.subjects` from a payload: a JSON array (or its string form, as
+/// stored in the column), NULL when absent or null.
+fn subjects_column(payload: &Value) -> Option<String> {
+    match payload.get("subjects") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(v) => Some(v.to_string()),
+    }
+}
+
+/// Last-write-wins check for a replicated UPDATE. Returns why the operation
+/// must be skipped when the local row changed after `_origin_at`. Operations
+/// without `_origin_at` (or with unparsable dates) are always applied.
+async fn update_conflict(
+    db: &DatabaseTransaction,
+    op: &operation_log::Model,
+) -> Result<Option<String>, DbErr> {
+    if !op.operation.eq_ignore_ascii_case("update") {
+        return Ok(None);
+    }
+    let Some(origin) = op
+        .payload
+        .as_deref()
+        .and_then(|p| serde_json::from_str::<Value>(p).ok())
+        .and_then(|p| p["_origin_at"].as_str().map(str::to_string))
+    else {
+        return Ok(None);
+    };
+
+    let id = op.entity_id.clone();
+    let local = match op.entity_type.to_lowercase().as_str() {
+        "book" => book::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|m| m.updated_at),
+        "copy" => copy::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|m| m.updated_at),
+        "contact" => contact::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|m| m.updated_at),
+        "loan" => loan::Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .map(|m| m.updated_at),
+        "library_config" => library_config::Entity::find()
+            .one(db)
+            .await?
+            .map(|m| m.updated_at),
+        _ => None,
+    };
+    let Some(local) = local else {
+        return Ok(None);
+    };
+
+    match (
+        chrono::DateTime::parse_from_rfc3339(&local),
+        chrono::DateTime::parse_from_rfc3339(&origin),
+    ) {
+        (Ok(l), Ok(o)) if l > o => Ok(Some(format!(
+            "conflict: local change at {local} is newer than remote change at {origin}"
+        ))),
+        _ => Ok(None),
+    }
+}
+
 /// Generic delete by entity ID (works for any entity with i32 PK)
 async fn apply_delete<E>(db: &DatabaseTransaction, id: String) -> Result<(), DbErr>
 where
@@ -365,24 +537,69 @@ async fn apply_book_update(
         .await?;
 
     if let Some(existing_book) = book {
-        // CONFLICT RESOLUTION (Last-Write-Wins)
-        // Compare op.created_at with existing_book.updated_at
-        // For now, we assume Op is authoritative for demo purposes
-
-        let payload_str = op
-            .payload
-            .as_ref()
-            .ok_or(DbErr::Custom("No payload".to_string()))?;
-        let payload: Value =
-            serde_json::from_str(payload_str).map_err(|e| DbErr::Custom(e.to_string()))?;
-
+        // Conflicts (last write wins) were settled by `update_conflict`.
+        let payload = parse_payload(op)?;
         let mut active_book: book::ActiveModel = existing_book.into();
 
         if let Some(t) = payload.get("title").and_then(|v| v.as_str()) {
             active_book.title = Set(t.to_string());
         }
+        if let Some(s) = payload.get("reading_status").and_then(|v| v.as_str()) {
+            active_book.reading_status = Set(s.to_string());
+        }
+        if let Some(o) = payload.get("owned").and_then(|v| v.as_bool()) {
+            active_book.owned = Set(o);
+        }
+        if let Some(a) = payload.get("archived").and_then(|v| v.as_bool()) {
+            active_book.archived = Set(a);
+        }
+        if let Some(v) = optional_str(&payload, "isbn") {
+            active_book.isbn = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "summary") {
+            active_book.summary = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "publisher") {
+            active_book.publisher = Set(v);
+        }
+        if let Some(v) = optional_i32(&payload, "publication_year") {
+            active_book.publication_year = Set(v);
+        }
+        if let Some(v) = optional_i32(&payload, "page_count") {
+            active_book.page_count = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "edition") {
+            active_book.edition = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "physical_format") {
+            active_book.physical_format = Set(v
+                .as_deref()
+                .and_then(crate::models::book::normalize_physical_format)
+                .map(str::to_string));
+        }
+        if let Some(v) = optional_str(&payload, "dimensions") {
+            active_book.dimensions = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "digital_source_url") {
+            active_book.digital_source_url = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "cover_url") {
+            active_book.cover_url = Set(v);
+        }
+        if payload.get("subjects").is_some() {
+            active_book.subjects = Set(subjects_column(&payload));
+        }
+        if let Some(v) = optional_str(&payload, "started_reading_at") {
+            active_book.started_reading_at = Set(v);
+        }
+        if let Some(v) = optional_str(&payload, "finished_reading_at") {
+            active_book.finished_reading_at = Set(v);
+        }
+        if let Some(v) = optional_i32(&payload, "user_rating") {
+            active_book.user_rating = Set(v);
+        }
 
-        active_book.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        active_book.updated_at = Set(origin_at(&payload));
         active_book.save(db).await?;
     } else {
         // Update for missing book? Treat as create or ignore?
@@ -474,21 +691,23 @@ async fn apply_copy_create(
         .unwrap_or("available")
         .to_string();
     let is_temporary = payload["is_temporary"].as_bool().unwrap_or(false);
-
-    // Deduplication: skip if a copy with same (book_id, status, is_temporary) already exists
-    //
-    // This key predates the rule that a book row carries one borrowed copy per lender,
-    // and would collapse two peers' loans of the same book into one. It is left as is
-    // because the replay path is unreachable: `process_next_batch` only replays
-    // operations whose `source != "local"`, and the sole writer of such a row,
-    // `sync::log_remote_operation`, has no production caller. Reviving device-to-device
-    // op replay means keying this on the lender first.
-    let existing = copy::Entity::find()
-        .filter(copy::Column::BookId.eq(book_id.clone()))
-        .filter(copy::Column::Status.eq(status.clone()))
-        .filter(copy::Column::IsTemporary.eq(is_temporary))
-        .one(db)
-        .await?;
+    let remote_id = remote_id(&payload);
+
+    // Deduplication. Device sync snapshots carry the copy's id, which is the
+    // only safe key: two peers' loans of the same book are two borrowed copies
+    // with the same (book_id, status, is_temporary). Older payloads without an
+    // id fall back to that triple.
+    let existing = match remote_id {
+        Some(ref id) => copy::Entity::find_by_id(id.clone()).one(db).await?,
+        None => {
+            copy::Entity::find()
+                .filter(copy::Column::BookId.eq(book_id.clone()))
+                .filter(copy::Column::Status.eq(status.clone()))
+                .filter(copy::Column::IsTemporary.eq(is_temporary))
+                .one(db)
+                .await?
+        }
+    };
     if existing.is_some() {
         tracing::info!("Skipping duplicate copy for book_id={book_id}");
         return Ok(());
@@ -498,6 +717,7 @@ async fn apply_copy_create(
     let lib_id = crate::utils::library_helpers::resolve_library_id(db).await?;
 
     let new_copy = copy::ActiveModel {
+        id: remote_id.map_or(NotSet, Set),
         book_id: Set(book_id),
         library_id: Set(lib_id),
         status: Set(status),
@@ -527,7 +747,14 @@ async fn apply_copy_update(
         if let Some(n) = payload.get("notes").and_then(|v| v.as_str()) {
             active.notes = Set(Some(n.to_string()));
         }
-        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        if let Some(w) = payload.get("weeding") {
+            let text = |key: &str| w.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            active.weeding_reason = Set(text("reason"));
+            active.weeding_note = Set(text("note"));
+            active.weeding_flagged_at = Set(text("flagged_at"));
+            active.withdrawn_at = Set(text("withdrawn_at"));
+        }
+        active.updated_at = Set(origin_at(&payload));
         active.save(db).await?;
     }
     Ok(())
@@ -554,6 +781,7 @@ async fn apply_contact_create(
     }
 
     let new_contact = contact::ActiveModel {
+        id: remote_id(&payload).map_or(NotSet, Set),
         r#type: Set(payload["type"].as_str().unwrap_or("Person").to_string()),
         name: Set(name),
         first_name: Set(payload["first_name"].as_str().map(|s| s.to_string())),
@@ -593,7 +821,15 @@ async fn apply_contact_update(
         if let Some(p) = payload.get("phone").and_then(|v| v.as_str()) {
             active.phone = Set(Some(p.to_string()));
         }
-        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        if let Some(m) = payload.get("membership") {
+            let m: crate::services::membership::Membership =
+                serde_json::from_value(m.clone()).unwrap_or_default();
+            active.member_number = Set(m.member_number);
+            active.member_since = Set(m.member_since);
+            active.membership_expires_at = Set(m.expires_at);
+            active.membership_fee_paid = Set(m.fee_paid);
+        }
+        active.updated_at = Set(origin_at(&payload));
         active.save(db).await?;
     }
     Ok(())
@@ -607,8 +843,20 @@ async fn apply_loan_create(
 ) -> Result<(), DbErr> {
     let payload = parse_payload(op)?;
     let now = chrono::Utc::now().to_rfc3339();
+    let remote_id = remote_id(&payload);
+
+    // Deduplication: skip if the loan was already replicated under its id
+    if let Some(ref id) = remote_id
+        && loan::Entity::find_by_id(id.clone())
+            .one(db)
+            .await?
+            .is_some()
+    {
+        return Ok(());
+    }
 
     let new_loan = loan::ActiveModel {
+        id: remote_id.map_or(NotSet, Set),
         copy_id: Set(payload["copy_id"].as_str().unwrap_or("").to_string()),
         contact_id: Set(payload["contact_id"].as_str().unwrap_or("").to_string()),
         library_id: Set(match payload["library_id"].as_i64().map(|v| v as i32) {
@@ -643,7 +891,10 @@ async fn apply_loan_update(
         if let Some(rd) = payload.get("return_date").and_then(|v| v.as_str()) {
             active.return_date = Set(Some(rd.to_string()));
         }
-        active.updated_at = Set(chrono::Utc::now().to_rfc3339());
+        if let Some(due) = payload.get("due_date").and_then(|v| v.as_str()) {
+            active.due_date = Set(due.to_string());
+        }
+        active.updated_at = Set(origin_at(&payload));
         active.save(db).await?;
     }
     Ok(())
@@ -673,6 +924,7 @@ async fn apply_tag_create(
         name: Set(name.clone()),
         parent_id: Set(payload["parent_id"].as_str().map(|s| s.to_string())),
         path: Set(payload["path"].as_str().unwrap_or(&name).to_string()),
+        private: Set(payload["private"].as_bool().unwrap_or(false)),
         created_at: Set(now.clone()),
         updated_at: Set(now),
         ..Default::default()
@@ -681,6 +933,25 @@ async fn apply_tag_create(
     Ok(())
 }
 
+async fn apply_tag_update(
+    db: &DatabaseTransaction,
+    op: &operation_log::Model,
+) -> Result<(), DbErr> {
+    let existing = tag::Entity::find_by_id(op.entity_id.clone())
+        .one(db)
+        .await?;
+    if let Some(t) = existing {
+        let payload = parse_payload(op)?;
+        let mut active: tag::ActiveModel = t.into();
+        if let Some(private) = payload.get("private").and_then(|v| v.as_bool()) {
+            active.private = Set(private);
+        }
+        active.updated_at = Set(origin_at(&payload));
+        active.save(db).await?;
+    }
+    Ok(())
+}
+
 // ── Author handler ───────────────────────────────────────────────────
 
 async fn apply_author_create(
@@ -711,6 +982,46 @@ async fn apply_author_create(
     Ok(())
 }
 
+async fn apply_author_update(
+    db: &DatabaseTransaction,
+    op: &operation_log::Model,
+) -> Result<(), DbErr> {
+    let payload = parse_payload(op)?;
+
+    // Authors created by a replayed insert get a local id: fall back to the
+    // name, the one before the rename for a rename.
+    let previous_name = payload.get("previous_name").and_then(|v| v.as_str());
+    let mut existing = author::Entity::find_by_id(op.entity_id.clone())
+        .one(db)
+        .await?;
+    if existing.is_none()
+        && let Some(name) = previous_name.or_else(|| payload.get("name").and_then(|v| v.as_str()))
+    {
+        existing = author::Entity::find()
+            .filter(author::Column::Name.eq(name))
+            .one(db)
+            .await?;
+    }
+    if let Some(a) = existing {
+        let mut active: author::ActiveModel = a.into();
+        if previous_name.is_some()
+            && let Some(name) = payload.get("name").and_then(|v| v.as_str())
+        {
+            active.name = Set(name.to_string());
+        }
+        if let Some(ids) = payload.get("identifiers") {
+            let text = |key: &str| ids.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            active.bnf_id = Set(text("bnf_id"));
+            active.viaf_id = Set(text("viaf_id"));
+            active.wikidata_id = Set(text("wikidata_id"));
+            active.openlibrary_id = Set(text("openlibrary_id"));
+        }
+        active.updated_at = Set(origin_at(&payload));
+        active.save(db).await?;
+    }
+    Ok(())
+}
+
 // ── Junction table helpers (resolved by natural keys) ─────────────────
 
 async fn apply_book_author_insert(
@@ -1137,6 +1448,59 @@ async fn apply_book_note_update(
     Ok(())
 }
 
+// ── Library settings handler (device sync only) ─────────────────────
+
+async fn apply_library_config_update(
+    db: &DatabaseTransaction,
+    op: &operation_log::Model,
+) -> Result<(), DbErr> {
+    let payload = parse_payload(op)?;
+    let updated_at = origin_at(&payload);
+
+    // `library_config` is a single row; its id is device-local.
+    let before = library_config::Entity::find().one(db).await?;
+    let mut active = match before.clone() {
+        Some(existing) => existing.into(),
+        None => library_config::ActiveModel {
+            name: Set(String::new()),
+            tags: Set("[]".to_string()),
+            created_at: Set(updated_at.clone()),
+            ..Default::default()
+        },
+    };
+    if let Some(n) = payload.get("name").and_then(|v| v.as_str()) {
+        active.name = Set(n.to_string());
+    }
+    if let Some(v) = optional_str(&payload, "description") {
+        active.description = Set(v);
+    }
+    if let Some(t) = payload.get("tags").and_then(|v| v.as_str()) {
+        active.tags = Set(t.to_string());
+    }
+    if let Some(v) = payload.get("latitude") {
+        active.latitude = Set(v.as_f64());
+    }
+    if let Some(v) = payload.get("longitude") {
+        active.longitude = Set(v.as_f64());
+    }
+    if let Some(v) = payload.get("share_location") {
+        active.share_location = Set(v.as_bool());
+    }
+    if let Some(v) = payload.get("show_borrowed_books") {
+        active.show_borrowed_books = Set(v.as_bool());
+    }
+    if let Some(v) = payload.get("currency") {
+        active.currency = Set(v.as_str().map(str::to_string));
+    }
+    if let Some(v) = payload.get("timezone") {
+        active.timezone = Set(v.as_str().map(str::to_string));
+    }
+    active.updated_at = Set(updated_at);
+    let saved = active.save(db).await?.try_into_model()?;
+    crate::services::config_history::record(db, before.as_ref(), &saved, "device_sync").await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1164,6 +1528,36 @@ mod tests {
         op.insert(db).await.expect("Failed to insert op")
     }
 
+    #[tokio::test]
+    async fn test_processor_flushes_pending_operations_on_shutdown() {
+        let db = init_db("sqlite::memory:").await.expect("Failed to init db");
+        let shutdown = Shutdown::new();
+        shutdown.trigger();
+
+        let op = insert_remote_op(
+            &db,
+            "book",
+            "1",
+            "create",
+            serde_json::json!({ "title": "Queued Before Shutdown" }),
+        )
+        .await;
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            run_processor_until(db.clone(), shutdown),
+        )
+        .await
+        .expect("processor stops once shutdown is triggered");
+
+        let op = operation_log::Entity::find_by_id(op.id)
+            .one(&db)
+            .await
+            .expect("DB error")
+            .unwrap();
+        assert_eq!(op.status, "applied");
+    }
+
     #[tokio::test]
     async fn test_apply_book_create_operation() {
         let db = init_db("sqlite::memory:").await.expect("Failed to init db");
@@ -1176,7 +1570,7 @@ mod tests {
 
         let op = insert_remote_op(&db, "book", "1", "create", payload).await;
 
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         let updated_op = operation_log::Entity::find_by_id(op.id)
             .one(&db)
@@ -1225,7 +1619,7 @@ mod tests {
         });
         let op = insert_remote_op(&db, "copy", "50", "insert", payload).await;
 
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         // Verify operation applied
         let updated_op = operation_log::Entity::find_by_id(op.id)
@@ -1262,7 +1656,7 @@ mod tests {
         });
         let op = insert_remote_op(&db, "copy", "50", "insert", payload).await;
 
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         // Operation should be applied (gracefully skipped, not failed)
         let updated_op = operation_log::Entity::find_by_id(op.id)
@@ -1304,7 +1698,7 @@ mod tests {
         });
         let op = insert_remote_op(&db, "book_note", "30", "insert", payload).await;
 
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         let updated_op = operation_log::Entity::find_by_id(op.id)
             .one(&db)
@@ -1352,11 +1746,11 @@ mod tests {
 
         // First sync: copy created
         insert_remote_op(&db, "copy", "50", "insert", payload.clone()).await;
-        process_next_batch(&db).await.unwrap();
+        process_one(&db).await.unwrap();
 
         // Second sync: same copy operation again
         insert_remote_op(&db, "copy", "50", "insert", payload).await;
-        process_next_batch(&db).await.unwrap();
+        process_one(&db).await.unwrap();
 
         // Only ONE copy should exist
         let copies = copy::Entity::find()
@@ -1382,7 +1776,7 @@ mod tests {
         });
 
         let op = insert_remote_op(&db, "book", "1", "insert", payload).await;
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         let updated_op = operation_log::Entity::find_by_id(op.id)
             .one(&db)
@@ -1471,7 +1865,7 @@ mod tests {
             "is_temporary": false,
         });
         let op = insert_remote_op(&db, "copy", "50", "insert", payload).await;
-        process_next_batch(&db).await.expect("Processing failed");
+        process_one(&db).await.expect("Processing failed");
 
         let updated_op = operation_log::Entity::find_by_id(op.id)
             .one(&db)
@@ -1525,7 +1919,7 @@ mod tests {
             "author_name": "Junction Author",
         });
         let op = insert_remote_op(&db, "book_author", "0", "insert", payload).await;
-        process_next_batch(&db).await.unwrap();
+        process_one(&db).await.unwrap();
 
         let updated_op = operation_log::Entity::find_by_id(op.id)
             .one(&db)
@@ -1570,11 +1964,11 @@ mod tests {
 
         // First sync: note created
         insert_remote_op(&db, "book_note", "30", "insert", payload.clone()).await;
-        process_next_batch(&db).await.unwrap();
+        process_one(&db).await.unwrap();
 
         // Second sync: same note again
         insert_remote_op(&db, "book_note", "30", "insert", payload).await;
-        process_next_batch(&db).await.unwrap();
+        process_one(&db).await.unwrap();
 
         // Only ONE note should exist
         let notes = bn::Entity::find()
@@ -1584,4 +1978,92 @@ mod tests {
             .unwrap();
         assert_eq!(notes.len(), 1, "Duplicate book_note should be skipped");
     }
+
+    #[tokio::test]
+    async fn test_remote_update_applies_reading_progress() {
+        let db = init_db("sqlite::memory:").await.expect("Failed to init db");
+        let local_book = book::ActiveModel {
+            title: Set("Middlemarch".to_string()),
+            reading_status: Set("to_read".to_string()),
+            created_at: Set("2026-01-01T00:00:00+00:00".to_string()),
+            updated_at: Set("2026-01-01T00:00:00+00:00".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let op = insert_remote_op(
+            &db,
+            "book",
+            &local_book.id,
+            "UPDATE",
+            serde_json::json!({
+                "reading_status": "reading",
+                "started_reading_at": "2026-02-01",
+                "user_rating": 8,
+                "_origin_at": "2026-02-01T10:00:00+00:00",
+            }),
+        )
+        .await;
+        process_one(&db).await.unwrap();
+
+        let updated = book::Entity::find_by_id(local_book.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.reading_status, "reading");
+        assert_eq!(updated.started_reading_at.as_deref(), Some("2026-02-01"));
+        assert_eq!(updated.user_rating, Some(8));
+        assert_eq!(updated.updated_at, "2026-02-01T10:00:00+00:00");
+        let op = operation_log::Entity::find_by_id(op.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(op.status, "applied");
+    }
+
+    #[tokio::test]
+    async fn test_remote_update_older_than_local_change_is_skipped() {
+        let db = init_db("sqlite::memory:").await.expect("Failed to init db");
+        let local_book = book::ActiveModel {
+            title: Set("Local Title".to_string()),
+            reading_status: Set("read".to_string()),
+            created_at: Set("2026-01-01T00:00:00+00:00".to_string()),
+            updated_at: Set("2026-03-01T00:00:00+00:00".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let op = insert_remote_op(
+            &db,
+            "book",
+            &local_book.id,
+            "UPDATE",
+            serde_json::json!({
+                "title": "Stale Title",
+                "_origin_at": "2026-02-01T00:00:00+00:00",
+            }),
+        )
+        .await;
+        process_one(&db).await.unwrap();
+
+        let kept = book::Entity::find_by_id(local_book.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.title, "Local Title");
+        let op = operation_log::Entity::find_by_id(op.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(op.status, "skipped");
+        assert!(op.error_message.unwrap().starts_with("conflict:"));
+    }
 }
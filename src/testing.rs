@@ -0,0 +1,291 @@
+//! Deterministic fixtures for tests (`testing` feature).
+//!
+//! [`TestLibrary::builder`] fills a database with a library, numbered books
+//! (one available copy each), peers and contacts. Ids, titles, ISBNs and
+//! timestamps depend only on the item's index, so two builds with the same
+//! counts produce the same rows and building again on the same database only
+//! adds what is missing.
+//!
+//! Integration tests build on an in-memory database; Flutter integration tests
+//! reach the same builder through `POST /api/debug/fixtures` ([`debug_routes`]),
+//! which only exists in builds with the `testing` feature.
+//!
+//! The library owner is the account [`resolve_library_id`] bootstraps on a
+//! fresh database (`admin` / `admin`), or the existing one.
+//!
+//! [`resolve_library_id`]: crate::utils::library_helpers::resolve_library_id
+
+use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::post};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::infrastructure::AppState;
+use crate::models::{book, contact, copy, peer};
+
+/// Creation time of every fixture row.
+pub const FIXTURE_TIMESTAMP: &str = "2024-01-01T00:00:00+00:00";
+
+/// Upper bound per kind accepted by the debug endpoint.
+const MAX_FIXTURES: usize = 10_000;
+
+/// A stable UUID v7-shaped id for the `n`-th fixture of a kind.
+pub fn fixture_uuid(kind: u16, n: usize) -> String {
+    format!("00000000-{kind:04x}-7000-8000-{n:012x}")
+}
+
+/// ISBN-13 (with a valid check digit) of the `n`-th fixture book.
+pub fn fixture_isbn(n: usize) -> String {
+    let body = format!("97900{:07}", n % 10_000_000);
+    let sum: u32 = body
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| u32::from(b - b'0') * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    format!("{body}{}", (10 - sum % 10) % 10)
+}
+
+const BOOK: u16 = 1;
+const COPY: u16 = 2;
+const CONTACT: u16 = 3;
+const PEER_LIBRARY: u16 = 4;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TestLibraryBuilder {
+    #[serde(default)]
+    pub books: usize,
+    #[serde(default)]
+    pub peers: usize,
+    #[serde(default)]
+    pub contacts: usize,
+}
+
+impl TestLibraryBuilder {
+    pub fn with_books(mut self, count: usize) -> Self {
+        self.books = count;
+        self
+    }
+
+    pub fn with_peers(mut self, count: usize) -> Self {
+        self.peers = count;
+        self
+    }
+
+    pub fn with_contacts(mut self, count: usize) -> Self {
+        self.contacts = count;
+        self
+    }
+
+    /// Build on a fresh in-memory database with all migrations applied.
+    pub async fn build_in_memory(self) -> Result<TestLibrary, DbErr> {
+        let db = crate::db::init_db("sqlite::memory:").await?;
+        self.build(&db).await
+    }
+
+    /// Add the fixtures to `db`, skipping the ones already there.
+    pub async fn build(self, db: &DatabaseConnection) -> Result<TestLibrary, DbErr> {
+        let library_id = crate::utils::library_helpers::resolve_library_id(db).await?;
+        let mut library = TestLibrary {
+            db: db.clone(),
+            library_id,
+            book_ids: Vec::with_capacity(self.books),
+            copy_ids: Vec::with_capacity(self.books),
+            peer_ids: Vec::with_capacity(self.peers),
+            contact_ids: Vec::with_capacity(self.contacts),
+        };
+
+        for n in 1..=self.books {
+            let book_id = fixture_uuid(BOOK, n);
+            book::Entity::insert(book::ActiveModel {
+                id: Set(book_id.clone()),
+                title: Set(format!("Fixture Book {n:03}")),
+                isbn: Set(Some(fixture_isbn(n))),
+                publisher: Set(Some("Fixture Press".to_owned())),
+                publication_year: Set(Some(1950 + (n % 75) as i32)),
+                reading_status: Set("to_read".to_owned()),
+                owned: Set(true),
+                private: Set(false),
+                created_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                updated_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                ..Default::default()
+            })
+            .on_conflict(OnConflict::column(book::Column::Id).do_nothing().to_owned())
+            .exec_without_returning(db)
+            .await?;
+
+            let copy_id = fixture_uuid(COPY, n);
+            copy::Entity::insert(copy::ActiveModel {
+                id: Set(copy_id.clone()),
+                book_id: Set(book_id.clone()),
+                library_id: Set(library_id),
+                status: Set("available".to_owned()),
+                is_temporary: Set(false),
+                created_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                updated_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                ..Default::default()
+            })
+            .on_conflict(OnConflict::column(copy::Column::Id).do_nothing().to_owned())
+            .exec_without_returning(db)
+            .await?;
+
+            library.book_ids.push(book_id);
+            library.copy_ids.push(copy_id);
+        }
+
+        for n in 1..=self.peers {
+            let url = format!("http://peer-{n}.fixture.test:8000");
+            peer::Entity::insert(peer::ActiveModel {
+                name: Set(format!("Fixture Peer {n}")),
+                url: Set(url.clone()),
+                library_uuid: Set(Some(fixture_uuid(PEER_LIBRARY, n))),
+                key_exchange_done: Set(false),
+                auto_approve: Set(false),
+                connection_status: Set("accepted".to_owned()),
+                created_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                updated_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::column(peer::Column::Url)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+            let peer = peer::Entity::find()
+                .filter(peer::Column::Url.eq(url))
+                .one(db)
+                .await?
+                .ok_or_else(|| DbErr::RecordNotFound("fixture peer".to_owned()))?;
+            library.peer_ids.push(peer.id);
+        }
+
+        for n in 1..=self.contacts {
+            let contact_id = fixture_uuid(CONTACT, n);
+            contact::Entity::insert(contact::ActiveModel {
+                id: Set(contact_id.clone()),
+                r#type: Set("borrower".to_owned()),
+                name: Set(format!("Fixture Contact {n:03}")),
+                library_owner_id: Set(library_id),
+                is_active: Set(true),
+                created_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                updated_at: Set(FIXTURE_TIMESTAMP.to_owned()),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::column(contact::Column::Id)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+            library.contact_ids.push(contact_id);
+        }
+
+        Ok(library)
+    }
+}
+
+/// The rows a [`TestLibraryBuilder`] produced, in index order.
+#[derive(Debug, Clone)]
+pub struct TestLibrary {
+    pub db: DatabaseConnection,
+    pub library_id: i32,
+    pub book_ids: Vec<String>,
+    pub copy_ids: Vec<String>,
+    pub peer_ids: Vec<i32>,
+    pub contact_ids: Vec<String>,
+}
+
+impl TestLibrary {
+    pub fn builder() -> TestLibraryBuilder {
+        TestLibraryBuilder::default()
+    }
+
+    /// An [`AppState`] over the fixture database, for router tests.
+    pub fn state(&self) -> AppState {
+        AppState::new(self.db.clone())
+    }
+}
+
+/// `POST /api/debug/fixtures`, merged into the owner routes in `testing`
+/// builds. Deliberately absent from the OpenAPI document.
+pub fn debug_routes() -> Router<AppState> {
+    Router::new().route("/debug/fixtures", post(create_fixtures))
+}
+
+async fn create_fixtures(
+    State(state): State<AppState>,
+    Json(req): Json<TestLibraryBuilder>,
+) -> impl IntoResponse {
+    if req.books.max(req.peers).max(req.contacts) > MAX_FIXTURES {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("At most {MAX_FIXTURES} fixtures per kind")})),
+        )
+            .into_response();
+    }
+    match req.build(state.db()).await {
+        Ok(library) => (
+            StatusCode::OK,
+            Json(json!({
+                "library_id": library.library_id,
+                "book_ids": library.book_ids,
+                "copy_ids": library.copy_ids,
+                "peer_ids": library.peer_ids,
+                "contact_ids": library.contact_ids,
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::PaginatorTrait;
+
+    #[test]
+    fn fixture_isbns_are_valid_and_stable() {
+        let isbn = fixture_isbn(1);
+        assert_eq!(isbn.len(), 13);
+        assert_eq!(isbn, fixture_isbn(1));
+        assert_ne!(isbn, fixture_isbn(2));
+        let sum: u32 = isbn
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| u32::from(b - b'0') * if i % 2 == 0 { 1 } else { 3 })
+            .sum();
+        assert_eq!(sum % 10, 0);
+    }
+
+    #[tokio::test]
+    async fn building_twice_adds_only_what_is_missing() {
+        let library = TestLibrary::builder()
+            .with_books(5)
+            .with_peers(2)
+            .with_contacts(1)
+            .build_in_memory()
+            .await
+            .unwrap();
+        assert_eq!(library.book_ids[0], fixture_uuid(BOOK, 1));
+        assert_eq!(library.peer_ids.len(), 2);
+
+        let again = TestLibrary::builder()
+            .with_books(8)
+            .with_peers(2)
+            .build(&library.db)
+            .await
+            .unwrap();
+        assert_eq!(again.book_ids[..5], library.book_ids[..]);
+        assert_eq!(again.peer_ids, library.peer_ids);
+        assert_eq!(book::Entity::find().count(&library.db).await.unwrap(), 8);
+        assert_eq!(copy::Entity::find().count(&library.db).await.unwrap(), 8);
+    }
+}
@@ -58,25 +58,84 @@ const SYNC_QUALITY_STEPS: [u8; 5] = [80, 70, 60, 50, 40];
 /// This is CPU-bound; callers running inside an async context should invoke
 /// it from `tokio::task::spawn_blocking`.
 pub fn resize_to_jpeg_thumbnail(input: &[u8]) -> Result<Vec<u8>, String> {
-    let padded = decode_orient_pad(input)?;
-    let (w, h) = padded.dimensions();
+    encode_capped(&decode_orient_pad(input)?, COVER_SIZE_CAP_BYTES)
+}
 
-    let mut best: Option<Vec<u8>> = None;
-    for quality in QUALITY_STEPS {
-        let buf = encode_jpeg(&padded, w, h, quality)?;
+/// One of the sizes an uploaded cover is stored at. `Medium` is the 300x450
+/// box every other cover path uses; `Large` is what the book's cover file
+/// holds, the other two are derived from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverSize {
+    Thumbnail,
+    Medium,
+    Large,
+}
 
-        let fits_cap = buf.len() <= COVER_SIZE_CAP_BYTES;
-        let is_smaller = best.as_ref().is_none_or(|b| buf.len() < b.len());
+impl CoverSize {
+    pub const ALL: [CoverSize; 3] = [CoverSize::Thumbnail, CoverSize::Medium, CoverSize::Large];
 
-        if fits_cap {
-            return Ok(buf);
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "thumbnail" => Some(Self::Thumbnail),
+            "medium" => Some(Self::Medium),
+            "large" => Some(Self::Large),
+            _ => None,
         }
-        if is_smaller {
-            best = Some(buf);
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Thumbnail => "thumbnail",
+            Self::Medium => "medium",
+            Self::Large => "large",
         }
     }
 
-    best.ok_or_else(|| "no encode attempt succeeded".to_string())
+    /// Output box in pixels, always 2:3.
+    pub fn dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Thumbnail => (100, 150),
+            Self::Medium => (COVER_MAX_WIDTH, COVER_MAX_HEIGHT),
+            Self::Large => (600, 900),
+        }
+    }
+
+    /// Soft size cap, scaled with the pixel count from the 300x450 one.
+    fn size_cap(self) -> usize {
+        match self {
+            Self::Thumbnail => 12 * 1024,
+            Self::Medium => COVER_SIZE_CAP_BYTES,
+            Self::Large => 180 * 1024,
+        }
+    }
+}
+
+/// Resize `input` to one cover size, padded to its exact box like
+/// `resize_to_jpeg_thumbnail`. CPU-bound, like every encoder here.
+pub fn resize_cover(input: &[u8], size: CoverSize) -> Result<Vec<u8>, String> {
+    cover_variants(input, &[size]).map(|mut v| v.remove(0).1)
+}
+
+/// Resize `input` to each of `sizes`, decoding it only once.
+pub fn cover_variants(
+    input: &[u8],
+    sizes: &[CoverSize],
+) -> Result<Vec<(CoverSize, Vec<u8>)>, String> {
+    cover_variants_of(&decode_oriented(input)?, sizes)
+}
+
+/// Resize an already decoded, upright image to each of `sizes`.
+pub fn cover_variants_of(
+    img: &DynamicImage,
+    sizes: &[CoverSize],
+) -> Result<Vec<(CoverSize, Vec<u8>)>, String> {
+    sizes
+        .iter()
+        .map(|&size| {
+            let (w, h) = size.dimensions();
+            encode_capped(&fit_and_pad(img, w, h), size.size_cap()).map(|jpeg| (size, jpeg))
+        })
+        .collect()
 }
 
 /// Re-encode `input` as a 300x450 JPEG that fits within `COVER_SYNC_CAP_BYTES`,
@@ -107,6 +166,12 @@ pub fn resize_to_jpeg_thumbnail_for_sync(input: &[u8]) -> Result<Option<Vec<u8>>
 /// 300x450, and pad to exactly 300x450. The single chokepoint both encoders
 /// share so serve-side and sync-side covers come from identical pixels.
 fn decode_orient_pad(input: &[u8]) -> Result<RgbImage, String> {
+    let img = decode_oriented(input)?;
+    Ok(fit_and_pad(&img, COVER_MAX_WIDTH, COVER_MAX_HEIGHT))
+}
+
+/// Decode an arbitrary image and bake in its EXIF orientation.
+pub(crate) fn decode_oriented(input: &[u8]) -> Result<DynamicImage, String> {
     if input.len() > COVER_MAX_INPUT_BYTES {
         return Err(format!(
             "input too large: {} bytes (max {})",
@@ -128,10 +193,36 @@ fn decode_orient_pad(input: &[u8]) -> Result<RgbImage, String> {
     let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
     let mut img = DynamicImage::from_decoder(decoder).map_err(|e| format!("decode: {e}"))?;
     img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// Resize `img` to fit within `w` x `h` and pad it to exactly that box.
+fn fit_and_pad(img: &DynamicImage, w: u32, h: u32) -> RgbImage {
+    let resized = img.thumbnail(w, h).to_rgb8();
+    pad_to_target(&resized, w, h)
+}
+
+/// Encode `padded` down the serve quality ladder: the first step under `cap`,
+/// else the smallest result obtained.
+fn encode_capped(padded: &RgbImage, cap: usize) -> Result<Vec<u8>, String> {
+    let (w, h) = padded.dimensions();
+
+    let mut best: Option<Vec<u8>> = None;
+    for quality in QUALITY_STEPS {
+        let buf = encode_jpeg(padded, w, h, quality)?;
+
+        let fits_cap = buf.len() <= cap;
+        let is_smaller = best.as_ref().is_none_or(|b| buf.len() < b.len());
+
+        if fits_cap {
+            return Ok(buf);
+        }
+        if is_smaller {
+            best = Some(buf);
+        }
+    }
 
-    let thumb = img.thumbnail(COVER_MAX_WIDTH, COVER_MAX_HEIGHT);
-    let resized = thumb.to_rgb8();
-    Ok(pad_to_target(&resized, COVER_MAX_WIDTH, COVER_MAX_HEIGHT))
+    best.ok_or_else(|| "no encode attempt succeeded".to_string())
 }
 
 /// Encode a padded RGB cover as JPEG at `quality`.
@@ -257,6 +348,22 @@ mod tests {
         assert_eq!(decoded.height(), COVER_MAX_HEIGHT);
     }
 
+    #[test]
+    fn cover_variants_hit_each_box() {
+        let png = encode_png(&cover_like_image(1200, 1500));
+
+        let variants = cover_variants(&png, &CoverSize::ALL).unwrap();
+
+        assert_eq!(variants.len(), 3);
+        for (size, jpeg) in variants {
+            let decoded = image::load_from_memory(&jpeg).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), size.dimensions());
+            assert!(jpeg.len() <= size.size_cap(), "{} over cap", size.as_str());
+        }
+        assert_eq!(CoverSize::parse("large"), Some(CoverSize::Large));
+        assert_eq!(CoverSize::parse("huge"), None);
+    }
+
     #[test]
     fn pads_square_input_to_2_3_box() {
         let src = cover_like_image(300, 300);
@@ -14,6 +14,8 @@
 use std::fmt;
 use std::path::{Path, PathBuf};
 
+use crate::utils::cover_image::CoverSize;
+
 /// Error raised when a cover URL rewrite intended for a relay-bound
 /// payload cannot produce a remotely reachable URL: the source is a
 /// local filesystem path and the hub prefix is missing.
@@ -205,6 +207,30 @@ pub fn is_local_cover(cover_url: &str) -> bool {
     !cover_url.is_empty() && !is_servable_on_lan(cover_url)
 }
 
+/// The file a book's local custom cover is read from: the stored path, re-based
+/// onto the current covers dir when one is registered (FFI mode). `None` for
+/// external or peer covers, and for a path with a `..` segment.
+pub fn local_cover_read_path(cover_url: &str, book_id: &str) -> Option<PathBuf> {
+    if !is_local_cover(cover_url) || cover_url.split(['/', '\\']).any(|seg| seg == "..") {
+        return None;
+    }
+    let registered = crate::infrastructure::paths::registered_covers_dir();
+    Some(match registered {
+        Some(dir) => rebase_local_cover_path(&dir, cover_url, book_id),
+        // A bare `<id>.jpg`, as stored by a cover upload, lives in the covers
+        // root; a full path is read as-is (paths are stable off-device).
+        None if Path::new(cover_url).parent() == Some(Path::new("")) => {
+            covers_root().join(cover_url)
+        }
+        None => PathBuf::from(cover_url),
+    })
+}
+
+/// Directory holding local covers (`infrastructure::paths::covers_dir`).
+pub fn covers_root() -> PathBuf {
+    crate::infrastructure::paths::covers_dir()
+}
+
 /// The canonical on-disk filename for a book's local custom cover, keyed by its
 /// uuid identity: `<uuid>.jpg`. Matches `rebase_local_cover_path`'s expectation
 /// and the Flutter `LocalCoverResolver`.
@@ -212,6 +238,16 @@ pub fn local_cover_filename(uuid: &str) -> String {
     format!("{uuid}.jpg")
 }
 
+/// The cached resized copy of an uploaded cover, `<uuid>.<size>.jpg`, kept
+/// next to the cover file it is derived from. The large size is the cover
+/// file itself.
+pub fn cover_variant_filename(uuid: &str, size: CoverSize) -> String {
+    match size {
+        CoverSize::Large => local_cover_filename(uuid),
+        _ => format!("{uuid}.{}.jpg", size.as_str()),
+    }
+}
+
 /// Plan the `cover_url` migration for one book during the id -> uuid flip (S4d).
 ///
 /// Given the currently-stored value and the book's uuid, returns:
@@ -115,7 +115,7 @@ fn normalized_isbn(raw: Option<&str>) -> Option<String> {
 
 /// Lowercase, drop punctuation, collapse whitespace. Best-effort normalization
 /// for the title/author fallback key.
-fn normalize_text(s: &str) -> String {
+pub(crate) fn normalize_text(s: &str) -> String {
     s.to_lowercase()
         .split_whitespace()
         .map(|w| {
@@ -0,0 +1,86 @@
+//! Fuzzy string similarity for names and titles typed by hand.
+//!
+//! Two measures, each in `0.0..=1.0`, of which a comparison keeps the best:
+//!
+//! - trigram overlap (Dice coefficient over the three-letter windows of each
+//!   word, padded as `pg_trgm` does), which shrugs off a missing or extra word;
+//! - Damerau-Levenshtein distance over the length, which forgives a typo or
+//!   two swapped letters ("Tolkein").
+//!
+//! Both sides are folded first: no case, no accents, punctuation as spaces,
+//! so "J.R.R." and "j r r" are the same. Names are also compared with their
+//! words sorted, so "Tolkien, J.R.R." finds "J.R.R. Tolkien".
+
+use std::collections::HashSet;
+
+use strsim::normalized_damerau_levenshtein;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+/// Lowercase, no accents, words of letters and digits separated by one space.
+pub fn fold(s: &str) -> String {
+    let folded: String = s
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn trigrams(folded: &str) -> HashSet<[char; 3]> {
+    let mut grams = HashSet::new();
+    for word in folded.split(' ').filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = "  ".chars().chain(word.chars()).chain([' ']).collect();
+        for window in padded.windows(3) {
+            grams.insert([window[0], window[1], window[2]]);
+        }
+    }
+    grams
+}
+
+/// Dice coefficient of the two strings' trigrams (both already folded).
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+fn folded_similarity(a: &str, b: &str) -> f64 {
+    trigram_similarity(a, b).max(normalized_damerau_levenshtein(a, b))
+}
+
+fn sorted_words(folded: &str) -> String {
+    let mut words: Vec<&str> = folded.split(' ').collect();
+    words.sort_unstable();
+    words.join(" ")
+}
+
+/// How alike two titles are.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    folded_similarity(&fold(a), &fold(b))
+}
+
+/// How alike two person names are, whichever order their parts come in.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (fold(a), fold(b));
+    folded_similarity(&a, &b).max(folded_similarity(&sorted_words(&a), &sorted_words(&b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_initials_and_inversions_stay_close() {
+        assert_eq!(fold("J.R.R. Tolkien"), "j r r tolkien");
+        assert!(name_similarity("J.R.R. Tolkein", "J.R.R. Tolkien") > 0.9);
+        assert_eq!(name_similarity("Tolkien, J.R.R.", "J. R. R. Tolkien"), 1.0);
+        assert_eq!(name_similarity("Émile Zola", "emile zola"), 1.0);
+        assert!(name_similarity("Jack London", "Jack Kerouac") < 0.6);
+
+        assert!(title_similarity("The Lord of the Rings", "Lord of the Rings") > 0.8);
+        assert!(title_similarity("Dune", "Emma") < 0.5);
+    }
+}
@@ -578,7 +578,7 @@ pub async fn sync_all_leaderboards(state: &AppState, skip_direct: bool) {
     }
 
     if !skip_direct {
-        let client = reqwest::Client::builder()
+        let client = crate::services::network_mode::client_builder()
             .timeout(std::time::Duration::from_secs(5))
             .build()
             .unwrap_or_default();
@@ -3,11 +3,17 @@ pub mod cover_url;
 pub mod dedup_key;
 pub mod default_library_name;
 pub mod etag;
+pub mod fuzzy;
 pub mod hub_url;
 pub mod isbn;
 pub mod lang;
 pub mod leaderboard_relay;
 pub mod library_helpers;
+pub mod money;
 pub mod net;
 pub mod peer_discovery;
+pub mod sanitize;
+pub mod stats_card;
+pub mod text_pdf;
+pub mod time_zone;
 pub mod uuid_gen;
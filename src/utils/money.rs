@@ -0,0 +1,163 @@
+//! Currencies and amounts of money.
+//!
+//! Prices, sale amounts and fees are stored as bare numbers in the library's
+//! currency (`library_config.currency`, an ISO 4217 code, EUR when unset).
+//! Responses carry the currency with the amount so a client in CHF and one
+//! in EUR both know what they read. No exchange rates: an amount is never
+//! converted, only labelled and rounded to the currency's minor unit.
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+use serde::Serialize;
+
+pub const DEFAULT_CURRENCY: &str = "EUR";
+
+/// How to write amounts of one currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct Currency {
+    /// ISO 4217 code ("CHF")
+    pub code: &'static str,
+    /// "€", "CHF", "$"
+    pub symbol: &'static str,
+    /// Digits after the decimal point (2 for EUR, 0 for JPY)
+    pub decimals: u8,
+}
+
+const fn currency(code: &'static str, symbol: &'static str, decimals: u8) -> Currency {
+    Currency {
+        code,
+        symbol,
+        decimals,
+    }
+}
+
+/// The currencies a library can keep its accounts in.
+pub const CURRENCIES: &[Currency] = &[
+    currency("EUR", "€", 2),
+    currency("CHF", "CHF", 2),
+    currency("USD", "$", 2),
+    currency("GBP", "£", 2),
+    currency("CAD", "$", 2),
+    currency("AUD", "$", 2),
+    currency("NZD", "$", 2),
+    currency("JPY", "¥", 0),
+    currency("CNY", "¥", 2),
+    currency("KRW", "₩", 0),
+    currency("INR", "₹", 2),
+    currency("BRL", "R$", 2),
+    currency("MXN", "$", 2),
+    currency("SEK", "kr", 2),
+    currency("NOK", "kr", 2),
+    currency("DKK", "kr", 2),
+    currency("ISK", "kr", 0),
+    currency("PLN", "zł", 2),
+    currency("CZK", "Kč", 2),
+    currency("HUF", "Ft", 2),
+    currency("RON", "lei", 2),
+    currency("TRY", "₺", 2),
+    currency("UAH", "₴", 2),
+    currency("ILS", "₪", 2),
+    currency("MAD", "DH", 2),
+    currency("TND", "DT", 3),
+    currency("DZD", "DA", 2),
+    currency("XOF", "F CFA", 0),
+    currency("XAF", "F CFA", 0),
+    currency("ZAR", "R", 2),
+];
+
+/// The currency with ISO 4217 `code`, in any case.
+pub fn find(code: &str) -> Option<Currency> {
+    let code = code.trim();
+    CURRENCIES
+        .iter()
+        .find(|c| c.code.eq_ignore_ascii_case(code))
+        .copied()
+}
+
+/// The currency of a stored code; the default when unset or unknown.
+pub fn or_default(code: Option<&str>) -> Currency {
+    code.and_then(find)
+        .or_else(|| find(DEFAULT_CURRENCY))
+        .expect("default currency is listed")
+}
+
+/// The currency the library keeps its accounts in.
+pub async fn library_currency(db: &DatabaseConnection) -> Result<Currency, DbErr> {
+    let config = crate::models::library_config::Entity::find_by_id(1)
+        .one(db)
+        .await?;
+    Ok(or_default(
+        config.as_ref().and_then(|c| c.currency.as_deref()),
+    ))
+}
+
+/// An amount, rounded to the minor unit of its currency.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct Money {
+    pub amount: f64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: f64, currency: Currency) -> Self {
+        Self {
+            amount: round(amount, currency),
+            currency,
+        }
+    }
+}
+
+impl std::fmt::Display for Money {
+    /// Code after the amount ("12.50 CHF"): unambiguous in every locale.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.*} {}",
+            self.currency.decimals as usize, self.amount, self.currency.code
+        )
+    }
+}
+
+/// `amount` rounded to the minor unit of `currency`.
+pub fn round(amount: f64, currency: Currency) -> f64 {
+    let scale = 10f64.powi(currency.decimals as i32);
+    (amount * scale).round() / scale
+}
+
+/// Why an amount given by a client is refused, or `None` when it is a
+/// non-negative number with no more decimals than `currency` has.
+pub fn check_amount(amount: f64, currency: Currency) -> Option<String> {
+    if !amount.is_finite() || amount < 0.0 {
+        return Some("must be a non-negative number".to_string());
+    }
+    if (amount - round(amount, currency)).abs() > 1e-9 {
+        return Some(format!(
+            "{} takes at most {} decimal(s)",
+            currency.code, currency.decimals
+        ));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_follow_their_currency() {
+        let chf = find("chf").unwrap();
+        let jpy = find("JPY").unwrap();
+
+        assert_eq!(Money::new(12.499, chf).amount, 12.5);
+        assert_eq!(Money::new(12.5, chf).to_string(), "12.50 CHF");
+        assert_eq!(Money::new(1200.4, jpy).to_string(), "1200 JPY");
+
+        assert_eq!(check_amount(9.95, chf), None);
+        assert!(check_amount(9.95, jpy).is_some());
+        assert!(check_amount(-1.0, chf).is_some());
+        assert!(check_amount(f64::NAN, chf).is_some());
+
+        assert_eq!(or_default(None).code, "EUR");
+        assert_eq!(or_default(Some("XYZ")).code, "EUR");
+        assert!(find("XYZ").is_none());
+    }
+}
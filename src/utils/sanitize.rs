@@ -0,0 +1,218 @@
+//! Cleaning of text received from peers and read from import files.
+//!
+//! Peer catalogues, loan requests and offers, E2EE messages and imported
+//! files are written by someone else. Before being stored, their text is:
+//!
+//! - stripped of control characters (in single-line fields, line breaks and
+//!   tabs become spaces; multi-line fields keep them) and of
+//!   the invisible formatting characters used to spoof text: bidirectional
+//!   overrides and isolates, zero-width spaces, byte order marks. The
+//!   zero-width (non-)joiners are kept: Persian and Indic scripts and emoji
+//!   sequences need them;
+//! - trimmed, and cut to a length per kind of field (`MAX_*_CHARS`), counted
+//!   in characters, so a peer cannot fill the database or the screen.
+//!
+//! HTML is not escaped nor stripped here: the text is stored as the plain
+//! text it is, and escaped where it is rendered into markup (`quick_xml` in
+//! the OPDS feed, `text_pdf`). Escaping on the way in would show `&amp;`
+//! in the app and double-escape on the way out.
+
+use serde_json::Value;
+
+use crate::models::Book;
+
+/// Person and library names, publishers
+pub const MAX_NAME_CHARS: usize = 200;
+/// Titles and other one-line labels
+pub const MAX_TITLE_CHARS: usize = 500;
+/// Summaries, notes, messages
+pub const MAX_TEXT_CHARS: usize = 20_000;
+pub const MAX_URL_CHARS: usize = 2_048;
+/// ISBNs, language codes, identifiers
+pub const MAX_CODE_CHARS: usize = 64;
+/// Entries of a list field (authors, subjects)
+pub const MAX_LIST_ITEMS: usize = 50;
+
+/// Invisible characters that change how the text around them reads.
+fn is_format_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}'                   // zero-width space
+            | '\u{200E}' | '\u{200F}' // LRM, RLM
+            | '\u{202A}'..='\u{202E}' // bidi embeddings and overrides
+            | '\u{2060}'..='\u{2064}' // word joiner, invisible operators
+            | '\u{2066}'..='\u{2069}' // bidi isolates
+            | '\u{FEFF}'              // byte order mark
+    )
+}
+
+/// Kept in multi-line text: all but control and formatting characters,
+/// line breaks and tabs excepted.
+fn keeps(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\t') || !(c.is_control() || is_format_char(c))
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => s[..end].trim_end().to_string(),
+        None => s.to_string(),
+    }
+}
+
+/// A one-line field: line breaks and tabs become spaces.
+pub fn line(s: &str, max_chars: usize) -> String {
+    let cleaned: String = s
+        .chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() || is_format_char(c) => None,
+            c => Some(c),
+        })
+        .collect();
+    truncate(cleaned.trim(), max_chars)
+}
+
+/// A multi-line field: line breaks (normalized to `\n`) and tabs are kept.
+pub fn text(s: &str, max_chars: usize) -> String {
+    let cleaned: String = s
+        .replace("\r\n", "\n")
+        .chars()
+        .map(|c| if c == '\r' { '\n' } else { c })
+        .filter(|c| keeps(*c))
+        .collect();
+    truncate(cleaned.trim(), max_chars)
+}
+
+pub fn opt_line(s: Option<String>, max_chars: usize) -> Option<String> {
+    s.map(|s| line(&s, max_chars))
+}
+
+pub fn opt_text(s: Option<String>, max_chars: usize) -> Option<String> {
+    s.map(|s| text(&s, max_chars))
+}
+
+fn opt_list(items: Option<Vec<String>>, max_chars: usize) -> Option<Vec<String>> {
+    items.map(|items| {
+        items
+            .iter()
+            .take(MAX_LIST_ITEMS)
+            .map(|item| line(item, max_chars))
+            .collect()
+    })
+}
+
+/// Strip control (but line breaks and tabs) and formatting characters from
+/// every string of a JSON document, without cutting any: a message payload
+/// may carry encoded images. Length limits apply where fields are stored.
+pub fn json_strings(value: &mut Value) {
+    match value {
+        Value::String(s) if !s.chars().all(keeps) => {
+            *s = s.chars().filter(|c| keeps(*c)).collect();
+        }
+        Value::Array(items) => items.iter_mut().for_each(json_strings),
+        Value::Object(map) => map.values_mut().for_each(json_strings),
+        _ => {}
+    }
+}
+
+/// A book from a peer's catalogue.
+pub fn book(book: Book) -> Book {
+    Book {
+        id: opt_line(book.id, MAX_CODE_CHARS),
+        title: line(&book.title, MAX_TITLE_CHARS),
+        isbn: opt_line(book.isbn, MAX_CODE_CHARS),
+        summary: opt_text(book.summary, MAX_TEXT_CHARS),
+        publisher: opt_line(book.publisher, MAX_NAME_CHARS),
+        dewey_decimal: opt_line(book.dewey_decimal, MAX_CODE_CHARS),
+        lcc: opt_line(book.lcc, MAX_CODE_CHARS),
+        subjects: opt_list(book.subjects, MAX_TITLE_CHARS),
+        marc_record: opt_text(book.marc_record, MAX_TEXT_CHARS),
+        cataloguing_notes: opt_text(book.cataloguing_notes, MAX_TEXT_CHARS),
+        source_data: opt_text(book.source_data, MAX_TEXT_CHARS),
+        reading_status: opt_line(book.reading_status, MAX_CODE_CHARS),
+        source: opt_line(book.source, MAX_CODE_CHARS),
+        author: opt_line(book.author, MAX_NAME_CHARS),
+        authors: opt_list(book.authors, MAX_NAME_CHARS),
+        cover_url: opt_line(book.cover_url, MAX_URL_CHARS),
+        large_cover_url: opt_line(book.large_cover_url, MAX_URL_CHARS),
+        language: opt_line(book.language, MAX_CODE_CHARS),
+        digital_formats: opt_list(book.digital_formats, MAX_CODE_CHARS),
+        edition: opt_line(book.edition, MAX_TITLE_CHARS),
+        physical_format: opt_line(book.physical_format, MAX_CODE_CHARS),
+        dimensions: opt_line(book.dimensions, MAX_CODE_CHARS),
+        digital_source_url: opt_line(book.digital_source_url, MAX_URL_CHARS),
+        added_at: opt_line(book.added_at, MAX_CODE_CHARS),
+        updated_at: opt_line(book.updated_at, MAX_CODE_CHARS),
+        ..book
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostile_text_is_cleaned_and_cut() {
+        // Terminal escapes, NULs, and a bidi override showing "fdp.exe" as "exe.pdf".
+        assert_eq!(
+            line(
+                "\u{1b}[31mLe Petit\0 Prince\u{202E}fdp.exe\n",
+                MAX_TITLE_CHARS
+            ),
+            "[31mLe Petit Princefdp.exe"
+        );
+        assert_eq!(line("Tom\tet\r\nJerry", 100), "Tom et  Jerry");
+        assert_eq!(
+            text("Ligne 1\r\nLigne 2\rLigne 3\u{7}", 100),
+            "Ligne 1\nLigne 2\nLigne 3"
+        );
+        assert_eq!(line("  \u{200B}\u{FEFF} ", 100), "");
+
+        // Cut on characters, never inside one.
+        assert_eq!(line("ééééé", 3), "ééé");
+        assert_eq!(
+            line(&"x".repeat(100_000), MAX_TITLE_CHARS).len(),
+            MAX_TITLE_CHARS
+        );
+
+        // Markup is left for the renderer to escape.
+        assert_eq!(
+            line("<script>alert(1)</script>", 100),
+            "<script>alert(1)</script>"
+        );
+    }
+
+    #[test]
+    fn payload_strings_are_cleaned_at_any_depth() {
+        let mut payload = serde_json::json!({
+            "title": "A\u{0}B",
+            "books": [{"author": "C\u{202E}D", "pages": 12}],
+            "note": "line 1\nline 2",
+        });
+        json_strings(&mut payload);
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "title": "AB",
+                "books": [{"author": "CD", "pages": 12}],
+                "note": "line 1\nline 2",
+            })
+        );
+    }
+
+    #[test]
+    fn joiners_needed_by_scripts_and_emoji_are_kept() {
+        // ZWNJ inside a Persian word, ZWJ inside the family emoji.
+        let persian = "\u{0645}\u{06CC}\u{200C}\u{062E}\u{0648}\u{0627}\u{0647}\u{0645}";
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(line(persian, MAX_TITLE_CHARS), persian);
+        assert_eq!(line(&format!("{family}\u{200B}"), 100), family);
+
+        let mut payload = serde_json::json!({ "title": persian, "message": family });
+        json_strings(&mut payload);
+        assert_eq!(
+            payload,
+            serde_json::json!({ "title": persian, "message": family })
+        );
+    }
+}
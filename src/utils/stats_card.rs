@@ -0,0 +1,568 @@
+//! PNG rendering of a shareable stats card (see `api::stats::get_stats_card`).
+//!
+//! The card holds a heading, a few figures, tag names and a mosaic of covers,
+//! and nothing that would let a viewer reconstruct the catalogue. Text is
+//! drawn with a built-in 5x7 bitmap font so no font file has to ship with the
+//! app: letters are upper-cased and stripped of accents, and characters the
+//! font lacks are left blank.
+//!
+//! This is CPU-bound; callers running inside an async context should invoke
+//! it from `tokio::task::spawn_blocking`.
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use serde::Deserialize;
+use std::io::Cursor;
+use unicode_normalization::UnicodeNormalization;
+
+/// Output dimensions of the card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CardSize {
+    /// 1080x1080, for feeds.
+    #[default]
+    Square,
+    /// 1200x630, for link previews.
+    Wide,
+    /// 1080x1920, for stories.
+    Story,
+}
+
+impl CardSize {
+    fn dimensions(self) -> (u32, u32) {
+        match self {
+            Self::Square => (1080, 1080),
+            Self::Wide => (1200, 630),
+            Self::Story => (1080, 1920),
+        }
+    }
+}
+
+/// Colour scheme of the card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CardTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+struct Palette {
+    background: Rgb<u8>,
+    text: Rgb<u8>,
+    accent: Rgb<u8>,
+}
+
+impl CardTheme {
+    fn palette(self) -> Palette {
+        match self {
+            Self::Light => Palette {
+                background: Rgb([250, 247, 240]),
+                text: Rgb([34, 34, 34]),
+                accent: Rgb([160, 82, 45]),
+            },
+            Self::Dark => Palette {
+                background: Rgb([24, 24, 28]),
+                text: Rgb([236, 236, 236]),
+                accent: Rgb([230, 170, 90]),
+            },
+        }
+    }
+}
+
+/// What goes on a card.
+#[derive(Debug, Clone, Default)]
+pub struct StatsCard {
+    pub heading: String,
+    /// Label and value of each figure, e.g. ("Books read", "42").
+    pub figures: Vec<(String, String)>,
+    pub tags: Vec<String>,
+    /// Encoded cover images (JPEG or PNG); undecodable ones are skipped.
+    pub covers: Vec<Vec<u8>>,
+}
+
+/// Rows of each glyph, five bits wide, most significant bit on the left.
+const GLYPHS: &[(char, [u8; 7])] = &[
+    (
+        'A',
+        [
+            0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'B',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'C',
+        [
+            0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'D',
+        [
+            0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110,
+        ],
+    ),
+    (
+        'E',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'F',
+        [
+            0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'G',
+        [
+            0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111,
+        ],
+    ),
+    (
+        'H',
+        [
+            0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'I',
+        [
+            0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        'J',
+        [
+            0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100,
+        ],
+    ),
+    (
+        'K',
+        [
+            0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'L',
+        [
+            0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        'M',
+        [
+            0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'N',
+        [
+            0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'O',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'P',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000,
+        ],
+    ),
+    (
+        'Q',
+        [
+            0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101,
+        ],
+    ),
+    (
+        'R',
+        [
+            0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001,
+        ],
+    ),
+    (
+        'S',
+        [
+            0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110,
+        ],
+    ),
+    (
+        'T',
+        [
+            0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'U',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        'V',
+        [
+            0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ],
+    ),
+    (
+        'W',
+        [
+            0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010,
+        ],
+    ),
+    (
+        'X',
+        [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001,
+        ],
+    ),
+    (
+        'Y',
+        [
+            0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100,
+        ],
+    ),
+    (
+        'Z',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
+        ],
+    ),
+    (
+        '0',
+        [
+            0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '1',
+        [
+            0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ],
+    ),
+    (
+        '2',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111,
+        ],
+    ),
+    (
+        '3',
+        [
+            0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '4',
+        [
+            0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010,
+        ],
+    ),
+    (
+        '5',
+        [
+            0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '6',
+        [
+            0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '7',
+        [
+            0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000,
+        ],
+    ),
+    (
+        '8',
+        [
+            0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110,
+        ],
+    ),
+    (
+        '9',
+        [
+            0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100,
+        ],
+    ),
+    (
+        '.',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100,
+        ],
+    ),
+    (
+        ',',
+        [
+            0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        ':',
+        [
+            0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000,
+        ],
+    ),
+    (
+        '-',
+        [
+            0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '/',
+        [
+            0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000,
+        ],
+    ),
+    (
+        '\'',
+        [
+            0b01100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000,
+        ],
+    ),
+    (
+        '!',
+        [
+            0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '?',
+        [
+            0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100,
+        ],
+    ),
+    (
+        '&',
+        [
+            0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101,
+        ],
+    ),
+    (
+        '+',
+        [
+            0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000,
+        ],
+    ),
+    (
+        '(',
+        [
+            0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010,
+        ],
+    ),
+    (
+        ')',
+        [
+            0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000,
+        ],
+    ),
+    (
+        '#',
+        [
+            0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010,
+        ],
+    ),
+    (
+        '%',
+        [
+            0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011,
+        ],
+    ),
+];
+
+/// Width of one character cell in font units: five columns and a gap.
+const ADVANCE: u32 = 6;
+
+fn glyph(c: char) -> Option<&'static [u8; 7]> {
+    GLYPHS.iter().find(|(g, _)| *g == c).map(|(_, rows)| rows)
+}
+
+/// `text` in the font's repertoire: upper case, accents removed.
+fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Draw `text` at (x, y) with `scale` pixels per font unit, clipped to
+/// `max_width`. Returns the width drawn.
+fn draw_text(
+    canvas: &mut RgbImage,
+    text: &str,
+    x: u32,
+    y: u32,
+    scale: u32,
+    max_width: u32,
+    color: Rgb<u8>,
+) -> u32 {
+    let fits = (max_width / (ADVANCE * scale)) as usize;
+    let mut cursor = x;
+    for c in fold(text).chars().take(fits) {
+        if let Some(rows) = glyph(c) {
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..5u32 {
+                    if bits & (0b10000 >> col) == 0 {
+                        continue;
+                    }
+                    let px = cursor + col * scale;
+                    let py = y + row as u32 * scale;
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            if px + dx < canvas.width() && py + dy < canvas.height() {
+                                canvas.put_pixel(px + dx, py + dy, color);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        cursor += ADVANCE * scale;
+    }
+    cursor - x
+}
+
+/// Render `card` as a PNG.
+pub fn render_card(card: &StatsCard, size: CardSize, theme: CardTheme) -> Result<Vec<u8>, String> {
+    let (width, height) = size.dimensions();
+    let palette = theme.palette();
+    let mut canvas = RgbImage::from_pixel(width, height, palette.background);
+
+    let margin = width / 18;
+    let inner = width - 2 * margin;
+    let title_scale = (width / 170).max(2);
+    let text_scale = (width / 260).max(1);
+    let line = 10 * text_scale;
+    let mut y = margin;
+
+    draw_text(
+        &mut canvas,
+        &card.heading,
+        margin,
+        y,
+        title_scale,
+        inner,
+        palette.accent,
+    );
+    y += 12 * title_scale;
+
+    for (label, value) in &card.figures {
+        let drawn = draw_text(
+            &mut canvas,
+            value,
+            margin,
+            y,
+            text_scale * 2,
+            inner,
+            palette.text,
+        );
+        draw_text(
+            &mut canvas,
+            label,
+            margin + drawn + ADVANCE * text_scale,
+            y + 7 * text_scale,
+            text_scale,
+            inner.saturating_sub(drawn + ADVANCE * text_scale),
+            palette.text,
+        );
+        y += 2 * line;
+    }
+
+    if !card.tags.is_empty() {
+        y += line / 2;
+        draw_text(
+            &mut canvas,
+            &card.tags.join("  /  "),
+            margin,
+            y,
+            text_scale,
+            inner,
+            palette.accent,
+        );
+        y += line;
+    }
+
+    // Covers mosaic in the space left, 2:3 cells.
+    let gap = text_scale * 4;
+    let columns: u32 = match size {
+        CardSize::Wide => 8,
+        _ => 5,
+    };
+    let cell_w = (inner - (columns - 1) * gap) / columns;
+    let cell_h = cell_w * 3 / 2;
+    let top = y + line;
+    let rows = (height.saturating_sub(top + margin) + gap) / (cell_h + gap);
+    let covers = card
+        .covers
+        .iter()
+        .filter_map(|bytes| image::load_from_memory(bytes).ok())
+        .take((rows * columns) as usize);
+    for (i, cover) in covers.enumerate() {
+        let i = i as u32;
+        let cell = cover
+            .resize_to_fill(cell_w, cell_h, FilterType::Triangle)
+            .to_rgb8();
+        let x = margin + (i % columns) * (cell_w + gap);
+        let cy = top + (i / columns) * (cell_h + gap);
+        imageops::overlay(&mut canvas, &cell, i64::from(x), i64::from(cy));
+    }
+
+    let mut png = Vec::new();
+    DynamicImage::ImageRgb8(canvas)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folding_keeps_text_in_the_font() {
+        assert_eq!(fold("Élodie's année"), "ELODIE'S ANNEE");
+        assert!(
+            fold("Livres lus: 42%")
+                .chars()
+                .all(|c| c == ' ' || glyph(c).is_some())
+        );
+    }
+
+    #[test]
+    fn card_renders_at_the_requested_size() {
+        let mut cover = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(60, 90, Rgb([200, 10, 10])))
+            .write_to(&mut Cursor::new(&mut cover), ImageFormat::Png)
+            .unwrap();
+        let card = StatsCard {
+            heading: "My 2025 in books".to_string(),
+            figures: vec![("Books read".to_string(), "42".to_string())],
+            tags: vec!["Science-fiction".to_string()],
+            covers: vec![cover, b"not an image".to_vec()],
+        };
+
+        for (size, dims) in [
+            (CardSize::Square, (1080, 1080)),
+            (CardSize::Wide, (1200, 630)),
+        ] {
+            let png = render_card(&card, size, CardTheme::Dark).unwrap();
+            let decoded = image::load_from_memory(&png).unwrap().to_rgb8();
+            assert_eq!(decoded.dimensions(), dims);
+        }
+    }
+}
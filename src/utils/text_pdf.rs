@@ -0,0 +1,134 @@
+//! Minimal PDF writer for printable reports: a title and lines of monospaced
+//! text, paginated on A4. It relies on the standard Courier font, which
+//! every PDF reader has, so nothing is embedded. Text is encoded as
+//! WinAnsi: characters outside Latin-1 print as `?`, and lines longer than
+//! the page is wide are cut.
+
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 40;
+const FONT_SIZE: u32 = 9;
+const LEADING: u32 = 11;
+/// Courier glyphs are 0.6 em wide.
+const MAX_COLUMNS: usize = ((PAGE_WIDTH - 2 * MARGIN) * 10 / (FONT_SIZE * 6)) as usize;
+/// Lines below the page header.
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2 * MARGIN) / LEADING) as usize - 2;
+
+/// A PDF string literal of `text`, cut to `MAX_COLUMNS`.
+fn literal(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars().take(MAX_COLUMNS) {
+        let byte = u8::try_from(u32::from(c)).unwrap_or(b'?');
+        match byte {
+            b'(' | b')' | b'\\' => out.extend([b'\\', byte]),
+            0..=0x1f => out.push(b' '),
+            _ => out.push(byte),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+fn page_content(header: &str, lines: &[String]) -> Vec<u8> {
+    let mut content = format!(
+        "BT\n/F1 {FONT_SIZE} Tf\n{LEADING} TL\n{MARGIN} {} Td\n",
+        PAGE_HEIGHT - MARGIN
+    )
+    .into_bytes();
+    for line in std::iter::once(header)
+        .chain(std::iter::once(""))
+        .chain(lines.iter().map(String::as_str))
+    {
+        content.extend(literal(line));
+        content.extend(b" Tj T*\n");
+    }
+    content.extend(b"ET\n");
+    content
+}
+
+/// A PDF document with `lines` under `title`, repeated with the page number
+/// at the top of each page.
+pub fn text_pdf(title: &str, lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(LINES_PER_PAGE).collect()
+    };
+
+    // Objects: 1 catalog, 2 page tree, 3 font, then a page and its content
+    // stream for each page.
+    let mut objects: Vec<Vec<u8>> = Vec::with_capacity(3 + 2 * pages.len());
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 4 + 2 * i))
+        .collect();
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    objects.push(
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_vec(),
+    );
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                5 + 2 * i
+            )
+            .into_bytes(),
+        );
+        let header = format!("{title} - page {}/{}", i + 1, pages.len());
+        let content = page_content(&header, page);
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend(content);
+        stream.extend(b"endstream");
+        objects.push(stream);
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+        pdf.extend(object);
+        pdf.extend(b"\nendobj\n");
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .into_bytes(),
+    );
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_reports_are_paginated_and_text_is_escaped() {
+        let lines: Vec<String> = (0..LINES_PER_PAGE + 1)
+            .map(|i| format!("line {i} (café)"))
+            .collect();
+        let pdf = text_pdf("Weeding report", &lines);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("(Weeding report - page 2/2)"));
+        // Parentheses escaped, é as its WinAnsi byte.
+        assert!(pdf.windows(12).any(|w| w == b"\\(caf\xe9\\)) Tj"));
+    }
+}
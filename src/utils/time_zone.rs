@@ -0,0 +1,156 @@
+//! The library's time zone, and dates read in it.
+//!
+//! Timestamps are stored as text: RFC 3339 (`Utc::now().to_rfc3339()`),
+//! `YYYY-MM-DD HH:MM:SS` in local time (loans, sales) or a bare
+//! `YYYY-MM-DD` (due dates). Comparing them as text only works while they
+//! share a format and an offset, so comparisons go through
+//! [`parse_timestamp`] instead. Dates that a person reads as days (a due
+//! date, "today") are days in the library's time zone
+//! (`library_config.timezone`, an IANA name such as `Europe/Zurich`), or in
+//! the host's when none is set: a loan due on the 17th is due until the end
+//! of the 17th where the library is, not at midnight UTC.
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
+
+/// The zone dates are read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryZone {
+    /// No zone configured: the host's local time
+    Host,
+    Named(Tz),
+}
+
+/// The zone with IANA name `name` ("Europe/Paris").
+pub fn parse_zone(name: &str) -> Option<Tz> {
+    name.trim().parse::<Tz>().ok()
+}
+
+/// The zone of a stored name; the host's when unset or unknown.
+pub fn zone_or_host(name: Option<&str>) -> LibraryZone {
+    name.and_then(parse_zone)
+        .map_or(LibraryZone::Host, LibraryZone::Named)
+}
+
+/// The library's zone.
+pub async fn library_zone(db: &DatabaseConnection) -> Result<LibraryZone, DbErr> {
+    let config = crate::models::library_config::Entity::find_by_id(1)
+        .one(db)
+        .await?;
+    Ok(zone_or_host(
+        config.as_ref().and_then(|c| c.timezone.as_deref()),
+    ))
+}
+
+impl LibraryZone {
+    /// IANA name, or `None` for the host's zone.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Host => None,
+            Self::Named(tz) => Some(tz.name()),
+        }
+    }
+
+    /// `instant` as a local time of the zone, with its offset.
+    pub fn to_local(&self, instant: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Host => instant.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => instant.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// The instant of a local wall-clock time. In a DST gap the time is
+    /// read with the offset before the gap; in an overlap, the earlier one.
+    pub fn from_local(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        fn resolve<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+            match zone.from_local_datetime(&local).earliest() {
+                Some(t) => t.with_timezone(&Utc),
+                None => {
+                    let offset = zone.offset_from_utc_datetime(&local).fix();
+                    Utc.from_utc_datetime(&(local - offset))
+                }
+            }
+        }
+        match self {
+            Self::Host => resolve(&Local, local),
+            Self::Named(tz) => resolve(tz, local),
+        }
+    }
+
+    /// The local date now.
+    pub fn today(&self) -> NaiveDate {
+        self.to_local(Utc::now()).date_naive()
+    }
+
+    /// The local time now, as `YYYY-MM-DD HH:MM:SS` (loan and sale dates).
+    pub fn now_local_string(&self) -> String {
+        self.to_local(Utc::now())
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+
+    /// The last instant of local day `day`: when something due that day
+    /// falls due.
+    pub fn end_of_day(&self, day: NaiveDate) -> DateTime<FixedOffset> {
+        let last = day.and_time(NaiveTime::from_hms_opt(23, 59, 59).expect("valid time"));
+        self.to_local(self.from_local(last))
+    }
+
+    /// When a loan with `due_date` falls due; `None` when unparseable.
+    pub fn due_at(&self, due_date: &str) -> Option<DateTime<FixedOffset>> {
+        let day = NaiveDate::parse_from_str(due_date.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(self.end_of_day(day))
+    }
+
+    /// A stored timestamp as RFC 3339 in the zone, for reports; the value
+    /// unchanged when it cannot be read.
+    pub fn format(&self, stored: &str) -> String {
+        parse_timestamp(stored, *self)
+            .map(|t| self.to_local(t).to_rfc3339())
+            .unwrap_or_else(|| stored.to_string())
+    }
+}
+
+/// The instant of a stored timestamp: RFC 3339, `YYYY-MM-DD HH:MM:SS` or
+/// `YYYY-MM-DD` (the start of that day), the last two in `zone`.
+pub fn parse_timestamp(value: &str, zone: LibraryZone) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&Utc));
+    }
+    if let Ok(local) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(zone.from_local(local));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|day| zone.from_local(day.and_time(NaiveTime::MIN)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn due_dates_end_with_the_local_day() {
+        let zurich = LibraryZone::Named(parse_zone("Europe/Zurich").unwrap());
+        let due = zurich.due_at("2026-10-17").unwrap();
+        assert_eq!(due.to_rfc3339(), "2026-10-17T23:59:59+02:00");
+        // Winter time: the offset follows the day, not the season of "now".
+        let due = zurich.due_at("2026-12-01 00:00:00").unwrap();
+        assert_eq!(due.to_rfc3339(), "2026-12-01T23:59:59+01:00");
+
+        let utc = parse_timestamp("2026-10-17 10:00:00", zurich).unwrap();
+        assert_eq!(utc.to_rfc3339(), "2026-10-17T08:00:00+00:00");
+        assert_eq!(
+            zurich.format("2026-10-17T08:00:00+00:00"),
+            "2026-10-17T10:00:00+02:00"
+        );
+        assert_eq!(zurich.format("not a date"), "not a date");
+
+        assert!(parse_zone("Mars/Olympus_Mons").is_none());
+        assert_eq!(zone_or_host(Some("nowhere")), LibraryZone::Host);
+    }
+}
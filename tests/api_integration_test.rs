@@ -122,6 +122,7 @@ async fn create_test_request(
         created_at: Set(now.clone()),
         updated_at: Set(now),
         requester_request_id: Set(None),
+        ..Default::default()
     };
     rust_lib_app::models::p2p_request::Entity::insert(request)
         .exec(db)
@@ -316,6 +317,7 @@ async fn test_borrow_request_auto_approve() {
         created_at: Set(chrono::Utc::now().to_rfc3339()),
         updated_at: Set(chrono::Utc::now().to_rfc3339()),
         requester_request_id: Set(None),
+        ..Default::default()
     };
     rust_lib_app::models::p2p_request::Entity::insert(request)
         .exec(&db)
@@ -562,7 +564,7 @@ async fn test_contact_creation_requires_valid_library() {
 
     // A dangling library_owner_id is rejected at the app layer (ADR-044).
     let rejected = rust_lib_app::services::contact_service::create_contact(
-        &db,
+        &rust_lib_app::infrastructure::SeaOrmContactRepository::new(db.clone()),
         rust_lib_app::services::contact_service::ContactDto {
             library_owner_id: Some(999),
             ..base()
@@ -579,7 +581,7 @@ async fn test_contact_creation_requires_valid_library() {
 
     // A valid owner id is accepted.
     let accepted = rust_lib_app::services::contact_service::create_contact(
-        &db,
+        &rust_lib_app::infrastructure::SeaOrmContactRepository::new(db.clone()),
         rust_lib_app::services::contact_service::ContactDto {
             library_owner_id: Some(library_id),
             ..base()
@@ -0,0 +1,175 @@
+//! HTTP-level tests for the bulk endpoints: POST /books/batch/delete,
+//! /books/batch/archive and /books/batch/unarchive, and how archived books
+//! drop out of the default list.
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode, header};
+use rust_lib_app::api::{batch, books};
+use rust_lib_app::auth::create_jwt;
+use rust_lib_app::db;
+use rust_lib_app::infrastructure::AppState;
+use rust_lib_app::models::{book, copy};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+async fn setup_db() -> DatabaseConnection {
+    db::init_db("sqlite::memory:")
+        .await
+        .expect("init_db in memory")
+}
+
+async fn insert_book(db: &DatabaseConnection, title: &str) -> String {
+    let now = chrono::Utc::now().to_rfc3339();
+    book::ActiveModel {
+        title: Set(title.to_string()),
+        owned: Set(true),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .expect("insert book")
+    .id
+}
+
+fn build_app(db: DatabaseConnection) -> axum::Router {
+    axum::Router::new()
+        .route("/api/books", axum::routing::get(books::list_books))
+        .route(
+            "/api/books/batch/delete",
+            axum::routing::post(batch::batch_delete),
+        )
+        .route(
+            "/api/books/batch/archive",
+            axum::routing::post(batch::batch_archive),
+        )
+        .route(
+            "/api/books/batch/unarchive",
+            axum::routing::post(batch::batch_unarchive),
+        )
+        .with_state(AppState::new(db))
+}
+
+async fn send(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let token = create_jwt("owner", "admin").expect("jwt");
+    let req = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .header(header::CONTENT_TYPE, "application/json");
+    let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+    let resp = app.clone().oneshot(req.body(body).unwrap()).await.unwrap();
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+fn titles(json: &Value) -> Vec<String> {
+    let mut titles: Vec<String> = json["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["title"].as_str().unwrap().to_string())
+        .collect();
+    titles.sort();
+    titles
+}
+
+#[tokio::test]
+async fn archived_books_leave_the_default_list_until_unarchived() {
+    let db = setup_db().await;
+    let old = insert_book(&db, "Old almanac").await;
+    insert_book(&db, "Dune").await;
+    let app = build_app(db);
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/books/batch/archive",
+        Some(json!({ "ids": [old, "missing"], "confirm_count": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["changed"], 1);
+
+    let (_, json) = send(&app, "GET", "/api/books", None).await;
+    assert_eq!(titles(&json), ["Dune"]);
+    let (_, json) = send(&app, "GET", "/api/books?archived=true", None).await;
+    assert_eq!(titles(&json), ["Old almanac"]);
+
+    send(
+        &app,
+        "POST",
+        "/api/books/batch/unarchive",
+        Some(json!({ "ids": [old], "confirm_count": 1 })),
+    )
+    .await;
+    let (_, json) = send(&app, "GET", "/api/books", None).await;
+    assert_eq!(titles(&json), ["Dune", "Old almanac"]);
+}
+
+#[tokio::test]
+async fn a_wrong_confirm_count_changes_nothing() {
+    let db = setup_db().await;
+    let a = insert_book(&db, "A").await;
+    let b = insert_book(&db, "B").await;
+    let app = build_app(db.clone());
+
+    for uri in ["/api/books/batch/delete", "/api/books/batch/archive"] {
+        let (status, json) = send(
+            &app,
+            "POST",
+            uri,
+            Some(json!({ "ids": [a, b], "confirm_count": 3 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{uri}");
+        assert_eq!(json["matched"], 2, "{uri}");
+    }
+
+    let (_, json) = send(&app, "GET", "/api/books", None).await;
+    assert_eq!(titles(&json), ["A", "B"]);
+}
+
+#[tokio::test]
+async fn batch_delete_removes_books_with_their_copies() {
+    let db = setup_db().await;
+    let a = insert_book(&db, "A").await;
+    let b = insert_book(&db, "B").await;
+    let now = chrono::Utc::now().to_rfc3339();
+    copy::ActiveModel {
+        book_id: Set(a.clone()),
+        library_id: Set(1),
+        status: Set("available".to_string()),
+        is_temporary: Set(false),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let app = build_app(db.clone());
+
+    let (status, json) = send(
+        &app,
+        "POST",
+        "/api/books/batch/delete",
+        Some(json!({ "ids": [a, b], "confirm_count": 2 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["deleted"], 2);
+    assert!(book::Entity::find().all(&db).await.unwrap().is_empty());
+    assert!(copy::Entity::find().all(&db).await.unwrap().is_empty());
+}
@@ -0,0 +1,250 @@
+//! Book file attachments: upload, list, download, delete and the OPDS feed.
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+};
+use rust_lib_app::db;
+use rust_lib_app::infrastructure::AppState;
+use rust_lib_app::models::book;
+use sea_orm::{ActiveModelTrait, Set};
+use tower::util::ServiceExt; // for `oneshot`
+
+const BOUNDARY: &str = "bibliogenius-test-boundary";
+
+/// Every test shares one media dir: `MEDIA_DIR` is process-wide, and files are
+/// namespaced by book id, so parallel tests do not collide.
+fn media_dir() -> &'static std::path::Path {
+    static DIR: std::sync::OnceLock<tempfile::TempDir> = std::sync::OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = tempfile::tempdir().expect("tempdir");
+        // SAFETY: set once, before any handler reads it; every test goes
+        // through this initializer first.
+        unsafe { std::env::set_var("MEDIA_DIR", dir.path()) };
+        dir
+    })
+    .path()
+}
+
+async fn setup() -> (Router, String) {
+    media_dir();
+    let db = db::init_db("sqlite::memory:")
+        .await
+        .expect("Failed to init DB");
+    let book = book::ActiveModel {
+        title: Set("Les Misérables".to_string()),
+        isbn: Set(Some("9782070409228".to_string())),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .expect("insert book");
+    let app = Router::new()
+        .merge(rust_lib_app::modules::book_files::routes())
+        .merge(rust_lib_app::modules::book_files::public_routes())
+        .with_state(AppState::new(db));
+    (app, book.id)
+}
+
+fn upload(book_id: &str, filename: &str, bytes: &[u8]) -> Request<Body> {
+    let mut body = Vec::new();
+    body.extend_from_slice(
+        format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    Request::builder()
+        .uri(format!("/books/{book_id}/files"))
+        .method("POST")
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+async fn json(response: axum::response::Response) -> serde_json::Value {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_upload_list_download_and_delete() {
+    let (app, book_id) = setup().await;
+
+    let response = app
+        .clone()
+        .oneshot(upload(&book_id, "les-miserables.epub", b"epub bytes"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let file = json(response).await;
+    assert_eq!(file["format"], "epub");
+    assert_eq!(file["mime_type"], "application/epub+zip");
+    assert_eq!(file["size_bytes"], 10);
+    let sha = file["sha256"].as_str().unwrap().to_string();
+    assert_eq!(sha.len(), 64);
+    let file_id = file["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .clone()
+        .oneshot(get(&format!("/books/{book_id}/files")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json(response).await.as_array().unwrap().len(), 1);
+
+    let response = app
+        .clone()
+        .oneshot(get(&format!("/books/{book_id}/files/{file_id}")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/epub+zip"
+    );
+    assert_eq!(response.headers()[header::ETAG], format!("\"{sha}\""));
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(&bytes[..], b"epub bytes");
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/books/{book_id}/files/{file_id}"))
+                .method("DELETE")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let blob = media_dir()
+        .join("books")
+        .join(&book_id)
+        .join(format!("{file_id}.epub"));
+    assert!(!blob.exists(), "blob removed with its row");
+
+    let response = app
+        .oneshot(get(&format!("/books/{book_id}/files/{file_id}")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_upload_rejects_unsupported_type_and_duplicates() {
+    let (app, book_id) = setup().await;
+
+    let response = app
+        .clone()
+        .oneshot(upload(&book_id, "notes.txt", b"plain text"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = app
+        .clone()
+        .oneshot(upload(&book_id, "chapter1.mp3", b"id3 audio"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app
+        .clone()
+        .oneshot(upload(&book_id, "chapter1-copy.mp3", b"id3 audio"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .oneshot(get(&format!("/books/{book_id}/files")))
+        .await
+        .unwrap();
+    assert_eq!(json(response).await.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_upload_to_unknown_book_is_not_found() {
+    let (app, _) = setup().await;
+    let response = app
+        .oneshot(upload("no-such-book", "book.pdf", b"%PDF-1.7"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_opds_feed_lists_attached_files() {
+    let (app, book_id) = setup().await;
+    let response = app
+        .clone()
+        .oneshot(upload(&book_id, "les-miserables.m4b", b"audiobook bytes"))
+        .await
+        .unwrap();
+    let file_id = json(response).await["id"].as_str().unwrap().to_string();
+
+    let response = app.clone().oneshot(get("/opds")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/opds/tokens")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"name":"Kobo"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let token = json(response).await["token"].as_str().unwrap().to_string();
+    let with_token = |uri: &str| {
+        Request::builder()
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(with_token(&format!("/opds/files/{file_id}")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.oneshot(with_token("/opds")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("application/atom+xml;profile=opds-catalog")
+    );
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let xml = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(xml.contains("<title>Les Misérables</title>"));
+    assert!(xml.contains(&format!(
+        r#"href="/api/opds/files/{file_id}" type="audio/mp4""#
+    )));
+}
@@ -5,6 +5,7 @@
 //! Tests status transitions: loan restrictions, sale flow, cancel sale.
 
 use rust_lib_app::db;
+use rust_lib_app::domain::Loan;
 use rust_lib_app::models::copy::{self, Entity as Copy};
 use rust_lib_app::models::loan::LoanDto;
 use rust_lib_app::models::sale::SaleDto;
@@ -12,6 +13,22 @@ use rust_lib_app::services::loan_service;
 use rust_lib_app::services::sale_service;
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
 
+async fn create_loan(
+    db: &DatabaseConnection,
+    dto: LoanDto,
+) -> Result<Loan, loan_service::ServiceError> {
+    let repos = loan_service::SeaOrmLoanRepos::new(db);
+    loan_service::create_loan(repos.repos(), dto).await
+}
+
+async fn return_loan(
+    db: &DatabaseConnection,
+    id: &str,
+) -> Result<Loan, loan_service::ServiceError> {
+    let repos = loan_service::SeaOrmLoanRepos::new(db);
+    loan_service::return_loan(repos.repos(), id).await
+}
+
 async fn setup_test_db() -> DatabaseConnection {
     db::init_db("sqlite::memory:")
         .await
@@ -126,8 +143,7 @@ async fn test_loan_available_copy_succeeds() {
     let (lib_id, book_id, contact_id) = seed_test_data(&db).await;
     let copy_id = create_copy(&db, book_id, lib_id, "available").await;
 
-    let result =
-        loan_service::create_loan(&db, make_loan_dto(copy_id.clone(), contact_id, lib_id)).await;
+    let result = create_loan(&db, make_loan_dto(copy_id.clone(), contact_id, lib_id)).await;
     assert!(result.is_ok(), "Loan on available copy should succeed");
 
     // Verify copy status changed to "loaned"
@@ -141,7 +157,7 @@ async fn test_loan_borrowed_copy_rejected() {
     let (lib_id, book_id, contact_id) = seed_test_data(&db).await;
     let copy_id = create_copy(&db, book_id, lib_id, "borrowed").await;
 
-    let result = loan_service::create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id)).await;
+    let result = create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id)).await;
     assert!(result.is_err(), "Loan on borrowed copy must be rejected");
 
     match result.unwrap_err() {
@@ -158,7 +174,7 @@ async fn test_loan_lost_copy_rejected() {
     let (lib_id, book_id, contact_id) = seed_test_data(&db).await;
     let copy_id = create_copy(&db, book_id, lib_id, "lost").await;
 
-    let result = loan_service::create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id)).await;
+    let result = create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id)).await;
     assert!(result.is_err(), "Loan on lost copy must be rejected");
 
     match result.unwrap_err() {
@@ -174,7 +190,7 @@ async fn test_loan_nonexistent_copy_returns_not_found() {
     let db = setup_test_db().await;
     let (lib_id, _book_id, contact_id) = seed_test_data(&db).await;
 
-    let result = loan_service::create_loan(
+    let result = create_loan(
         &db,
         make_loan_dto("nonexistent-copy".to_string(), contact_id, lib_id),
     )
@@ -196,12 +212,12 @@ async fn test_return_loan_restores_available_status() {
     let copy_id = create_copy(&db, book_id, lib_id, "available").await;
 
     // Create loan
-    let loan = loan_service::create_loan(&db, make_loan_dto(copy_id.clone(), contact_id, lib_id))
+    let loan = create_loan(&db, make_loan_dto(copy_id.clone(), contact_id, lib_id))
         .await
         .unwrap();
 
     // Return loan
-    let returned = loan_service::return_loan(&db, &loan.id).await.unwrap();
+    let returned = return_loan(&db, &loan.id).await.unwrap();
     assert_eq!(returned.status, "returned");
 
     // Copy is available again
@@ -215,13 +231,13 @@ async fn test_return_already_returned_loan_rejected() {
     let (lib_id, book_id, contact_id) = seed_test_data(&db).await;
     let copy_id = create_copy(&db, book_id, lib_id, "available").await;
 
-    let loan = loan_service::create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id))
+    let loan = create_loan(&db, make_loan_dto(copy_id, contact_id, lib_id))
         .await
         .unwrap();
-    loan_service::return_loan(&db, &loan.id).await.unwrap();
+    return_loan(&db, &loan.id).await.unwrap();
 
     // Try to return again
-    let result = loan_service::return_loan(&db, &loan.id).await;
+    let result = return_loan(&db, &loan.id).await;
     assert!(result.is_err(), "Double return must be rejected");
 
     match result.unwrap_err() {
@@ -0,0 +1,113 @@
+//! Integration tests for the `testing` fixture builders (run with
+//! `cargo test --features testing --test fixtures_test`).
+//!
+//! Locks the determinism contract downstream harnesses rely on: the same
+//! counts give the same ids on any database, and the debug endpoint builds
+//! exactly what the builder does.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode, header};
+use rust_lib_app::testing::{TestLibrary, fixture_isbn};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+async fn body_json(resp: axum::response::Response) -> Value {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+#[tokio::test]
+async fn same_counts_give_the_same_rows_on_separate_databases() {
+    let a = TestLibrary::builder()
+        .with_books(50)
+        .with_peers(2)
+        .build_in_memory()
+        .await
+        .expect("build a");
+    let b = TestLibrary::builder()
+        .with_books(50)
+        .with_peers(2)
+        .build_in_memory()
+        .await
+        .expect("build b");
+
+    assert_eq!(a.book_ids, b.book_ids);
+    assert_eq!(a.copy_ids, b.copy_ids);
+    assert_eq!(a.peer_ids, b.peer_ids);
+    assert_eq!(a.book_ids.len(), 50);
+}
+
+#[tokio::test]
+async fn fixture_books_are_listed_by_the_api() {
+    let library = TestLibrary::builder()
+        .with_books(12)
+        .build_in_memory()
+        .await
+        .expect("build");
+    let app = axum::Router::new()
+        .route(
+            "/api/books",
+            axum::routing::get(rust_lib_app::api::books::list_books),
+        )
+        .with_state(library.state());
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/books?limit=100")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = body_json(resp).await;
+    assert_eq!(body["total"], 12);
+    let isbns: Vec<&str> = body["books"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|b| b["isbn"].as_str())
+        .collect();
+    assert!(isbns.contains(&fixture_isbn(7).as_str()));
+}
+
+#[tokio::test]
+async fn debug_endpoint_builds_the_same_fixtures() {
+    let library = TestLibrary::builder()
+        .build_in_memory()
+        .await
+        .expect("build");
+    let app = rust_lib_app::testing::debug_routes().with_state(library.state());
+    let post = |payload: Value| {
+        let app = app.clone();
+        async move {
+            app.oneshot(
+                Request::post("/debug/fixtures")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        }
+    };
+
+    let resp = post(json!({"books": 3, "contacts": 2})).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = body_json(resp).await;
+    let expected = TestLibrary::builder()
+        .with_books(3)
+        .with_contacts(2)
+        .build(&library.db)
+        .await
+        .expect("rebuild");
+    assert_eq!(body["book_ids"], json!(expected.book_ids));
+    assert_eq!(body["contact_ids"], json!(expected.contact_ids));
+
+    let resp = post(json!({"books": 1_000_000})).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::needless_update)]
-//! HTTP-level tests for the loan endpoints (POST /loans, PUT /loans/:id/return).
+//! HTTP-level tests for the loan endpoints (POST /loans, PUT /loans/:id/return,
+//! PUT /loans/:id/renew) and the contact portal's view of them.
 //!
 //! These validate the API handler path (loan.rs), which is distinct from the
 //! service-layer tests in copy_status_test.rs that test loan_service.rs.
@@ -8,9 +9,9 @@ use axum::{
     Router,
     body::Body,
     http::{Request, StatusCode},
-    routing::{get, put},
+    routing::{get, post, put},
 };
-use rust_lib_app::api::loan;
+use rust_lib_app::api::{contact, loan, portal};
 use rust_lib_app::db;
 use rust_lib_app::infrastructure::AppState;
 use rust_lib_app::models::copy::{self, Entity as Copy};
@@ -94,7 +95,18 @@ async fn create_copy(
 fn loan_router() -> Router<AppState> {
     Router::new()
         .route("/loans", get(loan::list_loans).post(loan::create_loan))
+        .route("/loans/quick", post(loan::create_quick_loan))
         .route("/loans/:id/return", put(loan::return_loan))
+        .route("/loans/:id/renew", put(loan::renew_loan))
+        .route("/contacts/:id/promote", post(contact::promote_contact))
+        .route("/contacts/:id/membership", put(contact::update_membership))
+        .route("/contacts/:id/portal-token", post(portal::issue_token))
+        .route("/portal/loans", get(portal::my_loans))
+        .route("/portal/loans/:id/renewal", post(portal::request_renewal))
+        .route(
+            "/loan-settings",
+            get(loan::get_loan_settings).put(loan::update_loan_settings),
+        )
 }
 
 fn loan_body(copy_id: &str, contact_id: &str, library_id: i32) -> String {
@@ -110,6 +122,48 @@ fn loan_body(copy_id: &str, contact_id: &str, library_id: i32) -> String {
 
 // -- Tests --
 
+#[tokio::test]
+async fn test_http_create_loan_refused_to_a_lapsed_member() {
+    let (state, lib_id, book_id, contact_id) = setup().await;
+    let copy_id = create_copy(state.db(), &book_id, lib_id, "available").await;
+    let app = loan_router().with_state(state.clone());
+
+    let set_expiry = |expires_at: &str| {
+        Request::builder()
+            .method("PUT")
+            .uri(format!("/contacts/{contact_id}/membership"))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({ "member_number": "42", "expires_at": expires_at }).to_string(),
+            ))
+            .unwrap()
+    };
+    let lend = || {
+        Request::builder()
+            .method("POST")
+            .uri("/loans")
+            .header("content-type", "application/json")
+            .body(Body::from(loan_body(&copy_id, &contact_id, lib_id)))
+            .unwrap()
+    };
+
+    let resp = app.clone().oneshot(set_expiry("2020-12-31")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app.clone().oneshot(lend()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let copy = Copy::find_by_id(copy_id.clone())
+        .one(state.db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(copy.status, "available");
+
+    // Renewed: lending works again.
+    app.clone().oneshot(set_expiry("2999-12-31")).await.unwrap();
+    let resp = app.oneshot(lend()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_http_create_loan_sets_copy_status_to_loaned() {
     let (state, lib_id, book_id, contact_id) = setup().await;
@@ -228,3 +282,222 @@ async fn test_http_return_loan_restores_available() {
         .unwrap();
     assert_eq!(copy.status, "available");
 }
+
+#[tokio::test]
+async fn test_http_create_loan_without_due_date_applies_contact_type_policy() {
+    let (state, lib_id, book_id, contact_id) = setup().await;
+    let copy_id = create_copy(state.db(), &book_id, lib_id, "available").await;
+    let app = loan_router().with_state(state.clone());
+
+    // Policy: 10 days for "person" contacts, 2-day grace period
+    let req = Request::builder()
+        .method("PUT")
+        .uri("/loan-settings")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "default_loan_duration_days": 21,
+                "per_book_duration_enabled": false,
+                "grace_period_days": 2,
+                "contact_type_durations": { "person": 10 }
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let settings: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(settings["grace_period_days"], 2);
+    assert_eq!(settings["contact_type_durations"]["person"], 10);
+
+    let req = Request::builder()
+        .method("POST")
+        .uri("/loans")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "copy_id": copy_id,
+                "contact_id": contact_id,
+                "library_id": lib_id,
+                "loan_date": "2026-04-11",
+                "due_date": ""
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let expected = (chrono::Local::now().date_naive() + chrono::Duration::days(10))
+        .format("%Y-%m-%d")
+        .to_string();
+    assert_eq!(json["loan"]["due_date"], expected);
+}
+
+async fn post_json(
+    app: Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    let status = resp.status();
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_http_quick_loan_reuses_the_borrower_and_can_be_promoted() {
+    let (state, lib_id, book_id, _) = setup().await;
+    let first_copy = create_copy(state.db(), &book_id, lib_id, "available").await;
+    let second_copy = create_copy(state.db(), &book_id, lib_id, "available").await;
+    let app = loan_router().with_state(state.clone());
+
+    let (status, first) = post_json(
+        app.clone(),
+        "/loans/quick",
+        json!({ "copy_id": first_copy, "borrower_name": " Grandma " }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["contact"]["type"], "Quick");
+    assert_eq!(first["contact"]["name"], "Grandma");
+    assert!(!first["loan"]["due_date"].as_str().unwrap().is_empty());
+
+    let (_, second) = post_json(
+        app.clone(),
+        "/loans/quick",
+        json!({ "copy_id": second_copy, "borrower_name": "Grandma" }),
+    )
+    .await;
+    let contact_id = first["contact"]["id"].as_str().unwrap();
+    assert_eq!(
+        second["contact"]["id"], contact_id,
+        "same name, same ledger"
+    );
+
+    let (status, _) = post_json(
+        app.clone(),
+        "/loans/quick",
+        json!({ "copy_id": first_copy, "borrower_name": "Grandma" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "copy already lent");
+
+    let promoted = json!({
+        "type": "Borrower",
+        "name": "Grandma",
+        "phone": "0102030405",
+        "is_active": true,
+        "has_book": false
+    });
+    let (status, body) = post_json(
+        app.clone(),
+        &format!("/contacts/{contact_id}/promote"),
+        promoted.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["contact"]["type"], "Borrower");
+    assert_eq!(body["contact"]["id"], contact_id);
+
+    let (status, _) = post_json(app, &format!("/contacts/{contact_id}/promote"), promoted).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "already a full contact");
+}
+
+#[tokio::test]
+async fn test_http_portal_shows_own_loans_and_requests_a_renewal() {
+    let (state, lib_id, book_id, contact_id) = setup().await;
+    let copy_id = create_copy(state.db(), &book_id, lib_id, "available").await;
+    let app = loan_router().with_state(state.clone());
+
+    let (_, created) = post_json(
+        app.clone(),
+        "/loans",
+        serde_json::from_str(&loan_body(&copy_id, &contact_id, lib_id)).unwrap(),
+    )
+    .await;
+    let loan_id = created["loan"]["id"].as_str().unwrap().to_string();
+
+    let (status, issued) = post_json(
+        app.clone(),
+        &format!("/contacts/{contact_id}/portal-token"),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let token = issued["token"].as_str().unwrap().to_string();
+
+    let portal_request = |method: &str, uri: String| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let resp = app
+        .clone()
+        .oneshot(portal_request("GET", "/portal/loans".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let mine: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(mine["name"], "Alice");
+    assert_eq!(mine["loans"][0]["id"], loan_id.as_str());
+    assert_eq!(mine["loans"][0]["book_title"], "Test Book");
+
+    let resp = app
+        .clone()
+        .oneshot(portal_request(
+            "POST",
+            format!("/portal/loans/{loan_id}/renewal"),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/portal/loans")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "no token");
+
+    // The owner grants it: the policy period restarts today.
+    let req = Request::builder()
+        .method("PUT")
+        .uri(format!("/loans/{loan_id}/renew"))
+        .header("content-type", "application/json")
+        .body(Body::from("{}"))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let renewed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(renewed["loan"]["due_date"].as_str().unwrap() > "2026-05-11");
+}
@@ -77,6 +77,34 @@ async fn test_remote_op_safety_off_sets_pending() {
     assert_eq!(op.status, "pending");
 }
 
+#[tokio::test]
+async fn test_remote_update_dedup_only_on_identical_payload() {
+    let db = setup().await;
+    let first = serde_json::json!({ "title": "Dune" });
+    let second = serde_json::json!({ "title": "Dune Messiah" });
+
+    let a = log_remote_operation(
+        &db,
+        "book",
+        "book-1",
+        "UPDATE",
+        Some(first.clone()),
+        7,
+        false,
+    )
+    .await
+    .unwrap();
+    let replay = log_remote_operation(&db, "book", "book-1", "UPDATE", Some(first), 7, false)
+        .await
+        .unwrap();
+    let b = log_remote_operation(&db, "book", "book-1", "UPDATE", Some(second), 7, false)
+        .await
+        .unwrap();
+
+    assert_eq!(a, replay, "Replaying the same change is a duplicate");
+    assert_ne!(a, b, "A later edit of the same entity is a new change");
+}
+
 // ── Milestones ──────────────────────────────────────────────────────
 
 #[tokio::test]
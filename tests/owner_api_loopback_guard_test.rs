@@ -140,6 +140,17 @@ async fn listing_peers_is_refused_from_the_lan() {
     assert_eq!(status, StatusCode::FORBIDDEN);
 }
 
+#[tokio::test]
+async fn changing_or_deleting_a_peer_is_refused_from_the_lan() {
+    // Accepting, rejecting, blocking and deleting peers is the owner's call; a
+    // LAN host must not approve itself or unblock itself.
+    for (method, uri) in [("PUT", "/peers/1/status"), ("DELETE", "/peers/1")] {
+        let db = setup_db().await;
+        let status = status_of(db, method, uri, Some(LAN_PEER.parse().unwrap()), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+}
+
 #[tokio::test]
 async fn the_operation_log_pull_is_refused_from_the_lan() {
     // `GET /peers/pull` returns the entire operation_log. It served a shelved
@@ -291,3 +302,322 @@ async fn a_write_to_the_catalogue_detail_path_is_still_guarded() {
         "the guard must block the write before the handler runs"
     );
 }
+
+// ── Route audit: every owner write is guarded, the allow-list is not ─────────
+
+#[tokio::test]
+async fn every_owner_mutation_route_is_refused_from_the_lan() {
+    // The guard is applied to the owner half as a whole; this table pins the
+    // writes that carry no credential of their own, so moving one of them into
+    // `public_routes` by mistake fails here.
+    let routes = [
+        ("POST", "/peers/push"),
+        ("POST", "/peers/requests/incoming"),
+        ("POST", "/loans"),
+        ("POST", "/loans/quick"),
+        ("PUT", "/loans/1/return"),
+        ("PUT", "/loans/1/renew"),
+        ("POST", "/contacts/c1/portal-token"),
+        ("DELETE", "/contacts/c1/portal-token"),
+        ("PUT", "/loan-settings"),
+        ("POST", "/copies"),
+        ("PUT", "/copies/1"),
+        ("DELETE", "/copies/1"),
+        ("GET", "/copies/1/history"),
+        ("GET", "/stats/year/2025"),
+        ("GET", "/stats/p2p"),
+        ("GET", "/books/b1/digital-editions"),
+        ("POST", "/books/b1/cover"),
+        ("POST", "/images/process"),
+        ("POST", "/scan/corrections"),
+        ("GET", "/scan/corrections/export"),
+        ("POST", "/search/voice"),
+        ("GET", "/search/nl"),
+        ("GET", "/search/suggest"),
+        ("GET", "/reading-queue"),
+        ("POST", "/reading-queue"),
+        ("PUT", "/reading-queue"),
+        ("DELETE", "/reading-queue/1"),
+        ("GET", "/dashboard"),
+        ("GET", "/hub/sync"),
+        ("POST", "/hub/sync"),
+        ("GET", "/integrations/usage"),
+        ("PUT", "/integrations/quotas"),
+        ("GET", "/saved-searches"),
+        ("POST", "/saved-searches"),
+        ("DELETE", "/saved-searches/1"),
+        ("GET", "/saved-searches/1/matches"),
+        ("POST", "/saved-searches/1/run"),
+        ("GET", "/lookup/9780306406157/availability"),
+        ("GET", "/books/b1/relations"),
+        ("POST", "/books/b1/relations"),
+        ("DELETE", "/books/b1/relations/r1"),
+        ("PUT", "/books/b1/work"),
+        ("GET", "/works"),
+        ("POST", "/works/cluster"),
+        ("GET", "/works/w1"),
+        ("GET", "/stats/card.png"),
+        ("POST", "/contacts"),
+        ("POST", "/contacts/c1/promote"),
+        ("PUT", "/contacts/c1/membership"),
+        ("GET", "/contacts/members"),
+        ("POST", "/contacts/geocode"),
+        ("GET", "/contacts/nearby"),
+        ("POST", "/contacts/route"),
+        ("GET", "/library/config/history"),
+        ("POST", "/library/config/rollback/1"),
+        ("GET", "/contacts/c1/fees"),
+        ("POST", "/contacts/c1/fees/payments"),
+        ("GET", "/fees/report"),
+        ("GET", "/orders"),
+        ("POST", "/orders"),
+        ("GET", "/orders/budget"),
+        ("GET", "/orders/o1"),
+        ("POST", "/orders/o1/receive"),
+        ("POST", "/orders/o1/cancel"),
+        ("GET", "/periodicals"),
+        ("POST", "/periodicals"),
+        ("GET", "/periodicals/p1"),
+        ("PUT", "/periodicals/p1"),
+        ("DELETE", "/periodicals/p1"),
+        ("POST", "/periodicals/p1/issues"),
+        ("DELETE", "/periodicals/p1/issues/i1"),
+        ("GET", "/authors/match"),
+        ("GET", "/authors/normalize"),
+        ("POST", "/authors/normalize"),
+        ("GET", "/authors/a1/works"),
+        ("PUT", "/authors/a1/identifiers"),
+        ("GET", "/suggestions"),
+        ("POST", "/suggestions/s1/accept"),
+        ("POST", "/suggestions/s1/reject"),
+        ("POST", "/copies/c1/weeding"),
+        ("DELETE", "/copies/c1/weeding"),
+        ("GET", "/weeding"),
+        ("POST", "/weeding/withdraw"),
+        ("GET", "/weeding/report"),
+        ("GET", "/subject-authorities"),
+        ("POST", "/subject-authorities/rameau/import"),
+        ("DELETE", "/subject-authorities/rameau"),
+        ("GET", "/subject-authorities/suggest"),
+        ("POST", "/subject-authorities/validate"),
+        ("GET", "/subject-authorities/report"),
+        ("GET", "/reports/quality"),
+        ("GET", "/collections/c1/source"),
+        ("POST", "/collections/c1/sync"),
+        ("GET", "/peers/1/books/b1/blobs"),
+        ("GET", "/peers/1/blobs/abc"),
+        ("POST", "/peers/import"),
+        ("GET", "/peers/subscriptions"),
+        ("PUT", "/peers/1/subscription"),
+        ("DELETE", "/peers/1/subscription"),
+        ("POST", "/peers/1/subscription/sync"),
+        ("GET", "/peers/1/capabilities"),
+        ("POST", "/peers/1/handshake"),
+        ("GET", "/peers/certificates"),
+        ("GET", "/peers/certificates/probe"),
+        ("POST", "/peers/certificates/rotate"),
+        ("DELETE", "/peers/certificates"),
+        ("GET", "/peers/tor/config"),
+        ("PUT", "/peers/tor/config"),
+        ("GET", "/network/mode"),
+        ("PUT", "/network/mode"),
+        ("GET", "/announcements"),
+        ("POST", "/announcements"),
+        ("DELETE", "/announcements/a1"),
+        ("PUT", "/peers/1/announcements"),
+        ("GET", "/book-clubs"),
+        ("POST", "/book-clubs"),
+        ("GET", "/book-clubs/c1"),
+        ("DELETE", "/book-clubs/c1"),
+        ("PUT", "/book-clubs/c1/members"),
+        ("POST", "/book-clubs/c1/proposals"),
+        ("DELETE", "/book-clubs/c1/proposals/p1"),
+        ("PUT", "/book-clubs/c1/proposals/p1/vote"),
+        ("PUT", "/book-clubs/c1/current"),
+        ("PUT", "/book-clubs/c1/schedule"),
+        ("PUT", "/tags/t1/private"),
+        ("POST", "/books"),
+        ("POST", "/books/batch/delete"),
+        ("POST", "/books/batch/archive"),
+        ("POST", "/books/batch/unarchive"),
+        ("POST", "/books/b1/files"),
+        ("DELETE", "/books/b1/files/f1"),
+        ("POST", "/books/b1/notes"),
+        ("POST", "/import/file"),
+        ("POST", "/import"),
+        ("POST", "/import-upsert"),
+        ("GET", "/export"),
+        ("POST", "/setup"),
+        ("POST", "/reset"),
+        ("POST", "/admin/shutdown"),
+        ("GET", "/admin/maintenance"),
+        ("POST", "/admin/maintenance"),
+        ("GET", "/admin/consistency"),
+        ("POST", "/admin/consistency"),
+        ("GET", "/control/handshake"),
+        ("POST", "/peers/connect"),
+        ("POST", "/peers/1/request"),
+        ("GET", "/peers/requests"),
+        ("POST", "/peers/requests/outgoing"),
+        ("DELETE", "/peers/requests/outgoing/r1"),
+        ("DELETE", "/peers/requests/outgoing/clear"),
+        ("POST", "/peers/requests/outgoing/sync"),
+        ("DELETE", "/peers/requests/clear"),
+        ("POST", "/peers/requests/expire"),
+        ("DELETE", "/peers/requests/clear-resolved"),
+        ("DELETE", "/peers/requests/r1"),
+        ("POST", "/peers/return_book"),
+        ("POST", "/peers/1/offer-loan"),
+        ("POST", "/peers/outbox/retry"),
+        ("DELETE", "/peers/outbox/o1"),
+        ("POST", "/devices/pairing"),
+        ("POST", "/devices/pair-with"),
+        ("POST", "/devices/1/sync"),
+        ("DELETE", "/devices/1"),
+        ("GET", "/sync/status"),
+        ("GET", "/kiosk/tokens"),
+        ("POST", "/kiosk/tokens"),
+        ("DELETE", "/kiosk/tokens/k1"),
+        ("GET", "/opds/tokens"),
+        ("POST", "/opds/tokens"),
+        ("DELETE", "/opds/tokens/t1"),
+    ];
+    for (method, uri) in routes {
+        let db = setup_db().await;
+        let status = status_of(db, method, uri, Some(LAN_PEER.parse().unwrap()), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn every_allow_listed_route_passes_the_guard() {
+    // The handlers may still refuse an empty or anonymous call (400, 403, 415),
+    // but the loopback guard must not be what answers.
+    let routes = [
+        ("GET", "/health"),
+        ("GET", "/books"),
+        ("GET", "/config"),
+        ("GET", "/copies/c1/availability"),
+        ("GET", "/gamification/public-stats"),
+        ("GET", "/public-stats-bundle"),
+        ("POST", "/peers/incoming"),
+        ("POST", "/peers/notify-disconnect"),
+        ("POST", "/peers/verify-disconnect"),
+        ("POST", "/peers/handshake"),
+        ("POST", "/peers/search"),
+        ("POST", "/peers/request"),
+        ("POST", "/peers/loans/offer"),
+        ("POST", "/peers/loans/confirm"),
+        ("POST", "/peers/loans/returned"),
+        ("PUT", "/peers/requests/status/r1"),
+        ("POST", "/peers/requests/status-query"),
+        ("DELETE", "/peers/requests/cancel/r1"),
+        ("PUT", "/peers/requests/r1"),
+        ("POST", "/e2ee/message"),
+        ("POST", "/relay/mailbox"),
+        ("POST", "/devices/pair"),
+        ("GET", "/devices/sync/operations"),
+        ("POST", "/devices/sync/operations"),
+        ("POST", "/devices/sync/revoke"),
+        ("GET", "/kiosk/search"),
+        ("POST", "/kiosk/checkout"),
+        ("POST", "/kiosk/checkin"),
+        ("GET", "/portal/loans"),
+        ("POST", "/portal/loans/l1/renewal"),
+        ("GET", "/portal/catalog"),
+        ("GET", "/portal/suggestions"),
+        ("POST", "/portal/suggestions"),
+        ("GET", "/blobs"),
+        ("GET", "/blobs/abc"),
+        ("GET", "/blobs/abc/chunks/0"),
+        ("GET", "/opds"),
+        ("GET", "/opds/files/f1"),
+    ];
+    for (method, uri) in routes {
+        let db = setup_db().await;
+        let response = api_router(db)
+            .oneshot(request(method, uri, Some(LAN_PEER.parse().unwrap()), None))
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        assert!(
+            !String::from_utf8_lossy(&body).contains("This endpoint is local-only"),
+            "{method} {uri}"
+        );
+    }
+}
+
+async fn status_of_request_update(peer: &str, new_status: &str) -> StatusCode {
+    let db = setup_db().await;
+    let mut req = Request::builder()
+        .method("PUT")
+        .uri("/peers/requests/r1")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(format!(r#"{{"status":"{new_status}"}}"#)))
+        .expect("request");
+    req.extensions_mut()
+        .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+    api_router(db)
+        .oneshot(req)
+        .await
+        .expect("response")
+        .status()
+}
+
+#[tokio::test]
+async fn accepting_a_request_is_refused_from_the_lan() {
+    // `PUT /peers/requests/:id` is public for the legacy borrower return only:
+    // a LAN host must not accept, hold or reject the owner's requests.
+    for new_status in ["accepted", "ready", "rejected"] {
+        assert_eq!(
+            status_of_request_update(LAN_PEER, new_status).await,
+            StatusCode::FORBIDDEN,
+            "{new_status}"
+        );
+    }
+    // Past the guard the unknown request id answers 404.
+    assert_eq!(
+        status_of_request_update(LAN_PEER, "returned").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status_of_request_update(LOOPBACK, "accepted").await,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn an_e_reader_on_the_lan_reads_the_opds_feed_with_its_token() {
+    use base64::Engine;
+
+    let db = setup_db().await;
+    let (_, token) = rust_lib_app::modules::book_files::tokens::create_token(&db, "Kobo")
+        .await
+        .expect("token");
+    let feed = |authorization: Option<String>| {
+        let mut req = request("GET", "/opds", Some(LAN_PEER.parse().unwrap()), None);
+        if let Some(value) = authorization {
+            req.headers_mut()
+                .insert(header::AUTHORIZATION, value.parse().unwrap());
+        }
+        api_router(db.clone()).oneshot(req)
+    };
+
+    let anonymous = feed(None).await.expect("response");
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    assert!(anonymous.headers().contains_key(header::WWW_AUTHENTICATE));
+
+    let wrong = base64::engine::general_purpose::STANDARD.encode("kobo:guess");
+    let refused = feed(Some(format!("Basic {wrong}")))
+        .await
+        .expect("response");
+    assert_eq!(refused.status(), StatusCode::UNAUTHORIZED);
+
+    let credentials = base64::engine::general_purpose::STANDARD.encode(format!("kobo:{token}"));
+    let served = feed(Some(format!("Basic {credentials}")))
+        .await
+        .expect("response");
+    assert_eq!(served.status(), StatusCode::OK);
+}
@@ -0,0 +1,144 @@
+//! Peer status routes: `PUT /peers/:id/status` accepts, rejects, blocks and
+//! unblocks a peer, `DELETE /peers/:id` removes it. A blocked peer is kept so
+//! its next connection request is recognised and refused.
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Request, StatusCode, header};
+use rust_lib_app::api::api_router;
+use rust_lib_app::db;
+use rust_lib_app::models::peer;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use std::net::SocketAddr;
+use tower::ServiceExt;
+
+const LOOPBACK: &str = "127.0.0.1:54321";
+const LAN_PEER: &str = "192.168.1.50:54321";
+const PEER_URL: &str = "http://192.168.1.50:8000";
+
+async fn setup() -> (DatabaseConnection, i32) {
+    let db = db::init_db("sqlite::memory:")
+        .await
+        .expect("init_db in memory");
+    let now = chrono::Utc::now().to_rfc3339();
+    let peer = peer::ActiveModel {
+        name: Set("Intrus".to_string()),
+        url: Set(PEER_URL.to_string()),
+        connection_status: Set("pending".to_string()),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .expect("insert peer");
+    (db, peer.id)
+}
+
+async fn send(
+    db: &DatabaseConnection,
+    method: &str,
+    uri: &str,
+    from: &str,
+    body: serde_json::Value,
+) -> StatusCode {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("request");
+    req.extensions_mut()
+        .insert(ConnectInfo(from.parse::<SocketAddr>().unwrap()));
+    api_router(db.clone())
+        .oneshot(req)
+        .await
+        .expect("response")
+        .status()
+}
+
+async fn set_status(db: &DatabaseConnection, id: i32, body: serde_json::Value) -> StatusCode {
+    send(db, "PUT", &format!("/peers/{id}/status"), LOOPBACK, body).await
+}
+
+async fn connection_request(db: &DatabaseConnection) -> StatusCode {
+    send(
+        db,
+        "POST",
+        "/peers/incoming",
+        LAN_PEER,
+        serde_json::json!({ "name": "Intrus", "url": PEER_URL }),
+    )
+    .await
+}
+
+async fn find_peer(db: &DatabaseConnection, id: i32) -> Option<peer::Model> {
+    peer::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .expect("find peer")
+}
+
+#[tokio::test]
+async fn a_blocked_peer_cannot_request_again_until_unblocked() {
+    let (db, id) = setup().await;
+
+    let status = set_status(
+        &db,
+        id,
+        serde_json::json!({ "status": "rejected", "block": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let blocked = find_peer(&db, id).await.expect("blocked peer is kept");
+    assert_eq!(blocked.connection_status, "blocked");
+    assert!(!blocked.auto_approve);
+
+    assert_eq!(connection_request(&db).await, StatusCode::FORBIDDEN);
+
+    let status = set_status(&db, id, serde_json::json!({ "status": "unblocked" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        find_peer(&db, id).await.unwrap().connection_status,
+        "pending"
+    );
+
+    assert_eq!(connection_request(&db).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn unblocking_a_peer_that_is_not_blocked_is_a_conflict() {
+    let (db, id) = setup().await;
+    let status = set_status(&db, id, serde_json::json!({ "status": "unblocked" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn a_plain_rejection_still_removes_the_peer() {
+    let (db, id) = setup().await;
+    let status = set_status(&db, id, serde_json::json!({ "status": "rejected" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(find_peer(&db, id).await.is_none());
+}
+
+#[tokio::test]
+async fn accepting_a_peer_approves_it() {
+    let (db, id) = setup().await;
+    let status = set_status(&db, id, serde_json::json!({ "status": "active" })).await;
+    assert_eq!(status, StatusCode::OK);
+    let accepted = find_peer(&db, id).await.unwrap();
+    assert_eq!(accepted.connection_status, "accepted");
+    assert!(accepted.auto_approve);
+}
+
+#[tokio::test]
+async fn deleting_a_peer_removes_it() {
+    let (db, id) = setup().await;
+    let uri = format!("/peers/{id}");
+    let status = send(&db, "DELETE", &uri, LOOPBACK, serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(find_peer(&db, id).await.is_none());
+
+    let status = send(&db, "DELETE", &uri, LOOPBACK, serde_json::json!({})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
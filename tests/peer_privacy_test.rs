@@ -253,3 +253,46 @@ async fn get_book_auth_private_returns_full() {
     assert_eq!(status, StatusCode::OK);
     assert_eq!(json.get("user_rating").and_then(|v| v.as_i64()), Some(9));
 }
+
+#[tokio::test]
+async fn books_with_a_private_tag_are_hidden_from_peers() {
+    use rust_lib_app::models::{book_tags, tag};
+
+    let db = setup_db().await;
+    insert_rich_book(&db, "Public Book", false).await;
+    let gift = insert_rich_book(&db, "Gift", false).await;
+    let now = chrono::Utc::now().to_rfc3339();
+    let gifts = tag::ActiveModel {
+        name: Set("gifts".to_string()),
+        private: Set(true),
+        created_at: Set(now.clone()),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    book_tags::ActiveModel {
+        book_id: Set(gift.clone()),
+        tag_id: Set(gifts.id),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let app = build_app(db);
+
+    let (_, json) = get_json(&app, "/api/books", None).await;
+    let books = json.get("books").and_then(|v| v.as_array()).unwrap();
+    assert_eq!(
+        books.len(),
+        1,
+        "the privately tagged book must not be listed"
+    );
+    assert_eq!(json.get("total").and_then(|v| v.as_u64()), Some(1));
+    let (status, _) = get_json(&app, &format!("/api/books/{gift}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let token = create_jwt("owner", "admin").expect("jwt");
+    let (status, _) = get_json(&app, &format!("/api/books/{gift}"), Some(&token)).await;
+    assert_eq!(status, StatusCode::OK, "the owner still sees it");
+}
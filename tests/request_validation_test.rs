@@ -0,0 +1,168 @@
+//! Field-level 422 responses from the `ValidatedJson` extractor.
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header},
+};
+use rust_lib_app::api;
+use rust_lib_app::auth;
+use rust_lib_app::db;
+use rust_lib_app::infrastructure::AppState;
+use tower::util::ServiceExt; // for `oneshot`
+
+async fn setup_app() -> Router {
+    let db = db::init_db("sqlite::memory:")
+        .await
+        .expect("Failed to init DB");
+    Router::new()
+        .route("/books", axum::routing::post(api::books::create_book))
+        .route(
+            "/contacts",
+            axum::routing::post(api::contact::create_contact),
+        )
+        .route("/peers/connect", axum::routing::post(api::peer::connect))
+        .route("/copies/:id", axum::routing::put(api::copy::update_copy))
+        .with_state(AppState::new(db))
+}
+
+fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+    json_request("POST", uri, body)
+}
+
+fn json_request(method: &str, uri: &str, body: serde_json::Value) -> Request<Body> {
+    let token = auth::create_jwt("test_user", "admin").expect("Failed to create token");
+    Request::builder()
+        .uri(uri)
+        .method(method)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn error_fields(response: axum::response::Response) -> Vec<String> {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["error"], "Validation failed");
+    json["fields"]
+        .as_array()
+        .expect("fields array")
+        .iter()
+        .map(|f| f["field"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_create_book_rejects_invalid_fields() {
+    let app = setup_app().await;
+
+    let response = app
+        .oneshot(post_json(
+            "/books",
+            serde_json::json!({
+                "title": "  ",
+                "publication_year": 20245,
+                "user_rating": 42
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        error_fields(response).await,
+        vec!["title", "publication_year", "user_rating"]
+    );
+}
+
+#[tokio::test]
+async fn test_create_book_accepts_valid_payload() {
+    let app = setup_app().await;
+
+    let response = app
+        .oneshot(post_json(
+            "/books",
+            serde_json::json!({
+                "title": "Les Misérables",
+                "publication_year": 1862,
+                "user_rating": 10
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_create_contact_rejects_malformed_email() {
+    let app = setup_app().await;
+
+    let response = app
+        .oneshot(post_json(
+            "/contacts",
+            serde_json::json!({
+                "type": "Borrower",
+                "name": "Jean Valjean",
+                "email": "06 12 34 56 78",
+                "is_active": true,
+                "has_book": false
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(response).await, vec!["email"]);
+}
+
+#[tokio::test]
+async fn test_connect_rejects_blocked_peer_url() {
+    let app = setup_app().await;
+
+    let response = app
+        .oneshot(post_json(
+            "/peers/connect",
+            serde_json::json!({
+                "name": "Metadata",
+                "url": "http://169.254.169.254/latest"
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error_fields(response).await, vec!["url"]);
+}
+
+#[tokio::test]
+async fn test_update_copy_rejects_invalid_lending_terms() {
+    let app = setup_app().await;
+
+    let response = app
+        .oneshot(json_request(
+            "PUT",
+            "/copies/some-copy",
+            serde_json::json!({
+                "lending_terms": {
+                    "max_duration_days": 0,
+                    "renewable": true,
+                    "replacement_value": -5.0
+                }
+            }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        error_fields(response).await,
+        vec![
+            "lending_terms.max_duration_days",
+            "lending_terms.replacement_value"
+        ]
+    );
+}